@@ -0,0 +1,237 @@
+//! Automatic theme and tab color switching by remote host
+//!
+//! `[[auto_switch]]` rules restyle a tab while its shell is on a matching
+//! host, e.g. a red theme whenever you are logged into production. The
+//! current host comes from shell integration user variables (OSC 1337
+//! SetUserVar `hostname`/`username`, or WezTerm's `WEZTERM_HOST`/
+//! `WEZTERM_USER`) and, when those are absent, from a `user@host` prompt on
+//! the cursor line.
+//!
+//! The frontend keeps an [`AutoSwitcher`] per terminal, calls
+//! [`AutoSwitcher::update`] after output, and applies the returned change.
+
+use cterm_core::Screen;
+
+use crate::config::AutoSwitchRule;
+
+/// User variables that carry the host name, in order of preference
+const HOST_VARS: &[&str] = &["hostname", "host", "WEZTERM_HOST"];
+
+/// User variables that carry the user name, in order of preference
+const USER_VARS: &[&str] = &["username", "user", "WEZTERM_USER"];
+
+/// Where the shell currently is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteIdentity {
+    pub user: Option<String>,
+    pub host: String,
+}
+
+/// What the frontend should do after [`AutoSwitcher::update`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AutoSwitchChange {
+    /// A rule now matches: switch to its theme and/or tab color
+    Apply(AutoSwitchRule),
+    /// No rule matches anymore: restore the tab's own theme and color
+    Revert,
+}
+
+/// Tracks the detected host of one terminal and the rule it matched
+#[derive(Debug, Clone, Default)]
+pub struct AutoSwitcher {
+    rules: Vec<AutoSwitchRule>,
+    identity: Option<RemoteIdentity>,
+    active: Option<usize>,
+}
+
+impl AutoSwitcher {
+    pub fn new(rules: Vec<AutoSwitchRule>) -> Self {
+        Self {
+            rules,
+            identity: None,
+            active: None,
+        }
+    }
+
+    /// Re-detect the host and report a change of matching rule
+    ///
+    /// The last detected host is kept while nothing new is detected (e.g.
+    /// while a command's output hides the prompt), so only moving to another
+    /// host switches or reverts.
+    pub fn update(&mut self, screen: &Screen) -> Option<AutoSwitchChange> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let identity = detect_remote(screen)?;
+        if self.identity.as_ref() == Some(&identity) {
+            return None;
+        }
+        let matched = self.rules.iter().position(|rule| rule.matches(&identity));
+        log::debug!("Auto switch: now on {:?}, rule {:?}", identity, matched);
+        self.identity = Some(identity);
+        if matched == self.active {
+            return None;
+        }
+        self.active = matched;
+        Some(match matched {
+            Some(idx) => AutoSwitchChange::Apply(self.rules[idx].clone()),
+            None => AutoSwitchChange::Revert,
+        })
+    }
+}
+
+impl AutoSwitchRule {
+    /// Whether this rule applies to the given identity
+    pub fn matches(&self, identity: &RemoteIdentity) -> bool {
+        if !glob_match(&self.host, &identity.host) {
+            return false;
+        }
+        match (&self.user, &identity.user) {
+            (None, _) => true,
+            (Some(pattern), Some(user)) => glob_match(pattern, user),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Detect the shell's host from user variables, falling back to the prompt
+pub fn detect_remote(screen: &Screen) -> Option<RemoteIdentity> {
+    let var = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| screen.user_vars.get(*name))
+            .filter(|value| !value.is_empty())
+            .cloned()
+    };
+    if let Some(host) = var(HOST_VARS) {
+        return Some(RemoteIdentity {
+            user: var(USER_VARS),
+            host,
+        });
+    }
+
+    let cursor = &screen.cursor;
+    let line: String = (0..cursor.col)
+        .filter_map(|col| screen.get_cell(cursor.row, col))
+        .map(|cell| cell.c)
+        .collect();
+    prompt_identity(&line)
+}
+
+/// Find `user@host` in a shell prompt such as `me@db1:~$` or `[me@db1 ~]#`
+///
+/// The line must end in a prompt character so that `user@host` text in
+/// ordinary output is not mistaken for a prompt.
+pub fn prompt_identity(line: &str) -> Option<RemoteIdentity> {
+    let line = line.trim_end();
+    if !line.ends_with(['$', '#', '%', '>', '❯']) {
+        return None;
+    }
+    let is_user_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.');
+    let is_host_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '.');
+
+    for (at, _) in line.match_indices('@') {
+        let before = &line[..at];
+        let user_start = before
+            .rfind(|c: char| !is_user_char(c))
+            .map_or(0, |i| i + 1);
+        let user = &before[user_start..];
+        let after = &line[at + 1..];
+        let host_end = after
+            .find(|c: char| !is_host_char(c))
+            .unwrap_or(after.len());
+        let host = after[..host_end].trim_end_matches('.');
+        if !user.is_empty() && !host.is_empty() {
+            return Some(RemoteIdentity {
+                user: Some(user.to_string()),
+                host: host.to_string(),
+            });
+        }
+    }
+    None
+}
+
+/// Case-insensitive match where `*` stands for any run of characters
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let text = text.to_ascii_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No wildcard: the whole text must match
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cterm_core::screen::ScreenConfig;
+    use cterm_core::Parser;
+
+    fn rule(host: &str, theme: &str) -> AutoSwitchRule {
+        AutoSwitchRule {
+            host: host.into(),
+            user: None,
+            theme: Some(theme.into()),
+            tab_color: None,
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.prod.example.com", "db1.PROD.example.com"));
+        assert!(!glob_match("*.prod.example.com", "db1.staging.example.com"));
+        assert!(glob_match("prod-*", "prod-web3"));
+        assert!(glob_match("web*db*", "web-east-db2"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+        assert!(glob_match("*", "anything"));
+    }
+
+    #[test]
+    fn test_prompt_identity() {
+        let id = prompt_identity("deploy@db1.prod:~/app$ ").unwrap();
+        assert_eq!(id.user.as_deref(), Some("deploy"));
+        assert_eq!(id.host, "db1.prod");
+        assert_eq!(prompt_identity("[root@web-2 /etc]#").unwrap().host, "web-2");
+        // Addresses in output are not prompts
+        assert_eq!(prompt_identity("mail sent to me@example.com"), None);
+        assert_eq!(prompt_identity("$ "), None);
+    }
+
+    #[test]
+    fn test_switcher_applies_and_reverts() {
+        let mut screen = Screen::new(80, 24, ScreenConfig::default());
+        let mut parser = Parser::new();
+        let mut switcher = AutoSwitcher::new(vec![rule("*.prod", "Dracula")]);
+
+        parser.parse(&mut screen, b"me@laptop:~$ ");
+        assert_eq!(switcher.update(&screen), None);
+
+        parser.parse(&mut screen, b"ssh db1.prod\r\ndeploy@db1.prod:~$ ");
+        assert_eq!(
+            switcher.update(&screen),
+            Some(AutoSwitchChange::Apply(rule("*.prod", "Dracula")))
+        );
+        // Output without a prompt keeps the current host
+        parser.parse(&mut screen, b"\r\nsome output");
+        assert_eq!(switcher.update(&screen), None);
+
+        // User variables win over the prompt ("laptop")
+        parser.parse(&mut screen, b"\x1b]1337;SetUserVar=hostname=bGFwdG9w\x07");
+        assert_eq!(switcher.update(&screen), Some(AutoSwitchChange::Revert));
+        assert_eq!(switcher.update(&screen), None);
+    }
+}
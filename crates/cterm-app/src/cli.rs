@@ -0,0 +1,182 @@
+//! Subcommands of the `cterm` binary that run without opening a window
+//!
+//! Each frontend's argument parser embeds [`Command`] and hands it to
+//! [`run`] before starting the UI. `new-tab` and `new-window` are passed on
+//! to the running instance through [`crate::control`].
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Subcommand;
+
+use crate::config::{load_config, save_config};
+use crate::control::{self, ControlRequest};
+use crate::settings::{self, SettingKind, SETTINGS};
+use crate::usage_stats;
+
+/// Key of the color theme setting
+const THEME_KEY: &str = "appearance.theme";
+
+/// Subcommands of `cterm`
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Read or change settings in config.toml
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// List the color themes or switch to another one
+    #[command(subcommand)]
+    Theme(ThemeCommand),
+    /// Open a tab in the running cterm
+    NewTab {
+        /// Working directory of the new tab
+        #[arg(short = 'd', long = "directory")]
+        directory: Option<PathBuf>,
+    },
+    /// Open a window in the running cterm
+    NewWindow,
+    /// Show the local usage statistics
+    Stats {
+        /// Delete the collected statistics instead
+        #[arg(long)]
+        clear: bool,
+    },
+}
+
+/// `cterm config` subcommands
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the value of a setting
+    Get {
+        /// Dotted setting key, for example appearance.font.size
+        key: String,
+    },
+    /// Change a setting
+    Set {
+        /// Dotted setting key, for example appearance.font.size
+        key: String,
+        /// New value, checked against the setting's type and range
+        value: String,
+    },
+    /// Put a setting back to its default value
+    Reset {
+        /// Dotted setting key, for example appearance.font.size
+        key: String,
+    },
+    /// List the settings with their current values
+    List {
+        /// Only list settings matching these words
+        query: Vec<String>,
+    },
+}
+
+/// `cterm theme` subcommands
+#[derive(Subcommand, Debug)]
+pub enum ThemeCommand {
+    /// List the built-in and custom themes, marking the configured one
+    List,
+    /// Make a theme the configured one; new windows use it
+    Apply {
+        /// Theme name, for example "Tokyo Night"
+        name: String,
+    },
+}
+
+/// Run a subcommand, returning the process exit code
+pub fn run(command: Command) -> i32 {
+    let result = match command {
+        Command::Config(command) => run_config(command),
+        Command::Theme(command) => run_theme(command),
+        Command::NewTab { directory } => {
+            let directory = directory.map(std::path::absolute).transpose();
+            match directory {
+                Ok(directory) => send(ControlRequest::NewTab { directory }),
+                Err(e) => Err(e.into()),
+            }
+        }
+        Command::NewWindow => send(ControlRequest::NewWindow),
+        Command::Stats { clear } => run_stats(clear),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("cterm: {}", e);
+            1
+        }
+    }
+}
+
+fn run_config(command: ConfigCommand) -> anyhow::Result<()> {
+    let mut config = load_config()?;
+    match command {
+        ConfigCommand::Get { key } => {
+            let value = settings::get(&config, &key)?;
+            println!("{}", settings::format_value(&value));
+        }
+        ConfigCommand::Set { key, value } => {
+            settings::set_str(&mut config, &key, &value)?;
+            save_config(&config)?;
+        }
+        ConfigCommand::Reset { key } => {
+            if !settings::reset(&mut config, &key) {
+                return Err(settings::SettingError::Unknown(key).into());
+            }
+            save_config(&config)?;
+        }
+        ConfigCommand::List { query } => {
+            let query = query.join(" ");
+            for setting in SETTINGS.iter().filter(|s| s.matches(&query)) {
+                let value = settings::value(&config, setting.key)
+                    .map(|value| settings::format_value(&value))
+                    .unwrap_or_default();
+                let choices = match setting.kind {
+                    SettingKind::Choice(options) => format!(
+                        " [{}]",
+                        options
+                            .iter()
+                            .map(|(value, _)| *value)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    _ => String::new(),
+                };
+                println!("{} = {}", setting.key, value);
+                println!("    {}{}", setting.description, choices);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_theme(command: ThemeCommand) -> anyhow::Result<()> {
+    let mut config = load_config()?;
+    match command {
+        ThemeCommand::List => {
+            let setting = settings::find(THEME_KEY).expect("theme setting");
+            let choices = setting.choices();
+            let current = settings::value(&config, THEME_KEY)
+                .and_then(|value| setting.choice_index(&choices, &value));
+            for (i, (name, _)) in choices.iter().enumerate() {
+                let mark = if Some(i) == current { '*' } else { ' ' };
+                println!("{} {}", mark, name);
+            }
+        }
+        ThemeCommand::Apply { name } => {
+            settings::set_str(&mut config, THEME_KEY, &name)?;
+            save_config(&config)?;
+        }
+    }
+    Ok(())
+}
+
+fn run_stats(clear: bool) -> anyhow::Result<()> {
+    if clear {
+        return Ok(usage_stats::clear()?);
+    }
+    usage_stats::configure(&load_config()?);
+    print!("{}", usage_stats::report());
+    Ok(())
+}
+
+fn send(request: ControlRequest) -> anyhow::Result<()> {
+    control::send(&request).context("cannot reach a running cterm")
+}
@@ -0,0 +1,364 @@
+//! Clipboard history: earlier copies to paste again
+//!
+//! The frontends [`record`] every text copied from cterm and, with
+//! `clipboard_history.include_osc52`, every text a program puts on the
+//! clipboard with OSC 52. Edit > Paste from History lists [`items`], newest
+//! first, and pastes the chosen one through the usual paste path.
+//!
+//! The history stays in memory unless `persist` is on. It is then saved as
+//! `clipboard_history` in the local data directory, outside the config
+//! directory so that git sync never pushes it. With `encrypt` (the default)
+//! the file is sealed with ChaCha20-Poly1305 under a random key kept in
+//! `clipboard_history.key` beside it, both readable by the user only. That
+//! keeps copied secrets out of backups and indexers that pick up the history
+//! file alone, but not from anyone who can read both files.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use directories::ProjectDirs;
+use purecrypto::cipher::ChaCha20Poly1305;
+use purecrypto::rng::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ClipboardHistoryConfig, Config};
+
+/// Longest text kept, in bytes; larger copies are left out
+pub const MAX_TEXT_BYTES: usize = 1 << 20;
+
+/// Length of the picker's labels, in characters
+pub const LABEL_CHARS: usize = 60;
+
+/// Start of an encrypted history file
+const MAGIC: &[u8; 8] = b"CTCLIP1\0";
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Settings (none until configured, which keeps nothing) and the history,
+/// loaded from disk on first use
+static STATE: Mutex<State> = Mutex::new(State {
+    config: None,
+    history: None,
+});
+
+struct State {
+    config: Option<ClipboardHistoryConfig>,
+    history: Option<ClipboardHistory>,
+}
+
+impl State {
+    fn history(&mut self) -> &mut ClipboardHistory {
+        let persist = self.config.is_some_and(|config| config.persist);
+        self.history.get_or_insert_with(|| {
+            if persist {
+                load()
+            } else {
+                ClipboardHistory::default()
+            }
+        })
+    }
+}
+
+/// Where a copied text came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipSource {
+    /// Copied from a selection
+    Copy,
+    /// Written by a program with OSC 52
+    Osc52,
+}
+
+/// A copied text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipItem {
+    pub text: String,
+    pub source: ClipSource,
+}
+
+impl ClipItem {
+    /// One-line label for the picker, at most `max_chars` long: line breaks
+    /// show as ↵, other control characters as spaces
+    pub fn label(&self, max_chars: usize) -> String {
+        let mut chars: Vec<char> = self
+            .text
+            .trim()
+            .chars()
+            .filter_map(|c| match c {
+                '\r' => None,
+                '\n' => Some('↵'),
+                c if c.is_control() => Some(' '),
+                c => Some(c),
+            })
+            .take(max_chars + 1)
+            .collect();
+        if chars.len() > max_chars {
+            chars.truncate(max_chars.saturating_sub(1));
+            chars.push('…');
+        }
+        chars.into_iter().collect()
+    }
+}
+
+/// Copied texts, newest first, each once
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardHistory {
+    pub items: Vec<ClipItem>,
+}
+
+impl ClipboardHistory {
+    /// Add `item` as the newest, dropping an older copy of its text and
+    /// anything past `max_items`
+    pub fn push(&mut self, item: ClipItem, max_items: usize) {
+        self.items.retain(|old| old.text != item.text);
+        self.items.insert(0, item);
+        self.items.truncate(max_items);
+    }
+}
+
+/// Apply the `[clipboard_history]` settings
+///
+/// Turning `persist` off deletes the saved history; turning it on, or
+/// changing `encrypt`, saves the current one.
+pub fn configure(config: &Config) {
+    let settings = config.clipboard_history;
+    let mut state = lock();
+    let changed = state.config != Some(settings);
+    state.config = Some(settings);
+    if let Some(history) = state.history.as_mut() {
+        history.items.truncate(settings.max_items);
+    }
+    if !settings.persist {
+        if let Err(e) = remove_files() {
+            log::warn!("Failed to delete the clipboard history: {}", e);
+        }
+    } else if changed && state.history.is_some() {
+        let history = state.history().clone();
+        if let Err(e) = save(&history, settings.encrypt) {
+            log::warn!("Failed to save the clipboard history: {}", e);
+        }
+    }
+}
+
+/// Keep `text`, copied from `source`, if the settings allow
+pub fn record(text: &str, source: ClipSource) {
+    let mut state = lock();
+    let Some(config) = state.config else {
+        return;
+    };
+    if config.max_items == 0
+        || (source == ClipSource::Osc52 && !config.include_osc52)
+        || text.trim().is_empty()
+        || text.len() > MAX_TEXT_BYTES
+    {
+        return;
+    }
+    let item = ClipItem {
+        text: text.to_string(),
+        source,
+    };
+    state.history().push(item, config.max_items);
+    if config.persist {
+        let history = state.history().clone();
+        if let Err(e) = save(&history, config.encrypt) {
+            log::warn!("Failed to save the clipboard history: {}", e);
+        }
+    }
+}
+
+/// The kept texts, newest first
+pub fn items() -> Vec<ClipItem> {
+    let mut state = lock();
+    match state.config {
+        Some(config) if config.max_items > 0 => state.history().items.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// Forget every kept text, on disk too
+pub fn clear() -> std::io::Result<()> {
+    lock().history = Some(ClipboardHistory::default());
+    match history_path().map(std::fs::remove_file) {
+        Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Path of the saved history
+pub fn history_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("clipboard_history"))
+}
+
+/// Path of the key the saved history is encrypted with
+pub fn key_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("clipboard_history.key"))
+}
+
+fn data_dir() -> Option<PathBuf> {
+    ProjectDirs::from("com", "cterm", "cterm").map(|p| p.data_local_dir().to_path_buf())
+}
+
+fn lock() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The saved history, encrypted or not; empty when there is none or it
+/// can't be read
+fn load() -> ClipboardHistory {
+    let Some(data) = history_path().and_then(|path| std::fs::read(path).ok()) else {
+        return ClipboardHistory::default();
+    };
+    let json = if data.starts_with(MAGIC) {
+        let key = key_path().and_then(|path| read_key(&path));
+        match key.and_then(|key| open(&key, &data)) {
+            Some(json) => json,
+            None => {
+                log::warn!("Failed to decrypt the clipboard history");
+                return ClipboardHistory::default();
+            }
+        }
+    } else {
+        data
+    };
+    serde_json::from_slice(&json).unwrap_or_default()
+}
+
+fn save(history: &ClipboardHistory, encrypt: bool) -> std::io::Result<()> {
+    let (Some(path), Some(key_path)) = (history_path(), key_path()) else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_vec(history)?;
+    let data = if encrypt {
+        let key = match read_key(&key_path) {
+            Some(key) => key,
+            None => {
+                let mut key = [0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                write_private(&key_path, &key)?;
+                key
+            }
+        };
+        seal(&key, &json)
+    } else {
+        json
+    };
+    write_private(&path, &data)
+}
+
+fn remove_files() -> std::io::Result<()> {
+    for path in [history_path(), key_path()].into_iter().flatten() {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn read_key(path: &Path) -> Option<[u8; 32]> {
+    std::fs::read(path).ok()?.try_into().ok()
+}
+
+/// Write `data` to `path`, readable by the user only
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        let mut file = options.open(path)?;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        file.write_all(data)
+    }
+    #[cfg(not(unix))]
+    {
+        options.open(path)?.write_all(data)
+    }
+}
+
+/// `plain` encrypted under `key`: [`MAGIC`], a random nonce, the ciphertext
+/// and its tag
+fn seal(key: &[u8; 32], plain: &[u8]) -> Vec<u8> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let mut buffer = plain.to_vec();
+    let tag = ChaCha20Poly1305::new(key).encrypt(&nonce, MAGIC, &mut buffer);
+    [MAGIC.as_slice(), &nonce, &buffer, &tag].concat()
+}
+
+/// The plain text of `data` sealed under `key`, or None when it was sealed
+/// under another key or has been altered
+fn open(key: &[u8; 32], data: &[u8]) -> Option<Vec<u8>> {
+    let rest = data.strip_prefix(MAGIC.as_slice())?;
+    if rest.len() < NONCE_LEN + TAG_LEN {
+        return None;
+    }
+    let (nonce, rest) = rest.split_at(NONCE_LEN);
+    let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LEN);
+    let mut buffer = ciphertext.to_vec();
+    ChaCha20Poly1305::new(key)
+        .decrypt(
+            nonce.try_into().ok()?,
+            MAGIC,
+            &mut buffer,
+            tag.try_into().ok()?,
+        )
+        .ok()?;
+    Some(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str) -> ClipItem {
+        ClipItem {
+            text: text.to_string(),
+            source: ClipSource::Copy,
+        }
+    }
+
+    #[test]
+    fn test_push() {
+        let mut history = ClipboardHistory::default();
+        for text in ["a", "b", "c", "a"] {
+            history.push(item(text), 3);
+        }
+        let texts: Vec<&str> = history.items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, ["a", "c", "b"]);
+
+        history.push(item("d"), 3);
+        let texts: Vec<&str> = history.items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, ["d", "a", "c"]);
+    }
+
+    #[test]
+    fn test_label() {
+        assert_eq!(item("  ls -la\n").label(20), "ls -la");
+        assert_eq!(item("a\r\nb\tc").label(20), "a↵b c");
+        assert_eq!(item("abcdefgh").label(5), "abcd…");
+        assert_eq!(item("abcde").label(5), "abcde");
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = [7u8; 32];
+        let sealed = seal(&key, b"secret");
+        assert!(sealed.starts_with(MAGIC));
+        assert!(!sealed.windows(6).any(|w| w == b"secret"));
+        assert_eq!(open(&key, &sealed).as_deref(), Some(&b"secret"[..]));
+
+        assert_eq!(open(&[8u8; 32], &sealed), None);
+        let mut altered = sealed.clone();
+        *altered.last_mut().unwrap() ^= 1;
+        assert_eq!(open(&key, &altered), None);
+        assert_eq!(open(&key, MAGIC), None);
+    }
+}
@@ -0,0 +1,207 @@
+//! Recently closed tabs
+//!
+//! Frontends record each tab as it closes — the template it was opened from,
+//! its working directory, title, color and optionally a plain-text copy of
+//! its scrollback — and "Reopen Closed Tab" pops the most recent one. The
+//! stack is process-wide, so a tab closed in one window can be reopened from
+//! another.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use cterm_core::screen::Screen;
+use cterm_core::term::Terminal;
+
+use crate::config::{StickyTabConfig, TabsConfig};
+
+/// Maximum number of scrollback lines kept per closed tab
+pub const MAX_SNAPSHOT_LINES: usize = 1000;
+
+/// Recently closed tabs, most recent last
+static RECENTLY_CLOSED: Mutex<ClosedTabs> = Mutex::new(ClosedTabs::new());
+
+/// What is needed to bring a closed tab back
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClosedTab {
+    /// Tab title at the time it was closed
+    pub title: String,
+    /// Name of the tab template the tab was opened from
+    pub template: Option<String>,
+    /// Working directory of the foreground process
+    pub cwd: Option<String>,
+    /// Tab color override
+    pub color: Option<String>,
+    /// Text of the primary screen and scrollback
+    pub scrollback: Option<String>,
+    /// Lines the view was scrolled back from the bottom
+    pub scroll_offset: usize,
+}
+
+impl ClosedTab {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Save the last `MAX_SNAPSHOT_LINES` lines of the primary screen and its
+    /// scrollback, and how far it is scrolled back. Wrapped rows are joined so
+    /// the text reflows on replay.
+    pub fn capture_scrollback(&mut self, screen: &Screen) {
+        self.scroll_offset = screen.scroll_offset;
+        // While the alternate screen is active the primary grid is parked
+        let grid = screen.alternate_grid().unwrap_or_else(|| screen.grid());
+        let rows = screen
+            .scrollback()
+            .iter()
+            .chain((0..grid.height()).filter_map(|r| grid.row(r)));
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut row_text = String::new();
+        for row in rows {
+            row.write_text_to(&mut row_text);
+            match lines.last_mut() {
+                Some(line) if row.wrapped => line.push_str(&row_text),
+                _ => lines.push(row_text.clone()),
+            }
+        }
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        if lines.is_empty() {
+            self.scrollback = None;
+            return;
+        }
+
+        let start = lines.len().saturating_sub(MAX_SNAPSHOT_LINES);
+        self.scrollback = Some(lines[start..].join("\n"));
+    }
+
+    /// Feed the saved scrollback into a freshly opened tab's terminal and
+    /// scroll back to where the view was
+    pub fn replay_scrollback(&self, terminal: &mut Terminal) {
+        let Some(ref text) = self.scrollback else {
+            return;
+        };
+        let mut data = text.replace('\n', "\r\n");
+        data.push_str("\r\n");
+        terminal.process(data.as_bytes());
+        terminal.scroll_viewport_up(self.scroll_offset);
+    }
+
+    /// The template this tab was opened from, if it still exists
+    pub fn find_template<'a>(
+        &self,
+        templates: &'a [StickyTabConfig],
+    ) -> Option<&'a StickyTabConfig> {
+        let name = self.template.as_deref()?;
+        templates.iter().find(|t| t.name == name)
+    }
+}
+
+/// Bounded stack of closed tabs
+#[derive(Debug, Default)]
+pub struct ClosedTabs {
+    entries: VecDeque<ClosedTab>,
+}
+
+impl ClosedTabs {
+    pub const fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Push a closed tab, dropping the oldest ones beyond `capacity`
+    pub fn push(&mut self, tab: ClosedTab, capacity: usize) {
+        self.entries.push_back(tab);
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Take the most recently closed tab
+    pub fn pop(&mut self) -> Option<ClosedTab> {
+        self.entries.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Closed tabs, most recent first
+    pub fn iter(&self) -> impl Iterator<Item = &ClosedTab> {
+        self.entries.iter().rev()
+    }
+}
+
+/// Record a closed tab, honoring `[tabs] closed_tab_history` and
+/// `reopen_scrollback`
+pub fn remember(mut tab: ClosedTab, config: &TabsConfig) {
+    if config.closed_tab_history == 0 {
+        return;
+    }
+    if !config.reopen_scrollback {
+        tab.scrollback = None;
+    }
+    if let Ok(mut closed) = RECENTLY_CLOSED.lock() {
+        closed.push(tab, config.closed_tab_history);
+    }
+}
+
+/// Take the most recently closed tab, if any
+pub fn take_last() -> Option<ClosedTab> {
+    RECENTLY_CLOSED.lock().ok()?.pop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cterm_core::screen::ScreenConfig;
+
+    #[test]
+    fn test_bounded_stack() {
+        let mut closed = ClosedTabs::new();
+        for i in 0..5 {
+            closed.push(ClosedTab::new(format!("tab {i}")), 3);
+        }
+        assert_eq!(closed.len(), 3);
+        let titles: Vec<_> = closed.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["tab 4", "tab 3", "tab 2"]);
+        assert_eq!(closed.pop().unwrap().title, "tab 4");
+        assert_eq!(closed.len(), 2);
+    }
+
+    #[test]
+    fn test_scrollback_round_trip() {
+        let mut terminal = Terminal::new(10, 4, ScreenConfig::default());
+        terminal.process(b"hello\r\n0123456789abc\r\nlast");
+
+        let mut tab = ClosedTab::new("shell");
+        tab.capture_scrollback(terminal.screen());
+        assert_eq!(
+            tab.scrollback.as_deref(),
+            Some("hello\n0123456789abc\nlast")
+        );
+
+        let mut reopened = Terminal::new(20, 4, ScreenConfig::default());
+        tab.replay_scrollback(&mut reopened);
+        let screen = reopened.screen();
+        assert_eq!(screen.grid().row(1).unwrap().text(), "0123456789abc");
+        assert_eq!(screen.cursor.row, 3);
+
+        // The view goes back to where it was scrolled
+        terminal.process(b"\r\nmore\r\nlines");
+        terminal.scroll_viewport_up(2);
+        tab.capture_scrollback(terminal.screen());
+        assert_eq!(tab.scroll_offset, 2);
+        let mut reopened = Terminal::new(20, 4, ScreenConfig::default());
+        tab.replay_scrollback(&mut reopened);
+        assert_eq!(reopened.screen().scroll_offset, 2);
+    }
+}
@@ -4,13 +4,19 @@
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use cterm_core::mouse::MouseModifiers;
+use cterm_core::{AttrOverrides, ConformanceLevel, CursorStyle, PermissionPolicy, SelectionConfig};
 use cterm_ui::theme::{FontConfig, Theme};
 
+use crate::file_drop::PathQuoting;
+use crate::safe_mode;
+
 /// Configuration errors
 #[derive(Error, Debug)]
 pub enum ConfigError {
@@ -25,6 +31,9 @@ pub enum ConfigError {
 
     #[error("Config directory not found")]
     NoConfigDir,
+
+    #[error("The configuration cannot be changed in safe mode")]
+    SafeMode,
 }
 
 /// Main configuration struct
@@ -39,11 +48,351 @@ pub struct Config {
     pub tabs: TabsConfig,
     /// Shortcut bindings
     pub shortcuts: ShortcutsConfig,
+    /// Presentation mode settings
+    pub presentation: PresentationConfig,
+    /// Mouse wheel and pointer settings
+    pub input: InputConfig,
+    /// Word boundaries and copy trimming for selections
+    pub selection: SelectionConfig,
+    /// Toast or desktop notification when a long command finishes
+    pub command_notify: CommandNotifyConfig,
+    /// Sounds or speech for the bell and finished commands
+    pub sounds: SoundsConfig,
+    /// What programs may do through sensitive escape sequences
+    pub permissions: PermissionPolicy,
+    /// Clock, timer and battery widgets at the end of the tab bar
+    pub widgets: WidgetsConfig,
+    /// Earlier copies to paste again from the paste history picker
+    pub clipboard_history: ClipboardHistoryConfig,
     /// Named remote hosts (for daemon-backed remote sessions)
     #[serde(default)]
     pub remotes: Vec<RemoteConfig>,
+    /// Named per-tab settings for new tabs and templates
+    #[serde(default)]
+    pub profiles: Vec<ProfileConfig>,
     /// Sticky tabs configuration
     pub sticky_tabs: Vec<StickyTabConfig>,
+    /// Per-host theme and tab color rules
+    #[serde(default)]
+    pub auto_switch: Vec<AutoSwitchRule>,
+    /// Actions fired by output lines matching a regex
+    #[serde(default)]
+    pub triggers: Vec<TriggerConfig>,
+    /// Saved regexes for the Extract Matches panel
+    #[serde(default)]
+    pub extract_presets: Vec<ExtractPreset>,
+    /// Sets of tabs and panes opened together
+    #[serde(default)]
+    pub workspaces: Vec<WorkspaceConfig>,
+    /// Command outputs shown in tab titles through `tabs.title_format`
+    #[serde(default)]
+    pub status_segments: Vec<StatusSegmentConfig>,
+}
+
+/// Restyle a tab while its shell is on a matching host.
+///
+/// The host is taken from shell integration user variables or the prompt;
+/// see [`crate::auto_switch`].
+///
+/// ```toml
+/// [[auto_switch]]
+/// host = "*.prod.example.com"
+/// theme = "Dracula"
+/// tab_color = "#cc0000"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutoSwitchRule {
+    /// Host name pattern; `*` matches any run of characters
+    pub host: String,
+    /// User name pattern that must also match (any user when unset)
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Theme to switch to
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// Tab color to switch to (CSS hex)
+    #[serde(default)]
+    pub tab_color: Option<String>,
+}
+
+/// Fire an action when a line of output matches a regex.
+///
+/// Lines are checked once, as a line feed ends them; see
+/// [`crate::triggers`]. `$0` in `text` and `args` stands for the match and
+/// `$1`, `$2`, ... or `${name}` for its groups.
+///
+/// ```toml
+/// [[triggers]]
+/// regex = "FAILED|panicked"
+/// action = "highlight"
+/// color = "#5c1f1f"
+///
+/// [[triggers]]
+/// regex = "Deployed (\\S+) to production"
+/// action = "notify"
+/// text = "$1 is live"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriggerConfig {
+    /// Regular expression matched against each line
+    pub regex: String,
+    /// What to do on a match
+    pub action: TriggerAction,
+    /// Line background (`highlight`) or tab color (`tab_color`), CSS hex
+    pub color: Option<String>,
+    /// Text to type (`send`) or the notification body (`notify`)
+    pub text: Option<String>,
+    /// Program to run (`command`)
+    pub command: Option<String>,
+    /// Program arguments
+    pub args: Vec<String>,
+}
+
+/// Action of a `[[triggers]]` entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerAction {
+    /// Give the line a background color
+    #[default]
+    Highlight,
+    /// Type `text` into the terminal
+    Send,
+    /// Show a desktop notification
+    Notify,
+    /// Run `command` with `args`
+    Command,
+    /// Set the tab color
+    TabColor,
+}
+
+/// Text printed by a command, shown in tab titles as `{name}` in
+/// `tabs.title_format`; see [`crate::status_segments`].
+///
+/// ```toml
+/// [tabs]
+/// title_format = "{title} {branch}"
+///
+/// [[status_segments]]
+/// name = "branch"
+/// command = "git branch --show-current"
+/// refresh_after_command = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusSegmentConfig {
+    /// Placeholder name in `title_format`
+    pub name: String,
+    /// Shell command whose first line of output is the segment's text
+    pub command: String,
+    /// Seconds between runs (0 runs it only once per directory and after
+    /// commands if `refresh_after_command`)
+    pub interval: u64,
+    /// Also run it again when a command finishes (needs shell integration)
+    pub refresh_after_command: bool,
+    /// Run it in the tab's directory, keeping a text per directory; off for
+    /// segments that are the same everywhere, like a battery level
+    pub per_directory: bool,
+    /// Longest text shown, in characters
+    pub max_width: usize,
+}
+
+impl Default for StatusSegmentConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            command: String::new(),
+            interval: 30,
+            refresh_after_command: false,
+            per_directory: true,
+            max_width: 24,
+        }
+    }
+}
+
+/// Named per-tab settings: shell, working directory, environment, startup
+/// commands, theme, font, scrollback size, long command notification,
+/// permissions and host rules.
+///
+/// Picked from the "New Tab with Profile" menu or named by a tab template's
+/// `profile`; see [`crate::profile`]. Unset fields fall back to
+/// `[general]`, `[appearance]`, `[command_notify]` and `[permissions]`.
+///
+/// ```toml
+/// [[profiles]]
+/// name = "Work"
+/// command = "/bin/zsh"
+/// working_directory = "~/work"
+/// startup_commands = ["source .venv/bin/activate"]
+/// theme = "Nord"
+/// font_size = 13.0
+/// scrollback_lines = 50000
+/// permissions = { file_transfer = "deny" }
+///
+/// [profiles.env]
+/// AWS_PROFILE = "work"
+///
+/// [profiles.command_notify]
+/// min_seconds = 30
+///
+/// [[profiles.auto_switch]]
+/// host = "*.prod.example.com"
+/// theme = "Dracula"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// Name shown in the profile picker
+    pub name: String,
+    /// Command to run (None = default shell)
+    pub command: Option<String>,
+    /// Command arguments
+    pub args: Vec<String>,
+    /// Working directory (None = `[general] working_directory`)
+    pub working_directory: Option<PathBuf>,
+    /// Environment variables, on top of `[general] env`
+    pub env: HashMap<String, String>,
+    /// Theme name (None = the configured theme)
+    pub theme: Option<String>,
+    /// Font family (None = the configured font)
+    pub font_family: Option<String>,
+    /// Font size in points (None = the configured size)
+    pub font_size: Option<f64>,
+    /// Scrollback buffer size (None = `[general] scrollback_lines`)
+    pub scrollback_lines: Option<usize>,
+    /// Commands typed into the shell once it is ready, one per line
+    pub startup_commands: Vec<String>,
+    /// Long command notification override (None = use `[command_notify]`)
+    pub command_notify: Option<CommandNotifyConfig>,
+    /// Permission policy override (None = use `[permissions]`)
+    pub permissions: Option<PermissionPolicy>,
+    /// Host rules checked before the `[[auto_switch]]` ones
+    pub auto_switch: Vec<AutoSwitchRule>,
+}
+
+/// Tabs and panes opened together from File > Workspaces or with
+/// `cterm --workspace NAME`; see [`crate::workspace`].
+///
+/// ```toml
+/// [[workspaces]]
+/// name = "dev"
+///
+/// [[workspaces.tabs]]
+/// template = "Claude"
+///
+/// [[workspaces.tabs]]
+/// title = "Server"
+/// working_directory = "~/src/app"
+/// startup_commands = ["npm run dev"]
+///
+/// [[workspaces.tabs.panes]]
+/// split = "right"
+/// startup_commands = ["npm test -- --watch"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// Name shown in the menu and given to `--workspace`
+    pub name: String,
+    /// Tabs, opened in order
+    #[serde(default)]
+    pub tabs: Vec<WorkspaceTabConfig>,
+}
+
+/// A tab of a workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceTabConfig {
+    /// Tab template to start from (None = the default shell)
+    pub template: Option<String>,
+    /// Tab title (None = the template's name)
+    pub title: Option<String>,
+    /// Profile overriding the template's
+    pub profile: Option<String>,
+    /// Working directory overriding the template's
+    pub working_directory: Option<PathBuf>,
+    /// Command overriding the template's
+    pub command: Option<String>,
+    /// Command arguments
+    pub args: Vec<String>,
+    /// Commands typed into the shell once it is ready, replacing the
+    /// template's
+    pub startup_commands: Vec<String>,
+    /// Tab color (hex) overriding the template's
+    pub color: Option<String>,
+    /// Panes split off the tab, in order
+    pub panes: Vec<WorkspacePaneConfig>,
+}
+
+/// A pane split off a workspace tab's focused pane
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspacePaneConfig {
+    /// Where the pane goes
+    pub split: PaneSplit,
+    /// Working directory (None = the tab's)
+    pub working_directory: Option<PathBuf>,
+    /// Command to run (None = the shell)
+    pub command: Option<String>,
+    /// Command arguments
+    pub args: Vec<String>,
+    /// Commands typed into the shell once it is ready
+    pub startup_commands: Vec<String>,
+}
+
+/// Where a workspace pane goes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaneSplit {
+    /// Right of the focused pane
+    #[default]
+    Right,
+    /// Below the focused pane
+    Down,
+}
+
+/// A saved regex for the Extract Matches panel.
+///
+/// Listed after the built-in presets (see [`builtin_extract_presets`]).
+/// When the pattern has a capture group, the first group is listed.
+///
+/// ```toml
+/// [[extract_presets]]
+/// name = "Jira tickets"
+/// pattern = "\\b(?:OPS|WEB)-\\d+\\b"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractPreset {
+    /// Name shown in the preset list
+    pub name: String,
+    /// Regular expression to extract
+    pub pattern: String,
+}
+
+impl ExtractPreset {
+    fn new(name: &str, pattern: &str) -> Self {
+        Self {
+            name: name.into(),
+            pattern: pattern.into(),
+        }
+    }
+}
+
+/// Presets always offered by the Extract Matches panel
+pub fn builtin_extract_presets() -> Vec<ExtractPreset> {
+    vec![
+        ExtractPreset::new(
+            "IPv4 addresses",
+            r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b",
+        ),
+        ExtractPreset::new("URLs", r#"(?:https?|ftp)://[^\s<>"'`]+[^\s<>"'`.,;:!?)\]]"#),
+        ExtractPreset::new("Email addresses", r"\b[\w.+-]+@[\w-]+(?:\.[\w-]+)+\b"),
+        ExtractPreset::new("Ticket IDs", r"\b[A-Z][A-Z0-9]+-\d+\b"),
+        ExtractPreset::new(
+            "File names",
+            r"(?:^|[\s'\x22(])((?:[\w.~-]*/)*[\w-][\w.-]*\.[A-Za-z][A-Za-z0-9]{0,7})\b",
+        ),
+        ExtractPreset::new("Git hashes", r"\b[0-9a-f]{7,40}\b"),
+    ]
 }
 
 /// A named remote host for daemon-backed sessions.
@@ -74,6 +423,18 @@ impl Config {
     pub fn find_remote(&self, name: &str) -> Option<&RemoteConfig> {
         self.remotes.iter().find(|r| r.name == name)
     }
+
+    /// Look up a profile by name.
+    pub fn profile(&self, name: &str) -> Option<&ProfileConfig> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Built-in extraction presets followed by the saved ones.
+    pub fn all_extract_presets(&self) -> Vec<ExtractPreset> {
+        let mut presets = builtin_extract_presets();
+        presets.extend(self.extract_presets.iter().cloned());
+        presets
+    }
 }
 
 /// General settings
@@ -90,6 +451,15 @@ pub struct GeneralConfig {
     pub confirm_close_with_running: bool,
     /// Copy on select
     pub copy_on_select: bool,
+    /// Also copy the selection's colors and styles as RTF (macOS and
+    /// Windows)
+    pub copy_rich_text: bool,
+    /// Ask before pasting this many lines or more into a program that
+    /// hasn't enabled bracketed paste (0 never asks)
+    pub confirm_paste_lines: usize,
+    /// Ask before pasting control characters, such as escape sequences or
+    /// bidi overrides, into a program that hasn't enabled bracketed paste
+    pub confirm_paste_control: bool,
     /// Working directory for new tabs
     pub working_directory: Option<PathBuf>,
     /// Environment variables to set
@@ -97,8 +467,15 @@ pub struct GeneralConfig {
     /// TERM environment variable (default: xterm-256color)
     /// Common values: xterm-256color, xterm-direct, screen-256color
     pub term: Option<String>,
+    /// VT level reported to programs (device attributes, DECRQSS) until one
+    /// selects another with DECSCL
+    pub conformance_level: ConformanceLevel,
     /// Show the Debug submenu under Help
     pub show_debug_menu: bool,
+    /// Keep local usage statistics (never sent anywhere)
+    pub usage_stats: bool,
+    /// Reopen the windows and tabs open at exit on the next launch
+    pub restore_session: bool,
 }
 
 impl Default for GeneralConfig {
@@ -109,10 +486,16 @@ impl Default for GeneralConfig {
             scrollback_lines: 10000,
             confirm_close_with_running: true,
             copy_on_select: false,
+            copy_rich_text: false,
+            confirm_paste_lines: 2,
+            confirm_paste_control: true,
             working_directory: None,
             env: HashMap::new(),
             term: None,
+            conformance_level: ConformanceLevel::default(),
             show_debug_menu: false,
+            usage_stats: false,
+            restore_session: false,
         }
     }
 }
@@ -131,12 +514,21 @@ pub struct AppearanceConfig {
     pub cursor_style: CursorStyleConfig,
     /// Cursor blink
     pub cursor_blink: bool,
-    /// Opacity (0.0 - 1.0)
+    /// Background opacity (0.0 - 1.0); a theme's own `opacity` wins
     pub opacity: f64,
+    /// Blur what shows through a translucent background (macOS, Windows)
+    pub background_blur: bool,
     /// Padding around terminal content
     pub padding: u32,
     /// Enable bold text
     pub bold_is_bright: bool,
+    /// Render-time replacements for blink, concealed and dim text
+    pub text_styles: AttrOverrides,
+    /// Mark finished commands with a green or red bar along the left edge
+    /// (needs shell integration)
+    pub command_gutter: bool,
+    /// Font size on particular displays, by display name (macOS)
+    pub display_font_sizes: HashMap<String, f64>,
 }
 
 impl Default for AppearanceConfig {
@@ -148,14 +540,52 @@ impl Default for AppearanceConfig {
             cursor_style: CursorStyleConfig::Block,
             cursor_blink: true,
             opacity: 1.0,
+            background_blur: false,
             padding: 4,
             bold_is_bright: false,
+            text_styles: AttrOverrides::default(),
+            command_gutter: true,
+            display_font_sizes: HashMap::new(),
+        }
+    }
+}
+
+impl AppearanceConfig {
+    /// Lowest background opacity applied, so the window never disappears
+    pub const MIN_OPACITY: f64 = 0.1;
+
+    /// Background opacity to draw `theme` with: the theme's own opacity
+    /// when it sets one, else `opacity`
+    pub fn background_opacity(&self, theme: &Theme) -> f64 {
+        let opacity = theme.opacity.unwrap_or(self.opacity);
+        if opacity.is_nan() {
+            return 1.0;
         }
+        opacity.clamp(Self::MIN_OPACITY, 1.0)
+    }
+
+    /// Whether the window background is see-through at all
+    pub fn is_translucent(&self, theme: &Theme) -> bool {
+        self.background_opacity(theme) < 1.0
+    }
+
+    /// Font size for windows on the display named `display`: its entry in
+    /// `display_font_sizes` (matched ignoring case), else `font.size`
+    pub fn font_size_for_display(&self, display: Option<&str>) -> f64 {
+        display
+            .and_then(|display| {
+                self.display_font_sizes
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(display))
+            })
+            .map(|(_, &size)| size)
+            .filter(|size| size.is_finite() && *size > 0.0)
+            .unwrap_or(self.font.size)
     }
 }
 
 /// Cursor style options
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum CursorStyleConfig {
     #[default]
@@ -164,6 +594,16 @@ pub enum CursorStyleConfig {
     Bar,
 }
 
+impl From<CursorStyleConfig> for CursorStyle {
+    fn from(style: CursorStyleConfig) -> Self {
+        match style {
+            CursorStyleConfig::Block => CursorStyle::Block,
+            CursorStyleConfig::Underline => CursorStyle::Underline,
+            CursorStyleConfig::Bar => CursorStyle::Bar,
+        }
+    }
+}
+
 /// Tab settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -176,8 +616,13 @@ pub struct TabsConfig {
     pub new_tab_position: NewTabPosition,
     /// Show tab close button
     pub show_close_button: bool,
-    /// Tab title format
+    /// Tab title format: `{title}` for the title set by the shell and
+    /// `{name}` for the status segment called `name`
     pub title_format: String,
+    /// Number of closed tabs remembered for Reopen Closed Tab (0 disables)
+    pub closed_tab_history: usize,
+    /// Restore the scrollback text of reopened tabs
+    pub reopen_scrollback: bool,
 }
 
 impl Default for TabsConfig {
@@ -188,6 +633,267 @@ impl Default for TabsConfig {
             new_tab_position: NewTabPosition::End,
             show_close_button: true,
             title_format: "{title}".into(),
+            closed_tab_history: 10,
+            reopen_scrollback: true,
+        }
+    }
+}
+
+/// Presentation mode settings
+///
+/// Presentation mode enlarges the font, hides the tab bar and scrollbar, and
+/// enforces a minimum text contrast; it can also show recent keystrokes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PresentationConfig {
+    /// Font size multiplier while presenting
+    pub font_scale: f64,
+    /// Minimum WCAG contrast ratio between text and background (1.0 = off)
+    pub minimum_contrast: f64,
+    /// Show recently pressed keys on screen
+    pub show_keystrokes: bool,
+}
+
+impl Default for PresentationConfig {
+    fn default() -> Self {
+        Self {
+            font_scale: 1.5,
+            minimum_contrast: 4.5,
+            show_keystrokes: false,
+        }
+    }
+}
+
+impl PresentationConfig {
+    /// Font size to use while presenting, given the normal font size
+    pub fn font_size(&self, base: f64) -> f64 {
+        (base * self.font_scale.max(1.0)).min(72.0)
+    }
+}
+
+/// Mouse wheel and pointer settings
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputConfig {
+    /// Lines scrolled per mouse wheel notch
+    pub wheel_lines: usize,
+    /// Force natural (content follows the fingers) or traditional wheel
+    /// direction; unset follows the system
+    pub natural_scrolling: Option<bool>,
+    /// Lines scrolled per tick while a selection is dragged past the top or
+    /// bottom edge (0 disables)
+    pub autoscroll_lines: usize,
+    /// Paste on middle click (the primary selection on Linux, the clipboard
+    /// elsewhere)
+    pub middle_click_paste: bool,
+    /// Bytes written per tick while pasting (0 writes a paste in one go)
+    pub paste_chunk_bytes: usize,
+    /// Modifier that keeps the mouse for selecting, menus and scrolling while
+    /// an application is tracking it
+    pub mouse_override: MouseOverride,
+    /// Show a preview when hovering a URL (page title and icon, fetched from
+    /// the site) or the path of an image file (thumbnail)
+    pub hover_previews: bool,
+    /// Hide the mouse pointer over the terminal while typing, until the mouse
+    /// moves
+    pub hide_pointer_while_typing: bool,
+    /// How dropped file paths are quoted for the shell
+    pub path_quoting: PathQuoting,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            wheel_lines: 3,
+            natural_scrolling: None,
+            autoscroll_lines: 1,
+            middle_click_paste: true,
+            paste_chunk_bytes: 4096,
+            mouse_override: MouseOverride::Shift,
+            hover_previews: false,
+            hide_pointer_while_typing: true,
+            path_quoting: PathQuoting::default(),
+        }
+    }
+}
+
+impl InputConfig {
+    /// Time between selection autoscroll ticks
+    pub const AUTOSCROLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    /// Time between paste chunks
+    pub const PASTE_CHUNK_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Lines to scroll for `notches` wheel notches
+    pub fn wheel_scroll_lines(&self, notches: usize) -> usize {
+        self.wheel_lines.max(1) * notches.max(1)
+    }
+
+    /// Whether a wheel movement delivered as `up` scrolls towards older output
+    ///
+    /// `inverted` tells whether the system already reversed the direction for
+    /// natural scrolling; pass false where the platform doesn't report it.
+    pub fn wheel_up(&self, up: bool, inverted: bool) -> bool {
+        match self.natural_scrolling {
+            Some(natural) if natural != inverted => !up,
+            _ => up,
+        }
+    }
+
+    /// Whether a mouse event with `modifiers` bypasses an application's
+    /// mouse tracking and is handled locally instead
+    pub fn overrides_mouse_tracking(&self, modifiers: MouseModifiers) -> bool {
+        match self.mouse_override {
+            MouseOverride::Shift => modifiers.shift,
+            MouseOverride::Alt => modifiers.alt,
+        }
+    }
+}
+
+/// Modifier that bypasses mouse reporting
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MouseOverride {
+    /// The xterm/VTE convention
+    #[default]
+    Shift,
+    /// Alt, or Option on macOS; leaves Shift-clicks to the application
+    #[serde(alias = "option")]
+    Alt,
+}
+
+/// Report long-running commands when they finish
+///
+/// Needs shell integration marks (OSC 133 C before a command's output and
+/// OSC 133 D when it finishes). Templates can override this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandNotifyConfig {
+    /// Minimum run time in seconds for a command to be reported (0 disables)
+    pub min_seconds: u64,
+    /// Send a desktop notification instead when the window isn't focused
+    pub desktop_when_unfocused: bool,
+}
+
+impl Default for CommandNotifyConfig {
+    fn default() -> Self {
+        Self {
+            min_seconds: 10,
+            desktop_when_unfocused: true,
+        }
+    }
+}
+
+impl CommandNotifyConfig {
+    /// Whether a command that ran for `duration` should be reported
+    pub fn should_notify(&self, duration: Duration) -> bool {
+        self.min_seconds > 0 && duration.as_secs() >= self.min_seconds
+    }
+}
+
+/// Audio cues for the bell and for finished commands; see [`crate::sounds`]
+///
+/// Each cue is empty (silent), `"beep"` for the system alert sound,
+/// `"say:<text>"` to speak `<text>`, or the path of a sound file. Command cues
+/// play for the commands `[command_notify]` reports.
+///
+/// ```toml
+/// [sounds]
+/// bell = "beep"
+/// command_finished = "say:Done in {duration}"
+/// command_failed = "say:Failed with exit code {exit}"
+/// only_when_unfocused = true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundsConfig {
+    /// Cue for the terminal bell
+    pub bell: String,
+    /// Cue for a command that finished successfully
+    pub command_finished: String,
+    /// Cue for a command that exited with an error (`command_finished`
+    /// when empty)
+    pub command_failed: String,
+    /// Play cues only while the window is in the background
+    pub only_when_unfocused: bool,
+}
+
+/// Widgets shown at the end of the tab bar; see [`crate::tab_widgets`]
+///
+/// ```toml
+/// [widgets]
+/// clock = true
+/// timer = true
+/// timer_minutes = 25
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WidgetsConfig {
+    /// Show the time of day
+    pub clock: bool,
+    /// Show the clock as 2:05 PM instead of 14:05
+    pub clock_12h: bool,
+    /// Show a countdown timer (click to start or pause, right-click to reset)
+    pub timer: bool,
+    /// Length of the countdown in minutes
+    pub timer_minutes: u32,
+    /// Show the battery charge on machines with a battery
+    pub battery: bool,
+}
+
+impl Default for WidgetsConfig {
+    fn default() -> Self {
+        Self {
+            clock: false,
+            clock_12h: false,
+            timer: false,
+            timer_minutes: 25,
+            battery: false,
+        }
+    }
+}
+
+impl WidgetsConfig {
+    /// Whether any widget is shown
+    pub fn any(&self) -> bool {
+        self.clock || self.timer || self.battery
+    }
+
+    /// Length of the countdown
+    pub fn timer_length(&self) -> Duration {
+        Duration::from_secs(u64::from(self.timer_minutes.max(1)) * 60)
+    }
+}
+
+/// Text copied from cterm, kept for the paste history picker; see
+/// [`crate::clipboard_history`]
+///
+/// ```toml
+/// [clipboard_history]
+/// max_items = 100
+/// include_osc52 = true
+/// persist = true
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardHistoryConfig {
+    /// Copies kept, newest first (0 keeps none)
+    pub max_items: usize,
+    /// Also keep text that programs copy with OSC 52
+    pub include_osc52: bool,
+    /// Keep the history across restarts, in the local data directory
+    pub persist: bool,
+    /// Encrypt the saved history with a key stored next to it
+    pub encrypt: bool,
+}
+
+impl Default for ClipboardHistoryConfig {
+    fn default() -> Self {
+        Self {
+            max_items: 50,
+            include_osc52: false,
+            persist: false,
+            encrypt: true,
         }
     }
 }
@@ -202,8 +908,20 @@ pub enum TabBarVisibility {
     Never,
 }
 
+impl TabBarVisibility {
+    /// Whether the tab bar is shown with `tab_count` tabs open. With
+    /// `Multiple`, enabled tab bar widgets keep it shown for a single tab.
+    pub fn shows(self, tab_count: usize, widgets: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Multiple => tab_count > 1 || widgets,
+            Self::Never => false,
+        }
+    }
+}
+
 /// Tab bar position
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum TabBarPosition {
     #[default]
@@ -376,9 +1094,21 @@ pub struct SshTabConfig {
     pub extra_args: Vec<String>,
     /// Jump host / proxy (-J)
     pub jump_host: Option<String>,
+    /// Seconds between keepalive pings (`ServerAliveInterval`; default: 30,
+    /// 0 disables)
+    pub keepalive_interval: Option<u32>,
 }
 
+/// Keepalive interval used when an SSH tab does not set one
+const DEFAULT_SSH_KEEPALIVE_SECS: u32 = 30;
+
 impl SshTabConfig {
+    /// Effective keepalive interval in seconds (0 = disabled).
+    fn keepalive_secs(&self) -> u32 {
+        self.keepalive_interval
+            .unwrap_or(DEFAULT_SSH_KEEPALIVE_SECS)
+    }
+
     /// Convert to a native [`cterm_core::SshConfig`] for the puressh-based PTY
     /// backend. cterm no longer spawns the system `ssh` binary; SSH tabs open a
     /// native connection instead.
@@ -408,6 +1138,10 @@ impl SshTabConfig {
             // Interactive shell traffic is small and latency-sensitive; leave
             // compression off here (the gRPC daemon tunnel enables it itself).
             compress: false,
+            keepalive_interval: match self.keepalive_secs() {
+                0 => None,
+                secs => Some(std::time::Duration::from_secs(secs.into())),
+            },
             host_key_prompt: None,
             password_prompt: None,
             passphrase_prompt: None,
@@ -442,6 +1176,78 @@ impl SshTabConfig {
                     remote_port: f.remote_port as u32,
                 })
                 .collect(),
+            keepalive_interval: self.keepalive_secs(),
+        }
+    }
+}
+
+/// Serial-port configuration for a sticky tab
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SerialTabConfig {
+    /// Device path (`/dev/ttyUSB0`, `COM3`)
+    pub path: String,
+    /// Line speed (default: 115200)
+    pub baud_rate: u32,
+    /// Bits per character, 5 to 8 (default: 8)
+    pub data_bits: u8,
+    /// Parity: "none", "odd" or "even" (default: none)
+    pub parity: cterm_core::Parity,
+    /// Stop bits, 1 or 2 (default: 1)
+    pub stop_bits: u8,
+    /// Flow control: "none", "software" (XON/XOFF) or "hardware" (RTS/CTS)
+    pub flow_control: cterm_core::FlowControl,
+}
+
+impl Default for SerialTabConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            baud_rate: 115200,
+            data_bits: 8,
+            parity: cterm_core::Parity::None,
+            stop_bits: 1,
+            flow_control: cterm_core::FlowControl::None,
+        }
+    }
+}
+
+impl SerialTabConfig {
+    /// Convert to the [`cterm_core::SerialConfig`] used to open the port.
+    pub fn to_serial_config(&self) -> cterm_core::SerialConfig {
+        cterm_core::SerialConfig {
+            path: self.path.clone(),
+            baud_rate: self.baud_rate,
+            data_bits: self.data_bits,
+            parity: self.parity,
+            stop_bits: self.stop_bits,
+            flow_control: self.flow_control,
+        }
+    }
+
+    /// Convert to wire-level [`SerialParams`] for a daemon `CreateSession`
+    /// request.
+    ///
+    /// [`SerialParams`]: cterm_proto::proto::SerialParams
+    pub fn to_serial_params(&self) -> cterm_proto::proto::SerialParams {
+        use cterm_proto::proto::{SerialFlowControl, SerialParity};
+        let parity = match self.parity {
+            cterm_core::Parity::None => SerialParity::None,
+            cterm_core::Parity::Odd => SerialParity::Odd,
+            cterm_core::Parity::Even => SerialParity::Even,
+        };
+        let flow_control = match self.flow_control {
+            cterm_core::FlowControl::None => SerialFlowControl::None,
+            cterm_core::FlowControl::Software => SerialFlowControl::Software,
+            cterm_core::FlowControl::Hardware => SerialFlowControl::Hardware,
+        };
+        cterm_proto::proto::SerialParams {
+            path: self.path.clone(),
+            baud_rate: self.baud_rate,
+            data_bits: self.data_bits.into(),
+            parity: parity.into(),
+            stop_bits: self.stop_bits.into(),
+            flow_control: flow_control.into(),
         }
     }
 }
@@ -458,10 +1264,15 @@ pub struct ShortcutsConfig {
     pub close_window: String,
     pub copy: String,
     pub paste: String,
+    /// Pick an earlier copy to paste from the clipboard history
+    pub paste_history: String,
     pub select_all: String,
     pub zoom_in: String,
     pub zoom_out: String,
     pub zoom_reset: String,
+    pub magnify_in: String,
+    pub magnify_out: String,
+    pub magnify_reset: String,
     pub scroll_up: String,
     pub scroll_down: String,
     pub scroll_page_up: String,
@@ -469,6 +1280,47 @@ pub struct ShortcutsConfig {
     pub preferences: String,
     pub find: String,
     pub reset: String,
+    /// Toggle ASCII-art-safe rendering for the active tab (unbound by default)
+    pub ascii_art_safe: String,
+    /// Toggle presentation mode
+    pub presentation_mode: String,
+    /// Label URLs, paths and hashes on screen for keyboard selection
+    pub hints: String,
+    /// Open the Extract Matches panel
+    pub extract_matches: String,
+    /// Reopen the most recently closed tab
+    pub reopen_closed_tab: String,
+    /// Lock or unlock input to the active tab (unbound by default)
+    pub read_only: String,
+    /// Scroll to the previous or next shell prompt
+    pub previous_prompt: String,
+    pub next_prompt: String,
+    /// Select the output of the last command
+    pub select_command_output: String,
+    /// Split the focused pane, the new one on the right
+    pub split_right: String,
+    /// Split the focused pane, the new one below
+    pub split_down: String,
+    /// Close the focused pane
+    pub close_pane: String,
+    /// Focus the pane in a direction
+    pub focus_pane_left: String,
+    pub focus_pane_right: String,
+    pub focus_pane_up: String,
+    pub focus_pane_down: String,
+    /// Move the focused pane's divider in a direction
+    pub resize_pane_left: String,
+    pub resize_pane_right: String,
+    pub resize_pane_up: String,
+    pub resize_pane_down: String,
+    /// Lock or unlock scrolling of the focused pane to its neighbor
+    /// (unbound by default)
+    pub lock_scrolling: String,
+    /// Snap the window to the left or right half of its display, or fill
+    /// it (macOS; unbound elsewhere)
+    pub snap_left: String,
+    pub snap_right: String,
+    pub snap_maximize: String,
 }
 
 impl Default for ShortcutsConfig {
@@ -482,10 +1334,14 @@ impl Default for ShortcutsConfig {
             close_window: "Ctrl+Shift+Q".into(),
             copy: "Ctrl+Shift+C".into(),
             paste: "Ctrl+Shift+V".into(),
+            paste_history: "Ctrl+Alt+Shift+V".into(),
             select_all: "Ctrl+Shift+A".into(),
             zoom_in: "Ctrl+Plus".into(),
             zoom_out: "Ctrl+Minus".into(),
             zoom_reset: "Ctrl+0".into(),
+            magnify_in: "Ctrl+Alt+Plus".into(),
+            magnify_out: "Ctrl+Alt+Minus".into(),
+            magnify_reset: "Ctrl+Alt+0".into(),
             scroll_up: "Shift+PageUp".into(),
             scroll_down: "Shift+PageDown".into(),
             scroll_page_up: "PageUp".into(),
@@ -493,10 +1349,43 @@ impl Default for ShortcutsConfig {
             preferences: "Ctrl+Comma".into(),
             find: "Ctrl+Shift+F".into(),
             reset: "Ctrl+Shift+R".into(),
+            ascii_art_safe: String::new(),
+            presentation_mode: "Ctrl+Shift+P".into(),
+            hints: "Ctrl+Shift+E".into(),
+            extract_matches: "Ctrl+Shift+X".into(),
+            reopen_closed_tab: "Ctrl+Alt+Shift+T".into(),
+            read_only: String::new(),
+            previous_prompt: "Ctrl+Shift+PageUp".into(),
+            next_prompt: "Ctrl+Shift+PageDown".into(),
+            select_command_output: "Ctrl+Shift+O".into(),
+            split_right: "Ctrl+Shift+D".into(),
+            split_down: "Ctrl+Alt+Shift+D".into(),
+            close_pane: "Ctrl+Alt+Shift+W".into(),
+            focus_pane_left: "Ctrl+Shift+Left".into(),
+            focus_pane_right: "Ctrl+Shift+Right".into(),
+            focus_pane_up: "Ctrl+Shift+Up".into(),
+            focus_pane_down: "Ctrl+Shift+Down".into(),
+            resize_pane_left: "Ctrl+Alt+Shift+Left".into(),
+            resize_pane_right: "Ctrl+Alt+Shift+Right".into(),
+            resize_pane_up: "Ctrl+Alt+Shift+Up".into(),
+            resize_pane_down: "Ctrl+Alt+Shift+Down".into(),
+            lock_scrolling: String::new(),
+            snap_left: macos_shortcut("Ctrl+Alt+Left"),
+            snap_right: macos_shortcut("Ctrl+Alt+Right"),
+            snap_maximize: macos_shortcut("Ctrl+Alt+Enter"),
         }
     }
 }
 
+/// `shortcut` on macOS, unbound on platforms without the action
+fn macos_shortcut(shortcut: &str) -> String {
+    if cfg!(target_os = "macos") {
+        shortcut.into()
+    } else {
+        String::new()
+    }
+}
+
 /// Sticky tab configuration (tab template)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -527,13 +1416,29 @@ pub struct StickyTabConfig {
     /// Environment variables
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Inherit cterm's environment (false = start from HOME, USER, PATH and
+    /// a few other essentials, plus `env`)
+    #[serde(default = "default_true")]
+    pub inherit_env: bool,
+    /// Commands typed into the shell once it is ready (e.g. activating a
+    /// virtualenv), one per line
+    #[serde(default)]
+    pub startup_commands: Vec<String>,
     /// Docker-specific configuration (if present, this is a Docker tab)
     pub docker: Option<DockerTabConfig>,
     /// SSH-specific configuration (if present, this is an SSH remote tab)
     pub ssh: Option<SshTabConfig>,
+    /// Serial-port configuration (if present, this is a serial tab)
+    pub serial: Option<SerialTabConfig>,
     /// Remote host name (references a `[[remotes]]` entry).
     /// When set, the session runs on the remote ctermd daemon instead of locally.
     pub remote: Option<String>,
+    /// Long command notification override (None = use `[command_notify]`)
+    pub command_notify: Option<CommandNotifyConfig>,
+    /// Permission policy override (None = use `[permissions]`)
+    pub permissions: Option<PermissionPolicy>,
+    /// Profile whose settings fill in the ones this template leaves unset
+    pub profile: Option<String>,
 }
 
 impl Default for StickyTabConfig {
@@ -550,9 +1455,15 @@ impl Default for StickyTabConfig {
             keep_open: false,
             unique: false,
             env: HashMap::new(),
+            inherit_env: true,
+            startup_commands: Vec::new(),
             docker: None,
             ssh: None,
+            serial: None,
             remote: None,
+            command_notify: None,
+            permissions: None,
+            profile: None,
         }
     }
 }
@@ -624,6 +1535,11 @@ impl StickyTabConfig {
         self.ssh.is_some()
     }
 
+    /// Check if this is a serial tab
+    pub fn is_serial(&self) -> bool {
+        self.serial.is_some()
+    }
+
     /// Get the command and arguments for this sticky tab.
     ///
     /// For Docker tabs, this builds the appropriate docker exec/run command.
@@ -789,6 +1705,17 @@ impl StickyTabConfig {
             ..Default::default()
         }
     }
+
+    /// Create a serial console tab configuration
+    pub fn serial(serial: SerialTabConfig) -> Self {
+        Self {
+            name: format!("Serial: {}", serial.path),
+            color: Some("#f59e0b".into()), // Amber for hardware consoles
+            keep_open: true,
+            serial: Some(serial),
+            ..Default::default()
+        }
+    }
 }
 
 /// Expand shell variables and tilde in a path string.
@@ -796,7 +1723,7 @@ impl StickyTabConfig {
 /// Supports:
 /// - `~` or `~/...` → home directory
 /// - `$VAR` or `${VAR}` → environment variable
-fn expand_path(path: &Path) -> PathBuf {
+pub(crate) fn expand_path(path: &Path) -> PathBuf {
     let s = path.to_string_lossy();
 
     // Expand ~ at the start
@@ -883,13 +1810,26 @@ pub fn config_path() -> Option<PathBuf> {
     config_dir().map(|p| p.join("config.toml"))
 }
 
+/// Get the user themes directory
+pub fn themes_dir() -> Option<PathBuf> {
+    config_dir().map(|p| p.join("themes"))
+}
+
 /// Get the sticky tabs file path
 pub fn sticky_tabs_path() -> Option<PathBuf> {
     config_dir().map(|p| p.join("sticky_tabs.toml"))
 }
 
-/// Load configuration from file
+/// Load configuration from file, or the defaults in safe mode
 pub fn load_config() -> Result<Config, ConfigError> {
+    if safe_mode::is_enabled() {
+        return Ok(Config::default());
+    }
+    read_config()
+}
+
+/// Read and parse config.toml, even in safe mode
+pub(crate) fn read_config() -> Result<Config, ConfigError> {
     let path = config_path().ok_or(ConfigError::NoConfigDir)?;
 
     if !path.exists() {
@@ -903,6 +1843,11 @@ pub fn load_config() -> Result<Config, ConfigError> {
     if let Some(ref wd) = config.general.working_directory {
         config.general.working_directory = Some(expand_path(wd));
     }
+    for profile in &mut config.profiles {
+        if let Some(ref wd) = profile.working_directory {
+            profile.working_directory = Some(expand_path(wd));
+        }
+    }
 
     Ok(config)
 }
@@ -918,27 +1863,105 @@ pub fn resolve_theme(config: &Config) -> Theme {
         return custom.clone();
     }
 
-    let theme_id = &config.appearance.theme;
-    let themes = Theme::builtin_themes();
+    find_theme(&config.appearance.theme).unwrap_or_else(Theme::dark)
+}
+
+/// Look up a built-in theme by display name or short ID, then a user theme
+/// by name
+pub fn find_theme(theme_id: &str) -> Option<Theme> {
+    find_builtin_theme(theme_id)
+        .or_else(|| load_user_themes().into_iter().find(|t| t.name == theme_id))
+}
+
+fn find_builtin_theme(theme_id: &str) -> Option<Theme> {
+    Theme::builtin_themes().into_iter().find(|t| {
+        t.name == theme_id
+            || matches!(
+                (t.name.as_str(), theme_id),
+                ("Default Dark", "dark")
+                    | ("Default Light", "light")
+                    | ("Tokyo Night", "tokyo_night")
+                    | ("Tokyo Night", "tokyo-night")
+                    | ("Dracula", "dracula")
+                    | ("Nord", "nord")
+            )
+    })
+}
+
+/// Load the themes saved in the user themes directory, sorted by name
+pub fn load_user_themes() -> Vec<Theme> {
+    if safe_mode::is_enabled() {
+        return Vec::new();
+    }
+    themes_dir()
+        .map(|dir| load_themes_from(&dir))
+        .unwrap_or_default()
+}
+
+fn load_themes_from(dir: &Path) -> Vec<Theme> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut themes: Vec<Theme> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| {
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(ConfigError::from)
+                .and_then(|content| Ok(toml::from_str::<Theme>(&content)?));
+            match parsed {
+                Ok(theme) => Some(theme),
+                Err(e) => {
+                    log::warn!("Skipping theme {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+    themes.sort_by(|a, b| a.name.cmp(&b.name));
     themes
-        .into_iter()
-        .find(|t| {
-            t.name == *theme_id
-                || matches!(
-                    (t.name.as_str(), theme_id.as_str()),
-                    ("Default Dark", "dark")
-                        | ("Default Light", "light")
-                        | ("Tokyo Night", "tokyo_night")
-                        | ("Tokyo Night", "tokyo-night")
-                        | ("Dracula", "dracula")
-                        | ("Nord", "nord")
-                )
+}
+
+/// Save a theme to the user themes directory, replacing any theme with the
+/// same file name. Returns the path written.
+pub fn save_user_theme(theme: &Theme) -> Result<PathBuf, ConfigError> {
+    refuse_in_safe_mode()?;
+    let dir = themes_dir().ok_or(ConfigError::NoConfigDir)?;
+    save_theme_to(&dir, theme)
+}
+
+fn save_theme_to(dir: &Path, theme: &Theme) -> Result<PathBuf, ConfigError> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(theme_file_name(&theme.name));
+    std::fs::write(&path, toml::to_string_pretty(theme)?)?;
+    Ok(path)
+}
+
+/// File name for a theme: its name lowercased, with anything but letters
+/// and digits replaced by `_`
+fn theme_file_name(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
         })
-        .unwrap_or_else(Theme::dark)
+        .collect();
+    if stem.is_empty() {
+        "theme.toml".to_string()
+    } else {
+        format!("{}.toml", stem)
+    }
 }
 
 /// Save configuration to file
 pub fn save_config(config: &Config) -> Result<(), ConfigError> {
+    refuse_in_safe_mode()?;
     let dir = config_dir().ok_or(ConfigError::NoConfigDir)?;
     std::fs::create_dir_all(&dir)?;
 
@@ -946,11 +1969,27 @@ pub fn save_config(config: &Config) -> Result<(), ConfigError> {
     let content = toml::to_string_pretty(config)?;
     std::fs::write(&path, content)?;
 
+    // Saved preferences take effect in this process right away
+    crate::usage_stats::configure(config);
+    crate::clipboard_history::configure(config);
+
     Ok(())
 }
 
+/// Safe mode leaves the files in the config directory alone
+fn refuse_in_safe_mode() -> Result<(), ConfigError> {
+    if safe_mode::is_enabled() {
+        Err(ConfigError::SafeMode)
+    } else {
+        Ok(())
+    }
+}
+
 /// Load sticky tabs configuration
 pub fn load_sticky_tabs() -> Result<Vec<StickyTabConfig>, ConfigError> {
+    if safe_mode::is_enabled() {
+        return Ok(Vec::new());
+    }
     let path = sticky_tabs_path().ok_or(ConfigError::NoConfigDir)?;
 
     if !path.exists() {
@@ -974,6 +2013,7 @@ pub fn load_sticky_tabs() -> Result<Vec<StickyTabConfig>, ConfigError> {
 
 /// Save sticky tabs configuration
 pub fn save_sticky_tabs(tabs: &[StickyTabConfig]) -> Result<(), ConfigError> {
+    refuse_in_safe_mode()?;
     let dir = config_dir().ok_or(ConfigError::NoConfigDir)?;
     std::fs::create_dir_all(&dir)?;
 
@@ -994,6 +2034,9 @@ pub fn save_sticky_tabs(tabs: &[StickyTabConfig]) -> Result<(), ConfigError> {
 /// Perform background git pull if config is a git repo.
 /// Returns true if config was updated and should be reloaded.
 pub fn background_sync() -> bool {
+    if safe_mode::is_enabled() {
+        return false;
+    }
     let Some(dir) = config_dir() else {
         return false;
     };
@@ -1145,6 +2188,9 @@ pub fn default_tool_shortcuts() -> Vec<ToolShortcutEntry> {
 /// Load tool shortcuts from the platform-specific config file.
 /// Returns defaults if the file doesn't exist.
 pub fn load_tool_shortcuts() -> Result<Vec<ToolShortcutEntry>, ConfigError> {
+    if safe_mode::is_enabled() {
+        return Ok(default_tool_shortcuts());
+    }
     let path = tool_shortcuts_path().ok_or(ConfigError::NoConfigDir)?;
 
     if !path.exists() {
@@ -1164,6 +2210,7 @@ pub fn load_tool_shortcuts() -> Result<Vec<ToolShortcutEntry>, ConfigError> {
 
 /// Save tool shortcuts to the platform-specific config file
 pub fn save_tool_shortcuts(tools: &[ToolShortcutEntry]) -> Result<(), ConfigError> {
+    refuse_in_safe_mode()?;
     let dir = config_dir().ok_or(ConfigError::NoConfigDir)?;
     std::fs::create_dir_all(&dir)?;
 
@@ -1200,6 +2247,23 @@ mod tests {
         assert!(serialized.contains("[appearance]"));
     }
 
+    #[test]
+    fn test_user_themes_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut theme = Theme::nord();
+        theme.name = "My Nord!".into();
+        theme.colors.ansi[1] = cterm_core::color::Rgb::new(0xff, 0, 0);
+
+        let path = save_theme_to(dir.path(), &theme).unwrap();
+        assert_eq!(path.file_name().unwrap(), "my_nord_.toml");
+        std::fs::write(dir.path().join("broken.toml"), "not a theme").unwrap();
+
+        let themes = load_themes_from(dir.path());
+        assert_eq!(themes.len(), 1);
+        assert_eq!(themes[0].name, "My Nord!");
+        assert_eq!(themes[0].colors.ansi[1], theme.colors.ansi[1]);
+    }
+
     #[test]
     fn test_sticky_tab_claude() {
         let tab = StickyTabConfig::claude();
@@ -1317,6 +2381,23 @@ mod tests {
         assert_eq!(cfg.remote_command, Some("ls -la".to_string()));
     }
 
+    #[test]
+    fn test_ssh_keepalive_interval() {
+        let mut ssh = SshTabConfig {
+            host: "example.com".to_string(),
+            ..Default::default()
+        };
+        // Unset uses the default; 0 turns keepalives off.
+        assert_eq!(
+            ssh.to_ssh_config().keepalive_interval,
+            Some(std::time::Duration::from_secs(30))
+        );
+        assert_eq!(ssh.to_ssh_params().keepalive_interval, 30);
+        ssh.keepalive_interval = Some(0);
+        assert!(ssh.to_ssh_config().keepalive_interval.is_none());
+        assert_eq!(ssh.to_ssh_params().keepalive_interval, 0);
+    }
+
     // StickyTabConfig tests
     #[test]
     fn test_sticky_tab_docker_exec() {
@@ -1358,6 +2439,33 @@ mod tests {
         assert!(ssh.agent_forward);
     }
 
+    #[test]
+    fn test_sticky_tab_serial() {
+        let tab = StickyTabConfig::serial(SerialTabConfig {
+            path: "/dev/ttyUSB0".into(),
+            parity: cterm_core::Parity::Even,
+            ..Default::default()
+        });
+        assert_eq!(tab.name, "Serial: /dev/ttyUSB0");
+        assert!(tab.is_serial());
+        assert!(!tab.is_ssh());
+
+        let config = tab.serial.as_ref().unwrap().to_serial_config();
+        assert_eq!(config.baud_rate, 115200);
+        assert_eq!(config.parity, cterm_core::Parity::Even);
+        let params = tab.serial.as_ref().unwrap().to_serial_params();
+        assert_eq!(params.path, "/dev/ttyUSB0");
+        assert_eq!(params.parity(), cterm_proto::proto::SerialParity::Even);
+        assert_eq!(params.data_bits, 8);
+
+        let parsed: SerialTabConfig =
+            toml::from_str("path = \"COM3\"\nbaud_rate = 9600\nflow_control = \"hardware\"")
+                .unwrap();
+        assert_eq!(parsed.baud_rate, 9600);
+        assert_eq!(parsed.stop_bits, 1);
+        assert_eq!(parsed.flow_control, cterm_core::FlowControl::Hardware);
+    }
+
     #[test]
     fn test_get_command_args_regular() {
         let tab = StickyTabConfig {
@@ -1420,6 +2528,226 @@ mod tests {
         assert_eq!(docker.shell, Some("/bin/sh".to_string()));
     }
 
+    #[test]
+    fn test_sticky_tab_inherit_env_defaults_on() {
+        assert!(StickyTabConfig::default().inherit_env);
+
+        let tab: StickyTabConfig = toml::from_str("name = \"Plain\"").unwrap();
+        assert!(tab.inherit_env);
+
+        let tab: StickyTabConfig = toml::from_str(
+            r#"
+            name = "Clean"
+            inherit_env = false
+            env = { FOO = "bar" }
+        "#,
+        )
+        .unwrap();
+        assert!(!tab.inherit_env);
+        assert_eq!(tab.env.get("FOO").map(String::as_str), Some("bar"));
+    }
+
+    #[test]
+    fn test_input_config() {
+        let input = InputConfig::default();
+        assert_eq!(input.wheel_scroll_lines(1), 3);
+        assert_eq!(input.wheel_scroll_lines(0), 3);
+        assert!(input.wheel_up(true, false));
+        assert!(input.wheel_up(true, true));
+        assert!(input.middle_click_paste);
+
+        let input: InputConfig = toml::from_str(
+            r#"
+            wheel_lines = 5
+            natural_scrolling = true
+            middle_click_paste = false
+        "#,
+        )
+        .unwrap();
+        assert_eq!(input.wheel_scroll_lines(2), 10);
+        // Reversed unless the system already did it
+        assert!(!input.wheel_up(true, false));
+        assert!(input.wheel_up(true, true));
+        assert_eq!(input.autoscroll_lines, 1);
+        assert!(!input.middle_click_paste);
+        assert_eq!(input.paste_chunk_bytes, 4096);
+
+        let shift = MouseModifiers {
+            shift: true,
+            ..Default::default()
+        };
+        let alt = MouseModifiers {
+            alt: true,
+            ..Default::default()
+        };
+        assert!(input.overrides_mouse_tracking(shift));
+        assert!(!input.overrides_mouse_tracking(alt));
+        let input: InputConfig = toml::from_str(r#"mouse_override = "option""#).unwrap();
+        assert_eq!(input.mouse_override, MouseOverride::Alt);
+        assert!(!input.overrides_mouse_tracking(shift));
+        assert!(input.overrides_mouse_tracking(alt));
+
+        let input = InputConfig {
+            natural_scrolling: Some(false),
+            ..Default::default()
+        };
+        assert!(input.wheel_up(true, false));
+        assert!(!input.wheel_up(true, true));
+    }
+
+    #[test]
+    fn test_command_notify_config() {
+        let notify = CommandNotifyConfig::default();
+        assert!(!notify.should_notify(Duration::from_secs(9)));
+        assert!(notify.should_notify(Duration::from_secs(10)));
+
+        let config: Config = toml::from_str(
+            r#"
+            [command_notify]
+            min_seconds = 0
+
+            [[sticky_tabs]]
+            name = "Builds"
+            command_notify = { min_seconds = 60, desktop_when_unfocused = false }
+        "#,
+        )
+        .unwrap();
+        assert!(!config
+            .command_notify
+            .should_notify(Duration::from_secs(3600)));
+        let notify = config.sticky_tabs[0].command_notify.unwrap();
+        assert!(notify.should_notify(Duration::from_secs(60)));
+        assert!(!notify.desktop_when_unfocused);
+    }
+
+    #[test]
+    fn test_permissions_config() {
+        use cterm_core::{Capability, Permission};
+
+        let config: Config = toml::from_str(
+            r#"
+            [permissions]
+            file_transfer = "ask"
+
+            [[sticky_tabs]]
+            name = "Untrusted"
+            permissions = { clipboard_write = "deny", title_change = "deny" }
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.permissions.file_transfer, Permission::Ask);
+        assert_eq!(config.permissions.clipboard_read, Permission::Ask);
+        assert_eq!(config.permissions.notifications, Permission::Allow);
+        let policy = config.sticky_tabs[0].permissions.unwrap();
+        assert_eq!(policy.get(Capability::ClipboardWrite), Permission::Deny);
+        assert_eq!(policy.get(Capability::TitleChange), Permission::Deny);
+        assert_eq!(policy.get(Capability::ClipboardRead), Permission::Ask);
+    }
+
+    #[test]
+    fn test_extract_presets() {
+        let config: Config = toml::from_str(
+            r#"
+            [[extract_presets]]
+            name = "Tickets"
+            pattern = 'OPS-(\d+)'
+        "#,
+        )
+        .unwrap();
+        let presets = config.all_extract_presets();
+        assert_eq!(presets.len(), builtin_extract_presets().len() + 1);
+        assert_eq!(presets.last().unwrap().pattern, r"OPS-(\d+)");
+
+        let screen = cterm_core::Screen::new(80, 24, Default::default());
+        for preset in builtin_extract_presets() {
+            assert!(
+                screen.extract_matches(&preset.pattern).is_ok(),
+                "{}",
+                preset.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_selection_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [selection]
+            word_chars = "_.-/~"
+        "#,
+        )
+        .unwrap();
+        assert!(config.selection.is_word_char('/'));
+        assert!(config.selection.trim_trailing_whitespace);
+    }
+
+    #[test]
+    fn test_appearance_text_styles() {
+        use cterm_core::AttrSubstitute;
+
+        let appearance: AppearanceConfig = toml::from_str(
+            r#"
+            [text_styles]
+            blink = "off"
+            dim = "italic"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(appearance.text_styles.blink, AttrSubstitute::Off);
+        assert_eq!(appearance.text_styles.conceal, AttrSubstitute::Normal);
+        assert_eq!(appearance.text_styles.dim, AttrSubstitute::Italic);
+    }
+
+    #[test]
+    fn test_background_opacity() {
+        let mut appearance = AppearanceConfig::default();
+        let mut theme = Theme::dark();
+        assert_eq!(appearance.background_opacity(&theme), 1.0);
+        assert!(!appearance.is_translucent(&theme));
+
+        appearance.opacity = 0.8;
+        assert_eq!(appearance.background_opacity(&theme), 0.8);
+        assert!(appearance.is_translucent(&theme));
+
+        // The theme's opacity overrides the configured one
+        theme.opacity = Some(0.9);
+        assert_eq!(appearance.background_opacity(&theme), 0.9);
+        theme.opacity = Some(1.0);
+        assert!(!appearance.is_translucent(&theme));
+
+        // Out-of-range values are clamped
+        theme.opacity = None;
+        appearance.opacity = 0.0;
+        assert_eq!(
+            appearance.background_opacity(&theme),
+            AppearanceConfig::MIN_OPACITY
+        );
+        appearance.opacity = 3.0;
+        assert_eq!(appearance.background_opacity(&theme), 1.0);
+    }
+
+    #[test]
+    fn test_display_font_sizes() {
+        let mut appearance: AppearanceConfig = toml::from_str(
+            r#"
+            [display_font_sizes]
+            "DELL U2720Q" = 16.0
+            "Projector" = 0.0
+            "#,
+        )
+        .unwrap();
+        appearance.font.size = 13.0;
+        assert_eq!(appearance.font_size_for_display(Some("DELL U2720Q")), 16.0);
+        assert_eq!(appearance.font_size_for_display(Some("dell u2720q")), 16.0);
+        assert_eq!(
+            appearance.font_size_for_display(Some("Built-in Retina Display")),
+            13.0
+        );
+        assert_eq!(appearance.font_size_for_display(None), 13.0);
+        // Sizes that can't be drawn are ignored
+        assert_eq!(appearance.font_size_for_display(Some("Projector")), 13.0);
+    }
+
     #[test]
     fn test_docker_mode_default() {
         let mode = DockerMode::default();
@@ -1438,6 +2766,15 @@ mod tests {
         assert!(matches!(visibility, TabBarVisibility::Always));
     }
 
+    #[test]
+    fn test_tab_bar_visibility_shows() {
+        assert!(TabBarVisibility::Always.shows(1, false));
+        assert!(!TabBarVisibility::Multiple.shows(1, false));
+        assert!(TabBarVisibility::Multiple.shows(1, true));
+        assert!(TabBarVisibility::Multiple.shows(2, false));
+        assert!(!TabBarVisibility::Never.shows(3, true));
+    }
+
     #[test]
     fn test_default_tool_shortcuts_not_empty() {
         let shortcuts = default_tool_shortcuts();
@@ -0,0 +1,166 @@
+//! Control socket for driving a running cterm from a shell
+//!
+//! The frontend calls [`listen`] once at startup and carries out each
+//! [`ControlRequest`] on its main thread. `cterm new-tab`,
+//! `cterm new-window` and `cterm --workspace` connect with [`send`] and
+//! write one JSON request per connection. The socket lives next to the
+//! ctermd socket (a named pipe on Windows); the most recently started
+//! instance owns it.
+
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Something a shell asks the running cterm to do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Open a tab in the frontmost window, in `directory` if given and
+    /// otherwise where Ctrl+T would
+    NewTab { directory: Option<PathBuf> },
+    /// Open a window
+    NewWindow,
+    /// Open the tabs of a `[[workspaces]]` entry in the frontmost window
+    OpenWorkspace { name: String },
+}
+
+/// Path of the control socket
+pub fn control_socket_path() -> PathBuf {
+    let mut path = cterm_client::default_socket_path();
+    path.set_extension("ctl");
+    path
+}
+
+/// Send a request to the running instance
+///
+/// Fails with `NotFound` or `ConnectionRefused` when cterm is not running.
+pub fn send(request: &ControlRequest) -> io::Result<()> {
+    use std::io::Write;
+
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+
+    #[cfg(unix)]
+    let mut stream = std::os::unix::net::UnixStream::connect(control_socket_path())?;
+    #[cfg(windows)]
+    let mut stream = std::fs::OpenOptions::new()
+        .write(true)
+        .open(control_socket_path())?;
+
+    stream.write_all(line.as_bytes())
+}
+
+/// Start accepting requests on a background thread
+///
+/// `on_request` is called on that thread, so the frontend has to hand the
+/// request over to its main thread.
+#[cfg(unix)]
+pub fn listen(on_request: impl Fn(ControlRequest) + Send + 'static) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+
+    let path = control_socket_path();
+    // Take over from an instance that exited without cleaning up, or that
+    // is being replaced by this one
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    std::thread::Builder::new()
+        .name("cterm-control".into())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Some(request) = read_request(io::BufReader::new(stream)) {
+                            on_request(request);
+                        }
+                    }
+                    Err(e) => log::warn!("Control socket accept failed: {}", e),
+                }
+            }
+        })?;
+    Ok(())
+}
+
+/// Start accepting requests on a background thread
+///
+/// `on_request` is called on that thread, so the frontend has to hand the
+/// request over to its main thread.
+#[cfg(windows)]
+pub fn listen(on_request: impl Fn(ControlRequest) + Send + 'static) -> io::Result<()> {
+    use tokio::io::AsyncBufReadExt;
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let path = control_socket_path();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let mut server = {
+        let _guard = runtime.enter();
+        ServerOptions::new().create(&path)?
+    };
+
+    std::thread::Builder::new()
+        .name("cterm-control".into())
+        .spawn(move || {
+            runtime.block_on(async move {
+                loop {
+                    if let Err(e) = server.connect().await {
+                        log::warn!("Control pipe connect failed: {}", e);
+                        return;
+                    }
+                    let client = server;
+                    server = match ServerOptions::new().create(&path) {
+                        Ok(server) => server,
+                        Err(e) => {
+                            log::warn!("Failed to reopen control pipe: {}", e);
+                            return;
+                        }
+                    };
+                    let mut line = String::new();
+                    let mut reader = tokio::io::BufReader::new(client);
+                    if reader.read_line(&mut line).await.is_ok() {
+                        if let Some(request) = read_request(line.as_bytes()) {
+                            on_request(request);
+                        }
+                    }
+                }
+            })
+        })?;
+    Ok(())
+}
+
+/// Parse the first line of a connection
+fn read_request(mut reader: impl BufRead) -> Option<ControlRequest> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    match serde_json::from_str(&line) {
+        Ok(request) => Some(request),
+        Err(e) => {
+            log::warn!("Ignoring bad control request {:?}: {}", line.trim(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let request = ControlRequest::NewTab {
+            directory: Some(PathBuf::from("/tmp")),
+        };
+        let line = serde_json::to_string(&request).unwrap();
+        assert_eq!(line, r#"{"request":"new_tab","directory":"/tmp"}"#);
+        assert_eq!(read_request(line.as_bytes()), Some(request));
+        assert_eq!(
+            read_request(&b"{\"request\":\"new_window\"}\n"[..]),
+            Some(ControlRequest::NewWindow)
+        );
+        assert_eq!(read_request(&b"{\"request\":\"quit\"}\n"[..]), None);
+    }
+}
@@ -94,6 +94,26 @@ impl ReconnectedSession {
     }
 }
 
+/// Attach to a session as an extra viewer, e.g. for a monitor window.
+///
+/// Unlike reconnecting, this passes no size so the session keeps the
+/// dimensions of the client that owns it.
+pub async fn attach_viewer(
+    conn: &DaemonConnection,
+    session_id: &str,
+) -> Result<ReconnectedSession, ClientError> {
+    let info = conn.get_session(session_id).await?;
+    let (handle, screen) = conn.attach_session(session_id, 0, 0).await?;
+    Ok(ReconnectedSession {
+        handle,
+        title: info.title,
+        custom_title: info.custom_title,
+        tab_color: info.tab_color,
+        template_name: info.template_name,
+        screen,
+    })
+}
+
 /// Reconnect to all running daemon sessions.
 ///
 /// Returns a list of ReconnectedSessions including screen snapshots.
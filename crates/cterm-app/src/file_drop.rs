@@ -6,6 +6,10 @@ use std::io;
 use std::path::{Path, PathBuf};
 
 use base64::Engine;
+use cterm_core::hints::{Hint, HintKind};
+use cterm_core::pty::Pty;
+use cterm_core::term::Terminal;
+use serde::{Deserialize, Serialize};
 
 /// Size threshold above which a warning is shown in the dialog (1 MB).
 pub const SIZE_WARNING_THRESHOLD: u64 = 1_048_576;
@@ -20,7 +24,7 @@ pub struct FileDropInfo {
 
 /// Action the user chose from the file drop dialog.
 pub enum FileDropAction {
-    PastePath,
+    PastePath { quoting: PathQuoting },
     PasteContents,
     CreateViaBase64 { filename: String },
     CreateViaPrintf { filename: String },
@@ -70,10 +74,76 @@ pub fn shell_escape(s: &str) -> String {
     out
 }
 
+/// How paths inserted into a terminal are quoted for its shell
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathQuoting {
+    /// Pick from the shell running in the tab, POSIX when it is unknown
+    #[default]
+    Auto,
+    /// Single quotes, with `'` written as `'\''`
+    Posix,
+    /// Single quotes, with `'` doubled
+    #[serde(alias = "pwsh")]
+    PowerShell,
+    /// Double quotes, for cmd.exe (Windows paths can't contain `"`)
+    Cmd,
+    /// Insert paths as they are
+    None,
+}
+
+impl PathQuoting {
+    /// Resolve [`PathQuoting::Auto`] for a shell given by program name or path
+    pub fn for_shell(self, shell: Option<&str>) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        // Windows paths are split here too, whichever platform this runs on
+        let name = shell
+            .and_then(|shell| shell.rsplit(['/', '\\']).next())
+            .map(|name| name.to_ascii_lowercase());
+        match name.as_deref().map(|name| name.trim_end_matches(".exe")) {
+            Some("pwsh" | "powershell") => Self::PowerShell,
+            Some("cmd") => Self::Cmd,
+            _ => Self::Posix,
+        }
+    }
+
+    /// Resolve [`PathQuoting::Auto`] for the shell in `terminal`'s foreground,
+    /// or where that can't be seen (Windows), the program its PTY started
+    pub fn for_terminal(self, terminal: &Terminal) -> Self {
+        #[cfg(unix)]
+        let shell = terminal.foreground_process_name();
+        #[cfg(not(unix))]
+        let shell: Option<String> = None;
+        let shell = shell.or_else(|| terminal.pty().and_then(Pty::program).map(String::from));
+        self.for_shell(shell.as_deref())
+    }
+
+    /// Text to copy for a chosen hint: paths are quoted, anything else is
+    /// copied as shown
+    pub fn quote_hint(self, hint: &Hint) -> String {
+        match hint.kind {
+            HintKind::Path => self.quote(&hint.text),
+            HintKind::Url | HintKind::Hash | HintKind::Ip => hint.text.clone(),
+        }
+    }
+
+    /// Quote `path`; [`PathQuoting::Auto`] quotes as POSIX
+    pub fn quote(self, path: &str) -> String {
+        match self {
+            Self::Auto | Self::Posix => shell_escape(path),
+            Self::PowerShell => format!("'{}'", path.replace('\'', "''")),
+            Self::Cmd => format!("\"{}\"", path),
+            Self::None => path.to_string(),
+        }
+    }
+}
+
 /// Build the string that should be written to the PTY for the given action.
 pub fn build_pty_input(info: &FileDropInfo, action: FileDropAction) -> io::Result<String> {
     match action {
-        FileDropAction::PastePath => Ok(shell_escape(&info.path.to_string_lossy())),
+        FileDropAction::PastePath { quoting } => Ok(quoting.quote(&info.path.to_string_lossy())),
         FileDropAction::PasteContents => {
             let contents = std::fs::read_to_string(&info.path)?;
             Ok(contents)
@@ -167,8 +237,45 @@ mod tests {
             size: 100,
             is_text: true,
         };
-        let result = build_pty_input(&info, FileDropAction::PastePath).unwrap();
-        assert_eq!(result, "'/tmp/my file.txt'");
+        let paste = |quoting| build_pty_input(&info, FileDropAction::PastePath { quoting });
+        assert_eq!(paste(PathQuoting::Auto).unwrap(), "'/tmp/my file.txt'");
+        assert_eq!(paste(PathQuoting::None).unwrap(), "/tmp/my file.txt");
+    }
+
+    #[test]
+    fn test_path_quoting() {
+        let auto = PathQuoting::Auto;
+        assert_eq!(auto.for_shell(Some("bash")), PathQuoting::Posix);
+        assert_eq!(auto.for_shell(None), PathQuoting::Posix);
+        assert_eq!(
+            auto.for_shell(Some("/usr/bin/pwsh")),
+            PathQuoting::PowerShell
+        );
+        assert_eq!(
+            auto.for_shell(Some(r"C:\Windows\System32\cmd.exe")),
+            PathQuoting::Cmd
+        );
+        assert_eq!(PathQuoting::None.for_shell(Some("zsh")), PathQuoting::None);
+
+        assert_eq!(PathQuoting::Posix.quote("it's"), "'it'\\''s'");
+        assert_eq!(PathQuoting::PowerShell.quote("it's"), "'it''s'");
+        assert_eq!(
+            PathQuoting::Cmd.quote(r"C:\My Files\a.txt"),
+            r#""C:\My Files\a.txt""#
+        );
+        assert_eq!(PathQuoting::None.quote("it's"), "it's");
+
+        let hint = |kind, text: &str| Hint {
+            label: "a".into(),
+            kind,
+            text: text.into(),
+            row: 0,
+            col: 0,
+            width: text.len(),
+        };
+        let posix = PathQuoting::Posix;
+        assert_eq!(posix.quote_hint(&hint(HintKind::Path, "a b")), "'a b'");
+        assert_eq!(posix.quote_hint(&hint(HintKind::Hash, "abc123")), "abc123");
     }
 
     #[test]
@@ -125,6 +125,7 @@ impl PendingFileManager {
     /// Take the pending file with the given ID
     pub fn take_pending(&mut self, id: u64) -> Option<PendingFile> {
         if self.pending.as_ref().is_some_and(|p| p.id == id) {
+            remove_preview(id);
             self.pending.take()
         } else {
             None
@@ -134,6 +135,7 @@ impl PendingFileManager {
     /// Discard the pending file with the given ID
     pub fn discard(&mut self, id: u64) {
         if self.pending.as_ref().is_some_and(|p| p.id == id) {
+            remove_preview(id);
             if let Some(file) = self.pending.take() {
                 // Clean up temp file if needed
                 if let FileData::TempFile { path, .. } = file.data {
@@ -180,6 +182,38 @@ impl PendingFileManager {
         Some(dir.join(name))
     }
 
+    /// Write a copy of the pending file to a temporary directory under its
+    /// own name, so a previewer can tell its type from the extension
+    ///
+    /// The copy is removed when the file is saved or discarded.
+    pub fn preview_copy(&self, id: u64) -> std::io::Result<PathBuf> {
+        let file = self
+            .pending
+            .as_ref()
+            .filter(|p| p.id == id)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "No pending file"))?;
+
+        // The name comes from the remote side: keep only its last component
+        let name = file
+            .name
+            .as_deref()
+            .and_then(|name| std::path::Path::new(name).file_name())
+            .unwrap_or_else(|| std::ffi::OsStr::new("download"));
+
+        let dir = preview_dir(id);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(name);
+        match &file.data {
+            FileData::Memory(data) => std::fs::write(&path, data)?,
+            FileData::TempFile {
+                path: temp_path, ..
+            } => {
+                std::fs::copy(temp_path, &path)?;
+            }
+        }
+        Ok(path)
+    }
+
     /// Save the pending file to the given path
     pub fn save_to_path(&mut self, id: u64, path: &std::path::Path) -> std::io::Result<usize> {
         let file = self
@@ -215,6 +249,20 @@ impl PendingFileManager {
     }
 }
 
+/// Temporary directory holding the preview copy of a pending file
+fn preview_dir(id: u64) -> PathBuf {
+    std::env::temp_dir().join(format!("cterm-preview-{}-{}", std::process::id(), id))
+}
+
+fn remove_preview(id: u64) {
+    let dir = preview_dir(id);
+    if dir.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            log::warn!("Failed to remove preview {}: {}", dir.display(), e);
+        }
+    }
+}
+
 /// Helper module for common directories
 pub mod dirs {
     use std::path::PathBuf;
@@ -227,3 +275,27 @@ pub mod dirs {
         home_dir().map(|h| h.join("Downloads"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_copy_uses_file_name_only() {
+        let mut manager = PendingFileManager::new();
+        manager.set_pending(
+            u64::MAX - 7,
+            Some("../../etc/report.pdf".into()),
+            b"%PDF".to_vec(),
+        );
+
+        let path = manager.preview_copy(u64::MAX - 7).unwrap();
+        assert_eq!(path.file_name().unwrap(), "report.pdf");
+        assert_eq!(path.parent(), Some(preview_dir(u64::MAX - 7).as_path()));
+        assert_eq!(std::fs::read(&path).unwrap(), b"%PDF");
+        assert!(manager.preview_copy(1).is_err());
+
+        manager.discard(u64::MAX - 7);
+        assert!(!path.exists());
+    }
+}
@@ -4,23 +4,44 @@
 //! including configuration management, session handling, sticky tabs,
 //! seamless upgrade functionality, and daemon session management.
 
+pub mod auto_switch;
+pub mod cli;
+pub mod clipboard_history;
+pub mod closed_tabs;
 pub mod config;
+pub mod control;
 pub mod daemon_reconnect;
 pub mod daemon_session;
 pub mod docker;
 pub mod file_drop;
 pub mod file_transfer;
 pub mod git_sync;
+pub mod link_preview;
+pub mod live_config;
 pub mod log_capture;
+pub mod paste;
+pub mod presentation;
+pub mod profile;
 pub mod quick_open;
+pub mod safe_mode;
 pub mod session;
+pub mod settings;
 pub mod shortcuts;
+pub mod sounds;
 pub mod ssh_history;
+pub mod startup;
+pub mod status_segments;
+pub mod tab_widgets;
+pub mod tmux;
+pub mod triggers;
 pub mod upgrade;
+pub mod usage_stats;
+pub mod workspace;
 
 pub use config::{
     background_sync, load_config, load_sticky_tabs, load_tool_shortcuts, save_config,
-    save_config_with_sync, save_sticky_tabs, save_tool_shortcuts, Config, ToolShortcutEntry,
+    save_config_with_sync, save_sticky_tabs, save_tool_shortcuts, Config, ProfileConfig,
+    ToolShortcutEntry,
 };
 pub use daemon_reconnect::{
     check_daemon_sessions, reconnect_all_sessions, ReconnectCheck, ReconnectedSession,
@@ -36,5 +57,5 @@ pub use shortcuts::ShortcutManager;
 pub use upgrade::{execute_upgrade, receive_upgrade, UpgradeError};
 pub use upgrade::{UpdateError, UpdateInfo, Updater, UpgradeState};
 
-pub use config::resolve_theme;
+pub use config::{load_user_themes, resolve_theme, save_user_theme};
 pub use quick_open::{template_type_indicator, QuickOpenMatcher, TemplateMatch};
@@ -0,0 +1,490 @@
+//! Hover previews for links and image paths
+//!
+//! Resting the pointer on a URL or on the path of a local image for
+//! [`HOVER_DELAY`] shows a small popover: the page title and favicon for
+//! http(s) URLs, a thumbnail for PNG, JPEG and GIF files. [`preview`] does
+//! the blocking work (reading the file or fetching the page), so frontends
+//! call it off the UI thread. Results are kept in a small process-wide cache,
+//! failures included, so hovering the same link again shows it at once and a
+//! dead link is not fetched on every hover.
+//!
+//! Previews are off unless `input.hover_previews` is set, since fetching a
+//! page tells its server that the link was looked at.
+
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cterm_core::detect::{DetectedSpan, SpanKind};
+use cterm_core::image_decode::{decode_thumbnail, DecodedImage, ImageDecodeError};
+use thiserror::Error;
+
+/// How long the pointer rests on a link before its preview is shown
+pub const HOVER_DELAY: Duration = Duration::from_millis(600);
+
+/// Largest width or height of an image thumbnail, in pixels
+pub const THUMBNAIL_SIZE: u32 = 240;
+
+/// Largest width or height of a page icon, in pixels
+pub const ICON_SIZE: u32 = 32;
+
+/// Files larger than this are not decoded for a thumbnail
+const MAX_IMAGE_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Bytes of a page read while looking for its title and icon
+const MAX_PAGE_BYTES: u64 = 256 * 1024;
+
+/// Bytes of a favicon or a linked image read at most
+const MAX_REMOTE_IMAGE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Number of previews kept in the cache
+const CACHE_ENTRIES: usize = 32;
+
+/// File extensions that get a thumbnail
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif"];
+
+/// Recent previews, most recent last; `None` records a failed load
+static CACHE: Mutex<VecDeque<(PreviewTarget, Option<Arc<LinkPreview>>)>> =
+    Mutex::new(VecDeque::new());
+
+/// Something a preview can be shown for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PreviewTarget {
+    /// An http or https URL
+    Url(String),
+    /// A local PNG, JPEG or GIF file
+    Image(PathBuf),
+}
+
+impl PreviewTarget {
+    /// What to preview for a detected span, if anything
+    ///
+    /// Relative paths are resolved against `cwd` as for Ctrl/Cmd-click.
+    pub fn for_span(span: &DetectedSpan, cwd: Option<&str>) -> Option<Self> {
+        let target = span.open_target(cwd)?;
+        match span.kind {
+            SpanKind::Url if target.starts_with("http://") || target.starts_with("https://") => {
+                Some(Self::Url(target))
+            }
+            SpanKind::Url => target
+                .strip_prefix("file://")
+                .map(PathBuf::from)
+                .filter(|path| is_image_path(path))
+                .map(Self::Image),
+            SpanKind::Path => {
+                let path = PathBuf::from(target);
+                is_image_path(&path).then_some(Self::Image(path))
+            }
+            SpanKind::GitSha | SpanKind::IpAddress => None,
+        }
+    }
+
+    /// The URL or path, shown under the preview
+    pub fn label(&self) -> String {
+        match self {
+            Self::Url(url) => url.clone(),
+            Self::Image(path) => path.display().to_string(),
+        }
+    }
+}
+
+/// What the popover shows
+#[derive(Debug)]
+pub struct LinkPreview {
+    pub target: PreviewTarget,
+    /// Page title, or the file name of an image
+    pub title: Option<String>,
+    /// Thumbnail of an image, or the icon of a page (RGBA)
+    pub image: Option<DecodedImage>,
+}
+
+/// Why a preview could not be loaded
+#[derive(Debug, Error)]
+pub enum PreviewError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("HTTP error: {0}")]
+    Http(#[from] rsurl::Error),
+    #[error("HTTP status {0}")]
+    Status(u16),
+    #[error("Image error: {0}")]
+    Image(#[from] ImageDecodeError),
+    #[error("File too large to preview ({0} bytes)")]
+    TooLarge(u64),
+    #[error("Nothing to preview")]
+    Empty,
+}
+
+/// The preview for `target`, from the cache or else loaded now
+///
+/// Blocks on file or network I/O, so call it off the UI thread. Returns
+/// `None` if there is nothing to show.
+pub fn preview(target: &PreviewTarget) -> Option<Arc<LinkPreview>> {
+    if let Some(cached) = cached(target) {
+        return cached;
+    }
+    let loaded = match load(target) {
+        Ok(preview) => Some(Arc::new(preview)),
+        Err(e) => {
+            log::debug!("No preview for {}: {}", target.label(), e);
+            None
+        }
+    };
+    let mut cache = CACHE.lock().unwrap();
+    cache.retain(|(t, _)| t != target);
+    if cache.len() >= CACHE_ENTRIES {
+        cache.pop_front();
+    }
+    cache.push_back((target.clone(), loaded.clone()));
+    loaded
+}
+
+/// The cached preview for `target`: `Some(None)` if loading it failed,
+/// `None` if it has not been loaded yet
+pub fn cached(target: &PreviewTarget) -> Option<Option<Arc<LinkPreview>>> {
+    let cache = CACHE.lock().unwrap();
+    cache
+        .iter()
+        .find(|(t, _)| t == target)
+        .map(|(_, preview)| preview.clone())
+}
+
+/// Load the preview for `target`, bypassing the cache
+pub fn load(target: &PreviewTarget) -> Result<LinkPreview, PreviewError> {
+    match target {
+        PreviewTarget::Image(path) => load_image(path),
+        PreviewTarget::Url(url) => load_page(url),
+    }
+}
+
+/// Whether `path` names a file type that gets a thumbnail
+fn is_image_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+fn load_image(path: &Path) -> Result<LinkPreview, PreviewError> {
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_IMAGE_BYTES {
+        return Err(PreviewError::TooLarge(size));
+    }
+    let data = std::fs::read(path)?;
+    Ok(LinkPreview {
+        target: PreviewTarget::Image(path.to_path_buf()),
+        title: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        image: Some(decode_thumbnail(&data, THUMBNAIL_SIZE)?),
+    })
+}
+
+fn load_page(url: &str) -> Result<LinkPreview, PreviewError> {
+    let (content_type, body) = fetch(url, MAX_PAGE_BYTES)?;
+    let target = PreviewTarget::Url(url.to_string());
+
+    // A link straight to an image gets a thumbnail of it
+    if content_type.starts_with("image/") {
+        let (_, body) = fetch(url, MAX_REMOTE_IMAGE_BYTES)?;
+        return Ok(LinkPreview {
+            target,
+            title: None,
+            image: Some(decode_thumbnail(&body, THUMBNAIL_SIZE)?),
+        });
+    }
+
+    let html = String::from_utf8_lossy(&body);
+    let title = page_title(&html);
+    let icon_url = resolve_url(
+        url,
+        &icon_href(&html).unwrap_or_else(|| "/favicon.ico".into()),
+    );
+    let image = fetch(&icon_url, MAX_REMOTE_IMAGE_BYTES)
+        .ok()
+        .and_then(|(_, icon)| decode_thumbnail(&icon, ICON_SIZE).ok());
+    if title.is_none() && image.is_none() {
+        return Err(PreviewError::Empty);
+    }
+    Ok(LinkPreview {
+        target,
+        title,
+        image,
+    })
+}
+
+/// GET `url`, returning its lowercased content type and up to `limit`
+/// bytes of its body
+fn fetch(url: &str, limit: u64) -> Result<(String, Vec<u8>), PreviewError> {
+    let user_agent = format!("cterm/{}", env!("CARGO_PKG_VERSION"));
+    let reader = rsurl::Request::get(url)?
+        .header("User-Agent", &user_agent)
+        .send_reader()?;
+    if reader.status() >= 400 {
+        return Err(PreviewError::Status(reader.status()));
+    }
+    let content_type = reader
+        .header("content-type")
+        .map(|v| v.to_ascii_lowercase())
+        .unwrap_or_default();
+    let mut body = Vec::new();
+    reader.take(limit).read_to_end(&mut body)?;
+    Ok((content_type, body))
+}
+
+/// Text of the page's `<title>`, with entities decoded and white space
+/// collapsed
+fn page_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = decode_entities(&html[start..end])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// `href` of the first `<link rel="icon">` (or `shortcut icon`)
+fn icon_href(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut rest = 0;
+    while let Some(pos) = lower[rest..].find("<link") {
+        let start = rest + pos;
+        let end = start + lower[start..].find('>').unwrap_or(lower.len() - start);
+        let tag = &html[start..end];
+        rest = end;
+        let is_icon = attribute(tag, "rel").is_some_and(|rel| {
+            rel.split_whitespace()
+                .any(|token| token.eq_ignore_ascii_case("icon"))
+        });
+        if is_icon {
+            if let Some(href) = attribute(tag, "href") {
+                return Some(decode_entities(&href));
+            }
+        }
+    }
+    None
+}
+
+/// Value of attribute `name` in the HTML start tag `tag`
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(name) {
+        let at = from + pos;
+        from = at + name.len();
+        // Whole attribute names only (`rel`, not `data-rel`)
+        if !lower[..at].ends_with(|c: char| c.is_ascii_whitespace()) {
+            continue;
+        }
+        let after = lower[from..].trim_start();
+        let Some(after) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value_start = tag.len() - after.trim_start().len();
+        let value = &tag[value_start..];
+        return Some(match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or("").to_string(),
+            _ => value
+                .split(|c: char| c.is_ascii_whitespace())
+                .next()
+                .unwrap_or("")
+                .to_string(),
+        });
+    }
+    None
+}
+
+/// Resolve `href` against the page at `base`
+fn resolve_url(base: &str, href: &str) -> String {
+    if href.starts_with("http://") || href.starts_with("https://") {
+        return href.to_string();
+    }
+    let scheme_end = base.find("://").map_or(0, |i| i + 3);
+    if let Some(rest) = href.strip_prefix("//") {
+        return format!("{}{}", &base[..scheme_end], rest);
+    }
+    let origin_end = base[scheme_end..]
+        .find(['/', '?', '#'])
+        .map_or(base.len(), |i| scheme_end + i);
+    if href.starts_with('/') {
+        return format!("{}{}", &base[..origin_end], href);
+    }
+    // Relative to the directory of the page
+    let path = &base[origin_end..];
+    let path = &path[..path.find(['?', '#']).unwrap_or(path.len())];
+    let dir = &path[..path.rfind('/').map_or(0, |i| i + 1)];
+    let dir = if dir.is_empty() { "/" } else { dir };
+    format!("{}{}{}", &base[..origin_end], dir, href)
+}
+
+/// Decode the character references that turn up in titles and URLs
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(kind: SpanKind, text: &str) -> DetectedSpan {
+        DetectedSpan {
+            kind,
+            text: text.into(),
+            line: 0,
+            col: 0,
+            end_line: 0,
+            end_col: text.len(),
+            file_line: None,
+            file_column: None,
+        }
+    }
+
+    #[test]
+    fn test_target_for_span() {
+        assert_eq!(
+            PreviewTarget::for_span(&span(SpanKind::Url, "www.example.com"), None),
+            Some(PreviewTarget::Url("https://www.example.com".into()))
+        );
+        assert_eq!(
+            PreviewTarget::for_span(&span(SpanKind::Path, "shots/a.PNG"), Some("/home/me")),
+            Some(PreviewTarget::Image("/home/me/shots/a.PNG".into()))
+        );
+        assert_eq!(
+            PreviewTarget::for_span(&span(SpanKind::Url, "file:///tmp/b.jpg"), None),
+            Some(PreviewTarget::Image("/tmp/b.jpg".into()))
+        );
+        assert_eq!(
+            PreviewTarget::for_span(&span(SpanKind::Path, "/etc/hosts"), None),
+            None
+        );
+        assert_eq!(
+            PreviewTarget::for_span(&span(SpanKind::Url, "ftp://example.com/a"), None),
+            None
+        );
+        assert_eq!(
+            PreviewTarget::for_span(&span(SpanKind::GitSha, "0123abcd"), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_load_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dot.png");
+        // 1x1 transparent PNG
+        let png = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAAAAAA6fptVAAAACklEQVR4AWOwBQAAPwA+Eq7IEAAAAABJRU5ErkJggg==",
+        )
+        .unwrap();
+        std::fs::write(&path, png).unwrap();
+
+        let target = PreviewTarget::Image(path.clone());
+        let loaded = preview(&target).unwrap();
+        assert_eq!(loaded.title.as_deref(), Some("dot.png"));
+        let image = loaded.image.as_ref().unwrap();
+        assert_eq!((image.width, image.height), (1, 1));
+        assert!(cached(&target).unwrap().is_some());
+
+        // Failures are cached as well
+        let missing = PreviewTarget::Image(dir.path().join("missing.png"));
+        assert!(preview(&missing).is_none());
+        assert!(matches!(cached(&missing), Some(None)));
+    }
+
+    #[test]
+    fn test_page_title() {
+        assert_eq!(
+            page_title("<html><head><TITLE lang=en>\n  Tom &amp; Jerry &#8211; Home\n</title>"),
+            Some("Tom & Jerry – Home".into())
+        );
+        assert_eq!(page_title("<title></title>"), None);
+        assert_eq!(page_title("<p>no title</p>"), None);
+    }
+
+    #[test]
+    fn test_icon_href() {
+        let html = r#"<link rel="stylesheet" href="a.css">
+            <link data-rel="icon" href="wrong.png">
+            <LINK REL='shortcut icon' HREF='/static/fav.png?v=1&amp;x=2'>"#;
+        assert_eq!(icon_href(html), Some("/static/fav.png?v=1&x=2".into()));
+        assert_eq!(
+            icon_href("<link href=icon.png rel=icon>"),
+            Some("icon.png".into())
+        );
+        assert_eq!(
+            icon_href(r#"<link rel="apple-touch-icon" href="a.png">"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let base = "https://example.com/docs/page.html?q=1";
+        assert_eq!(
+            resolve_url(base, "https://cdn.example.org/i.png"),
+            "https://cdn.example.org/i.png"
+        );
+        assert_eq!(
+            resolve_url(base, "//cdn.example.org/i.png"),
+            "https://cdn.example.org/i.png"
+        );
+        assert_eq!(
+            resolve_url(base, "/favicon.ico"),
+            "https://example.com/favicon.ico"
+        );
+        assert_eq!(
+            resolve_url(base, "img/icon.png"),
+            "https://example.com/docs/img/icon.png"
+        );
+        assert_eq!(
+            resolve_url("https://example.com", "icon.png"),
+            "https://example.com/icon.png"
+        );
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &lt;b&gt; &#x41;&#66;"), "a <b> AB");
+        assert_eq!(decode_entities("AT&T & co;"), "AT&T & co;");
+    }
+}
@@ -0,0 +1,187 @@
+//! Live appearance changes
+//!
+//! Open terminal views subscribe here and restyle themselves when a
+//! configuration is published. The preferences dialogs publish through a
+//! [`Preview`]: Apply shows the edited settings without saving them, Cancel
+//! shows the saved ones again and OK keeps what was saved. Everything here
+//! lives on the UI thread.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::config::Config;
+
+/// Which parts of the appearance differ between two configurations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AppearanceChange {
+    /// Theme name or custom theme
+    pub theme: bool,
+    /// Font family or size
+    pub font: bool,
+    /// Background opacity
+    pub opacity: bool,
+    /// Cursor style or blink
+    pub cursor: bool,
+}
+
+impl AppearanceChange {
+    /// Compare the appearance of `old` and `new`
+    pub fn between(old: &Config, new: &Config) -> Self {
+        let (old, new) = (&old.appearance, &new.appearance);
+        Self {
+            theme: old.theme != new.theme || old.custom_theme != new.custom_theme,
+            font: old.font != new.font,
+            opacity: old.opacity != new.opacity,
+            cursor: old.cursor_style != new.cursor_style || old.cursor_blink != new.cursor_blink,
+        }
+    }
+
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+type Listener = Rc<dyn Fn(&Config, AppearanceChange)>;
+
+thread_local! {
+    static LISTENERS: RefCell<Vec<(u64, Listener)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Keeps a listener registered; dropping it unsubscribes
+#[must_use = "the listener is removed when the subscription is dropped"]
+pub struct Subscription(u64);
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let id = self.0;
+        // Ignore drops during thread teardown
+        let _ = LISTENERS.try_with(|listeners| {
+            listeners.borrow_mut().retain(|(other, _)| *other != id);
+        });
+    }
+}
+
+/// Call `listener` with each configuration published whose appearance
+/// differs from the one shown before
+pub fn subscribe(listener: impl Fn(&Config, AppearanceChange) + 'static) -> Subscription {
+    let id = NEXT_ID.with(|next| {
+        let id = next.get();
+        next.set(id + 1);
+        id
+    });
+    LISTENERS.with(|listeners| listeners.borrow_mut().push((id, Rc::new(listener))));
+    Subscription(id)
+}
+
+/// Tell the listeners that `new` replaces `old`; nothing happens when the
+/// appearance is the same
+pub fn publish(old: &Config, new: &Config) {
+    let change = AppearanceChange::between(old, new);
+    if change.is_empty() {
+        return;
+    }
+    // Listeners may subscribe or unsubscribe while being called
+    let listeners: Vec<Listener> = LISTENERS.with(|listeners| {
+        listeners
+            .borrow()
+            .iter()
+            .map(|(_, listener)| Rc::clone(listener))
+            .collect()
+    });
+    for listener in listeners {
+        listener(new, change);
+    }
+}
+
+/// Configuration shown while a preferences dialog is open
+pub struct Preview {
+    /// What is saved, and shown again on cancel
+    saved: Config,
+    /// What open terminals show
+    shown: Config,
+}
+
+impl Preview {
+    /// Start from the configuration the dialog opened with
+    pub fn new(config: &Config) -> Self {
+        Self {
+            saved: config.clone(),
+            shown: config.clone(),
+        }
+    }
+
+    /// Show `config` in open terminals without saving it
+    pub fn apply(&mut self, config: Config) {
+        publish(&self.shown, &config);
+        self.shown = config;
+    }
+
+    /// Show the saved configuration again
+    pub fn revert(&mut self) {
+        let saved = self.saved.clone();
+        self.apply(saved);
+    }
+
+    /// `config` has been saved: show it and keep it on a later revert
+    pub fn commit(&mut self, config: Config) {
+        self.saved = config.clone();
+        self.apply(config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CursorStyleConfig;
+
+    #[test]
+    fn test_appearance_change() {
+        let old = Config::default();
+        let mut new = old.clone();
+        assert!(AppearanceChange::between(&old, &new).is_empty());
+
+        new.appearance.theme = "Default Light".into();
+        new.appearance.cursor_style = CursorStyleConfig::Bar;
+        new.general.scrollback_lines += 1;
+        assert_eq!(
+            AppearanceChange::between(&old, &new),
+            AppearanceChange {
+                theme: true,
+                cursor: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_preview() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_by_listener = Rc::clone(&seen);
+        let subscription = subscribe(move |config, change| {
+            seen_by_listener
+                .borrow_mut()
+                .push((config.appearance.font.size, change.font));
+        });
+
+        let saved = Config::default();
+        let mut edited = saved.clone();
+        edited.appearance.font.size = 20.0;
+        let mut preview = Preview::new(&saved);
+        preview.apply(edited.clone());
+        // Applying again changes nothing
+        preview.apply(edited.clone());
+        preview.revert();
+        preview.commit(edited);
+        preview.revert();
+        assert_eq!(
+            *seen.borrow(),
+            vec![(20.0, true), (12.0, true), (20.0, true)]
+        );
+
+        drop(subscription);
+        preview.apply(saved);
+        assert_eq!(seen.borrow().len(), 3);
+    }
+}
@@ -0,0 +1,397 @@
+//! Chunked pasting
+//!
+//! A paste is written to the terminal a chunk at a time from the UI event
+//! loop, one chunk per [`InputConfig::PASTE_CHUNK_INTERVAL`] tick, instead of
+//! in one blocking write. That keeps the UI responsive and gives the program
+//! reading the PTY time to drain it. While a paste takes more than one chunk,
+//! frontends show its progress in the status strip and let the user cancel it.
+//! Uploads answering an OSC 1337 RequestUpload are streamed the same way.
+//!
+//! A program that hasn't enabled bracketed paste can't tell pasted text from
+//! typing, so a pasted line break runs the line before it and pasted escape
+//! sequences act as keys. Before such a paste, frontends ask [`PasteGuard`]
+//! whether to confirm it, and show what [`paste_preview`] highlights.
+//!
+//! [`InputConfig::PASTE_CHUNK_INTERVAL`]: crate::config::InputConfig::PASTE_CHUNK_INTERVAL
+
+use crate::config::GeneralConfig;
+
+/// Marks the start of a bracketed paste (DECSET 2004)
+pub const BRACKET_START: &[u8] = b"\x1b[200~";
+/// Marks the end of a bracketed paste
+pub const BRACKET_END: &[u8] = b"\x1b[201~";
+/// The end marker with the 8-bit CSI, which some programs also accept
+const BRACKET_END_C1: &str = "\u{9b}201~";
+
+/// When to confirm a paste into a program without bracketed paste, from
+/// `confirm_paste_lines` and `confirm_paste_control` in [`GeneralConfig`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PasteGuard {
+    /// Confirm this many lines or more (0 never asks)
+    pub lines: usize,
+    /// Confirm control characters
+    pub control: bool,
+}
+
+impl From<&GeneralConfig> for PasteGuard {
+    fn from(general: &GeneralConfig) -> Self {
+        Self {
+            lines: general.confirm_paste_lines,
+            control: general.confirm_paste_control,
+        }
+    }
+}
+
+impl PasteGuard {
+    /// Why pasting `text` unbracketed needs confirming, if it does
+    pub fn check(&self, text: &str) -> Option<PasteWarning> {
+        let warning = PasteWarning {
+            lines: line_count(text),
+            control_chars: text.chars().filter(|&c| is_dangerous(c)).count(),
+        };
+        let too_many_lines = self.lines > 0 && warning.lines >= self.lines;
+        let has_control = self.control && warning.control_chars > 0;
+        (too_many_lines || has_control).then_some(warning)
+    }
+}
+
+/// What a paste needing confirmation contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasteWarning {
+    /// Lines of text, not counting a final line break
+    pub lines: usize,
+    /// Control characters other than tab and line breaks
+    pub control_chars: usize,
+}
+
+impl PasteWarning {
+    /// Explanation for the confirmation dialog
+    pub fn message(&self) -> String {
+        let mut reasons = Vec::new();
+        if self.lines > 1 {
+            reasons.push(format!(
+                "{} lines, each of which may run as a command",
+                self.lines
+            ));
+        }
+        if self.control_chars > 0 {
+            reasons.push(format!(
+                "{} control character{} that may act as keystrokes or hide text",
+                self.control_chars,
+                if self.control_chars == 1 { "" } else { "s" }
+            ));
+        }
+        format!(
+            "The program in this terminal hasn't enabled bracketed paste, so it \
+             can't tell this paste from typing. The text contains {}.",
+            reasons.join(" and ")
+        )
+    }
+}
+
+/// Lines in `text`; a final line break doesn't start another
+fn line_count(text: &str) -> usize {
+    let text = text.replace("\r\n", "\n");
+    text.split_terminator(['\n', '\r']).count()
+}
+
+/// Whether `c` can do more than insert text: C0 and C1 controls other than
+/// tab and line breaks, DEL, and the invisible bidi and zero-width
+/// characters that can make a command look like another
+fn is_dangerous(c: char) -> bool {
+    matches!(c,
+        '\0'..='\u{8}' | '\u{b}' | '\u{c}' | '\u{e}'..='\u{1f}' | '\u{7f}'..='\u{9f}'
+        | '\u{200b}'..='\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+/// The start of `text`, at most `max_chars` characters, as runs to show in
+/// a confirmation dialog; runs flagged true are highlighted
+///
+/// Line breaks show as "↵" before the break, control characters in caret
+/// notation (`^[` for ESC) and other invisible characters as `<U+202E>`.
+pub fn paste_preview(text: &str, max_chars: usize) -> Vec<(String, bool)> {
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    let mut push = |s: &str, highlight: bool| match runs.last_mut() {
+        Some((run, h)) if *h == highlight => run.push_str(s),
+        _ => runs.push((s.to_string(), highlight)),
+    };
+    let mut chars = text.chars().peekable();
+    let mut shown = 0;
+    while let Some(c) = chars.next() {
+        if shown == max_chars {
+            push("…", false);
+            break;
+        }
+        shown += 1;
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                push("↵", true);
+                push("\n", false);
+            }
+            c if !is_dangerous(c) => push(c.encode_utf8(&mut [0; 4]), false),
+            '\u{7f}' => push("^?", true),
+            c if c < ' ' => push(&format!("^{}", (c as u8 + b'@') as char), true),
+            c => push(&format!("<U+{:04X}>", c as u32), true),
+        }
+    }
+    runs
+}
+
+/// What a [`PasteJob`] writes, which decides how it is cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PasteKind {
+    Plain,
+    Bracketed,
+    Upload,
+}
+
+/// A paste being written to the terminal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasteJob {
+    /// Bytes to write, including the bracket markers
+    data: Vec<u8>,
+    /// Bytes written so far
+    written: usize,
+    kind: PasteKind,
+}
+
+impl PasteJob {
+    /// Prepare `text` for pasting, wrapped in bracket markers if `bracketed`
+    ///
+    /// An end marker inside the text, with either a 7-bit or an 8-bit CSI,
+    /// would let it escape the bracketed paste and run as typed input, so
+    /// any are removed.
+    pub fn new(text: &str, bracketed: bool) -> Self {
+        let mut data = Vec::with_capacity(text.len() + 12);
+        if bracketed {
+            let end = std::str::from_utf8(BRACKET_END).unwrap_or_default();
+            let text = text.replace(end, "").replace(BRACKET_END_C1, "");
+            data.extend_from_slice(BRACKET_START);
+            data.extend_from_slice(text.as_bytes());
+            data.extend_from_slice(BRACKET_END);
+        } else {
+            data.extend_from_slice(text.as_bytes());
+        }
+        Self {
+            data,
+            written: 0,
+            kind: if bracketed {
+                PasteKind::Bracketed
+            } else {
+                PasteKind::Plain
+            },
+        }
+    }
+
+    /// Prepare an upload encoded by [`cterm_core::iterm2::encode_upload`]
+    pub fn upload(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            written: 0,
+            kind: PasteKind::Upload,
+        }
+    }
+
+    /// Total bytes to write
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether there is nothing to paste
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Bytes written so far
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Whether everything has been written
+    pub fn is_done(&self) -> bool {
+        self.written >= self.data.len()
+    }
+
+    /// Whether the paste needs more than one chunk of `chunk_bytes` (0 writes
+    /// everything at once), i.e. whether to show progress for it
+    pub fn is_chunked(&self, chunk_bytes: usize) -> bool {
+        chunk_bytes > 0 && self.data.len() > chunk_bytes
+    }
+
+    /// Percentage written, 0-100
+    pub fn percent(&self) -> u8 {
+        if self.data.is_empty() {
+            return 100;
+        }
+        (self.written * 100 / self.data.len()) as u8
+    }
+
+    /// Take the next chunk of at most `chunk_bytes` bytes (all that is left
+    /// if 0), never splitting a UTF-8 sequence; `None` once done
+    pub fn next_chunk(&mut self, chunk_bytes: usize) -> Option<&[u8]> {
+        if self.is_done() {
+            return None;
+        }
+        let start = self.written;
+        let mut end = if chunk_bytes == 0 {
+            self.data.len()
+        } else {
+            (start + chunk_bytes).min(self.data.len())
+        };
+        // Back up to a character boundary, unless the chunk is one character
+        while end < self.data.len() && end > start + 1 && is_continuation(self.data[end]) {
+            end -= 1;
+        }
+        while end < self.data.len() && is_continuation(self.data[end]) {
+            end += 1;
+        }
+        self.written = end;
+        Some(&self.data[start..end])
+    }
+
+    /// Stop pasting; returns what must still be written to leave the terminal
+    /// in a sane state: the end marker if a bracketed paste was started, and
+    /// for an upload whatever ends it on the remote
+    pub fn cancel(&mut self) -> &[u8] {
+        let end = self.data.len();
+        let from = match self.kind {
+            // The end marker may already be partly written
+            PasteKind::Bracketed if self.written > 0 => self.written.max(end - BRACKET_END.len()),
+            PasteKind::Upload if self.written == 0 => {
+                self.written = end;
+                return cterm_core::iterm2::UPLOAD_ABORT;
+            }
+            // Finish the current line, then the blank line ending the data.
+            // The remote can't decode what it got and reports the error.
+            PasteKind::Upload if self.data[self.written - 1] == b'\n' => self.written.max(end - 1),
+            PasteKind::Upload => self.written.max(end.saturating_sub(2)),
+            _ => end,
+        };
+        self.written = end;
+        &self.data[from..]
+    }
+}
+
+/// Whether `byte` continues a UTF-8 sequence
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(job: &mut PasteJob, chunk_bytes: usize) -> Vec<Vec<u8>> {
+        std::iter::from_fn(|| job.next_chunk(chunk_bytes).map(<[u8]>::to_vec)).collect()
+    }
+
+    #[test]
+    fn test_bracketed_paste() {
+        let job = PasteJob::new("ls\x1b[201~rm -rf ~\n", true);
+        assert_eq!(job.data, b"\x1b[200~lsrm -rf ~\n\x1b[201~");
+        let job = PasteJob::new("ls\u{9b}201~\n", true);
+        assert_eq!(job.data, b"\x1b[200~ls\n\x1b[201~");
+
+        let mut job = PasteJob::new("plain", false);
+        assert!(!job.is_chunked(4096));
+        assert_eq!(drain(&mut job, 4096), [b"plain".to_vec()]);
+        assert!(job.is_done());
+        assert_eq!(job.percent(), 100);
+    }
+
+    #[test]
+    fn test_paste_guard() {
+        let guard = PasteGuard::from(&GeneralConfig::default());
+        assert_eq!(guard.check("ls -l"), None);
+        assert_eq!(guard.check("ls -l\n"), None);
+        assert_eq!(
+            guard.check("cd /\r\nrm -rf *\n"),
+            Some(PasteWarning {
+                lines: 2,
+                control_chars: 0
+            })
+        );
+        let warning = guard.check("echo \x1b[31m\u{202e}").unwrap();
+        assert_eq!(warning.control_chars, 2);
+        assert!(warning.message().contains("2 control characters"));
+        assert!(!warning.message().contains("lines"));
+        assert_eq!(guard.check("a\tb"), None);
+
+        let off = PasteGuard {
+            lines: 0,
+            control: false,
+        };
+        assert_eq!(off.check("a\nb\x1b"), None);
+    }
+
+    #[test]
+    fn test_paste_preview() {
+        assert_eq!(
+            paste_preview("ls\x1b[A\r\n\u{202e}x\x7f", 100),
+            [
+                ("ls".to_string(), false),
+                ("^[".to_string(), true),
+                ("[A".to_string(), false),
+                ("↵".to_string(), true),
+                ("\n".to_string(), false),
+                ("<U+202E>".to_string(), true),
+                ("x".to_string(), false),
+                ("^?".to_string(), true),
+            ]
+        );
+        assert_eq!(paste_preview("abcdef", 3), [("abc…".to_string(), false)]);
+        assert_eq!(paste_preview("abc", 3), [("abc".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_chunks_keep_characters_whole() {
+        // "é" is two bytes, so a 3-byte chunk can't end after the second one
+        let mut job = PasteJob::new("aéé", false);
+        assert!(job.is_chunked(3));
+        assert_eq!(
+            drain(&mut job, 3),
+            ["aé".as_bytes().to_vec(), "é".as_bytes().to_vec()]
+        );
+
+        // A character wider than the chunk is still written whole
+        let mut job = PasteJob::new("€€", false);
+        assert_eq!(drain(&mut job, 1).len(), 2);
+
+        let mut job = PasteJob::new("abcdef", false);
+        job.next_chunk(2);
+        assert_eq!(job.percent(), 33);
+        assert_eq!(drain(&mut job, 0), [b"cdef".to_vec()]);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut job = PasteJob::new("0123456789", true);
+        job.next_chunk(8);
+        assert_eq!(job.cancel(), BRACKET_END);
+        assert!(job.is_done());
+        assert_eq!(job.next_chunk(8), None);
+
+        // Nothing was sent yet, so nothing needs closing
+        let mut job = PasteJob::new("0123456789", true);
+        assert!(job.cancel().is_empty());
+        let mut job = PasteJob::new("0123456789", false);
+        job.next_chunk(4);
+        assert!(job.cancel().is_empty());
+    }
+
+    #[test]
+    fn test_cancel_upload() {
+        let data = b"ok\nH4sI\nAAAA\n\n".to_vec();
+        let mut job = PasteJob::upload(data.clone());
+        assert_eq!(job.cancel(), cterm_core::iterm2::UPLOAD_ABORT);
+
+        // Mid-line, the line is ended before the blank line
+        let mut job = PasteJob::upload(data.clone());
+        job.next_chunk(5);
+        assert_eq!(job.cancel(), b"\n\n");
+        let mut job = PasteJob::upload(data);
+        job.next_chunk(8);
+        assert_eq!(job.cancel(), b"\n");
+    }
+}
@@ -0,0 +1,135 @@
+//! Presentation mode support
+//!
+//! Presentation mode is a one-shortcut switch for live demos: the frontend
+//! enlarges the font, hides the tab bar and scrollbar, and applies the
+//! configured minimum contrast. When keystroke display is enabled, the
+//! frontend feeds key presses into a [`KeystrokeLog`] and draws its text as an
+//! overlay.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use cterm_ui::events::{KeyCode, Modifiers, Shortcut};
+
+use crate::shortcuts::format_shortcut;
+
+/// How long a keystroke stays on screen
+pub const KEYSTROKE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Maximum number of entries kept in the overlay
+const MAX_ENTRIES: usize = 6;
+
+/// Maximum length of a run of typed text before it starts a new entry
+const MAX_TEXT_RUN: usize = 24;
+
+#[derive(Debug, Clone)]
+struct Entry {
+    label: String,
+    /// Plain typed text that later characters may be appended to
+    is_text: bool,
+    at: Instant,
+}
+
+/// Recent keystrokes for the on-screen overlay
+///
+/// Plain typed characters are merged into runs ("ls -la"), while chords and
+/// special keys get their own entry ("Ctrl+C", "Enter"). Entries expire after
+/// [`KEYSTROKE_TIMEOUT`].
+#[derive(Debug, Clone, Default)]
+pub struct KeystrokeLog {
+    entries: VecDeque<Entry>,
+}
+
+impl KeystrokeLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a key press
+    pub fn record(&mut self, key: KeyCode, modifiers: Modifiers, now: Instant) {
+        let chord = modifiers.intersects(Modifiers::CTRL | Modifiers::ALT | Modifiers::SUPER);
+        let typed = if chord {
+            None
+        } else if modifiers.contains(Modifiers::SHIFT) {
+            key.to_shifted_char()
+        } else {
+            key.to_char()
+        };
+
+        match typed {
+            Some(c) => {
+                if let Some(last) = self.entries.back_mut() {
+                    if last.is_text && last.label.chars().count() < MAX_TEXT_RUN {
+                        last.label.push(c);
+                        last.at = now;
+                        return;
+                    }
+                }
+                self.push(c.to_string(), true, now);
+            }
+            None => {
+                let label = format_shortcut(&Shortcut::new(key, modifiers));
+                self.push(label, false, now);
+            }
+        }
+    }
+
+    fn push(&mut self, label: String, is_text: bool, now: Instant) {
+        self.entries.push_back(Entry {
+            label,
+            is_text,
+            at: now,
+        });
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Drop expired entries; returns true if anything was removed
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let before = self.entries.len();
+        self.entries
+            .retain(|e| now.duration_since(e.at) < KEYSTROKE_TIMEOUT);
+        self.entries.len() != before
+    }
+
+    /// Text to display, or None when there is nothing to show
+    pub fn text(&self) -> Option<String> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let labels: Vec<&str> = self.entries.iter().map(|e| e.label.as_str()).collect();
+        Some(labels.join("  "))
+    }
+
+    /// Forget all recorded keystrokes
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_text_merges_into_runs() {
+        let mut log = KeystrokeLog::new();
+        let now = Instant::now();
+        log.record(KeyCode::L, Modifiers::empty(), now);
+        log.record(KeyCode::S, Modifiers::empty(), now);
+        log.record(KeyCode::Enter, Modifiers::empty(), now);
+        log.record(KeyCode::C, Modifiers::CTRL, now);
+        assert_eq!(log.text().as_deref(), Some("ls  Enter  Ctrl+C"));
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let mut log = KeystrokeLog::new();
+        let start = Instant::now();
+        log.record(KeyCode::A, Modifiers::empty(), start);
+        assert!(!log.expire(start + Duration::from_millis(500)));
+        assert!(log.expire(start + KEYSTROKE_TIMEOUT));
+        assert_eq!(log.text(), None);
+    }
+}
@@ -0,0 +1,391 @@
+//! Named per-tab profiles
+//!
+//! A `[[profiles]]` entry ([`ProfileConfig`]) bundles a shell, working
+//! directory, environment, startup commands, theme, font, scrollback size,
+//! long command notification, permission policy and `auto_switch` host
+//! rules. Frontends list the profiles in a "New Tab with Profile" menu, and
+//! a tab template can name one in its `profile` field to start from its
+//! settings. Whatever a profile leaves unset comes from the main
+//! configuration, so [`ProfileConfig::default`] describes a plain new tab.
+
+use std::collections::HashMap;
+
+use cterm_client::CreateSessionOpts;
+use cterm_core::pty::PtyConfig;
+use cterm_core::screen::ScreenConfig;
+use cterm_ui::theme::Theme;
+
+use crate::config::{
+    find_theme, AutoSwitchRule, Config, GeneralConfig, ProfileConfig, StickyTabConfig,
+};
+
+impl ProfileConfig {
+    /// Shell and arguments to run
+    fn shell(&self, general: &GeneralConfig) -> (Option<String>, Vec<String>) {
+        match self.command {
+            Some(ref command) => (Some(command.clone()), self.args.clone()),
+            None if self.args.is_empty() => {
+                (general.default_shell.clone(), general.shell_args.clone())
+            }
+            None => (general.default_shell.clone(), self.args.clone()),
+        }
+    }
+
+    /// `[general] env` with this profile's variables on top
+    fn env(&self, general: &GeneralConfig) -> Vec<(String, String)> {
+        let mut env: HashMap<&String, &String> = general.env.iter().collect();
+        env.extend(self.env.iter());
+        env.into_iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// PTY settings for a local tab using this profile
+    pub fn pty_config(&self, general: &GeneralConfig) -> PtyConfig {
+        let (shell, args) = self.shell(general);
+        PtyConfig {
+            shell,
+            args,
+            cwd: self
+                .working_directory
+                .clone()
+                .or_else(|| general.working_directory.clone()),
+            env: self.env(general),
+            term: general.term.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Daemon session options for a tab using this profile
+    pub fn session_opts(&self, general: &GeneralConfig) -> CreateSessionOpts {
+        let pty = self.pty_config(general);
+        CreateSessionOpts {
+            cols: 80,
+            rows: 24,
+            shell: pty.shell,
+            args: pty.args,
+            cwd: pty.cwd.map(|p| p.to_string_lossy().into_owned()),
+            env: pty.env,
+            term: pty.term,
+            startup_commands: self.startup_commands.clone(),
+            scrollback_lines: self.scrollback_lines,
+            ..Default::default()
+        }
+    }
+
+    /// Screen settings for a local tab using this profile
+    pub fn screen_config(&self, config: &Config) -> ScreenConfig {
+        ScreenConfig {
+            scrollback_lines: self
+                .scrollback_lines
+                .unwrap_or(config.general.scrollback_lines),
+            selection: config.selection.clone(),
+            conformance_level: config.general.conformance_level,
+            cursor_style: config.appearance.cursor_style.into(),
+            cursor_blink: config.appearance.cursor_blink,
+        }
+    }
+
+    /// Theme for a tab using this profile; `default` when it names none or
+    /// an unknown one
+    pub fn theme(&self, default: &Theme) -> Theme {
+        self.theme
+            .as_deref()
+            .and_then(|name| {
+                let theme = find_theme(name);
+                if theme.is_none() {
+                    log::warn!("Profile {:?}: unknown theme {:?}", self.name, name);
+                }
+                theme
+            })
+            .unwrap_or_else(|| default.clone())
+    }
+
+    /// `config` with this profile's font, scrollback size, long command
+    /// notification and permission policy, and its host rules ahead of the
+    /// `[[auto_switch]]` ones
+    ///
+    /// A profile's font size applies on every display, replacing
+    /// `[appearance] display_font_sizes`.
+    pub fn apply(&self, config: &Config) -> Config {
+        let mut config = config.clone();
+        if let Some(ref family) = self.font_family {
+            config.appearance.font.family = family.clone();
+        }
+        if let Some(size) = self.font_size {
+            config.appearance.font.size = size;
+            config.appearance.display_font_sizes.clear();
+        }
+        if let Some(lines) = self.scrollback_lines {
+            config.general.scrollback_lines = lines;
+        }
+        if let Some(notify) = self.command_notify {
+            config.command_notify = notify;
+        }
+        if let Some(permissions) = self.permissions {
+            config.permissions = permissions;
+        }
+        config.auto_switch = self.auto_switch_rules(&config);
+        config
+    }
+
+    /// This profile's host rules followed by the `[[auto_switch]]` ones
+    pub fn auto_switch_rules(&self, config: &Config) -> Vec<AutoSwitchRule> {
+        self.auto_switch
+            .iter()
+            .chain(&config.auto_switch)
+            .cloned()
+            .collect()
+    }
+}
+
+impl StickyTabConfig {
+    /// The profile this template names, if it exists
+    pub fn find_profile<'a>(&self, config: &'a Config) -> Option<&'a ProfileConfig> {
+        let name = self.profile.as_deref()?;
+        let profile = config.profile(name);
+        if profile.is_none() {
+            log::warn!("Template {:?}: unknown profile {:?}", self.name, name);
+        }
+        profile
+    }
+
+    /// This template with its profile's settings filled in, and that
+    /// profile, carrying the template's theme, notification and permission
+    /// overrides, for the tab's theme, font, scrollback size and host rules
+    pub fn resolve_profile(&self, config: &Config) -> (StickyTabConfig, Option<ProfileConfig>) {
+        let Some(profile) = self.find_profile(config) else {
+            return (self.clone(), None);
+        };
+        let template = self.with_profile(profile);
+        let profile = ProfileConfig {
+            theme: template.theme.clone(),
+            command_notify: template.command_notify,
+            permissions: template.permissions,
+            ..profile.clone()
+        };
+        (template, Some(profile))
+    }
+
+    /// This template with the settings it leaves unset taken from `profile`
+    ///
+    /// The profile's command only applies to templates that run the shell,
+    /// not to Docker, SSH or serial ones. Its environment is merged under
+    /// the template's, and its startup commands only run for templates
+    /// without any.
+    pub fn with_profile(&self, profile: &ProfileConfig) -> StickyTabConfig {
+        let mut template = self.clone();
+        let runs_shell = template.docker.is_none()
+            && template.ssh.is_none()
+            && template.serial.is_none()
+            && template.command.is_none();
+        if runs_shell {
+            template.command = profile.command.clone();
+            if template.args.is_empty() {
+                template.args = profile.args.clone();
+            }
+        }
+        if template.working_directory.is_none() {
+            template.working_directory = profile.working_directory.clone();
+        }
+        if template.theme.is_none() {
+            template.theme = profile.theme.clone();
+        }
+        if template.startup_commands.is_empty() {
+            template.startup_commands = profile.startup_commands.clone();
+        }
+        if template.command_notify.is_none() {
+            template.command_notify = profile.command_notify;
+        }
+        if template.permissions.is_none() {
+            template.permissions = profile.permissions;
+        }
+        let mut env = profile.env.clone();
+        env.extend(self.env.clone());
+        template.env = env;
+        template
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CommandNotifyConfig;
+    use cterm_core::{Permission, PermissionPolicy};
+    use std::path::PathBuf;
+
+    fn rule(host: &str) -> AutoSwitchRule {
+        AutoSwitchRule {
+            host: host.into(),
+            user: None,
+            theme: Some("Dracula".into()),
+            tab_color: None,
+        }
+    }
+
+    fn profile() -> ProfileConfig {
+        ProfileConfig {
+            name: "Work".into(),
+            command: Some("/bin/zsh".into()),
+            args: vec!["-l".into()],
+            working_directory: Some(PathBuf::from("/work")),
+            env: HashMap::from([("AWS_PROFILE".into(), "work".into())]),
+            theme: Some("Nord".into()),
+            font_family: Some("Fira Code".into()),
+            font_size: Some(15.0),
+            scrollback_lines: Some(500),
+            startup_commands: vec!["source .venv/bin/activate".into()],
+            command_notify: Some(CommandNotifyConfig {
+                min_seconds: 30,
+                desktop_when_unfocused: false,
+            }),
+            permissions: Some(PermissionPolicy {
+                file_transfer: Permission::Deny,
+                ..Default::default()
+            }),
+            auto_switch: vec![rule("*.prod")],
+        }
+    }
+
+    #[test]
+    fn test_profile_pty_config() {
+        let mut config = Config::default();
+        config.general.default_shell = Some("/bin/bash".into());
+        config.general.shell_args = vec!["--norc".into()];
+        config.general.env = HashMap::from([
+            ("AWS_PROFILE".into(), "home".into()),
+            ("EDITOR".into(), "vim".into()),
+        ]);
+
+        let pty = profile().pty_config(&config.general);
+        assert_eq!(pty.shell.as_deref(), Some("/bin/zsh"));
+        assert_eq!(pty.args, vec!["-l".to_string()]);
+        assert_eq!(pty.cwd, Some(PathBuf::from("/work")));
+        let mut env = pty.env;
+        env.sort();
+        assert_eq!(
+            env,
+            vec![
+                ("AWS_PROFILE".to_string(), "work".to_string()),
+                ("EDITOR".to_string(), "vim".to_string()),
+            ]
+        );
+
+        // An empty profile is a plain new tab
+        let pty = ProfileConfig::default().pty_config(&config.general);
+        assert_eq!(pty.shell.as_deref(), Some("/bin/bash"));
+        assert_eq!(pty.args, vec!["--norc".to_string()]);
+        assert_eq!(pty.cwd, None);
+
+        let opts = profile().session_opts(&config.general);
+        assert_eq!(opts.cwd.as_deref(), Some("/work"));
+        assert_eq!(opts.scrollback_lines, Some(500));
+        assert_eq!(opts.startup_commands, ["source .venv/bin/activate"]);
+    }
+
+    #[test]
+    fn test_profile_appearance() {
+        let mut config = Config::default();
+        config
+            .appearance
+            .display_font_sizes
+            .insert("Projector".into(), 20.0);
+        let applied = profile().apply(&config);
+        assert_eq!(applied.appearance.font.family, "Fira Code");
+        assert_eq!(applied.appearance.font.size, 15.0);
+        assert_eq!(
+            applied.appearance.font_size_for_display(Some("Projector")),
+            15.0
+        );
+        assert_eq!(applied.general.scrollback_lines, 500);
+        assert_eq!(profile().screen_config(&config).scrollback_lines, 500);
+
+        let default = Theme::dark();
+        assert_eq!(profile().theme(&default).name, "Nord");
+        assert_eq!(ProfileConfig::default().theme(&default).name, default.name);
+        let unknown = ProfileConfig {
+            theme: Some("No Such Theme".into()),
+            ..Default::default()
+        };
+        assert_eq!(unknown.theme(&default).name, default.name);
+    }
+
+    #[test]
+    fn test_profile_behavior() {
+        let mut config = Config::default();
+        config.auto_switch.push(rule("*"));
+
+        let applied = profile().apply(&config);
+        assert_eq!(applied.command_notify.min_seconds, 30);
+        assert_eq!(applied.permissions.file_transfer, Permission::Deny);
+        // The profile's host rules come first
+        let hosts: Vec<_> = applied.auto_switch.iter().map(|r| &r.host).collect();
+        assert_eq!(hosts, ["*.prod", "*"]);
+        assert_eq!(profile().auto_switch_rules(&config), applied.auto_switch);
+
+        let plain = ProfileConfig::default().apply(&config);
+        assert_eq!(plain.command_notify, config.command_notify);
+        assert_eq!(plain.permissions, config.permissions);
+        assert_eq!(plain.auto_switch, config.auto_switch);
+    }
+
+    #[test]
+    fn test_template_with_profile() {
+        let mut config = Config::default();
+        config.profiles.push(profile());
+
+        let template = StickyTabConfig {
+            name: "Deploy".into(),
+            working_directory: Some(PathBuf::from("/deploy")),
+            env: HashMap::from([("AWS_PROFILE".into(), "prod".into())]),
+            profile: Some("Work".into()),
+            ..Default::default()
+        };
+        let resolved = template.with_profile(template.find_profile(&config).unwrap());
+        assert_eq!(resolved.command.as_deref(), Some("/bin/zsh"));
+        assert_eq!(resolved.args, vec!["-l".to_string()]);
+        assert_eq!(resolved.working_directory, Some(PathBuf::from("/deploy")));
+        assert_eq!(resolved.theme.as_deref(), Some("Nord"));
+        assert_eq!(resolved.env["AWS_PROFILE"], "prod");
+        assert_eq!(resolved.startup_commands, ["source .venv/bin/activate"]);
+        assert_eq!(resolved.command_notify.unwrap().min_seconds, 30);
+        assert_eq!(
+            resolved.permissions.unwrap().file_transfer,
+            Permission::Deny
+        );
+
+        // A template's own startup commands and overrides win
+        let own = StickyTabConfig {
+            startup_commands: vec!["make".into()],
+            permissions: Some(PermissionPolicy::default()),
+            ..template.clone()
+        }
+        .with_profile(&profile());
+        assert_eq!(own.startup_commands, ["make"]);
+        assert_eq!(own.permissions, Some(PermissionPolicy::default()));
+
+        // A template's own command wins, and so do its arguments
+        let claude = StickyTabConfig::claude().with_profile(&profile());
+        assert_eq!(claude.command.as_deref(), Some("claude"));
+        assert!(claude.args.is_empty());
+
+        let themed = StickyTabConfig {
+            theme: Some("Dracula".into()),
+            ..template.clone()
+        };
+        let (resolved, profile) = themed.resolve_profile(&config);
+        assert_eq!(resolved.command.as_deref(), Some("/bin/zsh"));
+        let profile = profile.unwrap();
+        assert_eq!(profile.theme.as_deref(), Some("Dracula"));
+        assert_eq!(profile.scrollback_lines, Some(500));
+        assert_eq!(profile.command_notify.unwrap().min_seconds, 30);
+        assert_eq!(profile.auto_switch.len(), 1);
+
+        let missing = StickyTabConfig {
+            profile: Some("Missing".into()),
+            ..Default::default()
+        };
+        assert!(missing.find_profile(&config).is_none());
+        assert!(missing.resolve_profile(&config).1.is_none());
+    }
+}
@@ -0,0 +1,90 @@
+//! Safe mode and recovery from crashes during startup
+//!
+//! A broken config.toml, theme or template (often one pulled in by git
+//! sync) can crash cterm before its first window is up, and then every
+//! launch crashes the same way. Each launch drops a marker file with
+//! [`begin_startup`] and removes it with [`finish_startup`] once the first
+//! window is shown, so finding the marker means the previous startup never
+//! finished and the frontend offers safe mode.
+//!
+//! In safe mode the settings, theme, tab templates and tool shortcuts are
+//! the defaults, user themes and git sync are skipped, and nothing in the
+//! config directory is written, so the files can be fixed from a shell (for
+//! example with `cterm config reset`) while cterm runs.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use directories::ProjectDirs;
+
+use crate::config::{config_path, read_config};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Title of the recovery prompt
+pub const RECOVERY_TITLE: &str = "cterm did not start last time";
+
+/// Turn safe mode on for the rest of this process
+pub fn enable() {
+    log::warn!("Running in safe mode: ignoring the configuration");
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether this process runs in safe mode
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Path of the startup marker
+///
+/// It is kept out of the config directory so that git sync never commits it.
+fn marker_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "cterm", "cterm").map(|p| p.data_local_dir().join("startup.marker"))
+}
+
+/// Mark the start of a launch, returning whether the previous launch
+/// crashed before its first window was shown
+pub fn begin_startup() -> bool {
+    let Some(path) = marker_path() else {
+        return false;
+    };
+    let crashed = path.exists();
+    if crashed {
+        log::warn!("The previous launch did not finish starting up");
+    }
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, std::process::id().to_string()));
+    if let Err(e) = written {
+        log::warn!("Failed to write {}: {}", path.display(), e);
+    }
+    crashed
+}
+
+/// Mark the launch as finished once the first window is shown
+pub fn finish_startup() {
+    if let Some(path) = marker_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Text of the recovery prompt, naming the config problem when there is one
+pub fn recovery_message() -> String {
+    let mut message = String::from(
+        "cterm crashed while starting up. A broken configuration, for example \
+         one pulled in by git sync, can cause this.\n\n",
+    );
+    if let Err(e) = read_config() {
+        let path = config_path()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "config.toml".to_string());
+        message.push_str(&format!("{} has a problem: {}\n\n", path, e));
+    }
+    message.push_str(
+        "Start in safe mode? Safe mode uses the default settings and theme, \
+         skips git sync, custom themes, tab templates and tool shortcuts, and \
+         does not change your configuration files.",
+    );
+    message
+}
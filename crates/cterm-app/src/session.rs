@@ -1,22 +1,38 @@
 //! Session management
 //!
 //! Handles terminal sessions, tabs, and window state.
+//!
+//! With `[general] restore_session`, frontends save their windows as a
+//! [`SessionState`] when cterm exits — the windows still open, or the last
+//! one closed — and reopen them at the next launch when there are no daemon
+//! sessions to reconnect to. Each tab comes back the way a recently closed
+//! tab does: from its template or in its working directory, with its title,
+//! color, scrollback and scroll position.
 
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 
+use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 
 use cterm_core::pty::{PtyConfig, PtyError};
 use cterm_core::screen::ScreenConfig;
 use cterm_core::term::Terminal;
+use cterm_ui::events::PaneDirection;
+use cterm_ui::theme::Theme;
 
-use crate::config::StickyTabConfig;
+use crate::closed_tabs::ClosedTab;
+use crate::config::{resolve_theme, Config, NewTabPosition, ProfileConfig, StickyTabConfig};
 
 /// Global tab ID counter
 static TAB_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
 /// Global window ID counter
 static WINDOW_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
+/// Global pane ID counter
+static PANE_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
+/// Window closed most recently, saved at exit when no window is left open
+static LAST_CLOSED_WINDOW: Mutex<Option<WindowSessionState>> = Mutex::new(None);
 
 /// Generate a unique tab ID
 pub fn next_tab_id() -> u64 {
@@ -28,6 +44,11 @@ pub fn next_window_id() -> u64 {
     WINDOW_ID_COUNTER.fetch_add(1, Ordering::SeqCst)
 }
 
+/// Generate a unique pane ID
+pub fn next_pane_id() -> u64 {
+    PANE_ID_COUNTER.fetch_add(1, Ordering::SeqCst)
+}
+
 /// Tab state
 pub struct TabState {
     /// Unique tab ID
@@ -46,6 +67,10 @@ pub struct TabState {
     pub has_unread: bool,
     /// Working directory
     pub cwd: Option<PathBuf>,
+    /// Name of the profile the tab was opened with
+    pub profile: Option<String>,
+    /// Theme of the tab's profile (None = the window's theme)
+    pub theme: Option<Theme>,
 }
 
 impl TabState {
@@ -65,6 +90,8 @@ impl TabState {
             color: None,
             has_unread: false,
             cwd: None,
+            profile: None,
+            theme: None,
         })
     }
 
@@ -85,6 +112,7 @@ impl TabState {
                 .iter()
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect(),
+            clear_env: !config.inherit_env,
             ..Default::default()
         };
 
@@ -99,6 +127,8 @@ impl TabState {
             color: config.color.clone(),
             has_unread: false,
             cwd: config.working_directory.clone(),
+            profile: None,
+            theme: None,
         })
     }
 
@@ -130,6 +160,34 @@ impl TabState {
             color: None,
             has_unread: false,
             cwd,
+            profile: None,
+            theme: None,
+        })
+    }
+
+    /// Create a tab with `profile`'s shell, environment, scrollback and
+    /// theme, falling back to `config` for what it leaves unset
+    pub fn from_profile(
+        profile: &ProfileConfig,
+        config: &Config,
+        cols: usize,
+        rows: usize,
+    ) -> Result<Self, PtyError> {
+        let pty_config = profile.pty_config(&config.general);
+        let terminal =
+            Terminal::with_shell(cols, rows, profile.screen_config(config), &pty_config)?;
+
+        Ok(Self {
+            id: next_tab_id(),
+            terminal,
+            title: profile.name.clone(),
+            custom_title: None,
+            sticky_config: None,
+            color: None,
+            has_unread: false,
+            cwd: pty_config.cwd,
+            profile: Some(profile.name.clone()),
+            theme: Some(profile.theme(&resolve_theme(config))),
         })
     }
 
@@ -178,6 +236,9 @@ impl TabState {
             custom_title: self.custom_title.clone(),
             cwd: self.cwd.clone(),
             color: self.color.clone(),
+            title: self.display_title().to_string(),
+            scrollback: None,
+            scroll_offset: self.terminal.screen().scroll_offset,
         }
     }
 }
@@ -320,6 +381,449 @@ impl WindowState {
     }
 }
 
+/// Share of a split the divider moves per resize step
+pub const PANE_RESIZE_STEP: f64 = 0.05;
+
+/// Smallest share of a split either side keeps
+pub const MIN_SPLIT_RATIO: f64 = 0.1;
+
+/// How far a pane may reach past its neighbor's edge and still count as
+/// beside it, for frontends laying panes out on whole pixels
+const EDGE_TOLERANCE: f64 = 0.5;
+
+/// How a split divides its area
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitAxis {
+    /// Side by side, the new pane on the right
+    Horizontal,
+    /// Stacked, the new pane below
+    Vertical,
+}
+
+impl SplitAxis {
+    /// Axis along which `direction` moves
+    pub fn of(direction: PaneDirection) -> Self {
+        match direction {
+            PaneDirection::Left | PaneDirection::Right => SplitAxis::Horizontal,
+            PaneDirection::Up | PaneDirection::Down => SplitAxis::Vertical,
+        }
+    }
+}
+
+/// Area of a pane, in the frontend's units
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PaneRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl PaneRect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    /// Whether the point is inside the area
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Divide the area along `axis`, `gap` apart, giving `ratio` of the rest
+    /// to the first part
+    fn split(self, axis: SplitAxis, ratio: f64, gap: f64) -> (PaneRect, PaneRect) {
+        match axis {
+            SplitAxis::Horizontal => {
+                let space = (self.width - gap).max(0.0);
+                let width = (space * ratio).round();
+                let second = PaneRect {
+                    x: self.x + width + gap,
+                    width: space - width,
+                    ..self
+                };
+                (PaneRect { width, ..self }, second)
+            }
+            SplitAxis::Vertical => {
+                let space = (self.height - gap).max(0.0);
+                let height = (space * ratio).round();
+                let second = PaneRect {
+                    y: self.y + height + gap,
+                    height: space - height,
+                    ..self
+                };
+                (PaneRect { height, ..self }, second)
+            }
+        }
+    }
+}
+
+/// Node of a [`PaneTree`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaneNode {
+    /// A pane, by the frontend's id for it
+    Pane(u64),
+    /// Two nodes sharing an area
+    Split {
+        axis: SplitAxis,
+        /// Share of the area given to `first`
+        ratio: f64,
+        /// Left or top node
+        first: Box<PaneNode>,
+        /// Right or bottom node
+        second: Box<PaneNode>,
+    },
+}
+
+impl PaneNode {
+    fn contains(&self, pane: u64) -> bool {
+        match self {
+            PaneNode::Pane(id) => *id == pane,
+            PaneNode::Split { first, second, .. } => first.contains(pane) || second.contains(pane),
+        }
+    }
+
+    fn collect_panes(&self, panes: &mut Vec<u64>) {
+        match self {
+            PaneNode::Pane(id) => panes.push(*id),
+            PaneNode::Split { first, second, .. } => {
+                first.collect_panes(panes);
+                second.collect_panes(panes);
+            }
+        }
+    }
+
+    fn first_pane(&self) -> u64 {
+        match self {
+            PaneNode::Pane(id) => *id,
+            PaneNode::Split { first, .. } => first.first_pane(),
+        }
+    }
+
+    fn last_pane(&self) -> u64 {
+        match self {
+            PaneNode::Pane(id) => *id,
+            PaneNode::Split { second, .. } => second.last_pane(),
+        }
+    }
+
+    /// The pane across the innermost split holding `pane`: its sibling, or
+    /// the sibling's pane nearest to it
+    fn neighbor(&self, pane: u64) -> Option<u64> {
+        let PaneNode::Split { first, second, .. } = self else {
+            return None;
+        };
+        if **first == PaneNode::Pane(pane) {
+            Some(second.first_pane())
+        } else if **second == PaneNode::Pane(pane) {
+            Some(first.last_pane())
+        } else {
+            first.neighbor(pane).or_else(|| second.neighbor(pane))
+        }
+    }
+
+    fn leaf_mut(&mut self, pane: u64) -> Option<&mut PaneNode> {
+        if let PaneNode::Pane(id) = self {
+            return if *id == pane { Some(self) } else { None };
+        }
+        match self {
+            PaneNode::Split { first, second, .. } => {
+                first.leaf_mut(pane).or_else(|| second.leaf_mut(pane))
+            }
+            PaneNode::Pane(_) => None,
+        }
+    }
+
+    /// Remove `pane` from below this node, its sibling taking the place of
+    /// their split; returns the pane nearest to where it was
+    fn remove(&mut self, pane: u64) -> Option<u64> {
+        let PaneNode::Split { first, second, .. } = self else {
+            return None;
+        };
+        let (sibling, nearest) = if **first == PaneNode::Pane(pane) {
+            let sibling = std::mem::replace(&mut **second, PaneNode::Pane(pane));
+            let nearest = sibling.first_pane();
+            (sibling, nearest)
+        } else if **second == PaneNode::Pane(pane) {
+            let sibling = std::mem::replace(&mut **first, PaneNode::Pane(pane));
+            let nearest = sibling.last_pane();
+            (sibling, nearest)
+        } else {
+            return first.remove(pane).or_else(|| second.remove(pane));
+        };
+        *self = sibling;
+        Some(nearest)
+    }
+
+    /// Move the divider of the innermost `axis` split above `pane` by
+    /// `delta`; `None` when there is no such split
+    fn resize(&mut self, pane: u64, axis: SplitAxis, delta: f64) -> Option<bool> {
+        let PaneNode::Split {
+            axis: split_axis,
+            ratio,
+            first,
+            second,
+        } = self
+        else {
+            return None;
+        };
+        let child = if first.contains(pane) {
+            first
+        } else if second.contains(pane) {
+            second
+        } else {
+            return None;
+        };
+        if let Some(changed) = child.resize(pane, axis, delta) {
+            return Some(changed);
+        }
+        if *split_axis != axis {
+            return None;
+        }
+        let resized = (*ratio + delta).clamp(MIN_SPLIT_RATIO, 1.0 - MIN_SPLIT_RATIO);
+        let changed = resized != *ratio;
+        *ratio = resized;
+        Some(changed)
+    }
+
+    fn layout(&self, area: PaneRect, gap: f64, panes: &mut Vec<(u64, PaneRect)>) {
+        match self {
+            PaneNode::Pane(id) => panes.push((*id, area)),
+            PaneNode::Split {
+                axis,
+                ratio,
+                first,
+                second,
+            } => {
+                let (first_area, second_area) = area.split(*axis, *ratio, gap);
+                first.layout(first_area, gap, panes);
+                second.layout(second_area, gap, panes);
+            }
+        }
+    }
+}
+
+/// Split panes of a tab
+///
+/// Each split divides its area between two nodes, down to the panes, which
+/// the frontend identifies by its own ids and gives a terminal each. One
+/// pane has the focus; new panes take it. Two panes may scroll together,
+/// for comparing their output side by side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaneTree {
+    root: PaneNode,
+    focused: u64,
+    /// Panes whose viewports scroll together
+    scroll_lock: Option<(u64, u64)>,
+}
+
+impl PaneTree {
+    /// A tree holding the single pane `pane`
+    pub fn new(pane: u64) -> Self {
+        Self {
+            root: PaneNode::Pane(pane),
+            focused: pane,
+            scroll_lock: None,
+        }
+    }
+
+    pub fn root(&self) -> &PaneNode {
+        &self.root
+    }
+
+    /// The focused pane
+    pub fn focused(&self) -> u64 {
+        self.focused
+    }
+
+    /// All panes, left to right and top to bottom
+    pub fn panes(&self) -> Vec<u64> {
+        let mut panes = Vec::new();
+        self.root.collect_panes(&mut panes);
+        panes
+    }
+
+    /// Whether there is more than one pane
+    pub fn is_split(&self) -> bool {
+        matches!(self.root, PaneNode::Split { .. })
+    }
+
+    pub fn contains(&self, pane: u64) -> bool {
+        self.root.contains(pane)
+    }
+
+    /// Focus `pane`; false if it is not in the tree
+    pub fn focus(&mut self, pane: u64) -> bool {
+        if !self.contains(pane) {
+            return false;
+        }
+        self.focused = pane;
+        true
+    }
+
+    /// Split `pane` in half along `axis`, with `new_pane` right of or below
+    /// it, and focus `new_pane`
+    pub fn split(&mut self, pane: u64, axis: SplitAxis, new_pane: u64) -> bool {
+        if self.contains(new_pane) {
+            return false;
+        }
+        let Some(leaf) = self.root.leaf_mut(pane) else {
+            return false;
+        };
+        *leaf = PaneNode::Split {
+            axis,
+            ratio: 0.5,
+            first: Box::new(PaneNode::Pane(pane)),
+            second: Box::new(PaneNode::Pane(new_pane)),
+        };
+        self.focused = new_pane;
+        true
+    }
+
+    /// Remove `pane`, giving its area to the other side of its split
+    ///
+    /// The last pane stays: closing it closes the tab instead. When the
+    /// focused pane goes, the focus moves to the pane nearest to it, and
+    /// scrolling locked to it is unlocked.
+    pub fn remove(&mut self, pane: u64) -> bool {
+        let Some(nearest) = self.root.remove(pane) else {
+            return false;
+        };
+        if self.focused == pane {
+            self.focused = nearest;
+        }
+        if self.scroll_partner(pane).is_some() {
+            self.scroll_lock = None;
+        }
+        true
+    }
+
+    /// Lock the scrolling of the focused pane to the pane across its split,
+    /// or unlock it if any two panes are locked; returns whether scrolling
+    /// is locked now
+    pub fn toggle_scroll_lock(&mut self) -> bool {
+        self.scroll_lock = match self.scroll_lock {
+            Some(_) => None,
+            None => self
+                .root
+                .neighbor(self.focused)
+                .map(|neighbor| (self.focused, neighbor)),
+        };
+        self.scroll_lock.is_some()
+    }
+
+    /// The two panes scrolling together, if any
+    pub fn scroll_lock(&self) -> Option<(u64, u64)> {
+        self.scroll_lock
+    }
+
+    /// The pane scrolling together with `pane`
+    pub fn scroll_partner(&self, pane: u64) -> Option<u64> {
+        match self.scroll_lock? {
+            (a, b) if a == pane => Some(b),
+            (a, b) if b == pane => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Area of each pane within `area`, with `gap` between panes for the
+    /// dividers
+    pub fn layout(&self, area: PaneRect, gap: f64) -> Vec<(u64, PaneRect)> {
+        let mut panes = Vec::new();
+        self.root.layout(area, gap, &mut panes);
+        panes
+    }
+
+    /// Move the focus to the neighbor of the focused pane in `direction`,
+    /// laying the panes out in `area`; false if there is none
+    pub fn focus_toward(&mut self, direction: PaneDirection, area: PaneRect) -> bool {
+        let layout = self.layout(area, 0.0);
+        match pane_in_direction(&layout, self.focused, direction) {
+            Some(pane) => {
+                self.focused = pane;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move the divider nearest to the focused pane along `direction` by
+    /// `step` of its split's area; false if nothing moved
+    pub fn resize(&mut self, direction: PaneDirection, step: f64) -> bool {
+        let delta = match direction {
+            PaneDirection::Left | PaneDirection::Up => -step,
+            PaneDirection::Right | PaneDirection::Down => step,
+        };
+        self.root
+            .resize(self.focused, SplitAxis::of(direction), delta)
+            .unwrap_or(false)
+    }
+}
+
+/// Scroll `partner` as far as the viewport of the pane locked to it moved,
+/// from `from` to `to` lines back into the scrollback
+///
+/// The partner's viewport stops at the ends of its own scrollback.
+pub fn mirror_scroll(partner: &mut Terminal, from: usize, to: usize) {
+    if to > from {
+        partner.scroll_viewport_up(to - from);
+    } else {
+        partner.scroll_viewport_down(from - to);
+    }
+}
+
+/// The pane beside `from` in `direction` among laid out panes
+///
+/// Picks the closest pane overlapping `from` across the direction, and of
+/// those the one sharing the longest edge with it. Frontends with their own
+/// split widgets pass the panes' actual areas.
+pub fn pane_in_direction(
+    layout: &[(u64, PaneRect)],
+    from: u64,
+    direction: PaneDirection,
+) -> Option<u64> {
+    let (_, origin) = layout.iter().find(|(id, _)| *id == from)?;
+    let overlap = |a: f64, b: f64, c: f64, d: f64| b.min(d) - a.max(c);
+    layout
+        .iter()
+        .filter(|(id, _)| *id != from)
+        .filter_map(|(id, rect)| {
+            let (distance, shared) = match direction {
+                PaneDirection::Left => (
+                    origin.x - rect.right(),
+                    overlap(origin.y, origin.bottom(), rect.y, rect.bottom()),
+                ),
+                PaneDirection::Right => (
+                    rect.x - origin.right(),
+                    overlap(origin.y, origin.bottom(), rect.y, rect.bottom()),
+                ),
+                PaneDirection::Up => (
+                    origin.y - rect.bottom(),
+                    overlap(origin.x, origin.right(), rect.x, rect.right()),
+                ),
+                PaneDirection::Down => (
+                    rect.y - origin.bottom(),
+                    overlap(origin.x, origin.right(), rect.x, rect.right()),
+                ),
+            };
+            (distance >= -EDGE_TOLERANCE && shared > 0.0).then_some((*id, distance, shared))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(b.2.total_cmp(&a.2)))
+        .map(|(id, ..)| id)
+}
+
 /// Where to insert new tabs
 #[derive(Debug, Clone, Copy)]
 pub enum TabPosition {
@@ -378,30 +882,76 @@ pub struct TabSessionState {
     pub cwd: Option<PathBuf>,
     /// Tab color override
     pub color: Option<String>,
+    /// Tab title
+    #[serde(default)]
+    pub title: String,
+    /// Text of the primary screen and scrollback
+    #[serde(default)]
+    pub scrollback: Option<String>,
+    /// Lines the view was scrolled back from the bottom
+    #[serde(default)]
+    pub scroll_offset: usize,
+}
+
+impl From<ClosedTab> for TabSessionState {
+    fn from(tab: ClosedTab) -> Self {
+        Self {
+            template_name: tab.template,
+            custom_title: None,
+            cwd: tab.cwd.map(PathBuf::from),
+            color: tab.color,
+            title: tab.title,
+            scrollback: tab.scrollback,
+            scroll_offset: tab.scroll_offset,
+        }
+    }
+}
+
+impl TabSessionState {
+    /// What reopening the tab needs, the same as for a recently closed tab
+    pub fn to_closed_tab(&self) -> ClosedTab {
+        ClosedTab {
+            title: self
+                .custom_title
+                .clone()
+                .unwrap_or_else(|| self.title.clone()),
+            template: self.template_name.clone(),
+            cwd: self.cwd.as_ref().map(|p| p.display().to_string()),
+            color: self.color.clone(),
+            scrollback: self.scrollback.clone(),
+            scroll_offset: self.scroll_offset,
+        }
+    }
+}
+
+/// Path of the saved session
+///
+/// It is kept out of the config directory so that git sync never commits it.
+fn session_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "cterm", "cterm").map(|p| p.data_local_dir().join("session.toml"))
 }
 
 impl SessionState {
     /// Save session state
     pub fn save(&self) -> Result<(), std::io::Error> {
-        let path = crate::config::config_dir()
-            .map(|p| p.join("session.toml"))
-            .ok_or_else(|| {
-                std::io::Error::new(std::io::ErrorKind::NotFound, "Config directory not found")
-            })?;
+        let path = session_path().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "Data directory not found")
+        })?;
 
         let content = toml::to_string_pretty(self)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
 
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
         std::fs::write(path, content)
     }
 
     /// Load session state
     pub fn load() -> Result<Self, std::io::Error> {
-        let path = crate::config::config_dir()
-            .map(|p| p.join("session.toml"))
-            .ok_or_else(|| {
-                std::io::Error::new(std::io::ErrorKind::NotFound, "Config directory not found")
-            })?;
+        let path = session_path().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "Data directory not found")
+        })?;
 
         if !path.exists() {
             return Ok(Self {
@@ -413,6 +963,81 @@ impl SessionState {
         toml::from_str(&content)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
     }
+
+    /// Drop what `config` says not to keep: everything when
+    /// `[general] restore_session` is off, the scrollback when `[tabs]
+    /// reopen_scrollback` is off, and windows without tabs
+    fn apply_config(&mut self, config: &Config) {
+        if !config.general.restore_session {
+            self.windows.clear();
+        }
+        self.windows.retain(|w| !w.tabs.is_empty());
+        for window in &mut self.windows {
+            window.active_tab = window.active_tab.min(window.tabs.len() - 1);
+            if !config.tabs.reopen_scrollback {
+                for tab in &mut window.tabs {
+                    tab.scrollback = None;
+                }
+            }
+        }
+    }
+}
+
+/// Record a window as it closes, to be saved at exit if no window is left
+/// open by then
+pub fn remember_window(window: WindowSessionState) {
+    if let Ok(mut last) = LAST_CLOSED_WINDOW.lock() {
+        *last = Some(window);
+    }
+}
+
+/// Save the windows open at exit for the next launch, or the last window
+/// closed when there are none
+///
+/// With `[general] restore_session` off the saved session is removed instead.
+pub fn save_at_exit(windows: Vec<WindowSessionState>, config: &Config) {
+    if crate::safe_mode::is_enabled() {
+        return;
+    }
+    let windows = if windows.is_empty() {
+        LAST_CLOSED_WINDOW
+            .lock()
+            .ok()
+            .and_then(|mut last| last.take())
+            .into_iter()
+            .collect()
+    } else {
+        windows
+    };
+
+    let mut state = SessionState { windows };
+    state.apply_config(config);
+    if state.windows.is_empty() {
+        if let Some(path) = session_path() {
+            let _ = std::fs::remove_file(path);
+        }
+        return;
+    }
+    match state.save() {
+        Ok(()) => log::info!("Saved {} window(s) to restore", state.windows.len()),
+        Err(e) => log::warn!("Failed to save the session: {}", e),
+    }
+}
+
+/// Windows to reopen at launch, if `[general] restore_session` is on
+pub fn windows_to_restore(config: &Config) -> Vec<WindowSessionState> {
+    if crate::safe_mode::is_enabled() {
+        return Vec::new();
+    }
+    let mut state = match SessionState::load() {
+        Ok(state) => state,
+        Err(e) => {
+            log::warn!("Failed to load the saved session: {}", e);
+            return Vec::new();
+        }
+    };
+    state.apply_config(config);
+    state.windows
 }
 
 /// Application session managing all windows
@@ -453,6 +1078,43 @@ impl Session {
         Ok(id)
     }
 
+    /// Open a tab in the active window with `profile`, or the configured
+    /// defaults when there is none, returning its ID
+    pub fn new_tab(
+        &mut self,
+        profile: Option<&ProfileConfig>,
+        config: &Config,
+        cols: usize,
+        rows: usize,
+    ) -> Result<u64, PtyError> {
+        let default = ProfileConfig::default();
+        let mut tab = TabState::from_profile(profile.unwrap_or(&default), config, cols, rows)?;
+        if profile.is_none() {
+            // A plain tab follows the window's theme
+            tab.title = "Terminal".into();
+            tab.profile = None;
+            tab.theme = None;
+        }
+        let id = tab.id;
+        let position = match config.tabs.new_tab_position {
+            NewTabPosition::End => TabPosition::End,
+            NewTabPosition::AfterCurrent => TabPosition::AfterCurrent,
+        };
+        match self.active_window_mut() {
+            Some(window) => window.add_tab(tab, position),
+            None => {
+                self.windows.push(WindowState {
+                    id: next_window_id(),
+                    tabs: vec![tab],
+                    active_tab: 0,
+                    geometry: WindowGeometry::default(),
+                });
+                self.active_window = self.windows.len() - 1;
+            }
+        }
+        Ok(id)
+    }
+
     /// Close a window
     pub fn close_window(&mut self, index: usize) -> bool {
         if self.windows.len() <= 1 || index >= self.windows.len() {
@@ -496,10 +1158,196 @@ mod tests {
         assert_ne!(id1, id2);
     }
 
+    #[test]
+    fn test_session_state_restore() {
+        // Sessions saved before the title and scrollback were kept still load
+        let mut state: SessionState = toml::from_str(
+            r#"
+            [[windows]]
+            active_tab = 3
+            tabs = [{ template_name = "Logs", cwd = "/var/log" }]
+            [windows.geometry]
+            x = 0
+            y = 0
+            width = 800
+            height = 600
+            maximized = true
+
+            [[windows]]
+            active_tab = 0
+            tabs = []
+            [windows.geometry]
+            x = 0
+            y = 0
+            width = 800
+            height = 600
+            maximized = false
+            "#,
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config.general.restore_session = true;
+        config.tabs.reopen_scrollback = false;
+        state.windows[0].tabs[0].scrollback = Some("old output".into());
+        state.apply_config(&config);
+        // The window without tabs is dropped
+        assert_eq!(state.windows.len(), 1);
+        let window = &state.windows[0];
+        assert_eq!(window.active_tab, 0);
+        assert!(window.geometry.maximized);
+        let tab = &window.tabs[0];
+        assert_eq!(tab.title, "");
+        assert_eq!(tab.scrollback, None);
+
+        let closed = tab.to_closed_tab();
+        assert_eq!(closed.template.as_deref(), Some("Logs"));
+        assert_eq!(closed.cwd.as_deref(), Some("/var/log"));
+
+        let mut closed = ClosedTab::new("build");
+        closed.scrollback = Some("make: done".into());
+        closed.scroll_offset = 4;
+        let tab = TabSessionState::from(closed.clone());
+        let text = toml::to_string(&tab).unwrap();
+        let tab: TabSessionState = toml::from_str(&text).unwrap();
+        assert_eq!(tab.to_closed_tab(), closed);
+
+        config.general.restore_session = false;
+        state.apply_config(&config);
+        assert!(state.windows.is_empty());
+    }
+
     #[test]
     fn test_window_geometry_default() {
         let geo = WindowGeometry::default();
         assert_eq!(geo.width, 800);
         assert_eq!(geo.height, 600);
     }
+
+    #[test]
+    fn test_pane_tree_split_and_remove() {
+        let mut tree = PaneTree::new(1);
+        assert!(!tree.is_split());
+        assert!(!tree.remove(1));
+
+        assert!(tree.split(1, SplitAxis::Horizontal, 2));
+        assert!(tree.split(2, SplitAxis::Vertical, 3));
+        assert!(!tree.split(9, SplitAxis::Vertical, 4));
+        assert!(!tree.split(1, SplitAxis::Vertical, 3));
+        assert_eq!(tree.panes(), vec![1, 2, 3]);
+        assert_eq!(tree.focused(), 3);
+
+        // The pane below takes the place of its split and the focus
+        assert!(tree.remove(3));
+        assert_eq!(tree.focused(), 2);
+        assert!(tree.remove(1));
+        assert_eq!(tree.root(), &PaneNode::Pane(2));
+        assert!(!tree.is_split());
+    }
+
+    #[test]
+    fn test_pane_tree_layout() {
+        let mut tree = PaneTree::new(1);
+        tree.split(1, SplitAxis::Horizontal, 2);
+        tree.split(2, SplitAxis::Vertical, 3);
+
+        let layout = tree.layout(PaneRect::new(0.0, 0.0, 102.0, 52.0), 2.0);
+        assert_eq!(
+            layout,
+            vec![
+                (1, PaneRect::new(0.0, 0.0, 50.0, 52.0)),
+                (2, PaneRect::new(52.0, 0.0, 50.0, 25.0)),
+                (3, PaneRect::new(52.0, 27.0, 50.0, 25.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pane_tree_focus_toward() {
+        // 1 | 2
+        //   | -
+        //   | 3
+        let mut tree = PaneTree::new(1);
+        tree.split(1, SplitAxis::Horizontal, 2);
+        tree.split(2, SplitAxis::Vertical, 3);
+        let area = PaneRect::new(0.0, 0.0, 100.0, 50.0);
+
+        assert!(tree.focus_toward(PaneDirection::Up, area));
+        assert_eq!(tree.focused(), 2);
+        assert!(!tree.focus_toward(PaneDirection::Right, area));
+        assert!(tree.focus_toward(PaneDirection::Left, area));
+        assert_eq!(tree.focused(), 1);
+        assert!(!tree.focus_toward(PaneDirection::Down, area));
+        // Both right-hand panes touch 1 along the same length: the first wins
+        assert!(tree.focus_toward(PaneDirection::Right, area));
+        assert_eq!(tree.focused(), 2);
+        assert!(tree.focus_toward(PaneDirection::Down, area));
+        assert_eq!(tree.focused(), 3);
+    }
+
+    #[test]
+    fn test_pane_tree_resize() {
+        let mut tree = PaneTree::new(1);
+        tree.split(1, SplitAxis::Horizontal, 2);
+        tree.split(2, SplitAxis::Vertical, 3);
+        let area = PaneRect::new(0.0, 0.0, 100.0, 100.0);
+
+        // Pane 3 moves the divider above it, then the one to its left
+        assert!(tree.resize(PaneDirection::Up, 0.1));
+        assert_eq!(tree.layout(area, 0.0)[1].1.height, 40.0);
+        assert!(tree.resize(PaneDirection::Left, 0.2));
+        assert_eq!(tree.layout(area, 0.0)[0].1.width, 30.0);
+
+        // Dividers stop short of the edges
+        for _ in 0..10 {
+            tree.resize(PaneDirection::Left, 0.1);
+        }
+        assert!(!tree.resize(PaneDirection::Left, 0.1));
+        assert_eq!(tree.layout(area, 0.0)[0].1.width, 10.0);
+
+        let mut single = PaneTree::new(1);
+        assert!(!single.resize(PaneDirection::Right, 0.1));
+    }
+
+    #[test]
+    fn test_pane_tree_scroll_lock() {
+        // 1 | 2
+        //   | -
+        //   | 3
+        let mut tree = PaneTree::new(1);
+        assert!(!tree.toggle_scroll_lock());
+        tree.split(1, SplitAxis::Horizontal, 2);
+        tree.split(2, SplitAxis::Vertical, 3);
+
+        // Pane 3 locks to the pane above it
+        assert!(tree.toggle_scroll_lock());
+        assert_eq!(tree.scroll_lock(), Some((3, 2)));
+        assert_eq!(tree.scroll_partner(2), Some(3));
+        assert_eq!(tree.scroll_partner(1), None);
+        assert!(!tree.toggle_scroll_lock());
+        assert_eq!(tree.scroll_partner(2), None);
+
+        // Pane 1 locks to the nearest pane across its split
+        tree.focus(1);
+        assert!(tree.toggle_scroll_lock());
+        assert_eq!(tree.scroll_partner(1), Some(2));
+        // Closing another pane keeps the lock, closing a locked one ends it
+        tree.remove(3);
+        assert_eq!(tree.scroll_lock(), Some((1, 2)));
+        tree.remove(2);
+        assert_eq!(tree.scroll_lock(), None);
+    }
+
+    #[test]
+    fn test_mirror_scroll() {
+        let mut partner = Terminal::new(10, 2, ScreenConfig::default());
+        partner.process(b"1\r\n2\r\n3\r\n4\r\n5\r\n6");
+        mirror_scroll(&mut partner, 0, 3);
+        assert_eq!(partner.screen().scroll_offset, 3);
+        mirror_scroll(&mut partner, 3, 1);
+        assert_eq!(partner.screen().scroll_offset, 1);
+        // The partner stops at the top of its shorter scrollback
+        mirror_scroll(&mut partner, 1, 20);
+        assert_eq!(partner.screen().scroll_offset, 4);
+    }
 }
@@ -0,0 +1,819 @@
+//! Schema of the settings shown in the preferences windows
+//!
+//! Each [`Setting`] names a value in [`Config`] by its dotted TOML key
+//! (`appearance.font.size`) and describes its type, range, default and
+//! preferences tab. The macOS, GTK and Windows preferences build their rows
+//! from [`SETTINGS`], and `cterm config get/set` uses it to find and validate
+//! values, so every frontend agrees on which settings exist and what they
+//! accept.
+
+use thiserror::Error;
+
+use crate::config::{find_theme, load_user_themes, Config};
+use cterm_ui::theme::Theme;
+
+/// Setting values, as written to `config.toml`
+pub use toml::Value;
+
+/// Errors looking up, parsing or applying a setting
+#[derive(Error, Debug, PartialEq)]
+pub enum SettingError {
+    #[error("Unknown setting: {0}")]
+    Unknown(String),
+
+    #[error("{key} must be {expected}, not {value:?}")]
+    Invalid {
+        key: &'static str,
+        expected: String,
+        value: String,
+    },
+
+    #[error("{key} must be between {min} and {max}, not {value}")]
+    OutOfRange {
+        key: &'static str,
+        min: String,
+        max: String,
+        value: String,
+    },
+
+    #[error("Failed to apply {key}: {message}")]
+    Apply { key: &'static str, message: String },
+}
+
+/// Preferences tab a setting is shown on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettingCategory {
+    General,
+    Appearance,
+    Tabs,
+}
+
+impl SettingCategory {
+    /// Every category, in tab order
+    pub const ALL: [SettingCategory; 3] = [
+        SettingCategory::General,
+        SettingCategory::Appearance,
+        SettingCategory::Tabs,
+    ];
+
+    /// Tab title
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingCategory::General => "General",
+            SettingCategory::Appearance => "Appearance",
+            SettingCategory::Tabs => "Tabs",
+        }
+    }
+
+    /// Settings on this tab, in display order
+    pub fn settings(self) -> impl Iterator<Item = &'static Setting> {
+        SETTINGS
+            .iter()
+            .filter(move |setting| setting.category == self)
+    }
+}
+
+/// Type of a setting's value, which also picks the control editing it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingKind {
+    /// Checkbox or switch
+    Bool,
+    /// Whole number in `min..=max`, edited with a spin button
+    Integer { min: i64, max: i64, step: i64 },
+    /// Number in `min..=max`, edited with a spin button
+    Float { min: f64, max: f64, step: f64 },
+    /// Fraction from 0.0 to 1.0, edited with a slider
+    Ratio,
+    /// Free text
+    Text,
+    /// One of `(value, label)`, edited with a drop-down
+    Choice(&'static [(&'static str, &'static str)]),
+    /// Name of a built-in or user theme, edited with a drop-down
+    Theme,
+}
+
+impl SettingKind {
+    /// What a value must look like, for error messages
+    fn expected(&self) -> String {
+        match self {
+            SettingKind::Bool => "true or false".into(),
+            SettingKind::Integer { .. } => "a whole number".into(),
+            SettingKind::Float { .. } | SettingKind::Ratio => "a number".into(),
+            SettingKind::Text => "text".into(),
+            SettingKind::Choice(options) => format!(
+                "one of {}",
+                options
+                    .iter()
+                    .map(|(value, _)| *value)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            SettingKind::Theme => "a theme name".into(),
+        }
+    }
+}
+
+/// A setting shown in the preferences windows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Setting {
+    /// Dotted path of the value in `config.toml`
+    pub key: &'static str,
+    /// Row label
+    pub label: &'static str,
+    /// One-line explanation, used as tooltip and by `cterm config list`
+    pub description: &'static str,
+    pub category: SettingCategory,
+    pub kind: SettingKind,
+    /// Other words a search should find the setting by
+    pub keywords: &'static [&'static str],
+}
+
+impl Setting {
+    /// Whether every word of `query` appears in the label, key, keywords,
+    /// description or category, ignoring case. An empty query matches
+    /// everything.
+    pub fn matches(&self, query: &str) -> bool {
+        let haystack = format!(
+            "{} {} {} {} {}",
+            self.label,
+            self.key.replace(['.', '_'], " "),
+            self.keywords.join(" "),
+            self.description,
+            self.category.name()
+        )
+        .to_lowercase();
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word))
+    }
+
+    /// Default value
+    pub fn default_value(&self) -> Option<Value> {
+        default_value(self.key)
+    }
+
+    /// `(value, label)` options of a drop-down setting, empty for others.
+    /// Theme options are the built-in themes followed by the user themes.
+    pub fn choices(&self) -> Vec<(String, String)> {
+        match self.kind {
+            SettingKind::Choice(options) => options
+                .iter()
+                .map(|(value, label)| (value.to_string(), label.to_string()))
+                .collect(),
+            SettingKind::Theme => {
+                let mut names: Vec<String> = Theme::builtin_themes()
+                    .into_iter()
+                    .map(|theme| theme.name)
+                    .collect();
+                for theme in load_user_themes() {
+                    if !names.contains(&theme.name) {
+                        names.push(theme.name);
+                    }
+                }
+                names.into_iter().map(|name| (name.clone(), name)).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Index in `choices` of the option holding `value`. Themes also match
+    /// by their short IDs (`dark`, `tokyo_night`).
+    pub fn choice_index(&self, choices: &[(String, String)], value: &Value) -> Option<usize> {
+        let value = value.as_str()?;
+        choices
+            .iter()
+            .position(|(choice, _)| choice == value)
+            .or_else(|| {
+                let name = find_theme(value)?.name;
+                choices.iter().position(|(choice, _)| *choice == name)
+            })
+    }
+
+    /// Check a value against the type and range, converting whole numbers
+    /// for number settings
+    pub fn check(&self, value: Value) -> Result<Value, SettingError> {
+        let invalid = |value: &Value| SettingError::Invalid {
+            key: self.key,
+            expected: self.kind.expected(),
+            value: format_value(value),
+        };
+        match self.kind {
+            SettingKind::Bool => match value {
+                Value::Boolean(_) => Ok(value),
+                _ => Err(invalid(&value)),
+            },
+            SettingKind::Integer { min, max, .. } => match value {
+                Value::Integer(n) if (min..=max).contains(&n) => Ok(value),
+                Value::Integer(n) => Err(self.out_of_range(min, max, n)),
+                _ => Err(invalid(&value)),
+            },
+            SettingKind::Float { .. } | SettingKind::Ratio => {
+                let (min, max) = match self.kind {
+                    SettingKind::Float { min, max, .. } => (min, max),
+                    _ => (0.0, 1.0),
+                };
+                let n = match value {
+                    Value::Float(n) => n,
+                    Value::Integer(n) => n as f64,
+                    _ => return Err(invalid(&value)),
+                };
+                if (min..=max).contains(&n) {
+                    Ok(Value::Float(n))
+                } else {
+                    Err(self.out_of_range(min, max, n))
+                }
+            }
+            SettingKind::Text => match value {
+                Value::String(_) => Ok(value),
+                _ => Err(invalid(&value)),
+            },
+            SettingKind::Choice(options) => match value.as_str() {
+                Some(s) if options.iter().any(|(choice, _)| *choice == s) => Ok(value),
+                _ => Err(invalid(&value)),
+            },
+            SettingKind::Theme => match value.as_str() {
+                Some(s) if find_theme(s).is_some() => Ok(value),
+                _ => Err(invalid(&value)),
+            },
+        }
+    }
+
+    /// Parse a value typed by the user, as for `cterm config set`
+    pub fn parse(&self, text: &str) -> Result<Value, SettingError> {
+        let text = text.trim();
+        let invalid = || SettingError::Invalid {
+            key: self.key,
+            expected: self.kind.expected(),
+            value: text.to_string(),
+        };
+        let value = match self.kind {
+            SettingKind::Bool => match text.to_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Value::Boolean(true),
+                "false" | "no" | "off" | "0" => Value::Boolean(false),
+                _ => return Err(invalid()),
+            },
+            SettingKind::Integer { .. } => Value::Integer(text.parse().map_err(|_| invalid())?),
+            SettingKind::Float { .. } | SettingKind::Ratio => {
+                Value::Float(text.parse().map_err(|_| invalid())?)
+            }
+            SettingKind::Choice(options) => {
+                // Accept the label too ("After current")
+                let choice = options
+                    .iter()
+                    .find(|(value, label)| {
+                        value.eq_ignore_ascii_case(text) || label.eq_ignore_ascii_case(text)
+                    })
+                    .ok_or_else(invalid)?;
+                Value::String(choice.0.to_string())
+            }
+            SettingKind::Text | SettingKind::Theme => Value::String(text.to_string()),
+        };
+        self.check(value)
+    }
+
+    fn out_of_range<T: ToString>(&self, min: T, max: T, value: T) -> SettingError {
+        SettingError::OutOfRange {
+            key: self.key,
+            min: min.to_string(),
+            max: max.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+const CURSOR_STYLES: &[(&str, &str)] = &[
+    ("block", "Block"),
+    ("underline", "Underline"),
+    ("bar", "Bar"),
+];
+
+const TAB_BAR_VISIBILITY: &[(&str, &str)] = &[
+    ("always", "Always"),
+    ("multiple", "When multiple tabs"),
+    ("never", "Never"),
+];
+
+const TAB_BAR_POSITIONS: &[(&str, &str)] = &[("top", "Top"), ("bottom", "Bottom")];
+
+const NEW_TAB_POSITIONS: &[(&str, &str)] = &[("end", "At end"), ("aftercurrent", "After current")];
+
+/// All settings, grouped by category in display order
+pub const SETTINGS: &[Setting] = &[
+    Setting {
+        key: "general.scrollback_lines",
+        label: "Scrollback lines",
+        description: "Lines of history kept above the screen",
+        category: SettingCategory::General,
+        kind: SettingKind::Integer {
+            min: 0,
+            max: 1_000_000,
+            step: 1000,
+        },
+        keywords: &["history", "buffer", "lines"],
+    },
+    Setting {
+        key: "general.confirm_close_with_running",
+        label: "Confirm close with running processes",
+        description: "Ask before closing a tab or window with a running command",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["quit", "warn", "prompt"],
+    },
+    Setting {
+        key: "general.copy_on_select",
+        label: "Copy on select",
+        description: "Copy selected text to the clipboard right away",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["clipboard", "selection"],
+    },
+    Setting {
+        key: "general.copy_rich_text",
+        label: "Copy with colors",
+        description: "Also copy the colors and styles of the selection as rich text (macOS and Windows)",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["clipboard", "rtf", "formatting", "paste"],
+    },
+    Setting {
+        key: "general.confirm_paste_lines",
+        label: "Confirm pasting lines",
+        description: "Ask before pasting this many lines or more into a program without bracketed paste (0 never asks)",
+        category: SettingCategory::General,
+        kind: SettingKind::Integer {
+            min: 0,
+            max: 10_000,
+            step: 1,
+        },
+        keywords: &["clipboard", "multi-line", "newline", "protection", "warn"],
+    },
+    Setting {
+        key: "general.confirm_paste_control",
+        label: "Confirm pasting control characters",
+        description: "Ask before pasting escape sequences or other control characters into a program without bracketed paste",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["clipboard", "escape", "protection", "warn", "bidi"],
+    },
+    Setting {
+        key: "clipboard_history.max_items",
+        label: "Clipboard history size",
+        description: "Copies kept for Paste from History (0 keeps none)",
+        category: SettingCategory::General,
+        kind: SettingKind::Integer {
+            min: 0,
+            max: 1000,
+            step: 10,
+        },
+        keywords: &["clipboard", "paste", "history", "copy"],
+    },
+    Setting {
+        key: "clipboard_history.include_osc52",
+        label: "Keep copies made by programs",
+        description: "Also keep text that programs put on the clipboard with OSC 52",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["clipboard", "paste", "history", "osc 52", "tmux", "vim"],
+    },
+    Setting {
+        key: "clipboard_history.persist",
+        label: "Remember clipboard history",
+        description: "Keep the clipboard history across restarts, on this computer only",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["clipboard", "paste", "history", "save", "disk"],
+    },
+    Setting {
+        key: "clipboard_history.encrypt",
+        label: "Encrypt saved clipboard history",
+        description: "Encrypt the remembered history with a key stored beside it",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["clipboard", "paste", "history", "secret", "password"],
+    },
+    Setting {
+        key: "input.hover_previews",
+        label: "Link previews",
+        description: "Show the page title and icon of a hovered URL (fetched from the site) or a thumbnail of a hovered image path",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["hover", "popover", "thumbnail", "favicon", "url", "image"],
+    },
+    Setting {
+        key: "input.hide_pointer_while_typing",
+        label: "Hide pointer while typing",
+        description: "Hide the mouse pointer over the terminal when a key is pressed, until the mouse moves",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["mouse", "cursor", "keyboard"],
+    },
+    Setting {
+        key: "general.show_debug_menu",
+        label: "Show debug menu",
+        description: "Show the Debug submenu under Help",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["developer", "help"],
+    },
+    Setting {
+        key: "general.usage_stats",
+        label: "Collect usage statistics",
+        description: "Count commands and tab time for the Usage Statistics window; kept on this computer only",
+        category: SettingCategory::General,
+        kind: SettingKind::Bool,
+        keywords: &["stats", "dashboard", "history", "privacy"],
+    },
+    Setting {
+        key: "appearance.theme",
+        label: "Theme",
+        description: "Color theme, built-in or from the themes directory",
+        category: SettingCategory::Appearance,
+        kind: SettingKind::Theme,
+        keywords: &["colors", "palette", "scheme"],
+    },
+    Setting {
+        key: "appearance.font.family",
+        label: "Font",
+        description: "Font family name",
+        category: SettingCategory::Appearance,
+        kind: SettingKind::Text,
+        keywords: &["typeface", "family"],
+    },
+    Setting {
+        key: "appearance.font.size",
+        label: "Font size",
+        description: "Font size in points",
+        category: SettingCategory::Appearance,
+        kind: SettingKind::Float {
+            min: 6.0,
+            max: 72.0,
+            step: 1.0,
+        },
+        keywords: &["text", "points", "zoom"],
+    },
+    Setting {
+        key: "appearance.cursor_style",
+        label: "Cursor style",
+        description: "Shape of the text cursor",
+        category: SettingCategory::Appearance,
+        kind: SettingKind::Choice(CURSOR_STYLES),
+        keywords: &["block", "underline", "bar", "caret"],
+    },
+    Setting {
+        key: "appearance.cursor_blink",
+        label: "Cursor blink",
+        description: "Blink the text cursor",
+        category: SettingCategory::Appearance,
+        kind: SettingKind::Bool,
+        keywords: &["caret", "flash"],
+    },
+    Setting {
+        key: "appearance.opacity",
+        label: "Opacity",
+        description: "Window background opacity",
+        category: SettingCategory::Appearance,
+        kind: SettingKind::Ratio,
+        keywords: &["transparency", "translucent", "alpha"],
+    },
+    Setting {
+        key: "appearance.background_blur",
+        label: "Background blur",
+        description: "Blur what shows through the background when opacity is below 100%",
+        category: SettingCategory::Appearance,
+        kind: SettingKind::Bool,
+        keywords: &["frosted", "acrylic", "vibrancy", "glass"],
+    },
+    Setting {
+        key: "appearance.padding",
+        label: "Padding",
+        description: "Space in pixels around the terminal text",
+        category: SettingCategory::Appearance,
+        kind: SettingKind::Integer {
+            min: 0,
+            max: 64,
+            step: 1,
+        },
+        keywords: &["margin", "border", "spacing"],
+    },
+    Setting {
+        key: "appearance.bold_is_bright",
+        label: "Bold text uses bright colors",
+        description: "Draw bold text in the bright variant of its ANSI color",
+        category: SettingCategory::Appearance,
+        kind: SettingKind::Bool,
+        keywords: &["colors", "ansi"],
+    },
+    Setting {
+        key: "tabs.show_tab_bar",
+        label: "Show tab bar",
+        description: "When the tab bar is visible",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Choice(TAB_BAR_VISIBILITY),
+        keywords: &["hide", "visibility"],
+    },
+    Setting {
+        key: "tabs.tab_bar_position",
+        label: "Tab bar position",
+        description: "Edge of the window the tab bar is on",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Choice(TAB_BAR_POSITIONS),
+        keywords: &["top", "bottom"],
+    },
+    Setting {
+        key: "tabs.new_tab_position",
+        label: "New tab position",
+        description: "Where new tabs are inserted",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Choice(NEW_TAB_POSITIONS),
+        keywords: &["open", "insert", "order"],
+    },
+    Setting {
+        key: "tabs.show_close_button",
+        label: "Show close button",
+        description: "Show a close button on each tab",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Bool,
+        keywords: &["x"],
+    },
+    Setting {
+        key: "tabs.title_format",
+        label: "Title format",
+        description: "Tab title, with {title} for the title set by the shell and {name} for the status segment called name",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Text,
+        keywords: &["name", "label", "status", "segment", "git", "branch"],
+    },
+    Setting {
+        key: "tabs.closed_tab_history",
+        label: "Closed tab history",
+        description: "Closed tabs remembered for Reopen Closed Tab (0 disables)",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Integer {
+            min: 0,
+            max: 100,
+            step: 1,
+        },
+        keywords: &["reopen", "undo", "restore"],
+    },
+    Setting {
+        key: "tabs.reopen_scrollback",
+        label: "Restore scrollback of reopened tabs",
+        description: "Bring back the text of a tab when reopening it",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Bool,
+        keywords: &["reopen", "history", "restore"],
+    },
+    Setting {
+        key: "widgets.clock",
+        label: "Show clock",
+        description: "Show the time at the end of the tab bar",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Bool,
+        keywords: &["widget", "time"],
+    },
+    Setting {
+        key: "widgets.clock_12h",
+        label: "12-hour clock",
+        description: "Show the clock as 2:05 PM instead of 14:05",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Bool,
+        keywords: &["widget", "time", "am", "pm"],
+    },
+    Setting {
+        key: "widgets.timer",
+        label: "Show timer",
+        description: "Countdown at the end of the tab bar: click to start or pause, right-click to reset",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Bool,
+        keywords: &["widget", "pomodoro", "countdown"],
+    },
+    Setting {
+        key: "widgets.timer_minutes",
+        label: "Timer length",
+        description: "Length of the countdown in minutes",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Integer {
+            min: 1,
+            max: 600,
+            step: 5,
+        },
+        keywords: &["widget", "pomodoro", "countdown"],
+    },
+    Setting {
+        key: "widgets.battery",
+        label: "Show battery",
+        description: "Show the battery charge at the end of the tab bar",
+        category: SettingCategory::Tabs,
+        kind: SettingKind::Bool,
+        keywords: &["widget", "power", "charge"],
+    },
+];
+
+/// Look up a setting by key
+pub fn find(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+/// Settings matching `query`, in display order
+pub fn search(query: &str) -> Vec<&'static Setting> {
+    SETTINGS
+        .iter()
+        .filter(|setting| setting.matches(query))
+        .collect()
+}
+
+/// Current value of `key` in `config`, as it would be written to
+/// `config.toml`. None for unknown keys and for unset optional values.
+pub fn value(config: &Config, key: &str) -> Option<Value> {
+    let root = Value::try_from(config).ok()?;
+    key.split('.')
+        .try_fold(&root, |value, part| value.get(part))
+        .cloned()
+}
+
+/// Default value of `key`
+pub fn default_value(key: &str) -> Option<Value> {
+    value(&Config::default(), key)
+}
+
+/// Whether `key` has its default value in `config`
+pub fn is_default(config: &Config, key: &str) -> bool {
+    value(config, key) == default_value(key)
+}
+
+/// Current value of the setting `key`
+pub fn get(config: &Config, key: &str) -> Result<Value, SettingError> {
+    let setting = find(key).ok_or_else(|| SettingError::Unknown(key.to_string()))?;
+    value(config, setting.key).ok_or_else(|| SettingError::Unknown(key.to_string()))
+}
+
+/// Change the setting `key` after checking `value` against its schema
+pub fn set(config: &mut Config, key: &str, value: Value) -> Result<(), SettingError> {
+    let setting = find(key).ok_or_else(|| SettingError::Unknown(key.to_string()))?;
+    let value = setting.check(value)?;
+    put(config, setting.key, Some(value))
+}
+
+/// Change the setting `key` to a value typed by the user
+pub fn set_str(config: &mut Config, key: &str, text: &str) -> Result<(), SettingError> {
+    let setting = find(key).ok_or_else(|| SettingError::Unknown(key.to_string()))?;
+    let value = setting.parse(text)?;
+    put(config, setting.key, Some(value))
+}
+
+/// Put `key` back to its default value, leaving every other setting alone.
+/// Returns false if `key` is not a registered setting.
+pub fn reset(config: &mut Config, key: &str) -> bool {
+    let Some(setting) = find(key) else {
+        return false;
+    };
+    match put(config, setting.key, default_value(key)) {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("{}", e);
+            false
+        }
+    }
+}
+
+/// Format a value for display: strings without quotes, numbers and booleans
+/// as in `config.toml`
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Replace (or remove, for None) the value at `key` through a TOML round
+/// trip, so every setting is reachable by its dotted path
+fn put(config: &mut Config, key: &'static str, value: Option<Value>) -> Result<(), SettingError> {
+    let apply_error = |message: String| SettingError::Apply { key, message };
+    let mut root = Value::try_from(&*config).map_err(|e| apply_error(e.to_string()))?;
+    let (path, last) = match key.rsplit_once('.') {
+        Some((path, last)) => (Some(path), last),
+        None => (None, key),
+    };
+    let parent = path
+        .into_iter()
+        .flat_map(|path| path.split('.'))
+        .try_fold(&mut root, |value, part| value.get_mut(part));
+    let Some(Value::Table(table)) = parent else {
+        return Err(apply_error("not a table".into()));
+    };
+    match value {
+        Some(value) => table.insert(last.to_string(), value),
+        None => table.remove(last),
+    };
+    *config = root.try_into().map_err(|e| apply_error(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_resolve() {
+        let config = Config::default();
+        for setting in SETTINGS {
+            let default = value(&config, setting.key)
+                .unwrap_or_else(|| panic!("{} is not in Config", setting.key));
+            assert_eq!(
+                setting.check(default.clone()),
+                Ok(default),
+                "{} default does not fit its schema",
+                setting.key
+            );
+        }
+        assert_eq!(
+            find("tabs.show_tab_bar").map(|s| s.label),
+            Some("Show tab bar")
+        );
+        assert!(find("general.nope").is_none());
+    }
+
+    #[test]
+    fn test_search() {
+        let keys = |query: &str| search(query).iter().map(|s| s.key).collect::<Vec<_>>();
+        assert_eq!(keys("transparency"), vec!["appearance.opacity"]);
+        assert_eq!(
+            keys("CURSOR"),
+            vec!["appearance.cursor_style", "appearance.cursor_blink"]
+        );
+        assert_eq!(
+            keys("tab position"),
+            vec!["tabs.tab_bar_position", "tabs.new_tab_position"]
+        );
+        assert_eq!(search("").len(), SETTINGS.len());
+        assert!(search("no such setting").is_empty());
+    }
+
+    #[test]
+    fn test_reset_one_setting() {
+        let mut config = Config::default();
+        config.appearance.font.size = 20.0;
+        config.appearance.font.family = "Menlo".into();
+        config.general.copy_on_select = true;
+        assert!(!is_default(&config, "appearance.font.size"));
+
+        assert!(reset(&mut config, "appearance.font.size"));
+        assert!(is_default(&config, "appearance.font.size"));
+        assert_eq!(config.appearance.font.family, "Menlo");
+        assert!(config.general.copy_on_select);
+
+        assert!(!reset(&mut config, "appearance.font"));
+    }
+
+    #[test]
+    fn test_set_validates() {
+        let mut config = Config::default();
+        set_str(&mut config, "appearance.font.size", "16").unwrap();
+        assert_eq!(config.appearance.font.size, 16.0);
+        set_str(&mut config, "general.copy_on_select", "yes").unwrap();
+        assert!(config.general.copy_on_select);
+        set_str(&mut config, "tabs.new_tab_position", "After current").unwrap();
+        assert_eq!(
+            get(&config, "tabs.new_tab_position"),
+            Ok(Value::String("aftercurrent".into()))
+        );
+        set(&mut config, "appearance.opacity", Value::Float(0.5)).unwrap();
+        assert_eq!(config.appearance.opacity, 0.5);
+
+        assert!(matches!(
+            set_str(&mut config, "appearance.font.size", "200"),
+            Err(SettingError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            set_str(&mut config, "appearance.cursor_style", "round"),
+            Err(SettingError::Invalid { .. })
+        ));
+        assert!(matches!(
+            set(&mut config, "general.copy_on_select", Value::Integer(1)),
+            Err(SettingError::Invalid { .. })
+        ));
+        assert_eq!(
+            set_str(&mut config, "appearance.font", "x"),
+            Err(SettingError::Unknown("appearance.font".into()))
+        );
+        assert_eq!(config.appearance.font.size, 16.0);
+    }
+
+    #[test]
+    fn test_choices() {
+        let theme = find("appearance.theme").unwrap();
+        let choices = theme.choices();
+        assert_eq!(choices[0].0, "Default Dark");
+        assert_eq!(
+            theme.choice_index(&choices, &Value::String("dracula".into())),
+            Some(3)
+        );
+        let cursor = find("appearance.cursor_style").unwrap();
+        assert_eq!(
+            cursor.choice_index(&cursor.choices(), &Value::String("bar".into())),
+            Some(2)
+        );
+        assert!(find("appearance.opacity").unwrap().choices().is_empty());
+    }
+}
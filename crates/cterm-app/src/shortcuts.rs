@@ -4,7 +4,7 @@
 
 use std::collections::HashMap;
 
-use cterm_ui::events::{Action, KeyCode, Modifiers, Shortcut};
+use cterm_ui::events::{Action, KeyCode, Modifiers, PaneDirection, Shortcut};
 
 use crate::config::ShortcutsConfig;
 
@@ -42,10 +42,14 @@ impl ShortcutManager {
         manager.bind_str(&config.close_window, Action::CloseWindow);
         manager.bind_str(&config.copy, Action::Copy);
         manager.bind_str(&config.paste, Action::Paste);
+        manager.bind_str(&config.paste_history, Action::PasteHistory);
         manager.bind_str(&config.select_all, Action::SelectAll);
         manager.bind_str(&config.zoom_in, Action::ZoomIn);
         manager.bind_str(&config.zoom_out, Action::ZoomOut);
         manager.bind_str(&config.zoom_reset, Action::ZoomReset);
+        manager.bind_str(&config.magnify_in, Action::MagnifyIn);
+        manager.bind_str(&config.magnify_out, Action::MagnifyOut);
+        manager.bind_str(&config.magnify_reset, Action::MagnifyReset);
         manager.bind_str(&config.scroll_up, Action::ScrollUp);
         manager.bind_str(&config.scroll_down, Action::ScrollDown);
         manager.bind_str(&config.scroll_page_up, Action::ScrollPageUp);
@@ -53,6 +57,34 @@ impl ShortcutManager {
         manager.bind_str(&config.preferences, Action::OpenPreferences);
         manager.bind_str(&config.find, Action::FindText);
         manager.bind_str(&config.reset, Action::ResetTerminal);
+        manager.bind_str(&config.ascii_art_safe, Action::ToggleAsciiArtSafe);
+        manager.bind_str(&config.presentation_mode, Action::TogglePresentationMode);
+        manager.bind_str(&config.hints, Action::HintMode);
+        manager.bind_str(&config.extract_matches, Action::ExtractMatches);
+        manager.bind_str(&config.reopen_closed_tab, Action::ReopenClosedTab);
+        manager.bind_str(&config.read_only, Action::ToggleReadOnly);
+        manager.bind_str(&config.previous_prompt, Action::PreviousPrompt);
+        manager.bind_str(&config.next_prompt, Action::NextPrompt);
+        manager.bind_str(&config.select_command_output, Action::SelectCommandOutput);
+        manager.bind_str(&config.split_right, Action::SplitRight);
+        manager.bind_str(&config.split_down, Action::SplitDown);
+        manager.bind_str(&config.close_pane, Action::ClosePane);
+        let left = PaneDirection::Left;
+        let right = PaneDirection::Right;
+        let up = PaneDirection::Up;
+        let down = PaneDirection::Down;
+        manager.bind_str(&config.focus_pane_left, Action::FocusPane(left));
+        manager.bind_str(&config.focus_pane_right, Action::FocusPane(right));
+        manager.bind_str(&config.focus_pane_up, Action::FocusPane(up));
+        manager.bind_str(&config.focus_pane_down, Action::FocusPane(down));
+        manager.bind_str(&config.resize_pane_left, Action::ResizePane(left));
+        manager.bind_str(&config.resize_pane_right, Action::ResizePane(right));
+        manager.bind_str(&config.resize_pane_up, Action::ResizePane(up));
+        manager.bind_str(&config.resize_pane_down, Action::ResizePane(down));
+        manager.bind_str(&config.lock_scrolling, Action::ToggleScrollLock);
+        manager.bind_str(&config.snap_left, Action::SnapLeft);
+        manager.bind_str(&config.snap_right, Action::SnapRight);
+        manager.bind_str(&config.snap_maximize, Action::SnapMaximize);
 
         manager
     }
@@ -62,6 +94,13 @@ impl ShortcutManager {
         // Tab shortcuts
         self.bind(Shortcut::ctrl_shift(KeyCode::T), Action::NewTab);
         self.bind(Shortcut::ctrl_shift(KeyCode::W), Action::CloseTab);
+        self.bind(
+            Shortcut::new(
+                KeyCode::T,
+                Modifiers::CTRL | Modifiers::ALT | Modifiers::SHIFT,
+            ),
+            Action::ReopenClosedTab,
+        );
         self.bind(
             Shortcut::new(KeyCode::Tab, Modifiers::CTRL),
             Action::NextTab,
@@ -88,16 +127,60 @@ impl ShortcutManager {
         self.bind(Shortcut::ctrl_shift(KeyCode::N), Action::NewWindow);
         self.bind(Shortcut::ctrl_shift(KeyCode::Q), Action::CloseWindow);
 
+        // Window snapping (the Ctrl+Option keys of Rectangle on macOS)
+        #[cfg(target_os = "macos")]
+        {
+            let ctrl_alt = Modifiers::CTRL | Modifiers::ALT;
+            self.bind(Shortcut::new(KeyCode::Left, ctrl_alt), Action::SnapLeft);
+            self.bind(Shortcut::new(KeyCode::Right, ctrl_alt), Action::SnapRight);
+            self.bind(
+                Shortcut::new(KeyCode::Enter, ctrl_alt),
+                Action::SnapMaximize,
+            );
+        }
+
+        // Pane shortcuts
+        let ctrl_alt_shift = Modifiers::CTRL | Modifiers::ALT | Modifiers::SHIFT;
+        self.bind(Shortcut::ctrl_shift(KeyCode::D), Action::SplitRight);
+        self.bind(Shortcut::new(KeyCode::D, ctrl_alt_shift), Action::SplitDown);
+        self.bind(Shortcut::new(KeyCode::W, ctrl_alt_shift), Action::ClosePane);
+        for (key, direction) in [
+            (KeyCode::Left, PaneDirection::Left),
+            (KeyCode::Right, PaneDirection::Right),
+            (KeyCode::Up, PaneDirection::Up),
+            (KeyCode::Down, PaneDirection::Down),
+        ] {
+            self.bind(Shortcut::ctrl_shift(key), Action::FocusPane(direction));
+            self.bind(
+                Shortcut::new(key, ctrl_alt_shift),
+                Action::ResizePane(direction),
+            );
+        }
+
         // Edit shortcuts
         self.bind(Shortcut::ctrl_shift(KeyCode::C), Action::Copy);
         self.bind(Shortcut::ctrl_shift(KeyCode::V), Action::Paste);
+        self.bind(
+            Shortcut::new(KeyCode::V, ctrl_alt_shift),
+            Action::PasteHistory,
+        );
         self.bind(Shortcut::ctrl_shift(KeyCode::A), Action::SelectAll);
+        self.bind(
+            Shortcut::ctrl_shift(KeyCode::O),
+            Action::SelectCommandOutput,
+        );
 
         // Zoom shortcuts
         self.bind(Shortcut::ctrl(KeyCode::Equals), Action::ZoomIn);
         self.bind(Shortcut::ctrl(KeyCode::Minus), Action::ZoomOut);
         self.bind(Shortcut::ctrl(KeyCode::Key0), Action::ZoomReset);
 
+        // Magnify shortcuts (scale the view, keep the grid size)
+        let ctrl_alt = Modifiers::CTRL | Modifiers::ALT;
+        self.bind(Shortcut::new(KeyCode::Equals, ctrl_alt), Action::MagnifyIn);
+        self.bind(Shortcut::new(KeyCode::Minus, ctrl_alt), Action::MagnifyOut);
+        self.bind(Shortcut::new(KeyCode::Key0, ctrl_alt), Action::MagnifyReset);
+
         // Scroll shortcuts
         self.bind(
             Shortcut::new(KeyCode::PageUp, Modifiers::SHIFT),
@@ -109,10 +192,21 @@ impl ShortcutManager {
         );
         self.bind(Shortcut::ctrl_shift(KeyCode::Home), Action::ScrollToTop);
         self.bind(Shortcut::ctrl_shift(KeyCode::End), Action::ScrollToBottom);
+        self.bind(
+            Shortcut::ctrl_shift(KeyCode::PageUp),
+            Action::PreviousPrompt,
+        );
+        self.bind(Shortcut::ctrl_shift(KeyCode::PageDown), Action::NextPrompt);
 
         // Other shortcuts
         self.bind(Shortcut::ctrl(KeyCode::Comma), Action::OpenPreferences);
         self.bind(Shortcut::ctrl_shift(KeyCode::F), Action::FindText);
+        self.bind(
+            Shortcut::ctrl_shift(KeyCode::P),
+            Action::TogglePresentationMode,
+        );
+        self.bind(Shortcut::ctrl_shift(KeyCode::E), Action::HintMode);
+        self.bind(Shortcut::ctrl_shift(KeyCode::X), Action::ExtractMatches);
 
         // Quick open (Cmd+G on macOS, Ctrl+Shift+G on Linux/Windows)
         #[cfg(target_os = "macos")]
@@ -432,4 +526,101 @@ mod tests {
         let action = manager.match_event(KeyCode::T, Modifiers::CTRL | Modifiers::SHIFT);
         assert_eq!(action, Some(&Action::NewTab));
     }
+
+    #[test]
+    fn test_pane_shortcuts() {
+        let manager = ShortcutManager::from_config(&ShortcutsConfig::default());
+        let ctrl_alt_shift = Modifiers::CTRL | Modifiers::ALT | Modifiers::SHIFT;
+        assert_eq!(
+            manager.match_event(KeyCode::D, Modifiers::CTRL | Modifiers::SHIFT),
+            Some(&Action::SplitRight)
+        );
+        assert_eq!(
+            manager.match_event(KeyCode::Left, Modifiers::CTRL | Modifiers::SHIFT),
+            Some(&Action::FocusPane(PaneDirection::Left))
+        );
+        assert_eq!(
+            manager.match_event(KeyCode::Down, ctrl_alt_shift),
+            Some(&Action::ResizePane(PaneDirection::Down))
+        );
+        // Ctrl+Shift+W still closes the tab
+        assert_eq!(
+            manager.match_event(KeyCode::W, Modifiers::CTRL | Modifiers::SHIFT),
+            Some(&Action::CloseTab)
+        );
+    }
+
+    #[test]
+    fn test_paste_history_shortcut() {
+        let manager = ShortcutManager::from_config(&ShortcutsConfig::default());
+        assert_eq!(
+            manager.match_event(KeyCode::V, Modifiers::CTRL | Modifiers::SHIFT),
+            Some(&Action::Paste)
+        );
+        assert_eq!(
+            manager.match_event(
+                KeyCode::V,
+                Modifiers::CTRL | Modifiers::ALT | Modifiers::SHIFT
+            ),
+            Some(&Action::PasteHistory)
+        );
+    }
+
+    #[test]
+    fn test_ascii_art_safe_shortcut() {
+        // Unbound by default
+        let manager = ShortcutManager::from_config(&ShortcutsConfig::default());
+        assert_eq!(manager.get_shortcut(&Action::ToggleAsciiArtSafe), None);
+
+        let config = ShortcutsConfig {
+            ascii_art_safe: "Ctrl+Shift+L".into(),
+            ..Default::default()
+        };
+        let manager = ShortcutManager::from_config(&config);
+        let action = manager.match_event(KeyCode::L, Modifiers::CTRL | Modifiers::SHIFT);
+        assert_eq!(action, Some(&Action::ToggleAsciiArtSafe));
+    }
+
+    #[test]
+    fn test_snap_shortcuts() {
+        let manager = ShortcutManager::from_config(&ShortcutsConfig::default());
+        let ctrl_alt = Modifiers::CTRL | Modifiers::ALT;
+        let snap_left = manager.match_event(KeyCode::Left, ctrl_alt);
+        if cfg!(target_os = "macos") {
+            assert_eq!(snap_left, Some(&Action::SnapLeft));
+            assert_eq!(
+                manager.match_event(KeyCode::Enter, ctrl_alt),
+                Some(&Action::SnapMaximize)
+            );
+        } else {
+            assert_eq!(snap_left, None);
+        }
+
+        let config = ShortcutsConfig {
+            snap_right: "Ctrl+Alt+L".into(),
+            ..Default::default()
+        };
+        let manager = ShortcutManager::from_config(&config);
+        assert_eq!(
+            manager.match_event(KeyCode::L, ctrl_alt),
+            Some(&Action::SnapRight)
+        );
+    }
+
+    #[test]
+    fn test_zoom_and_magnify_are_distinct() {
+        let manager = ShortcutManager::from_config(&ShortcutsConfig::default());
+        assert_eq!(
+            manager.match_event(KeyCode::Equals, Modifiers::CTRL),
+            Some(&Action::ZoomIn)
+        );
+        assert_eq!(
+            manager.match_event(KeyCode::Equals, Modifiers::CTRL | Modifiers::ALT),
+            Some(&Action::MagnifyIn)
+        );
+        assert_eq!(
+            manager.match_event(KeyCode::Key0, Modifiers::CTRL | Modifiers::ALT),
+            Some(&Action::MagnifyReset)
+        );
+    }
 }
@@ -0,0 +1,228 @@
+//! Audio cues: sounds or speech for the bell and finished commands
+//!
+//! `[sounds]` ([`SoundsConfig`]) names a cue for each event. The frontend
+//! asks for the cue of a bell or of a command `[command_notify]` reports,
+//! plays [`Cue::Beep`] with the platform's alert sound, and hands the other
+//! cues to [`play`], which runs the platform's player or speech synthesizer
+//! in the background.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cterm_core::screen::CommandFinished;
+
+use crate::config::SoundsConfig;
+use crate::triggers::run_command;
+
+/// What to play for an event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cue {
+    /// The system alert sound
+    Beep,
+    /// Speak the text
+    Speak(String),
+    /// Play a sound file
+    File(PathBuf),
+}
+
+impl Cue {
+    /// Parse a cue from its configuration: empty for none, `beep`,
+    /// `say:<text>` or a file path (`~` is expanded)
+    pub fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() || spec.eq_ignore_ascii_case("none") {
+            return None;
+        }
+        if spec.eq_ignore_ascii_case("beep") {
+            return Some(Self::Beep);
+        }
+        if let Some(text) = spec.strip_prefix("say:") {
+            return Some(Self::Speak(text.trim().to_string()));
+        }
+        let path = match spec.strip_prefix("~/") {
+            Some(rest) => {
+                match std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+                    Some(home) => PathBuf::from(home).join(rest),
+                    None => PathBuf::from(spec),
+                }
+            }
+            None => PathBuf::from(spec),
+        };
+        Some(Self::File(path))
+    }
+}
+
+impl SoundsConfig {
+    /// Whether cues play now, given whether the window has focus
+    pub fn active(&self, focused: bool) -> bool {
+        !(self.only_when_unfocused && focused)
+    }
+
+    /// Cue for the terminal bell
+    pub fn bell_cue(&self) -> Option<Cue> {
+        Cue::parse(&self.bell)
+    }
+
+    /// Cue for a finished command, with `{duration}` and `{exit}` in spoken
+    /// text replaced by its run time and exit status
+    pub fn command_cue(&self, finished: &CommandFinished) -> Option<Cue> {
+        let failed = finished.exit_code.is_some_and(|code| code != 0);
+        let spec = if failed && !self.command_failed.trim().is_empty() {
+            &self.command_failed
+        } else {
+            &self.command_finished
+        };
+        match Cue::parse(spec)? {
+            Cue::Speak(text) => {
+                let exit = finished
+                    .exit_code
+                    .map(|code| code.to_string())
+                    .unwrap_or_else(|| "unknown".into());
+                Some(Cue::Speak(
+                    text.replace("{duration}", &spoken_duration(finished.duration))
+                        .replace("{exit}", &exit),
+                ))
+            }
+            cue => Some(cue),
+        }
+    }
+}
+
+/// A duration as it reads aloud: "45 seconds", "2 minutes 5 seconds",
+/// "1 hour 2 minutes"
+fn spoken_duration(duration: Duration) -> String {
+    fn unit(n: u64, name: &str) -> String {
+        if n == 1 {
+            format!("1 {}", name)
+        } else {
+            format!("{} {}s", n, name)
+        }
+    }
+    let secs = duration.as_secs();
+    let (major, minor) = if secs < 60 {
+        return unit(secs, "second");
+    } else if secs < 3600 {
+        (unit(secs / 60, "minute"), unit(secs % 60, "second"))
+    } else {
+        (unit(secs / 3600, "hour"), unit(secs % 3600 / 60, "minute"))
+    };
+    if minor.starts_with("0 ") {
+        major
+    } else {
+        format!("{} {}", major, minor)
+    }
+}
+
+/// Program and arguments playing `cue` on this platform; `None` for
+/// [`Cue::Beep`], which the frontend plays itself
+fn player(cue: &Cue) -> Option<(String, Vec<String>)> {
+    let (program, args) = match cue {
+        Cue::Beep => return None,
+        Cue::Speak(text) if cfg!(target_os = "macos") => ("say", vec![text.clone()]),
+        Cue::File(path) if cfg!(target_os = "macos") => {
+            ("afplay", vec![path.to_string_lossy().into_owned()])
+        }
+        Cue::Speak(text) if cfg!(windows) => (
+            "powershell",
+            powershell(&format!(
+                "Add-Type -AssemblyName System.Speech; \
+                 (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak('{}')",
+                text.replace('\'', "''")
+            )),
+        ),
+        Cue::File(path) if cfg!(windows) => (
+            "powershell",
+            powershell(&format!(
+                "(New-Object Media.SoundPlayer '{}').PlaySync()",
+                path.to_string_lossy().replace('\'', "''")
+            )),
+        ),
+        Cue::Speak(text) => ("spd-say", vec![text.clone()]),
+        Cue::File(path) => ("paplay", vec![path.to_string_lossy().into_owned()]),
+    };
+    Some((program.to_string(), args))
+}
+
+fn powershell(script: &str) -> Vec<String> {
+    vec![
+        "-NoProfile".into(),
+        "-NonInteractive".into(),
+        "-Command".into(),
+        script.into(),
+    ]
+}
+
+/// Play a sound file or speak text in the background
+///
+/// Does nothing for [`Cue::Beep`].
+pub fn play(cue: &Cue) {
+    let Some((program, args)) = player(cue) else {
+        return;
+    };
+    if let Err(e) = run_command(&program, &args) {
+        log::warn!("Failed to play {:?} with {}: {}", cue, program, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cue_parse() {
+        assert_eq!(Cue::parse(""), None);
+        assert_eq!(Cue::parse("none"), None);
+        assert_eq!(Cue::parse("beep"), Some(Cue::Beep));
+        assert_eq!(
+            Cue::parse("say: Build done"),
+            Some(Cue::Speak("Build done".into()))
+        );
+        assert_eq!(
+            Cue::parse("/usr/share/sounds/done.oga"),
+            Some(Cue::File(PathBuf::from("/usr/share/sounds/done.oga")))
+        );
+        assert!(player(&Cue::Beep).is_none());
+        assert!(player(&Cue::Speak("hi".into())).is_some());
+    }
+
+    #[test]
+    fn test_command_cues() {
+        let sounds = SoundsConfig {
+            command_finished: "say:Done in {duration}".into(),
+            command_failed: "say:Failed with exit code {exit}".into(),
+            ..Default::default()
+        };
+        let finished = |secs, exit_code| CommandFinished {
+            duration: Duration::from_secs(secs),
+            exit_code,
+        };
+        assert_eq!(
+            sounds.command_cue(&finished(125, Some(0))),
+            Some(Cue::Speak("Done in 2 minutes 5 seconds".into()))
+        );
+        assert_eq!(
+            sounds.command_cue(&finished(1, None)),
+            Some(Cue::Speak("Done in 1 second".into()))
+        );
+        assert_eq!(
+            sounds.command_cue(&finished(3600, Some(2))),
+            Some(Cue::Speak("Failed with exit code 2".into()))
+        );
+
+        // Failures fall back to the finished cue
+        let sounds = SoundsConfig {
+            command_finished: "beep".into(),
+            ..Default::default()
+        };
+        assert_eq!(sounds.command_cue(&finished(5, Some(1))), Some(Cue::Beep));
+        assert_eq!(sounds.bell_cue(), None);
+
+        assert!(sounds.active(true));
+        let background = SoundsConfig {
+            only_when_unfocused: true,
+            ..sounds
+        };
+        assert!(!background.active(true));
+        assert!(background.active(false));
+    }
+}
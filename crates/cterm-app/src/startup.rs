@@ -0,0 +1,99 @@
+//! Startup commands for shells the frontend runs itself
+//!
+//! ctermd types the `startup_commands` of its sessions; a frontend that
+//! starts a shell on a local PTY does the same with [`StartupCommands`]. It
+//! tells the queue when output arrives and polls it from a timer, and the
+//! queue hands out each command once the shell has printed something (its
+//! prompt, or the output of the previous command) and gone quiet for
+//! [`STARTUP_IDLE`], or after [`STARTUP_TIMEOUT`] without that, as the daemon
+//! does.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long the shell must stay quiet before the next command is typed
+pub const STARTUP_IDLE: Duration = Duration::from_millis(300);
+/// Longest wait for the shell before typing the next command anyway
+pub const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often frontends poll [`StartupCommands`]
+pub const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Commands waiting to be typed into a new shell
+#[derive(Debug, Clone)]
+pub struct StartupCommands {
+    commands: VecDeque<String>,
+    /// When the wait for the next command started
+    waiting_since: Instant,
+    /// Latest output during that wait
+    last_output: Option<Instant>,
+}
+
+impl StartupCommands {
+    /// Queue `commands` for a shell started at `now`, or `None` if there are
+    /// none
+    pub fn new(commands: &[String], now: Instant) -> Option<Self> {
+        (!commands.is_empty()).then(|| Self {
+            commands: commands.iter().cloned().collect(),
+            waiting_since: now,
+            last_output: None,
+        })
+    }
+
+    /// Note that the shell printed something at `now`
+    pub fn output(&mut self, now: Instant) {
+        self.last_output = Some(now);
+    }
+
+    /// The next command line to write to the PTY, with its carriage return,
+    /// if the shell is ready for it at `now`
+    pub fn poll(&mut self, now: Instant) -> Option<String> {
+        let quiet = self
+            .last_output
+            .is_some_and(|last| now.duration_since(last) >= STARTUP_IDLE);
+        let timed_out = now.duration_since(self.waiting_since) >= STARTUP_TIMEOUT;
+        if !quiet && !timed_out {
+            return None;
+        }
+        let command = self.commands.pop_front()?;
+        self.waiting_since = now;
+        self.last_output = None;
+        Some(format!("{}\r", command))
+    }
+
+    /// Whether every command has been handed out
+    pub fn is_done(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_startup_commands() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        assert!(StartupCommands::new(&[], start).is_none());
+
+        let commands = ["ssh host".to_string(), "cd /srv".to_string()];
+        let mut startup = StartupCommands::new(&commands, start).unwrap();
+
+        // Nothing until the shell prints its prompt and goes quiet
+        assert_eq!(startup.poll(at(500)), None);
+        startup.output(at(600));
+        assert_eq!(startup.poll(at(800)), None);
+        startup.output(at(850));
+        assert_eq!(startup.poll(at(1100)), None);
+        assert_eq!(startup.poll(at(1150)).as_deref(), Some("ssh host\r"));
+        assert!(!startup.is_done());
+
+        // Output before the previous command doesn't count; a silent shell
+        // gets the command once the wait times out
+        assert_eq!(startup.poll(at(1500)), None);
+        assert_eq!(startup.poll(at(11_149)), None);
+        assert_eq!(startup.poll(at(11_150)).as_deref(), Some("cd /srv\r"));
+        assert!(startup.is_done());
+        assert_eq!(startup.poll(at(20_000)), None);
+    }
+}
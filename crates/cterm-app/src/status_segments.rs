@@ -0,0 +1,255 @@
+//! Status segments: command output in tab titles
+//!
+//! Each `[[status_segments]]` entry runs a shell command, such as
+//! `git branch --show-current` or `kubectl config current-context`, and its
+//! first line of output fills the `{name}` placeholder of
+//! `tabs.title_format`. Frontends call [`values`] for each tab every
+//! [`REFRESH_INTERVAL`] and show [`format_title`]; after shell integration
+//! reports a finished command they call [`command_finished`].
+//!
+//! Commands run on a background thread, so [`values`] returns at once with
+//! the last text and the new one shows on a later refresh. Texts are shared
+//! by every tab of the process in the same directory.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::StatusSegmentConfig;
+
+/// How often frontends refresh the titles
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Texts by command and directory (none unless `per_directory`)
+static CACHE: Mutex<Option<HashMap<CacheKey, Entry>>> = Mutex::new(None);
+
+type CacheKey = (String, Option<PathBuf>);
+
+/// A command's last text and when to run it again
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Entry {
+    text: String,
+    /// When the last run finished
+    ran: Option<Instant>,
+    /// A run is in progress
+    running: bool,
+    /// Run again on the next refresh
+    stale: bool,
+}
+
+impl Entry {
+    /// Whether to start a run, with runs `interval` apart (never again
+    /// when `None`)
+    fn due(&self, interval: Option<Duration>, now: Instant) -> bool {
+        if self.running {
+            return false;
+        }
+        match (self.ran, interval) {
+            (None, _) => true,
+            _ if self.stale => true,
+            (Some(ran), Some(interval)) => now >= ran + interval,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// The current text of each segment for a tab in `dir`, by name, starting
+/// the commands that are due
+pub fn values(segments: &[StatusSegmentConfig], dir: Option<&Path>) -> Vec<(String, String)> {
+    if segments.is_empty() {
+        return Vec::new();
+    }
+    let now = Instant::now();
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = cache.get_or_insert_with(HashMap::new);
+    segments
+        .iter()
+        .map(|segment| {
+            let dir = dir.filter(|_| segment.per_directory);
+            let key = (segment.command.clone(), dir.map(Path::to_path_buf));
+            let entry = cache.entry(key.clone()).or_default();
+            let interval = (segment.interval > 0).then(|| Duration::from_secs(segment.interval));
+            if entry.due(interval, now) {
+                entry.running = true;
+                entry.stale = false;
+                std::thread::spawn(move || {
+                    let text = run(&key.0, key.1.as_deref());
+                    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+                    if let Some(entry) = cache.get_or_insert_with(HashMap::new).get_mut(&key) {
+                        entry.text = text;
+                        entry.ran = Some(Instant::now());
+                        entry.running = false;
+                    }
+                });
+            }
+            (
+                segment.name.clone(),
+                truncate(&entry.text, segment.max_width),
+            )
+        })
+        .collect()
+}
+
+/// A command finished in some tab: run the segments that ask for it again
+pub fn command_finished(segments: &[StatusSegmentConfig]) {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(cache) = cache.as_mut() else {
+        return;
+    };
+    for segment in segments.iter().filter(|s| s.refresh_after_command) {
+        for ((command, _), entry) in cache.iter_mut() {
+            if *command == segment.command {
+                entry.stale = true;
+            }
+        }
+    }
+}
+
+/// Fill `format` with `title` for `{title}` and the segment texts for their
+/// names; other placeholders are left as they are
+///
+/// Whitespace around empty texts collapses, so a segment with nothing to
+/// show leaves no gap. An empty result falls back to `title`.
+pub fn format_title(format: &str, title: &str, values: &[(String, String)]) -> String {
+    let mut out = String::with_capacity(format.len() + title.len());
+    let mut rest = format;
+    let mut blank = false;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let name = &rest[start + 1..start + len];
+        let value = match name {
+            "title" => Some(title),
+            _ => values
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, text)| text.as_str()),
+        };
+        match value {
+            Some(value) => {
+                blank |= value.is_empty();
+                out.push_str(value);
+            }
+            None => out.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    if blank {
+        out = out.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+    if out.is_empty() {
+        title.to_string()
+    } else {
+        out
+    }
+}
+
+/// First non-empty line of `command`'s output, run by the shell in `dir`
+fn run(command: &str, dir: Option<&Path>) -> String {
+    #[cfg(unix)]
+    let mut cmd = {
+        let mut cmd = Command::new("/bin/sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    };
+    if let Some(dir) = dir.filter(|dir| dir.is_dir()) {
+        cmd.current_dir(dir);
+    }
+    match cmd.stdin(Stdio::null()).stderr(Stdio::null()).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default()
+            .to_string(),
+        Err(e) => {
+            log::debug!("Status segment command '{}' failed: {}", command, e);
+            String::new()
+        }
+    }
+}
+
+/// `text` cut to `max` characters, ending in "…" if cut
+fn truncate(text: &str, max: usize) -> String {
+    if max == 0 || text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max - 1).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(name, text)| (name.to_string(), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_format_title() {
+        let segments = texts(&[("branch", "main"), ("k8s", "")]);
+        assert_eq!(format_title("{title}", "vim", &segments), "vim");
+        assert_eq!(
+            format_title("{title} [{branch}]", "vim", &segments),
+            "vim [main]"
+        );
+        assert_eq!(format_title("{k8s}  {title}", "vim", &segments), "vim");
+        assert_eq!(
+            format_title("{title} {nope} {", "vim", &segments),
+            "vim {nope} {"
+        );
+        assert_eq!(format_title("{k8s}", "vim", &segments), "vim");
+    }
+
+    #[test]
+    fn test_entry_due() {
+        let now = Instant::now();
+        let interval = Some(Duration::from_secs(30));
+        let mut entry = Entry::default();
+        assert!(entry.due(interval, now));
+
+        entry.running = true;
+        assert!(!entry.due(interval, now));
+
+        entry.running = false;
+        entry.ran = Some(now);
+        assert!(!entry.due(interval, now + Duration::from_secs(10)));
+        assert!(entry.due(interval, now + Duration::from_secs(30)));
+        assert!(!entry.due(None, now + Duration::from_secs(3600)));
+
+        entry.stale = true;
+        assert!(entry.due(None, now));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("main", 24), "main");
+        assert_eq!(truncate("feature/long-name", 8), "feature…");
+        assert_eq!(truncate("anything", 0), "anything");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        assert_eq!(run("printf '\\n  hello  \\nworld'", None), "hello");
+        assert_eq!(run("pwd", Some(Path::new("/"))), "/");
+        assert_eq!(run("exit 1", None), "");
+    }
+}
@@ -0,0 +1,335 @@
+//! Clock, timer and battery widgets at the end of the tab bar
+//!
+//! The `[widgets]` config section picks which widgets are shown. Frontends
+//! call [`widgets`] every [`REFRESH_INTERVAL`] and draw the returned texts
+//! after the tabs; clicking the timer calls [`toggle_timer`] and
+//! right-clicking it [`reset_timer`]. The timer belongs to the process, so
+//! every window shows the same countdown.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::WidgetsConfig;
+
+/// How often frontends refresh the widgets
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a battery reading is reused
+const BATTERY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Battery charge below which the widget asks for attention
+const LOW_BATTERY: u8 = 15;
+
+/// The countdown shared by all windows
+static TIMER: Mutex<Timer> = Mutex::new(Timer::Idle);
+
+/// Last battery reading and when it was taken
+static BATTERY: Mutex<Option<(Instant, Option<Battery>)>> = Mutex::new(None);
+
+/// What a widget shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetKind {
+    Clock,
+    Timer,
+    Battery,
+}
+
+/// A widget's current text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabWidget {
+    pub kind: WidgetKind,
+    pub text: String,
+    /// Whether the widget needs attention (timer done, battery low)
+    pub emphasis: bool,
+}
+
+/// Countdown state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Timer {
+    /// Not started: shows the full length
+    Idle,
+    Running {
+        until: Instant,
+    },
+    Paused {
+        left: Duration,
+    },
+    /// Reached zero, until clicked or reset
+    Done,
+}
+
+impl Timer {
+    /// Time left and whether the countdown is running, settling a running
+    /// timer that reached zero
+    fn left(&mut self, length: Duration, now: Instant) -> Option<(Duration, bool)> {
+        match *self {
+            Timer::Idle => Some((length, false)),
+            Timer::Running { until } if until <= now => {
+                *self = Timer::Done;
+                None
+            }
+            Timer::Running { until } => Some((until - now, true)),
+            Timer::Paused { left } => Some((left, false)),
+            Timer::Done => None,
+        }
+    }
+
+    /// Start, pause or resume
+    fn toggle(&mut self, length: Duration, now: Instant) {
+        *self = match self.left(length, now) {
+            Some((left, true)) => Timer::Paused { left },
+            Some((left, false)) => Timer::Running { until: now + left },
+            None => Timer::Running {
+                until: now + length,
+            },
+        };
+    }
+
+    fn widget(&mut self, length: Duration, now: Instant) -> TabWidget {
+        let (text, emphasis) = match self.left(length, now) {
+            Some((left, running)) => {
+                // Round up so the timer reads 25:00 when it starts
+                let secs = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+                let icon = if running || *self == Timer::Idle {
+                    '⏱'
+                } else {
+                    '⏸'
+                };
+                (format!("{} {:02}:{:02}", icon, secs / 60, secs % 60), false)
+            }
+            None => ("⏰ 00:00".to_string(), true),
+        };
+        TabWidget {
+            kind: WidgetKind::Timer,
+            text,
+            emphasis,
+        }
+    }
+}
+
+/// Battery charge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Battery {
+    percent: u8,
+    charging: bool,
+}
+
+impl Battery {
+    fn widget(self) -> TabWidget {
+        let icon = if self.charging { '⚡' } else { '🔋' };
+        TabWidget {
+            kind: WidgetKind::Battery,
+            text: format!("{} {}%", icon, self.percent),
+            emphasis: !self.charging && self.percent < LOW_BATTERY,
+        }
+    }
+}
+
+/// The enabled widgets, in display order
+pub fn widgets(config: &WidgetsConfig) -> Vec<TabWidget> {
+    let now = Instant::now();
+    let mut widgets = Vec::new();
+    if config.timer {
+        let mut timer = TIMER.lock().unwrap_or_else(|e| e.into_inner());
+        widgets.push(timer.widget(config.timer_length(), now));
+    }
+    if config.battery {
+        if let Some(battery) = battery(now) {
+            widgets.push(battery.widget());
+        }
+    }
+    if config.clock {
+        if let Some((hour, minute)) = local_time() {
+            widgets.push(TabWidget {
+                kind: WidgetKind::Clock,
+                text: format_clock(hour, minute, config.clock_12h),
+                emphasis: false,
+            });
+        }
+    }
+    widgets
+}
+
+/// Start, pause or resume the timer; a finished timer starts over
+pub fn toggle_timer(config: &WidgetsConfig) {
+    let mut timer = TIMER.lock().unwrap_or_else(|e| e.into_inner());
+    timer.toggle(config.timer_length(), Instant::now());
+}
+
+/// Stop the timer and put it back to its full length
+pub fn reset_timer() {
+    *TIMER.lock().unwrap_or_else(|e| e.into_inner()) = Timer::Idle;
+}
+
+/// `14:05`, or `2:05 PM` on a 12-hour clock
+fn format_clock(hour: u32, minute: u32, twelve_hour: bool) -> String {
+    if twelve_hour {
+        let suffix = if hour < 12 { "AM" } else { "PM" };
+        let hour = match hour % 12 {
+            0 => 12,
+            h => h,
+        };
+        format!("{}:{:02} {}", hour, minute, suffix)
+    } else {
+        format!("{:02}:{:02}", hour, minute)
+    }
+}
+
+/// Current local hour and minute
+#[cfg(unix)]
+fn local_time() -> Option<(u32, u32)> {
+    let time = unsafe { libc::time(std::ptr::null_mut()) };
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return None;
+    }
+    Some((tm.tm_hour as u32, tm.tm_min as u32))
+}
+
+#[cfg(windows)]
+fn local_time() -> Option<(u32, u32)> {
+    let mut time: winapi::um::minwinbase::SYSTEMTIME = unsafe { std::mem::zeroed() };
+    unsafe { winapi::um::sysinfoapi::GetLocalTime(&mut time) };
+    Some((time.wHour as u32, time.wMinute as u32))
+}
+
+/// Battery reading, refreshed every [`BATTERY_INTERVAL`]
+fn battery(now: Instant) -> Option<Battery> {
+    let mut cached = BATTERY.lock().unwrap_or_else(|e| e.into_inner());
+    match *cached {
+        Some((taken, battery)) if now.duration_since(taken) < BATTERY_INTERVAL => battery,
+        _ => {
+            let battery = read_battery();
+            *cached = Some((now, battery));
+            battery
+        }
+    }
+}
+
+/// First battery under /sys/class/power_supply
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_battery() -> Option<Battery> {
+    let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    supplies.flatten().find_map(|supply| {
+        let path = supply.path();
+        let read = |name: &str| std::fs::read_to_string(path.join(name)).ok();
+        if read("type")?.trim() != "Battery" {
+            return None;
+        }
+        let percent = read("capacity")?.trim().parse::<u8>().ok()?;
+        let status = read("status").unwrap_or_default();
+        Some(Battery {
+            percent: percent.min(100),
+            charging: matches!(status.trim(), "Charging" | "Full"),
+        })
+    })
+}
+
+/// Battery from `pmset -g batt`
+#[cfg(target_os = "macos")]
+fn read_battery() -> Option<Battery> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    parse_pmset(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse `pmset -g batt`, whose first line names the power source and whose
+/// battery lines read `-InternalBattery-0 (id=...) 85%; charging; ...`
+#[cfg(any(target_os = "macos", test))]
+fn parse_pmset(output: &str) -> Option<Battery> {
+    let mut lines = output.lines();
+    let on_ac = lines.next()?.contains("AC Power");
+    let line = lines.find(|line| line.contains("InternalBattery"))?;
+    let end = line.find('%')?;
+    let start = line[..end]
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    let percent = line[start..end].parse::<u8>().ok()?;
+    Some(Battery {
+        percent: percent.min(100),
+        charging: on_ac,
+    })
+}
+
+#[cfg(windows)]
+fn read_battery() -> Option<Battery> {
+    use winapi::um::winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    /// `BatteryFlag` bit meaning there is no battery
+    const NO_BATTERY: u8 = 128;
+    /// `BatteryFlag` and `BatteryLifePercent` value meaning unknown
+    const UNKNOWN: u8 = 255;
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0
+        || status.BatteryFlag == UNKNOWN
+        || status.BatteryFlag & NO_BATTERY != 0
+        || status.BatteryLifePercent == UNKNOWN
+    {
+        return None;
+    }
+    Some(Battery {
+        percent: status.BatteryLifePercent.min(100),
+        charging: status.ACLineStatus == 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer() {
+        let length = Duration::from_secs(25 * 60);
+        let start = Instant::now();
+        let mut timer = Timer::Idle;
+        assert_eq!(timer.widget(length, start).text, "⏱ 25:00");
+
+        timer.toggle(length, start);
+        let later = start + Duration::from_secs(90);
+        assert_eq!(timer.widget(length, later).text, "⏱ 23:30");
+
+        timer.toggle(length, later);
+        let much_later = later + Duration::from_secs(3600);
+        assert_eq!(timer.widget(length, much_later).text, "⏸ 23:30");
+
+        timer.toggle(length, much_later);
+        let done = timer.widget(length, much_later + length);
+        assert!(done.emphasis);
+        assert_eq!(timer, Timer::Done);
+
+        // Clicking a finished timer starts it over
+        timer.toggle(length, much_later + length);
+        assert_eq!(timer.widget(length, much_later + length).text, "⏱ 25:00");
+    }
+
+    #[test]
+    fn test_format_clock() {
+        assert_eq!(format_clock(14, 5, false), "14:05");
+        assert_eq!(format_clock(7, 30, false), "07:30");
+        assert_eq!(format_clock(14, 5, true), "2:05 PM");
+        assert_eq!(format_clock(0, 0, true), "12:00 AM");
+        assert_eq!(format_clock(12, 15, true), "12:15 PM");
+    }
+
+    #[test]
+    fn test_parse_pmset() {
+        let output = "Now drawing from 'Battery Power'\n \
+             -InternalBattery-0 (id=4653155)\t9%; discharging; 0:31 remaining present: true\n";
+        let battery = parse_pmset(output).unwrap();
+        assert_eq!(battery.percent, 9);
+        assert!(!battery.charging);
+        assert!(battery.widget().emphasis);
+
+        let output = "Now drawing from 'AC Power'\n \
+             -InternalBattery-0 (id=4653155)\t100%; charged; 0:00 remaining present: true\n";
+        assert_eq!(
+            parse_pmset(output).map(Battery::widget).unwrap().text,
+            "⚡ 100%"
+        );
+        // Desktops have no battery line
+        assert_eq!(parse_pmset("Now drawing from 'AC Power'\n"), None);
+    }
+}
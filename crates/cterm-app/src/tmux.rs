@@ -0,0 +1,732 @@
+//! tmux control mode client
+//!
+//! Running `tmux -CC` (or `tmux -CC attach`) in a tab switches tmux to
+//! control mode: instead of drawing its own screen it speaks a line protocol,
+//! which cterm-core hands over as [`TerminalEvent::TmuxControl`]. A
+//! [`TmuxGateway`] answers it through that tab, the gateway: it lists the
+//! session's panes, and the frontend opens each one as a native tab fed with
+//! the pane's `%output`. Keys typed in a pane's tab go back with `send-keys`,
+//! and the size of the window with `refresh-client -C`. cterm has no split
+//! panes, so each pane of a tmux window gets a tab of its own.
+//!
+//! [`TerminalEvent::TmuxControl`]: cterm_core::term::TerminalEvent::TmuxControl
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use cterm_core::pty::PtyError;
+use cterm_core::screen::TmuxControl;
+use cterm_core::term::WriteFn;
+use parking_lot::Mutex;
+
+/// tmux pane number (`%N`)
+pub type PaneId = u32;
+/// tmux window number (`@N`)
+pub type WindowId = u32;
+
+/// Writes commands to the gateway tab's terminal
+pub type GatewayWriteFn = Box<dyn Fn(&[u8]) + Send + Sync>;
+
+/// Keys sent with one `send-keys` command at most
+const SEND_KEYS_CHUNK: usize = 256;
+
+/// Pane fields asked for by `list-panes`, window name last as it may have
+/// spaces
+const LIST_PANES_FORMAT: &str = "#{window_id} #{pane_id} #{pane_width} #{pane_height} \
+                                 #{cursor_x} #{cursor_y} #{window_name}";
+
+/// What the frontend has to do for tmux
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TmuxEvent {
+    /// Open a tab for a pane of `cols` x `rows` cells
+    PaneOpened {
+        pane: PaneId,
+        title: String,
+        cols: usize,
+        rows: usize,
+    },
+    /// tmux resized a pane
+    PaneResized {
+        pane: PaneId,
+        cols: usize,
+        rows: usize,
+    },
+    /// Output to process in a pane's terminal
+    PaneOutput { pane: PaneId, data: Vec<u8> },
+    /// The pane's window was renamed
+    PaneTitle { pane: PaneId, title: String },
+    /// Close a pane's tab
+    PaneClosed { pane: PaneId },
+    /// tmux left control mode: close every pane's tab
+    Exit { reason: Option<String> },
+}
+
+/// What a command sent to tmux is answered with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reply {
+    /// Nothing of interest
+    Ignore,
+    /// Panes of the whole session, or of one new window
+    ListPanes { session: bool },
+    /// The visible contents of a pane
+    Capture(PaneId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pane {
+    window: WindowId,
+    cols: usize,
+    rows: usize,
+    cursor: (usize, usize),
+}
+
+/// The control protocol state for one gateway
+#[derive(Debug, Default)]
+pub struct TmuxClient {
+    /// Commands sent and not answered yet, oldest first
+    pending: VecDeque<Reply>,
+    /// Lines of the reply block being read
+    block: Option<Vec<Vec<u8>>>,
+    /// Whether the reply to the command that started control mode has been
+    /// skipped
+    started: bool,
+    window_names: HashMap<WindowId, String>,
+    panes: HashMap<PaneId, Pane>,
+    /// Client size last sent with `refresh-client -C`
+    size: Option<(usize, usize)>,
+    /// Commands to write to the gateway
+    output: Vec<u8>,
+    exited: bool,
+}
+
+impl TmuxClient {
+    /// Start a client, asking for the session's panes
+    pub fn new() -> Self {
+        let mut client = Self::default();
+        client.list_panes(None);
+        client
+    }
+
+    /// Commands to write to the gateway since the last call
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Whether tmux left control mode
+    pub fn is_exited(&self) -> bool {
+        self.exited
+    }
+
+    /// Send `data` typed in a pane's tab to the pane
+    pub fn send_keys(&mut self, pane: PaneId, data: &[u8]) {
+        for chunk in data.chunks(SEND_KEYS_CHUNK) {
+            let mut command = format!("send-keys -t %{} -H", pane);
+            for byte in chunk {
+                command.push_str(&format!(" {:02x}", byte));
+            }
+            self.command(&command, Reply::Ignore);
+        }
+    }
+
+    /// Tell tmux the size of the area tabs have, unless it already has it
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        if self.size == Some((cols, rows)) || cols == 0 || rows == 0 {
+            return;
+        }
+        self.size = Some((cols, rows));
+        self.command(
+            &format!("refresh-client -C {},{}", cols, rows),
+            Reply::Ignore,
+        );
+    }
+
+    /// Detach from tmux, leaving the session running
+    pub fn detach(&mut self) {
+        self.command("detach-client", Reply::Ignore);
+    }
+
+    /// Handle one line of the control protocol
+    pub fn handle_line(&mut self, line: &[u8]) -> Vec<TmuxEvent> {
+        if let Some(ref mut block) = self.block {
+            let error = line.starts_with(b"%error ");
+            if !error && !line.starts_with(b"%end ") {
+                block.push(line.to_vec());
+                return Vec::new();
+            }
+            let lines = self.block.take().unwrap_or_default();
+            if !std::mem::replace(&mut self.started, true) {
+                return Vec::new();
+            }
+            let reply = self.pending.pop_front().unwrap_or(Reply::Ignore);
+            return self.handle_reply(reply, &lines, error);
+        }
+
+        if line.starts_with(b"%begin ") {
+            self.block = Some(Vec::new());
+            return Vec::new();
+        }
+        if let Some(rest) = line.strip_prefix(b"%output ") {
+            return self.handle_output(rest).into_iter().collect();
+        }
+
+        let line = String::from_utf8_lossy(line);
+        let (name, args) = line.split_once(' ').unwrap_or((&line, ""));
+        match name {
+            "%window-add" => {
+                if let Some(window) = parse_id(args, '@') {
+                    self.list_panes(Some(window));
+                }
+                Vec::new()
+            }
+            "%window-close" | "%unlinked-window-close" => parse_id(args, '@')
+                .map(|window| self.close_window(window))
+                .unwrap_or_default(),
+            "%window-renamed" => {
+                let Some((window, name)) = args.split_once(' ') else {
+                    return Vec::new();
+                };
+                let Some(window) = parse_id(window, '@') else {
+                    return Vec::new();
+                };
+                self.window_names.insert(window, name.to_string());
+                self.window_panes(window)
+                    .into_iter()
+                    .map(|pane| TmuxEvent::PaneTitle {
+                        pane,
+                        title: name.to_string(),
+                    })
+                    .collect()
+            }
+            "%layout-change" => {
+                let mut args = args.split(' ');
+                match (args.next().and_then(|w| parse_id(w, '@')), args.next()) {
+                    (Some(window), Some(layout)) => self.apply_layout(window, layout),
+                    _ => Vec::new(),
+                }
+            }
+            "%session-changed" => {
+                self.list_panes(None);
+                Vec::new()
+            }
+            "%exit" => {
+                self.exited = true;
+                let reason = Some(args.trim()).filter(|r| !r.is_empty());
+                vec![TmuxEvent::Exit {
+                    reason: reason.map(str::to_string),
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn command(&mut self, command: &str, reply: Reply) {
+        self.output.extend_from_slice(command.as_bytes());
+        self.output.push(b'\n');
+        self.pending.push_back(reply);
+    }
+
+    /// Ask for the panes of `window`, or of the whole session
+    fn list_panes(&mut self, window: Option<WindowId>) {
+        let target = match window {
+            Some(window) => format!("-t @{}", window),
+            None => "-s".to_string(),
+        };
+        self.command(
+            &format!("list-panes {} -F \"{}\"", target, LIST_PANES_FORMAT),
+            Reply::ListPanes {
+                session: window.is_none(),
+            },
+        );
+    }
+
+    fn handle_output(&mut self, rest: &[u8]) -> Option<TmuxEvent> {
+        let space = rest.iter().position(|&b| b == b' ')?;
+        let pane = parse_id(std::str::from_utf8(&rest[..space]).ok()?, '%')?;
+        if !self.panes.contains_key(&pane) {
+            return None;
+        }
+        Some(TmuxEvent::PaneOutput {
+            pane,
+            data: unescape_output(&rest[space + 1..]),
+        })
+    }
+
+    fn handle_reply(&mut self, reply: Reply, lines: &[Vec<u8>], error: bool) -> Vec<TmuxEvent> {
+        if error {
+            let message = lines.first().map(|l| String::from_utf8_lossy(l));
+            log::warn!("tmux command failed: {}", message.unwrap_or_default());
+            return Vec::new();
+        }
+        match reply {
+            Reply::Ignore => Vec::new(),
+            Reply::ListPanes { session } => self.update_panes(lines, session),
+            Reply::Capture(pane) => {
+                let Some(info) = self.panes.get(&pane) else {
+                    return Vec::new();
+                };
+                // Redraw the pane from scratch, then put the cursor back
+                let mut data = b"\x1b[H\x1b[2J".to_vec();
+                for (i, line) in lines.iter().enumerate() {
+                    if i > 0 {
+                        data.extend_from_slice(b"\r\n");
+                    }
+                    data.extend_from_slice(line);
+                }
+                let (col, row) = info.cursor;
+                data.extend_from_slice(format!("\x1b[{};{}H", row + 1, col + 1).as_bytes());
+                vec![TmuxEvent::PaneOutput { pane, data }]
+            }
+        }
+    }
+
+    /// Open the panes in a `list-panes` reply that are new, and with the
+    /// whole `session` listed, close the ones that are gone
+    fn update_panes(&mut self, lines: &[Vec<u8>], session: bool) -> Vec<TmuxEvent> {
+        let mut events = Vec::new();
+        let mut listed = Vec::new();
+        for line in lines {
+            let line = String::from_utf8_lossy(line);
+            let mut fields = line.splitn(7, ' ');
+            let mut next = || fields.next();
+            let (Some(window), Some(pane), Some(cols), Some(rows), Some(x), Some(y)) = (
+                next().and_then(|f| parse_id(f, '@')),
+                next().and_then(|f| parse_id(f, '%')),
+                next().and_then(|f| f.parse().ok()),
+                next().and_then(|f| f.parse().ok()),
+                next().and_then(|f| f.parse().ok()),
+                next().and_then(|f| f.parse().ok()),
+            ) else {
+                continue;
+            };
+            let name = next().unwrap_or_default().to_string();
+            listed.push(pane);
+            self.window_names.insert(window, name.clone());
+            if self.panes.contains_key(&pane) {
+                continue;
+            }
+            self.panes.insert(
+                pane,
+                Pane {
+                    window,
+                    cols,
+                    rows,
+                    cursor: (x, y),
+                },
+            );
+            events.push(TmuxEvent::PaneOpened {
+                pane,
+                title: name,
+                cols,
+                rows,
+            });
+            self.command(
+                &format!("capture-pane -p -e -t %{}", pane),
+                Reply::Capture(pane),
+            );
+        }
+        if session {
+            let gone: Vec<PaneId> = self
+                .panes
+                .keys()
+                .copied()
+                .filter(|pane| !listed.contains(pane))
+                .collect();
+            for pane in gone {
+                self.panes.remove(&pane);
+                events.push(TmuxEvent::PaneClosed { pane });
+            }
+        }
+        events
+    }
+
+    /// Follow a window's new layout: panes split off it open, panes gone
+    /// from it close and the rest take their new sizes
+    fn apply_layout(&mut self, window: WindowId, layout: &str) -> Vec<TmuxEvent> {
+        // A window not listed yet gets its panes from the list-panes reply
+        let Some(name) = self.window_names.get(&window).cloned() else {
+            return Vec::new();
+        };
+        let Some(cells) = parse_layout(layout) else {
+            log::warn!("Unrecognized tmux layout: {}", layout);
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        for pane in self.window_panes(window) {
+            if !cells.iter().any(|&(p, _, _)| p == pane) {
+                self.panes.remove(&pane);
+                events.push(TmuxEvent::PaneClosed { pane });
+            }
+        }
+        for (pane, cols, rows) in cells {
+            match self.panes.get_mut(&pane) {
+                Some(info) => {
+                    if (info.cols, info.rows) != (cols, rows) {
+                        info.cols = cols;
+                        info.rows = rows;
+                        events.push(TmuxEvent::PaneResized { pane, cols, rows });
+                    }
+                }
+                None => {
+                    self.panes.insert(
+                        pane,
+                        Pane {
+                            window,
+                            cols,
+                            rows,
+                            cursor: (0, 0),
+                        },
+                    );
+                    events.push(TmuxEvent::PaneOpened {
+                        pane,
+                        title: name.clone(),
+                        cols,
+                        rows,
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    fn close_window(&mut self, window: WindowId) -> Vec<TmuxEvent> {
+        self.window_names.remove(&window);
+        self.window_panes(window)
+            .into_iter()
+            .map(|pane| {
+                self.panes.remove(&pane);
+                TmuxEvent::PaneClosed { pane }
+            })
+            .collect()
+    }
+
+    /// Panes of `window`, in order
+    fn window_panes(&self, window: WindowId) -> Vec<PaneId> {
+        let mut panes: Vec<PaneId> = self
+            .panes
+            .iter()
+            .filter(|(_, info)| info.window == window)
+            .map(|(&pane, _)| pane)
+            .collect();
+        panes.sort_unstable();
+        panes
+    }
+}
+
+/// A [`TmuxClient`] talking through the gateway tab's terminal
+///
+/// Frontends create one when the gateway's terminal reports
+/// [`TmuxControl::Started`] and share it with the pane tabs, whose terminals
+/// write through [`TmuxGateway::pane_write_fn`]. Its methods write to the
+/// gateway terminal, so they must not be called with it locked.
+pub struct TmuxGateway {
+    client: Mutex<TmuxClient>,
+    write: GatewayWriteFn,
+}
+
+impl TmuxGateway {
+    /// Start talking to tmux, writing commands with `write`
+    pub fn new(write: impl Fn(&[u8]) + Send + Sync + 'static) -> Arc<Self> {
+        let gateway = Arc::new(Self {
+            client: Mutex::new(TmuxClient::new()),
+            write: Box::new(write),
+        });
+        gateway.with_client(|_| ());
+        gateway
+    }
+
+    /// Handle control mode output from the gateway's terminal
+    pub fn handle(&self, control: &[TmuxControl]) -> Vec<TmuxEvent> {
+        self.with_client(|client| {
+            let mut events = Vec::new();
+            for item in control {
+                match item {
+                    TmuxControl::Started => {}
+                    TmuxControl::Line(line) => events.extend(client.handle_line(line)),
+                    TmuxControl::Ended => {
+                        if !client.exited {
+                            client.exited = true;
+                            events.push(TmuxEvent::Exit { reason: None });
+                        }
+                    }
+                }
+            }
+            events
+        })
+    }
+
+    /// Whether tmux left control mode
+    pub fn is_exited(&self) -> bool {
+        self.client.lock().is_exited()
+    }
+
+    /// Send keys typed in a pane's tab
+    pub fn send_keys(&self, pane: PaneId, data: &[u8]) {
+        self.with_client(|client| client.send_keys(pane, data));
+    }
+
+    /// Tell tmux the size of the area tabs have
+    pub fn resize(&self, cols: usize, rows: usize) {
+        self.with_client(|client| client.resize(cols, rows));
+    }
+
+    /// Detach from tmux, leaving the session running
+    pub fn detach(&self) {
+        self.with_client(TmuxClient::detach);
+    }
+
+    /// Write function for a pane's terminal: its input goes to the pane
+    pub fn pane_write_fn(self: &Arc<Self>, pane: PaneId) -> WriteFn {
+        let gateway = Arc::clone(self);
+        Box::new(move |data: &[u8]| -> Result<(), PtyError> {
+            gateway.send_keys(pane, data);
+            Ok(())
+        })
+    }
+
+    /// Run `f` on the client, then write the commands it queued (with the
+    /// client still locked, so they go out in order)
+    fn with_client<R>(&self, f: impl FnOnce(&mut TmuxClient) -> R) -> R {
+        let mut client = self.client.lock();
+        let result = f(&mut client);
+        let output = client.take_output();
+        if !output.is_empty() {
+            (self.write)(&output);
+        }
+        result
+    }
+}
+
+/// Parse `@N` or `%N` ids
+fn parse_id(text: &str, sigil: char) -> Option<u32> {
+    text.strip_prefix(sigil)?.parse().ok()
+}
+
+/// Decode `%output` data, where tmux escapes control characters and
+/// backslashes as `\ooo`
+fn unescape_output(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let octal = data
+            .get(i + 1..i + 4)
+            .filter(|digits| data[i] == b'\\' && digits.iter().all(|d| (b'0'..=b'7').contains(d)));
+        match octal {
+            Some(digits) => {
+                let value = digits.iter().fold(0u32, |v, d| v * 8 + (d - b'0') as u32);
+                out.push(value as u8);
+                i += 4;
+            }
+            None => {
+                out.push(data[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Panes and their sizes in a window layout such as
+/// `bb62,159x48,0,0{79x48,0,0,1,79x48,80,0,2}`
+fn parse_layout(layout: &str) -> Option<Vec<(PaneId, usize, usize)>> {
+    let (_checksum, cell) = layout.split_once(',')?;
+    let mut panes = Vec::new();
+    let rest = parse_layout_cell(cell, &mut panes)?;
+    rest.is_empty().then_some(panes)
+}
+
+/// Parse one `WxH,X,Y` cell: a pane (`,ID`) or a split of cells (`{...}`
+/// side by side, `[...]` stacked); returns what follows it
+fn parse_layout_cell<'a>(
+    cell: &'a str,
+    panes: &mut Vec<(PaneId, usize, usize)>,
+) -> Option<&'a str> {
+    let (size, rest) = cell.split_once(',')?;
+    let (cols, rows) = size.split_once('x')?;
+    let (cols, rows) = (cols.parse().ok()?, rows.parse().ok()?);
+    let (_x, rest) = rest.split_once(',')?;
+    let y_end = rest.find([',', '{', '[', '}', ']']).unwrap_or(rest.len());
+    let rest = &rest[y_end..];
+
+    if let Some(rest) = rest.strip_prefix(',') {
+        let id_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        panes.push((rest[..id_end].parse().ok()?, cols, rows));
+        return Some(&rest[id_end..]);
+    }
+
+    let close = match rest.chars().next()? {
+        '{' => '}',
+        '[' => ']',
+        _ => return None,
+    };
+    let mut rest = &rest[1..];
+    loop {
+        rest = parse_layout_cell(rest, panes)?;
+        if let Some(next) = rest.strip_prefix(',') {
+            rest = next;
+        } else {
+            return rest.strip_prefix(close);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(client: &mut TmuxClient, text: &str) -> Vec<TmuxEvent> {
+        text.lines()
+            .flat_map(|line| client.handle_line(line.as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        assert_eq!(parse_layout("b25d,80x24,0,0,3"), Some(vec![(3, 80, 24)]));
+        assert_eq!(
+            parse_layout("bb62,159x48,0,0{79x48,0,0,1,79x48,80,0[79x24,80,0,2,79x23,80,25,4]}"),
+            Some(vec![(1, 79, 48), (2, 79, 24), (4, 79, 23)])
+        );
+        assert_eq!(parse_layout("bb62,159x48,0,0{79x48,0,0,1"), None);
+    }
+
+    #[test]
+    fn test_unescape_output() {
+        assert_eq!(unescape_output(br"ls\015\012a\134b\9"), b"ls\r\na\\b\\9");
+    }
+
+    #[test]
+    fn test_tmux_client() {
+        let mut client = TmuxClient::new();
+        assert!(String::from_utf8(client.take_output())
+            .unwrap()
+            .starts_with("list-panes -s -F \"#{window_id}"));
+
+        // The reply to the command that started control mode is skipped
+        let events = lines(
+            &mut client,
+            "%begin 1 1 0\n%end 1 1 0\n%begin 1 2 1\n\
+             @1 %1 80 23 0 5 my shell\n@2 %3 80 23 2 0 vim\n%end 1 2 1",
+        );
+        assert_eq!(
+            events,
+            vec![
+                TmuxEvent::PaneOpened {
+                    pane: 1,
+                    title: "my shell".into(),
+                    cols: 80,
+                    rows: 23,
+                },
+                TmuxEvent::PaneOpened {
+                    pane: 3,
+                    title: "vim".into(),
+                    cols: 80,
+                    rows: 23,
+                },
+            ]
+        );
+        assert_eq!(
+            client.take_output(),
+            b"capture-pane -p -e -t %1\ncapture-pane -p -e -t %3\n"
+        );
+
+        // Captures redraw the panes; output for unknown panes is dropped
+        let events = lines(
+            &mut client,
+            "%begin 1 3 1\n$ ls\nfoo\n%end 1 3 1\n%begin 1 4 1\n%end 1 4 1\n\
+             %output %1 x\\015\\012\n%output %9 y",
+        );
+        assert_eq!(
+            events,
+            vec![
+                TmuxEvent::PaneOutput {
+                    pane: 1,
+                    data: b"\x1b[H\x1b[2J$ ls\r\nfoo\x1b[6;1H".to_vec(),
+                },
+                TmuxEvent::PaneOutput {
+                    pane: 3,
+                    data: b"\x1b[H\x1b[2J\x1b[1;3H".to_vec(),
+                },
+                TmuxEvent::PaneOutput {
+                    pane: 1,
+                    data: b"x\r\n".to_vec(),
+                },
+            ]
+        );
+
+        // Splitting a window opens a pane; renaming retitles its panes
+        let events = lines(
+            &mut client,
+            "%layout-change @1 bb62,80x23,0,0{40x23,0,0,1,39x23,41,0,5} x 0\n\
+             %window-renamed @1 build",
+        );
+        assert_eq!(
+            events,
+            vec![
+                TmuxEvent::PaneResized {
+                    pane: 1,
+                    cols: 40,
+                    rows: 23,
+                },
+                TmuxEvent::PaneOpened {
+                    pane: 5,
+                    title: "my shell".into(),
+                    cols: 39,
+                    rows: 23,
+                },
+                TmuxEvent::PaneTitle {
+                    pane: 1,
+                    title: "build".into(),
+                },
+                TmuxEvent::PaneTitle {
+                    pane: 5,
+                    title: "build".into(),
+                },
+            ]
+        );
+
+        let events = lines(&mut client, "%window-close @2\n%exit detached");
+        assert_eq!(
+            events,
+            vec![
+                TmuxEvent::PaneClosed { pane: 3 },
+                TmuxEvent::Exit {
+                    reason: Some("detached".into()),
+                },
+            ]
+        );
+        assert!(client.is_exited());
+    }
+
+    #[test]
+    fn test_tmux_client_input() {
+        let mut client = TmuxClient::new();
+        client.take_output();
+        client.send_keys(2, b"ls\r");
+        client.resize(100, 30);
+        client.resize(100, 30);
+        assert_eq!(
+            String::from_utf8(client.take_output()).unwrap(),
+            "send-keys -t %2 -H 6c 73 0d\nrefresh-client -C 100,30\n"
+        );
+
+        client.send_keys(2, &[b'a'; SEND_KEYS_CHUNK + 1]);
+        assert_eq!(client.take_output().split(|&b| b == b'\n').count(), 3);
+    }
+
+    #[test]
+    fn test_tmux_gateway() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&written);
+        let gateway = TmuxGateway::new(move |data| sink.lock().extend_from_slice(data));
+        assert!(written.lock().starts_with(b"list-panes -s"));
+
+        (gateway.pane_write_fn(7))(b"q").unwrap();
+        assert!(written.lock().ends_with(b"send-keys -t %7 -H 71\n"));
+
+        let events = gateway.handle(&[TmuxControl::Started, TmuxControl::Ended]);
+        assert_eq!(events, vec![TmuxEvent::Exit { reason: None }]);
+        assert!(gateway.is_exited());
+    }
+}
@@ -0,0 +1,293 @@
+//! Triggers: actions fired by output matching a regex
+//!
+//! `[[triggers]]` entries ([`TriggerConfig`]) look at each line of a
+//! terminal's output once, as a line feed ends it. Highlights are drawn by
+//! the terminal itself while it parses the output
+//! ([`Terminal::set_line_highlights`]); the other actions come back from
+//! [`TriggerEngine::scan`] as [`TriggerEffect`]s for the frontend to perform.
+//!
+//! The frontend keeps a [`TriggerEngine`] per terminal, calls
+//! [`TriggerEngine::attach`] when the terminal is created, and passes the
+//! lines of each [`TerminalEvent::LinesCompleted`] to
+//! [`TriggerEngine::scan`].
+//!
+//! [`TerminalEvent::LinesCompleted`]: cterm_core::term::TerminalEvent::LinesCompleted
+
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use cterm_core::{LineHighlight, Rgb, Terminal};
+use regex::Regex;
+
+use crate::config::{TriggerAction, TriggerConfig};
+
+/// What the frontend should do for a trigger that fired
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TriggerEffect {
+    /// Type text into the terminal
+    Send(String),
+    /// Show a desktop notification
+    Notify { title: String, body: String },
+    /// Start a program (see [`run_command`])
+    Command { program: String, args: Vec<String> },
+    /// Set the tab color (CSS hex)
+    TabColor(String),
+}
+
+/// A compiled `[[triggers]]` entry
+#[derive(Debug, Clone)]
+struct Trigger {
+    regex: Regex,
+    config: TriggerConfig,
+    last_fired: Option<Instant>,
+}
+
+/// The triggers of one terminal
+#[derive(Debug, Clone, Default)]
+pub struct TriggerEngine {
+    triggers: Vec<Trigger>,
+}
+
+impl TriggerEngine {
+    /// Minimum time between two firings of one trigger, so that text a
+    /// `send` trigger types, once echoed, can't set it off in a loop
+    pub const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Compile `configs`, leaving out (and logging) the invalid ones
+    pub fn new(configs: &[TriggerConfig]) -> Self {
+        let triggers = configs
+            .iter()
+            .filter_map(|config| match compile(config) {
+                Ok(regex) => Some(Trigger {
+                    regex,
+                    config: config.clone(),
+                    last_fired: None,
+                }),
+                Err(e) => {
+                    log::warn!("Trigger {:?}: {}", config.regex, e);
+                    None
+                }
+            })
+            .collect();
+        Self { triggers }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triggers.is_empty()
+    }
+
+    /// Set up `terminal` for these triggers: its line highlights, and line
+    /// events when other actions need them
+    pub fn attach(&self, terminal: &mut Terminal) {
+        terminal.set_line_highlights(self.highlights());
+        terminal.set_line_events(
+            self.triggers
+                .iter()
+                .any(|t| t.config.action != TriggerAction::Highlight),
+        );
+    }
+
+    /// Backgrounds of the `highlight` triggers, for the terminal to draw
+    pub fn highlights(&self) -> Vec<LineHighlight> {
+        self.triggers
+            .iter()
+            .filter(|t| t.config.action == TriggerAction::Highlight)
+            .filter_map(|t| {
+                Some(LineHighlight {
+                    regex: t.regex.clone(),
+                    color: t.config.color.as_deref().and_then(Rgb::from_hex)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Effects of the triggers newly completed `lines` match
+    ///
+    /// Each trigger fires at most once per call, on the first line it
+    /// matches, and not again within [`Self::MIN_INTERVAL`].
+    pub fn scan(&mut self, lines: &[String], now: Instant) -> Vec<TriggerEffect> {
+        let mut effects = Vec::new();
+        for trigger in &mut self.triggers {
+            if trigger.config.action == TriggerAction::Highlight {
+                continue;
+            }
+            let recent = trigger
+                .last_fired
+                .is_some_and(|at| now.saturating_duration_since(at) < Self::MIN_INTERVAL);
+            if recent {
+                continue;
+            }
+            let Some(caps) = lines.iter().find_map(|line| trigger.regex.captures(line)) else {
+                continue;
+            };
+            trigger.last_fired = Some(now);
+            let expand = |template: &str| {
+                let mut out = String::new();
+                caps.expand(template, &mut out);
+                out
+            };
+            let config = &trigger.config;
+            let effect = match config.action {
+                TriggerAction::Highlight => continue,
+                TriggerAction::Send => {
+                    TriggerEffect::Send(expand(config.text.as_deref().unwrap_or_default()))
+                }
+                TriggerAction::Notify => TriggerEffect::Notify {
+                    title: "Trigger matched".into(),
+                    body: match config.text {
+                        Some(ref text) => expand(text),
+                        None => caps[0].to_string(),
+                    },
+                },
+                TriggerAction::Command => TriggerEffect::Command {
+                    program: config.command.clone().unwrap_or_default(),
+                    args: config.args.iter().map(|arg| expand(arg)).collect(),
+                },
+                TriggerAction::TabColor => {
+                    TriggerEffect::TabColor(config.color.clone().unwrap_or_default())
+                }
+            };
+            log::debug!("Trigger {:?} fired: {:?}", config.regex, effect);
+            effects.push(effect);
+        }
+        effects
+    }
+}
+
+/// Regex of a trigger, checking it has what its action needs
+fn compile(config: &TriggerConfig) -> Result<Regex, String> {
+    let missing = match config.action {
+        TriggerAction::Highlight | TriggerAction::TabColor => config
+            .color
+            .as_deref()
+            .and_then(Rgb::from_hex)
+            .is_none()
+            .then_some("a valid color"),
+        TriggerAction::Send => config.text.is_none().then_some("text"),
+        TriggerAction::Notify => None,
+        TriggerAction::Command => config.command.is_none().then_some("a command"),
+    };
+    if let Some(missing) = missing {
+        return Err(format!("needs {}", missing));
+    }
+    Regex::new(&config.regex).map_err(|e| e.to_string())
+}
+
+/// Start `program` with `args` in the background
+///
+/// The program's output is discarded; a thread waits for it to exit.
+pub fn run_command(program: &str, args: &[String]) -> std::io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger(regex: &str, action: TriggerAction) -> TriggerConfig {
+        TriggerConfig {
+            regex: regex.into(),
+            action,
+            ..Default::default()
+        }
+    }
+
+    fn lines(l: &[&str]) -> Vec<String> {
+        l.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_trigger_effects() {
+        let start = Instant::now();
+        let mut engine = TriggerEngine::new(&[
+            TriggerConfig {
+                text: Some("yes\r".into()),
+                ..trigger(r"Continue\? \[y/N\]", TriggerAction::Send)
+            },
+            TriggerConfig {
+                text: Some("$1 wants a password".into()),
+                ..trigger(r"Password for (\S+):", TriggerAction::Notify)
+            },
+            TriggerConfig {
+                command: Some("say".into()),
+                args: vec!["build ${state}".into()],
+                ..trigger(r"build (?P<state>passed|failed)", TriggerAction::Command)
+            },
+            TriggerConfig {
+                color: Some("#cc0000".into()),
+                ..trigger("PRODUCTION", TriggerAction::TabColor)
+            },
+        ]);
+
+        assert!(engine.scan(&lines(&["nothing here"]), start).is_empty());
+        assert_eq!(
+            engine.scan(
+                &lines(&["Password for root@db:", "build failed", "on PRODUCTION"]),
+                start
+            ),
+            vec![
+                TriggerEffect::Notify {
+                    title: "Trigger matched".into(),
+                    body: "root@db wants a password".into()
+                },
+                TriggerEffect::Command {
+                    program: "say".into(),
+                    args: vec!["build failed".into()]
+                },
+                TriggerEffect::TabColor("#cc0000".into()),
+            ]
+        );
+
+        // A trigger that just fired waits before firing again
+        let prompt = lines(&["Continue? [y/N]"]);
+        assert_eq!(
+            engine.scan(&prompt, start),
+            vec![TriggerEffect::Send("yes\r".into())]
+        );
+        assert!(engine.scan(&prompt, start).is_empty());
+        let later = start + TriggerEngine::MIN_INTERVAL;
+        assert_eq!(engine.scan(&prompt, later).len(), 1);
+    }
+
+    #[test]
+    fn test_trigger_highlights_and_validation() {
+        let engine = TriggerEngine::new(&[
+            TriggerConfig {
+                color: Some("#5c1f1f".into()),
+                ..trigger("FAILED", TriggerAction::Highlight)
+            },
+            // Invalid: bad regex, no color, nothing to send or run
+            TriggerConfig {
+                color: Some("#5c1f1f".into()),
+                ..trigger("(", TriggerAction::Highlight)
+            },
+            trigger("x", TriggerAction::Highlight),
+            trigger("x", TriggerAction::Send),
+            trigger("x", TriggerAction::Command),
+        ]);
+        let highlights = engine.highlights();
+        assert_eq!(highlights.len(), 1);
+        assert_eq!(highlights[0].color, Rgb::new(0x5c, 0x1f, 0x1f));
+
+        // Highlights need no line events
+        let mut terminal = Terminal::new(20, 3, Default::default());
+        engine.attach(&mut terminal);
+        let events = terminal.process(b"test FAILED\r\n");
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, cterm_core::term::TerminalEvent::LinesCompleted(_))));
+        assert_eq!(
+            terminal.screen().get_cell(0, 0).unwrap().bg,
+            cterm_core::Color::Rgb(Rgb::new(0x5c, 0x1f, 0x1f))
+        );
+
+        assert!(TriggerEngine::new(&[]).is_empty());
+    }
+}
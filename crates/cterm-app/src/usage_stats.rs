@@ -0,0 +1,409 @@
+//! Opt-in usage statistics that stay on this computer
+//!
+//! With `general.usage_stats` on, the frontends report every command that
+//! finishes (from OSC 133 shell integration marks) and every tab that
+//! closes. Totals are kept per day for [`KEEP_DAYS`] days in
+//! `usage_stats.json` in the local data directory, outside the config
+//! directory so that git sync never pushes them, and nothing is sent
+//! anywhere. [`report`] formats them for the Usage Statistics window and
+//! `cterm stats`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use cterm_core::screen::{CommandFinished, Screen};
+use cterm_ui::utils::dir_title;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Days of statistics kept
+pub const KEEP_DAYS: i64 = 90;
+
+/// Tabs kept per day, busiest first
+const MAX_TABS_PER_DAY: usize = 50;
+
+/// Days shown in the commands-per-day chart
+const CHART_DAYS: i64 = 14;
+
+/// Days the totals and busiest tabs cover
+const SUMMARY_DAYS: i64 = 30;
+
+/// Width of the longest bar in the chart
+const BAR_WIDTH: u32 = 30;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Statistics loaded from disk on first use
+static STATS: Mutex<Option<UsageStats>> = Mutex::new(None);
+
+/// Totals for one day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayStats {
+    /// Commands that finished
+    pub commands: u32,
+    /// Commands that finished with a non-zero exit status
+    pub failed: u32,
+    /// Seconds spent running commands
+    pub command_secs: u64,
+    /// Tabs closed
+    pub sessions: u32,
+    /// Seconds the closed tabs were open
+    pub session_secs: u64,
+    /// Per-tab totals, by tab title
+    pub tabs: BTreeMap<String, TabStats>,
+}
+
+/// Totals for one tab title on one day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TabStats {
+    /// Commands that finished
+    pub commands: u32,
+    /// Seconds spent running commands
+    pub command_secs: u64,
+}
+
+/// Daily totals, keyed by local date (`YYYY-MM-DD`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageStats {
+    pub days: BTreeMap<String, DayStats>,
+}
+
+impl UsageStats {
+    /// Count a finished command run in the tab titled `tab` on `day`
+    pub fn record_command(&mut self, day: i64, tab: &str, finished: &CommandFinished) {
+        let stats = self.days.entry(day_key(day)).or_default();
+        let secs = finished.duration.as_secs();
+        stats.commands += 1;
+        if finished.exit_code.is_some_and(|code| code != 0) {
+            stats.failed += 1;
+        }
+        stats.command_secs += secs;
+
+        let tab = stats.tabs.entry(tab.to_string()).or_default();
+        tab.commands += 1;
+        tab.command_secs += secs;
+
+        if stats.tabs.len() > MAX_TABS_PER_DAY {
+            if let Some(quietest) = stats
+                .tabs
+                .iter()
+                .min_by_key(|(_, tab)| (tab.commands, tab.command_secs))
+                .map(|(title, _)| title.clone())
+            {
+                stats.tabs.remove(&quietest);
+            }
+        }
+    }
+
+    /// Count a tab that closed on `day` after being open for `duration`
+    pub fn record_session(&mut self, day: i64, duration: Duration) {
+        let stats = self.days.entry(day_key(day)).or_default();
+        stats.sessions += 1;
+        stats.session_secs += duration.as_secs();
+    }
+
+    /// Drop the days more than [`KEEP_DAYS`] before `today`
+    pub fn prune(&mut self, today: i64) {
+        let oldest = day_key(today - KEEP_DAYS + 1);
+        self.days.retain(|day, _| *day >= oldest);
+    }
+
+    /// Dashboard text for `today`: commands per day, totals and the
+    /// busiest tabs
+    pub fn report(&self, today: i64) -> String {
+        let mut out = String::new();
+
+        let chart: Vec<(i64, u32)> = (today - CHART_DAYS + 1..=today)
+            .map(|day| (day, self.day(day).map_or(0, |stats| stats.commands)))
+            .collect();
+        let most = chart.iter().map(|&(_, n)| n).max().unwrap_or(0).max(1);
+        let _ = writeln!(out, "Commands per day, last {} days", CHART_DAYS);
+        for &(day, commands) in &chart {
+            let bar = "█".repeat((commands * BAR_WIDTH).div_ceil(most) as usize);
+            let _ = writeln!(
+                out,
+                "  {} {}  {:<width$} {:>5}",
+                WEEKDAYS[(day + 4).rem_euclid(7) as usize],
+                day_key(day),
+                bar,
+                commands,
+                width = BAR_WIDTH as usize
+            );
+        }
+
+        let mut total = DayStats::default();
+        let mut tabs: BTreeMap<&str, TabStats> = BTreeMap::new();
+        for stats in (today - SUMMARY_DAYS + 1..=today).filter_map(|day| self.day(day)) {
+            total.commands += stats.commands;
+            total.failed += stats.failed;
+            total.command_secs += stats.command_secs;
+            total.sessions += stats.sessions;
+            total.session_secs += stats.session_secs;
+            for (title, tab) in &stats.tabs {
+                let sum = tabs.entry(title).or_default();
+                sum.commands += tab.commands;
+                sum.command_secs += tab.command_secs;
+            }
+        }
+
+        let _ = writeln!(out, "\nLast {} days", SUMMARY_DAYS);
+        let _ = writeln!(
+            out,
+            "  Commands          {} ({} failed)",
+            total.commands, total.failed
+        );
+        let _ = writeln!(
+            out,
+            "  Time in commands  {}",
+            format_secs(total.command_secs)
+        );
+        let average = total.session_secs / u64::from(total.sessions.max(1));
+        let _ = writeln!(
+            out,
+            "  Tabs closed       {} (open {} on average)",
+            total.sessions,
+            format_secs(average)
+        );
+
+        let mut busiest: Vec<(&str, TabStats)> = tabs.into_iter().collect();
+        busiest.sort_by(|a, b| {
+            (b.1.commands, b.1.command_secs).cmp(&(a.1.commands, a.1.command_secs))
+        });
+        let _ = writeln!(out, "\nBusiest tabs, last {} days", SUMMARY_DAYS);
+        if busiest.is_empty() {
+            let _ = writeln!(out, "  No commands yet");
+        }
+        for (title, tab) in busiest.iter().take(10) {
+            let title: String = title.chars().take(30).collect();
+            let _ = writeln!(
+                out,
+                "  {:<30}  {:>6} commands  {:>8}",
+                title,
+                tab.commands,
+                format_secs(tab.command_secs)
+            );
+        }
+        out
+    }
+
+    fn day(&self, day: i64) -> Option<&DayStats> {
+        self.days.get(&day_key(day))
+    }
+}
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Follow `general.usage_stats` from the configuration
+pub fn configure(config: &Config) {
+    ENABLED.store(config.general.usage_stats, Ordering::Relaxed);
+}
+
+/// Whether statistics are being collected
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Path of the statistics file
+pub fn stats_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "cterm", "cterm").map(|p| p.data_local_dir().join("usage_stats.json"))
+}
+
+/// Label a tab is counted under: the title set by the shell, or else the
+/// name of its working directory
+pub fn tab_label(screen: &Screen) -> String {
+    if !screen.title.is_empty() {
+        screen.title.clone()
+    } else if !screen.current_dir.is_empty() {
+        dir_title(&screen.current_dir)
+    } else {
+        "Shell".to_string()
+    }
+}
+
+/// Count a finished command, if collecting
+pub fn record_command(tab: &str, finished: &CommandFinished) {
+    update(|stats, today| stats.record_command(today, tab, finished));
+}
+
+/// Count a closed tab that was open for `duration`, if collecting
+pub fn record_session(duration: Duration) {
+    update(|stats, today| stats.record_session(today, duration));
+}
+
+/// Dashboard text, prefixed with how to turn collection on when it is off
+pub fn report() -> String {
+    let mut out = String::from("Usage statistics are kept on this computer only.\n");
+    if !is_enabled() {
+        out.push_str(
+            "Collection is off. Turn on \"Collect usage statistics\" in Preferences \
+             (general.usage_stats) to start.\n",
+        );
+    }
+    out.push('\n');
+    let today = local_day(SystemTime::now());
+    let report = with_stats(|stats| stats.report(today));
+    out.push_str(&report);
+    out
+}
+
+/// Delete all collected statistics
+pub fn clear() -> std::io::Result<()> {
+    with_stats(|stats| stats.days.clear());
+    match stats_path().map(std::fs::remove_file) {
+        Some(Err(e)) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Apply `f` to the statistics and save them, if collecting
+fn update(f: impl FnOnce(&mut UsageStats, i64)) {
+    if !is_enabled() {
+        return;
+    }
+    let today = local_day(SystemTime::now());
+    let stats = with_stats(|stats| {
+        f(stats, today);
+        stats.prune(today);
+        stats.clone()
+    });
+    if let Err(e) = save(&stats) {
+        log::warn!("Failed to save usage statistics: {}", e);
+    }
+}
+
+fn with_stats<T>(f: impl FnOnce(&mut UsageStats) -> T) -> T {
+    let mut guard = STATS.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(load))
+}
+
+fn load() -> UsageStats {
+    stats_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(stats: &UsageStats) -> std::io::Result<()> {
+    let Some(path) = stats_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string(stats)?)
+}
+
+/// Local day number (days since 1970-01-01) of a time
+///
+/// Windows counts days in UTC.
+fn local_day(time: SystemTime) -> i64 {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    (secs + utc_offset(secs)).div_euclid(86400)
+}
+
+#[cfg(unix)]
+fn utc_offset(secs: i64) -> i64 {
+    let time = secs as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        0
+    } else {
+        tm.tm_gmtoff as i64
+    }
+}
+
+#[cfg(not(unix))]
+fn utc_offset(_secs: i64) -> i64 {
+    0
+}
+
+/// `YYYY-MM-DD` of a day number (days since 1970-01-01)
+fn day_key(day: i64) -> String {
+    // Civil-from-days, proleptic Gregorian calendar
+    let z = day + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Seconds as `45s`, `12m` or `3h 20m`
+fn format_secs(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished(secs: u64, exit_code: i32) -> CommandFinished {
+        CommandFinished {
+            duration: Duration::from_secs(secs),
+            exit_code: Some(exit_code),
+        }
+    }
+
+    #[test]
+    fn test_day_key() {
+        assert_eq!(day_key(0), "1970-01-01");
+        assert_eq!(day_key(-1), "1969-12-31");
+        assert_eq!(day_key(11_016), "2000-02-29");
+        assert_eq!(day_key(20_742), "2026-10-16");
+    }
+
+    #[test]
+    fn test_record_and_prune() {
+        let today = 20_742;
+        let mut stats = UsageStats::default();
+        stats.record_command(today, "cterm", &finished(90, 0));
+        stats.record_command(today, "cterm", &finished(30, 1));
+        stats.record_command(today - 1, "logs", &finished(5, 0));
+        stats.record_session(today, Duration::from_secs(600));
+        stats.record_command(today - KEEP_DAYS, "old", &finished(1, 0));
+
+        stats.prune(today);
+        assert_eq!(stats.days.len(), 2);
+        let day = &stats.days["2026-10-16"];
+        assert_eq!((day.commands, day.failed, day.command_secs), (2, 1, 120));
+        assert_eq!((day.sessions, day.session_secs), (1, 600));
+        assert_eq!(day.tabs["cterm"].commands, 2);
+    }
+
+    #[test]
+    fn test_report() {
+        let today = 20_742;
+        let mut stats = UsageStats::default();
+        for _ in 0..4 {
+            stats.record_command(today, "cterm", &finished(60, 0));
+        }
+        stats.record_command(today - 2, "logs", &finished(3600, 2));
+
+        let report = stats.report(today);
+        assert!(report.contains(&format!("Fri 2026-10-16  {}", "█".repeat(30))));
+        assert!(report.contains("Commands          5 (1 failed)"));
+        assert!(report.contains("Time in commands  1h 04m"));
+        let cterm = report.find("  cterm ").unwrap();
+        let logs = report.find("  logs ").unwrap();
+        assert!(cterm < logs);
+    }
+}
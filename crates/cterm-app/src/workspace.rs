@@ -0,0 +1,229 @@
+//! Workspaces: several tabs and panes opened together
+//!
+//! A workspace lists tabs, each started from a tab template or the default
+//! shell with its own directory, command and startup commands, and panes
+//! split off it. Workspaces live in config.toml as `[[workspaces]]`, so git
+//! sync carries them along with the rest of the configuration.
+//!
+//! The frontends open one from File > Workspaces, from
+//! [`crate::control::ControlRequest::OpenWorkspace`], or at startup for
+//! `cterm --workspace NAME`: they [`resolve`] it against the tab templates,
+//! then open the tabs in order and split each tab's focused pane for each of
+//! its panes.
+
+use crate::config::{
+    expand_path, Config, PaneSplit, StickyTabConfig, WorkspaceConfig, WorkspacePaneConfig,
+    WorkspaceTabConfig,
+};
+use crate::control::{self, ControlRequest};
+use crate::session::SplitAxis;
+
+/// A tab to open, ready for the frontend's template code
+#[derive(Debug, Clone)]
+pub struct WorkspaceTab {
+    /// Template the tab opens from
+    pub template: StickyTabConfig,
+    /// Panes to split off it, in order
+    pub panes: Vec<WorkspacePane>,
+}
+
+/// A pane to split off the focused pane of its tab
+#[derive(Debug, Clone)]
+pub struct WorkspacePane {
+    /// Right of (horizontal) or below (vertical) the focused pane
+    pub axis: SplitAxis,
+    /// What runs in the pane
+    pub template: StickyTabConfig,
+}
+
+impl From<PaneSplit> for SplitAxis {
+    fn from(split: PaneSplit) -> Self {
+        match split {
+            PaneSplit::Right => SplitAxis::Horizontal,
+            PaneSplit::Down => SplitAxis::Vertical,
+        }
+    }
+}
+
+/// The workspace called `name`, ignoring case
+pub fn find<'a>(config: &'a Config, name: &str) -> Option<&'a WorkspaceConfig> {
+    config
+        .workspaces
+        .iter()
+        .find(|w| w.name == name)
+        .or_else(|| {
+            config
+                .workspaces
+                .iter()
+                .find(|w| w.name.eq_ignore_ascii_case(name))
+        })
+}
+
+/// Ask a running cterm to open workspace `name`
+///
+/// Returns false when no instance is running, in which case the caller
+/// opens the workspace itself.
+pub fn open_in_running(name: &str) -> bool {
+    control::send(&ControlRequest::OpenWorkspace {
+        name: name.to_string(),
+    })
+    .is_ok()
+}
+
+/// The tabs of `workspace`, their templates looked up in `templates`
+///
+/// A tab naming a template that no longer exists opens the default shell
+/// with the tab's own settings.
+pub fn resolve(workspace: &WorkspaceConfig, templates: &[StickyTabConfig]) -> Vec<WorkspaceTab> {
+    workspace
+        .tabs
+        .iter()
+        .map(|tab| {
+            let template = tab_template(workspace, tab, templates);
+            let panes = tab
+                .panes
+                .iter()
+                .map(|pane| WorkspacePane {
+                    axis: pane.split.into(),
+                    template: pane_template(&template, pane),
+                })
+                .collect();
+            WorkspaceTab { template, panes }
+        })
+        .collect()
+}
+
+fn tab_template(
+    workspace: &WorkspaceConfig,
+    tab: &WorkspaceTabConfig,
+    templates: &[StickyTabConfig],
+) -> StickyTabConfig {
+    let base = tab.template.as_ref().and_then(|name| {
+        let found = templates.iter().find(|t| t.name == *name);
+        if found.is_none() {
+            log::warn!(
+                "Workspace '{}' uses unknown template '{}'",
+                workspace.name,
+                name
+            );
+        }
+        found
+    });
+    let mut template = base.cloned().unwrap_or_default();
+    template.name = match (&tab.title, base, &tab.command) {
+        (Some(title), _, _) => title.clone(),
+        (None, Some(base), _) => base.name.clone(),
+        (None, None, Some(command)) => command.clone(),
+        (None, None, None) => workspace.name.clone(),
+    };
+    // A workspace always opens its own tabs
+    template.unique = false;
+    if tab.profile.is_some() {
+        template.profile = tab.profile.clone();
+    }
+    if let Some(ref dir) = tab.working_directory {
+        template.working_directory = Some(expand_path(dir));
+    }
+    if tab.command.is_some() {
+        template.command = tab.command.clone();
+        template.args = tab.args.clone();
+    }
+    if !tab.startup_commands.is_empty() {
+        template.startup_commands = tab.startup_commands.clone();
+    }
+    if tab.color.is_some() {
+        template.color = tab.color.clone();
+    }
+    template
+}
+
+/// A pane runs the shell, or its own command, where its tab does, with the
+/// tab's profile, environment and remote
+fn pane_template(tab: &StickyTabConfig, pane: &WorkspacePaneConfig) -> StickyTabConfig {
+    StickyTabConfig {
+        working_directory: pane
+            .working_directory
+            .as_deref()
+            .map(expand_path)
+            .or_else(|| tab.working_directory.clone()),
+        command: pane.command.clone(),
+        args: pane.args.clone(),
+        startup_commands: pane.startup_commands.clone(),
+        git_remote: None,
+        keep_open: false,
+        docker: None,
+        ssh: None,
+        serial: None,
+        ..tab.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_resolve_workspace() {
+        let config: Config = toml::from_str(
+            r#"
+            [[workspaces]]
+            name = "dev"
+
+            [[workspaces.tabs]]
+            template = "Claude"
+            title = "AI"
+
+            [[workspaces.tabs]]
+            working_directory = "/src/app"
+            command = "vim"
+            profile = "Work"
+            startup_commands = ["ls"]
+
+            [[workspaces.tabs.panes]]
+            startup_commands = ["npm test"]
+
+            [[workspaces.tabs.panes]]
+            split = "down"
+            working_directory = "/tmp"
+
+            [[workspaces.tabs]]
+            template = "Missing"
+            "#,
+        )
+        .unwrap();
+        assert!(find(&config, "nope").is_none());
+        let workspace = find(&config, "DEV").unwrap();
+
+        let tabs = resolve(workspace, &[StickyTabConfig::claude()]);
+        assert_eq!(tabs.len(), 3);
+
+        let ai = &tabs[0].template;
+        assert_eq!(ai.name, "AI");
+        assert_eq!(ai.command.as_deref(), Some("claude"));
+        assert!(!ai.unique);
+        assert!(tabs[0].panes.is_empty());
+
+        let editor = &tabs[1];
+        assert_eq!(editor.template.name, "vim");
+        assert_eq!(editor.template.profile.as_deref(), Some("Work"));
+        assert_eq!(editor.template.startup_commands, ["ls"]);
+        let [right, down] = &editor.panes[..] else {
+            panic!("expected two panes");
+        };
+        assert_eq!(right.axis, SplitAxis::Horizontal);
+        assert_eq!(right.template.command, None);
+        assert_eq!(right.template.profile.as_deref(), Some("Work"));
+        assert_eq!(right.template.startup_commands, ["npm test"]);
+        assert_eq!(
+            right.template.working_directory,
+            Some(PathBuf::from("/src/app"))
+        );
+        assert_eq!(down.axis, SplitAxis::Vertical);
+        assert_eq!(down.template.working_directory, Some(PathBuf::from("/tmp")));
+
+        // An unknown template falls back to the shell
+        assert_eq!(tabs[2].template.name, "dev");
+        assert_eq!(tabs[2].template.command, None);
+    }
+}
@@ -148,9 +148,17 @@ pub struct CreateSessionOpts {
     pub args: Vec<String>,
     pub cwd: Option<String>,
     pub env: Vec<(String, String)>,
+    /// Start from a minimal environment instead of inheriting the daemon's
+    pub clear_env: bool,
     pub term: Option<String>,
     /// When set, the daemon opens a native SSH session instead of a local shell.
     pub ssh: Option<SshParams>,
+    /// When set, the daemon attaches the session to a serial device instead.
+    pub serial: Option<SerialParams>,
+    /// Commands the daemon types into the shell once it is ready
+    pub startup_commands: Vec<String>,
+    /// Scrollback lines for this session (None = the daemon's setting)
+    pub scrollback_lines: Option<usize>,
 }
 
 /// Connection to a ctermd instance
@@ -559,6 +567,12 @@ impl DaemonConnection {
                 env: opts.env.into_iter().collect(),
                 term: opts.term,
                 ssh: opts.ssh,
+                serial: opts.serial,
+                startup_commands: opts.startup_commands,
+                clear_env: opts.clear_env,
+                scrollback_lines: opts
+                    .scrollback_lines
+                    .map(|n| n.try_into().unwrap_or(u32::MAX)),
             })
             .await?;
 
@@ -14,6 +14,8 @@ pub use connection::SshTunnelHandle;
 pub use connection::{CreateSessionOpts, DaemonConnection};
 /// SSH session parameters (re-exported proto type) for [`CreateSessionOpts::ssh`].
 pub use cterm_proto::proto::SshParams;
+/// Serial session parameters (re-exported proto type) for [`CreateSessionOpts::serial`].
+pub use cterm_proto::proto::{SerialFlowControl, SerialParams, SerialParity};
 pub use error::ClientError;
 pub use remote_manager::RemoteManager;
 pub use session::SessionHandle;
@@ -4,13 +4,18 @@ use crate::connection::DaemonInfo;
 use crate::error::Result;
 use cterm_proto::proto::terminal_service_client::TerminalServiceClient;
 use cterm_proto::proto::*;
+use std::path::Path;
 use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::Mutex;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tokio_stream::StreamExt;
 use tonic::transport::Channel;
 
+/// Chunk size for remote file transfers (the daemon caps chunks at 1 MiB)
+const TRANSFER_CHUNK: usize = 512 * 1024;
+
 /// Handle to a terminal session on ctermd
 #[derive(Clone)]
 pub struct SessionHandle {
@@ -158,6 +163,24 @@ impl SessionHandle {
         Ok(response.into_inner().lines)
     }
 
+    /// Render the visible screen to a PNG with the daemon's built-in font
+    ///
+    /// `scale` multiplies the 8x12 pixel cells (1 to 4).
+    pub async fn get_screenshot(&self, scale: u32, hide_cursor: bool) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .lock()
+            .await
+            .get_screenshot(GetScreenshotRequest {
+                session_id: self.session_id.clone(),
+                scale,
+                hide_cursor,
+            })
+            .await?;
+
+        Ok(response.into_inner().png)
+    }
+
     /// Get cursor position
     pub async fn get_cursor(&self) -> Result<CursorPosition> {
         let response = self
@@ -175,6 +198,36 @@ impl SessionHandle {
             .ok_or_else(|| crate::error::ClientError::SessionNotFound(self.session_id.clone()))
     }
 
+    /// List the images on the screen and in scrollback
+    pub async fn list_images(&self) -> Result<Vec<ImageInfo>> {
+        let response = self
+            .client
+            .lock()
+            .await
+            .list_images(ListImagesRequest {
+                session_id: self.session_id.clone(),
+            })
+            .await?;
+
+        Ok(response.into_inner().images)
+    }
+
+    /// Remove the images with the given ids, or all images and kitty
+    /// placements if `ids` is empty; returns how many were removed
+    pub async fn clear_images(&self, ids: Vec<u64>) -> Result<u32> {
+        let response = self
+            .client
+            .lock()
+            .await
+            .clear_images(ClearImagesRequest {
+                session_id: self.session_id.clone(),
+                ids,
+            })
+            .await?;
+
+        Ok(response.into_inner().removed)
+    }
+
     /// Resize the terminal
     pub async fn resize(&self, cols: u32, rows: u32) -> Result<()> {
         self.client
@@ -230,6 +283,128 @@ impl SessionHandle {
         Ok(response.into_inner().success)
     }
 
+    /// Reply to an `UploadRequestedEvent` with `(name, data)` files to upload
+    /// (an empty list aborts the upload).
+    pub async fn respond_upload(&self, files: Vec<(String, Vec<u8>)>) -> Result<bool> {
+        let response = self
+            .client
+            .lock()
+            .await
+            .respond_upload(RespondUploadRequest {
+                session_id: self.session_id.clone(),
+                files: files
+                    .into_iter()
+                    .map(|(name, data)| UploadFile { name, data })
+                    .collect(),
+            })
+            .await?;
+        Ok(response.into_inner().success)
+    }
+
+    /// Contents of the daemon's clipboard `selection`, if anything was
+    /// copied to it (by a program with OSC 52 or by a client)
+    pub async fn get_clipboard(&self, selection: ClipboardSelection) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .client
+            .lock()
+            .await
+            .get_clipboard(GetClipboardRequest {
+                selection: selection as i32,
+            })
+            .await?;
+        Ok(response.into_inner().data)
+    }
+
+    /// Copy `data` to the daemon's clipboard `selection`, so programs in its
+    /// sessions can read it with OSC 52 while no client is attached
+    pub async fn set_clipboard(&self, selection: ClipboardSelection, data: Vec<u8>) -> Result<()> {
+        self.client
+            .lock()
+            .await
+            .set_clipboard(SetClipboardRequest {
+                selection: selection as i32,
+                data,
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// List a remote directory over the session's SSH connection.
+    ///
+    /// An empty `path` lists the shell's reported working directory (OSC 1337
+    /// CurrentDir), falling back to the remote home directory. Only native
+    /// SSH sessions support this.
+    pub async fn list_remote_dir(&self, path: &str) -> Result<ListRemoteDirResponse> {
+        let response = self
+            .client
+            .lock()
+            .await
+            .list_remote_dir(ListRemoteDirRequest {
+                session_id: self.session_id.clone(),
+                path: path.to_string(),
+            })
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Download a remote file to `local_path`, returning the bytes copied.
+    pub async fn download_file(&self, remote_path: &str, local_path: &Path) -> Result<u64> {
+        let mut file = tokio::fs::File::create(local_path).await?;
+        let mut offset = 0u64;
+        loop {
+            let response = self
+                .client
+                .lock()
+                .await
+                .read_remote_file(ReadRemoteFileRequest {
+                    session_id: self.session_id.clone(),
+                    path: remote_path.to_string(),
+                    offset,
+                    length: TRANSFER_CHUNK as u32,
+                })
+                .await?
+                .into_inner();
+            file.write_all(&response.data).await?;
+            offset += response.data.len() as u64;
+            if response.data.len() < TRANSFER_CHUNK || offset >= response.size {
+                break;
+            }
+        }
+        file.flush().await?;
+        Ok(offset)
+    }
+
+    /// Upload `local_path` to `remote_path`, replacing any existing file.
+    /// Returns the bytes copied.
+    pub async fn upload_file(&self, local_path: &Path, remote_path: &str) -> Result<u64> {
+        let mut file = tokio::fs::File::open(local_path).await?;
+        let mut buf = vec![0u8; TRANSFER_CHUNK];
+        let mut offset = 0u64;
+        loop {
+            let n = file.read(&mut buf).await?;
+            // Always send the first chunk so empty files are still created
+            if n == 0 && offset > 0 {
+                break;
+            }
+            self.client
+                .lock()
+                .await
+                .write_remote_file(WriteRemoteFileRequest {
+                    session_id: self.session_id.clone(),
+                    path: remote_path.to_string(),
+                    offset,
+                    data: buf[..n].to_vec(),
+                    truncate: offset == 0,
+                })
+                .await?;
+            offset += n as u64;
+            if n == 0 {
+                break;
+            }
+        }
+        Ok(offset)
+    }
+
     /// Clear the bell/alert state on this session.
     pub async fn clear_alert(&self) -> Result<()> {
         self.client
@@ -243,6 +418,34 @@ impl SessionHandle {
         Ok(())
     }
 
+    /// Record the session output to an asciicast file written by the daemon
+    pub async fn start_recording(&self, path: &str) -> Result<()> {
+        self.client
+            .lock()
+            .await
+            .start_recording(StartRecordingRequest {
+                session_id: self.session_id.clone(),
+                path: path.to_string(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Finish the recording; returns false if none was running
+    pub async fn stop_recording(&self) -> Result<bool> {
+        let response = self
+            .client
+            .lock()
+            .await
+            .stop_recording(StopRecordingRequest {
+                session_id: self.session_id.clone(),
+            })
+            .await?;
+
+        Ok(response.into_inner().was_recording)
+    }
+
     /// Subscribe to raw PTY output
     pub async fn stream_output(&self) -> Result<tonic::Streaming<OutputChunk>> {
         let response = self
@@ -5,9 +5,9 @@
 use clap::Parser;
 use objc2::rc::Retained;
 use objc2::runtime::ProtocolObject;
-use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
+use objc2::{define_class, msg_send, sel, DefinedClass, MainThreadOnly};
 use objc2_app_kit::{
-    NSApplication, NSApplicationActivationPolicy, NSApplicationDelegate, NSWindow,
+    NSApplication, NSApplicationActivationPolicy, NSApplicationDelegate, NSMenuItem, NSWindow,
     NSWindowStyleMask,
 };
 use objc2_foundation::{
@@ -16,6 +16,8 @@ use objc2_foundation::{
 use std::path::PathBuf;
 
 use cterm_app::config::{load_config, Config};
+use cterm_app::control::ControlRequest;
+use cterm_app::session::WindowSessionState;
 use cterm_ui::theme::Theme;
 
 use crate::menu;
@@ -49,9 +51,26 @@ pub struct Args {
     #[arg(short = 't', long = "title")]
     pub title: Option<String>,
 
+    /// Play back an asciicast (.cast) or ttyrec recording in a read-only tab
+    #[arg(long, value_name = "FILE")]
+    pub play: Option<PathBuf>,
+
+    /// Open the tabs and panes of a `[[workspaces]]` entry, in the running
+    /// cterm if there is one
+    #[arg(long, value_name = "NAME")]
+    pub workspace: Option<String>,
+
+    /// Start with the default settings and theme, ignoring the configuration
+    #[arg(long)]
+    pub safe_mode: bool,
+
     /// Receive upgrade state from a file path (internal use)
     #[arg(long, hide = true)]
     pub upgrade_state: Option<String>,
+
+    /// Run a subcommand instead of opening a window
+    #[command(subcommand)]
+    pub subcommand: Option<cterm_app::cli::Command>,
 }
 
 /// Global application arguments (accessible from window creation)
@@ -109,6 +128,10 @@ define_class!(
 
             let mtm = MainThreadMarker::from(self);
 
+            // Runs after the windows below are up, ending the startup that
+            // a crash would otherwise leave marked
+            dispatch2::Queue::main().exec_async(cterm_app::safe_mode::finish_startup);
+
             // Check for seamless upgrade state
             if let Some(upgrade_state) = take_upgrade_state() {
                 log::info!(
@@ -185,6 +208,41 @@ define_class!(
                 }
             }
 
+            // `cterm --play` opens a window with just the recording
+            if let Some(ref path) = get_args().play {
+                match CtermWindow::playback(mtm, &self.ivars().config, &self.ivars().theme, path) {
+                    Ok(window) => {
+                        self.ivars().windows.borrow_mut().push(window.clone());
+                        window.makeKeyAndOrderFront(None);
+                        #[allow(deprecated)]
+                        NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+                        return;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to open recording {:?}: {}", path, e);
+                        crate::dialogs::show_error(
+                            mtm,
+                            None,
+                            "Playback Failed",
+                            &format!("Failed to open {}: {}", path.display(), e),
+                        );
+                    }
+                }
+            }
+
+            // `cterm --workspace` opens a window with just the workspace
+            if let Some(ref name) = get_args().workspace {
+                if let Some(window) =
+                    CtermWindow::workspace(mtm, &self.ivars().config, &self.ivars().theme, name)
+                {
+                    self.ivars().windows.borrow_mut().push(window.clone());
+                    window.makeKeyAndOrderFront(None);
+                    #[allow(deprecated)]
+                    NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+                    return;
+                }
+            }
+
             // Try to reconnect to existing daemon sessions
             {
                 let config = self.ivars().config.clone();
@@ -237,6 +295,23 @@ define_class!(
                 }
             }
 
+            // Reopen the windows of the last session
+            let restored = cterm_app::session::windows_to_restore(&self.ivars().config);
+            if !restored.is_empty() {
+                log::info!("Restoring {} window(s) from the last session", restored.len());
+                for state in &restored {
+                    if let Some(window) =
+                        CtermWindow::restore(mtm, &self.ivars().config, &self.ivars().theme, state)
+                    {
+                        self.ivars().windows.borrow_mut().push(window.clone());
+                        window.makeKeyAndOrderFront(None);
+                    }
+                }
+                #[allow(deprecated)]
+                NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+                return;
+            }
+
             // Normal startup - create the main window
             log::debug!("Creating main window...");
             let window = CtermWindow::new(mtm, &self.ivars().config, &self.ivars().theme);
@@ -256,6 +331,34 @@ define_class!(
             log::debug!("App activated");
         }
 
+        #[unsafe(method(applicationDidBecomeActive:))]
+        fn application_did_become_active(&self, _notification: &NSNotification) {
+            crate::secure_input::app_did_become_active();
+        }
+
+        #[unsafe(method(applicationWillResignActive:))]
+        fn application_will_resign_active(&self, _notification: &NSNotification) {
+            crate::secure_input::app_will_resign_active();
+        }
+
+        #[unsafe(method(applicationWillTerminate:))]
+        fn application_will_terminate(&self, _notification: &NSNotification) {
+            // Save the open windows for the next launch, or the last one
+            // closed when there are none
+            let saved = self
+                .tab_groups()
+                .iter()
+                .filter_map(|group| {
+                    Some(WindowSessionState {
+                        geometry: group.first()?.geometry(),
+                        tabs: group.iter().filter_map(|w| w.session_tab()).collect(),
+                        active_tab: group.iter().position(|w| w.isKeyWindow()).unwrap_or(0),
+                    })
+                })
+                .collect();
+            cterm_app::session::save_at_exit(saved, &self.ivars().config);
+        }
+
         #[unsafe(method(applicationShouldTerminateAfterLastWindowClosed:))]
         fn should_terminate_after_last_window_closed(&self, _sender: &NSApplication) -> bool {
             true
@@ -342,6 +445,19 @@ define_class!(
             });
         }
 
+        #[unsafe(method(toggleSecureKeyboardEntry:))]
+        fn action_toggle_secure_keyboard_entry(&self, sender: Option<&NSMenuItem>) {
+            let mtm = MainThreadMarker::from(self);
+            let enabled = !crate::secure_input::is_enabled();
+            crate::secure_input::set_enabled(mtm, enabled);
+            if let Some(item) = sender {
+                item.setState(enabled as isize);
+            }
+            for window in self.ivars().windows.borrow().iter() {
+                window.set_secure_input_indicator(enabled);
+            }
+        }
+
         #[unsafe(method(showTabTemplates:))]
         fn action_show_tab_templates(&self, _sender: Option<&objc2::runtime::AnyObject>) {
             let mtm = MainThreadMarker::from(self);
@@ -362,6 +478,12 @@ define_class!(
             crate::update_dialog::check_for_updates_sync(mtm);
         }
 
+        #[unsafe(method(showUsageStats:))]
+        fn action_show_usage_stats(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let mtm = MainThreadMarker::from(self);
+            crate::usage_stats_window::show_usage_stats(mtm);
+        }
+
         #[unsafe(method(showQuickOpen:))]
         fn action_show_quick_open(&self, _sender: Option<&objc2::runtime::AnyObject>) {
             let mtm = MainThreadMarker::from(self);
@@ -683,6 +805,15 @@ define_class!(
             });
         }
 
+        /// Show the New Serial Tab dialog and open the chosen port
+        #[unsafe(method(newSerialTab:))]
+        fn action_new_serial_tab(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(serial) = crate::dialogs::show_serial_dialog(mtm) {
+                self.open_template(&cterm_app::config::StickyTabConfig::serial(serial));
+            }
+        }
+
         /// Show SSH connection dialog
         #[unsafe(method(sshConnect:))]
         fn action_ssh_connect(&self, _sender: Option<&objc2::runtime::AnyObject>) {
@@ -859,6 +990,14 @@ define_class!(
             crate::remotes_dialog::show_remotes_dialog(mtm, config);
         }
 
+        /// Show the theme editor for the current theme
+        #[unsafe(method(editTheme:))]
+        fn action_edit_theme(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let mtm = MainThreadMarker::from(self);
+            let theme = get_theme(&self.ivars().config);
+            crate::theme_editor::show_theme_editor(mtm, theme);
+        }
+
         /// Called by windows when they close to remove from tracking
         #[unsafe(method(windowDidClose:))]
         fn window_did_close(&self, window: &CtermWindow) {
@@ -1088,6 +1227,10 @@ impl AppDelegate {
             }
         }
 
+        // Take what the template leaves unset from its profile
+        let (template, profile) = template.resolve_profile(&self.ivars().config);
+        let template = &template;
+
         // Prepare working directory (clone from git if needed)
         if let Some(ref working_dir) = template.working_directory {
             if let Err(e) =
@@ -1121,6 +1264,10 @@ impl AppDelegate {
                 .iter()
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect(),
+            serial: template.serial.as_ref().map(|s| s.to_serial_params()),
+            startup_commands: template.startup_commands.clone(),
+            clear_env: !template.inherit_env,
+            scrollback_lines: profile.as_ref().and_then(|p| p.scrollback_lines),
             ..Default::default()
         };
 
@@ -1166,11 +1313,21 @@ impl AppDelegate {
                     Some(template_name),
                     template_color,
                     template_bg_color,
+                    template.command_notify,
+                    template.permissions,
                     remote,
                     None,
+                    None,
+                    profile,
+                    None,
                 );
             } else {
-                // No key window — create a new standalone daemon-backed window
+                // No key window — create a new standalone daemon-backed
+                // window, with the profile's look for all of it
+                let (config, theme) = match profile {
+                    Some(ref profile) => (profile.apply(&config), profile.theme(&theme)),
+                    None => (config, theme),
+                };
                 let window = CtermWindow::new_daemon(
                     mtm,
                     &config,
@@ -1179,6 +1336,8 @@ impl AppDelegate {
                     template_name,
                     template_color,
                     template_bg_color,
+                    template.command_notify,
+                    template.permissions,
                 );
                 self.ivars().windows.borrow_mut().push(window.clone());
                 window.makeKeyAndOrderFront(None);
@@ -1186,6 +1345,38 @@ impl AppDelegate {
         }
     }
 
+    /// Windows grouped by the native tab group they are in, in tab order
+    fn tab_groups(&self) -> Vec<Vec<Retained<CtermWindow>>> {
+        let windows = self.ivars().windows.borrow();
+        let mut groups: Vec<Vec<Retained<CtermWindow>>> = Vec::new();
+        for window in windows.iter() {
+            let seen = groups
+                .iter()
+                .flatten()
+                .any(|w| Retained::as_ptr(w) == Retained::as_ptr(window));
+            if seen {
+                continue;
+            }
+            let tabbed: Option<Retained<objc2_foundation::NSArray<NSWindow>>> =
+                unsafe { msg_send![&**window, tabbedWindows] };
+            let group = match tabbed {
+                Some(tabbed_windows) => tabbed_windows
+                    .iter()
+                    .filter_map(|nswin| {
+                        let nswin_ptr = Retained::as_ptr(&nswin);
+                        windows
+                            .iter()
+                            .find(|w| Retained::as_ptr(*w) as *const NSWindow == nswin_ptr)
+                            .cloned()
+                    })
+                    .collect(),
+                None => vec![window.clone()],
+            };
+            groups.push(group);
+        }
+        groups
+    }
+
     /// Perform a seamless relaunch, preserving all windows and tabs
     ///
     /// Since all terminal sessions live in the ctermd daemon, upgrading only
@@ -1302,7 +1493,19 @@ fn get_theme(config: &Config) -> Theme {
 /// Run the native macOS application
 pub fn run() {
     // Parse command-line arguments first
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    // Subcommands like `cterm config get` run without a window
+    if let Some(command) = args.subcommand.take() {
+        std::process::exit(cterm_app::cli::run(command));
+    }
+
+    // A running cterm opens the workspace in its own window
+    if let Some(ref name) = args.workspace {
+        if cterm_app::workspace::open_in_running(name) {
+            std::process::exit(0);
+        }
+    }
 
     // Initialize logging with capture buffer for in-app log viewing
     crate::log_capture::init();
@@ -1363,12 +1566,80 @@ pub fn run() {
         std::process::exit(exit_code);
     }
 
+    let safe_mode = args.safe_mode;
+
     // Store args for later access
     let _ = APP_ARGS.set(args);
 
+    // Offer safe mode when the previous launch crashed before showing a window
+    let crashed = cterm_app::safe_mode::begin_startup();
+    if safe_mode {
+        cterm_app::safe_mode::enable();
+    } else if crashed {
+        let mtm = MainThreadMarker::new().expect("Must be called on main thread");
+        if confirm_safe_mode(mtm) {
+            cterm_app::safe_mode::enable();
+        }
+    }
+
     run_app_internal();
 }
 
+/// Ask whether to start in safe mode after a crash during startup
+fn confirm_safe_mode(mtm: MainThreadMarker) -> bool {
+    use objc2_app_kit::{NSAlert, NSAlertFirstButtonReturn, NSAlertStyle};
+
+    let alert = NSAlert::new(mtm);
+    alert.setAlertStyle(NSAlertStyle::Warning);
+    alert.setMessageText(&NSString::from_str(cterm_app::safe_mode::RECOVERY_TITLE));
+    alert.setInformativeText(&NSString::from_str(
+        &cterm_app::safe_mode::recovery_message(),
+    ));
+    alert.addButtonWithTitle(&NSString::from_str("Start in Safe Mode"));
+    alert.addButtonWithTitle(&NSString::from_str("Start Normally"));
+    alert.runModal() == NSAlertFirstButtonReturn
+}
+
+/// Carry out a `cterm new-tab`, `cterm new-window` or `cterm --workspace`
+/// request
+fn handle_control_request(mtm: MainThreadMarker, request: ControlRequest) {
+    let app = NSApplication::sharedApplication(mtm);
+    // Front to back, whether or not cterm is the active application
+    let front = app.orderedWindows().iter().find(|window| {
+        let is_cterm: bool =
+            unsafe { msg_send![&**window, isKindOfClass: objc2::class!(CtermWindow)] };
+        is_cterm
+    });
+
+    match (request, front) {
+        (ControlRequest::NewTab { directory }, Some(window)) => {
+            let cterm_window: &CtermWindow =
+                unsafe { &*(&*window as *const NSWindow as *const CtermWindow) };
+            cterm_window.create_new_tab_in(directory.map(|d| d.to_string_lossy().into_owned()));
+        }
+        (ControlRequest::OpenWorkspace { name }, Some(window)) => {
+            let cterm_window: &CtermWindow =
+                unsafe { &*(&*window as *const NSWindow as *const CtermWindow) };
+            cterm_window.open_workspace(&name);
+        }
+        (ControlRequest::OpenWorkspace { name }, None) => {
+            let config = load_config().unwrap_or_default();
+            let theme = cterm_app::resolve_theme(&config);
+            if let Some(window) = CtermWindow::workspace(mtm, &config, &theme, &name) {
+                if let Some(delegate) = app.delegate() {
+                    let _: () = unsafe { msg_send![&*delegate, registerWindow: &*window] };
+                }
+                window.makeKeyAndOrderFront(None);
+            }
+        }
+        // A new window, or a tab when no window is open
+        _ => unsafe {
+            app.sendAction_to_from(sel!(newWindow:), None, None);
+        },
+    }
+    app.activateIgnoringOtherApps(true);
+}
+
 /// Internal function to run the Cocoa application
 /// Called by both run() and upgrade_receiver after setup
 pub fn run_app_internal() {
@@ -1385,6 +1656,8 @@ pub fn run_app_internal() {
         log::warn!("Failed to load config, using defaults: {}", e);
         Config::default()
     });
+    cterm_app::usage_stats::configure(&config);
+    cterm_app::clipboard_history::configure(&config);
 
     // Get theme
     let theme = get_theme(&config);
@@ -1403,6 +1676,16 @@ pub fn run_app_internal() {
     let menu_bar = menu::create_menu_bar(mtm);
     app.setMainMenu(Some(&menu_bar));
 
+    // `cterm new-tab` and `cterm new-window` from a shell
+    let result = cterm_app::control::listen(|request| {
+        dispatch2::Queue::main().exec_async(move || {
+            handle_control_request(unsafe { MainThreadMarker::new_unchecked() }, request);
+        });
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to open the control socket: {}", e);
+    }
+
     log::info!("Starting main run loop");
 
     // Run the main event loop
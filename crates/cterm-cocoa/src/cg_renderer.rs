@@ -9,11 +9,15 @@ use objc2::{class, msg_send};
 use objc2_app_kit::{NSFont, NSFontManager, NSFontTraitMask, NSGraphicsContext};
 use objc2_foundation::{MainThreadMarker, NSPoint, NSRect, NSSize, NSString};
 
-use cterm_core::cell::CellAttrs;
-use cterm_core::color::{Color, Rgb};
+use cterm_core::cell::{AttrOverrides, Cell, CellAttrs};
+use cterm_core::color::{Color, ColorPalette, Rgb};
 use cterm_core::drcs::DrcsGlyph;
+use cterm_core::hints::HintMode;
 use cterm_core::Terminal;
 use cterm_core::TerminalImage;
+use cterm_ui::gutter::{gutter_color, GUTTER_WIDTH};
+use cterm_ui::scroll_marks::{scroll_mark_ticks, MARK_HEIGHT};
+use cterm_ui::status::{StatusSegment, StatusStrip};
 use cterm_ui::theme::Theme;
 
 /// CoreGraphics renderer for terminal display
@@ -23,12 +27,26 @@ pub struct CGRenderer {
     italic_font: Retained<NSFont>,
     bold_italic_font: Retained<NSFont>,
     theme: Theme,
+    /// Theme palette in the working color space (sRGB or Display P3)
+    palette: ColorPalette,
+    /// Whether colors are drawn in Display P3 rather than sRGB
+    wide_gamut: bool,
     cell_width: f64,
     cell_height: f64,
     /// Whether bold text uses bright ANSI colors
     bold_is_bright: bool,
     /// Optional background color override (from template)
     background_override: Option<Rgb>,
+    /// Opacity of the default background (1.0 is opaque)
+    opacity: f64,
+    /// Minimum text contrast ratio (presentation mode); 1.0 disables
+    minimum_contrast: f64,
+    /// Replacements for blink, concealed and dim text
+    attr_overrides: AttrOverrides,
+    /// Whether to draw the scrollbar overlay
+    show_scrollbar: bool,
+    /// Whether to draw the exit status gutter beside finished commands
+    command_gutter: bool,
 }
 
 impl CGRenderer {
@@ -69,13 +87,102 @@ impl CGRenderer {
             italic_font,
             bold_italic_font,
             theme: theme.clone(),
+            palette: theme.colors.clone(),
+            wide_gamut: false,
             cell_width,
             cell_height,
             bold_is_bright,
             background_override: None,
+            opacity: 1.0,
+            minimum_contrast: 1.0,
+            attr_overrides: AttrOverrides::default(),
+            show_scrollbar: true,
+            command_gutter: true,
         }
     }
 
+    /// Rebuild the fonts at a new size, keeping theme and display options
+    pub fn set_font_size(&mut self, mtm: MainThreadMarker, font_size: f64) {
+        let font_name = self.font.fontName().to_string();
+        self.set_font(mtm, &font_name, font_size);
+    }
+
+    /// Rebuild the fonts from another family at the current size
+    pub fn set_font_family(&mut self, mtm: MainThreadMarker, font_name: &str) {
+        self.set_font(mtm, font_name, self.font_size());
+    }
+
+    fn set_font(&mut self, mtm: MainThreadMarker, font_name: &str, font_size: f64) {
+        let mut resized = Self::new(mtm, font_name, font_size, &self.theme, self.bold_is_bright);
+        resized.background_override = self.background_override;
+        resized.opacity = self.opacity;
+        resized.minimum_contrast = self.minimum_contrast;
+        resized.attr_overrides = self.attr_overrides;
+        resized.show_scrollbar = self.show_scrollbar;
+        resized.command_gutter = self.command_gutter;
+        resized.set_wide_gamut(self.wide_gamut);
+        *self = resized;
+    }
+
+    /// Family of the text font, e.g. "Menlo"
+    pub fn font_family(&self) -> String {
+        match self.font.familyName() {
+            Some(family) => family.to_string(),
+            None => self.font.fontName().to_string(),
+        }
+    }
+
+    /// Current font size in points
+    pub fn font_size(&self) -> f64 {
+        self.font.pointSize()
+    }
+
+    /// Configure presentation mode rendering (contrast floor, scrollbar)
+    pub fn set_presentation(&mut self, minimum_contrast: f64, show_scrollbar: bool) {
+        self.minimum_contrast = minimum_contrast;
+        self.show_scrollbar = show_scrollbar;
+    }
+
+    /// Set how blink, concealed and dim text are drawn
+    pub fn set_attr_overrides(&mut self, overrides: AttrOverrides) {
+        self.attr_overrides = overrides;
+    }
+
+    /// Set whether finished commands get an exit status bar on the left edge
+    pub fn set_command_gutter(&mut self, enabled: bool) {
+        self.command_gutter = enabled;
+    }
+
+    pub(crate) fn shows_command_gutter(&self) -> bool {
+        self.command_gutter
+    }
+
+    pub(crate) fn attr_overrides(&self) -> AttrOverrides {
+        self.attr_overrides
+    }
+
+    /// Set the opacity of the default background (1.0 is opaque)
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = opacity;
+    }
+
+    pub(crate) fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    /// Draw in Display P3 (for wide-gamut screens) instead of sRGB
+    ///
+    /// Every color handed to AppKit is tagged with the working space, so
+    /// sRGB colors are converted rather than stretched to the panel's gamut.
+    pub fn set_wide_gamut(&mut self, wide_gamut: bool) {
+        self.wide_gamut = wide_gamut;
+        self.palette = self.theme.palette_for(wide_gamut);
+    }
+
+    pub(crate) fn is_wide_gamut(&self) -> bool {
+        self.wide_gamut
+    }
+
     /// Set an optional background color override (hex string like "#1a1b26")
     pub fn set_background_override(&mut self, color: Option<&str>) {
         self.background_override = color.and_then(|hex| {
@@ -132,6 +239,8 @@ impl CGRenderer {
 
             for col in 0..cols {
                 if let Some(cell) = screen.get_cell_with_scrollback(absolute_line, col) {
+                    let cell = self.attr_overrides.apply_to(cell);
+                    let cell = cell.as_ref();
                     // Skip wide char spacers - background handled by the wide cell
                     if cell.is_wide_spacer() {
                         continue;
@@ -142,56 +251,7 @@ impl CGRenderer {
 
                     // Check if cell is selected
                     let is_selected = screen.is_selected(absolute_line, col);
-
-                    // XOR selection with INVERSE attribute to determine if colors should be inverted
-                    let is_inverted = cell.attrs.contains(CellAttrs::INVERSE) != is_selected;
-
-                    // Apply bold_is_bright: map base ANSI fg colors to bright variants
-                    let fg = if self.bold_is_bright && cell.attrs.contains(CellAttrs::BOLD) {
-                        match cell.fg {
-                            Color::Ansi(ansi) => Color::Ansi(ansi.bright()),
-                            Color::Indexed(idx @ 0..=7) => Color::Indexed(idx + 8),
-                            other => other,
-                        }
-                    } else {
-                        cell.fg
-                    };
-
-                    // Determine actual foreground and background colors
-                    let (fg_color, bg_color) = if is_inverted {
-                        // Inverted: swap foreground and background
-                        let fg_rgb = if cell.bg.is_default() {
-                            self.theme.colors.background
-                        } else {
-                            self.color_to_rgb(&cell.bg)
-                        };
-                        let bg_rgb = if fg.is_default() {
-                            self.theme.colors.foreground
-                        } else {
-                            self.color_to_rgb(&fg)
-                        };
-                        (fg_rgb, bg_rgb)
-                    } else {
-                        (self.color_to_rgb(&fg), self.color_to_rgb(&cell.bg))
-                    };
-
-                    // Apply dim (SGR 2) — halve foreground brightness
-                    let fg_color = if cell.attrs.contains(CellAttrs::DIM) {
-                        Rgb::new(
-                            (fg_color.r as f64 * 0.5) as u8,
-                            (fg_color.g as f64 * 0.5) as u8,
-                            (fg_color.b as f64 * 0.5) as u8,
-                        )
-                    } else {
-                        fg_color
-                    };
-
-                    // Cornflower blue for hyperlinks with default foreground
-                    let fg_color = if cell.hyperlink.is_some() && fg.is_default() && !is_inverted {
-                        Rgb::new(100, 149, 237)
-                    } else {
-                        fg_color
-                    };
+                    let (fg_color, bg_color) = self.cell_colors(cell, is_selected);
 
                     // Use double width for wide characters
                     let char_width = if cell.is_wide() {
@@ -201,35 +261,32 @@ impl CGRenderer {
                     };
 
                     // Draw cell background if not default or if selected/inverted
-                    if !cell.bg.is_default() || is_inverted || is_selected {
+                    if let Some(bg_color) = bg_color {
                         self.draw_cell_background_sized(x, y, char_width, &bg_color);
                     }
 
                     // Draw character
-                    if cell.c != ' ' && cell.c != '\0' {
+                    if cell.c != ' ' && cell.c != '\0' && !cell.attrs.contains(CellAttrs::HIDDEN) {
                         // Check if this should be a DRCS glyph
                         if let Some(glyph) = screen.get_drcs_for_char(cell.c) {
                             self.draw_drcs_glyph(glyph, x, y, &fg_color);
                         } else {
-                            self.draw_char_rgb(cell.c, x, y, &fg_color, cell.attrs);
+                            let clip_width = screen.modes.ascii_art_safe.then_some(char_width);
+                            self.draw_text_rgb(
+                                &cell.grapheme(),
+                                x,
+                                y,
+                                &fg_color,
+                                cell.attrs,
+                                clip_width,
+                            );
                         }
                     }
 
                     // Draw underlines (regular underline attributes or hyperlinks)
                     let has_hyperlink = cell.hyperlink.is_some();
                     if cell.attrs.has_underline() || has_hyperlink {
-                        // Use hyperlink color (blue) for hyperlinks, otherwise use underline color or fg
-                        let underline_color = if has_hyperlink {
-                            Rgb {
-                                r: 100,
-                                g: 149,
-                                b: 237,
-                            } // Cornflower blue for hyperlinks
-                        } else if let Some(ref uc) = cell.underline_color {
-                            self.color_to_rgb(uc)
-                        } else {
-                            fg_color
-                        };
+                        let underline_color = self.underline_color(cell, fg_color);
 
                         self.draw_underline(
                             x,
@@ -277,13 +334,133 @@ impl CGRenderer {
             self.draw_cursor(cursor_x, cursor_y, cursor_width);
         }
 
+        if self.command_gutter {
+            self.draw_command_gutter(screen);
+        }
+
         // Draw scrollbar overlay when there is scrollback content
         let scrollback_len = screen.scrollback().len();
-        if scrollback_len > 0 {
+        if self.show_scrollbar && scrollback_len > 0 {
             self.draw_scrollbar(screen, bounds);
         }
     }
 
+    /// Resolve the colors a cell is drawn with: the foreground, and the
+    /// background fill (`None` when the cell shows the default background)
+    pub(crate) fn cell_colors(&self, cell: &Cell, is_selected: bool) -> (Rgb, Option<Rgb>) {
+        // XOR selection with INVERSE attribute to determine if colors should be inverted
+        let is_inverted = cell.attrs.contains(CellAttrs::INVERSE) != is_selected;
+
+        // Apply bold_is_bright: map base ANSI fg colors to bright variants
+        let fg = if self.bold_is_bright && cell.attrs.contains(CellAttrs::BOLD) {
+            match cell.fg {
+                Color::Ansi(ansi) => Color::Ansi(ansi.bright()),
+                Color::Indexed(idx @ 0..=7) => Color::Indexed(idx + 8),
+                other => other,
+            }
+        } else {
+            cell.fg
+        };
+
+        // Determine actual foreground and background colors
+        let (fg_color, bg_color) = if is_inverted {
+            // Inverted: swap foreground and background
+            let fg_rgb = if cell.bg.is_default() {
+                self.palette.background
+            } else {
+                self.color_to_rgb(&cell.bg)
+            };
+            let bg_rgb = if fg.is_default() {
+                self.palette.foreground
+            } else {
+                self.color_to_rgb(&fg)
+            };
+            (fg_rgb, bg_rgb)
+        } else {
+            (self.color_to_rgb(&fg), self.color_to_rgb(&cell.bg))
+        };
+
+        // Apply dim (SGR 2) — halve foreground brightness
+        let fg_color = if cell.attrs.contains(CellAttrs::DIM) {
+            Rgb::new(
+                (fg_color.r as f64 * 0.5) as u8,
+                (fg_color.g as f64 * 0.5) as u8,
+                (fg_color.b as f64 * 0.5) as u8,
+            )
+        } else {
+            fg_color
+        };
+
+        // Cornflower blue for hyperlinks with default foreground
+        let fg_color = if cell.hyperlink.is_some() && fg.is_default() && !is_inverted {
+            self.srgb(Rgb::new(100, 149, 237))
+        } else {
+            fg_color
+        };
+
+        let bg_color = (!cell.bg.is_default() || is_inverted || is_selected).then_some(bg_color);
+
+        let fg_color = if self.minimum_contrast > 1.0 {
+            let behind = bg_color.unwrap_or_else(|| self.background());
+            fg_color.with_min_contrast(&behind, self.minimum_contrast)
+        } else {
+            fg_color
+        };
+
+        (fg_color, bg_color)
+    }
+
+    /// Color of a cell's underline: blue for hyperlinks, otherwise the
+    /// underline color (SGR 58) or the foreground
+    pub(crate) fn underline_color(&self, cell: &Cell, fg_color: Rgb) -> Rgb {
+        if cell.hyperlink.is_some() {
+            self.srgb(Rgb::new(100, 149, 237)) // Cornflower blue for hyperlinks
+        } else if let Some(ref uc) = cell.underline_color {
+            self.color_to_rgb(uc)
+        } else {
+            fg_color
+        }
+    }
+
+    /// Default background (template override or theme)
+    pub(crate) fn background(&self) -> Rgb {
+        self.background_override
+            .map(|rgb| self.srgb(rgb))
+            .unwrap_or(self.palette.background)
+    }
+
+    /// The resolved theme palette in the working color space
+    pub(crate) fn palette(&self) -> &ColorPalette {
+        &self.palette
+    }
+
+    pub(crate) fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub(crate) fn shows_scrollbar(&self) -> bool {
+        self.show_scrollbar
+    }
+
+    /// Draw the exit status bars of finished commands along the left edge
+    fn draw_command_gutter(&self, screen: &cterm_core::Screen) {
+        for block in screen.command_blocks() {
+            let color = gutter_color(&block, &self.palette);
+            let rect = NSRect::new(
+                NSPoint::new(0.0, block.top as f64 * self.cell_height),
+                NSSize::new(
+                    GUTTER_WIDTH,
+                    (block.bottom - block.top) as f64 * self.cell_height - 1.0,
+                ),
+            );
+            unsafe {
+                let ns_color = self.ns_color_alpha(color.r, color.g, color.b, 0.8);
+                let _: () = msg_send![&*ns_color, setFill];
+                let _: () = msg_send![class!(NSBezierPath), fillRect: rect];
+            }
+        }
+    }
+
     /// Draw a thin scrollbar overlay on the right edge of the terminal
     fn draw_scrollbar(&self, screen: &cterm_core::Screen, bounds: NSRect) {
         let scrollback_len = screen.scrollback().len();
@@ -312,6 +489,17 @@ impl CGRenderer {
         // But our rendering uses flipped coordinates (y=0 at top), so:
         let thumb_y = (1.0 - fraction) * scrollable;
 
+        // Prompt, error and search marks under the thumb
+        let marks = screen.marks();
+        for (y, color) in scroll_mark_ticks(&marks, total_lines, view_height, &self.palette) {
+            let rect = NSRect::new(NSPoint::new(bar_x, y), NSSize::new(bar_width, MARK_HEIGHT));
+            unsafe {
+                let ns_color = self.ns_color_alpha(color.r, color.g, color.b, 0.9);
+                let _: () = msg_send![&*ns_color, setFill];
+                let _: () = msg_send![class!(NSBezierPath), fillRect: rect];
+            }
+        }
+
         // Draw thumb with rounded corners
         let opacity = if screen.scroll_offset > 0 { 0.5 } else { 0.25 };
         let Some(context) = NSGraphicsContext::currentContext() else {
@@ -428,6 +616,8 @@ impl CGRenderer {
         }
 
         extern "C" {
+            static kCGColorSpaceSRGB: *const std::ffi::c_void;
+
             fn CGDataProviderCreateWithData(
                 info: *mut std::ffi::c_void,
                 data: *const u8,
@@ -457,7 +647,7 @@ impl CGRenderer {
 
             fn CGImageRelease(image: *mut std::ffi::c_void);
             fn CGDataProviderRelease(provider: *mut std::ffi::c_void);
-            fn CGColorSpaceCreateDeviceRGB() -> *mut std::ffi::c_void;
+            fn CGColorSpaceCreateWithName(name: *const std::ffi::c_void) -> *mut std::ffi::c_void;
             fn CGColorSpaceRelease(color_space: *mut std::ffi::c_void);
             fn CGContextSaveGState(context: *mut std::ffi::c_void);
             fn CGContextRestoreGState(context: *mut std::ffi::c_void);
@@ -469,7 +659,9 @@ impl CGRenderer {
             let data_ptr = image.data.as_ptr();
             let data_len = image.data.len();
 
-            let cg_color_space = CGColorSpaceCreateDeviceRGB();
+            // Image pixels are sRGB; tagging them lets CoreGraphics match
+            // them to the display instead of treating them as device values
+            let cg_color_space = CGColorSpaceCreateWithName(kCGColorSpaceSRGB);
             if cg_color_space.is_null() {
                 log::warn!("Failed to create CGColorSpace");
                 return;
@@ -542,12 +734,9 @@ impl CGRenderer {
 
     fn draw_background(&self, bounds: NSRect) {
         // Use background override if set, otherwise use theme background
-        let bg = self
-            .background_override
-            .as_ref()
-            .unwrap_or(&self.theme.colors.background);
+        let bg = self.background();
         unsafe {
-            let color = Self::ns_color(bg.r, bg.g, bg.b);
+            let color = self.ns_color_alpha(bg.r, bg.g, bg.b, self.opacity);
             let _: () = msg_send![&*color, setFill];
             let _: () = msg_send![class!(NSBezierPath), fillRect: bounds];
         }
@@ -565,16 +754,48 @@ impl CGRenderer {
     fn draw_cell_background_sized(&self, x: f64, y: f64, width: f64, rgb: &Rgb) {
         let rect = NSRect::new(NSPoint::new(x, y), NSSize::new(width, self.cell_height));
         unsafe {
-            let ns_color = Self::ns_color(rgb.r, rgb.g, rgb.b);
+            let ns_color = self.ns_color(rgb.r, rgb.g, rgb.b);
             let _: () = msg_send![&*ns_color, setFill];
             let _: () = msg_send![class!(NSBezierPath), fillRect: rect];
         }
     }
 
-    fn draw_char_rgb(&self, ch: char, x: f64, y: f64, rgb: &Rgb, attrs: CellAttrs) {
-        let text = NSString::from_str(&ch.to_string());
+    /// Draw the grapheme cluster of one cell
+    ///
+    /// With `clip_width` set (ASCII-art-safe mode), ligatures are disabled and the
+    /// glyph is clipped to its cell so it can't spill into the next column.
+    fn draw_text_rgb(
+        &self,
+        text: &str,
+        x: f64,
+        y: f64,
+        rgb: &Rgb,
+        attrs: CellAttrs,
+        clip_width: Option<f64>,
+    ) {
+        let text = NSString::from_str(text);
+        let dict = self.glyph_attributes(attrs, rgb, clip_width.is_some());
+
+        unsafe {
+            if let Some(width) = clip_width {
+                let _: () = msg_send![class!(NSGraphicsContext), saveGraphicsState];
+                let clip = NSRect::new(NSPoint::new(x, y), NSSize::new(width, self.cell_height));
+                let _: () = msg_send![class!(NSBezierPath), clipRect: clip];
+            }
 
-        let font = match (
+            // In a flipped view, drawAtPoint places text with point as top-left of the text
+            let point = NSPoint::new(x, y);
+            let _: () = msg_send![&*text, drawAtPoint: point, withAttributes: &*dict];
+
+            if clip_width.is_some() {
+                let _: () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+            }
+        }
+    }
+
+    /// Font for a cell's bold/italic attributes
+    fn font_for(&self, attrs: CellAttrs) -> &NSFont {
+        match (
             attrs.contains(CellAttrs::BOLD),
             attrs.contains(CellAttrs::ITALIC),
         ) {
@@ -582,31 +803,43 @@ impl CGRenderer {
             (true, false) => &self.bold_font,
             (false, true) => &self.italic_font,
             (false, false) => &self.font,
-        };
+        }
+    }
 
+    /// Text attributes for drawing a cell's character, optionally with
+    /// ligatures disabled
+    pub(crate) fn glyph_attributes(
+        &self,
+        attrs: CellAttrs,
+        rgb: &Rgb,
+        no_ligatures: bool,
+    ) -> Retained<AnyObject> {
+        let font = self.font_for(attrs);
         unsafe {
-            let ns_color = Self::ns_color(rgb.r, rgb.g, rgb.b);
+            let ns_color = self.ns_color(rgb.r, rgb.g, rgb.b);
 
             // Use the actual string keys for NSAttributedString attributes
             let font_key = NSString::from_str("NSFont");
             let color_key = NSString::from_str("NSColor");
 
-            let keys: [&AnyObject; 2] = [
+            let ligature_key = NSString::from_str("NSLigature");
+            let no_ligatures_value: Retained<AnyObject> =
+                msg_send![class!(NSNumber), numberWithInt: 0i32];
+
+            let keys: [&AnyObject; 3] = [
                 std::mem::transmute::<&NSString, &AnyObject>(&font_key),
                 std::mem::transmute::<&NSString, &AnyObject>(&color_key),
+                std::mem::transmute::<&NSString, &AnyObject>(&ligature_key),
             ];
-            let values: [&AnyObject; 2] = [&**font, &*ns_color];
+            let values: [&AnyObject; 3] = [font, &*ns_color, &*no_ligatures_value];
+            let count = if no_ligatures { 3usize } else { 2usize };
 
-            let dict: Retained<AnyObject> = msg_send![
+            msg_send![
                 class!(NSDictionary),
                 dictionaryWithObjects: values.as_ptr(),
                 forKeys: keys.as_ptr(),
-                count: 2usize
-            ];
-
-            // In a flipped view, drawAtPoint places text with point as top-left of the text
-            let point = NSPoint::new(x, y);
-            let _: () = msg_send![&*text, drawAtPoint: point, withAttributes: &*dict];
+                count: count
+            ]
         }
     }
 
@@ -617,7 +850,7 @@ impl CGRenderer {
         let scale_y = self.cell_height / glyph.height as f64;
 
         unsafe {
-            let ns_color = Self::ns_color(rgb.r, rgb.g, rgb.b);
+            let ns_color = self.ns_color(rgb.r, rgb.g, rgb.b);
             let _: () = msg_send![&*ns_color, setFill];
 
             // Draw each pixel of the glyph as a small rectangle
@@ -638,10 +871,10 @@ impl CGRenderer {
     }
 
     fn draw_cursor(&self, x: f64, y: f64, width: f64) {
-        let cursor_color = &self.theme.colors.cursor;
+        let cursor_color = &self.palette.cursor;
         let rect = NSRect::new(NSPoint::new(x, y), NSSize::new(width, self.cell_height));
         unsafe {
-            let color = Self::ns_color_alpha(cursor_color.r, cursor_color.g, cursor_color.b, 0.7);
+            let color = self.ns_color_alpha(cursor_color.r, cursor_color.g, cursor_color.b, 0.7);
             let _: () = msg_send![&*color, setFill];
             let _: () = msg_send![class!(NSBezierPath), fillRect: rect];
         }
@@ -661,7 +894,7 @@ impl CGRenderer {
         let thickness = 1.0;
 
         unsafe {
-            let color = Self::ns_color(rgb.r, rgb.g, rgb.b);
+            let color = self.ns_color(rgb.r, rgb.g, rgb.b);
             let _: () = msg_send![&*color, setStroke];
 
             // For hyperlinks or regular underline, draw a simple line
@@ -731,7 +964,7 @@ impl CGRenderer {
     fn draw_strikethrough(&self, x: f64, y: f64, width: f64, rgb: &Rgb) {
         let strike_y = y + self.cell_height * 0.5;
         unsafe {
-            let color = Self::ns_color(rgb.r, rgb.g, rgb.b);
+            let color = self.ns_color(rgb.r, rgb.g, rgb.b);
             let _: () = msg_send![&*color, setStroke];
             let path: Retained<AnyObject> = msg_send![class!(NSBezierPath), bezierPath];
             let _: () = msg_send![&*path, setLineWidth: 1.0f64];
@@ -745,7 +978,7 @@ impl CGRenderer {
     fn draw_overline(&self, x: f64, y: f64, width: f64, rgb: &Rgb) {
         let overline_y = y + 1.0;
         unsafe {
-            let color = Self::ns_color(rgb.r, rgb.g, rgb.b);
+            let color = self.ns_color(rgb.r, rgb.g, rgb.b);
             let _: () = msg_send![&*color, setStroke];
             let path: Retained<AnyObject> = msg_send![class!(NSBezierPath), bezierPath];
             let _: () = msg_send![&*path, setLineWidth: 1.0f64];
@@ -755,27 +988,43 @@ impl CGRenderer {
         }
     }
 
-    fn ns_color(r: u8, g: u8, b: u8) -> Retained<AnyObject> {
-        Self::ns_color_alpha(r, g, b, 1.0)
+    fn ns_color(&self, r: u8, g: u8, b: u8) -> Retained<AnyObject> {
+        self.ns_color_alpha(r, g, b, 1.0)
     }
 
-    fn ns_color_alpha(r: u8, g: u8, b: u8, a: f64) -> Retained<AnyObject> {
+    /// An `NSColor` for a working-space color
+    fn ns_color_alpha(&self, r: u8, g: u8, b: u8, a: f64) -> Retained<AnyObject> {
+        let (r, g, b) = Rgb::new(r, g, b).to_f64();
         unsafe {
-            msg_send![
-                class!(NSColor),
-                colorWithRed: r as f64 / 255.0,
-                green: g as f64 / 255.0,
-                blue: b as f64 / 255.0,
-                alpha: a
-            ]
+            if self.wide_gamut {
+                msg_send![class!(NSColor), colorWithDisplayP3Red: r, green: g, blue: b, alpha: a]
+            } else {
+                msg_send![class!(NSColor), colorWithSRGBRed: r, green: g, blue: b, alpha: a]
+            }
+        }
+    }
+
+    /// An `NSColor` for an sRGB color, converted to the working space
+    fn ns_srgb_color(&self, r: u8, g: u8, b: u8, a: f64) -> Retained<AnyObject> {
+        let rgb = self.srgb(Rgb::new(r, g, b));
+        self.ns_color_alpha(rgb.r, rgb.g, rgb.b, a)
+    }
+
+    /// Convert an sRGB color (true color, 256-color cube, UI chrome) into the
+    /// working color space
+    fn srgb(&self, rgb: Rgb) -> Rgb {
+        if self.wide_gamut {
+            rgb.srgb_to_display_p3()
+        } else {
+            rgb
         }
     }
 
     fn color_to_rgb(&self, color: &Color) -> Rgb {
         match color {
-            Color::Default => self.theme.colors.foreground,
-            Color::Rgb(rgb) => *rgb,
-            Color::Ansi(ansi) => self.theme.colors.ansi[*ansi as usize],
+            Color::Default => self.palette.foreground,
+            Color::Rgb(rgb) => self.srgb(*rgb),
+            Color::Ansi(ansi) => self.palette.ansi[*ansi as usize],
             Color::Indexed(idx) => self.index_to_rgb(*idx),
         }
     }
@@ -783,19 +1032,19 @@ impl CGRenderer {
     fn index_to_rgb(&self, idx: u8) -> Rgb {
         match idx {
             // First 16 are ANSI colors
-            0..=15 => self.theme.colors.ansi[idx as usize],
+            0..=15 => self.palette.ansi[idx as usize],
             // 16-231 are a 6x6x6 color cube
             16..=231 => {
                 let n = idx - 16;
                 let b = (n % 6) * 51;
                 let g = ((n / 6) % 6) * 51;
                 let r = (n / 36) * 51;
-                Rgb::new(r, g, b)
+                self.srgb(Rgb::new(r, g, b))
             }
             // 232-255 are grayscale
             232..=255 => {
                 let gray = (idx - 232) * 10 + 8;
-                Rgb::new(gray, gray, gray)
+                self.srgb(Rgb::new(gray, gray, gray))
             }
         }
     }
@@ -803,6 +1052,7 @@ impl CGRenderer {
     /// Update theme colors
     pub fn set_theme(&mut self, theme: &Theme) {
         self.theme = theme.clone();
+        self.palette = theme.palette_for(self.wide_gamut);
     }
 
     /// Render IME marked text (composition text) at cursor position
@@ -825,7 +1075,7 @@ impl CGRenderer {
         );
         unsafe {
             // Use a light yellow background for marked text
-            let bg_color = Self::ns_color_alpha(255, 255, 200, 0.9);
+            let bg_color = self.ns_srgb_color(255, 255, 200, 0.9);
             let _: () = msg_send![&*bg_color, setFill];
             let _: () = msg_send![class!(NSBezierPath), fillRect: bg_rect];
         }
@@ -834,7 +1084,7 @@ impl CGRenderer {
         let ns_text = NSString::from_str(text);
         unsafe {
             // Use dark text color for marked text
-            let text_color = Self::ns_color(0, 0, 0);
+            let text_color = self.ns_color(0, 0, 0);
 
             let font_key = NSString::from_str("NSFont");
             let color_key = NSString::from_str("NSColor");
@@ -862,9 +1112,161 @@ impl CGRenderer {
             NSSize::new(text_width, 2.0),
         );
         unsafe {
-            let underline_color = Self::ns_color(0, 100, 200);
+            let underline_color = self.ns_srgb_color(0, 100, 200, 1.0);
             let _: () = msg_send![&*underline_color, setFill];
             let _: () = msg_send![class!(NSBezierPath), fillRect: underline_rect];
         }
     }
+
+    /// Text attributes (regular font, given color) for overlay text
+    fn overlay_text_attributes(&self, color: &Rgb) -> Retained<AnyObject> {
+        unsafe {
+            let text_color = self.ns_color(color.r, color.g, color.b);
+
+            let font_key = NSString::from_str("NSFont");
+            let color_key = NSString::from_str("NSColor");
+
+            let keys: [&AnyObject; 2] = [
+                std::mem::transmute::<&NSString, &AnyObject>(&font_key),
+                std::mem::transmute::<&NSString, &AnyObject>(&color_key),
+            ];
+            let values: [&AnyObject; 2] = [&*self.font, &*text_color];
+
+            msg_send![
+                class!(NSDictionary),
+                dictionaryWithObjects: values.as_ptr(),
+                forKeys: keys.as_ptr(),
+                count: 2usize
+            ]
+        }
+    }
+
+    /// Draw the status strip over the bottom row
+    pub fn render_status(&self, segments: &[StatusSegment], bounds: NSRect) {
+        if segments.is_empty() {
+            return;
+        }
+        let strip_y = (bounds.size.height - self.cell_height).max(0.0);
+        unsafe {
+            let bg = StatusStrip::background(&self.theme);
+            let bg_color = self.ns_srgb_color(bg.r, bg.g, bg.b, 0.9);
+            let _: () = msg_send![&*bg_color, setFill];
+            let strip_rect = NSRect::new(
+                NSPoint::new(0.0, strip_y),
+                NSSize::new(bounds.size.width, self.cell_height),
+            );
+            let _: () = msg_send![class!(NSBezierPath), fillRect: strip_rect];
+        }
+
+        let mut x = self.cell_width / 2.0;
+        for segment in segments {
+            let ns_text = NSString::from_str(&segment.text);
+            let dict = self.overlay_text_attributes(&self.srgb(segment.color(&self.theme)));
+            unsafe {
+                let size: NSSize = msg_send![&*ns_text, sizeWithAttributes: &*dict];
+                let point = NSPoint::new(x, strip_y);
+                let _: () = msg_send![&*ns_text, drawAtPoint: point, withAttributes: &*dict];
+                x += size.width + self.cell_width * 2.0;
+            }
+        }
+    }
+
+    /// Draw the session badge (OSC 1337 SetBadgeFormat) as large
+    /// translucent text in the top-right corner
+    pub fn render_badge(&self, text: &str, bounds: NSRect) {
+        if text.is_empty() {
+            return;
+        }
+        unsafe {
+            let size = (self.font.pointSize() * 2.5).min(bounds.size.height / 4.0);
+            let font: Retained<NSFont> =
+                msg_send![class!(NSFont), boldSystemFontOfSize: size.max(1.0)];
+            let fg = &self.palette.foreground;
+            let color = self.ns_color_alpha(fg.r, fg.g, fg.b, 0.2);
+
+            let font_key = NSString::from_str("NSFont");
+            let color_key = NSString::from_str("NSColor");
+            let keys: [&AnyObject; 2] = [
+                std::mem::transmute::<&NSString, &AnyObject>(&font_key),
+                std::mem::transmute::<&NSString, &AnyObject>(&color_key),
+            ];
+            let values: [&AnyObject; 2] = [&*font, &*color];
+            let dict: Retained<AnyObject> = msg_send![
+                class!(NSDictionary),
+                dictionaryWithObjects: values.as_ptr(),
+                forKeys: keys.as_ptr(),
+                count: 2usize
+            ];
+
+            let margin = self.cell_width * 2.0;
+            let mut y = self.cell_height / 2.0;
+            // One line per badge line, each right-aligned
+            for line in text.lines() {
+                let ns_text = NSString::from_str(line);
+                let line_size: NSSize = msg_send![&*ns_text, sizeWithAttributes: &*dict];
+                let x = (bounds.size.width - line_size.width - margin).max(0.0);
+                let _: () =
+                    msg_send![&*ns_text, drawAtPoint: NSPoint::new(x, y), withAttributes: &*dict];
+                y += line_size.height;
+            }
+        }
+    }
+
+    /// Draw hint mode labels over the start of each target, with the
+    /// target underlined
+    pub fn render_hints(&self, mode: &HintMode) {
+        let cursor = self.theme.cursor.color;
+        let dict = self.overlay_text_attributes(&self.theme.cursor.text_color);
+        for hint in mode.visible() {
+            let x = hint.col as f64 * self.cell_width;
+            let y = hint.row as f64 * self.cell_height;
+            let label = &hint.label[mode.typed().len()..];
+            let underline = NSRect::new(
+                NSPoint::new(x, y + self.cell_height - 2.0),
+                NSSize::new(hint.width as f64 * self.cell_width, 2.0),
+            );
+            let label_rect = NSRect::new(
+                NSPoint::new(x, y),
+                NSSize::new(
+                    label.chars().count() as f64 * self.cell_width,
+                    self.cell_height,
+                ),
+            );
+            let ns_text = NSString::from_str(label);
+            unsafe {
+                let color = self.ns_color_alpha(cursor.r, cursor.g, cursor.b, 1.0);
+                let _: () = msg_send![&*color, setFill];
+                let _: () = msg_send![class!(NSBezierPath), fillRect: underline];
+                let _: () = msg_send![class!(NSBezierPath), fillRect: label_rect];
+                let _: () =
+                    msg_send![&*ns_text, drawAtPoint: NSPoint::new(x, y), withAttributes: &*dict];
+            }
+        }
+    }
+
+    /// Draw the presentation keystroke overlay in the bottom-right corner
+    pub fn render_keystrokes(&self, text: &str, bounds: NSRect) {
+        let ns_text = NSString::from_str(text);
+        let dict = self.overlay_text_attributes(&self.palette.background);
+        unsafe {
+            let text_size: NSSize = msg_send![&*ns_text, sizeWithAttributes: &*dict];
+            let padding = self.cell_height / 2.0;
+            let box_width = text_size.width + padding * 2.0;
+            let box_height = text_size.height + padding;
+            let box_x = (bounds.size.width - box_width - padding).max(0.0);
+            let box_y = (bounds.size.height - box_height - padding).max(0.0);
+
+            let fg = &self.palette.foreground;
+            let bg_color = self.ns_color_alpha(fg.r, fg.g, fg.b, 0.85);
+            let _: () = msg_send![&*bg_color, setFill];
+            let box_rect = NSRect::new(
+                NSPoint::new(box_x, box_y),
+                NSSize::new(box_width, box_height),
+            );
+            let _: () = msg_send![class!(NSBezierPath), fillRect: box_rect];
+
+            let point = NSPoint::new(box_x + padding, box_y + padding / 2.0);
+            let _: () = msg_send![&*ns_text, drawAtPoint: point, withAttributes: &*dict];
+        }
+    }
 }
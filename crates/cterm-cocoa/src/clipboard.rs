@@ -43,6 +43,22 @@ pub fn set_html(html: &str, plain_text: &str) {
     }
 }
 
+/// Set both RTF and plain text to the system clipboard
+///
+/// Apps that take styled text (Keynote, Pages, Mail) paste the RTF version
+/// with its colors; others get the plain text.
+pub fn set_rtf(rtf: &str, plain_text: &str) {
+    use objc2_app_kit::NSPasteboardTypeRTF;
+
+    let pasteboard = unsafe { NSPasteboard::generalPasteboard() };
+
+    unsafe {
+        pasteboard.clearContents();
+        pasteboard.setString_forType(&NSString::from_str(rtf), NSPasteboardTypeRTF);
+        pasteboard.setString_forType(&NSString::from_str(plain_text), NSPasteboardTypeString);
+    }
+}
+
 /// Clipboard wrapper implementing cterm-ui traits
 pub struct Clipboard;
 
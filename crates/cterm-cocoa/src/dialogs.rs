@@ -3,8 +3,8 @@
 //! Native macOS dialogs using NSAlert and other AppKit dialogs.
 
 use objc2_app_kit::{
-    NSAlert, NSAlertFirstButtonReturn, NSAlertStyle, NSModalResponseOK, NSSavePanel, NSTextField,
-    NSWindow,
+    NSAlert, NSAlertFirstButtonReturn, NSAlertSecondButtonReturn, NSAlertStyle, NSModalResponseOK,
+    NSOpenPanel, NSSavePanel, NSTextField, NSWindow,
 };
 use objc2_foundation::{MainThreadMarker, NSPoint, NSSize, NSString, NSURL};
 use std::path::PathBuf;
@@ -87,6 +87,138 @@ pub fn show_input(
     }
 }
 
+/// Show the New Serial Tab dialog
+///
+/// Lists the serial ports found on this machine; port and baud rate also
+/// accept values that are not listed. Returns None if cancelled.
+pub fn show_serial_dialog(mtm: MainThreadMarker) -> Option<cterm_app::config::SerialTabConfig> {
+    use cterm_app::config::SerialTabConfig;
+    use cterm_core::{FlowControl, Parity, SerialConfig};
+    use objc2::rc::Retained;
+    use objc2_app_kit::{
+        NSComboBox, NSPopUpButton, NSStackView, NSUserInterfaceLayoutOrientation, NSView,
+    };
+
+    let defaults = SerialTabConfig::default();
+
+    let alert = NSAlert::new(mtm);
+    alert.setAlertStyle(NSAlertStyle::Informational);
+    alert.setMessageText(&NSString::from_str("New Serial Tab"));
+    alert.setInformativeText(&NSString::from_str(
+        "Choose a serial port and its line settings:",
+    ));
+    alert.addButtonWithTitle(&NSString::from_str("Open"));
+    alert.addButtonWithTitle(&NSString::from_str("Cancel"));
+
+    let form = unsafe {
+        let stack = NSStackView::new(mtm);
+        stack.setFrameSize(NSSize::new(320.0, 200.0));
+        stack
+    };
+    form.setOrientation(NSUserInterfaceLayoutOrientation::Vertical);
+    form.setSpacing(8.0);
+
+    let add_row = |label: &str, control: &NSView| {
+        let row = unsafe { NSStackView::new(mtm) };
+        row.setOrientation(NSUserInterfaceLayoutOrientation::Horizontal);
+        row.setSpacing(8.0);
+        let label_view = NSTextField::labelWithString(&NSString::from_str(label), mtm);
+        unsafe {
+            row.addArrangedSubview(&label_view);
+            row.addArrangedSubview(control);
+            form.addArrangedSubview(&row);
+        }
+    };
+    let combo = |values: &[String], current: &str| -> Retained<NSComboBox> {
+        unsafe {
+            let field = NSComboBox::new(mtm);
+            field.setFrameSize(NSSize::new(200.0, 26.0));
+            field.setUsesDataSource(false);
+            field.setCompletes(true);
+            for value in values {
+                field.addItemWithObjectValue(&NSString::from_str(value));
+            }
+            field.setStringValue(&NSString::from_str(current));
+            field
+        }
+    };
+    let popup = |titles: &[&str], selected: usize| -> Retained<NSPopUpButton> {
+        unsafe {
+            let popup = NSPopUpButton::new(mtm);
+            for title in titles {
+                popup.addItemWithTitle(&NSString::from_str(title));
+            }
+            popup.selectItemAtIndex(selected as isize);
+            popup
+        }
+    };
+
+    let ports = cterm_core::available_ports();
+    let port_field = combo(&ports, ports.first().map_or("", String::as_str));
+    add_row("Port:", &port_field);
+
+    let rates: Vec<String> = SerialConfig::BAUD_RATES
+        .iter()
+        .map(|r| r.to_string())
+        .collect();
+    let baud_field = combo(&rates, &defaults.baud_rate.to_string());
+    add_row("Baud rate:", &baud_field);
+
+    let data_bits_popup = popup(&["5", "6", "7", "8"], defaults.data_bits as usize - 5);
+    add_row("Data bits:", &data_bits_popup);
+
+    let parity_names = Parity::ALL.map(Parity::name);
+    let parity_popup = popup(&parity_names, 0);
+    add_row("Parity:", &parity_popup);
+
+    let stop_bits_popup = popup(&["1", "2"], defaults.stop_bits as usize - 1);
+    add_row("Stop bits:", &stop_bits_popup);
+
+    let flow_names = FlowControl::ALL.map(FlowControl::name);
+    let flow_popup = popup(&flow_names, 0);
+    add_row("Flow control:", &flow_popup);
+
+    alert.setAccessoryView(Some(&form));
+
+    if alert.runModal() != NSAlertFirstButtonReturn {
+        return None;
+    }
+
+    let path = port_field.stringValue().to_string().trim().to_string();
+    let baud_rate = baud_field
+        .stringValue()
+        .to_string()
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|&rate| rate > 0);
+    let Some(baud_rate) = baud_rate.filter(|_| !path.is_empty()) else {
+        show_error(
+            mtm,
+            None,
+            "New Serial Tab",
+            "Enter a serial port and a numeric baud rate.",
+        );
+        return None;
+    };
+
+    let selected = |popup: &NSPopUpButton| popup.indexOfSelectedItem().max(0) as usize;
+    Some(SerialTabConfig {
+        path,
+        baud_rate,
+        data_bits: selected(&data_bits_popup) as u8 + 5,
+        parity: Parity::ALL
+            .get(selected(&parity_popup))
+            .copied()
+            .unwrap_or_default(),
+        stop_bits: selected(&stop_bits_popup) as u8 + 1,
+        flow_control: FlowControl::ALL
+            .get(selected(&flow_popup))
+            .copied()
+            .unwrap_or_default(),
+    })
+}
+
 /// Show about dialog
 pub fn show_about(mtm: MainThreadMarker) {
     let alert = NSAlert::new(mtm);
@@ -142,6 +274,47 @@ pub fn show_save_panel(
     }
 }
 
+/// Show an open panel titled `title` for choosing one file
+///
+/// Returns the selected path, or None if cancelled.
+pub fn show_open_panel(mtm: MainThreadMarker, title: &str) -> Option<PathBuf> {
+    let panel = NSOpenPanel::openPanel(mtm);
+    panel.setTitle(&NSString::from_str(title));
+    panel.setCanChooseFiles(true);
+    panel.setCanChooseDirectories(false);
+    panel.setAllowsMultipleSelection(false);
+
+    if panel.runModal() == NSModalResponseOK {
+        panel
+            .URL()
+            .and_then(|url| url.path().map(|path| PathBuf::from(path.to_string())))
+    } else {
+        None
+    }
+}
+
+/// Show an open panel titled `title` for the files a remote program asked
+/// for (`rz` or `kermit -r`)
+///
+/// Returns the selected paths, empty if cancelled.
+pub fn show_send_files_panel(mtm: MainThreadMarker, title: &str) -> Vec<PathBuf> {
+    let panel = NSOpenPanel::openPanel(mtm);
+    panel.setTitle(&NSString::from_str(title));
+    panel.setPrompt(Some(&NSString::from_str("Send")));
+    panel.setCanChooseFiles(true);
+    panel.setCanChooseDirectories(false);
+    panel.setAllowsMultipleSelection(true);
+
+    if panel.runModal() != NSModalResponseOK {
+        return Vec::new();
+    }
+    panel
+        .URLs()
+        .iter()
+        .filter_map(|url| url.path().map(|path| PathBuf::from(path.to_string())))
+        .collect()
+}
+
 /// Result of the file drop dialog
 pub enum FileDropChoice {
     PastePath,
@@ -264,6 +437,78 @@ impl cterm_ui::traits::Dialogs for Dialogs {
     }
 }
 
+/// Characters of a paste shown in its confirmation dialog
+const PASTE_PREVIEW_CHARS: usize = 2000;
+
+/// Ask before pasting `text` into a program that hasn't enabled bracketed
+/// paste, showing it with line breaks and control characters highlighted
+///
+/// Returns true if the user chose to paste.
+pub fn confirm_paste(
+    mtm: MainThreadMarker,
+    text: &str,
+    warning: &cterm_app::paste::PasteWarning,
+) -> bool {
+    use objc2::msg_send;
+    use objc2::runtime::AnyObject;
+    use objc2_app_kit::{NSScrollView, NSTextView};
+    use objc2_foundation::{NSAttributedString, NSDictionary, NSRect};
+
+    let alert = NSAlert::new(mtm);
+    alert.setAlertStyle(NSAlertStyle::Warning);
+    alert.setMessageText(&NSString::from_str("Paste text?"));
+    alert.setInformativeText(&NSString::from_str(&warning.message()));
+    alert.addButtonWithTitle(&NSString::from_str("Cancel"));
+    alert.addButtonWithTitle(&NSString::from_str("Paste"));
+
+    let frame = NSRect::new(NSPoint::ZERO, NSSize::new(480.0, 200.0));
+    let scroll = unsafe {
+        let scroll = NSScrollView::initWithFrame(NSScrollView::alloc(mtm), frame);
+        scroll.setHasVerticalScroller(true);
+        scroll.setAutohidesScrollers(true);
+        scroll
+    };
+    let preview = unsafe {
+        let view = NSTextView::initWithFrame(NSTextView::alloc(mtm), frame);
+        view.setEditable(false);
+        view.setSelectable(true);
+
+        let font: *mut AnyObject = msg_send![
+            objc2::class!(NSFont),
+            monospacedSystemFontOfSize: 11.0f64,
+            weight: 0.0f64
+        ];
+        let text_color: *mut AnyObject = msg_send![objc2::class!(NSColor), textColor];
+        let highlight: *mut AnyObject = msg_send![objc2::class!(NSColor), systemYellowColor];
+        let black: *mut AnyObject = msg_send![objc2::class!(NSColor), blackColor];
+        let font_key = NSString::from_str("NSFont");
+        let fg_key = NSString::from_str("NSColor");
+        let bg_key = NSString::from_str("NSBackgroundColor");
+        let plain = NSDictionary::from_slices(&[&*font_key, &*fg_key], &[&*font, &*text_color]);
+        let marked = NSDictionary::from_slices(
+            &[&*font_key, &*fg_key, &*bg_key],
+            &[&*font, &*black, &*highlight],
+        );
+
+        let storage: *mut AnyObject = msg_send![&*view, textStorage];
+        for (run, highlighted) in cterm_app::paste::paste_preview(text, PASTE_PREVIEW_CHARS) {
+            let attrs = if highlighted { &marked } else { &plain };
+            let run = NSAttributedString::initWithString_attributes(
+                mtm.alloc(),
+                &NSString::from_str(&run),
+                Some(attrs),
+            );
+            let _: () = msg_send![storage, appendAttributedString: &*run];
+        }
+        view
+    };
+    scroll.setDocumentView(Some(&preview));
+    alert.setAccessoryView(Some(&scroll));
+
+    // Cancel is the first button, so Return doesn't paste by accident
+    alert.runModal() == NSAlertSecondButtonReturn
+}
+
 /// Result of the color picker dialog
 pub enum ColorPickerResult {
     /// User selected a color (hex string like "#FF5500")
@@ -31,20 +31,27 @@ pub mod file_transfer;
 pub mod log_capture;
 pub mod log_viewer;
 pub mod menu;
+pub mod metal_renderer;
 pub mod notification_bar;
 pub mod preferences;
+pub mod quick_look;
 pub mod quick_open;
 pub mod remotes_dialog;
+pub mod secure_input;
 pub mod ssh_prompt;
 pub mod tab_bar;
 pub mod tab_templates;
 pub mod terminal_view;
+pub mod theme_editor;
+pub mod touch_bar;
 pub mod update_dialog;
 #[cfg(unix)]
 pub mod upgrade_receiver;
+pub mod usage_stats_window;
 pub mod window;
 
 mod keycode;
+mod link_popover;
 mod mouse;
 
 pub use app::run;
@@ -0,0 +1,121 @@
+//! Hover preview popover for links and image paths
+//!
+//! Builds the NSPopover showing a page's title and icon, or an image's
+//! thumbnail. The terminal view decides when to show and close it.
+
+use objc2::rc::Retained;
+use objc2_app_kit::{
+    NSBitmapImageRep, NSFont, NSImage, NSImageView, NSLayoutAttribute, NSLineBreakMode, NSPopover,
+    NSPopoverBehavior, NSStackView, NSTextField, NSUserInterfaceLayoutOrientation, NSView,
+    NSViewController,
+};
+use objc2_foundation::{MainThreadMarker, NSEdgeInsets, NSRect, NSRectEdge, NSSize, NSString};
+
+use cterm_app::link_preview::LinkPreview;
+use cterm_core::DecodedImage;
+
+/// Widest the labels get before truncating
+const MAX_LABEL_WIDTH: f64 = 320.0;
+
+/// Show `preview` in a popover pointing at `rect` of `view`
+pub fn show(view: &NSView, preview: &LinkPreview, rect: NSRect) -> Retained<NSPopover> {
+    let mtm = MainThreadMarker::from(view);
+
+    let stack = unsafe {
+        let stack = NSStackView::new(mtm);
+        stack.setOrientation(NSUserInterfaceLayoutOrientation::Vertical);
+        stack.setAlignment(NSLayoutAttribute::Leading);
+        stack.setSpacing(6.0);
+        stack.setEdgeInsets(NSEdgeInsets {
+            top: 8.0,
+            left: 8.0,
+            bottom: 8.0,
+            right: 8.0,
+        });
+        stack
+    };
+
+    if let Some(image) = preview.image.as_ref().and_then(|i| ns_image(i, mtm)) {
+        let image_view = NSImageView::imageViewWithImage(&image, mtm);
+        unsafe {
+            stack.addArrangedSubview(&image_view);
+        }
+    }
+    if let Some(ref title) = preview.title {
+        let label = label(title, NSLineBreakMode::ByTruncatingTail, mtm);
+        label.setFont(Some(
+            &NSFont::boldSystemFontOfSize(NSFont::systemFontSize()),
+        ));
+        unsafe {
+            stack.addArrangedSubview(&label);
+        }
+    }
+    let target = label(
+        &preview.target.label(),
+        NSLineBreakMode::ByTruncatingMiddle,
+        mtm,
+    );
+    target.setFont(Some(&NSFont::systemFontOfSize(
+        NSFont::smallSystemFontSize(),
+    )));
+    unsafe {
+        stack.addArrangedSubview(&target);
+    }
+
+    let controller = NSViewController::new(mtm);
+    controller.setView(&stack);
+
+    let popover = NSPopover::new(mtm);
+    popover.setContentViewController(Some(&controller));
+    popover.setContentSize(stack.fittingSize());
+    // Closed by the view when the pointer moves off the target
+    popover.setBehavior(NSPopoverBehavior::ApplicationDefined);
+    popover.setAnimates(false);
+    popover.showRelativeToRect_ofView_preferredEdge(rect, view, NSRectEdge::MinY);
+    popover
+}
+
+/// Single-line label that truncates past [`MAX_LABEL_WIDTH`]
+fn label(text: &str, mode: NSLineBreakMode, mtm: MainThreadMarker) -> Retained<NSTextField> {
+    let label = NSTextField::labelWithString(&NSString::from_str(text), mtm);
+    label.setLineBreakMode(mode);
+    label.setMaximumNumberOfLines(1);
+    label
+        .widthAnchor()
+        .constraintLessThanOrEqualToConstant(MAX_LABEL_WIDTH)
+        .setActive(true);
+    label
+}
+
+/// NSImage of an RGBA image
+fn ns_image(image: &DecodedImage, mtm: MainThreadMarker) -> Option<Retained<NSImage>> {
+    let width = image.width as isize;
+    let height = image.height as isize;
+    unsafe {
+        let rep = NSBitmapImageRep::initWithBitmapDataPlanes_pixelsWide_pixelsHigh_bitsPerSample_samplesPerPixel_hasAlpha_isPlanar_colorSpaceName_bytesPerRow_bitsPerPixel(
+            mtm.alloc(),
+            std::ptr::null_mut(), // planes - will allocate
+            width,
+            height,
+            8, // bits per sample
+            4, // samples per pixel (RGBA)
+            true, // has alpha
+            false, // not planar
+            &NSString::from_str("NSDeviceRGBColorSpace"),
+            width * 4, // bytes per row
+            32, // bits per pixel
+        )?;
+        let bitmap_data = rep.bitmapData();
+        if bitmap_data.is_null() {
+            return None;
+        }
+        std::ptr::copy_nonoverlapping(image.data.as_ptr(), bitmap_data, image.data.len());
+
+        let ns_image = NSImage::initWithSize(
+            mtm.alloc(),
+            NSSize::new(image.width as f64, image.height as f64),
+        );
+        ns_image.addRepresentation(&rep);
+        Some(ns_image)
+    }
+}
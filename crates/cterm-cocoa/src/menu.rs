@@ -10,6 +10,8 @@ use objc2::sel;
 use objc2_app_kit::{NSEventModifierFlags, NSMenu, NSMenuItem};
 use objc2_foundation::{MainThreadMarker, NSString};
 
+use cterm_ui::events::{Action, PaneDirection};
+
 // Thread-local storage for the debug menu item (must be accessed on main thread)
 thread_local! {
     static DEBUG_MENU_ITEM: RefCell<Option<Retained<NSMenuItem>>> = const { RefCell::new(None) };
@@ -96,6 +98,25 @@ fn create_app_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         NSEventModifierFlags::Command,
     ));
 
+    menu.addItem(&create_menu_item(
+        mtm,
+        "Edit Theme...",
+        Some(sel!(editTheme:)),
+        "",
+    ));
+
+    menu.addItem(&NSMenuItem::separatorItem(mtm));
+
+    // Secure Keyboard Entry (checked while enabled)
+    let secure_item = create_menu_item(
+        mtm,
+        "Secure Keyboard Entry",
+        Some(sel!(toggleSecureKeyboardEntry:)),
+        "",
+    );
+    secure_item.setState(crate::secure_input::is_enabled() as isize);
+    menu.addItem(&secure_item);
+
     menu.addItem(&NSMenuItem::separatorItem(mtm));
 
     // Services submenu (standard macOS)
@@ -160,6 +181,42 @@ fn create_file_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         NSEventModifierFlags::Command,
     ));
 
+    // New Tab with Profile submenu, tagged with the profile's index
+    let config = cterm_app::config::load_config().unwrap_or_default();
+    let profiles = config.profiles;
+    if !profiles.is_empty() {
+        let profiles_submenu = NSMenu::new(mtm);
+        profiles_submenu.setTitle(&NSString::from_str("New Tab with Profile"));
+        for (i, profile) in profiles.iter().enumerate() {
+            let item = NSMenuItem::new(mtm);
+            item.setTitle(&NSString::from_str(&profile.name));
+            unsafe { item.setAction(Some(sel!(newProfileTab:))) };
+            item.setTag(i as isize);
+            profiles_submenu.addItem(&item);
+        }
+        let profiles_item = NSMenuItem::new(mtm);
+        profiles_item.setTitle(&NSString::from_str("New Tab with Profile"));
+        profiles_item.setSubmenu(Some(&profiles_submenu));
+        menu.addItem(&profiles_item);
+    }
+
+    // Workspaces submenu, tagged with the workspace's index
+    if !config.workspaces.is_empty() {
+        let workspaces_submenu = NSMenu::new(mtm);
+        workspaces_submenu.setTitle(&NSString::from_str("Workspaces"));
+        for (i, workspace) in config.workspaces.iter().enumerate() {
+            let item = NSMenuItem::new(mtm);
+            item.setTitle(&NSString::from_str(&workspace.name));
+            unsafe { item.setAction(Some(sel!(openWorkspace:))) };
+            item.setTag(i as isize);
+            workspaces_submenu.addItem(&item);
+        }
+        let workspaces_item = NSMenuItem::new(mtm);
+        workspaces_item.setTitle(&NSString::from_str("Workspaces"));
+        workspaces_item.setSubmenu(Some(&workspaces_submenu));
+        menu.addItem(&workspaces_item);
+    }
+
     // New Window
     menu.addItem(&create_menu_item_with_key(
         mtm,
@@ -169,6 +226,14 @@ fn create_file_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         NSEventModifierFlags::Command,
     ));
 
+    // New Serial Tab
+    menu.addItem(&create_menu_item(
+        mtm,
+        "New Serial Tab...",
+        Some(sel!(newSerialTab:)),
+        "",
+    ));
+
     menu.addItem(&NSMenuItem::separatorItem(mtm));
 
     // Quick Open Template
@@ -264,6 +329,32 @@ fn create_file_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
 
     menu.addItem(&NSMenuItem::separatorItem(mtm));
 
+    // Save the scrollback and screen with their colors
+    menu.addItem(&create_menu_item(
+        mtm,
+        "Save Buffer As...",
+        Some(sel!(saveBuffer:)),
+        "",
+    ));
+
+    // Record the output to an asciicast file, or stop recording
+    menu.addItem(&create_menu_item(
+        mtm,
+        "Start/Stop Recording...",
+        Some(sel!(toggleRecording:)),
+        "",
+    ));
+
+    // Play an asciicast or ttyrec recording in a read-only tab
+    menu.addItem(&create_menu_item(
+        mtm,
+        "Play Recording...",
+        Some(sel!(playRecording:)),
+        "",
+    ));
+
+    menu.addItem(&NSMenuItem::separatorItem(mtm));
+
     // Close Tab
     menu.addItem(&create_menu_item_with_key(
         mtm,
@@ -281,6 +372,15 @@ fn create_file_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         "",
     ));
 
+    // Reopen Closed Tab
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Reopen Closed Tab",
+        Some(sel!(reopenClosedTab:)),
+        "t",
+        NSEventModifierFlags::Command.union(NSEventModifierFlags::Shift),
+    ));
+
     // Close Window
     menu.addItem(&create_menu_item_with_key(
         mtm,
@@ -351,6 +451,14 @@ fn create_edit_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         NSEventModifierFlags::Command,
     ));
 
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Paste from History...",
+        Some(sel!(pasteFromHistory:)),
+        "v",
+        NSEventModifierFlags::Command.union(NSEventModifierFlags::Shift),
+    ));
+
     menu.addItem(&create_menu_item_with_key(
         mtm,
         "Select All",
@@ -359,6 +467,14 @@ fn create_edit_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         NSEventModifierFlags::Command,
     ));
 
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Select Command Output",
+        Some(sel!(selectCommandOutput:)),
+        "A",
+        NSEventModifierFlags::Command.union(NSEventModifierFlags::Shift),
+    ));
+
     menu.addItem(&NSMenuItem::separatorItem(mtm));
 
     // Find
@@ -370,6 +486,30 @@ fn create_edit_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         NSEventModifierFlags::Command,
     ));
 
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Select Link by Keyboard",
+        Some(sel!(showHints:)),
+        "E",
+        NSEventModifierFlags::Command.union(NSEventModifierFlags::Shift),
+    ));
+
+    // Prompt navigation (Cmd+Shift+Up/Down)
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Previous Prompt",
+        Some(sel!(previousPrompt:)),
+        "\u{f700}",
+        NSEventModifierFlags::Command.union(NSEventModifierFlags::Shift),
+    ));
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Next Prompt",
+        Some(sel!(nextPrompt:)),
+        "\u{f701}",
+        NSEventModifierFlags::Command.union(NSEventModifierFlags::Shift),
+    ));
+
     let menu_item = NSMenuItem::new(mtm);
     menu_item.setSubmenu(Some(&menu));
     menu_item
@@ -406,6 +546,42 @@ fn create_view_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
 
     menu.addItem(&NSMenuItem::separatorItem(mtm));
 
+    // Magnify (scales the view; rows/columns stay the same)
+    let command_option = NSEventModifierFlags::Command.union(NSEventModifierFlags::Option);
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Magnify",
+        Some(sel!(magnifyIn:)),
+        "+",
+        command_option,
+    ));
+
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Magnify Less",
+        Some(sel!(magnifyOut:)),
+        "-",
+        command_option,
+    ));
+
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Actual Size",
+        Some(sel!(magnifyReset:)),
+        "0",
+        command_option,
+    ));
+
+    menu.addItem(&NSMenuItem::separatorItem(mtm));
+
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Presentation Mode",
+        Some(sel!(togglePresentationMode:)),
+        "P",
+        NSEventModifierFlags::Command.union(NSEventModifierFlags::Shift),
+    ));
+
     // Fullscreen
     menu.addItem(&create_menu_item_with_key(
         mtm,
@@ -439,6 +615,19 @@ fn create_terminal_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         "",
     ));
 
+    menu.addItem(&create_menu_item(
+        mtm,
+        "ASCII Art Safe Rendering",
+        Some(sel!(toggleAsciiArtSafe:)),
+        "",
+    ));
+    menu.addItem(&create_menu_item(
+        mtm,
+        "Read-Only (Lock Input)",
+        Some(sel!(toggleReadOnly:)),
+        "",
+    ));
+
     menu.addItem(&NSMenuItem::separatorItem(mtm));
 
     // Set Title
@@ -446,8 +635,8 @@ fn create_terminal_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         mtm,
         "Set Title...",
         Some(sel!(setTerminalTitle:)),
-        "T",
-        NSEventModifierFlags::Command.union(NSEventModifierFlags::Shift),
+        "t",
+        NSEventModifierFlags::Command.union(NSEventModifierFlags::Option),
     ));
 
     // Set Tab Color
@@ -458,6 +647,22 @@ fn create_terminal_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         "",
     ));
 
+    // Watch Output
+    menu.addItem(&create_menu_item(
+        mtm,
+        "Watch Output...",
+        Some(sel!(watchOutput:)),
+        "",
+    ));
+
+    // Monitor in New Window
+    menu.addItem(&create_menu_item(
+        mtm,
+        "Monitor in New Window",
+        Some(sel!(monitorTab:)),
+        "",
+    ));
+
     menu.addItem(&NSMenuItem::separatorItem(mtm));
 
     // Send Signal submenu
@@ -633,6 +838,13 @@ fn create_window_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         "\u{f701}", // Down arrow
         NSEventModifierFlags::Control.union(NSEventModifierFlags::Option),
     ));
+    move_resize_menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Maximize",
+        Some(sel!(windowFill:)),
+        "\r",
+        NSEventModifierFlags::Control.union(NSEventModifierFlags::Option),
+    ));
 
     move_resize_menu.addItem(&NSMenuItem::separatorItem(mtm));
 
@@ -669,6 +881,92 @@ fn create_window_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
 
     menu.addItem(&NSMenuItem::separatorItem(mtm));
 
+    // Split panes
+    let command_option_shift = NSEventModifierFlags::Command
+        .union(NSEventModifierFlags::Option)
+        .union(NSEventModifierFlags::Shift);
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Split Right",
+        Some(sel!(splitRight:)),
+        "d",
+        NSEventModifierFlags::Command,
+    ));
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Split Down",
+        Some(sel!(splitDown:)),
+        "d",
+        command_option_shift,
+    ));
+    menu.addItem(&create_menu_item_with_key(
+        mtm,
+        "Close Pane",
+        Some(sel!(closePane:)),
+        "w",
+        command_option_shift,
+    ));
+
+    // Moving between and resizing panes (Cmd+Option+Arrows, Cmd+Ctrl+Arrows)
+    let arrows = [
+        (
+            "Left",
+            "\u{f702}",
+            sel!(focusPaneLeft:),
+            sel!(resizePaneLeft:),
+        ),
+        (
+            "Right",
+            "\u{f703}",
+            sel!(focusPaneRight:),
+            sel!(resizePaneRight:),
+        ),
+        ("Up", "\u{f700}", sel!(focusPaneUp:), sel!(resizePaneUp:)),
+        (
+            "Down",
+            "\u{f701}",
+            sel!(focusPaneDown:),
+            sel!(resizePaneDown:),
+        ),
+    ];
+    let select_pane_menu = NSMenu::new(mtm);
+    select_pane_menu.setTitle(&NSString::from_str("Select Pane"));
+    let resize_pane_menu = NSMenu::new(mtm);
+    resize_pane_menu.setTitle(&NSString::from_str("Resize Pane"));
+    for (direction, arrow, focus, resize) in arrows {
+        select_pane_menu.addItem(&create_menu_item_with_key(
+            mtm,
+            &format!("Pane {}", direction),
+            Some(focus),
+            arrow,
+            NSEventModifierFlags::Command.union(NSEventModifierFlags::Option),
+        ));
+        resize_pane_menu.addItem(&create_menu_item_with_key(
+            mtm,
+            &format!("Move Divider {}", direction),
+            Some(resize),
+            arrow,
+            NSEventModifierFlags::Command.union(NSEventModifierFlags::Control),
+        ));
+    }
+    for (title, submenu) in [
+        ("Select Pane", &select_pane_menu),
+        ("Resize Pane", &resize_pane_menu),
+    ] {
+        let item = NSMenuItem::new(mtm);
+        item.setTitle(&NSString::from_str(title));
+        item.setSubmenu(Some(submenu));
+        menu.addItem(&item);
+    }
+    menu.addItem(&create_menu_item(
+        mtm,
+        "Lock Scrolling",
+        Some(sel!(toggleScrollLock:)),
+        "",
+    ));
+
+    menu.addItem(&NSMenuItem::separatorItem(mtm));
+
     // Tab navigation (Ctrl+Tab and Ctrl+Shift+Tab)
     menu.addItem(&create_menu_item_with_key(
         mtm,
@@ -751,6 +1049,13 @@ fn create_help_menu(mtm: MainThreadMarker) -> Retained<NSMenuItem> {
         "",
     ));
 
+    menu.addItem(&create_menu_item(
+        mtm,
+        "Usage Statistics...",
+        Some(sel!(showUsageStats:)),
+        "",
+    ));
+
     menu.addItem(&NSMenuItem::separatorItem(mtm));
 
     // Debug submenu (hidden by default, shown when Shift is held)
@@ -885,3 +1190,71 @@ fn create_menu_item_with_key(
     item.setKeyEquivalentModifierMask(modifiers);
     item
 }
+
+/// Selector of the menu command performing `action`, for dispatching actions
+/// from gestures and the Touch Bar through the responder chain
+///
+/// Returns `None` for actions that have no menu command of their own (tab
+/// numbers and scrolling are handled by the key handler).
+pub fn action_selector(action: &Action) -> Option<Sel> {
+    let sel = match action {
+        Action::NewTab => sel!(newTab:),
+        Action::CloseTab => sel!(closeTab:),
+        Action::ReopenClosedTab => sel!(reopenClosedTab:),
+        Action::NextTab => sel!(selectNextTab:),
+        Action::PrevTab => sel!(selectPreviousTab:),
+        Action::NextAlertedTab => sel!(selectNextAlertedTab:),
+        Action::NewWindow => sel!(newWindow:),
+        Action::CloseWindow => sel!(performClose:),
+        Action::SnapLeft => sel!(windowLeftHalf:),
+        Action::SnapRight => sel!(windowRightHalf:),
+        Action::SnapMaximize => sel!(windowFill:),
+        Action::Copy => sel!(copy:),
+        Action::Paste => sel!(paste:),
+        Action::PasteHistory => sel!(pasteFromHistory:),
+        Action::SelectAll => sel!(selectAll:),
+        Action::SelectCommandOutput => sel!(selectCommandOutput:),
+        Action::PreviousPrompt => sel!(previousPrompt:),
+        Action::NextPrompt => sel!(nextPrompt:),
+        Action::ZoomIn => sel!(zoomIn:),
+        Action::ZoomOut => sel!(zoomOut:),
+        Action::ZoomReset => sel!(zoomReset:),
+        Action::MagnifyIn => sel!(magnifyIn:),
+        Action::MagnifyOut => sel!(magnifyOut:),
+        Action::MagnifyReset => sel!(magnifyReset:),
+        Action::ToggleFullscreen => sel!(toggleFullScreen:),
+        Action::TogglePresentationMode => sel!(togglePresentationMode:),
+        Action::ToggleAsciiArtSafe => sel!(toggleAsciiArtSafe:),
+        Action::ToggleReadOnly => sel!(toggleReadOnly:),
+        Action::HintMode => sel!(showHints:),
+        Action::OpenPreferences => sel!(showPreferences:),
+        Action::ResetTerminal => sel!(resetTerminal:),
+        Action::QuickOpenTemplate => sel!(showQuickOpen:),
+        Action::SplitRight => sel!(splitRight:),
+        Action::SplitDown => sel!(splitDown:),
+        Action::ClosePane => sel!(closePane:),
+        Action::FocusPane(direction) => match direction {
+            PaneDirection::Left => sel!(focusPaneLeft:),
+            PaneDirection::Right => sel!(focusPaneRight:),
+            PaneDirection::Up => sel!(focusPaneUp:),
+            PaneDirection::Down => sel!(focusPaneDown:),
+        },
+        Action::ResizePane(direction) => match direction {
+            PaneDirection::Left => sel!(resizePaneLeft:),
+            PaneDirection::Right => sel!(resizePaneRight:),
+            PaneDirection::Up => sel!(resizePaneUp:),
+            PaneDirection::Down => sel!(resizePaneDown:),
+        },
+        Action::ToggleScrollLock => sel!(toggleScrollLock:),
+        Action::Tab(_)
+        | Action::FindText
+        | Action::ExtractMatches
+        | Action::ScrollUp
+        | Action::ScrollDown
+        | Action::ScrollPageUp
+        | Action::ScrollPageDown
+        | Action::ScrollToTop
+        | Action::ScrollToBottom => return None,
+    };
+    Some(sel)
+}
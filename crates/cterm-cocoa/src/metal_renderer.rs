@@ -0,0 +1,834 @@
+//! Metal-based terminal renderer
+//!
+//! Used instead of [`CGRenderer`]'s cell drawing for large windows, where one
+//! CoreGraphics call per cell per frame becomes the bottleneck. Glyphs are
+//! rasterized once into a cached atlas texture and every cell becomes an
+//! instanced quad. Rows are hashed each frame so only the rows whose content
+//! changed are re-encoded, and a frame with no changes at all is skipped
+//! entirely (the layer keeps showing the last presented drawable).
+//!
+//! Colors, fonts and the theme still come from the [`CGRenderer`], so both
+//! paths draw identical cells. Anything only the CoreGraphics path knows how
+//! to draw (inline images, DRCS soft fonts) makes [`MetalRenderer::render`]
+//! decline the frame, and the view falls back to CoreGraphics for it.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ptr::NonNull;
+use std::time::Duration;
+
+use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, ProtocolObject};
+use objc2::{class, msg_send};
+use objc2_app_kit::NSScreen;
+use objc2_core_graphics::{kCGColorSpaceDisplayP3, kCGColorSpaceSRGB, CGColorSpace};
+use objc2_foundation::{NSPoint, NSRect, NSSize, NSString};
+use objc2_metal::{
+    MTLBlendFactor, MTLClearColor, MTLCommandBuffer, MTLCommandEncoder, MTLCommandQueue,
+    MTLCreateSystemDefaultDevice, MTLDevice, MTLLibrary, MTLLoadAction, MTLOrigin, MTLPixelFormat,
+    MTLPrimitiveType, MTLRegion, MTLRenderCommandEncoder, MTLRenderPassDescriptor,
+    MTLRenderPipelineDescriptor, MTLRenderPipelineState, MTLResourceOptions, MTLSize,
+    MTLStoreAction, MTLTexture, MTLTextureDescriptor,
+};
+use objc2_quartz_core::{CAMetalDrawable, CAMetalLayer, CATransaction};
+
+use cterm_core::cell::{Cell, CellAttrs, Combining};
+use cterm_core::color::Rgb;
+use cterm_core::{Screen, Terminal};
+use cterm_ui::gutter::{gutter_color, GUTTER_WIDTH};
+use cterm_ui::scroll_marks::{scroll_mark_ticks, MARK_HEIGHT};
+
+use crate::cg_renderer::CGRenderer;
+
+/// Grids with at least this many cells are drawn with Metal
+pub const MIN_CELLS: usize = 10_000;
+
+/// Side length of the glyph atlas texture, in pixels
+const ATLAS_SIZE: usize = 2048;
+
+const SHADER_SOURCE: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct Instance {
+    float2 pos;
+    float2 size;
+    float4 uv;
+    float4 color;
+};
+
+struct VertexOut {
+    float4 position [[position]];
+    float2 uv;
+    float4 color;
+    float textured;
+};
+
+vertex VertexOut cell_vertex(uint vid [[vertex_id]],
+                             uint iid [[instance_id]],
+                             const device Instance* instances [[buffer(0)]],
+                             constant float2& viewport [[buffer(1)]]) {
+    Instance inst = instances[iid];
+    float2 corner = float2(float(vid & 1), float(vid >> 1));
+    float2 p = inst.pos + corner * inst.size;
+    VertexOut out;
+    out.position = float4(p.x / viewport.x * 2.0 - 1.0, 1.0 - p.y / viewport.y * 2.0, 0.0, 1.0);
+    out.uv = inst.uv.xy + corner * inst.uv.zw;
+    out.color = inst.color;
+    out.textured = inst.uv.z > 0.0 ? 1.0 : 0.0;
+    return out;
+}
+
+fragment float4 cell_fragment(VertexOut in [[stage_in]],
+                              texture2d<float> atlas [[texture(0)]]) {
+    if (in.textured == 0.0) {
+        return in.color;
+    }
+    constexpr sampler s(coord::pixel, filter::nearest);
+    float4 texel = atlas.sample(s, in.uv);
+    // Negative alpha marks color glyphs (emoji), which keep their own colors
+    if (in.color.a < 0.0) {
+        return float4(texel.rgb / max(texel.a, 0.001), texel.a);
+    }
+    return float4(in.color.rgb, in.color.a * texel.a);
+}
+"#;
+
+/// Frame interval for the screen a window is on: 120Hz ProMotion displays
+/// get 8ms frames, everything else falls back to 60Hz
+pub fn frame_interval(screen: Option<&NSScreen>) -> Duration {
+    let fps = screen
+        .map(|screen| screen.maximumFramesPerSecond())
+        .filter(|&fps| fps > 0)
+        .unwrap_or(60);
+    Duration::from_secs_f64(1.0 / fps as f64)
+}
+
+/// One quad; layout matches `Instance` in the shader
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Instance {
+    pos: [f32; 2],
+    size: [f32; 2],
+    /// Atlas rectangle in pixels; zero width means a solid fill
+    uv: [f32; 4],
+    color: [f32; 4],
+}
+
+impl Instance {
+    fn solid(x: f32, y: f32, width: f32, height: f32, rgb: &Rgb, alpha: f32) -> Self {
+        Self {
+            pos: [x, y],
+            size: [width, height],
+            uv: [0.0; 4],
+            color: [
+                rgb.r as f32 / 255.0,
+                rgb.g as f32 / 255.0,
+                rgb.b as f32 / 255.0,
+                alpha,
+            ],
+        }
+    }
+}
+
+/// Grid geometry in drawable pixels; any change invalidates every cached row
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Layout {
+    cols: usize,
+    rows: usize,
+    cell_width: f32,
+    cell_height: f32,
+    scale: f32,
+    width: f32,
+    height: f32,
+}
+
+/// Encoded quads for one visible row
+#[derive(Default)]
+struct RowCache {
+    hash: u64,
+    backgrounds: Vec<Instance>,
+    glyphs: Vec<Instance>,
+    decorations: Vec<Instance>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    c: char,
+    combining: Combining,
+    bold: bool,
+    italic: bool,
+    wide: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AtlasSlot {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    /// Color glyph (emoji): drawn with its own colors instead of the cell's
+    colored: bool,
+}
+
+/// Glyph cache: rasterized characters packed into shelves of one texture
+struct GlyphAtlas {
+    texture: Retained<ProtocolObject<dyn MTLTexture>>,
+    slots: HashMap<GlyphKey, AtlasSlot>,
+    shelf_x: usize,
+    shelf_y: usize,
+    shelf_height: usize,
+}
+
+impl GlyphAtlas {
+    fn new(device: &ProtocolObject<dyn MTLDevice>) -> Option<Self> {
+        let descriptor = unsafe {
+            MTLTextureDescriptor::texture2DDescriptorWithPixelFormat_width_height_mipmapped(
+                MTLPixelFormat::RGBA8Unorm,
+                ATLAS_SIZE,
+                ATLAS_SIZE,
+                false,
+            )
+        };
+        let texture = device.newTextureWithDescriptor(&descriptor)?;
+        Some(Self {
+            texture,
+            slots: HashMap::new(),
+            shelf_x: 0,
+            shelf_y: 0,
+            shelf_height: 0,
+        })
+    }
+
+    fn clear(&mut self) {
+        self.slots.clear();
+        self.shelf_x = 0;
+        self.shelf_y = 0;
+        self.shelf_height = 0;
+    }
+
+    /// Reserve space for a glyph; `None` when the atlas is full
+    fn allocate(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+        if self.shelf_x + width > ATLAS_SIZE {
+            self.shelf_x = 0;
+            self.shelf_y += self.shelf_height;
+            self.shelf_height = 0;
+        }
+        if self.shelf_y + height > ATLAS_SIZE || width > ATLAS_SIZE {
+            return None;
+        }
+        let origin = (self.shelf_x, self.shelf_y);
+        self.shelf_x += width;
+        self.shelf_height = self.shelf_height.max(height);
+        Some(origin)
+    }
+
+    /// Look up a glyph, rasterizing it into the atlas on first use
+    fn get(&mut self, cg: &CGRenderer, key: GlyphKey, layout: &Layout) -> Option<AtlasSlot> {
+        if let Some(slot) = self.slots.get(&key) {
+            return Some(*slot);
+        }
+        let cells = if key.wide { 2.0 } else { 1.0 };
+        let width = (layout.cell_width * cells).ceil() as usize;
+        let height = layout.cell_height.ceil() as usize;
+        let (x, y) = self.allocate(width, height)?;
+
+        let (pixels, colored) = rasterize_glyph(cg, &key, width, height, layout.scale as f64);
+        let region = MTLRegion {
+            origin: MTLOrigin { x, y, z: 0 },
+            size: MTLSize {
+                width,
+                height,
+                depth: 1,
+            },
+        };
+        unsafe {
+            self.texture
+                .replaceRegion_mipmapLevel_withBytes_bytesPerRow(
+                    region,
+                    0,
+                    NonNull::new(pixels.as_ptr() as *mut std::ffi::c_void).unwrap(),
+                    width * 4,
+                );
+        }
+
+        let slot = AtlasSlot {
+            x,
+            y,
+            width,
+            height,
+            colored,
+        };
+        self.slots.insert(key, slot);
+        Some(slot)
+    }
+}
+
+/// Draw one grapheme cluster in white into an RGBA bitmap; returns the
+/// premultiplied pixels and whether the glyph has colors of its own
+fn rasterize_glyph(
+    cg: &CGRenderer,
+    key: &GlyphKey,
+    width: usize,
+    height: usize,
+    scale: f64,
+) -> (Vec<u8>, bool) {
+    extern "C" {
+        fn CGColorSpaceCreateDeviceRGB() -> *mut std::ffi::c_void;
+        fn CGColorSpaceRelease(color_space: *mut std::ffi::c_void);
+        fn CGBitmapContextCreate(
+            data: *mut std::ffi::c_void,
+            width: usize,
+            height: usize,
+            bits_per_component: usize,
+            bytes_per_row: usize,
+            color_space: *mut std::ffi::c_void,
+            bitmap_info: u32,
+        ) -> *mut std::ffi::c_void;
+        fn CGContextRelease(context: *mut std::ffi::c_void);
+        fn CGContextTranslateCTM(context: *mut std::ffi::c_void, tx: f64, ty: f64);
+        fn CGContextScaleCTM(context: *mut std::ffi::c_void, sx: f64, sy: f64);
+    }
+    /// kCGImageAlphaPremultipliedLast: RGBA byte order, matching RGBA8Unorm
+    const PREMULTIPLIED_LAST: u32 = 1;
+
+    let mut pixels = vec![0u8; width * height * 4];
+    let mut attrs = CellAttrs::empty();
+    attrs.set(CellAttrs::BOLD, key.bold);
+    attrs.set(CellAttrs::ITALIC, key.italic);
+    let dict = cg.glyph_attributes(attrs, &Rgb::new(255, 255, 255), true);
+    let mut text = key.c.to_string();
+    text.push_str(key.combining.as_str());
+    let text = NSString::from_str(&text);
+
+    unsafe {
+        let color_space = CGColorSpaceCreateDeviceRGB();
+        let context = CGBitmapContextCreate(
+            pixels.as_mut_ptr() as *mut std::ffi::c_void,
+            width,
+            height,
+            8,
+            width * 4,
+            color_space,
+            PREMULTIPLIED_LAST,
+        );
+        CGColorSpaceRelease(color_space);
+        if context.is_null() {
+            log::warn!("Failed to create glyph bitmap context");
+            return (pixels, false);
+        }
+
+        // Top-left origin in points, like the flipped terminal view
+        CGContextTranslateCTM(context, 0.0, height as f64);
+        CGContextScaleCTM(context, scale, -scale);
+
+        let _: () = msg_send![class!(NSGraphicsContext), saveGraphicsState];
+        let ns_context: Retained<AnyObject> = msg_send![
+            class!(NSGraphicsContext),
+            graphicsContextWithCGContext: context,
+            flipped: true
+        ];
+        let _: () = msg_send![class!(NSGraphicsContext), setCurrentContext: &*ns_context];
+        let _: () = msg_send![&*text, drawAtPoint: NSPoint::ZERO, withAttributes: &*dict];
+        let _: () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+
+        CGContextRelease(context);
+    }
+
+    // Drawn in white, so any pixel whose channels differ came from the glyph
+    let colored = pixels.chunks_exact(4).any(|p| p[0] != p[1] || p[1] != p[2]);
+    (pixels, colored)
+}
+
+/// Metal renderer drawing into a `CAMetalLayer` hosted by the terminal view
+pub struct MetalRenderer {
+    device: Retained<ProtocolObject<dyn MTLDevice>>,
+    queue: Retained<ProtocolObject<dyn MTLCommandQueue>>,
+    pipeline: Retained<ProtocolObject<dyn MTLRenderPipelineState>>,
+    layer: Retained<CAMetalLayer>,
+    atlas: GlyphAtlas,
+    layout: Option<Layout>,
+    rows: Vec<RowCache>,
+    /// Cursor and scrollbar quads of the last frame
+    overlay: Vec<Instance>,
+    /// Whether the layer is tagged Display P3 rather than sRGB
+    wide_gamut: bool,
+}
+
+impl MetalRenderer {
+    /// Set up the device, shaders and layer; `None` if Metal is unavailable
+    pub fn new() -> Option<Self> {
+        let device = MTLCreateSystemDefaultDevice()?;
+        let queue = device.newCommandQueue()?;
+
+        let library = device
+            .newLibraryWithSource_options_error(&NSString::from_str(SHADER_SOURCE), None)
+            .map_err(|e| log::warn!("Failed to compile Metal shaders: {}", e))
+            .ok()?;
+        let vertex = library.newFunctionWithName(&NSString::from_str("cell_vertex"))?;
+        let fragment = library.newFunctionWithName(&NSString::from_str("cell_fragment"))?;
+
+        let descriptor = MTLRenderPipelineDescriptor::new();
+        descriptor.setVertexFunction(Some(&vertex));
+        descriptor.setFragmentFunction(Some(&fragment));
+        let attachment = unsafe { descriptor.colorAttachments().objectAtIndexedSubscript(0) };
+        attachment.setPixelFormat(MTLPixelFormat::BGRA8Unorm);
+        attachment.setBlendingEnabled(true);
+        attachment.setSourceRGBBlendFactor(MTLBlendFactor::SourceAlpha);
+        attachment.setDestinationRGBBlendFactor(MTLBlendFactor::OneMinusSourceAlpha);
+        attachment.setSourceAlphaBlendFactor(MTLBlendFactor::One);
+        attachment.setDestinationAlphaBlendFactor(MTLBlendFactor::OneMinusSourceAlpha);
+        let pipeline = device
+            .newRenderPipelineStateWithDescriptor_error(&descriptor)
+            .map_err(|e| log::warn!("Failed to create Metal pipeline: {}", e))
+            .ok()?;
+
+        let atlas = GlyphAtlas::new(&device)?;
+
+        let layer = CAMetalLayer::new();
+        layer.setDevice(Some(&device));
+        layer.setPixelFormat(MTLPixelFormat::BGRA8Unorm);
+        layer.setOpaque(true);
+        layer.setHidden(true);
+        set_layer_colorspace(&layer, false);
+
+        log::info!("Metal renderer initialized");
+        Some(Self {
+            device,
+            queue,
+            pipeline,
+            layer,
+            atlas,
+            layout: None,
+            rows: Vec::new(),
+            overlay: Vec::new(),
+            wide_gamut: false,
+        })
+    }
+
+    /// The layer to host below the view's subviews
+    pub fn layer(&self) -> &CAMetalLayer {
+        &self.layer
+    }
+
+    /// Show or hide the Metal layer (the CoreGraphics drawing underneath
+    /// shows through while it is hidden)
+    pub fn set_visible(&self, visible: bool) {
+        if self.layer.isHidden() != visible {
+            return;
+        }
+        CATransaction::begin();
+        CATransaction::setDisableActions(true);
+        self.layer.setHidden(!visible);
+        CATransaction::commit();
+    }
+
+    /// Drop cached glyphs and rows after fonts, colors or theme changed
+    pub fn invalidate(&mut self) {
+        self.atlas.clear();
+        self.rows.clear();
+        self.layout = None;
+    }
+
+    /// Draw the terminal; returns false if this frame needs the CoreGraphics
+    /// renderer instead
+    pub fn render(
+        &mut self,
+        cg: &CGRenderer,
+        terminal: &Terminal,
+        bounds: NSRect,
+        scale: f64,
+    ) -> bool {
+        let screen = terminal.screen();
+        if !screen.visible_images().is_empty() {
+            return false;
+        }
+
+        // Cached rows hold colors in the old working space
+        if cg.is_wide_gamut() != self.wide_gamut {
+            self.wide_gamut = cg.is_wide_gamut();
+            set_layer_colorspace(&self.layer, self.wide_gamut);
+            self.invalidate();
+        }
+
+        // Cached rows leave the background to the clear color when it is
+        // translucent, see `encode_row`
+        let opaque = cg.opacity() >= 1.0;
+        if self.layer.isOpaque() != opaque {
+            self.layer.setOpaque(opaque);
+            self.invalidate();
+        }
+
+        let (cell_width, cell_height) = cg.cell_size();
+        let layout = Layout {
+            cols: screen.width(),
+            rows: screen.height(),
+            cell_width: (cell_width * scale) as f32,
+            cell_height: (cell_height * scale) as f32,
+            scale: scale as f32,
+            width: (bounds.size.width * scale) as f32,
+            height: (bounds.size.height * scale) as f32,
+        };
+        if layout.width < 1.0 || layout.height < 1.0 {
+            return false;
+        }
+        if self.layout != Some(layout) {
+            let glyph_size_changed = self.layout.is_none_or(|old| {
+                (old.cell_width, old.cell_height, old.scale)
+                    != (layout.cell_width, layout.cell_height, layout.scale)
+            });
+            if glyph_size_changed {
+                self.atlas.clear();
+            }
+            self.rows.clear();
+            self.layout = Some(layout);
+
+            CATransaction::begin();
+            CATransaction::setDisableActions(true);
+            self.layer.setFrame(bounds);
+            self.layer.setContentsScale(scale);
+            self.layer
+                .setDrawableSize(NSSize::new(layout.width as f64, layout.height as f64));
+            CATransaction::commit();
+        }
+        self.rows.resize_with(layout.rows, RowCache::default);
+
+        // Re-encode only the rows whose content changed; a full atlas is
+        // flushed once and the frame retried with every row rebuilt
+        let mut damaged = false;
+        let mut flushed = false;
+        let mut row = 0;
+        while row < layout.rows {
+            let hash = row_hash(screen, row);
+            if self.rows[row].hash == hash && !self.rows[row].backgrounds.is_empty() {
+                row += 1;
+                continue;
+            }
+            match self.encode_row(cg, screen, row, &layout) {
+                Some(mut encoded) => {
+                    encoded.hash = hash;
+                    self.rows[row] = encoded;
+                    damaged = true;
+                    row += 1;
+                }
+                None if !flushed && !self.atlas.slots.is_empty() => {
+                    log::debug!("Glyph atlas full, flushing");
+                    self.atlas.clear();
+                    self.rows.clear();
+                    self.rows.resize_with(layout.rows, RowCache::default);
+                    flushed = true;
+                    row = 0;
+                }
+                None => return false,
+            }
+        }
+
+        let overlay = self.encode_overlay(cg, screen, &layout);
+        if !damaged && overlay == self.overlay {
+            return true;
+        }
+        self.overlay = overlay;
+
+        self.draw(cg, &layout)
+    }
+
+    /// Build the quads for one visible row; `None` if a glyph can't be drawn
+    fn encode_row(
+        &mut self,
+        cg: &CGRenderer,
+        screen: &Screen,
+        row: usize,
+        layout: &Layout,
+    ) -> Option<RowCache> {
+        let mut encoded = RowCache::default();
+        let absolute_line = screen.visible_row_to_absolute_line(row);
+        let y = row as f32 * layout.cell_height;
+        let line = layout.scale.round().max(1.0);
+
+        // Always emit the row's background so an emptied row still replaces
+        // what was drawn there before. A translucent background comes from
+        // the clear color alone, since blending it again would thicken it.
+        encoded.backgrounds.push(Instance::solid(
+            0.0,
+            y,
+            layout.cols as f32 * layout.cell_width,
+            layout.cell_height,
+            &cg.background(),
+            if cg.opacity() < 1.0 { 0.0 } else { 1.0 },
+        ));
+
+        for col in 0..layout.cols {
+            let Some(cell) = screen.get_cell_with_scrollback(absolute_line, col) else {
+                continue;
+            };
+            let cell = cg.attr_overrides().apply_to(cell);
+            let cell = cell.as_ref();
+            if cell.is_wide_spacer() {
+                continue;
+            }
+            let x = col as f32 * layout.cell_width;
+            let width = if cell.is_wide() {
+                layout.cell_width * 2.0
+            } else {
+                layout.cell_width
+            };
+
+            let (fg, bg) = cg.cell_colors(cell, screen.is_selected(absolute_line, col));
+            if let Some(bg) = bg {
+                encoded.backgrounds.push(Instance::solid(
+                    x,
+                    y,
+                    width,
+                    layout.cell_height,
+                    &bg,
+                    1.0,
+                ));
+            }
+
+            if cell.c != ' ' && cell.c != '\0' && !cell.attrs.contains(CellAttrs::HIDDEN) {
+                if screen.get_drcs_for_char(cell.c).is_some() {
+                    return None;
+                }
+                let key = GlyphKey {
+                    c: cell.c,
+                    combining: cell.combining.clone(),
+                    bold: cell.attrs.contains(CellAttrs::BOLD),
+                    italic: cell.attrs.contains(CellAttrs::ITALIC),
+                    wide: cell.is_wide(),
+                };
+                let slot = self.atlas.get(cg, key, layout)?;
+                let mut glyph = Instance::solid(
+                    x,
+                    y,
+                    slot.width as f32,
+                    slot.height as f32,
+                    &fg,
+                    if slot.colored { -1.0 } else { 1.0 },
+                );
+                glyph.uv = [
+                    slot.x as f32,
+                    slot.y as f32,
+                    slot.width as f32,
+                    slot.height as f32,
+                ];
+                encoded.glyphs.push(glyph);
+            }
+
+            // Underline styles other than double are drawn as a single line
+            if cell.attrs.has_underline() || cell.hyperlink.is_some() {
+                let color = cg.underline_color(cell, fg);
+                let underline_y = y + layout.cell_height - 2.0 * layout.scale;
+                encoded
+                    .decorations
+                    .push(Instance::solid(x, underline_y, width, line, &color, 1.0));
+                if cell.attrs.contains(CellAttrs::DOUBLE_UNDERLINE) {
+                    encoded.decorations.push(Instance::solid(
+                        x,
+                        underline_y - 2.0 * layout.scale,
+                        width,
+                        line,
+                        &color,
+                        1.0,
+                    ));
+                }
+            }
+            if cell.attrs.contains(CellAttrs::STRIKETHROUGH) {
+                let strike_y = y + layout.cell_height * 0.5;
+                encoded
+                    .decorations
+                    .push(Instance::solid(x, strike_y, width, line, &fg, 1.0));
+            }
+            if cell.attrs.contains(CellAttrs::OVERLINE) {
+                encoded
+                    .decorations
+                    .push(Instance::solid(x, y + line, width, line, &fg, 1.0));
+            }
+        }
+        Some(encoded)
+    }
+
+    /// Cursor, gutter and scrollbar quads, drawn on top of every row
+    fn encode_overlay(&self, cg: &CGRenderer, screen: &Screen, layout: &Layout) -> Vec<Instance> {
+        let mut overlay = Vec::new();
+
+        let cursor = &screen.cursor;
+        if screen.modes.show_cursor && screen.scroll_offset == 0 {
+            let wide = screen
+                .grid()
+                .get(cursor.row, cursor.col)
+                .is_some_and(Cell::is_wide);
+            let width = if wide {
+                layout.cell_width * 2.0
+            } else {
+                layout.cell_width
+            };
+            overlay.push(Instance::solid(
+                cursor.col as f32 * layout.cell_width,
+                cursor.row as f32 * layout.cell_height,
+                width,
+                layout.cell_height,
+                &cg.palette().cursor,
+                0.7,
+            ));
+        }
+
+        if cg.shows_command_gutter() {
+            for block in screen.command_blocks() {
+                overlay.push(Instance::solid(
+                    0.0,
+                    block.top as f32 * layout.cell_height,
+                    GUTTER_WIDTH as f32 * layout.scale,
+                    (block.bottom - block.top) as f32 * layout.cell_height - layout.scale,
+                    &gutter_color(&block, cg.palette()),
+                    0.8,
+                ));
+            }
+        }
+
+        let scrollback_len = screen.scrollback().len();
+        if cg.shows_scrollbar() && scrollback_len > 0 {
+            let total_lines = scrollback_len + layout.rows;
+            let bar_width = 6.0 * layout.scale;
+            let bar_x = layout.width - bar_width - 2.0 * layout.scale;
+            let thumb_height =
+                (layout.rows as f32 / total_lines as f32 * layout.height).max(20.0 * layout.scale);
+            let fraction = screen.scroll_offset as f32 / scrollback_len as f32;
+            let thumb_y = (1.0 - fraction) * (layout.height - thumb_height);
+            let opacity = if screen.scroll_offset > 0 { 0.5 } else { 0.25 };
+            // Marks are laid out in points, like the CG scrollbar
+            let marks = screen.marks();
+            let height = (layout.height / layout.scale) as f64;
+            for (y, color) in scroll_mark_ticks(&marks, total_lines, height, cg.palette()) {
+                overlay.push(Instance::solid(
+                    bar_x,
+                    y as f32 * layout.scale,
+                    bar_width,
+                    MARK_HEIGHT as f32 * layout.scale,
+                    &color,
+                    0.9,
+                ));
+            }
+            overlay.push(Instance::solid(
+                bar_x,
+                thumb_y,
+                bar_width,
+                thumb_height,
+                &Rgb::new(128, 128, 128),
+                opacity,
+            ));
+        }
+
+        overlay
+    }
+
+    /// Encode every cached quad into one instanced draw and present it
+    fn draw(&self, cg: &CGRenderer, layout: &Layout) -> bool {
+        let Some(drawable) = self.layer.nextDrawable() else {
+            return false;
+        };
+
+        // Backgrounds of every row first so glyphs overhanging into a
+        // neighbouring row are not painted over
+        let mut instances: Vec<Instance> = Vec::new();
+        instances.extend(self.rows.iter().flat_map(|r| r.backgrounds.iter()));
+        instances.extend(self.rows.iter().flat_map(|r| r.glyphs.iter()));
+        instances.extend(self.rows.iter().flat_map(|r| r.decorations.iter()));
+        instances.extend(self.overlay.iter());
+
+        let bytes = std::mem::size_of_val(instances.as_slice());
+        let Some(buffer) = (unsafe {
+            self.device.newBufferWithBytes_length_options(
+                NonNull::new(instances.as_ptr() as *mut std::ffi::c_void).unwrap(),
+                bytes,
+                MTLResourceOptions::StorageModeShared,
+            )
+        }) else {
+            return false;
+        };
+
+        // The layer composites premultiplied colors
+        let background = cg.background();
+        let alpha = cg.opacity();
+        let pass = MTLRenderPassDescriptor::new();
+        let attachment = unsafe { pass.colorAttachments().objectAtIndexedSubscript(0) };
+        attachment.setTexture(Some(&drawable.texture()));
+        attachment.setLoadAction(MTLLoadAction::Clear);
+        attachment.setStoreAction(MTLStoreAction::Store);
+        attachment.setClearColor(MTLClearColor {
+            red: background.r as f64 / 255.0 * alpha,
+            green: background.g as f64 / 255.0 * alpha,
+            blue: background.b as f64 / 255.0 * alpha,
+            alpha,
+        });
+
+        let Some(commands) = self.queue.commandBuffer() else {
+            return false;
+        };
+        let Some(encoder) = commands.renderCommandEncoderWithDescriptor(&pass) else {
+            return false;
+        };
+        let viewport = [layout.width, layout.height];
+        unsafe {
+            encoder.setRenderPipelineState(&self.pipeline);
+            encoder.setVertexBuffer_offset_atIndex(Some(&buffer), 0, 0);
+            encoder.setVertexBytes_length_atIndex(
+                NonNull::new(viewport.as_ptr() as *mut std::ffi::c_void).unwrap(),
+                std::mem::size_of_val(&viewport),
+                1,
+            );
+            encoder.setFragmentTexture_atIndex(Some(&self.atlas.texture), 0);
+            encoder.drawPrimitives_vertexStart_vertexCount_instanceCount(
+                MTLPrimitiveType::TriangleStrip,
+                0,
+                4,
+                instances.len(),
+            );
+        }
+        encoder.endEncoding();
+        commands.presentDrawable(ProtocolObject::from_ref(&*drawable));
+        commands.commit();
+        true
+    }
+}
+
+/// Hash of everything that affects how a visible row is drawn
+/// Tag the layer with the space instance colors are expressed in, so Core
+/// Animation converts them for the display instead of passing them through
+fn set_layer_colorspace(layer: &CAMetalLayer, wide_gamut: bool) {
+    let name = unsafe {
+        if wide_gamut {
+            kCGColorSpaceDisplayP3
+        } else {
+            kCGColorSpaceSRGB
+        }
+    };
+    let colorspace = CGColorSpace::with_name(Some(name));
+    layer.setColorspace(colorspace.as_deref());
+}
+
+fn row_hash(screen: &Screen, row: usize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let absolute_line = screen.visible_row_to_absolute_line(row);
+    for col in 0..screen.width() {
+        match screen.get_cell_with_scrollback(absolute_line, col) {
+            Some(cell) => {
+                cell.c.hash(&mut hasher);
+                cell.combining.hash(&mut hasher);
+                cell.fg.hash(&mut hasher);
+                cell.bg.hash(&mut hasher);
+                cell.underline_color.hash(&mut hasher);
+                cell.attrs.bits().hash(&mut hasher);
+                cell.hyperlink.is_some().hash(&mut hasher);
+                screen.is_selected(absolute_line, col).hash(&mut hasher);
+            }
+            None => 0u8.hash(&mut hasher),
+        }
+    }
+    hasher.finish()
+}
+
+/// Whether a grid is large enough to be worth drawing with Metal
+pub fn wants_metal(cols: usize, rows: usize) -> bool {
+    cols * rows >= MIN_CELLS
+}
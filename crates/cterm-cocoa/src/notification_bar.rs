@@ -1,9 +1,11 @@
-//! Notification bar for file transfer UI
+//! Notification bar for in-window prompts
 //!
-//! Shows a dismissible notification when files are received via iTerm2 protocol.
-//! Format: "Received file: Name.bin (1.2 MB)" [Save] [Save As...] [Discard]
+//! Shows the current prompt of the terminal's [`cterm_ui::PromptQueue`], such
+//! as "Received file: Name.bin (1.2 MB)" [Preview] [Save] [Save As...]
+//! [Discard], with a button per action. Every button sends `promptAction:`
+//! to the target, tagged with its index.
 
-use cterm_ui::format_size;
+use cterm_ui::PromptAction;
 use objc2::rc::Retained;
 use objc2::{define_class, msg_send, sel, DefinedClass, MainThreadOnly};
 use objc2_app_kit::{NSBezelStyle, NSButton, NSButtonType, NSColor, NSTextField, NSView};
@@ -11,23 +13,27 @@ use objc2_foundation::{
     MainThreadMarker, NSAttributedString, NSDictionary, NSObjectProtocol, NSPoint, NSRect, NSSize,
     NSString,
 };
-use std::cell::{Cell, RefCell};
+use std::cell::RefCell;
 
 /// Notification bar height in pixels
 pub const NOTIFICATION_BAR_HEIGHT: f64 = 32.0;
 
+/// Most actions a prompt offers
+const MAX_BUTTONS: usize = 4;
+
+const PADDING: f64 = 8.0;
+const BUTTON_WIDTH: f64 = 90.0;
+const BUTTON_HEIGHT: f64 = 22.0;
+const BUTTON_SPACING: f64 = 8.0;
+
 /// Ivars for the notification bar
 pub struct NotificationBarIvars {
-    /// File ID for current notification
-    file_id: Cell<u64>,
-    /// Label showing file name and size
+    /// Actions of the buttons shown, in button order
+    actions: RefCell<Vec<PromptAction>>,
+    /// Label showing the prompt
     label: RefCell<Option<Retained<NSTextField>>>,
-    /// Save button
-    save_button: RefCell<Option<Retained<NSButton>>>,
-    /// Save As button
-    save_as_button: RefCell<Option<Retained<NSButton>>>,
-    /// Discard button
-    discard_button: RefCell<Option<Retained<NSButton>>>,
+    /// One button per action, the unused ones hidden
+    buttons: RefCell<Vec<Retained<NSButton>>>,
 }
 
 define_class!(
@@ -57,11 +63,9 @@ impl NotificationBar {
 
         let this = mtm.alloc::<Self>();
         let this = this.set_ivars(NotificationBarIvars {
-            file_id: Cell::new(0),
+            actions: RefCell::new(Vec::new()),
             label: RefCell::new(None),
-            save_button: RefCell::new(None),
-            save_as_button: RefCell::new(None),
-            discard_button: RefCell::new(None),
+            buttons: RefCell::new(Vec::new()),
         });
 
         let this: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
@@ -82,21 +86,10 @@ impl NotificationBar {
     }
 
     fn setup_ui(&self, mtm: MainThreadMarker, width: f64) {
-        let padding = 8.0;
-        let button_width = 70.0;
-        let button_height = 22.0;
-        let button_spacing = 8.0;
-
-        // Calculate positions from right edge
-        let discard_x = width - padding - button_width;
-        let save_as_x = discard_x - button_spacing - button_width;
-        let save_x = save_as_x - button_spacing - button_width;
-        let label_width = save_x - padding - padding;
-
         // Create label
         let label_frame = NSRect::new(
-            NSPoint::new(padding, (NOTIFICATION_BAR_HEIGHT - button_height) / 2.0),
-            NSSize::new(label_width, button_height),
+            NSPoint::new(PADDING, (NOTIFICATION_BAR_HEIGHT - BUTTON_HEIGHT) / 2.0),
+            NSSize::new(0.0, BUTTON_HEIGHT),
         );
         let label = unsafe { NSTextField::initWithFrame(mtm.alloc(), label_frame) };
         label.setBezeled(false);
@@ -112,69 +105,60 @@ impl NotificationBar {
         }
         *self.ivars().label.borrow_mut() = Some(label);
 
-        // Create Save button
-        let save_frame = NSRect::new(
-            NSPoint::new(save_x, (NOTIFICATION_BAR_HEIGHT - button_height) / 2.0),
-            NSSize::new(button_width, button_height),
-        );
-        let save_button = unsafe { NSButton::initWithFrame(mtm.alloc(), save_frame) };
-        Self::configure_button(mtm, &save_button, "Save", sel!(saveFile:));
-        unsafe {
-            self.addSubview(&save_button);
-        }
-        *self.ivars().save_button.borrow_mut() = Some(save_button);
-
-        // Create Save As button
-        let save_as_frame = NSRect::new(
-            NSPoint::new(save_as_x, (NOTIFICATION_BAR_HEIGHT - button_height) / 2.0),
-            NSSize::new(button_width, button_height),
-        );
-        let save_as_button = unsafe { NSButton::initWithFrame(mtm.alloc(), save_as_frame) };
-        Self::configure_button(mtm, &save_as_button, "Save As...", sel!(saveFileAs:));
-        unsafe {
-            self.addSubview(&save_as_button);
+        // Create the buttons, titled when a prompt is shown
+        let mut buttons = Vec::with_capacity(MAX_BUTTONS);
+        for tag in 0..MAX_BUTTONS {
+            let frame = NSRect::new(
+                NSPoint::new(0.0, (NOTIFICATION_BAR_HEIGHT - BUTTON_HEIGHT) / 2.0),
+                NSSize::new(BUTTON_WIDTH, BUTTON_HEIGHT),
+            );
+            let button = unsafe { NSButton::initWithFrame(mtm.alloc(), frame) };
+            button.setButtonType(NSButtonType::MomentaryPushIn);
+            button.setBezelStyle(NSBezelStyle::Recessed);
+            button.setBordered(true);
+            button.setTag(tag as isize);
+            unsafe {
+                button.setAction(Some(sel!(promptAction:)));
+                self.addSubview(&button);
+            }
+            buttons.push(button);
         }
-        *self.ivars().save_as_button.borrow_mut() = Some(save_as_button);
+        *self.ivars().buttons.borrow_mut() = buttons;
 
-        // Create Discard button
-        let discard_frame = NSRect::new(
-            NSPoint::new(discard_x, (NOTIFICATION_BAR_HEIGHT - button_height) / 2.0),
-            NSSize::new(button_width, button_height),
-        );
-        let discard_button = unsafe { NSButton::initWithFrame(mtm.alloc(), discard_frame) };
-        Self::configure_button(mtm, &discard_button, "Discard", sel!(discardFile:));
-        unsafe {
-            self.addSubview(&discard_button);
-        }
-        *self.ivars().discard_button.borrow_mut() = Some(discard_button);
+        self.layout_buttons(width);
     }
 
-    /// Show notification for a received file
-    pub fn show_file(&self, id: u64, name: Option<&str>, size: usize) {
-        self.ivars().file_id.set(id);
-
-        let display_name = name.unwrap_or("unnamed file");
-        let size_str = format_size(size);
-        let text = format!("Received file: {} ({})", display_name, size_str);
+    /// Show a prompt's message with a button per action
+    pub fn show_prompt(&self, message: &str, actions: &[PromptAction]) {
+        let mtm = MainThreadMarker::from(self);
+        for (i, button) in self.ivars().buttons.borrow().iter().enumerate() {
+            match actions.get(i) {
+                Some(action) => {
+                    Self::set_button_title(mtm, button, action.label());
+                    button.setHidden(false);
+                }
+                None => button.setHidden(true),
+            }
+        }
+        *self.ivars().actions.borrow_mut() = actions.iter().take(MAX_BUTTONS).copied().collect();
 
         if let Some(ref label) = *self.ivars().label.borrow() {
-            label.setStringValue(&NSString::from_str(&text));
+            label.setStringValue(&NSString::from_str(message));
         }
-
+        self.layout_buttons(self.frame().size.width);
         self.setHidden(false);
-
-        log::debug!("Showing notification for file {} (id={})", display_name, id);
     }
 
     /// Hide the notification bar
     pub fn hide(&self) {
         self.setHidden(true);
-        self.ivars().file_id.set(0);
+        self.ivars().actions.borrow_mut().clear();
     }
 
-    /// Get the current file ID
-    pub fn file_id(&self) -> u64 {
-        self.ivars().file_id.get()
+    /// Action of the button with `tag`
+    pub fn action(&self, tag: isize) -> Option<PromptAction> {
+        let tag = usize::try_from(tag).ok()?;
+        self.ivars().actions.borrow().get(tag).copied()
     }
 
     /// Update the bar width when window resizes
@@ -182,39 +166,25 @@ impl NotificationBar {
         let mut frame = self.frame();
         frame.size.width = width;
         self.setFrame(frame);
+        self.layout_buttons(width);
+    }
 
-        // Reposition buttons
-        let padding = 8.0;
-        let button_width = 70.0;
-        let button_height = 22.0;
-        let button_spacing = 8.0;
-
-        let discard_x = width - padding - button_width;
-        let save_as_x = discard_x - button_spacing - button_width;
-        let save_x = save_as_x - button_spacing - button_width;
-        let label_width = save_x - padding - padding;
+    /// Line the buttons in use up against the right edge, the label taking
+    /// the rest
+    fn layout_buttons(&self, width: f64) {
+        let count = self.ivars().actions.borrow().len();
+        let buttons_x =
+            width - PADDING - count as f64 * (BUTTON_WIDTH + BUTTON_SPACING) + BUTTON_SPACING;
 
         if let Some(ref label) = *self.ivars().label.borrow() {
             let mut frame = label.frame();
-            frame.size.width = label_width;
+            frame.size.width = (buttons_x - PADDING - PADDING).max(0.0);
             label.setFrame(frame);
         }
 
-        if let Some(ref button) = *self.ivars().save_button.borrow() {
+        for (i, button) in self.ivars().buttons.borrow().iter().take(count).enumerate() {
             let mut frame = button.frame();
-            frame.origin.x = save_x;
-            button.setFrame(frame);
-        }
-
-        if let Some(ref button) = *self.ivars().save_as_button.borrow() {
-            let mut frame = button.frame();
-            frame.origin.x = save_as_x;
-            button.setFrame(frame);
-        }
-
-        if let Some(ref button) = *self.ivars().discard_button.borrow() {
-            let mut frame = button.frame();
-            frame.origin.x = discard_x;
+            frame.origin.x = buttons_x + i as f64 * (BUTTON_WIDTH + BUTTON_SPACING);
             button.setFrame(frame);
         }
     }
@@ -245,18 +215,8 @@ impl NotificationBar {
         unsafe { msg_send![self, isHidden] }
     }
 
-    /// Configure a button with title and action for dark background
-    fn configure_button(
-        mtm: MainThreadMarker,
-        button: &NSButton,
-        title: &str,
-        action: objc2::runtime::Sel,
-    ) {
-        // Use momentary push button with recessed style (works better on dark backgrounds)
-        button.setButtonType(NSButtonType::MomentaryPushIn);
-        button.setBezelStyle(NSBezelStyle::Recessed);
-        button.setBordered(true);
-
+    /// Title a button in white, to read on the dark background
+    fn set_button_title(mtm: MainThreadMarker, button: &NSButton, title: &str) {
         unsafe {
             let title_str = NSString::from_str(title);
 
@@ -276,8 +236,6 @@ impl NotificationBar {
             );
 
             button.setAttributedTitle(&attr_title);
-            button.setTarget(None);
-            button.setAction(Some(action));
         }
     }
 
@@ -285,13 +243,7 @@ impl NotificationBar {
     /// Uses raw msg_send! to preserve the target's actual type information
     pub fn set_action_target<T: objc2::Message>(&self, target: &T) {
         unsafe {
-            if let Some(ref button) = *self.ivars().save_button.borrow() {
-                let _: () = msg_send![button, setTarget: target];
-            }
-            if let Some(ref button) = *self.ivars().save_as_button.borrow() {
-                let _: () = msg_send![button, setTarget: target];
-            }
-            if let Some(ref button) = *self.ivars().discard_button.borrow() {
+            for button in self.ivars().buttons.borrow().iter() {
                 let _: () = msg_send![button, setTarget: target];
             }
         }
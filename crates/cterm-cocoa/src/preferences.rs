@@ -8,8 +8,9 @@ use objc2::rc::Retained;
 use objc2::runtime::ProtocolObject;
 use objc2::{define_class, msg_send, sel, DefinedClass, MainThreadOnly};
 use objc2_app_kit::{
-    NSButton, NSPopUpButton, NSSlider, NSStackView, NSTabView, NSTabViewItem, NSTextField,
-    NSWindow, NSWindowDelegate, NSWindowStyleMask,
+    NSButton, NSControlTextEditingDelegate, NSMenuItem, NSPopUpButton, NSSearchField,
+    NSSearchFieldDelegate, NSSlider, NSStackView, NSTabView, NSTabViewItem, NSTextField,
+    NSTextFieldDelegate, NSView, NSWindow, NSWindowDelegate, NSWindowStyleMask,
 };
 use objc2_foundation::{
     MainThreadMarker, NSNotification, NSObjectProtocol, NSPoint, NSRect, NSSize, NSString,
@@ -17,9 +18,10 @@ use objc2_foundation::{
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use cterm_app::config::{
-    config_dir, save_config, Config, CursorStyleConfig, NewTabPosition, TabBarPosition,
-    TabBarVisibility, ToolShortcutEntry,
+use cterm_app::config::{config_dir, save_config, Config, ToolShortcutEntry};
+use cterm_app::live_config::Preview;
+use cterm_app::settings::{
+    self, Setting, SettingCategory, SettingError, SettingKind, Value, SETTINGS,
 };
 use cterm_app::{git_sync, PullResult};
 
@@ -49,27 +51,27 @@ fn format_timestamp(ts: i64) -> String {
     }
 }
 
+/// Control showing a registered setting
+enum SettingControl {
+    Field(Retained<NSTextField>),
+    Popup(Retained<NSPopUpButton>),
+    Slider(Retained<NSSlider>),
+    Checkbox(Retained<NSButton>),
+}
+
+/// A settings row, hidden when it does not match the search
+struct SettingRow {
+    setting: &'static Setting,
+    row: Retained<NSStackView>,
+    control: SettingControl,
+}
+
 /// Preferences window ivars
 pub struct PreferencesWindowIvars {
     config: RefCell<Config>,
     on_save: RefCell<Option<Box<dyn Fn(Config)>>>,
-    // General tab controls
-    scrollback_field: RefCell<Option<Retained<NSTextField>>>,
-    confirm_close_checkbox: RefCell<Option<Retained<NSButton>>>,
-    copy_on_select_checkbox: RefCell<Option<Retained<NSButton>>>,
-    // Appearance tab controls
-    theme_popup: RefCell<Option<Retained<NSPopUpButton>>>,
-    font_field: RefCell<Option<Retained<NSTextField>>>,
-    font_size_field: RefCell<Option<Retained<NSTextField>>>,
-    cursor_popup: RefCell<Option<Retained<NSPopUpButton>>>,
-    cursor_blink_checkbox: RefCell<Option<Retained<NSButton>>>,
-    opacity_slider: RefCell<Option<Retained<NSSlider>>>,
-    bold_bright_checkbox: RefCell<Option<Retained<NSButton>>>,
-    // Tabs tab controls
-    show_tab_bar_popup: RefCell<Option<Retained<NSPopUpButton>>>,
-    tab_position_popup: RefCell<Option<Retained<NSPopUpButton>>>,
-    new_tab_popup: RefCell<Option<Retained<NSPopUpButton>>>,
-    show_close_checkbox: RefCell<Option<Retained<NSButton>>>,
+    /// Appearance shown in open terminals while the window is open
+    preview: RefCell<Preview>,
     // Tools tab controls
     tool_entries_stack: RefCell<Option<Retained<NSStackView>>>,
     tool_entries: RefCell<
@@ -85,6 +87,9 @@ pub struct PreferencesWindowIvars {
     git_branch_label: RefCell<Option<Retained<NSTextField>>>,
     git_last_sync_label: RefCell<Option<Retained<NSTextField>>>,
     git_changes_label: RefCell<Option<Retained<NSTextField>>>,
+    // Search and per-setting reset
+    tab_view: RefCell<Option<Retained<NSTabView>>>,
+    setting_rows: RefCell<Vec<SettingRow>>,
 }
 
 define_class!(
@@ -100,9 +105,27 @@ define_class!(
         #[unsafe(method(windowWillClose:))]
         fn window_will_close(&self, _notification: &NSNotification) {
             log::debug!("Preferences window closing");
+            // Undo an applied preview unless OK saved it
+            self.ivars().preview.borrow_mut().revert();
         }
     }
 
+    unsafe impl NSControlTextEditingDelegate for PreferencesWindow {}
+
+    // Only the search field has a delegate
+    unsafe impl NSTextFieldDelegate for PreferencesWindow {
+        #[unsafe(method(controlTextDidChange:))]
+        fn control_text_did_change(&self, notification: &NSNotification) {
+            let Some(object) = notification.object() else {
+                return;
+            };
+            let field: &NSTextField = unsafe { &*(&*object as *const _ as *const NSTextField) };
+            self.filter_settings(&field.stringValue().to_string());
+        }
+    }
+
+    unsafe impl NSSearchFieldDelegate for PreferencesWindow {}
+
     // Button action handlers
     impl PreferencesWindow {
         #[unsafe(method(savePreferences:))]
@@ -118,7 +141,9 @@ define_class!(
 
         #[unsafe(method(applyPreferences:))]
         fn action_apply(&self, _sender: Option<&objc2::runtime::AnyObject>) {
-            self.collect_and_save();
+            // Apply previews; only OK saves
+            let config = self.collect_config();
+            self.ivars().preview.borrow_mut().apply(config);
         }
 
         #[unsafe(method(addToolEntry:))]
@@ -148,6 +173,15 @@ define_class!(
             }
         }
 
+        #[unsafe(method(resetSetting:))]
+        fn action_reset_setting(&self, sender: Option<&objc2::runtime::AnyObject>) {
+            let Some(sender) = sender else { return };
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            if let Some(setting) = SETTINGS.get(tag as usize) {
+                self.reset_setting(setting);
+            }
+        }
+
         #[unsafe(method(syncNow:))]
         fn action_sync_now(&self, _sender: Option<&objc2::runtime::AnyObject>) {
             self.perform_sync_now();
@@ -161,7 +195,7 @@ impl PreferencesWindow {
         config: &Config,
         on_save: impl Fn(Config) + 'static,
     ) -> Retained<Self> {
-        let content_rect = NSRect::new(NSPoint::new(200.0, 200.0), NSSize::new(500.0, 400.0));
+        let content_rect = NSRect::new(NSPoint::new(200.0, 200.0), NSSize::new(500.0, 440.0));
 
         let style_mask = NSWindowStyleMask::Titled
             | NSWindowStyleMask::Closable
@@ -172,20 +206,7 @@ impl PreferencesWindow {
         let this = this.set_ivars(PreferencesWindowIvars {
             config: RefCell::new(config.clone()),
             on_save: RefCell::new(Some(Box::new(on_save))),
-            scrollback_field: RefCell::new(None),
-            confirm_close_checkbox: RefCell::new(None),
-            copy_on_select_checkbox: RefCell::new(None),
-            theme_popup: RefCell::new(None),
-            font_field: RefCell::new(None),
-            font_size_field: RefCell::new(None),
-            cursor_popup: RefCell::new(None),
-            cursor_blink_checkbox: RefCell::new(None),
-            opacity_slider: RefCell::new(None),
-            bold_bright_checkbox: RefCell::new(None),
-            show_tab_bar_popup: RefCell::new(None),
-            tab_position_popup: RefCell::new(None),
-            new_tab_popup: RefCell::new(None),
-            show_close_checkbox: RefCell::new(None),
+            preview: RefCell::new(Preview::new(config)),
             tool_entries_stack: RefCell::new(None),
             tool_entries: RefCell::new(Vec::new()),
             git_remote_field: RefCell::new(None),
@@ -193,6 +214,8 @@ impl PreferencesWindow {
             git_branch_label: RefCell::new(None),
             git_last_sync_label: RefCell::new(None),
             git_changes_label: RefCell::new(None),
+            tab_view: RefCell::new(None),
+            setting_rows: RefCell::new(Vec::new()),
         });
 
         let this: Retained<Self> = unsafe {
@@ -224,21 +247,29 @@ impl PreferencesWindow {
             view
         };
 
+        // Search field filtering the settings on every tab
+        let search_field = NSSearchField::new(mtm);
+        unsafe {
+            search_field.setTranslatesAutoresizingMaskIntoConstraints(false);
+        }
+        search_field.setPlaceholderString(Some(&NSString::from_str("Search settings")));
+        search_field.setDelegate(Some(ProtocolObject::from_ref(self)));
+        unsafe {
+            container.addSubview(&search_field);
+        }
+
         // Create tab view
         let tab_view = NSTabView::new(mtm);
         unsafe {
             tab_view.setTranslatesAutoresizingMaskIntoConstraints(false);
         }
+        *self.ivars().tab_view.borrow_mut() = Some(tab_view.clone());
 
         // Add tabs
-        let general_tab = self.create_general_tab(mtm, config);
-        tab_view.addTabViewItem(&general_tab);
-
-        let appearance_tab = self.create_appearance_tab(mtm, config);
-        tab_view.addTabViewItem(&appearance_tab);
-
-        let tabs_tab = self.create_tabs_tab(mtm, config);
-        tab_view.addTabViewItem(&tabs_tab);
+        for category in SettingCategory::ALL {
+            let tab = self.create_settings_tab(mtm, config, category);
+            tab_view.addTabViewItem(&tab);
+        }
 
         let tools_tab = self.create_tools_tab(mtm);
         tab_view.addTabViewItem(&tools_tab);
@@ -323,10 +354,21 @@ impl PreferencesWindow {
         unsafe {
             use objc2_app_kit::NSLayoutConstraint;
 
-            // Tab view: pin to top, left, right with margins
-            let c1 = tab_view
+            // Search field: pin to top, left, right with margins
+            let s1 = search_field
                 .topAnchor()
                 .constraintEqualToAnchor_constant(&container.topAnchor(), 12.0);
+            let s2 = search_field
+                .leadingAnchor()
+                .constraintEqualToAnchor_constant(&container.leadingAnchor(), 12.0);
+            let s3 = search_field
+                .trailingAnchor()
+                .constraintEqualToAnchor_constant(&container.trailingAnchor(), -12.0);
+
+            // Tab view: below the search field, left, right with margins
+            let c1 = tab_view
+                .topAnchor()
+                .constraintEqualToAnchor_constant(&search_field.bottomAnchor(), 8.0);
             let c2 = tab_view
                 .leadingAnchor()
                 .constraintEqualToAnchor_constant(&container.leadingAnchor(), 12.0);
@@ -351,20 +393,22 @@ impl PreferencesWindow {
                 .constraintEqualToAnchor_constant(&button_stack.topAnchor(), -12.0);
 
             NSLayoutConstraint::activateConstraints(&objc2_foundation::NSArray::from_slice(&[
-                &*c1, &*c2, &*c3, &*c4, &*c5, &*c6, &*c7,
+                &*s1, &*s2, &*s3, &*c1, &*c2, &*c3, &*c4, &*c5, &*c6, &*c7,
             ]));
         }
 
         self.setContentView(Some(&container));
     }
 
-    fn create_general_tab(
+    /// Build a tab with a row for each setting of `category`
+    fn create_settings_tab(
         &self,
         mtm: MainThreadMarker,
         config: &Config,
+        category: SettingCategory,
     ) -> Retained<NSTabViewItem> {
         let tab = NSTabViewItem::new();
-        tab.setLabel(&NSString::from_str("General"));
+        tab.setLabel(&NSString::from_str(category.name()));
 
         let stack = unsafe {
             let stack = NSStackView::new(mtm);
@@ -380,217 +424,51 @@ impl PreferencesWindow {
             stack
         };
 
-        // Scrollback lines
-        let scrollback_row = self.create_label_field_row(
-            mtm,
-            "Scrollback lines:",
-            &config.general.scrollback_lines.to_string(),
-        );
-        *self.ivars().scrollback_field.borrow_mut() = Some(scrollback_row.1.clone());
-        unsafe {
-            stack.addArrangedSubview(&scrollback_row.0);
-        }
-
-        // Confirm close with running processes
-        let confirm_checkbox = self.create_checkbox(
-            mtm,
-            "Confirm close with running processes",
-            config.general.confirm_close_with_running,
-        );
-        *self.ivars().confirm_close_checkbox.borrow_mut() = Some(confirm_checkbox.clone());
-        unsafe {
-            stack.addArrangedSubview(&confirm_checkbox);
-        }
-
-        // Copy on select
-        let copy_checkbox =
-            self.create_checkbox(mtm, "Copy on select", config.general.copy_on_select);
-        *self.ivars().copy_on_select_checkbox.borrow_mut() = Some(copy_checkbox.clone());
-        unsafe {
-            stack.addArrangedSubview(&copy_checkbox);
+        for setting in category.settings() {
+            let (content, control) = self.create_setting_control(mtm, setting);
+            if let Some(value) = settings::value(config, setting.key) {
+                show_value(&control, setting, &value);
+            }
+            content.setToolTip(Some(&NSString::from_str(setting.description)));
+            self.add_setting_row(mtm, &stack, setting, &content, control);
         }
 
         tab.setView(Some(&stack));
         tab
     }
 
-    fn create_appearance_tab(
+    /// Create the control editing `setting`, picked by its kind, along with
+    /// the view holding it and its label
+    fn create_setting_control(
         &self,
         mtm: MainThreadMarker,
-        config: &Config,
-    ) -> Retained<NSTabViewItem> {
-        let tab = NSTabViewItem::new();
-        tab.setLabel(&NSString::from_str("Appearance"));
-
-        let stack = unsafe {
-            let stack = NSStackView::new(mtm);
-            stack.setOrientation(objc2_app_kit::NSUserInterfaceLayoutOrientation::Vertical);
-            stack.setAlignment(objc2_app_kit::NSLayoutAttribute::Leading);
-            stack.setSpacing(12.0);
-            stack.setEdgeInsets(objc2_foundation::NSEdgeInsets {
-                top: 16.0,
-                left: 16.0,
-                bottom: 16.0,
-                right: 16.0,
-            });
-            stack
-        };
-
-        // Theme popup
-        let themes = [
-            ("dark", "Default Dark"),
-            ("light", "Default Light"),
-            ("tokyo_night", "Tokyo Night"),
-            ("dracula", "Dracula"),
-            ("nord", "Nord"),
-        ];
-        let theme_row =
-            self.create_label_popup_row(mtm, "Theme:", &themes, &config.appearance.theme);
-        *self.ivars().theme_popup.borrow_mut() = Some(theme_row.1.clone());
-        unsafe {
-            stack.addArrangedSubview(&theme_row.0);
-        }
-
-        // Font
-        let font_row = self.create_label_field_row(mtm, "Font:", &config.appearance.font.family);
-        *self.ivars().font_field.borrow_mut() = Some(font_row.1.clone());
-        unsafe {
-            stack.addArrangedSubview(&font_row.0);
-        }
-
-        // Font size
-        let size_row = self.create_label_field_row(
-            mtm,
-            "Font size:",
-            &config.appearance.font.size.to_string(),
-        );
-        *self.ivars().font_size_field.borrow_mut() = Some(size_row.1.clone());
-        unsafe {
-            stack.addArrangedSubview(&size_row.0);
-        }
-
-        // Cursor style
-        let cursor_styles = [
-            ("block", "Block"),
-            ("underline", "Underline"),
-            ("bar", "Bar"),
-        ];
-        let cursor_id = match config.appearance.cursor_style {
-            CursorStyleConfig::Block => "block",
-            CursorStyleConfig::Underline => "underline",
-            CursorStyleConfig::Bar => "bar",
-        };
-        let cursor_row =
-            self.create_label_popup_row(mtm, "Cursor style:", &cursor_styles, cursor_id);
-        *self.ivars().cursor_popup.borrow_mut() = Some(cursor_row.1.clone());
-        unsafe {
-            stack.addArrangedSubview(&cursor_row.0);
-        }
-
-        // Cursor blink
-        let blink_checkbox =
-            self.create_checkbox(mtm, "Cursor blink", config.appearance.cursor_blink);
-        *self.ivars().cursor_blink_checkbox.borrow_mut() = Some(blink_checkbox.clone());
-        unsafe {
-            stack.addArrangedSubview(&blink_checkbox);
-        }
-
-        // Opacity slider
-        let opacity_row =
-            self.create_label_slider_row(mtm, "Opacity:", config.appearance.opacity, 0.0, 1.0);
-        *self.ivars().opacity_slider.borrow_mut() = Some(opacity_row.1.clone());
-        unsafe {
-            stack.addArrangedSubview(&opacity_row.0);
-        }
-
-        // Bold is bright
-        let bold_checkbox = self.create_checkbox(
-            mtm,
-            "Bold text uses bright colors",
-            config.appearance.bold_is_bright,
-        );
-        *self.ivars().bold_bright_checkbox.borrow_mut() = Some(bold_checkbox.clone());
-        unsafe {
-            stack.addArrangedSubview(&bold_checkbox);
-        }
-
-        tab.setView(Some(&stack));
-        tab
-    }
-
-    fn create_tabs_tab(&self, mtm: MainThreadMarker, config: &Config) -> Retained<NSTabViewItem> {
-        let tab = NSTabViewItem::new();
-        tab.setLabel(&NSString::from_str("Tabs"));
-
-        let stack = unsafe {
-            let stack = NSStackView::new(mtm);
-            stack.setOrientation(objc2_app_kit::NSUserInterfaceLayoutOrientation::Vertical);
-            stack.setAlignment(objc2_app_kit::NSLayoutAttribute::Leading);
-            stack.setSpacing(12.0);
-            stack.setEdgeInsets(objc2_foundation::NSEdgeInsets {
-                top: 16.0,
-                left: 16.0,
-                bottom: 16.0,
-                right: 16.0,
-            });
-            stack
-        };
-
-        // Show tab bar
-        let show_options = [
-            ("always", "Always"),
-            ("multiple", "When multiple tabs"),
-            ("never", "Never"),
-        ];
-        let show_id = match config.tabs.show_tab_bar {
-            TabBarVisibility::Always => "always",
-            TabBarVisibility::Multiple => "multiple",
-            TabBarVisibility::Never => "never",
-        };
-        let show_row = self.create_label_popup_row(mtm, "Show tab bar:", &show_options, show_id);
-        *self.ivars().show_tab_bar_popup.borrow_mut() = Some(show_row.1.clone());
-        unsafe {
-            stack.addArrangedSubview(&show_row.0);
-        }
-
-        // Tab bar position
-        let position_options = [("top", "Top"), ("bottom", "Bottom")];
-        let position_id = match config.tabs.tab_bar_position {
-            TabBarPosition::Top => "top",
-            TabBarPosition::Bottom => "bottom",
-        };
-        let position_row =
-            self.create_label_popup_row(mtm, "Tab bar position:", &position_options, position_id);
-        *self.ivars().tab_position_popup.borrow_mut() = Some(position_row.1.clone());
-        unsafe {
-            stack.addArrangedSubview(&position_row.0);
-        }
-
-        // New tab position
-        let new_options = [("end", "At end"), ("after_current", "After current")];
-        let new_id = match config.tabs.new_tab_position {
-            NewTabPosition::End => "end",
-            NewTabPosition::AfterCurrent => "after_current",
-        };
-        let new_row = self.create_label_popup_row(mtm, "New tab position:", &new_options, new_id);
-        *self.ivars().new_tab_popup.borrow_mut() = Some(new_row.1.clone());
-        unsafe {
-            stack.addArrangedSubview(&new_row.0);
-        }
-
-        // Show close button
-        let close_checkbox = self.create_checkbox(
-            mtm,
-            "Show close button on tabs",
-            config.tabs.show_close_button,
-        );
-        *self.ivars().show_close_checkbox.borrow_mut() = Some(close_checkbox.clone());
-        unsafe {
-            stack.addArrangedSubview(&close_checkbox);
+        setting: &Setting,
+    ) -> (Retained<NSView>, SettingControl) {
+        let label = format!("{}:", setting.label);
+        match setting.kind {
+            SettingKind::Bool => {
+                let checkbox = self.create_checkbox(mtm, setting.label, false);
+                let content = Retained::into_super(Retained::into_super(checkbox.clone()));
+                (content, SettingControl::Checkbox(checkbox))
+            }
+            SettingKind::Integer { .. } | SettingKind::Float { .. } | SettingKind::Text => {
+                let (row, field) = self.create_label_field_row(mtm, &label, "");
+                (Retained::into_super(row), SettingControl::Field(field))
+            }
+            SettingKind::Ratio => {
+                let (row, slider) = self.create_label_slider_row(mtm, &label, 0.0, 0.0, 1.0);
+                (Retained::into_super(row), SettingControl::Slider(slider))
+            }
+            SettingKind::Choice(_) | SettingKind::Theme => {
+                let choices = setting.choices();
+                let options: Vec<(&str, &str)> = choices
+                    .iter()
+                    .map(|(value, title)| (value.as_str(), title.as_str()))
+                    .collect();
+                let (row, popup) = self.create_label_popup_row(mtm, &label, &options, "");
+                (Retained::into_super(row), SettingControl::Popup(popup))
+            }
         }
-
-        tab.setView(Some(&stack));
-        tab
     }
 
     fn create_tools_tab(&self, mtm: MainThreadMarker) -> Retained<NSTabViewItem> {
@@ -1137,6 +1015,107 @@ impl PreferencesWindow {
         (row, slider)
     }
 
+    /// Add a settings row with a reset button and register it for search
+    fn add_setting_row(
+        &self,
+        mtm: MainThreadMarker,
+        stack: &NSStackView,
+        setting: &'static Setting,
+        content: &NSView,
+        control: SettingControl,
+    ) {
+        let Some(index) = SETTINGS.iter().position(|s| s.key == setting.key) else {
+            return;
+        };
+
+        let row = unsafe {
+            let row = NSStackView::new(mtm);
+            row.setOrientation(objc2_app_kit::NSUserInterfaceLayoutOrientation::Horizontal);
+            row.setSpacing(8.0);
+            row.addArrangedSubview(content);
+            row
+        };
+
+        let reset_btn = unsafe {
+            NSButton::buttonWithTitle_target_action(
+                &NSString::from_str("Reset"),
+                Some(self),
+                Some(sel!(resetSetting:)),
+                mtm,
+            )
+        };
+        reset_btn.setTag(index as isize);
+        reset_btn.setToolTip(Some(&NSString::from_str("Reset to default")));
+        unsafe {
+            row.addArrangedSubview(&reset_btn);
+            stack.addArrangedSubview(&row);
+        }
+
+        self.ivars().setting_rows.borrow_mut().push(SettingRow {
+            setting,
+            row,
+            control,
+        });
+    }
+
+    /// Put a setting's control back to the default value
+    fn reset_setting(&self, setting: &Setting) {
+        let Some(default) = settings::default_value(setting.key) else {
+            return;
+        };
+        let rows = self.ivars().setting_rows.borrow();
+        let Some(row) = rows.iter().find(|row| row.setting.key == setting.key) else {
+            return;
+        };
+        show_value(&row.control, setting, &default);
+    }
+
+    /// Show only the settings matching `query` and count matches per tab
+    fn filter_settings(&self, query: &str) {
+        let query = query.trim();
+        let mut matches = [0usize; SettingCategory::ALL.len()];
+        for row in self.ivars().setting_rows.borrow().iter() {
+            let visible = row.setting.matches(query);
+            row.row.setHidden(!visible);
+            if visible {
+                if let Some(i) = SettingCategory::ALL
+                    .iter()
+                    .position(|category| *category == row.setting.category)
+                {
+                    matches[i] += 1;
+                }
+            }
+        }
+
+        let Some(ref tab_view) = *self.ivars().tab_view.borrow() else {
+            return;
+        };
+        for (i, category) in SettingCategory::ALL.iter().enumerate() {
+            let label = if query.is_empty() {
+                category.name().to_string()
+            } else {
+                format!("{} ({})", category.name(), matches[i])
+            };
+            tab_view
+                .tabViewItemAtIndex(i as isize)
+                .setLabel(&NSString::from_str(&label));
+        }
+
+        // Jump to the first tab with a match if the current one has none
+        if query.is_empty() {
+            return;
+        }
+        let current = tab_view
+            .selectedTabViewItem()
+            .map(|item| tab_view.indexOfTabViewItem(&item))
+            .unwrap_or(0) as usize;
+        if matches.get(current).copied().unwrap_or(0) == 0 {
+            if let Some(first) = matches.iter().position(|&count| count > 0) {
+                tab_view.selectTabViewItemAtIndex(first as isize);
+            }
+        }
+    }
+
     fn create_checkbox(
         &self,
         mtm: MainThreadMarker,
@@ -1156,101 +1135,22 @@ impl PreferencesWindow {
         checkbox
     }
 
-    fn collect_and_save(&self) {
+    /// The configuration as edited in the window
+    fn collect_config(&self) -> Config {
         let mut config = self.ivars().config.borrow().clone();
 
-        // Collect General settings
-        if let Some(ref field) = *self.ivars().scrollback_field.borrow() {
-            let value = field.stringValue().to_string();
-            if let Ok(lines) = value.parse::<usize>() {
-                config.general.scrollback_lines = lines;
-            }
-        }
-        if let Some(ref checkbox) = *self.ivars().confirm_close_checkbox.borrow() {
-            config.general.confirm_close_with_running = checkbox.state() == 1;
-        }
-        if let Some(ref checkbox) = *self.ivars().copy_on_select_checkbox.borrow() {
-            config.general.copy_on_select = checkbox.state() == 1;
-        }
-
-        // Collect Appearance settings
-        if let Some(ref popup) = *self.ivars().theme_popup.borrow() {
-            if let Some(item) = popup.selectedItem() {
-                if let Some(obj) = item.representedObject() {
-                    let id: &NSString = unsafe { &*(&*obj as *const _ as *const NSString) };
-                    config.appearance.theme = id.to_string();
-                }
-            }
-        }
-        if let Some(ref field) = *self.ivars().font_field.borrow() {
-            config.appearance.font.family = field.stringValue().to_string();
-        }
-        if let Some(ref field) = *self.ivars().font_size_field.borrow() {
-            let value = field.stringValue().to_string();
-            if let Ok(size) = value.parse::<f64>() {
-                config.appearance.font.size = size;
-            }
-        }
-        if let Some(ref popup) = *self.ivars().cursor_popup.borrow() {
-            if let Some(item) = popup.selectedItem() {
-                if let Some(obj) = item.representedObject() {
-                    let id: &NSString = unsafe { &*(&*obj as *const _ as *const NSString) };
-                    config.appearance.cursor_style = match id.to_string().as_str() {
-                        "underline" => CursorStyleConfig::Underline,
-                        "bar" => CursorStyleConfig::Bar,
-                        _ => CursorStyleConfig::Block,
-                    };
-                }
+        for row in self.ivars().setting_rows.borrow().iter() {
+            let result = control_value(&row.control, row.setting)
+                .and_then(|value| settings::set(&mut config, row.setting.key, value));
+            if let Err(e) = result {
+                log::warn!("{}", e);
             }
         }
-        if let Some(ref checkbox) = *self.ivars().cursor_blink_checkbox.borrow() {
-            config.appearance.cursor_blink = checkbox.state() == 1;
-        }
-        if let Some(ref slider) = *self.ivars().opacity_slider.borrow() {
-            config.appearance.opacity = slider.doubleValue();
-        }
-        if let Some(ref checkbox) = *self.ivars().bold_bright_checkbox.borrow() {
-            config.appearance.bold_is_bright = checkbox.state() == 1;
-        }
+        config
+    }
 
-        // Collect Tabs settings
-        if let Some(ref popup) = *self.ivars().show_tab_bar_popup.borrow() {
-            if let Some(item) = popup.selectedItem() {
-                if let Some(obj) = item.representedObject() {
-                    let id: &NSString = unsafe { &*(&*obj as *const _ as *const NSString) };
-                    config.tabs.show_tab_bar = match id.to_string().as_str() {
-                        "multiple" => TabBarVisibility::Multiple,
-                        "never" => TabBarVisibility::Never,
-                        _ => TabBarVisibility::Always,
-                    };
-                }
-            }
-        }
-        if let Some(ref popup) = *self.ivars().tab_position_popup.borrow() {
-            if let Some(item) = popup.selectedItem() {
-                if let Some(obj) = item.representedObject() {
-                    let id: &NSString = unsafe { &*(&*obj as *const _ as *const NSString) };
-                    config.tabs.tab_bar_position = match id.to_string().as_str() {
-                        "bottom" => TabBarPosition::Bottom,
-                        _ => TabBarPosition::Top,
-                    };
-                }
-            }
-        }
-        if let Some(ref popup) = *self.ivars().new_tab_popup.borrow() {
-            if let Some(item) = popup.selectedItem() {
-                if let Some(obj) = item.representedObject() {
-                    let id: &NSString = unsafe { &*(&*obj as *const _ as *const NSString) };
-                    config.tabs.new_tab_position = match id.to_string().as_str() {
-                        "after_current" => NewTabPosition::AfterCurrent,
-                        _ => NewTabPosition::End,
-                    };
-                }
-            }
-        }
-        if let Some(ref checkbox) = *self.ivars().show_close_checkbox.borrow() {
-            config.tabs.show_close_button = checkbox.state() == 1;
-        }
+    fn collect_and_save(&self) {
+        let config = self.collect_config();
 
         // Save config to file
         if let Err(e) = save_config(&config) {
@@ -1301,6 +1201,8 @@ impl PreferencesWindow {
             }
         }
 
+        self.ivars().preview.borrow_mut().commit(config.clone());
+
         // Call the on_save callback
         if let Some(ref callback) = *self.ivars().on_save.borrow() {
             callback(config);
@@ -1308,6 +1210,59 @@ impl PreferencesWindow {
     }
 }
 
+/// Show `value` in the control of `setting`
+fn show_value(control: &SettingControl, setting: &Setting, value: &Value) {
+    match control {
+        SettingControl::Field(field) => {
+            field.setStringValue(&NSString::from_str(&settings::format_value(value)));
+        }
+        SettingControl::Popup(popup) => {
+            let choices: Vec<(String, String)> = popup
+                .itemArray()
+                .iter()
+                .map(|item| (represented_string(&item), item.title().to_string()))
+                .collect();
+            if let Some(index) = setting.choice_index(&choices, value) {
+                popup.selectItemAtIndex(index as isize);
+            }
+        }
+        SettingControl::Slider(slider) => {
+            if let Some(v) = value.as_float() {
+                slider.setDoubleValue(v);
+            }
+        }
+        SettingControl::Checkbox(checkbox) => {
+            checkbox.setState(if value.as_bool() == Some(true) { 1 } else { 0 });
+        }
+    }
+}
+
+/// Read the value shown in the control of `setting`
+fn control_value(control: &SettingControl, setting: &Setting) -> Result<Value, SettingError> {
+    match control {
+        SettingControl::Field(field) => setting.parse(&field.stringValue().to_string()),
+        SettingControl::Popup(popup) => {
+            let value = popup
+                .selectedItem()
+                .map(|item| represented_string(&item))
+                .unwrap_or_default();
+            setting.check(Value::String(value))
+        }
+        SettingControl::Slider(slider) => setting.check(Value::Float(slider.doubleValue())),
+        SettingControl::Checkbox(checkbox) => Ok(Value::Boolean(checkbox.state() == 1)),
+    }
+}
+
+/// The string a popup item was tagged with
+fn represented_string(item: &NSMenuItem) -> String {
+    item.representedObject()
+        .map(|obj| {
+            let id: &NSString = unsafe { &*(&*obj as *const _ as *const NSString) };
+            id.to_string()
+        })
+        .unwrap_or_default()
+}
+
 /// Show the preferences window
 pub fn show_preferences(
     mtm: MainThreadMarker,
@@ -0,0 +1,46 @@
+//! Quick Look previews
+//!
+//! QLPreviewPanel lives in the Quartz framework, which has no objc2 bindings,
+//! so the class is looked up at runtime. The panel asks the responder chain
+//! for a controller; terminal views accept control while they have an item
+//! to preview and act as the panel's data source.
+
+use objc2::msg_send;
+use objc2::rc::Retained;
+use objc2::runtime::{AnyClass, AnyObject};
+
+#[link(name = "Quartz", kind = "framework")]
+extern "C" {}
+
+/// The shared preview panel, if Quick Look is available
+fn shared_panel() -> Option<Retained<AnyObject>> {
+    let class = AnyClass::get(c"QLPreviewPanel")?;
+    unsafe { msg_send![class, sharedPreviewPanel] }
+}
+
+/// Show the preview panel, or reload it if it's already open
+///
+/// The panel takes its item from whichever responder accepts control, so the
+/// caller must be in the key window's responder chain.
+pub fn show() {
+    let Some(panel) = shared_panel() else {
+        log::warn!("Quick Look is not available");
+        return;
+    };
+    unsafe {
+        let visible: bool = msg_send![&*panel, isVisible];
+        if visible {
+            let _: () = msg_send![&*panel, reloadData];
+        } else {
+            let _: () = msg_send![&*panel, makeKeyAndOrderFront: std::ptr::null::<AnyObject>()];
+        }
+    }
+}
+
+/// Point the panel at a new controller
+pub fn set_controller(panel: &AnyObject, controller: Option<&AnyObject>) {
+    unsafe {
+        let _: () = msg_send![panel, setDataSource: controller];
+        let _: () = msg_send![panel, setDelegate: controller];
+    }
+}
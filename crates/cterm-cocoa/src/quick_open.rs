@@ -303,12 +303,10 @@ impl QuickOpenOverlay {
     pub fn hide(&self) {
         self.setHidden(true);
 
-        // Return focus to the window's content view (terminal)
+        // Return focus to the terminal the overlay is shown over
         unsafe {
-            if let Some(window) = self.window() {
-                if let Some(content) = window.contentView() {
-                    window.makeFirstResponder(Some(&content));
-                }
+            if let (Some(window), Some(terminal)) = (self.window(), self.superview()) {
+                window.makeFirstResponder(Some(&terminal));
             }
         }
     }
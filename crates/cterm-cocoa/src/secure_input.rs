@@ -0,0 +1,77 @@
+//! Secure Keyboard Entry
+//!
+//! While secure event input is on, macOS keeps other processes from reading
+//! keystrokes through event taps. Like Terminal.app, the setting persists in
+//! the user defaults and is only held while cterm is the active application:
+//! secure input is system-wide and would otherwise break global shortcuts in
+//! other apps.
+
+use std::cell::Cell;
+
+use objc2_app_kit::NSApplication;
+use objc2_foundation::{MainThreadMarker, NSString, NSUserDefaults};
+
+/// User defaults key for the setting
+const DEFAULTS_KEY: &str = "SecureKeyboardEntry";
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    fn EnableSecureEventInput() -> i32;
+    fn DisableSecureEventInput() -> i32;
+}
+
+thread_local! {
+    /// Whether we currently hold secure input (the calls are reference counted)
+    static HELD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether Secure Keyboard Entry is turned on
+pub fn is_enabled() -> bool {
+    NSUserDefaults::standardUserDefaults().boolForKey(&NSString::from_str(DEFAULTS_KEY))
+}
+
+/// Turn Secure Keyboard Entry on or off, taking effect immediately if cterm
+/// is active
+pub fn set_enabled(mtm: MainThreadMarker, enabled: bool) {
+    NSUserDefaults::standardUserDefaults()
+        .setBool_forKey(enabled, &NSString::from_str(DEFAULTS_KEY));
+    if enabled && NSApplication::sharedApplication(mtm).isActive() {
+        acquire();
+    } else if !enabled {
+        release();
+    }
+    log::info!(
+        "Secure Keyboard Entry {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+}
+
+/// Call when the application becomes active
+pub fn app_did_become_active() {
+    if is_enabled() {
+        acquire();
+    }
+}
+
+/// Call when the application is about to resign active
+pub fn app_will_resign_active() {
+    release();
+}
+
+fn acquire() {
+    if !HELD.replace(true) {
+        let status = unsafe { EnableSecureEventInput() };
+        if status != 0 {
+            log::warn!("EnableSecureEventInput failed: {}", status);
+            HELD.set(false);
+        }
+    }
+}
+
+fn release() {
+    if HELD.replace(false) {
+        unsafe {
+            DisableSecureEventInput();
+        }
+    }
+}
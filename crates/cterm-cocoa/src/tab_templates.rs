@@ -34,6 +34,7 @@ pub struct TabTemplatesWindowIvars {
     color_well: RefCell<Option<Retained<NSColorWell>>>,
     background_color_well: RefCell<Option<Retained<NSColorWell>>>,
     theme_field: RefCell<Option<Retained<NSTextField>>>,
+    profile_field: RefCell<Option<Retained<NSTextField>>>,
     unique_checkbox: RefCell<Option<Retained<NSButton>>>,
     keep_open_checkbox: RefCell<Option<Retained<NSButton>>>,
     // Remote daemon popup ("Local" + configured remotes)
@@ -251,6 +252,7 @@ impl TabTemplatesWindow {
             color_well: RefCell::new(None),
             background_color_well: RefCell::new(None),
             theme_field: RefCell::new(None),
+            profile_field: RefCell::new(None),
             unique_checkbox: RefCell::new(None),
             keep_open_checkbox: RefCell::new(None),
             // Remote daemon popup
@@ -530,6 +532,11 @@ impl TabTemplatesWindow {
         stack.addView_inGravity(&theme_row.0, NSStackViewGravity::Top);
         *self.ivars().theme_field.borrow_mut() = Some(theme_row.1);
 
+        // Profile field (a `[[profiles]]` name)
+        let profile_row = self.create_field_row(mtm, "Profile:", 150.0);
+        stack.addView_inGravity(&profile_row.0, NSStackViewGravity::Top);
+        *self.ivars().profile_field.borrow_mut() = Some(profile_row.1);
+
         // Checkboxes
         let unique_cb = unsafe {
             NSButton::checkboxWithTitle_target_action(
@@ -959,6 +966,11 @@ impl TabTemplatesWindow {
             if let Some(field) = self.ivars().theme_field.borrow().as_ref() {
                 field.setStringValue(&NSString::from_str(template.theme.as_deref().unwrap_or("")));
             }
+            if let Some(field) = self.ivars().profile_field.borrow().as_ref() {
+                field.setStringValue(&NSString::from_str(
+                    template.profile.as_deref().unwrap_or(""),
+                ));
+            }
             if let Some(cb) = self.ivars().unique_checkbox.borrow().as_ref() {
                 cb.setState(if template.unique { 1 } else { 0 });
             }
@@ -1155,6 +1167,9 @@ impl TabTemplatesWindow {
         if let Some(field) = self.ivars().theme_field.borrow().as_ref() {
             field.setStringValue(&empty);
         }
+        if let Some(field) = self.ivars().profile_field.borrow().as_ref() {
+            field.setStringValue(&empty);
+        }
         if let Some(cb) = self.ivars().unique_checkbox.borrow().as_ref() {
             cb.setState(0);
         }
@@ -1329,6 +1344,14 @@ impl TabTemplatesWindow {
                 let theme = field.stringValue().to_string();
                 template.theme = if theme.is_empty() { None } else { Some(theme) };
             }
+            if let Some(field) = self.ivars().profile_field.borrow().as_ref() {
+                let profile = field.stringValue().to_string();
+                template.profile = if profile.is_empty() {
+                    None
+                } else {
+                    Some(profile)
+                };
+            }
             if let Some(cb) = self.ivars().unique_checkbox.borrow().as_ref() {
                 template.unique = cb.state() != 0;
             }
@@ -1,16 +1,19 @@
 //! Terminal view implementation for macOS
 //!
-//! NSView subclass that renders the terminal using CoreGraphics.
+//! NSView subclass that renders the terminal using CoreGraphics, or Metal for
+//! large windows.
 
 use std::cell::{Cell, RefCell};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
-use objc2::rc::Retained;
+use objc2::rc::{Allocated, Retained};
 use objc2::runtime::AnyObject;
 use objc2::{class, define_class, msg_send, sel, DefinedClass, MainThreadOnly};
 use objc2_app_kit::{
-    NSApplication, NSEvent, NSMenu, NSMenuItem, NSRequestUserAttentionType, NSTextInputClient,
+    NSApplication, NSCursor, NSEvent, NSEventPhase, NSImage, NSMenu, NSMenuItem, NSPopover,
+    NSRequestUserAttentionType, NSTextInputClient, NSTouchBar, NSTouchBarDelegate, NSTouchBarItem,
     NSView,
 };
 use objc2_foundation::{
@@ -19,17 +22,44 @@ use objc2_foundation::{
 };
 use parking_lot::Mutex;
 
-use cterm_app::config::Config;
-use cterm_core::screen::{ScreenConfig, SelectionMode};
+use cterm_app::auto_switch::{AutoSwitchChange, AutoSwitcher};
+use cterm_app::clipboard_history::{self, ClipSource};
+use cterm_app::config::{
+    AutoSwitchRule, CommandNotifyConfig, Config, InputConfig, PresentationConfig, SoundsConfig,
+    TriggerConfig,
+};
+use cterm_app::link_preview::{self, LinkPreview, PreviewTarget, HOVER_DELAY};
+use cterm_app::live_config::AppearanceChange;
+use cterm_app::paste::{PasteGuard, PasteJob};
+use cterm_app::presentation::{KeystrokeLog, KEYSTROKE_TIMEOUT};
+use cterm_app::session;
+use cterm_app::sounds::{self, Cue};
+use cterm_app::status_segments;
+use cterm_app::tmux::{PaneId, TmuxEvent, TmuxGateway};
+use cterm_app::triggers::{self, TriggerEffect, TriggerEngine};
+use cterm_core::detect;
+use cterm_core::hints::{HintAction, HintInput, HintMode};
+use cterm_core::screen::{
+    ClipboardOperation, CommandFinished, PointerShape, ScreenConfig, SelectionMode, TmuxControl,
+};
 use cterm_core::term::TerminalEvent;
-use cterm_core::Terminal;
+use cterm_core::{
+    Capability, OutputStats, PermissionPolicy, Player, Terminal, TransferFile, WatchMatch,
+};
+use cterm_ui::events::Action;
+use cterm_ui::gutter::{gutter_block_at, gutter_tooltip, GUTTER_HOVER_WIDTH};
+use cterm_ui::pointer::{PointerHider, PointerMotion};
+use cterm_ui::prompt::{Prompt, PromptAction, PromptQueue};
+use cterm_ui::status::{command_summary, StatusStrip};
 use cterm_ui::theme::Theme;
+use cterm_ui::utils::{dir_title, Magnifier, PinchZoom};
 
 use crate::cg_renderer::CGRenderer;
 use crate::file_transfer::PendingFileManager;
+use crate::metal_renderer::{self, MetalRenderer};
 use crate::mouse::{self, MouseButton, MouseModifiers};
 use crate::notification_bar::{NotificationBar, NOTIFICATION_BAR_HEIGHT};
-use crate::{clipboard, keycode};
+use crate::{clipboard, keycode, link_popover, menu, quick_look, touch_bar};
 
 /// Shared state between the view and PTY thread
 struct ViewState {
@@ -45,6 +75,33 @@ struct ViewState {
     title_locked: AtomicBool,
     /// Flag indicating bell was triggered and needs UI update
     bell_changed: AtomicBool,
+    /// The program asked for another mouse pointer shape (OSC 22)
+    pointer_shape_changed: AtomicBool,
+    /// Commands finished since the UI last reported them
+    finished_commands: Mutex<Vec<CommandFinished>>,
+    /// Output rates not yet shown in the status strip
+    output_stats: Mutex<Option<OutputStats>>,
+    /// Latest watch expression match not yet shown on the tab
+    watch_match: Mutex<Option<WatchMatch>>,
+    /// Output lines not yet checked against the triggers
+    completed_lines: Mutex<Vec<String>>,
+    /// A ZMODEM or Kermit transfer started or finished
+    transfer_changed: AtomicBool,
+    /// A remote `rz` or `kermit -r` is waiting for files
+    transfer_files_requested: AtomicBool,
+    /// A remote `it2ul` is waiting for files
+    upload_requested: AtomicBool,
+    /// Clipboard operations, notifications and permission requests not yet
+    /// handled
+    requests: Mutex<Vec<TerminalEvent>>,
+    /// Redraw interval for the window's display (shorter on ProMotion screens)
+    frame_interval_us: AtomicU64,
+    /// Read-only mirror of a session owned by another tab
+    monitor: AtomicBool,
+    /// Client of the tmux running in control mode in this view, if any
+    tmux: Mutex<Option<Arc<TmuxGateway>>>,
+    /// What tmux asked of the UI since it last looked
+    tmux_events: Mutex<Vec<TmuxEvent>>,
 }
 
 impl Default for ViewState {
@@ -57,6 +114,41 @@ impl Default for ViewState {
             title_changed: AtomicBool::new(false),
             title_locked: AtomicBool::new(false),
             bell_changed: AtomicBool::new(false),
+            pointer_shape_changed: AtomicBool::new(false),
+            finished_commands: Mutex::new(Vec::new()),
+            output_stats: Mutex::new(None),
+            watch_match: Mutex::new(None),
+            completed_lines: Mutex::new(Vec::new()),
+            transfer_changed: AtomicBool::new(false),
+            transfer_files_requested: AtomicBool::new(false),
+            upload_requested: AtomicBool::new(false),
+            requests: Mutex::new(Vec::new()),
+            frame_interval_us: AtomicU64::new(16_667),
+            monitor: AtomicBool::new(false),
+            tmux: Mutex::new(None),
+            tmux_events: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl ViewState {
+    /// Pass control mode output of `terminal` to its tmux gateway, starting
+    /// one when control mode starts, and leave the events for the UI
+    fn handle_tmux(&self, terminal: &Arc<Mutex<Terminal>>, control: &[TmuxControl]) {
+        let mut tmux = self.tmux.lock();
+        if control.contains(&TmuxControl::Started) {
+            let terminal = Arc::clone(terminal);
+            *tmux = Some(TmuxGateway::new(move |data| {
+                if let Err(e) = terminal.lock().write(data) {
+                    log::error!("Failed to write to tmux: {}", e);
+                }
+            }));
+        }
+        if let Some(ref gateway) = *tmux {
+            self.tmux_events.lock().extend(gateway.handle(control));
+            if gateway.is_exited() {
+                *tmux = None;
+            }
         }
     }
 }
@@ -69,14 +161,24 @@ enum DaemonCommand {
     SetTitle(String),
     SetTabColor(String),
     SetTemplateName(String),
+    /// Copy to the daemon's clipboard
+    SetClipboard(Vec<u8>),
 }
 
 /// Terminal view state
 pub struct TerminalViewIvars {
     terminal: Arc<Mutex<Terminal>>,
     renderer: RefCell<Option<CGRenderer>>,
-    cell_width: f64,
-    cell_height: f64,
+    /// GPU renderer used for large windows (None if Metal is unavailable)
+    metal: RefCell<Option<MetalRenderer>>,
+    cell_width: Cell<f64>,
+    cell_height: Cell<f64>,
+    /// Current font size (changed by zoom and presentation mode)
+    font_size: Cell<f64>,
+    /// Configured font size for the window's display, restored by zoom reset
+    default_font_size: Cell<f64>,
+    /// Pinch gesture progress towards the next zoom step
+    pinch: Cell<PinchZoom>,
     /// Shared state with PTY thread
     state: Arc<ViewState>,
     /// Whether we're currently in a selection drag
@@ -87,22 +189,71 @@ pub struct TerminalViewIvars {
     auto_scroll_col: Cell<usize>,
     /// Timer for auto-scroll during selection drag
     auto_scroll_timer: RefCell<Option<Retained<objc2_foundation::NSTimer>>>,
+    /// Paste being written a chunk at a time
+    paste: RefCell<Option<PasteJob>>,
+    /// Timer writing the next paste chunk
+    paste_timer: RefCell<Option<Retained<objc2_foundation::NSTimer>>>,
+    /// Mouse wheel and pointer settings
+    input: InputConfig,
+    /// Hides the pointer while typing
+    pointer_hider: Cell<PointerHider>,
+    /// When to report finished commands
+    command_notify: Cell<CommandNotifyConfig>,
+    /// Sounds or speech for the bell and finished commands
+    sounds: SoundsConfig,
+    /// Whether copying also puts the selection's colors on the clipboard
+    copy_rich_text: bool,
+    /// When to confirm a paste
+    paste_guard: PasteGuard,
     /// Template name (if this view was created from a template)
     template_name: RefCell<Option<String>>,
     /// Daemon session ID for this terminal
     session_id: RefCell<Option<String>>,
     /// Marked text for IME input (Japanese, Chinese, etc.)
     marked_text: RefCell<String>,
-    /// Notification bar for file transfers
+    /// Notification bar showing the current prompt
     notification_bar: RefCell<Option<Retained<NotificationBar>>>,
+    /// Received files and clipboard reads waiting for an answer
+    prompts: RefCell<PromptQueue>,
     /// Pending file manager for file transfers
     file_manager: RefCell<PendingFileManager>,
+    /// File shown in the Quick Look panel while this view controls it
+    preview_url: RefCell<Option<Retained<objc2_foundation::NSURL>>>,
     /// Color palette for HTML export
-    color_palette: cterm_core::color::ColorPalette,
+    color_palette: RefCell<cterm_core::color::ColorPalette>,
     /// Command channel for daemon I/O (write + resize) — None for local PTY sessions
     daemon_cmd_tx: RefCell<Option<tokio::sync::mpsc::UnboundedSender<DaemonCommand>>>,
     /// Socket path for the daemon this terminal is connected to (None = local default)
     daemon_socket: RefCell<Option<std::path::PathBuf>>,
+    /// Surface magnification (scales the view, keeps the grid size)
+    magnifier: Cell<Magnifier>,
+    /// Recent keystrokes while presenting with the keystroke overlay enabled
+    keystrokes: RefCell<Option<KeystrokeLog>>,
+    /// Labelled links, paths and hashes while hint mode is active
+    hints: RefCell<Option<HintMode>>,
+    /// Transient states shown along the bottom edge
+    status: RefCell<StatusStrip>,
+    /// Per-host theme/tab color rules for this terminal
+    auto_switch: RefCell<AutoSwitcher>,
+    /// `[[triggers]]` watching this terminal's output
+    triggers: RefCell<TriggerEngine>,
+    /// Theme and tab color to restore when no auto switch rule matches
+    auto_switch_saved: RefCell<Option<(Theme, Option<String>)>>,
+    /// Recording played into the terminal — None for live sessions
+    playback: RefCell<Option<Player>>,
+    /// Timer advancing the playback
+    playback_timer: RefCell<Option<Retained<objc2_foundation::NSTimer>>>,
+    /// Gateway of the tmux session this view shows a pane of
+    tmux_pane: RefCell<Option<Arc<TmuxGateway>>>,
+    /// Views of the panes of the tmux running in this view
+    tmux_panes: RefCell<std::collections::HashMap<PaneId, Retained<TerminalView>>>,
+    /// Link or image path under the pointer and the cell it is at
+    hovered_link: RefCell<Option<(PreviewTarget, NSRect)>>,
+    /// Popover previewing the hovered link
+    link_popover: RefCell<Option<Retained<NSPopover>>>,
+    /// Set while the window moves the view into or out of a split, which
+    /// takes it out of the window for a moment
+    moving: Cell<bool>,
 }
 
 define_class!(
@@ -123,6 +274,9 @@ define_class!(
 
         #[unsafe(method(becomeFirstResponder))]
         fn become_first_responder(&self) -> bool {
+            if let Some(window) = self.cterm_window() {
+                window.pane_focused(self);
+            }
             true
         }
 
@@ -134,9 +288,13 @@ define_class!(
 
         #[unsafe(method(viewDidMoveToWindow))]
         fn view_did_move_to_window(&self) {
+            if self.ivars().moving.get() {
+                return;
+            }
             // Make ourselves first responder when added to window
             if let Some(window) = self.window() {
                 window.makeFirstResponder(Some(self));
+                self.setup_metal();
                 // Trigger initial resize to match window content size
                 self.handle_resize();
             }
@@ -154,10 +312,15 @@ define_class!(
         fn view_will_move_to_window(&self, new_window: Option<&objc2_app_kit::NSWindow>) {
             // If moving to nil window (being removed), mark view as invalid
             // This tells background threads to stop using the view pointer
-            if new_window.is_none() {
+            if new_window.is_none() && !self.ivars().moving.get() {
                 log::debug!("View being removed from window, marking invalid");
                 self.ivars().state.view_invalid.store(true, Ordering::SeqCst);
 
+                // The playback timer retains the view
+                if let Some(timer) = self.ivars().playback_timer.borrow_mut().take() {
+                    timer.invalidate();
+                }
+
                 // Destroy the daemon session (kill the PTY process)
                 if let Some(ref tx) = *self.ivars().daemon_cmd_tx.borrow() {
                     let _ = tx.send(DaemonCommand::Destroy);
@@ -179,27 +342,118 @@ define_class!(
             // Check for file transfers
             self.check_file_transfers();
 
+            self.update_frame_interval();
+            self.update_color_gamut();
+            self.update_auto_switch();
+
             if let Some(ref renderer) = *self.ivars().renderer.borrow() {
                 let terminal = self.ivars().terminal.lock();
                 // Always use full view bounds for rendering to avoid artifacts
                 // from partial dirty_rect updates after resize/fullscreen
                 let bounds: NSRect = unsafe { msg_send![self, bounds] };
+
+                let marked_text = self.ivars().marked_text.borrow();
+                let mut status = self.ivars().status.borrow_mut();
+                status.set_composing(Some(&marked_text));
+
+                // Overlays are only drawn by CoreGraphics
+                let badge = terminal.screen().badge();
+                let mut magnifier = self.ivars().magnifier.get();
+                let plain = !magnifier.is_active()
+                    && badge.is_empty()
+                    && !status.is_visible()
+                    && self.ivars().keystrokes.borrow().is_none()
+                    && self.ivars().hints.borrow().is_none();
+                if self.render_with_metal(renderer, &terminal, bounds, plain) {
+                    return;
+                }
+
+                // Magnify: scale the whole surface and pan to keep the cursor in view
+                if magnifier.is_active() {
+                    let cursor = &terminal.screen().cursor;
+                    let (cell_width, cell_height) = renderer.cell_size();
+                    magnifier.follow(
+                        (
+                            cursor.col as f64 * cell_width,
+                            cursor.row as f64 * cell_height,
+                            cell_width,
+                            cell_height,
+                        ),
+                        (bounds.size.width, bounds.size.height),
+                    );
+                    self.ivars().magnifier.set(magnifier);
+                    let (pan_x, pan_y) = magnifier.pan();
+                    unsafe {
+                        let _: () = msg_send![class!(NSGraphicsContext), saveGraphicsState];
+                        let transform: Retained<AnyObject> =
+                            msg_send![class!(NSAffineTransform), transform];
+                        let _: () = msg_send![&*transform, scaleBy: magnifier.factor()];
+                        let _: () = msg_send![&*transform, translateXBy: -pan_x, yBy: -pan_y];
+                        let _: () = msg_send![&*transform, concat];
+                    }
+                }
+
                 renderer.render(&terminal, bounds);
 
                 // Render IME marked text if present
-                let marked_text = self.ivars().marked_text.borrow();
                 if !marked_text.is_empty() {
                     let cursor = &terminal.screen().cursor;
                     renderer.render_marked_text(&marked_text, cursor.row, cursor.col);
                 }
+
+                if let Some(ref mode) = *self.ivars().hints.borrow() {
+                    renderer.render_hints(mode);
+                }
+
+                if magnifier.is_active() {
+                    unsafe {
+                        let _: () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+                    }
+                }
+
+                renderer.render_badge(&badge, bounds);
+                renderer.render_status(&status.segments(), bounds);
+                drop(status);
+
+                if let Some(ref mut log) = *self.ivars().keystrokes.borrow_mut() {
+                    log.expire(Instant::now());
+                    if let Some(text) = log.text() {
+                        renderer.render_keystrokes(&text, bounds);
+                    }
+                }
             }
         }
 
+        /// Redraw after a keystroke overlay entry has expired
+        #[unsafe(method(expireKeystrokes))]
+        fn expire_keystrokes(&self) {
+            self.set_needs_display();
+        }
+
+        /// Clear the command-finished toast once it has expired
+        #[unsafe(method(expireToast))]
+        fn expire_toast(&self) {
+            self.update_status(|status| status.expire_toast(Instant::now()));
+        }
+
         #[unsafe(method(performKeyEquivalent:))]
         fn perform_key_equivalent(&self, event: &NSEvent) -> objc2::runtime::Bool {
             let modifiers = keycode::modifiers_from_event(event);
             let raw_keycode = event.keyCode();
 
+            // Cmd+Shift+Return and friends answer the prompt in the
+            // notification bar
+            let prompt_action = keycode::keycode_from_event(event).and_then(|key| {
+                self.ivars()
+                    .prompts
+                    .borrow()
+                    .action_for_key(key, modifiers, true)
+            });
+            if let Some(action) = prompt_action {
+                self.answer_prompt(action);
+                return objc2::runtime::Bool::YES;
+            }
+
             // Handle Ctrl+Tab / Ctrl+Shift+Tab for tab switching
             // Tab key is virtual keycode 0x30 on macOS
             if raw_keycode == 0x30 && modifiers.contains(cterm_ui::events::Modifiers::CTRL) {
@@ -231,6 +485,43 @@ define_class!(
                 return;
             }
 
+            // Modifier keys alone come as flagsChanged:, not here
+            let mut hider = self.ivars().pointer_hider.get();
+            if hider.key_pressed(false) {
+                NSCursor::setHiddenUntilMouseMoves(true);
+            }
+            self.ivars().pointer_hider.set(hider);
+
+            // Hint mode takes every key until a label is chosen or cancelled
+            if self.ivars().hints.borrow().is_some() {
+                self.hint_mode_key(event);
+                return;
+            }
+
+            // Playback views take unmodified keys as player controls
+            let plain = !modifiers
+                .intersects(cterm_ui::events::Modifiers::CTRL | cterm_ui::events::Modifiers::ALT);
+            if plain && self.playback_key(event) {
+                return;
+            }
+
+            // Window snapping keys bound in [shortcuts]
+            if let Some(action) = self.snap_action(event, modifiers) {
+                self.perform_action(&action);
+                return;
+            }
+
+            // Escape cancels a paste in progress
+            if event.keyCode() == 53 && self.cancel_paste() {
+                return;
+            }
+
+            // Read-only: swallow the key without leaving the scrollback
+            if self.ivars().terminal.lock().is_input_locked() {
+                objc2_app_kit::NSBeep();
+                return;
+            }
+
             // Check if IME composition is in progress (has marked text)
             let has_marked_text = !self.ivars().marked_text.borrow().is_empty();
 
@@ -243,6 +534,21 @@ define_class!(
                 return;
             }
 
+            if let Some(ref mut log) = *self.ivars().keystrokes.borrow_mut() {
+                if let Some(key) = keycode::keycode_from_event(event) {
+                    log.record(key, modifiers, Instant::now());
+                    self.set_needs_display();
+                    unsafe {
+                        let _: () = msg_send![
+                            self,
+                            performSelector: sel!(expireKeystrokes),
+                            withObject: std::ptr::null::<AnyObject>(),
+                            afterDelay: KEYSTROKE_TIMEOUT.as_secs_f64()
+                        ];
+                    }
+                }
+            }
+
             // Reset scroll offset when any key is pressed (return to current content)
             {
                 let mut terminal = self.ivars().terminal.lock();
@@ -305,13 +611,47 @@ define_class!(
                 _ => None,
             };
 
+            // Keypad keys skip the input method when the application asked
+            // for keypad sequences (DECKPAM or the kitty keyboard protocol)
+            let key = key.or_else(|| {
+                let keypad = match raw_keycode {
+                    0x52 => '0',
+                    0x53 => '1',
+                    0x54 => '2',
+                    0x55 => '3',
+                    0x56 => '4',
+                    0x57 => '5',
+                    0x58 => '6',
+                    0x59 => '7',
+                    0x5B => '8',
+                    0x5C => '9',
+                    0x41 => '.',
+                    0x45 => '+',
+                    0x4E => '-',
+                    0x43 => '*',
+                    0x4B => '/',
+                    0x51 => '=',
+                    0x4C => '\r',
+                    _ => return None,
+                };
+                let terminal = self.ivars().terminal.lock();
+                let screen = terminal.screen();
+                (screen.modes.application_keypad || !screen.keyboard_flags().is_empty())
+                    .then_some(Key::Keypad(keypad))
+            });
+
             // Convert cterm_ui Modifiers to cterm_core Modifiers
             let core_mods = cterm_core::term::Modifiers::from_bits_truncate(modifiers.bits());
 
             // If it's a special key, use Terminal::handle_key to get the escape sequence
             if let Some(key) = key {
+                let kind = if event.isARepeat() {
+                    cterm_core::KeyEventKind::Repeat
+                } else {
+                    cterm_core::KeyEventKind::Press
+                };
                 let terminal = self.ivars().terminal.lock();
-                if let Some(data) = terminal.handle_key(key, core_mods) {
+                if let Some(data) = terminal.handle_key_event(key, core_mods, kind) {
                     drop(terminal);
                     log::debug!("Special key: {:?} -> {:?}", key, data);
                     self.write_to_pty(&data);
@@ -357,27 +697,39 @@ define_class!(
             let location = self.convert_point_from_view(location_in_window, None);
 
             // Calculate cell position
-            let col = (location.x / self.ivars().cell_width).floor().max(0.0) as usize;
-            let row = (location.y / self.ivars().cell_height).floor().max(0.0) as usize;
+            let col = (location.x / self.ivars().cell_width.get()).floor().max(0.0) as usize;
+            let row = (location.y / self.ivars().cell_height.get()).floor().max(0.0) as usize;
+
+            // The status strip's Cancel button covers the bottom row
+            let on_strip = row + 1 >= self.ivars().terminal.lock().screen().height();
+            if self.ivars().status.borrow().is_pasting() && on_strip {
+                self.cancel_paste();
+                return;
+            }
+            if self.ivars().status.borrow().is_transferring() && on_strip {
+                self.ivars().terminal.lock().cancel_file_transfer();
+                self.update_status(|status| status.set_transfer(None));
+                return;
+            }
 
-            // Check for Cmd+click on hyperlinks
+            // Check for Cmd+click on hyperlinks and detected URLs or paths
             let flags = event.modifierFlags();
             if flags.contains(NSEventModifierFlags::Command) {
+                let cwd = self.foreground_cwd();
                 let terminal = self.ivars().terminal.lock();
                 let absolute_line = terminal.screen().visible_row_to_absolute_line(row);
+                let target = detect::span_at(terminal.screen(), absolute_line, col)
+                    .and_then(|span| span.open_target(cwd.as_deref()));
+                drop(terminal);
 
-                if let Some(cell) = terminal.screen().get_cell_with_scrollback(absolute_line, col) {
-                    if let Some(ref hyperlink) = cell.hyperlink {
-                        let uri = hyperlink.uri.clone();
-                        drop(terminal);
-                        self.open_url(&uri);
-                        return;
-                    }
+                if let Some(target) = target {
+                    self.open_target(&target);
+                    return;
                 }
-                drop(terminal);
             }
 
-            // Forward the press to a tracking application (Shift bypasses).
+            // Forward the press to a tracking application (the override
+            // modifier bypasses).
             if self.report_mouse_button(event, MouseButton::Left, false) {
                 // Track the drag so mouseDragged/mouseUp keep reporting.
                 self.ivars().is_selecting.set(true);
@@ -419,7 +771,8 @@ define_class!(
             self.ivars().is_selecting.set(false);
             self.stop_auto_scroll();
 
-            // Forward the release to a tracking application (Shift bypasses).
+            // Forward the release to a tracking application (the override
+            // modifier bypasses).
             if self.report_mouse_button(event, MouseButton::Release, false) {
                 return;
             }
@@ -461,12 +814,12 @@ define_class!(
             let location = self.convert_point_from_view(location_in_window, None);
 
             // Calculate cell position (clamp to valid range)
-            let col = (location.x / self.ivars().cell_width).floor().max(0.0) as usize;
-            let row = (location.y / self.ivars().cell_height).floor().max(0.0) as usize;
+            let col = (location.x / self.ivars().cell_width.get()).floor().max(0.0) as usize;
+            let row = (location.y / self.ivars().cell_height.get()).floor().max(0.0) as usize;
 
             // Forward drag motion to a tracking application (ButtonEvent /
-            // AnyEvent modes); Shift bypasses so a Shift-drag extends a local
-            // text selection instead.
+            // AnyEvent modes); the override modifier bypasses so such a drag
+            // extends a local text selection instead.
             if self.report_mouse_button(event, MouseButton::Left, true) {
                 return;
             }
@@ -474,7 +827,9 @@ define_class!(
             // Check if mouse is above or below the view for auto-scroll
             let view_frame: NSRect = unsafe { msg_send![self, frame] };
             let view_height = view_frame.size.height;
-            if location.y < 0.0 {
+            if self.ivars().input.autoscroll_lines == 0 {
+                // Auto-scroll disabled
+            } else if location.y < 0.0 {
                 // Mouse is above the view (flipped coords) - scroll up
                 self.ivars().auto_scroll_col.set(col);
                 self.start_auto_scroll(-1);
@@ -505,59 +860,73 @@ define_class!(
             }
 
             let col = self.ivars().auto_scroll_col.get();
-            let mut terminal = self.ivars().terminal.lock();
-
-            if direction < 0 {
-                // Scroll up (into scrollback)
-                terminal.scroll_viewport_up(1);
-                let line = terminal.screen().visible_row_to_absolute_line(0);
-                terminal.screen_mut().extend_selection(line, col);
-            } else {
-                // Scroll down (towards bottom)
-                terminal.scroll_viewport_down(1);
-                let rows = terminal.screen().height();
-                let line = terminal.screen().visible_row_to_absolute_line(rows.saturating_sub(1));
-                let width = terminal.screen().width();
-                terminal
-                    .screen_mut()
-                    .extend_selection(line, width.saturating_sub(1));
-            }
+            let lines = self.ivars().input.autoscroll_lines;
+            self.ivars()
+                .terminal
+                .lock()
+                .autoscroll_selection(direction < 0, lines, col);
 
-            drop(terminal);
             self.set_needs_display();
         }
 
+        /// Timer callback writing the next chunk of a paste
+        #[unsafe(method(pasteFire:))]
+        fn paste_fire(&self, _timer: &objc2_foundation::NSTimer) {
+            self.write_paste_chunk();
+        }
+
+        #[unsafe(method(playbackFire:))]
+        fn playback_fire(&self, _timer: &objc2_foundation::NSTimer) {
+            let mut playback = self.ivars().playback.borrow_mut();
+            let Some(player) = playback.as_mut() else {
+                return;
+            };
+            let changed = player.tick(&mut self.ivars().terminal.lock());
+            let status = player.status();
+            drop(playback);
+            self.update_status(|strip| strip.set_playback(Some(&status)));
+            if changed {
+                self.set_needs_display();
+            }
+        }
+
         #[unsafe(method(scrollWheel:))]
         fn scroll_wheel(&self, event: &NSEvent) {
-            use objc2_app_kit::NSEventModifierFlags;
-
             let delta_y = event.scrollingDeltaY();
             log::trace!("Scroll wheel delta: {}", delta_y);
             if delta_y == 0.0 {
                 return;
             }
-            let up = delta_y > 0.0;
-            let shift = event.modifierFlags().contains(NSEventModifierFlags::Shift);
-            // Movement scales with the wheel/trackpad delta, as the local
-            // scrollback path has always done on macOS.
-            let count = (delta_y.abs() / 2.0).max(1.0) as usize;
+            let input = self.ivars().input;
+            let up = input.wheel_up(delta_y > 0.0, event.isDirectionInvertedFromDevice());
+            let local = input.overrides_mouse_tracking(self.get_mouse_modifiers(event));
+            // Trackpad movement scales with the delta, as the local scrollback
+            // path has always done on macOS; wheel notches use wheel_lines.
+            let count = if event.hasPreciseScrollingDeltas() {
+                (delta_y.abs() / 2.0).max(1.0) as usize
+            } else {
+                input.wheel_scroll_lines(delta_y.abs().round() as usize)
+            };
 
             let terminal = self.ivars().terminal.lock();
             let mouse_mode = terminal.screen().modes.mouse_mode;
             let sgr_mouse = terminal.screen().modes.sgr_mouse;
             let in_alternate_screen = terminal.screen().modes.alternate_screen;
             let alternate_scroll = terminal.screen().modes.alternate_scroll;
+            let locked = terminal.is_input_locked();
             drop(terminal);
 
-            // Shift+wheel always scrolls cterm's own scrollback, overriding any
-            // application mouse/alternate-scroll handling (xterm/VTE convention).
-            if !shift {
+            // With the override modifier (Shift by default, the xterm/VTE
+            // convention) the wheel scrolls cterm's own scrollback, overriding
+            // any application mouse/alternate-scroll handling. So does the
+            // wheel of a read-only terminal.
+            if !local && !locked {
                 // 1) Application is tracking the mouse: forward a wheel report.
                 if mouse::should_capture_mouse(mouse_mode) {
                     let location_in_window = event.locationInWindow();
                     let location = self.convert_point_from_view(location_in_window, None);
-                    let col = (location.x / self.ivars().cell_width).floor().max(0.0) as usize;
-                    let row = (location.y / self.ivars().cell_height).floor().max(0.0) as usize;
+                    let col = (location.x / self.ivars().cell_width.get()).floor().max(0.0) as usize;
+                    let row = (location.y / self.ivars().cell_height.get()).floor().max(0.0) as usize;
                     let modifiers = self.get_mouse_modifiers(event);
                     let button = if up {
                         MouseButton::WheelUp
@@ -604,14 +973,21 @@ define_class!(
 
             // 3) Default: scroll cterm's local scrollback viewport.
             let mut terminal = self.ivars().terminal.lock();
+            let from = terminal.screen().scroll_offset;
             if up {
                 terminal.scroll_viewport_up(count);
             } else {
                 terminal.scroll_viewport_down(count);
             }
+            let to = terminal.screen().scroll_offset;
             drop(terminal);
 
             self.set_needs_display();
+            if from != to {
+                if let Some(partner) = self.cterm_window().and_then(|w| w.scroll_partner(self)) {
+                    partner.follow_scroll(from, to);
+                }
+            }
         }
 
         /// Set up mouse tracking area for hover detection
@@ -632,6 +1008,7 @@ define_class!(
             // Create new tracking area covering the entire view
             let mtm = MainThreadMarker::from(self);
             let options = NSTrackingAreaOptions::MouseMoved
+                | NSTrackingAreaOptions::MouseEnteredAndExited
                 | NSTrackingAreaOptions::ActiveInKeyWindow
                 | NSTrackingAreaOptions::InVisibleRect;
 
@@ -655,8 +1032,37 @@ define_class!(
             let location_in_window = event.locationInWindow();
             let location = self.convert_point_from_view(location_in_window, None);
 
-            let col = (location.x / self.ivars().cell_width).floor().max(0.0) as usize;
-            let row = (location.y / self.ivars().cell_height).floor().max(0.0) as usize;
+            // AppKit shows a pointer hidden while typing once the mouse moves;
+            // until then, leave the cursor alone
+            let mut hider = self.ivars().pointer_hider.get();
+            let motion = hider.pointer_moved(location_in_window.x, location_in_window.y);
+            self.ivars().pointer_hider.set(hider);
+            if motion == PointerMotion::Still {
+                return;
+            }
+
+            let col = (location.x / self.ivars().cell_width.get()).floor().max(0.0) as usize;
+            let row = (location.y / self.ivars().cell_height.get()).floor().max(0.0) as usize;
+
+            if self.ivars().input.hover_previews {
+                self.hover_link(col, row);
+            }
+
+            // Over the exit status gutter, describe the command there
+            let gutter = self
+                .ivars()
+                .renderer
+                .borrow()
+                .as_ref()
+                .is_some_and(|renderer| renderer.shows_command_gutter());
+            if gutter && location.x < GUTTER_HOVER_WIDTH {
+                let blocks = self.ivars().terminal.lock().screen().command_blocks();
+                match gutter_block_at(&blocks, location.x, row) {
+                    Some(block) => self.set_tooltip(&gutter_tooltip(block)),
+                    None => self.clear_tooltip(),
+                }
+                return;
+            }
 
             // Check if we're over a hyperlink
             let terminal = self.ivars().terminal.lock();
@@ -677,14 +1083,51 @@ define_class!(
                     return;
                 }
             }
+            let shape = terminal.screen().pointer_shape();
             drop(terminal);
 
-            // Clear tooltip and reset cursor if not over a hyperlink
+            // Clear tooltip and show the program's pointer if not over a
+            // hyperlink
             self.clear_tooltip();
-            unsafe {
-                let cursor: Retained<AnyObject> = msg_send![class!(NSCursor), IBeamCursor];
-                let _: () = msg_send![&*cursor, set];
+            set_pointer_cursor(shape, MainThreadMarker::from(self));
+        }
+
+        #[unsafe(method(mouseExited:))]
+        fn mouse_exited(&self, _event: &NSEvent) {
+            self.set_hovered_link(None);
+            let mut hider = self.ivars().pointer_hider.get();
+            hider.pointer_left();
+            self.ivars().pointer_hider.set(hider);
+        }
+
+        /// Preview the hovered link once the pointer has rested on it
+        #[unsafe(method(showLinkPreview))]
+        fn show_link_preview(&self) {
+            let Some((target, _)) = self.ivars().hovered_link.borrow().clone() else {
+                return;
+            };
+            if let Some(preview) = link_preview::cached(&target) {
+                if let Some(preview) = preview {
+                    self.present_link_preview(&preview);
+                }
+                return;
             }
+
+            // Load in the background, then show it if still hovered
+            let state = self.ivars().state.clone();
+            let view_ptr = self as *const Self as usize;
+            std::thread::spawn(move || {
+                let Some(preview) = link_preview::preview(&target) else {
+                    return;
+                };
+                #[allow(deprecated)]
+                dispatch2::Queue::main().exec_async(move || {
+                    if !state.view_invalid.load(Ordering::SeqCst) {
+                        let view = unsafe { &*(view_ptr as *const TerminalView) };
+                        view.present_link_preview(&preview);
+                    }
+                });
+            });
         }
 
         /// Copy selection to clipboard (Command+C)
@@ -692,9 +1135,26 @@ define_class!(
         fn action_copy(&self, _sender: Option<&objc2::runtime::AnyObject>) {
             let terminal = self.ivars().terminal.lock();
             if let Some(text) = terminal.screen().get_selected_text() {
+                let rtf = if self.ivars().copy_rich_text {
+                    let palette = self.ivars().color_palette.borrow();
+                    let font = self.ivars().renderer.borrow().as_ref().map(|r| r.font_family());
+                    terminal
+                        .screen()
+                        .selection_to_rtf(&palette, font.as_deref().unwrap_or("Menlo"))
+                } else {
+                    None
+                };
                 drop(terminal);
-                clipboard::set_text(&text);
+                match rtf {
+                    Some(rtf) => clipboard::set_rtf(&rtf, &text),
+                    None => clipboard::set_text(&text),
+                }
+                clipboard_history::record(&text, ClipSource::Copy);
                 log::debug!("Copied {} chars to clipboard", text.len());
+                // Programs on the daemon's host can read it while detached
+                if let Some(ref tx) = *self.ivars().daemon_cmd_tx.borrow() {
+                    let _ = tx.send(DaemonCommand::SetClipboard(text.into_bytes()));
+                }
             }
         }
 
@@ -702,8 +1162,8 @@ define_class!(
         #[unsafe(method(copyAsHTML:))]
         fn action_copy_as_html(&self, _sender: Option<&objc2::runtime::AnyObject>) {
             let terminal = self.ivars().terminal.lock();
-            let palette = &self.ivars().color_palette;
-            if let Some(html) = terminal.screen().get_selected_html(palette) {
+            let palette = self.ivars().color_palette.borrow();
+            if let Some(html) = terminal.screen().get_selected_html(&palette) {
                 let plain_text = terminal.screen().get_selected_text().unwrap_or_default();
                 drop(terminal);
                 clipboard::set_html(&html, &plain_text);
@@ -715,19 +1175,26 @@ define_class!(
         #[unsafe(method(paste:))]
         fn action_paste(&self, _sender: Option<&objc2::runtime::AnyObject>) {
             if let Some(text) = clipboard::get_text() {
-                // Check if bracketed paste mode is enabled
-                let terminal = self.ivars().terminal.lock();
-                let bracketed = terminal.screen().modes.bracketed_paste;
-                drop(terminal);
+                self.paste_text(&text, true);
+            }
+        }
 
-                let paste_text = if bracketed {
-                    format!("\x1b[200~{}\x1b[201~", text)
-                } else {
-                    text
-                };
+        /// Pick an earlier copy to paste from the clipboard history
+        /// (Command+Shift+V)
+        #[unsafe(method(pasteFromHistory:))]
+        fn action_paste_from_history(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.show_paste_history();
+        }
 
-                self.write_to_pty(paste_text.as_bytes());
-            }
+        /// Paste the text of a clipboard history menu item
+        #[unsafe(method(pasteHistoryItem:))]
+        fn action_paste_history_item(&self, sender: Option<&NSMenuItem>) {
+            let Some(obj) = sender.and_then(|item| item.representedObject()) else {
+                return;
+            };
+            // The represented object is an NSString
+            let text: &NSString = unsafe { &*(&*obj as *const _ as *const NSString) };
+            self.paste_text(&text.to_string(), true);
         }
 
         /// Select all text (Command+A)
@@ -805,6 +1272,184 @@ define_class!(
             log::debug!("Terminal cleared and reset");
         }
 
+        /// Magnify the rendered view without changing the font or grid size
+        #[unsafe(method(magnifyIn:))]
+        fn action_magnify_in(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.magnify(Magnifier::magnify_in);
+        }
+
+        /// Reduce magnification one step
+        #[unsafe(method(magnifyOut:))]
+        fn action_magnify_out(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.magnify(Magnifier::magnify_out);
+        }
+
+        /// Return to unmagnified rendering
+        #[unsafe(method(magnifyReset:))]
+        fn action_magnify_reset(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.magnify(Magnifier::reset);
+        }
+
+        /// Increase the font size one step; the grid reflows to fit
+        #[unsafe(method(zoomIn:))]
+        fn action_zoom_in(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.set_font_size((self.ivars().font_size.get() + 1.0).min(72.0));
+        }
+
+        /// Decrease the font size one step
+        #[unsafe(method(zoomOut:))]
+        fn action_zoom_out(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.set_font_size((self.ivars().font_size.get() - 1.0).max(6.0));
+        }
+
+        /// Return to the configured font size
+        #[unsafe(method(zoomReset:))]
+        fn action_zoom_reset(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.set_font_size(self.ivars().default_font_size.get());
+        }
+
+        /// Trackpad pinch: zoom the font in steps
+        #[unsafe(method(magnifyWithEvent:))]
+        fn magnify_with_event(&self, event: &NSEvent) {
+            let mut pinch = self.ivars().pinch.get();
+            if let Some(action) = pinch.update(event.magnification()) {
+                self.perform_action(&action);
+            }
+            if event
+                .phase()
+                .intersects(NSEventPhase::Ended | NSEventPhase::Cancelled)
+            {
+                pinch.end();
+            }
+            self.ivars().pinch.set(pinch);
+        }
+
+        /// Three-finger tap (or force click): look up the selected text
+        #[unsafe(method(quickLookWithEvent:))]
+        fn quick_look_with_event(&self, event: &NSEvent) {
+            let Some(text) = self
+                .get_selected_text()
+                .filter(|text| !text.trim().is_empty())
+            else {
+                return;
+            };
+            let point: NSPoint =
+                unsafe { msg_send![self, convertPoint: event.locationInWindow(), fromView: std::ptr::null::<NSView>()] };
+            let text = NSAttributedString::from_nsstring(&NSString::from_str(text.trim()));
+            self.showDefinitionForAttributedString_atPoint(Some(&text), point);
+        }
+
+        #[unsafe(method_id(makeTouchBar))]
+        fn make_touch_bar(&self) -> Option<Retained<NSTouchBar>> {
+            let mtm = MainThreadMarker::from(self);
+            Some(touch_bar::make_touch_bar(
+                mtm,
+                objc2::runtime::ProtocolObject::from_ref(self),
+            ))
+        }
+
+        /// A Touch Bar button was pressed; its tag identifies the action
+        #[unsafe(method(touchBarAction:))]
+        fn touch_bar_action(&self, sender: &objc2_app_kit::NSButton) {
+            if let Some(action) = touch_bar::item_action(sender.tag()) {
+                self.perform_action(&action);
+            }
+        }
+
+        /// Select the output of the last command
+        #[unsafe(method(selectCommandOutput:))]
+        fn action_select_command_output(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            if !self.ivars().terminal.lock().select_last_command_output() {
+                objc2_app_kit::NSBeep();
+            }
+            self.set_needs_display();
+        }
+
+        /// Scroll the shell prompt above the view to its top
+        #[unsafe(method(previousPrompt:))]
+        fn action_previous_prompt(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.scroll_to_prompt(false);
+        }
+
+        /// Scroll the next shell prompt to the top of the view
+        #[unsafe(method(nextPrompt:))]
+        fn action_next_prompt(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.scroll_to_prompt(true);
+        }
+
+        /// Label the links, paths and hashes on screen for keyboard selection
+        #[unsafe(method(showHints:))]
+        fn action_show_hints(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let mode = {
+                let terminal = self.ivars().terminal.lock();
+                HintMode::new(terminal.screen(), terminal.screen().hint_alphabet())
+            };
+            if mode.is_none() {
+                objc2_app_kit::NSBeep();
+            }
+            self.update_status(|status| status.set_hints(mode.as_ref().map(HintMode::typed)));
+            *self.ivars().hints.borrow_mut() = mode;
+            self.set_needs_display();
+        }
+
+        /// Toggle ligature-free, cell-exact rendering for this tab
+        #[unsafe(method(toggleAsciiArtSafe:))]
+        fn action_toggle_ascii_art_safe(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let mut terminal = self.ivars().terminal.lock();
+            let screen = terminal.screen_mut();
+            screen.modes.ascii_art_safe = !screen.modes.ascii_art_safe;
+            let enabled = screen.modes.ascii_art_safe;
+            drop(terminal);
+            self.set_needs_display();
+            log::debug!("ASCII-art-safe rendering: {}", enabled);
+        }
+
+        /// Lock or unlock input to this tab; scrolling and copying keep working
+        #[unsafe(method(toggleReadOnly:))]
+        fn action_toggle_read_only(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let mut terminal = self.ivars().terminal.lock();
+            let locked = !terminal.is_input_locked();
+            terminal.set_input_locked(locked);
+            drop(terminal);
+            self.update_status(|status| status.set_read_only(locked));
+        }
+
+        /// Set or clear the watch expression via dialog
+        #[unsafe(method(watchOutput:))]
+        fn action_watch_output(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let mtm = objc2_foundation::MainThreadMarker::from(self);
+            let current = self
+                .ivars()
+                .terminal
+                .lock()
+                .watch()
+                .map(|watch| watch.pattern().to_string())
+                .unwrap_or_default();
+            let Some(pattern) = crate::dialogs::show_input(
+                mtm,
+                None,
+                "Watch Output",
+                "Count new output lines matching this regular expression (leave empty to stop watching):",
+                &current,
+            ) else {
+                return;
+            };
+            let pattern = pattern.trim();
+            let pattern = Some(pattern).filter(|p| !p.is_empty());
+            let result = self.ivars().terminal.lock().set_watch(pattern);
+            if let Err(e) = result {
+                crate::dialogs::show_error(
+                    mtm,
+                    None,
+                    "Invalid Watch Expression",
+                    &e.to_string(),
+                );
+                return;
+            }
+            *self.ivars().state.watch_match.lock() = None;
+            self.update_tab_label();
+        }
+
         /// Set terminal title via dialog
         #[unsafe(method(setTerminalTitle:))]
         fn action_set_terminal_title(&self, _sender: Option<&objc2::runtime::AnyObject>) {
@@ -843,6 +1488,7 @@ define_class!(
                 if let Some(window) = self.window() {
                     window.setTitle(&new_title);
                 }
+                self.update_tab_label();
                 // Lock the title so OSC sequences won't override it
                 self.ivars().state.title_locked.store(true, Ordering::Relaxed);
                 // Persist custom title to daemon
@@ -893,33 +1539,67 @@ define_class!(
             TerminalView::do_send_signal(self, 12); // SIGUSR2
         }
 
-        /// Notification bar save action
-        #[unsafe(method(saveFile:))]
-        fn save_file(&self, _sender: Option<&objc2::runtime::AnyObject>) {
-            if self.ivars().file_manager.borrow().has_pending() {
-                if let Some(ref bar) = *self.ivars().notification_bar.borrow() {
-                    self.handle_file_save(bar.file_id());
+        /// Save the scrollback and screen, with their colors, to a file
+        #[unsafe(method(saveBuffer:))]
+        fn action_save_buffer(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let text = self.ivars().terminal.lock().screen().to_ansi();
+            let mtm = MainThreadMarker::from(self);
+            if let Some(path) = crate::dialogs::show_save_panel(
+                mtm,
+                self.window().as_deref(),
+                Some("scrollback.log"),
+                None,
+            ) {
+                if let Err(e) = std::fs::write(&path, text) {
+                    log::error!("Failed to save buffer to {:?}: {}", path, e);
                 }
             }
         }
 
-        /// Notification bar save as action
-        #[unsafe(method(saveFileAs:))]
-        fn save_file_as(&self, _sender: Option<&objc2::runtime::AnyObject>) {
-            if self.ivars().file_manager.borrow().has_pending() {
-                if let Some(ref bar) = *self.ivars().notification_bar.borrow() {
-                    self.handle_file_save_as(bar.file_id());
+        #[unsafe(method(toggleRecording:))]
+        fn action_toggle_recording(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            // Stop the recording in progress, otherwise ask where to start one
+            let stopped = self.ivars().terminal.lock().stop_recording();
+            match stopped {
+                Ok(true) => {
+                    self.update_status(|status| status.set_recording(false));
+                    return;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    log::error!("Failed to finish recording: {}", e);
+                    self.update_status(|status| status.set_recording(false));
+                    return;
+                }
+            }
+            let mtm = MainThreadMarker::from(self);
+            let Some(path) = crate::dialogs::show_save_panel(
+                mtm,
+                self.window().as_deref(),
+                Some("session.cast"),
+                None,
+            ) else {
+                return;
+            };
+            match self.ivars().terminal.lock().start_recording(&path) {
+                Ok(()) => {
+                    self.update_status(|status| status.set_recording(true));
                 }
+                Err(e) => log::error!("Failed to start recording to {:?}: {}", path, e),
             }
         }
 
-        /// Notification bar discard action
-        #[unsafe(method(discardFile:))]
-        fn discard_file(&self, _sender: Option<&objc2::runtime::AnyObject>) {
-            if self.ivars().file_manager.borrow().has_pending() {
-                if let Some(ref bar) = *self.ivars().notification_bar.borrow() {
-                    self.handle_file_discard(bar.file_id());
-                }
+        /// Notification bar button action
+        #[unsafe(method(promptAction:))]
+        fn prompt_action(&self, sender: &objc2_app_kit::NSButton) {
+            let action = self
+                .ivars()
+                .notification_bar
+                .borrow()
+                .as_ref()
+                .and_then(|bar| bar.action(sender.tag()));
+            if let Some(action) = action {
+                self.answer_prompt(action);
             }
         }
 
@@ -927,7 +1607,8 @@ define_class!(
         #[unsafe(method(rightMouseDown:))]
         fn right_mouse_down(&self, event: &NSEvent) {
             // A tracking application gets the right button; only fall through to
-            // cterm's context menus when reporting is off or Shift is held.
+            // cterm's context menus when reporting is off or the override
+            // modifier is held.
             if self.report_mouse_button(event, MouseButton::Right, false) {
                 return;
             }
@@ -937,8 +1618,8 @@ define_class!(
             let location = self.convert_point_from_view(location_in_window, None);
 
             // Calculate cell position
-            let col = (location.x / self.ivars().cell_width).floor().max(0.0) as usize;
-            let row = (location.y / self.ivars().cell_height).floor().max(0.0) as usize;
+            let col = (location.x / self.ivars().cell_width.get()).floor().max(0.0) as usize;
+            let row = (location.y / self.ivars().cell_height.get()).floor().max(0.0) as usize;
 
             let terminal = self.ivars().terminal.lock();
             let absolute_line = terminal.screen().visible_row_to_absolute_line(row);
@@ -982,8 +1663,12 @@ define_class!(
         fn other_mouse_down(&self, event: &NSEvent) {
             // Only the middle button (buttonNumber 2) is reported; ignore the
             // rest so we don't misencode extra mouse buttons.
-            if event.buttonNumber() == 2 {
-                self.report_mouse_button(event, MouseButton::Middle, false);
+            if event.buttonNumber() == 2
+                && !self.report_mouse_button(event, MouseButton::Middle, false)
+                && self.ivars().input.middle_click_paste
+            {
+                // No primary selection on macOS: paste the clipboard
+                self.action_paste(None);
             }
         }
 
@@ -1061,20 +1746,7 @@ define_class!(
                 return;
             }
 
-            let terminal = self.ivars().terminal.lock();
-            if let Some(image) = terminal.screen().image_by_id(image_id) {
-                let data = image.data.clone();
-                let width = image.pixel_width;
-                let height = image.pixel_height;
-                drop(terminal);
-
-                // Save to temp file and open
-                let temp_path = std::env::temp_dir().join(format!("cterm_image_{}.png", image_id));
-                if let Err(e) = self.save_image_as_png(&data, width, height, &temp_path) {
-                    log::error!("Failed to save temp image: {}", e);
-                    return;
-                }
-
+            if let Some(temp_path) = self.image_temp_file(image_id) {
                 // Open with default application
                 use objc2_app_kit::NSWorkspace;
                 use objc2_foundation::NSURL;
@@ -1086,6 +1758,49 @@ define_class!(
             }
         }
 
+        /// Preview image with Quick Look
+        #[unsafe(method(quickLookImage:))]
+        fn quick_look_image(&self, sender: Option<&NSMenuItem>) {
+            let Some(sender) = sender else { return };
+            let image_id = self.get_image_id_from_menu_item(sender);
+            if image_id == 0 {
+                return;
+            }
+            if let Some(temp_path) = self.image_temp_file(image_id) {
+                self.show_preview(&temp_path);
+            }
+        }
+
+        #[unsafe(method(acceptsPreviewPanelControl:))]
+        fn accepts_preview_panel_control(&self, _panel: &AnyObject) -> bool {
+            self.ivars().preview_url.borrow().is_some()
+        }
+
+        #[unsafe(method(beginPreviewPanelControl:))]
+        fn begin_preview_panel_control(&self, panel: &AnyObject) {
+            quick_look::set_controller(panel, Some(self));
+        }
+
+        #[unsafe(method(endPreviewPanelControl:))]
+        fn end_preview_panel_control(&self, panel: &AnyObject) {
+            quick_look::set_controller(panel, None);
+            *self.ivars().preview_url.borrow_mut() = None;
+        }
+
+        #[unsafe(method(numberOfPreviewItemsInPreviewPanel:))]
+        fn number_of_preview_items(&self, _panel: &AnyObject) -> isize {
+            self.ivars().preview_url.borrow().is_some() as isize
+        }
+
+        #[unsafe(method_id(previewPanel:previewItemAtIndex:))]
+        fn preview_item_at_index(
+            &self,
+            _panel: &AnyObject,
+            _index: isize,
+        ) -> Option<Retained<objc2_foundation::NSURL>> {
+            self.ivars().preview_url.borrow().clone()
+        }
+
         /// Open URL from context menu
         #[unsafe(method(openURL:))]
         fn open_url_action(&self, sender: Option<&NSMenuItem>) {
@@ -1142,6 +1857,22 @@ define_class!(
         }
     }
 
+    unsafe impl NSTouchBarDelegate for TerminalView {
+        #[unsafe(method_id(touchBar:makeItemForIdentifier:))]
+        fn touch_bar_make_item(
+            &self,
+            _touch_bar: &NSTouchBar,
+            identifier: &NSString,
+        ) -> Option<Retained<NSTouchBarItem>> {
+            touch_bar::make_item(
+                MainThreadMarker::from(self),
+                identifier,
+                self,
+                sel!(touchBarAction:),
+            )
+        }
+    }
+
     // NSTextInputClient protocol for IME support (Japanese, Chinese, Korean, etc.)
     unsafe impl NSTextInputClient for TerminalView {
         #[unsafe(method(insertText:replacementRange:))]
@@ -1264,8 +1995,8 @@ define_class!(
             // Use cursor position
             let terminal = self.ivars().terminal.lock();
             let cursor = &terminal.screen().cursor;
-            let cell_width = self.ivars().cell_width;
-            let cell_height = self.ivars().cell_height;
+            let cell_width = self.ivars().cell_width.get();
+            let cell_height = self.ivars().cell_height.get();
 
             let x = cursor.col as f64 * cell_width;
             let y = cursor.row as f64 * cell_height;
@@ -1296,6 +2027,13 @@ define_class!(
 #[derive(Default)]
 struct ViewInitOptions {
     template_name: Option<String>,
+    auto_switch: Vec<AutoSwitchRule>,
+    triggers: Vec<TriggerConfig>,
+    input: InputConfig,
+    command_notify: CommandNotifyConfig,
+    sounds: SoundsConfig,
+    copy_rich_text: bool,
+    paste_guard: PasteGuard,
 }
 
 impl TerminalView {
@@ -1308,6 +2046,13 @@ impl TerminalView {
         options: ViewInitOptions,
     ) -> (Retained<Self>, Arc<ViewState>) {
         let (cell_width, cell_height) = renderer.cell_size();
+        let triggers = TriggerEngine::new(&options.triggers);
+        {
+            let mut terminal = terminal.lock();
+            triggers.attach(&mut terminal);
+            terminal.set_color_scheme(theme.colors.color_scheme());
+        }
+        let font_size = renderer.font_size();
         let state = Arc::new(ViewState::default());
         let frame = NSRect::new(NSPoint::ZERO, NSSize::new(800.0, 600.0));
 
@@ -1315,21 +2060,49 @@ impl TerminalView {
         let this = this.set_ivars(TerminalViewIvars {
             terminal: terminal.clone(),
             renderer: RefCell::new(Some(renderer)),
-            cell_width,
-            cell_height,
+            metal: RefCell::new(None),
+            cell_width: Cell::new(cell_width),
+            cell_height: Cell::new(cell_height),
+            font_size: Cell::new(font_size),
+            default_font_size: Cell::new(font_size),
+            pinch: Cell::new(PinchZoom::new()),
             state: state.clone(),
             is_selecting: Cell::new(false),
             auto_scroll_direction: Cell::new(0),
             auto_scroll_col: Cell::new(0),
             auto_scroll_timer: RefCell::new(None),
+            paste: RefCell::new(None),
+            paste_timer: RefCell::new(None),
+            input: options.input,
+            pointer_hider: Cell::new(PointerHider::new(options.input.hide_pointer_while_typing)),
+            command_notify: Cell::new(options.command_notify),
+            sounds: options.sounds,
+            copy_rich_text: options.copy_rich_text,
+            paste_guard: options.paste_guard,
             template_name: RefCell::new(options.template_name),
             session_id: RefCell::new(None),
             marked_text: RefCell::new(String::new()),
             notification_bar: RefCell::new(None),
+            prompts: RefCell::new(PromptQueue::new()),
             file_manager: RefCell::new(PendingFileManager::new()),
-            color_palette: theme.colors.clone(),
+            preview_url: RefCell::new(None),
+            color_palette: RefCell::new(theme.colors.clone()),
             daemon_cmd_tx: RefCell::new(None),
             daemon_socket: RefCell::new(None),
+            magnifier: Cell::new(Magnifier::new()),
+            keystrokes: RefCell::new(None),
+            hints: RefCell::new(None),
+            status: RefCell::new(StatusStrip::new()),
+            auto_switch: RefCell::new(AutoSwitcher::new(options.auto_switch)),
+            triggers: RefCell::new(triggers),
+            auto_switch_saved: RefCell::new(None),
+            playback: RefCell::new(None),
+            playback_timer: RefCell::new(None),
+            tmux_pane: RefCell::new(None),
+            tmux_panes: RefCell::new(std::collections::HashMap::new()),
+            hovered_link: RefCell::new(None),
+            link_popover: RefCell::new(None),
+            moving: Cell::new(false),
         });
 
         let this: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
@@ -1355,16 +2128,28 @@ impl TerminalView {
         theme: &Theme,
         session: cterm_client::SessionHandle,
     ) -> Retained<Self> {
-        let renderer = CGRenderer::new(
+        let mut renderer = CGRenderer::new(
             mtm,
             &config.appearance.font.family,
             config.appearance.font.size,
             theme,
             config.appearance.bold_is_bright,
         );
+        renderer.set_attr_overrides(config.appearance.text_styles);
+        renderer.set_command_gutter(config.appearance.command_gutter);
+        renderer.set_opacity(config.appearance.background_opacity(theme));
         let (cell_width, cell_height) = renderer.cell_size();
 
-        let mut terminal = Terminal::new(80, 24, ScreenConfig::default());
+        let screen_config = ScreenConfig {
+            selection: config.selection.clone(),
+            conformance_level: config.general.conformance_level,
+            cursor_style: config.appearance.cursor_style.into(),
+            cursor_blink: config.appearance.cursor_blink,
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(80, 24, screen_config);
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(config.permissions);
         terminal.screen_mut().set_cell_height_hint(cell_height);
         terminal.screen_mut().set_cell_width_hint(cell_width);
 
@@ -1387,7 +2172,16 @@ impl TerminalView {
             renderer,
             terminal.clone(),
             theme,
-            ViewInitOptions::default(),
+            ViewInitOptions {
+                auto_switch: config.auto_switch.clone(),
+                triggers: config.triggers.clone(),
+                input: config.input,
+                command_notify: config.command_notify,
+                sounds: config.sounds.clone(),
+                copy_rich_text: config.general.copy_rich_text,
+                paste_guard: PasteGuard::from(&config.general),
+                ..Default::default()
+            },
         );
 
         // Store daemon session ID, command channel, and socket path
@@ -1418,16 +2212,52 @@ impl TerminalView {
         theme: &Theme,
         recon: cterm_app::daemon_reconnect::ReconnectedSession,
     ) -> Retained<Self> {
-        let renderer = CGRenderer::new(
-            mtm,
+        Self::attach_daemon_view(mtm, config, theme, recon, false)
+    }
+
+    /// Create a read-only view mirroring a session owned by another tab.
+    ///
+    /// Keyboard input is dropped, the view follows the session's size
+    /// instead of resizing it, and closing the view leaves the session
+    /// running.
+    pub fn monitor(
+        mtm: MainThreadMarker,
+        config: &Config,
+        theme: &Theme,
+        recon: cterm_app::daemon_reconnect::ReconnectedSession,
+    ) -> Retained<Self> {
+        Self::attach_daemon_view(mtm, config, theme, recon, true)
+    }
+
+    fn attach_daemon_view(
+        mtm: MainThreadMarker,
+        config: &Config,
+        theme: &Theme,
+        recon: cterm_app::daemon_reconnect::ReconnectedSession,
+        monitor: bool,
+    ) -> Retained<Self> {
+        let mut renderer = CGRenderer::new(
+            mtm,
             &config.appearance.font.family,
             config.appearance.font.size,
             theme,
             config.appearance.bold_is_bright,
         );
+        renderer.set_attr_overrides(config.appearance.text_styles);
+        renderer.set_command_gutter(config.appearance.command_gutter);
+        renderer.set_opacity(config.appearance.background_opacity(theme));
         let (cell_width, cell_height) = renderer.cell_size();
 
-        let mut terminal = Terminal::new(80, 24, ScreenConfig::default());
+        let screen_config = ScreenConfig {
+            selection: config.selection.clone(),
+            conformance_level: config.general.conformance_level,
+            cursor_style: config.appearance.cursor_style.into(),
+            cursor_blink: config.appearance.cursor_blink,
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(80, 24, screen_config);
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(config.permissions);
         terminal.screen_mut().set_cell_height_hint(cell_height);
         terminal.screen_mut().set_cell_width_hint(cell_width);
 
@@ -1440,11 +2270,16 @@ impl TerminalView {
         // Set up command channel — write/resize callbacks send to the background I/O thread
         let (cmd_tx, cmd_rx) = tokio::sync::mpsc::unbounded_channel::<DaemonCommand>();
 
-        let write_tx = cmd_tx.clone();
-        terminal.set_write_fn(Box::new(move |data: &[u8]| {
-            let _ = write_tx.send(DaemonCommand::Write(data.to_vec()));
-            Ok(())
-        }));
+        if monitor {
+            // Replies to queries (DA, DSR, ...) are the owning tab's job
+            terminal.set_write_fn(Box::new(|_: &[u8]| Ok(())));
+        } else {
+            let write_tx = cmd_tx.clone();
+            terminal.set_write_fn(Box::new(move |data: &[u8]| {
+                let _ = write_tx.send(DaemonCommand::Write(data.to_vec()));
+                Ok(())
+            }));
+        }
 
         let terminal = Arc::new(Mutex::new(terminal));
 
@@ -1453,12 +2288,28 @@ impl TerminalView {
             renderer,
             terminal.clone(),
             theme,
-            ViewInitOptions::default(),
+            ViewInitOptions {
+                auto_switch: config.auto_switch.clone(),
+                triggers: config.triggers.clone(),
+                input: config.input,
+                command_notify: config.command_notify,
+                sounds: config.sounds.clone(),
+                copy_rich_text: config.general.copy_rich_text,
+                paste_guard: PasteGuard::from(&config.general),
+                ..Default::default()
+            },
         );
 
-        // Store daemon session ID, command channel, and socket path
+        // Store daemon session ID, command channel, and socket path. A
+        // monitor keeps no command channel, so it never resizes, renames or
+        // destroys the session it mirrors.
         this.set_session_id(Some(sid.clone()));
-        *this.ivars().daemon_cmd_tx.borrow_mut() = Some(cmd_tx);
+        if monitor {
+            state.monitor.store(true, Ordering::Relaxed);
+            drop(cmd_tx);
+        } else {
+            *this.ivars().daemon_cmd_tx.borrow_mut() = Some(cmd_tx);
+        }
         let daemon_socket = recon.handle.socket_path().map(|p| p.to_owned());
         *this.ivars().daemon_socket.borrow_mut() = daemon_socket.clone();
 
@@ -1474,6 +2325,237 @@ impl TerminalView {
         this
     }
 
+    /// Create a read-only view playing back a recording.
+    ///
+    /// The Terminal keeps the recording's size and has no PTY; keys control
+    /// the player (see [`Player::handle_key`]) instead of being sent anywhere.
+    pub fn for_playback(
+        mtm: MainThreadMarker,
+        config: &Config,
+        theme: &Theme,
+        player: Player,
+    ) -> Retained<Self> {
+        let mut renderer = CGRenderer::new(
+            mtm,
+            &config.appearance.font.family,
+            config.appearance.font.size,
+            theme,
+            config.appearance.bold_is_bright,
+        );
+        renderer.set_attr_overrides(config.appearance.text_styles);
+        renderer.set_command_gutter(config.appearance.command_gutter);
+        renderer.set_opacity(config.appearance.background_opacity(theme));
+        let (cell_width, cell_height) = renderer.cell_size();
+
+        let screen_config = ScreenConfig {
+            selection: config.selection.clone(),
+            conformance_level: config.general.conformance_level,
+            cursor_style: config.appearance.cursor_style.into(),
+            cursor_blink: config.appearance.cursor_blink,
+            ..Default::default()
+        };
+        let mut terminal = player.new_terminal(screen_config);
+        terminal.screen_mut().set_cell_height_hint(cell_height);
+        terminal.screen_mut().set_cell_width_hint(cell_width);
+
+        let (this, _state) = Self::init_view(
+            mtm,
+            renderer,
+            Arc::new(Mutex::new(terminal)),
+            theme,
+            ViewInitOptions {
+                input: config.input,
+                command_notify: config.command_notify,
+                copy_rich_text: config.general.copy_rich_text,
+                paste_guard: PasteGuard::from(&config.general),
+                ..Default::default()
+            },
+        );
+
+        *this.ivars().playback.borrow_mut() = Some(player);
+        let timer: Retained<objc2_foundation::NSTimer> = unsafe {
+            msg_send![
+                class!(NSTimer),
+                scheduledTimerWithTimeInterval: Player::TICK.as_secs_f64(),
+                target: &*this,
+                selector: sel!(playbackFire:),
+                userInfo: std::ptr::null::<AnyObject>(),
+                repeats: true
+            ]
+        };
+        *this.ivars().playback_timer.borrow_mut() = Some(timer);
+        this
+    }
+
+    /// Create a view for a pane of a tmux session in control mode.
+    ///
+    /// The Terminal keeps the pane's size, set by tmux; output comes from
+    /// [`TerminalView::feed`] and input goes to the pane through `gateway`.
+    pub fn for_tmux_pane(
+        mtm: MainThreadMarker,
+        config: &Config,
+        theme: &Theme,
+        gateway: &Arc<TmuxGateway>,
+        pane: PaneId,
+        cols: usize,
+        rows: usize,
+    ) -> Retained<Self> {
+        let mut renderer = CGRenderer::new(
+            mtm,
+            &config.appearance.font.family,
+            config.appearance.font.size,
+            theme,
+            config.appearance.bold_is_bright,
+        );
+        renderer.set_attr_overrides(config.appearance.text_styles);
+        renderer.set_command_gutter(config.appearance.command_gutter);
+        renderer.set_opacity(config.appearance.background_opacity(theme));
+        let (cell_width, cell_height) = renderer.cell_size();
+
+        let screen_config = ScreenConfig {
+            selection: config.selection.clone(),
+            conformance_level: config.general.conformance_level,
+            cursor_style: config.appearance.cursor_style.into(),
+            cursor_blink: config.appearance.cursor_blink,
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(cols, rows, screen_config);
+        terminal.screen_mut().set_cell_height_hint(cell_height);
+        terminal.screen_mut().set_cell_width_hint(cell_width);
+        terminal.set_write_fn(gateway.pane_write_fn(pane));
+
+        let (this, state) = Self::init_view(
+            mtm,
+            renderer,
+            Arc::new(Mutex::new(terminal)),
+            theme,
+            ViewInitOptions {
+                input: config.input,
+                command_notify: config.command_notify,
+                sounds: config.sounds.clone(),
+                copy_rich_text: config.general.copy_rich_text,
+                paste_guard: PasteGuard::from(&config.general),
+                ..Default::default()
+            },
+        );
+        *this.ivars().tmux_pane.borrow_mut() = Some(Arc::clone(gateway));
+
+        let view_ptr = &*this as *const _ as usize;
+        this.schedule_redraw_check(view_ptr, state);
+        this
+    }
+
+    /// Process output of a tmux pane
+    pub fn feed(&self, data: &[u8]) {
+        let events = self.ivars().terminal.lock().process(data);
+        let state = &self.ivars().state;
+        for event in events {
+            match event {
+                TerminalEvent::Bell => state.bell_changed.store(true, Ordering::Relaxed),
+                TerminalEvent::PointerShapeChanged(_) => {
+                    state.pointer_shape_changed.store(true, Ordering::Relaxed)
+                }
+                TerminalEvent::UploadRequested => {
+                    state.upload_requested.store(true, Ordering::Relaxed)
+                }
+                TerminalEvent::ClipboardRequest(_)
+                | TerminalEvent::Notification(_)
+                | TerminalEvent::PermissionRequested(_) => state.requests.lock().push(event),
+                _ => {}
+            }
+        }
+        state.needs_redraw.store(true, Ordering::Relaxed);
+    }
+
+    /// Open, feed and close the tabs of the panes of the tmux running in
+    /// this view
+    fn handle_tmux_events(&self) {
+        let events = std::mem::take(&mut *self.ivars().state.tmux_events.lock());
+        let gateway = self.ivars().state.tmux.lock().clone();
+        let mut panes = self.ivars().tmux_panes.borrow_mut();
+        for event in events {
+            match event {
+                TmuxEvent::PaneOpened {
+                    pane,
+                    title,
+                    cols,
+                    rows,
+                } => {
+                    let (Some(gateway), Some(window)) = (gateway.as_ref(), self.window()) else {
+                        continue;
+                    };
+                    let window_ptr = Retained::as_ptr(&window) as *const crate::window::CtermWindow;
+                    let cterm_window: &crate::window::CtermWindow = unsafe { &*window_ptr };
+                    let view = cterm_window.create_tmux_pane_tab(gateway, pane, &title, cols, rows);
+                    panes.insert(pane, view);
+                }
+                TmuxEvent::PaneResized { pane, cols, rows } => {
+                    if let Some(view) = panes.get(&pane) {
+                        view.ivars().terminal.lock().resize(cols, rows);
+                        view.ivars()
+                            .state
+                            .needs_redraw
+                            .store(true, Ordering::Relaxed);
+                    }
+                }
+                TmuxEvent::PaneOutput { pane, data } => {
+                    if let Some(view) = panes.get(&pane) {
+                        view.feed(&data);
+                    }
+                }
+                TmuxEvent::PaneTitle { pane, title } => {
+                    if let Some(window) = panes.get(&pane).and_then(|view| view.window()) {
+                        window.setTitle(&NSString::from_str(&title));
+                    }
+                }
+                TmuxEvent::PaneClosed { pane } => {
+                    if let Some(window) = panes.remove(&pane).and_then(|view| view.window()) {
+                        window.close();
+                    }
+                }
+                TmuxEvent::Exit { reason } => {
+                    log::info!("tmux control mode ended: {}", reason.unwrap_or_default());
+                    for (_, view) in panes.drain() {
+                        if let Some(window) = view.window() {
+                            window.close();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pass a key to the player of a playback view; returns false if this
+    /// isn't one or the key isn't a control
+    fn playback_key(&self, event: &NSEvent) -> bool {
+        use cterm_core::term::Key;
+
+        let mut playback = self.ivars().playback.borrow_mut();
+        let Some(player) = playback.as_mut() else {
+            return false;
+        };
+        let key = match event.keyCode() {
+            0x7E => Some(Key::Up),
+            0x7D => Some(Key::Down),
+            0x7B => Some(Key::Left),
+            0x7C => Some(Key::Right),
+            0x73 => Some(Key::Home),
+            0x77 => Some(Key::End),
+            _ => keycode::characters_ignoring_modifiers(event)
+                .and_then(|chars| chars.chars().next())
+                .map(Key::Char),
+        };
+        let handled =
+            key.is_some_and(|key| player.handle_key(&mut self.ivars().terminal.lock(), key));
+        if handled {
+            let status = player.status();
+            drop(playback);
+            self.update_status(|strip| strip.set_playback(Some(&status)));
+            self.set_needs_display();
+        }
+        handled
+    }
+
     /// Background thread to read output from a daemon session.
     ///
     /// Creates a local tokio runtime with its own daemon connection,
@@ -1623,10 +2705,42 @@ impl TerminalView {
                                 log::error!("Failed to set template name: {}", e);
                             }
                         }
+                        DaemonCommand::SetClipboard(data) => {
+                            if let Err(e) = cmd_session
+                                .set_clipboard(cterm_proto::proto::ClipboardSelection::Clipboard, data)
+                                .await
+                            {
+                                log::error!("Failed to copy to daemon clipboard: {}", e);
+                            }
+                        }
                     }
                 }
             });
 
+            // Monitors don't own the session size; follow the owning tab's
+            if state.monitor.load(Ordering::Relaxed) {
+                let size_session = session.clone();
+                let size_terminal = Arc::clone(&terminal);
+                let size_state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+                    while !size_state.view_invalid.load(Ordering::Relaxed) {
+                        interval.tick().await;
+                        let Ok(info) = size_session.info().await else {
+                            continue;
+                        };
+                        let (cols, rows) = (info.cols as usize, info.rows as usize);
+                        let mut term = size_terminal.lock();
+                        let screen = term.screen();
+                        if cols > 0 && rows > 0 && (screen.width(), screen.height()) != (cols, rows) {
+                            term.resize(cols, rows);
+                            drop(term);
+                            size_state.needs_redraw.store(true, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+
             // Notify used to cancel the output stream when process exits
             let exit_notify = Arc::new(tokio::sync::Notify::new());
 
@@ -1701,6 +2815,7 @@ impl TerminalView {
                                     Ok(chunk) => {
                                         let mut term = terminal.lock();
                                         let events = term.process(&chunk.data);
+                                        let mut tmux_control = Vec::new();
 
                                         for event in events {
                                             match event {
@@ -1710,14 +2825,76 @@ impl TerminalView {
                                                     }
                                                     state.title_changed.store(true, Ordering::Relaxed);
                                                 }
+                                                // Without a title from the shell, show where it is
+                                                TerminalEvent::CwdChanged(ref dir)
+                                                    if term.screen().title.is_empty()
+                                                        && !dir.is_empty() =>
+                                                {
+                                                    if let Ok(mut current_title) = state.title.write() {
+                                                        *current_title = dir_title(dir);
+                                                    }
+                                                    state.title_changed.store(true, Ordering::Relaxed);
+                                                }
                                                 TerminalEvent::Bell => {
                                                     state.bell_changed.store(true, Ordering::Relaxed);
                                                 }
+                                                TerminalEvent::PointerShapeChanged(_) => {
+                                                    state
+                                                        .pointer_shape_changed
+                                                        .store(true, Ordering::Relaxed);
+                                                }
+                                                TerminalEvent::CommandFinished(finished) => {
+                                                    cterm_app::usage_stats::record_command(
+                                                        &cterm_app::usage_stats::tab_label(
+                                                            term.screen(),
+                                                        ),
+                                                        &finished,
+                                                    );
+                                                    state.finished_commands.lock().push(finished);
+                                                }
+                                                TerminalEvent::OutputStats(stats) => {
+                                                    *state.output_stats.lock() = Some(stats);
+                                                }
+                                                TerminalEvent::TmuxControl(control) => {
+                                                    tmux_control.extend(control);
+                                                }
+                                                TerminalEvent::WatchMatched(found) => {
+                                                    let mut pending = state.watch_match.lock();
+                                                    // Keep a notification still due
+                                                    let notify = found.notify
+                                                        || pending.as_ref().is_some_and(|p| p.notify);
+                                                    *pending = Some(WatchMatch { notify, ..found });
+                                                }
+                                                TerminalEvent::LinesCompleted(lines) => {
+                                                    state.completed_lines.lock().extend(lines);
+                                                }
+                                                TerminalEvent::TransferStarted(..)
+                                                | TerminalEvent::TransferFinished => {
+                                                    state.transfer_changed.store(true, Ordering::Relaxed);
+                                                }
+                                                TerminalEvent::TransferSendRequested => {
+                                                    state
+                                                        .transfer_files_requested
+                                                        .store(true, Ordering::Relaxed);
+                                                }
+                                                TerminalEvent::UploadRequested => {
+                                                    state
+                                                        .upload_requested
+                                                        .store(true, Ordering::Relaxed);
+                                                }
+                                                TerminalEvent::ClipboardRequest(_)
+                                                | TerminalEvent::Notification(_)
+                                                | TerminalEvent::PermissionRequested(_) => {
+                                                    state.requests.lock().push(event);
+                                                }
                                                 _ => {}
                                             }
                                         }
 
                                         drop(term);
+                                        if !tmux_control.is_empty() {
+                                            state.handle_tmux(&terminal, &tmux_control);
+                                        }
                                         state.needs_redraw.store(true, Ordering::Relaxed);
                                     }
                                     Err(e) => {
@@ -1740,12 +2917,15 @@ impl TerminalView {
     }
 
     fn schedule_redraw_check(&self, view_ptr: usize, state: Arc<ViewState>) {
+        let terminal = Arc::clone(&self.ivars().terminal);
         // Start a background thread that periodically triggers redraws on main thread
         std::thread::spawn(move || {
             // Wait briefly for app to initialize
             std::thread::sleep(std::time::Duration::from_millis(100));
             loop {
-                std::thread::sleep(std::time::Duration::from_millis(16));
+                std::thread::sleep(std::time::Duration::from_micros(
+                    state.frame_interval_us.load(Ordering::Relaxed),
+                ));
 
                 // Check if view has been invalidated (window closed)
                 if state.view_invalid.load(Ordering::SeqCst) {
@@ -1753,9 +2933,10 @@ impl TerminalView {
                     break;
                 }
 
-                // Check if PTY closed - if so, close the window
+                // Check if PTY closed - if so, close the pane (or the window
+                // with its last pane)
                 if state.pty_closed.load(Ordering::Relaxed) {
-                    log::info!("PTY closed, closing window");
+                    log::info!("PTY closed, closing pane");
                     // Only close if view is still valid
                     if !state.view_invalid.load(Ordering::SeqCst) {
                         let state_clone = state.clone();
@@ -1765,8 +2946,8 @@ impl TerminalView {
                             if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
                                 unsafe {
                                     let view = &*(view_ptr as *const TerminalView);
-                                    if let Some(window) = view.window() {
-                                        window.close();
+                                    if let Some(window) = view.cterm_window() {
+                                        window.close_pane(view);
                                     }
                                 }
                             }
@@ -1789,15 +2970,33 @@ impl TerminalView {
                             if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
                                 unsafe {
                                     let view = &*(view_ptr as *const TerminalView);
-                                    if let Some(window) = view.window() {
-                                        window.setTitle(&NSString::from_str(&new_title));
+                                    // Only the active pane titles the window
+                                    if let Some(window) = view.cterm_window() {
+                                        if window.is_active_pane(view) {
+                                            window.setTitle(&NSString::from_str(&new_title));
+                                        }
                                     }
+                                    view.update_tab_label();
                                 }
                             }
                         });
                     }
                 }
 
+                // Show a new pointer shape at once if the mouse is over the view
+                if state.pointer_shape_changed.swap(false, Ordering::Relaxed)
+                    && !state.view_invalid.load(Ordering::SeqCst)
+                {
+                    let state_clone = state.clone();
+                    #[allow(deprecated)]
+                    dispatch2::Queue::main().exec_async(move || {
+                        if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
+                            let view = unsafe { &*(view_ptr as *const TerminalView) };
+                            view.apply_pointer_shape();
+                        }
+                    });
+                }
+
                 // Check for bell
                 if state.bell_changed.swap(false, Ordering::Relaxed)
                     && !state.view_invalid.load(Ordering::SeqCst)
@@ -1826,6 +3025,7 @@ impl TerminalView {
                                             &*window_ptr;
                                         cterm_window.set_bell(true);
                                     }
+                                    view.play_cue(view.ivars().sounds.bell_cue());
                                     // Request attention in the dock
                                     let app = NSApplication::sharedApplication(
                                         MainThreadMarker::new().unwrap(),
@@ -1839,6 +3039,135 @@ impl TerminalView {
                     });
                 }
 
+                // Check for finished commands
+                if !state.finished_commands.lock().is_empty()
+                    && !state.view_invalid.load(Ordering::SeqCst)
+                {
+                    let state_clone = state.clone();
+                    #[allow(deprecated)]
+                    dispatch2::Queue::main().exec_async(move || {
+                        if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
+                            unsafe {
+                                let view = &*(view_ptr as *const TerminalView);
+                                view.report_finished_commands();
+                            }
+                        }
+                    });
+                }
+
+                // Check for watch expression matches
+                if state.watch_match.lock().is_some() && !state.view_invalid.load(Ordering::SeqCst)
+                {
+                    let state_clone = state.clone();
+                    #[allow(deprecated)]
+                    dispatch2::Queue::main().exec_async(move || {
+                        if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
+                            unsafe {
+                                let view = &*(view_ptr as *const TerminalView);
+                                view.report_watch_match();
+                            }
+                        }
+                    });
+                }
+
+                // Check for output lines the triggers haven't seen
+                if !state.completed_lines.lock().is_empty()
+                    && !state.view_invalid.load(Ordering::SeqCst)
+                {
+                    let state_clone = state.clone();
+                    #[allow(deprecated)]
+                    dispatch2::Queue::main().exec_async(move || {
+                        if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
+                            unsafe {
+                                let view = &*(view_ptr as *const TerminalView);
+                                view.run_triggers();
+                            }
+                        }
+                    });
+                }
+
+                // Check for ZMODEM and Kermit transfers starting, ending or
+                // asking for files
+                if (state.transfer_changed.swap(false, Ordering::Relaxed)
+                    || state.transfer_files_requested.load(Ordering::Relaxed))
+                    && !state.view_invalid.load(Ordering::SeqCst)
+                {
+                    let state_clone = state.clone();
+                    #[allow(deprecated)]
+                    dispatch2::Queue::main().exec_async(move || {
+                        if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
+                            unsafe {
+                                let view = &*(view_ptr as *const TerminalView);
+                                view.sync_transfer();
+                            }
+                        }
+                    });
+                }
+
+                // Check for a remote `it2ul` waiting for files
+                if state.upload_requested.swap(false, Ordering::Relaxed)
+                    && !state.view_invalid.load(Ordering::SeqCst)
+                {
+                    let state_clone = state.clone();
+                    #[allow(deprecated)]
+                    dispatch2::Queue::main().exec_async(move || {
+                        if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
+                            let view = unsafe { &*(view_ptr as *const TerminalView) };
+                            view.request_upload();
+                        }
+                    });
+                }
+
+                // Check for clipboard operations, notifications and
+                // permission requests
+                if !state.requests.lock().is_empty() && !state.view_invalid.load(Ordering::SeqCst) {
+                    let state_clone = state.clone();
+                    #[allow(deprecated)]
+                    dispatch2::Queue::main().exec_async(move || {
+                        if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
+                            unsafe {
+                                let view = &*(view_ptr as *const TerminalView);
+                                view.handle_requests();
+                            }
+                        }
+                    });
+                }
+
+                // Check for tmux panes to open, feed or close
+                if !state.tmux_events.lock().is_empty()
+                    && !state.view_invalid.load(Ordering::SeqCst)
+                {
+                    let state_clone = state.clone();
+                    #[allow(deprecated)]
+                    dispatch2::Queue::main().exec_async(move || {
+                        if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
+                            unsafe {
+                                let view = &*(view_ptr as *const TerminalView);
+                                view.handle_tmux_events();
+                            }
+                        }
+                    });
+                }
+
+                // Check for output rates, polling for the last report once
+                // output has stopped
+                let polled = terminal.lock().poll_output_stats();
+                let stats = state.output_stats.lock().take().or(polled);
+                if let Some(stats) = stats {
+                    if !state.view_invalid.load(Ordering::SeqCst) {
+                        let state_clone = state.clone();
+                        #[allow(deprecated)]
+                        dispatch2::Queue::main().exec_async(move || {
+                            if !state_clone.view_invalid.load(Ordering::SeqCst) && view_ptr != 0 {
+                                unsafe {
+                                    let view = &*(view_ptr as *const TerminalView);
+                                    view.update_status(|status| status.set_output_stats(&stats));
+                                }
+                            }
+                        });
+                    }
+                }
+
                 // Check for redraw
                 if state.needs_redraw.swap(false, Ordering::Relaxed) {
                     // Only dispatch if view is still valid
@@ -1875,9 +3204,354 @@ impl TerminalView {
         if let Some(ref mut renderer) = *self.ivars().renderer.borrow_mut() {
             renderer.set_background_override(color);
         }
+        if let Some(ref mut metal) = *self.ivars().metal.borrow_mut() {
+            metal.invalidate();
+        }
+    }
+
+    /// Enter presentation mode at `font_size`, or leave it with `None` and
+    /// return to `font_size`
+    pub fn set_presentation(&self, presentation: Option<&PresentationConfig>, font_size: f64) {
+        if let Some(ref mut renderer) = *self.ivars().renderer.borrow_mut() {
+            match presentation {
+                Some(cfg) => renderer.set_presentation(cfg.minimum_contrast, false),
+                None => renderer.set_presentation(1.0, true),
+            }
+        }
+        *self.ivars().keystrokes.borrow_mut() = presentation
+            .filter(|cfg| cfg.show_keystrokes)
+            .map(|_| KeystrokeLog::new());
+        self.set_font_size(font_size);
+    }
+
+    /// Change the configured font size, as when the window moves to a
+    /// display with its own size, keeping any zoom steps taken
+    pub fn set_base_font_size(&self, font_size: f64) {
+        let old = self.ivars().default_font_size.replace(font_size);
+        if old != font_size {
+            let zoomed = self.ivars().font_size.get() + font_size - old;
+            self.set_font_size(zoomed.clamp(6.0, 72.0));
+        }
+    }
+
+    /// Restyle after the configured appearance changed, e.g. while
+    /// preferences are previewed; `font_size` is the configured size for
+    /// the window's display
+    pub fn apply_appearance(
+        &self,
+        config: &Config,
+        theme: &Theme,
+        font_size: f64,
+        change: AppearanceChange,
+    ) {
+        let appearance = &config.appearance;
+        if let Some(ref mut renderer) = *self.ivars().renderer.borrow_mut() {
+            if change.theme {
+                renderer.set_theme(theme);
+            }
+            if change.theme || change.opacity {
+                renderer.set_opacity(appearance.background_opacity(theme));
+            }
+            if change.font {
+                renderer.set_font_family(MainThreadMarker::from(self), &appearance.font.family);
+            }
+        }
+        if change.theme {
+            *self.ivars().color_palette.borrow_mut() = theme.colors.clone();
+            self.ivars()
+                .terminal
+                .lock()
+                .set_color_scheme(theme.colors.color_scheme());
+        }
+        if change.cursor {
+            self.ivars()
+                .terminal
+                .lock()
+                .screen_mut()
+                .set_cursor_shape(appearance.cursor_style.into(), appearance.cursor_blink);
+        }
+        if change.font {
+            // Keep zoom steps; the new family may also have other metrics
+            let old = self.ivars().default_font_size.replace(font_size);
+            let zoomed = self.ivars().font_size.get() + font_size - old;
+            self.set_font_size(zoomed.clamp(6.0, 72.0));
+        } else {
+            if let Some(ref mut metal) = *self.ivars().metal.borrow_mut() {
+                metal.invalidate();
+            }
+            self.set_needs_display();
+        }
+    }
+
+    /// Rebuild the fonts at `font_size` and reflow the grid to the view
+    fn set_font_size(&self, font_size: f64) {
+        let mtm = MainThreadMarker::from(self);
+        self.ivars().font_size.set(font_size);
+        if let Some(ref mut renderer) = *self.ivars().renderer.borrow_mut() {
+            renderer.set_font_size(mtm, font_size);
+            let (cell_width, cell_height) = renderer.cell_size();
+            self.ivars().cell_width.set(cell_width);
+            self.ivars().cell_height.set(cell_height);
+            let mut terminal = self.ivars().terminal.lock();
+            terminal.screen_mut().set_cell_height_hint(cell_height);
+            terminal.screen_mut().set_cell_width_hint(cell_width);
+        }
+        if let Some(ref mut metal) = *self.ivars().metal.borrow_mut() {
+            metal.invalidate();
+        }
+        self.handle_resize();
+        self.set_needs_display();
+    }
+
+    /// Perform an [`Action`] by sending its menu command up the responder
+    /// chain, as if its shortcut had been pressed
+    fn perform_action(&self, action: &Action) {
+        let Some(selector) = menu::action_selector(action) else {
+            return;
+        };
+        let app = NSApplication::sharedApplication(MainThreadMarker::from(self));
+        unsafe {
+            app.sendAction_to_from(selector, None, Some(self));
+        }
+    }
+
+    /// Scroll the previous (or with `next`, the next) shell prompt to the
+    /// top of the view, beeping when there is none
+    fn scroll_to_prompt(&self, next: bool) {
+        if !self.ivars().terminal.lock().scroll_to_prompt(next) {
+            objc2_app_kit::NSBeep();
+        }
+        self.set_needs_display();
+    }
+
+    /// Window snapping action `[shortcuts]` binds to the key of `event`
+    ///
+    /// Other actions keep their menu key equivalents, but the snapping ones
+    /// can be rebound, as the menu's are fixed.
+    fn snap_action(
+        &self,
+        event: &NSEvent,
+        modifiers: cterm_ui::events::Modifiers,
+    ) -> Option<Action> {
+        let key = keycode::keycode_from_event(event)?;
+        let window = self.cterm_window()?;
+        let action = window.shortcuts().match_event(key, modifiers)?;
+        matches!(
+            action,
+            Action::SnapLeft | Action::SnapRight | Action::SnapMaximize
+        )
+        .then(|| action.clone())
+    }
+
+    /// Override when finished commands are reported (from a template)
+    pub fn set_command_notify(&self, notify: CommandNotifyConfig) {
+        self.ivars().command_notify.set(notify);
+    }
+
+    /// Toast commands that ran long enough, with a desktop notification
+    /// if the window isn't key
+    fn report_finished_commands(&self) {
+        let finished = std::mem::take(&mut *self.ivars().state.finished_commands.lock());
+        if !finished.is_empty() {
+            if let Some(window) = self.cterm_window() {
+                status_segments::command_finished(&window.config().status_segments);
+            }
+        }
+        let notify = self.ivars().command_notify.get();
+        for finished in finished.iter().filter(|f| notify.should_notify(f.duration)) {
+            self.play_cue(self.ivars().sounds.command_cue(finished));
+            self.update_status(|status| {
+                status.show_command_finished(finished, Instant::now());
+                true
+            });
+            unsafe {
+                let _: () = msg_send![
+                    self,
+                    performSelector: sel!(expireToast),
+                    withObject: std::ptr::null::<AnyObject>(),
+                    afterDelay: StatusStrip::TOAST_DURATION.as_secs_f64()
+                ];
+            }
+
+            let Some(window) = self.window() else {
+                continue;
+            };
+            if notify.desktop_when_unfocused && !window.isKeyWindow() {
+                deliver_notification(&window.title().to_string(), &command_summary(finished));
+            }
+        }
+    }
+
+    /// Play a `[sounds]` cue, unless it is for the background only and the
+    /// window is key
+    fn play_cue(&self, cue: Option<Cue>) {
+        let focused = self.window().is_some_and(|window| window.isKeyWindow());
+        if !self.ivars().sounds.active(focused) {
+            return;
+        }
+        match cue {
+            Some(Cue::Beep) => objc2_app_kit::NSBeep(),
+            Some(cue) => sounds::play(&cue),
+            None => {}
+        }
+    }
+
+    /// Perform what the triggers matching new output lines ask for
+    fn run_triggers(&self) {
+        let lines = std::mem::take(&mut *self.ivars().state.completed_lines.lock());
+        let effects = self
+            .ivars()
+            .triggers
+            .borrow_mut()
+            .scan(&lines, Instant::now());
+        for effect in effects {
+            match effect {
+                TriggerEffect::Send(text) => self.write_to_pty(text.as_bytes()),
+                TriggerEffect::Notify { title, body } => {
+                    let title = match self.window() {
+                        Some(window) => format!("{}: {}", window.title(), title),
+                        None => title,
+                    };
+                    deliver_notification(&title, &body);
+                }
+                TriggerEffect::Command { program, args } => {
+                    if let Err(e) = triggers::run_command(&program, &args) {
+                        log::error!("Trigger failed to run {}: {}", program, e);
+                    }
+                }
+                TriggerEffect::TabColor(color) => {
+                    if let Some(window) = self.cterm_window() {
+                        window.set_tab_color(Some(&color));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Show the latest watch expression match on the tab, with a desktop
+    /// notification if the window isn't key
+    fn report_watch_match(&self) {
+        let Some(found) = self.ivars().state.watch_match.lock().take() else {
+            return;
+        };
+        self.update_tab_label();
+        let Some(window) = self.window() else {
+            return;
+        };
+        if found.notify && !window.isKeyWindow() {
+            deliver_notification(&format!("{}: watch matched", window.title()), &found.line);
+        }
+    }
+
+    /// Label the tab with its title through the title format, followed by
+    /// the watch match count; a label that is just the title is cleared so
+    /// the tab follows the window title
+    pub fn update_tab_label(&self) {
+        let count = self
+            .ivars()
+            .terminal
+            .lock()
+            .watch()
+            .map(|watch| watch.count());
+        let Some(window) = self.window() else {
+            return;
+        };
+        let title = window.title().to_string();
+        let mut label = match self.cterm_window() {
+            Some(cterm_window) => cterm_window.tab_label(&title),
+            None => title.clone(),
+        };
+        if let Some(count) = count {
+            label = format!("{} [{}]", label, count);
+        }
+        unsafe {
+            let tab: *mut AnyObject = msg_send![&window, tab];
+            if tab.is_null() {
+                return;
+            }
+            if label == title {
+                let _: () = msg_send![tab, setTitle: std::ptr::null::<NSString>()];
+            } else {
+                let _: () = msg_send![tab, setTitle: &*NSString::from_str(&label)];
+            }
+        }
+    }
+
+    /// Show the ZMODEM or Kermit transfer in progress in the status strip,
+    /// and ask for files when a remote `rz` or `kermit -r` waits for them
+    fn sync_transfer(&self) {
+        let transfer = self.ivars().terminal.lock().file_transfer();
+        self.update_status(|status| status.set_transfer(transfer));
+        if !self
+            .ivars()
+            .state
+            .transfer_files_requested
+            .swap(false, Ordering::Relaxed)
+        {
+            return;
+        }
+        let Some((protocol, _)) = transfer else {
+            return;
+        };
+
+        let mtm = MainThreadMarker::from(self);
+        let title = format!("Send Files ({})", protocol.name());
+        let paths = crate::dialogs::show_send_files_panel(mtm, &title);
+        let mut terminal = self.ivars().terminal.lock();
+        let events = if paths.is_empty() {
+            terminal.cancel_file_transfer()
+        } else {
+            terminal.send_transfer_files(read_files(&paths))
+        };
+        drop(terminal);
+        if events
+            .iter()
+            .any(|e| matches!(e, TerminalEvent::TransferFinished))
+        {
+            self.update_status(|status| status.set_transfer(None));
+        }
+        self.set_needs_display();
+    }
+
+    /// Ask for files to upload after an OSC 1337 RequestUpload (`it2ul`) and
+    /// stream them to the remote; aborts the upload if none are chosen
+    fn request_upload(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let paths = crate::dialogs::show_send_files_panel(mtm, "Upload Files");
+        let files = read_files(&paths);
+        if files.is_empty() {
+            self.write_to_pty(cterm_core::iterm2::UPLOAD_ABORT);
+            return;
+        }
+        match cterm_core::iterm2::encode_upload(&files) {
+            Ok(data) => self.start_paste(PasteJob::upload(data)),
+            Err(e) => {
+                log::error!("Failed to encode upload: {}", e);
+                self.write_to_pty(cterm_core::iterm2::UPLOAD_ABORT);
+            }
+        }
+    }
+
+    /// Scroll as far as the pane locked to this one did, from `from` to `to`
+    /// lines back
+    pub fn follow_scroll(&self, from: usize, to: usize) {
+        session::mirror_scroll(&mut self.ivars().terminal.lock(), from, to);
+        self.set_needs_display();
+    }
+
+    /// Update the status strip, redrawing if anything changed
+    pub fn update_status(&self, update: impl FnOnce(&mut StatusStrip) -> bool) {
+        if update(&mut self.ivars().status.borrow_mut()) {
+            self.set_needs_display();
+        }
     }
 
     /// Check if the title is locked (user-set or template-set)
+    /// Whether this view is a read-only monitor of another tab's session
+    pub fn is_monitor(&self) -> bool {
+        self.ivars().state.monitor.load(Ordering::Relaxed)
+    }
+
     pub fn is_title_locked(&self) -> bool {
         self.ivars()
             .state
@@ -1893,11 +3567,24 @@ impl TerminalView {
             .store(locked, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// Mark the view as moving between splits, so leaving the window for a
+    /// moment does not end its session
+    pub fn set_moving(&self, moving: bool) {
+        self.ivars().moving.set(moving);
+    }
+
+    /// Window hosting the view
+    fn cterm_window(&self) -> Option<Retained<crate::window::CtermWindow>> {
+        // Terminal views are only hosted in CtermWindows
+        let window = self.window()?;
+        unsafe { Retained::retain(Retained::as_ptr(&window) as *mut crate::window::CtermWindow) }
+    }
+
     /// Handle window resize
     pub fn handle_resize(&self) {
         let frame = self.frame();
-        let cell_width = self.ivars().cell_width;
-        let cell_height = self.ivars().cell_height;
+        let cell_width = self.ivars().cell_width.get();
+        let cell_height = self.ivars().cell_height.get();
 
         log::debug!(
             "handle_resize: frame={}x{}, cell={}x{}",
@@ -1920,7 +3607,15 @@ impl TerminalView {
         let cols = (frame.size.width / cell_width).floor() as usize;
         let rows = (frame.size.height / cell_height).floor() as usize;
 
-        if cols > 0 && rows > 0 {
+        // tmux sizes its panes to fit the view
+        if let Some(ref gateway) = *self.ivars().tmux_pane.borrow() {
+            gateway.resize(cols, rows);
+            return;
+        }
+
+        // A monitor follows the size of the session it mirrors, and playback
+        // the size of the recording
+        if cols > 0 && rows > 0 && !self.is_monitor() && self.ivars().playback.borrow().is_none() {
             let mut terminal = self.ivars().terminal.lock();
             terminal.resize(cols, rows);
             drop(terminal);
@@ -1941,6 +3636,91 @@ impl TerminalView {
         }
     }
 
+    /// Paste `text`, a chunk at a time if it is large, in bracketed paste
+    /// markers if `bracketable` and the application asked for them; replaces
+    /// any paste in progress
+    ///
+    /// Pasting bracketable text into an application without bracketed paste
+    /// asks first if the paste guard says so.
+    fn paste_text(&self, text: &str, bracketable: bool) {
+        let bracketed = bracketable && self.ivars().terminal.lock().screen().modes.bracketed_paste;
+        if bracketable && !bracketed {
+            if let Some(warning) = self.ivars().paste_guard.check(text) {
+                let mtm = MainThreadMarker::from(self);
+                if !crate::dialogs::confirm_paste(mtm, text, &warning) {
+                    return;
+                }
+            }
+        }
+        self.start_paste(PasteJob::new(text, bracketed));
+    }
+
+    /// Write `job` at once if it is small, else a chunk at a time; replaces
+    /// any paste in progress
+    fn start_paste(&self, mut job: PasteJob) {
+        self.cancel_paste();
+        let chunk_bytes = self.ivars().input.paste_chunk_bytes;
+        if !job.is_chunked(chunk_bytes) {
+            if let Some(data) = job.next_chunk(0) {
+                self.write_to_pty(data);
+            }
+            return;
+        }
+
+        *self.ivars().paste.borrow_mut() = Some(job);
+        self.write_paste_chunk();
+        let timer: Retained<objc2_foundation::NSTimer> = unsafe {
+            msg_send![
+                class!(NSTimer),
+                scheduledTimerWithTimeInterval: InputConfig::PASTE_CHUNK_INTERVAL.as_secs_f64(),
+                target: self,
+                selector: sel!(pasteFire:),
+                userInfo: std::ptr::null::<AnyObject>(),
+                repeats: true
+            ]
+        };
+        *self.ivars().paste_timer.borrow_mut() = Some(timer);
+    }
+
+    /// Write the next chunk of the paste in progress and update its progress
+    fn write_paste_chunk(&self) {
+        let mut paste = self.ivars().paste.borrow_mut();
+        let Some(job) = paste.as_mut() else {
+            return;
+        };
+        let chunk = job
+            .next_chunk(self.ivars().input.paste_chunk_bytes)
+            .map(<[u8]>::to_vec);
+        let progress = (!job.is_done()).then(|| (job.written(), job.len()));
+        if progress.is_none() {
+            *paste = None;
+            self.stop_paste_timer();
+        }
+        drop(paste);
+
+        if let Some(chunk) = chunk {
+            self.write_to_pty(&chunk);
+        }
+        self.update_status(|status| status.set_paste_progress(progress));
+    }
+
+    /// Stop the paste in progress; returns false if there was none
+    fn cancel_paste(&self) -> bool {
+        self.stop_paste_timer();
+        let Some(mut job) = self.ivars().paste.borrow_mut().take() else {
+            return false;
+        };
+        self.write_to_pty(job.cancel());
+        self.update_status(|status| status.set_paste_progress(None));
+        true
+    }
+
+    fn stop_paste_timer(&self) {
+        if let Some(timer) = self.ivars().paste_timer.borrow_mut().take() {
+            timer.invalidate();
+        }
+    }
+
     /// Handle a drop operation — extract file URL, show dialog, write to PTY
     fn handle_drop(&self, sender: &AnyObject) -> bool {
         use cterm_app::file_drop::{build_pty_input, FileDropAction, FileDropInfo};
@@ -1986,7 +3766,13 @@ impl TerminalView {
         let choice = crate::dialogs::show_file_drop_dialog(mtm, &info);
 
         let action = match choice {
-            crate::dialogs::FileDropChoice::PastePath => FileDropAction::PastePath,
+            crate::dialogs::FileDropChoice::PastePath => FileDropAction::PastePath {
+                quoting: self
+                    .ivars()
+                    .input
+                    .path_quoting
+                    .for_terminal(&self.ivars().terminal.lock()),
+            },
             crate::dialogs::FileDropChoice::PasteContents => FileDropAction::PasteContents,
             crate::dialogs::FileDropChoice::CreateViaBase64(filename) => {
                 FileDropAction::CreateViaBase64 { filename }
@@ -2000,21 +3786,7 @@ impl TerminalView {
         let use_bracketed = matches!(action, FileDropAction::PasteContents);
 
         match build_pty_input(&info, action) {
-            Ok(text) => {
-                if use_bracketed {
-                    let terminal = self.ivars().terminal.lock();
-                    let bracketed = terminal.screen().modes.bracketed_paste;
-                    drop(terminal);
-                    let paste = if bracketed {
-                        format!("\x1b[200~{}\x1b[201~", text)
-                    } else {
-                        text
-                    };
-                    self.write_to_pty(paste.as_bytes());
-                } else {
-                    self.write_to_pty(text.as_bytes());
-                }
-            }
+            Ok(text) => self.paste_text(&text, use_bracketed),
             Err(e) => {
                 log::error!("Failed to build PTY input for dropped file: {}", e);
             }
@@ -2030,7 +3802,10 @@ impl TerminalView {
 
     /// Get the cell size (width, height) for grid snapping
     pub fn cell_size(&self) -> (f64, f64) {
-        (self.ivars().cell_width, self.ivars().cell_height)
+        (
+            self.ivars().cell_width.get(),
+            self.ivars().cell_height.get(),
+        )
     }
 
     /// Send focus event to terminal if focus events mode is enabled (DECSET 1004)
@@ -2100,14 +3875,14 @@ impl TerminalView {
         })
     }
 
-    /// Get the current working directory of the foreground process (if any)
+    /// Get the current working directory of the foreground process, or else
+    /// the one the shell reported with OSC 7 (if any)
     #[cfg(unix)]
     pub fn foreground_cwd(&self) -> Option<String> {
-        self.ivars()
-            .terminal
-            .lock()
-            .foreground_cwd()
+        let term = self.ivars().terminal.lock();
+        term.foreground_cwd()
             .map(|p| p.to_string_lossy().into_owned())
+            .or_else(|| term.screen().local_current_dir().map(String::from))
     }
 
     /// Get the daemon socket path this terminal is connected to (None = local default)
@@ -2115,6 +3890,130 @@ impl TerminalView {
         self.ivars().daemon_socket.borrow().clone()
     }
 
+    /// Host a Metal layer below the subviews, once, if Metal is available
+    fn setup_metal(&self) {
+        if self.ivars().metal.borrow().is_some() {
+            return;
+        }
+        let Some(metal) = MetalRenderer::new() else {
+            log::info!("Metal unavailable, using CoreGraphics rendering");
+            return;
+        };
+        self.setWantsLayer(true);
+        if let Some(layer) = self.layer() {
+            layer.insertSublayer_atIndex(metal.layer(), 0);
+        }
+        *self.ivars().metal.borrow_mut() = Some(metal);
+    }
+
+    /// Draw the frame with Metal if the grid is large enough and nothing in
+    /// it needs CoreGraphics (`plain`); hides the Metal layer otherwise
+    fn render_with_metal(
+        &self,
+        renderer: &CGRenderer,
+        terminal: &Terminal,
+        bounds: NSRect,
+        plain: bool,
+    ) -> bool {
+        let mut metal = self.ivars().metal.borrow_mut();
+        let Some(metal) = metal.as_mut() else {
+            return false;
+        };
+        let screen = terminal.screen();
+        let scale = self.window().map_or(2.0, |w| w.backingScaleFactor());
+        let drawn = plain
+            && metal_renderer::wants_metal(screen.width(), screen.height())
+            && metal.render(renderer, terminal, bounds, scale);
+        metal.set_visible(drawn);
+        drawn
+    }
+
+    /// Pace redraws to the refresh rate of the window's screen
+    fn update_frame_interval(&self) {
+        let screen = self.window().and_then(|w| w.screen());
+        let interval = metal_renderer::frame_interval(screen.as_deref());
+        self.ivars()
+            .state
+            .frame_interval_us
+            .store(interval.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Draw in Display P3 while the window sits on a wide-gamut screen
+    fn update_color_gamut(&self) {
+        let wide_gamut = self
+            .window()
+            .is_some_and(|w| w.canRepresentDisplayGamut(objc2_app_kit::NSDisplayGamut::P3));
+        if let Some(ref mut renderer) = *self.ivars().renderer.borrow_mut() {
+            if renderer.is_wide_gamut() != wide_gamut {
+                log::debug!("Switching color space (Display P3: {})", wide_gamut);
+                renderer.set_wide_gamut(wide_gamut);
+            }
+        }
+    }
+
+    /// Apply `[[auto_switch]]` rules when the shell moves to another host
+    fn update_auto_switch(&self) {
+        let change = {
+            let terminal = self.ivars().terminal.lock();
+            self.ivars()
+                .auto_switch
+                .borrow_mut()
+                .update(terminal.screen())
+        };
+        let Some(change) = change else {
+            return;
+        };
+        let window = self.window();
+        // Terminal views are only hosted in CtermWindows
+        let cterm_window = window
+            .as_ref()
+            .map(|w| unsafe { &*(Retained::as_ptr(w) as *const crate::window::CtermWindow) });
+        let mut saved = self.ivars().auto_switch_saved.borrow_mut();
+        let (theme, tab_color) = match change {
+            AutoSwitchChange::Apply(rule) => {
+                if saved.is_none() {
+                    let theme = self
+                        .ivars()
+                        .renderer
+                        .borrow()
+                        .as_ref()
+                        .map(|r| r.theme().clone());
+                    let tab_color = cterm_window.and_then(|w| w.tab_color());
+                    *saved = theme.map(|theme| (theme, tab_color));
+                }
+                let theme = rule.theme.as_deref().and_then(|name| {
+                    let theme = cterm_app::config::find_theme(name);
+                    if theme.is_none() {
+                        log::warn!("Auto switch: unknown theme {:?}", name);
+                    }
+                    theme
+                });
+                (theme, rule.tab_color.map(Some))
+            }
+            AutoSwitchChange::Revert => match saved.take() {
+                Some((theme, tab_color)) => (Some(theme), Some(tab_color)),
+                None => return,
+            },
+        };
+        drop(saved);
+
+        if let Some(theme) = theme {
+            if let Some(ref mut renderer) = *self.ivars().renderer.borrow_mut() {
+                renderer.set_theme(&theme);
+            }
+            self.ivars()
+                .terminal
+                .lock()
+                .set_color_scheme(theme.colors.color_scheme());
+            if let Some(ref mut metal) = *self.ivars().metal.borrow_mut() {
+                metal.invalidate();
+            }
+        }
+        if let (Some(color), Some(window)) = (tab_color, cterm_window) {
+            window.set_tab_color(color.as_deref());
+        }
+    }
+
     /// Request display update
     fn set_needs_display(&self) {
         unsafe {
@@ -2135,11 +4034,11 @@ impl TerminalView {
             timer.invalidate();
         }
 
-        // Create a repeating timer (every 50ms = ~20 lines/sec)
+        // Create a repeating timer (every 50ms)
         let timer: Retained<objc2_foundation::NSTimer> = unsafe {
             msg_send![
                 class!(NSTimer),
-                scheduledTimerWithTimeInterval: 0.05f64,
+                scheduledTimerWithTimeInterval: InputConfig::AUTOSCROLL_INTERVAL.as_secs_f64(),
                 target: self,
                 selector: sel!(autoScrollFire:),
                 userInfo: std::ptr::null::<AnyObject>(),
@@ -2167,34 +4066,74 @@ impl TerminalView {
     }
 
     /// Convert point from window coordinates to view coordinates
+    /// Convert a point into terminal surface coordinates, undoing any magnification
     fn convert_point_from_view(&self, point: NSPoint, view: Option<&NSView>) -> NSPoint {
-        unsafe { msg_send![self, convertPoint: point, fromView: view] }
+        let point: NSPoint = unsafe { msg_send![self, convertPoint: point, fromView: view] };
+        let (x, y) = self
+            .ivars()
+            .magnifier
+            .get()
+            .view_to_surface(point.x, point.y);
+        NSPoint::new(x, y)
+    }
+
+    /// Show the pointer shape the program asked for if the mouse is over
+    /// the view and not hidden while typing, without waiting for it to move
+    fn apply_pointer_shape(&self) {
+        let Some(window) = self.window() else {
+            return;
+        };
+        let location = window.mouseLocationOutsideOfEventStream();
+        let point: NSPoint =
+            unsafe { msg_send![self, convertPoint: location, fromView: None::<&NSView>] };
+        let bounds = self.bounds();
+        let inside = point.x >= 0.0
+            && point.y >= 0.0
+            && point.x < bounds.size.width
+            && point.y < bounds.size.height;
+        if inside && !self.ivars().pointer_hider.get().is_hidden() {
+            let shape = self.ivars().terminal.lock().screen().pointer_shape();
+            set_pointer_cursor(shape, MainThreadMarker::from(self));
+        }
+    }
+
+    /// Apply a magnification change and redraw if it took effect
+    fn magnify(&self, change: fn(&mut Magnifier) -> bool) {
+        let mut magnifier = self.ivars().magnifier.get();
+        if change(&mut magnifier) {
+            self.ivars().magnifier.set(magnifier);
+            self.set_needs_display();
+        }
     }
 
     /// Forward a mouse button event to a tracking application.
     ///
     /// Returns `true` if the event was consumed — i.e. an application has mouse
-    /// reporting enabled and Shift is not held. Holding Shift bypasses reporting
-    /// (xterm/VTE convention) so text selection, scrollback, and context menus
-    /// keep working while an app grabs the mouse. When this returns `false` the
-    /// caller falls back to cterm's own local handling.
+    /// reporting enabled and the `input.mouse_override` modifier (Shift by
+    /// default, the xterm/VTE convention) is not held. Holding it bypasses
+    /// reporting so text selection, scrollback, and context menus keep working
+    /// while an app grabs the mouse. When this returns `false` the caller falls
+    /// back to cterm's own local handling.
     fn report_mouse_button(&self, event: &NSEvent, button: MouseButton, is_drag: bool) -> bool {
-        let shift = event
-            .modifierFlags()
-            .contains(objc2_app_kit::NSEventModifierFlags::Shift);
+        let modifiers = self.get_mouse_modifiers(event);
+        let local = self.ivars().input.overrides_mouse_tracking(modifiers);
         let terminal = self.ivars().terminal.lock();
         let mouse_mode = terminal.screen().modes.mouse_mode;
         let sgr_mouse = terminal.screen().modes.sgr_mouse;
+        let locked = terminal.is_input_locked();
         drop(terminal);
 
-        if shift || !mouse::should_capture_mouse(mouse_mode) {
+        if local || locked || !mouse::should_capture_mouse(mouse_mode) {
             return false;
         }
 
         let location = self.convert_point_from_view(event.locationInWindow(), None);
-        let col = (location.x / self.ivars().cell_width).floor().max(0.0) as usize;
-        let row = (location.y / self.ivars().cell_height).floor().max(0.0) as usize;
-        let modifiers = self.get_mouse_modifiers(event);
+        let col = (location.x / self.ivars().cell_width.get())
+            .floor()
+            .max(0.0) as usize;
+        let row = (location.y / self.ivars().cell_height.get())
+            .floor()
+            .max(0.0) as usize;
 
         if let Some(seq) =
             mouse::encode_mouse_event(mouse_mode, sgr_mouse, button, col, row, modifiers, is_drag)
@@ -2301,6 +4240,9 @@ impl TerminalView {
         let mut terminal = self.ivars().terminal.lock();
         let transfers = terminal.screen_mut().take_file_transfers();
         drop(terminal);
+        if transfers.is_empty() {
+            return;
+        }
 
         for transfer in transfers {
             match transfer {
@@ -2311,15 +4253,12 @@ impl TerminalView {
                         .borrow_mut()
                         .set_pending(id, name.clone(), data);
 
-                    if let Some(ref bar) = *self.ivars().notification_bar.borrow() {
-                        bar.show_file(id, name.as_deref(), size);
-                    }
-
                     log::info!(
                         "File transfer received: {:?} ({} bytes)",
                         name.as_deref().unwrap_or("unnamed"),
                         size
                     );
+                    self.ivars().prompts.borrow_mut().push_file(id, name, size);
                 }
                 cterm_core::FileTransferOperation::StreamingFileReceived { id, result } => {
                     let name = result.params.name.clone();
@@ -2330,22 +4269,166 @@ impl TerminalView {
                         .borrow_mut()
                         .set_pending_streaming(id, name.clone(), result.data);
 
-                    if let Some(ref bar) = *self.ivars().notification_bar.borrow() {
-                        bar.show_file(id, name.as_deref(), size);
-                    }
-
                     log::info!(
                         "Streaming file transfer received: {:?} ({} bytes)",
                         name.as_deref().unwrap_or("unnamed"),
                         size
                     );
+                    self.ivars().prompts.borrow_mut().push_file(id, name, size);
+                }
+            }
+        }
+        self.show_prompt();
+    }
+
+    /// Carry out the clipboard operations and notifications the permission
+    /// policy allowed, and ask about what it holds
+    fn handle_requests(&self) {
+        let requests = std::mem::take(&mut *self.ivars().state.requests.lock());
+        for request in requests {
+            self.handle_request(request);
+        }
+        self.show_prompt();
+    }
+
+    fn handle_request(&self, event: TerminalEvent) {
+        match event {
+            TerminalEvent::ClipboardRequest(ClipboardOperation::Set { selection: _, data }) => {
+                if let Ok(text) = String::from_utf8(data) {
+                    clipboard::set_text(&text);
+                    clipboard_history::record(&text, ClipSource::Osc52);
+                }
+            }
+            TerminalEvent::ClipboardRequest(ClipboardOperation::Query { selection }) => {
+                self.send_clipboard(selection);
+            }
+            TerminalEvent::Notification(notification) => {
+                let title = notification.title.unwrap_or_else(|| {
+                    self.window()
+                        .map(|w| w.title().to_string())
+                        .unwrap_or_else(|| "cterm".to_string())
+                });
+                deliver_notification(&title, &notification.body);
+            }
+            // A view is one terminal, so the queue's tab is always 0
+            TerminalEvent::PermissionRequested(capability) => {
+                self.ivars()
+                    .prompts
+                    .borrow_mut()
+                    .ask_permission(0, capability);
+            }
+            _ => {}
+        }
+    }
+
+    /// Override the permission policy (from a template)
+    pub fn set_permissions(&self, permissions: PermissionPolicy) {
+        self.ivars().terminal.lock().set_permissions(permissions);
+    }
+
+    /// Release or drop what waits for the user's permission; with
+    /// `remember` the answer holds for later requests
+    fn answer_permission(&self, capability: Capability, allow: bool, remember: bool) {
+        let events = self
+            .ivars()
+            .terminal
+            .lock()
+            .answer_permission(capability, allow, remember);
+        let state = &self.ivars().state;
+        for event in events {
+            match event {
+                TerminalEvent::TitleChanged(title) => {
+                    if let Ok(mut current_title) = state.title.write() {
+                        *current_title = title;
+                    }
+                    state.title_changed.store(true, Ordering::Relaxed);
+                }
+                TerminalEvent::TransferStarted(..) | TerminalEvent::TransferFinished => {
+                    state.transfer_changed.store(true, Ordering::Relaxed);
+                }
+                TerminalEvent::TransferSendRequested => {
+                    state
+                        .transfer_files_requested
+                        .store(true, Ordering::Relaxed);
+                }
+                event => self.handle_request(event),
+            }
+        }
+        state.needs_redraw.store(true, Ordering::Relaxed);
+    }
+
+    /// Answer an OSC 52 read with the pasteboard contents
+    fn send_clipboard(&self, selection: cterm_core::screen::ClipboardSelection) {
+        let text = clipboard::get_text().unwrap_or_default();
+        let mut terminal = self.ivars().terminal.lock();
+        if let Err(e) = terminal.send_clipboard_response(selection, text.as_bytes()) {
+            log::error!("Failed to send clipboard contents: {}", e);
+        }
+    }
+
+    /// Show the prompt at the front of the queue, or hide the bar when
+    /// nothing is waiting
+    fn show_prompt(&self) {
+        let Some(ref bar) = *self.ivars().notification_bar.borrow() else {
+            return;
+        };
+        let prompts = self.ivars().prompts.borrow();
+        match (prompts.current(), prompts.message()) {
+            (Some(prompt), Some(message)) => bar.show_prompt(&message, &prompt.actions(true)),
+            _ => bar.hide(),
+        }
+    }
+
+    /// Carry out the user's answer to the current prompt
+    fn answer_prompt(&self, action: PromptAction) {
+        let file_id = self
+            .ivars()
+            .prompts
+            .borrow()
+            .current()
+            .and_then(|p| p.file_id());
+        match (action, file_id) {
+            // Previewing leaves the file waiting
+            (PromptAction::Preview, Some(file_id)) => {
+                let result = self.ivars().file_manager.borrow().preview_copy(file_id);
+                match result {
+                    Ok(path) => self.show_preview(&path),
+                    Err(e) => log::error!("Failed to prepare file preview: {}", e),
+                }
+                return;
+            }
+            // So does cancelling the save panel
+            (PromptAction::SaveAs, Some(file_id)) => {
+                if !self.handle_file_save_as(file_id) {
+                    return;
+                }
+                // Another file may have replaced it while the panel was open
+                let mut prompts = self.ivars().prompts.borrow_mut();
+                if prompts.current().and_then(|p| p.file_id()) == Some(file_id) {
+                    prompts.resolve(action);
+                }
+            }
+            _ => {
+                let prompt = self.ivars().prompts.borrow_mut().resolve(action);
+                match prompt {
+                    Some(Prompt::File { id, .. }) if action == PromptAction::Save => {
+                        self.handle_file_save(id)
+                    }
+                    Some(Prompt::File { id, .. }) => self.handle_file_discard(id),
+                    Some(Prompt::Permission { capability, .. }) => self.answer_permission(
+                        capability,
+                        !action.is_dismissal(),
+                        action == PromptAction::AlwaysAllow,
+                    ),
+                    None => return,
                 }
             }
         }
+        self.show_prompt();
     }
 
-    /// Handle save button click from notification bar
-    pub fn handle_file_save(&self, file_id: u64) {
+    /// Save a received file to the default location
+    fn handle_file_save(&self, file_id: u64) {
         let mut manager = self.ivars().file_manager.borrow_mut();
 
         if let Some(path) = manager.default_save_path() {
@@ -2358,15 +4441,11 @@ impl TerminalView {
                 }
             }
         }
-        drop(manager);
-
-        if let Some(ref bar) = *self.ivars().notification_bar.borrow() {
-            bar.hide();
-        }
     }
 
-    /// Handle save-as button click from notification bar
-    pub fn handle_file_save_as(&self, file_id: u64) {
+    /// Ask where to save a received file and save it there; returns false
+    /// if the panel was cancelled
+    fn handle_file_save_as(&self, file_id: u64) -> bool {
         let mtm = MainThreadMarker::from(self);
         let manager = self.ivars().file_manager.borrow();
 
@@ -2374,37 +4453,100 @@ impl TerminalView {
         let suggested_dir = manager.last_save_dir().cloned();
         drop(manager);
 
-        if let Some(path) = crate::dialogs::show_save_panel(
+        let Some(path) = crate::dialogs::show_save_panel(
             mtm,
             self.window().as_deref(),
             suggested_name.as_deref(),
             suggested_dir.as_deref(),
-        ) {
-            let mut manager = self.ivars().file_manager.borrow_mut();
-            match manager.save_to_path(file_id, &path) {
-                Ok(size) => {
-                    log::info!("Saved {} bytes to {:?}", size, path);
-                }
-                Err(e) => {
-                    log::error!("Failed to save file: {}", e);
-                }
+        ) else {
+            return false;
+        };
+        let mut manager = self.ivars().file_manager.borrow_mut();
+        match manager.save_to_path(file_id, &path) {
+            Ok(size) => {
+                log::info!("Saved {} bytes to {:?}", size, path);
+            }
+            Err(e) => {
+                log::error!("Failed to save file: {}", e);
             }
         }
-
-        if let Some(ref bar) = *self.ivars().notification_bar.borrow() {
-            bar.hide();
-        }
+        true
     }
 
-    /// Handle discard button click from notification bar
-    pub fn handle_file_discard(&self, file_id: u64) {
+    /// Delete a received file
+    fn handle_file_discard(&self, file_id: u64) {
         self.ivars().file_manager.borrow_mut().discard(file_id);
+        log::debug!("Discarded file {}", file_id);
+    }
 
-        if let Some(ref bar) = *self.ivars().notification_bar.borrow() {
-            bar.hide();
+    /// Note the link or image path at a cell for the hover preview
+    fn hover_link(&self, col: usize, row: usize) {
+        let cwd = self.foreground_cwd();
+        let terminal = self.ivars().terminal.lock();
+        let absolute_line = terminal.screen().visible_row_to_absolute_line(row);
+        let target = detect::span_at(terminal.screen(), absolute_line, col)
+            .and_then(|span| PreviewTarget::for_span(&span, cwd.as_deref()));
+        drop(terminal);
+
+        let cell_width = self.ivars().cell_width.get();
+        let cell_height = self.ivars().cell_height.get();
+        let rect = NSRect::new(
+            NSPoint::new(col as f64 * cell_width, row as f64 * cell_height),
+            NSSize::new(cell_width, cell_height),
+        );
+        self.set_hovered_link(target.map(|target| (target, rect)));
+    }
+
+    /// Switch the hovered link, closing the preview of the previous one
+    ///
+    /// A new link is previewed after [`HOVER_DELAY`] unless the pointer
+    /// moves on first.
+    fn set_hovered_link(&self, hovered: Option<(PreviewTarget, NSRect)>) {
+        let unchanged = match (&*self.ivars().hovered_link.borrow(), &hovered) {
+            (Some((old, _)), Some((new, _))) => old == new,
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
         }
 
-        log::debug!("Discarded file {}", file_id);
+        unsafe {
+            let _: () = msg_send![
+                class!(NSObject),
+                cancelPreviousPerformRequestsWithTarget: self,
+                selector: sel!(showLinkPreview),
+                object: std::ptr::null::<AnyObject>()
+            ];
+        }
+        if let Some(popover) = self.ivars().link_popover.borrow_mut().take() {
+            popover.close();
+        }
+        let pending = hovered.is_some();
+        *self.ivars().hovered_link.borrow_mut() = hovered;
+        if pending {
+            unsafe {
+                let _: () = msg_send![
+                    self,
+                    performSelector: sel!(showLinkPreview),
+                    withObject: std::ptr::null::<AnyObject>(),
+                    afterDelay: HOVER_DELAY.as_secs_f64()
+                ];
+            }
+        }
+    }
+
+    /// Show a loaded preview if its link is still the hovered one
+    fn present_link_preview(&self, preview: &LinkPreview) {
+        let rect = match &*self.ivars().hovered_link.borrow() {
+            Some((target, rect)) if *target == preview.target => *rect,
+            _ => return,
+        };
+        if let Some(popover) = self.ivars().link_popover.borrow_mut().take() {
+            popover.close();
+        }
+        let popover = link_popover::show(self, preview, rect);
+        *self.ivars().link_popover.borrow_mut() = Some(popover);
     }
 
     /// Set tooltip for the view (shows URL on hyperlink hover)
@@ -2418,6 +4560,72 @@ impl TerminalView {
         let _: () = unsafe { msg_send![self, setToolTip: std::ptr::null::<NSString>()] };
     }
 
+    /// Handle a key press while hint mode is active
+    ///
+    /// Letters narrow down the labels, Backspace undoes one and Escape (or a
+    /// letter matching no label) leaves hint mode.
+    fn hint_mode_key(&self, event: &NSEvent) {
+        let mut hints = self.ivars().hints.borrow_mut();
+        let Some(mode) = hints.as_mut() else {
+            return;
+        };
+        let input = match event.keyCode() {
+            0x35 => HintInput::NoMatch, // Escape
+            0x33 => {
+                // Backspace
+                mode.backspace();
+                HintInput::Pending
+            }
+            _ => match keycode::characters_ignoring_modifiers(event)
+                .and_then(|chars| chars.chars().next())
+            {
+                Some(c) if c.is_alphabetic() => mode.input(c),
+                _ => HintInput::Pending,
+            },
+        };
+
+        let typed = matches!(input, HintInput::Pending).then(|| mode.typed().to_string());
+        if typed.is_none() {
+            *hints = None;
+        }
+        drop(hints);
+        self.update_status(|status| status.set_hints(typed.as_deref()));
+        self.set_needs_display();
+
+        let HintInput::Chosen(hint, action) = input else {
+            return;
+        };
+        let cwd = self.foreground_cwd().or_else(|| {
+            Some(self.ivars().terminal.lock().screen().current_dir.clone())
+                .filter(|dir| !dir.is_empty())
+        });
+        match (action, hint.open_target(cwd.as_deref())) {
+            (HintAction::Open, Some(target)) => self.open_target(&target),
+            // Hashes have nothing to open, so they are always copied
+            _ => {
+                let quoting = self
+                    .ivars()
+                    .input
+                    .path_quoting
+                    .for_terminal(&self.ivars().terminal.lock());
+                clipboard::set_text(&quoting.quote_hint(&hint));
+            }
+        }
+    }
+
+    /// Open a URL, or a file path with its default application
+    fn open_target(&self, target: &str) {
+        use objc2_app_kit::NSWorkspace;
+        use objc2_foundation::NSURL;
+
+        if target.contains("://") {
+            self.open_url(target);
+        } else {
+            let url = NSURL::fileURLWithPath(&NSString::from_str(target));
+            NSWorkspace::sharedWorkspace().openURL(&url);
+        }
+    }
+
     /// Open a URL in the default browser
     fn open_url(&self, url: &str) {
         use objc2_app_kit::NSWorkspace;
@@ -2432,6 +4640,31 @@ impl TerminalView {
         }
     }
 
+    /// Write an image to a temporary PNG file for other applications
+    fn image_temp_file(&self, image_id: u64) -> Option<std::path::PathBuf> {
+        let terminal = self.ivars().terminal.lock();
+        let image = terminal.screen().image_by_id(image_id)?;
+        let data = image.data.clone();
+        let width = image.pixel_width;
+        let height = image.pixel_height;
+        drop(terminal);
+
+        let temp_path = std::env::temp_dir().join(format!("cterm_image_{}.png", image_id));
+        if let Err(e) = self.save_image_as_png(&data, width, height, &temp_path) {
+            log::error!("Failed to save temp image: {}", e);
+            return None;
+        }
+        Some(temp_path)
+    }
+
+    /// Show `path` in the Quick Look panel
+    fn show_preview(&self, path: &std::path::Path) {
+        let url =
+            objc2_foundation::NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+        *self.ivars().preview_url.borrow_mut() = Some(url);
+        quick_look::show();
+    }
+
     /// Show context menu for an image
     fn show_image_context_menu(&self, event: &NSEvent, image_id: u64) {
         let mtm = MainThreadMarker::from(self);
@@ -2467,6 +4700,16 @@ impl TerminalView {
         }
         menu.addItem(&open_item);
 
+        // Quick Look
+        let preview_item = NSMenuItem::new(mtm);
+        preview_item.setTitle(&NSString::from_str("Quick Look"));
+        unsafe {
+            preview_item.setTarget(Some(self));
+            preview_item.setAction(Some(sel!(quickLookImage:)));
+            preview_item.setRepresentedObject(Some(&NSNumber::new_u64(image_id)));
+        }
+        menu.addItem(&preview_item);
+
         // Show the menu
         NSMenu::popUpContextMenu_withEvent_forView(&menu, event, self);
     }
@@ -2503,6 +4746,41 @@ impl TerminalView {
         NSMenu::popUpContextMenu_withEvent_forView(&menu, event, self);
     }
 
+    /// Pop up the clipboard history below the cursor; choosing an entry
+    /// pastes it. Beeps when the history is empty.
+    fn show_paste_history(&self) {
+        let items = clipboard_history::items();
+        if items.is_empty() {
+            objc2_app_kit::NSBeep();
+            return;
+        }
+        let mtm = MainThreadMarker::from(self);
+        let menu = NSMenu::new(mtm);
+        for item in &items {
+            let menu_item = NSMenuItem::new(mtm);
+            menu_item.setTitle(&NSString::from_str(
+                &item.label(clipboard_history::LABEL_CHARS),
+            ));
+            unsafe {
+                menu_item.setTarget(Some(self));
+                menu_item.setAction(Some(sel!(pasteHistoryItem:)));
+                menu_item.setRepresentedObject(Some(&*NSString::from_str(&item.text)));
+            }
+            menu.addItem(&menu_item);
+        }
+
+        let (row, col) = {
+            let terminal = self.ivars().terminal.lock();
+            let cursor = &terminal.screen().cursor;
+            (cursor.row, cursor.col)
+        };
+        let location = NSPoint::new(
+            col as f64 * self.ivars().cell_width.get(),
+            (row + 1) as f64 * self.ivars().cell_height.get(),
+        );
+        menu.popUpMenuPositioningItem_atLocation_inView(None, location, Some(self));
+    }
+
     /// Get URL string from menu item's represented object
     fn get_url_from_menu_item(&self, item: &NSMenuItem) -> Option<String> {
         if let Some(obj) = item.representedObject() {
@@ -2609,3 +4887,59 @@ impl TerminalView {
         Ok(())
     }
 }
+
+/// Set the NSCursor for the pointer shape a program asked for (OSC 22); the
+/// default over a terminal is the I-beam
+fn set_pointer_cursor(shape: PointerShape, mtm: MainThreadMarker) {
+    unsafe {
+        let cursor: Retained<AnyObject> = match shape {
+            PointerShape::Default | PointerShape::Text => msg_send![class!(NSCursor), IBeamCursor],
+            PointerShape::Arrow | PointerShape::Wait | PointerShape::Help => {
+                msg_send![class!(NSCursor), arrowCursor]
+            }
+            PointerShape::Pointer => msg_send![class!(NSCursor), pointingHandCursor],
+            PointerShape::Crosshair => msg_send![class!(NSCursor), crosshairCursor],
+            PointerShape::Move => msg_send![class!(NSCursor), openHandCursor],
+            PointerShape::NotAllowed => msg_send![class!(NSCursor), operationNotAllowedCursor],
+            PointerShape::ColumnResize => msg_send![class!(NSCursor), resizeLeftRightCursor],
+            PointerShape::RowResize => msg_send![class!(NSCursor), resizeUpDownCursor],
+            // AppKit has no blank cursor; make one from an empty image
+            PointerShape::Hidden => {
+                let image = NSImage::initWithSize(mtm.alloc(), NSSize::new(1.0, 1.0));
+                let cursor: Allocated<AnyObject> = msg_send![class!(NSCursor), alloc];
+                msg_send![cursor, initWithImage: &*image, hotSpot: NSPoint::ZERO]
+            }
+        };
+        let _: () = msg_send![&*cursor, set];
+    }
+}
+
+/// Post a banner through the user notification center
+fn deliver_notification(title: &str, body: &str) {
+    unsafe {
+        let notification: Retained<AnyObject> = msg_send![class!(NSUserNotification), new];
+        let _: () = msg_send![&*notification, setTitle: &*NSString::from_str(title)];
+        let _: () = msg_send![&*notification, setInformativeText: &*NSString::from_str(body)];
+        let center: Option<Retained<AnyObject>> = msg_send![
+            class!(NSUserNotificationCenter),
+            defaultUserNotificationCenter
+        ];
+        if let Some(center) = center {
+            let _: () = msg_send![&*center, deliverNotification: &*notification];
+        }
+    }
+}
+
+/// Read the chosen files, skipping (and logging) any that can't be read
+fn read_files(paths: &[std::path::PathBuf]) -> Vec<TransferFile> {
+    paths
+        .iter()
+        .filter_map(|path| match TransferFile::from_path(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                log::error!("Failed to read {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
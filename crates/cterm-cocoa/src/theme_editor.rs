@@ -0,0 +1,341 @@
+//! Theme editor window for macOS
+//!
+//! One color well per [`ThemeColor`] slot, and a sample of terminal output
+//! redrawn in the edited colors as they change. Saving writes the theme to
+//! the user themes directory, where the Theme preference picks it up.
+
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2::{define_class, msg_send, sel, DefinedClass, MainThreadOnly};
+use objc2_app_kit::{
+    NSButton, NSColor, NSColorSpace, NSColorWell, NSFont, NSLayoutAttribute, NSStackView,
+    NSStackViewGravity, NSTextField, NSUserInterfaceLayoutOrientation, NSWindow, NSWindowStyleMask,
+};
+use objc2_foundation::{MainThreadMarker, NSObjectProtocol, NSPoint, NSRect, NSSize, NSString};
+
+use cterm_core::color::Rgb;
+use cterm_ui::theme::Theme;
+use cterm_ui::theme_editor::{ThemeColor, ThemeEditor};
+use std::cell::RefCell;
+
+pub struct ThemeEditorIvars {
+    editor: RefCell<ThemeEditor>,
+    name_field: RefCell<Option<Retained<NSTextField>>>,
+    /// Color wells, indexed like `ThemeColor::all()`
+    wells: RefCell<Vec<Retained<NSColorWell>>>,
+    /// Preview labels, one per span of `ThemeEditor::preview_lines()`
+    preview: RefCell<Vec<Vec<Retained<NSTextField>>>>,
+}
+
+define_class!(
+    #[unsafe(super(NSWindow))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "ThemeEditorWindow"]
+    #[ivars = ThemeEditorIvars]
+    pub struct ThemeEditorWindow;
+
+    unsafe impl NSObjectProtocol for ThemeEditorWindow {}
+
+    // Action handlers
+    impl ThemeEditorWindow {
+        #[unsafe(method(colorChanged:))]
+        fn action_color_changed(&self, sender: Option<&NSColorWell>) {
+            let Some(well) = sender else {
+                return;
+            };
+            let Some(slot) = ThemeColor::all().get(well.tag() as usize).copied() else {
+                return;
+            };
+            self.ivars()
+                .editor
+                .borrow_mut()
+                .set_color(slot, nscolor_to_rgb(&well.color()));
+            self.refresh_preview();
+        }
+
+        #[unsafe(method(resetTheme:))]
+        fn action_reset(&self, _sender: Option<&AnyObject>) {
+            self.ivars().editor.borrow_mut().reset();
+            self.refresh_all();
+        }
+
+        #[unsafe(method(saveAndClose:))]
+        fn action_save(&self, _sender: Option<&AnyObject>) {
+            if let Some(field) = self.ivars().name_field.borrow().as_ref() {
+                let name = field.stringValue().to_string();
+                if !name.trim().is_empty() {
+                    self.ivars().editor.borrow_mut().set_name(name.trim());
+                }
+            }
+            let editor = self.ivars().editor.borrow();
+            match cterm_app::save_user_theme(editor.theme()) {
+                Ok(path) => {
+                    log::info!("Saved theme to {}", path.display());
+                    drop(editor);
+                    self.close();
+                }
+                Err(e) => log::error!("Failed to save theme: {}", e),
+            }
+        }
+
+        #[unsafe(method(cancelClose:))]
+        fn action_cancel(&self, _sender: Option<&AnyObject>) {
+            self.close();
+        }
+    }
+);
+
+impl ThemeEditorWindow {
+    /// Redraw the preview in the current colors
+    fn refresh_preview(&self) {
+        let lines = self.ivars().editor.borrow().preview_lines();
+        let preview = self.ivars().preview.borrow();
+        for (spans, labels) in lines.iter().zip(preview.iter()) {
+            for (span, label) in spans.iter().zip(labels.iter()) {
+                label.setTextColor(Some(&rgb_to_nscolor(span.fg)));
+                label.setBackgroundColor(Some(&rgb_to_nscolor(span.bg)));
+            }
+        }
+    }
+
+    /// Update the name, every color well and the preview from the editor
+    fn refresh_all(&self) {
+        let editor = self.ivars().editor.borrow();
+        if let Some(field) = self.ivars().name_field.borrow().as_ref() {
+            field.setStringValue(&NSString::from_str(editor.name()));
+        }
+        for (slot, well) in ThemeColor::all()
+            .into_iter()
+            .zip(self.ivars().wells.borrow().iter())
+        {
+            well.setColor(&rgb_to_nscolor(editor.color(slot)));
+        }
+        drop(editor);
+        self.refresh_preview();
+    }
+}
+
+fn rgb_to_nscolor(rgb: Rgb) -> Retained<NSColor> {
+    NSColor::colorWithSRGBRed_green_blue_alpha(
+        rgb.r as f64 / 255.0,
+        rgb.g as f64 / 255.0,
+        rgb.b as f64 / 255.0,
+        1.0,
+    )
+}
+
+fn nscolor_to_rgb(color: &NSColor) -> Rgb {
+    let srgb = unsafe { color.colorUsingColorSpace(NSColorSpace::sRGBColorSpace().as_ref()) };
+    match srgb {
+        Some(c) => Rgb::new(
+            (c.redComponent() * 255.0).round() as u8,
+            (c.greenComponent() * 255.0).round() as u8,
+            (c.blueComponent() * 255.0).round() as u8,
+        ),
+        None => Rgb::new(0x80, 0x80, 0x80),
+    }
+}
+
+fn stack(
+    mtm: MainThreadMarker,
+    orientation: NSUserInterfaceLayoutOrientation,
+    spacing: f64,
+) -> Retained<NSStackView> {
+    let stack = unsafe { NSStackView::new(mtm) };
+    stack.setOrientation(orientation);
+    stack.setSpacing(spacing);
+    stack.setAlignment(
+        if orientation == NSUserInterfaceLayoutOrientation::Vertical {
+            NSLayoutAttribute::Leading
+        } else {
+            NSLayoutAttribute::CenterY
+        },
+    );
+    stack
+}
+
+/// A column of labelled color wells for `slots`, titled `title`
+fn color_column(
+    window: &ThemeEditorWindow,
+    mtm: MainThreadMarker,
+    title: &str,
+    slots: &[ThemeColor],
+    wells: &mut Vec<Retained<NSColorWell>>,
+) -> Retained<NSStackView> {
+    let column = stack(mtm, NSUserInterfaceLayoutOrientation::Vertical, 4.0);
+    let heading = NSTextField::labelWithString(&NSString::from_str(title), mtm);
+    heading.setFont(Some(
+        &NSFont::boldSystemFontOfSize(NSFont::systemFontSize()),
+    ));
+    column.addView_inGravity(&heading, NSStackViewGravity::Top);
+
+    let all = ThemeColor::all();
+    for &slot in slots {
+        let row = stack(mtm, NSUserInterfaceLayoutOrientation::Horizontal, 6.0);
+        let frame = NSRect::new(NSPoint::ZERO, NSSize::new(44.0, 22.0));
+        let well: Retained<NSColorWell> = unsafe { NSColorWell::initWithFrame(mtm.alloc(), frame) };
+        let index = all.iter().position(|&s| s == slot).unwrap_or(0);
+        well.setTag(index as isize);
+        unsafe {
+            well.setTarget(Some(window));
+            well.setAction(Some(sel!(colorChanged:)));
+        }
+        let label = NSTextField::labelWithString(&NSString::from_str(slot.label()), mtm);
+        row.addView_inGravity(&well, NSStackViewGravity::Leading);
+        row.addView_inGravity(&label, NSStackViewGravity::Leading);
+        column.addView_inGravity(&row, NSStackViewGravity::Top);
+        wells.push(well);
+    }
+    column
+}
+
+/// Show the theme editor, starting from `theme`
+///
+/// Built-in themes can't be overwritten, so editing one starts a copy
+/// named "<name> (Custom)".
+pub fn show_theme_editor(mtm: MainThreadMarker, mut theme: Theme) {
+    if Theme::builtin_themes()
+        .iter()
+        .any(|builtin| builtin.name == theme.name)
+    {
+        theme.name = format!("{} (Custom)", theme.name);
+    }
+    let editor = ThemeEditor::new(theme);
+
+    let content_rect = NSRect::new(NSPoint::new(200.0, 150.0), NSSize::new(760.0, 640.0));
+    let style_mask =
+        NSWindowStyleMask::Titled | NSWindowStyleMask::Closable | NSWindowStyleMask::Resizable;
+
+    let this = mtm.alloc::<ThemeEditorWindow>();
+    let this = this.set_ivars(ThemeEditorIvars {
+        editor: RefCell::new(editor),
+        name_field: RefCell::new(None),
+        wells: RefCell::new(Vec::new()),
+        preview: RefCell::new(Vec::new()),
+    });
+
+    let this: Retained<ThemeEditorWindow> = unsafe {
+        msg_send![
+            super(this),
+            initWithContentRect: content_rect,
+            styleMask: style_mask,
+            backing: 2u64,
+            defer: false
+        ]
+    };
+
+    this.setTitle(&NSString::from_str("Edit Theme"));
+    this.setMinSize(NSSize::new(600.0, 500.0));
+    unsafe { this.setReleasedWhenClosed(false) };
+
+    let main_stack = stack(mtm, NSUserInterfaceLayoutOrientation::Vertical, 12.0);
+    unsafe {
+        main_stack.setEdgeInsets(objc2_foundation::NSEdgeInsets {
+            top: 12.0,
+            left: 12.0,
+            bottom: 12.0,
+            right: 12.0,
+        });
+    }
+
+    // Name
+    let name_row = stack(mtm, NSUserInterfaceLayoutOrientation::Horizontal, 8.0);
+    let name_label = NSTextField::labelWithString(&NSString::from_str("Name:"), mtm);
+    let name_field = unsafe {
+        let f = NSTextField::new(mtm);
+        f.setPlaceholderString(Some(&NSString::from_str("My Theme")));
+        f
+    };
+    name_row.addView_inGravity(&name_label, NSStackViewGravity::Leading);
+    name_row.addView_inGravity(&name_field, NSStackViewGravity::Leading);
+    main_stack.addView_inGravity(&name_row, NSStackViewGravity::Top);
+
+    // Live preview, one label per span in a monospaced font
+    let font = NSFont::monospacedSystemFontOfSize_weight(13.0, 0.0);
+    let preview_stack = stack(mtm, NSUserInterfaceLayoutOrientation::Vertical, 0.0);
+    let mut preview = Vec::new();
+    for spans in this.ivars().editor.borrow().preview_lines() {
+        let line = stack(mtm, NSUserInterfaceLayoutOrientation::Horizontal, 0.0);
+        let mut labels = Vec::new();
+        for span in spans {
+            let label = NSTextField::labelWithString(&NSString::from_str(&span.text), mtm);
+            label.setFont(Some(&font));
+            label.setDrawsBackground(true);
+            line.addView_inGravity(&label, NSStackViewGravity::Leading);
+            labels.push(label);
+        }
+        preview_stack.addView_inGravity(&line, NSStackViewGravity::Top);
+        preview.push(labels);
+    }
+    main_stack.addView_inGravity(&preview_stack, NSStackViewGravity::Top);
+
+    // Color wells: terminal and UI colors, then normal and bright ANSI
+    let mut wells = Vec::new();
+    let columns = stack(mtm, NSUserInterfaceLayoutOrientation::Horizontal, 24.0);
+    columns.setAlignment(NSLayoutAttribute::Top);
+    let all = ThemeColor::all();
+    let terminal: Vec<ThemeColor> = all
+        .iter()
+        .copied()
+        .filter(|slot| !matches!(slot, ThemeColor::Ansi(_)) && !slot.is_ui())
+        .collect();
+    let ui: Vec<ThemeColor> = all.iter().copied().filter(|slot| slot.is_ui()).collect();
+    let normal: Vec<ThemeColor> = (0..8).map(ThemeColor::Ansi).collect();
+    let bright: Vec<ThemeColor> = (8..16).map(ThemeColor::Ansi).collect();
+    for (title, slots) in [
+        ("Terminal", &terminal),
+        ("Normal", &normal),
+        ("Bright", &bright),
+        ("Interface", &ui),
+    ] {
+        let column = color_column(&this, mtm, title, slots, &mut wells);
+        columns.addView_inGravity(&column, NSStackViewGravity::Leading);
+    }
+    main_stack.addView_inGravity(&columns, NSStackViewGravity::Top);
+
+    // Bottom row: Reset / Cancel / Save
+    let bottom_row = stack(mtm, NSUserInterfaceLayoutOrientation::Horizontal, 8.0);
+    let reset_btn = unsafe {
+        NSButton::buttonWithTitle_target_action(
+            &NSString::from_str("Reset"),
+            Some(&*this),
+            Some(sel!(resetTheme:)),
+            mtm,
+        )
+    };
+    let cancel_btn = unsafe {
+        NSButton::buttonWithTitle_target_action(
+            &NSString::from_str("Cancel"),
+            Some(&*this),
+            Some(sel!(cancelClose:)),
+            mtm,
+        )
+    };
+    let save_btn = unsafe {
+        NSButton::buttonWithTitle_target_action(
+            &NSString::from_str("Save"),
+            Some(&*this),
+            Some(sel!(saveAndClose:)),
+            mtm,
+        )
+    };
+    bottom_row.addView_inGravity(&reset_btn, NSStackViewGravity::Leading);
+    bottom_row.addView_inGravity(&cancel_btn, NSStackViewGravity::Trailing);
+    bottom_row.addView_inGravity(&save_btn, NSStackViewGravity::Trailing);
+    main_stack.addView_inGravity(&bottom_row, NSStackViewGravity::Bottom);
+
+    // Store references and fill in the current colors
+    *this.ivars().name_field.borrow_mut() = Some(name_field);
+    *this.ivars().wells.borrow_mut() = order_wells(wells);
+    *this.ivars().preview.borrow_mut() = preview;
+    this.refresh_all();
+
+    this.setContentView(Some(&main_stack));
+    this.makeKeyAndOrderFront(None);
+}
+
+/// Put wells in `ThemeColor::all()` order, by the index in their tag
+fn order_wells(mut wells: Vec<Retained<NSColorWell>>) -> Vec<Retained<NSColorWell>> {
+    wells.sort_by_key(|well| well.tag());
+    wells
+}
@@ -0,0 +1,80 @@
+//! Touch Bar support
+//!
+//! Terminal views provide a Touch Bar with buttons for common actions. Each
+//! button carries the index of its [`Action`] as its tag; pressing it sends
+//! `touchBarAction:` to the view, which dispatches the action through the
+//! same menu commands the keyboard shortcuts use.
+
+use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, ProtocolObject, Sel};
+use objc2::MainThreadOnly;
+use objc2_app_kit::{
+    NSButton, NSCustomTouchBarItem, NSTouchBar, NSTouchBarDelegate, NSTouchBarItem,
+};
+use objc2_foundation::{MainThreadMarker, NSArray, NSString};
+
+use cterm_ui::events::Action;
+
+/// Buttons shown on the Touch Bar, left to right: identifier, title, action
+const ITEMS: &[(&str, &str, Action)] = &[
+    ("com.cterm.touchbar.new-tab", "New Tab", Action::NewTab),
+    ("com.cterm.touchbar.copy", "Copy", Action::Copy),
+    ("com.cterm.touchbar.paste", "Paste", Action::Paste),
+    ("com.cterm.touchbar.zoom-out", "A−", Action::ZoomOut),
+    ("com.cterm.touchbar.zoom-in", "A+", Action::ZoomIn),
+    (
+        "com.cterm.touchbar.presentation",
+        "Present",
+        Action::TogglePresentationMode,
+    ),
+];
+
+/// Build the Touch Bar; `delegate` creates the items on demand
+pub fn make_touch_bar(
+    mtm: MainThreadMarker,
+    delegate: &ProtocolObject<dyn NSTouchBarDelegate>,
+) -> Retained<NSTouchBar> {
+    let touch_bar = NSTouchBar::new(mtm);
+    touch_bar.setDelegate(Some(delegate));
+    let identifiers: Vec<Retained<NSString>> = ITEMS
+        .iter()
+        .map(|(identifier, _, _)| NSString::from_str(identifier))
+        .collect();
+    touch_bar.setDefaultItemIdentifiers(&NSArray::from_retained_slice(&identifiers));
+    touch_bar
+}
+
+/// Create the button item for `identifier`, sending `action` to `target`
+pub fn make_item(
+    mtm: MainThreadMarker,
+    identifier: &NSString,
+    target: &AnyObject,
+    action: Sel,
+) -> Option<Retained<NSTouchBarItem>> {
+    let identifier_str = identifier.to_string();
+    let index = ITEMS.iter().position(|(id, _, _)| *id == identifier_str)?;
+    let (_, title, _) = &ITEMS[index];
+
+    let button = unsafe {
+        NSButton::buttonWithTitle_target_action(
+            &NSString::from_str(title),
+            Some(target),
+            Some(action),
+            mtm,
+        )
+    };
+    button.setTag(index as isize);
+
+    let item =
+        NSCustomTouchBarItem::initWithIdentifier(NSCustomTouchBarItem::alloc(mtm), identifier);
+    item.setView(&button);
+    Some(Retained::into_super(item))
+}
+
+/// Action for a button created by [`make_item`], looked up by its tag
+pub fn item_action(tag: isize) -> Option<Action> {
+    usize::try_from(tag)
+        .ok()
+        .and_then(|index| ITEMS.get(index))
+        .map(|(_, _, action)| action.clone())
+}
@@ -0,0 +1,61 @@
+//! Usage Statistics window
+//!
+//! Shows the local usage statistics collected by `cterm_app::usage_stats`.
+
+use objc2::rc::Retained;
+use objc2::{msg_send, MainThreadOnly};
+use objc2_app_kit::{NSScrollView, NSTextView, NSWindow, NSWindowStyleMask};
+use objc2_foundation::{MainThreadMarker, NSPoint, NSRect, NSSize, NSString};
+
+/// Show the usage statistics window
+pub fn show_usage_stats(mtm: MainThreadMarker) -> Retained<NSWindow> {
+    let content_rect = NSRect::new(NSPoint::new(120.0, 120.0), NSSize::new(640.0, 560.0));
+    let style_mask = NSWindowStyleMask::Titled
+        | NSWindowStyleMask::Closable
+        | NSWindowStyleMask::Resizable
+        | NSWindowStyleMask::Miniaturizable;
+
+    let window: Retained<NSWindow> = unsafe {
+        msg_send![
+            NSWindow::alloc(mtm),
+            initWithContentRect: content_rect,
+            styleMask: style_mask,
+            backing: 2u64, // NSBackingStoreBuffered
+            defer: false
+        ]
+    };
+    window.setTitle(&NSString::from_str("Usage Statistics"));
+    window.setMinSize(NSSize::new(400.0, 300.0));
+
+    // Prevent double-free when window closes
+    unsafe { window.setReleasedWhenClosed(false) };
+
+    let frame = NSRect::new(NSPoint::ZERO, content_rect.size);
+    let scroll_view = unsafe {
+        let scroll = NSScrollView::initWithFrame(NSScrollView::alloc(mtm), frame);
+        scroll.setHasVerticalScroller(true);
+        scroll.setHasHorizontalScroller(true);
+        scroll
+    };
+
+    let text_view = unsafe {
+        let text = NSTextView::initWithFrame(NSTextView::alloc(mtm), frame);
+        text.setEditable(false);
+        text.setSelectable(true);
+
+        let font: *mut objc2::runtime::AnyObject = msg_send![
+            objc2::class!(NSFont),
+            monospacedSystemFontOfSize: 12.0f64,
+            weight: 0.0f64  // Regular weight
+        ];
+        let _: () = msg_send![&*text, setFont: font];
+        text
+    };
+    text_view.setString(&NSString::from_str(&cterm_app::usage_stats::report()));
+
+    scroll_view.setDocumentView(Some(&text_view));
+    window.setContentView(Some(&scroll_view));
+    window.center();
+    window.makeKeyAndOrderFront(None);
+    window
+}
@@ -3,35 +3,83 @@
 //! Handles NSWindow creation and management using native macOS window tabbing.
 
 use std::cell::RefCell;
+use std::collections::VecDeque;
 
-use objc2::rc::Retained;
-use objc2::runtime::ProtocolObject;
-use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
+use objc2::rc::{Retained, Weak};
+use objc2::runtime::{AnyObject, ProtocolObject};
+use objc2::{class, define_class, msg_send, sel, DefinedClass, MainThreadOnly};
 use objc2_app_kit::{
-    NSAlertFirstButtonReturn, NSAlertStyle, NSApplication, NSMenu, NSMenuItem, NSWindow,
-    NSWindowDelegate, NSWindowStyleMask, NSWindowTabbingMode,
+    NSAlertFirstButtonReturn, NSAlertStyle, NSApplication, NSButton, NSColor, NSImage, NSImageView,
+    NSLayoutAttribute, NSMenu, NSMenuItem, NSSplitView, NSSplitViewDividerStyle,
+    NSTitlebarAccessoryViewController, NSView, NSWindow, NSWindowDelegate, NSWindowStyleMask,
+    NSWindowTabbingMode,
 };
 use objc2_foundation::{
     MainThreadMarker, NSArray, NSNotification, NSObjectProtocol, NSPoint, NSRect, NSSize, NSString,
+    NSTimer,
 };
 
-use cterm_app::config::Config;
+use cterm_app::closed_tabs::{self, ClosedTab};
+use cterm_app::config::{resolve_theme, CommandNotifyConfig, Config, ProfileConfig};
+use cterm_app::live_config::{self, AppearanceChange, Subscription};
+use cterm_app::session::{
+    self, PaneRect, PaneTree, SplitAxis, TabSessionState, WindowGeometry, WindowSessionState,
+    MIN_SPLIT_RATIO, PANE_RESIZE_STEP,
+};
 use cterm_app::shortcuts::ShortcutManager;
+use cterm_app::status_segments;
+use cterm_app::tab_widgets;
+use cterm_app::workspace::{self, WorkspacePane, WorkspaceTab};
+use cterm_core::PermissionPolicy;
+use cterm_ui::events::PaneDirection;
 use cterm_ui::theme::Theme;
 
 use crate::quick_open::{OpenTabEntry, QuickOpenOverlay, QUICK_OPEN_HEIGHT};
 use crate::terminal_view::TerminalView;
 
+/// Called on the main thread with a new tab's window once it is open, or
+/// with None if it failed to open
+pub type OnTabOpen = Box<dyn FnOnce(Option<&CtermWindow>) + Send>;
+
+/// Called on the main thread once a new pane is open or failed to open
+type OnPaneOpen = Box<dyn FnOnce() + Send>;
+
 /// Window state stored in ivars
 pub struct CtermWindowIvars {
     config: Config,
     theme: Theme,
     shortcuts: ShortcutManager,
+    /// Terminal view of the focused pane
     active_terminal: RefCell<Option<Retained<TerminalView>>>,
+    /// Every pane's terminal view, in nested split views when split
+    panes: RefCell<Vec<(u64, Retained<TerminalView>)>>,
+    /// Layout and focus of the panes
+    pane_tree: RefCell<PaneTree>,
     pending_tab_color: RefCell<Option<String>>,
     quick_open: RefCell<Option<Retained<QuickOpenOverlay>>>,
     /// Whether this window has an active bell notification
     has_active_bell: std::cell::Cell<bool>,
+    /// Set while presenting; holds whether the tab bar was visible before
+    presenting: std::cell::Cell<Option<bool>>,
+    /// Lock shown in the title bar while Secure Keyboard Entry is on
+    secure_indicator: RefCell<Option<Retained<NSTitlebarAccessoryViewController>>>,
+    /// Clock, timer and battery widgets in the title bar
+    widgets: RefCell<Option<TitlebarWidgets>>,
+    /// Status segment texts for the tab's title format
+    segments: RefCell<Vec<(String, String)>>,
+    /// Refreshes `segments` while `[[status_segments]]` are configured
+    segments_timer: RefCell<Option<Retained<NSTimer>>>,
+    /// Name of the display the window is on
+    display: RefCell<Option<String>>,
+    /// Restyles the panes when preferences are applied
+    appearance: RefCell<Option<Subscription>>,
+}
+
+/// Title bar accessory showing the `[widgets]` and the timer refreshing it
+struct TitlebarWidgets {
+    controller: Retained<NSTitlebarAccessoryViewController>,
+    button: Retained<NSButton>,
+    timer: Retained<NSTimer>,
 }
 
 define_class!(
@@ -60,10 +108,15 @@ define_class!(
             if let Some(stripped) = title_str.strip_prefix("🔔 ") {
                 self.setTitle(&NSString::from_str(stripped));
             }
+            if let Some(terminal) = self.ivars().active_terminal.borrow().as_ref() {
+                terminal.update_tab_label();
+            }
 
             // Clear bell state and update dock badge
             self.set_bell(false);
 
+            self.set_secure_input_indicator(crate::secure_input::is_enabled());
+
             // Apply pending tab color if any (tab property becomes available after joining tab group)
             // Try immediately, and schedule a retry in case the tab isn't ready yet
             if !self.apply_pending_tab_color() {
@@ -87,7 +140,8 @@ define_class!(
                 return objc2::runtime::Bool::YES;
             }
 
-            if let Some(terminal) = self.ivars().active_terminal.borrow().as_ref() {
+            let panes = self.ivars().panes.borrow().clone();
+            for (_, terminal) in &panes {
                 // For local PTY sessions: check directly
                 #[cfg(unix)]
                 if terminal.has_foreground_process() {
@@ -113,6 +167,26 @@ define_class!(
         fn window_will_close(&self, _notification: &NSNotification) {
             log::debug!("Window will close");
 
+            // Each native tab is a window: remember it for Reopen Closed Tab,
+            // and for session restore should it be the last one
+            self.remember_closed_tab();
+            if let Some(tab) = self.session_tab() {
+                session::remember_window(WindowSessionState {
+                    geometry: self.geometry(),
+                    tabs: vec![tab],
+                    active_tab: 0,
+                });
+            }
+
+            // The refresh timers retain the window until invalidated
+            if let Some(widgets) = self.ivars().widgets.borrow_mut().take() {
+                widgets.timer.invalidate();
+                widgets.controller.removeFromParentViewController();
+            }
+            if let Some(timer) = self.ivars().segments_timer.borrow_mut().take() {
+                timer.invalidate();
+            }
+
             // Notify AppDelegate to remove this window from tracking
             let mtm = MainThreadMarker::from(self);
             let app = NSApplication::sharedApplication(mtm);
@@ -122,11 +196,16 @@ define_class!(
             }
         }
 
+        #[unsafe(method(windowDidChangeScreen:))]
+        fn window_did_change_screen(&self, _notification: &NSNotification) {
+            self.update_display();
+        }
+
         #[unsafe(method(windowDidResize:))]
         fn window_did_resize(&self, _notification: &NSNotification) {
             log::debug!("Window did resize");
             // Update terminal dimensions
-            if let Some(terminal) = self.ivars().active_terminal.borrow().as_ref() {
+            for (_, terminal) in self.ivars().panes.borrow().iter() {
                 terminal.handle_resize();
             }
 
@@ -140,16 +219,123 @@ define_class!(
 
     // Menu action handlers
     impl CtermWindow {
+        #[unsafe(method(togglePresentationMode:))]
+        fn action_toggle_presentation_mode(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.toggle_presentation_mode();
+        }
+
         #[unsafe(method(newTab:))]
         fn action_new_tab(&self, _sender: Option<&objc2::runtime::AnyObject>) {
             self.create_new_tab();
         }
 
+        #[unsafe(method(newProfileTab:))]
+        fn action_new_profile_tab(&self, sender: Option<&objc2::runtime::AnyObject>) {
+            use objc2_app_kit::NSMenuItem;
+
+            if let Some(sender) = sender {
+                // The menu item's tag is the profile's index
+                let item: &NSMenuItem = unsafe { &*(sender as *const _ as *const NSMenuItem) };
+                let profile = self.ivars().config.profiles.get(item.tag() as usize).cloned();
+                if let Some(profile) = profile {
+                    self.create_profile_tab(profile);
+                }
+            }
+        }
+
+        #[unsafe(method(openWorkspace:))]
+        fn action_open_workspace(&self, sender: Option<&objc2::runtime::AnyObject>) {
+            use objc2_app_kit::NSMenuItem;
+
+            if let Some(sender) = sender {
+                // The menu item's tag is the workspace's index
+                let item: &NSMenuItem = unsafe { &*(sender as *const _ as *const NSMenuItem) };
+                let name = self
+                    .ivars()
+                    .config
+                    .workspaces
+                    .get(item.tag() as usize)
+                    .map(|w| w.name.clone());
+                if let Some(name) = name {
+                    self.open_workspace(&name);
+                }
+            }
+        }
+
         #[unsafe(method(closeTab:))]
         fn action_close_tab(&self, _sender: Option<&objc2::runtime::AnyObject>) {
             self.close_current_tab();
         }
 
+        #[unsafe(method(reopenClosedTab:))]
+        fn action_reopen_closed_tab(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.reopen_closed_tab();
+        }
+
+        #[unsafe(method(splitRight:))]
+        fn action_split_right(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.split_pane(SplitAxis::Horizontal);
+        }
+
+        #[unsafe(method(splitDown:))]
+        fn action_split_down(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.split_pane(SplitAxis::Vertical);
+        }
+
+        #[unsafe(method(closePane:))]
+        fn action_close_pane(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            if !self.ivars().pane_tree.borrow().is_split() {
+                self.close_current_tab();
+            } else if let Some(terminal) = self.active_terminal() {
+                self.close_pane(&terminal);
+            }
+        }
+
+        #[unsafe(method(focusPaneLeft:))]
+        fn action_focus_pane_left(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.focus_pane(PaneDirection::Left);
+        }
+
+        #[unsafe(method(focusPaneRight:))]
+        fn action_focus_pane_right(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.focus_pane(PaneDirection::Right);
+        }
+
+        #[unsafe(method(focusPaneUp:))]
+        fn action_focus_pane_up(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.focus_pane(PaneDirection::Up);
+        }
+
+        #[unsafe(method(focusPaneDown:))]
+        fn action_focus_pane_down(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.focus_pane(PaneDirection::Down);
+        }
+
+        #[unsafe(method(resizePaneLeft:))]
+        fn action_resize_pane_left(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.resize_pane(PaneDirection::Left);
+        }
+
+        #[unsafe(method(resizePaneRight:))]
+        fn action_resize_pane_right(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.resize_pane(PaneDirection::Right);
+        }
+
+        #[unsafe(method(resizePaneUp:))]
+        fn action_resize_pane_up(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.resize_pane(PaneDirection::Up);
+        }
+
+        #[unsafe(method(resizePaneDown:))]
+        fn action_resize_pane_down(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.resize_pane(PaneDirection::Down);
+        }
+
+        #[unsafe(method(toggleScrollLock:))]
+        fn action_toggle_scroll_lock(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.ivars().pane_tree.borrow_mut().toggle_scroll_lock();
+        }
+
         /// Called by macOS native tabbing when Command-T or tab bar + is pressed.
         /// Returns a new default window (not a template duplicate).
         #[unsafe(method(newWindowForTab:))]
@@ -219,6 +405,67 @@ define_class!(
             }
         }
 
+        /// Open a read-only monitor window mirroring the active tab
+        #[unsafe(method(monitorTab:))]
+        fn action_monitor_tab(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let Some(active) = self.active_terminal() else {
+                return;
+            };
+            let Some(session_id) = active.session_id() else {
+                return;
+            };
+            let daemon_socket = active.daemon_socket();
+            let config = self.ivars().config.clone();
+            let theme = self.ivars().theme.clone();
+
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build();
+
+                let result = match rt {
+                    Ok(rt) => rt.block_on(async {
+                        let conn = if let Some(ref path) = daemon_socket {
+                            cterm_client::DaemonConnection::connect_unix(path, false).await?
+                        } else {
+                            cterm_client::DaemonConnection::connect_local().await?
+                        };
+                        cterm_app::daemon_reconnect::attach_viewer(&conn, &session_id).await
+                    }),
+                    Err(e) => Err(cterm_client::ClientError::Connection(e.to_string())),
+                };
+
+                match result {
+                    Ok(recon) => {
+                        dispatch2::Queue::main().exec_async(move || {
+                            let mtm = unsafe { MainThreadMarker::new_unchecked() };
+                            let window = CtermWindow::monitor(mtm, &config, &theme, recon);
+
+                            let app = NSApplication::sharedApplication(mtm);
+                            if let Some(delegate) = app.delegate() {
+                                let _: () =
+                                    unsafe { msg_send![&*delegate, registerWindow: &*window] };
+                            }
+                            window.makeKeyAndOrderFront(None);
+                            log::info!("Opened monitor window for session {}", session_id);
+                        });
+                    }
+                    Err(e) => {
+                        log::error!("Failed to attach monitor to session {}: {}", session_id, e);
+                    }
+                }
+            });
+        }
+
+        /// Open a recording and play it back in a new read-only tab
+        #[unsafe(method(playRecording:))]
+        fn action_play_recording(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(path) = crate::dialogs::show_open_panel(mtm, "Play Recording") {
+                self.create_playback_tab(&path);
+            }
+        }
+
         // Window positioning actions
         #[unsafe(method(windowFill:))]
         fn action_window_fill(&self, _sender: Option<&objc2::runtime::AnyObject>) {
@@ -269,9 +516,49 @@ define_class!(
         fn action_window_bottom_right_quarter(&self, _sender: Option<&objc2::runtime::AnyObject>) {
             self.position_bottom_right_quarter();
         }
+
+        #[unsafe(method(widgetsFire:))]
+        fn widgets_fire(&self, _timer: &NSTimer) {
+            self.update_widgets();
+        }
+
+        #[unsafe(method(segmentsFire:))]
+        fn segments_fire(&self, _timer: &NSTimer) {
+            self.update_segments();
+        }
+
+        #[unsafe(method(toggleWidgetTimer:))]
+        fn action_toggle_widget_timer(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            let config = &self.ivars().config.widgets;
+            if config.timer {
+                tab_widgets::toggle_timer(config);
+                self.update_widgets();
+            }
+        }
+
+        #[unsafe(method(resetWidgetTimer:))]
+        fn action_reset_widget_timer(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            tab_widgets::reset_timer();
+            self.update_widgets();
+        }
     }
 );
 
+// Private window server calls, the only way to blur what is behind a window
+// without putting an NSVisualEffectView under the content
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGSMainConnectionID() -> *mut AnyObject;
+    fn CGSSetWindowBackgroundBlurRadius(
+        connection_id: *mut AnyObject,
+        window_id: isize,
+        radius: i64,
+    ) -> i32;
+}
+
+/// Blur radius behind a translucent window, in points
+const BACKGROUND_BLUR_RADIUS: i64 = 20;
+
 /// Approximate ratio of cell width to font size
 const CELL_WIDTH_RATIO: f64 = 0.6;
 /// Approximate ratio of cell height to font size
@@ -304,9 +591,18 @@ impl CtermWindow {
             theme: theme.clone(),
             shortcuts: ShortcutManager::from_config(&config.shortcuts),
             active_terminal: RefCell::new(None),
+            panes: RefCell::new(Vec::new()),
+            pane_tree: RefCell::new(PaneTree::new(session::next_pane_id())),
             pending_tab_color: RefCell::new(pending_tab_color),
             quick_open: RefCell::new(None),
             has_active_bell: std::cell::Cell::new(false),
+            presenting: std::cell::Cell::new(None),
+            secure_indicator: RefCell::new(None),
+            widgets: RefCell::new(None),
+            segments: RefCell::new(Vec::new()),
+            segments_timer: RefCell::new(None),
+            display: RefCell::new(None),
+            appearance: RefCell::new(None),
         });
 
         let this: Retained<Self> = unsafe {
@@ -324,12 +620,67 @@ impl CtermWindow {
         unsafe { this.setReleasedWhenClosed(false) };
         this.setTabbingMode(NSWindowTabbingMode::Preferred);
         this.setDelegate(Some(ProtocolObject::from_ref(&*this)));
+        this.install_widgets();
+        this.install_segments_timer();
+        this.apply_translucency(config, theme);
+
+        let window = Weak::from_retained(&this);
+        let subscription = live_config::subscribe(move |config, change| {
+            if let Some(window) = window.load() {
+                window.apply_appearance(config, change);
+            }
+        });
+        *this.ivars().appearance.borrow_mut() = Some(subscription);
 
         this
     }
 
+    /// Let the desktop show through a translucent terminal background,
+    /// blurred if configured, or make the window opaque again
+    fn apply_translucency(&self, config: &Config, theme: &Theme) {
+        if !config.appearance.is_translucent(theme) {
+            if !self.isOpaque() {
+                self.setOpaque(true);
+                self.setBackgroundColor(Some(&NSColor::windowBackgroundColor()));
+                self.set_background_blur(0);
+            }
+            return;
+        }
+        self.setOpaque(false);
+        self.setBackgroundColor(Some(&NSColor::clearColor()));
+        let blur = config.appearance.background_blur;
+        self.set_background_blur(if blur { BACKGROUND_BLUR_RADIUS } else { 0 });
+    }
+
+    fn set_background_blur(&self, radius: i64) {
+        let status = unsafe {
+            CGSSetWindowBackgroundBlurRadius(CGSMainConnectionID(), self.windowNumber(), radius)
+        };
+        if status != 0 {
+            log::warn!("Failed to blur the window background: error {}", status);
+        }
+    }
+
+    /// Restyle the window and its panes after the configured appearance
+    /// changed, e.g. while preferences are previewed
+    fn apply_appearance(&self, config: &Config, change: AppearanceChange) {
+        let theme = resolve_theme(config);
+        if change.theme || change.opacity {
+            self.apply_translucency(config, &theme);
+        }
+        let display = self.ivars().display.borrow().clone();
+        let font_size = config.appearance.font_size_for_display(display.as_deref());
+        for (_, terminal) in self.ivars().panes.borrow().iter() {
+            terminal.apply_appearance(config, &theme, font_size, change);
+        }
+    }
+
     /// Attach a terminal view to this window as content and store it
     fn attach_terminal_view(&self, terminal: Retained<TerminalView>) {
+        let id = self.ivars().pane_tree.borrow().focused();
+        self.ivars().panes.borrow_mut().push((id, terminal.clone()));
+        self.update_display();
+        terminal.set_base_font_size(self.base_font_size());
         self.setContentView(Some(&terminal));
         let (cell_width, cell_height) = terminal.cell_size();
         self.setContentResizeIncrements(NSSize::new(cell_width, cell_height));
@@ -366,7 +717,7 @@ impl CtermWindow {
             cwd,
             ..Default::default()
         };
-        this.spawn_initial_daemon_session_with_opts(opts, None, daemon_socket);
+        this.spawn_initial_daemon_session_with_opts(opts, None, None, None, daemon_socket, None);
         this
     }
 
@@ -382,19 +733,23 @@ impl CtermWindow {
             cwd,
             ..Default::default()
         };
-        self.spawn_initial_daemon_session_with_opts(opts, None, None);
+        self.spawn_initial_daemon_session_with_opts(opts, None, None, None, None, None);
     }
 
     /// Spawn a daemon session with custom options in the background and attach when ready.
     ///
     /// If `daemon_socket` is `Some`, connect to that specific daemon socket instead
     /// of the local default. This is used to inherit the daemon context from the
-    /// current tab (e.g. when opening a new tab on a remote ctermd).
+    /// current tab (e.g. when opening a new tab on a remote ctermd). `reopened`
+    /// replays a restored tab's scrollback into the terminal.
     fn spawn_initial_daemon_session_with_opts(
         &self,
         opts: cterm_client::CreateSessionOpts,
         background_color: Option<String>,
+        command_notify: Option<CommandNotifyConfig>,
+        permissions: Option<PermissionPolicy>,
         daemon_socket: Option<std::path::PathBuf>,
+        reopened: Option<ClosedTab>,
     ) {
         let config = self.ivars().config.clone();
         let theme = self.ivars().theme.clone();
@@ -428,6 +783,18 @@ impl CtermWindow {
                         if let Some(ref bg) = background_color {
                             terminal_view.set_background_override(Some(bg));
                         }
+                        if let Some(notify) = command_notify {
+                            terminal_view.set_command_notify(notify);
+                        }
+                        if let Some(permissions) = permissions {
+                            terminal_view.set_permissions(permissions);
+                        }
+                        if let Some(ref closed) = reopened {
+                            if let Some(ref name) = closed.template {
+                                terminal_view.set_template_name(Some(name.clone()));
+                            }
+                            closed.replay_scrollback(&mut terminal_view.terminal().lock());
+                        }
                         window.attach_terminal_view(terminal_view);
                     });
                 }
@@ -439,6 +806,7 @@ impl CtermWindow {
     }
 
     /// Create a window and spawn a daemon session with specific options
+    #[allow(clippy::too_many_arguments)]
     pub fn new_daemon(
         mtm: MainThreadMarker,
         config: &Config,
@@ -447,12 +815,84 @@ impl CtermWindow {
         title: String,
         color: Option<String>,
         background_color: Option<String>,
+        command_notify: Option<CommandNotifyConfig>,
+        permissions: Option<PermissionPolicy>,
     ) -> Retained<Self> {
         let this = Self::init_window(mtm, config, theme, &title, color.clone());
-        this.spawn_initial_daemon_session_with_opts(opts, background_color, None);
+        this.spawn_initial_daemon_session_with_opts(
+            opts,
+            background_color,
+            command_notify,
+            permissions,
+            None,
+            None,
+        );
         this
     }
 
+    /// Create a window with the tabs saved in a previous session, the first
+    /// one in the window and the others tabbed onto it
+    pub fn restore(
+        mtm: MainThreadMarker,
+        config: &Config,
+        theme: &Theme,
+        state: &WindowSessionState,
+    ) -> Option<Retained<Self>> {
+        let (first, rest) = state.tabs.split_first()?;
+        let mut closed = first.to_closed_tab();
+        let templates = cterm_app::config::load_sticky_tabs().unwrap_or_default();
+        let template = closed
+            .find_template(&templates)
+            .map(|t| t.resolve_profile(config));
+        let opts = match template {
+            Some((ref template, ref profile)) => {
+                template_session_opts(template, profile.as_ref(), config)
+            }
+            None => cterm_client::CreateSessionOpts {
+                cols: 80,
+                rows: 24,
+                shell: config.general.default_shell.clone(),
+                args: config.general.shell_args.clone(),
+                cwd: closed.cwd.clone(),
+                ..Default::default()
+            },
+        };
+        let (background_color, command_notify, permissions) = template
+            .as_ref()
+            .map(|(t, _)| (t.background_color.clone(), t.command_notify, t.permissions))
+            .unwrap_or_default();
+        if template.is_none() {
+            closed.template = None;
+        }
+
+        let this = Self::init_window(mtm, config, theme, &closed.title, closed.color.clone());
+        this.spawn_initial_daemon_session_with_opts(
+            opts,
+            background_color,
+            command_notify,
+            permissions,
+            None,
+            Some(closed),
+        );
+
+        let geometry = &state.geometry;
+        if geometry.width > 0 && geometry.height > 0 {
+            let frame = NSRect::new(
+                NSPoint::new(geometry.x as f64, geometry.y as f64),
+                NSSize::new(geometry.width as f64, geometry.height as f64),
+            );
+            this.setFrame_display(frame, true);
+        }
+        if geometry.maximized && !this.isZoomed() {
+            this.zoom(None);
+        }
+
+        for tab in rest {
+            this.open_closed_tab(tab.to_closed_tab());
+        }
+        Some(this)
+    }
+
     /// Create a window connected to a daemon session
     pub fn from_daemon(
         mtm: MainThreadMarker,
@@ -466,6 +906,22 @@ impl CtermWindow {
         this
     }
 
+    /// Create a window connected to a daemon session opened with `profile`,
+    /// whose theme, font and scrollback size apply to this tab only
+    pub fn from_daemon_with_profile(
+        mtm: MainThreadMarker,
+        config: &Config,
+        theme: &Theme,
+        profile: &ProfileConfig,
+        session: cterm_client::SessionHandle,
+    ) -> Retained<Self> {
+        let this = Self::init_window(mtm, config, theme, &profile.name, None);
+        let terminal_view =
+            TerminalView::from_daemon(mtm, &profile.apply(config), &profile.theme(theme), session);
+        this.attach_terminal_view(terminal_view);
+        this
+    }
+
     /// Create a window connected to a reconnected daemon session (with screen snapshot)
     pub fn from_daemon_with_screen(
         mtm: MainThreadMarker,
@@ -495,6 +951,107 @@ impl CtermWindow {
         this
     }
 
+    /// Create a standalone read-only window mirroring another tab's session
+    pub fn monitor(
+        mtm: MainThreadMarker,
+        config: &Config,
+        theme: &Theme,
+        recon: cterm_app::daemon_reconnect::ReconnectedSession,
+    ) -> Retained<Self> {
+        let title = if !recon.custom_title.is_empty() {
+            recon.custom_title.clone()
+        } else if !recon.title.is_empty() {
+            recon.title.clone()
+        } else {
+            "Terminal".to_string()
+        };
+        let this = Self::init_window(mtm, config, theme, &title, None);
+        this.setSubtitle(&NSString::from_str("Monitor (read-only)"));
+        // Keep the monitor out of the tab group so it can sit beside its source
+        this.setTabbingMode(NSWindowTabbingMode::Disallowed);
+        let terminal_view = TerminalView::monitor(mtm, config, theme, recon);
+        this.attach_terminal_view(terminal_view);
+        this
+    }
+
+    /// Create a window playing back the recording at `path`, read-only
+    pub fn playback(
+        mtm: MainThreadMarker,
+        config: &Config,
+        theme: &Theme,
+        path: &std::path::Path,
+    ) -> Result<Retained<Self>, cterm_core::PlaybackError> {
+        let recording = cterm_core::Recording::open(path)?;
+        let this = Self::init_window(mtm, config, theme, &recording.tab_title(path), None);
+        this.setSubtitle(&NSString::from_str("Playback (read-only)"));
+        let terminal_view =
+            TerminalView::for_playback(mtm, config, theme, cterm_core::Player::new(recording));
+        terminal_view.set_title_locked(true);
+        this.attach_terminal_view(terminal_view);
+        Ok(this)
+    }
+
+    /// Create a tab playing back the recording at `path`
+    pub fn create_playback_tab(&self, path: &std::path::Path) {
+        let mtm = MainThreadMarker::from(self);
+
+        let new_window =
+            match CtermWindow::playback(mtm, &self.ivars().config, &self.ivars().theme, path) {
+                Ok(window) => window,
+                Err(e) => {
+                    log::error!("Failed to open recording {:?}: {}", path, e);
+                    crate::dialogs::show_error(
+                        mtm,
+                        Some(&**self),
+                        "Playback Failed",
+                        &format!("Failed to open {}: {}", path.display(), e),
+                    );
+                    return;
+                }
+            };
+
+        // Register with AppDelegate
+        let app = NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, registerWindow: &*new_window] };
+        }
+
+        // Add as tab to this window
+        self.addTabbedWindow_ordered(&new_window, objc2_app_kit::NSWindowOrderingMode::Above);
+        new_window.makeKeyAndOrderFront(None);
+    }
+
+    /// Create a tab for a pane of the tmux session `gateway` talks to
+    pub fn create_tmux_pane_tab(
+        &self,
+        gateway: &std::sync::Arc<cterm_app::tmux::TmuxGateway>,
+        pane: cterm_app::tmux::PaneId,
+        title: &str,
+        cols: usize,
+        rows: usize,
+    ) -> Retained<TerminalView> {
+        let mtm = MainThreadMarker::from(self);
+        let config = &self.ivars().config;
+        let theme = &self.ivars().theme;
+
+        let new_window = Self::init_window(mtm, config, theme, title, None);
+        new_window.setSubtitle(&NSString::from_str("tmux"));
+        let terminal_view =
+            TerminalView::for_tmux_pane(mtm, config, theme, gateway, pane, cols, rows);
+        new_window.attach_terminal_view(terminal_view.clone());
+
+        // Register with AppDelegate
+        let app = NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, registerWindow: &*new_window] };
+        }
+
+        // Add as tab to this window
+        self.addTabbedWindow_ordered(&new_window, objc2_app_kit::NSWindowOrderingMode::Above);
+        new_window.makeKeyAndOrderFront(None);
+        terminal_view
+    }
+
     /// Create a new tab connected to a daemon session (using native macOS window tabbing)
     pub fn create_daemon_tab(&self, session: cterm_client::SessionHandle) {
         let mtm = MainThreadMarker::from(self);
@@ -517,17 +1074,27 @@ impl CtermWindow {
 
     /// Create a new tab (daemon-backed via ctermd)
     pub fn create_new_tab(&self) {
+        self.create_new_tab_in(None);
+    }
+
+    /// Create a new tab in `directory`, or else in the active tab's directory
+    pub fn create_new_tab_in(&self, directory: Option<String>) {
         let active = self.ivars().active_terminal.borrow();
 
         // Get the current working directory from the active terminal
         #[cfg(unix)]
-        let cwd = active.as_ref().and_then(|t| t.foreground_cwd());
+        let active_cwd = active.as_ref().and_then(|t| t.foreground_cwd());
         #[cfg(not(unix))]
-        let cwd: Option<String> = None;
-
-        // Inherit the daemon socket from the active tab (for remote sessions)
-        let daemon_socket = active.as_ref().and_then(|t| t.daemon_socket());
+        let active_cwd: Option<String> = None;
+
+        // Inherit the daemon socket from the active tab (for remote sessions),
+        // unless a local directory was asked for
+        let daemon_socket = active
+            .as_ref()
+            .filter(|_| directory.is_none())
+            .and_then(|t| t.daemon_socket());
         drop(active);
+        let cwd = directory.or(active_cwd);
 
         let config = self.ivars().config.clone();
         let opts = cterm_client::CreateSessionOpts {
@@ -539,48 +1106,169 @@ impl CtermWindow {
             ..Default::default()
         };
 
-        self.spawn_daemon_tab(opts, None, None, None, None, daemon_socket);
+        self.spawn_daemon_tab(
+            opts,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            daemon_socket,
+            None,
+            None,
+            None,
+        );
     }
 
-    /// Spawn a daemon session in a background thread and create a tab when ready.
-    ///
-    /// If `remote` is `Some((manager, name, host))`, the session is created on
-    /// the remote ctermd (connecting via SSH if needed). If `daemon_socket` is
-    /// `Some`, connect to that specific daemon socket. Otherwise uses the local
-    /// daemon.
-    pub fn spawn_daemon_tab(
-        &self,
-        opts: cterm_client::CreateSessionOpts,
-        template_name: Option<String>,
-        color: Option<String>,
-        background_color: Option<String>,
-        remote: Option<(cterm_client::RemoteManager, String, String, bool)>,
-        daemon_socket: Option<std::path::PathBuf>,
-    ) {
-        let config = self.ivars().config.clone();
-        let theme = self.ivars().theme.clone();
-        // SAFETY: self is MainThreadOnly, we use the raw pointer only inside
-        // dispatch2::Queue::main().exec_async() which runs on the main thread
-        let window_ptr = self as *const Self as usize;
+    /// Create a new tab with a profile's shell, environment, theme, font and
+    /// scrollback size
+    pub fn create_profile_tab(&self, profile: ProfileConfig) {
+        let opts = profile.session_opts(&self.ivars().config.general);
+        self.spawn_daemon_tab(
+            opts,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(profile),
+            None,
+        );
+    }
 
-        std::thread::spawn(move || {
-            let rt = tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build();
+    /// Reopen the most recently closed tab with its template or working
+    /// directory, color and scrollback
+    pub fn reopen_closed_tab(&self) {
+        if let Some(closed) = closed_tabs::take_last() {
+            self.open_closed_tab(closed);
+        }
+    }
 
-            let result = match rt {
-                Ok(rt) => rt.block_on(async {
-                    let conn = if let Some((mgr, ref name, ref host, compress)) = remote {
-                        mgr.get_or_connect(name, host, compress).await?
-                    } else if let Some(ref path) = daemon_socket {
-                        cterm_client::DaemonConnection::connect_unix(path, false).await?
-                    } else {
-                        cterm_client::DaemonConnection::connect_local().await?
-                    };
-                    let session = conn.create_session(opts).await?;
-                    Ok::<_, cterm_client::ClientError>(session)
-                }),
-                Err(e) => Err(cterm_client::ClientError::Connection(e.to_string())),
+    /// Open a tab like `closed` from its template or in its working
+    /// directory, with its color and scrollback
+    fn open_closed_tab(&self, closed: ClosedTab) {
+        let templates = cterm_app::config::load_sticky_tabs().unwrap_or_default();
+        if let Some(template) = closed.find_template(&templates) {
+            self.open_template_tab(template, Some(closed.clone()), None);
+            return;
+        }
+
+        let config = &self.ivars().config;
+        let opts = cterm_client::CreateSessionOpts {
+            cols: 80,
+            rows: 24,
+            shell: config.general.default_shell.clone(),
+            args: config.general.shell_args.clone(),
+            cwd: closed.cwd.clone(),
+            ..Default::default()
+        };
+        let color = closed.color.clone();
+        self.spawn_daemon_tab(
+            opts,
+            None,
+            color,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(closed),
+            None,
+            None,
+        );
+    }
+
+    /// What reopening this window's tab needs: its template or working
+    /// directory, title, color and scrollback
+    fn closed_tab(&self) -> Option<ClosedTab> {
+        let tv = self.active_terminal()?;
+        let mut closed = ClosedTab::new(self.title().to_string());
+        closed.template = tv.template_name();
+        closed.color = self.tab_color();
+        closed.cwd = tv.foreground_cwd();
+        closed.capture_scrollback(tv.terminal().lock().screen());
+        Some(closed)
+    }
+
+    /// Record this window's tab for Reopen Closed Tab and the usage
+    /// statistics
+    fn remember_closed_tab(&self) {
+        let Some(closed) = self.closed_tab() else {
+            return;
+        };
+        if let Some(tv) = self.active_terminal() {
+            cterm_app::usage_stats::record_session(tv.terminal().lock().age());
+        }
+        closed_tabs::remember(closed, &self.ivars().config.tabs);
+    }
+
+    /// This window's tab as saved for session restore
+    pub fn session_tab(&self) -> Option<TabSessionState> {
+        self.closed_tab().map(TabSessionState::from)
+    }
+
+    /// Frame and zoom state, saved for session restore
+    pub fn geometry(&self) -> WindowGeometry {
+        let frame = self.frame();
+        WindowGeometry {
+            x: frame.origin.x as i32,
+            y: frame.origin.y as i32,
+            width: frame.size.width.max(0.0) as u32,
+            height: frame.size.height.max(0.0) as u32,
+            maximized: self.isZoomed(),
+        }
+    }
+
+    /// Spawn a daemon session in a background thread and create a tab when ready.
+    ///
+    /// If `remote` is `Some((manager, name, host))`, the session is created on
+    /// the remote ctermd (connecting via SSH if needed). If `daemon_socket` is
+    /// `Some`, connect to that specific daemon socket. Otherwise uses the local
+    /// daemon. `reopened` replays a closed tab's scrollback into the new tab,
+    /// and `profile` sets its theme, font and scrollback size.
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn_daemon_tab(
+        &self,
+        opts: cterm_client::CreateSessionOpts,
+        template_name: Option<String>,
+        color: Option<String>,
+        background_color: Option<String>,
+        command_notify: Option<CommandNotifyConfig>,
+        permissions: Option<PermissionPolicy>,
+        remote: Option<(cterm_client::RemoteManager, String, String, bool)>,
+        daemon_socket: Option<std::path::PathBuf>,
+        reopened: Option<ClosedTab>,
+        profile: Option<ProfileConfig>,
+        on_open: Option<OnTabOpen>,
+    ) {
+        let config = self.ivars().config.clone();
+        let theme = self.ivars().theme.clone();
+        // SAFETY: self is MainThreadOnly, we use the raw pointer only inside
+        // dispatch2::Queue::main().exec_async() which runs on the main thread
+        let window_ptr = self as *const Self as usize;
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build();
+
+            let result = match rt {
+                Ok(rt) => rt.block_on(async {
+                    let conn = if let Some((mgr, ref name, ref host, compress)) = remote {
+                        mgr.get_or_connect(name, host, compress).await?
+                    } else if let Some(ref path) = daemon_socket {
+                        cterm_client::DaemonConnection::connect_unix(path, false).await?
+                    } else {
+                        cterm_client::DaemonConnection::connect_local().await?
+                    };
+                    let session = conn.create_session(opts).await?;
+                    Ok::<_, cterm_client::ClientError>(session)
+                }),
+                Err(e) => Err(cterm_client::ClientError::Connection(e.to_string())),
             };
 
             match result {
@@ -591,9 +1279,15 @@ impl CtermWindow {
 
                         let title = template_name
                             .clone()
+                            .or_else(|| profile.as_ref().map(|p| p.name.clone()))
                             .unwrap_or_else(|| "Terminal".to_string());
 
-                        let new_window = CtermWindow::from_daemon(mtm, &config, &theme, session);
+                        let new_window = match profile {
+                            Some(ref profile) => CtermWindow::from_daemon_with_profile(
+                                mtm, &config, &theme, profile, session,
+                            ),
+                            None => CtermWindow::from_daemon(mtm, &config, &theme, session),
+                        };
                         new_window.setTitle(&NSString::from_str(&title));
 
                         // Store template name and apply background color on the terminal view
@@ -605,6 +1299,15 @@ impl CtermWindow {
                             if let Some(ref bg) = background_color {
                                 tv.set_background_override(Some(bg));
                             }
+                            if let Some(notify) = command_notify {
+                                tv.set_command_notify(notify);
+                            }
+                            if let Some(permissions) = permissions {
+                                tv.set_permissions(permissions);
+                            }
+                            if let Some(ref closed) = reopened {
+                                closed.replay_scrollback(&mut tv.terminal().lock());
+                            }
                         }
 
                         let app = NSApplication::sharedApplication(mtm);
@@ -624,10 +1327,16 @@ impl CtermWindow {
                         }
 
                         log::info!("Created daemon tab: {}", title);
+                        if let Some(on_open) = on_open {
+                            on_open(Some(&*new_window));
+                        }
                     });
                 }
                 Err(e) => {
                     log::error!("Failed to create daemon session: {}", e);
+                    if let Some(on_open) = on_open {
+                        dispatch2::Queue::main().exec_async(move || on_open(None));
+                    }
                 }
             }
         });
@@ -646,6 +1355,11 @@ impl CtermWindow {
         &self.ivars().config
     }
 
+    /// Shortcuts from `[shortcuts]`
+    pub fn shortcuts(&self) -> &ShortcutManager {
+        &self.ivars().shortcuts
+    }
+
     /// Get theme reference
     pub fn theme(&self) -> &Theme {
         &self.ivars().theme
@@ -656,6 +1370,446 @@ impl CtermWindow {
         self.ivars().active_terminal.borrow().clone()
     }
 
+    /// Whether `terminal` is the focused pane
+    pub fn is_active_pane(&self, terminal: &TerminalView) -> bool {
+        self.ivars()
+            .active_terminal
+            .borrow()
+            .as_deref()
+            .is_some_and(|active| std::ptr::eq(active, terminal))
+    }
+
+    /// Note `terminal` taking keyboard focus: it becomes the focused pane
+    /// and titles the window
+    pub fn pane_focused(&self, terminal: &TerminalView) {
+        if self.is_active_pane(terminal) {
+            return;
+        }
+        let pane = self
+            .ivars()
+            .panes
+            .borrow()
+            .iter()
+            .find(|(_, view)| std::ptr::eq(&**view, terminal))
+            .cloned();
+        let Some((id, view)) = pane else {
+            return;
+        };
+        self.ivars().pane_tree.borrow_mut().focus(id);
+        *self.ivars().active_terminal.borrow_mut() = Some(view);
+
+        if !terminal.is_title_locked() {
+            let title = terminal.terminal().lock().title().to_string();
+            if !title.is_empty() {
+                self.setTitle(&NSString::from_str(&title));
+            }
+        }
+        terminal.update_tab_label();
+    }
+
+    /// Split the focused pane along `axis`, the new pane starting a shell in
+    /// the same directory on the same daemon
+    pub fn split_pane(&self, axis: SplitAxis) {
+        let Some(active) = self.active_terminal() else {
+            return;
+        };
+        #[cfg(unix)]
+        let cwd = active.foreground_cwd();
+        #[cfg(not(unix))]
+        let cwd: Option<String> = None;
+
+        let config = &self.ivars().config;
+        let opts = cterm_client::CreateSessionOpts {
+            cols: 80,
+            rows: 24,
+            shell: config.general.default_shell.clone(),
+            args: config.general.shell_args.clone(),
+            cwd,
+            ..Default::default()
+        };
+        self.split_pane_with(axis, opts, None);
+    }
+
+    /// Split the focused pane along `axis`, the new pane running a session
+    /// started with `opts` on the same daemon; `on_open` is called once it
+    /// is open or failed to
+    fn split_pane_with(
+        &self,
+        axis: SplitAxis,
+        opts: cterm_client::CreateSessionOpts,
+        on_open: Option<OnPaneOpen>,
+    ) {
+        let Some(active) = self.active_terminal() else {
+            if let Some(on_open) = on_open {
+                on_open();
+            }
+            return;
+        };
+        let daemon_socket = active.daemon_socket();
+
+        let config = self.ivars().config.clone();
+        let theme = self.ivars().theme.clone();
+        let window_ptr = self as *const Self as usize;
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build();
+
+            let result = match rt {
+                Ok(rt) => rt.block_on(async {
+                    let conn = if let Some(ref path) = daemon_socket {
+                        cterm_client::DaemonConnection::connect_unix(path, false).await?
+                    } else {
+                        cterm_client::DaemonConnection::connect_local().await?
+                    };
+                    conn.create_session(opts).await
+                }),
+                Err(e) => Err(cterm_client::ClientError::Connection(e.to_string())),
+            };
+
+            match result {
+                Ok(session) => {
+                    dispatch2::Queue::main().exec_async(move || {
+                        let mtm = unsafe { MainThreadMarker::new_unchecked() };
+                        let window: &CtermWindow = unsafe { &*(window_ptr as *const CtermWindow) };
+                        let terminal = TerminalView::from_daemon(mtm, &config, &theme, session);
+                        window.insert_pane(terminal, axis);
+                        if let Some(on_open) = on_open {
+                            on_open();
+                        }
+                    });
+                }
+                Err(e) => {
+                    log::error!("Failed to create daemon session for pane: {}", e);
+                    if let Some(on_open) = on_open {
+                        dispatch2::Queue::main().exec_async(on_open);
+                    }
+                }
+            }
+        });
+    }
+
+    /// A window showing the first tab of workspace `name`, which goes on to
+    /// open the tab's panes and the other tabs (`cterm --workspace`)
+    pub fn workspace(
+        mtm: MainThreadMarker,
+        config: &Config,
+        theme: &Theme,
+        name: &str,
+    ) -> Option<Retained<Self>> {
+        let Some(workspace) = workspace::find(config, name) else {
+            log::error!("No workspace named {:?}", name);
+            return None;
+        };
+        let templates = cterm_app::config::load_sticky_tabs().unwrap_or_default();
+        let mut queue: VecDeque<_> = workspace::resolve(workspace, &templates).into();
+        let first = queue.pop_front()?;
+
+        let (template, profile) = first.template.resolve_profile(config);
+        if let Some(ref working_dir) = template.working_directory {
+            if let Err(e) =
+                cterm_app::prepare_working_directory(working_dir, template.git_remote.as_deref())
+            {
+                log::error!("Failed to prepare working directory: {}", e);
+            }
+        }
+        let opts = template_session_opts(&template, profile.as_ref(), config);
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .ok()?;
+        let session = rt.block_on(async {
+            let conn = cterm_client::DaemonConnection::connect_local().await?;
+            conn.create_session(opts).await
+        });
+        let session = match session {
+            Ok(session) => session,
+            Err(e) => {
+                log::error!("Failed to create daemon session: {}", e);
+                return None;
+            }
+        };
+
+        let window = match profile {
+            Some(ref profile) => {
+                Self::from_daemon_with_profile(mtm, config, theme, profile, session)
+            }
+            None => Self::from_daemon(mtm, config, theme, session),
+        };
+        window.setTitle(&NSString::from_str(&template.name));
+        if let Some(tv) = window.active_terminal() {
+            tv.set_template_name(Some(template.name.clone()));
+            tv.set_template_name_on_daemon(&template.name);
+        }
+        if let Some(ref color) = template.color {
+            window.set_tab_color(Some(color));
+        }
+        window.open_workspace_panes(first.panes.into(), queue);
+        Some(window)
+    }
+
+    /// Open the tabs and panes of workspace `name` next to this tab
+    pub fn open_workspace(&self, name: &str) {
+        let Some(workspace) = workspace::find(&self.ivars().config, name) else {
+            log::warn!("No workspace named {:?}", name);
+            return;
+        };
+        let templates = cterm_app::config::load_sticky_tabs().unwrap_or_default();
+        self.open_workspace_tabs(workspace::resolve(workspace, &templates).into());
+    }
+
+    /// Open the tabs in `queue` one after another, each once the previous
+    /// one has its panes
+    fn open_workspace_tabs(&self, mut queue: VecDeque<WorkspaceTab>) {
+        let Some(tab) = queue.pop_front() else {
+            return;
+        };
+        let window_ptr = self as *const Self as usize;
+        let panes: VecDeque<_> = tab.panes.into();
+        self.open_template_tab(
+            &tab.template,
+            None,
+            Some(Box::new(move |new_window| match new_window {
+                Some(new_window) => new_window.open_workspace_panes(panes, queue),
+                None => {
+                    let window: &CtermWindow = unsafe { &*(window_ptr as *const CtermWindow) };
+                    window.open_workspace_tabs(queue);
+                }
+            })),
+        );
+    }
+
+    /// Split off `panes` one after another, then open the tabs in `queue`
+    fn open_workspace_panes(
+        &self,
+        mut panes: VecDeque<WorkspacePane>,
+        queue: VecDeque<WorkspaceTab>,
+    ) {
+        let Some(pane) = panes.pop_front() else {
+            self.open_workspace_tabs(queue);
+            return;
+        };
+        let config = &self.ivars().config;
+        let (template, profile) = pane.template.resolve_profile(config);
+        let opts = template_session_opts(&template, profile.as_ref(), config);
+        let window_ptr = self as *const Self as usize;
+        self.split_pane_with(
+            pane.axis,
+            opts,
+            Some(Box::new(move || {
+                let window: &CtermWindow = unsafe { &*(window_ptr as *const CtermWindow) };
+                window.open_workspace_panes(panes, queue);
+            })),
+        );
+    }
+
+    /// Put `terminal` right of or below the focused pane, in a split view
+    /// taking the focused pane's place
+    fn insert_pane(&self, terminal: Retained<TerminalView>, axis: SplitAxis) {
+        let Some(active) = self.active_terminal() else {
+            return;
+        };
+        let mtm = MainThreadMarker::from(self);
+        let split = NSSplitView::new(mtm);
+        split.setFrame(active.frame());
+        split.setAutoresizingMask(active.autoresizingMask());
+        // A vertical divider puts the panes side by side
+        split.setVertical(axis == SplitAxis::Horizontal);
+        split.setDividerStyle(NSSplitViewDividerStyle::Thin);
+
+        let id = session::next_pane_id();
+        {
+            let mut tree = self.ivars().pane_tree.borrow_mut();
+            let focused = tree.focused();
+            tree.split(focused, axis, id);
+        }
+        self.ivars().panes.borrow_mut().push((id, terminal.clone()));
+        terminal.set_base_font_size(self.base_font_size());
+
+        self.moving_panes(|| {
+            self.replace_view(&active, &split);
+            split.addSubview(&active);
+        });
+        split.addSubview(&terminal);
+        let size = if split.isVertical() {
+            split.frame().size.width
+        } else {
+            split.frame().size.height
+        };
+        split.setPosition_ofDividerAtIndex((size / 2.0).round(), 0);
+        self.makeFirstResponder(Some(&terminal));
+    }
+
+    /// Close the pane showing `terminal`, ending its session, or the window
+    /// with its last pane
+    pub fn close_pane(&self, terminal: &TerminalView) {
+        if !self.ivars().pane_tree.borrow().is_split() {
+            self.close();
+            return;
+        }
+        let index = self
+            .ivars()
+            .panes
+            .borrow()
+            .iter()
+            .position(|(_, view)| std::ptr::eq(&**view, terminal));
+        let Some(index) = index else {
+            return;
+        };
+        let (id, view) = self.ivars().panes.borrow_mut().remove(index);
+        let focused = {
+            let mut tree = self.ivars().pane_tree.borrow_mut();
+            tree.remove(id);
+            tree.focused()
+        };
+        let focused = self
+            .ivars()
+            .panes
+            .borrow()
+            .iter()
+            .find(|(id, _)| *id == focused)
+            .map(|(_, view)| view.clone());
+
+        // The split's other side takes the split's place
+        let Some(split) = view.superview() else {
+            return;
+        };
+        let closed: &NSView = &view;
+        let sibling = split
+            .subviews()
+            .iter()
+            .find(|subview| !std::ptr::eq(&**subview, closed));
+        // Leaving the window ends the closed pane's session
+        view.removeFromSuperview();
+        if let Some(sibling) = sibling {
+            let frame = split.frame();
+            self.moving_panes(|| {
+                sibling.removeFromSuperview();
+                self.replace_view(&split, &sibling);
+            });
+            sibling.setAutoresizingMask(split.autoresizingMask());
+            sibling.setFrame(frame);
+        }
+
+        *self.ivars().active_terminal.borrow_mut() = None;
+        if let Some(focused) = focused {
+            self.makeFirstResponder(Some(&focused));
+            if !self.is_active_pane(&focused) {
+                self.pane_focused(&focused);
+            }
+        }
+    }
+
+    /// View of the pane scrolling together with `terminal`'s, if locked
+    pub fn scroll_partner(&self, terminal: &TerminalView) -> Option<Retained<TerminalView>> {
+        let panes = self.ivars().panes.borrow();
+        let (id, _) = panes
+            .iter()
+            .find(|(_, view)| std::ptr::eq(&**view, terminal))?;
+        let partner = self.ivars().pane_tree.borrow().scroll_partner(*id)?;
+        panes
+            .iter()
+            .find(|(id, _)| *id == partner)
+            .map(|(_, view)| view.clone())
+    }
+
+    /// Focus the pane next to the focused one in `direction`
+    fn focus_pane(&self, direction: PaneDirection) {
+        let Some(content) = self.contentView() else {
+            return;
+        };
+        let target = {
+            let panes = self.ivars().panes.borrow();
+            // Split views are flipped, so y grows downward as the model expects
+            let areas: Vec<(u64, PaneRect)> = panes
+                .iter()
+                .map(|(id, view)| {
+                    let rect = view.convertRect_toView(view.bounds(), Some(&content));
+                    (
+                        *id,
+                        PaneRect::new(
+                            rect.origin.x,
+                            rect.origin.y,
+                            rect.size.width,
+                            rect.size.height,
+                        ),
+                    )
+                })
+                .collect();
+            let focused = self.ivars().pane_tree.borrow().focused();
+            session::pane_in_direction(&areas, focused, direction).and_then(|target| {
+                panes
+                    .iter()
+                    .find(|(id, _)| *id == target)
+                    .map(|(_, view)| view.clone())
+            })
+        };
+        if let Some(target) = target {
+            self.makeFirstResponder(Some(&target));
+        }
+    }
+
+    /// Move the divider of the innermost split around the focused pane that
+    /// runs across `direction`
+    fn resize_pane(&self, direction: PaneDirection) {
+        let Some(active) = self.active_terminal() else {
+            return;
+        };
+        let vertical = SplitAxis::of(direction) == SplitAxis::Horizontal;
+        let mut child: Retained<NSView> = Retained::into_super(active);
+        while let Some(parent) = child.superview() {
+            let Some(split) = parent.downcast_ref::<NSSplitView>() else {
+                return;
+            };
+            if split.isVertical() == vertical {
+                let Some(first) = split.subviews().firstObject() else {
+                    return;
+                };
+                let (position, size) = if vertical {
+                    (first.frame().size.width, split.frame().size.width)
+                } else {
+                    (first.frame().size.height, split.frame().size.height)
+                };
+                let step = size * PANE_RESIZE_STEP;
+                let position = match direction {
+                    PaneDirection::Left | PaneDirection::Up => position - step,
+                    PaneDirection::Right | PaneDirection::Down => position + step,
+                };
+                let position =
+                    position.clamp(size * MIN_SPLIT_RATIO, size * (1.0 - MIN_SPLIT_RATIO));
+                split.setPosition_ofDividerAtIndex(position.round(), 0);
+                return;
+            }
+            child = parent;
+        }
+    }
+
+    /// Run `f`, which moves panes between split views, without the panes
+    /// taking it for being closed
+    fn moving_panes(&self, f: impl FnOnce()) {
+        let panes = self.ivars().panes.borrow().clone();
+        for (_, view) in &panes {
+            view.set_moving(true);
+        }
+        f();
+        for (_, view) in &panes {
+            view.set_moving(false);
+        }
+    }
+
+    /// Put `new` where `old` is: the content view or a split view's subview
+    fn replace_view(&self, old: &NSView, new: &NSView) {
+        let is_content = self
+            .contentView()
+            .is_some_and(|content| std::ptr::eq(&*content, old));
+        if is_content {
+            self.setContentView(Some(new));
+        } else if let Some(parent) = old.superview() {
+            parent.replaceSubview_with(old, new);
+        }
+    }
+
     /// Set the bell state for this window and update dock badge
     pub fn set_bell(&self, active: bool) {
         let was_active = self.ivars().has_active_bell.get();
@@ -678,6 +1832,159 @@ impl CtermWindow {
         }
     }
 
+    /// Show or hide the Secure Keyboard Entry lock in the title bar
+    pub fn set_secure_input_indicator(&self, on: bool) {
+        let mut indicator = self.ivars().secure_indicator.borrow_mut();
+        if on == indicator.is_some() {
+            return;
+        }
+        if let Some(controller) = indicator.take() {
+            controller.removeFromParentViewController();
+            return;
+        }
+
+        let mtm = MainThreadMarker::from(self);
+        let description = NSString::from_str("Secure Keyboard Entry is on");
+        let Some(image) = NSImage::imageWithSystemSymbolName_accessibilityDescription(
+            &NSString::from_str("lock.fill"),
+            Some(&description),
+        ) else {
+            return;
+        };
+        let image_view = NSImageView::imageViewWithImage(&image, mtm);
+        image_view.setFrameSize(NSSize::new(28.0, 16.0));
+        image_view.setToolTip(Some(&description));
+
+        let controller = NSTitlebarAccessoryViewController::new(mtm);
+        controller.setView(&image_view);
+        controller.setLayoutAttribute(NSLayoutAttribute::Right);
+        self.addTitlebarAccessoryViewController(&controller);
+        *indicator = Some(controller);
+    }
+
+    /// Add the clock, timer and battery widgets to the title bar
+    ///
+    /// Native tabs have no room for them, so they sit at the right of the
+    /// title bar instead. Clicking starts or pauses the timer; its menu
+    /// resets it.
+    fn install_widgets(&self) {
+        let config = &self.ivars().config.widgets;
+        if !config.any() {
+            return;
+        }
+
+        let mtm = MainThreadMarker::from(self);
+        let button = unsafe {
+            NSButton::buttonWithTitle_target_action(
+                &NSString::from_str(""),
+                Some(self),
+                Some(sel!(toggleWidgetTimer:)),
+                mtm,
+            )
+        };
+        button.setBordered(false);
+        if config.timer {
+            button.setToolTip(Some(&NSString::from_str(
+                "Click to start or pause the timer, right-click to reset it",
+            )));
+            let menu = NSMenu::new(mtm);
+            let item = NSMenuItem::new(mtm);
+            item.setTitle(&NSString::from_str("Reset Timer"));
+            unsafe {
+                item.setAction(Some(sel!(resetWidgetTimer:)));
+                item.setTarget(Some(self));
+            }
+            menu.addItem(&item);
+            button.setMenu(Some(&menu));
+        }
+
+        let controller = NSTitlebarAccessoryViewController::new(mtm);
+        controller.setView(&button);
+        controller.setLayoutAttribute(NSLayoutAttribute::Right);
+        self.addTitlebarAccessoryViewController(&controller);
+
+        let timer: Retained<NSTimer> = unsafe {
+            msg_send![
+                class!(NSTimer),
+                scheduledTimerWithTimeInterval: tab_widgets::REFRESH_INTERVAL.as_secs_f64(),
+                target: self,
+                selector: sel!(widgetsFire:),
+                userInfo: std::ptr::null::<AnyObject>(),
+                repeats: true
+            ]
+        };
+        *self.ivars().widgets.borrow_mut() = Some(TitlebarWidgets {
+            controller,
+            button,
+            timer,
+        });
+        self.update_widgets();
+    }
+
+    /// Refresh the status segments every [`status_segments::REFRESH_INTERVAL`]
+    /// if any are configured
+    fn install_segments_timer(&self) {
+        if self.ivars().config.status_segments.is_empty() {
+            return;
+        }
+        let timer: Retained<NSTimer> = unsafe {
+            msg_send![
+                class!(NSTimer),
+                scheduledTimerWithTimeInterval: status_segments::REFRESH_INTERVAL.as_secs_f64(),
+                target: self,
+                selector: sel!(segmentsFire:),
+                userInfo: std::ptr::null::<AnyObject>(),
+                repeats: true
+            ]
+        };
+        *self.ivars().segments_timer.borrow_mut() = Some(timer);
+    }
+
+    /// Update the status segments from the focused pane's directory, and the
+    /// tab label if they changed
+    fn update_segments(&self) {
+        let Some(terminal) = self.ivars().active_terminal.borrow().clone() else {
+            return;
+        };
+        let dir = terminal.foreground_cwd().map(std::path::PathBuf::from);
+        let segments =
+            status_segments::values(&self.ivars().config.status_segments, dir.as_deref());
+        if *self.ivars().segments.borrow() != segments {
+            *self.ivars().segments.borrow_mut() = segments;
+            terminal.update_tab_label();
+        }
+    }
+
+    /// The tab label for window title `title`, through the title format
+    pub fn tab_label(&self, title: &str) -> String {
+        status_segments::format_title(
+            &self.ivars().config.tabs.title_format,
+            title,
+            &self.ivars().segments.borrow(),
+        )
+    }
+
+    /// Refresh the title bar widget texts
+    fn update_widgets(&self) {
+        let accessory = self.ivars().widgets.borrow();
+        let Some(button) = accessory.as_ref().map(|widgets| &widgets.button) else {
+            return;
+        };
+        let widgets = tab_widgets::widgets(&self.ivars().config.widgets);
+        let text = widgets
+            .iter()
+            .map(|widget| widget.text.as_str())
+            .collect::<Vec<_>>()
+            .join("   ");
+        button.setTitle(&NSString::from_str(&text));
+        let tint = widgets
+            .iter()
+            .any(|widget| widget.emphasis)
+            .then(NSColor::systemOrangeColor);
+        button.setContentTintColor(tint.as_deref());
+        button.sizeToFit();
+    }
+
     /// Check if this window has an active bell notification
     pub fn has_bell(&self) -> bool {
         self.ivars().has_active_bell.get()
@@ -702,7 +2009,7 @@ impl CtermWindow {
             let window_ptr = self as *const Self;
             overlay.set_on_select(move |template| unsafe {
                 let window = &*window_ptr;
-                window.open_template_tab(&template);
+                window.open_template_tab(&template, None, None);
             });
 
             // Set up callback for switching to an open tab
@@ -713,33 +2020,31 @@ impl CtermWindow {
 
             overlay.set_open_tabs(open_tabs);
 
-            // Add to window content view
-            if let Some(content_view) = self.contentView() {
+            *self.ivars().quick_open.borrow_mut() = Some(overlay);
+        } else {
+            // Update templates and open tabs in case they changed
+            if let Some(ref overlay) = *self.ivars().quick_open.borrow() {
+                overlay.set_templates_and_tabs(templates, open_tabs);
+            }
+        }
+
+        // Show the overlay over the focused pane
+        if let Some(ref overlay) = *self.ivars().quick_open.borrow() {
+            if let Some(content_view) = self.active_terminal() {
                 unsafe {
-                    content_view.addSubview(&overlay);
+                    content_view.addSubview(overlay);
                 }
 
-                // Position at top of window
+                // Position at top of the pane
                 let content_bounds = content_view.bounds();
                 let overlay_frame = NSRect::new(
                     NSPoint::new(0.0, 0.0),
                     NSSize::new(content_bounds.size.width, QUICK_OPEN_HEIGHT),
                 );
                 unsafe {
-                    let _: () = msg_send![&*overlay, setFrame: overlay_frame];
+                    let _: () = msg_send![&**overlay, setFrame: overlay_frame];
                 }
             }
-
-            *self.ivars().quick_open.borrow_mut() = Some(overlay);
-        } else {
-            // Update templates and open tabs in case they changed
-            if let Some(ref overlay) = *self.ivars().quick_open.borrow() {
-                overlay.set_templates_and_tabs(templates, open_tabs);
-            }
-        }
-
-        // Show the overlay
-        if let Some(ref overlay) = *self.ivars().quick_open.borrow() {
             overlay.show();
         }
     }
@@ -776,7 +2081,16 @@ impl CtermWindow {
     }
 
     /// Open a new tab from a template (daemon-backed via ctermd)
-    fn open_template_tab(&self, template: &cterm_app::config::StickyTabConfig) {
+    fn open_template_tab(
+        &self,
+        template: &cterm_app::config::StickyTabConfig,
+        reopened: Option<ClosedTab>,
+        on_open: Option<OnTabOpen>,
+    ) {
+        // Take what the template leaves unset from its profile
+        let (template, profile) = template.resolve_profile(&self.ivars().config);
+        let template = &template;
+
         // Prepare working directory (clone from git if needed)
         if let Some(ref working_dir) = template.working_directory {
             if let Err(e) =
@@ -786,40 +2100,19 @@ impl CtermWindow {
             }
         }
 
-        let config = &self.ivars().config;
-        let opts = cterm_client::CreateSessionOpts {
-            cols: 80,
-            rows: 24,
-            shell: template
-                .command
-                .clone()
-                .or_else(|| config.general.default_shell.clone()),
-            args: if template.args.is_empty() && template.command.is_none() {
-                config.general.shell_args.clone()
-            } else {
-                template.args.clone()
-            },
-            cwd: template
-                .working_directory
-                .as_ref()
-                .map(|p| p.to_string_lossy().to_string()),
-            env: template
-                .env
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect(),
-            // SSH tabs open a native puressh connection on the daemon.
-            ssh: template.ssh.as_ref().map(|s| s.to_ssh_params()),
-            ..Default::default()
-        };
-
+        let opts = template_session_opts(template, profile.as_ref(), &self.ivars().config);
         self.spawn_daemon_tab(
             opts,
             Some(template.name.clone()),
             template.color.clone(),
             template.background_color.clone(),
+            template.command_notify,
+            template.permissions,
             None,
             None,
+            reopened,
+            profile,
+            on_open,
         );
     }
 
@@ -876,6 +2169,80 @@ impl CtermWindow {
         }
     }
 
+    /// Toggle presentation mode: larger font, no tab bar or scrollbar, and a
+    /// contrast floor for the text
+    fn toggle_presentation_mode(&self) {
+        if self.ivars().panes.borrow().is_empty() {
+            return;
+        }
+        let config = &self.ivars().config;
+        let base_size = self.base_font_size();
+        let tab_bar_visible = |window: &Self| -> bool {
+            unsafe {
+                let group: Option<Retained<objc2::runtime::AnyObject>> =
+                    msg_send![window, tabGroup];
+                group.is_some_and(|group| msg_send![&*group, isTabBarVisible])
+            }
+        };
+        let toggle_tab_bar = |window: &Self| unsafe {
+            let _: () = msg_send![
+                window,
+                toggleTabBar: std::ptr::null::<objc2::runtime::AnyObject>()
+            ];
+        };
+
+        match self.ivars().presenting.take() {
+            None => {
+                let was_visible = tab_bar_visible(self);
+                if was_visible {
+                    toggle_tab_bar(self);
+                }
+                self.ivars().presenting.set(Some(was_visible));
+                for (_, terminal) in self.ivars().panes.borrow().iter() {
+                    terminal.set_presentation(
+                        Some(&config.presentation),
+                        config.presentation.font_size(base_size),
+                    );
+                }
+            }
+            Some(was_visible) => {
+                if was_visible && !tab_bar_visible(self) {
+                    toggle_tab_bar(self);
+                }
+                for (_, terminal) in self.ivars().panes.borrow().iter() {
+                    terminal.set_presentation(None, base_size);
+                }
+            }
+        }
+    }
+
+    /// Font size configured for the display the window is on
+    fn base_font_size(&self) -> f64 {
+        let display = self.ivars().display.borrow();
+        self.ivars()
+            .config
+            .appearance
+            .font_size_for_display(display.as_deref())
+    }
+
+    /// Note which display the window is on and, when it changed, resize
+    /// the panes' fonts to the size `display_font_sizes` gives it
+    fn update_display(&self) {
+        let name = self.screen().map(|screen| {
+            let name: Retained<NSString> = unsafe { msg_send![&*screen, localizedName] };
+            name.to_string()
+        });
+        if *self.ivars().display.borrow() == name {
+            return;
+        }
+        log::debug!("Window is on display {:?}", name);
+        *self.ivars().display.borrow_mut() = name;
+        let size = self.base_font_size();
+        for (_, terminal) in self.ivars().panes.borrow().iter() {
+            terminal.set_base_font_size(size);
+        }
+    }
+
     /// Schedule a retry for applying tab color after a short delay
     fn schedule_tab_color_retry(&self) {
         unsafe {
@@ -1092,3 +2459,41 @@ impl CtermWindow {
         self.setFrame_display(frame, true);
     }
 }
+
+/// Daemon session options for a tab opened from `template`, with its
+/// `profile`'s scrollback size
+fn template_session_opts(
+    template: &cterm_app::config::StickyTabConfig,
+    profile: Option<&ProfileConfig>,
+    config: &Config,
+) -> cterm_client::CreateSessionOpts {
+    cterm_client::CreateSessionOpts {
+        cols: 80,
+        rows: 24,
+        shell: template
+            .command
+            .clone()
+            .or_else(|| config.general.default_shell.clone()),
+        args: if template.args.is_empty() && template.command.is_none() {
+            config.general.shell_args.clone()
+        } else {
+            template.args.clone()
+        },
+        cwd: template
+            .working_directory
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string()),
+        env: template
+            .env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        // SSH tabs open a native puressh connection on the daemon.
+        ssh: template.ssh.as_ref().map(|s| s.to_ssh_params()),
+        serial: template.serial.as_ref().map(|s| s.to_serial_params()),
+        startup_commands: template.startup_commands.clone(),
+        clear_env: !template.inherit_env,
+        scrollback_lines: profile.and_then(|p| p.scrollback_lines),
+        ..Default::default()
+    }
+}
@@ -5,8 +5,11 @@
 
 use crate::color::Color;
 use bitflags::bitflags;
-use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, LazyLock, Mutex};
 
 bitflags! {
     /// Cell rendering attributes
@@ -69,6 +72,82 @@ impl CellAttrs {
     }
 }
 
+/// How a renderer should draw an SGR attribute the user finds hard to read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttrSubstitute {
+    /// Render the attribute as the application asked
+    #[default]
+    Normal,
+    /// Ignore the attribute
+    Off,
+    /// Render italic text instead
+    Italic,
+    /// Render underlined text instead
+    Underline,
+}
+
+impl AttrSubstitute {
+    /// Replace `attr` in `attrs` according to this substitute
+    fn apply(self, attrs: &mut CellAttrs, attr: CellAttrs) {
+        if self == Self::Normal || !attrs.contains(attr) {
+            return;
+        }
+        attrs.remove(attr);
+        match self {
+            Self::Normal | Self::Off => {}
+            Self::Italic => attrs.insert(CellAttrs::ITALIC),
+            Self::Underline => {
+                if !attrs.has_underline() {
+                    attrs.insert(CellAttrs::UNDERLINE);
+                }
+            }
+        }
+    }
+}
+
+/// Render-time replacements for blink, concealed and dim text
+///
+/// Only affects how cells are drawn; the grid keeps the attributes the
+/// application set, so copying text or toggling the option back is lossless.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttrOverrides {
+    /// Replacement for blinking text (SGR 5/6)
+    pub blink: AttrSubstitute,
+    /// Replacement for concealed text (SGR 8)
+    pub conceal: AttrSubstitute,
+    /// Replacement for dim/faint text (SGR 2)
+    pub dim: AttrSubstitute,
+}
+
+impl AttrOverrides {
+    /// Check whether every attribute renders as requested
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Attributes to draw in place of `attrs`
+    pub fn apply(&self, mut attrs: CellAttrs) -> CellAttrs {
+        self.blink.apply(&mut attrs, CellAttrs::BLINK);
+        self.conceal.apply(&mut attrs, CellAttrs::HIDDEN);
+        self.dim.apply(&mut attrs, CellAttrs::DIM);
+        attrs
+    }
+
+    /// The cell to draw in place of `cell`, borrowing it when unchanged
+    pub fn apply_to<'a>(&self, cell: &'a Cell) -> Cow<'a, Cell> {
+        let attrs = self.apply(cell.attrs);
+        if attrs == cell.attrs {
+            Cow::Borrowed(cell)
+        } else {
+            let mut cell = cell.clone();
+            cell.attrs = attrs;
+            Cow::Owned(cell)
+        }
+    }
+}
+
 /// Hyperlink information (OSC 8)
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Hyperlink {
@@ -88,11 +167,114 @@ impl Hyperlink {
     }
 }
 
+/// Bytes of combining characters stored in the cell itself
+const INLINE_COMBINING: usize = 14;
+
+/// Most distinct long clusters kept in the intern table
+const MAX_INTERNED: usize = 4096;
+
+/// Long clusters seen so far, so repeats share one allocation
+static INTERNED: LazyLock<Mutex<HashSet<Arc<str>>>> = LazyLock::new(Default::default);
+
+/// Characters that follow a cell's base character in its grapheme cluster
+///
+/// Combining accents, variation selectors, skin tone modifiers and the
+/// zero-width-joined parts of emoji sequences. Up to 14 bytes are stored
+/// inline; longer tails go to a process-wide intern table, so a ZWJ family
+/// emoji printed on every prompt is only allocated once.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Combining(CombiningRepr);
+
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+enum CombiningRepr {
+    #[default]
+    Empty,
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_COMBINING],
+    },
+    Interned(Arc<str>),
+}
+
+impl Combining {
+    /// Store `s`, inline if it is short enough
+    pub fn new(s: &str) -> Self {
+        if s.is_empty() {
+            return Self::default();
+        }
+        if s.len() <= INLINE_COMBINING {
+            let mut bytes = [0; INLINE_COMBINING];
+            bytes[..s.len()].copy_from_slice(s.as_bytes());
+            return Self(CombiningRepr::Inline {
+                len: s.len() as u8,
+                bytes,
+            });
+        }
+
+        let mut table = INTERNED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(interned) = table.get(s) {
+            return Self(CombiningRepr::Interned(Arc::clone(interned)));
+        }
+        let interned: Arc<str> = Arc::from(s);
+        if table.len() < MAX_INTERNED {
+            table.insert(Arc::clone(&interned));
+        }
+        Self(CombiningRepr::Interned(interned))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            CombiningRepr::Empty => "",
+            // Only whole strings are copied in, so this is valid UTF-8
+            CombiningRepr::Inline { len, bytes } => {
+                std::str::from_utf8(&bytes[..*len as usize]).unwrap_or_default()
+            }
+            CombiningRepr::Interned(s) => s,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self.0, CombiningRepr::Empty)
+    }
+
+    /// Append one character
+    pub fn push(&mut self, c: char) {
+        let mut s = self.as_str().to_owned();
+        s.push(c);
+        *self = Self::new(&s);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl fmt::Debug for Combining {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Serialize for Combining {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Combining {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::new(&s))
+    }
+}
+
 /// A single terminal cell
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Cell {
-    /// The character in this cell
+    /// The character in this cell (the first of its grapheme cluster)
     pub c: char,
+    /// The rest of the grapheme cluster started by `c`
+    pub combining: Combining,
     /// Foreground color
     pub fg: Color,
     /// Background color
@@ -109,6 +291,7 @@ impl Default for Cell {
     fn default() -> Self {
         Self {
             c: ' ',
+            combining: Combining::default(),
             fg: Color::Default,
             bg: Color::Default,
             underline_color: None,
@@ -132,9 +315,36 @@ impl Cell {
         Self::default()
     }
 
+    /// Set the cell's text to a single character
+    pub fn set_char(&mut self, c: char) {
+        self.c = c;
+        self.combining.clear();
+    }
+
+    /// Set the cell's text to a grapheme cluster, or a space if it is empty
+    pub fn set_grapheme(&mut self, grapheme: &str) {
+        let mut chars = grapheme.chars();
+        self.c = chars.next().unwrap_or(' ');
+        self.combining = Combining::new(chars.as_str());
+    }
+
+    /// The cell's grapheme cluster
+    pub fn grapheme(&self) -> String {
+        let mut s = String::new();
+        self.push_grapheme(&mut s);
+        s
+    }
+
+    /// Append the cell's grapheme cluster to `out`
+    pub fn push_grapheme(&self, out: &mut String) {
+        out.push(self.c);
+        out.push_str(self.combining.as_str());
+    }
+
     /// Check if this cell is empty (space with default colors and no attrs)
     pub fn is_empty(&self) -> bool {
         self.c == ' '
+            && self.combining.is_empty()
             && self.fg == Color::Default
             && self.bg == Color::Default
             && self.attrs.is_empty()
@@ -190,6 +400,7 @@ impl CellStyle {
     pub fn create_cell(&self, c: char) -> Cell {
         Cell {
             c,
+            combining: Combining::default(),
             fg: self.fg,
             bg: self.bg,
             underline_color: self.underline_color,
@@ -225,6 +436,32 @@ mod tests {
         assert!(!cell.is_empty());
     }
 
+    #[test]
+    fn test_cell_grapheme() {
+        let mut cell = Cell::new('e');
+        cell.combining.push('\u{301}');
+        assert_eq!(cell.grapheme(), "e\u{301}");
+        assert!(matches!(
+            cell.combining.0,
+            CombiningRepr::Inline { len: 2, .. }
+        ));
+
+        // A family emoji's tail is too long to store inline
+        let family = "\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}";
+        let a = Combining::new(family);
+        let b = Combining::new(family);
+        match (&a.0, &b.0) {
+            (CombiningRepr::Interned(a), CombiningRepr::Interned(b)) => {
+                assert!(Arc::ptr_eq(a, b))
+            }
+            _ => panic!("long tails should be interned"),
+        }
+        assert_eq!(a.as_str(), family);
+
+        cell.set_char('x');
+        assert_eq!(cell.grapheme(), "x");
+    }
+
     #[test]
     fn test_cell_attrs() {
         let mut attrs = CellAttrs::BOLD | CellAttrs::UNDERLINE;
@@ -249,4 +486,44 @@ mod tests {
         assert_eq!(cell.fg, Color::Ansi(crate::color::AnsiColor::Red));
         assert!(cell.attrs.contains(CellAttrs::BOLD));
     }
+
+    #[test]
+    fn test_attr_overrides() {
+        let overrides = AttrOverrides::default();
+        let attrs = CellAttrs::BLINK | CellAttrs::DIM | CellAttrs::HIDDEN;
+        assert!(overrides.is_default());
+        assert_eq!(overrides.apply(attrs), attrs);
+
+        let overrides = AttrOverrides {
+            blink: AttrSubstitute::Italic,
+            conceal: AttrSubstitute::Off,
+            dim: AttrSubstitute::Underline,
+        };
+        let drawn = overrides.apply(attrs | CellAttrs::BOLD);
+        assert_eq!(
+            drawn,
+            CellAttrs::ITALIC | CellAttrs::UNDERLINE | CellAttrs::BOLD
+        );
+
+        // An existing underline style is kept rather than doubled up
+        let drawn = overrides.apply(CellAttrs::DIM | CellAttrs::CURLY_UNDERLINE);
+        assert_eq!(drawn, CellAttrs::CURLY_UNDERLINE);
+    }
+
+    #[test]
+    fn test_attr_overrides_apply_to_cell() {
+        let overrides = AttrOverrides {
+            conceal: AttrSubstitute::Off,
+            ..Default::default()
+        };
+        let plain = Cell::new('a');
+        assert!(matches!(overrides.apply_to(&plain), Cow::Borrowed(_)));
+
+        let mut hidden = Cell::new('b');
+        hidden.attrs = CellAttrs::HIDDEN;
+        let drawn = overrides.apply_to(&hidden);
+        assert!(drawn.attrs.is_empty());
+        assert_eq!(drawn.c, 'b');
+        assert!(hidden.attrs.contains(CellAttrs::HIDDEN));
+    }
 }
@@ -45,6 +45,248 @@ impl Rgb {
             self.b as f64 / 255.0,
         )
     }
+
+    /// WCAG relative luminance (0.0 = black, 1.0 = white)
+    pub fn relative_luminance(&self) -> f64 {
+        fn linear(c: u8) -> f64 {
+            let c = c as f64 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        }
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// WCAG contrast ratio against another color (1.0 to 21.0)
+    pub fn contrast_ratio(&self, other: &Rgb) -> f64 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+        let (hi, lo) = if a > b { (a, b) } else { (b, a) };
+        (hi + 0.05) / (lo + 0.05)
+    }
+
+    /// Adjust this (foreground) color until it reaches `min_ratio` contrast
+    /// against `background`
+    ///
+    /// The color is blended toward white or black, whichever direction gives
+    /// more contrast, keeping as much of the original hue as possible. Colors
+    /// that already meet the ratio are returned unchanged.
+    pub fn with_min_contrast(self, background: &Rgb, min_ratio: f64) -> Rgb {
+        if self.contrast_ratio(background) >= min_ratio {
+            return self;
+        }
+        let target = if background.relative_luminance() > 0.5 {
+            Rgb::new(0, 0, 0)
+        } else {
+            Rgb::new(255, 255, 255)
+        };
+        let blend = |t: f64| self.blend(&target, t);
+        // Binary search for the smallest blend that meets the ratio
+        let (mut lo, mut hi) = (0.0, 1.0);
+        for _ in 0..12 {
+            let mid = (lo + hi) / 2.0;
+            if blend(mid).contrast_ratio(background) >= min_ratio {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        blend(hi)
+    }
+
+    /// Mix `t` (0.0-1.0) of `other` into this color
+    fn blend(&self, other: &Rgb, t: f64) -> Rgb {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgb::new(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+        )
+    }
+
+    /// Express this sRGB color in Display P3 coordinates
+    ///
+    /// Both spaces share the sRGB transfer curve and white point, so only the
+    /// primaries change. Every sRGB color lies inside the P3 gamut.
+    pub fn srgb_to_display_p3(&self) -> Rgb {
+        self.convert_primaries(&SRGB_TO_DISPLAY_P3)
+    }
+
+    /// Express this Display P3 color in sRGB, clipping anything outside the
+    /// sRGB gamut
+    pub fn display_p3_to_srgb(&self) -> Rgb {
+        self.convert_primaries(&DISPLAY_P3_TO_SRGB)
+    }
+
+    /// How this color appears to someone with the given color vision
+    /// deficiency (Machado et al. 2009, full severity)
+    pub fn simulate_cvd(&self, cvd: ColorVisionDeficiency) -> Rgb {
+        self.convert_primaries(cvd.simulation_matrix())
+    }
+
+    /// Shift the information lost under `cvd` into channels that remain
+    /// visible (daltonization), so colors that collapse together under the
+    /// deficiency move apart again
+    pub fn daltonize(&self, cvd: ColorVisionDeficiency) -> Rgb {
+        let simulated = self.simulate_cvd(cvd);
+        let (r, g, b) = self.to_f64();
+        let (sr, sg, sb) = simulated.to_f64();
+        let error = [r - sr, g - sg, b - sb];
+        let shift = |row: &[f64; 3]| row[0] * error[0] + row[1] * error[1] + row[2] * error[2];
+        let matrix = cvd.correction_matrix();
+        let channel =
+            |c: f64, row: &[f64; 3]| ((c + shift(row)).clamp(0.0, 1.0) * 255.0).round() as u8;
+        Rgb::new(
+            channel(r, &matrix[0]),
+            channel(g, &matrix[1]),
+            channel(b, &matrix[2]),
+        )
+    }
+
+    /// Perceptual distance to another color (CIE76 delta E; about 2.3 is a
+    /// just-noticeable difference)
+    pub fn delta_e(&self, other: &Rgb) -> f64 {
+        let (l1, a1, b1) = self.to_lab();
+        let (l2, a2, b2) = other.to_lab();
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    /// CIELAB coordinates (D65 white point)
+    fn to_lab(self) -> (f64, f64, f64) {
+        let (r, g, b) = (
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+        );
+        let x = (0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b) / 0.950_47;
+        let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175_0 * b;
+        let z = (0.019_333_9 * r + 0.119_192_0 * g + 0.950_304_1 * b) / 1.088_83;
+        let f = |t: f64| {
+            if t > 216.0 / 24389.0 {
+                t.cbrt()
+            } else {
+                (24389.0 / 27.0 * t + 16.0) / 116.0
+            }
+        };
+        let (fx, fy, fz) = (f(x), f(y), f(z));
+        (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+    }
+
+    fn convert_primaries(&self, matrix: &[[f64; 3]; 3]) -> Rgb {
+        let linear = [
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+        ];
+        let channel = |row: &[f64; 3]| {
+            linear_to_srgb(row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2])
+        };
+        Rgb::new(
+            channel(&matrix[0]),
+            channel(&matrix[1]),
+            channel(&matrix[2]),
+        )
+    }
+}
+
+/// Linear sRGB to linear Display P3 (D65)
+const SRGB_TO_DISPLAY_P3: [[f64; 3]; 3] = [
+    [0.822_462_1, 0.177_538_0, 0.0],
+    [0.033_194_1, 0.966_805_8, 0.0],
+    [0.017_082_7, 0.072_397_4, 0.910_519_9],
+];
+
+/// Linear Display P3 to linear sRGB (D65)
+const DISPLAY_P3_TO_SRGB: [[f64; 3]; 3] = [
+    [1.224_940_1, -0.224_940_4, 0.0],
+    [-0.042_056_9, 1.042_057_1, 0.0],
+    [-0.019_637_6, -0.078_636_1, 1.098_273_5],
+];
+
+/// Dichromatic color vision deficiencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorVisionDeficiency {
+    /// No working red cones
+    Protanopia,
+    /// No working green cones (the most common form)
+    Deuteranopia,
+    /// No working blue cones
+    Tritanopia,
+}
+
+impl ColorVisionDeficiency {
+    pub const ALL: [Self; 3] = [Self::Protanopia, Self::Deuteranopia, Self::Tritanopia];
+
+    /// Display name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Protanopia => "Protanopia",
+            Self::Deuteranopia => "Deuteranopia",
+            Self::Tritanopia => "Tritanopia",
+        }
+    }
+
+    /// Linear RGB simulation matrix
+    fn simulation_matrix(self) -> &'static [[f64; 3]; 3] {
+        match self {
+            Self::Protanopia => &PROTANOPIA_SIMULATION,
+            Self::Deuteranopia => &DEUTERANOPIA_SIMULATION,
+            Self::Tritanopia => &TRITANOPIA_SIMULATION,
+        }
+    }
+
+    /// Where [`Rgb::daltonize`] moves the lost signal: into green and blue
+    /// for red-green deficiencies, into red and green for tritanopia
+    fn correction_matrix(self) -> &'static [[f64; 3]; 3] {
+        match self {
+            Self::Protanopia | Self::Deuteranopia => &RED_GREEN_CORRECTION,
+            Self::Tritanopia => &BLUE_YELLOW_CORRECTION,
+        }
+    }
+}
+
+const PROTANOPIA_SIMULATION: [[f64; 3]; 3] = [
+    [0.152_286, 1.052_583, -0.204_868],
+    [0.114_503, 0.786_281, 0.099_216],
+    [-0.003_882, -0.048_116, 1.051_998],
+];
+
+const DEUTERANOPIA_SIMULATION: [[f64; 3]; 3] = [
+    [0.367_322, 0.860_646, -0.227_968],
+    [0.280_085, 0.672_501, 0.047_413],
+    [-0.011_820, 0.042_940, 0.968_881],
+];
+
+const TRITANOPIA_SIMULATION: [[f64; 3]; 3] = [
+    [1.255_528, -0.076_749, -0.178_779],
+    [-0.078_411, 0.930_809, 0.147_602],
+    [0.004_733, 0.691_367, 0.303_900],
+];
+
+const RED_GREEN_CORRECTION: [[f64; 3]; 3] = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+const BLUE_YELLOW_CORRECTION: [[f64; 3]; 3] = [[1.0, 0.0, 0.7], [0.0, 1.0, 0.7], [0.0, 0.0, 0.0]];
+
+fn srgb_to_linear(c: u8) -> f64 {
+    let c = c as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f64) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let encoded = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
 }
 
 /// Standard ANSI colors (0-15)
@@ -159,8 +401,31 @@ impl Color {
     }
 }
 
+/// ANSI palette indices compared for distinguishability: the normal and
+/// bright red..cyan runs
+const CHROMATIC_GROUPS: [[usize; 6]; 2] = [[1, 2, 3, 4, 5, 6], [9, 10, 11, 12, 13, 14]];
+
+/// Whether the terminal shows light text on a dark background or the
+/// other way around, as reported to programs (DSR 996 and mode 2031)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorScheme {
+    Dark,
+    Light,
+}
+
+impl ColorScheme {
+    /// `CSI ? 997 ; Ps n` report of this scheme (1 = dark, 2 = light)
+    pub fn report(self) -> Vec<u8> {
+        let ps = match self {
+            Self::Dark => 1,
+            Self::Light => 2,
+        };
+        format!("\x1b[?997;{}n", ps).into_bytes()
+    }
+}
+
 /// Color palette for rendering
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ColorPalette {
     /// 16 ANSI colors
     pub ansi: [Rgb; 16],
@@ -181,6 +446,93 @@ impl Default for ColorPalette {
 }
 
 impl ColorPalette {
+    /// Light when the background is brighter than the foreground
+    pub fn color_scheme(&self) -> ColorScheme {
+        if self.background.relative_luminance() > self.foreground.relative_luminance() {
+            ColorScheme::Light
+        } else {
+            ColorScheme::Dark
+        }
+    }
+
+    /// The same palette expressed in Display P3 coordinates
+    pub fn to_display_p3(&self) -> Self {
+        Self {
+            ansi: self.ansi.map(|c| c.srgb_to_display_p3()),
+            foreground: self.foreground.srgb_to_display_p3(),
+            background: self.background.srgb_to_display_p3(),
+            cursor: self.cursor.srgb_to_display_p3(),
+            selection: self.selection.srgb_to_display_p3(),
+        }
+    }
+
+    /// The palette as it appears under `cvd`
+    pub fn simulate_cvd(&self, cvd: ColorVisionDeficiency) -> Self {
+        Self {
+            ansi: self.ansi.map(|c| c.simulate_cvd(cvd)),
+            foreground: self.foreground.simulate_cvd(cvd),
+            background: self.background.simulate_cvd(cvd),
+            cursor: self.cursor.simulate_cvd(cvd),
+            selection: self.selection.simulate_cvd(cvd),
+        }
+    }
+
+    /// Smallest delta E between two chromatic ANSI colors of the same
+    /// intensity (red..cyan, or bright red..bright cyan) as seen under `cvd`
+    pub fn min_cvd_distance(&self, cvd: ColorVisionDeficiency) -> f64 {
+        let simulated = self.ansi.map(|c| c.simulate_cvd(cvd));
+        CHROMATIC_GROUPS
+            .iter()
+            .flat_map(|group| {
+                group
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(n, &i)| group[n + 1..].iter().map(move |&j| (i, j)))
+            })
+            .map(|(i, j)| simulated[i].delta_e(&simulated[j]))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// A copy of this palette whose chromatic ANSI colors stay apart under
+    /// `cvd`
+    ///
+    /// Each color is daltonized first. Pairs that are still closer than
+    /// `min_delta_e` when simulated are then separated by lightness, moving
+    /// the later color of the pair away from the background. Black, white,
+    /// foreground and background are left unchanged.
+    pub fn adjusted_for_cvd(&self, cvd: ColorVisionDeficiency, min_delta_e: f64) -> Self {
+        let mut adjusted = self.clone();
+        for group in &CHROMATIC_GROUPS {
+            for &i in group {
+                adjusted.ansi[i] = self.ansi[i].daltonize(cvd);
+            }
+        }
+
+        let target = if self.background.relative_luminance() > 0.5 {
+            Rgb::new(0, 0, 0)
+        } else {
+            Rgb::new(255, 255, 255)
+        };
+        for group in &CHROMATIC_GROUPS {
+            for (n, &j) in group.iter().enumerate() {
+                for &i in &group[..n] {
+                    let base = adjusted.ansi[j];
+                    let mut step = 0;
+                    while step < 10
+                        && adjusted.ansi[i]
+                            .simulate_cvd(cvd)
+                            .delta_e(&adjusted.ansi[j].simulate_cvd(cvd))
+                            < min_delta_e
+                    {
+                        step += 1;
+                        adjusted.ansi[j] = base.blend(&target, step as f64 * 0.06);
+                    }
+                }
+            }
+        }
+        adjusted
+    }
+
     /// Default dark theme palette
     pub fn default_dark() -> Self {
         Self {
@@ -304,4 +656,116 @@ mod tests {
         assert_eq!(gray.r, gray.g);
         assert_eq!(gray.g, gray.b);
     }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = Rgb::new(0, 0, 0);
+        let white = Rgb::new(255, 255, 255);
+        assert!((black.contrast_ratio(&white) - 21.0).abs() < 0.01);
+        assert!((white.contrast_ratio(&white) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_with_min_contrast() {
+        let bg = Rgb::new(0x1e, 0x1e, 0x1e);
+        let dim = Rgb::new(0x40, 0x40, 0x60);
+        let fixed = dim.with_min_contrast(&bg, 4.5);
+        assert!(fixed.contrast_ratio(&bg) >= 4.5);
+        // Lightened, not darkened, on a dark background
+        assert!(fixed.relative_luminance() > dim.relative_luminance());
+
+        // Already readable colors are left alone
+        let white = Rgb::new(255, 255, 255);
+        assert_eq!(white.with_min_contrast(&bg, 4.5), white);
+
+        // Light backgrounds push toward black
+        let light_bg = Rgb::new(0xf0, 0xf0, 0xf0);
+        let pale = Rgb::new(0xc0, 0xc0, 0xc0);
+        let fixed = pale.with_min_contrast(&light_bg, 4.5);
+        assert!(fixed.contrast_ratio(&light_bg) >= 4.5);
+        assert!(fixed.relative_luminance() < pale.relative_luminance());
+    }
+
+    #[test]
+    fn test_display_p3_conversion() {
+        // Black, white and grays sit on the shared neutral axis
+        for gray in [0u8, 0x80, 0xff] {
+            let c = Rgb::new(gray, gray, gray);
+            assert_eq!(c.srgb_to_display_p3(), c);
+            assert_eq!(c.display_p3_to_srgb(), c);
+        }
+
+        // Pure sRGB red is a less saturated color in P3
+        assert_eq!(
+            Rgb::new(255, 0, 0).srgb_to_display_p3(),
+            Rgb::new(0xea, 0x33, 0x23)
+        );
+
+        // Round trips through P3 stay within rounding error
+        let c = Rgb::new(0x7a, 0xa2, 0xf7);
+        let back = c.srgb_to_display_p3().display_p3_to_srgb();
+        assert!((back.r as i16 - c.r as i16).abs() <= 1);
+        assert!((back.g as i16 - c.g as i16).abs() <= 1);
+        assert!((back.b as i16 - c.b as i16).abs() <= 1);
+
+        // P3 red lies outside sRGB and gets clipped
+        assert_eq!(
+            Rgb::new(255, 0, 0).display_p3_to_srgb(),
+            Rgb::new(255, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_cvd_simulation() {
+        // Neutral colors look the same to everyone
+        for cvd in ColorVisionDeficiency::ALL {
+            for gray in [0u8, 0x80, 0xff] {
+                let c = Rgb::new(gray, gray, gray);
+                let sim = c.simulate_cvd(cvd);
+                assert!((sim.r as i16 - gray as i16).abs() <= 1, "{:?}", cvd);
+                assert!((sim.b as i16 - gray as i16).abs() <= 1, "{:?}", cvd);
+            }
+        }
+
+        // Red and green collapse together without green cones, but not
+        // without blue cones
+        let red = Rgb::new(0xcc, 0x44, 0x44);
+        let green = Rgb::new(0x44, 0xaa, 0x44);
+        let normal = red.delta_e(&green);
+        let deutan = red
+            .simulate_cvd(ColorVisionDeficiency::Deuteranopia)
+            .delta_e(&green.simulate_cvd(ColorVisionDeficiency::Deuteranopia));
+        let tritan = red
+            .simulate_cvd(ColorVisionDeficiency::Tritanopia)
+            .delta_e(&green.simulate_cvd(ColorVisionDeficiency::Tritanopia));
+        assert!(deutan < normal / 2.0, "{} vs {}", deutan, normal);
+        assert!(tritan > deutan);
+    }
+
+    #[test]
+    fn test_delta_e() {
+        let c = Rgb::new(0x12, 0x34, 0x56);
+        assert_eq!(c.delta_e(&c), 0.0);
+        let black = Rgb::new(0, 0, 0);
+        let white = Rgb::new(255, 255, 255);
+        assert!((black.delta_e(&white) - 100.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_palette_adjusted_for_cvd() {
+        let palette = ColorPalette::default_dark();
+        for cvd in ColorVisionDeficiency::ALL {
+            let before = palette.min_cvd_distance(cvd);
+            let adjusted = palette.adjusted_for_cvd(cvd, 15.0);
+            let after = adjusted.min_cvd_distance(cvd);
+            assert!(after > before, "{:?}: {} -> {}", cvd, before, after);
+
+            // Only the chromatic ANSI colors change
+            assert_eq!(adjusted.ansi[0], palette.ansi[0]);
+            assert_eq!(adjusted.ansi[7], palette.ansi[7]);
+            assert_eq!(adjusted.ansi[15], palette.ansi[15]);
+            assert_eq!(adjusted.foreground, palette.foreground);
+            assert_eq!(adjusted.background, palette.background);
+        }
+    }
 }
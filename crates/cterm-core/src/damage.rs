@@ -0,0 +1,295 @@
+//! Damage tracking
+//!
+//! [`Screen`](crate::screen::Screen) records which visible rows changed, how
+//! regions scrolled and where the cursor moved, so a renderer can repaint only
+//! what changed. [`Screen::take_damage`](crate::screen::Screen::take_damage)
+//! returns everything since the previous call; a screen has a single damage
+//! consumer.
+//!
+//! Row ranges refer to the grid after the scrolls have been applied. A
+//! renderer that keeps its previous frame replays [`Damage::scrolls`] (moving
+//! the pixels) and then repaints [`Damage::rows`]; one that does not can
+//! repaint [`Damage::redraw_rows`] instead.
+
+use std::ops::Range;
+
+/// Consecutive scrolls recorded before everything is considered damaged
+const MAX_SCROLLS: usize = 16;
+
+/// Lines of a region moving up (positive) or down (negative)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollDelta {
+    /// First row of the region
+    pub top: usize,
+    /// Row after the last row of the region
+    pub bottom: usize,
+    /// Lines scrolled; positive moves content up
+    pub lines: isize,
+}
+
+/// Where the cursor was drawn and where it is now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorMove {
+    /// (row, col) at the previous [`take_damage`](crate::screen::Screen::take_damage)
+    pub from: (usize, usize),
+    /// Current (row, col)
+    pub to: (usize, usize),
+}
+
+/// Changes since the damage was last taken
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Damage {
+    /// Everything must be redrawn; the other fields are empty
+    pub full: bool,
+    /// Changed rows, sorted and non-overlapping
+    pub rows: Vec<Range<usize>>,
+    /// Scrolls, in the order they happened
+    pub scrolls: Vec<ScrollDelta>,
+    /// Cursor movement or a change of its shape or visibility
+    pub cursor: Option<CursorMove>,
+}
+
+impl Damage {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        !self.full && self.rows.is_empty() && self.scrolls.is_empty() && self.cursor.is_none()
+    }
+
+    /// Rows to repaint for a renderer that does not replay scrolls: the
+    /// damaged rows, the scrolled regions and the old and new cursor rows
+    pub fn redraw_rows(&self, height: usize) -> Vec<Range<usize>> {
+        let mut rows = RowFlags::new(height);
+        if self.full {
+            rows.mark(0..height);
+            return rows.ranges();
+        }
+        for range in &self.rows {
+            rows.mark(range.clone());
+        }
+        for scroll in &self.scrolls {
+            rows.mark(scroll.top..scroll.bottom);
+        }
+        if let Some(cursor) = self.cursor {
+            rows.mark(cursor.from.0..cursor.from.0 + 1);
+            rows.mark(cursor.to.0..cursor.to.0 + 1);
+        }
+        rows.ranges()
+    }
+}
+
+/// One dirty flag per visible row
+#[derive(Debug, Clone, Default)]
+struct RowFlags(Vec<bool>);
+
+impl RowFlags {
+    fn new(height: usize) -> Self {
+        Self(vec![false; height])
+    }
+
+    fn mark(&mut self, rows: Range<usize>) {
+        let end = rows.end.min(self.0.len());
+        if rows.start < end {
+            self.0[rows.start..end].fill(true);
+        }
+    }
+
+    fn any(&self) -> bool {
+        self.0.contains(&true)
+    }
+
+    fn ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (row, _) in self.0.iter().enumerate().filter(|(_, &dirty)| dirty) {
+            match ranges.last_mut() {
+                Some(last) if last.end == row => last.end = row + 1,
+                _ => ranges.push(row..row + 1),
+            }
+        }
+        ranges
+    }
+}
+
+/// Damage accumulated by a screen between calls to `take_damage`
+#[derive(Debug, Clone)]
+pub(crate) struct DamageTracker {
+    full: bool,
+    rows: RowFlags,
+    scrolls: Vec<ScrollDelta>,
+}
+
+impl Default for DamageTracker {
+    /// Everything damaged, whatever the height
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl DamageTracker {
+    /// A tracker for `height` rows; everything starts damaged
+    pub fn new(height: usize) -> Self {
+        Self {
+            full: true,
+            rows: RowFlags::new(height),
+            scrolls: Vec::new(),
+        }
+    }
+
+    /// Damage everything, e.g. after a resize or screen switch
+    pub fn all(&mut self) {
+        self.full = true;
+    }
+
+    /// Damage `rows`
+    pub fn rows(&mut self, rows: Range<usize>) {
+        if !self.full {
+            self.rows.mark(rows);
+        }
+    }
+
+    /// Damage a single row
+    pub fn row(&mut self, row: usize) {
+        self.rows(row..row + 1);
+    }
+
+    /// Record rows `top..bottom` moving up by `lines` (down if negative)
+    ///
+    /// Dirty flags move with their rows and the rows scrolled in are damaged,
+    /// so the flags keep describing the grid after the scroll.
+    pub fn scroll(&mut self, top: usize, bottom: usize, lines: isize) {
+        let bottom = bottom.min(self.rows.0.len());
+        if self.full || lines == 0 || top >= bottom {
+            return;
+        }
+        let count = lines.unsigned_abs();
+        if count >= bottom - top {
+            self.rows(top..bottom);
+            return;
+        }
+
+        let region = &mut self.rows.0[top..bottom];
+        if lines > 0 {
+            region.rotate_left(count);
+            self.rows(bottom - count..bottom);
+        } else {
+            region.rotate_right(count);
+            self.rows(top..top + count);
+        }
+
+        let recorded = self.scrolls.len();
+        match self.scrolls.last_mut() {
+            Some(last)
+                if last.top == top
+                    && last.bottom == bottom
+                    && last.lines.signum() == lines.signum() =>
+            {
+                last.lines += lines;
+                if last.lines.unsigned_abs() >= bottom - top {
+                    // Nothing of the region survives; just repaint it
+                    self.scrolls.pop();
+                }
+            }
+            _ if recorded >= MAX_SCROLLS => self.full = true,
+            _ => self.scrolls.push(ScrollDelta { top, bottom, lines }),
+        }
+    }
+
+    /// Whether any rows were damaged since the last take
+    pub fn is_damaged(&self) -> bool {
+        self.full || self.rows.any() || !self.scrolls.is_empty()
+    }
+
+    /// Take the accumulated damage, resetting the tracker for `height` rows
+    pub fn take(&mut self, height: usize, cursor: Option<CursorMove>) -> Damage {
+        let tracker = std::mem::replace(
+            self,
+            Self {
+                full: false,
+                rows: RowFlags::new(height),
+                scrolls: Vec::new(),
+            },
+        );
+        if tracker.full || tracker.rows.0.len() != height {
+            return Damage {
+                full: true,
+                ..Default::default()
+            };
+        }
+        Damage {
+            full: false,
+            rows: tracker.rows.ranges(),
+            scrolls: tracker.scrolls,
+            cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(rows: &[Range<usize>]) -> Vec<(usize, usize)> {
+        rows.iter().map(|r| (r.start, r.end)).collect()
+    }
+
+    fn taken(mut tracker: DamageTracker) -> Damage {
+        let height = tracker.rows.0.len();
+        tracker.take(height, None)
+    }
+
+    #[test]
+    fn test_rows_and_scrolls() {
+        let mut tracker = DamageTracker::new(10);
+        assert!(taken(tracker.clone()).full);
+        tracker.take(10, None);
+
+        tracker.row(2);
+        tracker.row(3);
+        tracker.row(7);
+        // Three lines scroll up: row 7 moves to 4, rows 7..10 are new
+        tracker.scroll(0, 10, 1);
+        tracker.scroll(0, 10, 2);
+        let damage = taken(tracker);
+        assert_eq!(spans(&damage.rows), [(0, 1), (4, 5), (7, 10)]);
+        assert_eq!(
+            damage.scrolls,
+            [ScrollDelta {
+                top: 0,
+                bottom: 10,
+                lines: 3
+            }]
+        );
+        assert_eq!(spans(&damage.redraw_rows(10)), [(0, 10)]);
+    }
+
+    #[test]
+    fn test_scroll_down_in_region() {
+        let mut tracker = DamageTracker::new(10);
+        tracker.take(10, None);
+        tracker.row(3);
+        tracker.scroll(2, 6, -1);
+        let damage = taken(tracker);
+        assert_eq!(spans(&damage.rows), [(2, 3), (4, 5)]);
+        assert_eq!(spans(&damage.redraw_rows(10)), [(2, 6)]);
+
+        // Scrolling a whole region away needs no delta
+        let mut tracker = DamageTracker::new(10);
+        tracker.take(10, None);
+        tracker.scroll(0, 4, 4);
+        let damage = taken(tracker);
+        assert_eq!(spans(&damage.rows), [(0, 4)]);
+        assert!(damage.scrolls.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_rows_redrawn() {
+        let damage = Damage {
+            cursor: Some(CursorMove {
+                from: (1, 4),
+                to: (3, 0),
+            }),
+            ..Default::default()
+        };
+        assert!(!damage.is_empty());
+        assert_eq!(spans(&damage.redraw_rows(5)), [(1, 2), (3, 4)]);
+    }
+}
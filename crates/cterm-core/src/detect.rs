@@ -0,0 +1,413 @@
+//! Detection of URLs, file paths, git SHAs and IP addresses in terminal text
+//!
+//! [`detect`] scans scrollback and visible rows and returns typed spans in
+//! absolute line coordinates (0 = oldest scrollback line). Soft-wrapped rows
+//! are joined first, so a long URL broken across rows is a single span, and
+//! OSC 8 hyperlinks are reported as URLs. The frontends use [`span_at`] to
+//! find what is under the mouse for Ctrl/Cmd-click, and hint mode labels the
+//! spans on the visible screen.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::ops::Range;
+use std::sync::{Arc, LazyLock};
+
+use regex::Regex;
+
+use crate::cell::Hyperlink;
+use crate::screen::Screen;
+
+static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:https?|ftp|file)://[^\s<>"'`]+|www\.[^\s<>"'`]+\.[^\s<>"'`]+"#).unwrap()
+});
+static PATH_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"((?:~|[\w.\-]+)?(?:/[\w.@+\-]+)+/?)(?::(\d+)(?::(\d+))?)?").unwrap()
+});
+static IPV4_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\d{1,3}(?:\.\d{1,3}){3}\b").unwrap());
+static IPV6_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[0-9A-Fa-f:]{3,39}").unwrap());
+static SHA_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[0-9a-f]{7,64}\b").unwrap());
+
+/// What a detected span is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    Url,
+    Path,
+    GitSha,
+    IpAddress,
+}
+
+/// A URL, path, SHA or IP address found in the terminal text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedSpan {
+    pub kind: SpanKind,
+    /// Detected text: the URI for OSC 8 hyperlinks, and paths without their
+    /// `:line:col` suffix
+    pub text: String,
+    /// Absolute line and column of the first cell
+    pub line: usize,
+    pub col: usize,
+    /// Absolute line and column just past the last cell, after `line` when
+    /// the span continues onto soft-wrapped rows
+    pub end_line: usize,
+    pub end_col: usize,
+    /// Line number following a path (`src/main.rs:42`)
+    pub file_line: Option<u32>,
+    /// Column number following the line number (`src/main.rs:42:7`)
+    pub file_column: Option<u32>,
+}
+
+impl DetectedSpan {
+    /// Whether the cell at absolute `line` and `col` is part of the span
+    pub fn contains(&self, line: usize, col: usize) -> bool {
+        (line, col) >= (self.line, self.col) && (line, col) < (self.end_line, self.end_col)
+    }
+
+    /// What to hand to the system opener, or `None` for SHAs and addresses
+    ///
+    /// `www.` URLs get an `https://` scheme, `~` expands to the home directory
+    /// and relative paths are resolved against `cwd` (the shell's working
+    /// directory, when known).
+    pub fn open_target(&self, cwd: Option<&str>) -> Option<String> {
+        match self.kind {
+            SpanKind::Url => Some(url_target(&self.text)),
+            SpanKind::Path => Some(path_target(&self.text, cwd)),
+            SpanKind::GitSha | SpanKind::IpAddress => None,
+        }
+    }
+}
+
+/// Find the spans in the logical lines overlapping the absolute `lines`
+///
+/// Spans are ordered by position. A logical line that starts above
+/// `lines.start` (the first row is a wrapped continuation) is scanned from
+/// its start, so spans can begin before the range.
+pub fn detect(screen: &Screen, lines: Range<usize>) -> Vec<DetectedSpan> {
+    let mut spans = Vec::new();
+    let total = screen.total_lines();
+    let mut line = lines.start;
+    while line > 0 && is_continuation(screen, line) {
+        line -= 1;
+    }
+
+    let mut text = LineText::default();
+    while line < total && (line < lines.end || is_continuation(screen, line)) {
+        if !is_continuation(screen, line) && !text.text.is_empty() {
+            text.scan(&mut spans);
+            text = LineText::default();
+        }
+        text.push_row(screen, line);
+        line += 1;
+    }
+    text.scan(&mut spans);
+    spans
+}
+
+/// Find the spans on the visible screen
+pub fn detect_visible(screen: &Screen) -> Vec<DetectedSpan> {
+    let first = screen.visible_row_to_absolute_line(0);
+    detect(screen, first..first + screen.height())
+}
+
+/// The span covering the cell at absolute `line` and `col`, if any
+pub fn span_at(screen: &Screen, line: usize, col: usize) -> Option<DetectedSpan> {
+    detect(screen, line..line + 1)
+        .into_iter()
+        .find(|span| span.contains(line, col))
+}
+
+/// URL to open for a detected URL
+pub(crate) fn url_target(text: &str) -> String {
+    if text.starts_with("www.") {
+        format!("https://{}", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Absolute path to open for a detected path
+pub(crate) fn path_target(text: &str, cwd: Option<&str>) -> String {
+    let home = std::env::var("HOME").ok();
+    match (text.strip_prefix('~'), home, cwd) {
+        (Some(rest), Some(home), _) => format!("{home}{rest}"),
+        (None, _, Some(cwd)) if !text.starts_with('/') => {
+            format!("{}/{}", cwd.trim_end_matches('/'), text)
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Whether absolute `line` continues the row above it
+fn is_continuation(screen: &Screen, line: usize) -> bool {
+    screen
+        .get_row_by_absolute_line(line)
+        .is_some_and(|row| row.wrapped)
+}
+
+/// Text of one logical line with the cell each byte came from
+#[derive(Default)]
+struct LineText {
+    text: String,
+    /// Line, first column and end column of the cell of each byte
+    cells: Vec<(usize, usize, usize)>,
+    /// Byte ranges of OSC 8 hyperlinks
+    links: Vec<(Range<usize>, Arc<Hyperlink>)>,
+}
+
+impl LineText {
+    fn push_row(&mut self, screen: &Screen, line: usize) {
+        let Some(row) = screen.get_row_by_absolute_line(line) else {
+            return;
+        };
+        for (col, cell) in row.iter().enumerate() {
+            if cell.is_wide_spacer() {
+                continue;
+            }
+            let width = if cell.is_wide() { 2 } else { 1 };
+            let start = self.text.len();
+            if cell.c == '\0' {
+                self.text.push(' ');
+            } else {
+                cell.push_grapheme(&mut self.text);
+            }
+            let end = self.text.len();
+            self.cells
+                .extend(std::iter::repeat_n((line, col, col + width), end - start));
+
+            match (&cell.hyperlink, self.links.last_mut()) {
+                (Some(link), Some((range, last)))
+                    if Arc::ptr_eq(link, last) && range.end == start =>
+                {
+                    range.end = end;
+                }
+                (Some(link), _) => self.links.push((start..end, Arc::clone(link))),
+                (None, _) => {}
+            }
+        }
+    }
+
+    /// Add the spans of this line to `spans`
+    fn scan(&self, spans: &mut Vec<DetectedSpan>) {
+        let text = self.text.as_str();
+        // Byte ranges already claimed, so a path inside a URL is not
+        // reported twice
+        let mut taken: Vec<Range<usize>> = Vec::new();
+        let mut found = Vec::new();
+        let mut claim =
+            |kind, range: Range<usize>, value: &str, position: (Option<u32>, Option<u32>)| {
+                if range.is_empty()
+                    || taken
+                        .iter()
+                        .any(|t| range.start < t.end && t.start < range.end)
+                {
+                    return;
+                }
+                taken.push(range.clone());
+                found.push(self.span(kind, range, value, position));
+            };
+
+        for (range, link) in &self.links {
+            claim(SpanKind::Url, range.clone(), &link.uri, (None, None));
+        }
+
+        for m in URL_RE.find_iter(text) {
+            let url = trim_trailing_punctuation(m.as_str());
+            claim(
+                SpanKind::Url,
+                m.start()..m.start() + url.len(),
+                url,
+                (None, None),
+            );
+        }
+
+        for caps in PATH_RE.captures_iter(text) {
+            let (Some(all), Some(path)) = (caps.get(0), caps.get(1)) else {
+                continue;
+            };
+            let file_line = caps.get(2).and_then(|m| m.as_str().parse().ok());
+            let file_column = caps.get(3).and_then(|m| m.as_str().parse().ok());
+            // Sentence punctuation only needs trimming without a suffix
+            let (value, end) = if file_line.is_some() {
+                (path.as_str(), all.end())
+            } else {
+                let value = trim_trailing_punctuation(path.as_str());
+                (value, path.start() + value.len())
+            };
+            if looks_like_path(value) {
+                claim(
+                    SpanKind::Path,
+                    all.start()..end,
+                    value,
+                    (file_line, file_column),
+                );
+            }
+        }
+
+        for m in IPV4_RE.find_iter(text) {
+            // Skip runs of more than four numbers, like version strings
+            let before = text[..m.start()].chars().next_back();
+            let mut after = text[m.end()..].chars();
+            let longer = before == Some('.')
+                || (after.next() == Some('.') && after.next().is_some_and(|c| c.is_ascii_digit()));
+            if !longer && m.as_str().parse::<Ipv4Addr>().is_ok() {
+                claim(SpanKind::IpAddress, m.range(), m.as_str(), (None, None));
+            }
+        }
+
+        for m in IPV6_RE.find_iter(text) {
+            let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+            let bordered = !text[..m.start()].chars().next_back().is_some_and(is_word)
+                && !text[m.end()..].chars().next().is_some_and(is_word);
+            let valid = m.as_str().matches(':').count() >= 2
+                && m.as_str()
+                    .parse::<Ipv6Addr>()
+                    .is_ok_and(|addr| !addr.is_unspecified());
+            if bordered && valid {
+                claim(SpanKind::IpAddress, m.range(), m.as_str(), (None, None));
+            }
+        }
+
+        for m in SHA_RE.find_iter(text) {
+            // Plain numbers and words like "deadbeef" are not hashes
+            let sha = m.as_str();
+            if sha.bytes().any(|b| b.is_ascii_digit())
+                && sha.bytes().any(|b| b.is_ascii_alphabetic())
+            {
+                claim(SpanKind::GitSha, m.range(), sha, (None, None));
+            }
+        }
+
+        found.sort_by_key(|span: &DetectedSpan| (span.line, span.col));
+        spans.extend(found);
+    }
+
+    fn span(
+        &self,
+        kind: SpanKind,
+        range: Range<usize>,
+        value: &str,
+        (file_line, file_column): (Option<u32>, Option<u32>),
+    ) -> DetectedSpan {
+        let (line, col, _) = self.cells[range.start];
+        let (end_line, _, end_col) = self.cells[range.end - 1];
+        DetectedSpan {
+            kind,
+            text: value.to_string(),
+            line,
+            col,
+            end_line,
+            end_col,
+            file_line,
+            file_column,
+        }
+    }
+}
+
+/// Drop sentence punctuation and unbalanced closing brackets from a match
+fn trim_trailing_punctuation(s: &str) -> &str {
+    let mut s = s;
+    loop {
+        let Some(last) = s.chars().last() else {
+            return s;
+        };
+        let unbalanced = match last {
+            ')' => s.matches('(').count() < s.matches(')').count(),
+            ']' => s.matches('[').count() < s.matches(']').count(),
+            '}' => s.matches('{').count() < s.matches('}').count(),
+            '.' | ',' | ';' | ':' | '!' | '?' | '\'' | '"' => true,
+            _ => false,
+        };
+        if !unbalanced {
+            return s;
+        }
+        s = &s[..s.len() - last.len_utf8()];
+    }
+}
+
+/// Reject a lone `/` and similar fragments that the path pattern allows
+fn looks_like_path(s: &str) -> bool {
+    s.trim_start_matches(['~', '.'])
+        .trim_matches('/')
+        .chars()
+        .any(|c| c.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::screen::ScreenConfig;
+
+    fn screen_with(width: usize, text: &[u8]) -> Screen {
+        let mut screen = Screen::new(width, 5, ScreenConfig::default());
+        Parser::new().parse(&mut screen, text);
+        screen
+    }
+
+    fn found(screen: &Screen) -> Vec<(SpanKind, String, usize, usize)> {
+        detect_visible(screen)
+            .into_iter()
+            .map(|span| (span.kind, span.text, span.line, span.col))
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_kinds() {
+        let screen = screen_with(
+            80,
+            b"see https://example.com/a_(b)). at 10.0.0.1, not 1.2.3.4.5\r\n\
+              --> src/main.rs:42:7 and fe80::1 from 3f2a9c1d, not 1234567\r\n",
+        );
+        assert_eq!(
+            found(&screen),
+            [
+                (SpanKind::Url, "https://example.com/a_(b)".into(), 0, 4),
+                (SpanKind::IpAddress, "10.0.0.1".into(), 0, 35),
+                (SpanKind::Path, "src/main.rs".into(), 1, 4),
+                (SpanKind::IpAddress, "fe80::1".into(), 1, 25),
+                (SpanKind::GitSha, "3f2a9c1d".into(), 1, 38),
+            ]
+        );
+
+        let path = &detect_visible(&screen)[2];
+        assert_eq!((path.file_line, path.file_column), (Some(42), Some(7)));
+        // The suffix is part of the span
+        assert_eq!((path.end_line, path.end_col), (1, 20));
+    }
+
+    #[test]
+    fn test_detect_across_wrapped_rows() {
+        let screen = screen_with(10, b"go https://a.io/xyz now\r\n");
+        let spans = detect_visible(&screen);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "https://a.io/xyz");
+        assert_eq!((spans[0].line, spans[0].col), (0, 3));
+        assert_eq!((spans[0].end_line, spans[0].end_col), (1, 9));
+
+        // Either row of the URL finds it
+        assert_eq!(span_at(&screen, 1, 2).map(|s| s.col), Some(3));
+        assert!(span_at(&screen, 0, 1).is_none());
+        assert!(span_at(&screen, 2, 0).is_none());
+    }
+
+    #[test]
+    fn test_open_target() {
+        let screen = screen_with(
+            80,
+            b"\x1b]8;;https://cterm.dev\x1b\\docs\x1b]8;;\x1b\\ www.example.com src/lib.rs\r\n",
+        );
+        let cwd = Some("/home/me/");
+        let targets: Vec<_> = detect_visible(&screen)
+            .iter()
+            .map(|span| span.open_target(cwd))
+            .collect();
+        assert_eq!(
+            targets,
+            [
+                Some("https://cterm.dev".into()),
+                Some("https://www.example.com".into()),
+                Some("/home/me/src/lib.rs".into()),
+            ]
+        );
+        assert_eq!(span_at(&screen, 0, 2).map(|s| s.end_col), Some(4));
+    }
+}
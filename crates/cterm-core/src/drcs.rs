@@ -5,8 +5,10 @@
 
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 /// A single DRCS glyph (character bitmap)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DrcsGlyph {
     /// Bitmap data (1 bit per pixel, row-major)
     /// Each byte contains 8 horizontal pixels
@@ -56,7 +58,7 @@ impl DrcsGlyph {
 }
 
 /// A DRCS font (collection of glyphs)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DrcsFont {
     /// Font number (0-2)
     pub font_number: u8,
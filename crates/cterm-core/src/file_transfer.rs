@@ -0,0 +1,216 @@
+//! File transfers over the terminal's own byte stream
+//!
+//! [`detect`] spots a remote program starting a transfer in the output: `sz`
+//! or `rz` for [ZMODEM](crate::zmodem), `kermit -s` or `kermit -r` for
+//! [Kermit](crate::kermit). From there a [`FileTransfer`] consumes the output
+//! instead of the parser until the transfer ends. Both protocols report the
+//! same [`TransferEvent`]s, so frontends handle them alike.
+
+use std::path::Path;
+
+use crate::kermit::{self, Kermit};
+use crate::zmodem::{self, Zmodem};
+
+/// Largest file accepted when receiving
+pub const MAX_FILE_SIZE: u64 = 1 << 30;
+
+/// Protocol of a transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferProtocol {
+    Zmodem,
+    Kermit,
+}
+
+impl TransferProtocol {
+    /// Name shown to the user
+    pub fn name(self) -> &'static str {
+        match self {
+            TransferProtocol::Zmodem => "ZMODEM",
+            TransferProtocol::Kermit => "Kermit",
+        }
+    }
+}
+
+/// Which way files go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    /// The remote sends (`sz`, `kermit -s`); files come to us
+    Receive,
+    /// The remote receives (`rz`, `kermit -r`); files go from us
+    Send,
+}
+
+/// A file to send
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferFile {
+    /// Name offered to the receiver (without directories)
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+impl TransferFile {
+    /// Read a local file to send under its own name
+    pub fn from_path(path: &Path) -> std::io::Result<Self> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Self {
+            name,
+            data: std::fs::read(path)?,
+        })
+    }
+}
+
+/// Something the frontend should act on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferEvent {
+    /// The remote is waiting for files
+    SendRequested,
+    /// A file was received completely
+    Received { name: String, data: Vec<u8> },
+}
+
+/// Find the start of a transfer in either protocol, whichever comes first.
+/// Returns where it begins, the protocol and which way files go.
+pub fn detect(data: &[u8]) -> Option<(usize, TransferProtocol, TransferDirection)> {
+    let zmodem =
+        zmodem::detect(data).map(|(start, direction)| (start, TransferProtocol::Zmodem, direction));
+    let kermit =
+        kermit::detect(data).map(|(start, direction)| (start, TransferProtocol::Kermit, direction));
+    zmodem
+        .into_iter()
+        .chain(kermit)
+        .min_by_key(|&(start, ..)| start)
+}
+
+/// Drop the transfer start frames (and retries of them) from `data`, keeping
+/// the regular output around them. Used for output that waited with a
+/// transfer that was then refused.
+pub fn strip_start_frames(mut data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len());
+    while let Some((start, ..)) = detect(data) {
+        output.extend_from_slice(&data[..start]);
+        // Both protocols end their start frames with a line ending
+        let Some(end) = data[start..]
+            .iter()
+            .position(|b| matches!(b, b'\r' | b'\n'))
+        else {
+            return output;
+        };
+        let rest = &data[start + end..];
+        let skip = rest
+            .iter()
+            .take_while(|b| matches!(b, b'\r' | b'\n' | 0x8a | 0x11))
+            .count();
+        data = &rest[skip..];
+    }
+    output.extend_from_slice(data);
+    output
+}
+
+/// One transfer in either protocol
+#[derive(Debug)]
+pub enum FileTransfer {
+    Zmodem(Zmodem),
+    Kermit(Kermit),
+}
+
+impl FileTransfer {
+    pub fn new(protocol: TransferProtocol, direction: TransferDirection) -> Self {
+        match protocol {
+            TransferProtocol::Zmodem => FileTransfer::Zmodem(Zmodem::new(direction)),
+            TransferProtocol::Kermit => FileTransfer::Kermit(Kermit::new(direction)),
+        }
+    }
+
+    pub fn protocol(&self) -> TransferProtocol {
+        match self {
+            FileTransfer::Zmodem(_) => TransferProtocol::Zmodem,
+            FileTransfer::Kermit(_) => TransferProtocol::Kermit,
+        }
+    }
+
+    pub fn direction(&self) -> TransferDirection {
+        match self {
+            FileTransfer::Zmodem(transfer) => transfer.direction(),
+            FileTransfer::Kermit(transfer) => transfer.direction(),
+        }
+    }
+
+    /// Whether the transfer is over
+    pub fn is_done(&self) -> bool {
+        match self {
+            FileTransfer::Zmodem(transfer) => transfer.is_done(),
+            FileTransfer::Kermit(transfer) => transfer.is_done(),
+        }
+    }
+
+    /// Bytes to write to the PTY
+    pub fn take_output(&mut self) -> Vec<u8> {
+        match self {
+            FileTransfer::Zmodem(transfer) => transfer.take_output(),
+            FileTransfer::Kermit(transfer) => transfer.take_output(),
+        }
+    }
+
+    /// Events since last taken
+    pub fn take_events(&mut self) -> Vec<TransferEvent> {
+        match self {
+            FileTransfer::Zmodem(transfer) => transfer.take_events(),
+            FileTransfer::Kermit(transfer) => transfer.take_events(),
+        }
+    }
+
+    /// Feed output from the remote; returns how many bytes were consumed. Once
+    /// the transfer is over the rest is regular terminal output.
+    pub fn feed(&mut self, data: &[u8]) -> usize {
+        match self {
+            FileTransfer::Zmodem(transfer) => transfer.feed(data),
+            FileTransfer::Kermit(transfer) => transfer.feed(data),
+        }
+    }
+
+    /// Send `files` to the waiting remote; an empty list cancels
+    pub fn send_files(&mut self, files: Vec<TransferFile>) {
+        match self {
+            FileTransfer::Zmodem(transfer) => transfer.send_files(files),
+            FileTransfer::Kermit(transfer) => transfer.send_files(files),
+        }
+    }
+
+    /// Abort the transfer
+    pub fn cancel(&mut self) {
+        match self {
+            FileTransfer::Zmodem(transfer) => transfer.cancel(),
+            FileTransfer::Kermit(transfer) => transfer.cancel(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_first_protocol() {
+        // A Kermit Send-Init, then a ZMODEM ZRINIT
+        let data = b"\x01# S\x00 **\x18B0100000000aa51\r";
+        assert_eq!(
+            detect(data).map(|(start, protocol, _)| (start, protocol)),
+            Some((0, TransferProtocol::Kermit))
+        );
+        assert_eq!(
+            detect(&data[1..]),
+            Some((5, TransferProtocol::Zmodem, TransferDirection::Send))
+        );
+        assert_eq!(detect(b"no transfer here"), None);
+    }
+
+    #[test]
+    fn test_strip_start_frames() {
+        let data = b"$ sz f\r\n**\x18B00000000000000\r\x8a\x11\x01# N3\rdone\r\n**\x18B01";
+        assert_eq!(strip_start_frames(data), b"$ sz f\r\ndone\r\n");
+        assert_eq!(strip_start_frames(b"plain"), b"plain");
+    }
+}
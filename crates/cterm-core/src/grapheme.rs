@@ -0,0 +1,119 @@
+//! Grapheme cluster rules for text arriving one character at a time
+//!
+//! The parser hands the screen one character at a time, so instead of
+//! segmenting whole strings the screen asks [`continues_cluster`] whether
+//! the next character belongs to the cluster in the cell left of the cursor.
+//! The rules are the parts of UAX #29 that matter in a terminal: combining
+//! marks and other zero-width characters, variation selectors, emoji
+//! modifiers, zero-width joiner sequences and regional indicator pairs.
+//! [`cluster_width`] gives the number of cells the whole cluster takes.
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Zero-width joiner
+const ZWJ: char = '\u{200D}';
+
+/// Whether `c` continues the cluster made of `base` followed by `tail`
+pub fn continues_cluster(base: char, tail: &str, c: char) -> bool {
+    if c.is_control() || base == '\0' {
+        return false;
+    }
+    let last = tail.chars().next_back().unwrap_or(base);
+
+    // GB11: an emoji after a joiner
+    if last == ZWJ {
+        return is_extended_pictographic(c);
+    }
+    // GB12/13: regional indicators pair up into flags
+    if is_regional_indicator(c) {
+        return tail.is_empty() && is_regional_indicator(base);
+    }
+    if is_emoji_modifier(c) {
+        return is_extended_pictographic(last);
+    }
+    // GB9/GB9a: extending characters and joiners (combining marks,
+    // variation selectors and Hangul vowel/final jamo are all zero width)
+    c == ZWJ || c.width() == Some(0)
+}
+
+/// Number of cells the cluster `base` + `tail` takes, 1 or 2
+pub fn cluster_width(base: char, tail: &str) -> usize {
+    let width = if tail.is_empty() {
+        base.width().unwrap_or(1)
+    } else {
+        let mut cluster = String::with_capacity(base.len_utf8() + tail.len());
+        cluster.push(base);
+        cluster.push_str(tail);
+        cluster.width()
+    };
+    width.clamp(1, 2)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+fn is_emoji_modifier(c: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+}
+
+/// Approximation of the Extended_Pictographic property by its blocks
+fn is_extended_pictographic(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00A9}'
+            | '\u{00AE}'
+            | '\u{203C}'
+            | '\u{2049}'
+            | '\u{2122}'
+            | '\u{2139}'
+            | '\u{2194}'..='\u{21AA}'
+            | '\u{2300}'..='\u{23FF}'
+            | '\u{24C2}'
+            | '\u{25A0}'..='\u{25FF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2934}'..='\u{2935}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{3030}'
+            | '\u{303D}'
+            | '\u{3297}'
+            | '\u{3299}'
+            | '\u{1F000}'..='\u{1F1E5}'
+            | '\u{1F200}'..='\u{1F3FA}'
+            | '\u{1F400}'..='\u{1FAFF}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_continues_cluster() {
+        // Combining acute accent
+        assert!(continues_cluster('e', "", '\u{301}'));
+        // Emoji presentation selector
+        assert!(continues_cluster('\u{2764}', "", '\u{FE0F}'));
+        // Skin tone on a waving hand, but not on a letter
+        assert!(continues_cluster('\u{1F44B}', "", '\u{1F3FD}'));
+        assert!(!continues_cluster('a', "", '\u{1F3FD}'));
+        // Woman + ZWJ + laptop
+        assert!(continues_cluster('\u{1F469}', "", ZWJ));
+        assert!(continues_cluster('\u{1F469}', "\u{200D}", '\u{1F4BB}'));
+        assert!(!continues_cluster('\u{1F469}', "", '\u{1F4BB}'));
+        // Flags are pairs of regional indicators
+        assert!(continues_cluster('\u{1F1EB}', "", '\u{1F1F7}'));
+        assert!(!continues_cluster('\u{1F1EB}', "\u{1F1F7}", '\u{1F1E9}'));
+        assert!(!continues_cluster('a', "", 'b'));
+    }
+
+    #[test]
+    fn test_cluster_width() {
+        assert_eq!(cluster_width('e', "\u{301}"), 1);
+        assert_eq!(cluster_width('\u{2764}', ""), 1);
+        assert_eq!(cluster_width('\u{2764}', "\u{FE0F}"), 2);
+        assert_eq!(cluster_width('\u{1F469}', "\u{200D}\u{1F4BB}"), 2);
+        assert_eq!(cluster_width('\u{1F1EB}', "\u{1F1F7}"), 2);
+        assert_eq!(cluster_width('\u{4E2D}', ""), 2);
+    }
+}
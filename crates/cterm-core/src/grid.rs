@@ -3,8 +3,26 @@
 //! The grid represents the visible terminal area and provides efficient
 //! access to cells by row and column.
 
-use crate::cell::Cell;
+use crate::cell::{Cell, CellAttrs};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Shell integration mark (OSC 133) recorded on the row the cursor was on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SemanticMark {
+    /// `A`: a prompt starts
+    Prompt,
+    /// `B`: the command line (what the user types) starts
+    CommandInput,
+    /// `C`: the command's output starts
+    Output,
+    /// `D`: the command finished, with its exit status if reported and its
+    /// run time if this screen saw its output mark
+    CommandEnd {
+        exit_code: Option<i32>,
+        duration: Option<Duration>,
+    },
+}
 
 /// A row of cells in the terminal
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +30,8 @@ pub struct Row {
     cells: Vec<Cell>,
     /// Whether this row has been wrapped from the previous row
     pub wrapped: bool,
+    /// Shell integration marks received on this row, in order
+    marks: Vec<SemanticMark>,
 }
 
 impl Row {
@@ -20,6 +40,7 @@ impl Row {
         Self {
             cells: vec![Cell::default(); width],
             wrapped: false,
+            marks: Vec::new(),
         }
     }
 
@@ -44,6 +65,17 @@ impl Row {
             cell.reset();
         }
         self.wrapped = false;
+        self.marks.clear();
+    }
+
+    /// Shell integration marks received on this row, in order
+    pub fn marks(&self) -> &[SemanticMark] {
+        &self.marks
+    }
+
+    /// Record a shell integration mark on this row
+    pub fn add_mark(&mut self, mark: SemanticMark) {
+        self.marks.push(mark);
     }
 
     /// Get a reference to a cell at the given column
@@ -80,7 +112,7 @@ impl Row {
     pub fn write_text_to(&self, buf: &mut String) {
         buf.clear();
         for cell in &self.cells {
-            buf.push(cell.c);
+            cell.push_grapheme(buf);
         }
         let trimmed_len = buf.trim_end().len();
         buf.truncate(trimmed_len);
@@ -90,6 +122,16 @@ impl Row {
     pub fn is_all_empty(&self) -> bool {
         self.cells.iter().all(|c| c.is_empty())
     }
+
+    /// Build a row of exactly `width` cells, padding with blanks
+    fn from_cells(mut cells: Vec<Cell>, width: usize, wrapped: bool) -> Self {
+        cells.resize(width, Cell::default());
+        Self {
+            cells,
+            wrapped,
+            marks: Vec::new(),
+        }
+    }
 }
 
 impl std::ops::Index<usize> for Row {
@@ -231,6 +273,51 @@ impl Grid {
         }
     }
 
+    /// Scroll columns `left..right` of rows `top..bottom` up by `count`
+    /// lines, leaving the cells outside the columns in place (used with
+    /// left/right margins)
+    pub fn scroll_rect_up(
+        &mut self,
+        count: usize,
+        top: usize,
+        bottom: usize,
+        left: usize,
+        right: usize,
+    ) {
+        let count = count.min(bottom - top);
+        for row in top..bottom {
+            for col in left..right {
+                self.rows[row][col] = if row + count < bottom {
+                    self.rows[row + count][col].clone()
+                } else {
+                    Cell::default()
+                };
+            }
+        }
+    }
+
+    /// Scroll columns `left..right` of rows `top..bottom` down by `count`
+    /// lines, leaving the cells outside the columns in place
+    pub fn scroll_rect_down(
+        &mut self,
+        count: usize,
+        top: usize,
+        bottom: usize,
+        left: usize,
+        right: usize,
+    ) {
+        let count = count.min(bottom - top);
+        for row in (top..bottom).rev() {
+            for col in left..right {
+                self.rows[row][col] = if row >= top + count {
+                    self.rows[row - count][col].clone()
+                } else {
+                    Cell::default()
+                };
+            }
+        }
+    }
+
     /// Iterator over rows
     pub fn iter(&self) -> impl Iterator<Item = &Row> {
         self.rows.iter()
@@ -241,6 +328,25 @@ impl Grid {
         self.rows.iter_mut()
     }
 
+    /// Take the rows out of the grid
+    pub(crate) fn into_rows(self) -> Vec<Row> {
+        self.rows
+    }
+
+    /// Build a grid from rows, padding or truncating to `height` rows
+    pub(crate) fn from_rows(mut rows: Vec<Row>, width: usize, height: usize) -> Self {
+        rows.truncate(height);
+        rows.resize_with(height, || Row::new(width));
+        for row in &mut rows {
+            row.resize(width);
+        }
+        Self {
+            rows,
+            width,
+            height,
+        }
+    }
+
     /// Get all text content from the grid
     pub fn text(&self) -> String {
         self.rows
@@ -251,6 +357,124 @@ impl Grid {
     }
 }
 
+/// Rows re-wrapped to a new width by [`reflow_rows`]
+pub(crate) struct Reflowed {
+    pub rows: Vec<Row>,
+    /// New (row, col) of the tracked cursor
+    pub cursor: Option<(usize, usize)>,
+    /// New index of each input row
+    pub row_map: Vec<usize>,
+}
+
+/// Re-wrap `rows` to `width` columns.
+///
+/// Rows flagged `wrapped` continue the previous row, so consecutive rows form
+/// one logical line; each line is joined and split again at the new width,
+/// without splitting wide characters. Trailing blanks of a line are dropped,
+/// and shell integration marks move to its first row. `cursor` is a
+/// (row, col) position in `rows` to carry over.
+pub(crate) fn reflow_rows(
+    rows: Vec<Row>,
+    width: usize,
+    cursor: Option<(usize, usize)>,
+) -> Reflowed {
+    let width = width.max(1);
+    let mut out: Vec<Row> = Vec::with_capacity(rows.len());
+    let mut row_map = Vec::with_capacity(rows.len());
+    let mut new_cursor = None;
+
+    // Logical line being assembled: cells, starting offset of each source
+    // row, marks of all its rows
+    let mut line: Vec<Cell> = Vec::new();
+    let mut starts: Vec<usize> = Vec::new();
+    let mut marks: Vec<SemanticMark> = Vec::new();
+    let mut cursor_offset = None;
+
+    let mut flush = |line: &mut Vec<Cell>,
+                     starts: &mut Vec<usize>,
+                     marks: &mut Vec<SemanticMark>,
+                     cursor_offset: &mut Option<usize>,
+                     out: &mut Vec<Row>| {
+        let content_len = line
+            .iter()
+            .rposition(|c| !c.is_empty())
+            .map_or(0, |i| i + 1);
+        line.truncate(content_len);
+
+        let first = out.len();
+        // (row, col) where each cell of the line lands
+        let mut positions = Vec::with_capacity(line.len());
+        let mut current: Vec<Cell> = Vec::with_capacity(width);
+        let mut row_index = 0;
+        for cell in line.drain(..) {
+            let wide = cell.attrs.contains(CellAttrs::WIDE);
+            if current.len() == width || (wide && width > 1 && current.len() + 1 == width) {
+                out.push(Row::from_cells(
+                    std::mem::take(&mut current),
+                    width,
+                    row_index > 0,
+                ));
+                row_index += 1;
+            }
+            positions.push((row_index, current.len()));
+            current.push(cell);
+        }
+        let end = (row_index, current.len());
+        out.push(Row::from_cells(current, width, row_index > 0));
+        out[first].marks = std::mem::take(marks);
+
+        for &start in starts.iter() {
+            let row = positions.get(start).map_or(end.0, |p| p.0);
+            row_map.push(first + row);
+        }
+        starts.clear();
+
+        if let Some(offset) = cursor_offset.take() {
+            let (row, col) = match positions.get(offset) {
+                Some(&pos) => pos,
+                // Past the content: stay on its last row
+                None => (end.0, (end.1 + offset - content_len).min(width - 1)),
+            };
+            new_cursor = Some((first + row, col));
+        }
+    };
+
+    for (i, row) in rows.into_iter().enumerate() {
+        if !row.wrapped && !starts.is_empty() {
+            flush(
+                &mut line,
+                &mut starts,
+                &mut marks,
+                &mut cursor_offset,
+                &mut out,
+            );
+        }
+        starts.push(line.len());
+        if let Some((cursor_row, cursor_col)) = cursor {
+            if cursor_row == i {
+                cursor_offset = Some(line.len() + cursor_col);
+            }
+        }
+        line.extend(row.cells);
+        marks.extend(row.marks);
+    }
+    if !starts.is_empty() {
+        flush(
+            &mut line,
+            &mut starts,
+            &mut marks,
+            &mut cursor_offset,
+            &mut out,
+        );
+    }
+
+    Reflowed {
+        rows: out,
+        cursor: new_cursor,
+        row_map,
+    }
+}
+
 impl std::ops::Index<usize> for Grid {
     type Output = Row;
 
@@ -305,6 +529,32 @@ mod tests {
         assert_eq!(grid.height(), 10);
     }
 
+    #[test]
+    fn test_reflow_rows() {
+        let row = |text: &str, wrapped: bool| {
+            let cells = text.chars().map(Cell::new).collect();
+            Row::from_cells(cells, 4, wrapped)
+        };
+        // "abcdefg" wrapped at 4 columns, then "hi"
+        let rows = vec![row("abcd", false), row("efg", true), row("hi", false)];
+
+        let reflowed = reflow_rows(rows.clone(), 3, Some((1, 2)));
+        let text: Vec<_> = reflowed.rows.iter().map(|r| r.text()).collect();
+        assert_eq!(text, ["abc", "def", "g", "hi"]);
+        assert!(!reflowed.rows[0].wrapped);
+        assert!(reflowed.rows[1].wrapped && reflowed.rows[2].wrapped);
+        assert!(!reflowed.rows[3].wrapped);
+        // Cursor stays on 'g'
+        assert_eq!(reflowed.cursor, Some((2, 0)));
+        assert_eq!(reflowed.row_map, [0, 1, 3]);
+
+        let reflowed = reflow_rows(rows, 10, Some((1, 2)));
+        let text: Vec<_> = reflowed.rows.iter().map(|r| r.text()).collect();
+        assert_eq!(text, ["abcdefg", "hi"]);
+        assert_eq!(reflowed.cursor, Some((0, 6)));
+        assert_eq!(reflowed.row_map, [0, 0, 1]);
+    }
+
     #[test]
     fn test_grid_scroll_up() {
         let mut grid = Grid::new(80, 5);
@@ -345,6 +595,26 @@ mod tests {
         assert_eq!(grid[4][0].c, '2');
     }
 
+    #[test]
+    fn test_grid_scroll_rect() {
+        let mut grid = Grid::new(4, 3);
+        for row in 0..3 {
+            for col in 0..4 {
+                grid[row][col].c = char::from(b'a' + (row * 4 + col) as u8);
+            }
+        }
+
+        grid.scroll_rect_up(1, 0, 3, 1, 3);
+        assert_eq!(grid[0].text(), "afgd");
+        assert_eq!(grid[1].text(), "ejkh");
+        assert_eq!(grid[2].text(), "i  l");
+
+        grid.scroll_rect_down(2, 0, 3, 1, 3);
+        assert_eq!(grid[0].text(), "a  d");
+        assert_eq!(grid[1].text(), "e  h");
+        assert_eq!(grid[2].text(), "ifgl");
+    }
+
     #[test]
     fn test_row_text() {
         let mut row = Row::new(10);
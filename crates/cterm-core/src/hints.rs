@@ -0,0 +1,325 @@
+//! Keyboard hints for the visible screen
+//!
+//! Hint mode labels every URL, file path, hex hash and IP address that
+//! [`crate::detect`] finds in the viewport with a short letter sequence (like
+//! kitty's `open_url_with hints`). Typing a label picks its target: lowercase
+//! opens it, uppercase copies it, so links can be used without touching the
+//! mouse.
+
+use crate::detect::{self, DetectedSpan, SpanKind};
+use crate::screen::Screen;
+
+/// Default label letters, home row first
+pub const DEFAULT_HINT_ALPHABET: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+/// What a hint points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintKind {
+    Url,
+    Path,
+    Hash,
+    Ip,
+}
+
+impl From<SpanKind> for HintKind {
+    fn from(kind: SpanKind) -> Self {
+        match kind {
+            SpanKind::Url => HintKind::Url,
+            SpanKind::Path => HintKind::Path,
+            SpanKind::GitSha => HintKind::Hash,
+            SpanKind::IpAddress => HintKind::Ip,
+        }
+    }
+}
+
+/// A labelled target on the visible screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    /// Letters that select this hint
+    pub label: String,
+    pub kind: HintKind,
+    /// Text to open or copy (the URI for OSC 8 hyperlinks)
+    pub text: String,
+    /// Visible row and first column of the target
+    pub row: usize,
+    pub col: usize,
+    /// Width of the target in cells
+    pub width: usize,
+}
+
+impl Hint {
+    /// What to hand to the system opener, or `None` for hashes and addresses
+    ///
+    /// `~` expands to the home directory and relative paths are resolved
+    /// against `cwd` (the shell's working directory, when known).
+    pub fn open_target(&self, cwd: Option<&str>) -> Option<String> {
+        match self.kind {
+            HintKind::Url => Some(detect::url_target(&self.text)),
+            HintKind::Path => Some(detect::path_target(&self.text, cwd)),
+            HintKind::Hash | HintKind::Ip => None,
+        }
+    }
+}
+
+/// What to do with a chosen hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintAction {
+    /// Open with the system handler (lowercase label)
+    Open,
+    /// Copy to the clipboard (uppercase label)
+    Copy,
+}
+
+/// Result of typing a character in hint mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintInput {
+    /// The typed prefix still matches several hints
+    Pending,
+    /// A hint was chosen; hint mode is over
+    Chosen(Hint, HintAction),
+    /// No hint starts with the typed letters; hint mode is over
+    NoMatch,
+}
+
+/// Active hint mode for one terminal
+#[derive(Debug, Clone)]
+pub struct HintMode {
+    hints: Vec<Hint>,
+    typed: String,
+}
+
+impl HintMode {
+    /// Label the targets on the visible screen, or `None` if there are none
+    pub fn new(screen: &Screen, alphabet: &str) -> Option<Self> {
+        let hints = find_hints(screen, alphabet);
+        (!hints.is_empty()).then(|| Self {
+            hints,
+            typed: String::new(),
+        })
+    }
+
+    /// Hints still reachable with the letters typed so far
+    pub fn visible(&self) -> impl Iterator<Item = &Hint> {
+        self.hints
+            .iter()
+            .filter(|h| h.label.starts_with(&self.typed))
+    }
+
+    /// Letters typed so far
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+
+    /// Type one label letter; uppercase selects [`HintAction::Copy`]
+    pub fn input(&mut self, c: char) -> HintInput {
+        let action = if c.is_uppercase() {
+            HintAction::Copy
+        } else {
+            HintAction::Open
+        };
+        self.typed.extend(c.to_lowercase());
+        let mut matches = self.visible();
+        match (matches.next(), matches.next()) {
+            (None, _) => HintInput::NoMatch,
+            (Some(hint), None) if hint.label == self.typed => {
+                HintInput::Chosen(hint.clone(), action)
+            }
+            _ => HintInput::Pending,
+        }
+    }
+
+    /// Remove the last typed letter
+    pub fn backspace(&mut self) {
+        self.typed.pop();
+    }
+}
+
+/// Find and label the URLs, paths, hashes and addresses on the visible screen
+///
+/// Hints are ordered top to bottom, left to right. Labels all have the same
+/// length, so no label is a prefix of another.
+pub fn find_hints(screen: &Screen, alphabet: &str) -> Vec<Hint> {
+    let first = screen.visible_row_to_absolute_line(0);
+    let rows = first..first + screen.height();
+    let mut hints: Vec<Hint> = detect::detect(screen, rows.clone())
+        .into_iter()
+        .filter(|span| rows.contains(&span.line))
+        .map(|span| hint_for_span(screen, first, span))
+        .collect();
+    let labels = hint_labels(hints.len(), alphabet);
+    for (hint, label) in hints.iter_mut().zip(labels) {
+        hint.label = label;
+    }
+    hints
+}
+
+/// `count` distinct labels of equal length drawn from `alphabet`
+pub fn hint_labels(count: usize, alphabet: &str) -> Vec<String> {
+    let mut letters: Vec<char> = Vec::new();
+    for c in alphabet.chars().flat_map(char::to_lowercase) {
+        if c.is_alphabetic() && !letters.contains(&c) {
+            letters.push(c);
+        }
+    }
+    if letters.len() < 2 {
+        letters = DEFAULT_HINT_ALPHABET.chars().collect();
+    }
+
+    let base = letters.len();
+    let mut len = 1;
+    let mut capacity = base;
+    while capacity < count {
+        len += 1;
+        capacity = capacity.saturating_mul(base);
+    }
+
+    (0..count)
+        .map(|mut n| {
+            let mut label = vec![letters[0]; len];
+            for slot in label.iter_mut().rev() {
+                *slot = letters[n % base];
+                n /= base;
+            }
+            label.into_iter().collect()
+        })
+        .collect()
+}
+
+/// Unlabelled hint for a span starting on visible row `span.line - first`
+///
+/// A span continuing onto wrapped rows is labelled on its first row only.
+fn hint_for_span(screen: &Screen, first: usize, span: DetectedSpan) -> Hint {
+    let end = if span.end_line == span.line {
+        span.end_col
+    } else {
+        screen.width()
+    };
+    Hint {
+        label: String::new(),
+        kind: span.kind.into(),
+        text: span.text,
+        row: span.line - first,
+        col: span.col,
+        width: end - span.col,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::screen::{ScreenConfig, SelectionConfig};
+
+    fn screen_with(text: &[u8]) -> Screen {
+        let mut screen = Screen::new(80, 5, ScreenConfig::default());
+        Parser::new().parse(&mut screen, text);
+        screen
+    }
+
+    #[test]
+    fn test_hint_labels() {
+        assert_eq!(hint_labels(3, "abc"), ["a", "b", "c"]);
+        assert_eq!(hint_labels(4, "ab"), ["aa", "ab", "ba", "bb"]);
+        // Duplicates and non-letters are ignored
+        assert_eq!(hint_labels(2, "aA1b"), ["a", "b"]);
+        assert_eq!(hint_labels(27, DEFAULT_HINT_ALPHABET)[26], "sa");
+    }
+
+    #[test]
+    fn test_find_hints() {
+        let screen = screen_with(
+            b"see https://example.com/docs). in ~/src/cterm/Cargo.toml\r\n\
+              commit 3f2a9c1d merged, not deadbeef or /\r\n\
+              edit src/main.rs\r\n",
+        );
+        let hints = find_hints(&screen, DEFAULT_HINT_ALPHABET);
+        let found: Vec<_> = hints
+            .iter()
+            .map(|h| (h.kind, h.text.as_str(), h.row, h.col))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (HintKind::Url, "https://example.com/docs", 0, 4),
+                (HintKind::Path, "~/src/cterm/Cargo.toml", 0, 34),
+                (HintKind::Hash, "3f2a9c1d", 1, 7),
+                (HintKind::Path, "src/main.rs", 2, 5),
+            ]
+        );
+        assert_eq!(hints[0].width, 24);
+        assert_eq!(hints[2].label, "d");
+    }
+
+    #[test]
+    fn test_open_target() {
+        let hint = |kind, text: &str| Hint {
+            label: "a".into(),
+            kind,
+            text: text.into(),
+            row: 0,
+            col: 0,
+            width: text.len(),
+        };
+        let cwd = Some("/home/me/");
+        assert_eq!(
+            hint(HintKind::Url, "www.example.com").open_target(cwd),
+            Some("https://www.example.com".into())
+        );
+        assert_eq!(
+            hint(HintKind::Path, "src/main.rs").open_target(cwd),
+            Some("/home/me/src/main.rs".into())
+        );
+        assert_eq!(
+            hint(HintKind::Path, "/etc/hosts").open_target(cwd),
+            Some("/etc/hosts".into())
+        );
+        assert_eq!(hint(HintKind::Hash, "3f2a9c1d").open_target(cwd), None);
+    }
+
+    #[test]
+    fn test_osc8_hyperlink_hint() {
+        let screen = screen_with(b"\x1b]8;;https://cterm.dev\x1b\\docs\x1b]8;;\x1b\\ here\r\n");
+        let hints = find_hints(&screen, DEFAULT_HINT_ALPHABET);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].text, "https://cterm.dev");
+        assert_eq!((hints[0].col, hints[0].width), (0, 4));
+    }
+
+    #[test]
+    fn test_hint_mode_input() {
+        let screen = screen_with(b"/a /b /c\r\n");
+        let mut mode = HintMode::new(&screen, "xy").unwrap();
+        assert_eq!(mode.visible().count(), 3);
+        assert_eq!(mode.input('y'), HintInput::Pending);
+        assert_eq!(mode.visible().count(), 1);
+        mode.backspace();
+        assert_eq!(mode.input('x'), HintInput::Pending);
+        match mode.input('Y') {
+            HintInput::Chosen(hint, action) => {
+                assert_eq!(hint.text, "/b");
+                assert_eq!(action, HintAction::Copy);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let mut mode = HintMode::new(&screen, "xy").unwrap();
+        assert_eq!(mode.input('q'), HintInput::NoMatch);
+        assert!(HintMode::new(&screen_with(b"nothing here"), "xy").is_none());
+    }
+
+    #[test]
+    fn test_configured_alphabet() {
+        let config = ScreenConfig {
+            selection: SelectionConfig {
+                hint_alphabet: "aoeu".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut screen = Screen::new(80, 5, config);
+        Parser::new().parse(&mut screen, b"/a /b /c /d /e\r\n");
+        let mode = HintMode::new(&screen, screen.hint_alphabet()).unwrap();
+        let labels: Vec<_> = mode.visible().map(|h| h.label.as_str()).collect();
+        assert_eq!(labels, ["aa", "ao", "ae", "au", "oa"]);
+    }
+}
@@ -21,6 +21,10 @@ pub enum ImageDecodeError {
 /// Maximum image dimensions to prevent memory issues
 const MAX_IMAGE_DIMENSION: u32 = 4096;
 
+/// Maximum source dimensions for thumbnails, which are never kept at full
+/// size (camera photos are often larger than `MAX_IMAGE_DIMENSION`)
+const MAX_THUMBNAIL_SOURCE: u32 = 16384;
+
 /// Decoded image data
 #[derive(Debug)]
 pub struct DecodedImage {
@@ -65,6 +69,38 @@ pub fn decode_image(data: &[u8]) -> Result<DecodedImage, ImageDecodeError> {
     })
 }
 
+/// Decode an image and scale it down to fit in `max_size` x `max_size`
+///
+/// The aspect ratio is kept and small images are not enlarged. For GIF,
+/// only the first frame is decoded.
+pub fn decode_thumbnail(data: &[u8], max_size: u32) -> Result<DecodedImage, ImageDecodeError> {
+    let reader = image::ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|_| ImageDecodeError::UnknownFormat)?;
+
+    let (width, height) = reader.into_dimensions().map_err(ImageDecodeError::from)?;
+    if width == 0 || height == 0 || width > MAX_THUMBNAIL_SOURCE || height > MAX_THUMBNAIL_SOURCE {
+        return Err(ImageDecodeError::TooLarge(width, height));
+    }
+
+    let img = image::ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|_| ImageDecodeError::UnknownFormat)?
+        .decode()?;
+    let img = if width > max_size || height > max_size {
+        img.thumbnail(max_size, max_size)
+    } else {
+        img
+    };
+
+    let (width, height) = img.dimensions();
+    Ok(DecodedImage {
+        data: img.to_rgba8().into_raw(),
+        width: width as usize,
+        height: height as usize,
+    })
+}
+
 /// Guess if data looks like an image based on magic bytes
 pub fn looks_like_image(data: &[u8]) -> bool {
     if data.len() < 3 {
@@ -149,4 +185,27 @@ mod tests {
         assert_eq!(img.height, 1);
         assert_eq!(img.data.len(), 4); // 1x1 RGBA
     }
+
+    #[test]
+    fn test_decode_thumbnail() {
+        let mut png = Vec::new();
+        image::RgbaImage::from_pixel(400, 100, image::Rgba([255, 0, 0, 255]))
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        // Scaled down to fit, keeping the aspect ratio
+        let thumb = decode_thumbnail(&png, 200).unwrap();
+        assert_eq!((thumb.width, thumb.height), (200, 50));
+        assert_eq!(thumb.data.len(), 200 * 50 * 4);
+        assert_eq!(&thumb.data[..4], &[255, 0, 0, 255]);
+
+        // Small images are left alone
+        let thumb = decode_thumbnail(&png, 1000).unwrap();
+        assert_eq!((thumb.width, thumb.height), (400, 100));
+
+        assert!(matches!(
+            decode_thumbnail(b"not an image", 200),
+            Err(ImageDecodeError::UnknownFormat | ImageDecodeError::DecodeError(_))
+        ));
+    }
 }
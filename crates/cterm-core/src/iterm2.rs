@@ -15,9 +15,24 @@
 //! - `height=auto|Npx|Ncells|N%` - Display height
 //! - `preserveAspectRatio=0|1` - Default 1
 //! - `inline=0|1` - 1=display image, 0=file transfer
+//!
+//! The other way, `OSC 1337 ; RequestUpload=format=tgz ST` (sent by `it2ul`)
+//! asks the user for files. The terminal answers with [`encode_upload`], or
+//! [`UPLOAD_ABORT`] if the user picks none.
+
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use base64::Engine;
 
+use crate::file_transfer::TransferFile;
+
+/// Answer to an OSC 1337 RequestUpload when the user picks no files (or the
+/// format isn't supported)
+pub const UPLOAD_ABORT: &[u8] = b"abort\n";
+
+/// Length of the base64 lines of an upload
+const UPLOAD_LINE_LENGTH: usize = 76;
+
 /// Dimension specification for iTerm2 images
 #[derive(Debug, Clone, PartialEq, Default)]
 pub enum Iterm2Dimension {
@@ -148,9 +163,74 @@ impl Iterm2FileParams {
     }
 }
 
+/// Answer an OSC 1337 RequestUpload with `files`: `ok`, then the files as a
+/// gzipped tar archive in lines of base64, then a blank line
+pub fn encode_upload(files: &[TransferFile]) -> std::io::Result<Vec<u8>> {
+    let mtime = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut archive = tar::Builder::new(gzip);
+    for file in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(file.data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        archive.append_data(&mut header, &file.name, file.data.as_slice())?;
+    }
+    let tgz = archive.into_inner()?.finish()?;
+
+    let encoded = base64::engine::general_purpose::STANDARD.encode(tgz);
+    let mut out = Vec::with_capacity(encoded.len() + encoded.len() / UPLOAD_LINE_LENGTH + 8);
+    out.extend_from_slice(b"ok\n");
+    for line in encoded.as_bytes().chunks(UPLOAD_LINE_LENGTH) {
+        out.extend_from_slice(line);
+        out.push(b'\n');
+    }
+    out.push(b'\n');
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_encode_upload() {
+        let files = vec![
+            TransferFile {
+                name: "notes.txt".into(),
+                data: b"hello".to_vec(),
+            },
+            TransferFile {
+                name: "blob.bin".into(),
+                data: (0..=255).collect(),
+            },
+        ];
+        let upload = encode_upload(&files).unwrap();
+        let text = std::str::from_utf8(&upload).unwrap();
+        let lines: Vec<_> = text.strip_prefix("ok\n").unwrap().lines().collect();
+        assert!(text.ends_with("\n\n"));
+        assert!(lines.iter().all(|line| line.len() <= UPLOAD_LINE_LENGTH));
+
+        let tgz = base64::engine::general_purpose::STANDARD
+            .decode(lines.concat())
+            .unwrap();
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(tgz.as_slice()));
+        let unpacked: Vec<_> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let name = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).unwrap();
+                TransferFile { name, data }
+            })
+            .collect();
+        assert_eq!(unpacked, files);
+    }
 
     #[test]
     fn test_dimension_parse() {
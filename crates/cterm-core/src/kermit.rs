@@ -0,0 +1,891 @@
+//! Kermit file transfers (`kermit -s`/`kermit -r`)
+//!
+//! For embedded and retro systems that have Kermit but no ZMODEM. [`detect`]
+//! spots the Send-Init of a remote `kermit -s`, or the NAK a remote
+//! `kermit -r` repeats while it waits. A [`Kermit`] session then consumes the
+//! output like a [`Zmodem`](crate::zmodem::Zmodem) one does and reports the
+//! same [`TransferEvent`]s.
+//!
+//! Packets are the classic short ones (at most 94 bytes), with control
+//! prefixing and, when the other side agrees, 8th-bit and repeat prefixing.
+//! Block checks 1, 2 and 3 are supported. The session keeps no clock: the
+//! remote's timeouts drive retransmission, and a stalled transfer is ended by
+//! the user cancelling it.
+
+use std::collections::VecDeque;
+
+use crate::file_transfer::{TransferDirection, TransferEvent, TransferFile, MAX_FILE_SIZE};
+
+/// Packet lead-in (MARK)
+const SOH: u8 = 0x01;
+/// Packet terminator we ask for and send
+const CR: u8 = b'\r';
+/// Longest packet (LEN field) we accept
+const MAXL: u8 = 94;
+/// Control prefix we send with
+const QCTL: u8 = b'#';
+/// Repeat prefix we offer
+const REPT: u8 = b'~';
+/// Block check we offer when sending
+const CHKT: u8 = b'3';
+/// Longest run a repeat prefix covers
+const MAX_REPEAT: usize = 94;
+/// Bytes of non-Kermit output that end a session
+const GARBAGE_LIMIT: usize = 256;
+
+fn tochar(x: u8) -> u8 {
+    x + 32
+}
+
+fn unchar(c: u8) -> u8 {
+    c.wrapping_sub(32)
+}
+
+/// Toggle a character between control and printable
+fn ctl(c: u8) -> u8 {
+    c ^ 64
+}
+
+/// Find the start of a transfer: the Send-Init a `kermit -s` sends first, or
+/// the NAK for packet 0 of a waiting `kermit -r`. Returns where the packet
+/// begins and which way files go.
+pub fn detect(data: &[u8]) -> Option<(usize, TransferDirection)> {
+    data.windows(4).enumerate().find_map(|(i, window)| {
+        let &[SOH, len, b' ', kind] = window else {
+            return None;
+        };
+        if !(b'#'..=b'~').contains(&len) {
+            return None;
+        }
+        match kind {
+            b'S' => Some((i, TransferDirection::Receive)),
+            b'N' => Some((i, TransferDirection::Send)),
+            _ => None,
+        }
+    })
+}
+
+/// Length of a block check
+fn check_len(check: u8) -> usize {
+    match check {
+        b'2' => 2,
+        b'3' => 3,
+        _ => 1,
+    }
+}
+
+/// CRC-16/KERMIT
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x8408
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// Block check over a packet from LEN to the end of its data
+fn block_check(data: &[u8], check: u8) -> Vec<u8> {
+    let sum: u32 = data.iter().map(|&b| b as u32).sum();
+    match check {
+        b'2' => vec![
+            tochar(((sum >> 6) & 0x3f) as u8),
+            tochar((sum & 0x3f) as u8),
+        ],
+        b'3' => {
+            let crc = crc16(data);
+            vec![
+                tochar(((crc >> 12) & 0x0f) as u8),
+                tochar(((crc >> 6) & 0x3f) as u8),
+                tochar((crc & 0x3f) as u8),
+            ]
+        }
+        _ => vec![tochar(((sum + ((sum & 0xc0) >> 6)) & 0x3f) as u8)],
+    }
+}
+
+fn encode_packet(out: &mut Vec<u8>, seq: u8, kind: u8, data: &[u8], check: u8) {
+    let start = out.len();
+    out.push(SOH);
+    out.push(tochar((2 + data.len() + check_len(check)) as u8));
+    out.push(tochar(seq));
+    out.push(kind);
+    out.extend_from_slice(data);
+    let sum = block_check(&out[start + 1..], check);
+    out.extend(sum);
+    out.push(CR);
+}
+
+/// A decoded packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Packet {
+    seq: u8,
+    kind: u8,
+    data: Vec<u8>,
+}
+
+/// Prefixing in effect for one direction of data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Prefixes {
+    qctl: u8,
+    qbin: Option<u8>,
+    rept: Option<u8>,
+}
+
+impl Prefixes {
+    /// Decode a data field
+    fn decode(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(data.len());
+        let mut bytes = data.iter().copied();
+        while let Some(mut byte) = bytes.next() {
+            let mut count = 1;
+            if Some(byte) == self.rept {
+                count = unchar(bytes.next().unwrap_or(b' ')) as usize;
+                let Some(next) = bytes.next() else { break };
+                byte = next;
+            }
+            let mut high = 0;
+            if Some(byte) == self.qbin {
+                high = 0x80;
+                let Some(next) = bytes.next() else { break };
+                byte = next;
+            }
+            if byte == self.qctl {
+                let Some(next) = bytes.next() else { break };
+                byte = if (0x3f..=0x5f).contains(&(next & 0x7f)) {
+                    ctl(next)
+                } else {
+                    next
+                };
+            }
+            out.extend(std::iter::repeat_n(byte | high, count));
+        }
+        out
+    }
+
+    /// Encode one byte
+    fn encode_byte(&self, out: &mut Vec<u8>, mut byte: u8) {
+        if let Some(qbin) = self.qbin {
+            if byte & 0x80 != 0 {
+                out.push(qbin);
+                byte &= 0x7f;
+            }
+        }
+        let low = byte & 0x7f;
+        if low < 0x20 || low == 0x7f {
+            out.extend([self.qctl, ctl(byte)]);
+        } else if low == self.qctl || Some(low) == self.qbin || Some(low) == self.rept {
+            out.extend([self.qctl, byte]);
+        } else {
+            out.push(byte);
+        }
+    }
+
+    /// Encode as much of `data` as fits in `room` bytes; returns the encoded
+    /// field and how many bytes of `data` it holds
+    fn encode(&self, data: &[u8], room: usize) -> (Vec<u8>, usize) {
+        let mut out = Vec::with_capacity(room);
+        let mut used = 0;
+        let mut encoded = Vec::new();
+        while used < data.len() {
+            let byte = data[used];
+            let run = match self.rept {
+                Some(_) => data[used..]
+                    .iter()
+                    .take(MAX_REPEAT)
+                    .take_while(|&&b| b == byte)
+                    .count(),
+                None => 1,
+            };
+            encoded.clear();
+            let taken = match self.rept {
+                Some(rept) if run > 2 => {
+                    encoded.extend([rept, tochar(run as u8)]);
+                    self.encode_byte(&mut encoded, byte);
+                    run
+                }
+                _ => {
+                    self.encode_byte(&mut encoded, byte);
+                    1
+                }
+            };
+            if out.len() + encoded.len() > room {
+                break;
+            }
+            out.extend_from_slice(&encoded);
+            used += taken;
+        }
+        (out, used)
+    }
+}
+
+/// A file being received
+#[derive(Debug)]
+struct Incoming {
+    name: String,
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Receiving,
+    /// Waiting for the frontend's files
+    AwaitingFiles,
+    /// Send-Init sent
+    Init,
+    /// File header sent
+    File,
+    /// Data packet sent, holding this many bytes of the file
+    Data {
+        len: usize,
+    },
+    /// End of file sent
+    Eof,
+    /// End of transmission sent
+    Break,
+    Done,
+}
+
+#[derive(Debug)]
+enum Scan {
+    /// Between packets
+    Idle,
+    /// After the mark, waiting for LEN
+    Length,
+    /// Collecting LEN bytes after LEN
+    Body { len: usize },
+}
+
+/// One Kermit transfer
+#[derive(Debug)]
+pub struct Kermit {
+    direction: TransferDirection,
+    phase: Phase,
+    scan: Scan,
+    /// Packet being collected, from LEN on
+    buf: Vec<u8>,
+    /// Bytes of non-Kermit output since the last packet
+    garbage: usize,
+    /// Whether the Send-Init exchange is over, so `check` applies
+    negotiated: bool,
+    /// Block check type ('1', '2' or '3')
+    check: u8,
+    /// Longest packet the other side takes
+    their_maxl: u8,
+    /// Prefixes for what the other side sends
+    incoming_prefixes: Prefixes,
+    /// Prefixes for what we send
+    outgoing_prefixes: Prefixes,
+    /// Sequence number of the packet expected (receiving) or sent (sending)
+    seq: u8,
+    /// Last packet sent, repeated when the other side asks
+    last_packet: Vec<u8>,
+    incoming: Option<Incoming>,
+    /// Files still to send, current first
+    outgoing: VecDeque<TransferFile>,
+    /// Bytes of the current file acknowledged
+    position: usize,
+    requested: bool,
+    output: Vec<u8>,
+    events: Vec<TransferEvent>,
+}
+
+impl Kermit {
+    pub fn new(direction: TransferDirection) -> Self {
+        let prefixes = Prefixes {
+            qctl: QCTL,
+            qbin: None,
+            rept: None,
+        };
+        Self {
+            direction,
+            phase: match direction {
+                TransferDirection::Receive => Phase::Receiving,
+                TransferDirection::Send => Phase::AwaitingFiles,
+            },
+            scan: Scan::Idle,
+            buf: Vec::new(),
+            garbage: 0,
+            negotiated: false,
+            check: b'1',
+            their_maxl: 80,
+            incoming_prefixes: prefixes,
+            outgoing_prefixes: prefixes,
+            seq: 0,
+            last_packet: Vec::new(),
+            incoming: None,
+            outgoing: VecDeque::new(),
+            position: 0,
+            requested: false,
+            output: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn direction(&self) -> TransferDirection {
+        self.direction
+    }
+
+    /// Whether the transfer is over
+    pub fn is_done(&self) -> bool {
+        self.phase == Phase::Done
+    }
+
+    /// Bytes to write to the PTY
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Events since last taken
+    pub fn take_events(&mut self) -> Vec<TransferEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Feed output from the remote; returns how many bytes were consumed. Once
+    /// the transfer is over the rest is regular terminal output.
+    pub fn feed(&mut self, data: &[u8]) -> usize {
+        for (i, &byte) in data.iter().enumerate() {
+            if byte == SOH {
+                self.buf.clear();
+                self.scan = Scan::Length;
+                continue;
+            }
+            match self.scan {
+                Scan::Idle => {
+                    if !matches!(byte, b'\r' | b'\n') {
+                        self.garbage += 1;
+                    }
+                    if self.garbage > GARBAGE_LIMIT {
+                        log::warn!("Kermit: no packets from the remote, ending the transfer");
+                        self.phase = Phase::Done;
+                    }
+                }
+                Scan::Length => {
+                    let len = unchar(byte) as usize;
+                    if !(3..=MAXL as usize).contains(&len) {
+                        self.scan = Scan::Idle;
+                        continue;
+                    }
+                    self.buf.push(byte);
+                    self.scan = Scan::Body { len };
+                }
+                Scan::Body { len } => {
+                    self.buf.push(byte);
+                    if self.buf.len() == len + 1 {
+                        self.scan = Scan::Idle;
+                        self.garbage = 0;
+                        let raw = std::mem::take(&mut self.buf);
+                        self.received(&raw);
+                    }
+                }
+            }
+            if self.phase == Phase::Done {
+                return i + 1;
+            }
+        }
+        data.len()
+    }
+
+    /// Send `files` to the waiting `kermit -r`; an empty list cancels
+    pub fn send_files(&mut self, files: Vec<TransferFile>) {
+        if self.phase != Phase::AwaitingFiles {
+            return;
+        }
+        if files.is_empty() {
+            self.cancel();
+            return;
+        }
+        self.outgoing = files.into();
+        self.seq = 0;
+        let params = self.params(b'Y', CHKT);
+        self.send(b'S', &params);
+        self.phase = Phase::Init;
+    }
+
+    /// Abort the transfer
+    pub fn cancel(&mut self) {
+        if self.phase != Phase::Done {
+            self.send(b'E', b"Cancelled");
+            self.phase = Phase::Done;
+        }
+    }
+
+    /// Check and dispatch a packet (LEN through the block check)
+    fn received(&mut self, raw: &[u8]) {
+        let kind = raw[2];
+        // Send-Init and its acknowledgement always use a type 1 check
+        let check = if kind == b'S' || !self.negotiated {
+            b'1'
+        } else {
+            self.check
+        };
+        let data_end = raw.len().saturating_sub(check_len(check));
+        if data_end < 3 || block_check(&raw[..data_end], check) != raw[data_end..] {
+            log::debug!("Kermit: corrupt packet");
+            self.corrupt();
+            return;
+        }
+        let packet = Packet {
+            seq: unchar(raw[1]) % 64,
+            kind,
+            data: raw[3..data_end].to_vec(),
+        };
+        if packet.kind == b'E' {
+            log::info!(
+                "Kermit: transfer ended by the remote: {}",
+                String::from_utf8_lossy(&self.incoming_prefixes.decode(&packet.data))
+            );
+            self.phase = Phase::Done;
+            return;
+        }
+        match self.direction {
+            TransferDirection::Receive => self.receive_packet(packet),
+            TransferDirection::Send => self.send_packet(packet),
+        }
+    }
+
+    fn corrupt(&mut self) {
+        match self.direction {
+            TransferDirection::Receive => self.send_nak(),
+            TransferDirection::Send => self.resend(),
+        }
+    }
+
+    fn receive_packet(&mut self, packet: Packet) {
+        if packet.seq != self.seq {
+            // Our acknowledgement got lost; repeat it
+            if packet.seq == (self.seq + 63) % 64 {
+                self.resend();
+            } else {
+                self.send_nak();
+            }
+            return;
+        }
+        match packet.kind {
+            b'S' => {
+                let (qbin, check) = self.negotiate(&packet.data);
+                let params = self.params(qbin, check);
+                self.ack(&params);
+                self.negotiated = true;
+                return;
+            }
+            b'F' => {
+                let path = self.incoming_prefixes.decode(&packet.data);
+                let path = String::from_utf8_lossy(&path);
+                let name = path
+                    .rsplit(['/', '\\'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                if name.is_empty() {
+                    self.error("Bad file name");
+                    return;
+                }
+                self.incoming = Some(Incoming {
+                    name,
+                    data: Vec::new(),
+                });
+            }
+            b'A' => {
+                if attribute_size(&packet.data).is_some_and(|size| size > MAX_FILE_SIZE) {
+                    log::warn!("Kermit: refusing a file over the size limit");
+                    self.ack(b"N1");
+                    return;
+                }
+            }
+            b'D' => {
+                let data = self.incoming_prefixes.decode(&packet.data);
+                let Some(ref mut file) = self.incoming else {
+                    self.error("Data without a file header");
+                    return;
+                };
+                if (file.data.len() + data.len()) as u64 > MAX_FILE_SIZE {
+                    log::warn!("Kermit: {} is too large, cancelling", file.name);
+                    self.error("File too large");
+                    return;
+                }
+                file.data.extend(data);
+            }
+            b'Z' => {
+                let discard = self.incoming_prefixes.decode(&packet.data) == b"D";
+                if let Some(file) = self.incoming.take().filter(|_| !discard) {
+                    log::info!("Kermit: received {} ({} bytes)", file.name, file.data.len());
+                    self.events.push(TransferEvent::Received {
+                        name: file.name,
+                        data: file.data,
+                    });
+                }
+            }
+            b'B' => {
+                self.ack(&[]);
+                self.phase = Phase::Done;
+                return;
+            }
+            _ => {
+                self.error("Unsupported packet type");
+                return;
+            }
+        }
+        self.ack(&[]);
+    }
+
+    fn send_packet(&mut self, packet: Packet) {
+        if self.phase == Phase::AwaitingFiles {
+            if packet.kind == b'N' && !std::mem::replace(&mut self.requested, true) {
+                self.events.push(TransferEvent::SendRequested);
+            }
+            return;
+        }
+        // A NAK for the next packet implies the current one arrived
+        let acked = match packet.kind {
+            b'Y' => packet.seq == self.seq,
+            b'N' => packet.seq == (self.seq + 1) % 64,
+            _ => false,
+        };
+        if !acked {
+            if packet.kind == b'N' {
+                self.resend();
+            }
+            return;
+        }
+        self.seq = (self.seq + 1) % 64;
+        match self.phase {
+            Phase::Init => {
+                if packet.kind == b'Y' {
+                    self.accept_params(&packet.data);
+                }
+                self.negotiated = true;
+                self.send_file_header();
+            }
+            Phase::File => self.send_data(),
+            Phase::Data { len } => {
+                // "X" skips this file, "Z" the rest of them
+                match packet.data.first() {
+                    Some(b'X') => self.send(b'Z', b"D"),
+                    Some(b'Z') => {
+                        self.outgoing.truncate(1);
+                        self.send(b'Z', b"D");
+                    }
+                    _ => {
+                        self.position += len;
+                        self.send_data();
+                        return;
+                    }
+                }
+                self.phase = Phase::Eof;
+            }
+            Phase::Eof => {
+                self.outgoing.pop_front();
+                self.send_file_header();
+            }
+            Phase::Break => self.phase = Phase::Done,
+            Phase::Receiving | Phase::AwaitingFiles | Phase::Done => {}
+        }
+    }
+
+    /// Our Send-Init parameters
+    fn params(&self, qbin: u8, check: u8) -> Vec<u8> {
+        vec![
+            tochar(MAXL),
+            tochar(10),
+            tochar(0),
+            ctl(0),
+            tochar(CR),
+            QCTL,
+            qbin,
+            check,
+            REPT,
+        ]
+    }
+
+    /// Take the sender's Send-Init parameters; returns our answers for
+    /// 8th-bit prefixing and the block check
+    fn negotiate(&mut self, params: &[u8]) -> (u8, u8) {
+        self.take_params(params);
+        self.check = match params.get(7) {
+            Some(&check @ (b'1' | b'2' | b'3')) => check,
+            _ => b'1',
+        };
+        let qbin = if self.outgoing_prefixes.qbin.is_some() {
+            b'Y'
+        } else {
+            b'N'
+        };
+        (qbin, self.check)
+    }
+
+    /// Take the receiver's answer to our Send-Init
+    fn accept_params(&mut self, params: &[u8]) {
+        self.take_params(params);
+        self.check = match params.get(7) {
+            Some(&CHKT) => CHKT,
+            _ => b'1',
+        };
+    }
+
+    /// Packet length and prefixes from the other side's parameters. 8th-bit
+    /// prefixing is on when it names a prefix: we offer it but never ask.
+    fn take_params(&mut self, params: &[u8]) {
+        if let Some(&maxl) = params.first() {
+            self.their_maxl = unchar(maxl).clamp(20, MAXL);
+        }
+        if let Some(&qctl) = params.get(5).filter(|&&c| is_prefix(c)) {
+            self.incoming_prefixes.qctl = qctl;
+        }
+        let qbin = params.get(6).copied().filter(|&c| is_prefix(c));
+        let rept = params.get(8).copied().filter(|&c| c == REPT);
+        for prefixes in [&mut self.incoming_prefixes, &mut self.outgoing_prefixes] {
+            prefixes.qbin = qbin;
+            prefixes.rept = rept;
+        }
+    }
+
+    /// Header for the next file, or end of transmission when none are left
+    fn send_file_header(&mut self) {
+        let Some(file) = self.outgoing.front() else {
+            self.send(b'B', &[]);
+            self.phase = Phase::Break;
+            return;
+        };
+        let room = self.data_room();
+        let (name, _) = self.outgoing_prefixes.encode(file.name.as_bytes(), room);
+        self.position = 0;
+        self.send(b'F', &name);
+        self.phase = Phase::File;
+    }
+
+    /// Next data packet of the current file, or end of file
+    fn send_data(&mut self) {
+        let Some(file) = self.outgoing.front() else {
+            return;
+        };
+        let rest = &file.data[self.position.min(file.data.len())..];
+        if rest.is_empty() {
+            self.send(b'Z', &[]);
+            self.phase = Phase::Eof;
+            return;
+        }
+        let (data, len) = self.outgoing_prefixes.encode(rest, self.data_room());
+        self.send(b'D', &data);
+        self.phase = Phase::Data { len };
+    }
+
+    /// Room for data in the packets we send
+    fn data_room(&self) -> usize {
+        self.their_maxl as usize - 2 - check_len(self.check)
+    }
+
+    fn ack(&mut self, data: &[u8]) {
+        self.send(b'Y', data);
+        self.seq = (self.seq + 1) % 64;
+    }
+
+    fn send_nak(&mut self) {
+        let mut out = Vec::new();
+        encode_packet(&mut out, self.seq, b'N', &[], self.current_check());
+        self.output.extend(out);
+    }
+
+    /// Error packet, ending the transfer
+    fn error(&mut self, message: &str) {
+        self.send(b'E', message.as_bytes());
+        self.phase = Phase::Done;
+    }
+
+    fn send(&mut self, kind: u8, data: &[u8]) {
+        let check = if kind == b'S' {
+            b'1'
+        } else {
+            self.current_check()
+        };
+        self.last_packet.clear();
+        encode_packet(&mut self.last_packet, self.seq, kind, data, check);
+        self.output.extend_from_slice(&self.last_packet);
+    }
+
+    fn resend(&mut self) {
+        self.output.extend_from_slice(&self.last_packet);
+    }
+
+    fn current_check(&self) -> u8 {
+        if self.negotiated {
+            self.check
+        } else {
+            b'1'
+        }
+    }
+}
+
+/// Whether `c` can serve as a prefix character
+fn is_prefix(c: u8) -> bool {
+    (33..=62).contains(&c) || (96..=126).contains(&c)
+}
+
+/// File size from an Attribute packet (`1`, or `!` in kilobytes)
+fn attribute_size(data: &[u8]) -> Option<u64> {
+    let mut rest = data;
+    while let [tag, len, tail @ ..] = rest {
+        let len = (unchar(*len) as usize).min(tail.len());
+        let value = std::str::from_utf8(&tail[..len]).ok();
+        match tag {
+            b'1' => return value?.parse().ok(),
+            b'!' => return value?.parse::<u64>().ok().map(|k| k * 1024),
+            _ => {}
+        }
+        rest = &tail[len..];
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What a waiting `kermit -r` sends
+    fn nak() -> Vec<u8> {
+        let mut out = Vec::new();
+        encode_packet(&mut out, 0, b'N', &[], b'1');
+        out
+    }
+
+    /// Run a transfer between our sender and our receiver
+    fn transfer(files: Vec<TransferFile>, mangle: impl Fn(&mut Vec<u8>)) -> Vec<TransferEvent> {
+        let mut receiver = Kermit::new(TransferDirection::Receive);
+        let mut sender = Kermit::new(TransferDirection::Send);
+        sender.feed(&nak());
+        assert_eq!(sender.take_events(), [TransferEvent::SendRequested]);
+        sender.send_files(files);
+
+        let mut received = Vec::new();
+        for _ in 0..1000 {
+            let mut to_receiver = sender.take_output();
+            mangle(&mut to_receiver);
+            receiver.feed(&to_receiver);
+            received.extend(receiver.take_events());
+            let to_sender = receiver.take_output();
+            sender.feed(&to_sender);
+            if sender.is_done() && receiver.is_done() {
+                return received;
+            }
+        }
+        panic!("transfer did not finish");
+    }
+
+    #[test]
+    fn test_detect() {
+        let mut data = b"$ kermit -s notes.txt\r\n".to_vec();
+        let start = data.len();
+        encode_packet(&mut data, 0, b'S', b"~* @-#Y3~", b'1');
+        assert_eq!(detect(&data), Some((start, TransferDirection::Receive)));
+        assert_eq!(detect(&nak()), Some((0, TransferDirection::Send)));
+        assert_eq!(detect(b"\x01# D"), None);
+        assert_eq!(detect(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_block_checks() {
+        // Type 1 check of the classic "N" packet for sequence 0
+        assert_eq!(nak(), b"\x01# N3\r");
+        assert_eq!(crc16(b"123456789"), 0x2189);
+        assert_eq!(block_check(b"# N", b'3').len(), 3);
+    }
+
+    #[test]
+    fn test_prefixes_round_trip() {
+        let data: Vec<u8> = (0..=255u8).chain([b'x'; 40]).chain(*b"##~~&&").collect();
+        for (qbin, rept) in [(None, None), (Some(b'&'), Some(REPT))] {
+            let prefixes = Prefixes {
+                qctl: QCTL,
+                qbin,
+                rept,
+            };
+            let mut encoded = Vec::new();
+            let mut rest = &data[..];
+            while !rest.is_empty() {
+                let (field, used) = prefixes.encode(rest, 90);
+                assert!(field.len() <= 90 && used > 0);
+                if qbin.is_some() {
+                    assert!(field.iter().all(|&b| (0x20..0x7f).contains(&b)));
+                }
+                encoded.extend(prefixes.decode(&field));
+                rest = &rest[used..];
+            }
+            assert_eq!(encoded, data);
+        }
+    }
+
+    #[test]
+    fn test_transfer() {
+        // Every byte value, so all the prefixes are exercised
+        let binary: Vec<u8> = (0..5000u32).map(|i| (i * 7) as u8).collect();
+        let files = vec![
+            TransferFile {
+                name: "all-bytes.bin".into(),
+                data: binary.clone(),
+            },
+            TransferFile {
+                name: "empty".into(),
+                data: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            transfer(files, |_| {}),
+            [
+                TransferEvent::Received {
+                    name: "all-bytes.bin".into(),
+                    data: binary,
+                },
+                TransferEvent::Received {
+                    name: "empty".into(),
+                    data: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_corrupt_packet_is_resent() {
+        let data = vec![b'x'; 3000];
+        let files = vec![TransferFile {
+            name: "x.txt".into(),
+            data: data.clone(),
+        }];
+        // Damage the first data packet, once
+        let packets = std::cell::Cell::new(0);
+        let events = transfer(files, |out| {
+            if packets.replace(packets.get() + 1) == 2 {
+                out[5] ^= 0x01;
+            }
+        });
+        assert_eq!(
+            events,
+            [TransferEvent::Received {
+                name: "x.txt".into(),
+                data,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_output_after_transfer_is_returned() {
+        let mut sender = Kermit::new(TransferDirection::Send);
+        assert_eq!(sender.feed(&nak()), nak().len());
+        sender.send_files(Vec::new());
+        assert!(sender.is_done());
+        assert_eq!(sender.take_output()[3], b'E');
+
+        // The remote gives up; its shell prompt is regular output again
+        let mut receiver = Kermit::new(TransferDirection::Receive);
+        let mut data = Vec::new();
+        encode_packet(&mut data, 0, b'E', b"Timeout", b'1');
+        data.extend(b"$ ");
+        assert_eq!(receiver.feed(&data), data.len() - 3);
+        assert!(receiver.is_done());
+    }
+}
@@ -0,0 +1,1111 @@
+//! Kitty graphics protocol
+//!
+//! Decodes kitty graphics commands (`ESC _ G <control> ; <payload> ESC \`)
+//! and applies them to a [`Screen`]. Transmitted images are kept in
+//! [`KittyGraphics`]; each placement is cropped and scaled to its final
+//! pixel size and stored as a regular [`TerminalImage`](crate::TerminalImage),
+//! so every renderer that draws Sixel and iTerm2 images draws kitty images
+//! too.
+//!
+//! Supported: direct (`t=d`), file (`t=f`), temporary file (`t=t`) and
+//! shared-memory (`t=s`) transmission, zlib compression (`o=z`), chunked
+//! payloads (`m=1`), RGB/RGBA/PNG data, placements with source rectangles,
+//! cell sizes and pixel offsets, queries, and all deletion specifiers.
+//! Animation commands are rejected with `EINVAL`.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use base64::Engine;
+use image::{imageops, RgbaImage};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::image_decode::decode_image;
+use crate::screen::Screen;
+use crate::sixel::SixelImage;
+
+/// Largest accepted image dimension in pixels (width or height)
+const MAX_DIMENSION: u32 = 10_000;
+
+/// Largest payload accepted from a single transmission, after decompression
+const MAX_DATA_SIZE: usize = 400 * 1024 * 1024;
+
+/// Total image data kept before the oldest images are evicted
+const MAX_STORAGE: usize = 320 * 1024 * 1024;
+
+/// Largest cell count accepted for `c=` / `r=`
+const MAX_CELLS: u32 = 1000;
+
+/// Substring required in temporary file names (`t=t`), as in the spec
+const TEMP_FILE_MARKER: &str = "tty-graphics-protocol";
+
+/// Error returned to the client; `Display` is the protocol message.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum KittyError {
+    #[error("EINVAL:{0}")]
+    Invalid(String),
+    #[error("ENOENT:{0}")]
+    NotFound(String),
+    #[error("ENODATA:{0}")]
+    NoData(String),
+    #[error("EBADPNG:{0}")]
+    BadPng(String),
+    #[error("EFBIG:{0}")]
+    TooBig(String),
+    #[error("EIO:{0}")]
+    Io(String),
+}
+
+/// Action requested by a command (`a=` key)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KittyAction {
+    /// `t`: transmit data only
+    #[default]
+    Transmit,
+    /// `T`: transmit and display
+    TransmitAndDisplay,
+    /// `p`: display a previously transmitted image
+    Put,
+    /// `d`: delete placements and/or images
+    Delete,
+    /// `q`: check whether an image could be loaded, without storing it
+    Query,
+    /// Anything else (animation frames and control)
+    Unsupported(u8),
+}
+
+/// Pixel format of the transmitted data (`f=` key)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KittyFormat {
+    /// `f=24`: 3 bytes per pixel
+    Rgb,
+    /// `f=32`: 4 bytes per pixel
+    #[default]
+    Rgba,
+    /// `f=100`: PNG file
+    Png,
+}
+
+/// Where the image data comes from (`t=` key)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KittyMedium {
+    /// `d`: base64 payload
+    #[default]
+    Direct,
+    /// `f`: path to a regular file
+    File,
+    /// `t`: path to a temporary file, deleted after reading
+    TempFile,
+    /// `s`: name of a POSIX shared-memory object, unlinked after reading
+    SharedMemory,
+}
+
+/// A parsed kitty graphics command (control data only)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KittyCommand {
+    pub action: KittyAction,
+    pub format: KittyFormat,
+    pub medium: KittyMedium,
+    /// `o=z`: payload is zlib-compressed
+    pub compressed: bool,
+    /// `s=` / `v=`: pixel size of raw RGB(A) data
+    pub data_width: u32,
+    pub data_height: u32,
+    /// `S=` / `O=`: size and offset when reading from a file or shared memory
+    pub data_size: usize,
+    pub data_offset: usize,
+    /// `i=`: image id
+    pub image_id: u32,
+    /// `I=`: image number
+    pub image_number: u32,
+    /// `p=`: placement id
+    pub placement_id: u32,
+    /// `m=1`: more chunks follow
+    pub more: bool,
+    /// `x=` / `y=` / `w=` / `h=`: source rectangle (cell coordinates for `a=d`)
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// `X=` / `Y=`: pixel offset inside the first cell
+    pub cell_x_offset: u32,
+    pub cell_y_offset: u32,
+    /// `c=` / `r=`: display size in cells
+    pub columns: u32,
+    pub rows: u32,
+    /// `C=1`: do not move the cursor after placing
+    pub no_cursor_move: bool,
+    /// `q=`: 1 suppresses OK responses, 2 suppresses all responses
+    pub quiet: u8,
+    /// `z=`: stacking order
+    pub z_index: i32,
+    /// `d=`: deletion specifier
+    pub delete: u8,
+}
+
+impl KittyCommand {
+    /// Parse the comma-separated `key=value` control data.
+    ///
+    /// Unknown keys and malformed values are ignored.
+    pub fn parse(control: &str) -> Self {
+        let mut cmd = Self {
+            delete: b'a',
+            ..Self::default()
+        };
+        for pair in control.split(',') {
+            let Some((key, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let uint = || value.parse::<u32>().unwrap_or(0);
+            let first = value.bytes().next().unwrap_or(0);
+            match key {
+                "a" => {
+                    cmd.action = match first {
+                        b't' => KittyAction::Transmit,
+                        b'T' => KittyAction::TransmitAndDisplay,
+                        b'p' => KittyAction::Put,
+                        b'd' => KittyAction::Delete,
+                        b'q' => KittyAction::Query,
+                        other => KittyAction::Unsupported(other),
+                    }
+                }
+                "f" => {
+                    cmd.format = match uint() {
+                        24 => KittyFormat::Rgb,
+                        100 => KittyFormat::Png,
+                        _ => KittyFormat::Rgba,
+                    }
+                }
+                "t" => {
+                    cmd.medium = match first {
+                        b'f' => KittyMedium::File,
+                        b't' => KittyMedium::TempFile,
+                        b's' => KittyMedium::SharedMemory,
+                        _ => KittyMedium::Direct,
+                    }
+                }
+                "o" => cmd.compressed = first == b'z',
+                "s" => cmd.data_width = uint(),
+                "v" => cmd.data_height = uint(),
+                "S" => cmd.data_size = uint() as usize,
+                "O" => cmd.data_offset = uint() as usize,
+                "i" => cmd.image_id = uint(),
+                "I" => cmd.image_number = uint(),
+                "p" => cmd.placement_id = uint(),
+                "m" => cmd.more = first == b'1',
+                "x" => cmd.x = uint(),
+                "y" => cmd.y = uint(),
+                "w" => cmd.width = uint(),
+                "h" => cmd.height = uint(),
+                "X" => cmd.cell_x_offset = uint(),
+                "Y" => cmd.cell_y_offset = uint(),
+                "c" => cmd.columns = uint().min(MAX_CELLS),
+                "r" => cmd.rows = uint().min(MAX_CELLS),
+                "C" => cmd.no_cursor_move = first == b'1',
+                "q" => cmd.quiet = value.parse().unwrap_or(0),
+                "z" => cmd.z_index = value.parse().unwrap_or(0),
+                "d" => cmd.delete = first,
+                _ => {}
+            }
+        }
+        cmd
+    }
+}
+
+/// A transmitted image, decoded to RGBA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KittyImage {
+    /// Client-visible image id
+    pub id: u32,
+    /// Image number (`I=`) it was transmitted with, 0 if none
+    pub number: u32,
+    /// RGBA pixel data
+    pub data: Arc<Vec<u8>>,
+    /// Pixel width
+    pub width: u32,
+    /// Pixel height
+    pub height: u32,
+    /// Transmission order, used for eviction
+    age: u64,
+}
+
+/// A displayed placement of a [`KittyImage`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KittyPlacement {
+    /// Image being displayed
+    pub image_id: u32,
+    /// Client placement id, 0 if none
+    pub placement_id: u32,
+    /// Id of the [`TerminalImage`](crate::TerminalImage) that renders it
+    pub terminal_image_id: u64,
+    /// Stacking order
+    pub z_index: i32,
+}
+
+/// A chunked transmission waiting for its final chunk
+#[derive(Debug)]
+struct PendingTransmission {
+    command: KittyCommand,
+    payload: Vec<u8>,
+}
+
+/// Kitty graphics state kept on a [`Screen`]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct KittyGraphics {
+    images: HashMap<u32, KittyImage>,
+    /// Newest image id for each image number
+    numbers: HashMap<u32, u32>,
+    placements: Vec<KittyPlacement>,
+    #[serde(skip)]
+    pending: Option<PendingTransmission>,
+    next_age: u64,
+    next_auto_id: u32,
+}
+
+impl KittyGraphics {
+    /// Get a stored image by id
+    pub fn image(&self, id: u32) -> Option<&KittyImage> {
+        self.images.get(&id)
+    }
+
+    /// All current placements
+    pub fn placements(&self) -> &[KittyPlacement] {
+        &self.placements
+    }
+
+    /// The placement rendered by the [`TerminalImage`](crate::TerminalImage)
+    /// with id `terminal_image_id`, if it is a kitty image
+    pub fn placement_for(&self, terminal_image_id: u64) -> Option<&KittyPlacement> {
+        self.placements
+            .iter()
+            .find(|p| p.terminal_image_id == terminal_image_id)
+    }
+
+    /// Drop all images, placements and partial transmissions
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Drop placements matching `pred` whose rendered images are already gone
+    pub(crate) fn forget_placements(&mut self, pred: impl Fn(&KittyPlacement) -> bool) {
+        self.placements.retain(|p| !pred(p));
+    }
+
+    fn store(&mut self, mut image: KittyImage) {
+        image.age = self.next_age;
+        self.next_age += 1;
+        if image.number != 0 {
+            self.numbers.insert(image.number, image.id);
+        }
+        self.images.insert(image.id, image);
+
+        let mut total: usize = self.images.values().map(|img| img.data.len()).sum();
+        while total > MAX_STORAGE && self.images.len() > 1 {
+            let Some(oldest) = self.images.values().min_by_key(|img| img.age).map(|i| i.id) else {
+                break;
+            };
+            if let Some(evicted) = self.images.remove(&oldest) {
+                total -= evicted.data.len();
+            }
+        }
+    }
+
+    fn auto_id(&mut self) -> u32 {
+        loop {
+            self.next_auto_id = self.next_auto_id.wrapping_add(1).max(1 << 24);
+            if !self.images.contains_key(&self.next_auto_id) {
+                return self.next_auto_id;
+            }
+        }
+    }
+
+    fn resolve_id(&self, cmd: &KittyCommand) -> Option<u32> {
+        if cmd.image_id != 0 {
+            Some(cmd.image_id)
+        } else if cmd.image_number != 0 {
+            self.numbers.get(&cmd.image_number).copied()
+        } else {
+            None
+        }
+    }
+}
+
+/// Split an APC payload (after the leading `G`) into command and payload.
+pub fn parse_apc(data: &[u8]) -> (KittyCommand, &[u8]) {
+    let (control, payload) = match data.iter().position(|&b| b == b';') {
+        Some(idx) => (&data[..idx], &data[idx + 1..]),
+        None => (data, &[][..]),
+    };
+    (
+        KittyCommand::parse(&String::from_utf8_lossy(control)),
+        payload,
+    )
+}
+
+/// Handle one kitty graphics APC (the bytes after `ESC _ G`, before `ESC \`).
+pub fn handle_apc(screen: &mut Screen, data: &[u8]) {
+    let (cmd, payload) = parse_apc(data);
+
+    // Continuation chunks only carry `m` (and maybe `q`); the first chunk's
+    // control data applies to the whole transmission.
+    let (cmd, payload) = match screen.kitty.pending.take() {
+        Some(mut pending) => {
+            pending.payload.extend_from_slice(payload);
+            if pending.payload.len() > MAX_DATA_SIZE {
+                respond(
+                    screen,
+                    &pending.command,
+                    0,
+                    Err(KittyError::TooBig("payload too large".into())),
+                );
+                return;
+            }
+            if cmd.more {
+                screen.kitty.pending = Some(pending);
+                return;
+            }
+            (pending.command, pending.payload)
+        }
+        None if cmd.more => {
+            screen.kitty.pending = Some(PendingTransmission {
+                command: cmd,
+                payload: payload.to_vec(),
+            });
+            return;
+        }
+        None => (cmd, payload.to_vec()),
+    };
+
+    // Placements whose image was cleared or scrolled away are gone.
+    let live: Vec<bool> = screen
+        .kitty
+        .placements
+        .iter()
+        .map(|p| screen.image_by_id(p.terminal_image_id).is_some())
+        .collect();
+    let mut live = live.into_iter();
+    screen
+        .kitty
+        .placements
+        .retain(|_| live.next().unwrap_or(false));
+
+    match cmd.action {
+        KittyAction::Transmit | KittyAction::TransmitAndDisplay => {
+            let result = load_image(&cmd, &payload);
+            let id = match result {
+                Ok((data, width, height)) => {
+                    let id = if cmd.image_id != 0 {
+                        cmd.image_id
+                    } else {
+                        screen.kitty.auto_id()
+                    };
+                    remove_placements(screen, |p| p.image_id == id);
+                    screen.kitty.store(KittyImage {
+                        id,
+                        number: cmd.image_number,
+                        data: Arc::new(data),
+                        width,
+                        height,
+                        age: 0,
+                    });
+                    id
+                }
+                Err(e) => {
+                    respond(screen, &cmd, cmd.image_id, Err(e));
+                    return;
+                }
+            };
+            let result = if cmd.action == KittyAction::TransmitAndDisplay {
+                place(screen, &cmd, id)
+            } else {
+                Ok(())
+            };
+            respond(screen, &cmd, id, result);
+        }
+        KittyAction::Query => {
+            let result = load_image(&cmd, &payload).map(|_| ());
+            respond(screen, &cmd, cmd.image_id, result);
+        }
+        KittyAction::Put => match screen.kitty.resolve_id(&cmd) {
+            Some(id) if screen.kitty.images.contains_key(&id) => {
+                let result = place(screen, &cmd, id);
+                respond(screen, &cmd, id, result);
+            }
+            _ => respond(
+                screen,
+                &cmd,
+                cmd.image_id,
+                Err(KittyError::NotFound("no such image".into())),
+            ),
+        },
+        KittyAction::Delete => delete(screen, &cmd),
+        KittyAction::Unsupported(a) => {
+            log::debug!("Unsupported kitty graphics action {:?}", a as char);
+            respond(
+                screen,
+                &cmd,
+                cmd.image_id,
+                Err(KittyError::Invalid("unsupported action".into())),
+            );
+        }
+    }
+}
+
+/// Queue the response for a command, honoring `q=` and the rule that
+/// commands without `i=` or `I=` get no response.
+fn respond(screen: &mut Screen, cmd: &KittyCommand, id: u32, result: Result<(), KittyError>) {
+    if cmd.image_id == 0 && cmd.image_number == 0 {
+        return;
+    }
+    let message = match result {
+        Ok(()) if cmd.quiet >= 1 => return,
+        Ok(()) => "OK".to_string(),
+        Err(_) if cmd.quiet >= 2 => return,
+        Err(e) => e.to_string(),
+    };
+    let mut keys = Vec::new();
+    if id != 0 {
+        keys.push(format!("i={id}"));
+    }
+    if cmd.image_number != 0 {
+        keys.push(format!("I={}", cmd.image_number));
+    }
+    if cmd.placement_id != 0 {
+        keys.push(format!("p={}", cmd.placement_id));
+    }
+    let response = format!("\x1b_G{};{}\x1b\\", keys.join(","), message);
+    screen.queue_response(response.into_bytes());
+}
+
+/// Read, decompress and decode the image data of a transmission.
+///
+/// Returns RGBA pixels and the image size.
+fn load_image(cmd: &KittyCommand, payload: &[u8]) -> Result<(Vec<u8>, u32, u32), KittyError> {
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(payload.trim_ascii())
+        .map_err(|e| KittyError::Invalid(format!("bad base64: {e}")))?;
+
+    let raw = match cmd.medium {
+        KittyMedium::Direct => decoded,
+        KittyMedium::File | KittyMedium::TempFile => {
+            let path = PathBuf::from(String::from_utf8_lossy(&decoded).into_owned());
+            let temp = cmd.medium == KittyMedium::TempFile;
+            read_file(&path, cmd.data_offset, cmd.data_size, temp)?
+        }
+        KittyMedium::SharedMemory => {
+            let name = String::from_utf8_lossy(&decoded).into_owned();
+            read_shared_memory(&name, cmd.data_offset, cmd.data_size)?
+        }
+    };
+
+    let raw = if cmd.compressed {
+        let mut inflated = Vec::new();
+        flate2::read::ZlibDecoder::new(raw.as_slice())
+            .take(MAX_DATA_SIZE as u64 + 1)
+            .read_to_end(&mut inflated)
+            .map_err(|e| KittyError::Invalid(format!("bad zlib data: {e}")))?;
+        if inflated.len() > MAX_DATA_SIZE {
+            return Err(KittyError::TooBig("decompressed data too large".into()));
+        }
+        inflated
+    } else {
+        raw
+    };
+
+    match cmd.format {
+        KittyFormat::Png => {
+            let image = decode_image(&raw).map_err(|e| KittyError::BadPng(e.to_string()))?;
+            Ok((image.data, image.width as u32, image.height as u32))
+        }
+        KittyFormat::Rgb | KittyFormat::Rgba => {
+            let (w, h) = (cmd.data_width, cmd.data_height);
+            if w == 0 || h == 0 {
+                return Err(KittyError::Invalid("missing image size".into()));
+            }
+            if w > MAX_DIMENSION || h > MAX_DIMENSION {
+                return Err(KittyError::TooBig(format!("{w}x{h} image too large")));
+            }
+            let pixels = w as usize * h as usize;
+            let data = if cmd.format == KittyFormat::Rgb {
+                if raw.len() < pixels * 3 {
+                    return Err(KittyError::NoData("insufficient image data".into()));
+                }
+                raw[..pixels * 3]
+                    .chunks_exact(3)
+                    .flat_map(|px| [px[0], px[1], px[2], 0xff])
+                    .collect()
+            } else {
+                if raw.len() < pixels * 4 {
+                    return Err(KittyError::NoData("insufficient image data".into()));
+                }
+                let mut raw = raw;
+                raw.truncate(pixels * 4);
+                raw
+            };
+            Ok((data, w, h))
+        }
+    }
+}
+
+/// Read `size` bytes (0 = to the end) at `offset` from a regular file.
+///
+/// Temporary files must live in a temp directory and carry the protocol
+/// marker in their name; they are deleted once read. Every failure gets the
+/// same reply, so the remote side cannot probe which paths exist.
+fn read_file(path: &Path, offset: usize, size: usize, temp: bool) -> Result<Vec<u8>, KittyError> {
+    try_read_file(path, offset, size, temp)
+        .ok_or_else(|| KittyError::Io("failed to read file".into()))
+}
+
+fn try_read_file(path: &Path, offset: usize, size: usize, temp: bool) -> Option<Vec<u8>> {
+    let path = path.canonicalize().ok()?;
+    if is_forbidden_path(&path, temp) || (temp && !is_temp_file(&path)) {
+        return None;
+    }
+    let metadata = std::fs::metadata(&path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    let len = metadata.len() as usize;
+    if offset > len {
+        return None;
+    }
+    let want = if size == 0 { len - offset } else { size };
+    if want > MAX_DATA_SIZE {
+        return None;
+    }
+
+    let mut file = std::fs::File::open(&path).ok()?;
+    std::io::Seek::seek(&mut file, std::io::SeekFrom::Start(offset as u64)).ok()?;
+    let mut data = Vec::with_capacity(want);
+    file.take(want as u64).read_to_end(&mut data).ok()?;
+
+    if temp {
+        let _ = std::fs::remove_file(&path);
+    }
+    Some(data)
+}
+
+/// Whether `path` (canonical) is a pseudo-filesystem that must never be
+/// read on behalf of the remote side. Temporary files may still come from
+/// `/dev/shm`.
+fn is_forbidden_path(path: &Path, temp: bool) -> bool {
+    if temp && path.starts_with("/dev/shm") {
+        return false;
+    }
+    ["/proc", "/sys", "/dev"]
+        .iter()
+        .any(|dir| path.starts_with(dir))
+}
+
+fn is_temp_file(path: &Path) -> bool {
+    let named = path
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().contains(TEMP_FILE_MARKER));
+    let mut dirs = vec![std::env::temp_dir(), PathBuf::from("/tmp")];
+    if cfg!(target_os = "linux") {
+        dirs.push(PathBuf::from("/dev/shm"));
+    }
+    named
+        && dirs
+            .iter()
+            .filter_map(|d| d.canonicalize().ok())
+            .any(|d| path.starts_with(d))
+}
+
+/// Copy data out of a POSIX shared-memory object and unlink it.
+///
+/// Like [`read_file`], every failure gets the same reply.
+#[cfg(unix)]
+fn read_shared_memory(name: &str, offset: usize, size: usize) -> Result<Vec<u8>, KittyError> {
+    try_read_shared_memory(name, offset, size)
+        .ok_or_else(|| KittyError::Io("failed to read shared memory".into()))
+}
+
+#[cfg(unix)]
+fn try_read_shared_memory(name: &str, offset: usize, size: usize) -> Option<Vec<u8>> {
+    let cname = std::ffi::CString::new(name).ok()?;
+
+    // SAFETY: cname is a valid NUL-terminated string; the descriptor is
+    // closed on every path, and the mapping is only read within its length
+    // before being unmapped.
+    unsafe {
+        let fd = libc::shm_open(cname.as_ptr(), libc::O_RDONLY, 0);
+        if fd < 0 {
+            return None;
+        }
+        let mut stat: libc::stat = std::mem::zeroed();
+        if libc::fstat(fd, &mut stat) != 0 {
+            libc::close(fd);
+            return None;
+        }
+        let len = stat.st_size as usize;
+        let want = if size == 0 {
+            len.saturating_sub(offset)
+        } else {
+            size
+        };
+        if offset.saturating_add(want) > len {
+            libc::close(fd);
+            return None;
+        }
+        if want > MAX_DATA_SIZE {
+            libc::close(fd);
+            return None;
+        }
+        let data = if len == 0 {
+            Vec::new()
+        } else {
+            let map = libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            );
+            if map == libc::MAP_FAILED {
+                libc::close(fd);
+                return None;
+            }
+            let bytes = std::slice::from_raw_parts((map as *const u8).add(offset), want).to_vec();
+            libc::munmap(map, len);
+            bytes
+        };
+        libc::close(fd);
+        libc::shm_unlink(cname.as_ptr());
+        Some(data)
+    }
+}
+
+#[cfg(not(unix))]
+fn read_shared_memory(_name: &str, _offset: usize, _size: usize) -> Result<Vec<u8>, KittyError> {
+    Err(KittyError::Invalid(
+        "shared memory transmission not supported".into(),
+    ))
+}
+
+/// Display image `id` at the cursor according to `cmd`.
+fn place(screen: &mut Screen, cmd: &KittyCommand, id: u32) -> Result<(), KittyError> {
+    let Some(image) = screen.kitty.images.get(&id).cloned() else {
+        return Err(KittyError::NotFound("no such image".into()));
+    };
+
+    // Source rectangle, clamped to the image
+    let x = cmd.x.min(image.width);
+    let y = cmd.y.min(image.height);
+    let src_w = match cmd.width {
+        0 => image.width - x,
+        w => w.min(image.width - x),
+    };
+    let src_h = match cmd.height {
+        0 => image.height - y,
+        h => h.min(image.height - y),
+    };
+    if src_w == 0 || src_h == 0 {
+        return Err(KittyError::Invalid("empty source rectangle".into()));
+    }
+
+    let rgba = RgbaImage::from_raw(image.width, image.height, image.data.to_vec())
+        .ok_or_else(|| KittyError::Invalid("corrupt image data".into()))?;
+    let mut pixels = imageops::crop_imm(&rgba, x, y, src_w, src_h).to_image();
+
+    // Scale to the requested cell box, keeping the aspect ratio when only
+    // one side is given
+    let cell_w = screen.cell_width_hint().max(1.0);
+    let cell_h = screen.cell_height_hint().max(1.0);
+    let target = match (cmd.columns, cmd.rows) {
+        (0, 0) => None,
+        (c, 0) => {
+            let w = c as f64 * cell_w;
+            Some((w, src_h as f64 * w / src_w as f64))
+        }
+        (0, r) => {
+            let h = r as f64 * cell_h;
+            Some((src_w as f64 * h / src_h as f64, h))
+        }
+        (c, r) => Some((c as f64 * cell_w, r as f64 * cell_h)),
+    };
+    if let Some((w, h)) = target {
+        let w = (w.round() as u32).clamp(1, MAX_DIMENSION);
+        let h = (h.round() as u32).clamp(1, MAX_DIMENSION);
+        if (w, h) != pixels.dimensions() {
+            pixels = imageops::resize(&pixels, w, h, imageops::FilterType::Triangle);
+        }
+    }
+
+    // Pixel offsets inside the first cell become transparent padding
+    let off_x = cmd.cell_x_offset.min(cell_w as u32 - 1);
+    let off_y = cmd.cell_y_offset.min(cell_h as u32 - 1);
+    if off_x > 0 || off_y > 0 {
+        let (w, h) = pixels.dimensions();
+        let mut padded = RgbaImage::new(w + off_x, h + off_y);
+        imageops::replace(&mut padded, &pixels, off_x as i64, off_y as i64);
+        pixels = padded;
+    }
+
+    let (width, height) = pixels.dimensions();
+    let cols = if cmd.columns != 0 {
+        cmd.columns as usize
+    } else {
+        screen.image_cols_for_width(width as usize)
+    };
+    let rows = if cmd.rows != 0 {
+        cmd.rows as usize
+    } else {
+        screen.image_rows_for_height(height as usize)
+    };
+
+    if cmd.placement_id != 0 {
+        remove_placements(screen, |p| {
+            p.image_id == id && p.placement_id == cmd.placement_id
+        });
+    }
+
+    let col = screen.cursor.col;
+    let row = screen.cursor.row;
+    let terminal_image_id = screen.insert_image(
+        col,
+        row,
+        cols,
+        rows,
+        SixelImage {
+            data: pixels.into_raw(),
+            width: width as usize,
+            height: height as usize,
+        },
+    );
+    screen.kitty.placements.push(KittyPlacement {
+        image_id: id,
+        placement_id: cmd.placement_id,
+        terminal_image_id,
+        z_index: cmd.z_index,
+    });
+
+    if !cmd.no_cursor_move {
+        // Cursor ends up just after the image, on its last row
+        let last_row = row + rows.saturating_sub(1);
+        if last_row >= screen.height() {
+            let scroll_amount = last_row - screen.height() + 1;
+            screen.scroll_up(scroll_amount);
+            screen.cursor.row = screen.height() - 1;
+        } else {
+            screen.cursor.row = last_row;
+        }
+        screen.cursor.col = (col + cols).min(screen.width().saturating_sub(1));
+    }
+
+    log::debug!(
+        "Kitty image {} placed at ({}, {}) spanning {}x{} cells",
+        id,
+        col,
+        row,
+        cols,
+        rows
+    );
+    Ok(())
+}
+
+/// Remove placements matching `pred` and their rendered images.
+///
+/// Returns the ids of the images whose placements were removed.
+fn remove_placements(screen: &mut Screen, pred: impl Fn(&KittyPlacement) -> bool) -> Vec<u32> {
+    let (removed, kept): (Vec<_>, Vec<_>) = screen.kitty.placements.iter().partition(|p| pred(p));
+    screen.kitty.placements = kept;
+    let mut image_ids = Vec::new();
+    for placement in removed {
+        screen.remove_image(placement.terminal_image_id);
+        if !image_ids.contains(&placement.image_id) {
+            image_ids.push(placement.image_id);
+        }
+    }
+    image_ids
+}
+
+/// Apply an `a=d` command.
+///
+/// Lowercase specifiers remove placements only; uppercase ones also free
+/// the image data once no placement refers to it.
+fn delete(screen: &mut Screen, cmd: &KittyCommand) {
+    // Screen area covered by each placement, in visible cell coordinates
+    let area = |screen: &Screen, p: &KittyPlacement| -> Option<(i64, i64, i64, i64)> {
+        let img = screen.image_by_id(p.terminal_image_id)?;
+        let top = img.line as i64 - screen.scrollback().len() as i64;
+        Some((
+            img.col as i64,
+            top,
+            img.cell_width.max(1) as i64,
+            img.cell_height.max(1) as i64,
+        ))
+    };
+    let covers = |screen: &Screen, p: &KittyPlacement, col: Option<i64>, row: Option<i64>| {
+        area(screen, p).is_some_and(|(x, y, w, h)| {
+            col.is_none_or(|c| c >= x && c < x + w) && row.is_none_or(|r| r >= y && r < y + h)
+        })
+    };
+
+    let cursor = (screen.cursor.col as i64, screen.cursor.row as i64);
+    // Cell coordinates in delete commands are 1-based
+    let cell_x = cmd.x as i64 - 1;
+    let cell_y = cmd.y as i64 - 1;
+
+    let spec = cmd.delete;
+    let targets: Vec<KittyPlacement> = screen
+        .kitty
+        .placements
+        .iter()
+        .filter(|p| match spec.to_ascii_lowercase() {
+            b'a' => area(screen, p).is_some_and(|(_, y, _, h)| y + h > 0),
+            b'i' => {
+                p.image_id == cmd.image_id
+                    && (cmd.placement_id == 0 || p.placement_id == cmd.placement_id)
+            }
+            b'n' => {
+                screen.kitty.numbers.get(&cmd.image_number) == Some(&p.image_id)
+                    && (cmd.placement_id == 0 || p.placement_id == cmd.placement_id)
+            }
+            b'c' => covers(screen, p, Some(cursor.0), Some(cursor.1)),
+            b'p' => covers(screen, p, Some(cell_x), Some(cell_y)),
+            b'q' => covers(screen, p, Some(cell_x), Some(cell_y)) && p.z_index == cmd.z_index,
+            b'x' => covers(screen, p, Some(cell_x), None),
+            b'y' => covers(screen, p, None, Some(cell_y)),
+            b'z' => p.z_index == cmd.z_index,
+            b'r' => p.image_id >= cmd.x && p.image_id <= cmd.y,
+            _ => false,
+        })
+        .copied()
+        .collect();
+
+    let mut image_ids = remove_placements(screen, |p| targets.contains(p));
+
+    if spec.is_ascii_uppercase() {
+        // Images addressed directly are freed even without placements
+        match spec {
+            b'I' => image_ids.push(cmd.image_id),
+            b'N' => image_ids.extend(screen.kitty.numbers.get(&cmd.image_number).copied()),
+            b'R' => image_ids.extend(
+                screen
+                    .kitty
+                    .images
+                    .keys()
+                    .filter(|&&id| id >= cmd.x && id <= cmd.y),
+            ),
+            _ => {}
+        }
+        for id in image_ids {
+            if !screen.kitty.placements.iter().any(|p| p.image_id == id) {
+                if let Some(image) = screen.kitty.images.remove(&id) {
+                    if image.number != 0 && screen.kitty.numbers.get(&image.number) == Some(&id) {
+                        screen.kitty.numbers.remove(&image.number);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screen::ScreenConfig;
+    use crate::Parser;
+
+    fn screen() -> Screen {
+        let mut screen = Screen::new(80, 24, ScreenConfig::default());
+        screen.set_cell_width_hint(10.0);
+        screen.set_cell_height_hint(20.0);
+        screen
+    }
+
+    fn apc(control: &str, payload: &[u8]) -> Vec<u8> {
+        let mut seq = format!("\x1b_G{control}").into_bytes();
+        if !payload.is_empty() {
+            seq.push(b';');
+            seq.extend_from_slice(
+                base64::engine::general_purpose::STANDARD
+                    .encode(payload)
+                    .as_bytes(),
+            );
+        }
+        seq.extend_from_slice(b"\x1b\\");
+        seq
+    }
+
+    fn responses(screen: &mut Screen) -> Vec<String> {
+        screen
+            .take_pending_responses()
+            .into_iter()
+            .map(|r| String::from_utf8(r).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let cmd = KittyCommand::parse("a=T,f=24,s=10,v=20,i=7,p=3,m=1,c=4,C=1,q=2,z=-5,o=z");
+        assert_eq!(cmd.action, KittyAction::TransmitAndDisplay);
+        assert_eq!(cmd.format, KittyFormat::Rgb);
+        assert_eq!((cmd.data_width, cmd.data_height), (10, 20));
+        assert_eq!((cmd.image_id, cmd.placement_id), (7, 3));
+        assert!(cmd.more && cmd.no_cursor_move && cmd.compressed);
+        assert_eq!((cmd.columns, cmd.quiet, cmd.z_index), (4, 2, -5));
+        assert_eq!(KittyCommand::parse("").delete, b'a');
+    }
+
+    #[test]
+    fn test_transmit_and_display_chunked() {
+        let mut screen = screen();
+        let mut parser = Parser::new();
+        let pixels = vec![0x80u8; 20 * 40 * 4];
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&pixels);
+        let (first, rest) = encoded.as_bytes().split_at(4096);
+        let mut input = b"\x1b_Ga=T,f=32,s=20,v=40,i=5,m=1;".to_vec();
+        input.extend_from_slice(first);
+        input.extend_from_slice(b"\x1b\\\x1b_Gm=0;");
+        input.extend_from_slice(rest);
+        input.extend_from_slice(b"\x1b\\");
+        parser.parse(&mut screen, &input);
+
+        assert_eq!(responses(&mut screen), ["\x1b_Gi=5;OK\x1b\\"]);
+        let images = screen.visible_images();
+        assert_eq!(images.len(), 1);
+        assert_eq!((images[0].pixel_width, images[0].pixel_height), (20, 40));
+        assert_eq!((images[0].cell_width, images[0].cell_height), (2, 2));
+        // Cursor moves after the image on its last row
+        assert_eq!((screen.cursor.col, screen.cursor.row), (2, 1));
+        assert_eq!(screen.kitty_graphics().placements().len(), 1);
+    }
+
+    #[test]
+    fn test_rgb_compressed_scaled_placement() {
+        use std::io::Write;
+        let mut screen = screen();
+        let pixels = vec![0x10u8; 4 * 4 * 3];
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&pixels).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        Parser::new().parse(
+            &mut screen,
+            &apc("a=T,f=24,s=4,v=4,o=z,c=3,r=2,C=1", &compressed),
+        );
+        // No i= or I=, so no response
+        assert!(responses(&mut screen).is_empty());
+        let images = screen.visible_images();
+        assert_eq!((images[0].pixel_width, images[0].pixel_height), (30, 40));
+        assert_eq!(&images[0].data[..4], &[0x10, 0x10, 0x10, 0xff]);
+        assert_eq!((screen.cursor.col, screen.cursor.row), (0, 0));
+    }
+
+    #[test]
+    fn test_put_query_and_errors() {
+        let mut screen = screen();
+        let mut parser = Parser::new();
+        let pixels = vec![0xffu8; 8 * 8 * 4];
+        parser.parse(&mut screen, &apc("a=t,s=8,v=8,i=9,q=1", &pixels));
+        assert!(responses(&mut screen).is_empty());
+        assert!(screen.visible_images().is_empty());
+
+        parser.parse(&mut screen, b"\x1b_Ga=p,i=9,p=2,x=2,y=2,w=4,h=4\x1b\\");
+        assert_eq!(responses(&mut screen), ["\x1b_Gi=9,p=2;OK\x1b\\"]);
+        assert_eq!(screen.visible_images()[0].pixel_width, 4);
+
+        // Same placement id replaces the placement
+        parser.parse(&mut screen, b"\x1b_Ga=p,i=9,p=2,q=1\x1b\\");
+        assert_eq!(screen.visible_images().len(), 1);
+        assert_eq!(screen.visible_images()[0].pixel_width, 8);
+
+        parser.parse(&mut screen, b"\x1b_Ga=p,i=42\x1b\\");
+        let resp = responses(&mut screen);
+        assert!(resp[0].starts_with("\x1b_Gi=42;ENOENT:"), "{resp:?}");
+
+        parser.parse(&mut screen, &apc("a=q,s=8,v=8,i=31", &[0u8; 10]));
+        let resp = responses(&mut screen);
+        assert!(resp[0].starts_with("\x1b_Gi=31;ENODATA:"), "{resp:?}");
+        assert!(screen.kitty_graphics().image(31).is_none());
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut screen = screen();
+        let mut parser = Parser::new();
+        let pixels = vec![0xffu8; 10 * 20 * 4];
+        parser.parse(&mut screen, &apc("a=T,s=10,v=20,i=1,q=2", &pixels));
+        parser.parse(&mut screen, b"\x1b_Ga=p,i=1,p=7,q=2\x1b\\");
+        assert_eq!(screen.visible_images().len(), 2);
+
+        // Lowercase keeps the image data
+        parser.parse(&mut screen, b"\x1b_Ga=d,d=i,i=1,p=7\x1b\\");
+        assert_eq!(screen.visible_images().len(), 1);
+        parser.parse(&mut screen, b"\x1b_Ga=d,d=p,x=1,y=1\x1b\\");
+        assert!(screen.visible_images().is_empty());
+        assert!(screen.kitty_graphics().image(1).is_some());
+
+        // Uppercase frees it
+        parser.parse(&mut screen, b"\x1b_Ga=p,i=1,q=2\x1b\\");
+        parser.parse(&mut screen, b"\x1b_Ga=d,d=A\x1b\\");
+        assert!(screen.visible_images().is_empty());
+        assert!(screen.kitty_graphics().image(1).is_none());
+    }
+
+    #[test]
+    fn test_clear_images() {
+        let mut screen = screen();
+        let mut parser = Parser::new();
+        let pixels = vec![0xffu8; 10 * 20 * 4];
+        parser.parse(&mut screen, &apc("a=T,s=10,v=20,i=4,p=2,q=2", &pixels));
+        parser.parse(&mut screen, &apc("a=T,s=10,v=20,q=2", &pixels));
+
+        let images = screen.images();
+        assert_eq!(images.len(), 2);
+        let placement = screen.kitty_graphics().placement_for(images[0].id).unwrap();
+        assert_eq!((placement.image_id, placement.placement_id), (4, 2));
+
+        // Removing an image drops its placement too
+        let id = images[0].id;
+        assert!(screen.remove_image(id));
+        assert_eq!(screen.kitty_graphics().placements().len(), 1);
+
+        assert_eq!(screen.clear_images(), 1);
+        assert!(screen.images().is_empty());
+        assert!(screen.kitty_graphics().placements().is_empty());
+        // The image data stays, so it can be placed again
+        parser.parse(&mut screen, b"\x1b_Ga=p,i=4,q=2\x1b\\");
+        assert_eq!(screen.images().len(), 1);
+    }
+
+    #[test]
+    fn test_file_transmission() {
+        let mut screen = screen();
+        let path = std::env::temp_dir().join(format!(
+            "cterm-{}-tty-graphics-protocol.rgba",
+            std::process::id()
+        ));
+        std::fs::write(&path, vec![0x20u8; 2 * 2 * 4]).unwrap();
+        let name = path.to_string_lossy().into_owned();
+
+        Parser::new().parse(&mut screen, &apc("a=T,t=t,s=2,v=2,I=3", name.as_bytes()));
+        let resp = responses(&mut screen);
+        assert_eq!(resp.len(), 1);
+        assert!(resp[0].ends_with(",I=3;OK\x1b\\"), "{resp:?}");
+        assert_eq!(screen.visible_images().len(), 1);
+        // Temporary files are deleted after reading
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_file_transmission_errors_are_uniform() {
+        let mut screen = screen();
+        let mut parser = Parser::new();
+        let missing = std::env::temp_dir().join("cterm-no-such-file.rgba");
+        for path in ["/proc/self/status", "/dev/null", &missing.to_string_lossy()] {
+            parser.parse(&mut screen, &apc("a=T,t=f,s=1,v=1,i=9", path.as_bytes()));
+            let resp = responses(&mut screen);
+            assert_eq!(resp, ["\x1b_Gi=9;EIO:failed to read file\x1b\\"], "{path}");
+        }
+        assert!(screen.visible_images().is_empty());
+    }
+}
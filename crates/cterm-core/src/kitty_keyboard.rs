@@ -0,0 +1,385 @@
+//! Kitty keyboard protocol (progressive enhancement)
+//!
+//! Applications opt in with `CSI > flags u` (push), `CSI < n u` (pop) or
+//! `CSI = flags ; mode u` (set), and query with `CSI ? u`. Each screen
+//! (main and alternate) keeps its own stack of [`KeyboardFlags`]; while the
+//! top of the active stack is non-empty, [`Terminal::handle_key_event`]
+//! reports keys as `CSI code[:alternate] ; modifiers[:event] [; text] u`
+//! instead of legacy escape sequences.
+//!
+//! [`Terminal::handle_key_event`]: crate::term::Terminal::handle_key_event
+
+use serde::{Deserialize, Serialize};
+
+use crate::term::{Key, Modifiers};
+
+/// Deepest stack kept per screen; older entries are dropped on overflow
+const MAX_STACK_DEPTH: usize = 32;
+
+bitflags::bitflags! {
+    /// Progressive enhancement flags
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+    pub struct KeyboardFlags: u8 {
+        /// Report Esc, and keys with Ctrl/Alt, as unambiguous CSI u
+        const DISAMBIGUATE = 1 << 0;
+        /// Report key repeat and release events
+        const REPORT_EVENT_TYPES = 1 << 1;
+        /// Report the shifted key as an alternate key code
+        const REPORT_ALTERNATE_KEYS = 1 << 2;
+        /// Report every key, including text keys, as an escape code
+        const REPORT_ALL_KEYS = 1 << 3;
+        /// Report the text a key produces along with its code
+        const REPORT_TEXT = 1 << 4;
+    }
+}
+
+/// Kind of key event being reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyEventKind {
+    #[default]
+    Press,
+    Repeat,
+    Release,
+}
+
+impl KeyEventKind {
+    fn code(self) -> u8 {
+        match self {
+            Self::Press => 1,
+            Self::Repeat => 2,
+            Self::Release => 3,
+        }
+    }
+}
+
+/// Stack of enhancement flags for one screen
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyboardFlagStack {
+    stack: Vec<KeyboardFlags>,
+}
+
+impl KeyboardFlagStack {
+    /// Flags currently in effect
+    pub fn current(&self) -> KeyboardFlags {
+        self.stack.last().copied().unwrap_or_default()
+    }
+
+    /// `CSI > flags u`
+    pub fn push(&mut self, flags: KeyboardFlags) {
+        if self.stack.len() >= MAX_STACK_DEPTH {
+            self.stack.remove(0);
+        }
+        self.stack.push(flags);
+    }
+
+    /// `CSI < n u`; popping more entries than exist empties the stack
+    pub fn pop(&mut self, count: usize) {
+        let keep = self.stack.len().saturating_sub(count);
+        self.stack.truncate(keep);
+    }
+
+    /// `CSI = flags ; mode u`: 1 replaces, 2 adds, 3 removes flags
+    pub fn set(&mut self, flags: KeyboardFlags, mode: usize) {
+        let current = self.current();
+        let flags = match mode {
+            2 => current | flags,
+            3 => current - flags,
+            _ => flags,
+        };
+        match self.stack.last_mut() {
+            Some(top) => *top = flags,
+            None => self.stack.push(flags),
+        }
+    }
+
+    /// Drop all entries
+    pub fn clear(&mut self) {
+        self.stack.clear();
+    }
+}
+
+/// Encode a key event under the given (non-empty) flags.
+///
+/// Returns `None` for events that are not reported (releases without
+/// [`KeyboardFlags::REPORT_EVENT_TYPES`], for instance).
+pub(crate) fn encode_key(
+    key: Key,
+    modifiers: Modifiers,
+    kind: KeyEventKind,
+    flags: KeyboardFlags,
+) -> Option<Vec<u8>> {
+    let all_keys = flags.contains(KeyboardFlags::REPORT_ALL_KEYS);
+    let event_types = flags.contains(KeyboardFlags::REPORT_EVENT_TYPES);
+    if kind == KeyEventKind::Release && !event_types {
+        return None;
+    }
+    // Without event type reporting a repeat is just another press
+    let kind = if event_types {
+        kind
+    } else {
+        KeyEventKind::Press
+    };
+    let ctrl_alt_super = modifiers.intersects(Modifiers::CTRL | Modifiers::ALT | Modifiers::SUPER);
+
+    match key {
+        Key::Char(c) => {
+            // Text keys keep sending text unless everything is reported
+            if !all_keys && !ctrl_alt_super {
+                return match kind {
+                    KeyEventKind::Release => None,
+                    _ => Some(c.to_string().into_bytes()),
+                };
+            }
+            let base = c.to_lowercase().next().unwrap_or(c);
+            let alternate = (flags.contains(KeyboardFlags::REPORT_ALTERNATE_KEYS)
+                && modifiers.contains(Modifiers::SHIFT)
+                && base != c)
+                .then_some(c);
+            let text = (flags.contains(KeyboardFlags::REPORT_TEXT)
+                && !ctrl_alt_super
+                && kind != KeyEventKind::Release)
+                .then_some(c);
+            Some(csi_u(base as u32, alternate, modifiers, kind, text))
+        }
+        Key::Enter | Key::Tab | Key::Backspace => {
+            // Plain Enter/Tab/Backspace stay legacy so a shell remains
+            // usable after a crashed program leaves the protocol enabled
+            if !all_keys && modifiers.is_empty() {
+                let legacy: &[u8] = match key {
+                    Key::Enter => b"\r",
+                    Key::Tab => b"\t",
+                    _ => b"\x7f",
+                };
+                return (kind != KeyEventKind::Release).then(|| legacy.to_vec());
+            }
+            let code = match key {
+                Key::Enter => 13,
+                Key::Tab => 9,
+                _ => 127,
+            };
+            Some(csi_u(code, None, modifiers, kind, None))
+        }
+        Key::Escape => Some(csi_u(27, None, modifiers, kind, None)),
+        Key::Up => Some(csi_letter(b'A', modifiers, kind)),
+        Key::Down => Some(csi_letter(b'B', modifiers, kind)),
+        Key::Right => Some(csi_letter(b'C', modifiers, kind)),
+        Key::Left => Some(csi_letter(b'D', modifiers, kind)),
+        Key::Home => Some(csi_letter(b'H', modifiers, kind)),
+        Key::End => Some(csi_letter(b'F', modifiers, kind)),
+        Key::Insert => Some(csi_tilde(2, modifiers, kind)),
+        Key::Delete => Some(csi_tilde(3, modifiers, kind)),
+        Key::PageUp => Some(csi_tilde(5, modifiers, kind)),
+        Key::PageDown => Some(csi_tilde(6, modifiers, kind)),
+        Key::F(1) => Some(csi_letter(b'P', modifiers, kind)),
+        Key::F(2) => Some(csi_letter(b'Q', modifiers, kind)),
+        Key::F(3) => Some(csi_tilde(13, modifiers, kind)),
+        Key::F(4) => Some(csi_letter(b'S', modifiers, kind)),
+        Key::F(n @ 5..=12) => {
+            let code = [15, 17, 18, 19, 20, 21, 23, 24][n as usize - 5];
+            Some(csi_tilde(code, modifiers, kind))
+        }
+        // F13-F35 only exist as CSI u codes
+        Key::F(n @ 13..=35) => Some(csi_u(57376 + (n as u32 - 13), None, modifiers, kind, None)),
+        Key::F(_) => None,
+        Key::Keypad(c) => {
+            let code = match c {
+                '0'..='9' => 57399 + (c as u32 - '0' as u32),
+                '.' => 57409,
+                '/' => 57410,
+                '*' => 57411,
+                '-' => 57412,
+                '+' => 57413,
+                '\r' => 57414,
+                '=' => 57415,
+                ',' => 57416,
+                _ => return None,
+            };
+            let text = (flags.contains(KeyboardFlags::REPORT_TEXT)
+                && !ctrl_alt_super
+                && kind != KeyEventKind::Release
+                && c != '\r')
+                .then_some(c);
+            Some(csi_u(code, None, modifiers, kind, text))
+        }
+    }
+}
+
+/// Kitty modifier value: 1 + shift(1) + alt(2) + ctrl(4) + super(8)
+fn modifier_value(modifiers: Modifiers) -> u32 {
+    let mut value = 1;
+    if modifiers.contains(Modifiers::SHIFT) {
+        value += 1;
+    }
+    if modifiers.contains(Modifiers::ALT) {
+        value += 2;
+    }
+    if modifiers.contains(Modifiers::CTRL) {
+        value += 4;
+    }
+    if modifiers.contains(Modifiers::SUPER) {
+        value += 8;
+    }
+    value
+}
+
+/// `;modifiers[:event]`, or nothing when both are defaults
+fn modifier_field(modifiers: Modifiers, kind: KeyEventKind) -> String {
+    let value = modifier_value(modifiers);
+    match kind {
+        KeyEventKind::Press if value == 1 => String::new(),
+        KeyEventKind::Press => format!(";{value}"),
+        _ => format!(";{value}:{}", kind.code()),
+    }
+}
+
+fn csi_u(
+    code: u32,
+    alternate: Option<char>,
+    modifiers: Modifiers,
+    kind: KeyEventKind,
+    text: Option<char>,
+) -> Vec<u8> {
+    let mut seq = format!("\x1b[{code}");
+    if let Some(alt) = alternate {
+        seq.push_str(&format!(":{}", alt as u32));
+    }
+    let mods = modifier_field(modifiers, kind);
+    match text {
+        Some(text) => {
+            let mods = if mods.is_empty() { ";1" } else { &mods };
+            seq.push_str(&format!("{mods};{}", text as u32));
+        }
+        None => seq.push_str(&mods),
+    }
+    seq.push('u');
+    seq.into_bytes()
+}
+
+/// `CSI 1 ; mods X`, or `CSI X` without modifiers or event type
+fn csi_letter(letter: u8, modifiers: Modifiers, kind: KeyEventKind) -> Vec<u8> {
+    let mods = modifier_field(modifiers, kind);
+    if mods.is_empty() {
+        format!("\x1b[{}", letter as char).into_bytes()
+    } else {
+        format!("\x1b[1{mods}{}", letter as char).into_bytes()
+    }
+}
+
+fn csi_tilde(code: u8, modifiers: Modifiers, kind: KeyEventKind) -> Vec<u8> {
+    format!("\x1b[{code}{}~", modifier_field(modifiers, kind)).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(key: Key, mods: Modifiers, kind: KeyEventKind, flags: u8) -> Option<String> {
+        encode_key(key, mods, kind, KeyboardFlags::from_bits_truncate(flags))
+            .map(|b| String::from_utf8(b).unwrap())
+    }
+
+    #[test]
+    fn test_flag_stack() {
+        let mut stack = KeyboardFlagStack::default();
+        assert!(stack.current().is_empty());
+        stack.push(KeyboardFlags::DISAMBIGUATE);
+        stack.push(KeyboardFlags::REPORT_ALL_KEYS);
+        stack.set(KeyboardFlags::REPORT_EVENT_TYPES, 2);
+        assert_eq!(stack.current().bits(), 0b1010);
+        stack.set(KeyboardFlags::REPORT_ALL_KEYS, 3);
+        assert_eq!(stack.current(), KeyboardFlags::REPORT_EVENT_TYPES);
+        stack.pop(1);
+        assert_eq!(stack.current(), KeyboardFlags::DISAMBIGUATE);
+        stack.pop(5);
+        assert!(stack.current().is_empty());
+    }
+
+    #[test]
+    fn test_disambiguate() {
+        let press = KeyEventKind::Press;
+        assert_eq!(
+            encode(Key::Escape, Modifiers::empty(), press, 1).unwrap(),
+            "\x1b[27u"
+        );
+        assert_eq!(
+            encode(Key::Char('a'), Modifiers::empty(), press, 1).unwrap(),
+            "a"
+        );
+        assert_eq!(
+            encode(Key::Char('c'), Modifiers::CTRL, press, 1).unwrap(),
+            "\x1b[99;5u"
+        );
+        assert_eq!(
+            encode(Key::Char('A'), Modifiers::CTRL | Modifiers::SHIFT, press, 1).unwrap(),
+            "\x1b[97;6u"
+        );
+        assert_eq!(
+            encode(Key::Enter, Modifiers::empty(), press, 1).unwrap(),
+            "\r"
+        );
+        assert_eq!(
+            encode(Key::Enter, Modifiers::SHIFT, press, 1).unwrap(),
+            "\x1b[13;2u"
+        );
+        assert_eq!(
+            encode(Key::Up, Modifiers::empty(), press, 1).unwrap(),
+            "\x1b[A"
+        );
+        assert_eq!(
+            encode(Key::F(5), Modifiers::ALT, press, 1).unwrap(),
+            "\x1b[15;3~"
+        );
+        assert_eq!(
+            encode(Key::F(1), Modifiers::empty(), press, 1).unwrap(),
+            "\x1b[P"
+        );
+        assert_eq!(
+            encode(Key::Keypad('1'), Modifiers::empty(), press, 1).unwrap(),
+            "\x1b[57400u"
+        );
+        assert_eq!(
+            encode(Key::Keypad('\r'), Modifiers::CTRL, press, 1).unwrap(),
+            "\x1b[57414;5u"
+        );
+        // Releases are not reported without flag 2
+        assert!(encode(Key::Escape, Modifiers::empty(), KeyEventKind::Release, 1).is_none());
+    }
+
+    #[test]
+    fn test_event_types_and_all_keys() {
+        assert_eq!(
+            encode(Key::Up, Modifiers::empty(), KeyEventKind::Release, 3).unwrap(),
+            "\x1b[1;1:3A"
+        );
+        assert_eq!(
+            encode(Key::Char('x'), Modifiers::CTRL, KeyEventKind::Repeat, 3).unwrap(),
+            "\x1b[120;5:2u"
+        );
+        // Text keys send text, and their releases are only reported with flag 8
+        assert!(encode(Key::Char('x'), Modifiers::empty(), KeyEventKind::Release, 3).is_none());
+        assert_eq!(
+            encode(
+                Key::Char('x'),
+                Modifiers::empty(),
+                KeyEventKind::Release,
+                11
+            )
+            .unwrap(),
+            "\x1b[120;1:3u"
+        );
+        assert_eq!(
+            encode(
+                Key::Char('A'),
+                Modifiers::SHIFT,
+                KeyEventKind::Press,
+                8 | 4 | 16
+            )
+            .unwrap(),
+            "\x1b[97:65;2;65u"
+        );
+        assert_eq!(
+            encode(Key::Enter, Modifiers::empty(), KeyEventKind::Press, 8).unwrap(),
+            "\x1b[13u"
+        );
+    }
+}
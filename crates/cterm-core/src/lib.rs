@@ -4,38 +4,77 @@
 //! - Color and cell attribute types
 //! - Screen buffer management (grid, scrollback)
 //! - ANSI/VT sequence parsing
-//! - Cross-platform PTY handling
+//! - Cross-platform PTY handling (local, SSH and serial)
 
+pub mod bitmap_font;
 pub mod cell;
 pub mod color;
+pub mod damage;
+pub mod detect;
 pub mod drcs;
+pub mod file_transfer;
+pub mod grapheme;
 pub mod grid;
+pub mod hints;
 pub mod image_decode;
 pub mod iterm2;
+pub mod kermit;
+pub mod kitty_graphics;
+pub mod kitty_keyboard;
 pub mod mouse;
 pub mod parser;
+pub mod permissions;
+pub mod playback;
 pub mod pty;
+pub mod recording;
+pub mod render;
 pub mod screen;
+pub mod serial;
+pub mod sftp;
 pub mod sixel;
 pub mod ssh;
 pub mod streaming_file;
 pub mod term;
+pub mod throughput;
+pub mod watch;
+pub mod zmodem;
 
-pub use cell::{Cell, CellAttrs};
-pub use color::{AnsiColor, Color, Rgb};
+pub use cell::{AttrOverrides, AttrSubstitute, Cell, CellAttrs, Combining};
+pub use color::{AnsiColor, Color, ColorScheme, Rgb};
+pub use damage::{CursorMove, Damage, ScrollDelta};
+pub use detect::{detect, detect_visible, span_at, DetectedSpan, SpanKind};
 pub use drcs::{DecdldDecoder, DrcsFont, DrcsGlyph};
-pub use grid::Grid;
-pub use image_decode::{decode_image, DecodedImage, ImageDecodeError};
+pub use file_transfer::{
+    FileTransfer, TransferDirection, TransferEvent, TransferFile, TransferProtocol,
+};
+pub use grid::{Grid, SemanticMark};
+pub use hints::{
+    find_hints, Hint, HintAction, HintInput, HintKind, HintMode, DEFAULT_HINT_ALPHABET,
+};
+pub use image_decode::{decode_image, decode_thumbnail, DecodedImage, ImageDecodeError};
 pub use iterm2::{Iterm2Dimension, Iterm2FileParams};
+pub use kitty_graphics::{
+    KittyAction, KittyCommand, KittyError, KittyFormat, KittyGraphics, KittyImage, KittyMedium,
+    KittyPlacement,
+};
+pub use kitty_keyboard::{KeyEventKind, KeyboardFlagStack, KeyboardFlags};
 pub use parser::Parser;
+pub use permissions::{Capability, Permission, PermissionPolicy};
+pub use playback::{Frame, FrameKind, PlaybackError, Player, Recording};
 #[cfg(unix)]
 pub use pty::save_original_nofile_limit;
-pub use pty::{Pty, PtyConfig, PtyError, PtySize};
+pub use pty::{validate_env_var, Pty, PtyConfig, PtyError, PtySize};
+pub use recording::Recorder;
+pub use render::{ScreenRenderer, Screenshot};
 pub use screen::{
-    ClipboardOperation, ClipboardSelection, ColorQuery, FileTransferOperation, Screen,
-    SearchResult, Selection, SelectionMode, SelectionPoint, TerminalImage,
+    ClipboardOperation, ClipboardSelection, ColorQuery, CommandBlock, CommandFinished,
+    ConformanceLevel, CursorStyle, FileTransferOperation, Notification, PointerShape, PromptMark,
+    Screen, ScrollMark, ScrollMarkKind, SearchMatch, SearchOptions, SearchResult, Selection,
+    SelectionConfig, SelectionMode, SelectionPoint, TerminalImage, TmuxControl,
 };
-pub use sixel::{SixelDecoder, SixelImage};
+pub use serial::{available_ports, FlowControl, Parity, SerialConfig, SerialPty};
+pub use sftp::{join_remote_path, parent_remote_path, RemoteEntry, RemoteFs};
+pub use sixel::{SixelDecoder, SixelEncoder, SixelImage};
 pub use ssh::{
     HostKeyPrompt, HostKeyRequest, LocalForward, PassphrasePrompt, PasswordPrompt, SshConfig,
     SshPrompts,
@@ -43,4 +82,6 @@ pub use ssh::{
 #[cfg(unix)]
 pub use ssh::{SshChannelOpener, SshChannelReader, SshChannelWriter, SshTunnel};
 pub use streaming_file::{StreamingFileData, StreamingFileReceiver, StreamingFileResult};
-pub use term::{Terminal, WriteFn};
+pub use term::{SnapshotError, Terminal, WriteFn};
+pub use throughput::{OutputStats, ThroughputMeter};
+pub use watch::{LineHighlight, Watch, WatchMatch};
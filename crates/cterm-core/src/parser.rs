@@ -4,7 +4,8 @@
 //! actions that can be applied to the terminal screen.
 //!
 //! Special handling is provided for OSC 1337 (iTerm2) file transfers
-//! which are intercepted before VTE to enable streaming large files.
+//! which are intercepted before VTE to enable streaming large files, and
+//! for APC sequences (kitty graphics), whose contents VTE discards.
 
 use std::sync::Arc;
 use vte::Params;
@@ -12,11 +13,14 @@ use vte::Params;
 use crate::cell::{CellAttrs, Hyperlink};
 use crate::color::{AnsiColor, Color, Rgb};
 use crate::drcs::DecdldDecoder;
+use crate::grid::SemanticMark;
 use crate::image_decode::decode_image;
 use crate::iterm2::{Iterm2Dimension, Iterm2FileParams};
+use crate::kitty_graphics;
+use crate::kitty_keyboard::KeyboardFlags;
 use crate::screen::{
-    ClearMode, ClipboardOperation, ClipboardSelection, CursorStyle, LineClearMode, MouseMode,
-    Screen,
+    ClearMode, ClipboardOperation, ClipboardSelection, ConformanceLevel, CursorStyle,
+    LineClearMode, MouseMode, Notification, PointerShape, Screen, TmuxControl,
 };
 use crate::sixel::{SixelDecoder, SixelImage};
 use crate::streaming_file::StreamingFileReceiver;
@@ -33,8 +37,16 @@ enum DcsState {
     },
     /// DECDLD (soft font download) in progress
     Decdld { decoder: DecdldDecoder },
+    /// tmux control mode (`tmux -CC`): the protocol runs inside one DCS
+    /// string, a line at a time, until tmux exits
+    TmuxControl { line: Vec<u8> },
+    /// Request Status String (DECRQSS): the setting being asked about
+    Decrqss { request: Vec<u8> },
 }
 
+/// Longest DECRQSS request worth collecting; real ones are one or two bytes
+const DECRQSS_MAX: usize = 8;
+
 /// State for intercepting OSC 1337 File transfers before VTE buffers them
 #[derive(Debug, Default)]
 enum Osc1337State {
@@ -53,6 +65,25 @@ enum Osc1337State {
     Osc1337Data(StreamingFileReceiver),
 }
 
+/// Largest APC string collected; longer ones are dropped
+const MAX_APC_LEN: usize = 32 * 1024 * 1024;
+
+/// State for observing APC strings (`ESC _ ... ESC \`) alongside VTE
+#[derive(Debug, Default)]
+enum ApcState {
+    /// Not in an APC string
+    #[default]
+    None,
+    /// Saw ESC, waiting for _
+    Escape,
+    /// Inside an APC string, collecting its contents
+    Content(Vec<u8>),
+    /// Saw ESC inside an APC string, waiting for \
+    ContentEscape(Vec<u8>),
+    /// Inside an APC string that exceeded `MAX_APC_LEN`
+    Overflow,
+}
+
 /// Parser wraps the vte parser and applies actions to a Screen
 pub struct Parser {
     state_machine: vte::Parser,
@@ -61,6 +92,8 @@ pub struct Parser {
     osc_1337_state: Osc1337State,
     /// Whether an OSC 1337 string terminator (BEL or ESC \) was seen
     osc_1337_terminated: bool,
+    /// State for observing APC strings
+    apc_state: ApcState,
 }
 
 impl Default for Parser {
@@ -76,6 +109,7 @@ impl Parser {
             dcs_state: DcsState::None,
             osc_1337_state: Osc1337State::None,
             osc_1337_terminated: false,
+            apc_state: ApcState::None,
         }
     }
 
@@ -91,10 +125,23 @@ impl Parser {
 
             if consumed {
                 // Check if we need to finish the streaming transfer
+                let terminated = self.osc_1337_terminated;
                 self.check_osc_1337_finish(screen);
+                if terminated {
+                    // VTE only saw "ESC ] 1337"; close that OSC so the output
+                    // following the sequence isn't swallowed into it
+                    let mut performer = ScreenPerformer {
+                        screen,
+                        dcs_state: &mut self.dcs_state,
+                    };
+                    self.state_machine.advance(&mut performer, 0x07);
+                }
                 continue;
             }
 
+            // VTE ignores APC contents, so collect them on the side
+            self.observe_apc_byte(screen, byte);
+
             // Normal VTE processing
             let mut performer = ScreenPerformer {
                 screen,
@@ -104,6 +151,40 @@ impl Parser {
         }
     }
 
+    /// Track APC strings and dispatch complete kitty graphics commands
+    ///
+    /// Bytes are not consumed; VTE still sees (and discards) the string.
+    fn observe_apc_byte(&mut self, screen: &mut Screen, byte: u8) {
+        self.apc_state = match (std::mem::take(&mut self.apc_state), byte) {
+            (ApcState::None, 0x1b) => ApcState::Escape,
+            (ApcState::None, _) => ApcState::None,
+            (ApcState::Escape, b'_') => ApcState::Content(Vec::new()),
+            (ApcState::Escape, 0x1b) => ApcState::Escape,
+            (ApcState::Escape, _) => ApcState::None,
+            (ApcState::Content(content), 0x1b) => ApcState::ContentEscape(content),
+            (ApcState::Content(_) | ApcState::Overflow, 0x18 | 0x1a) => ApcState::None,
+            (ApcState::Content(content), _) if content.len() >= MAX_APC_LEN => {
+                log::debug!("APC string exceeds {} bytes, dropping", MAX_APC_LEN);
+                ApcState::Overflow
+            }
+            (ApcState::Content(mut content), _) => {
+                content.push(byte);
+                ApcState::Content(content)
+            }
+            (ApcState::ContentEscape(content), b'\\') => {
+                if let Some(data) = content.strip_prefix(b"G") {
+                    kitty_graphics::handle_apc(screen, data);
+                }
+                ApcState::None
+            }
+            // Any other escape ends the string and may start a new one
+            (ApcState::ContentEscape(_), b'_') => ApcState::Content(Vec::new()),
+            (ApcState::ContentEscape(_), _) => ApcState::None,
+            (ApcState::Overflow, 0x1b) => ApcState::Escape,
+            (ApcState::Overflow, _) => ApcState::Overflow,
+        };
+    }
+
     /// Pre-check a byte for OSC 1337 interception (before performer is created)
     ///
     /// Returns true if the byte was consumed
@@ -253,8 +334,14 @@ impl Parser {
         self.osc_1337_terminated = false;
 
         match &self.osc_1337_state {
-            Osc1337State::Osc1337Content(_) | Osc1337State::Osc1337Params(_) => {
-                // Non-File content or terminated params without data - just reset
+            Osc1337State::Osc1337Content(content) => {
+                // Non-File subcommand (Key=Value)
+                let content = String::from_utf8_lossy(content).into_owned();
+                self.osc_1337_state = Osc1337State::None;
+                handle_osc_1337_command(screen, &content);
+            }
+            Osc1337State::Osc1337Params(_) => {
+                // Terminated params without data - just reset
                 self.osc_1337_state = Osc1337State::None;
             }
             Osc1337State::Osc1337Data(_) => {
@@ -369,11 +456,7 @@ impl vte::Perform for ScreenPerformer<'_> {
                 log::debug!("Bell");
             }
             // Backspace (BS)
-            0x08 => {
-                if self.screen.cursor.col > 0 {
-                    self.screen.cursor.col -= 1;
-                }
-            }
+            0x08 => self.screen.backspace(),
             // Horizontal Tab (HT)
             0x09 => {
                 self.screen.tab_forward(1);
@@ -437,6 +520,20 @@ impl vte::Perform for ScreenPerformer<'_> {
                     decoder: DecdldDecoder::new(&params_vec),
                 };
             }
+            // tmux control mode: DCS 1000 p
+            'p' if intermediates.is_empty() && params_vec == [1000] => {
+                log::debug!("Entering tmux control mode");
+                self.screen.push_tmux_control(TmuxControl::Started);
+                *self.dcs_state = DcsState::TmuxControl { line: Vec::new() };
+            }
+            // Request Status String (DECRQSS): DCS $ q Pt ST, from the VT320 on
+            'q' if intermediates == [b'$']
+                && self.screen.conformance_level() >= ConformanceLevel::Vt320 =>
+            {
+                *self.dcs_state = DcsState::Decrqss {
+                    request: Vec::new(),
+                };
+            }
             _ => {
                 log::trace!("Unhandled DCS action: {:?}", action);
             }
@@ -454,6 +551,21 @@ impl vte::Perform for ScreenPerformer<'_> {
             DcsState::Decdld { ref mut decoder } => {
                 decoder.put(byte);
             }
+            DcsState::TmuxControl { ref mut line } => match byte {
+                b'\n' => {
+                    if line.last() == Some(&b'\r') {
+                        line.pop();
+                    }
+                    let line = std::mem::take(line);
+                    self.screen.push_tmux_control(TmuxControl::Line(line));
+                }
+                _ => line.push(byte),
+            },
+            DcsState::Decrqss { ref mut request } => {
+                if request.len() < DECRQSS_MAX {
+                    request.push(byte);
+                }
+            }
             DcsState::None => {}
         }
     }
@@ -537,6 +649,21 @@ impl vte::Perform for ScreenPerformer<'_> {
                     self.screen.add_drcs_font(font, erase_control, font_number);
                 }
             }
+            DcsState::TmuxControl { line } => {
+                if !line.is_empty() {
+                    self.screen.push_tmux_control(TmuxControl::Line(line));
+                }
+                log::debug!("Leaving tmux control mode");
+                self.screen.push_tmux_control(TmuxControl::Ended);
+            }
+            DcsState::Decrqss { request } => {
+                // Valid requests get DCS 1 $ r Pt ST, others DCS 0 $ r ST
+                let response = match self.status_string(&request) {
+                    Some(status) => format!("\x1bP1$r{}\x1b\\", status),
+                    None => "\x1bP0$r\x1b\\".to_string(),
+                };
+                self.screen.queue_response(response.into_bytes());
+            }
             DcsState::None => {}
         }
     }
@@ -569,6 +696,18 @@ impl vte::Perform for ScreenPerformer<'_> {
                     }
                 }
             }
+            // Working directory (OSC 7): file://host/path, percent-encoded
+            7 => {
+                let uri = params[1..]
+                    .iter()
+                    .map(|p| String::from_utf8_lossy(p))
+                    .collect::<Vec<_>>()
+                    .join(";");
+                match parse_file_uri(&uri) {
+                    Some((host, path)) => self.screen.set_current_dir(&host, &path),
+                    None => log::debug!("OSC 7: unsupported URI {:?}", uri),
+                }
+            }
             // Hyperlink (OSC 8)
             8 => {
                 if params.len() >= 3 {
@@ -594,6 +733,23 @@ impl vte::Perform for ScreenPerformer<'_> {
                     }
                 }
             }
+            // Desktop notification (OSC 9 ; body); a number instead is one of
+            // ConEmu's OSC 9 commands, such as progress (OSC 9 ; 4)
+            9 => {
+                let Some(first) = params.get(1) else {
+                    return;
+                };
+                if std::str::from_utf8(first).is_ok_and(|p| p.parse::<u32>().is_ok()) {
+                    return;
+                }
+                let body = params[1..]
+                    .iter()
+                    .map(|p| String::from_utf8_lossy(p))
+                    .collect::<Vec<_>>()
+                    .join(";");
+                self.screen
+                    .queue_notification(Notification { title: None, body });
+            }
             // Set/query colors (10-19)
             // OSC 10 = foreground, 11 = background, 12 = cursor
             10..=12 => {
@@ -625,10 +781,65 @@ impl vte::Perform for ScreenPerformer<'_> {
             13..=19 => {
                 log::trace!("Unhandled color OSC: {}", command);
             }
+            // Mouse pointer shape (OSC 22): a CSS or X11 cursor name, or a
+            // comma-separated list of them to fall back through; kitty's
+            // "=" (set) and ">" (push) prefixes set it, "<" (pop) restores
+            // the default and "?" queries are not answered
+            22 => {
+                let Some(Ok(value)) = params.get(1).map(|p| std::str::from_utf8(p)) else {
+                    return;
+                };
+                let shape = match value.as_bytes().first() {
+                    Some(b'?') => return,
+                    Some(b'<') => Some(PointerShape::Default),
+                    Some(b'=' | b'>') => value[1..].split(',').find_map(PointerShape::from_name),
+                    _ => value.split(',').find_map(PointerShape::from_name),
+                };
+                match shape {
+                    Some(shape) => self.screen.set_pointer_shape(shape),
+                    None => log::debug!("OSC 22: unknown pointer shape {:?}", value),
+                }
+            }
             // iTerm2 inline images and file transfer (1337)
             1337 => {
                 self.handle_osc_1337(params);
             }
+            // Shell integration marks (133): A prompt, B command line,
+            // C command output, D command finished with optional exit status
+            133 => {
+                let mark = match params.get(1).copied() {
+                    Some(b"A") => SemanticMark::Prompt,
+                    Some(b"B") => SemanticMark::CommandInput,
+                    Some(b"C") => SemanticMark::Output,
+                    Some(b"D") => SemanticMark::CommandEnd {
+                        exit_code: params
+                            .get(2)
+                            .and_then(|p| std::str::from_utf8(p).ok())
+                            .and_then(|s| s.parse().ok()),
+                        duration: None,
+                    },
+                    _ => return,
+                };
+                self.screen.semantic_mark(mark);
+            }
+            // Desktop notification (OSC 777 ; notify ; title ; body)
+            777 => {
+                if params.get(1).copied() != Some(b"notify".as_slice()) {
+                    return;
+                }
+                let title = params
+                    .get(2)
+                    .map(|p| String::from_utf8_lossy(p).into_owned())
+                    .filter(|t| !t.is_empty());
+                let body = params
+                    .get(3..)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|p| String::from_utf8_lossy(p))
+                    .collect::<Vec<_>>()
+                    .join(";");
+                self.screen.queue_notification(Notification { title, body });
+            }
             // Copy to clipboard (52)
             52 => {
                 // OSC 52 ; Pc ; Pd ST
@@ -708,18 +919,24 @@ impl vte::Perform for ScreenPerformer<'_> {
             ('E', []) => {
                 let n = first_param(&params_vec, 1) as i32;
                 self.screen.move_cursor_relative(n, 0);
-                self.screen.cursor.col = 0;
+                self.screen.carriage_return();
             }
             // Cursor Previous Line (CPL)
             ('F', []) => {
                 let n = first_param(&params_vec, 1) as i32;
                 self.screen.move_cursor_relative(-n, 0);
-                self.screen.cursor.col = 0;
+                self.screen.carriage_return();
             }
-            // Cursor Horizontal Absolute (CHA)
-            ('G', []) => {
+            // Cursor Horizontal Absolute (CHA) / Horizontal Position
+            // Absolute (HPA)
+            ('G', []) | ('`', []) => {
                 let col = first_param(&params_vec, 1).saturating_sub(1);
-                self.screen.cursor.col = col.min(self.screen.width().saturating_sub(1));
+                self.screen.move_cursor_to_col(col);
+            }
+            // Horizontal Position Relative (HPR)
+            ('a', []) => {
+                let n = first_param(&params_vec, 1) as i32;
+                self.screen.move_cursor_relative(0, n);
             }
             // Cursor Position (CUP) / Horizontal and Vertical Position (HVP)
             ('H', []) | ('f', []) => {
@@ -776,15 +993,7 @@ impl vte::Perform for ScreenPerformer<'_> {
             // Erase Characters (ECH)
             ('X', []) => {
                 let n = first_param(&params_vec, 1);
-                let cursor_row = self.screen.cursor.row;
-                let cursor_col = self.screen.cursor.col;
-                let width = self.screen.width();
-                let count = n.min(width.saturating_sub(cursor_col));
-                if let Some(row) = self.screen.grid_mut().row_mut(cursor_row) {
-                    for i in 0..count {
-                        row[cursor_col + i].reset();
-                    }
-                }
+                self.screen.erase_chars(n);
             }
             // Cursor Backward Tabulation (CBT)
             ('Z', []) => {
@@ -794,24 +1003,17 @@ impl vte::Perform for ScreenPerformer<'_> {
             // Insert Characters (ICH)
             ('@', []) => {
                 let n = first_param(&params_vec, 1);
-                let cursor_row = self.screen.cursor.row;
-                let col = self.screen.cursor.col;
-                let width = self.screen.width();
-                if let Some(row) = self.screen.grid_mut().row_mut(cursor_row) {
-                    // Shift characters right
-                    for i in (col + n..width).rev() {
-                        row[i] = row[i - n].clone();
-                    }
-                    // Clear inserted positions
-                    for i in col..col + n.min(width.saturating_sub(col)) {
-                        row[i].reset();
-                    }
-                }
+                self.screen.insert_chars(n);
             }
             // Vertical Line Position Absolute (VPA)
             ('d', []) => {
                 let row = first_param(&params_vec, 1).saturating_sub(1);
-                self.screen.cursor.row = row.min(self.screen.height().saturating_sub(1));
+                self.screen.move_cursor_to_row(row);
+            }
+            // Vertical Position Relative (VPR)
+            ('e', []) => {
+                let n = first_param(&params_vec, 1) as i32;
+                self.screen.move_cursor_relative(n, 0);
             }
             // SGR - Select Graphic Rendition
             ('m', []) => {
@@ -827,9 +1029,8 @@ impl vte::Perform for ScreenPerformer<'_> {
                     }
                     6 => {
                         // Cursor position report - respond with CSI row;col R
-                        let row = self.screen.cursor.row + 1;
-                        let col = self.screen.cursor.col + 1;
-                        let response = format!("\x1b[{};{}R", row, col);
+                        let (row, col) = self.screen.reported_cursor_position();
+                        let response = format!("\x1b[{};{}R", row + 1, col + 1);
                         self.screen.queue_response(response.into_bytes());
                     }
                     _ => {
@@ -837,18 +1038,36 @@ impl vte::Perform for ScreenPerformer<'_> {
                     }
                 }
             }
+            // DEC-specific DSR: color scheme query (996), answered with
+            // CSI ? 997 ; 1 n (dark) or 2 (light)
+            ('n', [b'?']) => {
+                let mode = first_param(&params_vec, 0);
+                match (mode, self.screen.color_scheme()) {
+                    (996, Some(scheme)) => self.screen.queue_response(scheme.report()),
+                    _ => {
+                        log::trace!("Unknown DEC DSR mode: {}", mode);
+                    }
+                }
+            }
             // Set Top and Bottom Margins (DECSTBM)
             ('r', []) => {
                 let top = first_param(&params_vec, 1).saturating_sub(1);
-                let bottom = if params_vec.len() > 1 {
+                let bottom = second_param(&params_vec, self.screen.height());
+                self.screen.set_scroll_region(top, bottom);
+                self.screen.move_cursor(0, 0);
+            }
+            // Set Left and Right Margins (DECSLRM) in left/right margin
+            // mode, else Save Cursor (SCOSC)
+            ('s', []) if self.screen.modes.left_right_margin_mode => {
+                let left = first_param(&params_vec, 1).saturating_sub(1);
+                let right = if params_vec.len() > 1 && params_vec[1] > 0 {
                     params_vec[1]
                 } else {
-                    self.screen.height()
+                    self.screen.width()
                 };
-                self.screen.set_scroll_region(top, bottom);
+                self.screen.set_left_right_margins(left, right);
                 self.screen.move_cursor(0, 0);
             }
-            // Save Cursor (DECSC)
             ('s', []) => {
                 self.screen.save_cursor();
             }
@@ -856,6 +1075,25 @@ impl vte::Perform for ScreenPerformer<'_> {
             ('u', []) => {
                 self.screen.restore_cursor();
             }
+            // Kitty keyboard protocol: push, pop, set and query flags
+            ('u', [b'>']) => {
+                let flags = keyboard_flags_param(&params_vec);
+                self.screen.keyboard_flags_mut().push(flags);
+            }
+            ('u', [b'<']) => {
+                let count = first_param(&params_vec, 1);
+                self.screen.keyboard_flags_mut().pop(count);
+            }
+            ('u', [b'=']) => {
+                let flags = keyboard_flags_param(&params_vec);
+                let mode = second_param(&params_vec, 1);
+                self.screen.keyboard_flags_mut().set(flags, mode);
+            }
+            ('u', [b'?']) => {
+                let flags = self.screen.keyboard_flags().bits();
+                self.screen
+                    .queue_response(format!("\x1b[?{}u", flags).into_bytes());
+            }
             // Window manipulation (XTWINOPS)
             ('t', []) => {
                 log::trace!("Window manipulation: {:?}", params_vec);
@@ -875,17 +1113,30 @@ impl vte::Perform for ScreenPerformer<'_> {
                 }
             }
             // Soft reset (DECSTR)
-            ('p', [b'!']) => {
-                self.screen.style.reset();
-                self.screen.modes.insert_mode = false;
-                self.screen.modes.origin_mode = false;
-                self.screen.reset_scroll_region();
+            ('p', [b'!']) => self.soft_reset(),
+            // Set Conformance Level (DECSCL), which also soft resets
+            ('p', [b'"']) => {
+                if let Some(level) = ConformanceLevel::from_decscl(first_param(&params_vec, 65)) {
+                    self.screen.set_conformance_level(level);
+                    self.soft_reset();
+                }
+            }
+            // Primary Device Attributes (DA1)
+            ('c', []) if first_param(&params_vec, 0) == 0 => {
+                let reply = self.screen.conformance_level().primary_attributes();
+                self.screen.queue_response(reply);
+            }
+            // Secondary Device Attributes (DA2)
+            ('c', [b'>']) if first_param(&params_vec, 0) == 0 => {
+                let reply = self.screen.conformance_level().secondary_attributes();
+                self.screen.queue_response(reply);
             }
             // Set cursor style (DECSCUSR)
             ('q', [b' ']) => {
                 let style = first_param(&params_vec, 0);
                 match style {
-                    0 | 1 => {
+                    0 => self.screen.restore_cursor_shape(),
+                    1 => {
                         self.screen.cursor.style = CursorStyle::Block;
                         self.screen.cursor.blink = true;
                     }
@@ -962,11 +1213,7 @@ impl vte::Perform for ScreenPerformer<'_> {
             }
             // Reverse Index (RI) - move cursor up, scroll if at top
             (b'M', []) => {
-                if self.screen.cursor.row == self.screen.scroll_region().top {
-                    self.screen.scroll_down(1);
-                } else if self.screen.cursor.row > 0 {
-                    self.screen.cursor.row -= 1;
-                }
+                self.screen.reverse_index();
             }
             // Application Keypad (DECKPAM)
             (b'=', []) => {
@@ -1019,6 +1266,53 @@ impl vte::Perform for ScreenPerformer<'_> {
 }
 
 impl ScreenPerformer<'_> {
+    /// Soft terminal reset (DECSTR)
+    fn soft_reset(&mut self) {
+        self.screen.style.reset();
+        self.screen.modes.insert_mode = false;
+        self.screen.modes.origin_mode = false;
+        self.screen.reset_scroll_region();
+    }
+
+    /// Answer a DECRQSS request with the control function that restores the
+    /// setting, or `None` for settings we don't report
+    fn status_string(&self, request: &[u8]) -> Option<String> {
+        let screen = &*self.screen;
+        let status = match request {
+            // Select Graphic Rendition (SGR)
+            b"m" => format!("{}m", screen.sgr_params()),
+            // Set Top and Bottom Margins (DECSTBM)
+            b"r" => {
+                let region = screen.scroll_region();
+                format!("{};{}r", region.top + 1, region.bottom)
+            }
+            // Set Left and Right Margins (DECSLRM)
+            b"s" => {
+                let region = screen.scroll_region();
+                format!("{};{}s", region.left + 1, region.right)
+            }
+            // Set Cursor Style (DECSCUSR)
+            b" q" => {
+                let shape = match screen.cursor.style {
+                    CursorStyle::Block => 1,
+                    CursorStyle::Underline => 3,
+                    CursorStyle::Bar => 5,
+                };
+                format!("{} q", shape + usize::from(!screen.cursor.blink))
+            }
+            // Set Conformance Level (DECSCL), with 7-bit controls
+            b"\"p" => format!("{};1\"p", screen.conformance_level().decscl()),
+            // Select Character Protection Attribute (DECSCA)
+            b"\"q" => "0\"q".to_string(),
+            // Set Lines Per Page (DECSLPP)
+            b"t" => format!("{}t", screen.height()),
+            // Set Columns Per Page (DECSCPP)
+            b"$|" => format!("{}$|", screen.width()),
+            _ => return None,
+        };
+        Some(status)
+    }
+
     /// Handle OSC 1337 (iTerm2 inline images and file transfer)
     ///
     /// Protocol format: OSC 1337 ; File=[params] : base64data ST
@@ -1038,7 +1332,7 @@ impl ScreenPerformer<'_> {
 
         // Check for File= prefix
         if !content.starts_with("File=") {
-            log::trace!("OSC 1337: unhandled subcommand");
+            handle_osc_1337_command(self.screen, &content);
             return;
         }
 
@@ -1397,6 +1691,15 @@ impl ScreenPerformer<'_> {
             }
             // DECAWM - Auto Wrap Mode
             7 => self.screen.modes.auto_wrap = set,
+            // Reverse Wraparound Mode
+            45 => self.screen.modes.reverse_wraparound = set,
+            // DECLRMM - Left/Right Margin Mode
+            69 => {
+                self.screen.modes.left_right_margin_mode = set;
+                if !set {
+                    self.screen.reset_left_right_margins();
+                }
+            }
             // X10 Mouse Reporting
             9 => {
                 self.screen.modes.mouse_mode = if set { MouseMode::X10 } else { MouseMode::None };
@@ -1469,6 +1772,8 @@ impl ScreenPerformer<'_> {
             }
             // Bracketed Paste Mode
             2004 => self.screen.modes.bracketed_paste = set,
+            // Color scheme update notifications
+            2031 => self.screen.modes.color_scheme_updates = set,
             _ => {
                 log::trace!("Unknown DEC mode: {} = {}", mode, set);
             }
@@ -1491,6 +1796,121 @@ impl ScreenPerformer<'_> {
 
 // Helper functions
 
+/// Handle a non-File OSC 1337 subcommand of the form `Key=Value`
+fn handle_osc_1337_command(screen: &mut Screen, content: &str) {
+    let (key, value) = content.split_once('=').unwrap_or((content, ""));
+    match key {
+        // cterm extension: OSC 1337 ; AsciiArtSafe=<0|1> ST
+        "AsciiArtSafe" => {
+            screen.modes.ascii_art_safe = matches!(value, "1" | "yes" | "true");
+            screen.damage_all();
+            log::debug!("ASCII-art-safe rendering: {}", screen.modes.ascii_art_safe);
+        }
+        // OSC 1337 ; SetBadgeFormat=<base64 template> ST (empty clears)
+        "SetBadgeFormat" => {
+            let Some(format) = decode_base64_text(value) else {
+                log::warn!("OSC 1337 SetBadgeFormat: invalid base64");
+                return;
+            };
+            screen.badge_format = format;
+            screen.damage_all();
+        }
+        // OSC 1337 ; SetUserVar=<name>=<base64 value> ST
+        "SetUserVar" => {
+            let Some((name, encoded)) = value.split_once('=') else {
+                log::warn!("OSC 1337 SetUserVar: missing value");
+                return;
+            };
+            let Some(decoded) = decode_base64_text(encoded) else {
+                log::warn!("OSC 1337 SetUserVar {:?}: invalid base64", name);
+                return;
+            };
+            screen.set_user_var(name, decoded);
+        }
+        // OSC 1337 ; CurrentDir=<path> ST
+        "CurrentDir" => {
+            screen.set_current_dir("", value);
+        }
+        // OSC 1337 ; ReportVariable=<base64 name> ST, answered with
+        // OSC 1337 ; ReportVariable=<base64 value> BEL (empty when unset)
+        "ReportVariable" => {
+            use base64::Engine;
+            let Some(name) = decode_base64_text(value) else {
+                log::warn!("OSC 1337 ReportVariable: invalid base64");
+                return;
+            };
+            let value = screen.variable(&name).unwrap_or_default();
+            let encoded = base64::engine::general_purpose::STANDARD.encode(value);
+            screen.queue_response(format!("\x1b]1337;ReportVariable={}\x07", encoded).into_bytes());
+        }
+        // OSC 1337 ; RequestUpload=format=tgz ST, answered by the frontend
+        // (see iterm2::encode_upload)
+        "RequestUpload" => {
+            if value.strip_prefix("format=") == Some("tgz") {
+                screen.request_upload();
+            } else {
+                log::warn!("OSC 1337 RequestUpload: unsupported {:?}", value);
+                screen.queue_response(crate::iterm2::UPLOAD_ABORT.to_vec());
+            }
+        }
+        _ => {
+            log::trace!("OSC 1337: unhandled subcommand {:?}", key);
+        }
+    }
+}
+
+/// Split a `file://host/path` URI (OSC 7) into its host and decoded path.
+/// kitty's `kitty-shell-cwd://` form, which is not percent-encoded, is
+/// accepted as well.
+fn parse_file_uri(uri: &str) -> Option<(String, String)> {
+    let (rest, encoded) = if let Some(rest) = uri.strip_prefix("file://") {
+        (rest, true)
+    } else {
+        (uri.strip_prefix("kitty-shell-cwd://")?, false)
+    };
+    let slash = rest.find('/')?;
+    let (host, path) = rest.split_at(slash);
+    let path = if encoded {
+        percent_decode(path)
+    } else {
+        path.to_string()
+    };
+    Some((host.to_string(), path))
+}
+
+/// Decode `%XX` escapes as (lossy) UTF-8; malformed escapes are kept as-is
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Decode a base64 OSC payload as (lossy) UTF-8 text
+fn decode_base64_text(data: &str) -> Option<String> {
+    use base64::Engine;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
 fn params_to_vec(params: &Params) -> Vec<usize> {
     let mut result = Vec::new();
     for item in params.iter() {
@@ -1501,6 +1921,12 @@ fn params_to_vec(params: &Params) -> Vec<usize> {
     result
 }
 
+/// Enhancement flags from the first parameter (unknown bits are ignored)
+fn keyboard_flags_param(params: &[usize]) -> KeyboardFlags {
+    let bits = params.first().copied().unwrap_or(0).min(u8::MAX as usize) as u8;
+    KeyboardFlags::from_bits_truncate(bits)
+}
+
 fn first_param(params: &[usize], default: usize) -> usize {
     params
         .first()
@@ -1520,7 +1946,7 @@ fn second_param(params: &[usize], default: usize) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::screen::ScreenConfig;
+    use crate::screen::{PromptMark, ScreenConfig};
 
     fn make_screen() -> Screen {
         Screen::new(80, 24, ScreenConfig::default())
@@ -1550,6 +1976,76 @@ mod tests {
         assert_eq!(screen.cursor.col, 10);
     }
 
+    #[test]
+    fn test_cursor_position_params() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        // Zero and omitted parameters mean 1
+        parser.parse(&mut screen, b"\x1b[5;5H\x1b[0;0H");
+        assert_eq!((screen.cursor.row, screen.cursor.col), (0, 0));
+        parser.parse(&mut screen, b"\x1b[5;5H\x1b[;8H");
+        assert_eq!((screen.cursor.row, screen.cursor.col), (0, 7));
+        parser.parse(&mut screen, b"\x1b[3H");
+        assert_eq!((screen.cursor.row, screen.cursor.col), (2, 0));
+
+        // Out-of-range positions stop at the edge of the screen
+        parser.parse(&mut screen, b"\x1b[999;999H");
+        assert_eq!((screen.cursor.row, screen.cursor.col), (23, 79));
+
+        // HPA, HPR, VPA and VPR
+        parser.parse(&mut screen, b"\x1b[H\x1b[10`");
+        assert_eq!(screen.cursor.col, 9);
+        parser.parse(&mut screen, b"\x1b[0`\x1b[3a");
+        assert_eq!(screen.cursor.col, 3);
+        parser.parse(&mut screen, b"\x1b[0d\x1b[e");
+        assert_eq!((screen.cursor.row, screen.cursor.col), (1, 3));
+        parser.parse(&mut screen, b"\x1b[4d\x1b[0e");
+        assert_eq!(screen.cursor.row, 4);
+
+        // A zero bottom margin means the bottom of the screen
+        parser.parse(&mut screen, b"\x1b[5;0r");
+        assert_eq!(screen.scroll_region().top, 4);
+        assert_eq!(screen.scroll_region().bottom, 24);
+    }
+
+    #[test]
+    fn test_cursor_position_clears_pending_wrap() {
+        let mut screen = Screen::new(10, 3, ScreenConfig::default());
+        let mut parser = Parser::new();
+
+        // Printing up to the right margin leaves a wrap pending; moving the
+        // cursor back to the same column cancels it
+        parser.parse(&mut screen, b"\x1b[?69h\x1b[1;5sabcde\x1b[1;5Hx");
+        assert_eq!(screen.grid().row(0).unwrap().text(), "abcdx");
+        assert_eq!(screen.cursor.row, 0);
+    }
+
+    #[test]
+    fn test_cursor_position_report_at_right_margin() {
+        let mut screen = Screen::new(10, 4, ScreenConfig::default());
+        let mut parser = Parser::new();
+
+        // Printing up to the right margin leaves the cursor on it
+        parser.parse(&mut screen, b"\x1b[?69h\x1b[3;6s\x1b[4;3HABCD\x1b[6n");
+        assert_eq!(screen.take_pending_responses(), vec![b"\x1b[4;6R".to_vec()]);
+        assert_eq!(screen.grid().row(3).unwrap().text(), "  ABCD");
+    }
+
+    #[test]
+    fn test_line_feed_after_filling_to_right_margin() {
+        let mut screen = Screen::new(10, 4, ScreenConfig::default());
+        let mut parser = Parser::new();
+
+        // LF on the bottom margin with a wrap pending scrolls the region
+        parser.parse(&mut screen, b"0123456789\x1b[?69h\x1b[3;6s\x1b[1;2r");
+        parser.parse(&mut screen, b"\x1b[2;3HABCD\n");
+        assert_eq!(screen.grid().row(0).unwrap().text(), "01ABCD6789");
+        assert_eq!(screen.grid().row(1).unwrap().text(), "");
+        assert_eq!(screen.cursor.row, 1);
+        assert_eq!(screen.cursor.col, 5);
+    }
+
     #[test]
     fn test_sgr_colors() {
         let mut screen = make_screen();
@@ -1693,4 +2189,419 @@ mod tests {
         parser.parse(&mut screen, b"\x1b[?1007h"); // re-enable
         assert!(screen.modes.alternate_scroll);
     }
+
+    #[test]
+    fn test_osc_1337_ascii_art_safe() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        assert!(!screen.modes.ascii_art_safe);
+        parser.parse(&mut screen, b"\x1b]1337;AsciiArtSafe=1\x07ok");
+        assert!(screen.modes.ascii_art_safe);
+        // Text after the sequence must still be printed
+        assert_eq!(screen.get_cell(0, 0).unwrap().c, 'o');
+        assert_eq!(screen.get_cell(0, 1).unwrap().c, 'k');
+
+        parser.parse(&mut screen, b"\x1b]1337;AsciiArtSafe=0\x1b\\");
+        assert!(!screen.modes.ascii_art_safe);
+
+        // Cleared by a full reset
+        parser.parse(&mut screen, b"\x1b]1337;AsciiArtSafe=1\x07");
+        parser.parse(&mut screen, b"\x1bc");
+        assert!(!screen.modes.ascii_art_safe);
+    }
+
+    #[test]
+    fn test_osc_1337_badge_and_user_vars() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        // "prod" and "\(user.host) [\(session.columns)]"
+        parser.parse(&mut screen, b"\x1b]1337;SetUserVar=host=cHJvZA==\x07");
+        parser.parse(
+            &mut screen,
+            b"\x1b]1337;SetBadgeFormat=XCh1c2VyLmhvc3QpIFtcKHNlc3Npb24uY29sdW1ucyld\x07ok",
+        );
+        assert_eq!(
+            screen.user_vars.get("host").map(String::as_str),
+            Some("prod")
+        );
+        assert_eq!(screen.badge(), "prod [80]");
+        assert_eq!(screen.get_cell(0, 0).unwrap().c, 'o');
+
+        // An empty value unsets the variable; unknown references expand to nothing
+        parser.parse(&mut screen, b"\x1b]1337;SetUserVar=host=\x07");
+        assert!(screen.user_vars.is_empty());
+        assert_eq!(screen.badge(), " [80]");
+
+        // Invalid base64 leaves the badge alone
+        parser.parse(&mut screen, b"\x1b]1337;SetBadgeFormat=!!!\x07");
+        assert_eq!(screen.badge(), " [80]");
+
+        parser.parse(&mut screen, b"\x1b]1337;SetBadgeFormat=\x07");
+        assert_eq!(screen.badge(), "");
+    }
+
+    #[test]
+    fn test_osc_1337_report_variable() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        parser.parse(&mut screen, b"\x1b]1337;SetUserVar=host=cHJvZA==\x07");
+        // "user.host", then "user.none" which is unset
+        parser.parse(&mut screen, b"\x1b]1337;ReportVariable=dXNlci5ob3N0\x07");
+        parser.parse(&mut screen, b"\x1b]1337;ReportVariable=dXNlci5ub25l\x1b\\");
+        assert_eq!(
+            screen.take_pending_responses(),
+            vec![
+                b"\x1b]1337;ReportVariable=cHJvZA==\x07".to_vec(),
+                b"\x1b]1337;ReportVariable=\x07".to_vec(),
+            ]
+        );
+
+        // Invalid base64 gets no answer
+        parser.parse(&mut screen, b"\x1b]1337;ReportVariable=!!!\x07");
+        assert!(!screen.has_pending_responses());
+    }
+
+    #[test]
+    fn test_osc_1337_request_upload() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        parser.parse(&mut screen, b"\x1b]1337;RequestUpload=format=tgz\x07");
+        assert!(screen.take_upload_request());
+        assert!(!screen.take_upload_request());
+
+        // Other formats are declined right away
+        parser.parse(&mut screen, b"\x1b]1337;RequestUpload=format=zip\x07");
+        assert!(!screen.take_upload_request());
+        assert_eq!(screen.take_pending_responses(), vec![b"abort\n".to_vec()]);
+    }
+
+    #[test]
+    fn test_conformance_level() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        parser.parse(&mut screen, b"\x1b[c\x1b[>c");
+        assert_eq!(
+            screen.take_pending_responses(),
+            vec![b"\x1b[?65;4;22c".to_vec(), b"\x1b[>64;0;0c".to_vec()]
+        );
+
+        // DECSCL soft resets and downgrades the reports
+        parser.parse(&mut screen, b"\x1b[1m\x1b[61\"p\x1b[c\x1b[>c\x1bP$qm\x1b\\");
+        assert_eq!(screen.conformance_level(), ConformanceLevel::Vt100);
+        assert!(screen.style.attrs.is_empty());
+        assert_eq!(
+            screen.take_pending_responses(),
+            vec![b"\x1b[?1;2c".to_vec(), b"\x1b[>0;0;0c".to_vec()]
+        );
+
+        parser.parse(&mut screen, b"\x1b[64;1\"p\x1bP$q\"p\x1b\\");
+        assert_eq!(
+            screen.take_pending_responses(),
+            vec![b"\x1bP1$r64;1\"p\x1b\\".to_vec()]
+        );
+
+        // Unknown levels are ignored; a full reset restores the configured one
+        parser.parse(&mut screen, b"\x1b[70\"p");
+        assert_eq!(screen.conformance_level(), ConformanceLevel::Vt420);
+        let config = ScreenConfig {
+            conformance_level: ConformanceLevel::Vt220,
+            ..Default::default()
+        };
+        let mut screen = Screen::new(80, 24, config);
+        parser.parse(&mut screen, b"\x1b[65\"p\x1bc\x1b[c");
+        assert_eq!(
+            screen.take_pending_responses(),
+            vec![b"\x1b[?62;4;22c".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_decrqss() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        parser.parse(&mut screen, b"\x1b[1;4;31;48;5;200m\x1b[5;20r\x1b[4 q");
+        parser.parse(&mut screen, b"\x1bP$qm\x1b\\\x1bP$qr\x1b\\\x1bP$q q\x1b\\");
+        parser.parse(&mut screen, b"\x1bP$q\"p\x1b\\\x1bP$qx\x1b\\");
+        assert_eq!(
+            screen.take_pending_responses(),
+            vec![
+                b"\x1bP1$r0;1;4;31;48;5;200m\x1b\\".to_vec(),
+                b"\x1bP1$r5;20r\x1b\\".to_vec(),
+                b"\x1bP1$r4 q\x1b\\".to_vec(),
+                b"\x1bP1$r65;1\"p\x1b\\".to_vec(),
+                b"\x1bP0$r\x1b\\".to_vec(),
+            ]
+        );
+
+        // The default style reports only the reset
+        parser.parse(&mut screen, b"\x1b[0m\x1bP$qm\x1b\\");
+        assert_eq!(
+            screen.take_pending_responses(),
+            vec![b"\x1bP1$r0m\x1b\\".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_configured_cursor_shape() {
+        let config = ScreenConfig {
+            cursor_style: CursorStyle::Bar,
+            cursor_blink: false,
+            ..Default::default()
+        };
+        let mut screen = Screen::new(80, 24, config);
+        let mut parser = Parser::new();
+        assert_eq!(screen.cursor.style, CursorStyle::Bar);
+
+        parser.parse(&mut screen, b"\x1b[3 q");
+        assert_eq!(screen.cursor.style, CursorStyle::Underline);
+        parser.parse(&mut screen, b"\x1b[0 q");
+        assert_eq!(screen.cursor.style, CursorStyle::Bar);
+        assert!(!screen.cursor.blink);
+
+        // A new configured shape replaces the old one, but not a program's
+        screen.set_cursor_shape(CursorStyle::Underline, true);
+        assert_eq!(screen.cursor.style, CursorStyle::Underline);
+        parser.parse(&mut screen, b"\x1b[2 q");
+        screen.set_cursor_shape(CursorStyle::Bar, true);
+        assert_eq!(screen.cursor.style, CursorStyle::Block);
+        parser.parse(&mut screen, b"\x1bc");
+        assert_eq!(screen.cursor.style, CursorStyle::Bar);
+    }
+
+    #[test]
+    fn test_tmux_control_mode() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        parser.parse(&mut screen, b"\x1bP1000p%begin 1 2 0\r\n%end 1 2 0\n%out");
+        parser.parse(&mut screen, b"put %1 hi\\015\\012\n%exit\n\x1b\\after");
+        assert_eq!(
+            screen.take_tmux_control(),
+            vec![
+                TmuxControl::Started,
+                TmuxControl::Line(b"%begin 1 2 0".to_vec()),
+                TmuxControl::Line(b"%end 1 2 0".to_vec()),
+                TmuxControl::Line(b"%output %1 hi\\015\\012".to_vec()),
+                TmuxControl::Line(b"%exit".to_vec()),
+                TmuxControl::Ended,
+            ]
+        );
+        // Only what follows control mode reaches the screen
+        assert_eq!(screen.grid().row(0).unwrap().text(), "after");
+    }
+
+    #[test]
+    fn test_osc_7_current_dir() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        parser.parse(&mut screen, b"\x1b]7;file://laptop/home/me/My%20Docs\x07");
+        assert_eq!(screen.current_dir, "/home/me/My Docs");
+        assert_eq!(screen.current_host, "laptop");
+        assert!(screen.take_cwd_change());
+
+        // Same directory again is not a change
+        parser.parse(&mut screen, b"\x1b]7;file://laptop/home/me/My%20Docs\x1b\\");
+        assert!(!screen.take_cwd_change());
+
+        parser.parse(&mut screen, b"\x1b]7;file:///tmp/100%\x07");
+        assert_eq!(screen.current_dir, "/tmp/100%");
+        assert_eq!(screen.local_current_dir(), Some("/tmp/100%"));
+
+        parser.parse(
+            &mut screen,
+            b"\x1b]7;kitty-shell-cwd://localhost/srv/a b\x07",
+        );
+        assert_eq!(screen.current_dir, "/srv/a b");
+        assert_eq!(screen.local_current_dir(), Some("/srv/a b"));
+
+        // Not a file URI: ignored
+        parser.parse(&mut screen, b"\x1b]7;http://example.com/\x07");
+        assert_eq!(screen.current_dir, "/srv/a b");
+    }
+
+    #[test]
+    fn test_osc_22_pointer_shape() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+        assert_eq!(screen.take_pointer_shape_change(), None);
+
+        parser.parse(&mut screen, b"\x1b]22;xterm\x07");
+        assert_eq!(screen.take_pointer_shape_change(), Some(PointerShape::Text));
+        parser.parse(&mut screen, b"\x1b]22;text\x1b\\");
+        assert_eq!(screen.take_pointer_shape_change(), None);
+
+        // The first known name of a list wins
+        parser.parse(&mut screen, b"\x1b]22;zoom-in,hand2,text\x07");
+        assert_eq!(screen.pointer_shape(), PointerShape::Pointer);
+        parser.parse(&mut screen, b"\x1b]22;>none\x07");
+        assert_eq!(screen.pointer_shape(), PointerShape::Hidden);
+
+        // Unknown names and queries change nothing
+        parser.parse(&mut screen, b"\x1b]22;zoom-in\x07");
+        parser.parse(&mut screen, b"\x1b]22;?text\x07");
+        assert_eq!(screen.pointer_shape(), PointerShape::Hidden);
+
+        parser.parse(&mut screen, b"\x1b]22;<\x07");
+        assert_eq!(screen.pointer_shape(), PointerShape::Default);
+
+        parser.parse(&mut screen, b"\x1b]22;crosshair\x07");
+        parser.parse(&mut screen, b"\x1bc");
+        assert_eq!(screen.pointer_shape(), PointerShape::Default);
+    }
+
+    #[test]
+    fn test_osc_notifications() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        parser.parse(&mut screen, b"\x1b]9;Build done; 3 warnings\x07");
+        parser.parse(&mut screen, b"\x1b]777;notify;Deploy;prod is up\x1b\\");
+        // ConEmu progress, and OSC 777 commands other than notify
+        parser.parse(&mut screen, b"\x1b]9;4;1;50\x07");
+        parser.parse(&mut screen, b"\x1b]777;precmd\x07");
+        assert_eq!(
+            screen.take_notifications(),
+            vec![
+                Notification {
+                    title: None,
+                    body: "Build done; 3 warnings".into(),
+                },
+                Notification {
+                    title: Some("Deploy".into()),
+                    body: "prod is up".into(),
+                },
+            ]
+        );
+        assert!(screen.take_notifications().is_empty());
+    }
+
+    #[test]
+    fn test_osc_1337_current_dir() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        parser.parse(&mut screen, b"\x1b]1337;CurrentDir=/srv/app\x07");
+        assert_eq!(screen.current_dir, "/srv/app");
+        assert_eq!(screen.variable("session.path").as_deref(), Some("/srv/app"));
+    }
+
+    #[test]
+    fn test_osc_133_command_marks() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+
+        // A finish mark before any command (first prompt) is ignored
+        parser.parse(&mut screen, b"\x1b]133;D\x07\x1b]133;A\x07$ \x1b]133;B\x07");
+        assert!(screen.take_finished_commands().is_empty());
+
+        parser.parse(&mut screen, b"\x1b]133;C\x07output\r\n\x1b]133;D;2\x07");
+        let finished = screen.take_finished_commands();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].exit_code, Some(2));
+
+        // Each start mark is finished only once
+        parser.parse(&mut screen, b"\x1b]133;D;0\x1b\\");
+        assert!(screen.take_finished_commands().is_empty());
+
+        parser.parse(&mut screen, b"\x1b]133;C\x07\x1b]133;D\x07");
+        assert_eq!(screen.take_finished_commands()[0].exit_code, None);
+    }
+
+    #[test]
+    fn test_osc_133_prompt_marks() {
+        let mut screen = make_screen();
+        let mut parser = Parser::new();
+        parser.parse(
+            &mut screen,
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\nb\r\n\
+              \x1b]133;D;1\x07\x1b]133;A\x07$ \x1b]133;B\x07",
+        );
+
+        let prompts = screen.prompt_marks();
+        assert_eq!(prompts.len(), 2);
+        assert_eq!(
+            prompts[0],
+            PromptMark {
+                line: 0,
+                command_line: Some(0),
+                output_line: Some(1),
+                end_line: Some(3),
+                exit_code: Some(1),
+                duration: prompts[0].duration,
+            }
+        );
+        assert_eq!((prompts[1].line, prompts[1].output_line), (3, None));
+
+        assert_eq!(screen.command_output_range(0), Some(1..3));
+        assert_eq!(screen.command_output_range(2), Some(1..3));
+        assert_eq!(screen.command_output_range(3), None);
+
+        // A running command's output ends at the cursor
+        parser.parse(&mut screen, b"cat\r\n\x1b]133;C\x07x\r\ny");
+        assert_eq!(screen.command_output_range(3), Some(4..6));
+    }
+
+    #[test]
+    fn test_command_blocks() {
+        let mut screen = Screen::new(20, 4, ScreenConfig::default());
+        let mut parser = Parser::new();
+        parser.parse(
+            &mut screen,
+            b"\x1b]133;A\x07$ true\r\n\x1b]133;C\x07\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ false\r\n\x1b]133;C\x07a\r\nb\r\n\x1b]133;D;1\x07\
+              \x1b]133;A\x07$ ",
+        );
+        // The first prompt scrolled into the scrollback
+        let blocks = screen.command_blocks();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!((blocks[0].top, blocks[0].bottom), (0, 3));
+        assert!(blocks[0].failed());
+        assert!(blocks[0].duration.is_some());
+        assert!(blocks[0].contains(2) && !blocks[0].contains(3));
+
+        screen.scroll_offset = 1;
+        let blocks = screen.command_blocks();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!((blocks[0].top, blocks[0].bottom), (0, 1));
+        assert_eq!(blocks[0].exit_code, Some(0));
+        assert_eq!((blocks[1].top, blocks[1].bottom), (1, 4));
+    }
+
+    #[test]
+    fn test_scroll_marks() {
+        use crate::screen::{ScrollMark, ScrollMarkKind::*};
+        let marks = |screen: &Screen| -> Vec<(usize, _)> {
+            screen
+                .marks()
+                .into_iter()
+                .map(|ScrollMark { line, kind }| (line, kind))
+                .collect()
+        };
+        let config = ScreenConfig {
+            scrollback_lines: 3,
+            ..Default::default()
+        };
+        let mut screen = Screen::new(20, 3, config);
+        let mut parser = Parser::new();
+        parser.parse(
+            &mut screen,
+            b"$ make\r\nerror: oops\r\nok\r\nno errors\r\n$ ",
+        );
+        assert_eq!(marks(&screen), [(0, Prompt), (1, Error), (4, Prompt)]);
+
+        // Marks follow their lines as the oldest one is dropped
+        screen.set_search_lines([2]);
+        parser.parse(&mut screen, b"x\r\ny\r\nz");
+        assert_eq!(marks(&screen), [(0, Error), (1, SearchHit), (3, Prompt)]);
+
+        parser.parse(&mut screen, b"\x1b[3J");
+        assert_eq!(marks(&screen), [(0, Prompt)]);
+    }
 }
@@ -0,0 +1,125 @@
+//! Permissions for sensitive escape sequences
+//!
+//! Programs in a terminal can read and write the clipboard (OSC 52), send
+//! files (ZMODEM, Kermit, OSC 1337 File), show desktop notifications (OSC 9,
+//! OSC 777) and change the title (OSC 0/2). A [`PermissionPolicy`] allows,
+//! denies or asks about each of these; [`Terminal`](crate::Terminal) enforces
+//! it before emitting the matching events.
+
+use serde::{Deserialize, Serialize};
+
+/// What happens when a program does something sensitive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    /// Go ahead
+    #[default]
+    Allow,
+    /// Hold it until the user answers a prompt
+    Ask,
+    /// Drop it
+    Deny,
+}
+
+/// Something sensitive a program can do through escape sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Read a clipboard (OSC 52 query)
+    ClipboardRead,
+    /// Write a clipboard (OSC 52)
+    ClipboardWrite,
+    /// Send or receive files (ZMODEM, Kermit, OSC 1337 File)
+    FileTransfer,
+    /// Show a desktop notification (OSC 9, OSC 777)
+    Notification,
+    /// Change the title (OSC 0/2)
+    TitleChange,
+}
+
+impl Capability {
+    /// Every capability
+    pub const ALL: [Capability; 5] = [
+        Capability::ClipboardRead,
+        Capability::ClipboardWrite,
+        Capability::FileTransfer,
+        Capability::Notification,
+        Capability::TitleChange,
+    ];
+
+    /// What the program wants to do, completing "The program wants to ..."
+    pub fn description(self) -> &'static str {
+        match self {
+            Capability::ClipboardRead => "read the clipboard",
+            Capability::ClipboardWrite => "write to the clipboard",
+            Capability::FileTransfer => "transfer files",
+            Capability::Notification => "show a notification",
+            Capability::TitleChange => "change the title",
+        }
+    }
+}
+
+/// Permission for each [`Capability`]
+///
+/// ```toml
+/// [permissions]
+/// clipboard_read = "ask"
+/// clipboard_write = "allow"
+/// file_transfer = "ask"
+/// notifications = "allow"
+/// title_change = "deny"
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PermissionPolicy {
+    pub clipboard_read: Permission,
+    pub clipboard_write: Permission,
+    pub file_transfer: Permission,
+    pub notifications: Permission,
+    pub title_change: Permission,
+}
+
+impl Default for PermissionPolicy {
+    fn default() -> Self {
+        Self {
+            clipboard_read: Permission::Ask,
+            clipboard_write: Permission::Allow,
+            file_transfer: Permission::Allow,
+            notifications: Permission::Allow,
+            title_change: Permission::Allow,
+        }
+    }
+}
+
+impl PermissionPolicy {
+    /// Allow everything, for terminals whose frontends apply their own
+    /// policy (such as the daemon's)
+    pub fn allow_all() -> Self {
+        Self {
+            clipboard_read: Permission::Allow,
+            ..Self::default()
+        }
+    }
+
+    /// Permission for `capability`
+    pub fn get(&self, capability: Capability) -> Permission {
+        match capability {
+            Capability::ClipboardRead => self.clipboard_read,
+            Capability::ClipboardWrite => self.clipboard_write,
+            Capability::FileTransfer => self.file_transfer,
+            Capability::Notification => self.notifications,
+            Capability::TitleChange => self.title_change,
+        }
+    }
+
+    /// Set the permission for `capability`
+    pub fn set(&mut self, capability: Capability, permission: Permission) {
+        let slot = match capability {
+            Capability::ClipboardRead => &mut self.clipboard_read,
+            Capability::ClipboardWrite => &mut self.clipboard_write,
+            Capability::FileTransfer => &mut self.file_transfer,
+            Capability::Notification => &mut self.notifications,
+            Capability::TitleChange => &mut self.title_change,
+        };
+        *slot = permission;
+    }
+}
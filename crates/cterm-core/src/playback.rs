@@ -0,0 +1,470 @@
+//! Playback of recorded sessions
+//!
+//! A [`Recording`] is read from an asciicast v2 `.cast` file (as written by
+//! [`crate::Recorder`] or asciinema) or a ttyrec file. A [`Player`] feeds it
+//! into a [`Terminal`] at the recorded speed; frontends call
+//! [`Player::tick`] every [`Player::TICK`] and redraw, and pass keys to
+//! [`Player::handle_key`] for the pause, seek and speed controls.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::screen::ScreenConfig;
+use crate::term::{Key, Terminal};
+
+/// Size of ttyrec recordings, which don't store one
+const TTYREC_SIZE: (usize, usize) = (80, 24);
+
+/// Errors reading a recording
+#[derive(Error, Debug)]
+pub enum PlaybackError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid asciicast on line {line}: {source}")]
+    Json {
+        line: usize,
+        source: serde_json::Error,
+    },
+
+    #[error("Unsupported asciicast version {0} (expected 2)")]
+    Version(u32),
+
+    #[error("Truncated ttyrec frame at byte {0}")]
+    Truncated(usize),
+}
+
+/// What happens at a point of a recording
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameKind {
+    /// Terminal output
+    Output(Vec<u8>),
+    /// The terminal was resized to columns x rows
+    Resize(usize, usize),
+}
+
+/// One timed event of a recording
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Time since the start of the recording
+    pub time: Duration,
+    pub kind: FrameKind,
+}
+
+/// A recorded session
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    /// Terminal size at the start
+    pub cols: usize,
+    pub rows: usize,
+    /// Title from the asciicast header
+    pub title: Option<String>,
+    pub frames: Vec<Frame>,
+}
+
+/// Asciicast v2 header line
+#[derive(Deserialize)]
+struct CastHeader {
+    version: u32,
+    width: usize,
+    height: usize,
+    #[serde(default)]
+    title: Option<String>,
+    /// Longest pause kept on playback, in seconds
+    #[serde(default)]
+    idle_time_limit: Option<f64>,
+}
+
+impl Recording {
+    /// Read an asciicast or ttyrec file
+    pub fn open(path: &Path) -> Result<Self, PlaybackError> {
+        Self::parse(&std::fs::read(path)?)
+    }
+
+    /// Parse an asciicast recording, or a ttyrec one if it doesn't start
+    /// with a JSON header
+    pub fn parse(data: &[u8]) -> Result<Self, PlaybackError> {
+        if data.trim_ascii_start().starts_with(b"{") {
+            Self::parse_asciicast(&String::from_utf8_lossy(data))
+        } else {
+            Self::parse_ttyrec(data)
+        }
+    }
+
+    /// Parse an asciicast v2 recording; input and marker events are skipped
+    pub fn parse_asciicast(text: &str) -> Result<Self, PlaybackError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let json = |line: usize, source| PlaybackError::Json {
+            line: line + 1,
+            source,
+        };
+
+        let (n, header) = lines.next().unwrap_or((0, "{}"));
+        let header: CastHeader = serde_json::from_str(header).map_err(|e| json(n, e))?;
+        if header.version != 2 {
+            return Err(PlaybackError::Version(header.version));
+        }
+        let idle_limit = header
+            .idle_time_limit
+            .filter(|limit| *limit > 0.0)
+            .map(Duration::from_secs_f64);
+
+        let mut frames = Vec::new();
+        let mut recorded = Duration::ZERO;
+        let mut time = Duration::ZERO;
+        for (n, line) in lines {
+            let (at, code, data): (f64, String, String) =
+                serde_json::from_str(line).map_err(|e| json(n, e))?;
+            let at = Duration::from_secs_f64(at.max(0.0));
+            let gap = at.saturating_sub(recorded);
+            recorded = recorded.max(at);
+            time += idle_limit.map_or(gap, |limit| gap.min(limit));
+            let kind = match code.as_str() {
+                "o" => FrameKind::Output(data.into_bytes()),
+                "r" => match data.split_once('x').and_then(|(cols, rows)| {
+                    Some((cols.trim().parse().ok()?, rows.trim().parse().ok()?))
+                }) {
+                    Some((cols, rows)) => FrameKind::Resize(cols, rows),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            frames.push(Frame { time, kind });
+        }
+
+        Ok(Self {
+            cols: header.width,
+            rows: header.height,
+            title: header.title.filter(|title| !title.is_empty()),
+            frames,
+        })
+    }
+
+    /// Parse a ttyrec recording: frames of seconds, microseconds and length
+    /// (little-endian u32s) followed by the output
+    pub fn parse_ttyrec(mut data: &[u8]) -> Result<Self, PlaybackError> {
+        let total = data.len();
+        let mut frames = Vec::new();
+        let mut first = None;
+        while !data.is_empty() {
+            let offset = total - data.len();
+            let Some((header, rest)) = data.split_first_chunk::<12>() else {
+                return Err(PlaybackError::Truncated(offset));
+            };
+            let field = |i: usize| u32::from_le_bytes(header[i * 4..i * 4 + 4].try_into().unwrap());
+            let at = Duration::new(field(0) as u64, 0) + Duration::from_micros(field(1) as u64);
+            let len = field(2) as usize;
+            if rest.len() < len {
+                return Err(PlaybackError::Truncated(offset));
+            }
+            let start = *first.get_or_insert(at);
+            frames.push(Frame {
+                time: at.saturating_sub(start),
+                kind: FrameKind::Output(rest[..len].to_vec()),
+            });
+            data = &rest[len..];
+        }
+
+        Ok(Self {
+            cols: TTYREC_SIZE.0,
+            rows: TTYREC_SIZE.1,
+            title: None,
+            frames,
+        })
+    }
+
+    /// Time of the last frame
+    pub fn duration(&self) -> Duration {
+        self.frames
+            .last()
+            .map_or(Duration::ZERO, |frame| frame.time)
+    }
+
+    /// Tab title for playing the recording read from `path`: its title, or
+    /// else the file name
+    pub fn tab_title(&self, path: &Path) -> String {
+        let name = match self.title {
+            Some(ref title) => title.clone(),
+            None => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        format!("▶ {}", name)
+    }
+}
+
+/// Plays a [`Recording`] into a [`Terminal`]
+pub struct Player {
+    recording: Recording,
+    /// Position in recording time
+    position: Duration,
+    /// Index of the next frame to play
+    next: usize,
+    speed: f64,
+    paused: bool,
+    /// When the position was last advanced
+    last_tick: Option<Instant>,
+}
+
+impl Player {
+    /// How often frontends should call [`Self::tick`]
+    pub const TICK: Duration = Duration::from_millis(16);
+    /// How far the arrow keys seek
+    pub const SEEK_STEP: Duration = Duration::from_secs(5);
+    pub const MIN_SPEED: f64 = 0.25;
+    pub const MAX_SPEED: f64 = 16.0;
+
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            position: Duration::ZERO,
+            next: 0,
+            speed: 1.0,
+            paused: false,
+            last_tick: None,
+        }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// A read-only terminal of the recording's size to play into
+    pub fn new_terminal(&self, config: ScreenConfig) -> Terminal {
+        let mut term = Terminal::new(
+            self.recording.cols.max(1),
+            self.recording.rows.max(1),
+            config,
+        );
+        term.set_input_locked(true);
+        term
+    }
+
+    /// Play the frames that are due; returns true if the terminal changed
+    pub fn tick(&mut self, term: &mut Terminal) -> bool {
+        self.tick_at(term, Instant::now())
+    }
+
+    fn tick_at(&mut self, term: &mut Terminal, now: Instant) -> bool {
+        if let Some(last) = self.last_tick {
+            if !self.paused {
+                let elapsed = now.saturating_duration_since(last).mul_f64(self.speed);
+                self.position = (self.position + elapsed).min(self.recording.duration());
+            }
+        }
+        self.last_tick = Some(now);
+        self.play_due(term)
+    }
+
+    /// Feed frames up to the current position
+    fn play_due(&mut self, term: &mut Terminal) -> bool {
+        let start = self.next;
+        while let Some(frame) = self.recording.frames.get(self.next) {
+            if frame.time > self.position {
+                break;
+            }
+            match frame.kind {
+                FrameKind::Output(ref data) => {
+                    term.process(data);
+                }
+                FrameKind::Resize(cols, rows) => term.resize(cols.max(1), rows.max(1)),
+            }
+            self.next += 1;
+        }
+        self.next != start
+    }
+
+    /// Jump to `to`, replaying from the start when going backward
+    pub fn seek(&mut self, term: &mut Terminal, to: Duration) {
+        let to = to.min(self.recording.duration());
+        if to < self.position {
+            term.screen_mut().reset();
+            term.resize(self.recording.cols.max(1), self.recording.rows.max(1));
+            self.next = 0;
+        }
+        self.position = to;
+        self.play_due(term);
+    }
+
+    /// Pause or resume; resuming at the end starts over
+    pub fn toggle_pause(&mut self, term: &mut Terminal) {
+        if self.paused && self.is_finished() {
+            self.seek(term, Duration::ZERO);
+        }
+        self.paused = !self.paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Whether every frame has been played
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.recording.frames.len()
+    }
+
+    /// Playback speed, 1.0 being the recorded speed
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Set the playback speed, within [`Self::MIN_SPEED`] and
+    /// [`Self::MAX_SPEED`]
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(Self::MIN_SPEED, Self::MAX_SPEED);
+    }
+
+    pub fn position(&self) -> Duration {
+        self.position
+    }
+
+    /// Playback controls: Space pauses and resumes, Left and Right seek by
+    /// [`Self::SEEK_STEP`], Up and Down (or `+` and `-`) double and halve the
+    /// speed, Home and End jump to the start and end
+    ///
+    /// Returns false for keys that aren't controls.
+    pub fn handle_key(&mut self, term: &mut Terminal, key: Key) -> bool {
+        match key {
+            Key::Char(' ') | Key::Char('p') => self.toggle_pause(term),
+            Key::Left => self.seek(term, self.position.saturating_sub(Self::SEEK_STEP)),
+            Key::Right => self.seek(term, self.position + Self::SEEK_STEP),
+            Key::Up | Key::Char('+') | Key::Char('=') => self.set_speed(self.speed * 2.0),
+            Key::Down | Key::Char('-') => self.set_speed(self.speed / 2.0),
+            Key::Home => self.seek(term, Duration::ZERO),
+            Key::End => self.seek(term, self.recording.duration()),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Status strip text, e.g. `▶ 0:12 / 1:30 (2x)`
+    pub fn status(&self) -> String {
+        let icon = if self.paused { '⏸' } else { '▶' };
+        let mut text = format!(
+            "{} {} / {}",
+            icon,
+            format_time(self.position),
+            format_time(self.recording.duration())
+        );
+        if self.speed != 1.0 {
+            text.push_str(&format!(" ({}x)", self.speed));
+        }
+        text
+    }
+}
+
+/// `m:ss`
+fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAST: &str = "{\"version\": 2, \"width\": 20, \"height\": 5, \"title\": \"demo\"}\n\
+        [0.5, \"o\", \"one \"]\n\
+        [1.0, \"i\", \"typed\"]\n\
+        [1.5, \"o\", \"two \"]\n\
+        [2.0, \"r\", \"30x6\"]\n\
+        [3.0, \"o\", \"three\"]\n";
+
+    #[test]
+    fn test_parse_asciicast() {
+        let rec = Recording::parse(CAST.as_bytes()).unwrap();
+        assert_eq!((rec.cols, rec.rows), (20, 5));
+        assert_eq!(rec.title.as_deref(), Some("demo"));
+        assert_eq!(rec.frames.len(), 4);
+        assert_eq!(rec.frames[2].kind, FrameKind::Resize(30, 6));
+        assert_eq!(rec.duration(), Duration::from_secs(3));
+        assert_eq!(rec.tab_title(Path::new("/tmp/x.cast")), "▶ demo");
+
+        // Pauses longer than idle_time_limit are shortened
+        let capped = "{\"version\": 2, \"width\": 80, \"height\": 24, \"idle_time_limit\": 1}\n\
+            [0.5, \"o\", \"a\"]\n[10.5, \"o\", \"b\"]\n";
+        let rec = Recording::parse_asciicast(capped).unwrap();
+        assert_eq!(rec.frames[1].time, Duration::from_millis(1500));
+
+        assert!(matches!(
+            Recording::parse_asciicast("{\"version\": 1, \"width\": 80, \"height\": 24}"),
+            Err(PlaybackError::Version(1))
+        ));
+        assert!(matches!(
+            Recording::parse_asciicast(
+                "{\"version\": 2, \"width\": 80, \"height\": 24}\n[0.5, \"o\"]"
+            ),
+            Err(PlaybackError::Json { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_ttyrec() {
+        let mut data = Vec::new();
+        for (sec, usec, text) in [(100u32, 0u32, "ab"), (101, 500_000, "cd")] {
+            data.extend_from_slice(&sec.to_le_bytes());
+            data.extend_from_slice(&usec.to_le_bytes());
+            data.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            data.extend_from_slice(text.as_bytes());
+        }
+        let rec = Recording::parse(&data).unwrap();
+        assert_eq!((rec.cols, rec.rows), TTYREC_SIZE);
+        assert_eq!(rec.tab_title(Path::new("/tmp/x.rec")), "▶ x.rec");
+        assert_eq!(rec.frames[0].time, Duration::ZERO);
+        assert_eq!(rec.frames[1].time, Duration::from_millis(1500));
+        assert_eq!(rec.frames[1].kind, FrameKind::Output(b"cd".to_vec()));
+
+        assert!(matches!(
+            Recording::parse_ttyrec(&data[..data.len() - 1]),
+            Err(PlaybackError::Truncated(14))
+        ));
+    }
+
+    #[test]
+    fn test_player() {
+        let mut player = Player::new(Recording::parse(CAST.as_bytes()).unwrap());
+        let mut term = player.new_terminal(ScreenConfig::default());
+        let text = |term: &Terminal| term.screen().grid().row(0).unwrap().text();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        assert!(!player.tick_at(&mut term, start));
+        assert!(player.tick_at(&mut term, at(600)));
+        assert_eq!(text(&term), "one");
+
+        // Twice as fast: 1.5s of recording in 750ms
+        player.set_speed(2.0);
+        player.tick_at(&mut term, at(1350));
+        assert_eq!(text(&term), "one two");
+        assert_eq!(term.cols(), 30);
+        assert_eq!(player.status(), "▶ 0:02 / 0:03 (2x)");
+
+        // Nothing plays while paused
+        player.toggle_pause(&mut term);
+        player.tick_at(&mut term, at(5000));
+        assert_eq!(player.position(), Duration::from_millis(2100));
+
+        // Seeking back replays from the start
+        player.seek(&mut term, Duration::from_secs(1));
+        assert_eq!(text(&term), "one");
+        assert_eq!(term.cols(), 20);
+
+        assert!(player.handle_key(&mut term, Key::End));
+        assert!(player.is_finished());
+        assert_eq!(text(&term), "one two three");
+        assert!(!player.handle_key(&mut term, Key::Char('x')));
+
+        // Resuming at the end starts over
+        player.toggle_pause(&mut term);
+        assert!(!player.is_paused());
+        assert_eq!(player.position(), Duration::ZERO);
+        assert_eq!(text(&term), "");
+    }
+}
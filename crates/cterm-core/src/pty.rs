@@ -33,6 +33,9 @@ pub enum PtyError {
 
     #[error("PTY not running")]
     NotRunning,
+
+    #[error("Invalid environment variable: {0}")]
+    InvalidEnv(String),
 }
 
 /// PTY configuration
@@ -48,10 +51,49 @@ pub struct PtyConfig {
     pub cwd: Option<PathBuf>,
     /// Environment variables to set
     pub env: Vec<(String, String)>,
+    /// Start from a minimal environment (see [`MINIMAL_ENV_VARS`]) instead of
+    /// inheriting the parent's; `env` is applied on top either way
+    pub clear_env: bool,
     /// TERM environment variable value (default: xterm-256color)
     pub term: Option<String>,
 }
 
+/// Variables kept from the parent environment when [`PtyConfig::clear_env`]
+/// is set, so the shell can still find its home directory and programs
+pub const MINIMAL_ENV_VARS: &[&str] = &[
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "PATH",
+    "LANG",
+    "SYSTEMROOT",
+    "USERPROFILE",
+];
+
+/// Check that an environment variable can be passed to a child process.
+///
+/// Names must be non-empty and may not contain `=` or NUL; values may not
+/// contain NUL.
+pub fn validate_env_var(name: &str, value: &str) -> Result<(), PtyError> {
+    if name.is_empty() {
+        return Err(PtyError::InvalidEnv("empty name".into()));
+    }
+    if name.contains('=') || name.contains('\0') {
+        return Err(PtyError::InvalidEnv(format!(
+            "name {:?} contains '=' or NUL",
+            name
+        )));
+    }
+    if value.contains('\0') {
+        return Err(PtyError::InvalidEnv(format!(
+            "value of {} contains NUL",
+            name
+        )));
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Unix Implementation
 // ============================================================================
@@ -486,6 +528,18 @@ mod unix {
                 }
             }
 
+            // Drop inherited variables if a clean environment was requested
+            if config.clear_env {
+                for (key, _) in std::env::vars_os() {
+                    let keep = key.to_str().is_some_and(|k| MINIMAL_ENV_VARS.contains(&k));
+                    if !keep {
+                        if let Ok(key_c) = CString::new(key.as_encoded_bytes()) {
+                            libc::unsetenv(key_c.as_ptr());
+                        }
+                    }
+                }
+            }
+
             // Set environment variables
             for (key, value) in &config.env {
                 if let (Ok(key_c), Ok(value_c)) =
@@ -571,7 +625,7 @@ mod unix {
     }
 
     /// Get the default shell for the current user
-    fn get_default_shell() -> String {
+    pub(super) fn get_default_shell() -> String {
         // Try to get shell from environment
         if let Ok(shell) = std::env::var("SHELL") {
             return shell;
@@ -944,7 +998,7 @@ mod windows {
         }
     }
 
-    fn get_default_shell() -> String {
+    pub(super) fn get_default_shell() -> String {
         // Try COMSPEC first
         if let Ok(shell) = std::env::var("COMSPEC") {
             return shell;
@@ -957,8 +1011,12 @@ mod windows {
     fn build_environment_block(config: &PtyConfig) -> Vec<u16> {
         use std::collections::HashMap;
 
-        // Start with current environment
-        let mut env_map: HashMap<String, String> = std::env::vars().collect();
+        // Start with current environment (or the minimal subset of it)
+        let mut env_map: HashMap<String, String> = std::env::vars()
+            .filter(|(k, _)| {
+                !config.clear_env || MINIMAL_ENV_VARS.iter().any(|m| m.eq_ignore_ascii_case(k))
+            })
+            .collect();
 
         // Add config environment variables
         for (key, value) in &config.env {
@@ -994,10 +1052,10 @@ mod windows {
 #[cfg(unix)]
 pub use unix::save_original_nofile_limit;
 #[cfg(unix)]
-use unix::LocalPty;
+use unix::{get_default_shell, LocalPty};
 
 #[cfg(windows)]
-use windows::LocalPty;
+use windows::{get_default_shell, LocalPty};
 
 /// Platform-specific raw handle type
 /// On Unix this is RawFd (i32), on Windows this is RawHandle (isize)
@@ -1017,25 +1075,33 @@ pub type RawPtyHandle = std::os::windows::io::RawHandle;
 /// `Ssh` is a native puressh channel that implements blocking read/write/resize
 /// directly — it has no file descriptor, so FD-specific operations
 /// ([`Pty::try_raw_fd`], `foreground_*`) return `None` for it.
+/// `Serial` is an open serial device; it has no child process either.
 enum Backend {
     Local(LocalPty),
     Ssh(crate::ssh::SshPty),
+    Serial(crate::serial::SerialPty),
 }
 
 /// A pseudo-terminal handle.
 ///
 /// Most code treats this as an opaque source/sink of terminal bytes. Under the
-/// hood it is either a local OS PTY or a native SSH session; the methods below
+/// hood it is a local OS PTY, a native SSH session or a serial port; the methods below
 /// dispatch to whichever backend is in use.
 pub struct Pty {
     backend: Backend,
+    /// Program a local PTY was started with
+    program: Option<String>,
 }
 
 impl Pty {
     /// Create a new local PTY and spawn the configured shell.
     pub fn new(config: &PtyConfig) -> Result<Self, PtyError> {
+        for (key, value) in &config.env {
+            validate_env_var(key, value)?;
+        }
         Ok(Self {
             backend: Backend::Local(LocalPty::new(config)?),
+            program: Some(config.shell.clone().unwrap_or_else(get_default_shell)),
         })
     }
 
@@ -1043,6 +1109,15 @@ impl Pty {
     pub fn connect_ssh(config: crate::ssh::SshConfig, size: PtySize) -> Result<Self, PtyError> {
         Ok(Self {
             backend: Backend::Ssh(crate::ssh::SshPty::connect(config, size)?),
+            program: None,
+        })
+    }
+
+    /// Open and configure a serial device.
+    pub fn open_serial(config: &crate::serial::SerialConfig) -> Result<Self, PtyError> {
+        Ok(Self {
+            backend: Backend::Serial(crate::serial::SerialPty::open(config)?),
+            program: None,
         })
     }
 
@@ -1055,6 +1130,7 @@ impl Pty {
     pub unsafe fn from_raw_fd(fd: RawPtyHandle, child_pid: i32) -> Self {
         Self {
             backend: Backend::Local(LocalPty::from_raw_fd(fd, child_pid)),
+            program: None,
         }
     }
 
@@ -1066,7 +1142,7 @@ impl Pty {
     pub fn try_raw_fd(&self) -> Option<RawPtyHandle> {
         match &self.backend {
             Backend::Local(p) => Some(p.raw_fd()),
-            Backend::Ssh(_) => None,
+            Backend::Ssh(_) | Backend::Serial(_) => None,
         }
     }
 
@@ -1079,6 +1155,10 @@ impl Pty {
                 io::ErrorKind::Unsupported,
                 "dup_fd is not supported for SSH-backed PTYs",
             )),
+            Backend::Serial(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "dup_fd is not supported for serial ports",
+            )),
         }
     }
 
@@ -1088,6 +1168,7 @@ impl Pty {
         match &self.backend {
             Backend::Local(p) => p.child_pid(),
             Backend::Ssh(p) => p.child_pid(),
+            Backend::Serial(p) => p.child_pid(),
         }
     }
 
@@ -1096,6 +1177,7 @@ impl Pty {
         match &mut self.backend {
             Backend::Local(p) => p.write(data),
             Backend::Ssh(p) => p.write(data),
+            Backend::Serial(p) => p.write(data),
         }
     }
 
@@ -1104,6 +1186,7 @@ impl Pty {
         match &mut self.backend {
             Backend::Local(p) => p.read(buf),
             Backend::Ssh(p) => p.read(buf),
+            Backend::Serial(p) => p.read(buf),
         }
     }
 
@@ -1112,6 +1195,7 @@ impl Pty {
         match &self.backend {
             Backend::Local(p) => p.resize(rows, cols),
             Backend::Ssh(p) => p.resize(rows, cols),
+            Backend::Serial(p) => p.resize(rows, cols),
         }
     }
 
@@ -1120,6 +1204,7 @@ impl Pty {
         match &mut self.backend {
             Backend::Local(p) => p.is_running(),
             Backend::Ssh(p) => p.is_running(),
+            Backend::Serial(p) => p.is_running(),
         }
     }
 
@@ -1128,6 +1213,7 @@ impl Pty {
         match &mut self.backend {
             Backend::Local(p) => p.wait(),
             Backend::Ssh(p) => p.wait(),
+            Backend::Serial(p) => p.wait(),
         }
     }
 
@@ -1136,6 +1222,7 @@ impl Pty {
         match &mut self.backend {
             Backend::Local(p) => p.try_wait(),
             Backend::Ssh(p) => p.try_wait(),
+            Backend::Serial(p) => p.try_wait(),
         }
     }
 
@@ -1144,6 +1231,7 @@ impl Pty {
         match &self.backend {
             Backend::Local(p) => p.send_signal(signal),
             Backend::Ssh(p) => p.send_signal(signal),
+            Backend::Serial(p) => p.send_signal(signal),
         }
     }
 
@@ -1152,10 +1240,12 @@ impl Pty {
         match &self.backend {
             Backend::Local(p) => Ok(Box::new(p.try_clone_reader()?)),
             Backend::Ssh(p) => p.try_clone_reader(),
+            Backend::Serial(p) => p.try_clone_reader(),
         }
     }
 
-    /// Clone an independent blocking writer over the PTY input, for local PTYs.
+    /// Clone an independent blocking writer over the PTY input, for local PTYs and
+    /// serial ports.
     ///
     /// A duplicated local PTY master fd is bidirectional, so the returned `File` can
     /// be written to from a dedicated thread without holding any terminal lock. SSH
@@ -1165,6 +1255,38 @@ impl Pty {
         match &self.backend {
             Backend::Local(p) => p.try_clone_reader().ok(),
             Backend::Ssh(_) => None,
+            Backend::Serial(p) => p.try_clone_writer().ok(),
+        }
+    }
+
+    /// Whether this PTY is a native SSH session.
+    pub fn is_ssh(&self) -> bool {
+        matches!(self.backend, Backend::Ssh(_))
+    }
+
+    /// Whether this PTY is a serial port.
+    pub fn is_serial(&self) -> bool {
+        matches!(self.backend, Backend::Serial(_))
+    }
+
+    /// Program this local PTY was started with: the configured shell or the
+    /// user's default one.
+    ///
+    /// `None` for SSH sessions, serial ports and PTYs taken over from another
+    /// process.
+    pub fn program(&self) -> Option<&str> {
+        self.program.as_deref()
+    }
+
+    /// Open an SFTP session on the SSH connection behind this PTY.
+    ///
+    /// Fails for local PTYs, which have no remote end to transfer files with.
+    pub fn open_sftp(&self) -> Result<crate::sftp::RemoteFs, PtyError> {
+        match &self.backend {
+            Backend::Local(_) | Backend::Serial(_) => Err(PtyError::Spawn(
+                "file transfer requires an SSH session".to_string(),
+            )),
+            Backend::Ssh(p) => p.open_sftp(),
         }
     }
 
@@ -1173,7 +1295,7 @@ impl Pty {
     pub fn foreground_process_group(&self) -> Option<i32> {
         match &self.backend {
             Backend::Local(p) => p.foreground_process_group(),
-            Backend::Ssh(_) => None,
+            Backend::Ssh(_) | Backend::Serial(_) => None,
         }
     }
 
@@ -1182,7 +1304,7 @@ impl Pty {
     pub fn has_foreground_process(&self) -> bool {
         match &self.backend {
             Backend::Local(p) => p.has_foreground_process(),
-            Backend::Ssh(_) => false,
+            Backend::Ssh(_) | Backend::Serial(_) => false,
         }
     }
 
@@ -1191,7 +1313,7 @@ impl Pty {
     pub fn foreground_process_name(&self) -> Option<String> {
         match &self.backend {
             Backend::Local(p) => p.foreground_process_name(),
-            Backend::Ssh(_) => None,
+            Backend::Ssh(_) | Backend::Serial(_) => None,
         }
     }
 
@@ -1200,7 +1322,7 @@ impl Pty {
     pub fn foreground_cwd(&self) -> Option<PathBuf> {
         match &self.backend {
             Backend::Local(p) => p.foreground_cwd(),
-            Backend::Ssh(_) => None,
+            Backend::Ssh(_) | Backend::Serial(_) => None,
         }
     }
 }
@@ -1262,6 +1384,7 @@ mod tests {
 
         let mut pty = Pty::new(&config).expect("Failed to create PTY");
         assert!(pty.child_pid() > 0);
+        assert_eq!(pty.program(), Some("/bin/sh"));
 
         // Give the command time to produce output
         std::thread::sleep(std::time::Duration::from_millis(100));
@@ -1566,4 +1689,52 @@ mod tests {
         let output = String::from_utf8_lossy(&buf[..n]);
         assert!(output.contains("test_value_123"), "Output was: {}", output);
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_pty_clear_env_unix() {
+        std::env::set_var("CTERM_TEST_INHERITED", "leaked");
+        let config = PtyConfig {
+            size: PtySize {
+                rows: 24,
+                cols: 80,
+                ..Default::default()
+            },
+            shell: Some("/bin/sh".to_string()),
+            args: vec![
+                "-c".to_string(),
+                "echo \"[$CTERM_TEST_INHERITED|$TEST_VAR]\"".to_string(),
+            ],
+            env: vec![("TEST_VAR".to_string(), "kept".to_string())],
+            clear_env: true,
+            ..Default::default()
+        };
+
+        let pty = Pty::new(&config).expect("Failed to create PTY");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut reader = pty.try_clone_reader().expect("Failed to clone reader");
+        let mut buf = [0u8; 1024];
+        let n = reader.read(&mut buf).expect("Failed to read");
+        let output = String::from_utf8_lossy(&buf[..n]);
+        assert!(output.contains("[|kept]"), "Output was: {}", output);
+    }
+
+    #[test]
+    fn test_validate_env_var() {
+        assert!(validate_env_var("FOO", "bar").is_ok());
+        assert!(validate_env_var("FOO", "").is_ok());
+        assert!(validate_env_var("", "bar").is_err());
+        assert!(validate_env_var("A=B", "bar").is_err());
+        assert!(validate_env_var("FOO", "b\0ar").is_err());
+    }
+
+    #[test]
+    fn test_pty_rejects_invalid_env() {
+        let config = PtyConfig {
+            env: vec![("BAD=NAME".to_string(), "x".to_string())],
+            ..Default::default()
+        };
+        assert!(matches!(Pty::new(&config), Err(PtyError::InvalidEnv(_))));
+    }
 }
@@ -0,0 +1,283 @@
+//! Asciicast v2 session recording
+//!
+//! A [`Recorder`] writes terminal output to an asciinema `.cast` file: a JSON
+//! header line followed by one `[time, "o", data]` line per output chunk and
+//! `[time, "r", "COLSxROWS"]` lines for resizes. Time stops while the
+//! recording is paused, so playback skips the pause.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Records terminal output to an asciicast v2 stream
+pub struct Recorder {
+    out: Box<dyn Write + Send>,
+    /// When the recording started
+    start: Instant,
+    /// When the current pause began
+    paused_at: Option<Instant>,
+    /// Total time spent in earlier pauses
+    paused_for: Duration,
+    /// Start of a UTF-8 sequence cut off at the end of the last chunk
+    pending: Vec<u8>,
+}
+
+impl Recorder {
+    /// Create `path` and write the header for a `cols`x`rows` terminal
+    pub fn create(path: &Path, cols: usize, rows: usize, title: &str) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), cols, rows, title)
+    }
+
+    /// Record to `out`, writing the header for a `cols`x`rows` terminal
+    pub fn new(
+        out: impl Write + Send + 'static,
+        cols: usize,
+        rows: usize,
+        title: &str,
+    ) -> io::Result<Self> {
+        Self::new_at(Box::new(out), cols, rows, title, Instant::now())
+    }
+
+    fn new_at(
+        mut out: Box<dyn Write + Send>,
+        cols: usize,
+        rows: usize,
+        title: &str,
+        now: Instant,
+    ) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let mut header = format!(
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}",
+            cols, rows, timestamp
+        );
+        if !title.is_empty() {
+            header.push_str(", \"title\": ");
+            push_json_string(&mut header, title);
+        }
+        header.push('}');
+        writeln!(out, "{}", header)?;
+        Ok(Self {
+            out,
+            start: now,
+            paused_at: None,
+            paused_for: Duration::ZERO,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Record a chunk of output; dropped while paused
+    pub fn output(&mut self, data: &[u8]) -> io::Result<()> {
+        self.output_at(data, Instant::now())
+    }
+
+    fn output_at(&mut self, data: &[u8], now: Instant) -> io::Result<()> {
+        if self.is_paused() {
+            return Ok(());
+        }
+        let text = self.take_text(data);
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.event(now, 'o', &text)
+    }
+
+    /// Record a terminal resize
+    pub fn resize(&mut self, cols: usize, rows: usize) -> io::Result<()> {
+        self.resize_at(cols, rows, Instant::now())
+    }
+
+    fn resize_at(&mut self, cols: usize, rows: usize, now: Instant) -> io::Result<()> {
+        self.event(now, 'r', &format!("{}x{}", cols, rows))
+    }
+
+    /// Stop recording output until [`Self::resume`]
+    pub fn pause(&mut self) {
+        self.pause_at(Instant::now());
+    }
+
+    fn pause_at(&mut self, now: Instant) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(now);
+            // A sequence cut off before the pause can't continue after it
+            self.pending.clear();
+        }
+    }
+
+    /// Record output again after [`Self::pause`]
+    pub fn resume(&mut self) {
+        self.resume_at(Instant::now());
+    }
+
+    fn resume_at(&mut self, now: Instant) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_for += now.saturating_duration_since(paused_at);
+        }
+    }
+
+    /// Whether the recording is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Recorded time so far, not counting pauses
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_at(Instant::now())
+    }
+
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        let now = self.paused_at.unwrap_or(now);
+        now.saturating_duration_since(self.start)
+            .saturating_sub(self.paused_for)
+    }
+
+    /// Write anything still buffered and flush the file
+    pub fn finish(mut self) -> io::Result<()> {
+        self.flush_pending()?;
+        self.out.flush()
+    }
+
+    /// Output up to the last complete UTF-8 sequence; the rest waits for the
+    /// next chunk
+    fn take_text(&mut self, data: &[u8]) -> String {
+        self.pending.extend_from_slice(data);
+        let keep = incomplete_tail(&self.pending);
+        let tail = self.pending.split_off(self.pending.len() - keep);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = tail;
+        text
+    }
+
+    fn flush_pending(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&std::mem::take(&mut self.pending)).into_owned();
+        self.event(Instant::now(), 'o', &text)
+    }
+
+    fn event(&mut self, now: Instant, code: char, data: &str) -> io::Result<()> {
+        let mut line = format!("[{:.6}, \"{}\", ", self.elapsed_at(now).as_secs_f64(), code);
+        push_json_string(&mut line, data);
+        line.push(']');
+        writeln!(self.out, "{}", line)
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let _ = self.flush_pending();
+        let _ = self.out.flush();
+    }
+}
+
+/// Length of an unfinished UTF-8 sequence at the end of `bytes`
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    for i in 1..=bytes.len().min(3) {
+        let b = bytes[bytes.len() - i];
+        if b & 0xC0 == 0x80 {
+            continue;
+        }
+        let needed = match b {
+            0xF0.. => 4,
+            0xE0.. => 3,
+            0xC0.. => 2,
+            _ => 1,
+        };
+        return if needed > i { i } else { 0 };
+    }
+    0
+}
+
+/// Append `s` as a JSON string literal
+fn push_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\x7f' => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Shared {
+        fn lines(&self) -> Vec<String> {
+            let data = self.0.lock().unwrap();
+            String::from_utf8(data.clone())
+                .unwrap()
+                .lines()
+                .map(String::from)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_recorder() {
+        let out = Shared::default();
+        let start = Instant::now();
+        let mut rec = Recorder::new_at(Box::new(out.clone()), 80, 24, "vim \"x\"", start).unwrap();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        rec.output_at(b"hello\r\n\x1b[1m", at(500)).unwrap();
+        // "é" split across two chunks
+        rec.output_at(b"caf\xc3", at(1000)).unwrap();
+        rec.output_at(b"\xa9", at(1250)).unwrap();
+        rec.pause_at(at(2000));
+        rec.output_at(b"secret", at(2500)).unwrap();
+        rec.resume_at(at(5000));
+        assert_eq!(rec.elapsed_at(at(5000)), Duration::from_secs(2));
+        rec.resize_at(100, 30, at(5500)).unwrap();
+        rec.finish().unwrap();
+
+        let lines = out.lines();
+        assert!(lines[0].starts_with("{\"version\": 2, \"width\": 80, \"height\": 24, "));
+        assert!(lines[0].ends_with(", \"title\": \"vim \\\"x\\\"\"}"));
+        assert_eq!(
+            &lines[1..],
+            [
+                "[0.500000, \"o\", \"hello\\r\\n\\u001b[1m\"]",
+                "[1.000000, \"o\", \"caf\"]",
+                "[1.250000, \"o\", \"é\"]",
+                "[2.500000, \"r\", \"100x30\"]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_incomplete_tail() {
+        assert_eq!(incomplete_tail(b"abc"), 0);
+        assert_eq!(incomplete_tail(b"a\xc3"), 1);
+        assert_eq!(incomplete_tail("é".as_bytes()), 0);
+        assert_eq!(incomplete_tail(b"\xe2\x82"), 2);
+        assert_eq!(incomplete_tail("€".as_bytes()), 0);
+        assert_eq!(incomplete_tail(b"\xf0\x9f\x98"), 3);
+    }
+}
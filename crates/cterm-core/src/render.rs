@@ -0,0 +1,636 @@
+//! Software rendering of a screen to an image
+//!
+//! [`ScreenRenderer`] rasterizes the visible grid of a [`Screen`] with the
+//! embedded [`bitmap_font`](crate::bitmap_font): cell colors and text
+//! attributes, box drawing and block elements, images and the cursor. It
+//! needs no window system or font library, so the headless daemon can take
+//! screenshots for CI systems and bots.
+
+use image::ImageEncoder;
+
+use crate::bitmap_font::{self, GLYPH_SIZE};
+use crate::cell::{Cell, CellAttrs};
+use crate::color::{Color, ColorPalette, Rgb};
+use crate::screen::{CursorStyle, Screen, TerminalImage};
+
+/// Cell width in pixels at scale 1
+pub const CELL_WIDTH: usize = 8;
+/// Cell height in pixels at scale 1
+pub const CELL_HEIGHT: usize = 12;
+/// Largest supported scale factor
+pub const MAX_SCALE: usize = 4;
+
+/// Row of the cell where the glyph starts, at scale 1
+const GLYPH_TOP: usize = 2;
+/// Row of the cell holding underlines, at scale 1
+const UNDERLINE_ROW: usize = GLYPH_TOP + GLYPH_SIZE;
+
+/// Line weights of box drawing arms
+const LIGHT: u8 = 1;
+const HEAVY: u8 = 2;
+const DOUBLE: u8 = 3;
+
+/// A rendered screen
+#[derive(Debug, Clone)]
+pub struct Screenshot {
+    /// Width in pixels
+    pub width: usize,
+    /// Height in pixels
+    pub height: usize,
+    /// RGBA pixel data (4 bytes per pixel)
+    pub data: Vec<u8>,
+}
+
+impl Screenshot {
+    /// Color of the pixel at `x`, `y`
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Rgb> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y * self.width + x) * 4;
+        Some(Rgb::new(self.data[i], self.data[i + 1], self.data[i + 2]))
+    }
+
+    /// Encode the screenshot as PNG
+    pub fn to_png(&self) -> Result<Vec<u8>, image::ImageError> {
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png).write_image(
+            &self.data,
+            self.width as u32,
+            self.height as u32,
+            image::ExtendedColorType::Rgba8,
+        )?;
+        Ok(png)
+    }
+}
+
+/// Renders screens to [`Screenshot`]s
+#[derive(Debug, Clone)]
+pub struct ScreenRenderer {
+    palette: ColorPalette,
+    scale: usize,
+    draw_cursor: bool,
+}
+
+impl Default for ScreenRenderer {
+    fn default() -> Self {
+        Self::new(ColorPalette::default())
+    }
+}
+
+impl ScreenRenderer {
+    /// Renderer using `palette` at scale 1, drawing the cursor
+    pub fn new(palette: ColorPalette) -> Self {
+        Self {
+            palette,
+            scale: 1,
+            draw_cursor: true,
+        }
+    }
+
+    /// Scale every pixel of the font up by `scale` (1 to [`MAX_SCALE`])
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.clamp(1, MAX_SCALE);
+        self
+    }
+
+    /// Whether to draw the cursor (it is never drawn while hidden by DECTCEM)
+    pub fn with_cursor(mut self, draw_cursor: bool) -> Self {
+        self.draw_cursor = draw_cursor;
+        self
+    }
+
+    /// Size of a cell in pixels
+    pub fn cell_size(&self) -> (usize, usize) {
+        (CELL_WIDTH * self.scale, CELL_HEIGHT * self.scale)
+    }
+
+    /// Render the visible grid of `screen`
+    pub fn render(&self, screen: &Screen) -> Screenshot {
+        let (cw, ch) = self.cell_size();
+        let grid = screen.grid();
+        let mut canvas = Canvas::new(grid.width() * cw, grid.height() * ch);
+
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                self.draw_cell(&mut canvas, col * cw, row * ch, cell);
+            }
+        }
+
+        let top = screen.scrollback().len();
+        for image in screen.images() {
+            self.draw_image(&mut canvas, image, top);
+        }
+
+        let cursor = &screen.cursor;
+        if self.draw_cursor && screen.modes.show_cursor {
+            if let Some(cell) = grid.get(cursor.row, cursor.col) {
+                self.draw_cursor(
+                    &mut canvas,
+                    cursor.col * cw,
+                    cursor.row * ch,
+                    cursor.style,
+                    cell,
+                );
+            }
+        }
+
+        Screenshot {
+            width: canvas.width,
+            height: canvas.height,
+            data: canvas.data,
+        }
+    }
+
+    /// Foreground and background of a cell after inverse and dim
+    fn cell_colors(&self, cell: &Cell) -> (Rgb, Rgb) {
+        let mut fg = cell.fg.to_rgb(&self.palette);
+        let mut bg = match cell.bg {
+            Color::Default => self.palette.background,
+            color => color.to_rgb(&self.palette),
+        };
+        if cell.attrs.contains(CellAttrs::INVERSE) {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+        if cell.attrs.contains(CellAttrs::DIM) {
+            fg = mix(fg, bg, 0.5);
+        }
+        (fg, bg)
+    }
+
+    fn draw_cell(&self, canvas: &mut Canvas, x: usize, y: usize, cell: &Cell) {
+        let (cw, ch) = self.cell_size();
+        let (fg, bg) = self.cell_colors(cell);
+        canvas.fill(x, y, cw, ch, bg);
+        if cell.is_wide_spacer() || cell.attrs.contains(CellAttrs::HIDDEN) {
+            return;
+        }
+
+        let width = if cell.is_wide() { 2 * cw } else { cw };
+        self.draw_char(canvas, x, y, width, cell, fg, bg);
+
+        let s = self.scale;
+        let attrs = cell.attrs;
+        if attrs.has_underline() {
+            let color = cell
+                .underline_color
+                .map_or(fg, |color| color.to_rgb(&self.palette));
+            let line = y + UNDERLINE_ROW * s;
+            if attrs.contains(CellAttrs::DOUBLE_UNDERLINE) {
+                canvas.fill(x, line - s, width, s, color);
+                canvas.fill(x, line + s, width, s, color);
+            } else {
+                for i in (0..width).step_by(s) {
+                    let (dy, on) = if attrs.contains(CellAttrs::CURLY_UNDERLINE) {
+                        ((i / (2 * s)) % 2 * s, true)
+                    } else if attrs.contains(CellAttrs::DOTTED_UNDERLINE) {
+                        (0, (i / s).is_multiple_of(2))
+                    } else if attrs.contains(CellAttrs::DASHED_UNDERLINE) {
+                        (0, (i / (3 * s)).is_multiple_of(2))
+                    } else {
+                        (0, true)
+                    };
+                    if on {
+                        canvas.fill(x + i, line + dy, s, s, color);
+                    }
+                }
+            }
+        }
+        if attrs.contains(CellAttrs::STRIKETHROUGH) {
+            canvas.fill(x, y + ch / 2, width, s, fg);
+        }
+        if attrs.contains(CellAttrs::OVERLINE) {
+            canvas.fill(x, y, width, s, fg);
+        }
+    }
+
+    /// Draw the character of a cell `width` pixels wide in `fg` over `bg`
+    #[allow(clippy::too_many_arguments)]
+    fn draw_char(
+        &self,
+        canvas: &mut Canvas,
+        x: usize,
+        y: usize,
+        width: usize,
+        cell: &Cell,
+        fg: Rgb,
+        bg: Rgb,
+    ) {
+        let (_, ch) = self.cell_size();
+        let s = self.scale;
+        let c = cell.c;
+        if c == ' ' || c == '\0' {
+            return;
+        }
+
+        if let Some(glyph) = bitmap_font::glyph(c) {
+            let bold = cell.attrs.contains(CellAttrs::BOLD);
+            let italic = cell.attrs.contains(CellAttrs::ITALIC);
+            for (gy, bits) in glyph.iter().enumerate() {
+                // Lean the top half of italic glyphs one pixel to the right
+                let shift = if italic && gy < GLYPH_SIZE / 2 { s } else { 0 };
+                let py = y + (GLYPH_TOP + gy) * s;
+                for gx in (0..GLYPH_SIZE).filter(|gx| bits & (1 << gx) != 0) {
+                    let px = x + gx * s + shift;
+                    canvas.fill(px, py, s, s, fg);
+                    if bold {
+                        canvas.fill(px + s, py, s, s, fg);
+                    }
+                }
+            }
+        } else if let Some(arms) = box_arms(c) {
+            self.draw_box(canvas, x, y, width, ch, arms, fg);
+        } else if !draw_block(canvas, x, y, width, ch, c, fg, bg) {
+            // Anything else is shown as an empty box
+            let (left, top) = (x + s, y + GLYPH_TOP * s);
+            let (w, h) = (width - 2 * s, GLYPH_SIZE * s - s);
+            canvas.fill(left, top, w, s, fg);
+            canvas.fill(left, top + h - s, w, s, fg);
+            canvas.fill(left, top, s, h, fg);
+            canvas.fill(left + w - s, top, s, h, fg);
+        }
+    }
+
+    /// Draw box drawing arms (up, right, down, left) meeting at the center
+    #[allow(clippy::too_many_arguments)]
+    fn draw_box(
+        &self,
+        canvas: &mut Canvas,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        arms: [u8; 4],
+        color: Rgb,
+    ) {
+        let s = self.scale;
+        let [up, right, down, left] = arms;
+        let (cx, cy) = ((x + w / 2) as isize, (y + h / 2) as isize);
+        // Horizontal arms run into the vertical lines and back
+        let (v_lo, v_hi) = line_extent(&[up, down], s);
+        let (h_lo, h_hi) = line_extent(&[left, right], s);
+        let (x, y, w, h) = (x as isize, y as isize, w as isize, h as isize);
+
+        for (offset, t) in line_offsets(left, s) {
+            canvas.fill_signed(x, cy + offset, cx + v_hi - x, t, color);
+        }
+        for (offset, t) in line_offsets(right, s) {
+            canvas.fill_signed(cx + v_lo, cy + offset, x + w - cx - v_lo, t, color);
+        }
+        for (offset, t) in line_offsets(up, s) {
+            canvas.fill_signed(cx + offset, y, t, cy + h_hi - y, color);
+        }
+        for (offset, t) in line_offsets(down, s) {
+            canvas.fill_signed(cx + offset, cy + h_lo, t, y + h - cy - h_lo, color);
+        }
+    }
+
+    /// Draw an image scaled to the cells it covers, `top` being the absolute
+    /// line of the first grid row
+    fn draw_image(&self, canvas: &mut Canvas, image: &TerminalImage, top: usize) {
+        let (cw, ch) = self.cell_size();
+        let (pw, ph) = (image.pixel_width, image.pixel_height);
+        if pw == 0 || ph == 0 || image.data.len() < pw * ph * 4 {
+            return;
+        }
+        let row = image.line as isize - top as isize;
+        let (x0, y0) = (image.col * cw, row * ch as isize);
+        let (w, h) = (image.cell_width * cw, image.cell_height * ch);
+
+        for dy in 0..h {
+            let y = y0 + dy as isize;
+            if y < 0 || y as usize >= canvas.height {
+                continue;
+            }
+            let sy = dy * ph / h;
+            for dx in 0..w {
+                let x = x0 + dx;
+                if x >= canvas.width {
+                    break;
+                }
+                let i = (sy * pw + dx * pw / w) * 4;
+                canvas.blend(x, y as usize, &image.data[i..i + 4]);
+            }
+        }
+    }
+
+    fn draw_cursor(
+        &self,
+        canvas: &mut Canvas,
+        x: usize,
+        y: usize,
+        style: CursorStyle,
+        cell: &Cell,
+    ) {
+        let (cw, ch) = self.cell_size();
+        let s = self.scale;
+        let width = if cell.is_wide() { 2 * cw } else { cw };
+        let color = self.palette.cursor;
+        match style {
+            CursorStyle::Block => {
+                canvas.fill(x, y, width, ch, color);
+                if !cell.attrs.contains(CellAttrs::HIDDEN) {
+                    let text = self.palette.background;
+                    self.draw_char(canvas, x, y, width, cell, text, color);
+                }
+            }
+            CursorStyle::Underline => canvas.fill(x, y + ch - 2 * s, width, 2 * s, color),
+            CursorStyle::Bar => canvas.fill(x, y, 2 * s, ch, color),
+        }
+    }
+}
+
+/// RGBA pixel buffer with clipped drawing
+struct Canvas {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0xFF; width * height * 4],
+        }
+    }
+
+    /// Fill a rectangle, clipped to the canvas
+    fn fill(&mut self, x: usize, y: usize, w: usize, h: usize, color: Rgb) {
+        let right = (x + w).min(self.width);
+        for py in y..(y + h).min(self.height) {
+            for px in x..right {
+                let i = (py * self.width + px) * 4;
+                self.data[i..i + 3].copy_from_slice(&[color.r, color.g, color.b]);
+            }
+        }
+    }
+
+    /// [`Canvas::fill`] for coordinates that may be off the canvas
+    fn fill_signed(&mut self, x: isize, y: isize, w: isize, h: isize, color: Rgb) {
+        let (left, top) = (x.max(0), y.max(0));
+        let (w, h) = (x + w - left, y + h - top);
+        if w > 0 && h > 0 {
+            self.fill(left as usize, top as usize, w as usize, h as usize, color);
+        }
+    }
+
+    /// Draw an RGBA pixel over the canvas
+    fn blend(&mut self, x: usize, y: usize, rgba: &[u8]) {
+        let i = (y * self.width + x) * 4;
+        let alpha = rgba[3] as u32;
+        for (under, &over) in self.data[i..i + 3].iter_mut().zip(rgba) {
+            *under = ((over as u32 * alpha + *under as u32 * (255 - alpha)) / 255) as u8;
+        }
+    }
+}
+
+/// Blend `a` into `b`, `amount` being the share of `a`
+fn mix(a: Rgb, b: Rgb, amount: f32) -> Rgb {
+    let channel = |a: u8, b: u8| (a as f32 * amount + b as f32 * (1.0 - amount)).round() as u8;
+    Rgb::new(channel(a.r, b.r), channel(a.g, b.g), channel(a.b, b.b))
+}
+
+/// Offsets from the center and thicknesses of the lines of one arm
+fn line_offsets(weight: u8, s: usize) -> Vec<(isize, isize)> {
+    let s = s as isize;
+    let light = -(s / 2);
+    match weight {
+        LIGHT => vec![(light, s)],
+        HEAVY => vec![(-s, 2 * s)],
+        DOUBLE => vec![(light - s, s), (light + s, s)],
+        _ => Vec::new(),
+    }
+}
+
+/// Span covered by the lines of some arms, relative to the center
+fn line_extent(arms: &[u8], s: usize) -> (isize, isize) {
+    arms.iter()
+        .flat_map(|&weight| line_offsets(weight, s))
+        .fold(None, |extent: Option<(isize, isize)>, (offset, t)| {
+            let (lo, hi) = extent.unwrap_or((offset, offset + t));
+            Some((lo.min(offset), hi.max(offset + t)))
+        })
+        .unwrap_or((0, 0))
+}
+
+/// Arm weights (up, right, down, left) of box drawing characters
+fn box_arms(c: char) -> Option<[u8; 4]> {
+    const L: u8 = LIGHT;
+    const H: u8 = HEAVY;
+    const D: u8 = DOUBLE;
+    Some(match c {
+        '─' => [0, L, 0, L],
+        '━' => [0, H, 0, H],
+        '│' => [L, 0, L, 0],
+        '┃' => [H, 0, H, 0],
+        '┌' | '╭' => [0, L, L, 0],
+        '┏' => [0, H, H, 0],
+        '┐' | '╮' => [0, 0, L, L],
+        '┓' => [0, 0, H, H],
+        '└' | '╰' => [L, L, 0, 0],
+        '┗' => [H, H, 0, 0],
+        '┘' | '╯' => [L, 0, 0, L],
+        '┛' => [H, 0, 0, H],
+        '├' => [L, L, L, 0],
+        '┣' => [H, H, H, 0],
+        '┤' => [L, 0, L, L],
+        '┫' => [H, 0, H, H],
+        '┬' => [0, L, L, L],
+        '┳' => [0, H, H, H],
+        '┴' => [L, L, 0, L],
+        '┻' => [H, H, 0, H],
+        '┼' => [L, L, L, L],
+        '╋' => [H, H, H, H],
+        '═' => [0, D, 0, D],
+        '║' => [D, 0, D, 0],
+        '╔' => [0, D, D, 0],
+        '╗' => [0, 0, D, D],
+        '╚' => [D, D, 0, 0],
+        '╝' => [D, 0, 0, D],
+        '╠' => [D, D, D, 0],
+        '╣' => [D, 0, D, D],
+        '╦' => [0, D, D, D],
+        '╩' => [D, D, 0, D],
+        '╬' => [D, D, D, D],
+        '╴' => [0, 0, 0, L],
+        '╵' => [L, 0, 0, 0],
+        '╶' => [0, L, 0, 0],
+        '╷' => [0, 0, L, 0],
+        _ => return None,
+    })
+}
+
+/// Draw a block element (U+2580..U+259F), returning false for other
+/// characters
+#[allow(clippy::too_many_arguments)]
+fn draw_block(
+    canvas: &mut Canvas,
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    c: char,
+    fg: Rgb,
+    bg: Rgb,
+) -> bool {
+    let eighths = |n: usize, of: usize| n * of / 8;
+    match c {
+        '▀' => canvas.fill(x, y, w, h / 2, fg),
+        '▁'..='█' => {
+            let height = eighths(c as usize - 0x2580, h);
+            canvas.fill(x, y + h - height, w, height, fg);
+        }
+        '▉'..='▏' => canvas.fill(x, y, eighths(0x2590 - c as usize, w), h, fg),
+        '▐' => canvas.fill(x + w / 2, y, w - w / 2, h, fg),
+        '░' | '▒' | '▓' => {
+            let amount = (c as usize - 0x2590) as f32 / 4.0;
+            canvas.fill(x, y, w, h, mix(fg, bg, amount));
+        }
+        '▔' => canvas.fill(x, y, w, eighths(1, h), fg),
+        '▕' => {
+            let width = eighths(1, w);
+            canvas.fill(x + w - width, y, width, h, fg);
+        }
+        '▖'..='▟' => {
+            // Quadrants: upper left, upper right, lower left, lower right
+            const QUADRANTS: [u8; 10] = [4, 8, 1, 13, 9, 7, 11, 2, 6, 14];
+            let bits = QUADRANTS[c as usize - 0x2596];
+            let (hw, hh) = (w / 2, h / 2);
+            let corners = [(x, y), (x + hw, y), (x, y + hh), (x + hw, y + hh)];
+            for (i, (qx, qy)) in corners.into_iter().enumerate() {
+                if bits & (1 << i) != 0 {
+                    canvas.fill(qx, qy, w - hw, h - hh, fg);
+                }
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::screen::ScreenConfig;
+    use crate::sixel::SixelImage;
+
+    fn screen_with(text: &[u8]) -> Screen {
+        let mut screen = Screen::new(10, 3, ScreenConfig::default());
+        Parser::new().parse(&mut screen, text);
+        screen
+    }
+
+    #[test]
+    fn test_render_size() {
+        let screen = screen_with(b"");
+        let shot = ScreenRenderer::default().render(&screen);
+        assert_eq!((shot.width, shot.height), (80, 36));
+        assert_eq!(shot.data.len(), 80 * 36 * 4);
+
+        let shot = ScreenRenderer::default().with_scale(2).render(&screen);
+        assert_eq!((shot.width, shot.height), (160, 72));
+        let shot = ScreenRenderer::default().with_scale(100).render(&screen);
+        assert_eq!(shot.width, 10 * CELL_WIDTH * MAX_SCALE);
+    }
+
+    #[test]
+    fn test_render_text() {
+        let palette = ColorPalette::default();
+        // Red "A" on blue, then a reset space
+        let screen = screen_with(b"\x1b[?25l\x1b[31;44mA\x1b[m ");
+        let shot = ScreenRenderer::new(palette.clone()).render(&screen);
+        let red = palette.ansi[1];
+        let blue = palette.ansi[4];
+
+        // The top row of 'A' is 0x0C: pixels 2 and 3 are set
+        let y = GLYPH_TOP;
+        assert_eq!(shot.pixel(2, y), Some(red));
+        assert_eq!(shot.pixel(0, y), Some(blue));
+        assert_eq!(shot.pixel(0, 0), Some(blue));
+        // The next cell has the default background
+        assert_eq!(shot.pixel(CELL_WIDTH + 2, y), Some(palette.background));
+    }
+
+    #[test]
+    fn test_render_attributes() {
+        let palette = ColorPalette::default();
+        let screen = screen_with(b"\x1b[?25l\x1b[4m \x1b[7m ");
+        let shot = ScreenRenderer::new(palette.clone()).render(&screen);
+        assert_eq!(shot.pixel(3, UNDERLINE_ROW), Some(palette.foreground));
+        assert_eq!(shot.pixel(3, UNDERLINE_ROW - 1), Some(palette.background));
+        // Inverse swaps the default colors
+        assert_eq!(shot.pixel(CELL_WIDTH, 0), Some(palette.foreground));
+    }
+
+    #[test]
+    fn test_render_box_drawing() {
+        let palette = ColorPalette::default();
+        let screen = screen_with("\x1b[?25l─█".as_bytes());
+        let shot = ScreenRenderer::new(palette.clone()).render(&screen);
+        let middle = CELL_HEIGHT / 2;
+        for x in 0..CELL_WIDTH {
+            assert_eq!(shot.pixel(x, middle), Some(palette.foreground));
+            assert_eq!(shot.pixel(x, middle - 2), Some(palette.background));
+        }
+        assert_eq!(shot.pixel(CELL_WIDTH, 0), Some(palette.foreground));
+        assert_eq!(
+            shot.pixel(2 * CELL_WIDTH - 1, CELL_HEIGHT - 1),
+            Some(palette.foreground)
+        );
+    }
+
+    #[test]
+    fn test_render_cursor() {
+        let palette = ColorPalette::default();
+        let screen = screen_with(b"\x1b[2;3H");
+        let renderer = ScreenRenderer::new(palette.clone());
+        let shot = renderer.render(&screen);
+        let (x, y) = (2 * CELL_WIDTH, CELL_HEIGHT);
+        assert_eq!(shot.pixel(x, y), Some(palette.cursor));
+
+        let shot = renderer.clone().with_cursor(false).render(&screen);
+        assert_eq!(shot.pixel(x, y), Some(palette.background));
+
+        // Hidden with DECTCEM
+        let screen = screen_with(b"\x1b[2;3H\x1b[?25l");
+        let shot = renderer.render(&screen);
+        assert_eq!(shot.pixel(x, y), Some(palette.background));
+    }
+
+    #[test]
+    fn test_render_image() {
+        let mut screen = screen_with(b"\x1b[?25l");
+        // A 2x1 image: opaque green, then fully transparent
+        let data = vec![0, 255, 0, 255, 255, 0, 0, 0];
+        screen.insert_image(
+            1,
+            1,
+            2,
+            1,
+            SixelImage {
+                data,
+                width: 2,
+                height: 1,
+            },
+        );
+        let palette = ColorPalette::default();
+        let shot = ScreenRenderer::new(palette.clone()).render(&screen);
+        let y = CELL_HEIGHT + 3;
+        assert_eq!(shot.pixel(CELL_WIDTH, y), Some(Rgb::new(0, 255, 0)));
+        assert_eq!(shot.pixel(2 * CELL_WIDTH + 4, y), Some(palette.background));
+    }
+
+    #[test]
+    fn test_to_png() {
+        let screen = screen_with(b"hello");
+        let shot = ScreenRenderer::default().render(&screen);
+        let png = shot.to_png().unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let decoded = image::load_from_memory(&png).unwrap();
+        assert_eq!(decoded.width(), shot.width as u32);
+        assert_eq!(decoded.height(), shot.height as u32);
+    }
+}
@@ -3,31 +3,146 @@
 //! Manages the visible grid and scrollback history, handling resize
 //! and scroll operations.
 
-use crate::cell::{Cell, CellStyle};
+use crate::cell::{Cell, CellAttrs, CellStyle, Hyperlink};
+use crate::color::{Color, ColorScheme, Rgb};
+use crate::damage::{CursorMove, Damage, DamageTracker};
 use crate::drcs::{DrcsFont, DrcsGlyph};
-use crate::grid::{Grid, Row};
+use crate::grid::{reflow_rows, Grid, Row, SemanticMark};
+use crate::hints::DEFAULT_HINT_ALPHABET;
+use crate::kitty_graphics::KittyGraphics;
+use crate::kitty_keyboard::{KeyboardFlagStack, KeyboardFlags};
 use crate::sixel::SixelImage;
+use crate::watch::LineHighlight;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Configuration for the screen
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScreenConfig {
     /// Maximum scrollback lines (0 = no scrollback)
     pub scrollback_lines: usize,
+    /// Word boundaries and copy trimming for selections
+    #[serde(default)]
+    pub selection: SelectionConfig,
+    /// VT level reported until a program changes it with DECSCL
+    pub conformance_level: ConformanceLevel,
+    /// Cursor shape used until a program picks one with DECSCUSR, and
+    /// restored by DECSCUSR 0 and a full reset
+    pub cursor_style: CursorStyle,
+    /// Whether that cursor blinks
+    pub cursor_blink: bool,
 }
 
 impl Default for ScreenConfig {
     fn default() -> Self {
         Self {
             scrollback_lines: 10000,
+            selection: SelectionConfig::default(),
+            conformance_level: ConformanceLevel::default(),
+            cursor_style: CursorStyle::default(),
+            cursor_blink: true,
         }
     }
 }
 
+/// Word boundaries and copy trimming for selections
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelectionConfig {
+    /// Characters besides letters and digits that word selection
+    /// (double-click) treats as part of a word
+    pub word_chars: String,
+    /// Strip trailing whitespace from each copied line, and trailing blank
+    /// lines from the copied text
+    pub trim_trailing_whitespace: bool,
+    /// Letters hint mode labels targets with, home row first
+    pub hint_alphabet: String,
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        Self {
+            word_chars: "_.".into(),
+            trim_trailing_whitespace: true,
+            hint_alphabet: DEFAULT_HINT_ALPHABET.into(),
+        }
+    }
+}
+
+impl SelectionConfig {
+    /// Check if a character is part of a word
+    pub fn is_word_char(&self, c: char) -> bool {
+        c.is_alphanumeric() || self.word_chars.contains(c)
+    }
+}
+
+/// VT terminal model the screen presents itself as (DECSCL)
+///
+/// Programs that only expect an older terminal select a lower level; device
+/// attribute and status string reports then describe that terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConformanceLevel {
+    Vt100,
+    Vt220,
+    Vt320,
+    Vt420,
+    #[default]
+    Vt520,
+}
+
+impl ConformanceLevel {
+    /// Level for DECSCL's first parameter (61 for VT100 to 65 for VT500)
+    pub fn from_decscl(param: usize) -> Option<Self> {
+        match param {
+            61 => Some(Self::Vt100),
+            62 => Some(Self::Vt220),
+            63 => Some(Self::Vt320),
+            64 => Some(Self::Vt420),
+            65 => Some(Self::Vt520),
+            _ => None,
+        }
+    }
+
+    /// DECSCL parameter selecting this level
+    pub fn decscl(self) -> u16 {
+        match self {
+            Self::Vt100 => 61,
+            Self::Vt220 => 62,
+            Self::Vt320 => 63,
+            Self::Vt420 => 64,
+            Self::Vt520 => 65,
+        }
+    }
+
+    /// Primary device attributes (DA1): the service class and, from the
+    /// VT220 on, sixel graphics (4) and ANSI color (22)
+    pub fn primary_attributes(self) -> Vec<u8> {
+        match self {
+            Self::Vt100 => b"\x1b[?1;2c".to_vec(),
+            level => format!("\x1b[?{};4;22c", level.decscl()).into_bytes(),
+        }
+    }
+
+    /// Secondary device attributes (DA2): the terminal model, firmware
+    /// version 0 and no keyboard options
+    pub fn secondary_attributes(self) -> Vec<u8> {
+        let model = match self {
+            Self::Vt100 => 0,
+            Self::Vt220 => 1,
+            Self::Vt320 => 24,
+            Self::Vt420 => 41,
+            Self::Vt520 => 64,
+        };
+        format!("\x1b[>{};0;0c", model).into_bytes()
+    }
+}
+
 /// Cursor position and state
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Cursor {
     /// Column position (0-indexed)
     pub col: usize,
@@ -48,17 +163,86 @@ pub enum CursorStyle {
     Bar,
 }
 
-/// Scroll region bounds
+/// Mouse pointer shape requested with OSC 22
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PointerShape {
+    /// The frontend's usual pointer over the terminal
+    #[default]
+    Default,
+    /// Arrow
+    Arrow,
+    /// Text beam
+    Text,
+    /// Pointing hand, as over a link
+    Pointer,
+    Crosshair,
+    Wait,
+    Help,
+    Move,
+    NotAllowed,
+    /// Left-right arrows
+    ColumnResize,
+    /// Up-down arrows
+    RowResize,
+    /// No pointer at all
+    Hidden,
+}
+
+impl PointerShape {
+    /// Shape for a CSS cursor name or its X11 cursor-font counterpart, as
+    /// sent by xterm and kitty; an empty name restores the default
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name.trim() {
+            "" | "default" => PointerShape::Default,
+            "left_ptr" | "arrow" | "top_left_arrow" => PointerShape::Arrow,
+            "text" | "xterm" | "ibeam" | "vertical-text" => PointerShape::Text,
+            "pointer" | "hand" | "hand1" | "hand2" => PointerShape::Pointer,
+            "crosshair" | "cross" | "tcross" | "cell" => PointerShape::Crosshair,
+            "wait" | "watch" | "progress" => PointerShape::Wait,
+            "help" | "question_arrow" => PointerShape::Help,
+            "move" | "fleur" | "all-scroll" | "grab" | "grabbing" => PointerShape::Move,
+            "not-allowed" | "no-drop" | "X_cursor" => PointerShape::NotAllowed,
+            "ew-resize" | "col-resize" | "e-resize" | "w-resize" | "sb_h_double_arrow" => {
+                PointerShape::ColumnResize
+            }
+            "ns-resize" | "row-resize" | "n-resize" | "s-resize" | "sb_v_double_arrow" => {
+                PointerShape::RowResize
+            }
+            "none" => PointerShape::Hidden,
+            _ => return None,
+        })
+    }
+}
+
+/// Scroll region bounds: rows `top..bottom` (DECSTBM) and columns
+/// `left..right` (DECSLRM)
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ScrollRegion {
     pub top: usize,
     pub bottom: usize,
+    pub left: usize,
+    pub right: usize,
 }
 
 impl ScrollRegion {
+    /// Region covering a whole `width` x `height` screen
+    fn full(width: usize, height: usize) -> Self {
+        Self {
+            top: 0,
+            bottom: height,
+            left: 0,
+            right: width,
+        }
+    }
+
     pub fn contains(&self, row: usize) -> bool {
         row >= self.top && row < self.bottom
     }
+
+    /// Whether `col` is between the left and right margins
+    pub fn contains_col(&self, col: usize) -> bool {
+        col >= self.left && col < self.right
+    }
 }
 
 /// Terminal modes that affect behavior
@@ -70,8 +254,14 @@ pub struct TerminalModes {
     pub application_keypad: bool,
     /// Auto-wrap mode (DECAWM)
     pub auto_wrap: bool,
-    /// Origin mode (DECOM)
+    /// Reverse wraparound (mode 45): with auto-wrap on, backspace in the
+    /// first column moves to the last column of the line above
+    pub reverse_wraparound: bool,
+    /// Origin mode (DECOM): cursor addressing is relative to the margins
     pub origin_mode: bool,
+    /// Left/right margin mode (DECLRMM, mode 69): enables DECSLRM, which
+    /// otherwise is `CSI s` (save cursor)
+    pub left_right_margin_mode: bool,
     /// Insert mode (IRM)
     pub insert_mode: bool,
     /// Line feed/new line mode (LNM)
@@ -90,6 +280,9 @@ pub struct TerminalModes {
     pub bracketed_paste: bool,
     /// Focus events reporting
     pub focus_events: bool,
+    /// Color scheme updates (mode 2031): report changes between a dark and a
+    /// light theme with `CSI ? 997 ; Ps n`
+    pub color_scheme_updates: bool,
     /// Alternate screen buffer active
     pub alternate_screen: bool,
     /// Active charset (true = G1, false = G0) - controlled by SO/SI
@@ -102,6 +295,10 @@ pub struct TerminalModes {
     pub charset_g0: Option<String>,
     /// G1 character set designator (None = standard)
     pub charset_g1: Option<String>,
+    /// ASCII-art-safe rendering: renderers disable font ligatures and keep every
+    /// glyph inside its own cell, so box art and side-by-side diffs stay aligned.
+    /// Toggled per tab from the UI or with `OSC 1337 ; AsciiArtSafe=<0|1> ST`.
+    pub ascii_art_safe: bool,
 }
 
 /// Character set designations
@@ -132,7 +329,7 @@ pub enum MouseMode {
 }
 
 /// Clipboard selection type for OSC 52
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ClipboardSelection {
     /// System clipboard (c)
     Clipboard,
@@ -189,6 +386,101 @@ pub enum FileTransferOperation {
     },
 }
 
+/// A shell command that finished, from OSC 133 shell integration marks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandFinished {
+    /// Time from the command start mark (`C`) to the finish mark (`D`)
+    pub duration: Duration,
+    /// Exit status reported with the finish mark, if any
+    pub exit_code: Option<i32>,
+}
+
+/// A desktop notification a program asked for (OSC 9 or OSC 777 notify)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// Title, when the program gave one (OSC 777)
+    pub title: Option<String>,
+    pub body: String,
+}
+
+/// Output of tmux control mode (`tmux -CC`), which tmux wraps in a DCS
+/// string so the terminal can tell it from ordinary output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TmuxControl {
+    /// tmux entered control mode (DCS 1000 p)
+    Started,
+    /// One line of the control protocol, without its line ending
+    Line(Vec<u8>),
+    /// tmux left control mode (the DCS string ended)
+    Ended,
+}
+
+/// A shell prompt found from OSC 133 marks, with the command run from it
+///
+/// Lines are absolute (0 = oldest scrollback line).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptMark {
+    /// Line the prompt starts on (`A`)
+    pub line: usize,
+    /// Line the command line starts on (`B`), normally the prompt's own
+    pub command_line: Option<usize>,
+    /// Line the command's output starts on (`C`), once it ran
+    pub output_line: Option<usize>,
+    /// Line of the finish mark (`D`), once the command finished
+    pub end_line: Option<usize>,
+    /// Exit status reported with the finish mark
+    pub exit_code: Option<i32>,
+    /// Run time from the output mark to the finish mark, when this screen
+    /// received both
+    pub duration: Option<Duration>,
+}
+
+/// Rows of a finished command on the screen, for the exit status gutter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// First visible row of the block: its prompt, or the top row if the
+    /// prompt scrolled out of view
+    pub top: usize,
+    /// Visible row after the block's last row
+    pub bottom: usize,
+    /// Exit status reported with the finish mark
+    pub exit_code: Option<i32>,
+    /// Run time, when known
+    pub duration: Option<Duration>,
+}
+
+impl CommandBlock {
+    /// Whether the command failed (a non-zero exit status)
+    pub fn failed(&self) -> bool {
+        self.exit_code.is_some_and(|code| code != 0)
+    }
+
+    /// Whether the block covers visible `row`
+    pub fn contains(&self, row: usize) -> bool {
+        (self.top..self.bottom).contains(&row)
+    }
+}
+
+/// What a scrollbar mark points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ScrollMarkKind {
+    /// A shell prompt
+    Prompt,
+    /// A line that reports an error
+    Error,
+    /// A line with a search match
+    SearchHit,
+}
+
+/// A line to mark along the scrollbar
+///
+/// Lines are absolute (0 = oldest scrollback line).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollMark {
+    pub line: usize,
+    pub kind: ScrollMarkKind,
+}
+
 /// A point in the terminal buffer (absolute line index + column)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SelectionPoint {
@@ -225,7 +517,7 @@ impl Ord for SelectionPoint {
 }
 
 /// Text selection state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Selection {
     /// Starting point of selection (where mouse was pressed)
     pub anchor: SelectionPoint,
@@ -344,7 +636,7 @@ impl Selection {
 }
 
 /// A terminal image (from Sixel or other protocols)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerminalImage {
     /// Unique image ID
     pub id: u64,
@@ -369,7 +661,11 @@ pub struct TerminalImage {
 const COL_END_OF_ROW: usize = usize::MAX;
 
 /// Terminal screen state
-#[derive(Debug)]
+///
+/// Serializing a screen keeps its contents, modes and images; damage,
+/// pending responses and events, and a running command's start time are
+/// transient and not kept.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Screen {
     /// Active display grid
     grid: Grid,
@@ -383,10 +679,18 @@ pub struct Screen {
     pub cursor: Cursor,
     /// Saved cursor state (for save/restore)
     saved_cursor: Option<Cursor>,
+    /// Kitty keyboard protocol flags for the main screen
+    keyboard_main: KeyboardFlagStack,
+    /// Kitty keyboard protocol flags for the alternate screen
+    keyboard_alt: KeyboardFlagStack,
     /// Alternate saved cursor (for alternate screen)
     alt_saved_cursor: Option<Cursor>,
     /// Scroll region
     scroll_region: ScrollRegion,
+    /// Cursor position left on the right margin by the last character, where
+    /// the next one wraps to the left margin
+    #[serde(skip)]
+    margin_wrap: Option<(usize, usize)>,
     /// Current cell styling
     pub style: CellStyle,
     /// Terminal modes
@@ -395,19 +699,84 @@ pub struct Screen {
     pub title: String,
     /// Icon name
     pub icon_name: String,
+    /// Badge template from OSC 1337 SetBadgeFormat; see [`Screen::badge`]
+    pub badge_format: String,
+    /// User variables from OSC 1337 SetUserVar; change them through
+    /// [`Screen::set_user_var`] so the change is reported
+    pub user_vars: BTreeMap<String, String>,
+    /// Shell working directory reported by OSC 7 or OSC 1337 CurrentDir
+    /// (empty if unknown); change it through [`Screen::set_current_dir`]
+    pub current_dir: String,
+    /// Host named in the OSC 7 URI for `current_dir` (empty if none)
+    pub current_host: String,
     /// Whether content has changed since last render
+    #[serde(skip)]
     pub dirty: bool,
+    /// Rows and scrolls changed since the damage was last taken
+    #[serde(skip)]
+    damage: DamageTracker,
+    /// Cursor, and whether it was shown, when the damage was last taken
+    #[serde(skip)]
+    drawn_cursor: (Cursor, bool),
+    /// Selection when the damage was last taken
+    #[serde(skip)]
+    drawn_selection: Option<Selection>,
+    /// Viewport scroll offset when the damage was last taken
+    #[serde(skip)]
+    drawn_scroll_offset: usize,
     /// Current scroll offset (for viewing scrollback)
     pub scroll_offset: usize,
     /// Bell was triggered (should be cleared after notification)
+    #[serde(skip)]
     pub bell: bool,
     /// Tab stop positions (columns where tabs stop)
     tab_stops: Vec<bool>,
     /// Pending responses to send back to the PTY (for DSR etc)
+    #[serde(skip)]
     pending_responses: Vec<Vec<u8>>,
+    /// Names of user variables changed since last taken
+    #[serde(skip)]
+    pending_user_var_changes: Vec<String>,
+    /// When the running command started (OSC 133 C), until it finishes
+    #[serde(skip)]
+    command_started: Option<Instant>,
+    /// Commands finished (OSC 133 D) since last taken
+    #[serde(skip)]
+    pending_finished_commands: Vec<CommandFinished>,
+    /// An upload was requested (OSC 1337 RequestUpload) since last taken
+    #[serde(skip)]
+    upload_requested: bool,
+    /// tmux control mode output since last taken
+    #[serde(skip)]
+    pending_tmux_control: Vec<TmuxControl>,
+    /// Lines ended by a line feed since last taken, while capturing them
+    #[serde(skip)]
+    completed_lines: Option<Vec<String>>,
+    /// Backgrounds given to lines as a line feed ends them
+    #[serde(skip)]
+    line_highlights: Vec<LineHighlight>,
+    /// Dark or light theme of the frontend showing the screen, if it said
+    #[serde(skip)]
+    color_scheme: Option<ColorScheme>,
+    /// VT level selected with DECSCL
+    conformance_level: ConformanceLevel,
+    /// The working directory changed since last taken
+    #[serde(skip)]
+    cwd_changed: bool,
+    /// Mouse pointer shape asked for with OSC 22
+    #[serde(skip)]
+    pointer_shape: PointerShape,
+    /// The pointer shape changed since last taken
+    #[serde(skip)]
+    pointer_shape_changed: bool,
     /// Pending clipboard operations from OSC 52
+    #[serde(skip)]
     pending_clipboard_ops: Vec<ClipboardOperation>,
+    /// Pending desktop notifications (OSC 9, OSC 777)
+    #[serde(skip)]
+    pending_notifications: Vec<Notification>,
     /// Pending color queries (OSC 10-12)
+    #[serde(skip)]
     pending_color_queries: Vec<ColorQuery>,
     /// Current text selection (if any)
     pub selection: Option<Selection>,
@@ -415,7 +784,10 @@ pub struct Screen {
     images: HashMap<u64, TerminalImage>,
     /// Next image ID
     next_image_id: u64,
+    /// Kitty graphics images and placements
+    pub(crate) kitty: KittyGraphics,
     /// Pending file transfer operations (iTerm2 OSC 1337 with inline=0)
+    #[serde(skip)]
     pending_file_transfers: Vec<FileTransferOperation>,
     /// Next file transfer ID
     next_file_transfer_id: u64,
@@ -428,6 +800,15 @@ pub struct Screen {
     /// Total number of lines ever pushed to scrollback (monotonically increasing).
     /// Used to compute correct absolute line numbers for image pruning.
     scrollback_total_pushed: usize,
+    /// Scrollback lines that look like prompts or errors, numbered like
+    /// `scrollback_total_pushed` so they survive lines being dropped
+    line_marks: VecDeque<(usize, ScrollMarkKind)>,
+    /// Lines with search matches, numbered the same way
+    #[serde(skip)]
+    search_lines: Vec<usize>,
+    /// Lines scrolled up in any region so far, for throughput statistics
+    #[serde(skip)]
+    lines_scrolled: u64,
 }
 
 impl Screen {
@@ -440,42 +821,70 @@ impl Screen {
             alternate_scroll: true, // Alternate-screen wheel-to-arrows enabled by default
             ..Default::default()
         };
+        let conformance_level = config.conformance_level;
+        let cursor = Cursor {
+            style: config.cursor_style,
+            blink: config.cursor_blink,
+            ..Default::default()
+        };
 
         Self {
             grid: Grid::new(width, height),
             scrollback: VecDeque::with_capacity(config.scrollback_lines.min(1000)),
             alternate_grid: None,
+            keyboard_main: KeyboardFlagStack::default(),
+            keyboard_alt: KeyboardFlagStack::default(),
             config,
-            cursor: Cursor {
-                blink: true,
-                ..Default::default()
-            },
+            cursor,
             saved_cursor: None,
             alt_saved_cursor: None,
-            scroll_region: ScrollRegion {
-                top: 0,
-                bottom: height,
-            },
+            scroll_region: ScrollRegion::full(width, height),
+            margin_wrap: None,
             style: CellStyle::default(),
             modes,
             title: String::new(),
             icon_name: String::new(),
+            badge_format: String::new(),
+            user_vars: BTreeMap::new(),
+            current_dir: String::new(),
+            current_host: String::new(),
             dirty: true,
+            damage: DamageTracker::new(height),
+            drawn_cursor: (Cursor::default(), false),
+            drawn_selection: None,
+            drawn_scroll_offset: 0,
             scroll_offset: 0,
             bell: false,
             tab_stops: Self::default_tab_stops(width),
             pending_responses: Vec::new(),
+            pending_user_var_changes: Vec::new(),
+            command_started: None,
+            pending_finished_commands: Vec::new(),
+            upload_requested: false,
+            pending_tmux_control: Vec::new(),
+            completed_lines: None,
+            line_highlights: Vec::new(),
+            color_scheme: None,
+            conformance_level,
+            cwd_changed: false,
+            pointer_shape: PointerShape::Default,
+            pointer_shape_changed: false,
             pending_clipboard_ops: Vec::new(),
+            pending_notifications: Vec::new(),
             pending_color_queries: Vec::new(),
             selection: None,
             images: HashMap::new(),
             next_image_id: 0,
+            kitty: KittyGraphics::default(),
             pending_file_transfers: Vec::new(),
             next_file_transfer_id: 0,
             cell_height_hint: 16.0, // Default assumption
             cell_width_hint: 8.0,   // Default assumption
             drcs_fonts: HashMap::new(),
             scrollback_total_pushed: 0,
+            line_marks: VecDeque::new(),
+            search_lines: Vec::new(),
+            lines_scrolled: 0,
         }
     }
 
@@ -484,6 +893,474 @@ impl Screen {
         self.pending_responses.push(response);
     }
 
+    /// Set a user variable (OSC 1337 SetUserVar); an empty value unsets it
+    pub fn set_user_var(&mut self, name: &str, value: String) {
+        let changed = if value.is_empty() {
+            self.user_vars.remove(name).is_some()
+        } else if self.user_vars.get(name) == Some(&value) {
+            false
+        } else {
+            self.user_vars.insert(name.to_string(), value);
+            true
+        };
+        if changed {
+            self.note_user_var_change(name);
+            // The badge may show the variable
+            self.damage_all();
+        }
+    }
+
+    fn note_user_var_change(&mut self, name: &str) {
+        if !self.pending_user_var_changes.iter().any(|n| n == name) {
+            self.pending_user_var_changes.push(name.to_string());
+        }
+    }
+
+    /// Take the names of user variables changed since the last call
+    pub fn take_user_var_changes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.pending_user_var_changes)
+    }
+
+    /// Set the shell working directory (OSC 7, OSC 1337 CurrentDir), on
+    /// `host` if the report named one
+    pub fn set_current_dir(&mut self, host: &str, dir: &str) {
+        if self.current_dir != dir || self.current_host != host {
+            self.current_dir = dir.to_string();
+            self.current_host = host.to_string();
+            self.cwd_changed = true;
+        }
+    }
+
+    /// Whether the working directory changed since the last call
+    pub fn take_cwd_change(&mut self) -> bool {
+        std::mem::take(&mut self.cwd_changed)
+    }
+
+    /// Mouse pointer shape the application asked for (OSC 22)
+    pub fn pointer_shape(&self) -> PointerShape {
+        self.pointer_shape
+    }
+
+    /// Set the mouse pointer shape (OSC 22)
+    pub fn set_pointer_shape(&mut self, shape: PointerShape) {
+        if self.pointer_shape != shape {
+            self.pointer_shape = shape;
+            self.pointer_shape_changed = true;
+        }
+    }
+
+    /// The new pointer shape, if it changed since the last call
+    pub fn take_pointer_shape_change(&mut self) -> Option<PointerShape> {
+        std::mem::take(&mut self.pointer_shape_changed).then_some(self.pointer_shape)
+    }
+
+    /// The working directory, if it is known and on this machine, so new
+    /// tabs can start there. Directories reported from another host (e.g.
+    /// over SSH) are not returned.
+    pub fn local_current_dir(&self) -> Option<&str> {
+        if self.current_dir.is_empty() {
+            return None;
+        }
+        let host = self.current_host.as_str();
+        let local = host.is_empty()
+            || host.eq_ignore_ascii_case("localhost")
+            || local_hostname().is_some_and(|name| name.eq_ignore_ascii_case(host));
+        local.then_some(self.current_dir.as_str())
+    }
+
+    /// Mark the start of a command's output (OSC 133 C)
+    pub fn command_start(&mut self) {
+        self.command_started = Some(Instant::now());
+    }
+
+    /// Mark the end of a command (OSC 133 D), returning how long it ran
+    ///
+    /// Ignored without a preceding start mark, which is what shells send
+    /// before their first prompt.
+    pub fn command_end(&mut self, exit_code: Option<i32>) -> Option<Duration> {
+        let duration = self.command_started.take()?.elapsed();
+        self.pending_finished_commands.push(CommandFinished {
+            duration,
+            exit_code,
+        });
+        Some(duration)
+    }
+
+    /// Take the commands finished since the last call
+    pub fn take_finished_commands(&mut self) -> Vec<CommandFinished> {
+        std::mem::take(&mut self.pending_finished_commands)
+    }
+
+    /// Record an OSC 133 mark on the cursor's row; output start and command
+    /// end marks also time the command
+    pub fn semantic_mark(&mut self, mut mark: SemanticMark) {
+        match mark {
+            SemanticMark::Output => self.command_start(),
+            SemanticMark::CommandEnd {
+                exit_code,
+                ref mut duration,
+            } => *duration = self.command_end(exit_code),
+            SemanticMark::Prompt | SemanticMark::CommandInput => {}
+        }
+        let row = self.cursor.row;
+        if let Some(row) = self.grid.row_mut(row) {
+            row.add_mark(mark);
+        }
+    }
+
+    /// Prompts found from OSC 133 marks in the scrollback and on the screen,
+    /// oldest first
+    pub fn prompt_marks(&self) -> Vec<PromptMark> {
+        let mut prompts: Vec<PromptMark> = Vec::new();
+        for (line, row) in self.scrollback.iter().chain(self.grid.iter()).enumerate() {
+            for &mark in row.marks() {
+                if mark == SemanticMark::Prompt {
+                    prompts.push(PromptMark {
+                        line,
+                        command_line: None,
+                        output_line: None,
+                        end_line: None,
+                        exit_code: None,
+                        duration: None,
+                    });
+                    continue;
+                }
+                // Shells send a finish mark before their first prompt
+                let Some(prompt) = prompts.last_mut() else {
+                    continue;
+                };
+                match mark {
+                    SemanticMark::CommandInput if prompt.command_line.is_none() => {
+                        prompt.command_line = Some(line);
+                    }
+                    SemanticMark::Output if prompt.output_line.is_none() => {
+                        prompt.output_line = Some(line);
+                    }
+                    SemanticMark::CommandEnd {
+                        exit_code,
+                        duration,
+                    } if prompt.end_line.is_none() => {
+                        prompt.end_line = Some(line);
+                        prompt.exit_code = exit_code;
+                        prompt.duration = duration;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        prompts
+    }
+
+    /// Lines of output of the command run from the last prompt at or above
+    /// absolute `line`: from its output mark up to its finish mark, or to the
+    /// next prompt or the cursor if it has none. `None` if that command
+    /// produced no output mark.
+    pub fn command_output_range(&self, line: usize) -> Option<Range<usize>> {
+        let prompts = self.prompt_marks();
+        let index = prompts.iter().rposition(|p| p.line <= line)?;
+        let prompt = &prompts[index];
+        let start = prompt.output_line?;
+        let end = prompt
+            .end_line
+            .or_else(|| prompts.get(index + 1).map(|next| next.line))
+            .unwrap_or(self.scrollback.len() + self.cursor.row + 1);
+        Some(start..end.max(start))
+    }
+
+    /// Absolute lines that start a prompt, oldest first
+    ///
+    /// These come from OSC 133 marks. Without any, lines that look like a
+    /// shell prompt are used instead: text from the first column with a
+    /// `$`, `#`, `%`, `>` or `❯` early on the line, followed by a space or
+    /// ending it.
+    pub fn prompt_lines(&self) -> Vec<usize> {
+        let marked: Vec<usize> = self.prompt_marks().iter().map(|p| p.line).collect();
+        if !marked.is_empty() {
+            return marked;
+        }
+        let mut text = String::new();
+        let mut continued = false;
+        let mut lines = Vec::new();
+        for (line, row) in self.scrollback.iter().chain(self.grid.iter()).enumerate() {
+            if !continued {
+                row.write_text_to(&mut text);
+                if looks_like_prompt(&text) {
+                    lines.push(line);
+                }
+            }
+            continued = row.wrapped;
+        }
+        lines
+    }
+
+    /// Lines of output of the last command that produced any, for
+    /// selecting it
+    ///
+    /// With OSC 133 marks this is [`Self::command_output_range`] of the
+    /// last prompt with an output mark; otherwise the lines between the last
+    /// two prompt-like lines.
+    pub fn last_command_output(&self) -> Option<Range<usize>> {
+        let prompts = self.prompt_marks();
+        if !prompts.is_empty() {
+            let prompt = prompts.iter().rev().find(|p| p.output_line.is_some())?;
+            return self
+                .command_output_range(prompt.line)
+                .filter(|range| !range.is_empty());
+        }
+        match self.prompt_lines()[..] {
+            [.., previous, last] if last > previous + 1 => Some(previous + 1..last),
+            _ => None,
+        }
+    }
+
+    /// Select whole absolute `lines`
+    pub fn select_lines(&mut self, lines: Range<usize>) {
+        if lines.is_empty() {
+            return;
+        }
+        self.start_selection(lines.start, 0, SelectionMode::Line);
+        self.extend_selection(lines.end - 1, COL_END_OF_ROW);
+    }
+
+    /// Finished commands on the visible screen, from their prompt to their
+    /// finish mark, top to bottom; none on the alternate screen
+    pub fn command_blocks(&self) -> Vec<CommandBlock> {
+        if self.modes.alternate_screen {
+            return Vec::new();
+        }
+        let first = self.visible_row_to_absolute_line(0);
+        let rows = first..first + self.height();
+        self.prompt_marks()
+            .into_iter()
+            .filter_map(|prompt| {
+                // The finish mark is normally on the next prompt's row
+                let end = prompt.end_line?.max(prompt.line + 1);
+                let top = prompt.line.max(rows.start);
+                let bottom = end.min(rows.end);
+                (top < bottom).then(|| CommandBlock {
+                    top: top - first,
+                    bottom: bottom - first,
+                    exit_code: prompt.exit_code,
+                    duration: prompt.duration,
+                })
+            })
+            .collect()
+    }
+
+    /// Prompts, errors and search matches to mark along the scrollbar,
+    /// ordered by line; none on the alternate screen
+    ///
+    /// Prompts come from OSC 133 marks, or without any from lines that look
+    /// like one (see [`Self::prompt_lines`]). Errors are lines with a word
+    /// such as "error", "failed" or "panicked", in any case. Search matches
+    /// are the lines given to [`Self::set_search_lines`].
+    pub fn marks(&self) -> Vec<ScrollMark> {
+        if self.modes.alternate_screen {
+            return Vec::new();
+        }
+        let first = self.first_line_number();
+        let marked_prompts = self.prompt_marks();
+        let mut marks: Vec<ScrollMark> = marked_prompts
+            .iter()
+            .map(|p| ScrollMark {
+                line: p.line,
+                kind: ScrollMarkKind::Prompt,
+            })
+            .collect();
+        let heuristic = marked_prompts.is_empty();
+        let mut push = |line, kind| {
+            if heuristic || kind != ScrollMarkKind::Prompt {
+                marks.push(ScrollMark { line, kind });
+            }
+        };
+
+        // Scrollback lines were looked at as they scrolled off the screen
+        for &(n, kind) in &self.line_marks {
+            if let Some(line) = n.checked_sub(first) {
+                push(line, kind);
+            }
+        }
+        let mut text = String::new();
+        let mut continued = self.scrollback.back().is_some_and(|r| r.wrapped);
+        for (row, line) in self.grid.iter().zip(self.scrollback.len()..) {
+            row.write_text_to(&mut text);
+            if let Some(kind) = line_mark_kind(&text, continued) {
+                push(line, kind);
+            }
+            continued = row.wrapped;
+        }
+
+        let end = self.scrollback.len() + self.height();
+        marks.extend(
+            self.search_lines
+                .iter()
+                .filter_map(|&n| n.checked_sub(first))
+                .filter(|&line| line < end)
+                .map(|line| ScrollMark {
+                    line,
+                    kind: ScrollMarkKind::SearchHit,
+                }),
+        );
+        marks.sort_by_key(|mark| (mark.line, mark.kind));
+        marks.dedup();
+        marks
+    }
+
+    /// Mark absolute `lines` as having search matches, replacing the lines
+    /// marked before; the marks follow their lines as output scrolls
+    pub fn set_search_lines(&mut self, lines: impl IntoIterator<Item = usize>) {
+        let first = self.first_line_number();
+        self.search_lines = lines.into_iter().map(|line| line + first).collect();
+        self.search_lines.dedup();
+        self.dirty = true;
+    }
+
+    /// Number among all lines ever pushed to the scrollback of its oldest
+    /// line, or of the top row when it is empty
+    fn first_line_number(&self) -> usize {
+        self.scrollback_total_pushed
+            .saturating_sub(self.scrollback.len())
+    }
+
+    /// Look for prompts and errors in the whole scrollback again
+    fn scan_line_marks(&mut self) {
+        let first = self.first_line_number();
+        let mut text = String::new();
+        let mut continued = false;
+        self.line_marks.clear();
+        for (row, n) in self.scrollback.iter().zip(first..) {
+            row.write_text_to(&mut text);
+            if let Some(kind) = line_mark_kind(&text, continued) {
+                self.line_marks.push_back((n, kind));
+            }
+            continued = row.wrapped;
+        }
+    }
+
+    /// Note that the application asked for files (OSC 1337 RequestUpload)
+    pub fn request_upload(&mut self) {
+        self.upload_requested = true;
+    }
+
+    /// Whether an upload was requested since the last call
+    pub fn take_upload_request(&mut self) -> bool {
+        std::mem::take(&mut self.upload_requested)
+    }
+
+    /// Queue output of tmux control mode
+    pub fn push_tmux_control(&mut self, control: TmuxControl) {
+        self.pending_tmux_control.push(control);
+    }
+
+    /// Take the tmux control mode output since the last call
+    pub fn take_tmux_control(&mut self) -> Vec<TmuxControl> {
+        std::mem::take(&mut self.pending_tmux_control)
+    }
+
+    /// Collect the text of each line a line feed ends on the primary
+    /// screen, for [`Self::take_completed_lines`]
+    pub fn set_line_capture(&mut self, enabled: bool) {
+        self.completed_lines = enabled.then(Vec::new);
+    }
+
+    /// Give lines matching a highlight its background as a line feed ends
+    /// them on the primary screen; the first match wins
+    pub fn set_line_highlights(&mut self, highlights: Vec<LineHighlight>) {
+        self.line_highlights = highlights;
+    }
+
+    /// Color scheme reported to programs; `None` (the default, as in the
+    /// daemon) leaves DSR 996 queries unanswered
+    pub fn color_scheme(&self) -> Option<ColorScheme> {
+        self.color_scheme
+    }
+
+    /// Set the color scheme, returning whether it changed
+    pub fn set_color_scheme(&mut self, scheme: ColorScheme) -> bool {
+        self.color_scheme.replace(scheme) != Some(scheme)
+    }
+
+    /// VT level the screen reports itself as
+    pub fn conformance_level(&self) -> ConformanceLevel {
+        self.conformance_level
+    }
+
+    /// Select the VT level (DECSCL); a full reset restores the configured one
+    pub fn set_conformance_level(&mut self, level: ConformanceLevel) {
+        self.conformance_level = level;
+    }
+
+    /// Give the cursor the configured shape and blink again (DECSCUSR 0)
+    pub fn restore_cursor_shape(&mut self) {
+        self.cursor.style = self.config.cursor_style;
+        self.cursor.blink = self.config.cursor_blink;
+        self.dirty = true;
+    }
+
+    /// Change the configured cursor shape; the cursor takes it right away
+    /// unless a program has picked a different one
+    pub fn set_cursor_shape(&mut self, style: CursorStyle, blink: bool) {
+        let configured = (self.config.cursor_style, self.config.cursor_blink);
+        self.config.cursor_style = style;
+        self.config.cursor_blink = blink;
+        if (self.cursor.style, self.cursor.blink) == configured {
+            self.restore_cursor_shape();
+        }
+    }
+
+    /// Take the lines completed since the last call; soft-wrapped rows are
+    /// joined into one line
+    pub fn take_completed_lines(&mut self) -> Vec<String> {
+        self.completed_lines
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Record the logical line the cursor is on as completed, highlighting
+    /// it if it matches
+    fn capture_cursor_line(&mut self) {
+        let end = self.scrollback.len() + self.cursor.row;
+        let mut start = end;
+        while start > 0
+            && self
+                .get_row_by_absolute_line(start)
+                .is_some_and(|row| row.wrapped)
+        {
+            start -= 1;
+        }
+        let mut line = String::new();
+        let mut row_text = String::new();
+        for abs in start..=end {
+            if let Some(row) = self.get_row_by_absolute_line(abs) {
+                row.write_text_to(&mut row_text);
+                line.push_str(&row_text);
+            }
+        }
+        let highlight = self
+            .line_highlights
+            .iter()
+            .find(|highlight| highlight.regex.is_match(&line))
+            .map(|highlight| highlight.color);
+        if let Some(color) = highlight {
+            let scrollback_len = self.scrollback.len();
+            for abs in start..=end {
+                let row = if abs < scrollback_len {
+                    self.scrollback.get_mut(abs)
+                } else {
+                    self.damage.row(abs - scrollback_len);
+                    self.grid.row_mut(abs - scrollback_len)
+                };
+                for cell in row.into_iter().flat_map(Row::iter_mut) {
+                    cell.bg = Color::Rgb(color);
+                }
+            }
+        }
+        if let Some(lines) = self.completed_lines.as_mut() {
+            lines.push(line);
+        }
+    }
+
     /// Queue a clipboard operation (from OSC 52)
     pub fn queue_clipboard_op(&mut self, op: ClipboardOperation) {
         self.pending_clipboard_ops.push(op);
@@ -499,6 +1376,16 @@ impl Screen {
         !self.pending_clipboard_ops.is_empty()
     }
 
+    /// Queue a desktop notification (from OSC 9 or OSC 777)
+    pub fn queue_notification(&mut self, notification: Notification) {
+        self.pending_notifications.push(notification);
+    }
+
+    /// Take all pending notifications (drains the queue)
+    pub fn take_notifications(&mut self) -> Vec<Notification> {
+        std::mem::take(&mut self.pending_notifications)
+    }
+
     /// Queue a color query (from OSC 10-12)
     pub fn queue_color_query(&mut self, osc_code: u8) {
         let query = match osc_code {
@@ -638,10 +1525,68 @@ impl Screen {
     }
 
     /// Get a mutable reference to the active grid
+    ///
+    /// Changes made through it are not tracked row by row, so the whole
+    /// screen is damaged.
     pub fn grid_mut(&mut self) -> &mut Grid {
+        self.damage.all();
         &mut self.grid
     }
 
+    /// Damage the whole screen, e.g. after a change to how every row is drawn
+    pub fn damage_all(&mut self) {
+        self.damage.all();
+        self.dirty = true;
+    }
+
+    /// Take the rows, scrolls and cursor changes since the last call; see
+    /// [`crate::damage`]
+    ///
+    /// While the scrollback is being viewed, or when the viewport moved, the
+    /// damage is full since grid rows are not where they are drawn.
+    pub fn take_damage(&mut self) -> Damage {
+        let cursor = (self.cursor.clone(), self.modes.show_cursor);
+        let drawn = std::mem::replace(&mut self.drawn_cursor, cursor.clone());
+        let cursor_move = (drawn != cursor).then_some(CursorMove {
+            from: (drawn.0.row, drawn.0.col),
+            to: (cursor.0.row, cursor.0.col),
+        });
+
+        if self.selection != self.drawn_selection {
+            let drawn = std::mem::replace(&mut self.drawn_selection, self.selection.clone());
+            for selection in drawn.iter().chain(self.selection.iter()) {
+                let rows = self.selection_rows(selection);
+                self.damage.rows(rows);
+            }
+        }
+
+        let viewport_moved = std::mem::replace(&mut self.drawn_scroll_offset, self.scroll_offset)
+            != self.scroll_offset;
+        if viewport_moved
+            || (self.scroll_offset > 0 && (self.damage.is_damaged() || cursor_move.is_some()))
+        {
+            self.damage.all();
+        }
+        self.damage.take(self.height(), cursor_move)
+    }
+
+    /// Grid rows covered by a selection
+    fn selection_rows(&self, selection: &Selection) -> Range<usize> {
+        let (start, end) = selection.ordered();
+        let first = self.scrollback.len();
+        start.line.saturating_sub(first)..(end.line + 1).saturating_sub(first)
+    }
+
+    /// SGR parameters that recreate the current style, e.g. `0;1;31`, as
+    /// DECRQSS reports them
+    pub fn sgr_params(&self) -> String {
+        let mut cell = Cell::default();
+        self.style.apply_to(&mut cell);
+        let mut out = String::new();
+        push_sgr_params(&mut out, &cell);
+        out
+    }
+
     /// Get scroll region
     pub fn scroll_region(&self) -> &ScrollRegion {
         &self.scroll_region
@@ -651,15 +1596,39 @@ impl Screen {
     pub fn set_scroll_region(&mut self, top: usize, bottom: usize) {
         let top = top.min(self.height().saturating_sub(1));
         let bottom = bottom.min(self.height()).max(top + 1);
-        self.scroll_region = ScrollRegion { top, bottom };
+        self.scroll_region.top = top;
+        self.scroll_region.bottom = bottom;
+    }
+
+    /// Set the left and right margins (DECSLRM) to columns `left..right`.
+    /// Ignored unless left/right margin mode is set, or if the margins
+    /// would be less than two columns apart.
+    pub fn set_left_right_margins(&mut self, left: usize, right: usize) {
+        if !self.modes.left_right_margin_mode {
+            return;
+        }
+        let right = right.min(self.width());
+        if left + 1 >= right {
+            return;
+        }
+        self.scroll_region.left = left;
+        self.scroll_region.right = right;
+    }
+
+    /// Reset the left and right margins to the full width
+    pub fn reset_left_right_margins(&mut self) {
+        self.scroll_region.left = 0;
+        self.scroll_region.right = self.width();
     }
 
-    /// Reset scroll region to full screen
+    /// Reset scroll region (and left/right margins) to full screen
     pub fn reset_scroll_region(&mut self) {
-        self.scroll_region = ScrollRegion {
-            top: 0,
-            bottom: self.height(),
-        };
+        self.scroll_region = ScrollRegion::full(self.width(), self.height());
+    }
+
+    /// Whether left/right margins narrower than the screen are in effect
+    fn has_left_right_margins(&self) -> bool {
+        self.scroll_region.left > 0 || self.scroll_region.right < self.width()
     }
 
     /// Get scrollback buffer
@@ -692,6 +1661,11 @@ impl Screen {
         &self.tab_stops
     }
 
+    /// Lines scrolled up in any region since the screen was created
+    pub fn lines_scrolled(&self) -> u64 {
+        self.lines_scrolled
+    }
+
     /// Total lines (scrollback + visible)
     pub fn total_lines(&self) -> usize {
         self.scrollback.len() + self.height()
@@ -706,13 +1680,15 @@ impl Screen {
         // Save old dimensions BEFORE resizing grid, for scroll region adjustment
         let old_height = self.height();
         let old_scroll_bottom = self.scroll_region.bottom;
+        let old_scroll_right = self.scroll_region.right;
         let old_width = self.width();
 
-        self.grid.resize(width, height);
-
-        if let Some(ref mut alt) = self.alternate_grid {
-            alt.resize(width, height);
+        // The primary screen and its scrollback re-wrap to the new size; the
+        // alternate screen belongs to a full-screen app that redraws itself
+        if self.modes.alternate_screen {
+            self.grid.resize(width, height);
         }
+        self.reflow_primary(width, height);
 
         // Update scroll region
         // If scroll region was at full screen height, extend it to new height
@@ -722,6 +1698,15 @@ impl Screen {
             self.scroll_region.bottom = self.scroll_region.bottom.min(height);
         }
         self.scroll_region.top = self.scroll_region.top.min(height.saturating_sub(1));
+        if old_scroll_right == old_width {
+            self.scroll_region.right = width;
+        } else {
+            self.scroll_region.right = self.scroll_region.right.min(width);
+        }
+        self.scroll_region.left = self
+            .scroll_region
+            .left
+            .min(self.scroll_region.right.saturating_sub(1));
 
         // Clamp cursor position
         self.cursor.col = self.cursor.col.min(width.saturating_sub(1));
@@ -734,9 +1719,105 @@ impl Screen {
             self.tab_stops[i] = i % 8 == 0;
         }
 
+        self.damage = DamageTracker::new(height);
         self.dirty = true;
     }
 
+    /// Re-wrap the primary grid and scrollback to `width` x `height`.
+    ///
+    /// Soft-wrapped lines are joined and split again at the new width, the
+    /// cursor follows its character, and rows that no longer fit on screen
+    /// move into the scrollback (or come back from it when growing). The
+    /// selection is cleared since its coordinates no longer apply.
+    fn reflow_primary(&mut self, width: usize, height: usize) {
+        let alternate = self.modes.alternate_screen;
+        let primary = match self.alternate_grid.take() {
+            Some(grid) if alternate => grid,
+            other => {
+                self.alternate_grid = other;
+                std::mem::replace(&mut self.grid, Grid::new(0, 0))
+            }
+        };
+        let cursor = if alternate {
+            self.alt_saved_cursor.as_ref().map(|c| (c.row, c.col))
+        } else {
+            Some((self.cursor.row, self.cursor.col))
+        };
+
+        // Blank rows below both the content and the cursor are not kept
+        let old_scrollback_len = self.scrollback.len();
+        let mut rows: Vec<Row> = self.scrollback.drain(..).collect();
+        let mut grid_rows = primary.into_rows();
+        let last_content = grid_rows.iter().rposition(|r| !r.is_all_empty());
+        let keep = match (last_content, cursor) {
+            (Some(last), Some((row, _))) => last.max(row) + 1,
+            (Some(last), None) => last + 1,
+            (None, Some((row, _))) => row + 1,
+            (None, None) => 0,
+        };
+        grid_rows.truncate(keep);
+        let old_total = old_scrollback_len + grid_rows.len();
+        rows.extend(grid_rows);
+
+        let reflowed = reflow_rows(
+            rows,
+            width,
+            cursor.map(|(row, col)| (old_scrollback_len + row, col)),
+        );
+        let mut rows = reflowed.rows;
+        let reflowed_total = rows.len();
+
+        // Keep the cursor on screen, preferring to show the latest rows
+        let cursor_line = reflowed.cursor.map_or(0, |(line, _)| line);
+        let top = rows.len().saturating_sub(height).min(cursor_line);
+        let visible = rows.split_off(top);
+        let mut scrollback: VecDeque<Row> = rows.into();
+        let dropped = scrollback
+            .len()
+            .saturating_sub(self.config.scrollback_lines);
+        scrollback.drain(..dropped);
+        let new_scrollback_len = scrollback.len();
+        self.scrollback = scrollback;
+
+        let grid = Grid::from_rows(visible, width, height);
+        if alternate {
+            self.alternate_grid = Some(grid);
+        } else {
+            self.grid = grid;
+        }
+
+        if let Some((line, col)) = reflowed.cursor {
+            let row = (line - top).min(height.saturating_sub(1));
+            let col = col.min(width.saturating_sub(1));
+            let target = if alternate {
+                self.alt_saved_cursor.as_mut()
+            } else {
+                Some(&mut self.cursor)
+            };
+            if let Some(target) = target {
+                target.row = row;
+                target.col = col;
+            }
+        }
+
+        // Images are anchored to absolute lines
+        let row_map = reflowed.row_map;
+        for img in self.images.values_mut() {
+            let line = match row_map.get(img.line) {
+                Some(&line) => line,
+                // Below the kept rows, which were blank
+                None => img.line - old_total + reflowed_total,
+            };
+            img.line = line.saturating_sub(dropped);
+        }
+        self.scrollback_total_pushed =
+            (self.scrollback_total_pushed + new_scrollback_len).saturating_sub(old_scrollback_len);
+        self.scan_line_marks();
+        self.search_lines.clear();
+        self.scroll_offset = self.scroll_offset.min(new_scrollback_len);
+        self.selection = None;
+    }
+
     /// Get a cell at the given position
     pub fn get_cell(&self, row: usize, col: usize) -> Option<&Cell> {
         self.grid.get(row, col)
@@ -753,24 +1834,34 @@ impl Screen {
     }
 
     /// Put a character at the current cursor position
+    ///
+    /// A character that continues the grapheme cluster left of the cursor
+    /// (a combining accent, a variation selector, the rest of a ZWJ emoji
+    /// sequence) is added to that cell instead of taking a new one.
     pub fn put_char(&mut self, c: char) {
+        if self.extend_cluster(c) {
+            return;
+        }
         let width = unicode_width::UnicodeWidthChar::width(c).unwrap_or(1);
 
-        // Handle auto-wrap
-        if self.cursor.col >= self.width() {
+        // Handle auto-wrap, at the right margin if the last character was
+        // written up to it
+        let at_margin = self.margin_wrap.take() == Some((self.cursor.row, self.cursor.col));
+        if at_margin || self.cursor.col >= self.width() {
             if self.modes.auto_wrap {
                 self.carriage_return();
-                self.line_feed();
+                self.advance_line();
                 if let Some(row) = self.grid.row_mut(self.cursor.row) {
                     row.wrapped = true;
                 }
-            } else {
+            } else if !at_margin {
                 self.cursor.col = self.width() - 1;
             }
         }
+        let line_end = self.line_end();
 
         // Insert mode: shift characters right
-        if self.modes.insert_mode && self.cursor.col < self.width() {
+        if self.modes.insert_mode && self.cursor.col < line_end {
             self.insert_cells(width);
         }
 
@@ -779,7 +1870,7 @@ impl Screen {
 
         // Write the character
         if let Some(cell) = self.grid.get_mut(self.cursor.row, self.cursor.col) {
-            cell.c = c;
+            cell.set_char(c);
             self.style.apply_to(cell);
 
             if width > 1 {
@@ -790,23 +1881,101 @@ impl Screen {
         // Handle wide characters (write spacer in next cell)
         if width > 1 && self.cursor.col + 1 < self.width() {
             if let Some(cell) = self.grid.get_mut(self.cursor.row, self.cursor.col + 1) {
-                cell.c = ' ';
+                cell.set_char(' ');
                 cell.attrs = crate::cell::CellAttrs::WIDE_SPACER;
             }
         }
 
         // Advance cursor
+        self.damage.row(self.cursor.row);
+        self.advance_cursor(width, line_end);
+        self.dirty = true;
+    }
+
+    /// Move the cursor right after writing `width` cells, stopping on the
+    /// right margin with a wrap pending
+    fn advance_cursor(&mut self, width: usize, line_end: usize) {
         self.cursor.col += width;
+        if self.cursor.col >= line_end && line_end < self.width() {
+            self.cursor.col = line_end - 1;
+            self.margin_wrap = Some((self.cursor.row, self.cursor.col));
+        }
+    }
+
+    /// Add `c` to the grapheme cluster left of the cursor if it continues it
+    ///
+    /// When the cluster becomes wide (an emoji presentation selector after a
+    /// narrow symbol) and the next cell is free, the cell is widened and the
+    /// cursor moves past it.
+    fn extend_cluster(&mut self, c: char) -> bool {
+        let row = self.cursor.row;
+        // With a wrap pending at the right margin, the cluster is under the
+        // cursor
+        let end = if self.margin_wrap == Some((row, self.cursor.col)) {
+            self.cursor.col + 1
+        } else {
+            self.cursor.col.min(self.width())
+        };
+        let Some(mut col) = end.checked_sub(1) else {
+            return false;
+        };
+        if col > 0
+            && self
+                .grid
+                .get(row, col)
+                .is_some_and(|cell| cell.is_wide_spacer())
+        {
+            col -= 1;
+        }
+        let line_end = self.line_end();
+        let Some(cell) = self.grid.get_mut(row, col) else {
+            return false;
+        };
+        if !crate::grapheme::continues_cluster(cell.c, cell.combining.as_str(), c) {
+            return false;
+        }
+        cell.combining.push(c);
+
+        let widened =
+            !cell.is_wide() && crate::grapheme::cluster_width(cell.c, cell.combining.as_str()) > 1;
+        if widened && end == col + 1 && col + 2 <= line_end {
+            cell.attrs.insert(crate::cell::CellAttrs::WIDE);
+            if let Some(spacer) = self.grid.get_mut(row, col + 1) {
+                spacer.set_char(' ');
+                spacer.attrs = crate::cell::CellAttrs::WIDE_SPACER;
+            }
+            self.advance_cursor(1, line_end);
+        }
+        self.damage.row(row);
         self.dirty = true;
+        true
     }
 
-    /// Insert blank cells at cursor, shifting existing cells right
+    /// Column where the cursor's line ends: the right margin while the cursor
+    /// is between the margins, else the screen's edge
+    fn line_end(&self) -> usize {
+        if self.scroll_region.contains_col(self.cursor.col) {
+            self.scroll_region.right
+        } else {
+            self.width()
+        }
+    }
+
+    /// Insert blank cells at cursor, shifting existing cells right up to the
+    /// end of the line
+    ///
+    /// A wide character split by the cursor, or pushed half past the end of
+    /// the line, is erased rather than left without its other half.
     fn insert_cells(&mut self, count: usize) {
         let cursor_row = self.cursor.row;
         let cursor_col = self.cursor.col;
-        let width = self.width();
+        let width = self.line_end();
 
         if let Some(row) = self.grid.row_mut(cursor_row) {
+            if cursor_col > 0 && cursor_col < width && row[cursor_col].is_wide_spacer() {
+                row[cursor_col - 1].reset();
+                row[cursor_col].reset();
+            }
             for i in (cursor_col + count..width).rev() {
                 let src_col = i - count;
                 let src_cell = row[src_col].clone();
@@ -817,42 +1986,134 @@ impl Screen {
                     row[i].reset();
                 }
             }
+            if width > 0 && row[width - 1].is_wide() {
+                row[width - 1].reset();
+            }
+        }
+    }
+
+    /// Move the cursor one column left (BS)
+    ///
+    /// With a wrap pending the cursor is shown in the last column, so it
+    /// lands one column left of that, as in xterm.
+    pub fn backspace(&mut self) {
+        self.margin_wrap = None;
+        if self.cursor.col >= self.width() {
+            self.cursor.col = self.cursor.col.saturating_sub(2);
+        } else if self.cursor.col > 0 {
+            self.cursor.col -= 1;
+        } else if self.modes.reverse_wraparound && self.modes.auto_wrap && self.cursor.row > 0 {
+            self.cursor.row -= 1;
+            self.cursor.col = self.width() - 1;
         }
     }
 
-    /// Move cursor to start of line
+    /// Move cursor to start of line: the left margin, or column 0 if the
+    /// cursor is left of it
     pub fn carriage_return(&mut self) {
-        self.cursor.col = 0;
+        let left = self.scroll_region.left;
+        self.cursor.col = if self.cursor.col >= left || self.modes.origin_mode {
+            left
+        } else {
+            0
+        };
     }
 
-    /// Move cursor down, scrolling if needed
+    /// Move cursor down, scrolling at the bottom margin
+    ///
+    /// Outside the left/right margins the region doesn't scroll and the
+    /// cursor stays on the margin.
     pub fn line_feed(&mut self) {
-        if self.cursor.row + 1 >= self.scroll_region.bottom {
-            self.scroll_up(1);
-        } else {
+        let capture = self.completed_lines.is_some() || !self.line_highlights.is_empty();
+        if capture && !self.modes.alternate_screen {
+            self.capture_cursor_line();
+        }
+        self.advance_line();
+    }
+
+    /// The cursor movement of [`Self::line_feed`], which auto-wrap shares
+    fn advance_line(&mut self) {
+        if self.cursor.row + 1 == self.scroll_region.bottom {
+            if self.cursor_in_margins() {
+                self.scroll_up(1);
+            }
+        } else if self.cursor.row + 1 < self.height() {
             self.cursor.row += 1;
         }
         self.dirty = true;
     }
 
+    /// Whether the cursor is between the left/right margins (a pending wrap
+    /// counts as the last column)
+    fn cursor_in_margins(&self) -> bool {
+        let col = self.cursor.col.min(self.width().saturating_sub(1));
+        self.scroll_region.contains_col(col)
+    }
+
+    /// Move cursor up, scrolling down at the top margin (RI)
+    pub fn reverse_index(&mut self) {
+        if self.cursor.row == self.scroll_region.top {
+            if self.cursor_in_margins() {
+                self.scroll_down(1);
+            }
+        } else if self.cursor.row > 0 {
+            self.cursor.row -= 1;
+        }
+        self.dirty = true;
+    }
+
     /// Scroll up within scroll region
+    ///
+    /// With left/right margins only the columns between them move, and
+    /// nothing goes to the scrollback.
     pub fn scroll_up(&mut self, count: usize) {
+        let height = self.scroll_region.bottom - self.scroll_region.top;
+        self.lines_scrolled += count.min(height) as u64;
+        if self.has_left_right_margins() {
+            let ScrollRegion {
+                top,
+                bottom,
+                left,
+                right,
+            } = self.scroll_region;
+            self.clear_selection_if_rows_selected(top, bottom);
+            self.grid.scroll_rect_up(count, top, bottom, left, right);
+            self.damage.rows(top..bottom);
+            self.dirty = true;
+            return;
+        }
         let scrolled =
             self.grid
                 .scroll_up(count, self.scroll_region.top, self.scroll_region.bottom);
+        self.damage.scroll(
+            self.scroll_region.top,
+            self.scroll_region.bottom,
+            count as isize,
+        );
 
         // Add to scrollback if not in alternate screen and scrolling from top
         if !self.modes.alternate_screen && self.scroll_region.top == 0 {
             let lines_added = scrolled.len();
             let mut lines_removed = 0;
+            let mut text = String::new();
             for row in scrolled {
                 if self.scrollback.len() >= self.config.scrollback_lines {
                     self.scrollback.pop_front();
                     lines_removed += 1;
                 }
+                let continued = self.scrollback.back().is_some_and(|r| r.wrapped);
+                row.write_text_to(&mut text);
+                if let Some(kind) = line_mark_kind(&text, continued) {
+                    self.line_marks
+                        .push_back((self.scrollback_total_pushed, kind));
+                }
                 self.scrollback.push_back(row);
                 self.scrollback_total_pushed += 1;
             }
+            let first = self.first_line_number();
+            while self.line_marks.front().is_some_and(|&(n, _)| n < first) {
+                self.line_marks.pop_front();
+            }
 
             // If user is viewing scrollback (not at bottom), adjust scroll_offset
             // to keep the same content visible. Adding lines pushes content "up"
@@ -895,34 +2156,102 @@ impl Screen {
 
     /// Scroll down within scroll region
     pub fn scroll_down(&mut self, count: usize) {
+        if self.has_left_right_margins() {
+            let ScrollRegion {
+                top,
+                bottom,
+                left,
+                right,
+            } = self.scroll_region;
+            self.clear_selection_if_rows_selected(top, bottom);
+            self.grid.scroll_rect_down(count, top, bottom, left, right);
+            self.damage.rows(top..bottom);
+            self.dirty = true;
+            return;
+        }
         self.grid
             .scroll_down(count, self.scroll_region.top, self.scroll_region.bottom);
+        self.damage.scroll(
+            self.scroll_region.top,
+            self.scroll_region.bottom,
+            -(count as isize),
+        );
         self.dirty = true;
     }
 
-    /// Move cursor to position
+    /// Move cursor to position (CUP), relative to the margins in origin mode
     pub fn move_cursor(&mut self, row: usize, col: usize) {
+        self.move_cursor_to_row(row);
+        self.move_cursor_to_col(col);
+    }
+
+    /// Move cursor to a row (VPA), relative to the top margin in origin mode
+    pub fn move_cursor_to_row(&mut self, row: usize) {
+        self.margin_wrap = None;
         let (base_row, max_row) = if self.modes.origin_mode {
             (self.scroll_region.top, self.scroll_region.bottom)
         } else {
             (0, self.height())
         };
-
         self.cursor.row = (base_row + row).min(max_row.saturating_sub(1));
-        self.cursor.col = col.min(self.width().saturating_sub(1));
+    }
+
+    /// Move cursor to a column (CHA), relative to the left margin in origin
+    /// mode
+    pub fn move_cursor_to_col(&mut self, col: usize) {
+        self.margin_wrap = None;
+        let (base_col, max_col) = if self.modes.origin_mode {
+            (self.scroll_region.left, self.scroll_region.right)
+        } else {
+            (0, self.width())
+        };
+        self.cursor.col = (base_col + col).min(max_col.saturating_sub(1));
+    }
+
+    /// Cursor position as reported to applications (DSR), relative to the
+    /// margins in origin mode
+    pub fn reported_cursor_position(&self) -> (usize, usize) {
+        if self.modes.origin_mode {
+            (
+                self.cursor.row.saturating_sub(self.scroll_region.top),
+                self.cursor.col.saturating_sub(self.scroll_region.left),
+            )
+        } else {
+            (self.cursor.row, self.cursor.col)
+        }
     }
 
     /// Move cursor relative to current position
+    ///
+    /// The cursor stops at a margin it starts inside of, else at the edge of
+    /// the screen.
     pub fn move_cursor_relative(&mut self, row_delta: i32, col_delta: i32) {
-        let new_row = (self.cursor.row as i32 + row_delta)
-            .max(0)
-            .min(self.height() as i32 - 1) as usize;
-        let new_col = (self.cursor.col as i32 + col_delta)
-            .max(0)
-            .min(self.width() as i32 - 1) as usize;
+        self.margin_wrap = None;
+        let region = self.scroll_region;
+        let row = self.cursor.row;
+        let col = self.cursor.col.min(self.width().saturating_sub(1));
+
+        let (min_row, max_row) = (
+            if row >= region.top { region.top } else { 0 },
+            if row < region.bottom {
+                region.bottom
+            } else {
+                self.height()
+            },
+        );
+        let (min_col, max_col) = (
+            if col >= region.left { region.left } else { 0 },
+            if col < region.right {
+                region.right
+            } else {
+                self.width()
+            },
+        );
 
-        self.cursor.row = new_row;
-        self.cursor.col = new_col;
+        self.cursor.row =
+            (row as i64 + row_delta as i64).clamp(min_row as i64, max_row as i64 - 1) as usize;
+        self.cursor.col =
+            (col as i64 + col_delta as i64).clamp(min_col as i64, max_col as i64 - 1) as usize;
     }
 
     /// Save cursor state
@@ -937,6 +2266,24 @@ impl Screen {
         }
     }
 
+    /// Kitty keyboard protocol flags in effect on the active screen
+    pub fn keyboard_flags(&self) -> KeyboardFlags {
+        if self.modes.alternate_screen {
+            self.keyboard_alt.current()
+        } else {
+            self.keyboard_main.current()
+        }
+    }
+
+    /// Kitty keyboard protocol flag stack of the active screen
+    pub fn keyboard_flags_mut(&mut self) -> &mut KeyboardFlagStack {
+        if self.modes.alternate_screen {
+            &mut self.keyboard_alt
+        } else {
+            &mut self.keyboard_main
+        }
+    }
+
     /// Switch to alternate screen buffer
     pub fn enter_alternate_screen(&mut self) {
         if self.modes.alternate_screen {
@@ -950,7 +2297,7 @@ impl Screen {
         self.alternate_grid = Some(std::mem::replace(&mut self.grid, alt));
 
         self.cursor = Cursor::default();
-        self.dirty = true;
+        self.damage_all();
     }
 
     /// Switch back to primary screen buffer
@@ -960,6 +2307,7 @@ impl Screen {
         }
 
         self.modes.alternate_screen = false;
+        self.keyboard_alt.clear();
 
         if let Some(primary) = self.alternate_grid.take() {
             self.grid = primary;
@@ -969,7 +2317,7 @@ impl Screen {
             self.cursor = saved;
         }
 
-        self.dirty = true;
+        self.damage_all();
     }
 
     /// Clear screen (or parts of it)
@@ -1030,8 +2378,16 @@ impl Screen {
             }
             ClearMode::Scrollback => {
                 self.scrollback.clear();
+                self.line_marks.clear();
             }
         }
+        match mode {
+            ClearMode::Below => self.damage.rows(cursor_row..height),
+            ClearMode::Above => self.damage.rows(0..cursor_row + 1),
+            ClearMode::All => self.damage.rows(0..height),
+            // Only visible while viewing the scrollback
+            ClearMode::Scrollback => self.damage.all(),
+        }
         self.dirty = true;
     }
 
@@ -1055,14 +2411,19 @@ impl Screen {
                 row[col].reset();
             }
         }
+        self.damage.row(cursor_row);
         self.dirty = true;
     }
 
-    /// Delete characters at cursor position
+    /// Delete characters at cursor position, shifting the rest of the line
+    /// (up to the right margin) left
     pub fn delete_chars(&mut self, count: usize) {
         let cursor_row = self.cursor.row;
         let cursor_col = self.cursor.col;
-        let width = self.width();
+        if !self.scroll_region.contains_col(cursor_col) {
+            return;
+        }
+        let width = self.scroll_region.right;
         let count = count.min(width.saturating_sub(cursor_col));
 
         // Clear selection if it overlaps with the modified row
@@ -1079,12 +2440,47 @@ impl Screen {
                 row[col].reset();
             }
         }
+        self.damage.row(cursor_row);
+        self.dirty = true;
+    }
+
+    /// Erase characters at the cursor without moving the rest of the line
+    /// (ECH)
+    pub fn erase_chars(&mut self, count: usize) {
+        let cursor_row = self.cursor.row;
+        let cursor_col = self.cursor.col;
+        let count = count.min(self.width().saturating_sub(cursor_col));
+
+        if let Some(row) = self.grid.row_mut(cursor_row) {
+            for col in cursor_col..cursor_col + count {
+                row[col].reset();
+            }
+        }
+        self.damage.row(cursor_row);
+        self.dirty = true;
+    }
+
+    /// Insert blank characters at the cursor, shifting the rest of the line
+    /// (up to the right margin) right (ICH)
+    pub fn insert_chars(&mut self, count: usize) {
+        if !self.scroll_region.contains_col(self.cursor.col) {
+            return;
+        }
+        let count = count.min(self.scroll_region.right - self.cursor.col);
+        self.insert_cells(count);
+        self.damage.row(self.cursor.row);
         self.dirty = true;
     }
 
+    /// Whether the cursor is inside the scroll region and between the
+    /// left/right margins, where IL and DL apply
+    fn cursor_in_region(&self) -> bool {
+        self.scroll_region.contains(self.cursor.row) && self.cursor_in_margins()
+    }
+
     /// Insert blank lines at cursor position
     pub fn insert_lines(&mut self, count: usize) {
-        if !self.scroll_region.contains(self.cursor.row) {
+        if !self.cursor_in_region() {
             return;
         }
 
@@ -1092,15 +2488,28 @@ impl Screen {
         self.clear_selection_if_rows_selected(self.cursor.row, self.scroll_region.bottom);
 
         // Scroll the region below cursor down
-        let region_bottom = self.scroll_region.bottom;
-        self.grid.scroll_down(count, self.cursor.row, region_bottom);
-        self.cursor.col = 0;
+        let ScrollRegion {
+            bottom,
+            left,
+            right,
+            ..
+        } = self.scroll_region;
+        if self.has_left_right_margins() {
+            self.grid
+                .scroll_rect_down(count, self.cursor.row, bottom, left, right);
+            self.damage.rows(self.cursor.row..bottom);
+        } else {
+            self.grid.scroll_down(count, self.cursor.row, bottom);
+            self.damage
+                .scroll(self.cursor.row, bottom, -(count as isize));
+        }
+        self.cursor.col = left;
         self.dirty = true;
     }
 
     /// Delete lines at cursor position
     pub fn delete_lines(&mut self, count: usize) {
-        if !self.scroll_region.contains(self.cursor.row) {
+        if !self.cursor_in_region() {
             return;
         }
 
@@ -1108,9 +2517,21 @@ impl Screen {
         self.clear_selection_if_rows_selected(self.cursor.row, self.scroll_region.bottom);
 
         // Scroll the region from cursor up
-        let region_bottom = self.scroll_region.bottom;
-        self.grid.scroll_up(count, self.cursor.row, region_bottom);
-        self.cursor.col = 0;
+        let ScrollRegion {
+            bottom,
+            left,
+            right,
+            ..
+        } = self.scroll_region;
+        if self.has_left_right_margins() {
+            self.grid
+                .scroll_rect_up(count, self.cursor.row, bottom, left, right);
+            self.damage.rows(self.cursor.row..bottom);
+        } else {
+            self.grid.scroll_up(count, self.cursor.row, bottom);
+            self.damage.scroll(self.cursor.row, bottom, count as isize);
+        }
+        self.cursor.col = left;
         self.dirty = true;
     }
 
@@ -1118,17 +2539,15 @@ impl Screen {
     pub fn reset(&mut self) {
         self.grid.clear();
         self.scrollback.clear();
+        self.line_marks.clear();
+        self.search_lines.clear();
         self.alternate_grid = None;
-        self.cursor = Cursor {
-            blink: true,
-            ..Default::default()
-        };
+        self.cursor = Cursor::default();
+        self.restore_cursor_shape();
         self.saved_cursor = None;
         self.alt_saved_cursor = None;
-        self.scroll_region = ScrollRegion {
-            top: 0,
-            bottom: self.height(),
-        };
+        self.scroll_region = ScrollRegion::full(self.width(), self.height());
+        self.margin_wrap = None;
         self.style = CellStyle::default();
         self.modes = TerminalModes {
             auto_wrap: true,
@@ -1139,10 +2558,62 @@ impl Screen {
         };
         self.title.clear();
         self.icon_name.clear();
-        self.dirty = true;
+        self.badge_format.clear();
+        for name in std::mem::take(&mut self.user_vars).into_keys() {
+            self.note_user_var_change(&name);
+        }
+        self.set_current_dir("", "");
+        self.set_pointer_shape(PointerShape::Default);
+        self.command_started = None;
+        self.damage_all();
         self.scroll_offset = 0;
         self.images.clear();
+        self.kitty.clear();
+        self.keyboard_main.clear();
+        self.keyboard_alt.clear();
         self.drcs_fonts.clear();
+        self.conformance_level = self.config.conformance_level;
+    }
+
+    /// Value of an iTerm2-style variable name
+    ///
+    /// `user.<var>` is a user variable, `session.name` the window title,
+    /// `session.path` the reported working directory and `session.columns` /
+    /// `session.rows` the screen size.
+    pub fn variable(&self, name: &str) -> Option<String> {
+        match name {
+            "session.name" => Some(self.title.clone()),
+            "session.path" => Some(self.current_dir.clone()),
+            "session.columns" => Some(self.width().to_string()),
+            "session.rows" => Some(self.height().to_string()),
+            _ => name
+                .strip_prefix("user.")
+                .and_then(|var| self.user_vars.get(var))
+                .cloned(),
+        }
+    }
+
+    /// Badge text to show over the terminal, or an empty string for none
+    ///
+    /// Expands iTerm2-style `\(name)` references in the badge format using
+    /// [`Screen::variable`]. Unknown names expand to nothing.
+    pub fn badge(&self) -> String {
+        let mut out = String::new();
+        let mut rest = self.badge_format.as_str();
+        while let Some(start) = rest.find("\\(") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find(')') else {
+                out.push_str(&rest[start..]);
+                return out;
+            };
+            if let Some(value) = self.variable(after[..end].trim()) {
+                out.push_str(&value);
+            }
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+        out
     }
 
     /// Search for text in scrollback and visible buffer
@@ -1243,17 +2714,144 @@ impl Screen {
                 lower_buf.as_str()
             };
 
-            let mut start = 0;
-            while let Some(pos) = search_text[start..].find(pattern) {
-                let col = start + pos;
-                results.push(SearchResult {
-                    line: line_idx,
-                    col,
-                    len: pattern.len(),
-                });
-                start = col + 1;
+            let mut start = 0;
+            while let Some(pos) = search_text[start..].find(pattern) {
+                let col = start + pos;
+                results.push(SearchResult {
+                    line: line_idx,
+                    col,
+                    len: pattern.len(),
+                });
+                start = col + 1;
+            }
+        }
+    }
+
+    /// Find every match of the regular expression `pattern` in scrollback
+    /// and the visible buffer
+    ///
+    /// Soft-wrapped rows are joined before matching, so a match can continue
+    /// onto the following rows. Matches are in absolute line coordinates
+    /// (0 = oldest scrollback line), oldest first. Empty matches are skipped.
+    pub fn search_regex(
+        &self,
+        pattern: &str,
+        options: SearchOptions,
+    ) -> Result<Vec<SearchMatch>, regex::Error> {
+        let regex = regex::RegexBuilder::new(pattern)
+            .case_insensitive(options.case_insensitive)
+            .build()?;
+        let mut matches = Vec::new();
+        let mut text = String::new();
+        // Line, first column and end column of the cell each byte of `text`
+        // came from
+        let mut cells: Vec<(usize, usize, usize)> = Vec::new();
+
+        for line in 0..self.total_lines() {
+            let Some(row) = self.get_row_by_absolute_line(line) else {
+                continue;
+            };
+            if !row.wrapped && !text.is_empty() {
+                Self::collect_search_matches(&regex, options, &text, &cells, &mut matches);
+                text.clear();
+                cells.clear();
+            }
+            for (col, cell) in row.iter().enumerate() {
+                if cell.is_wide_spacer() {
+                    continue;
+                }
+                let start = text.len();
+                if cell.c == '\0' {
+                    text.push(' ');
+                } else {
+                    cell.push_grapheme(&mut text);
+                }
+                let width = if cell.is_wide() { 2 } else { 1 };
+                cells.extend(std::iter::repeat_n(
+                    (line, col, col + width),
+                    text.len() - start,
+                ));
+            }
+        }
+        Self::collect_search_matches(&regex, options, &text, &cells, &mut matches);
+
+        Ok(matches)
+    }
+
+    /// Add the matches of `regex` in one logical line to `matches`
+    fn collect_search_matches(
+        regex: &regex::Regex,
+        options: SearchOptions,
+        text: &str,
+        cells: &[(usize, usize, usize)],
+        matches: &mut Vec<SearchMatch>,
+    ) {
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+        let text = text.trim_end();
+        for m in regex.find_iter(text) {
+            if m.is_empty() {
+                continue;
+            }
+            if options.whole_word {
+                let before = text[..m.start()].chars().next_back();
+                let after = text[m.end()..].chars().next();
+                if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+                    continue;
+                }
+            }
+            let (start_line, start_col, _) = cells[m.start()];
+            let (end_line, _, end_col) = cells[m.end() - 1];
+            matches.push(SearchMatch {
+                start_line,
+                start_col,
+                end_line,
+                end_col,
+                text: m.as_str().to_string(),
+            });
+        }
+    }
+
+    /// Collect the distinct matches of `regex` in scrollback and the visible buffer
+    ///
+    /// Wrapped rows are joined first, so a match can span a soft line break.
+    /// When the pattern has a capture group, the first group is collected
+    /// instead of the whole match. Matches are returned in order of first
+    /// appearance, oldest first.
+    pub fn extract_matches(&self, pattern: &str) -> Result<Vec<String>, regex::Error> {
+        let regex = regex::Regex::new(pattern)?;
+        let mut seen = std::collections::HashSet::new();
+        let mut matches = Vec::new();
+        let mut line = String::new();
+        let mut row_text = String::new();
+
+        let mut collect = |line: &str| {
+            for caps in regex.captures_iter(line) {
+                let Some(m) = caps.get(1).or_else(|| caps.get(0)) else {
+                    continue;
+                };
+                if !m.as_str().is_empty() && seen.insert(m.as_str().to_string()) {
+                    matches.push(m.as_str().to_string());
+                }
+            }
+        };
+
+        let rows = self
+            .scrollback
+            .iter()
+            .chain((0..self.grid.height()).filter_map(|r| self.grid.row(r)));
+        for row in rows {
+            if !row.wrapped && !line.is_empty() {
+                collect(&line);
+                line.clear();
             }
+            row.write_text_to(&mut row_text);
+            line.push_str(&row_text);
+        }
+        if !line.is_empty() {
+            collect(&line);
         }
+
+        Ok(matches)
     }
 
     /// Convert a line index from find() to scroll offset
@@ -1288,6 +2886,25 @@ impl Screen {
         cell_rows: usize,
         sixel_image: SixelImage,
     ) {
+        // Clear grid cells underneath the image (xterm behavior)
+        // This ensures text doesn't show through the image
+        self.clear_cells_for_image(col, row, cell_cols, cell_rows);
+
+        self.insert_image(col, row, cell_cols, cell_rows, sixel_image);
+    }
+
+    /// Add an image without touching the text underneath it
+    ///
+    /// Used for kitty graphics placements, which are drawn over the text.
+    /// Returns the new image's ID.
+    pub fn insert_image(
+        &mut self,
+        col: usize,
+        row: usize,
+        cell_cols: usize,
+        cell_rows: usize,
+        sixel_image: SixelImage,
+    ) -> u64 {
         let id = self.next_image_id;
         self.next_image_id += 1;
 
@@ -1305,15 +2922,23 @@ impl Screen {
             pixel_height: sixel_image.height,
         };
 
-        // Clear grid cells underneath the image (xterm behavior)
-        // This ensures text doesn't show through the image
-        self.clear_cells_for_image(col, row, cell_cols, cell_rows);
-
         self.images.insert(id, image);
-        self.dirty = true;
+        self.damage_all();
 
         // Prune old images that have scrolled too far
         self.prune_old_images();
+        id
+    }
+
+    /// Remove a single image by its ID, along with the kitty placement it
+    /// renders (the transmitted kitty image is kept)
+    pub fn remove_image(&mut self, id: u64) -> bool {
+        let removed = self.images.remove(&id).is_some();
+        if removed {
+            self.kitty.forget_placements(|p| p.terminal_image_id == id);
+            self.damage_all();
+        }
+        removed
     }
 
     /// Clear grid cells that will be covered by an image
@@ -1331,7 +2956,7 @@ impl Screen {
                         break;
                     }
                     // Clear the cell but keep it as a space (not truly empty)
-                    grid_row[c].c = ' ';
+                    grid_row[c].set_char(' ');
                     grid_row[c].attrs = crate::cell::CellAttrs::empty();
                 }
             }
@@ -1376,6 +3001,13 @@ impl Screen {
         }
     }
 
+    /// All images on the screen and in the scrollback, oldest first
+    pub fn images(&self) -> Vec<&TerminalImage> {
+        let mut images: Vec<_> = self.images.values().collect();
+        images.sort_by_key(|img| img.id);
+        images
+    }
+
     /// Get the image at a given visible row and column position
     ///
     /// Returns the image if one exists at that position, or None otherwise.
@@ -1404,6 +3036,11 @@ impl Screen {
         self.images.get(&id)
     }
 
+    /// Kitty graphics images and placements
+    pub fn kitty_graphics(&self) -> &KittyGraphics {
+        &self.kitty
+    }
+
     /// Prune images that have scrolled off the top of the scrollback buffer
     fn prune_old_images(&mut self) {
         if self.images.is_empty() {
@@ -1421,9 +3058,16 @@ impl Screen {
         self.images.retain(|_, img| img.line >= min_valid_line);
     }
 
-    /// Clear all images (called on screen clear)
-    pub fn clear_images(&mut self) {
+    /// Remove all images and kitty placements; transmitted kitty images are
+    /// kept so programs can place them again. Returns how many were removed.
+    pub fn clear_images(&mut self) -> usize {
+        let count = self.images.len();
         self.images.clear();
+        self.kitty.forget_placements(|_| true);
+        if count > 0 {
+            self.damage_all();
+        }
+        count
     }
 
     /// Set the cell height hint (call from UI layer when font metrics are known)
@@ -1501,7 +3145,7 @@ impl Screen {
         }
 
         self.drcs_fonts.insert(designator, font);
-        self.dirty = true;
+        self.damage_all();
     }
 
     /// Get a DRCS glyph by designator and character position
@@ -1564,8 +3208,13 @@ impl Screen {
     // ========== Selection Methods ==========
 
     /// Check if a character is a word character (for word selection)
-    fn is_word_char(c: char) -> bool {
-        c.is_alphanumeric() || c == '_' || c == '.'
+    fn is_word_char(&self, c: char) -> bool {
+        self.config.selection.is_word_char(c)
+    }
+
+    /// Letters hint mode labels targets with (`[selection] hint_alphabet`)
+    pub fn hint_alphabet(&self) -> &str {
+        &self.config.selection.hint_alphabet
     }
 
     /// Find word boundaries around a column position in a row
@@ -1591,7 +3240,7 @@ impl Screen {
         let center_char = row.get(col).map(|c| c.c).unwrap_or(' ');
 
         // If we clicked on a non-word character, just select that character
-        if !Self::is_word_char(center_char) {
+        if !self.is_word_char(center_char) {
             return (
                 SelectionPoint::new(line, col),
                 SelectionPoint::new(line, col),
@@ -1605,7 +3254,7 @@ impl Screen {
             if start_col > 0 {
                 let r = self.get_row_by_absolute_line(start_line).unwrap();
                 if let Some(cell) = r.get(start_col - 1) {
-                    if Self::is_word_char(cell.c) {
+                    if self.is_word_char(cell.c) {
                         start_col -= 1;
                         continue;
                     }
@@ -1623,7 +3272,7 @@ impl Screen {
                     let prev_len = prev_row.len();
                     if prev_len > 0 {
                         if let Some(cell) = prev_row.get(prev_len - 1) {
-                            if Self::is_word_char(cell.c) {
+                            if self.is_word_char(cell.c) {
                                 start_line -= 1;
                                 start_col = prev_len - 1;
                                 continue;
@@ -1643,7 +3292,7 @@ impl Screen {
             let r_len = r.len();
             if end_col < r_len - 1 {
                 if let Some(cell) = r.get(end_col + 1) {
-                    if Self::is_word_char(cell.c) {
+                    if self.is_word_char(cell.c) {
                         end_col += 1;
                         continue;
                     }
@@ -1654,7 +3303,7 @@ impl Screen {
             if let Some(next_row) = self.get_row_by_absolute_line(end_line + 1) {
                 if next_row.wrapped {
                     if let Some(cell) = next_row.get(0) {
-                        if Self::is_word_char(cell.c) {
+                        if self.is_word_char(cell.c) {
                             end_line += 1;
                             end_col = 0;
                             continue;
@@ -1853,30 +3502,35 @@ impl Screen {
                 if let Some(cell) = row.get(col) {
                     // Skip wide character spacers
                     if !cell.attrs.contains(crate::cell::CellAttrs::WIDE_SPACER) {
-                        result.push(cell.c);
+                        cell.push_grapheme(&mut result);
                     }
                 }
             }
 
             // Add newline between lines
             // For block selection: always add newlines between lines
-            // For normal selection: skip newline after wrapped lines
-            if line_idx < end_line && (is_block || !row.wrapped) {
+            // For normal selection: skip newline before a wrapped continuation
+            if line_idx < end_line && (is_block || !self.continues_on_next_line(line_idx)) {
                 result.push('\n');
             }
         }
 
-        // Trim trailing whitespace from each line but keep newlines
-        let trimmed: String = result
-            .lines()
-            .map(|l| l.trim_end())
-            .collect::<Vec<_>>()
-            .join("\n");
+        if self.config.selection.trim_trailing_whitespace {
+            // Trim trailing whitespace from each line but keep newlines
+            // between them, then drop trailing blank lines
+            result = result
+                .lines()
+                .map(|l| l.trim_end())
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim_end_matches('\n')
+                .to_string();
+        }
 
-        if trimmed.is_empty() {
+        if result.is_empty() {
             None
         } else {
-            Some(trimmed)
+            Some(result)
         }
     }
 
@@ -2044,6 +3698,7 @@ impl Screen {
                         '\'' => result.push_str("&#39;"),
                         c => result.push(c),
                     }
+                    result.push_str(cell.combining.as_str());
                 }
             }
 
@@ -2056,7 +3711,7 @@ impl Screen {
             }
 
             // Add newline between lines
-            if line_idx < end_line && (is_block || !row.wrapped) {
+            if line_idx < end_line && (is_block || !self.continues_on_next_line(line_idx)) {
                 result.push('\n');
             }
         }
@@ -2070,8 +3725,205 @@ impl Screen {
         }
     }
 
+    /// Get the selected text as RTF with its colors and styles, set in
+    /// `font_family`
+    ///
+    /// Text without colors of its own gets the palette's foreground and
+    /// background, so it reads the same when pasted on a white page. Bold,
+    /// italic, underline and strikethrough are kept, inverse text has its
+    /// colors swapped, and blank cells at the end of each line are dropped.
+    pub fn selection_to_rtf(
+        &self,
+        palette: &crate::color::ColorPalette,
+        font_family: &str,
+    ) -> Option<String> {
+        use std::fmt::Write;
+
+        let selection = self.selection.as_ref()?;
+        let (start, end) = selection.ordered();
+        let total = self.total_lines();
+        if start.line >= total {
+            return None;
+        }
+        let end_line = end.line.min(total - 1);
+        let is_block = selection.mode == SelectionMode::Block;
+        let block_cols = (
+            selection.anchor.col.min(selection.end.col),
+            selection.anchor.col.max(selection.end.col),
+        );
+
+        // Color table entries, numbered from 1
+        let mut colors: Vec<Rgb> = Vec::new();
+        let mut color_index = |rgb: Rgb| match colors.iter().position(|&c| c == rgb) {
+            Some(index) => index + 1,
+            None => {
+                colors.push(rgb);
+                colors.len()
+            }
+        };
+        let mut body = String::new();
+        let mut grapheme = String::new();
+        let mut last_style = None;
+        for line_idx in start.line..=end_line {
+            let Some(row) = self.get_row_by_absolute_line(line_idx) else {
+                continue;
+            };
+            let last_col = row.len().saturating_sub(1);
+            let (start_col, end_col) = if is_block {
+                (block_cols.0, block_cols.1.min(last_col))
+            } else {
+                let sc = if line_idx == start.line { start.col } else { 0 };
+                let ec = if line_idx == end.line {
+                    end.col.min(last_col)
+                } else {
+                    last_col
+                };
+                (sc, ec)
+            };
+            let mut cells: Vec<&Cell> = (start_col..=end_col)
+                .filter_map(|col| row.get(col))
+                .collect();
+            while cells.last().is_some_and(|cell| is_blank_cell(cell)) {
+                cells.pop();
+            }
+
+            for cell in cells {
+                if cell.attrs.contains(CellAttrs::WIDE_SPACER) {
+                    continue;
+                }
+                let mut fg = cell.fg.to_rgb(palette);
+                let mut bg = if cell.bg.is_default() {
+                    palette.background
+                } else {
+                    cell.bg.to_rgb(palette)
+                };
+                if cell.attrs.contains(CellAttrs::INVERSE) {
+                    std::mem::swap(&mut fg, &mut bg);
+                }
+                let style = (
+                    fg,
+                    bg,
+                    cell.attrs.contains(CellAttrs::BOLD),
+                    cell.attrs.contains(CellAttrs::ITALIC),
+                    cell.attrs.has_underline(),
+                    cell.attrs.contains(CellAttrs::STRIKETHROUGH),
+                );
+                if last_style != Some(style) {
+                    let (fg, bg) = (color_index(fg), color_index(bg));
+                    // Word reads the background from \chcbpat, others from \cb
+                    let _ = write!(
+                        body,
+                        "\\cf{}\\chcbpat{}\\cb{}\\b{}\\i{}\\{}\\strike{} ",
+                        fg,
+                        bg,
+                        bg,
+                        if style.2 { "" } else { "0" },
+                        if style.3 { "" } else { "0" },
+                        if style.4 { "ul" } else { "ulnone" },
+                        if style.5 { "" } else { "0" },
+                    );
+                    last_style = Some(style);
+                }
+                grapheme.clear();
+                cell.push_grapheme(&mut grapheme);
+                push_rtf_text(&mut body, &grapheme);
+            }
+
+            if line_idx < end_line && (is_block || !self.continues_on_next_line(line_idx)) {
+                body.push_str("\\par\n");
+            }
+        }
+        last_style?;
+        // Blank lines at the end are dropped too
+        while let Some(rest) = body.strip_suffix("\\par\n") {
+            body.truncate(rest.len());
+        }
+
+        let mut rtf = String::from("{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern ");
+        push_rtf_text(&mut rtf, font_family);
+        rtf.push_str(";}}{\\colortbl;");
+        for color in &colors {
+            let _ = write!(rtf, "\\red{}\\green{}\\blue{};", color.r, color.g, color.b);
+        }
+        rtf.push_str("}\n\\f0\\fs24 ");
+        rtf.push_str(&body);
+        rtf.push('}');
+        Some(rtf)
+    }
+
+    /// Serialize the scrollback and screen back to text with escape sequences
+    ///
+    /// Each line carries the SGR sequences (and OSC 8 hyperlinks) needed to
+    /// reproduce its colors and attributes, so printing the result in a
+    /// terminal shows the session as it looked. Soft-wrapped rows are joined,
+    /// trailing blank cells and lines are dropped, and every line ends with
+    /// attributes reset.
+    pub fn to_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for line_idx in 0..self.total_lines() {
+            let Some(row) = self.get_row_by_absolute_line(line_idx) else {
+                continue;
+            };
+            let len = (0..row.len())
+                .rev()
+                .find(|&col| !is_blank_cell(&row[col]))
+                .map_or(0, |col| col + 1);
+
+            let mut style = Cell::default();
+            let mut link: Option<&Arc<Hyperlink>> = None;
+            for cell in row.iter().take(len) {
+                if cell.attrs.contains(CellAttrs::WIDE_SPACER) {
+                    continue;
+                }
+                if !same_style(cell, &style) {
+                    push_sgr(&mut line, cell);
+                    style = cell.clone();
+                }
+                let same_link = match (link, cell.hyperlink.as_ref()) {
+                    (None, None) => true,
+                    (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                    _ => false,
+                };
+                if !same_link {
+                    push_hyperlink(&mut line, cell.hyperlink.as_deref());
+                    link = cell.hyperlink.as_ref();
+                }
+                cell.push_grapheme(&mut line);
+            }
+            if link.is_some() {
+                push_hyperlink(&mut line, None);
+            }
+            if !same_style(&style, &Cell::default()) {
+                line.push_str("\x1b[0m");
+            }
+
+            if !self.continues_on_next_line(line_idx) {
+                lines.push(std::mem::take(&mut line));
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let mut result = lines.join("\n");
+        if !result.is_empty() {
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Whether the row after absolute `line` is its soft-wrapped continuation
+    fn continues_on_next_line(&self, line: usize) -> bool {
+        self.get_row_by_absolute_line(line + 1)
+            .is_some_and(|row| row.wrapped)
+    }
+
     /// Get a row by absolute line index (0 = oldest scrollback line)
-    fn get_row_by_absolute_line(&self, line: usize) -> Option<&Row> {
+    pub(crate) fn get_row_by_absolute_line(&self, line: usize) -> Option<&Row> {
         let scrollback_len = self.scrollback.len();
         if line < scrollback_len {
             self.scrollback.get(line)
@@ -2106,6 +3958,32 @@ pub struct SearchResult {
     pub len: usize,
 }
 
+/// Options for [`Screen::search_regex`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Ignore the case of letters
+    pub case_insensitive: bool,
+    /// Skip matches with a letter, digit or `_` right before or after them
+    pub whole_word: bool,
+}
+
+/// A match of [`Screen::search_regex`], in absolute line coordinates
+/// (0 = oldest scrollback line)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Line of the first matched cell
+    pub start_line: usize,
+    /// Column of the first matched cell
+    pub start_col: usize,
+    /// Line of the last matched cell, after `start_line` when the match
+    /// continues onto soft-wrapped rows
+    pub end_line: usize,
+    /// Column just past the last matched cell
+    pub end_col: usize,
+    /// Matched text
+    pub text: String,
+}
+
 /// Line clear mode
 #[derive(Debug, Clone, Copy)]
 pub enum LineClearMode {
@@ -2117,6 +3995,189 @@ pub enum LineClearMode {
     All,
 }
 
+/// Whether a trailing cell can be dropped without changing how the line looks
+fn is_blank_cell(cell: &Cell) -> bool {
+    cell.c == ' '
+        && cell.combining.is_empty()
+        && cell.bg.is_default()
+        && cell.hyperlink.is_none()
+        && !cell.attrs.has_underline()
+        && !cell
+            .attrs
+            .intersects(CellAttrs::INVERSE | CellAttrs::STRIKETHROUGH | CellAttrs::OVERLINE)
+}
+
+/// Append `text` to an RTF document, escaping its special characters and
+/// writing non-ASCII ones as UTF-16 code units
+fn push_rtf_text(out: &mut String, text: &str) {
+    use std::fmt::Write;
+
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            _ => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    // RTF takes signed 16-bit numbers, with `?` for readers
+                    // that do not know \u
+                    let _ = write!(out, "\\u{}?", *unit as i16);
+                }
+            }
+        }
+    }
+}
+
+/// Whether two cells are drawn with the same colors and attributes
+fn same_style(a: &Cell, b: &Cell) -> bool {
+    let style = |attrs: CellAttrs| attrs - (CellAttrs::WIDE | CellAttrs::WIDE_SPACER);
+    a.fg == b.fg
+        && a.bg == b.bg
+        && a.underline_color == b.underline_color
+        && style(a.attrs) == style(b.attrs)
+}
+
+/// Append the SGR sequence that sets `cell`'s colors and attributes from
+/// scratch
+fn push_sgr(out: &mut String, cell: &Cell) {
+    out.push_str("\x1b[");
+    push_sgr_params(out, cell);
+    out.push('m');
+}
+
+/// Append the SGR parameters of `cell`'s colors and attributes, starting
+/// with a reset
+fn push_sgr_params(out: &mut String, cell: &Cell) {
+    const ATTRS: [(CellAttrs, &str); 13] = [
+        (CellAttrs::BOLD, "1"),
+        (CellAttrs::DIM, "2"),
+        (CellAttrs::ITALIC, "3"),
+        (CellAttrs::UNDERLINE, "4"),
+        (CellAttrs::DOUBLE_UNDERLINE, "4:2"),
+        (CellAttrs::CURLY_UNDERLINE, "4:3"),
+        (CellAttrs::DOTTED_UNDERLINE, "4:4"),
+        (CellAttrs::DASHED_UNDERLINE, "4:5"),
+        (CellAttrs::BLINK, "5"),
+        (CellAttrs::INVERSE, "7"),
+        (CellAttrs::HIDDEN, "8"),
+        (CellAttrs::STRIKETHROUGH, "9"),
+        (CellAttrs::OVERLINE, "53"),
+    ];
+
+    out.push('0');
+    for (attr, code) in ATTRS {
+        if cell.attrs.contains(attr) {
+            out.push(';');
+            out.push_str(code);
+        }
+    }
+    push_sgr_color(out, cell.fg, 30);
+    push_sgr_color(out, cell.bg, 40);
+    if let Some(color) = cell.underline_color {
+        push_sgr_color(out, color, 50);
+    }
+}
+
+/// Append `;<parameters>` for a color; `base` is 30 for the foreground, 40
+/// for the background and 50 for the underline
+fn push_sgr_color(out: &mut String, color: Color, base: u8) {
+    use std::fmt::Write;
+
+    let _ = match color {
+        Color::Default => Ok(()),
+        // Underlines have no short form for the 16 colors
+        Color::Ansi(ansi) if base == 50 => write!(out, ";58;5;{}", ansi as u8),
+        Color::Ansi(ansi) if (ansi as u8) < 8 => write!(out, ";{}", base + ansi as u8),
+        Color::Ansi(ansi) => write!(out, ";{}", base + 60 + ansi as u8 - 8),
+        Color::Indexed(index) => write!(out, ";{};5;{}", base + 8, index),
+        Color::Rgb(rgb) => write!(out, ";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b),
+    };
+}
+
+/// Append an OSC 8 sequence opening `link`, or closing the current one
+fn push_hyperlink(out: &mut String, link: Option<&Hyperlink>) {
+    out.push_str("\x1b]8;");
+    if let Some(link) = link {
+        if let Some(ref id) = link.id {
+            out.push_str("id=");
+            out.push_str(id);
+        }
+        out.push(';');
+        out.push_str(&link.uri);
+    } else {
+        out.push(';');
+    }
+    out.push_str("\x1b\\");
+}
+
+/// How many characters into a line a prompt character is looked for
+const PROMPT_SCAN: usize = 64;
+
+/// Whether a line looks like a shell prompt, for prompt navigation without
+/// OSC 133 marks
+fn looks_like_prompt(text: &str) -> bool {
+    if text.starts_with(char::is_whitespace) {
+        return false;
+    }
+    let mut chars = text.chars().take(PROMPT_SCAN).peekable();
+    while let Some(c) = chars.next() {
+        if matches!(c, '$' | '#' | '%' | '>' | '❯') && chars.peek().is_none_or(|&n| n == ' ') {
+            return true;
+        }
+    }
+    false
+}
+
+/// Words that make a line an error for the scrollbar marks
+const ERROR_WORDS: &[&str] = &[
+    "error",
+    "fatal",
+    "failed",
+    "failure",
+    "panic",
+    "panicked",
+    "exception",
+    "traceback",
+];
+
+/// Whether a line has one of [`ERROR_WORDS`] on its own, in any case
+fn looks_like_error(text: &str) -> bool {
+    text.split(|c: char| !c.is_alphanumeric())
+        .any(|word| ERROR_WORDS.iter().any(|e| word.eq_ignore_ascii_case(e)))
+}
+
+/// What a line is marked as on the scrollbar, apart from search matches;
+/// `continued` is whether it continues a wrapped line
+fn line_mark_kind(text: &str, continued: bool) -> Option<ScrollMarkKind> {
+    if !continued && looks_like_prompt(text) {
+        Some(ScrollMarkKind::Prompt)
+    } else if looks_like_error(text) {
+        Some(ScrollMarkKind::Error)
+    } else {
+        None
+    }
+}
+
+/// This machine's host name, to tell local OSC 7 directories from remote ones
+fn local_hostname() -> Option<String> {
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut _, buf.len()) } != 0 {
+            return None;
+        }
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        Some(String::from_utf8_lossy(&buf[..len]).into_owned())
+    }
+    #[cfg(not(unix))]
+    {
+        std::env::var("COMPUTERNAME").ok()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2130,6 +4191,86 @@ mod tests {
         assert_eq!(screen.cursor.col, 0);
     }
 
+    #[test]
+    fn test_extract_matches() {
+        let mut screen = Screen::new(10, 4, ScreenConfig::default());
+        for c in "ip 10.0.0.1 ok".chars() {
+            screen.put_char(c);
+        }
+        screen.carriage_return();
+        screen.line_feed();
+        for c in "id=42 10.0.0.1".chars() {
+            screen.put_char(c);
+        }
+
+        // The first address wraps across rows and still matches once
+        let ips = screen.extract_matches(r"\d+\.\d+\.\d+\.\d+").unwrap();
+        assert_eq!(ips, ["10.0.0.1"]);
+
+        // A capture group selects what is collected
+        assert_eq!(screen.extract_matches(r"id=(\d+)").unwrap(), ["42"]);
+        assert!(screen.extract_matches("(unclosed").is_err());
+    }
+
+    #[test]
+    fn test_search_regex() {
+        let mut screen = Screen::new(10, 2, ScreenConfig::default());
+        for (i, line) in ["Error: foo", "errors", "an error, x"].iter().enumerate() {
+            if i > 0 {
+                screen.carriage_return();
+                screen.line_feed();
+            }
+            for c in line.chars() {
+                screen.put_char(c);
+            }
+        }
+        // "an error, x" wraps onto a second row and pushes "Error: foo"
+        // into scrollback
+        assert_eq!(screen.scrollback().len(), 2);
+
+        let found = |pattern: &str, options: SearchOptions| {
+            screen
+                .search_regex(pattern, options)
+                .unwrap()
+                .into_iter()
+                .map(|m| (m.start_line, m.start_col, m.end_line, m.end_col))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            found("error", SearchOptions::default()),
+            [(1, 0, 1, 5), (2, 3, 2, 8)]
+        );
+        let ignore_case = SearchOptions {
+            case_insensitive: true,
+            ..Default::default()
+        };
+        assert_eq!(found("error", ignore_case).len(), 3);
+        let whole_word = SearchOptions {
+            case_insensitive: true,
+            whole_word: true,
+        };
+        assert_eq!(found("error", whole_word), [(0, 0, 0, 5), (2, 3, 2, 8)]);
+
+        // A match continues across the soft wrap
+        let wrapped = screen
+            .search_regex(r"error, x", SearchOptions::default())
+            .unwrap();
+        assert_eq!(wrapped.len(), 1);
+        assert_eq!(
+            (
+                wrapped[0].start_line,
+                wrapped[0].end_line,
+                wrapped[0].end_col
+            ),
+            (2, 3, 1)
+        );
+        assert_eq!(wrapped[0].text, "error, x");
+
+        assert!(screen
+            .search_regex("(unclosed", SearchOptions::default())
+            .is_err());
+    }
+
     #[test]
     fn test_put_char() {
         let mut screen = Screen::new(80, 24, ScreenConfig::default());
@@ -2142,6 +4283,31 @@ mod tests {
         assert_eq!(screen.cursor.col, 2);
     }
 
+    #[test]
+    fn test_put_char_grapheme_clusters() {
+        let mut screen = Screen::new(10, 3, ScreenConfig::default());
+        // e + combining acute, heart + emoji presentation, woman + ZWJ + laptop
+        for c in "e\u{301}\u{2764}\u{FE0F}\u{1F469}\u{200D}\u{1F4BB}!".chars() {
+            screen.put_char(c);
+        }
+
+        assert_eq!(screen.get_cell(0, 0).unwrap().grapheme(), "e\u{301}");
+        let heart = screen.get_cell(0, 1).unwrap();
+        assert_eq!(heart.grapheme(), "\u{2764}\u{FE0F}");
+        assert!(heart.is_wide());
+        assert!(screen.get_cell(0, 2).unwrap().is_wide_spacer());
+        let coder = screen.get_cell(0, 3).unwrap();
+        assert_eq!(coder.grapheme(), "\u{1F469}\u{200D}\u{1F4BB}");
+        assert!(coder.is_wide());
+        assert_eq!(screen.get_cell(0, 5).unwrap().c, '!');
+        assert_eq!(screen.cursor.col, 6);
+
+        // Overwriting a cell drops its combining characters
+        screen.cursor.col = 0;
+        screen.put_char('x');
+        assert_eq!(screen.get_cell(0, 0).unwrap().grapheme(), "x");
+    }
+
     #[test]
     fn test_auto_wrap() {
         let mut screen = Screen::new(5, 3, ScreenConfig::default());
@@ -2155,6 +4321,87 @@ mod tests {
         assert_eq!(screen.grid().row(2).unwrap().text(), "d");
     }
 
+    #[test]
+    fn test_auto_wrap_off() {
+        let mut screen = Screen::new(5, 3, ScreenConfig::default());
+        screen.modes.auto_wrap = false;
+
+        // The last column is overwritten instead of wrapping
+        for c in "Hello World".chars() {
+            screen.put_char(c);
+        }
+        assert_eq!(screen.grid().row(0).unwrap().text(), "Helld");
+        assert_eq!(screen.grid().row(1).unwrap().text(), "");
+        assert_eq!(screen.cursor.row, 0);
+    }
+
+    #[test]
+    fn test_insert_mode() {
+        let mut screen = Screen::new(6, 2, ScreenConfig::default());
+        for c in "abcdef".chars() {
+            screen.put_char(c);
+        }
+        screen.modes.insert_mode = true;
+        screen.move_cursor(0, 1);
+        screen.put_char('X');
+        assert_eq!(screen.grid().row(0).unwrap().text(), "aXbcde");
+        assert_eq!(screen.cursor.col, 2);
+
+        // A wide character pushed half off the end of the line is erased
+        screen.move_cursor(1, 0);
+        for c in "abcd中".chars() {
+            screen.put_char(c);
+        }
+        screen.move_cursor(1, 0);
+        screen.put_char('X');
+        assert_eq!(screen.grid().row(1).unwrap().text(), "Xabcd");
+        assert!(!screen.get_cell(1, 5).unwrap().is_wide());
+
+        // Inserting inside a wide character erases both halves
+        screen.modes.insert_mode = false;
+        screen.move_cursor(1, 0);
+        for c in "中abc".chars() {
+            screen.put_char(c);
+        }
+        screen.modes.insert_mode = true;
+        screen.move_cursor(1, 1);
+        screen.put_char('X');
+        assert_eq!(screen.grid().row(1).unwrap().text(), " X abc");
+    }
+
+    #[test]
+    fn test_backspace_reverse_wraparound() {
+        let mut screen = Screen::new(5, 3, ScreenConfig::default());
+        for c in "Hello".chars() {
+            screen.put_char(c);
+        }
+        // From the pending wrap, BS lands left of the last column
+        screen.backspace();
+        assert_eq!(screen.cursor.col, 3);
+        screen.put_char('X');
+        assert_eq!(screen.grid().row(0).unwrap().text(), "HelXo");
+
+        // Without mode 45, BS stops at the first column
+        screen.move_cursor(1, 0);
+        screen.backspace();
+        assert_eq!((screen.cursor.row, screen.cursor.col), (1, 0));
+
+        screen.modes.reverse_wraparound = true;
+        screen.backspace();
+        assert_eq!((screen.cursor.row, screen.cursor.col), (0, 4));
+
+        // The top-left corner stays put
+        screen.move_cursor(0, 0);
+        screen.backspace();
+        assert_eq!((screen.cursor.row, screen.cursor.col), (0, 0));
+
+        // Reverse wraparound needs auto-wrap
+        screen.modes.auto_wrap = false;
+        screen.move_cursor(1, 0);
+        screen.backspace();
+        assert_eq!((screen.cursor.row, screen.cursor.col), (1, 0));
+    }
+
     #[test]
     fn test_scroll_up() {
         let mut screen = Screen::new(80, 3, ScreenConfig::default());
@@ -2170,6 +4417,7 @@ mod tests {
         screen.line_feed(); // This should scroll
 
         assert_eq!(screen.scrollback.len(), 1);
+        assert_eq!(screen.lines_scrolled(), 1);
         assert_eq!(screen.scrollback[0][0].c, '1');
         assert_eq!(screen.grid()[0][0].c, '2');
         assert_eq!(screen.grid()[1][0].c, '3');
@@ -2390,6 +4638,198 @@ mod tests {
         assert_eq!(sel.end, SelectionPoint::new(0, 5));
     }
 
+    #[test]
+    fn test_word_selection_custom_word_chars() {
+        let text = "see /usr/lib-x foo";
+        let mut screen = screen_with_text(text);
+        screen.start_selection(0, 10, SelectionMode::Word);
+        assert_eq!(screen.get_selected_text().as_deref(), Some("lib"));
+
+        let config = ScreenConfig {
+            selection: SelectionConfig {
+                word_chars: "/-".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut screen = Screen::new(80, 24, config);
+        for c in text.chars() {
+            screen.put_char(c);
+        }
+        screen.start_selection(0, 10, SelectionMode::Word);
+        assert_eq!(screen.get_selected_text().as_deref(), Some("/usr/lib-x"));
+    }
+
+    #[test]
+    fn test_to_ansi() {
+        let mut screen = Screen::new(10, 3, ScreenConfig::default());
+        let mut parser = crate::parser::Parser::new();
+        parser.parse(
+            &mut screen,
+            b"\x1b[1;31mred\x1b[0m ok\r\n\x1b[4:3;38;2;1;2;3mcurly\x1b[0m\r\n\
+              0123456789wrapped",
+        );
+        // The first line scrolled into the scrollback; the last one wrapped
+        assert_eq!(
+            screen.to_ansi(),
+            "\x1b[0;1;31mred\x1b[0m ok\n\
+             \x1b[0;4:3;38;2;1;2;3mcurly\x1b[0m\n\
+             0123456789wrapped\n"
+        );
+
+        // Printing the export (with the tty's newline translation) reproduces
+        // the styles
+        let mut copy = Screen::new(10, 6, ScreenConfig::default());
+        parser.parse(&mut copy, screen.to_ansi().replace('\n', "\r\n").as_bytes());
+        let cell = copy.grid().get(1, 0).unwrap();
+        assert!(cell.attrs.contains(CellAttrs::CURLY_UNDERLINE));
+        assert_eq!(cell.fg, Color::rgb(1, 2, 3));
+        assert_eq!(copy.grid().get(0, 4).unwrap().fg, Color::Default);
+        assert_eq!(copy.to_ansi(), screen.to_ansi());
+
+        assert_eq!(Screen::new(10, 3, ScreenConfig::default()).to_ansi(), "");
+    }
+
+    #[test]
+    fn test_selected_text_trimming() {
+        let fill = |screen: &mut Screen| {
+            for c in "ab  ".chars() {
+                screen.put_char(c);
+            }
+            screen.line_feed();
+            screen.carriage_return();
+        };
+
+        let mut screen = Screen::new(10, 4, ScreenConfig::default());
+        fill(&mut screen);
+        screen.start_selection(0, 0, SelectionMode::Char);
+        screen.extend_selection(2, 9);
+        assert_eq!(screen.get_selected_text().as_deref(), Some("ab"));
+
+        let config = ScreenConfig {
+            selection: SelectionConfig {
+                trim_trailing_whitespace: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut screen = Screen::new(10, 4, config);
+        fill(&mut screen);
+        screen.start_selection(0, 0, SelectionMode::Char);
+        screen.extend_selection(1, 9);
+        assert_eq!(
+            screen.get_selected_text().as_deref(),
+            Some("ab        \n          ")
+        );
+    }
+
+    #[test]
+    fn test_selection_to_rtf() {
+        let mut palette = crate::color::ColorPalette {
+            foreground: Rgb::new(1, 2, 3),
+            background: Rgb::new(4, 5, 6),
+            ..Default::default()
+        };
+        palette.ansi[1] = Rgb::new(7, 8, 9);
+
+        let mut screen = Screen::new(10, 3, ScreenConfig::default());
+        let mut parser = crate::parser::Parser::new();
+        parser.parse(&mut screen, "\x1b[1;31mhi\x1b[0m {x}\r\né\\".as_bytes());
+        assert!(screen.selection_to_rtf(&palette, "Menlo").is_none());
+
+        screen.start_selection(0, 0, SelectionMode::Char);
+        screen.extend_selection(2, 9);
+        assert_eq!(
+            screen.selection_to_rtf(&palette, "Menlo").unwrap(),
+            "{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern Menlo;}}\
+             {\\colortbl;\\red7\\green8\\blue9;\\red4\\green5\\blue6;\\red1\\green2\\blue3;}\n\
+             \\f0\\fs24 \\cf1\\chcbpat2\\cb2\\b\\i0\\ulnone\\strike0 hi\
+             \\cf3\\chcbpat2\\cb2\\b0\\i0\\ulnone\\strike0  \\{x\\}\\par\n\
+             \\u233?\\\\}"
+        );
+    }
+
+    #[test]
+    fn test_resize_reflows_wrapped_lines() {
+        let mut screen = Screen::new(10, 3, ScreenConfig::default());
+        for c in "0123456789abcde".chars() {
+            screen.put_char(c);
+        }
+        assert_eq!((screen.cursor.row, screen.cursor.col), (1, 5));
+
+        // Wider: the wrapped line is joined back together
+        screen.resize(20, 3);
+        assert_eq!(screen.grid().row(0).unwrap().text(), "0123456789abcde");
+        assert!(!screen.grid().row(1).unwrap().wrapped);
+        assert_eq!((screen.cursor.row, screen.cursor.col), (0, 15));
+
+        // Narrower: rows that no longer fit move to scrollback
+        screen.resize(4, 3);
+        assert_eq!(screen.scrollback().len(), 1);
+        assert_eq!(screen.scrollback()[0].text(), "0123");
+        assert_eq!(screen.grid().row(2).unwrap().text(), "cde");
+        assert_eq!((screen.cursor.row, screen.cursor.col), (2, 3));
+
+        // Copying the line does not break it at the old wrap points
+        let start = screen.visible_row_to_absolute_line(0) - 1;
+        let end = screen.visible_row_to_absolute_line(2);
+        screen.start_selection(start, 0, SelectionMode::Char);
+        screen.extend_selection(end, 3);
+        assert_eq!(
+            screen.get_selected_text().as_deref(),
+            Some("0123456789abcde")
+        );
+
+        // Resizing invalidates the selection
+        screen.resize(10, 3);
+        assert!(screen.selection.is_none());
+    }
+
+    #[test]
+    fn test_take_damage() {
+        use crate::damage::{CursorMove, ScrollDelta};
+
+        let mut screen = Screen::new(10, 4, ScreenConfig::default());
+        assert!(screen.take_damage().full);
+        assert!(screen.take_damage().is_empty());
+
+        screen.move_cursor(2, 0);
+        screen.put_char('x');
+        let damage = screen.take_damage();
+        assert_eq!(damage.rows, vec![2..3]);
+        assert_eq!(
+            damage.cursor,
+            Some(CursorMove {
+                from: (0, 0),
+                to: (2, 1)
+            })
+        );
+
+        // Output on the last row scrolls the screen up
+        screen.move_cursor(3, 0);
+        screen.line_feed();
+        screen.put_char('y');
+        let damage = screen.take_damage();
+        assert_eq!(
+            damage.scrolls,
+            [ScrollDelta {
+                top: 0,
+                bottom: 4,
+                lines: 1
+            }]
+        );
+        assert_eq!(damage.rows, vec![3..4]);
+
+        // Selecting repaints the selected rows
+        let line = screen.visible_row_to_absolute_line(1);
+        screen.start_selection(line, 0, SelectionMode::Line);
+        assert_eq!(screen.take_damage().rows, vec![1..2]);
+
+        // Viewing the scrollback repaints everything
+        screen.scroll_offset = 1;
+        assert!(screen.take_damage().full);
+    }
+
     #[test]
     fn test_word_selection_survives_scrollback_wrap() {
         // Regression: when scrollback is full and a line is evicted,
@@ -2397,6 +4837,7 @@ mod tests {
         // the ordered() range expands as new output arrives.
         let config = ScreenConfig {
             scrollback_lines: 2,
+            ..Default::default()
         };
         let mut screen = Screen::new(80, 3, config);
 
@@ -0,0 +1,591 @@
+//! Serial port backend for [`crate::pty::Pty`]
+//!
+//! A serial tab talks to a device on a serial line (a USB adapter, a board's
+//! debug console) instead of a local shell. [`SerialPty`] opens and configures
+//! the port and exposes the same blocking read/write surface as the local
+//! PTY. There is no child process and no window size to report: resizes are
+//! ignored and the session lasts until the device goes away.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pty::PtyError;
+
+/// Parity bit of each character
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Parity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+impl Parity {
+    pub const ALL: [Self; 3] = [Self::None, Self::Odd, Self::Even];
+
+    /// Display name
+    pub fn name(self) -> &'static str {
+        match self {
+            Parity::None => "None",
+            Parity::Odd => "Odd",
+            Parity::Even => "Even",
+        }
+    }
+}
+
+/// Flow control on the line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlowControl {
+    #[default]
+    None,
+    /// XON/XOFF
+    Software,
+    /// RTS/CTS
+    Hardware,
+}
+
+impl FlowControl {
+    pub const ALL: [Self; 3] = [Self::None, Self::Software, Self::Hardware];
+
+    /// Display name
+    pub fn name(self) -> &'static str {
+        match self {
+            FlowControl::None => "None",
+            FlowControl::Software => "XON/XOFF",
+            FlowControl::Hardware => "RTS/CTS",
+        }
+    }
+}
+
+/// Line settings of a serial port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerialConfig {
+    /// Device path, e.g. `/dev/ttyUSB0` or `COM3`
+    pub path: String,
+    pub baud_rate: u32,
+    /// Bits per character, 5 to 8
+    pub data_bits: u8,
+    pub parity: Parity,
+    /// 1 or 2
+    pub stop_bits: u8,
+    pub flow_control: FlowControl,
+}
+
+impl SerialConfig {
+    /// Baud rates offered by the New Serial Tab dialogs
+    pub const BAUD_RATES: &'static [u32] = &[
+        1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600,
+    ];
+
+    /// 8N1 without flow control at `baud_rate`
+    pub fn new(path: impl Into<String>, baud_rate: u32) -> Self {
+        Self {
+            path: path.into(),
+            baud_rate,
+            data_bits: 8,
+            parity: Parity::None,
+            stop_bits: 1,
+            flow_control: FlowControl::None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PtyError> {
+        let invalid = |msg: String| {
+            PtyError::Create(Box::new(io::Error::new(io::ErrorKind::InvalidInput, msg)))
+        };
+        if self.path.is_empty() {
+            return Err(invalid("no serial device given".to_string()));
+        }
+        if self.baud_rate == 0 {
+            return Err(invalid("baud rate must not be zero".to_string()));
+        }
+        if !(5..=8).contains(&self.data_bits) {
+            return Err(invalid(format!(
+                "unsupported data bits: {}",
+                self.data_bits
+            )));
+        }
+        if !matches!(self.stop_bits, 1 | 2) {
+            return Err(invalid(format!(
+                "unsupported stop bits: {}",
+                self.stop_bits
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// An open, configured serial port
+pub struct SerialPty {
+    port: File,
+    /// Set once a read hits EOF or fails, i.e. the device went away
+    closed: Arc<AtomicBool>,
+}
+
+impl SerialPty {
+    pub fn open(config: &SerialConfig) -> Result<Self, PtyError> {
+        config.validate()?;
+        let port = open_port(config)
+            .map_err(|e| PtyError::Create(format!("{}: {e}", config.path).into()))?;
+        Ok(Self {
+            port,
+            closed: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub fn child_pid(&self) -> i32 {
+        // No local child process behind a serial line.
+        -1
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.port.write(data)
+    }
+
+    pub fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let result = read_port(&mut self.port, buf);
+        watch_close(result, buf.len(), &self.closed)
+    }
+
+    pub fn resize(&self, _rows: u16, _cols: u16) -> io::Result<()> {
+        // A serial line has no notion of window size.
+        Ok(())
+    }
+
+    pub fn is_running(&mut self) -> bool {
+        !self.closed.load(Ordering::Relaxed)
+    }
+
+    pub fn wait(&mut self) -> io::Result<i32> {
+        let mut scratch = [0u8; 4096];
+        while self.is_running() {
+            // An error ends the session just like EOF.
+            let _ = self.read(&mut scratch);
+        }
+        Ok(0)
+    }
+
+    pub fn try_wait(&mut self) -> io::Result<Option<i32>> {
+        Ok((!self.is_running()).then_some(0))
+    }
+
+    pub fn send_signal(&self, _signal: i32) -> io::Result<()> {
+        // No process to signal; Ctrl+C and friends travel as bytes.
+        Ok(())
+    }
+
+    pub fn try_clone_reader(&self) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(PortReader {
+            port: self.port.try_clone()?,
+            closed: Arc::clone(&self.closed),
+        }))
+    }
+
+    pub fn try_clone_writer(&self) -> io::Result<File> {
+        self.port.try_clone()
+    }
+}
+
+/// Reader over a cloned port handle, see [`read_port`]
+struct PortReader {
+    port: File,
+    closed: Arc<AtomicBool>,
+}
+
+impl Read for PortReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let result = read_port(&mut self.port, buf);
+        watch_close(result, buf.len(), &self.closed)
+    }
+}
+
+/// Record a read that ended the session (EOF or error) in `closed`
+fn watch_close(result: io::Result<usize>, len: usize, closed: &AtomicBool) -> io::Result<usize> {
+    let ended = match &result {
+        Ok(n) => *n == 0 && len > 0,
+        Err(e) => e.kind() != io::ErrorKind::Interrupted,
+    };
+    if ended {
+        closed.store(true, Ordering::Relaxed);
+    }
+    result
+}
+
+#[cfg(unix)]
+fn read_port(port: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    port.read(buf)
+}
+
+/// Reads on Windows time out (see [`open_port`]) so a pending read does not
+/// hold up writes on the same synchronous handle; a timeout returns no bytes,
+/// which must not be mistaken for EOF.
+#[cfg(windows)]
+fn read_port(port: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+    loop {
+        let n = port.read(buf)?;
+        if n > 0 {
+            return Ok(n);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn open_port(config: &SerialConfig) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+
+    // Non-blocking so the open does not wait for carrier detect.
+    let port = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY | libc::O_NONBLOCK)
+        .open(&config.path)?;
+    let fd = port.as_raw_fd();
+    let check = |ret: libc::c_int| {
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(ret)
+        }
+    };
+
+    unsafe {
+        // Keep other programs off the line while we hold it.
+        libc::ioctl(fd, libc::TIOCEXCL);
+
+        let mut tio: libc::termios = std::mem::zeroed();
+        check(libc::tcgetattr(fd, &mut tio))?;
+        libc::cfmakeraw(&mut tio);
+        let speed = baud_speed(config.baud_rate)?;
+        check(libc::cfsetispeed(&mut tio, speed))?;
+        check(libc::cfsetospeed(&mut tio, speed))?;
+
+        tio.c_cflag &= !(libc::CSIZE | libc::PARENB | libc::PARODD | libc::CSTOPB | libc::CRTSCTS);
+        tio.c_cflag |= libc::CLOCAL | libc::CREAD;
+        tio.c_cflag |= match config.data_bits {
+            5 => libc::CS5,
+            6 => libc::CS6,
+            7 => libc::CS7,
+            _ => libc::CS8,
+        };
+        match config.parity {
+            Parity::None => {}
+            Parity::Odd => tio.c_cflag |= libc::PARENB | libc::PARODD,
+            Parity::Even => tio.c_cflag |= libc::PARENB,
+        }
+        if config.stop_bits == 2 {
+            tio.c_cflag |= libc::CSTOPB;
+        }
+        tio.c_iflag &= !(libc::IXON | libc::IXOFF | libc::IXANY);
+        match config.flow_control {
+            FlowControl::None => {}
+            FlowControl::Software => tio.c_iflag |= libc::IXON | libc::IXOFF,
+            FlowControl::Hardware => tio.c_cflag |= libc::CRTSCTS,
+        }
+        tio.c_cc[libc::VMIN] = 1;
+        tio.c_cc[libc::VTIME] = 0;
+        check(libc::tcsetattr(fd, libc::TCSANOW, &tio))?;
+        libc::tcflush(fd, libc::TCIOFLUSH);
+
+        // Blocking reads from here on, like a local PTY.
+        let flags = check(libc::fcntl(fd, libc::F_GETFL))?;
+        check(libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK))?;
+    }
+    Ok(port)
+}
+
+#[cfg(target_os = "linux")]
+fn baud_speed(rate: u32) -> io::Result<libc::speed_t> {
+    Ok(match rate {
+        50 => libc::B50,
+        75 => libc::B75,
+        110 => libc::B110,
+        134 => libc::B134,
+        150 => libc::B150,
+        200 => libc::B200,
+        300 => libc::B300,
+        600 => libc::B600,
+        1200 => libc::B1200,
+        1800 => libc::B1800,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        230400 => libc::B230400,
+        460800 => libc::B460800,
+        500000 => libc::B500000,
+        576000 => libc::B576000,
+        921600 => libc::B921600,
+        1000000 => libc::B1000000,
+        1152000 => libc::B1152000,
+        1500000 => libc::B1500000,
+        2000000 => libc::B2000000,
+        2500000 => libc::B2500000,
+        3000000 => libc::B3000000,
+        3500000 => libc::B3500000,
+        4000000 => libc::B4000000,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported baud rate: {rate}"),
+            ))
+        }
+    })
+}
+
+/// BSD-style termios takes the rate itself as the speed value.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn baud_speed(rate: u32) -> io::Result<libc::speed_t> {
+    Ok(rate as libc::speed_t)
+}
+
+#[cfg(windows)]
+fn open_port(config: &SerialConfig) -> io::Result<File> {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::commapi::{GetCommState, SetCommState, SetCommTimeouts};
+    use winapi::um::winbase::{
+        COMMTIMEOUTS, DCB, DTR_CONTROL_ENABLE, EVENPARITY, NOPARITY, ODDPARITY, ONESTOPBIT,
+        RTS_CONTROL_ENABLE, RTS_CONTROL_HANDSHAKE, TWOSTOPBITS,
+    };
+
+    // COM10 and up are only reachable through the device namespace.
+    let path = if config.path.starts_with(r"\\.\") {
+        config.path.clone()
+    } else {
+        format!(r"\\.\{}", config.path)
+    };
+    let port = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    let handle = port.as_raw_handle() as winapi::um::winnt::HANDLE;
+    let hardware = config.flow_control == FlowControl::Hardware;
+    let software = config.flow_control == FlowControl::Software;
+
+    unsafe {
+        let mut dcb: DCB = std::mem::zeroed();
+        dcb.DCBlength = std::mem::size_of::<DCB>() as DWORD;
+        if GetCommState(handle, &mut dcb) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        dcb.BaudRate = config.baud_rate;
+        dcb.ByteSize = config.data_bits;
+        dcb.Parity = match config.parity {
+            Parity::None => NOPARITY,
+            Parity::Odd => ODDPARITY,
+            Parity::Even => EVENPARITY,
+        };
+        dcb.StopBits = if config.stop_bits == 2 {
+            TWOSTOPBITS
+        } else {
+            ONESTOPBIT
+        };
+        dcb.set_fBinary(1);
+        dcb.set_fParity((config.parity != Parity::None) as DWORD);
+        dcb.set_fOutxCtsFlow(hardware as DWORD);
+        dcb.set_fOutxDsrFlow(0);
+        dcb.set_fDtrControl(DTR_CONTROL_ENABLE);
+        dcb.set_fRtsControl(if hardware {
+            RTS_CONTROL_HANDSHAKE
+        } else {
+            RTS_CONTROL_ENABLE
+        });
+        dcb.set_fOutX(software as DWORD);
+        dcb.set_fInX(software as DWORD);
+        if SetCommState(handle, &mut dcb) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Return as soon as a byte is there, or after 50ms with nothing so a
+        // pending read never blocks writes for long (see `read_port`).
+        let mut timeouts = COMMTIMEOUTS {
+            ReadIntervalTimeout: DWORD::MAX,
+            ReadTotalTimeoutMultiplier: DWORD::MAX,
+            ReadTotalTimeoutConstant: 50,
+            WriteTotalTimeoutMultiplier: 0,
+            WriteTotalTimeoutConstant: 0,
+        };
+        if SetCommTimeouts(handle, &mut timeouts) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(port)
+}
+
+/// Serial devices present on this machine, for the New Serial Tab dialogs
+///
+/// Best effort: an empty list just means the user has to type the path.
+#[cfg(target_os = "linux")]
+pub fn available_ports() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/sys/class/tty") else {
+        return Vec::new();
+    };
+    let mut ports: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let device = entry.path().join("device");
+            // Virtual consoles and ptys have no backing device.
+            if !device.exists() {
+                return None;
+            }
+            // The legacy 8250 driver registers placeholder ttyS ports whether
+            // or not the hardware is there; those hang off the platform bus.
+            let subsystem = std::fs::read_link(device.join("subsystem")).ok();
+            if name.starts_with("ttyS")
+                && subsystem.is_some_and(|s| s.file_name().is_some_and(|n| n == "platform"))
+            {
+                return None;
+            }
+            Some(format!("/dev/{name}"))
+        })
+        .collect();
+    ports.sort();
+    ports
+}
+
+/// Serial devices present on this machine, for the New Serial Tab dialogs
+///
+/// Lists the call-out devices (`cu.*` on macOS, `cua*` on the BSDs), which
+/// open without waiting for carrier detect.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn available_ports() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/dev") else {
+        return Vec::new();
+    };
+    let mut ports: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with("cu.") || name.starts_with("cua"))
+        .map(|name| format!("/dev/{name}"))
+        .collect();
+    ports.sort();
+    ports
+}
+
+/// Serial devices present on this machine, for the New Serial Tab dialogs
+#[cfg(windows)]
+pub fn available_ports() -> Vec<String> {
+    use winapi::shared::winerror::ERROR_INSUFFICIENT_BUFFER;
+    use winapi::um::fileapi::QueryDosDeviceW;
+
+    let mut buf = vec![0u16; 16 * 1024];
+    let len = loop {
+        let len = unsafe { QueryDosDeviceW(std::ptr::null(), buf.as_mut_ptr(), buf.len() as u32) };
+        if len != 0 {
+            break len as usize;
+        }
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as i32) || buf.len() > 1 << 22 {
+            return Vec::new();
+        }
+        buf.resize(buf.len() * 2, 0);
+    };
+
+    let mut ports: Vec<String> = buf[..len]
+        .split(|&c| c == 0)
+        .map(String::from_utf16_lossy)
+        .filter(|name| {
+            name.strip_prefix("COM")
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .collect();
+    ports.sort_by_key(|name| name[3..].parse::<u32>().unwrap_or(u32::MAX));
+    ports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serial_config_validate() {
+        let config = SerialConfig::new("/dev/ttyUSB0", 115200);
+        assert_eq!(config.data_bits, 8);
+        assert_eq!(config.stop_bits, 1);
+        assert!(config.validate().is_ok());
+
+        for bad in [
+            SerialConfig::new("", 9600),
+            SerialConfig::new("/dev/ttyUSB0", 0),
+            SerialConfig {
+                data_bits: 9,
+                ..config.clone()
+            },
+            SerialConfig {
+                stop_bits: 3,
+                ..config.clone()
+            },
+        ] {
+            assert!(bad.validate().is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_serial_pty_over_pty_pair() {
+        use std::os::unix::io::FromRawFd;
+
+        // The slave side of a pseudo-terminal stands in for a serial device.
+        let mut master = -1;
+        let mut slave = -1;
+        let ret = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        assert_eq!(ret, 0);
+        let mut master = unsafe { File::from_raw_fd(master) };
+        let name = unsafe { libc::ttyname(slave) };
+        assert!(!name.is_null());
+        let path = unsafe { std::ffi::CStr::from_ptr(name) }
+            .to_string_lossy()
+            .into_owned();
+        let slave = unsafe { File::from_raw_fd(slave) };
+
+        let config = SerialConfig {
+            parity: Parity::Even,
+            flow_control: FlowControl::Software,
+            ..SerialConfig::new(path, 9600)
+        };
+        let mut port = SerialPty::open(&config).expect("open serial port");
+        drop(slave);
+        assert_eq!(port.child_pid(), -1);
+        assert!(port.resize(24, 80).is_ok());
+
+        port.write(b"ping").unwrap();
+        let mut buf = [0u8; 16];
+        let n = master.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"ping");
+
+        master.write_all(b"pong").unwrap();
+        let mut reader = port.try_clone_reader().unwrap();
+        let n = reader.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"pong");
+        assert!(port.is_running());
+        assert_eq!(port.try_wait().unwrap(), None);
+
+        // Hanging up the other end is the device going away.
+        drop(master);
+        let _ = reader.read(&mut buf);
+        assert!(!port.is_running());
+        assert_eq!(port.try_wait().unwrap(), Some(0));
+    }
+}
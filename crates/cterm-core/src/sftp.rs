@@ -0,0 +1,224 @@
+//! SFTP file browsing and transfer over a native SSH session.
+//!
+//! [`RemoteFs`] runs the `sftp` subsystem on an extra channel of the
+//! connection behind an [`SshPty`](crate::ssh::SshPty), so the transfer
+//! panel reuses the tab's already-authenticated connection instead of
+//! logging in again. All operations are blocking.
+
+use std::io;
+
+use puressh::sftp::{Attrs, NameEntry, FXF_CREAT, FXF_READ, FXF_TRUNC, FXF_WRITE};
+use puressh::shared::SftpSession;
+
+/// POSIX file-type mask and directory bits of `st_mode`.
+const S_IFMT: u32 = 0o170000;
+const S_IFDIR: u32 = 0o040000;
+
+/// Largest single SFTP read/write request; servers commonly cap at 32 KiB.
+const SFTP_CHUNK: usize = 32 * 1024;
+
+/// One entry of a remote directory listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry {
+    /// Bare file name (no path components)
+    pub name: String,
+    /// Whether the entry is a directory
+    pub is_dir: bool,
+    /// Size in bytes (0 when the server does not report it)
+    pub size: u64,
+    /// Modification time as Unix seconds (0 when unknown)
+    pub mtime: u32,
+}
+
+impl RemoteEntry {
+    fn from_name_entry(entry: &NameEntry) -> Self {
+        Self {
+            name: String::from_utf8_lossy(&entry.filename).into_owned(),
+            is_dir: entry
+                .attrs
+                .permissions
+                .is_some_and(|mode| mode & S_IFMT == S_IFDIR),
+            size: entry.attrs.size.unwrap_or(0),
+            mtime: entry.attrs.atime_mtime.map(|(_, m)| m).unwrap_or(0),
+        }
+    }
+}
+
+/// An SFTP session on an existing SSH connection.
+pub struct RemoteFs {
+    session: SftpSession,
+}
+
+impl RemoteFs {
+    pub(crate) fn new(session: SftpSession) -> Self {
+        Self { session }
+    }
+
+    /// Resolve `path` to an absolute remote path (`"."` is the login directory).
+    pub fn realpath(&mut self, path: &str) -> io::Result<String> {
+        let path = if path.is_empty() { "." } else { path };
+        let resolved = self.session.realpath(path.as_bytes()).map_err(sftp_err)?;
+        Ok(String::from_utf8_lossy(&resolved).into_owned())
+    }
+
+    /// List a remote directory, directories first, then by name.
+    ///
+    /// `.` and `..` are omitted; use [`parent_remote_path`] to go up.
+    pub fn list_dir(&mut self, path: &str) -> io::Result<Vec<RemoteEntry>> {
+        let handle = self.session.opendir(path.as_bytes()).map_err(sftp_err)?;
+        let mut names = Vec::new();
+        let result = loop {
+            match self.session.readdir(&handle) {
+                Ok(Some(batch)) => names.extend(batch),
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(sftp_err(e)),
+            }
+        };
+        let _ = self.session.close(&handle);
+        result?;
+        Ok(entries_from_names(&names))
+    }
+
+    /// Size of a remote file in bytes.
+    pub fn file_size(&mut self, path: &str) -> io::Result<u64> {
+        let attrs = self.session.stat(path.as_bytes()).map_err(sftp_err)?;
+        Ok(attrs.size.unwrap_or(0))
+    }
+
+    /// Read up to `len` bytes of `path` starting at `offset`.
+    ///
+    /// Returns fewer bytes only at end of file.
+    pub fn read_at(&mut self, path: &str, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let handle = self
+            .session
+            .open(path.as_bytes(), FXF_READ, Attrs::default())
+            .map_err(sftp_err)?;
+        let mut data = Vec::with_capacity(len.min(SFTP_CHUNK * 32));
+        let result = loop {
+            if data.len() >= len {
+                break Ok(());
+            }
+            let want = (len - data.len()).min(SFTP_CHUNK) as u32;
+            match self.session.read(&handle, offset + data.len() as u64, want) {
+                Ok(chunk) if chunk.is_empty() => break Ok(()),
+                Ok(chunk) => data.extend_from_slice(&chunk),
+                Err(e) => break Err(sftp_err(e)),
+            }
+        };
+        let _ = self.session.close(&handle);
+        result.map(|()| data)
+    }
+
+    /// Write `data` to `path` at `offset`, creating the file if needed.
+    ///
+    /// With `truncate`, any existing contents are discarded first (used for
+    /// the first chunk of an upload).
+    pub fn write_at(
+        &mut self,
+        path: &str,
+        offset: u64,
+        data: &[u8],
+        truncate: bool,
+    ) -> io::Result<()> {
+        let mut flags = FXF_WRITE | FXF_CREAT;
+        if truncate {
+            flags |= FXF_TRUNC;
+        }
+        let handle = self
+            .session
+            .open(path.as_bytes(), flags, Attrs::default())
+            .map_err(sftp_err)?;
+        let mut result = Ok(());
+        for (i, chunk) in data.chunks(SFTP_CHUNK).enumerate() {
+            let at = offset + (i * SFTP_CHUNK) as u64;
+            if let Err(e) = self.session.write(&handle, at, chunk) {
+                result = Err(sftp_err(e));
+                break;
+            }
+        }
+        let _ = self.session.close(&handle);
+        result
+    }
+}
+
+/// Join a remote directory and a file name with `/`.
+pub fn join_remote_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else if dir.ends_with('/') {
+        format!("{dir}{name}")
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+/// Parent of an absolute remote path (`/` is its own parent).
+pub fn parent_remote_path(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    match trimmed.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(idx) => trimmed[..idx].to_string(),
+    }
+}
+
+fn entries_from_names(names: &[NameEntry]) -> Vec<RemoteEntry> {
+    let mut entries: Vec<RemoteEntry> = names
+        .iter()
+        .filter(|n| n.filename != b"." && n.filename != b"..")
+        .map(RemoteEntry::from_name_entry)
+        .collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    entries
+}
+
+fn sftp_err(e: puressh::sftp::SftpError) -> io::Error {
+    match e {
+        puressh::sftp::SftpError::Io(e) => e,
+        other => io::Error::other(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(filename: &str, mode: u32, size: u64) -> NameEntry {
+        NameEntry {
+            filename: filename.as_bytes().to_vec(),
+            longname: Vec::new(),
+            attrs: Attrs {
+                size: Some(size),
+                permissions: Some(mode),
+                atime_mtime: Some((0, 1_700_000_000)),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_entries_from_names() {
+        let names = vec![
+            name(".", 0o040755, 0),
+            name("..", 0o040755, 0),
+            name("zeta.txt", 0o100644, 12),
+            name("src", 0o040755, 4096),
+            name("alpha.txt", 0o100644, 3),
+        ];
+        let entries = entries_from_names(&names);
+        let listed: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(listed, ["src", "alpha.txt", "zeta.txt"]);
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[2].size, 12);
+        assert_eq!(entries[2].mtime, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_remote_paths() {
+        assert_eq!(join_remote_path("/home/me", "a.txt"), "/home/me/a.txt");
+        assert_eq!(join_remote_path("/", "etc"), "/etc");
+        assert_eq!(parent_remote_path("/home/me"), "/home");
+        assert_eq!(parent_remote_path("/home/me/"), "/home");
+        assert_eq!(parent_remote_path("/home"), "/");
+        assert_eq!(parent_remote_path("/"), "/");
+    }
+}
@@ -1,8 +1,10 @@
-//! Sixel graphics decoder
+//! Sixel graphics decoder and encoder
 //!
 //! Implements the DEC Sixel graphics protocol for inline bitmap images.
 //! Sixel is a format where each character (63-126) represents 6 vertical pixels.
 
+use std::collections::HashMap;
+
 /// Decoded sixel image as RGBA pixels
 #[derive(Debug, Clone)]
 pub struct SixelImage {
@@ -466,6 +468,245 @@ impl Default for SixelDecoder {
     }
 }
 
+/// Number of palette registers the encoder uses at most
+const MAX_SIXEL_COLORS: usize = 256;
+
+/// Pixels with less alpha than this are left unpainted by the encoder
+const OPAQUE_ALPHA: u8 = 128;
+
+/// Sixel encoder
+///
+/// Turns RGBA pixels (a [`SixelImage`], a `DecodedImage` or a rendered
+/// screen) into sixel data. The colors are quantized to a palette of at most
+/// `max_colors` registers with median cut, and each 6-pixel band is written
+/// as one run-length encoded line per color. Transparent pixels are not
+/// painted, so they keep the terminal background.
+#[derive(Debug, Clone)]
+pub struct SixelEncoder {
+    /// Maximum number of palette registers (2-256)
+    max_colors: usize,
+}
+
+impl SixelEncoder {
+    /// Create an encoder using up to 256 colors
+    pub fn new() -> Self {
+        Self {
+            max_colors: MAX_SIXEL_COLORS,
+        }
+    }
+
+    /// Create an encoder using up to `max_colors` colors, for terminals with
+    /// fewer palette registers
+    pub fn with_max_colors(max_colors: usize) -> Self {
+        Self {
+            max_colors: max_colors.clamp(2, MAX_SIXEL_COLORS),
+        }
+    }
+
+    /// Encode an image as a complete DCS sequence, ready to write to a
+    /// terminal
+    ///
+    /// `data` holds `width * height` RGBA pixels, row by row.
+    pub fn encode(&self, data: &[u8], width: usize, height: usize) -> Vec<u8> {
+        // P2=1: unpainted pixels keep the background
+        let mut out = b"\x1bP0;1;0q".to_vec();
+        out.extend_from_slice(&self.encode_data(data, width, height));
+        out.extend_from_slice(b"\x1b\\");
+        out
+    }
+
+    /// Encode an image as the sixel data between the DCS introducer and the
+    /// string terminator, which is what [`SixelDecoder::put`] takes
+    pub fn encode_data(&self, data: &[u8], width: usize, height: usize) -> Vec<u8> {
+        let mut out = format!("\"1;1;{};{}", width, height).into_bytes();
+        if width == 0 || height == 0 {
+            return out;
+        }
+
+        let pixel = |x: usize, y: usize| -> Option<[u8; 3]> {
+            let idx = (y * width + x) * 4;
+            let rgba = data.get(idx..idx + 4)?;
+            (rgba[3] >= OPAQUE_ALPHA).then_some([rgba[0], rgba[1], rgba[2]])
+        };
+
+        // Histogram of the opaque colors
+        let mut histogram: HashMap<[u8; 3], u32> = HashMap::new();
+        for y in 0..height {
+            for x in 0..width {
+                if let Some(rgb) = pixel(x, y) {
+                    *histogram.entry(rgb).or_insert(0) += 1;
+                }
+            }
+        }
+        let palette = quantize(histogram.into_iter().collect(), self.max_colors);
+
+        for (i, [r, g, b]) in palette.iter().enumerate() {
+            let percent = |v: u8| (v as usize * 100 + 127) / 255;
+            out.extend_from_slice(
+                format!("#{};2;{};{};{}", i, percent(*r), percent(*g), percent(*b)).as_bytes(),
+            );
+        }
+
+        // Palette index of every pixel, None where transparent
+        let mut nearest: HashMap<[u8; 3], u8> = HashMap::new();
+        let indices: Vec<Option<u8>> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let rgb = pixel(x, y)?;
+                Some(
+                    *nearest
+                        .entry(rgb)
+                        .or_insert_with(|| nearest_color(&palette, rgb)),
+                )
+            })
+            .collect();
+
+        let mut line = vec![0u8; width];
+        for band in 0..height.div_ceil(6) {
+            if band > 0 {
+                out.push(b'-');
+            }
+            let top = band * 6;
+            let rows = (height - top).min(6);
+
+            let mut colors: Vec<u8> = indices[top * width..(top + rows) * width]
+                .iter()
+                .flatten()
+                .copied()
+                .collect();
+            colors.sort_unstable();
+            colors.dedup();
+
+            for (n, &color) in colors.iter().enumerate() {
+                for (x, bits) in line.iter_mut().enumerate() {
+                    *bits = (0..rows)
+                        .filter(|row| indices[(top + row) * width + x] == Some(color))
+                        .fold(0, |bits, row| bits | (1 << row));
+                }
+                if n > 0 {
+                    out.push(b'$');
+                }
+                out.extend_from_slice(format!("#{}", color).as_bytes());
+                push_runs(&mut out, &line);
+            }
+        }
+        out
+    }
+}
+
+impl Default for SixelEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reduce the colors of a histogram to at most `max_colors` with median cut
+///
+/// Each box of colors is split at its weighted median along its widest
+/// channel until there are enough boxes; the palette is the weighted mean
+/// of each box.
+fn quantize(mut colors: Vec<([u8; 3], u32)>, max_colors: usize) -> Vec<[u8; 3]> {
+    if colors.len() <= max_colors {
+        colors.sort_unstable();
+        return colors.into_iter().map(|(rgb, _)| rgb).collect();
+    }
+
+    let mut boxes = vec![colors];
+    while boxes.len() < max_colors {
+        // The box with the widest channel range, and that channel
+        let Some((index, channel, _)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(i, colors)| {
+                let (channel, range) = (0..3)
+                    .map(|c| {
+                        let min = colors.iter().map(|(rgb, _)| rgb[c]).min().unwrap_or(0);
+                        let max = colors.iter().map(|(rgb, _)| rgb[c]).max().unwrap_or(0);
+                        (c, max - min)
+                    })
+                    .max_by_key(|&(_, range)| range)
+                    .unwrap_or((0, 0));
+                (i, channel, range)
+            })
+            .max_by_key(|&(_, _, range)| range)
+        else {
+            break;
+        };
+
+        let mut colors = boxes.swap_remove(index);
+        colors.sort_unstable_by_key(|(rgb, _)| rgb[channel]);
+        let total: u64 = colors.iter().map(|&(_, count)| count as u64).sum();
+        let mut seen = 0u64;
+        let median = colors
+            .iter()
+            .position(|&(_, count)| {
+                seen += count as u64;
+                seen * 2 >= total
+            })
+            .unwrap_or(0);
+        // Both halves keep at least one color
+        let split = (median + 1).clamp(1, colors.len() - 1);
+        let upper = colors.split_off(split);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .map(|colors| {
+            let total: u64 = colors.iter().map(|&(_, count)| count as u64).sum();
+            let mean = |c: usize| {
+                let sum: u64 = colors
+                    .iter()
+                    .map(|&(rgb, count)| rgb[c] as u64 * count as u64)
+                    .sum();
+                ((sum + total / 2) / total.max(1)) as u8
+            };
+            [mean(0), mean(1), mean(2)]
+        })
+        .collect()
+}
+
+/// Index of the palette color closest to `rgb`
+fn nearest_color(palette: &[[u8; 3]], rgb: [u8; 3]) -> u8 {
+    let distance = |p: &[u8; 3]| -> i32 {
+        (0..3)
+            .map(|c| {
+                let d = p[c] as i32 - rgb[c] as i32;
+                d * d
+            })
+            .sum()
+    };
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, p)| distance(p))
+        .map_or(0, |(i, _)| i as u8)
+}
+
+/// Append one color line of sixel bits, run-length encoded and without its
+/// trailing empty sixels
+fn push_runs(out: &mut Vec<u8>, line: &[u8]) {
+    let end = line
+        .iter()
+        .rposition(|&bits| bits != 0)
+        .map_or(0, |i| i + 1);
+    let mut x = 0;
+    while x < end {
+        let bits = line[x];
+        let run = line[x..end].iter().take_while(|&&b| b == bits).count();
+        let sixel = bits + 63;
+        if run > 3 {
+            out.extend_from_slice(format!("!{}", run).as_bytes());
+            out.push(sixel);
+        } else {
+            out.extend(std::iter::repeat_n(sixel, run));
+        }
+        x += run;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -568,4 +809,78 @@ mod tests {
         let image = decoder.finish().unwrap();
         assert_eq!(image.width, 3); // Max width is still 3
     }
+
+    /// Feed encoded sixel data through the decoder
+    fn round_trip(encoder: &SixelEncoder, data: &[u8], width: usize, height: usize) -> SixelImage {
+        let mut decoder = SixelDecoder::with_params(&[0, 1, 0]);
+        for &byte in &encoder.encode_data(data, width, height) {
+            decoder.put(byte);
+        }
+        decoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        // 3x7 image: red, green and blue columns, with a transparent pixel
+        let mut data = Vec::new();
+        for y in 0..7 {
+            for x in 0..3 {
+                let mut rgba = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]][x];
+                if (x, y) == (1, 3) {
+                    rgba = [0, 0, 0, 0];
+                }
+                data.extend_from_slice(&rgba);
+            }
+        }
+
+        let image = round_trip(&SixelEncoder::new(), &data, 3, 7);
+        assert_eq!(image.width, 3);
+        // Decoded height is a whole number of bands
+        assert_eq!(image.height, 12);
+        for y in 0..7 {
+            for x in 0..3 {
+                let idx = (y * 3 + x) * 4;
+                assert_eq!(
+                    image.data[idx..idx + 4],
+                    data[idx..idx + 4],
+                    "({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_run_length() {
+        let data = [255, 255, 255, 255].repeat(10 * 6);
+        let out = SixelEncoder::new().encode(&data, 10, 6);
+        assert_eq!(out, b"\x1bP0;1;0q\"1;1;10;6#0;2;100;100;100#0!10~\x1b\\");
+    }
+
+    #[test]
+    fn test_encode_quantizes_palette() {
+        // A gradient with 64 colors, reduced to 4
+        let data: Vec<u8> = (0..64u8)
+            .flat_map(|v| [v * 4, 0, 255 - v * 4, 255])
+            .collect();
+        let encoder = SixelEncoder::with_max_colors(4);
+        let out = String::from_utf8(encoder.encode_data(&data, 64, 1)).unwrap();
+        assert!(out.contains("#3;2;"));
+        assert!(!out.contains("#4;2;"));
+
+        let image = round_trip(&encoder, &data, 64, 1);
+        assert_eq!(image.width, 64);
+        // Each pixel ends up near its original color
+        for x in 0..64 {
+            let (orig, decoded) = (data[x * 4] as i32, image.data[x * 4] as i32);
+            assert!(
+                (orig - decoded).abs() <= 40,
+                "{}: {} vs {}",
+                x,
+                orig,
+                decoded
+            );
+        }
+    }
 }
@@ -18,6 +18,7 @@ use std::io::Write;
 use std::io::{self, Read};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use sha2::{Digest, Sha256};
 
@@ -128,6 +129,11 @@ pub struct SshConfig {
     /// server also supports it; falls back to `none` otherwise. Worthwhile for
     /// the gRPC tunnel, where screen snapshots and scrollback compress well.
     pub compress: bool,
+    /// Send a `ping@openssh.com` at this interval (like OpenSSH's
+    /// `ServerAliveInterval`), keeping NAT/firewall state alive across idle
+    /// periods and surfacing dead connections as a write error. `None`
+    /// disables keepalives.
+    pub keepalive_interval: Option<Duration>,
 
     /// Prompt for accepting unknown/changed host keys.
     pub host_key_prompt: Option<HostKeyPrompt>,
@@ -148,7 +154,7 @@ pub struct SshPty {
     stream: Mutex<Option<OwnedChannelStream>>,
     /// Last requested size, for completeness.
     size: Mutex<PtySize>,
-    /// Stop flag for `-L` forward listener threads; set on drop.
+    /// Stop flag for `-L` forward listener and keepalive threads; set on drop.
     forwards_stop: Arc<std::sync::atomic::AtomicBool>,
 }
 
@@ -169,6 +175,9 @@ impl SshPty {
         for fwd in &config.local_forwards {
             start_local_forward(shared.clone(), fwd.clone(), Arc::clone(&forwards_stop));
         }
+        if let Some(interval) = config.keepalive_interval {
+            start_keepalive(shared.clone(), interval, Arc::clone(&forwards_stop));
+        }
 
         Ok(Self {
             client: shared,
@@ -246,6 +255,15 @@ impl SshPty {
         Ok(())
     }
 
+    /// Open an SFTP session on this connection, reusing its authentication.
+    pub fn open_sftp(&self) -> Result<crate::sftp::RemoteFs, PtyError> {
+        let session = self
+            .client
+            .sftp()
+            .map_err(|e| PtyError::Spawn(format!("SFTP subsystem request failed: {e}")))?;
+        Ok(crate::sftp::RemoteFs::new(session))
+    }
+
     /// Hand the channel stream to a reader (the daemon's per-session thread).
     ///
     /// `OwnedChannelStream` is itself a blocking `Read + Send`, so it *is* the
@@ -321,6 +339,44 @@ fn start_local_forward(
     });
 }
 
+/// How often the keepalive thread wakes to check its stop flag, and the read
+/// timeout that lets it take the client lock while the reader is idle.
+const KEEPALIVE_TICK: Duration = Duration::from_millis(100);
+
+/// Start a keepalive thread that pings the server every `interval`.
+///
+/// The pump holds the client lock while it blocks on the socket, so a read
+/// timeout is set first to make it yield periodically. The thread exits on
+/// the first failed ping (the reader sees the broken connection as EOF).
+fn start_keepalive(
+    client: SharedClient,
+    interval: Duration,
+    stop: Arc<std::sync::atomic::AtomicBool>,
+) {
+    use std::sync::atomic::Ordering;
+
+    if let Err(e) = client.set_read_timeout(Some(KEEPALIVE_TICK)) {
+        log::warn!("SSH keepalive disabled: failed to set read timeout: {e}");
+        return;
+    }
+    let interval = interval.max(Duration::from_secs(1));
+    std::thread::spawn(move || {
+        let mut waited = Duration::ZERO;
+        while !stop.load(Ordering::Relaxed) {
+            std::thread::sleep(KEEPALIVE_TICK);
+            waited += KEEPALIVE_TICK;
+            if waited < interval {
+                continue;
+            }
+            waited = Duration::ZERO;
+            if let Err(e) = client.send_ping(b"") {
+                log::warn!("SSH keepalive failed: {e}");
+                break;
+            }
+        }
+    });
+}
+
 /// Bidirectionally splice a TCP stream and a `direct-tcpip` channel. Reads from
 /// the channel use the owned stream; writes go through the shared client by
 /// channel id, so the two directions run on independent threads.
@@ -2,9 +2,27 @@
 //!
 //! Provides a high-level interface for terminal emulation.
 
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::color::ColorScheme;
+use crate::file_transfer::{
+    self, FileTransfer, TransferDirection, TransferEvent, TransferFile, TransferProtocol,
+};
+use crate::kitty_keyboard::{self, KeyEventKind};
 use crate::parser::Parser;
+use crate::permissions::{Capability, Permission, PermissionPolicy};
 use crate::pty::{Pty, PtyConfig, PtyError};
-use crate::screen::{ClipboardOperation, Screen, ScreenConfig, SearchResult};
+use crate::recording::Recorder;
+use crate::screen::{
+    ClipboardOperation, CommandFinished, Notification, PointerShape, Screen, ScreenConfig,
+    SearchResult, TmuxControl,
+};
+use crate::throughput::{OutputStats, ThroughputMeter};
+use crate::watch::{LineHighlight, Watch, WatchMatch};
 
 /// Events emitted by the terminal
 #[derive(Debug, Clone)]
@@ -19,6 +37,43 @@ pub enum TerminalEvent {
     ContentChanged,
     /// Clipboard operation requested (OSC 52)
     ClipboardRequest(ClipboardOperation),
+    /// A user variable was set or, with `value` None, unset (OSC 1337
+    /// SetUserVar)
+    UserVarChanged { name: String, value: Option<String> },
+    /// The shell's working directory changed (OSC 7 or OSC 1337 CurrentDir);
+    /// empty when it became unknown, e.g. on reset
+    CwdChanged(String),
+    /// The program asked for another mouse pointer shape (OSC 22); back to
+    /// [`PointerShape::Default`] on reset
+    PointerShapeChanged(PointerShape),
+    /// A shell command finished (OSC 133 D after OSC 133 C)
+    CommandFinished(CommandFinished),
+    /// A ZMODEM or Kermit transfer started; output is consumed by it until it
+    /// ends
+    TransferStarted(TransferProtocol, TransferDirection),
+    /// The remote is waiting for files, see [`Terminal::send_transfer_files`]
+    TransferSendRequested,
+    /// The transfer ended; received files were queued as file transfers
+    TransferFinished,
+    /// The application asked for files (OSC 1337 RequestUpload); answer with
+    /// [`crate::iterm2::encode_upload`] or [`crate::iterm2::UPLOAD_ABORT`]
+    UploadRequested,
+    /// Output rates over the last second, sent while output flows and once
+    /// more after it stops (see also [`Terminal::poll_output_stats`])
+    OutputStats(OutputStats),
+    /// tmux control mode started, sent protocol lines or ended; none of it
+    /// is drawn on the screen
+    TmuxControl(Vec<TmuxControl>),
+    /// New output matched the watch expression (see [`Terminal::set_watch`])
+    WatchMatched(WatchMatch),
+    /// Output lines a line feed ended, while line events are on (see
+    /// [`Terminal::set_line_events`])
+    LinesCompleted(Vec<String>),
+    /// The program asked for a desktop notification (OSC 9, OSC 777)
+    Notification(Notification),
+    /// Something the policy asks about is waiting; answer with
+    /// [`Terminal::answer_permission`]. Sent once until answered.
+    PermissionRequested(Capability),
 }
 
 /// Terminal configuration
@@ -30,6 +85,38 @@ pub struct TerminalConfig {
     pub pty: PtyConfig,
 }
 
+/// Version of the [`Terminal::serialize`] format
+const SNAPSHOT_VERSION: u32 = 11;
+
+/// Errors restoring or saving a terminal snapshot
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Unsupported snapshot version {0} (expected {SNAPSHOT_VERSION})")]
+    Version(u32),
+
+    #[error("Invalid snapshot: {0}")]
+    Encoding(#[from] bincode::Error),
+}
+
+/// Terminal state written by [`Terminal::serialize`]
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    screen: &'a Screen,
+    last_title: &'a str,
+    input_locked: bool,
+}
+
+/// Terminal state read by [`Terminal::deserialize`]
+#[derive(Deserialize)]
+struct Snapshot {
+    screen: Screen,
+    last_title: String,
+    input_locked: bool,
+}
+
+/// Most output kept while a file transfer waits for permission
+const MAX_HELD_TRANSFER: usize = 1 << 20;
+
 /// Callback for writing data when no PTY is present (e.g., daemon mode)
 pub type WriteFn = Box<dyn Fn(&[u8]) -> Result<(), PtyError> + Send + Sync>;
 
@@ -40,17 +127,56 @@ pub struct Terminal {
     pty: Option<Pty>,
     write_fn: Option<WriteFn>,
     last_title: String,
+    /// Drop user input (keys, paste, mouse reports) while set
+    input_locked: bool,
+    /// Whether ZMODEM and Kermit transfers are detected in the output
+    transfers_enabled: bool,
+    /// Transfer in progress
+    transfer: Option<FileTransfer>,
+    /// When this terminal was created
+    created: Instant,
+    /// Asciicast recording of the output, if one is running
+    recorder: Option<Recorder>,
+    /// Output rates reported as [`TerminalEvent::OutputStats`]
+    throughput: ThroughputMeter,
+    /// Expression counted over new output, see [`Self::set_watch`]
+    watch: Option<Watch>,
+    /// Whether completed lines are reported, see [`Self::set_line_events`]
+    line_events: bool,
+    /// What programs may do through sensitive escape sequences
+    permissions: PermissionPolicy,
+    /// Events waiting for the user's permission
+    held: Vec<(Capability, TerminalEvent)>,
+    /// A file transfer waiting for permission, with the output since it
+    /// started
+    held_transfer: Option<(TransferProtocol, TransferDirection, Vec<u8>)>,
 }
 
 impl Terminal {
     /// Create a new terminal with the given dimensions
     pub fn new(cols: usize, rows: usize, config: ScreenConfig) -> Self {
+        Self::with_screen(Screen::new(cols, rows, config))
+    }
+
+    /// Create a terminal showing `screen`, with no PTY
+    fn with_screen(screen: Screen) -> Self {
         Self {
-            screen: Screen::new(cols, rows, config),
+            screen,
             parser: Parser::new(),
             pty: None,
             write_fn: None,
             last_title: String::new(),
+            input_locked: false,
+            transfers_enabled: false,
+            transfer: None,
+            created: Instant::now(),
+            recorder: None,
+            throughput: ThroughputMeter::new(Instant::now()),
+            watch: None,
+            line_events: false,
+            permissions: PermissionPolicy::default(),
+            held: Vec::new(),
+            held_transfer: None,
         }
     }
 
@@ -68,14 +194,16 @@ impl Terminal {
         let pty = Pty::new(&config)?;
 
         Ok(Self {
-            screen: Screen::new(cols, rows, screen_config),
-            parser: Parser::new(),
             pty: Some(pty),
-            write_fn: None,
-            last_title: String::new(),
+            ..Self::new(cols, rows, screen_config)
         })
     }
 
+    /// Time since this terminal was created (or restored)
+    pub fn age(&self) -> Duration {
+        self.created.elapsed()
+    }
+
     /// Get a reference to the screen
     pub fn screen(&self) -> &Screen {
         &self.screen
@@ -114,6 +242,42 @@ impl Terminal {
         self.pty.take()
     }
 
+    /// Checkpoint the terminal state: the grid, scrollback, alternate screen,
+    /// cursor and saved cursors, modes, tab stops, character sets, selection
+    /// and images
+    ///
+    /// The PTY, write callback and any escape sequence cut off mid-way are not
+    /// part of the snapshot.
+    pub fn serialize(&self) -> Result<Vec<u8>, SnapshotError> {
+        let mut data = bincode::serialize(&SNAPSHOT_VERSION)?;
+        bincode::serialize_into(
+            &mut data,
+            &SnapshotRef {
+                screen: &self.screen,
+                last_title: &self.last_title,
+                input_locked: self.input_locked,
+            },
+        )?;
+        Ok(data)
+    }
+
+    /// Restore a terminal saved with [`Terminal::serialize`]
+    ///
+    /// The terminal has no PTY; attach one with [`Terminal::set_pty`] or
+    /// [`Terminal::set_write_fn`]. Everything is damaged so it gets redrawn.
+    pub fn deserialize(mut data: &[u8]) -> Result<Self, SnapshotError> {
+        let version: u32 = bincode::deserialize_from(&mut data)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::Version(version));
+        }
+        let snapshot: Snapshot = bincode::deserialize(data)?;
+        let mut terminal = Self::with_screen(snapshot.screen);
+        terminal.screen.dirty = true;
+        terminal.last_title = snapshot.last_title;
+        terminal.input_locked = snapshot.input_locked;
+        Ok(terminal)
+    }
+
     /// Process input from the PTY and update the screen
     pub fn process(&mut self, data: &[u8]) -> Vec<TerminalEvent> {
         let (events, responses) = self.process_collecting(data);
@@ -122,7 +286,7 @@ impl Terminal {
         // path (GUI); the daemon uses `process_collecting` and writes responses through
         // a dedicated off-thread writer so a blocking PTY write can't stall it.
         for response in responses {
-            if let Err(e) = self.write(&response) {
+            if let Err(e) = self.write_unlocked(&response) {
                 log::error!("Failed to send response to PTY: {}", e);
             }
         }
@@ -138,22 +302,83 @@ impl Terminal {
     /// returned responses back to the PTY to keep terminal queries functioning.
     pub fn process_collecting(&mut self, data: &[u8]) -> (Vec<TerminalEvent>, Vec<Vec<u8>>) {
         let mut events = Vec::new();
+        let mut responses = Vec::new();
 
-        self.parser.parse(&mut self.screen, data);
+        if let Some(ref mut recorder) = self.recorder {
+            if let Err(e) = recorder.output(data) {
+                log::error!("Recording stopped: {}", e);
+                self.recorder = None;
+            }
+        }
+
+        let scrolled = self.screen.lines_scrolled();
+        self.parse_output(data, &mut events, &mut responses);
+        let lines = self.screen.lines_scrolled().saturating_sub(scrolled);
 
         // Collect any pending responses for the caller to write back to the PTY
-        let responses = if self.screen.has_pending_responses() {
-            self.screen.take_pending_responses()
-        } else {
-            Vec::new()
-        };
+        if self.screen.has_pending_responses() {
+            responses.extend(self.screen.take_pending_responses());
+        }
 
         // Emit clipboard operation events
         if self.screen.has_clipboard_ops() {
             for op in self.screen.take_clipboard_ops() {
-                events.push(TerminalEvent::ClipboardRequest(op));
+                let capability = match op {
+                    ClipboardOperation::Query { .. } => Capability::ClipboardRead,
+                    ClipboardOperation::Set { .. } => Capability::ClipboardWrite,
+                };
+                self.permit(capability, TerminalEvent::ClipboardRequest(op), &mut events);
+            }
+        }
+
+        for notification in self.screen.take_notifications() {
+            self.permit(
+                Capability::Notification,
+                TerminalEvent::Notification(notification),
+                &mut events,
+            );
+        }
+
+        // Files sent with OSC 1337 wait in the frontend's prompt unless denied
+        if self.permissions.file_transfer == Permission::Deny && self.screen.has_file_transfers() {
+            self.screen.take_file_transfers();
+            log::debug!("Denied: {}", Capability::FileTransfer.description());
+        }
+
+        for name in self.screen.take_user_var_changes() {
+            let value = self.screen.user_vars.get(&name).cloned();
+            events.push(TerminalEvent::UserVarChanged { name, value });
+        }
+
+        if self.screen.take_cwd_change() {
+            events.push(TerminalEvent::CwdChanged(self.screen.current_dir.clone()));
+        }
+        if let Some(shape) = self.screen.take_pointer_shape_change() {
+            events.push(TerminalEvent::PointerShapeChanged(shape));
+        }
+
+        for finished in self.screen.take_finished_commands() {
+            events.push(TerminalEvent::CommandFinished(finished));
+        }
+
+        if self.screen.take_upload_request() {
+            events.push(TerminalEvent::UploadRequested);
+        }
+
+        let completed = self.screen.take_completed_lines();
+        if let Some(ref mut watch) = self.watch {
+            if let Some(found) = watch.scan(&completed, Instant::now()) {
+                events.push(TerminalEvent::WatchMatched(found));
             }
         }
+        if self.line_events && !completed.is_empty() {
+            events.push(TerminalEvent::LinesCompleted(completed));
+        }
+
+        let tmux = self.screen.take_tmux_control();
+        if !tmux.is_empty() {
+            events.push(TerminalEvent::TmuxControl(tmux));
+        }
 
         // Check for bell
         if self.screen.bell {
@@ -161,10 +386,14 @@ impl Terminal {
             events.push(TerminalEvent::Bell);
         }
 
-        // Check for title change
+        // Check for title change; the screen keeps the last allowed title
         if self.screen.title != self.last_title {
-            self.last_title = self.screen.title.clone();
-            events.push(TerminalEvent::TitleChanged(self.last_title.clone()));
+            let title = std::mem::replace(&mut self.screen.title, self.last_title.clone());
+            self.permit(
+                Capability::TitleChange,
+                TerminalEvent::TitleChanged(title),
+                &mut events,
+            );
         }
 
         // Always emit content changed if there was data
@@ -172,11 +401,323 @@ impl Terminal {
             events.push(TerminalEvent::ContentChanged);
         }
 
+        if let Some(stats) = self.throughput.record(data.len(), lines, Instant::now()) {
+            events.push(TerminalEvent::OutputStats(stats));
+        }
+
         (events, responses)
     }
 
+    /// Output rates if a second has passed since the last report; call this
+    /// periodically so the final report arrives once output stops
+    pub fn poll_output_stats(&mut self) -> Option<OutputStats> {
+        self.throughput.poll(Instant::now())
+    }
+
+    /// Hand output to the parser, or to the file transfer while one runs
+    fn parse_output(
+        &mut self,
+        mut data: &[u8],
+        events: &mut Vec<TerminalEvent>,
+        responses: &mut Vec<Vec<u8>>,
+    ) {
+        while !data.is_empty() {
+            // Output goes with the transfer waiting for permission, until
+            // there is too much of it
+            if let Some((protocol, _, ref mut held)) = self.held_transfer {
+                if held.len() + data.len() <= MAX_HELD_TRANSFER {
+                    held.extend_from_slice(data);
+                    return;
+                }
+                log::warn!(
+                    "{}: too much output while waiting for permission, transfer cancelled",
+                    protocol.name()
+                );
+                self.refuse_held_transfer(responses);
+                continue;
+            }
+            let Some(ref mut transfer) = self.transfer else {
+                let Some((start, protocol, direction)) = (self.transfers_enabled
+                    && self.permissions.file_transfer != Permission::Deny)
+                    .then(|| file_transfer::detect(data))
+                    .flatten()
+                else {
+                    self.parser.parse(&mut self.screen, data);
+                    return;
+                };
+                self.parser.parse(&mut self.screen, &data[..start]);
+                data = &data[start..];
+                if self.permissions.file_transfer == Permission::Ask {
+                    log::info!("{}: transfer waiting for permission", protocol.name());
+                    self.held_transfer = Some((protocol, direction, Vec::new()));
+                    events.push(TerminalEvent::PermissionRequested(Capability::FileTransfer));
+                    continue;
+                }
+                log::info!("{}: transfer started ({:?})", protocol.name(), direction);
+                self.transfer = Some(FileTransfer::new(protocol, direction));
+                events.push(TerminalEvent::TransferStarted(protocol, direction));
+                continue;
+            };
+
+            let used = transfer.feed(data);
+            data = &data[used..];
+            self.drain_transfer(events, responses);
+        }
+    }
+
+    /// Collect the transfer's output and events, ending it once it's done
+    fn drain_transfer(&mut self, events: &mut Vec<TerminalEvent>, responses: &mut Vec<Vec<u8>>) {
+        let Some(ref mut transfer) = self.transfer else {
+            return;
+        };
+        let output = transfer.take_output();
+        if !output.is_empty() {
+            responses.push(output);
+        }
+        for event in transfer.take_events() {
+            match event {
+                TransferEvent::SendRequested => events.push(TerminalEvent::TransferSendRequested),
+                TransferEvent::Received { name, data } => {
+                    self.screen.queue_file_transfer(Some(name), data)
+                }
+            }
+        }
+        if self.transfer.as_ref().is_some_and(FileTransfer::is_done) {
+            self.transfer = None;
+            events.push(TerminalEvent::TransferFinished);
+        }
+    }
+
+    /// Cancel the transfer waiting for permission so the remote stops
+    /// waiting, and show the regular output that waited with it
+    ///
+    /// Returns whether any output was shown.
+    fn refuse_held_transfer(&mut self, responses: &mut Vec<Vec<u8>>) -> bool {
+        let Some((protocol, direction, data)) = self.held_transfer.take() else {
+            return false;
+        };
+        let mut transfer = FileTransfer::new(protocol, direction);
+        transfer.cancel();
+        responses.push(transfer.take_output());
+        let output = file_transfer::strip_start_frames(&data);
+        if !output.is_empty() {
+            self.parser.parse(&mut self.screen, &output);
+        }
+        !output.is_empty()
+    }
+
+    /// Emit `event` if the policy allows `capability`, hold it if it asks,
+    /// drop it if it denies
+    fn permit(
+        &mut self,
+        capability: Capability,
+        event: TerminalEvent,
+        events: &mut Vec<TerminalEvent>,
+    ) {
+        match self.permissions.get(capability) {
+            Permission::Allow => {
+                self.apply(&event);
+                events.push(event);
+            }
+            Permission::Ask => {
+                // Only the latest request of each kind waits
+                if let Some(held) = self.held.iter_mut().find(|(c, _)| *c == capability) {
+                    held.1 = event;
+                } else {
+                    events.push(TerminalEvent::PermissionRequested(capability));
+                    self.held.push((capability, event));
+                }
+            }
+            Permission::Deny => log::debug!("Denied: {}", capability.description()),
+        }
+    }
+
+    /// Carry out what an allowed event changes in the terminal itself
+    fn apply(&mut self, event: &TerminalEvent) {
+        if let TerminalEvent::TitleChanged(title) = event {
+            self.screen.title = title.clone();
+            self.last_title = title.clone();
+        }
+    }
+
+    /// The policy for sensitive escape sequences
+    pub fn permissions(&self) -> &PermissionPolicy {
+        &self.permissions
+    }
+
+    /// Set the policy for sensitive escape sequences; what already waits
+    /// for an answer keeps waiting
+    pub fn set_permissions(&mut self, permissions: PermissionPolicy) {
+        self.permissions = permissions;
+    }
+
+    /// Answer a [`TerminalEvent::PermissionRequested`]: release what waits
+    /// for `capability` if `allow`, else drop it. With `remember` the answer
+    /// becomes the policy for `capability` in this terminal.
+    ///
+    /// Returns the released events. A released file transfer starts; a
+    /// refused one is cancelled so the remote stops waiting, and the regular
+    /// output that waited with it is shown.
+    pub fn answer_permission(
+        &mut self,
+        capability: Capability,
+        allow: bool,
+        remember: bool,
+    ) -> Vec<TerminalEvent> {
+        if remember {
+            let permission = if allow {
+                Permission::Allow
+            } else {
+                Permission::Deny
+            };
+            self.permissions.set(capability, permission);
+        }
+
+        let (held, kept) = std::mem::take(&mut self.held)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(c, _)| *c == capability);
+        self.held = kept;
+        let mut events = Vec::new();
+        if allow {
+            for (_, event) in held {
+                self.apply(&event);
+                events.push(event);
+            }
+        }
+
+        if capability == Capability::FileTransfer {
+            let mut responses = Vec::new();
+            if !allow {
+                if self.refuse_held_transfer(&mut responses) {
+                    events.push(TerminalEvent::ContentChanged);
+                }
+            } else if let Some((protocol, direction, data)) = self.held_transfer.take() {
+                log::info!("{}: transfer started ({:?})", protocol.name(), direction);
+                self.transfer = Some(FileTransfer::new(protocol, direction));
+                events.push(TerminalEvent::TransferStarted(protocol, direction));
+                self.parse_output(&data, &mut events, &mut responses);
+            }
+            for response in responses {
+                if let Err(e) = self.write_unlocked(&response) {
+                    log::error!("Failed to send file transfer data to PTY: {}", e);
+                }
+            }
+        }
+        events
+    }
+
+    /// Detect ZMODEM (`sz`/`rz`) and Kermit transfers in the output. Off by
+    /// default so only the terminal that can show file dialogs answers the
+    /// remote.
+    pub fn set_file_transfers_enabled(&mut self, enabled: bool) {
+        self.transfers_enabled = enabled;
+    }
+
+    /// Count matches of `pattern` in output from now on, or stop with `None`
+    ///
+    /// Each line is searched once, when a line feed ends it; new matches are
+    /// reported as [`TerminalEvent::WatchMatched`]. Setting a pattern starts
+    /// the count from zero.
+    pub fn set_watch(&mut self, pattern: Option<&str>) -> Result<(), regex::Error> {
+        self.watch = pattern.map(Watch::new).transpose()?;
+        self.update_line_capture();
+        Ok(())
+    }
+
+    /// Report each output line as a line feed ends it, in
+    /// [`TerminalEvent::LinesCompleted`]
+    ///
+    /// As with [`Self::set_watch`], soft-wrapped rows are joined and lines on
+    /// the alternate screen are left out.
+    pub fn set_line_events(&mut self, enabled: bool) {
+        self.line_events = enabled;
+        self.update_line_capture();
+    }
+
+    /// Give the lines of output matching a [`LineHighlight`] its background
+    /// as a line feed ends them
+    pub fn set_line_highlights(&mut self, highlights: Vec<LineHighlight>) {
+        self.screen.set_line_highlights(highlights);
+    }
+
+    /// Tell programs whether the theme is dark or light: answers DSR 996
+    /// queries from now on, and reports a change right away to programs that
+    /// enabled mode 2031
+    pub fn set_color_scheme(&mut self, scheme: ColorScheme) {
+        if self.screen.set_color_scheme(scheme) && self.screen.modes.color_scheme_updates {
+            if let Err(e) = self.write_unlocked(&scheme.report()) {
+                log::error!("Failed to send color scheme report: {}", e);
+            }
+        }
+    }
+
+    /// Capture completed lines while something reads them
+    fn update_line_capture(&mut self) {
+        self.screen
+            .set_line_capture(self.watch.is_some() || self.line_events);
+    }
+
+    /// The watch expression, if one is set
+    pub fn watch(&self) -> Option<&Watch> {
+        self.watch.as_ref()
+    }
+
+    /// Start the watch count from zero again
+    pub fn reset_watch(&mut self) {
+        if let Some(ref mut watch) = self.watch {
+            watch.reset();
+        }
+    }
+
+    /// Protocol and direction of the file transfer in progress, if any
+    pub fn file_transfer(&self) -> Option<(TransferProtocol, TransferDirection)> {
+        self.transfer
+            .as_ref()
+            .map(|transfer| (transfer.protocol(), transfer.direction()))
+    }
+
+    /// Send files to the remote after [`TerminalEvent::TransferSendRequested`];
+    /// an empty list declines. Returns the resulting events.
+    pub fn send_transfer_files(&mut self, files: Vec<TransferFile>) -> Vec<TerminalEvent> {
+        if let Some(ref mut transfer) = self.transfer {
+            transfer.send_files(files);
+        }
+        self.flush_transfer()
+    }
+
+    /// Abort the file transfer in progress. Returns the resulting events.
+    pub fn cancel_file_transfer(&mut self) -> Vec<TerminalEvent> {
+        if let Some(ref mut transfer) = self.transfer {
+            transfer.cancel();
+        }
+        self.flush_transfer()
+    }
+
+    /// Write the transfer's pending output to the PTY
+    fn flush_transfer(&mut self) -> Vec<TerminalEvent> {
+        let mut events = Vec::new();
+        let mut responses = Vec::new();
+        self.drain_transfer(&mut events, &mut responses);
+        for response in responses {
+            if let Err(e) = self.write_unlocked(&response) {
+                log::error!("Failed to send file transfer data to PTY: {}", e);
+            }
+        }
+        events
+    }
+
     /// Write input to the PTY (keyboard input)
+    ///
+    /// Dropped while input is locked, see [`Self::set_input_locked`].
     pub fn write(&mut self, data: &[u8]) -> Result<(), PtyError> {
+        if self.input_locked {
+            return Ok(());
+        }
+        self.write_unlocked(data)
+    }
+
+    /// Write to the PTY regardless of the input lock (terminal responses)
+    fn write_unlocked(&mut self, data: &[u8]) -> Result<(), PtyError> {
         if let Some(ref mut pty) = self.pty {
             pty.write(data)?;
         } else if let Some(ref write_fn) = self.write_fn {
@@ -196,6 +737,14 @@ impl Terminal {
         selection: crate::screen::ClipboardSelection,
         data: &[u8],
     ) -> Result<(), PtyError> {
+        self.write_unlocked(&Self::clipboard_response(selection, data))
+    }
+
+    /// OSC 52 response carrying `data` for `selection`
+    pub fn clipboard_response(
+        selection: crate::screen::ClipboardSelection,
+        data: &[u8],
+    ) -> Vec<u8> {
         use crate::screen::ClipboardSelection;
         use base64::Engine;
 
@@ -206,12 +755,63 @@ impl Terminal {
         };
 
         let encoded = base64::engine::general_purpose::STANDARD.encode(data);
-        let response = format!("\x1b]52;{};{}\x07", selection_char, encoded);
-        self.write(response.as_bytes())
+        format!("\x1b]52;{};{}\x07", selection_char, encoded).into_bytes()
+    }
+
+    /// Lock or unlock input. While locked, [`Self::write`] drops keystrokes,
+    /// pastes and mouse reports; responses to terminal queries still go out.
+    pub fn set_input_locked(&mut self, locked: bool) {
+        self.input_locked = locked;
+    }
+
+    /// Whether input is locked (read-only)
+    pub fn is_input_locked(&self) -> bool {
+        self.input_locked
+    }
+
+    /// Start recording the output to an asciicast v2 file at `path`,
+    /// replacing any recording in progress
+    pub fn start_recording(&mut self, path: &Path) -> std::io::Result<()> {
+        let recorder = Recorder::create(path, self.cols(), self.rows(), &self.screen.title)?;
+        if let Some(old) = self.recorder.replace(recorder) {
+            old.finish()?;
+        }
+        Ok(())
+    }
+
+    /// Finish the recording in progress; returns false if there was none
+    pub fn stop_recording(&mut self) -> std::io::Result<bool> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish().map(|()| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Pause or resume the recording in progress
+    pub fn set_recording_paused(&mut self, paused: bool) {
+        if let Some(ref mut recorder) = self.recorder {
+            if paused {
+                recorder.pause();
+            } else {
+                recorder.resume();
+            }
+        }
+    }
+
+    /// The recording in progress, if any
+    pub fn recording(&self) -> Option<&Recorder> {
+        self.recorder.as_ref()
     }
 
     /// Resize the terminal
     pub fn resize(&mut self, cols: usize, rows: usize) {
+        let size_changed = (cols, rows) != (self.cols(), self.rows());
+        if let Some(recorder) = self.recorder.as_mut().filter(|_| size_changed) {
+            if let Err(e) = recorder.resize(cols, rows) {
+                log::error!("Recording stopped: {}", e);
+                self.recorder = None;
+            }
+        }
         self.screen.resize(cols, rows);
         if let Some(ref pty) = self.pty {
             let _ = pty.resize(rows as u16, cols as u16);
@@ -301,6 +901,27 @@ impl Terminal {
         self.screen.scroll_offset = self.screen.scroll_offset.saturating_sub(lines);
     }
 
+    /// Scroll the viewport while a selection is dragged past the top (`up`)
+    /// or bottom edge, extending the selection onto the revealed rows
+    ///
+    /// Dragging up extends to `col` of the top row; dragging down extends to
+    /// the end of the bottom row.
+    pub fn autoscroll_selection(&mut self, up: bool, lines: usize, col: usize) {
+        if up {
+            self.scroll_viewport_up(lines);
+            let line = self.screen.visible_row_to_absolute_line(0);
+            self.screen.extend_selection(line, col);
+        } else {
+            self.scroll_viewport_down(lines);
+            let rows = self.screen.height();
+            let line = self
+                .screen
+                .visible_row_to_absolute_line(rows.saturating_sub(1));
+            let width = self.screen.width();
+            self.screen.extend_selection(line, width.saturating_sub(1));
+        }
+    }
+
     /// Reset viewport to bottom
     pub fn scroll_viewport_to_bottom(&mut self) {
         self.screen.scroll_offset = 0;
@@ -316,15 +937,78 @@ impl Terminal {
         self.screen.find(pattern, case_sensitive, regex)
     }
 
+    /// Distinct matches of `regex` in scrollback and visible buffer
+    pub fn extract_matches(&self, pattern: &str) -> Result<Vec<String>, regex::Error> {
+        self.screen.extract_matches(pattern)
+    }
+
     /// Scroll to show a specific line from find results
     pub fn scroll_to_line(&mut self, line_idx: usize) {
         self.screen.scroll_offset = self.screen.line_to_scroll_offset(line_idx);
     }
 
+    /// Scroll the prompt before (or with `next`, after) the top of the
+    /// viewport to the top of it; returns whether there was one
+    ///
+    /// A prompt on the last page scrolls to the bottom.
+    pub fn scroll_to_prompt(&mut self, next: bool) -> bool {
+        let top = self.screen.visible_row_to_absolute_line(0);
+        let prompts = self.screen.prompt_lines();
+        let target = if next {
+            prompts.into_iter().find(|&line| line > top)
+        } else {
+            prompts.into_iter().rev().find(|&line| line < top)
+        };
+        let Some(line) = target else {
+            return false;
+        };
+        let offset = self.screen.line_to_scroll_offset(line);
+        if offset == self.screen.scroll_offset {
+            return false;
+        }
+        self.screen.scroll_offset = offset;
+        true
+    }
+
+    /// Select the output of the last command, scrolling its first line into
+    /// view; returns whether there was any
+    pub fn select_last_command_output(&mut self) -> bool {
+        let Some(lines) = self.screen.last_command_output() else {
+            return false;
+        };
+        if lines.start < self.screen.visible_row_to_absolute_line(0) {
+            self.scroll_to_line(lines.start);
+        }
+        self.screen.select_lines(lines);
+        true
+    }
+
     /// Handle keyboard input and generate appropriate escape sequences
     pub fn handle_key(&self, key: Key, modifiers: Modifiers) -> Option<Vec<u8>> {
+        self.handle_key_event(key, modifiers, KeyEventKind::Press)
+    }
+
+    /// Handle a key press, repeat or release
+    ///
+    /// While the application has enabled the kitty keyboard protocol, keys
+    /// are encoded as CSI u; otherwise legacy sequences are produced and
+    /// releases are not reported.
+    pub fn handle_key_event(
+        &self,
+        key: Key,
+        modifiers: Modifiers,
+        kind: KeyEventKind,
+    ) -> Option<Vec<u8>> {
+        let flags = self.screen.keyboard_flags();
+        if !flags.is_empty() {
+            return kitty_keyboard::encode_key(key, modifiers, kind, flags);
+        }
+        if kind == KeyEventKind::Release {
+            return None;
+        }
+
         let app_cursor = self.screen.modes.application_cursor;
-        let _app_keypad = self.screen.modes.application_keypad;
+        let app_keypad = self.screen.modes.application_keypad;
 
         match key {
             Key::Char(c) => {
@@ -392,10 +1076,37 @@ impl Terminal {
             Key::Insert => Some(tilde_key(2, modifiers)),
             Key::Delete => Some(tilde_key(3, modifiers)),
             Key::F(n) => Some(function_key(n, modifiers)),
+            Key::Keypad(c) => {
+                // DECKPAM: unmodified keypad keys send SS3 sequences
+                let code = keypad_code(c).filter(|_| {
+                    app_keypad && !modifiers.intersects(Modifiers::CTRL | Modifiers::ALT)
+                });
+                match (code, c) {
+                    (Some(code), _) => Some(vec![0x1b, b'O', code]),
+                    (None, '\r') => self.handle_key_event(Key::Enter, modifiers, kind),
+                    (None, c) => self.handle_key_event(Key::Char(c), modifiers, kind),
+                }
+            }
         }
     }
 }
 
+/// Final byte of a keypad key's SS3 sequence in application keypad mode
+fn keypad_code(c: char) -> Option<u8> {
+    Some(match c {
+        '0'..='9' => b'p' + (c as u8 - b'0'),
+        '\r' => b'M',
+        '*' => b'j',
+        '+' => b'k',
+        ',' => b'l',
+        '-' => b'm',
+        '.' => b'n',
+        '/' => b'o',
+        '=' => b'X',
+        _ => return None,
+    })
+}
+
 fn cursor_key(key: u8, modifiers: Modifiers, app_cursor: bool) -> Vec<u8> {
     let modifier = modifier_param(modifiers);
 
@@ -479,6 +1190,9 @@ pub enum Key {
     Insert,
     Delete,
     F(u8),
+    /// Numeric keypad key, as the character it types: `0`-`9`, `.`, `,`,
+    /// `+`, `-`, `*`, `/`, `=`, or `\r` for the keypad Enter
+    Keypad(char),
 }
 
 bitflags::bitflags! {
@@ -495,6 +1209,7 @@ bitflags::bitflags! {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::color::{Color, Rgb};
 
     #[test]
     fn test_terminal_new() {
@@ -513,6 +1228,144 @@ mod tests {
         assert_eq!(term.screen().get_cell(0, 12).unwrap().c, '!');
     }
 
+    #[test]
+    fn test_prompt_navigation() {
+        // Without OSC 133 marks, prompts are recognized by their look
+        let mut term = Terminal::new(20, 3, ScreenConfig::default());
+        term.process(b"user$ ls\r\na\r\nb\r\nuser$ echo\r\nc\r\nuser$ ");
+        assert_eq!(term.screen().prompt_lines(), vec![0, 3, 5]);
+
+        assert!(term.scroll_to_prompt(false));
+        assert_eq!(term.screen().scroll_offset, 3);
+        assert!(!term.scroll_to_prompt(false));
+        assert!(term.scroll_to_prompt(true));
+        assert!(term.is_at_bottom());
+        assert!(!term.scroll_to_prompt(true));
+
+        assert!(term.select_last_command_output());
+        assert_eq!(term.screen().get_selected_text().as_deref(), Some("c"));
+
+        // Marks take precedence, and the output ends at the finish mark
+        let mut term = Terminal::new(20, 3, ScreenConfig::default());
+        term.process(
+            b"\x1b]133;A\x07> make\r\n\x1b]133;C\x07x\r\ny\r\nz\r\n\x1b]133;D;0\x07\
+              \x1b]133;A\x07> ",
+        );
+        assert_eq!(term.screen().prompt_lines(), vec![0, 4]);
+        assert!(term.select_last_command_output());
+        assert_eq!(term.screen().scroll_offset, 1);
+        assert_eq!(
+            term.screen().get_selected_text().as_deref(),
+            Some("x\ny\nz")
+        );
+    }
+
+    #[test]
+    fn test_watch_counts_new_lines_once() {
+        let mut term = Terminal::new(10, 3, ScreenConfig::default());
+        term.process(b"ERROR before\r\n");
+        term.set_watch(Some("ERROR")).unwrap();
+
+        let watched = |events: Vec<TerminalEvent>| {
+            events.into_iter().find_map(|event| match event {
+                TerminalEvent::WatchMatched(found) => Some(found),
+                _ => None,
+            })
+        };
+
+        // A line counts once a line feed ends it, even across chunks and
+        // soft wraps
+        assert!(watched(term.process(b"ok\r\nsome ER")).is_none());
+        let found = watched(term.process(b"ROR here\r\n")).unwrap();
+        assert_eq!(found.count, 1);
+        assert_eq!(found.line, "some ERROR here");
+
+        // Full-screen apps on the alternate screen aren't watched
+        assert!(watched(term.process(b"\x1b[?1049hERROR\r\n\x1b[?1049l")).is_none());
+        assert_eq!(term.watch().unwrap().count(), 1);
+
+        term.set_watch(None).unwrap();
+        assert!(watched(term.process(b"ERROR\r\n")).is_none());
+    }
+
+    #[test]
+    fn test_color_scheme_reports() {
+        use crate::color::ColorPalette;
+        use std::sync::{Arc, Mutex};
+
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&written);
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        term.set_write_fn(Box::new(move |data| {
+            sink.lock().unwrap().extend_from_slice(data);
+            Ok(())
+        }));
+        let take = || std::mem::take(&mut *written.lock().unwrap());
+
+        // Without a scheme from the frontend, queries go unanswered
+        term.process(b"\x1b[?996n");
+        assert!(take().is_empty());
+
+        term.set_color_scheme(ColorPalette::default_dark().color_scheme());
+        term.process(b"\x1b[?996n");
+        assert_eq!(take(), b"\x1b[?997;1n");
+
+        // Changes are reported only with mode 2031 on, and only once
+        term.set_color_scheme(ColorScheme::Light);
+        assert!(take().is_empty());
+        term.process(b"\x1b[?2031h");
+        term.set_color_scheme(ColorPalette::default_dark().color_scheme());
+        term.set_color_scheme(ColorScheme::Dark);
+        assert_eq!(take(), b"\x1b[?997;1n");
+        term.set_color_scheme(ColorPalette::default_light().color_scheme());
+        assert_eq!(take(), b"\x1b[?997;2n");
+
+        term.process(b"\x1b[?2031l");
+        term.set_color_scheme(ColorScheme::Dark);
+        assert!(take().is_empty());
+    }
+
+    #[test]
+    fn test_line_events_and_highlights() {
+        let mut term = Terminal::new(10, 3, ScreenConfig::default());
+        let completed = |events: Vec<TerminalEvent>| {
+            events.into_iter().find_map(|event| match event {
+                TerminalEvent::LinesCompleted(lines) => Some(lines),
+                _ => None,
+            })
+        };
+        assert!(completed(term.process(b"off\r\n")).is_none());
+
+        term.set_line_events(true);
+        let red = Rgb::new(0x80, 0, 0);
+        term.set_line_highlights(vec![LineHighlight {
+            regex: regex::Regex::new("FAIL").unwrap(),
+            color: red,
+        }]);
+        let lines = completed(term.process(b"ok\r\ntest FAILED\r\npartial")).unwrap();
+        assert_eq!(lines, vec!["ok".to_string(), "test FAILED".to_string()]);
+
+        // Both rows of the soft-wrapped matching line are highlighted
+        let screen = term.screen();
+        assert_eq!(screen.get_cell(0, 0).unwrap().bg, Color::Rgb(red));
+        assert_eq!(screen.get_cell(0, 9).unwrap().bg, Color::Rgb(red));
+        assert_eq!(screen.get_cell(1, 0).unwrap().bg, Color::Rgb(red));
+        assert_eq!(screen.get_cell(2, 0).unwrap().bg, Color::Default);
+        let ok_row = screen.scrollback().back().unwrap();
+        assert_eq!(ok_row.get(0).unwrap().bg, Color::Default);
+
+        // Watching and line events share the captured lines
+        term.set_watch(Some("done")).unwrap();
+        let events = term.process(b" done\r\n");
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, TerminalEvent::WatchMatched(_))));
+        assert_eq!(completed(events).unwrap(), vec!["partial done".to_string()]);
+
+        term.set_line_events(false);
+        assert!(completed(term.process(b"more\r\n")).is_none());
+    }
+
     #[test]
     fn test_process_collecting_returns_responses() {
         // A terminal with no PTY: parser responses (e.g. DSR cursor-position report)
@@ -540,6 +1393,100 @@ mod tests {
         assert_eq!(term.screen().get_cell(0, 0).unwrap().c, 'H');
     }
 
+    #[test]
+    fn test_input_lock() {
+        use std::sync::{Arc, Mutex};
+
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&written);
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        term.set_write_fn(Box::new(move |data| {
+            sink.lock().unwrap().extend_from_slice(data);
+            Ok(())
+        }));
+
+        term.set_input_locked(true);
+        assert!(term.is_input_locked());
+        term.write_str("rm -rf /\r").unwrap();
+        assert!(written.lock().unwrap().is_empty());
+
+        // Query responses are not user input and still reach the PTY
+        term.process(b"\x1b[6n");
+        assert_eq!(*written.lock().unwrap(), b"\x1b[1;1R");
+
+        term.set_input_locked(false);
+        term.write_str("ls").unwrap();
+        assert!(written.lock().unwrap().ends_with(b"ls"));
+    }
+
+    #[test]
+    fn test_snapshot_layout() {
+        // A snapshot of the same terminal must keep its exact bytes. When a
+        // change to Screen or the types in it breaks this, bump
+        // SNAPSHOT_VERSION and update the expected values.
+        let config = ScreenConfig {
+            scrollback_lines: 100,
+            ..Default::default()
+        };
+        let mut term = Terminal::new(10, 3, config);
+        term.process(b"one\r\ntwo\r\nthree\r\nfour\x1b]2;title\x07");
+        term.process(b"\x1b[1;31mx\x1b(@\x1b[3g\x1b[1;3H\x1bH\x1b[?2004h\x1b[2;6H\x1b7\x1b[3;5H");
+        let data = term.serialize().unwrap();
+        // FNV-1a
+        let hash = data.iter().fold(0xcbf29ce484222325u64, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        assert_eq!(
+            (SNAPSHOT_VERSION, data.len(), hash),
+            (11, 1350, 0x2df6_12cd_5981_5c46)
+        );
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let config = ScreenConfig {
+            scrollback_lines: 100,
+            ..Default::default()
+        };
+        let mut term = Terminal::new(10, 3, config);
+        term.process(b"one\r\ntwo\r\nthree\r\nfour");
+        // Bold red, a soft font designated as G0, a tab stop at column 2, bracketed
+        // paste, and the cursor saved at 2;6
+        term.process(b"\x1b[1;31mx\x1b(@\x1b[3g\x1b[1;3H\x1bH\x1b[?2004h\x1b[2;6H\x1b7\x1b[3;5H");
+        term.set_input_locked(true);
+
+        let data = term.serialize().unwrap();
+        let mut restored = Terminal::deserialize(&data).unwrap();
+        assert_eq!(restored.serialize().unwrap(), data);
+
+        let screen = restored.screen();
+        assert_eq!(screen.scrollback().len(), 1);
+        assert_eq!(screen.cursor, term.screen().cursor);
+        assert!(screen.modes.bracketed_paste);
+        assert_eq!(screen.modes.charset_g0.as_deref(), Some("@"));
+        assert!(restored.is_input_locked());
+        assert_eq!(screen.get_cell(2, 4).unwrap().c, 'x');
+        assert!(screen
+            .get_cell(2, 4)
+            .unwrap()
+            .attrs
+            .contains(crate::cell::CellAttrs::BOLD));
+
+        // Restored state keeps behaving the same: tab stops, saved cursor
+        restored.process(b"\x1b[1;1H\tT\x1b8S");
+        assert_eq!(restored.screen().get_cell(0, 2).unwrap().c, 'T');
+        assert_eq!(restored.screen().get_cell(1, 5).unwrap().c, 'S');
+        assert!(restored.screen_mut().take_damage().full);
+
+        let mut bad = data.clone();
+        bad[0] = 99;
+        assert!(matches!(
+            Terminal::deserialize(&bad),
+            Err(SnapshotError::Version(99))
+        ));
+        assert!(Terminal::deserialize(&data[..data.len() / 2]).is_err());
+    }
+
     #[test]
     fn test_terminal_resize() {
         let mut term = Terminal::new(80, 24, ScreenConfig::default());
@@ -578,4 +1525,333 @@ mod tests {
         let up = term.handle_key(Key::Up, Modifiers::empty());
         assert_eq!(up, Some(b"\x1b[A".to_vec()));
     }
+
+    #[test]
+    fn test_recording() {
+        let path =
+            std::env::temp_dir().join(format!("cterm_recording_{}.cast", std::process::id()));
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        term.start_recording(&path).unwrap();
+        term.process(b"one\r\n");
+        term.set_recording_paused(true);
+        term.process(b"hidden");
+        term.set_recording_paused(false);
+        term.resize(80, 24);
+        term.resize(100, 30);
+        assert!(term.stop_recording().unwrap());
+        assert!(!term.stop_recording().unwrap());
+        term.process(b"after");
+
+        let cast = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = cast.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("{\"version\": 2, \"width\": 80, \"height\": 24"));
+        assert!(lines[1].ends_with(", \"o\", \"one\\r\\n\"]"));
+        assert!(lines[2].ends_with(", \"r\", \"100x30\"]"));
+    }
+
+    #[test]
+    fn test_handle_key_application_keypad() {
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        let key = |term: &Terminal, key, mods| term.handle_key(key, mods).unwrap();
+
+        // Numeric keypad mode types the characters
+        assert_eq!(key(&term, Key::Keypad('7'), Modifiers::empty()), b"7");
+        assert_eq!(key(&term, Key::Keypad('\r'), Modifiers::empty()), b"\r");
+
+        // DECKPAM
+        term.process(b"\x1b=");
+        assert_eq!(key(&term, Key::Keypad('0'), Modifiers::empty()), b"\x1bOp");
+        assert_eq!(key(&term, Key::Keypad('9'), Modifiers::empty()), b"\x1bOy");
+        assert_eq!(key(&term, Key::Keypad('.'), Modifiers::empty()), b"\x1bOn");
+        assert_eq!(key(&term, Key::Keypad('+'), Modifiers::SHIFT), b"\x1bOk");
+        assert_eq!(key(&term, Key::Keypad('\r'), Modifiers::empty()), b"\x1bOM");
+        assert_eq!(key(&term, Key::Keypad('='), Modifiers::empty()), b"\x1bOX");
+        // Ctrl and Alt keep their usual meaning
+        assert_eq!(key(&term, Key::Keypad('5'), Modifiers::ALT), b"\x1b5");
+
+        // DECKPNM
+        term.process(b"\x1b>");
+        assert_eq!(key(&term, Key::Keypad('/'), Modifiers::empty()), b"/");
+    }
+
+    #[test]
+    fn test_handle_key_kitty_protocol() {
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+
+        // Push disambiguate + event types
+        term.process(b"\x1b[>3u");
+        assert_eq!(
+            term.handle_key(Key::Escape, Modifiers::empty()),
+            Some(b"\x1b[27u".to_vec())
+        );
+        assert_eq!(
+            term.handle_key_event(Key::Char('a'), Modifiers::CTRL, KeyEventKind::Release),
+            Some(b"\x1b[97;5:3u".to_vec())
+        );
+        let (_, responses) = term.process_collecting(b"\x1b[?u");
+        assert_eq!(responses, [b"\x1b[?3u".to_vec()]);
+
+        // The alternate screen has its own stack
+        term.process(b"\x1b[?1049h");
+        assert_eq!(
+            term.handle_key(Key::Escape, Modifiers::empty()),
+            Some(b"\x1b".to_vec())
+        );
+        term.process(b"\x1b[?1049l");
+
+        // Pop restores legacy encoding, where releases aren't reported
+        term.process(b"\x1b[<u");
+        assert_eq!(
+            term.handle_key(Key::Escape, Modifiers::empty()),
+            Some(b"\x1b".to_vec())
+        );
+        assert_eq!(
+            term.handle_key_event(Key::Escape, Modifiers::empty(), KeyEventKind::Release),
+            None
+        );
+    }
+
+    #[test]
+    fn test_autoscroll_selection() {
+        let mut term = Terminal::new(10, 3, ScreenConfig::default());
+        for i in 0..10 {
+            term.process(format!("line{}\r\n", i).as_bytes());
+        }
+        let bottom = term.screen().visible_row_to_absolute_line(2);
+        term.screen_mut()
+            .start_selection(bottom, 4, crate::SelectionMode::Char);
+
+        term.autoscroll_selection(true, 2, 1);
+        assert_eq!(term.screen().scroll_offset, 2);
+        let top = term.screen().visible_row_to_absolute_line(0);
+        let selection = term.screen().selection.clone().unwrap();
+        assert_eq!((selection.end.line, selection.end.col), (top, 1));
+
+        term.autoscroll_selection(false, 5, 1);
+        assert!(term.is_at_bottom());
+        let selection = term.screen().selection.clone().unwrap();
+        assert_eq!((selection.end.line, selection.end.col), (bottom, 9));
+    }
+
+    #[test]
+    fn test_user_var_changed_events() {
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        let user_var_events = |events: Vec<TerminalEvent>| {
+            events
+                .into_iter()
+                .filter_map(|event| match event {
+                    TerminalEvent::UserVarChanged { name, value } => Some((name, value)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let events = term.process(b"\x1b]1337;SetUserVar=host=cHJvZA==\x07");
+        assert_eq!(
+            user_var_events(events),
+            vec![("host".to_string(), Some("prod".to_string()))]
+        );
+
+        // Setting the same value again is not a change
+        let events = term.process(b"\x1b]1337;SetUserVar=host=cHJvZA==\x07");
+        assert!(user_var_events(events).is_empty());
+
+        let events = term.process(b"\x1bc");
+        assert_eq!(user_var_events(events), vec![("host".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_cwd_changed_event() {
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        let cwd_events = |events: Vec<TerminalEvent>| {
+            events
+                .into_iter()
+                .filter_map(|event| match event {
+                    TerminalEvent::CwdChanged(dir) => Some(dir),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let events = term.process(b"\x1b]7;file:///home/me\x07");
+        assert_eq!(cwd_events(events), vec!["/home/me".to_string()]);
+        let events = term.process(b"\x1b]7;file:///home/me\x07");
+        assert!(cwd_events(events).is_empty());
+        let events = term.process(b"\x1bc");
+        assert_eq!(cwd_events(events), vec![String::new()]);
+    }
+
+    #[test]
+    fn test_command_finished_event() {
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+
+        let events = term.process(b"\x1b]133;C\x07");
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, TerminalEvent::CommandFinished(_))));
+
+        let events = term.process(b"\x1b]133;D;1\x07");
+        let finished: Vec<_> = events
+            .into_iter()
+            .filter_map(|event| match event {
+                TerminalEvent::CommandFinished(finished) => Some(finished),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].exit_code, Some(1));
+    }
+
+    #[test]
+    fn test_file_transfer_detection() {
+        let zrinit = b"rz\r\n**\x18B0100000000aa51\r\x8a\x11";
+        let transfer_events = |events: Vec<TerminalEvent>| {
+            events
+                .into_iter()
+                .filter(|e| !matches!(e, TerminalEvent::ContentChanged))
+                .map(|e| format!("{:?}", e))
+                .collect::<Vec<_>>()
+        };
+
+        // Off by default: the header is just output
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        assert!(transfer_events(term.process(zrinit)).is_empty());
+        assert_eq!(term.file_transfer(), None);
+
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        term.set_file_transfers_enabled(true);
+        assert_eq!(
+            transfer_events(term.process(zrinit)),
+            ["TransferStarted(Zmodem, Send)", "TransferSendRequested"]
+        );
+        assert_eq!(
+            term.file_transfer(),
+            Some((TransferProtocol::Zmodem, TransferDirection::Send))
+        );
+        assert_eq!(term.screen().get_cell(0, 1).unwrap().c, 'z');
+        assert_eq!(term.screen().get_cell(1, 0).unwrap().c, ' ');
+
+        // Declining ends the transfer and output goes to the screen again
+        assert_eq!(
+            transfer_events(term.send_transfer_files(Vec::new())),
+            ["TransferFinished"]
+        );
+        term.process(b"$");
+        assert_eq!(term.screen().get_cell(1, 0).unwrap().c, '$');
+
+        // A waiting `kermit -r` repeats its NAK
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        term.set_file_transfers_enabled(true);
+        assert_eq!(
+            transfer_events(term.process(b"\x01# N3\r\x01# N3\r")),
+            ["TransferStarted(Kermit, Send)", "TransferSendRequested"]
+        );
+        assert_eq!(
+            transfer_events(term.cancel_file_transfer()),
+            ["TransferFinished"]
+        );
+    }
+
+    #[test]
+    fn test_permissions() {
+        let visible = |events: Vec<TerminalEvent>| {
+            events
+                .into_iter()
+                .filter(|e| !matches!(e, TerminalEvent::ContentChanged))
+                .map(|e| format!("{:?}", e))
+                .collect::<Vec<_>>()
+        };
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        let mut policy = PermissionPolicy {
+            title_change: Permission::Ask,
+            notifications: Permission::Deny,
+            ..Default::default()
+        };
+        term.set_permissions(policy);
+
+        // Held until answered, asking once; only the latest title is kept
+        assert_eq!(
+            visible(term.process(b"\x1b]2;one\x07\x1b]9;hi\x07")),
+            ["PermissionRequested(TitleChange)"]
+        );
+        assert_eq!(term.title(), "");
+        assert!(visible(term.process(b"\x1b]2;two\x07")).is_empty());
+        assert_eq!(
+            visible(term.answer_permission(Capability::TitleChange, true, false)),
+            ["TitleChanged(\"two\")"]
+        );
+        assert_eq!(term.title(), "two");
+
+        // Remembered answers become the policy
+        term.process(b"\x1b]2;three\x07");
+        assert!(term
+            .answer_permission(Capability::TitleChange, false, true)
+            .is_empty());
+        assert_eq!(term.title(), "two");
+        assert!(visible(term.process(b"\x1b]2;four\x07")).is_empty());
+        assert_eq!(term.permissions().title_change, Permission::Deny);
+
+        // Clipboard reads ask by default
+        assert_eq!(
+            visible(term.process(b"\x1b]52;c;?\x07")),
+            ["PermissionRequested(ClipboardRead)"]
+        );
+        assert!(visible(term.process(b"\x1b]52;p;?\x07")).is_empty());
+        assert_eq!(
+            visible(term.answer_permission(Capability::ClipboardRead, true, false)),
+            ["ClipboardRequest(Query { selection: Primary })"]
+        );
+
+        // A transfer waits with its output, then starts
+        policy.file_transfer = Permission::Ask;
+        term.set_permissions(policy);
+        term.set_file_transfers_enabled(true);
+        assert_eq!(
+            visible(term.process(b"\x01# N3\r")),
+            ["PermissionRequested(FileTransfer)"]
+        );
+        term.process(b"\x01# N3\r");
+        assert_eq!(term.file_transfer(), None);
+        assert_eq!(
+            visible(term.answer_permission(Capability::FileTransfer, true, false)),
+            ["TransferStarted(Kermit, Send)", "TransferSendRequested"]
+        );
+
+        // Refusing a transfer keeps the output that waited with it
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        term.set_file_transfers_enabled(true);
+        policy.file_transfer = Permission::Ask;
+        term.set_permissions(policy);
+        term.process(b"\x01# N3\r");
+        term.process(b"\x01# N3\rplain output");
+        term.answer_permission(Capability::FileTransfer, false, false);
+        assert_eq!(term.file_transfer(), None);
+        assert_eq!(term.screen().get_cell(0, 0).unwrap().c, 'p');
+        assert_eq!(term.screen().get_cell(0, 11).unwrap().c, 't');
+
+        // Too much output while waiting cancels the transfer and shows it
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        term.set_file_transfers_enabled(true);
+        term.set_permissions(policy);
+        term.process(b"\x01# N3\r");
+        term.process(&vec![b'x'; MAX_HELD_TRANSFER]);
+        term.process(b"\r\nplain output");
+        assert_eq!(term.file_transfer(), None);
+        assert_eq!(term.screen().get_cell(22, 0).unwrap().c, 'x');
+        assert_eq!(term.screen().get_cell(23, 0).unwrap().c, 'p');
+        assert!(term
+            .answer_permission(Capability::FileTransfer, true, false)
+            .is_empty());
+        assert_eq!(term.file_transfer(), None);
+
+        // Denied transfers are not detected
+        let mut term = Terminal::new(80, 24, ScreenConfig::default());
+        term.set_file_transfers_enabled(true);
+        policy.file_transfer = Permission::Deny;
+        term.set_permissions(policy);
+        assert!(visible(term.process(b"\x01# N3\r")).is_empty());
+        assert_eq!(term.file_transfer(), None);
+    }
 }
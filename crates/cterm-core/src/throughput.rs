@@ -0,0 +1,128 @@
+//! Output throughput statistics
+//!
+//! A [`ThroughputMeter`] counts the bytes a terminal parses and the lines it
+//! scrolls, and reports their rates once per [`ThroughputMeter::INTERVAL`]
+//! while output is flowing, plus one final report of zero once it stops.
+//! Frontends use [`OutputStats::is_fast`] to show that output is arriving
+//! faster than it can be drawn, so rendering is throttled to the frame rate.
+
+use std::time::{Duration, Instant};
+
+/// Output rates over the last [`ThroughputMeter::INTERVAL`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputStats {
+    /// Bytes of output parsed per second
+    pub bytes_per_sec: u64,
+    /// Lines scrolled per second
+    pub lines_per_sec: u64,
+}
+
+impl OutputStats {
+    /// Byte rate above which output counts as fast
+    pub const FAST_BYTES_PER_SEC: u64 = 1024 * 1024;
+    /// Scroll rate above which output counts as fast
+    pub const FAST_LINES_PER_SEC: u64 = 2000;
+
+    /// Whether output arrives faster than a frame can show it
+    pub fn is_fast(&self) -> bool {
+        self.bytes_per_sec >= Self::FAST_BYTES_PER_SEC
+            || self.lines_per_sec >= Self::FAST_LINES_PER_SEC
+    }
+}
+
+/// Accumulates output and reports [`OutputStats`] once per interval
+#[derive(Debug, Clone)]
+pub struct ThroughputMeter {
+    /// Start of the current interval
+    start: Instant,
+    bytes: u64,
+    lines: u64,
+    /// Whether the last report was non-zero, so a zero one is still due
+    reported: bool,
+}
+
+impl ThroughputMeter {
+    /// How often the rates are reported
+    pub const INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new(now: Instant) -> Self {
+        Self {
+            start: now,
+            bytes: 0,
+            lines: 0,
+            reported: false,
+        }
+    }
+
+    /// Count `bytes` of output that scrolled `lines`; returns the rates when
+    /// an interval has ended
+    pub fn record(&mut self, bytes: usize, lines: u64, now: Instant) -> Option<OutputStats> {
+        if self.bytes == 0 && self.lines == 0 && !self.reported {
+            // Idle until now: start the interval with this output
+            self.start = now;
+        }
+        self.bytes += bytes as u64;
+        self.lines += lines;
+        self.poll(now)
+    }
+
+    /// Return the rates if an interval has ended with output in it, or with
+    /// none after one that had some
+    pub fn poll(&mut self, now: Instant) -> Option<OutputStats> {
+        let elapsed = now.saturating_duration_since(self.start);
+        if elapsed < Self::INTERVAL {
+            return None;
+        }
+        let active = self.bytes > 0 || self.lines > 0;
+        if !active && !self.reported {
+            return None;
+        }
+        let millis = elapsed.as_millis().max(1) as u64;
+        let stats = OutputStats {
+            bytes_per_sec: self.bytes * 1000 / millis,
+            lines_per_sec: self.lines * 1000 / millis,
+        };
+        self.start = now;
+        self.bytes = 0;
+        self.lines = 0;
+        self.reported = active;
+        Some(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throughput_meter() {
+        let start = Instant::now();
+        let mut meter = ThroughputMeter::new(start);
+        let at = |ms| start + Duration::from_millis(ms);
+
+        // Idle at first: nothing to report
+        assert_eq!(meter.poll(at(5000)), None);
+
+        // Output starts the interval, which reports once it has passed
+        assert_eq!(meter.record(1 << 20, 100, at(6000)), None);
+        assert_eq!(meter.record(1 << 20, 3900, at(6500)), None);
+        let stats = meter.record(0, 0, at(7000)).unwrap();
+        assert_eq!(stats.bytes_per_sec, 2 << 20);
+        assert_eq!(stats.lines_per_sec, 4000);
+        assert!(stats.is_fast());
+
+        // One report of zero once output stops, then quiet
+        assert_eq!(meter.poll(at(7500)), None);
+        assert_eq!(meter.poll(at(8000)), Some(OutputStats::default()));
+        assert_eq!(meter.poll(at(9000)), None);
+
+        assert_eq!(meter.record(500, 1, at(9000)), None);
+        assert_eq!(
+            meter.poll(at(10_000)),
+            Some(OutputStats {
+                bytes_per_sec: 500,
+                lines_per_sec: 1,
+            })
+        );
+    }
+}
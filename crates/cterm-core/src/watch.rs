@@ -0,0 +1,131 @@
+//! Output watch expressions
+//!
+//! A [`Watch`] counts the matches of a regular expression in a terminal's
+//! output. Only output since the watch was set is searched: the screen hands
+//! over each line as a line feed ends it, so the scrollback is never searched
+//! again, and full-screen apps on the alternate screen are left out.
+//! Frontends show the running count as a tab badge and may notify on
+//! [`WatchMatch::notify`].
+//!
+//! A [`LineHighlight`] looks at the same lines, giving those that match a
+//! background color.
+
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+use crate::color::Rgb;
+
+/// Background color for output lines matching a regular expression
+#[derive(Debug, Clone)]
+pub struct LineHighlight {
+    pub regex: Regex,
+    pub color: Rgb,
+}
+
+/// New matches found in one batch of output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchMatch {
+    /// Matches since the watch was set or last reset
+    pub count: u64,
+    /// The last line that matched
+    pub line: String,
+    /// Whether this is worth a notification (at most one per
+    /// [`Watch::NOTIFY_INTERVAL`])
+    pub notify: bool,
+}
+
+/// A regular expression counted over new output lines
+#[derive(Debug, Clone)]
+pub struct Watch {
+    regex: Regex,
+    count: u64,
+    last_notify: Option<Instant>,
+}
+
+impl Watch {
+    /// Minimum time between two matches that ask for a notification
+    pub const NOTIFY_INTERVAL: Duration = Duration::from_secs(10);
+
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            regex: Regex::new(pattern)?,
+            count: 0,
+            last_notify: None,
+        })
+    }
+
+    /// The expression being watched
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    /// Matches since the watch was set or last reset
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Start counting from zero again
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.last_notify = None;
+    }
+
+    /// Count the matches in newly completed `lines`
+    pub fn scan(&mut self, lines: &[String], now: Instant) -> Option<WatchMatch> {
+        let mut last = None;
+        for line in lines {
+            let found = self.regex.find_iter(line).filter(|m| !m.is_empty()).count();
+            if found > 0 {
+                self.count += found as u64;
+                last = Some(line);
+            }
+        }
+        let line = last?.clone();
+        let notify = self
+            .last_notify
+            .is_none_or(|at| now.saturating_duration_since(at) >= Self::NOTIFY_INTERVAL);
+        if notify {
+            self.last_notify = Some(now);
+        }
+        Some(WatchMatch {
+            count: self.count,
+            line,
+            notify,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_scan() {
+        let start = Instant::now();
+        let mut watch = Watch::new("ERROR|FATAL").unwrap();
+        let lines = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(watch.scan(&lines(&["ok", "fine"]), start), None);
+
+        let found = watch
+            .scan(&lines(&["ERROR one", "ok", "ERROR two FATAL"]), start)
+            .unwrap();
+        assert_eq!(found.count, 3);
+        assert_eq!(found.line, "ERROR two FATAL");
+        assert!(found.notify);
+
+        // Notifications are rate limited, the count is not
+        let found = watch
+            .scan(&lines(&["FATAL"]), start + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(found.count, 4);
+        assert!(!found.notify);
+        let later = start + Watch::NOTIFY_INTERVAL + Duration::from_secs(1);
+        assert!(watch.scan(&lines(&["ERROR"]), later).unwrap().notify);
+
+        watch.reset();
+        assert_eq!(watch.count(), 0);
+        assert_eq!(watch.pattern(), "ERROR|FATAL");
+    }
+}
@@ -0,0 +1,967 @@
+//! ZMODEM file transfers (`sz`/`rz`)
+//!
+//! [`detect`] spots a remote `sz` or `rz` starting a transfer in the output
+//! stream. From there a [`Zmodem`] session consumes the output instead of the
+//! parser until the transfer ends:
+//!
+//! - Receiving (remote `sz`): files are accepted as they are offered and
+//!   reported as [`TransferEvent::Received`] once complete.
+//! - Sending (remote `rz`): [`TransferEvent::SendRequested`] asks the frontend
+//!   for files, which it hands over with [`Zmodem::send_files`] (or declines
+//!   with [`Zmodem::cancel`]).
+//!
+//! Bytes to write back to the PTY accumulate in [`Zmodem::take_output`]. The
+//! session keeps no clock: a transfer that stalls is ended by the user
+//! cancelling it or by the output turning into something other than ZMODEM.
+
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::file_transfer::{TransferDirection, TransferEvent, TransferFile, MAX_FILE_SIZE};
+
+/// Header lead-in
+const ZPAD: u8 = b'*';
+/// Escape character (also CAN)
+const ZDLE: u8 = 0x18;
+/// Binary header with a 16-bit CRC
+const ZBIN: u8 = b'A';
+/// Hex header
+const ZHEX: u8 = b'B';
+/// Binary header with a 32-bit CRC
+const ZBIN32: u8 = b'C';
+
+// Frame types
+const ZRQINIT: u8 = 0;
+const ZRINIT: u8 = 1;
+const ZSINIT: u8 = 2;
+const ZACK: u8 = 3;
+const ZFILE: u8 = 4;
+const ZSKIP: u8 = 5;
+const ZNAK: u8 = 6;
+const ZABORT: u8 = 7;
+const ZFIN: u8 = 8;
+const ZRPOS: u8 = 9;
+const ZDATA: u8 = 10;
+const ZEOF: u8 = 11;
+const ZFERR: u8 = 12;
+const ZCAN: u8 = 16;
+const ZCOMMAND: u8 = 18;
+
+// Data subpacket terminators
+const ZCRCE: u8 = b'h';
+const ZCRCG: u8 = b'i';
+const ZCRCQ: u8 = b'j';
+const ZCRCW: u8 = b'k';
+const ZRUB0: u8 = b'l';
+const ZRUB1: u8 = b'm';
+
+// ZRINIT capabilities (ZF0)
+const CANFDX: u8 = 0x01;
+const CANOVIO: u8 = 0x02;
+const CANFC32: u8 = 0x20;
+
+/// ZFILE conversion option (ZF0): binary transfer
+const ZCBIN: u8 = 1;
+
+const XON: u8 = 0x11;
+const XOFF: u8 = 0x13;
+
+/// Bytes per data subpacket when sending
+const SUBPACKET_SIZE: usize = 1024;
+/// Longest data subpacket accepted
+const MAX_SUBPACKET: usize = 8192;
+/// Bytes of non-ZMODEM output that end a session
+const GARBAGE_LIMIT: usize = 256;
+
+/// Cancels a transfer: CANs, then backspaces to erase them from a shell line
+const CANCEL_SEQUENCE: [u8; 20] = [
+    ZDLE, ZDLE, ZDLE, ZDLE, ZDLE, ZDLE, ZDLE, ZDLE, ZDLE, ZDLE, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8,
+];
+
+/// Find the start of a transfer: the ZRQINIT an `sz` or the ZRINIT an `rz`
+/// sends first. Returns where the header begins and which way files go.
+pub fn detect(data: &[u8]) -> Option<(usize, TransferDirection)> {
+    const START: &[u8] = b"**\x18B0";
+    data.windows(START.len() + 1)
+        .enumerate()
+        .find_map(|(i, window)| {
+            if &window[..START.len()] != START {
+                return None;
+            }
+            match window[START.len()] {
+                b'0' => Some((i, TransferDirection::Receive)),
+                b'1' => Some((i, TransferDirection::Send)),
+                _ => None,
+            }
+        })
+}
+
+/// Four header bytes (ZP0..ZP3, or ZF3..ZF0)
+type HeaderData = [u8; 4];
+
+/// A decoded header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Header {
+    kind: u8,
+    data: HeaderData,
+}
+
+impl Header {
+    fn new(kind: u8, data: HeaderData) -> Self {
+        Self { kind, data }
+    }
+
+    fn with_position(kind: u8, position: u32) -> Self {
+        Self::new(kind, position.to_le_bytes())
+    }
+
+    fn position(&self) -> u32 {
+        u32::from_le_bytes(self.data)
+    }
+
+    /// ZF0, the first flags byte
+    fn flags(&self) -> u8 {
+        self.data[3]
+    }
+
+    /// Whether a data subpacket follows this header
+    fn has_data(&self) -> bool {
+        matches!(self.kind, ZSINIT | ZFILE | ZDATA | ZCOMMAND)
+    }
+}
+
+/// What the decoder recognized
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Frame {
+    Header(Header),
+    /// A data subpacket and how it ended (ZCRCE, ZCRCG, ZCRCQ or ZCRCW)
+    Data(Vec<u8>, u8),
+    /// A header or subpacket failed its CRC or was malformed
+    Corrupt,
+    /// The other side sent a cancel sequence
+    Cancel,
+}
+
+/// Result of unescaping one byte
+enum Unescaped {
+    Byte(u8),
+    End(u8),
+    Pending,
+    Invalid,
+}
+
+#[derive(Debug)]
+enum DecodeState {
+    /// Looking for a header; `stage` counts matched lead-in bytes
+    Seek {
+        stage: u8,
+    },
+    Hex(Vec<u8>),
+    Binary {
+        crc32: bool,
+        buf: Vec<u8>,
+    },
+    Data {
+        crc32: bool,
+        buf: Vec<u8>,
+        end: Option<u8>,
+        crc: Vec<u8>,
+    },
+}
+
+/// Splits the incoming byte stream into frames
+#[derive(Debug)]
+struct Decoder {
+    state: DecodeState,
+    escaped: bool,
+    /// Consecutive CAN bytes
+    cans: usize,
+    /// Bytes seen since the last frame while looking for a header
+    garbage: usize,
+}
+
+impl Decoder {
+    fn new() -> Self {
+        Self {
+            state: DecodeState::Seek { stage: 0 },
+            escaped: false,
+            cans: 0,
+            garbage: 0,
+        }
+    }
+
+    fn unescape(&mut self, byte: u8) -> Unescaped {
+        if self.escaped {
+            if byte == ZDLE {
+                return Unescaped::Pending;
+            }
+            self.escaped = false;
+            return match byte {
+                ZCRCE | ZCRCG | ZCRCQ | ZCRCW => Unescaped::End(byte),
+                ZRUB0 => Unescaped::Byte(0x7f),
+                ZRUB1 => Unescaped::Byte(0xff),
+                b if b & 0x60 == 0x40 => Unescaped::Byte(b ^ 0x40),
+                _ => Unescaped::Invalid,
+            };
+        }
+        match byte {
+            ZDLE => {
+                self.escaped = true;
+                Unescaped::Pending
+            }
+            // Flow control is always escaped when it's data
+            b if b & 0x7f == XON || b & 0x7f == XOFF => Unescaped::Pending,
+            b => Unescaped::Byte(b),
+        }
+    }
+
+    /// Take one byte; returns a frame when one is complete
+    fn push(&mut self, byte: u8) -> Option<Frame> {
+        if byte == ZDLE {
+            self.cans += 1;
+            if self.cans >= 5 {
+                self.cans = 0;
+                self.reset();
+                return Some(Frame::Cancel);
+            }
+        } else {
+            self.cans = 0;
+        }
+
+        match self.state {
+            DecodeState::Seek { stage } => {
+                self.garbage += 1;
+                let stage = match (stage, byte) {
+                    (_, ZPAD) => 1,
+                    (1, ZDLE) => 2,
+                    (2, ZHEX) => {
+                        self.state = DecodeState::Hex(Vec::with_capacity(14));
+                        return None;
+                    }
+                    (2, ZBIN | ZBIN32) => {
+                        self.state = DecodeState::Binary {
+                            crc32: byte == ZBIN32,
+                            buf: Vec::with_capacity(9),
+                        };
+                        return None;
+                    }
+                    _ => 0,
+                };
+                self.state = DecodeState::Seek { stage };
+                None
+            }
+            DecodeState::Hex(ref mut buf) => {
+                buf.push(byte);
+                if buf.len() < 14 {
+                    return None;
+                }
+                let frame = decode_hex_header(buf).map_or(Frame::Corrupt, Frame::Header);
+                self.reset();
+                Some(frame)
+            }
+            DecodeState::Binary { .. } => {
+                let byte = match self.unescape(byte) {
+                    Unescaped::Byte(b) => b,
+                    Unescaped::Pending => return None,
+                    Unescaped::End(_) | Unescaped::Invalid => {
+                        self.reset();
+                        return Some(Frame::Corrupt);
+                    }
+                };
+                let DecodeState::Binary { crc32, ref mut buf } = self.state else {
+                    return None;
+                };
+                buf.push(byte);
+                if buf.len() < 5 + crc_len(crc32) {
+                    return None;
+                }
+                let header = decode_binary_header(buf, crc32);
+                match header {
+                    Some(header) if header.has_data() => {
+                        self.state = DecodeState::Data {
+                            crc32,
+                            buf: Vec::new(),
+                            end: None,
+                            crc: Vec::with_capacity(4),
+                        };
+                        self.garbage = 0;
+                    }
+                    _ => self.reset(),
+                }
+                Some(header.map_or(Frame::Corrupt, Frame::Header))
+            }
+            DecodeState::Data { .. } => {
+                let unescaped = self.unescape(byte);
+                let DecodeState::Data {
+                    crc32,
+                    ref mut buf,
+                    ref mut end,
+                    ref mut crc,
+                } = self.state
+                else {
+                    return None;
+                };
+                match (unescaped, *end) {
+                    (Unescaped::Pending, _) => return None,
+                    (Unescaped::Byte(b), None) if buf.len() < MAX_SUBPACKET => buf.push(b),
+                    (Unescaped::End(e), None) => *end = Some(e),
+                    (Unescaped::Byte(b), Some(_)) => crc.push(b),
+                    _ => {
+                        self.reset();
+                        return Some(Frame::Corrupt);
+                    }
+                }
+                let end = (*end)?;
+                if crc.len() < crc_len(crc32) {
+                    return None;
+                }
+
+                let mut checked = std::mem::take(buf);
+                checked.push(end);
+                let valid = if crc32 {
+                    crc.as_slice() == crc32_of(&checked).to_le_bytes()
+                } else {
+                    crc.as_slice() == crc16(&checked).to_be_bytes()
+                };
+                checked.pop();
+                if !valid {
+                    self.reset();
+                    return Some(Frame::Corrupt);
+                }
+                if matches!(end, ZCRCG | ZCRCQ) {
+                    // More subpackets follow
+                    self.state = DecodeState::Data {
+                        crc32,
+                        buf: Vec::new(),
+                        end: None,
+                        crc: Vec::with_capacity(4),
+                    };
+                } else {
+                    self.reset();
+                }
+                Some(Frame::Data(checked, end))
+            }
+        }
+    }
+
+    /// Go back to looking for a header
+    fn reset(&mut self) {
+        self.state = DecodeState::Seek { stage: 0 };
+        self.escaped = false;
+        self.garbage = 0;
+    }
+}
+
+fn crc_len(crc32: bool) -> usize {
+    if crc32 {
+        4
+    } else {
+        2
+    }
+}
+
+/// CRC-16/XMODEM
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// CRC-32 as used by zlib
+fn crc32_of(data: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+fn decode_hex_header(hex: &[u8]) -> Option<Header> {
+    let text = std::str::from_utf8(hex).ok()?;
+    let mut bytes = [0u8; 7];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(text.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    if crc16(&bytes[..5]).to_be_bytes() != bytes[5..] {
+        return None;
+    }
+    Some(Header::new(
+        bytes[0],
+        [bytes[1], bytes[2], bytes[3], bytes[4]],
+    ))
+}
+
+fn decode_binary_header(raw: &[u8], crc32: bool) -> Option<Header> {
+    let valid = if crc32 {
+        crc32_of(&raw[..5]).to_le_bytes() == raw[5..9]
+    } else {
+        crc16(&raw[..5]).to_be_bytes() == raw[5..7]
+    };
+    valid.then(|| Header::new(raw[0], [raw[1], raw[2], raw[3], raw[4]]))
+}
+
+/// Append `data` ZDLE-escaped
+fn escape_into(out: &mut Vec<u8>, data: &[u8]) {
+    for &byte in data {
+        let after_at = out.last().is_some_and(|&last| last & 0x7f == b'@');
+        match byte {
+            ZDLE | 0x10 | 0x90 | XON | 0x91 | XOFF | 0x93 => out.extend([ZDLE, byte ^ 0x40]),
+            0x0d | 0x8d if after_at => out.extend([ZDLE, byte ^ 0x40]),
+            _ => out.push(byte),
+        }
+    }
+}
+
+fn encode_hex_header(out: &mut Vec<u8>, header: Header) {
+    let mut raw = vec![header.kind];
+    raw.extend(header.data);
+    raw.extend(crc16(&raw).to_be_bytes());
+    out.extend([ZPAD, ZPAD, ZDLE, ZHEX]);
+    for byte in raw {
+        out.extend(format!("{:02x}", byte).bytes());
+    }
+    out.extend([b'\r', 0x8a]);
+    if header.kind != ZFIN && header.kind != ZACK {
+        out.push(XON);
+    }
+}
+
+fn encode_binary_header(out: &mut Vec<u8>, header: Header, crc32: bool) {
+    let mut raw = vec![header.kind];
+    raw.extend(header.data);
+    if crc32 {
+        raw.extend(crc32_of(&raw).to_le_bytes());
+        out.extend([ZPAD, ZDLE, ZBIN32]);
+    } else {
+        raw.extend(crc16(&raw).to_be_bytes());
+        out.extend([ZPAD, ZDLE, ZBIN]);
+    }
+    escape_into(out, &raw);
+}
+
+fn encode_subpacket(out: &mut Vec<u8>, data: &[u8], end: u8, crc32: bool) {
+    escape_into(out, data);
+    out.extend([ZDLE, end]);
+    let mut checked = data.to_vec();
+    checked.push(end);
+    if crc32 {
+        escape_into(out, &crc32_of(&checked).to_le_bytes());
+    } else {
+        escape_into(out, &crc16(&checked).to_be_bytes());
+    }
+}
+
+/// A file being received
+#[derive(Debug)]
+struct Incoming {
+    name: String,
+    data: Vec<u8>,
+    /// Discard subpackets until the sender repositions with ZDATA
+    skipping: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Receiving,
+    /// Waiting for the frontend's files
+    AwaitingFiles,
+    /// ZFILE sent, waiting for ZRPOS or ZSKIP
+    Offered,
+    /// Data and ZEOF sent, waiting for ZRINIT
+    Sent,
+    /// ZFIN sent, waiting for the receiver's ZFIN
+    Finishing,
+    /// Dropping the end of the last header and, after we received, the
+    /// sender's "OO"
+    Closing {
+        over_and_out: usize,
+    },
+    Done,
+}
+
+/// One ZMODEM transfer
+#[derive(Debug)]
+pub struct Zmodem {
+    direction: TransferDirection,
+    phase: Phase,
+    decoder: Decoder,
+    /// Header whose data subpackets are being received
+    last_header: Option<Header>,
+    incoming: Option<Incoming>,
+    /// Files still to send, current first
+    outgoing: VecDeque<TransferFile>,
+    /// Whether the receiver takes 32-bit CRCs
+    crc32: bool,
+    requested: bool,
+    output: Vec<u8>,
+    events: Vec<TransferEvent>,
+}
+
+impl Zmodem {
+    pub fn new(direction: TransferDirection) -> Self {
+        Self {
+            direction,
+            phase: match direction {
+                TransferDirection::Receive => Phase::Receiving,
+                TransferDirection::Send => Phase::AwaitingFiles,
+            },
+            decoder: Decoder::new(),
+            last_header: None,
+            incoming: None,
+            outgoing: VecDeque::new(),
+            crc32: false,
+            requested: false,
+            output: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn direction(&self) -> TransferDirection {
+        self.direction
+    }
+
+    /// Whether the transfer is over
+    pub fn is_done(&self) -> bool {
+        self.phase == Phase::Done
+    }
+
+    /// Bytes to write to the PTY
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Events since last taken
+    pub fn take_events(&mut self) -> Vec<TransferEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Feed output from the remote; returns how many bytes were consumed. Once
+    /// the transfer is over the rest is regular terminal output.
+    pub fn feed(&mut self, data: &[u8]) -> usize {
+        for (i, &byte) in data.iter().enumerate() {
+            if let Phase::Closing { over_and_out } = self.phase {
+                match byte {
+                    b'\r' | b'\n' | 0x8a | XON => continue,
+                    b'O' if over_and_out < 2 => {
+                        self.phase = Phase::Closing {
+                            over_and_out: over_and_out + 1,
+                        };
+                        continue;
+                    }
+                    _ => {
+                        self.phase = Phase::Done;
+                        return i;
+                    }
+                }
+            }
+            if let Some(frame) = self.decoder.push(byte) {
+                self.handle(frame);
+            } else if self.decoder.garbage > GARBAGE_LIMIT && !self.resyncing() {
+                log::warn!("ZMODEM: no frames from the remote, ending the transfer");
+                self.phase = Phase::Done;
+            }
+            if self.phase == Phase::Done {
+                return i + 1;
+            }
+        }
+        if let Phase::Closing { over_and_out: 2 } = self.phase {
+            self.phase = Phase::Done;
+        }
+        data.len()
+    }
+
+    /// Whether the rest of a streamed file is being dropped after a corrupt
+    /// subpacket, until the sender repositions
+    fn resyncing(&self) -> bool {
+        self.incoming.as_ref().is_some_and(|file| file.skipping)
+    }
+
+    /// Send `files` to the waiting `rz`; an empty list cancels
+    pub fn send_files(&mut self, files: Vec<TransferFile>) {
+        if self.phase != Phase::AwaitingFiles {
+            return;
+        }
+        if files.is_empty() {
+            self.cancel();
+            return;
+        }
+        self.outgoing = files.into();
+        self.offer();
+    }
+
+    /// Abort the transfer
+    pub fn cancel(&mut self) {
+        if self.phase != Phase::Done {
+            self.output.extend(CANCEL_SEQUENCE);
+            self.phase = Phase::Done;
+        }
+    }
+
+    fn handle(&mut self, frame: Frame) {
+        match frame {
+            Frame::Cancel => {
+                log::info!("ZMODEM: transfer cancelled by the remote");
+                self.phase = Phase::Done;
+            }
+            Frame::Header(header) => {
+                self.last_header = header.has_data().then_some(header);
+                match self.direction {
+                    TransferDirection::Receive => self.receive_header(header),
+                    TransferDirection::Send => self.send_header(header),
+                }
+            }
+            Frame::Data(data, end) => self.receive_data(data, end),
+            Frame::Corrupt => {
+                log::debug!("ZMODEM: corrupt frame");
+                if self.direction == TransferDirection::Receive {
+                    match self.incoming {
+                        Some(ref mut file) => {
+                            file.skipping = true;
+                            let position = file.data.len() as u32;
+                            self.hex(Header::with_position(ZRPOS, position));
+                        }
+                        None => self.hex(Header::new(ZNAK, [0; 4])),
+                    }
+                }
+            }
+        }
+    }
+
+    fn receive_header(&mut self, header: Header) {
+        match header.kind {
+            ZRQINIT => self.hex(Header::new(ZRINIT, [0, 0, 0, CANFDX | CANOVIO | CANFC32])),
+            ZDATA => {
+                let Some(ref mut file) = self.incoming else {
+                    return;
+                };
+                let position = file.data.len() as u32;
+                file.skipping = header.position() != position;
+                if file.skipping {
+                    self.hex(Header::with_position(ZRPOS, position));
+                }
+            }
+            ZEOF => {
+                let complete = self
+                    .incoming
+                    .as_ref()
+                    .is_some_and(|file| file.data.len() as u32 == header.position());
+                if !complete {
+                    return;
+                }
+                if let Some(file) = self.incoming.take() {
+                    log::info!("ZMODEM: received {} ({} bytes)", file.name, file.data.len());
+                    self.events.push(TransferEvent::Received {
+                        name: file.name,
+                        data: file.data,
+                    });
+                }
+                self.hex(Header::new(ZRINIT, [0, 0, 0, CANFDX | CANOVIO | CANFC32]));
+            }
+            ZFIN => {
+                self.hex(Header::new(ZFIN, [0; 4]));
+                self.phase = Phase::Closing { over_and_out: 0 };
+            }
+            ZABORT | ZFERR | ZCAN => self.phase = Phase::Done,
+            _ => {}
+        }
+    }
+
+    fn receive_data(&mut self, data: Vec<u8>, end: u8) {
+        let Some(header) = self.last_header else {
+            return;
+        };
+        match header.kind {
+            ZSINIT => self.hex(Header::new(ZACK, [0; 4])),
+            ZFILE if self.direction == TransferDirection::Receive => self.file_offered(&data),
+            ZDATA => {
+                let Some(ref mut file) = self.incoming else {
+                    return;
+                };
+                if file.skipping {
+                    return;
+                }
+                if (file.data.len() + data.len()) as u64 > MAX_FILE_SIZE {
+                    log::warn!("ZMODEM: {} is too large, cancelling", file.name);
+                    self.cancel();
+                    return;
+                }
+                file.data.extend_from_slice(&data);
+                let position = file.data.len() as u32;
+                if matches!(end, ZCRCQ | ZCRCW) {
+                    self.hex(Header::with_position(ZACK, position));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Accept or skip the file described by a ZFILE subpacket
+    fn file_offered(&mut self, info: &[u8]) {
+        let mut fields = info.split(|&b| b == 0);
+        let path = String::from_utf8_lossy(fields.next().unwrap_or_default());
+        let name = path
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let size: Option<u64> = fields
+            .next()
+            .and_then(|rest| std::str::from_utf8(rest).ok())
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|size| size.parse().ok());
+
+        if name.is_empty() || size.is_some_and(|size| size > MAX_FILE_SIZE) {
+            log::warn!("ZMODEM: skipping {:?} ({:?} bytes)", path, size);
+            self.hex(Header::new(ZSKIP, [0; 4]));
+            return;
+        }
+        let capacity = size.unwrap_or(0).min(1 << 20) as usize;
+        self.incoming = Some(Incoming {
+            name,
+            data: Vec::with_capacity(capacity),
+            skipping: false,
+        });
+        self.hex(Header::with_position(ZRPOS, 0));
+    }
+
+    fn send_header(&mut self, header: Header) {
+        match (header.kind, self.phase) {
+            (ZRINIT, Phase::AwaitingFiles) => {
+                self.crc32 = header.flags() & CANFC32 != 0;
+                if !std::mem::replace(&mut self.requested, true) {
+                    self.events.push(TransferEvent::SendRequested);
+                }
+            }
+            // The receiver missed our offer
+            (ZRINIT | ZNAK, Phase::Offered) => self.offer(),
+            (ZRINIT, Phase::Sent) | (ZSKIP, Phase::Offered | Phase::Sent) => {
+                self.outgoing.pop_front();
+                self.offer();
+            }
+            (ZRPOS, Phase::Offered | Phase::Sent) => self.send_data(header.position() as usize),
+            (ZFIN, Phase::Finishing) => {
+                self.output.extend(b"OO");
+                self.phase = Phase::Closing { over_and_out: 2 };
+            }
+            (ZABORT | ZFERR | ZCAN, _) => self.phase = Phase::Done,
+            _ => {}
+        }
+    }
+
+    /// Offer the next file, or end the session when none are left
+    fn offer(&mut self) {
+        let Some(file) = self.outgoing.front() else {
+            self.hex(Header::new(ZFIN, [0; 4]));
+            self.phase = Phase::Finishing;
+            return;
+        };
+        let mtime = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let remaining: usize = self.outgoing.iter().map(|f| f.data.len()).sum();
+        let mut info = file.name.as_bytes().to_vec();
+        info.push(0);
+        info.extend(
+            format!(
+                "{} {:o} 100644 0 {} {}",
+                file.data.len(),
+                mtime,
+                self.outgoing.len(),
+                remaining
+            )
+            .bytes(),
+        );
+        info.push(0);
+
+        encode_binary_header(
+            &mut self.output,
+            Header::new(ZFILE, [0, 0, 0, ZCBIN]),
+            self.crc32,
+        );
+        encode_subpacket(&mut self.output, &info, ZCRCW, self.crc32);
+        self.phase = Phase::Offered;
+    }
+
+    /// Send the current file from `position`, then ZEOF
+    fn send_data(&mut self, position: usize) {
+        let Some(file) = self.outgoing.front() else {
+            return;
+        };
+        let position = position.min(file.data.len());
+        encode_binary_header(
+            &mut self.output,
+            Header::with_position(ZDATA, position as u32),
+            self.crc32,
+        );
+        let rest = &file.data[position..];
+        let mut chunks = rest.chunks(SUBPACKET_SIZE).peekable();
+        if chunks.peek().is_none() {
+            encode_subpacket(&mut self.output, &[], ZCRCE, self.crc32);
+        }
+        while let Some(chunk) = chunks.next() {
+            let end = if chunks.peek().is_some() {
+                ZCRCG
+            } else {
+                ZCRCE
+            };
+            encode_subpacket(&mut self.output, chunk, end, self.crc32);
+        }
+        encode_binary_header(
+            &mut self.output,
+            Header::with_position(ZEOF, file.data.len() as u32),
+            self.crc32,
+        );
+        self.phase = Phase::Sent;
+    }
+
+    fn hex(&mut self, header: Header) {
+        encode_hex_header(&mut self.output, header);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What `sz` sends first
+    fn zrqinit() -> Vec<u8> {
+        let mut out = b"rz\r".to_vec();
+        encode_hex_header(&mut out, Header::new(ZRQINIT, [0; 4]));
+        out
+    }
+
+    /// Run a transfer between our sender and our receiver
+    fn transfer(files: Vec<TransferFile>, mangle: impl Fn(&mut Vec<u8>)) -> Vec<TransferEvent> {
+        let mut receiver = Zmodem::new(TransferDirection::Receive);
+        let mut sender = Zmodem::new(TransferDirection::Send);
+        receiver.feed(&zrqinit());
+
+        let mut received = Vec::new();
+        for _ in 0..100 {
+            let to_sender = receiver.take_output();
+            sender.feed(&to_sender);
+            if sender.take_events() == [TransferEvent::SendRequested] {
+                sender.send_files(files.clone());
+            }
+            let mut to_receiver = sender.take_output();
+            mangle(&mut to_receiver);
+            receiver.feed(&to_receiver);
+            received.extend(receiver.take_events());
+            if sender.is_done() && receiver.is_done() {
+                return received;
+            }
+        }
+        panic!("transfer did not finish");
+    }
+
+    #[test]
+    fn test_detect() {
+        let mut data = b"$ sz notes.txt\r\n".to_vec();
+        let start = data.len();
+        data.extend(zrqinit().split_off(3));
+        assert_eq!(detect(&data), Some((start, TransferDirection::Receive)));
+
+        let mut zrinit = Vec::new();
+        encode_hex_header(&mut zrinit, Header::new(ZRINIT, [0, 0, 0, CANFC32]));
+        assert_eq!(detect(&zrinit), Some((0, TransferDirection::Send)));
+        assert_eq!(detect(b"**\x18B"), None);
+        assert_eq!(detect(b"plain ** text"), None);
+    }
+
+    #[test]
+    fn test_headers_round_trip() {
+        for crc32 in [false, true] {
+            let header = Header::with_position(ZDATA, 0x1811_1390);
+            let mut out = Vec::new();
+            encode_binary_header(&mut out, header, crc32);
+            let mut decoder = Decoder::new();
+            let frames: Vec<_> = out.iter().filter_map(|&b| decoder.push(b)).collect();
+            assert_eq!(frames, [Frame::Header(header)]);
+        }
+
+        let header = Header::new(ZRINIT, [0, 0, 0, CANFDX | CANFC32]);
+        let mut out = Vec::new();
+        encode_hex_header(&mut out, header);
+        let mut decoder = Decoder::new();
+        let frames: Vec<_> = out.iter().filter_map(|&b| decoder.push(b)).collect();
+        assert_eq!(frames, [Frame::Header(header)]);
+    }
+
+    #[test]
+    fn test_transfer() {
+        // Every byte value, so all the escapes are exercised
+        let binary: Vec<u8> = (0..5000u32).map(|i| (i * 7) as u8).collect();
+        let files = vec![
+            TransferFile {
+                name: "all-bytes.bin".into(),
+                data: binary.clone(),
+            },
+            TransferFile {
+                name: "empty".into(),
+                data: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            transfer(files, |_| {}),
+            [
+                TransferEvent::Received {
+                    name: "all-bytes.bin".into(),
+                    data: binary,
+                },
+                TransferEvent::Received {
+                    name: "empty".into(),
+                    data: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_corrupt_data_is_resent() {
+        let data = vec![b'x'; 3000];
+        let files = vec![TransferFile {
+            name: "x.txt".into(),
+            data: data.clone(),
+        }];
+        // Flip a byte in the middle of the second subpacket, once
+        let damaged = std::cell::Cell::new(false);
+        let events = transfer(files, |out| {
+            if out.len() > 2000 && !damaged.replace(true) {
+                out[1500] ^= 0x01;
+            }
+        });
+        assert_eq!(
+            events,
+            [TransferEvent::Received {
+                name: "x.txt".into(),
+                data,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_output_after_transfer_is_returned() {
+        let mut sender = Zmodem::new(TransferDirection::Send);
+        let mut zrinit = Vec::new();
+        encode_hex_header(&mut zrinit, Header::new(ZRINIT, [0; 4]));
+        assert_eq!(sender.feed(&zrinit), zrinit.len());
+        sender.send_files(Vec::new());
+        assert!(sender.is_done());
+        assert!(sender.take_output().starts_with(&[ZDLE; 10]));
+
+        // The remote gives up; its shell prompt is regular output again
+        let mut receiver = Zmodem::new(TransferDirection::Receive);
+        let mut data = zrqinit();
+        data.extend([ZDLE; 5]);
+        data.extend(b"$ ");
+        assert_eq!(receiver.feed(&data), data.len() - 2);
+        assert!(receiver.is_done());
+    }
+}
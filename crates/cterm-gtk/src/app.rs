@@ -1,8 +1,10 @@
 //! Application setup and management
 
-use gtk4::{gdk, Application, CssProvider};
+use gtk4::prelude::*;
+use gtk4::{gdk, gio, glib, Application, CssProvider};
 
 use cterm_app::config::{load_config, Config};
+use cterm_app::control::ControlRequest;
 use cterm_ui::theme::Theme;
 
 use crate::window::CtermWindow;
@@ -19,6 +21,8 @@ pub fn build_ui(app: &Application) {
         log::warn!("Failed to load config, using defaults: {}", e);
         Config::default()
     });
+    cterm_app::usage_stats::configure(&config);
+    cterm_app::clipboard_history::configure(&config);
 
     // Load theme
     let theme = get_theme(&config);
@@ -26,6 +30,32 @@ pub fn build_ui(app: &Application) {
     // Apply CSS styling
     apply_css(&theme);
 
+    // `cterm --play` opens a window with just the recording
+    if let Some(ref path) = crate::get_args().play {
+        match cterm_core::Recording::open(path) {
+            Ok(recording) => {
+                let window = CtermWindow::new_empty(app, &config, &theme);
+                window.add_playback_tab(recording, path);
+                window.present();
+                cterm_app::safe_mode::finish_startup();
+                return;
+            }
+            Err(e) => log::error!("Failed to open recording {:?}: {}", path, e),
+        }
+    }
+
+    // `cterm --workspace` opens a window with just the workspace
+    if let Some(ref name) = crate::get_args().workspace {
+        if cterm_app::workspace::find(&config, name).is_some_and(|w| !w.tabs.is_empty()) {
+            let window = CtermWindow::new_empty(app, &config, &theme);
+            window.open_workspace(name);
+            window.present();
+            cterm_app::safe_mode::finish_startup();
+            return;
+        }
+        log::error!("No workspace named {:?}", name);
+    }
+
     // Try to reconnect to existing daemon sessions before creating a new one
     let reconnected = {
         let rt = tokio::runtime::Builder::new_current_thread()
@@ -62,9 +92,88 @@ pub fn build_ui(app: &Application) {
         log::info!("Reconnected to daemon sessions, skipping normal startup");
         window.present();
     } else {
-        // Normal startup - create the main window with a fresh session
-        let window = CtermWindow::new(app, &config, &theme);
-        window.present();
+        let restored = cterm_app::session::windows_to_restore(&config);
+        if restored.is_empty() {
+            // Normal startup - create the main window with a fresh session
+            let window = CtermWindow::new(app, &config, &theme);
+            window.present();
+        } else {
+            log::info!(
+                "Restoring {} window(s) from the last session",
+                restored.len()
+            );
+            for state in &restored {
+                let window = CtermWindow::restore(app, &config, &theme, state);
+                window.present();
+            }
+        }
+    }
+
+    // The first window is up, so this launch did not crash during startup
+    cterm_app::safe_mode::finish_startup();
+}
+
+/// Save the windows for the next launch, for `[general] restore_session`
+pub fn save_session() {
+    let config = load_config().unwrap_or_default();
+    cterm_app::session::save_at_exit(Vec::new(), &config);
+}
+
+/// Offer safe mode after the previous launch crashed during startup, then
+/// build the UI either way
+pub fn show_recovery_prompt(app: &Application) {
+    use cterm_app::safe_mode;
+    use gtk4::ResponseType;
+
+    // As an application window the dialog keeps the application running
+    let dialog = gtk4::MessageDialog::builder()
+        .application(app)
+        .message_type(gtk4::MessageType::Warning)
+        .text(safe_mode::RECOVERY_TITLE)
+        .secondary_text(safe_mode::recovery_message())
+        .build();
+    dialog.add_button("Start Normally", ResponseType::Reject);
+    dialog.add_button("Start in Safe Mode", ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Accept);
+
+    let app = app.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Accept {
+            safe_mode::enable();
+        }
+        build_ui(&app);
+        dialog.close();
+    });
+    dialog.present();
+}
+
+/// Carry out a `cterm new-tab` or `cterm new-window` request
+pub fn handle_control_request(request: ControlRequest) {
+    let Some(app) = gio::Application::default().and_then(|app| app.downcast::<Application>().ok())
+    else {
+        return;
+    };
+    let Some(window) = app.active_window() else {
+        app.activate();
+        return;
+    };
+    // Raise the window first so that a new window ends up in front of it
+    window.present();
+    let result = match &request {
+        ControlRequest::NewTab {
+            directory: Some(directory),
+        } => {
+            let directory = directory.to_string_lossy().into_owned();
+            window.activate_action("win.new-tab-in", Some(&directory.to_variant()))
+        }
+        ControlRequest::NewTab { directory: None } => window.activate_action("win.new-tab", None),
+        ControlRequest::NewWindow => window.activate_action("win.new-window", None),
+        ControlRequest::OpenWorkspace { name } => {
+            window.activate_action("win.open-workspace", Some(&name.to_variant()))
+        }
+    };
+    if let Err(e) = result {
+        log::warn!("Failed to carry out {:?}: {}", request, e);
     }
 }
 
@@ -97,6 +206,16 @@ fn apply_css(theme: &Theme) {
             padding: 0;
         }}
 
+        /* Below full opacity the terminal paints a translucent background,
+           so the window itself must not paint one behind it */
+        window.translucent {{
+            background-color: transparent;
+        }}
+
+        window.translucent menubar {{
+            background-color: {tab_bar_bg};
+        }}
+
         /* Tab bar styling */
         .tab-bar {{
             background-color: {tab_bar_bg};
@@ -144,6 +263,15 @@ fn apply_css(theme: &Theme) {
             font-size: 10px;
         }}
 
+        .tab-watch-badge {{
+            padding: 0 5px;
+            border-radius: 8px;
+            font-size: 10px;
+            font-weight: bold;
+            color: {tab_active_text};
+            background-color: alpha(#e8a735, 0.6);
+        }}
+
         .tab-close-button {{
             padding: 0 2px;
             min-width: 16px;
@@ -184,6 +312,17 @@ fn apply_css(theme: &Theme) {
             background-color: alpha({tab_active_bg}, 0.5);
         }}
 
+        /* Clock, timer and battery widgets */
+        .tab-widget {{
+            padding: 0 8px 4px 8px;
+            color: {tab_inactive_text};
+        }}
+
+        .tab-widget.emphasis {{
+            color: #e8a735;
+            font-weight: bold;
+        }}
+
         /* Notification bar */
         .notification-bar {{
             background-color: {tab_bar_bg};
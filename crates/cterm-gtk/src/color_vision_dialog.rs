@@ -0,0 +1,157 @@
+//! Color vision preview dialog for GTK4
+//!
+//! Shows the current theme's ANSI palette as seen with protanopia,
+//! deuteranopia or tritanopia, next to a derived palette adjusted to keep
+//! the colors apart, and lets the user switch to the derived theme.
+
+use std::rc::Rc;
+
+use gtk4::prelude::*;
+use gtk4::{Align, ComboBoxText, Dialog, Grid, Label, ResponseType, Window};
+
+use cterm_core::color::{ColorPalette, ColorVisionDeficiency, Rgb};
+use cterm_ui::theme::{Theme, CVD_MIN_DELTA_E};
+
+/// Labels updated when the simulated deficiency changes
+struct PreviewLabels {
+    original: Label,
+    simulated: Label,
+    adjusted: Label,
+    distance: Label,
+}
+
+/// Show the color vision dialog for `theme`
+///
+/// `on_apply` receives the adjusted theme when the user chooses to use it.
+pub fn show_color_vision_dialog<F>(parent: &impl IsA<Window>, theme: Theme, on_apply: F)
+where
+    F: Fn(Theme) + 'static,
+{
+    let dialog = Dialog::builder()
+        .title("Color Vision")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(520)
+        .build();
+
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.add_button("Use Adjusted Theme", ResponseType::Accept);
+
+    let content = dialog.content_area();
+    content.set_spacing(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(12);
+
+    let row_label = |text: &str, row: i32| {
+        let label = Label::new(Some(text));
+        label.set_halign(Align::End);
+        label.set_valign(Align::Start);
+        grid.attach(&label, 0, row, 1, 1);
+    };
+    let swatch_label = |row: i32| {
+        let label = Label::new(None);
+        label.set_halign(Align::Start);
+        grid.attach(&label, 1, row, 1, 1);
+        label
+    };
+
+    row_label("Simulate:", 0);
+    let cvd_combo = ComboBoxText::new();
+    for cvd in ColorVisionDeficiency::ALL {
+        cvd_combo.append_text(cvd.name());
+    }
+    // Deuteranopia is by far the most common
+    cvd_combo.set_active(Some(1));
+    cvd_combo.set_halign(Align::Start);
+    grid.attach(&cvd_combo, 1, 0, 1, 1);
+
+    row_label(&format!("{}:", theme.name), 1);
+    let original = swatch_label(1);
+    row_label("As seen:", 2);
+    let simulated = swatch_label(2);
+    row_label("Adjusted, as seen:", 3);
+    let adjusted = swatch_label(3);
+
+    content.append(&grid);
+
+    let distance = Label::new(None);
+    distance.set_halign(Align::Start);
+    distance.add_css_class("dim-label");
+    content.append(&distance);
+
+    let labels = Rc::new(PreviewLabels {
+        original,
+        simulated,
+        adjusted,
+        distance,
+    });
+    let theme = Rc::new(theme);
+
+    update_preview(&labels, &theme, selected_cvd(&cvd_combo));
+    {
+        let labels = Rc::clone(&labels);
+        let theme = Rc::clone(&theme);
+        cvd_combo.connect_changed(move |combo| {
+            update_preview(&labels, &theme, selected_cvd(combo));
+        });
+    }
+
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Accept {
+            on_apply(theme.adjusted_for_cvd(selected_cvd(&cvd_combo)));
+        }
+        dialog.close();
+    });
+
+    dialog.present();
+}
+
+fn selected_cvd(combo: &ComboBoxText) -> ColorVisionDeficiency {
+    combo
+        .active()
+        .and_then(|i| ColorVisionDeficiency::ALL.get(i as usize).copied())
+        .unwrap_or(ColorVisionDeficiency::Deuteranopia)
+}
+
+fn update_preview(labels: &PreviewLabels, theme: &Theme, cvd: ColorVisionDeficiency) {
+    let adjusted = theme.colors.adjusted_for_cvd(cvd, CVD_MIN_DELTA_E);
+
+    labels.original.set_markup(&palette_markup(&theme.colors));
+    labels
+        .simulated
+        .set_markup(&palette_markup(&theme.colors.simulate_cvd(cvd)));
+    labels
+        .adjusted
+        .set_markup(&palette_markup(&adjusted.simulate_cvd(cvd)));
+    labels.distance.set_text(&format!(
+        "Closest colors differ by \u{0394}E {:.1} as seen, {:.1} after adjusting (aiming for {:.0})",
+        theme.colors.min_cvd_distance(cvd),
+        adjusted.min_cvd_distance(cvd),
+        CVD_MIN_DELTA_E,
+    ));
+}
+
+/// Two lines of sample text, normal then bright ANSI colors, on the
+/// palette's background
+fn palette_markup(palette: &ColorPalette) -> String {
+    let background = palette.background.to_hex();
+    let line = |colors: &[Rgb]| {
+        colors
+            .iter()
+            .map(|c| {
+                format!(
+                    "<span font_family=\"monospace\" background=\"{}\" foreground=\"{}\"> Aa </span>",
+                    background,
+                    c.to_hex()
+                )
+            })
+            .collect::<String>()
+    };
+    format!("{}\n{}", line(&palette.ansi[..8]), line(&palette.ansi[8..]))
+}
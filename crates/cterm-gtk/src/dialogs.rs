@@ -7,12 +7,12 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use gtk4::prelude::*;
 use gtk4::{
     Align, Box as GtkBox, Button, ComboBoxText, Dialog, Entry, Grid, Label, Orientation,
-    ResponseType, ScrolledWindow, SpinButton, Switch, Window,
+    ResponseType, ScrolledWindow, SearchEntry, SpinButton, Switch, Window,
 };
 
-use cterm_app::config::{
-    config_dir, Config, CursorStyleConfig, NewTabPosition, TabBarPosition, TabBarVisibility,
-};
+use cterm_app::config::{config_dir, resolve_theme, Config};
+use cterm_app::live_config::Preview;
+use cterm_app::settings::{self, Setting, SettingCategory, SettingKind, Value};
 use cterm_app::{git_sync, PullResult};
 
 /// Type alias for the on_save callback to avoid clippy::type_complexity warning
@@ -89,6 +89,53 @@ where
     dialog.present();
 }
 
+/// Show the "Watch Output" dialog; an empty expression stops watching
+pub fn show_watch_dialog<F>(parent: &impl IsA<Window>, current_pattern: &str, callback: F)
+where
+    F: Fn(String) + 'static,
+{
+    let dialog = Dialog::builder()
+        .title("Watch Output")
+        .transient_for(parent)
+        .modal(true)
+        .build();
+
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("OK", ResponseType::Ok);
+
+    let content = dialog.content_area();
+    content.set_spacing(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+
+    let label = Label::new(Some(
+        "Count new output lines matching this regular expression\n(leave empty to stop watching):",
+    ));
+    label.set_halign(Align::Start);
+    content.append(&label);
+
+    let entry = Entry::new();
+    entry.set_text(current_pattern);
+    entry.set_placeholder_text(Some("ERROR|FATAL"));
+    entry.set_hexpand(true);
+    entry.set_activates_default(true);
+    content.append(&entry);
+
+    dialog.set_default_response(ResponseType::Ok);
+
+    let entry_clone = entry.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            callback(entry_clone.text().trim().to_string());
+        }
+        dialog.close();
+    });
+
+    dialog.present();
+}
+
 /// Show the "Set Color" dialog
 pub fn show_set_color_dialog<F>(parent: &impl IsA<Window>, callback: F)
 where
@@ -300,6 +347,93 @@ where
     dialog.present();
 }
 
+/// Show the images of a terminal, each with a Remove button, and a Clear All
+/// button
+///
+/// `images` pairs each image with its kitty image and placement ids, if any.
+/// `on_remove` is called with an image id, or `None` to clear them all.
+pub fn show_images_dialog<F>(
+    parent: &impl IsA<Window>,
+    images: Vec<(cterm_core::TerminalImage, Option<(u32, u32)>)>,
+    on_remove: F,
+) where
+    F: Fn(Option<u64>) + 'static,
+{
+    let dialog = Dialog::builder()
+        .title("Images")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(420)
+        .build();
+
+    dialog.add_button("Close", ResponseType::Close);
+    let clear_button = dialog.add_button("Clear All", ResponseType::Other(1));
+    clear_button.set_sensitive(!images.is_empty());
+
+    let content = dialog.content_area();
+    content.set_spacing(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+
+    let on_remove = Rc::new(on_remove);
+    if images.is_empty() {
+        let label = Label::new(Some("This terminal shows no images."));
+        label.set_halign(Align::Start);
+        content.append(&label);
+    } else {
+        let scroll = ScrolledWindow::new();
+        scroll.set_min_content_height(200);
+        scroll.set_vexpand(true);
+        let list = GtkBox::new(Orientation::Vertical, 4);
+        for (image, kitty) in images {
+            let row = GtkBox::new(Orientation::Horizontal, 12);
+            let mut text = format!(
+                "{}×{} px, {}×{} cells at column {}",
+                image.pixel_width,
+                image.pixel_height,
+                image.cell_width,
+                image.cell_height,
+                image.col + 1
+            );
+            if let Some((image_id, placement_id)) = kitty {
+                text.push_str(&format!(" (kitty image {}", image_id));
+                if placement_id != 0 {
+                    text.push_str(&format!(", placement {}", placement_id));
+                }
+                text.push(')');
+            }
+            let label = Label::new(Some(&text));
+            label.set_halign(Align::Start);
+            label.set_hexpand(true);
+            row.append(&label);
+
+            let remove = Button::with_label("Remove");
+            let on_remove = Rc::clone(&on_remove);
+            let list_clone = list.clone();
+            let row_clone = row.clone();
+            remove.connect_clicked(move |_| {
+                on_remove(Some(image.id));
+                list_clone.remove(&row_clone);
+            });
+            row.append(&remove);
+            list.append(&row);
+        }
+        scroll.set_child(Some(&list));
+        content.append(&scroll);
+    }
+
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Other(1) {
+            on_remove(None);
+        }
+        dialog.close();
+    });
+
+    dialog.present();
+}
+
 /// Show the About dialog
 pub fn show_about_dialog(parent: &impl IsA<Window>) {
     let about = gtk4::AboutDialog::builder()
@@ -317,26 +451,227 @@ pub fn show_about_dialog(parent: &impl IsA<Window>) {
     about.present();
 }
 
+/// Combo id of the theme made with the color vision tool
+const CUSTOM_THEME_ID: &str = "custom";
+
+/// Widget editing a registered setting, picked by the setting's kind
+#[derive(Clone)]
+enum SettingWidget {
+    Switch(Switch),
+    Spin(SpinButton),
+    Scale(gtk4::Scale),
+    Entry(Entry),
+    Combo(ComboBoxText),
+}
+
+impl SettingWidget {
+    /// Create the widget for `setting`, showing its value in `config`
+    fn new(setting: &Setting, config: &Config) -> Self {
+        let widget = match setting.kind {
+            SettingKind::Bool => {
+                let switch = Switch::new();
+                switch.set_halign(Align::Start);
+                SettingWidget::Switch(switch)
+            }
+            SettingKind::Integer { min, max, step } => {
+                SettingWidget::Spin(SpinButton::with_range(min as f64, max as f64, step as f64))
+            }
+            SettingKind::Float { min, max, step } => {
+                SettingWidget::Spin(SpinButton::with_range(min, max, step))
+            }
+            SettingKind::Ratio => {
+                let scale = gtk4::Scale::with_range(Orientation::Horizontal, 0.0, 1.0, 0.1);
+                scale.set_hexpand(true);
+                scale.set_width_request(200);
+                SettingWidget::Scale(scale)
+            }
+            SettingKind::Text => {
+                let entry = Entry::new();
+                entry.set_hexpand(true);
+                SettingWidget::Entry(entry)
+            }
+            SettingKind::Choice(_) | SettingKind::Theme => {
+                let combo = ComboBoxText::new();
+                let custom_name = config
+                    .appearance
+                    .custom_theme
+                    .as_ref()
+                    .map(|t| t.name.as_str());
+                fill_combo(&combo, setting, custom_name);
+                SettingWidget::Combo(combo)
+            }
+        };
+        widget.widget().set_tooltip_text(Some(setting.description));
+
+        match (&widget, &config.appearance.custom_theme) {
+            // A custom theme takes precedence over the named one
+            (SettingWidget::Combo(combo), Some(_)) if setting.kind == SettingKind::Theme => {
+                combo.set_active_id(Some(CUSTOM_THEME_ID));
+            }
+            _ => {
+                if let Some(value) = settings::value(config, setting.key) {
+                    widget.show(setting, &value);
+                }
+            }
+        }
+        widget
+    }
+
+    fn widget(&self) -> gtk4::Widget {
+        match self {
+            SettingWidget::Switch(switch) => switch.clone().upcast(),
+            SettingWidget::Spin(spin) => spin.clone().upcast(),
+            SettingWidget::Scale(scale) => scale.clone().upcast(),
+            SettingWidget::Entry(entry) => entry.clone().upcast(),
+            SettingWidget::Combo(combo) => combo.clone().upcast(),
+        }
+    }
+
+    /// Value shown, None if no option is selected
+    fn value(&self, setting: &Setting) -> Option<Value> {
+        Some(match self {
+            SettingWidget::Switch(switch) => Value::Boolean(switch.is_active()),
+            SettingWidget::Spin(spin) => match setting.kind {
+                SettingKind::Integer { .. } => Value::Integer(spin.value_as_int() as i64),
+                _ => Value::Float(spin.value()),
+            },
+            SettingWidget::Scale(scale) => Value::Float(scale.value()),
+            SettingWidget::Entry(entry) => Value::String(entry.text().to_string()),
+            SettingWidget::Combo(combo) => Value::String(combo.active_id()?.to_string()),
+        })
+    }
+
+    /// Show `value`
+    fn show(&self, setting: &Setting, value: &Value) {
+        match self {
+            SettingWidget::Switch(switch) => switch.set_active(value.as_bool().unwrap_or(false)),
+            SettingWidget::Spin(spin) => {
+                if let Some(n) = value.as_float().or(value.as_integer().map(|n| n as f64)) {
+                    spin.set_value(n);
+                }
+            }
+            SettingWidget::Scale(scale) => {
+                if let Some(n) = value.as_float() {
+                    scale.set_value(n);
+                }
+            }
+            SettingWidget::Entry(entry) => entry.set_text(&settings::format_value(value)),
+            SettingWidget::Combo(combo) => {
+                if let Some(index) = setting.choice_index(&setting.choices(), value) {
+                    combo.set_active(Some(index as u32));
+                }
+            }
+        }
+    }
+}
+
+/// Add the options of `setting` to `combo`, followed for the theme by the
+/// custom theme made with the color vision tool
+fn fill_combo(combo: &ComboBoxText, setting: &Setting, custom_theme: Option<&str>) {
+    for (value, label) in setting.choices() {
+        combo.append(Some(&value), &label);
+    }
+    if let Some(name) = custom_theme.filter(|_| setting.kind == SettingKind::Theme) {
+        combo.append(Some(CUSTOM_THEME_ID), name);
+    }
+}
+
+/// A registered setting's row: its editing widget, the widgets hidden when
+/// it does not match the search, and its reset button
+struct SettingRow {
+    setting: &'static Setting,
+    editor: SettingWidget,
+    // Adwaita preferences windows do their own searching
+    #[cfg_attr(feature = "adwaita", allow(dead_code))]
+    widgets: Vec<gtk4::Widget>,
+    reset_button: Button,
+}
+
+/// Rows of the settings schema, for collecting values, search and
+/// per-setting reset
+#[derive(Default)]
+struct SettingRows(RefCell<Vec<SettingRow>>);
+
+impl SettingRows {
+    /// Reset button for `setting`, registering the row made of `widgets`
+    fn register(
+        &self,
+        setting: &'static Setting,
+        editor: &SettingWidget,
+        widgets: &[gtk4::Widget],
+    ) -> Button {
+        let reset_button = Button::from_icon_name("edit-undo-symbolic");
+        reset_button.set_tooltip_text(Some("Reset to default"));
+        reset_button.set_valign(Align::Center);
+        reset_button.add_css_class("flat");
+        self.0.borrow_mut().push(SettingRow {
+            setting,
+            editor: editor.clone(),
+            widgets: widgets.to_vec(),
+            reset_button: reset_button.clone(),
+        });
+        reset_button
+    }
+
+    /// Attach a labelled row for `setting` to a preferences grid
+    #[cfg(not(feature = "adwaita"))]
+    fn attach(&self, grid: &Grid, row: i32, setting: &'static Setting, config: &Config) {
+        let label = Label::new(Some(&format!("{}:", setting.label)));
+        label.set_halign(Align::End);
+        label.set_tooltip_text(Some(setting.description));
+        let editor = SettingWidget::new(setting, config);
+        let widget = editor.widget();
+        grid.attach(&label, 0, row, 1, 1);
+        grid.attach(&widget, 1, row, 1, 1);
+        let reset_button = self.register(setting, &editor, &[label.upcast(), widget]);
+        grid.attach(&reset_button, 2, row, 1, 1);
+    }
+
+    /// Editing widget of setting `key`
+    fn editor(&self, key: &str) -> Option<SettingWidget> {
+        self.0
+            .borrow()
+            .iter()
+            .find(|row| row.setting.key == key)
+            .map(|row| row.editor.clone())
+    }
+
+    /// Make the reset buttons put their setting back to its default
+    fn connect_reset(&self, widgets: &Rc<PreferencesWidgets>) {
+        for row in self.0.borrow().iter() {
+            let widgets = Rc::clone(widgets);
+            let key = row.setting.key;
+            row.reset_button
+                .connect_clicked(move |_| widgets.reset_setting(key));
+        }
+    }
+
+    /// Show only the rows matching `query`, returning the number of matches
+    /// per category
+    #[cfg(not(feature = "adwaita"))]
+    fn filter(&self, query: &str) -> [usize; SettingCategory::ALL.len()] {
+        let mut matches = [0; SettingCategory::ALL.len()];
+        for row in self.0.borrow().iter() {
+            let visible = row.setting.matches(query);
+            for widget in row.widgets.iter().chain([row.reset_button.upcast_ref()]) {
+                widget.set_visible(visible);
+            }
+            if visible {
+                if let Some(i) = SettingCategory::ALL
+                    .iter()
+                    .position(|category| *category == row.setting.category)
+                {
+                    matches[i] += 1;
+                }
+            }
+        }
+        matches
+    }
+}
+
 /// Widgets for collecting preference values
 struct PreferencesWidgets {
-    // General
-    scrollback_spin: SpinButton,
-    confirm_switch: Switch,
-    copy_select_switch: Switch,
-    debug_menu_switch: Switch,
-    // Appearance
-    theme_combo: ComboBoxText,
-    font_entry: Entry,
-    size_spin: SpinButton,
-    cursor_combo: ComboBoxText,
-    blink_switch: Switch,
-    opacity_scale: gtk4::Scale,
-    bold_switch: Switch,
-    // Tabs
-    show_combo: ComboBoxText,
-    position_combo: ComboBoxText,
-    new_combo: ComboBoxText,
-    close_switch: Switch,
+    setting_rows: SettingRows,
     // Shortcuts
     shortcut_entries: Vec<(String, Entry)>,
     // Git Sync
@@ -347,6 +682,8 @@ struct PreferencesWidgets {
     git_changes_label: Label,
     on_save_callback: SaveCallback,
     base_config: Rc<RefCell<Config>>,
+    /// Appearance shown in open terminals while the dialog is open
+    preview: RefCell<Preview>,
 }
 
 impl PreferencesWidgets {
@@ -469,45 +806,89 @@ impl PreferencesWidgets {
         self.git_changes_label.set_text(changes_text);
     }
 
-    fn collect_config(&self, base_config: &Config) -> Config {
-        let mut config = base_config.clone();
+    /// Save the configuration and tool shortcuts, push them if git sync is
+    /// set up, and notify the caller
+    fn save(&self, tool_entries: &[ToolEntryRow]) {
+        let final_config = self.collect_config(&self.base_config.borrow());
 
-        // General
-        config.general.scrollback_lines = self.scrollback_spin.value() as usize;
-        config.general.confirm_close_with_running = self.confirm_switch.is_active();
-        config.general.copy_on_select = self.copy_select_switch.is_active();
-        config.general.show_debug_menu = self.debug_menu_switch.is_active();
+        if let Err(e) = cterm_app::config::save_config(&final_config) {
+            log::error!("Failed to save config: {}", e);
+        }
 
-        // Appearance
-        if let Some(theme_id) = self.theme_combo.active_id() {
-            config.appearance.theme = theme_id.to_string();
+        let tools: Vec<cterm_app::config::ToolShortcutEntry> = tool_entries
+            .iter()
+            .filter_map(|(name_e, cmd_e, args_e)| {
+                let name = name_e.text().to_string();
+                let command = cmd_e.text().to_string();
+                if name.is_empty() || command.is_empty() {
+                    return None;
+                }
+                let args = args_e
+                    .text()
+                    .split_whitespace()
+                    .map(|s| s.to_string())
+                    .collect();
+                Some(cterm_app::config::ToolShortcutEntry {
+                    name,
+                    command,
+                    args,
+                })
+            })
+            .collect();
+        if let Err(e) = cterm_app::config::save_tool_shortcuts(&tools) {
+            log::error!("Failed to save tool shortcuts: {}", e);
         }
-        config.appearance.font.family = self.font_entry.text().to_string();
-        config.appearance.font.size = self.size_spin.value();
-        config.appearance.cursor_style = match self.cursor_combo.active_id().as_deref() {
-            Some("underline") => CursorStyleConfig::Underline,
-            Some("bar") => CursorStyleConfig::Bar,
-            _ => CursorStyleConfig::Block,
-        };
-        config.appearance.cursor_blink = self.blink_switch.is_active();
-        config.appearance.opacity = self.opacity_scale.value();
-        config.appearance.bold_is_bright = self.bold_switch.is_active();
-
-        // Tabs
-        config.tabs.show_tab_bar = match self.show_combo.active_id().as_deref() {
-            Some("multiple") => TabBarVisibility::Multiple,
-            Some("never") => TabBarVisibility::Never,
-            _ => TabBarVisibility::Always,
-        };
-        config.tabs.tab_bar_position = match self.position_combo.active_id().as_deref() {
-            Some("bottom") => TabBarPosition::Bottom,
-            _ => TabBarPosition::Top,
-        };
-        config.tabs.new_tab_position = match self.new_combo.active_id().as_deref() {
-            Some("after_current") => NewTabPosition::AfterCurrent,
-            _ => NewTabPosition::End,
+
+        // If git sync is configured, commit and push
+        if let Some(dir) = config_dir() {
+            if git_sync::is_git_repo(&dir) && git_sync::get_remote_url(&dir).is_some() {
+                if let Err(e) = git_sync::commit_and_push(&dir, "Update configuration") {
+                    log::error!("Failed to push config: {}", e);
+                }
+            }
+        }
+
+        self.preview.borrow_mut().commit(final_config.clone());
+        if let Some(ref callback) = *self.on_save_callback.borrow() {
+            callback(final_config);
+        }
+    }
+
+    /// Show the edited appearance in open terminals without saving it
+    fn apply_preview(&self) {
+        let config = self.collect_config(&self.base_config.borrow());
+        self.preview.borrow_mut().apply(config);
+    }
+
+    /// Put a registered setting's widget back to the default value
+    fn reset_setting(&self, key: &str) {
+        let (Some(setting), Some(default)) = (settings::find(key), settings::default_value(key))
+        else {
+            return;
         };
-        config.tabs.show_close_button = self.close_switch.is_active();
+        if let Some(editor) = self.setting_rows.editor(key) {
+            editor.show(setting, &default);
+        }
+    }
+
+    fn collect_config(&self, base_config: &Config) -> Config {
+        let mut config = base_config.clone();
+
+        for row in self.setting_rows.0.borrow().iter() {
+            let Some(value) = row.editor.value(row.setting) else {
+                continue;
+            };
+            if row.setting.kind == SettingKind::Theme {
+                // The custom theme is stored apart from the named ones
+                if value.as_str() == Some(CUSTOM_THEME_ID) {
+                    continue;
+                }
+                config.appearance.custom_theme = None;
+            }
+            if let Err(e) = settings::set(&mut config, row.setting.key, value) {
+                log::warn!("{}", e);
+            }
+        }
 
         // Shortcuts
         for (name, entry) in &self.shortcut_entries {
@@ -540,6 +921,19 @@ pub fn show_preferences_dialog(
     parent: &impl IsA<Window>,
     config: &Config,
     on_save: impl Fn(Config) + 'static,
+) {
+    #[cfg(feature = "adwaita")]
+    show_adwaita_preferences(parent, config, on_save);
+    #[cfg(not(feature = "adwaita"))]
+    show_notebook_preferences(parent, config, on_save);
+}
+
+/// Preferences as a dialog with a notebook page per category
+#[cfg(not(feature = "adwaita"))]
+fn show_notebook_preferences(
+    parent: &impl IsA<Window>,
+    config: &Config,
+    on_save: impl Fn(Config) + 'static,
 ) {
     let dialog = Dialog::builder()
         .title("Preferences")
@@ -556,33 +950,30 @@ pub fn show_preferences_dialog(
     let content = dialog.content_area();
     content.set_spacing(0);
 
+    // Search entry filtering the settings on every tab
+    let search_entry = SearchEntry::new();
+    search_entry.set_placeholder_text(Some("Search settings"));
+    search_entry.set_margin_top(8);
+    search_entry.set_margin_bottom(8);
+    search_entry.set_margin_start(8);
+    search_entry.set_margin_end(8);
+    content.append(&search_entry);
+
     // Create notebook for preference categories
     let notebook = gtk4::Notebook::new();
     notebook.set_vexpand(true);
     content.append(&notebook);
 
-    // General tab
-    let (general_page, scrollback_spin, confirm_switch, copy_select_switch, debug_menu_switch) =
-        create_general_preferences(config);
-    notebook.append_page(&general_page, Some(&Label::new(Some("General"))));
-
-    // Appearance tab
-    let (
-        appearance_page,
-        theme_combo,
-        font_entry,
-        size_spin,
-        cursor_combo,
-        blink_switch,
-        opacity_scale,
-        bold_switch,
-    ) = create_appearance_preferences(config);
-    notebook.append_page(&appearance_page, Some(&Label::new(Some("Appearance"))));
-
-    // Tabs tab
-    let (tabs_page, show_combo, position_combo, new_combo, close_switch) =
-        create_tabs_preferences(config);
-    notebook.append_page(&tabs_page, Some(&Label::new(Some("Tabs"))));
+    // A page per settings category
+    let setting_rows = SettingRows::default();
+    let mut appearance_page = None;
+    for category in SettingCategory::ALL {
+        let page = create_settings_preferences(config, category, &setting_rows);
+        notebook.append_page(&page, Some(&Label::new(Some(category.name()))));
+        if category == SettingCategory::Appearance {
+            appearance_page = Some(page);
+        }
+    }
 
     // Shortcuts tab
     let (shortcuts_page, shortcut_entries) = create_shortcuts_preferences(config);
@@ -608,21 +999,7 @@ pub fn show_preferences_dialog(
     let base_config = Rc::new(RefCell::new(config.clone()));
 
     let widgets = Rc::new(PreferencesWidgets {
-        scrollback_spin,
-        confirm_switch,
-        copy_select_switch,
-        debug_menu_switch,
-        theme_combo,
-        font_entry,
-        size_spin,
-        cursor_combo,
-        blink_switch,
-        opacity_scale,
-        bold_switch,
-        show_combo,
-        position_combo,
-        new_combo,
-        close_switch,
+        setting_rows,
         shortcut_entries,
         git_remote_entry,
         git_status_label,
@@ -631,6 +1008,40 @@ pub fn show_preferences_dialog(
         git_changes_label,
         on_save_callback: Rc::clone(&on_save_callback),
         base_config: Rc::clone(&base_config),
+        preview: RefCell::new(Preview::new(config)),
+    });
+
+    if let Some(page) = appearance_page {
+        let color_vision_button = color_vision_button(&widgets);
+        color_vision_button.set_halign(Align::Start);
+        page.append(&color_vision_button);
+    }
+
+    widgets.setting_rows.connect_reset(&widgets);
+    let notebook_for_search = notebook.clone();
+    let widgets_for_search = Rc::clone(&widgets);
+    search_entry.connect_search_changed(move |entry| {
+        let query = entry.text();
+        let query = query.trim();
+        let matches = widgets_for_search.setting_rows.filter(query);
+        for (i, category) in SettingCategory::ALL.iter().enumerate() {
+            let Some(page) = notebook_for_search.nth_page(Some(i as u32)) else {
+                continue;
+            };
+            let label = if query.is_empty() {
+                category.name().to_string()
+            } else {
+                format!("{} ({})", category.name(), matches[i])
+            };
+            notebook_for_search.set_tab_label_text(&page, &label);
+        }
+        // Jump to the first tab with a match if the current one has none
+        let current = notebook_for_search.current_page().unwrap_or(0) as usize;
+        if !query.is_empty() && matches.get(current).copied().unwrap_or(0) == 0 {
+            if let Some(first) = matches.iter().position(|&count| count > 0) {
+                notebook_for_search.set_current_page(Some(first as u32));
+            }
+        }
     });
 
     // Connect sync button
@@ -640,63 +1051,15 @@ pub fn show_preferences_dialog(
     });
 
     let widgets_for_response = Rc::clone(&widgets);
-    let tool_entries_for_response = Rc::clone(&tool_entries);
     dialog.connect_response(move |dialog, response| match response {
-        ResponseType::Ok | ResponseType::Apply => {
-            let final_config =
-                widgets_for_response.collect_config(&widgets_for_response.base_config.borrow());
-
-            // Save config and sync if git is configured
-            if let Err(e) = cterm_app::config::save_config(&final_config) {
-                log::error!("Failed to save config: {}", e);
-            }
-
-            // Save tool shortcuts
-            {
-                let entries = tool_entries_for_response.borrow();
-                let tools: Vec<cterm_app::config::ToolShortcutEntry> = entries
-                    .iter()
-                    .filter_map(|(name_e, cmd_e, args_e)| {
-                        let name = name_e.text().to_string();
-                        let command = cmd_e.text().to_string();
-                        if name.is_empty() || command.is_empty() {
-                            return None;
-                        }
-                        let args_str = args_e.text().to_string();
-                        let args: Vec<String> = if args_str.is_empty() {
-                            Vec::new()
-                        } else {
-                            args_str.split_whitespace().map(|s| s.to_string()).collect()
-                        };
-                        Some(cterm_app::config::ToolShortcutEntry {
-                            name,
-                            command,
-                            args,
-                        })
-                    })
-                    .collect();
-                if let Err(e) = cterm_app::config::save_tool_shortcuts(&tools) {
-                    log::error!("Failed to save tool shortcuts: {}", e);
-                }
-            }
-
-            // If git sync is configured, commit and push
-            if let Some(dir) = config_dir() {
-                if git_sync::is_git_repo(&dir) && git_sync::get_remote_url(&dir).is_some() {
-                    if let Err(e) = git_sync::commit_and_push(&dir, "Update configuration") {
-                        log::error!("Failed to push config: {}", e);
-                    }
-                }
-            }
-
-            if let Some(ref callback) = *widgets_for_response.on_save_callback.borrow() {
-                callback(final_config);
-            }
-            if response == ResponseType::Ok {
-                dialog.close();
-            }
+        ResponseType::Ok => {
+            widgets_for_response.save(&tool_entries.borrow());
+            dialog.close();
         }
+        // Apply previews; only OK saves
+        ResponseType::Apply => widgets_for_response.apply_preview(),
         _ => {
+            widgets_for_response.preview.borrow_mut().revert();
             dialog.close();
         }
     });
@@ -704,184 +1067,214 @@ pub fn show_preferences_dialog(
     dialog.present();
 }
 
-fn create_general_preferences(config: &Config) -> (GtkBox, SpinButton, Switch, Switch, Switch) {
-    let page = GtkBox::new(Orientation::Vertical, 12);
-    page.set_margin_top(12);
-    page.set_margin_bottom(12);
-    page.set_margin_start(12);
-    page.set_margin_end(12);
-
-    let grid = Grid::new();
-    grid.set_row_spacing(8);
-    grid.set_column_spacing(12);
-
-    // Scrollback lines
-    let scrollback_label = Label::new(Some("Scrollback lines:"));
-    scrollback_label.set_halign(Align::End);
-    grid.attach(&scrollback_label, 0, 0, 1, 1);
-
-    let scrollback_spin = SpinButton::with_range(0.0, 100000.0, 1000.0);
-    scrollback_spin.set_value(config.general.scrollback_lines as f64);
-    grid.attach(&scrollback_spin, 1, 0, 1, 1);
-
-    // Confirm close
-    let confirm_label = Label::new(Some("Confirm close with running processes:"));
-    confirm_label.set_halign(Align::End);
-    grid.attach(&confirm_label, 0, 1, 1, 1);
-
-    let confirm_switch = Switch::new();
-    confirm_switch.set_active(config.general.confirm_close_with_running);
-    confirm_switch.set_halign(Align::Start);
-    grid.attach(&confirm_switch, 1, 1, 1, 1);
-
-    // Copy on select
-    let copy_select_label = Label::new(Some("Copy on select:"));
-    copy_select_label.set_halign(Align::End);
-    grid.attach(&copy_select_label, 0, 2, 1, 1);
-
-    let copy_select_switch = Switch::new();
-    copy_select_switch.set_active(config.general.copy_on_select);
-    copy_select_switch.set_halign(Align::Start);
-    grid.attach(&copy_select_switch, 1, 2, 1, 1);
-
-    // Show debug menu
-    let debug_menu_label = Label::new(Some("Show debug menu:"));
-    debug_menu_label.set_halign(Align::End);
-    grid.attach(&debug_menu_label, 0, 3, 1, 1);
-
-    let debug_menu_switch = Switch::new();
-    debug_menu_switch.set_active(config.general.show_debug_menu);
-    debug_menu_switch.set_halign(Align::Start);
-    grid.attach(&debug_menu_switch, 1, 3, 1, 1);
+/// Button that opens the color vision tool for the selected theme and, if
+/// the user adopts the adjusted theme, stores it as the custom theme
+fn color_vision_button(widgets: &Rc<PreferencesWidgets>) -> Button {
+    let button = Button::with_label("Color Vision...");
+    button.set_tooltip_text(Some(
+        "Preview the theme with color blindness and derive an adjusted theme",
+    ));
+    let widgets = Rc::clone(widgets);
+    button.connect_clicked(move |button| {
+        let Some(parent) = button.root().and_downcast::<Window>() else {
+            return;
+        };
+        let theme = resolve_theme(&widgets.collect_config(&widgets.base_config.borrow()));
+        let widgets = Rc::clone(&widgets);
+        crate::color_vision_dialog::show_color_vision_dialog(&parent, theme, move |adjusted| {
+            let theme = settings::find("appearance.theme");
+            if let (Some(setting), Some(SettingWidget::Combo(combo))) =
+                (theme, widgets.setting_rows.editor("appearance.theme"))
+            {
+                combo.remove_all();
+                fill_combo(&combo, setting, Some(&adjusted.name));
+                combo.set_active_id(Some(CUSTOM_THEME_ID));
+            }
+            widgets.base_config.borrow_mut().appearance.custom_theme = Some(adjusted);
+        });
+    });
+    button
+}
 
-    page.append(&grid);
-    (
-        page,
-        scrollback_spin,
-        confirm_switch,
-        copy_select_switch,
-        debug_menu_switch,
-    )
+/// Editable shortcuts: (config key, label, current value)
+fn shortcut_fields(config: &Config) -> [(&'static str, &'static str, &str); 14] {
+    let shortcuts = &config.shortcuts;
+    [
+        ("new_tab", "New Tab", &shortcuts.new_tab),
+        ("close_tab", "Close Tab", &shortcuts.close_tab),
+        ("next_tab", "Next Tab", &shortcuts.next_tab),
+        ("prev_tab", "Previous Tab", &shortcuts.prev_tab),
+        ("new_window", "New Window", &shortcuts.new_window),
+        ("close_window", "Close Window", &shortcuts.close_window),
+        ("copy", "Copy", &shortcuts.copy),
+        ("paste", "Paste", &shortcuts.paste),
+        ("select_all", "Select All", &shortcuts.select_all),
+        ("zoom_in", "Zoom In", &shortcuts.zoom_in),
+        ("zoom_out", "Zoom Out", &shortcuts.zoom_out),
+        ("zoom_reset", "Zoom Reset", &shortcuts.zoom_reset),
+        ("find", "Find", &shortcuts.find),
+        ("reset", "Reset", &shortcuts.reset),
+    ]
 }
 
-fn create_appearance_preferences(
+/// Preferences as an Adwaita preferences window
+///
+/// Changes are saved when the window is closed, following the GNOME
+/// convention of not having OK/Cancel buttons.
+#[cfg(feature = "adwaita")]
+fn show_adwaita_preferences(
+    parent: &impl IsA<Window>,
     config: &Config,
-) -> (
-    GtkBox,
-    ComboBoxText,
-    Entry,
-    SpinButton,
-    ComboBoxText,
-    Switch,
-    gtk4::Scale,
-    Switch,
+    on_save: impl Fn(Config) + 'static,
 ) {
-    let page = GtkBox::new(Orientation::Vertical, 12);
-    page.set_margin_top(12);
-    page.set_margin_bottom(12);
-    page.set_margin_start(12);
-    page.set_margin_end(12);
+    use libadwaita::prelude::*;
+    use libadwaita::{PreferencesGroup, PreferencesPage, PreferencesWindow};
+
+    let window = PreferencesWindow::new();
+    window.set_title(Some("Preferences"));
+    window.set_transient_for(Some(parent));
+    window.set_modal(true);
+    window.set_search_enabled(true);
+    window.set_default_size(640, 560);
+
+    let page = |title: &str, icon: &str| {
+        let page = PreferencesPage::new();
+        page.set_title(title);
+        page.set_icon_name(Some(icon));
+        window.add(&page);
+        page
+    };
+    let group = |page: &PreferencesPage, title: &str| {
+        let group = PreferencesGroup::new();
+        group.set_title(title);
+        page.add(&group);
+        group
+    };
+    let setting_rows = SettingRows::default();
+    let mut appearance_group = None;
+    for category in SettingCategory::ALL {
+        let icon = match category {
+            SettingCategory::General => "preferences-system-symbolic",
+            SettingCategory::Appearance => "applications-graphics-symbolic",
+            SettingCategory::Tabs => "tab-new-symbolic",
+        };
+        let category_page = page(category.name(), icon);
+        let settings_group = PreferencesGroup::new();
+        category_page.add(&settings_group);
+        for setting in category.settings() {
+            let editor = SettingWidget::new(setting, config);
+            let row = adwaita_row(setting.label, &editor.widget());
+            row.set_subtitle(setting.description);
+            let reset_button = setting_rows.register(setting, &editor, &[row.clone().upcast()]);
+            row.add_suffix(&reset_button);
+            settings_group.add(&row);
+        }
+        if category == SettingCategory::Appearance {
+            appearance_group = Some(settings_group);
+        }
+    }
 
-    let grid = Grid::new();
-    grid.set_row_spacing(8);
-    grid.set_column_spacing(12);
+    // Shortcuts
+    let shortcuts = page(
+        "Shortcuts",
+        "preferences-desktop-keyboard-shortcuts-symbolic",
+    );
+    let shortcuts_group = group(&shortcuts, "Keyboard Shortcuts");
+    let mut shortcut_entries = Vec::new();
+    for (key, name, shortcut) in shortcut_fields(config) {
+        let entry = Entry::new();
+        entry.set_text(shortcut);
+        shortcuts_group.add(&adwaita_row(name, &entry));
+        shortcut_entries.push((key.to_string(), entry));
+    }
 
-    // Theme
-    let theme_label = Label::new(Some("Theme:"));
-    theme_label.set_halign(Align::End);
-    grid.attach(&theme_label, 0, 0, 1, 1);
-
-    let theme_combo = ComboBoxText::new();
-    theme_combo.append(Some("dark"), "Default Dark");
-    theme_combo.append(Some("light"), "Default Light");
-    theme_combo.append(Some("tokyo_night"), "Tokyo Night");
-    theme_combo.append(Some("dracula"), "Dracula");
-    theme_combo.append(Some("nord"), "Nord");
-    theme_combo.set_active_id(Some(&config.appearance.theme));
-    grid.attach(&theme_combo, 1, 0, 1, 1);
-
-    // Font family
-    let font_label = Label::new(Some("Font:"));
-    font_label.set_halign(Align::End);
-    grid.attach(&font_label, 0, 1, 1, 1);
-
-    let font_entry = Entry::new();
-    font_entry.set_text(&config.appearance.font.family);
-    font_entry.set_hexpand(true);
-    grid.attach(&font_entry, 1, 1, 1, 1);
-
-    // Font size
-    let size_label = Label::new(Some("Font size:"));
-    size_label.set_halign(Align::End);
-    grid.attach(&size_label, 0, 2, 1, 1);
-
-    let size_spin = SpinButton::with_range(6.0, 72.0, 1.0);
-    size_spin.set_value(config.appearance.font.size);
-    grid.attach(&size_spin, 1, 2, 1, 1);
-
-    // Cursor style
-    let cursor_label = Label::new(Some("Cursor style:"));
-    cursor_label.set_halign(Align::End);
-    grid.attach(&cursor_label, 0, 3, 1, 1);
-
-    let cursor_combo = ComboBoxText::new();
-    cursor_combo.append(Some("block"), "Block");
-    cursor_combo.append(Some("underline"), "Underline");
-    cursor_combo.append(Some("bar"), "Bar");
-    let cursor_id = match config.appearance.cursor_style {
-        CursorStyleConfig::Block => "block",
-        CursorStyleConfig::Underline => "underline",
-        CursorStyleConfig::Bar => "bar",
+    // Tools
+    let tools = page("Tools", "applications-utilities-symbolic");
+    let tools_group = PreferencesGroup::new();
+    let (tools_editor, tool_entries) = create_tools_preferences();
+    tools_editor.set_size_request(-1, 360);
+    tools_group.add(&tools_editor);
+    tools.add(&tools_group);
+
+    // Git Sync
+    let git_sync = page("Git Sync", "emblem-synchronizing-symbolic");
+    let remote_group = group(&git_sync, "Remote Repository");
+    let git_remote_entry = Entry::new();
+    git_remote_entry.set_placeholder_text(Some("https://github.com/user/cterm-config.git"));
+    git_remote_entry.set_width_chars(32);
+    if let Some(url) = config_dir().and_then(|dir| git_sync::get_remote_url(&dir)) {
+        git_remote_entry.set_text(&url);
+    }
+    remote_group.add(&adwaita_row("Git remote URL", &git_remote_entry));
+
+    let status_group = group(&git_sync, "Sync Status");
+    let status_label = |title: &str| {
+        let label = Label::new(None);
+        label.add_css_class("dim-label");
+        status_group.add(&adwaita_row(title, &label));
+        label
     };
-    cursor_combo.set_active_id(Some(cursor_id));
-    grid.attach(&cursor_combo, 1, 3, 1, 1);
-
-    // Cursor blink
-    let blink_label = Label::new(Some("Cursor blink:"));
-    blink_label.set_halign(Align::End);
-    grid.attach(&blink_label, 0, 4, 1, 1);
-
-    let blink_switch = Switch::new();
-    blink_switch.set_active(config.appearance.cursor_blink);
-    blink_switch.set_halign(Align::Start);
-    grid.attach(&blink_switch, 1, 4, 1, 1);
-
-    // Opacity
-    let opacity_label = Label::new(Some("Opacity:"));
-    opacity_label.set_halign(Align::End);
-    grid.attach(&opacity_label, 0, 5, 1, 1);
-
-    let opacity_scale = gtk4::Scale::with_range(Orientation::Horizontal, 0.0, 1.0, 0.1);
-    opacity_scale.set_value(config.appearance.opacity);
-    opacity_scale.set_hexpand(true);
-    grid.attach(&opacity_scale, 1, 5, 1, 1);
-
-    // Bold is bright
-    let bold_label = Label::new(Some("Bold text uses bright colors:"));
-    bold_label.set_halign(Align::End);
-    grid.attach(&bold_label, 0, 6, 1, 1);
-
-    let bold_switch = Switch::new();
-    bold_switch.set_active(config.appearance.bold_is_bright);
-    bold_switch.set_halign(Align::Start);
-    grid.attach(&bold_switch, 1, 6, 1, 1);
+    let git_status_label = status_label("Status");
+    let git_branch_label = status_label("Branch");
+    let git_last_sync_label = status_label("Last sync");
+    let git_changes_label = status_label("Changes");
+    let sync_button = Button::with_label("Sync Now");
+    status_group.add(&adwaita_row("Pull and push changes", &sync_button));
 
-    page.append(&grid);
-    (
-        page,
-        theme_combo,
-        font_entry,
-        size_spin,
-        cursor_combo,
-        blink_switch,
-        opacity_scale,
-        bold_switch,
-    )
+    let widgets = Rc::new(PreferencesWidgets {
+        setting_rows,
+        shortcut_entries,
+        git_remote_entry,
+        git_status_label,
+        git_branch_label,
+        git_last_sync_label,
+        git_changes_label,
+        on_save_callback: Rc::new(RefCell::new(Some(Box::new(on_save)))),
+        base_config: Rc::new(RefCell::new(config.clone())),
+        preview: RefCell::new(Preview::new(config)),
+    });
+    widgets.update_status_display();
+    widgets.setting_rows.connect_reset(&widgets);
+
+    if let Some(group) = appearance_group {
+        group.add(&adwaita_row("Color vision", &color_vision_button(&widgets)));
+    }
+
+    let widgets_for_sync = Rc::clone(&widgets);
+    sync_button.connect_clicked(move |_| {
+        widgets_for_sync.sync_now();
+    });
+
+    window.connect_close_request(move |_| {
+        widgets.save(&tool_entries.borrow());
+        gtk4::glib::Propagation::Proceed
+    });
+
+    window.present();
+}
+
+/// An Adwaita row with `title` and `widget` at its end
+#[cfg(feature = "adwaita")]
+fn adwaita_row(title: &str, widget: &impl IsA<gtk4::Widget>) -> libadwaita::ActionRow {
+    use libadwaita::prelude::*;
+
+    let row = libadwaita::ActionRow::new();
+    row.set_title(title);
+    widget.set_valign(Align::Center);
+    row.add_suffix(widget);
+    if widget.is::<Switch>() {
+        row.set_activatable_widget(Some(widget));
+    }
+    row
 }
 
-fn create_tabs_preferences(
+/// Notebook page with a row for each setting of `category`
+#[cfg(not(feature = "adwaita"))]
+fn create_settings_preferences(
     config: &Config,
-) -> (GtkBox, ComboBoxText, ComboBoxText, ComboBoxText, Switch) {
+    category: SettingCategory,
+    rows: &SettingRows,
+) -> GtkBox {
     let page = GtkBox::new(Orientation::Vertical, 12);
     page.set_margin_top(12);
     page.set_margin_bottom(12);
@@ -892,67 +1285,15 @@ fn create_tabs_preferences(
     grid.set_row_spacing(8);
     grid.set_column_spacing(12);
 
-    // Show tab bar
-    let show_label = Label::new(Some("Show tab bar:"));
-    show_label.set_halign(Align::End);
-    grid.attach(&show_label, 0, 0, 1, 1);
-
-    let show_combo = ComboBoxText::new();
-    show_combo.append(Some("always"), "Always");
-    show_combo.append(Some("multiple"), "When multiple tabs");
-    show_combo.append(Some("never"), "Never");
-    let show_id = match config.tabs.show_tab_bar {
-        TabBarVisibility::Always => "always",
-        TabBarVisibility::Multiple => "multiple",
-        TabBarVisibility::Never => "never",
-    };
-    show_combo.set_active_id(Some(show_id));
-    grid.attach(&show_combo, 1, 0, 1, 1);
-
-    // Tab bar position
-    let position_label = Label::new(Some("Tab bar position:"));
-    position_label.set_halign(Align::End);
-    grid.attach(&position_label, 0, 1, 1, 1);
-
-    let position_combo = ComboBoxText::new();
-    position_combo.append(Some("top"), "Top");
-    position_combo.append(Some("bottom"), "Bottom");
-    let position_id = match config.tabs.tab_bar_position {
-        TabBarPosition::Top => "top",
-        TabBarPosition::Bottom => "bottom",
-    };
-    position_combo.set_active_id(Some(position_id));
-    grid.attach(&position_combo, 1, 1, 1, 1);
-
-    // New tab position
-    let new_label = Label::new(Some("New tab position:"));
-    new_label.set_halign(Align::End);
-    grid.attach(&new_label, 0, 2, 1, 1);
-
-    let new_combo = ComboBoxText::new();
-    new_combo.append(Some("end"), "At end");
-    new_combo.append(Some("after_current"), "After current");
-    let new_id = match config.tabs.new_tab_position {
-        NewTabPosition::End => "end",
-        NewTabPosition::AfterCurrent => "after_current",
-    };
-    new_combo.set_active_id(Some(new_id));
-    grid.attach(&new_combo, 1, 2, 1, 1);
-
-    // Show close button
-    let close_label = Label::new(Some("Show close button:"));
-    close_label.set_halign(Align::End);
-    grid.attach(&close_label, 0, 3, 1, 1);
-
-    let close_switch = Switch::new();
-    close_switch.set_active(config.tabs.show_close_button);
-    close_switch.set_halign(Align::Start);
-    grid.attach(&close_switch, 1, 3, 1, 1);
+    for (row, setting) in category.settings().enumerate() {
+        rows.attach(&grid, row as i32, setting, config);
+    }
 
     page.append(&grid);
-    (page, show_combo, position_combo, new_combo, close_switch)
+    page
 }
 
+#[cfg(not(feature = "adwaita"))]
 fn create_shortcuts_preferences(config: &Config) -> (GtkBox, Vec<(String, Entry)>) {
     let page = GtkBox::new(Orientation::Vertical, 12);
     page.set_margin_top(12);
@@ -972,31 +1313,10 @@ fn create_shortcuts_preferences(config: &Config) -> (GtkBox, Vec<(String, Entry)
     grid.set_row_spacing(4);
     grid.set_column_spacing(12);
 
-    let shortcuts = [
-        ("new_tab", "New Tab", &config.shortcuts.new_tab),
-        ("close_tab", "Close Tab", &config.shortcuts.close_tab),
-        ("next_tab", "Next Tab", &config.shortcuts.next_tab),
-        ("prev_tab", "Previous Tab", &config.shortcuts.prev_tab),
-        ("new_window", "New Window", &config.shortcuts.new_window),
-        (
-            "close_window",
-            "Close Window",
-            &config.shortcuts.close_window,
-        ),
-        ("copy", "Copy", &config.shortcuts.copy),
-        ("paste", "Paste", &config.shortcuts.paste),
-        ("select_all", "Select All", &config.shortcuts.select_all),
-        ("zoom_in", "Zoom In", &config.shortcuts.zoom_in),
-        ("zoom_out", "Zoom Out", &config.shortcuts.zoom_out),
-        ("zoom_reset", "Zoom Reset", &config.shortcuts.zoom_reset),
-        ("find", "Find", &config.shortcuts.find),
-        ("reset", "Reset", &config.shortcuts.reset),
-    ];
-
     let mut entries = Vec::new();
 
-    for (i, (key, name, shortcut)) in shortcuts.iter().enumerate() {
-        let name_label = Label::new(Some(*name));
+    for (i, (key, name, shortcut)) in shortcut_fields(config).into_iter().enumerate() {
+        let name_label = Label::new(Some(name));
         name_label.set_halign(Align::End);
         grid.attach(&name_label, 0, i as i32, 1, 1);
 
@@ -1181,6 +1501,7 @@ fn create_tools_preferences() -> (GtkBox, Rc<RefCell<Vec<ToolEntryRow>>>) {
     (page, entries)
 }
 
+#[cfg(not(feature = "adwaita"))]
 fn create_git_sync_preferences() -> (GtkBox, Entry, Label, Label, Label, Label, Button) {
     let page = GtkBox::new(Orientation::Vertical, 12);
     page.set_margin_top(12);
@@ -1525,6 +1846,82 @@ pub fn show_file_drop_dialog<F>(
     dialog.present();
 }
 
+/// Characters of a paste shown in its confirmation dialog
+const PASTE_PREVIEW_CHARS: usize = 2000;
+
+/// Ask before pasting `text` into a program that hasn't enabled bracketed
+/// paste, showing it with line breaks and control characters highlighted;
+/// calls `on_paste` if the user goes ahead
+pub fn show_paste_confirm_dialog<F>(
+    parent: &impl IsA<Window>,
+    text: &str,
+    warning: &cterm_app::paste::PasteWarning,
+    on_paste: F,
+) where
+    F: FnOnce() + 'static,
+{
+    let dialog = Dialog::builder()
+        .title("Paste Text?")
+        .transient_for(parent)
+        .modal(true)
+        .default_width(560)
+        .build();
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Paste", ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Cancel);
+
+    let content = dialog.content_area();
+    content.set_spacing(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+
+    let label = Label::new(Some(&warning.message()));
+    label.set_halign(Align::Start);
+    label.set_wrap(true);
+    label.set_max_width_chars(70);
+    content.append(&label);
+
+    let markup: String = cterm_app::paste::paste_preview(text, PASTE_PREVIEW_CHARS)
+        .into_iter()
+        .map(|(run, highlight)| {
+            let run = gtk4::glib::markup_escape_text(&run);
+            if highlight {
+                format!(
+                    "<span background=\"#f5c542\" foreground=\"#000000\">{}</span>",
+                    run
+                )
+            } else {
+                run.to_string()
+            }
+        })
+        .collect();
+    let preview = Label::new(None);
+    preview.set_markup(&format!("<tt>{}</tt>", markup));
+    preview.set_halign(Align::Start);
+    preview.set_valign(Align::Start);
+    preview.set_selectable(true);
+    let scroll = ScrolledWindow::new();
+    scroll.set_min_content_height(160);
+    scroll.set_max_content_height(320);
+    scroll.set_propagate_natural_height(true);
+    scroll.set_child(Some(&preview));
+    content.append(&scroll);
+
+    let on_paste = RefCell::new(Some(on_paste));
+    dialog.connect_response(move |dialog, response| {
+        dialog.close();
+        if response == ResponseType::Accept {
+            if let Some(on_paste) = on_paste.borrow_mut().take() {
+                on_paste();
+            }
+        }
+    });
+
+    dialog.present();
+}
+
 /// Show a filename input dialog. Calls `callback` with the entered name,
 /// or does nothing if cancelled.
 fn show_filename_input<F>(parent: &Window, default: &str, callback: F)
@@ -1565,3 +1962,118 @@ where
 
     dialog.present();
 }
+
+/// Ask where to save the terminal buffer
+///
+/// The `callback` is called with the chosen path; it isn't called if
+/// cancelled.
+pub fn show_save_buffer_dialog<F>(parent: &impl IsA<Window>, callback: F)
+where
+    F: Fn(std::path::PathBuf) + 'static,
+{
+    show_save_dialog(parent, "Save Buffer As", "scrollback.log", callback);
+}
+
+/// Show a save dialog for an asciicast recording
+pub fn show_save_recording_dialog<F>(parent: &impl IsA<Window>, callback: F)
+where
+    F: Fn(std::path::PathBuf) + 'static,
+{
+    show_save_dialog(parent, "Record Session", "session.cast", callback);
+}
+
+/// Show a file chooser for an asciicast or ttyrec recording to play
+pub fn show_open_recording_dialog<F>(parent: &impl IsA<Window>, callback: F)
+where
+    F: Fn(std::path::PathBuf) + 'static,
+{
+    let file_chooser = gtk4::FileChooserDialog::new(
+        Some("Play Recording"),
+        Some(parent),
+        gtk4::FileChooserAction::Open,
+        &[
+            ("Cancel", ResponseType::Cancel),
+            ("Play", ResponseType::Accept),
+        ],
+    );
+
+    file_chooser.connect_response(move |dialog, response| {
+        if response == ResponseType::Accept {
+            if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                callback(path);
+            }
+        }
+        dialog.close();
+    });
+
+    file_chooser.present();
+}
+
+/// Show a save dialog titled `title`, suggesting `name`
+fn show_save_dialog<F>(parent: &impl IsA<Window>, title: &str, name: &str, callback: F)
+where
+    F: Fn(std::path::PathBuf) + 'static,
+{
+    let file_chooser = gtk4::FileChooserDialog::new(
+        Some(title),
+        Some(parent),
+        gtk4::FileChooserAction::Save,
+        &[
+            ("Cancel", ResponseType::Cancel),
+            ("Save", ResponseType::Accept),
+        ],
+    );
+    file_chooser.set_current_name(name);
+
+    file_chooser.connect_response(move |dialog, response| {
+        if response == ResponseType::Accept {
+            if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                callback(path);
+            }
+        }
+        dialog.close();
+    });
+
+    file_chooser.present();
+}
+
+/// Show a file chooser for the files a remote program asked for (`rz`,
+/// `kermit -r` or `it2ul`), titled `title`
+///
+/// The `callback` is called with the chosen paths, or an empty list if
+/// cancelled.
+pub fn show_send_files_dialog<F>(parent: &impl IsA<Window>, title: &str, callback: F)
+where
+    F: Fn(Vec<std::path::PathBuf>) + 'static,
+{
+    let file_chooser = gtk4::FileChooserDialog::new(
+        Some(title),
+        Some(parent),
+        gtk4::FileChooserAction::Open,
+        &[
+            ("Cancel", ResponseType::Cancel),
+            ("Send", ResponseType::Accept),
+        ],
+    );
+    file_chooser.set_select_multiple(true);
+
+    file_chooser.connect_response(move |dialog, response| {
+        let mut paths = Vec::new();
+        if response == ResponseType::Accept {
+            let files = dialog.files();
+            for i in 0..files.n_items() {
+                if let Some(path) = files
+                    .item(i)
+                    .and_downcast::<gtk4::gio::File>()
+                    .and_then(|file| file.path())
+                {
+                    paths.push(path);
+                }
+            }
+        }
+        callback(paths);
+        dialog.close();
+    });
+
+    file_chooser.present();
+}
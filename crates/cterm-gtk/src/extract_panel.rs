@@ -0,0 +1,282 @@
+//! Extract Matches panel
+//!
+//! Runs a regex over a tab's buffer and scrollback and lists the distinct
+//! matches (addresses, ticket IDs, file names, ...) ready to copy. Presets
+//! are the built-in ones followed by `[[extract_presets]]` from the config;
+//! the current pattern can be saved as a new preset.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk4::prelude::*;
+use gtk4::{
+    gdk, Align, Button, ComboBoxText, Entry, Label, ListBox, Orientation, ScrolledWindow,
+    SelectionMode, Window,
+};
+
+use cterm_app::config::{save_config, Config, ExtractPreset};
+
+/// Runs a pattern against the tab's terminal
+type Extractor = Box<dyn Fn(&str) -> Result<Vec<String>, String>>;
+
+/// Panel state shared by the widget callbacks
+struct PanelState {
+    extract: Extractor,
+    config: Rc<RefCell<Config>>,
+    /// Presets in combo box order
+    presets: Vec<ExtractPreset>,
+    /// Matches in list order
+    matches: Vec<String>,
+    list: ListBox,
+    status_label: Label,
+}
+
+impl PanelState {
+    /// Re-run `pattern` and refill the list
+    fn refresh(&mut self, pattern: &str) {
+        while let Some(row) = self.list.row_at_index(0) {
+            self.list.remove(&row);
+        }
+        self.matches.clear();
+        if pattern.is_empty() {
+            self.status_label.set_text("Enter a regular expression");
+            return;
+        }
+        match (self.extract)(pattern) {
+            Ok(matches) => {
+                for text in &matches {
+                    let label = Label::new(Some(text));
+                    label.set_halign(Align::Start);
+                    label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+                    label.set_margin_top(2);
+                    label.set_margin_bottom(2);
+                    self.list.append(&label);
+                }
+                let text = match matches.len() {
+                    1 => "1 match".to_string(),
+                    n => format!("{n} matches"),
+                };
+                self.status_label.set_text(&text);
+                self.matches = matches;
+            }
+            Err(e) => self.status_label.set_text(&format!("Invalid pattern: {e}")),
+        }
+    }
+
+    fn selected_text(&self) -> String {
+        let mut rows: Vec<usize> = self
+            .list
+            .selected_rows()
+            .iter()
+            .filter_map(|row| usize::try_from(row.index()).ok())
+            .collect();
+        rows.sort_unstable();
+        rows.iter()
+            .filter_map(|&i| self.matches.get(i).map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Show the Extract Matches panel for a tab.
+///
+/// `extract` runs a pattern against the tab's terminal (see
+/// `TerminalWidget::match_extractor`).
+pub fn show_extract_panel(
+    parent: &impl IsA<Window>,
+    config: Rc<RefCell<Config>>,
+    extract: impl Fn(&str) -> Result<Vec<String>, String> + 'static,
+) {
+    let window = Window::builder()
+        .title("Extract Matches")
+        .transient_for(parent)
+        .default_width(460)
+        .default_height(520)
+        .build();
+
+    let vbox = gtk4::Box::new(Orientation::Vertical, 6);
+    vbox.set_margin_top(8);
+    vbox.set_margin_bottom(8);
+    vbox.set_margin_start(8);
+    vbox.set_margin_end(8);
+
+    let preset_combo = ComboBoxText::new();
+    vbox.append(&preset_combo);
+
+    let pattern_entry = Entry::new();
+    pattern_entry.set_placeholder_text(Some("Regular expression"));
+    vbox.append(&pattern_entry);
+
+    let list = ListBox::new();
+    list.set_selection_mode(SelectionMode::Multiple);
+    list.set_activate_on_single_click(false);
+    let scroll = ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_child(Some(&list));
+    vbox.append(&scroll);
+
+    let status_label = Label::new(None);
+    status_label.set_halign(Align::Start);
+    status_label.add_css_class("dim-label");
+    vbox.append(&status_label);
+
+    let copy_box = gtk4::Box::new(Orientation::Horizontal, 6);
+    let refresh_btn = Button::with_label("Refresh");
+    let copy_selected_btn = Button::with_label("Copy Selected");
+    let copy_all_btn = Button::with_label("Copy All");
+    copy_box.append(&refresh_btn);
+    let spacer = gtk4::Box::new(Orientation::Horizontal, 0);
+    spacer.set_hexpand(true);
+    copy_box.append(&spacer);
+    copy_box.append(&copy_selected_btn);
+    copy_box.append(&copy_all_btn);
+    vbox.append(&copy_box);
+
+    let save_box = gtk4::Box::new(Orientation::Horizontal, 6);
+    let name_entry = Entry::new();
+    name_entry.set_placeholder_text(Some("Preset name"));
+    name_entry.set_hexpand(true);
+    let save_btn = Button::with_label("Save Preset");
+    save_box.append(&name_entry);
+    save_box.append(&save_btn);
+    vbox.append(&save_box);
+
+    window.set_child(Some(&vbox));
+
+    let presets = config.borrow().all_extract_presets();
+    for preset in &presets {
+        preset_combo.append_text(&preset.name);
+    }
+    let state = Rc::new(RefCell::new(PanelState {
+        extract: Box::new(extract),
+        config,
+        presets,
+        matches: Vec::new(),
+        list: list.clone(),
+        status_label,
+    }));
+
+    {
+        let state = Rc::clone(&state);
+        pattern_entry.connect_changed(move |entry| {
+            state.borrow_mut().refresh(&entry.text());
+        });
+    }
+
+    {
+        let state = Rc::clone(&state);
+        let pattern_entry = pattern_entry.clone();
+        let name_entry = name_entry.clone();
+        preset_combo.connect_changed(move |combo| {
+            let preset = combo
+                .active()
+                .and_then(|i| state.borrow().presets.get(i as usize).cloned());
+            if let Some(preset) = preset {
+                name_entry.set_text(&preset.name);
+                pattern_entry.set_text(&preset.pattern);
+            }
+        });
+    }
+
+    {
+        let state = Rc::clone(&state);
+        let pattern_entry = pattern_entry.clone();
+        refresh_btn.connect_clicked(move |_| {
+            state.borrow_mut().refresh(&pattern_entry.text());
+        });
+    }
+
+    {
+        let state = Rc::clone(&state);
+        copy_selected_btn.connect_clicked(move |_| {
+            copy_to_clipboard(&state.borrow().selected_text());
+        });
+    }
+
+    {
+        let state = Rc::clone(&state);
+        copy_all_btn.connect_clicked(move |_| {
+            copy_to_clipboard(&state.borrow().matches.join("\n"));
+        });
+    }
+
+    // Double-click copies a single match
+    {
+        let state = Rc::clone(&state);
+        list.connect_row_activated(move |_, row| {
+            let s = state.borrow();
+            if let Some(text) = usize::try_from(row.index())
+                .ok()
+                .and_then(|i| s.matches.get(i))
+            {
+                copy_to_clipboard(text);
+            }
+        });
+    }
+
+    {
+        let state = Rc::clone(&state);
+        let pattern_entry = pattern_entry.clone();
+        let preset_combo = preset_combo.clone();
+        save_btn.connect_clicked(move |_| {
+            let name = name_entry.text().trim().to_string();
+            let pattern = pattern_entry.text().to_string();
+            if name.is_empty() || pattern.is_empty() {
+                return;
+            }
+            if let Err(e) = (state.borrow().extract)(&pattern) {
+                state
+                    .borrow()
+                    .status_label
+                    .set_text(&format!("Invalid pattern: {e}"));
+                return;
+            }
+            save_preset(&state, &preset_combo, ExtractPreset { name, pattern });
+        });
+    }
+
+    preset_combo.set_active(Some(0));
+    window.present();
+}
+
+/// Add or replace a saved preset and write the config.
+fn save_preset(state: &Rc<RefCell<PanelState>>, combo: &ComboBoxText, preset: ExtractPreset) {
+    let mut s = state.borrow_mut();
+    let name = preset.name.clone();
+    let presets = {
+        let mut config = s.config.borrow_mut();
+        match config.extract_presets.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = preset,
+            None => config.extract_presets.push(preset),
+        }
+        if let Err(e) = save_config(&config) {
+            log::error!("Failed to save config: {}", e);
+        }
+        config.all_extract_presets()
+    };
+
+    let index = presets.iter().rposition(|p| p.name == name);
+    s.presets = presets.clone();
+    // The combo's handlers borrow the state
+    drop(s);
+
+    combo.remove_all();
+    for p in &presets {
+        combo.append_text(&p.name);
+    }
+    // Selecting the saved preset re-fills the entries with the same values
+    combo.set_active(index.map(|i| i as u32));
+    state
+        .borrow()
+        .status_label
+        .set_text(&format!("Saved preset \"{name}\""));
+}
+
+fn copy_to_clipboard(text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(display) = gdk::Display::default() {
+        display.clipboard().set_text(text);
+    }
+}
@@ -3,27 +3,38 @@
 //! This crate implements the cterm terminal emulator UI using GTK4.
 
 mod app;
+mod color_vision_dialog;
 mod dialogs;
 mod docker_dialog;
+mod extract_panel;
 mod file_transfer;
+mod link_popover;
 mod log_viewer;
 mod menu;
+mod node_renderer;
 mod notification_bar;
+mod panes;
 mod quick_open;
 mod remotes_dialog;
+mod serial_dialog;
 mod session_dialog;
 mod ssh_prompt;
 mod tab_bar;
 mod tab_templates_dialog;
+mod terminal_area;
 mod terminal_widget;
+mod transfer_panel;
 mod update_dialog;
 mod upgrade_receiver;
+mod usage_stats_window;
 mod window;
 
 use clap::Parser;
 use gtk4::prelude::*;
 use gtk4::Application;
+use std::cell::Cell;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 /// Command-line arguments for cterm
 #[derive(Parser, Debug)]
@@ -53,9 +64,26 @@ pub struct Args {
     #[arg(short = 't', long = "title")]
     pub title: Option<String>,
 
+    /// Play back an asciicast (.cast) or ttyrec recording in a read-only tab
+    #[arg(long, value_name = "FILE")]
+    pub play: Option<PathBuf>,
+
+    /// Open the tabs and panes of a `[[workspaces]]` entry, in the running
+    /// cterm if there is one
+    #[arg(long, value_name = "NAME")]
+    pub workspace: Option<String>,
+
+    /// Start with the default settings and theme, ignoring the configuration
+    #[arg(long)]
+    pub safe_mode: bool,
+
     /// Path to upgrade state file (internal use)
     #[arg(long, hide = true)]
     pub upgrade_state: Option<String>,
+
+    /// Run a subcommand instead of opening a window
+    #[command(subcommand)]
+    pub subcommand: Option<cterm_app::cli::Command>,
 }
 
 /// Global application arguments (accessible from window creation)
@@ -84,7 +112,19 @@ pub fn run() {
     }
 
     // Parse command-line arguments first (before GTK consumes them)
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    // Subcommands like `cterm config get` run without a window
+    if let Some(command) = args.subcommand.take() {
+        std::process::exit(cterm_app::cli::run(command));
+    }
+
+    // A running cterm opens the workspace in its own window
+    if let Some(ref name) = args.workspace {
+        if cterm_app::workspace::open_in_running(name) {
+            std::process::exit(0);
+        }
+    }
 
     // Initialize logging with capture for in-app viewing
     cterm_app::log_capture::init();
@@ -123,6 +163,8 @@ pub fn run() {
         std::process::exit(exit_code.value());
     }
 
+    let safe_mode = args.safe_mode;
+
     // Store args for later access
     let _ = APP_ARGS.set(args);
 
@@ -135,11 +177,40 @@ pub fn run() {
         .application_id("com.cterm.terminal")
         .build();
 
+    // Set when the previous launch crashed before showing a window
+    let offer_recovery = Rc::new(Cell::new(false));
+
+    // Only the primary instance starts up; later launches just activate it
+    app.connect_startup({
+        let offer_recovery = offer_recovery.clone();
+        move |_| {
+            if safe_mode {
+                cterm_app::safe_mode::enable();
+            }
+            let crashed = cterm_app::safe_mode::begin_startup();
+            offer_recovery.set(crashed && !safe_mode);
+
+            let result = cterm_app::control::listen(|request| {
+                glib::idle_add_once(move || app::handle_control_request(request));
+            });
+            if let Err(e) = result {
+                log::warn!("Failed to open the control socket: {}", e);
+            }
+        }
+    });
+
     // Connect to the activate signal
-    app.connect_activate(|app| {
-        app::build_ui(app);
+    app.connect_activate(move |app| {
+        if offer_recovery.replace(false) {
+            app::show_recovery_prompt(app);
+        } else {
+            app::build_ui(app);
+        }
     });
 
+    // Every window is closed by now, the last one remembered for restore
+    app.connect_shutdown(|_| app::save_session());
+
     // Run the application
     // Use run_with_args with empty args to prevent GTK from parsing
     // the command line (which contains flags that GTK doesn't know)
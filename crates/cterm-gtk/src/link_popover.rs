@@ -0,0 +1,162 @@
+//! Hover preview popover for links and image paths
+//!
+//! Shows the page title and icon of a hovered URL, or a thumbnail of a
+//! hovered image path, once the pointer has rested on it for
+//! [`HOVER_DELAY`]. Loading happens on a background thread.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use gtk4::prelude::*;
+use gtk4::{gdk, glib, pango, Align, Box as GtkBox, Label, Orientation, Picture, Popover};
+
+use cterm_app::link_preview::{self, LinkPreview, PreviewTarget, HOVER_DELAY};
+
+/// Popover attached to a terminal's drawing area
+pub struct LinkPopover {
+    popover: Popover,
+    picture: Picture,
+    title: Label,
+    target: Label,
+    /// Target under the pointer and where it is, in widget coordinates
+    hovered: RefCell<Option<(PreviewTarget, gdk::Rectangle)>>,
+    /// Pending timeout that loads the hovered target's preview
+    timeout: RefCell<Option<glib::SourceId>>,
+}
+
+impl LinkPopover {
+    pub fn new(parent: &impl IsA<gtk4::Widget>) -> Rc<Self> {
+        let popover = Popover::new();
+        popover.set_parent(parent);
+        popover.set_autohide(false);
+        popover.set_can_focus(false);
+        popover.set_position(gtk4::PositionType::Top);
+
+        let content = GtkBox::new(Orientation::Vertical, 6);
+        content.set_margin_top(6);
+        content.set_margin_bottom(6);
+        content.set_margin_start(6);
+        content.set_margin_end(6);
+
+        let picture = Picture::new();
+        picture.set_can_shrink(false);
+        picture.set_halign(Align::Center);
+        content.append(&picture);
+
+        let title = Label::new(None);
+        title.set_halign(Align::Start);
+        title.set_max_width_chars(48);
+        title.set_ellipsize(pango::EllipsizeMode::End);
+        title.add_css_class("heading");
+        content.append(&title);
+
+        let target = Label::new(None);
+        target.set_halign(Align::Start);
+        target.set_max_width_chars(48);
+        target.set_ellipsize(pango::EllipsizeMode::Middle);
+        target.add_css_class("dim-label");
+        content.append(&target);
+
+        popover.set_child(Some(&content));
+
+        Rc::new(Self {
+            popover,
+            picture,
+            title,
+            target,
+            hovered: RefCell::new(None),
+            timeout: RefCell::new(None),
+        })
+    }
+
+    /// Note what is under the pointer (`None` for nothing previewable)
+    ///
+    /// A new target hides the current preview and shows its own after
+    /// [`HOVER_DELAY`]; the same target again leaves things as they are.
+    pub fn hover(self: &Rc<Self>, target: Option<PreviewTarget>, rect: gdk::Rectangle) {
+        let same = match (&*self.hovered.borrow(), &target) {
+            (Some((hovered, _)), Some(target)) => hovered == target,
+            (None, None) => true,
+            _ => false,
+        };
+        if same {
+            return;
+        }
+
+        if let Some(id) = self.timeout.borrow_mut().take() {
+            id.remove();
+        }
+        self.popover.popdown();
+        let Some(target) = target else {
+            *self.hovered.borrow_mut() = None;
+            return;
+        };
+        *self.hovered.borrow_mut() = Some((target.clone(), rect));
+
+        let weak = Rc::downgrade(self);
+        let id = glib::timeout_add_local_once(HOVER_DELAY, move || {
+            let Some(this) = weak.upgrade() else {
+                return;
+            };
+            this.timeout.borrow_mut().take();
+            glib::spawn_future_local(async move {
+                let preview = match link_preview::cached(&target) {
+                    Some(preview) => preview,
+                    None => load(target.clone()).await,
+                };
+                // Only if the pointer is still on the same target
+                let rect = match &*this.hovered.borrow() {
+                    Some((hovered, rect)) if *hovered == target => *rect,
+                    _ => return,
+                };
+                if let Some(preview) = preview {
+                    this.show(&preview, rect);
+                }
+            });
+        });
+        *self.timeout.borrow_mut() = Some(id);
+    }
+
+    /// Hide the preview and forget the hovered target
+    pub fn hide(self: &Rc<Self>) {
+        self.hover(None, gdk::Rectangle::new(0, 0, 0, 0));
+    }
+
+    fn show(&self, preview: &LinkPreview, rect: gdk::Rectangle) {
+        match preview.image {
+            Some(ref image) => {
+                let texture = gdk::MemoryTexture::new(
+                    image.width as i32,
+                    image.height as i32,
+                    gdk::MemoryFormat::R8g8b8a8,
+                    &glib::Bytes::from(&image.data),
+                    image.width * 4,
+                );
+                self.picture.set_paintable(Some(&texture));
+                self.picture.set_visible(true);
+            }
+            None => self.picture.set_visible(false),
+        }
+        match preview.title {
+            Some(ref title) => {
+                self.title.set_text(title);
+                self.title.set_visible(true);
+            }
+            None => self.title.set_visible(false),
+        }
+        self.target.set_text(&preview.target.label());
+
+        self.popover.set_pointing_to(Some(&rect));
+        self.popover.popup();
+    }
+}
+
+/// Load a preview on a background thread
+async fn load(target: PreviewTarget) -> Option<Arc<LinkPreview>> {
+    let (tx, rx) = futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(link_preview::preview(&target));
+    });
+    rx.await.ok().flatten()
+}
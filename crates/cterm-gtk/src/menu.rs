@@ -1,5 +1,6 @@
 //! Application menu system
 
+use cterm_app::config::{ProfileConfig, WorkspaceConfig};
 use gtk4::{gio, glib};
 
 /// Helper to create a menu item with a shortcut label displayed in the menu.
@@ -17,13 +18,27 @@ fn menu_item(label: &str, action: &str, accel: Option<&str>) -> gio::MenuItem {
 /// Create the application menu model with options
 ///
 /// If `show_debug` is true, includes a Debug submenu in the Help menu
-/// with developer/testing options.
-pub fn create_menu_model_with_options(show_debug: bool) -> gio::Menu {
+/// with developer/testing options. `profiles` are listed under File > New
+/// Tab with Profile and `workspaces` under File > Workspaces.
+pub fn create_menu_model_with_options(
+    show_debug: bool,
+    profiles: &[ProfileConfig],
+    workspaces: &[WorkspaceConfig],
+) -> gio::Menu {
     let menu = gio::Menu::new();
 
     // File menu
     let file_menu = gio::Menu::new();
     file_menu.append_item(&menu_item("New Tab", "win.new-tab", Some("<Ctrl><Shift>t")));
+    if !profiles.is_empty() {
+        file_menu.append_submenu(
+            Some("New Tab with Profile"),
+            &create_profiles_submenu(profiles),
+        );
+    }
+    if !workspaces.is_empty() {
+        file_menu.append_submenu(Some("Workspaces"), &create_workspaces_submenu(workspaces));
+    }
     file_menu.append_item(&menu_item(
         "New Window",
         "win.new-window",
@@ -39,6 +54,7 @@ pub fn create_menu_model_with_options(show_debug: bool) -> gio::Menu {
     let docker_menu = gio::Menu::new();
     docker_menu.append(Some("Docker Terminal..."), Some("win.docker-picker"));
     file_menu.append_submenu(Some("Docker"), &docker_menu);
+    file_menu.append(Some("New Serial Tab..."), Some("win.serial-connect"));
 
     // Session submenu (daemon)
     let session_menu = gio::Menu::new();
@@ -48,12 +64,20 @@ pub fn create_menu_model_with_options(show_debug: bool) -> gio::Menu {
     file_menu.append_submenu(Some("Sessions"), &session_menu);
 
     file_menu.append(Some("Tab Templates..."), Some("win.tab-templates"));
+    file_menu.append(Some("Save Buffer As..."), Some("win.save-buffer"));
+    file_menu.append(Some("Start/Stop Recording..."), Some("win.record"));
+    file_menu.append(Some("Play Recording..."), Some("win.play-recording"));
     file_menu.append_item(&menu_item(
         "Close Tab",
         "win.close-tab",
         Some("<Ctrl><Shift>w"),
     ));
     file_menu.append(Some("Close Other Tabs"), Some("win.close-other-tabs"));
+    file_menu.append_item(&menu_item(
+        "Reopen Closed Tab",
+        "win.reopen-closed-tab",
+        Some("<Ctrl><Alt><Shift>t"),
+    ));
     file_menu.append_item(&menu_item("Quit", "win.quit", Some("<Ctrl><Shift>q")));
     menu.append_submenu(Some("File"), &file_menu);
 
@@ -62,11 +86,21 @@ pub fn create_menu_model_with_options(show_debug: bool) -> gio::Menu {
     edit_menu.append_item(&menu_item("Copy", "win.copy", Some("<Ctrl><Shift>c")));
     edit_menu.append(Some("Copy as HTML"), Some("win.copy-html"));
     edit_menu.append_item(&menu_item("Paste", "win.paste", Some("<Ctrl><Shift>v")));
+    edit_menu.append_item(&menu_item(
+        "Paste from History...",
+        "win.paste-history",
+        Some("<Ctrl><Alt><Shift>v"),
+    ));
     edit_menu.append_item(&menu_item(
         "Select All",
         "win.select-all",
         Some("<Ctrl><Shift>a"),
     ));
+    edit_menu.append_item(&menu_item(
+        "Select Command Output",
+        "win.select-command-output",
+        Some("<Ctrl><Shift>o"),
+    ));
     menu.append_submenu(Some("Edit"), &edit_menu);
 
     // Terminal menu
@@ -74,6 +108,29 @@ pub fn create_menu_model_with_options(show_debug: bool) -> gio::Menu {
     terminal_menu.append(Some("Set Title..."), Some("win.set-title"));
     terminal_menu.append(Some("Set Color..."), Some("win.set-color"));
     terminal_menu.append_item(&menu_item("Find...", "win.find", Some("<Ctrl><Shift>f")));
+    terminal_menu.append_item(&menu_item(
+        "Previous Prompt",
+        "win.previous-prompt",
+        Some("<Ctrl><Shift>Page_Up"),
+    ));
+    terminal_menu.append_item(&menu_item(
+        "Next Prompt",
+        "win.next-prompt",
+        Some("<Ctrl><Shift>Page_Down"),
+    ));
+    terminal_menu.append_item(&menu_item(
+        "Select Link by Keyboard",
+        "win.hints",
+        Some("<Ctrl><Shift>e"),
+    ));
+    terminal_menu.append_item(&menu_item(
+        "Extract Matches...",
+        "win.extract-matches",
+        Some("<Ctrl><Shift>x"),
+    ));
+    terminal_menu.append(Some("Watch Output..."), Some("win.watch"));
+    terminal_menu.append(Some("Transfer Files..."), Some("win.transfer-files"));
+    terminal_menu.append(Some("Images..."), Some("win.images"));
 
     // Encoding submenu
     let encoding_menu = gio::Menu::new();
@@ -93,6 +150,13 @@ pub fn create_menu_model_with_options(show_debug: bool) -> gio::Menu {
     signal_menu.append(Some("SIGUSR2 (12)"), Some("win.send-signal::12"));
     terminal_menu.append_submenu(Some("Send Signal"), &signal_menu);
 
+    terminal_menu.append(Some("ASCII Art Safe Rendering"), Some("win.ascii-art-safe"));
+    terminal_menu.append(Some("Read-Only (Lock Input)"), Some("win.read-only"));
+    terminal_menu.append_item(&menu_item(
+        "Presentation Mode",
+        "win.presentation-mode",
+        Some("<Ctrl><Shift>p"),
+    ));
     terminal_menu.append(Some("Reset"), Some("win.reset"));
     terminal_menu.append(Some("Clear Scrollback && Reset"), Some("win.clear-reset"));
     menu.append_submenu(Some("Terminal"), &terminal_menu);
@@ -116,6 +180,7 @@ pub fn create_menu_model_with_options(show_debug: bool) -> gio::Menu {
     // Help menu
     let help_menu = gio::Menu::new();
     help_menu.append(Some("Preferences..."), Some("win.preferences"));
+    help_menu.append(Some("Usage Statistics..."), Some("win.usage-stats"));
     help_menu.append(Some("Check for Updates..."), Some("win.check-updates"));
     help_menu.append(Some("About"), Some("win.about"));
 
@@ -147,10 +212,39 @@ fn create_tools_submenu() -> gio::Menu {
     menu
 }
 
+/// Create the New Tab with Profile submenu
+fn create_profiles_submenu(profiles: &[ProfileConfig]) -> gio::Menu {
+    let menu = gio::Menu::new();
+    for (i, profile) in profiles.iter().enumerate() {
+        let action = format!("win.new-profile-tab::{}", i);
+        menu.append(Some(&profile.name), Some(&action));
+    }
+    menu
+}
+
+/// Create the Workspaces submenu
+fn create_workspaces_submenu(workspaces: &[WorkspaceConfig]) -> gio::Menu {
+    let menu = gio::Menu::new();
+    for workspace in workspaces {
+        let item = gio::MenuItem::new(Some(&workspace.name), None);
+        item.set_action_and_target_value(
+            Some("win.open-workspace"),
+            Some(&glib::Variant::from(workspace.name.as_str())),
+        );
+        menu.append_item(&item);
+    }
+    menu
+}
+
 /// Rebuild the Tools menu in the menu bar (called after preferences save).
 /// Rebuilds the entire menu model and replaces it on the PopoverMenuBar.
 #[allow(dead_code)]
-pub fn rebuild_menu_bar(menu_bar: &gtk4::PopoverMenuBar, show_debug: bool) {
-    let menu = create_menu_model_with_options(show_debug);
+pub fn rebuild_menu_bar(
+    menu_bar: &gtk4::PopoverMenuBar,
+    show_debug: bool,
+    profiles: &[ProfileConfig],
+    workspaces: &[WorkspaceConfig],
+) {
+    let menu = create_menu_model_with_options(show_debug, profiles, workspaces);
     menu_bar.set_menu_model(Some(&menu));
 }
@@ -0,0 +1,502 @@
+//! Terminal rendering with GSK render nodes
+//!
+//! Each visible row becomes one render node built in row-local coordinates
+//! and cached by the hash of its contents. Unchanged rows reuse last frame's
+//! node, so only edited rows are shaped with Pango again, and scrolling just
+//! moves existing nodes. Because GSK diffs the node tree between frames,
+//! reused nodes also keep their pixels out of the damage region: typing at a
+//! prompt repaints one row rather than the whole window.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use gtk4::prelude::*;
+use gtk4::{gdk, graphene, gsk, pango, Snapshot};
+
+use cterm_core::cell::{AttrOverrides, Cell, CellAttrs};
+use cterm_core::color::{Color, ColorPalette, Rgb};
+use cterm_core::hints::HintMode;
+use cterm_core::screen::{CursorStyle, Screen};
+use cterm_core::term::Terminal;
+use cterm_ui::gutter::{gutter_color, GUTTER_WIDTH};
+use cterm_ui::scroll_marks::{scroll_mark_ticks, MARK_HEIGHT};
+use cterm_ui::status::{StatusSegment, StatusStrip};
+use cterm_ui::theme::Theme;
+
+use crate::terminal_widget::CellDimensions;
+
+/// Cornflower blue used for hyperlinks with the default foreground
+const HYPERLINK_COLOR: Rgb = Rgb::new(100, 149, 237);
+
+/// Rendering parameters for a frame
+pub struct RenderConfig<'a> {
+    pub font_family: &'a str,
+    pub font_size: f64,
+    pub cell_dims: CellDimensions,
+    pub background_override: Option<Rgb>,
+    /// Opacity of the default background (1.0 is opaque)
+    pub opacity: f64,
+    /// Minimum text contrast ratio (presentation mode); 1.0 disables
+    pub minimum_contrast: f64,
+    /// Replacements for blink, concealed and dim text
+    pub attr_overrides: AttrOverrides,
+    /// Exit status bars along finished commands
+    pub command_gutter: bool,
+    pub show_scrollbar: bool,
+    /// Keystroke overlay text (presentation mode)
+    pub keystrokes: Option<String>,
+    /// Status strip contents (empty hides the strip)
+    pub status: Vec<StatusSegment>,
+    /// Input method composition text, drawn at the cursor
+    pub preedit: Option<String>,
+    /// Active hint mode, whose labels are drawn over their targets
+    pub hints: Option<&'a HintMode>,
+}
+
+/// Builds the terminal's render nodes, reusing rows that didn't change
+#[derive(Default)]
+pub struct NodeRenderer {
+    /// Row nodes from the last frame by content hash (None for blank rows)
+    rows: HashMap<u64, Option<gsk::RenderNode>>,
+    /// Hash of the settings that affect every row; a change drops the cache
+    style: u64,
+}
+
+impl NodeRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the terminal contents for a `width` x `height` widget
+    pub fn snapshot(
+        &mut self,
+        snapshot: &Snapshot,
+        context: &pango::Context,
+        terminal: &Terminal,
+        theme: &Theme,
+        config: &RenderConfig<'_>,
+        (width, height): (f32, f32),
+    ) {
+        let screen = terminal.screen();
+        let palette = &theme.colors;
+        let bg = config.background_override.unwrap_or(palette.background);
+
+        snapshot.append_color(
+            &rgba(bg, config.opacity as f32),
+            &graphene::Rect::new(0.0, 0.0, width, height),
+        );
+
+        let layout = pango::Layout::new(context);
+        let font_desc = pango::FontDescription::from_string(&format!(
+            "{} {}",
+            config.font_family, config.font_size
+        ));
+        layout.set_font_description(Some(&font_desc));
+
+        let style = style_hash(config, bg, screen.modes.ascii_art_safe);
+        if style != self.style {
+            self.rows.clear();
+            self.style = style;
+        }
+
+        let cell_width = config.cell_dims.width as f32;
+        let cell_height = config.cell_dims.height as f32;
+        let rows = screen.height();
+        let cols = screen.width();
+
+        let mut frame_rows = HashMap::with_capacity(rows);
+        for row in 0..rows {
+            let hash = row_hash(screen, row);
+            let node = match self.rows.get(&hash).or_else(|| frame_rows.get(&hash)) {
+                Some(node) => node.clone(),
+                None => build_row(screen, row, &layout, palette, bg, config),
+            };
+            if let Some(ref node) = node {
+                snapshot.save();
+                snapshot.translate(&graphene::Point::new(0.0, row as f32 * cell_height));
+                snapshot.append_node(node);
+                snapshot.restore();
+            }
+            frame_rows.insert(hash, node);
+        }
+        self.rows = frame_rows;
+
+        let text = |text: &str, x: f32, y: f32, color: Rgb| {
+            layout.set_text(text);
+            append_layout(snapshot, &layout, x, y, rgba(color, 1.0));
+        };
+
+        // Cursor
+        if screen.modes.show_cursor && screen.scroll_offset == 0 {
+            let cursor = &screen.cursor;
+            let x = cursor.col as f32 * cell_width;
+            let y = cursor.row as f32 * cell_height;
+            let color = rgba(theme.cursor.color, 1.0);
+
+            match cursor.style {
+                CursorStyle::Block => {
+                    snapshot
+                        .append_color(&color, &graphene::Rect::new(x, y, cell_width, cell_height));
+                    if let Some(cell) = screen.get_cell(cursor.row, cursor.col) {
+                        if cell.c != ' ' {
+                            text(&cell.grapheme(), x, y, theme.cursor.text_color);
+                        }
+                    }
+                }
+                CursorStyle::Underline => {
+                    snapshot.append_color(
+                        &color,
+                        &graphene::Rect::new(x, y + cell_height - 2.0, cell_width, 2.0),
+                    );
+                }
+                CursorStyle::Bar => {
+                    snapshot.append_color(&color, &graphene::Rect::new(x, y, 2.0, cell_height));
+                }
+            }
+        }
+
+        // Input method composition text at the cursor position
+        if let Some(ref preedit) = config.preedit {
+            if screen.scroll_offset == 0 {
+                let cursor = &screen.cursor;
+                let x = cursor.col as f32 * cell_width;
+                let y = cursor.row as f32 * cell_height;
+                let preedit_width = preedit.chars().count() as f32 * cell_width;
+
+                snapshot.append_color(
+                    &rgba(palette.foreground, 1.0),
+                    &graphene::Rect::new(x, y, preedit_width, cell_height),
+                );
+                text(preedit, x, y, palette.background);
+                // Underline to indicate composition
+                snapshot.append_color(
+                    &rgba(palette.background, 1.0),
+                    &graphene::Rect::new(x, y + cell_height - 1.0, preedit_width, 1.0),
+                );
+            }
+        }
+
+        // Exit status gutter, with a gap between adjacent commands
+        if config.command_gutter {
+            for block in screen.command_blocks() {
+                snapshot.append_color(
+                    &rgba(gutter_color(&block, palette), 0.8),
+                    &graphene::Rect::new(
+                        0.0,
+                        block.top as f32 * cell_height,
+                        GUTTER_WIDTH as f32,
+                        (block.bottom - block.top) as f32 * cell_height - 1.0,
+                    ),
+                );
+            }
+        }
+
+        // Scrollbar overlay when there is scrollback content
+        let scrollback_len = screen.scrollback().len();
+        if config.show_scrollbar && scrollback_len > 0 {
+            let total_lines = scrollback_len + rows;
+            let view_height = rows as f32 * cell_height;
+            let view_width = cols as f32 * cell_width;
+
+            let bar_width = 6.0;
+            let bar_inset = 2.0;
+            let min_thumb_height = 20.0;
+            let thumb_height =
+                (rows as f32 / total_lines as f32 * view_height).max(min_thumb_height);
+            let scrollable = view_height - thumb_height;
+            let fraction = screen.scroll_offset as f32 / scrollback_len as f32;
+            // fraction=0 (at bottom) → thumb at bottom, fraction=1 → thumb at top
+            let thumb_y = (1.0 - fraction) * scrollable;
+            let opacity = if screen.scroll_offset > 0 { 0.5 } else { 0.25 };
+            let bar_x = view_width - bar_width - bar_inset;
+
+            // Prompt, error and search marks under the thumb
+            let marks = screen.marks();
+            for (y, color) in scroll_mark_ticks(&marks, total_lines, view_height as f64, palette) {
+                snapshot.append_color(
+                    &rgba(color, 0.9),
+                    &graphene::Rect::new(bar_x, y as f32, bar_width, MARK_HEIGHT as f32),
+                );
+            }
+
+            let thumb = graphene::Rect::new(bar_x, thumb_y, bar_width, thumb_height);
+            snapshot.push_rounded_clip(&gsk::RoundedRect::from_rect(thumb, bar_width / 2.0));
+            snapshot.append_color(&gdk::RGBA::new(0.5, 0.5, 0.5, opacity), &thumb);
+            snapshot.pop();
+        }
+
+        // Session badge (OSC 1337 SetBadgeFormat) in the top-right corner
+        let badge = screen.badge();
+        if !badge.is_empty() {
+            let badge_layout = pango::Layout::new(context);
+            let mut badge_font = font_desc.clone();
+            badge_font.set_weight(pango::Weight::Bold);
+            badge_font.set_size((font_desc.size() as f64 * 2.5) as i32);
+            badge_layout.set_font_description(Some(&badge_font));
+            badge_layout.set_alignment(pango::Alignment::Right);
+            badge_layout.set_text(&badge);
+            let badge_width = badge_layout.pixel_size().0 as f32;
+            let x = (cols as f32 * cell_width - badge_width - cell_width * 2.0).max(0.0);
+            append_layout(
+                snapshot,
+                &badge_layout,
+                x,
+                cell_height / 2.0,
+                rgba(palette.foreground, 0.2),
+            );
+        }
+
+        // Status strip over the bottom row
+        if !config.status.is_empty() {
+            let strip_y = rows.saturating_sub(1) as f32 * cell_height;
+            snapshot.append_color(
+                &rgba(StatusStrip::background(theme), 0.9),
+                &graphene::Rect::new(0.0, strip_y, cols as f32 * cell_width, cell_height),
+            );
+            let mut x = cell_width / 2.0;
+            for segment in &config.status {
+                text(&segment.text, x, strip_y, segment.color(theme));
+                x += layout.pixel_size().0 as f32 + cell_width * 2.0;
+            }
+        }
+
+        // Hint labels over the start of each target, with the target underlined
+        if let Some(mode) = config.hints {
+            let color = rgba(theme.cursor.color, 1.0);
+            for hint in mode.visible() {
+                let x = hint.col as f32 * cell_width;
+                let y = hint.row as f32 * cell_height;
+                snapshot.append_color(
+                    &color,
+                    &graphene::Rect::new(
+                        x,
+                        y + cell_height - 2.0,
+                        hint.width as f32 * cell_width,
+                        2.0,
+                    ),
+                );
+                let label = &hint.label[mode.typed().len()..];
+                snapshot.append_color(
+                    &color,
+                    &graphene::Rect::new(
+                        x,
+                        y,
+                        label.chars().count() as f32 * cell_width,
+                        cell_height,
+                    ),
+                );
+                text(label, x, y, theme.cursor.text_color);
+            }
+        }
+
+        // Presentation keystroke overlay in the bottom-right corner
+        if let Some(ref keys) = config.keystrokes {
+            layout.set_text(keys);
+            let (text_width, text_height) = layout.pixel_size();
+            let padding = cell_height / 2.0;
+            let box_width = text_width as f32 + padding * 2.0;
+            let box_height = text_height as f32 + padding;
+            let box_x = (cols as f32 * cell_width - box_width - padding).max(0.0);
+            let box_y = (rows as f32 * cell_height - box_height - padding).max(0.0);
+
+            snapshot.append_color(
+                &rgba(palette.foreground, 0.85),
+                &graphene::Rect::new(box_x, box_y, box_width, box_height),
+            );
+            text(
+                keys,
+                box_x + padding,
+                box_y + padding / 2.0,
+                palette.background,
+            );
+        }
+    }
+}
+
+/// Build the node for one visible row, in row-local coordinates
+fn build_row(
+    screen: &Screen,
+    row: usize,
+    layout: &pango::Layout,
+    palette: &ColorPalette,
+    bg: Rgb,
+    config: &RenderConfig<'_>,
+) -> Option<gsk::RenderNode> {
+    let snapshot = Snapshot::new();
+    let cell_width = config.cell_dims.width as f32;
+    let cell_height = config.cell_dims.height as f32;
+    let ascii_art_safe = screen.modes.ascii_art_safe;
+    let absolute_line = screen.visible_row_to_absolute_line(row);
+
+    for col in 0..screen.width() {
+        let Some(cell) = screen.get_cell_with_scrollback(absolute_line, col) else {
+            continue;
+        };
+        let cell = config.attr_overrides.apply_to(cell);
+        let cell = cell.as_ref();
+        if cell.attrs.contains(CellAttrs::WIDE_SPACER) {
+            continue;
+        }
+
+        let x = col as f32 * cell_width;
+        let width = if cell.is_wide() {
+            cell_width * 2.0
+        } else {
+            cell_width
+        };
+        let is_selected = screen.is_selected(absolute_line, col);
+        let (cell_bg, fg) = cell_colors(cell, is_selected, palette, bg, config.minimum_contrast);
+
+        if let Some(cell_bg) = cell_bg {
+            snapshot.append_color(
+                &rgba(cell_bg, 1.0),
+                &graphene::Rect::new(x, 0.0, width, cell_height),
+            );
+        }
+
+        if cell.c != ' ' && !cell.attrs.contains(CellAttrs::HIDDEN) {
+            layout.set_attributes(Some(&cell_attributes(cell, ascii_art_safe)));
+            layout.set_text(&cell.grapheme());
+            // Keep the glyph inside its cell so fallback fonts with a
+            // wider advance can't spill into the neighbouring column
+            if ascii_art_safe {
+                snapshot.push_clip(&graphene::Rect::new(x, 0.0, width, cell_height));
+            }
+            append_layout(&snapshot, layout, x, 0.0, rgba(fg, 1.0));
+            if ascii_art_safe {
+                snapshot.pop();
+            }
+        }
+    }
+
+    layout.set_attributes(None::<&pango::AttrList>);
+    snapshot.to_node()
+}
+
+/// Background (None for the default background) and foreground of a cell
+fn cell_colors(
+    cell: &Cell,
+    is_selected: bool,
+    palette: &ColorPalette,
+    bg: Rgb,
+    minimum_contrast: f64,
+) -> (Option<Rgb>, Rgb) {
+    // INVERSE XOR selection
+    let is_inverted = cell.attrs.contains(CellAttrs::INVERSE) != is_selected;
+
+    let cell_bg = if is_inverted {
+        Some(if cell.fg == Color::Default {
+            palette.foreground
+        } else {
+            cell.fg.to_rgb(palette)
+        })
+    } else if cell.bg != Color::Default || is_selected {
+        Some(cell.bg.to_rgb(palette))
+    } else {
+        None
+    };
+
+    let fg = if is_inverted {
+        cell.bg.to_rgb(palette)
+    } else if cell.hyperlink.is_some() && cell.fg == Color::Default {
+        HYPERLINK_COLOR
+    } else if cell.fg == Color::Default {
+        palette.foreground
+    } else {
+        cell.fg.to_rgb(palette)
+    };
+    let fg = if cell.attrs.contains(CellAttrs::DIM) {
+        Rgb::new(fg.r / 2, fg.g / 2, fg.b / 2)
+    } else {
+        fg
+    };
+    let fg = if minimum_contrast > 1.0 {
+        fg.with_min_contrast(&cell_bg.unwrap_or(bg), minimum_contrast)
+    } else {
+        fg
+    };
+
+    (cell_bg, fg)
+}
+
+/// Pango attributes for a cell's text style
+fn cell_attributes(cell: &Cell, ascii_art_safe: bool) -> pango::AttrList {
+    let attrs = pango::AttrList::new();
+
+    if cell.attrs.contains(CellAttrs::BOLD) {
+        attrs.insert(pango::AttrInt::new_weight(pango::Weight::Bold));
+    }
+    if cell.attrs.contains(CellAttrs::ITALIC) {
+        attrs.insert(pango::AttrInt::new_style(pango::Style::Italic));
+    }
+    if cell.attrs.contains(CellAttrs::UNDERLINE) || cell.hyperlink.is_some() {
+        attrs.insert(pango::AttrInt::new_underline(pango::Underline::Single));
+        if cell.hyperlink.is_some() {
+            let Rgb { r, g, b } = HYPERLINK_COLOR;
+            attrs.insert(pango::AttrColor::new_underline_color(
+                r as u16 * 256,
+                g as u16 * 256,
+                b as u16 * 256,
+            ));
+        }
+    }
+    if cell.attrs.contains(CellAttrs::STRIKETHROUGH) {
+        attrs.insert(pango::AttrInt::new_strikethrough(true));
+    }
+    if ascii_art_safe {
+        attrs.insert(pango::AttrFontFeatures::new(
+            "liga 0, clig 0, calt 0, dlig 0",
+        ));
+    }
+
+    attrs
+}
+
+/// Append `layout` with its top-left corner at (`x`, `y`)
+fn append_layout(snapshot: &Snapshot, layout: &pango::Layout, x: f32, y: f32, color: gdk::RGBA) {
+    snapshot.save();
+    snapshot.translate(&graphene::Point::new(x, y));
+    snapshot.append_layout(layout, &color);
+    snapshot.restore();
+}
+
+fn rgba(color: Rgb, alpha: f32) -> gdk::RGBA {
+    let (r, g, b) = color.to_f64();
+    gdk::RGBA::new(r as f32, g as f32, b as f32, alpha)
+}
+
+/// Hash of the settings that change how every row is drawn
+fn style_hash(config: &RenderConfig<'_>, bg: Rgb, ascii_art_safe: bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    config.font_family.hash(&mut hasher);
+    config.font_size.to_bits().hash(&mut hasher);
+    config.cell_dims.width.to_bits().hash(&mut hasher);
+    config.cell_dims.height.to_bits().hash(&mut hasher);
+    config.minimum_contrast.to_bits().hash(&mut hasher);
+    config.attr_overrides.hash(&mut hasher);
+    bg.hash(&mut hasher);
+    ascii_art_safe.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hash of everything that affects how a visible row is drawn
+fn row_hash(screen: &Screen, row: usize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let absolute_line = screen.visible_row_to_absolute_line(row);
+    for col in 0..screen.width() {
+        match screen.get_cell_with_scrollback(absolute_line, col) {
+            Some(cell) => {
+                cell.c.hash(&mut hasher);
+                cell.combining.hash(&mut hasher);
+                cell.fg.hash(&mut hasher);
+                cell.bg.hash(&mut hasher);
+                cell.attrs.bits().hash(&mut hasher);
+                cell.hyperlink.is_some().hash(&mut hasher);
+                screen.is_selected(absolute_line, col).hash(&mut hasher);
+            }
+            None => 0u8.hash(&mut hasher),
+        }
+    }
+    hasher.finish()
+}
@@ -1,49 +1,41 @@
-//! Notification bar for file transfer UI
+//! Notification bar for in-window prompts
 //!
-//! Shows a dismissible notification when files are received via iTerm2 protocol.
-//! Format: "Received file: Name.bin (1.2 MB)" [Save] [Save As...] [Discard]
+//! Shows the current prompt of the window's [`PromptQueue`] with a button per
+//! action, such as "Received file: Name.bin (1.2 MB)" [Save] [Save As...]
+//! [Discard], or a request the permission policy asks about, such as an OSC
+//! 52 clipboard read to allow or deny.
 
-use std::cell::{Cell, RefCell};
+use std::cell::RefCell;
 use std::rc::Rc;
 
-use cterm_ui::format_size;
+use cterm_core::Capability;
+use cterm_ui::events::{KeyCode, Modifiers};
+use cterm_ui::prompt::{Prompt, PromptAction, PromptQueue};
 use gtk4::prelude::*;
 use gtk4::{Box as GtkBox, Button, Label, Orientation};
 
 /// Notification bar height in pixels
 pub const NOTIFICATION_BAR_HEIGHT: i32 = 32;
 
-/// Callback type for save actions
-type SaveCallback = Rc<RefCell<Option<Box<dyn Fn(u64)>>>>;
-/// Callback type for save-as actions
-type SaveAsCallback = Rc<RefCell<Option<Box<dyn Fn(u64)>>>>;
-/// Callback type for discard actions
-type DiscardCallback = Rc<RefCell<Option<Box<dyn Fn(u64)>>>>;
+/// Most actions a prompt offers without a preview
+const MAX_BUTTONS: usize = 3;
 
-/// Notification bar widget for file transfers
+/// Callback type for answered prompts
+type AnswerCallback = Rc<RefCell<Option<Box<dyn Fn(Prompt, PromptAction)>>>>;
+
+/// Notification bar widget for prompts
 #[derive(Clone)]
 pub struct NotificationBar {
     /// Main container widget
     container: GtkBox,
-    /// Label showing file name and size
+    /// Label showing the prompt
     label: Label,
-    /// Save button (kept for potential future use)
-    #[allow(dead_code)]
-    save_button: Button,
-    /// Save As button (kept for potential future use)
-    #[allow(dead_code)]
-    save_as_button: Button,
-    /// Discard button (kept for potential future use)
-    #[allow(dead_code)]
-    discard_button: Button,
-    /// Current file ID
-    file_id: Rc<Cell<u64>>,
-    /// Save callback
-    on_save: SaveCallback,
-    /// Save As callback
-    on_save_as: SaveAsCallback,
-    /// Discard callback
-    on_discard: DiscardCallback,
+    /// One button per action, the unused ones hidden
+    buttons: Vec<Button>,
+    /// Prompts waiting for an answer
+    prompts: Rc<RefCell<PromptQueue>>,
+    /// Answer callback
+    on_answer: AnswerCallback,
 }
 
 impl NotificationBar {
@@ -63,71 +55,42 @@ impl NotificationBar {
         label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
         container.append(&label);
 
-        // Create Save button
-        let save_button = Button::with_label("Save");
-        container.append(&save_button);
-
-        // Create Save As button
-        let save_as_button = Button::with_label("Save As...");
-        container.append(&save_as_button);
-
-        // Create Discard button
-        let discard_button = Button::with_label("Discard");
-        container.append(&discard_button);
+        // Create the buttons, labelled when a prompt is shown
+        let buttons: Vec<Button> = (0..MAX_BUTTONS)
+            .map(|_| {
+                let button = Button::new();
+                container.append(&button);
+                button
+            })
+            .collect();
 
         // Initially hidden
         container.set_visible(false);
 
-        let file_id = Rc::new(Cell::new(0u64));
-        let on_save: SaveCallback = Rc::new(RefCell::new(None));
-        let on_save_as: SaveAsCallback = Rc::new(RefCell::new(None));
-        let on_discard: DiscardCallback = Rc::new(RefCell::new(None));
+        let bar = Self {
+            container,
+            label,
+            buttons,
+            prompts: Rc::new(RefCell::new(PromptQueue::new())),
+            on_answer: Rc::new(RefCell::new(None)),
+        };
 
         // Connect button signals
-        {
-            let file_id = Rc::clone(&file_id);
-            let on_save = Rc::clone(&on_save);
-            save_button.connect_clicked(move |_| {
-                let id = file_id.get();
-                if let Some(ref cb) = *on_save.borrow() {
-                    cb(id);
+        for (i, button) in bar.buttons.iter().enumerate() {
+            let bar_clicked = bar.clone();
+            button.connect_clicked(move |_| {
+                let action = bar_clicked
+                    .prompts
+                    .borrow()
+                    .current()
+                    .and_then(|p| p.actions(false).get(i).copied());
+                if let Some(action) = action {
+                    bar_clicked.answer(action);
                 }
             });
         }
 
-        {
-            let file_id = Rc::clone(&file_id);
-            let on_save_as = Rc::clone(&on_save_as);
-            save_as_button.connect_clicked(move |_| {
-                let id = file_id.get();
-                if let Some(ref cb) = *on_save_as.borrow() {
-                    cb(id);
-                }
-            });
-        }
-
-        {
-            let file_id = Rc::clone(&file_id);
-            let on_discard = Rc::clone(&on_discard);
-            discard_button.connect_clicked(move |_| {
-                let id = file_id.get();
-                if let Some(ref cb) = *on_discard.borrow() {
-                    cb(id);
-                }
-            });
-        }
-
-        Self {
-            container,
-            label,
-            save_button,
-            save_as_button,
-            discard_button,
-            file_id,
-            on_save,
-            on_save_as,
-            on_discard,
-        }
+        bar
     }
 
     /// Get the widget to add to the UI
@@ -135,24 +98,56 @@ impl NotificationBar {
         &self.container
     }
 
-    /// Show notification for a received file
+    /// Ask what to do with a received file
     pub fn show_file(&self, id: u64, name: Option<&str>, size: usize) {
-        self.file_id.set(id);
+        log::debug!("Showing notification for file {:?} (id={})", name, id);
+        self.prompts
+            .borrow_mut()
+            .push_file(id, name.map(str::to_string), size);
+        self.refresh();
+    }
 
-        let display_name = name.unwrap_or("unnamed file");
-        let size_str = format_size(size);
-        let text = format!("Received file: {} ({})", display_name, size_str);
+    /// Ask whether tab `tab_id` may use `capability`
+    pub fn ask_permission(&self, tab_id: u64, capability: Capability) {
+        self.prompts.borrow_mut().ask_permission(tab_id, capability);
+        self.refresh();
+    }
 
-        self.label.set_text(&text);
-        self.container.set_visible(true);
+    /// Answer the current prompt
+    pub fn answer(&self, action: PromptAction) {
+        let prompt = self.prompts.borrow_mut().resolve(action);
+        self.refresh();
+        if let Some(prompt) = prompt {
+            if let Some(ref cb) = *self.on_answer.borrow() {
+                cb(prompt, action);
+            }
+        }
+    }
 
-        log::debug!("Showing notification for file {} (id={})", display_name, id);
+    /// Action of the current prompt picked by a key press, if any
+    pub fn action_for_key(&self, key: KeyCode, modifiers: Modifiers) -> Option<PromptAction> {
+        self.prompts.borrow().action_for_key(key, modifiers, false)
     }
 
-    /// Hide the notification bar
-    pub fn hide(&self) {
-        self.container.set_visible(false);
-        self.file_id.set(0);
+    /// Show the current prompt, or hide the bar when nothing is waiting
+    fn refresh(&self) {
+        let prompts = self.prompts.borrow();
+        let (Some(prompt), Some(message)) = (prompts.current(), prompts.message()) else {
+            self.container.set_visible(false);
+            return;
+        };
+        let actions = prompt.actions(false);
+        for (i, button) in self.buttons.iter().enumerate() {
+            match actions.get(i) {
+                Some(action) => {
+                    button.set_label(action.label());
+                    button.set_visible(true);
+                }
+                None => button.set_visible(false),
+            }
+        }
+        self.label.set_text(&message);
+        self.container.set_visible(true);
     }
 
     /// Check if the notification bar is visible
@@ -161,34 +156,12 @@ impl NotificationBar {
         self.container.is_visible()
     }
 
-    /// Get the current file ID
-    #[allow(dead_code)]
-    pub fn file_id(&self) -> u64 {
-        self.file_id.get()
-    }
-
-    /// Set callback for Save button
-    pub fn set_on_save<F>(&self, callback: F)
-    where
-        F: Fn(u64) + 'static,
-    {
-        *self.on_save.borrow_mut() = Some(Box::new(callback));
-    }
-
-    /// Set callback for Save As button
-    pub fn set_on_save_as<F>(&self, callback: F)
-    where
-        F: Fn(u64) + 'static,
-    {
-        *self.on_save_as.borrow_mut() = Some(Box::new(callback));
-    }
-
-    /// Set callback for Discard button
-    pub fn set_on_discard<F>(&self, callback: F)
+    /// Set callback for answered prompts
+    pub fn set_on_answer<F>(&self, callback: F)
     where
-        F: Fn(u64) + 'static,
+        F: Fn(Prompt, PromptAction) + 'static,
     {
-        *self.on_discard.borrow_mut() = Some(Box::new(callback));
+        *self.on_answer.borrow_mut() = Some(Box::new(callback));
     }
 }
 
@@ -0,0 +1,124 @@
+//! Split pane widgets
+//!
+//! A tab's notebook page holds its panes' terminals in nested `Paned`
+//! widgets that follow the tab's [`PaneTree`](cterm_app::session::PaneTree):
+//! splitting a pane puts it and the new pane in a `Paned` where it was, and
+//! closing one gives its place to its sibling. Dividers can be dragged.
+
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Orientation, Paned, Widget};
+
+use cterm_app::session::{PaneRect, SplitAxis};
+use cterm_ui::events::PaneDirection;
+
+use crate::terminal_widget::TerminalWidget;
+
+/// Notebook page holding a tab's panes, starting with `terminal`
+pub fn page(terminal: &TerminalWidget) -> GtkBox {
+    let page = GtkBox::new(Orientation::Vertical, 0);
+    let widget = terminal.widget();
+    widget.set_hexpand(true);
+    widget.set_vexpand(true);
+    page.append(widget);
+    page
+}
+
+/// Put `new` right of or below `widget`, splitting its area in half
+pub fn split(widget: &Widget, new: &Widget, axis: SplitAxis) {
+    let Some(parent) = widget.parent() else {
+        return;
+    };
+    let (orientation, size) = match axis {
+        SplitAxis::Horizontal => (Orientation::Horizontal, widget.width()),
+        SplitAxis::Vertical => (Orientation::Vertical, widget.height()),
+    };
+    new.set_hexpand(true);
+    new.set_vexpand(true);
+
+    let paned = Paned::new(orientation);
+    paned.set_shrink_start_child(false);
+    paned.set_shrink_end_child(false);
+    replace(&parent, widget, paned.upcast_ref());
+    paned.set_start_child(Some(widget));
+    paned.set_end_child(Some(new));
+    paned.set_position(size / 2);
+}
+
+/// Take `widget` out of its split, its sibling taking the split's place
+pub fn remove(widget: &Widget) {
+    let Some(paned) = widget.parent().and_downcast::<Paned>() else {
+        return;
+    };
+    let sibling = if paned.start_child().as_ref() == Some(widget) {
+        paned.end_child()
+    } else {
+        paned.start_child()
+    };
+    paned.set_start_child(None::<&Widget>);
+    paned.set_end_child(None::<&Widget>);
+    if let (Some(parent), Some(sibling)) = (paned.parent(), sibling) {
+        replace(&parent, paned.upcast_ref(), &sibling);
+    }
+}
+
+/// Put `new` where `old` is in `parent`, a split or the page
+fn replace(parent: &Widget, old: &Widget, new: &Widget) {
+    if let Some(paned) = parent.downcast_ref::<Paned>() {
+        if paned.start_child().as_ref() == Some(old) {
+            paned.set_start_child(Some(new));
+        } else {
+            paned.set_end_child(Some(new));
+        }
+    } else if let Some(page) = parent.downcast_ref::<GtkBox>() {
+        page.remove(old);
+        page.append(new);
+    }
+}
+
+/// Where each pane is shown within `page`
+pub fn areas(page: &Widget, panes: &[(u64, Widget)]) -> Vec<(u64, PaneRect)> {
+    panes
+        .iter()
+        .filter_map(|(id, widget)| {
+            let bounds = widget.compute_bounds(page)?;
+            Some((
+                *id,
+                PaneRect::new(
+                    bounds.x() as f64,
+                    bounds.y() as f64,
+                    bounds.width() as f64,
+                    bounds.height() as f64,
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// Move the divider of the innermost split around `widget` that runs
+/// across `direction`, by `step` of the split's size; false if there is none
+pub fn resize(widget: &Widget, direction: PaneDirection, step: f64) -> bool {
+    let orientation = match SplitAxis::of(direction) {
+        SplitAxis::Horizontal => Orientation::Horizontal,
+        SplitAxis::Vertical => Orientation::Vertical,
+    };
+    let mut child = widget.clone();
+    while let Some(paned) = child.parent().and_downcast::<Paned>() {
+        if paned.orientation() == orientation {
+            let size = match orientation {
+                Orientation::Horizontal => paned.width(),
+                _ => paned.height(),
+            };
+            let delta = (size as f64 * step).round() as i32;
+            let delta = match direction {
+                PaneDirection::Left | PaneDirection::Up => -delta,
+                PaneDirection::Right | PaneDirection::Down => delta,
+            };
+            let position =
+                (paned.position() + delta).clamp(paned.min_position(), paned.max_position());
+            paned.set_position(position);
+            return true;
+        }
+        child = paned.upcast();
+    }
+    false
+}
@@ -0,0 +1,148 @@
+//! New Serial Tab dialog for GTK4
+//!
+//! Lists the serial ports found on this machine and lets the user pick the
+//! line settings before opening a serial console tab.
+
+use gtk4::prelude::*;
+use gtk4::{Align, ComboBoxText, Dialog, Grid, Label, ResponseType, Window};
+
+use cterm_app::config::SerialTabConfig;
+use cterm_core::{FlowControl, Parity, SerialConfig};
+
+/// Show the New Serial Tab dialog
+///
+/// `on_open` receives the chosen port and line settings.
+pub fn show_serial_dialog<F>(parent: &impl IsA<Window>, on_open: F)
+where
+    F: Fn(SerialTabConfig) + 'static,
+{
+    let defaults = SerialTabConfig::default();
+
+    let dialog = Dialog::builder()
+        .title("New Serial Tab")
+        .transient_for(parent)
+        .modal(true)
+        .build();
+
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Open", ResponseType::Ok);
+
+    let content = dialog.content_area();
+    content.set_spacing(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(8);
+    grid.set_column_spacing(12);
+
+    let row_label = |text: &str, row: i32| {
+        let label = Label::new(Some(text));
+        label.set_halign(Align::End);
+        grid.attach(&label, 0, row, 1, 1);
+    };
+
+    // Port and baud rate also accept values that are not listed
+    row_label("Port:", 0);
+    let port_combo = ComboBoxText::with_entry();
+    for port in cterm_core::available_ports() {
+        port_combo.append_text(&port);
+    }
+    port_combo.set_active(Some(0));
+    port_combo.set_hexpand(true);
+    grid.attach(&port_combo, 1, 0, 1, 1);
+
+    row_label("Baud rate:", 1);
+    let baud_combo = ComboBoxText::with_entry();
+    for rate in SerialConfig::BAUD_RATES {
+        baud_combo.append_text(&rate.to_string());
+    }
+    baud_combo.set_active(
+        SerialConfig::BAUD_RATES
+            .iter()
+            .position(|&r| r == defaults.baud_rate)
+            .map(|i| i as u32),
+    );
+    grid.attach(&baud_combo, 1, 1, 1, 1);
+
+    row_label("Data bits:", 2);
+    let data_bits_combo = ComboBoxText::new();
+    for bits in 5..=8 {
+        data_bits_combo.append(Some(&bits.to_string()), &bits.to_string());
+    }
+    data_bits_combo.set_active_id(Some(&defaults.data_bits.to_string()));
+    grid.attach(&data_bits_combo, 1, 2, 1, 1);
+
+    row_label("Parity:", 3);
+    let parity_combo = ComboBoxText::new();
+    for parity in Parity::ALL {
+        parity_combo.append_text(parity.name());
+    }
+    parity_combo.set_active(Some(0));
+    grid.attach(&parity_combo, 1, 3, 1, 1);
+
+    row_label("Stop bits:", 4);
+    let stop_bits_combo = ComboBoxText::new();
+    stop_bits_combo.append(Some("1"), "1");
+    stop_bits_combo.append(Some("2"), "2");
+    stop_bits_combo.set_active_id(Some(&defaults.stop_bits.to_string()));
+    grid.attach(&stop_bits_combo, 1, 4, 1, 1);
+
+    row_label("Flow control:", 5);
+    let flow_combo = ComboBoxText::new();
+    for flow in FlowControl::ALL {
+        flow_combo.append_text(flow.name());
+    }
+    flow_combo.set_active(Some(0));
+    grid.attach(&flow_combo, 1, 5, 1, 1);
+
+    content.append(&grid);
+    dialog.set_default_response(ResponseType::Ok);
+
+    dialog.connect_response(move |dialog, response| {
+        if response != ResponseType::Ok {
+            dialog.close();
+            return;
+        }
+
+        let path = port_combo
+            .active_text()
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+        let baud_rate = baud_combo
+            .active_text()
+            .and_then(|s| s.trim().parse::<u32>().ok())
+            .filter(|&rate| rate > 0);
+        let Some(baud_rate) = baud_rate.filter(|_| !path.is_empty()) else {
+            // Keep the dialog open until there is something to connect to
+            dialog.error_bell();
+            return;
+        };
+
+        on_open(SerialTabConfig {
+            path,
+            baud_rate,
+            data_bits: data_bits_combo
+                .active_id()
+                .and_then(|id| id.parse().ok())
+                .unwrap_or(defaults.data_bits),
+            parity: parity_combo
+                .active()
+                .and_then(|i| Parity::ALL.get(i as usize).copied())
+                .unwrap_or_default(),
+            stop_bits: stop_bits_combo
+                .active_id()
+                .and_then(|id| id.parse().ok())
+                .unwrap_or(defaults.stop_bits),
+            flow_control: flow_combo
+                .active()
+                .and_then(|i| FlowControl::ALL.get(i as usize).copied())
+                .unwrap_or_default(),
+        });
+        dialog.close();
+    });
+
+    dialog.present();
+}
@@ -1,12 +1,15 @@
 //! Custom tab bar widget
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 
+use cterm_app::config::{ProfileConfig, TabBarVisibility, TabsConfig, WidgetsConfig};
+use cterm_app::status_segments;
+use cterm_app::tab_widgets::{self, WidgetKind};
 use gtk4::gio::{Menu, SimpleAction, SimpleActionGroup};
 use gtk4::prelude::*;
-use gtk4::{Box as GtkBox, Button, GestureClick, Label, Orientation, PopoverMenu};
+use gtk4::{glib, Box as GtkBox, Button, GestureClick, Label, Orientation, PopoverMenu};
 
 /// Callback type for tab bar events
 type TabCallback = Rc<RefCell<Option<Box<dyn Fn()>>>>;
@@ -20,8 +23,9 @@ type TabIdCallback = Rc<RefCell<Option<Box<dyn Fn(u64)>>>>;
 pub struct TabBar {
     container: GtkBox,
     tabs_box: GtkBox,
-    #[allow(dead_code)] // Kept to prevent button from being dropped
     new_tab_button: Button,
+    /// Profiles offered by right-clicking the new tab button
+    new_tab_menu: Menu,
     tabs: Rc<RefCell<Vec<TabInfo>>>,
     active_tab: Rc<RefCell<Option<u64>>>,
     on_new_tab: TabCallback,
@@ -32,14 +36,29 @@ pub struct TabBar {
     on_disconnect: TabIdCallback,
     /// Current tab ID for context menu actions
     context_menu_tab_id: Rc<RefCell<Option<u64>>>,
+    /// Hide the bar regardless of tab count (presentation mode)
+    force_hidden: Rc<RefCell<bool>>,
+    /// When the bar is shown, from the tabs config
+    visibility: Rc<Cell<TabBarVisibility>>,
+    /// Whether tabs have a close button, from the tabs config
+    show_close_button: Rc<Cell<bool>>,
+    /// Label format, from the tabs config
+    title_format: Rc<RefCell<String>>,
+    /// Clock, timer and battery labels after the new tab button
+    widgets: TabWidgets,
 }
 
 struct TabInfo {
     id: u64,
     widget: GtkBox,
     label: Label,
+    /// Title before `title_format` is applied
+    title: String,
+    /// Status segment texts for `title_format`
+    segments: Vec<(String, String)>,
     bell_icon: Label,
-    #[allow(dead_code)] // Kept to prevent button from being dropped
+    /// Match count of the tab's watch expression, hidden without one
+    watch_badge: Label,
     close_button: Button,
     context_popover: PopoverMenu,
     /// Menu model behind `context_popover`. Kept so `mark_tab_remote` can
@@ -53,6 +72,98 @@ struct TabInfo {
     is_remote: bool,
 }
 
+/// Labels of the tab bar widgets, refreshed every second while any is shown
+#[derive(Clone)]
+struct TabWidgets {
+    labels: Rc<[(WidgetKind, Label); 3]>,
+    config: Rc<Cell<WidgetsConfig>>,
+    refresh: Rc<RefCell<Option<glib::SourceId>>>,
+}
+
+impl TabWidgets {
+    fn new(container: &GtkBox) -> Self {
+        let labels = [WidgetKind::Timer, WidgetKind::Battery, WidgetKind::Clock].map(|kind| {
+            let label = Label::new(None);
+            label.add_css_class("tab-widget");
+            label.set_visible(false);
+            container.append(&label);
+            (kind, label)
+        });
+        let labels = Rc::new(labels);
+        let config = Rc::new(Cell::new(WidgetsConfig::default()));
+
+        // Click the timer to start or pause it, right-click to reset it
+        let timer = &labels[0].1;
+        timer.set_tooltip_text(Some("Click to start or pause, right-click to reset"));
+        let gesture = GestureClick::new();
+        gesture.set_button(0);
+        let labels_click = Rc::downgrade(&labels);
+        let config_click = Rc::clone(&config);
+        gesture.connect_released(move |gesture, _, _, _| {
+            if gesture.current_button() == gtk4::gdk::BUTTON_SECONDARY {
+                tab_widgets::reset_timer();
+            } else {
+                tab_widgets::toggle_timer(&config_click.get());
+            }
+            if let Some(labels) = labels_click.upgrade() {
+                update_labels(&labels[..], &config_click.get());
+            }
+        });
+        timer.add_controller(gesture);
+
+        Self {
+            labels,
+            config,
+            refresh: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// Switch to `config`, refreshing every second while any widget is shown
+    fn set_config(&self, config: WidgetsConfig) {
+        self.config.set(config);
+        update_labels(&self.labels[..], &config);
+        let mut refresh = self.refresh.borrow_mut();
+        if !config.any() {
+            if let Some(source) = refresh.take() {
+                source.remove();
+            }
+        } else if refresh.is_none() {
+            // Stops once the tab bar is dropped with its window
+            let labels = Rc::downgrade(&self.labels);
+            let config = Rc::clone(&self.config);
+            *refresh = Some(glib::timeout_add_local(
+                tab_widgets::REFRESH_INTERVAL,
+                move || match labels.upgrade() {
+                    Some(labels) => {
+                        update_labels(&labels[..], &config.get());
+                        glib::ControlFlow::Continue
+                    }
+                    None => glib::ControlFlow::Break,
+                },
+            ));
+        }
+    }
+}
+
+/// Show the current widget texts, hiding the labels of disabled widgets
+fn update_labels(labels: &[(WidgetKind, Label)], config: &WidgetsConfig) {
+    let current = tab_widgets::widgets(config);
+    for (kind, label) in labels {
+        match current.iter().find(|widget| widget.kind == *kind) {
+            Some(widget) => {
+                label.set_text(&widget.text);
+                if widget.emphasis {
+                    label.add_css_class("emphasis");
+                } else {
+                    label.remove_css_class("emphasis");
+                }
+                label.set_visible(true);
+            }
+            None => label.set_visible(false),
+        }
+    }
+}
+
 impl TabBar {
     /// Create a new tab bar
     pub fn new() -> Self {
@@ -74,11 +185,32 @@ impl TabBar {
 
         container.append(&tabs_box);
         container.append(&new_tab_button);
+        let widgets = TabWidgets::new(&container);
+
+        // Right-click the new tab button to pick a profile
+        let new_tab_menu = Menu::new();
+        let new_tab_popover = PopoverMenu::from_model(Some(&new_tab_menu));
+        new_tab_popover.set_parent(&new_tab_button);
+        new_tab_popover.set_has_arrow(false);
+        let gesture = GestureClick::new();
+        gesture.set_button(3);
+        let menu_gesture = new_tab_menu.clone();
+        gesture.connect_pressed(move |gesture, _, _, _| {
+            if menu_gesture.n_items() == 0 {
+                return;
+            }
+            gesture.set_state(gtk4::EventSequenceState::Claimed);
+            // Deferred like the tab context menu, see add_tab
+            let popover = new_tab_popover.clone();
+            glib::idle_add_local_once(move || popover.popup());
+        });
+        new_tab_button.add_controller(gesture);
 
         let tab_bar = Self {
             container,
             tabs_box,
             new_tab_button: new_tab_button.clone(),
+            new_tab_menu,
             tabs: Rc::new(RefCell::new(Vec::new())),
             active_tab: Rc::new(RefCell::new(None)),
             on_new_tab: Rc::new(RefCell::new(None)),
@@ -88,6 +220,11 @@ impl TabBar {
             on_set_color: Rc::new(RefCell::new(None)),
             on_disconnect: Rc::new(RefCell::new(None)),
             context_menu_tab_id: Rc::new(RefCell::new(None)),
+            force_hidden: Rc::new(RefCell::new(false)),
+            visibility: Rc::new(Cell::new(TabBarVisibility::default())),
+            show_close_button: Rc::new(Cell::new(true)),
+            title_format: Rc::new(RefCell::new(String::from("{title}"))),
+            widgets,
         };
 
         // Set up new tab button click
@@ -119,29 +256,43 @@ impl TabBar {
         bell_icon.set_visible(false);
         bell_icon.add_css_class("tab-bell-icon");
 
-        let label = Label::new(Some(title));
+        // Watch match count (hidden until a watch is set)
+        let watch_badge = Label::new(None);
+        watch_badge.set_visible(false);
+        watch_badge.add_css_class("tab-watch-badge");
+
+        let label = Label::new(Some(&status_segments::format_title(
+            &self.title_format.borrow(),
+            title,
+            &[],
+        )));
         label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
         label.set_max_width_chars(30);
 
         let close_button = Button::builder().label("×").focusable(false).build();
         close_button.add_css_class("tab-close-button");
         close_button.add_css_class("flat");
+        close_button.set_visible(self.show_close_button.get());
 
         tab_widget.append(&bell_icon);
         tab_widget.append(&label);
+        tab_widget.append(&watch_badge);
         tab_widget.append(&close_button);
 
         // Set up close button
         let close_callbacks = Rc::clone(&self.on_close_callbacks);
         let tab_id = id;
-        close_button.connect_clicked(move |_| {
-            // Take the callback out of the map so the borrow is released before
-            // invoking it — the callback calls remove_tab() which needs borrow_mut.
-            let callback = close_callbacks.borrow_mut().remove(&tab_id);
-            if let Some(cb) = callback {
-                cb();
-            }
+        close_button.connect_clicked(move |_| close_tab(&close_callbacks, tab_id));
+
+        // Middle-click closes the tab, with or without a close button
+        let middle_gesture = GestureClick::new();
+        middle_gesture.set_button(2);
+        let close_callbacks = Rc::clone(&self.on_close_callbacks);
+        middle_gesture.connect_released(move |gesture, _, _, _| {
+            gesture.set_state(gtk4::EventSequenceState::Claimed);
+            close_tab(&close_callbacks, tab_id);
         });
+        tab_widget.add_controller(middle_gesture);
 
         // Set up tab click via GestureClick (left button)
         let click_gesture = GestureClick::new();
@@ -238,7 +389,10 @@ impl TabBar {
             id,
             widget: tab_widget,
             label,
+            title: title.to_string(),
+            segments: Vec::new(),
             bell_icon,
+            watch_badge,
             close_button,
             context_popover: popover,
             menu,
@@ -281,14 +435,32 @@ impl TabBar {
 
     /// Update tab title
     pub fn set_title(&self, id: u64, title: &str) {
-        for tab in self.tabs.borrow().iter() {
-            if tab.id == id {
-                tab.label.set_text(title);
-                break;
+        if let Some(tab) = self.tabs.borrow_mut().iter_mut().find(|t| t.id == id) {
+            tab.title = title.to_string();
+            self.show_title(tab);
+        }
+    }
+
+    /// Update the status segment texts shown in a tab's title
+    pub fn set_segments(&self, id: u64, segments: Vec<(String, String)>) {
+        if let Some(tab) = self.tabs.borrow_mut().iter_mut().find(|t| t.id == id) {
+            if tab.segments != segments {
+                tab.segments = segments;
+                self.show_title(tab);
             }
         }
     }
 
+    /// Show a tab's title through the title format
+    fn show_title(&self, tab: &TabInfo) {
+        let format = self.title_format.borrow();
+        tab.label.set_text(&status_segments::format_title(
+            &format,
+            &tab.title,
+            &tab.segments,
+        ));
+    }
+
     /// Set tab color
     pub fn set_color(&self, id: u64, color: Option<&str>) {
         for tab in self.tabs.borrow().iter() {
@@ -341,7 +513,6 @@ impl TabBar {
     }
 
     /// Mark tab as having unread content
-    #[allow(dead_code)]
     pub fn set_unread(&self, id: u64, unread: bool) {
         for tab in self.tabs.borrow().iter() {
             if tab.id == id {
@@ -355,6 +526,21 @@ impl TabBar {
         }
     }
 
+    /// List `profiles` in the new tab button's right-click menu
+    pub fn set_profiles(&self, profiles: &[ProfileConfig]) {
+        self.new_tab_menu.remove_all();
+        for (i, profile) in profiles.iter().enumerate() {
+            let action = format!("win.new-profile-tab::{}", i);
+            self.new_tab_menu.append(Some(&profile.name), Some(&action));
+        }
+        self.new_tab_button
+            .set_tooltip_text(if profiles.is_empty() {
+                None
+            } else {
+                Some("New tab (right-click for profiles)")
+            });
+    }
+
     /// Set callback for new tab button
     pub fn set_on_new_tab<F: Fn() + 'static>(&self, callback: F) {
         *self.on_new_tab.borrow_mut() = Some(Box::new(callback));
@@ -457,11 +643,52 @@ impl TabBar {
             .any(|tab| tab.id == id && tab.bell_icon.is_visible())
     }
 
-    /// Update tab bar visibility based on tab count
-    /// Hide when there's only one tab, show when there are multiple
+    /// Show the match count of a tab's watch expression, or hide it with
+    /// `None` when the tab has no watch
+    pub fn set_watch_count(&self, id: u64, count: Option<u64>) {
+        if let Some(tab) = self.tabs.borrow().iter().find(|t| t.id == id) {
+            match count {
+                Some(count) => {
+                    tab.watch_badge.set_text(&count.to_string());
+                    tab.watch_badge.set_visible(true);
+                }
+                None => tab.watch_badge.set_visible(false),
+            }
+        }
+    }
+
+    /// Update tab bar visibility based on tab count and the configured
+    /// visibility
     pub fn update_visibility(&self) {
         let tab_count = self.tabs.borrow().len();
-        self.container.set_visible(tab_count > 1);
+        let widgets = self.widgets.config.get().any();
+        let shown = self.visibility.get().shows(tab_count, widgets);
+        self.container
+            .set_visible(shown && !*self.force_hidden.borrow());
+    }
+
+    /// Apply the tab bar visibility, close button and title format settings
+    pub fn set_config(&self, config: &TabsConfig) {
+        self.visibility.set(config.show_tab_bar);
+        self.show_close_button.set(config.show_close_button);
+        *self.title_format.borrow_mut() = config.title_format.clone();
+        for tab in self.tabs.borrow().iter() {
+            tab.close_button.set_visible(config.show_close_button);
+            self.show_title(tab);
+        }
+        self.update_visibility();
+    }
+
+    /// Show the clock, timer and battery widgets enabled in `config`
+    pub fn set_widgets(&self, config: WidgetsConfig) {
+        self.widgets.set_config(config);
+        self.update_visibility();
+    }
+
+    /// Hide the tab bar even when several tabs are open
+    pub fn set_force_hidden(&self, hidden: bool) {
+        *self.force_hidden.borrow_mut() = hidden;
+        self.update_visibility();
     }
 
     /// Check if any tab has a bell indicator active
@@ -474,6 +701,16 @@ impl TabBar {
     }
 }
 
+/// Run the close callback of a tab
+fn close_tab(close_callbacks: &TabCallbackMap, tab_id: u64) {
+    // Take the callback out of the map so the borrow is released before
+    // invoking it — the callback calls remove_tab() which needs borrow_mut.
+    let callback = close_callbacks.borrow_mut().remove(&tab_id);
+    if let Some(cb) = callback {
+        cb();
+    }
+}
+
 impl Default for TabBar {
     fn default() -> Self {
         Self::new()
@@ -9,7 +9,7 @@ use std::rc::Rc;
 use gtk4::prelude::*;
 use gtk4::{
     Align, Box as GtkBox, Button, CheckButton, ColorButton, ComboBoxText, Dialog, Entry, Grid,
-    Label, Notebook, Orientation, ResponseType, ScrolledWindow, Window,
+    Label, ListBox, ListBoxRow, Notebook, Orientation, ResponseType, ScrolledWindow, Window,
 };
 
 use cterm_app::config::{
@@ -31,6 +31,8 @@ struct TemplateWidgets {
     git_remote_entry: Entry,
     color_button: ColorButton,
     theme_entry: Entry,
+    profile_combo: ComboBoxText,
+    profile_names: Vec<String>,
     unique_check: CheckButton,
     keep_open_check: CheckButton,
     // Docker tab
@@ -58,6 +60,9 @@ struct TemplateWidgets {
     ssh_remote_command_entry: Entry,
     ssh_x11_check: CheckButton,
     ssh_agent_check: CheckButton,
+    // Environment tab
+    inherit_env_check: CheckButton,
+    env_list: ListBox,
 }
 
 /// Show the tab templates dialog with an "Open" callback
@@ -279,6 +284,8 @@ fn create_widgets(notebook: &Notebook) -> TemplateWidgets {
         git_remote_entry,
         color_button,
         theme_entry,
+        profile_combo,
+        profile_names,
         unique_check,
         keep_open_check,
     ) = create_general_tab();
@@ -317,6 +324,10 @@ fn create_widgets(notebook: &Notebook) -> TemplateWidgets {
     ) = create_ssh_tab();
     notebook.append_page(&ssh_page, Some(&Label::new(Some("Remote/SSH"))));
 
+    // Environment tab
+    let (env_page, inherit_env_check, env_list) = create_environment_tab();
+    notebook.append_page(&env_page, Some(&Label::new(Some("Environment"))));
+
     // We need to get the template_combo from the dialog, but we'll set a placeholder
     // This is a bit awkward but we'll wire it up after creation
     let template_combo = ComboBoxText::new();
@@ -332,6 +343,8 @@ fn create_widgets(notebook: &Notebook) -> TemplateWidgets {
         git_remote_entry,
         color_button,
         theme_entry,
+        profile_combo,
+        profile_names,
         unique_check,
         keep_open_check,
         docker_mode_combo,
@@ -355,6 +368,8 @@ fn create_widgets(notebook: &Notebook) -> TemplateWidgets {
         ssh_remote_command_entry,
         ssh_x11_check,
         ssh_agent_check,
+        inherit_env_check,
+        env_list,
     }
 }
 
@@ -370,6 +385,8 @@ fn create_general_tab() -> (
     Entry,
     ColorButton,
     Entry,
+    ComboBoxText,
+    Vec<String>,
     CheckButton,
     CheckButton,
 ) {
@@ -405,11 +422,10 @@ fn create_general_tab() -> (
     remote_combo.set_hexpand(true);
     remote_combo.append_text("Local");
     let mut remote_names = Vec::new();
-    if let Ok(cfg) = cterm_app::config::load_config() {
-        for remote in &cfg.remotes {
-            remote_combo.append_text(&remote.name);
-            remote_names.push(remote.name.clone());
-        }
+    let cfg = cterm_app::config::load_config().unwrap_or_default();
+    for remote in &cfg.remotes {
+        remote_combo.append_text(&remote.name);
+        remote_names.push(remote.name.clone());
     }
     remote_combo.set_active(Some(0));
     grid.attach(&remote_combo, 1, row, 1, 1);
@@ -476,6 +492,23 @@ fn create_general_tab() -> (
     let theme_entry = Entry::new();
     theme_entry.set_placeholder_text(Some("(default)"));
     grid.attach(&theme_entry, 1, row, 1, 1);
+    row += 1;
+
+    // Profile
+    let profile_label = Label::new(Some("Profile:"));
+    profile_label.set_halign(Align::End);
+    grid.attach(&profile_label, 0, row, 1, 1);
+    let profile_combo = ComboBoxText::new();
+    profile_combo.append_text("None");
+    let profile_names: Vec<String> = cfg.profiles.iter().map(|p| p.name.clone()).collect();
+    for name in &profile_names {
+        profile_combo.append_text(name);
+    }
+    profile_combo.set_active(Some(0));
+    profile_combo.set_tooltip_text(Some(
+        "Shell, environment, theme, font and scrollback for what this template leaves unset",
+    ));
+    grid.attach(&profile_combo, 1, row, 1, 1);
 
     page.append(&grid);
 
@@ -504,6 +537,8 @@ fn create_general_tab() -> (
         git_remote_entry,
         color_button,
         theme_entry,
+        profile_combo,
+        profile_names,
         unique_check,
         keep_open_check,
     )
@@ -829,6 +864,118 @@ fn create_ssh_tab() -> (
     )
 }
 
+fn create_environment_tab() -> (ScrolledWindow, CheckButton, ListBox) {
+    let scroll = ScrolledWindow::new();
+    scroll.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+
+    let page = GtkBox::new(Orientation::Vertical, 8);
+    page.set_margin_top(12);
+    page.set_margin_bottom(12);
+    page.set_margin_start(12);
+    page.set_margin_end(12);
+
+    let inherit_env_check = CheckButton::with_label("Inherit cterm's environment");
+    inherit_env_check.set_tooltip_text(Some(
+        "When off, only HOME, USER, PATH and a few other essentials are passed on",
+    ));
+    page.append(&inherit_env_check);
+
+    let info_label = Label::new(Some(
+        "Variables below are added to the session, overriding inherited values:",
+    ));
+    info_label.set_halign(Align::Start);
+    info_label.add_css_class("dim-label");
+    page.append(&info_label);
+
+    let env_list = ListBox::new();
+    env_list.set_selection_mode(gtk4::SelectionMode::None);
+    env_list.add_css_class("boxed-list");
+    page.append(&env_list);
+
+    let add_button = Button::with_label("Add Variable");
+    add_button.set_halign(Align::Start);
+    let list_clone = env_list.clone();
+    add_button.connect_clicked(move |_| {
+        append_env_row(&list_clone, "", "");
+    });
+    page.append(&add_button);
+
+    scroll.set_child(Some(&page));
+
+    (scroll, inherit_env_check, env_list)
+}
+
+/// Append a NAME = value row with its own remove button to the environment list
+fn append_env_row(list: &ListBox, name: &str, value: &str) {
+    let row_box = GtkBox::new(Orientation::Horizontal, 8);
+    row_box.set_margin_top(4);
+    row_box.set_margin_bottom(4);
+    row_box.set_margin_start(4);
+    row_box.set_margin_end(4);
+
+    let name_entry = Entry::new();
+    name_entry.set_placeholder_text(Some("NAME"));
+    name_entry.set_text(name);
+    name_entry.connect_changed(|entry| {
+        let name = entry.text();
+        if name.is_empty() || cterm_core::validate_env_var(&name, "").is_ok() {
+            entry.remove_css_class("error");
+        } else {
+            entry.add_css_class("error");
+        }
+    });
+    row_box.append(&name_entry);
+
+    row_box.append(&Label::new(Some("=")));
+
+    let value_entry = Entry::new();
+    value_entry.set_placeholder_text(Some("value"));
+    value_entry.set_text(value);
+    value_entry.set_hexpand(true);
+    row_box.append(&value_entry);
+
+    let remove_button = Button::with_label("-");
+    remove_button.set_tooltip_text(Some("Remove variable"));
+    row_box.append(&remove_button);
+
+    let row = ListBoxRow::new();
+    row.set_child(Some(&row_box));
+    list.append(&row);
+
+    let list = list.clone();
+    remove_button.connect_clicked(move |_| {
+        list.remove(&row);
+    });
+}
+
+/// Read the NAME = value pairs back out of the environment list
+fn env_rows(list: &ListBox) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut index = 0;
+    while let Some(row) = list.row_at_index(index) {
+        index += 1;
+        let Some(name_entry) = row
+            .child()
+            .and_then(|b| b.first_child())
+            .and_then(|w| w.downcast::<Entry>().ok())
+        else {
+            continue;
+        };
+        let Some(value_entry) = name_entry
+            .next_sibling()
+            .and_then(|label| label.next_sibling())
+            .and_then(|w| w.downcast::<Entry>().ok())
+        else {
+            continue;
+        };
+        pairs.push((
+            name_entry.text().to_string(),
+            value_entry.text().to_string(),
+        ));
+    }
+    pairs
+}
+
 fn load_template_into_widgets(widgets: &TemplateWidgets, template: &StickyTabConfig) {
     // General
     widgets.name_entry.set_text(&template.name);
@@ -878,6 +1025,20 @@ fn load_template_into_widgets(widgets: &TemplateWidgets, template: &StickyTabCon
         .unwrap_or(0);
     widgets.remote_combo.set_active(Some(remote_idx));
 
+    // Profile
+    let profile_idx = template
+        .profile
+        .as_ref()
+        .and_then(|name| {
+            widgets
+                .profile_names
+                .iter()
+                .position(|p| p == name)
+                .map(|i| (i + 1) as u32)
+        })
+        .unwrap_or(0);
+    widgets.profile_combo.set_active(Some(profile_idx));
+
     // Docker
     let docker_mode = match &template.docker {
         None => 0,
@@ -973,6 +1134,17 @@ fn load_template_into_widgets(widgets: &TemplateWidgets, template: &StickyTabCon
         widgets.ssh_x11_check.set_active(false);
         widgets.ssh_agent_check.set_active(false);
     }
+
+    // Environment
+    widgets.inherit_env_check.set_active(template.inherit_env);
+    while let Some(row) = widgets.env_list.row_at_index(0) {
+        widgets.env_list.remove(&row);
+    }
+    let mut env: Vec<_> = template.env.iter().collect();
+    env.sort();
+    for (name, value) in env {
+        append_env_row(&widgets.env_list, name, value);
+    }
 }
 
 fn save_widgets_to_template(widgets: &TemplateWidgets, template: &mut StickyTabConfig) {
@@ -1025,6 +1197,14 @@ fn save_widgets_to_template(widgets: &TemplateWidgets, template: &mut StickyTabC
         widgets.remote_names.get(remote_idx - 1).cloned()
     };
 
+    // Profile
+    let profile_idx = widgets.profile_combo.active().unwrap_or(0) as usize;
+    template.profile = if profile_idx == 0 {
+        None
+    } else {
+        widgets.profile_names.get(profile_idx - 1).cloned()
+    };
+
     // Docker
     let docker_mode = widgets.docker_mode_combo.active().unwrap_or(0);
     if docker_mode == 0 {
@@ -1087,6 +1267,24 @@ fn save_widgets_to_template(widgets: &TemplateWidgets, template: &mut StickyTabC
         ssh.x11_forward = widgets.ssh_x11_check.is_active();
         ssh.agent_forward = widgets.ssh_agent_check.is_active();
     }
+
+    // Environment
+    template.inherit_env = widgets.inherit_env_check.is_active();
+    template.env.clear();
+    for (name, value) in env_rows(&widgets.env_list) {
+        if name.is_empty() {
+            continue;
+        }
+        if let Err(e) = cterm_core::validate_env_var(&name, &value) {
+            log::warn!(
+                "Skipping environment variable in '{}': {}",
+                template.name,
+                e
+            );
+            continue;
+        }
+        template.env.insert(name, value);
+    }
 }
 
 fn connect_field_signals(
@@ -0,0 +1,66 @@
+//! Drawing area that renders through GTK snapshots
+//!
+//! A plain `DrawingArea` hands its draw function a Cairo context, which GTK
+//! rasterizes on the CPU. This subclass overrides `snapshot` instead, so the
+//! terminal can emit render nodes that the GL/Vulkan renderer draws on the GPU.
+
+use std::cell::RefCell;
+
+use gtk4::prelude::*;
+use gtk4::subclass::prelude::*;
+use gtk4::{glib, Snapshot};
+
+mod imp {
+    use super::*;
+
+    pub(super) type SnapshotFunc = Box<dyn Fn(&super::TerminalArea, &Snapshot)>;
+
+    #[derive(Default)]
+    pub struct TerminalArea {
+        pub(super) snapshot_func: RefCell<Option<SnapshotFunc>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TerminalArea {
+        const NAME: &'static str = "CtermTerminalArea";
+        type Type = super::TerminalArea;
+        type ParentType = gtk4::DrawingArea;
+    }
+
+    impl ObjectImpl for TerminalArea {}
+
+    impl WidgetImpl for TerminalArea {
+        fn snapshot(&self, snapshot: &Snapshot) {
+            if let Some(ref func) = *self.snapshot_func.borrow() {
+                let obj: &super::TerminalArea = &self.obj();
+                func(obj, snapshot);
+            }
+        }
+    }
+
+    impl DrawingAreaImpl for TerminalArea {}
+}
+
+glib::wrapper! {
+    pub struct TerminalArea(ObjectSubclass<imp::TerminalArea>)
+        @extends gtk4::DrawingArea, gtk4::Widget,
+        @implements gtk4::Accessible, gtk4::Buildable, gtk4::ConstraintTarget;
+}
+
+impl TerminalArea {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Set the function that fills in the widget's snapshot on each frame
+    pub fn set_snapshot_func<F: Fn(&Self, &Snapshot) + 'static>(&self, func: F) {
+        *self.imp().snapshot_func.borrow_mut() = Some(Box::new(func));
+        self.queue_draw();
+    }
+}
+
+impl Default for TerminalArea {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,23 +1,53 @@
-//! Terminal rendering widget using Cairo
+//! Terminal widget, rendered with GSK render nodes
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 use std::rc::Rc;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use gtk4::prelude::*;
 use gtk4::{
-    gdk, gio, glib, pango, DrawingArea, EventControllerKey, EventControllerScroll, GestureClick,
+    gdk, gio, glib, graphene, pango, DrawingArea, EventControllerKey, EventControllerScroll,
+    GestureClick, Label, ListBox, Popover, ScrolledWindow,
 };
 use parking_lot::Mutex;
 
-use cterm_app::config::Config;
-use cterm_core::cell::CellAttrs;
-use cterm_core::color::{Color, Rgb};
+use cterm_app::auto_switch::{AutoSwitchChange, AutoSwitcher};
+use cterm_app::clipboard_history::{self, ClipSource};
+use cterm_app::config::{CommandNotifyConfig, Config, InputConfig, PresentationConfig};
+use cterm_app::file_drop::PathQuoting;
+use cterm_app::link_preview::PreviewTarget;
+use cterm_app::live_config::AppearanceChange;
+use cterm_app::paste::{PasteGuard, PasteJob};
+use cterm_app::presentation::{KeystrokeLog, KEYSTROKE_TIMEOUT};
+use cterm_app::session;
+use cterm_app::tmux::{PaneId, TmuxGateway};
+use cterm_app::triggers::{self, TriggerEffect, TriggerEngine};
+use cterm_core::cell::AttrOverrides;
+use cterm_core::detect;
+use cterm_core::hints::{HintAction, HintInput, HintMode};
 use cterm_core::mouse::{encode_mouse_event, MouseButton, MouseModifiers};
-use cterm_core::screen::{ClipboardOperation, CursorStyle, MouseMode, ScreenConfig};
+use cterm_core::screen::{
+    ClipboardOperation, ClipboardSelection, CommandFinished, MouseMode, Notification, PointerShape,
+    ScreenConfig, TmuxControl,
+};
 use cterm_core::term::{Key, Modifiers, Terminal, TerminalEvent};
+use cterm_core::watch::WatchMatch;
+use cterm_core::{
+    Capability, KeyEventKind, KeyboardFlags, PermissionPolicy, Player, TerminalImage, TransferFile,
+    TransferProtocol,
+};
+use cterm_ui::gutter::{gutter_block_at, gutter_tooltip, GUTTER_HOVER_WIDTH};
+use cterm_ui::pointer::{PointerHider, PointerMotion};
+use cterm_ui::status::{command_summary, StatusStrip};
 use cterm_ui::theme::Theme;
+use cterm_ui::utils::{dir_title, Magnifier};
+
+use crate::link_popover::LinkPopover;
+use crate::node_renderer::{NodeRenderer, RenderConfig};
+use crate::terminal_area::TerminalArea;
+use crate::transfer_panel::{run_remote_request, RemoteReply, RemoteRequest};
 
 /// Cell dimensions calculated from font metrics
 #[derive(Debug, Clone, Copy)]
@@ -30,8 +60,182 @@ pub struct CellDimensions {
 type EventCallback = Rc<RefCell<Option<Box<dyn Fn()>>>>;
 /// Callback type for title change events
 type TitleCallback = Rc<RefCell<Option<Box<dyn Fn(&str)>>>>;
+/// Callback type for desktop notifications
+type NotifyCallback = Rc<RefCell<Option<Box<dyn Fn(&str)>>>>;
+/// Callback type for reported finished commands
+type CommandFinishedCallback = Rc<RefCell<Option<Box<dyn Fn(&CommandFinished)>>>>;
 /// Callback type for file transfer events
 type FileTransferCallback = Rc<RefCell<Option<Box<dyn Fn(cterm_core::FileTransferOperation)>>>>;
+/// Callback type for notifications a program asked for
+type NotificationCallback = Rc<RefCell<Option<Box<dyn Fn(&Notification)>>>>;
+/// Callback type for requests the permission policy asks about
+type PermissionCallback = Rc<RefCell<Option<Box<dyn Fn(Capability)>>>>;
+/// Callback type for tmux control mode output
+type TmuxControlCallback = Rc<RefCell<Option<Box<dyn Fn(Vec<TmuxControl>)>>>>;
+/// Callback type for new matches of the watch expression
+type WatchCallback = Rc<RefCell<Option<Box<dyn Fn(&WatchMatch)>>>>;
+/// Callback type for trigger effects the window performs
+type TriggerCallback = Rc<RefCell<Option<Box<dyn Fn(&TriggerEffect)>>>>;
+/// Callback type for `[[auto_switch]]` changes the window applies
+type AutoSwitchCallback = Rc<RefCell<Option<Box<dyn Fn(&AutoSwitchChange)>>>>;
+
+/// Presentation mode state shared with the draw function
+#[derive(Default)]
+struct PresentationState {
+    active: bool,
+    minimum_contrast: f64,
+    /// Recent keystrokes, present only when the overlay is enabled
+    keystrokes: Option<KeystrokeLog>,
+}
+
+/// Selection autoscroll while the pointer is dragged past the top or bottom
+#[derive(Default)]
+struct AutoScroll {
+    /// -1 scrolls into the scrollback, 1 towards the bottom
+    direction: i32,
+    /// Pointer column the selection follows when scrolling up
+    col: usize,
+    timer: Option<glib::SourceId>,
+}
+
+impl AutoScroll {
+    fn stop(&mut self) {
+        self.direction = 0;
+        if let Some(timer) = self.timer.take() {
+            timer.remove();
+        }
+    }
+}
+
+/// Paste being written a chunk at a time
+#[derive(Default)]
+struct PasteState {
+    job: Option<PasteJob>,
+    timer: Option<glib::SourceId>,
+}
+
+/// Writes pastes to the terminal a chunk at a time and shows their progress;
+/// clones share the paste in progress
+#[derive(Clone)]
+struct Paster {
+    terminal: Arc<Mutex<Terminal>>,
+    status: Rc<RefCell<StatusStrip>>,
+    drawing_area: TerminalArea,
+    state: Rc<RefCell<PasteState>>,
+    chunk_bytes: usize,
+    guard: PasteGuard,
+}
+
+impl Paster {
+    /// Paste `text`, in bracketed paste markers if `bracketable` and the
+    /// application enabled bracketed paste; replaces any paste in progress
+    ///
+    /// Pasting bracketable text into an application without bracketed paste
+    /// asks first if the guard says so.
+    fn paste(&self, text: &str, bracketable: bool) {
+        let bracketed = bracketable && self.terminal.lock().screen().modes.bracketed_paste;
+        let warning = if bracketable && !bracketed {
+            self.guard.check(text)
+        } else {
+            None
+        };
+        let window = self
+            .drawing_area
+            .root()
+            .and_then(|root| root.downcast::<gtk4::Window>().ok());
+        if let (Some(warning), Some(window)) = (warning, window) {
+            let paster = self.clone();
+            let owned = text.to_string();
+            crate::dialogs::show_paste_confirm_dialog(&window, text, &warning, move || {
+                paster.cancel();
+                paster.start(PasteJob::new(&owned, false));
+            });
+            return;
+        }
+        self.cancel();
+        self.start(PasteJob::new(text, bracketed));
+    }
+
+    /// Stream an upload encoded by [`cterm_core::iterm2::encode_upload`];
+    /// replaces any paste in progress
+    fn upload(&self, data: Vec<u8>) {
+        self.cancel();
+        self.start(PasteJob::upload(data));
+    }
+
+    fn start(&self, mut job: PasteJob) {
+        if !job.is_chunked(self.chunk_bytes) {
+            if let Some(data) = job.next_chunk(0) {
+                self.write(data);
+            }
+            return;
+        }
+
+        self.state.borrow_mut().job = Some(job);
+        self.write_chunk();
+        let paster = self.clone();
+        let timer = glib::timeout_add_local(InputConfig::PASTE_CHUNK_INTERVAL, move || {
+            if paster.write_chunk() {
+                glib::ControlFlow::Continue
+            } else {
+                paster.state.borrow_mut().timer = None;
+                glib::ControlFlow::Break
+            }
+        });
+        self.state.borrow_mut().timer = Some(timer);
+    }
+
+    /// Write the next chunk; returns false once the paste is done
+    fn write_chunk(&self) -> bool {
+        let mut state = self.state.borrow_mut();
+        let Some(job) = state.job.as_mut() else {
+            return false;
+        };
+        let chunk = job.next_chunk(self.chunk_bytes).map(<[u8]>::to_vec);
+        let progress = (!job.is_done()).then(|| (job.written(), job.len()));
+        if progress.is_none() {
+            state.job = None;
+        }
+        drop(state);
+
+        if let Some(chunk) = chunk {
+            self.write(&chunk);
+        }
+        self.set_progress(progress);
+        progress.is_some()
+    }
+
+    /// Stop the paste in progress; returns false if there was none
+    fn cancel(&self) -> bool {
+        let mut state = self.state.borrow_mut();
+        if let Some(timer) = state.timer.take() {
+            timer.remove();
+        }
+        let Some(mut job) = state.job.take() else {
+            return false;
+        };
+        drop(state);
+        self.write(job.cancel());
+        self.set_progress(None);
+        true
+    }
+
+    fn write(&self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        if let Err(e) = self.terminal.lock().write(data) {
+            log::error!("Failed to paste: {}", e);
+        }
+        self.drawing_area.queue_draw();
+    }
+
+    fn set_progress(&self, progress: Option<(usize, usize)>) {
+        if self.status.borrow_mut().set_paste_progress(progress) {
+            self.drawing_area.queue_draw();
+        }
+    }
+}
 
 /// Preedit (input method composition) state
 #[derive(Default, Clone)]
@@ -41,31 +245,87 @@ struct PreeditState {
     active: bool,
 }
 
+/// `[[auto_switch]]` state of a [`TerminalWidget`]
+struct AutoSwitch {
+    switcher: AutoSwitcher,
+    /// Theme to restore once no rule matches
+    saved_theme: Option<Theme>,
+}
+
+/// Terminal of the pane scrolling together with a [`TerminalWidget`]
+struct ScrollPeer {
+    terminal: Arc<Mutex<Terminal>>,
+    drawing_area: glib::WeakRef<TerminalArea>,
+}
+
 /// Terminal widget wrapping GTK drawing area
+///
+/// Clones share the widget and its terminal.
+#[derive(Clone)]
 pub struct TerminalWidget {
-    drawing_area: DrawingArea,
+    drawing_area: TerminalArea,
     terminal: Arc<Mutex<Terminal>>,
-    theme: Theme,
-    font_family: String,
+    theme: Rc<RefCell<Theme>>,
+    font_family: Rc<RefCell<String>>,
+    /// Replacements for blink, concealed and dim text
+    attr_overrides: AttrOverrides,
+    /// Draw the exit status gutter
+    command_gutter: bool,
+    /// Opacity of the default background
+    opacity: Rc<Cell<f64>>,
+    /// Mouse wheel and pointer settings
+    input: InputConfig,
+    /// Hides the pointer while typing
+    pointer_hider: Rc<RefCell<PointerHider>>,
+    /// Pane scrolling together with this one
+    scroll_peer: Rc<RefCell<Option<ScrollPeer>>>,
     font_size: Rc<RefCell<f64>>,
-    default_font_size: f64,
+    default_font_size: Rc<Cell<f64>>,
     cell_dims: Rc<RefCell<CellDimensions>>,
+    /// Surface magnification (scales the view, keeps the grid size)
+    magnifier: Rc<RefCell<Magnifier>>,
     /// Optional background color override (from template)
     background_override: Rc<RefCell<Option<cterm_core::color::Rgb>>>,
     /// Input method preedit (composition) state
     preedit: Rc<RefCell<PreeditState>>,
+    presentation: Rc<RefCell<PresentationState>>,
+    /// Labelled links, paths and hashes while hint mode is active
+    hints: Rc<RefCell<Option<HintMode>>>,
+    /// Transient states shown along the bottom edge
+    status: Rc<RefCell<StatusStrip>>,
+    /// Paste in progress
+    paste: Rc<RefCell<PasteState>>,
+    /// When to confirm a paste
+    paste_guard: PasteGuard,
+    /// When to report finished commands
+    command_notify: Rc<RefCell<CommandNotifyConfig>>,
     on_exit: EventCallback,
     on_bell: EventCallback,
+    on_output: EventCallback,
     on_title_change: TitleCallback,
+    on_command_notify: NotifyCallback,
+    on_command_finished: CommandFinishedCallback,
     on_file_transfer: FileTransferCallback,
+    on_notification: NotificationCallback,
+    on_permission: PermissionCallback,
+    on_tmux_control: TmuxControlCallback,
+    on_watch_match: WatchCallback,
+    /// `[[triggers]]` watching this terminal's output
+    triggers: Rc<RefCell<TriggerEngine>>,
+    on_trigger: TriggerCallback,
+    /// Host rules restyling this terminal
+    auto_switch: Rc<RefCell<AutoSwitch>>,
+    on_auto_switch: AutoSwitchCallback,
     /// Command channel for daemon I/O — None for local PTY sessions
     daemon_cmd_tx: Option<tokio::sync::mpsc::UnboundedSender<DaemonCommand>>,
+    /// Recording played into the terminal — None for live sessions
+    playback: Option<Rc<RefCell<Player>>>,
 }
 
 impl TerminalWidget {
     /// Get the widget for adding to containers
     pub fn widget(&self) -> &DrawingArea {
-        &self.drawing_area
+        self.drawing_area.upcast_ref()
     }
 
     /// Get the current cell dimensions
@@ -119,6 +379,12 @@ impl TerminalWidget {
         }
     }
 
+    /// Handle for the transfer panel to run SFTP operations on this tab's
+    /// session. `None` for local PTY tabs.
+    pub fn remote_requester(&self) -> Option<RemoteRequester> {
+        self.daemon_cmd_tx.clone().map(|tx| RemoteRequester { tx })
+    }
+
     /// Set callback for when the terminal process exits
     pub fn set_on_exit<F: Fn() + 'static>(&self, callback: F) {
         *self.on_exit.borrow_mut() = Some(Box::new(callback));
@@ -129,11 +395,32 @@ impl TerminalWidget {
         *self.on_bell.borrow_mut() = Some(Box::new(callback));
     }
 
+    /// Set callback for new output from the terminal's process
+    pub fn set_on_output<F: Fn() + 'static>(&self, callback: F) {
+        *self.on_output.borrow_mut() = Some(Box::new(callback));
+    }
+
     /// Set callback for when the terminal title changes
     pub fn set_on_title_change<F: Fn(&str) + 'static>(&self, callback: F) {
         *self.on_title_change.borrow_mut() = Some(Box::new(callback));
     }
 
+    /// Set callback offering a desktop notification for a finished long
+    /// command; it should only notify when the window isn't focused
+    pub fn set_on_command_notify<F: Fn(&str) + 'static>(&self, callback: F) {
+        *self.on_command_notify.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Set callback for each finished command that is reported
+    pub fn set_on_command_finished<F: Fn(&CommandFinished) + 'static>(&self, callback: F) {
+        *self.on_command_finished.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Override when finished commands are reported (from a template)
+    pub fn set_command_notify(&self, notify: CommandNotifyConfig) {
+        *self.command_notify.borrow_mut() = notify;
+    }
+
     /// Set callback for when a file is received
     pub fn set_on_file_transfer<F: Fn(cterm_core::FileTransferOperation) + 'static>(
         &self,
@@ -142,21 +429,129 @@ impl TerminalWidget {
         *self.on_file_transfer.borrow_mut() = Some(Box::new(callback));
     }
 
+    /// Set callback for desktop notifications the program asks for
+    /// (OSC 9, OSC 777)
+    pub fn set_on_notification<F: Fn(&Notification) + 'static>(&self, callback: F) {
+        *self.on_notification.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Set callback for requests the permission policy asks about; what
+    /// was asked waits for [`Self::answer_permission`]
+    ///
+    /// Without a callback, such requests wait forever.
+    pub fn set_on_permission<F: Fn(Capability) + 'static>(&self, callback: F) {
+        *self.on_permission.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Override the permission policy (from a template)
+    pub fn set_permissions(&self, permissions: PermissionPolicy) {
+        self.terminal.lock().set_permissions(permissions);
+    }
+
+    /// Answer a permission request, carrying out what it releases; with
+    /// `remember` the answer holds for later requests
+    pub fn answer_permission(&self, capability: Capability, allow: bool, remember: bool) {
+        let events = self
+            .terminal
+            .lock()
+            .answer_permission(capability, allow, remember);
+
+        // From the main loop, as the callbacks may need what the caller holds
+        let terminal = Arc::clone(&self.terminal);
+        let drawing_area = self.drawing_area.clone();
+        let status = Rc::clone(&self.status);
+        let on_title_change = Rc::clone(&self.on_title_change);
+        let on_notification = Rc::clone(&self.on_notification);
+        glib::idle_add_local_once(move || {
+            for event in events {
+                match event {
+                    TerminalEvent::ClipboardRequest(op) => handle_clipboard(&terminal, op),
+                    TerminalEvent::TitleChanged(ref title) => {
+                        if let Some(ref callback) = *on_title_change.borrow() {
+                            callback(title);
+                        }
+                    }
+                    TerminalEvent::Notification(ref notification) => {
+                        if let Some(ref callback) = *on_notification.borrow() {
+                            callback(notification);
+                        }
+                    }
+                    TerminalEvent::TransferStarted(protocol, direction) => {
+                        status
+                            .borrow_mut()
+                            .set_transfer(Some((protocol, direction)));
+                    }
+                    TerminalEvent::TransferSendRequested => {
+                        let protocol = terminal.lock().file_transfer().map(|(p, _)| p);
+                        if let Some(protocol) = protocol {
+                            request_transfer_files(&drawing_area, &terminal, &status, protocol);
+                        }
+                    }
+                    TerminalEvent::TransferFinished => status.borrow_mut().set_transfer(None),
+                    _ => {}
+                }
+            }
+            drawing_area.queue_draw();
+        });
+    }
+
+    /// Set callback for output of tmux control mode (`tmux -CC`) started in
+    /// this tab
+    pub fn set_on_tmux_control<F: Fn(Vec<TmuxControl>) + 'static>(&self, callback: F) {
+        *self.on_tmux_control.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Set callback for output matching the watch expression
+    pub fn set_on_watch_match<F: Fn(&WatchMatch) + 'static>(&self, callback: F) {
+        *self.on_watch_match.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Set callback for trigger effects that concern the window: desktop
+    /// notifications and the tab color
+    pub fn set_on_trigger<F: Fn(&TriggerEffect) + 'static>(&self, callback: F) {
+        *self.on_trigger.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Set callback for `[[auto_switch]]` changes; the theme is switched
+    /// here, the tab color is up to the window
+    pub fn set_on_auto_switch<F: Fn(&AutoSwitchChange) + 'static>(&self, callback: F) {
+        *self.on_auto_switch.borrow_mut() = Some(Box::new(callback));
+    }
+
     /// Get the terminal for file transfer operations
     pub fn terminal(&self) -> &Arc<Mutex<Terminal>> {
         &self.terminal
     }
 
-    /// Get the current working directory of the foreground process (if any)
+    /// Get the current working directory of the foreground process, or else
+    /// the one the shell reported with OSC 7 (if any)
     #[cfg(unix)]
     pub fn foreground_cwd(&self) -> Option<String> {
-        self.terminal
-            .lock()
-            .foreground_cwd()
+        let term = self.terminal.lock();
+        term.foreground_cwd()
             .map(|p| p.to_string_lossy().into_owned())
+            .or_else(|| term.screen().local_current_dir().map(String::from))
     }
 
-    /// Write a string to the terminal (for paste operations)
+    /// Paste text, a chunk at a time if it is large, in bracketed paste
+    /// markers if the application asked for them
+    pub fn paste(&self, text: &str) {
+        self.paster().paste(text, true);
+    }
+
+    /// Writes pastes to this terminal
+    fn paster(&self) -> Paster {
+        Paster {
+            terminal: Arc::clone(&self.terminal),
+            status: Rc::clone(&self.status),
+            drawing_area: self.drawing_area.clone(),
+            state: Rc::clone(&self.paste),
+            chunk_bytes: self.input.paste_chunk_bytes,
+            guard: self.paste_guard,
+        }
+    }
+
+    /// Write a string to the terminal
     pub fn write_str(&self, s: &str) {
         let mut term = self.terminal.lock();
         if let Err(e) = term.write_str(s) {
@@ -205,23 +600,60 @@ impl TerminalWidget {
 
     /// Reset font size to default
     pub fn zoom_reset(&self) {
-        *self.font_size.borrow_mut() = self.default_font_size;
-        self.update_cell_dimensions(self.default_font_size);
+        let font_size = self.default_font_size.get();
+        *self.font_size.borrow_mut() = font_size;
+        self.update_cell_dimensions(font_size);
         self.trigger_resize();
     }
 
     /// Update cell dimensions after font size change
     fn update_cell_dimensions(&self, font_size: f64) {
-        let new_dims = calculate_cell_dimensions(&self.font_family, font_size);
+        let new_dims = calculate_cell_dimensions(&self.font_family.borrow(), font_size);
         *self.cell_dims.borrow_mut() = new_dims;
     }
 
+    /// Restyle after the configured appearance changed, e.g. while
+    /// preferences are previewed
+    pub fn apply_appearance(&self, config: &Config, theme: &Theme, change: AppearanceChange) {
+        let appearance = &config.appearance;
+        if change.theme {
+            *self.theme.borrow_mut() = theme.clone();
+            self.terminal
+                .lock()
+                .set_color_scheme(theme.colors.color_scheme());
+        }
+        if change.theme || change.opacity {
+            self.opacity.set(appearance.background_opacity(theme));
+        }
+        if change.cursor {
+            self.terminal
+                .lock()
+                .screen_mut()
+                .set_cursor_shape(appearance.cursor_style.into(), appearance.cursor_blink);
+        }
+        if change.font {
+            *self.font_family.borrow_mut() = appearance.font.family.clone();
+            let old = self.default_font_size.replace(appearance.font.size);
+            // Keep zoom steps; presentation mode keeps its size until it ends
+            let mut font_size = self.font_size.borrow_mut();
+            if !self.presentation.borrow().active {
+                *font_size = (*font_size + appearance.font.size - old).clamp(6.0, 72.0);
+            }
+            let new_size = *font_size;
+            drop(font_size);
+            self.update_cell_dimensions(new_size);
+            self.trigger_resize();
+        }
+        self.drawing_area.queue_draw();
+    }
+
     /// Reset the terminal (soft reset - keeps scrollback)
     pub fn reset(&self) {
         let mut term = self.terminal.lock();
         let screen = term.screen_mut();
         // Soft reset: reset modes and cursor but keep scrollback
         screen.cursor = cterm_core::screen::Cursor::default();
+        screen.restore_cursor_shape();
         screen.style = cterm_core::cell::CellStyle::default();
         screen.modes = cterm_core::screen::TerminalModes {
             auto_wrap: true,
@@ -234,6 +666,130 @@ impl TerminalWidget {
         self.drawing_area.queue_draw();
     }
 
+    /// Magnify the rendered view one step without resizing the grid
+    pub fn magnify_in(&self) {
+        if self.magnifier.borrow_mut().magnify_in() {
+            self.drawing_area.queue_draw();
+        }
+    }
+
+    /// Reduce magnification one step
+    pub fn magnify_out(&self) {
+        if self.magnifier.borrow_mut().magnify_out() {
+            self.drawing_area.queue_draw();
+        }
+    }
+
+    /// Return to unmagnified rendering
+    pub fn magnify_reset(&self) {
+        if self.magnifier.borrow_mut().reset() {
+            self.drawing_area.queue_draw();
+        }
+    }
+
+    /// Enter presentation mode with the given settings, or leave it with `None`
+    pub fn set_presentation(&self, presentation: Option<&PresentationConfig>) {
+        let font_size = match presentation {
+            Some(cfg) => {
+                *self.presentation.borrow_mut() = PresentationState {
+                    active: true,
+                    minimum_contrast: cfg.minimum_contrast,
+                    keystrokes: cfg.show_keystrokes.then(KeystrokeLog::new),
+                };
+                cfg.font_size(self.default_font_size.get())
+            }
+            None => {
+                *self.presentation.borrow_mut() = PresentationState::default();
+                self.default_font_size.get()
+            }
+        };
+        *self.font_size.borrow_mut() = font_size;
+        self.update_cell_dimensions(font_size);
+        self.trigger_resize();
+    }
+
+    /// Record a key press for the presentation keystroke overlay
+    pub fn record_keystroke(
+        &self,
+        key: cterm_ui::events::KeyCode,
+        modifiers: cterm_ui::events::Modifiers,
+    ) {
+        let mut state = self.presentation.borrow_mut();
+        let Some(log) = state.keystrokes.as_mut() else {
+            return;
+        };
+        log.record(key, modifiers, Instant::now());
+        drop(state);
+        self.drawing_area.queue_draw();
+
+        // Redraw once the entry has expired so the overlay clears itself
+        let drawing_area = self.drawing_area.clone();
+        glib::timeout_add_local_once(KEYSTROKE_TIMEOUT, move || drawing_area.queue_draw());
+    }
+
+    /// Label the links, paths and hashes on screen for keyboard selection
+    ///
+    /// Rings the bell instead when there is nothing to label.
+    pub fn start_hint_mode(&self) {
+        let mode = {
+            let terminal = self.terminal.lock();
+            HintMode::new(terminal.screen(), terminal.screen().hint_alphabet())
+        };
+        if mode.is_none() {
+            self.drawing_area.error_bell();
+        }
+        self.update_status(|status| status.set_hints(mode.as_ref().map(HintMode::typed)));
+        *self.hints.borrow_mut() = mode;
+        self.drawing_area.queue_draw();
+    }
+
+    /// Toggle ASCII-art-safe rendering (no ligatures, glyphs clipped to cells)
+    pub fn toggle_ascii_art_safe(&self) {
+        let mut term = self.terminal.lock();
+        let screen = term.screen_mut();
+        screen.modes.ascii_art_safe = !screen.modes.ascii_art_safe;
+        screen.dirty = true;
+        drop(term);
+        self.drawing_area.queue_draw();
+    }
+
+    /// Scroll `peer` together with this terminal, or stop with `None`
+    pub fn set_scroll_peer(&self, peer: Option<&TerminalWidget>) {
+        *self.scroll_peer.borrow_mut() = peer.map(|peer| ScrollPeer {
+            terminal: Arc::clone(&peer.terminal),
+            drawing_area: peer.drawing_area.downgrade(),
+        });
+    }
+
+    /// Lock or unlock input to this terminal; scrolling and copying keep working
+    pub fn toggle_read_only(&self) {
+        let mut term = self.terminal.lock();
+        let locked = !term.is_input_locked();
+        term.set_input_locked(locked);
+        drop(term);
+        self.update_status(|status| status.set_read_only(locked));
+    }
+
+    /// Start recording the output to an asciicast file at `path`
+    pub fn start_recording(&self, path: &std::path::Path) -> std::io::Result<()> {
+        self.terminal.lock().start_recording(path)?;
+        self.update_status(|status| status.set_recording(true));
+        Ok(())
+    }
+
+    /// Finish the recording in progress; returns false if there was none
+    pub fn stop_recording(&self) -> bool {
+        let stopped = match self.terminal.lock().stop_recording() {
+            Ok(stopped) => stopped,
+            Err(e) => {
+                log::error!("Failed to finish recording: {}", e);
+                true
+            }
+        };
+        self.update_status(|status| status.set_recording(false));
+        stopped
+    }
+
     /// Clear scrollback buffer and fully reset the terminal
     pub fn clear_scrollback_and_reset(&self) {
         let mut term = self.terminal.lock();
@@ -242,6 +798,41 @@ impl TerminalWidget {
         self.drawing_area.queue_draw();
     }
 
+    /// Images on the screen and in scrollback, oldest first, with their
+    /// kitty image and placement ids if they have any
+    pub fn images(&self) -> Vec<(TerminalImage, Option<(u32, u32)>)> {
+        let term = self.terminal.lock();
+        let screen = term.screen();
+        screen
+            .images()
+            .into_iter()
+            .map(|img| {
+                let kitty = screen
+                    .kitty_graphics()
+                    .placement_for(img.id)
+                    .map(|p| (p.image_id, p.placement_id));
+                (img.clone(), kitty)
+            })
+            .collect()
+    }
+
+    /// Remove one image from this view. Image ids are assigned by each
+    /// parser, so the daemon's copy of the screen is left alone.
+    pub fn remove_image(&self, id: u64) {
+        if self.terminal.lock().screen_mut().remove_image(id) {
+            self.drawing_area.queue_draw();
+        }
+    }
+
+    /// Remove all images and kitty placements, here and on the daemon
+    pub fn clear_images(&self) {
+        self.terminal.lock().screen_mut().clear_images();
+        if let Some(ref tx) = self.daemon_cmd_tx {
+            let _ = tx.send(DaemonCommand::ClearImages);
+        }
+        self.drawing_area.queue_draw();
+    }
+
     /// Send a signal to the terminal process
     pub fn send_signal(&self, signal: i32) {
         let term = self.terminal.lock();
@@ -266,23 +857,44 @@ impl TerminalWidget {
     ///
     /// Returns the number of matches found. If matches are found, scrolls to the first match.
     pub fn find(&self, pattern: &str, case_sensitive: bool, regex: bool) -> usize {
-        let term = self.terminal.lock();
+        let mut term = self.terminal.lock();
         let results = term.find(pattern, case_sensitive, regex);
         let count = results.len();
+        let current = usize::from(count > 0);
+        self.update_status(|status| status.set_search(pattern, current, count));
 
+        // Matches are marked on the scrollbar until the search ends
+        term.screen_mut()
+            .set_search_lines(results.iter().map(|result| result.line));
         if let Some(first) = results.first() {
-            // Need to release the lock before we can take mutable lock
-            let line_idx = first.line;
-            drop(term);
-
-            let mut term = self.terminal.lock();
-            term.scroll_to_line(line_idx);
-            self.drawing_area.queue_draw();
+            term.scroll_to_line(first.line);
         }
+        self.drawing_area.queue_draw();
 
         count
     }
 
+    /// Distinct regex matches in this terminal's buffer and scrollback
+    ///
+    /// The returned closure holds the terminal, so a panel can re-run it
+    /// as the pattern changes.
+    pub fn match_extractor(&self) -> impl Fn(&str) -> Result<Vec<String>, String> + 'static {
+        let terminal = Arc::clone(&self.terminal);
+        move |pattern| {
+            terminal
+                .lock()
+                .extract_matches(pattern)
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    /// Update the status strip, redrawing if anything changed
+    pub fn update_status(&self, update: impl FnOnce(&mut StatusStrip) -> bool) {
+        if update(&mut self.status.borrow_mut()) {
+            self.drawing_area.queue_draw();
+        }
+    }
+
     /// Search and return all matches (for iteration/highlighting)
     #[allow(dead_code)]
     pub fn find_all(
@@ -369,13 +981,18 @@ impl TerminalWidget {
                 let clipboard = display.clipboard();
                 clipboard.set_text(&text);
             }
+            clipboard_history::record(&text, ClipSource::Copy);
+            // Programs on the daemon's host can read it while detached
+            if let Some(ref tx) = self.daemon_cmd_tx {
+                let _ = tx.send(DaemonCommand::SetClipboard(text.into_bytes()));
+            }
         }
     }
 
     /// Copy the current selection to clipboard as HTML
     pub fn copy_selection_html(&self) {
         let term = self.terminal.lock();
-        let html = term.screen().get_selected_html(&self.theme.colors);
+        let html = term.screen().get_selected_html(&self.theme.borrow().colors);
         let text = term.screen().get_selected_text();
         drop(term);
 
@@ -407,6 +1024,87 @@ impl TerminalWidget {
         self.drawing_area.queue_draw();
     }
 
+    /// Scroll the previous (or with `next`, the next) shell prompt to the
+    /// top of the view, ringing the bell when there is none
+    pub fn scroll_to_prompt(&self, next: bool) {
+        if !self.terminal.lock().scroll_to_prompt(next) {
+            self.drawing_area.error_bell();
+        }
+        self.drawing_area.queue_draw();
+    }
+
+    /// Select the output of the last command, ringing the bell when there
+    /// is none
+    pub fn select_command_output(&self) {
+        if !self.terminal.lock().select_last_command_output() {
+            self.drawing_area.error_bell();
+        }
+        self.drawing_area.queue_draw();
+    }
+
+    /// Pick an earlier copy from the clipboard history in a popover at the
+    /// cursor and paste it, ringing the bell when the history is empty
+    pub fn show_paste_history(&self) {
+        let items = clipboard_history::items();
+        if items.is_empty() {
+            self.drawing_area.error_bell();
+            return;
+        }
+
+        let list = ListBox::new();
+        for item in &items {
+            let label = Label::new(Some(&item.label(clipboard_history::LABEL_CHARS)));
+            label.set_xalign(0.0);
+            label.set_margin_start(4);
+            label.set_margin_end(4);
+            list.append(&label);
+        }
+        let scroll = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk4::PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(400)
+            .child(&list)
+            .build();
+
+        let popover = Popover::new();
+        popover.set_child(Some(&scroll));
+        popover.set_parent(&self.drawing_area);
+        let (row, col) = {
+            let term = self.terminal.lock();
+            let cursor = &term.screen().cursor;
+            (cursor.row, cursor.col)
+        };
+        let dims = *self.cell_dims.borrow();
+        popover.set_pointing_to(Some(&gdk::Rectangle::new(
+            (col as f64 * dims.width) as i32,
+            (row as f64 * dims.height) as i32,
+            dims.width.ceil() as i32,
+            dims.height.ceil() as i32,
+        )));
+
+        let paster = self.paster();
+        let popover_weak = popover.downgrade();
+        list.connect_row_activated(move |_, row| {
+            if let Some(popover) = popover_weak.upgrade() {
+                popover.popdown();
+            }
+            if let Some(item) = items.get(row.index() as usize) {
+                paster.paste(&item.text, true);
+            }
+        });
+        let drawing_area = self.drawing_area.clone();
+        popover.connect_closed(move |popover| {
+            let popover = popover.clone();
+            glib::idle_add_local_once(move || popover.unparent());
+            drawing_area.grab_focus();
+        });
+        popover.popup();
+        if let Some(first) = list.row_at_index(0) {
+            list.select_row(Some(&first));
+            first.grab_focus();
+        }
+    }
+
     /// Copy the current selection to primary selection (Unix only)
     #[cfg(unix)]
     #[allow(dead_code)]
@@ -427,20 +1125,11 @@ impl TerminalWidget {
             return;
         };
         let primary = display.primary_clipboard();
-        let terminal = Arc::clone(&self.terminal);
-        let drawing_area = self.drawing_area.clone();
+        let paster = self.paster();
 
         primary.read_text_async(None::<&gio::Cancellable>, move |result| {
             if let Ok(Some(text)) = result {
-                let mut term = terminal.lock();
-                // Use bracketed paste if enabled
-                let paste_text = if term.screen().modes.bracketed_paste {
-                    format!("\x1b[200~{}\x1b[201~", text)
-                } else {
-                    text.to_string()
-                };
-                let _ = term.write_str(&paste_text);
-                drawing_area.queue_draw();
+                paster.paste(&text, true);
             }
         });
     }
@@ -467,33 +1156,107 @@ impl TerminalWidget {
     /// Set up the draw function
     fn setup_drawing(&self) {
         let terminal = Arc::clone(&self.terminal);
-        let theme = self.theme.clone();
-        let font_family = self.font_family.clone();
+        let theme = Rc::clone(&self.theme);
+        let font_family = Rc::clone(&self.font_family);
+        let attr_overrides = self.attr_overrides;
+        let command_gutter = self.command_gutter;
+        let opacity = Rc::clone(&self.opacity);
         let font_size = Rc::clone(&self.font_size);
         let cell_dims = Rc::clone(&self.cell_dims);
         let background_override = Rc::clone(&self.background_override);
         let preedit = Rc::clone(&self.preedit);
-
-        self.drawing_area
-            .set_draw_func(move |_area, cr, _width, _height| {
-                let font_size = *font_size.borrow();
-                let dims = *cell_dims.borrow();
-                let bg_override = *background_override.borrow();
-                let preedit_state = preedit.borrow().clone();
-                let render_config = RenderConfig {
-                    font_family: &font_family,
-                    font_size,
-                    cell_dims: dims,
-                    background_override: bg_override,
+        let magnifier = Rc::clone(&self.magnifier);
+        let presentation = Rc::clone(&self.presentation);
+        let status = Rc::clone(&self.status);
+        let hints = Rc::clone(&self.hints);
+
+        let renderer = RefCell::new(NodeRenderer::new());
+
+        self.drawing_area.set_snapshot_func(move |area, snapshot| {
+            let (width, height) = (area.width() as f32, area.height() as f32);
+            let font_size = *font_size.borrow();
+            let dims = *cell_dims.borrow();
+
+            snapshot.push_clip(&graphene::Rect::new(0.0, 0.0, width, height));
+            snapshot.save();
+
+            // Magnify: scale the whole surface and pan to keep the cursor in view
+            let mut magnifier = magnifier.borrow_mut();
+            if magnifier.is_active() {
+                let (row, col) = {
+                    let term = terminal.lock();
+                    let cursor = &term.screen().cursor;
+                    (cursor.row, cursor.col)
                 };
-                draw_terminal(cr, &terminal, &theme, &render_config, &preedit_state);
+                magnifier.follow(
+                    (
+                        col as f64 * dims.width,
+                        row as f64 * dims.height,
+                        dims.width,
+                        dims.height,
+                    ),
+                    (width as f64, height as f64),
+                );
+                let (pan_x, pan_y) = magnifier.pan();
+                let factor = magnifier.factor() as f32;
+                snapshot.scale(factor, factor);
+                snapshot.translate(&graphene::Point::new(-pan_x as f32, -pan_y as f32));
+            }
+            drop(magnifier);
+
+            let bg_override = *background_override.borrow();
+            let preedit_state = preedit.borrow().clone();
+            let mut presentation = presentation.borrow_mut();
+            let keystrokes = presentation.keystrokes.as_mut().and_then(|log| {
+                log.expire(Instant::now());
+                log.text()
             });
+            let hints = hints.borrow();
+            let font_family = font_family.borrow();
+            let render_config = RenderConfig {
+                font_family: &font_family,
+                font_size,
+                cell_dims: dims,
+                background_override: bg_override,
+                opacity: opacity.get(),
+                minimum_contrast: if presentation.active {
+                    presentation.minimum_contrast
+                } else {
+                    1.0
+                },
+                attr_overrides,
+                command_gutter,
+                show_scrollbar: !presentation.active,
+                keystrokes,
+                status: status.borrow().segments(),
+                preedit: (preedit_state.active && !preedit_state.text.is_empty())
+                    .then_some(preedit_state.text),
+                hints: hints.as_ref(),
+            };
+            drop(presentation);
+
+            let term = terminal.lock();
+            renderer.borrow_mut().snapshot(
+                snapshot,
+                &area.pango_context(),
+                &term,
+                &theme.borrow(),
+                &render_config,
+                (width, height),
+            );
+            drop(term);
+
+            snapshot.restore();
+            snapshot.pop();
+        });
     }
 
     /// Set up input handling
     fn setup_input(&self) {
         let terminal = Arc::clone(&self.terminal);
         let cell_dims = Rc::clone(&self.cell_dims);
+        let magnifier = Rc::clone(&self.magnifier);
+        let input = self.input;
 
         // Keyboard input — we manage the IM context explicitly so that
         // Japanese/CJK composition works reliably with IBus/Fcitx.
@@ -520,6 +1283,7 @@ impl TerminalWidget {
 
         // IM preedit: display composition text while the user is typing
         let preedit_changed = Rc::clone(&self.preedit);
+        let status_preedit = Rc::clone(&self.status);
         let drawing_area_preedit = self.drawing_area.clone();
         im_context.connect_preedit_changed(move |im| {
             let (text, _attrs, cursor_pos) = im.preedit_string();
@@ -527,16 +1291,19 @@ impl TerminalWidget {
             state.text = text.to_string();
             state.cursor_pos = cursor_pos;
             state.active = !state.text.is_empty();
+            status_preedit.borrow_mut().set_composing(Some(&state.text));
             drawing_area_preedit.queue_draw();
         });
 
         let preedit_end = Rc::clone(&self.preedit);
+        let status_preedit_end = Rc::clone(&self.status);
         let drawing_area_preedit_end = self.drawing_area.clone();
         im_context.connect_preedit_end(move |_| {
             let mut state = preedit_end.borrow_mut();
             state.text.clear();
             state.cursor_pos = 0;
             state.active = false;
+            status_preedit_end.borrow_mut().set_composing(None);
             drawing_area_preedit_end.queue_draw();
         });
 
@@ -557,7 +1324,65 @@ impl TerminalWidget {
         // Key press handler
         let terminal_key = Arc::clone(&terminal);
         let im_key = im_context.clone();
-        key_controller.connect_key_pressed(move |controller, keyval, _keycode, state| {
+        let status_key = Rc::clone(&self.status);
+        let hints_key = Rc::clone(&self.hints);
+        let path_quoting_key = self.input.path_quoting;
+        let paster_key = self.paster();
+        let playback_key = self.playback.clone();
+        let drawing_area_key = self.drawing_area.clone();
+        let pointer_hider_key = Rc::clone(&self.pointer_hider);
+        // Hardware keycodes currently held down, to tell repeats from presses
+        let held_keys: Rc<RefCell<HashSet<u32>>> = Rc::new(RefCell::new(HashSet::new()));
+        let held_press = Rc::clone(&held_keys);
+        key_controller.connect_key_pressed(move |controller, keyval, keycode, state| {
+            let modifier_only = controller
+                .current_event()
+                .and_then(|e| e.downcast::<gdk::KeyEvent>().ok())
+                .is_some_and(|e| e.is_modifier());
+            if pointer_hider_key.borrow_mut().key_pressed(modifier_only) {
+                drawing_area_key.set_cursor_from_name(Some("none"));
+            }
+
+            // Hint mode takes every key until a label is chosen or cancelled
+            if hints_key.borrow().is_some() {
+                hint_mode_key(
+                    &hints_key,
+                    &status_key,
+                    &terminal_key,
+                    path_quoting_key,
+                    keyval,
+                );
+                drawing_area_key.queue_draw();
+                return glib::Propagation::Stop;
+            }
+
+            // Playback tabs take unmodified keys as player controls
+            let plain =
+                !state.intersects(gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::ALT_MASK);
+            if let Some(player) = playback_key.as_ref().filter(|_| plain) {
+                let mut player = player.borrow_mut();
+                let mut term = terminal_key.lock();
+                let handled = keyval_to_key(keyval)
+                    .or_else(|| keyval.to_unicode().map(Key::Char))
+                    .is_some_and(|key| player.handle_key(&mut term, key));
+                if handled {
+                    status_key.borrow_mut().set_playback(Some(&player.status()));
+                    drawing_area_key.queue_draw();
+                    return glib::Propagation::Stop;
+                }
+            }
+
+            // Escape cancels a paste in progress
+            if keyval == gdk::Key::Escape && paster_key.cancel() {
+                return glib::Propagation::Stop;
+            }
+
+            // Read-only: swallow the key without leaving the scrollback
+            if terminal_key.lock().is_input_locked() {
+                drawing_area_key.error_bell();
+                return glib::Propagation::Stop;
+            }
+
             // Reset scroll to bottom on any user input
             {
                 let mut term = terminal_key.lock();
@@ -566,6 +1391,12 @@ impl TerminalWidget {
                 }
             }
 
+            // Typing again ends the search shown in the status strip
+            if status_key.borrow_mut().clear_search() {
+                terminal_key.lock().screen_mut().set_search_lines([]);
+                drawing_area_key.queue_draw();
+            }
+
             // Ctrl+Shift combinations are handled by the window's CAPTURE
             // controller (shortcuts). If they reach here, just ignore.
             let has_ctrl = state.contains(gdk::ModifierType::CONTROL_MASK);
@@ -575,6 +1406,50 @@ impl TerminalWidget {
                 return glib::Propagation::Proceed;
             }
 
+            let modifiers = gtk_state_to_modifiers(state);
+            let has_alt = state.contains(gdk::ModifierType::ALT_MASK);
+            let kind = if held_press.borrow_mut().insert(keycode) {
+                KeyEventKind::Press
+            } else {
+                KeyEventKind::Repeat
+            };
+
+            // With the kitty keyboard protocol enabled, the terminal encodes
+            // Ctrl/Alt combinations, and plain text too when the application
+            // asked for all keys; other text still goes through the IM below
+            if let Some(c) = keyval
+                .to_unicode()
+                .filter(|_| keyval_to_key(keyval).is_none())
+            {
+                let mut term = terminal_key.lock();
+                let flags = term.screen().keyboard_flags();
+                if !flags.is_empty()
+                    && (has_ctrl || has_alt || flags.contains(KeyboardFlags::REPORT_ALL_KEYS))
+                {
+                    if let Some(bytes) = term.handle_key_event(Key::Char(c), modifiers, kind) {
+                        if let Err(e) = term.write(&bytes) {
+                            log::error!("Failed to write to PTY: {}", e);
+                        }
+                    }
+                    return glib::Propagation::Stop;
+                }
+            }
+
+            // Keypad keys skip the IM when the application asked for keypad
+            // sequences (DECKPAM or the kitty keyboard protocol)
+            if let Some(key @ Key::Keypad(_)) = keyval_to_key(keyval) {
+                let mut term = terminal_key.lock();
+                let screen = term.screen();
+                if screen.modes.application_keypad || !screen.keyboard_flags().is_empty() {
+                    if let Some(bytes) = term.handle_key_event(key, modifiers, kind) {
+                        if let Err(e) = term.write(&bytes) {
+                            log::error!("Failed to write to PTY: {}", e);
+                        }
+                    }
+                    return glib::Propagation::Stop;
+                }
+            }
+
             // Let the IM context try to handle the key first.
             // This handles Ctrl+Space (IBus trigger), Japanese composition, etc.
             if let Some(event) = controller.current_event() {
@@ -583,13 +1458,10 @@ impl TerminalWidget {
                 }
             }
 
-            let modifiers = gtk_state_to_modifiers(state);
-            let has_alt = state.contains(gdk::ModifierType::ALT_MASK);
-
             // Handle special keys (arrows, function keys, etc.)
             if let Some(key) = keyval_to_key(keyval) {
                 let mut term = terminal_key.lock();
-                if let Some(bytes) = term.handle_key(key, modifiers) {
+                if let Some(bytes) = term.handle_key_event(key, modifiers, kind) {
                     if let Err(e) = term.write(&bytes) {
                         log::error!("Failed to write to PTY: {}", e);
                     }
@@ -651,18 +1523,34 @@ impl TerminalWidget {
             glib::Propagation::Proceed
         });
 
-        // Key release handler — IM contexts need release events too
+        // Key release handler — IM contexts need release events too, and
+        // the kitty keyboard protocol may ask for releases
         let im_release = im_context.clone();
-        key_controller.connect_key_released(move |controller, _keyval, _keycode, _state| {
+        let terminal_release = Arc::clone(&terminal);
+        key_controller.connect_key_released(move |controller, keyval, keycode, state| {
             if let Some(event) = controller.current_event() {
                 im_release.filter_keypress(&event);
             }
+            if !held_keys.borrow_mut().remove(&keycode) {
+                return;
+            }
+            let key = keyval_to_key(keyval).or_else(|| keyval.to_unicode().map(Key::Char));
+            if let Some(key) = key {
+                let mut term = terminal_release.lock();
+                let modifiers = gtk_state_to_modifiers(state);
+                if let Some(bytes) = term.handle_key_event(key, modifiers, KeyEventKind::Release) {
+                    if let Err(e) = term.write(&bytes) {
+                        log::error!("Failed to write to PTY: {}", e);
+                    }
+                }
+            }
         });
 
         self.drawing_area.add_controller(key_controller);
 
         // Selection state: tracks whether we're in a drag operation
         let selecting = Rc::new(RefCell::new(false));
+        let autoscroll = Rc::new(RefCell::new(AutoScroll::default()));
 
         // Mouse-forwarding state (for applications that enable mouse tracking).
         // `last_cell` is the pointer's current cell, needed by the scroll handler
@@ -677,44 +1565,64 @@ impl TerminalWidget {
 
         let terminal_click = Arc::clone(&terminal);
         let cell_dims_click = Rc::clone(&cell_dims);
+        let magnifier_click = Rc::clone(&magnifier);
         let drawing_area_click = self.drawing_area.clone();
         let selecting_pressed = Rc::clone(&selecting);
         let pressed_button_click = Rc::clone(&pressed_button);
+        let status_click = Rc::clone(&self.status);
+        let paster_click = self.paster();
 
         click_controller.connect_pressed(move |gesture, n_press, x, y| {
             drawing_area_click.grab_focus();
 
+            let (x, y) = magnifier_click.borrow().view_to_surface(x, y);
+
             let dims = cell_dims_click.borrow();
             let col = (x / dims.width).floor() as usize;
             let row = (y / dims.height).floor() as usize;
             drop(dims);
 
+            // The status strip's Cancel button covers the bottom row
+            let on_strip = row + 1 >= terminal_click.lock().screen().height();
+            if status_click.borrow().is_pasting() && on_strip {
+                paster_click.cancel();
+                return;
+            }
+            if status_click.borrow().is_transferring() && on_strip {
+                terminal_click.lock().cancel_file_transfer();
+                status_click.borrow_mut().set_transfer(None);
+                drawing_area_click.queue_draw();
+                return;
+            }
+
             let state = gesture
                 .current_event()
                 .map(|e| e.modifier_state())
                 .unwrap_or_else(gdk::ModifierType::empty);
 
-            // Ctrl+click to open hyperlinks
+            // Ctrl+click to open hyperlinks and detected URLs or paths
             if state.contains(gdk::ModifierType::CONTROL_MASK) {
                 let term = terminal_click.lock();
-                if let Some(uri) = term
-                    .screen()
-                    .get_cell(row, col)
-                    .and_then(|c| c.hyperlink.as_ref())
-                    .map(|h| h.uri.clone())
+                let line = term.screen().visible_row_to_absolute_line(row);
+                let cwd = term
+                    .foreground_cwd()
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .or_else(|| term.screen().local_current_dir().map(String::from));
+                if let Some(target) = detect::span_at(term.screen(), line, col)
+                    .and_then(|span| span.open_target(cwd.as_deref()))
                 {
                     drop(term);
-                    if let Err(e) = open::that(&uri) {
-                        log::error!("Failed to open URL {}: {}", uri, e);
+                    if let Err(e) = open::that(&target) {
+                        log::error!("Failed to open {}: {}", target, e);
                     }
                     return;
                 }
             }
 
-            // Forward to a mouse-tracking application unless Shift is held (Shift
-            // always falls through to local text selection).
-            let shift = state.contains(gdk::ModifierType::SHIFT_MASK);
-            if !shift {
+            // Forward to a mouse-tracking application unless the override
+            // modifier (Shift by default) is held: it falls through to local
+            // text selection.
+            if !input.overrides_mouse_tracking(gtk_state_to_mouse_mods(state)) {
                 let mut term = terminal_click.lock();
                 if mouse_tracking_active(&term)
                     && report_mouse(
@@ -751,8 +1659,10 @@ impl TerminalWidget {
 
         let terminal_released = Arc::clone(&terminal);
         let cell_dims_released = Rc::clone(&cell_dims);
+        let magnifier_released = Rc::clone(&magnifier);
         let drawing_area_released = self.drawing_area.clone();
         let selecting_released = Rc::clone(&selecting);
+        let autoscroll_released = Rc::clone(&autoscroll);
         let pressed_button_released = Rc::clone(&pressed_button);
 
         click_controller.connect_released(move |gesture, _n_press, x, y| {
@@ -760,6 +1670,7 @@ impl TerminalWidget {
             // and skip the selection-finalize path.
             if pressed_button_released.borrow().is_some() {
                 *pressed_button_released.borrow_mut() = None;
+                let (x, y) = magnifier_released.borrow().view_to_surface(x, y);
                 let dims = cell_dims_released.borrow();
                 let col = (x / dims.width).floor() as usize;
                 let row = (y / dims.height).floor() as usize;
@@ -781,6 +1692,7 @@ impl TerminalWidget {
             }
 
             *selecting_released.borrow_mut() = false;
+            autoscroll_released.borrow_mut().stop();
 
             // Check if selection is empty (same start and end) and clear it
             // Only clear char/block selections - word/line selections are never "empty"
@@ -819,10 +1731,12 @@ impl TerminalWidget {
 
             let terminal_rc = Arc::clone(&terminal);
             let cell_dims_rc = Rc::clone(&cell_dims);
+            let magnifier_rc = Rc::clone(&magnifier);
             let drawing_area_rc = self.drawing_area.clone();
             let pressed_button_rc = Rc::clone(&pressed_button);
 
             right_click.connect_pressed(move |gesture, _n_press, x, y| {
+                let (x, y) = magnifier_rc.borrow().view_to_surface(x, y);
                 let dims = cell_dims_rc.borrow();
                 let col = (x / dims.width).floor() as usize;
                 let row = (y / dims.height).floor() as usize;
@@ -832,10 +1746,9 @@ impl TerminalWidget {
                     .current_event()
                     .map(|e| e.modifier_state())
                     .unwrap_or_else(gdk::ModifierType::empty);
-                let shift = state.contains(gdk::ModifierType::SHIFT_MASK);
 
-                // Forward to a mouse-tracking app unless Shift is held.
-                if !shift {
+                // Forward to a mouse-tracking app unless the override is held.
+                if !input.overrides_mouse_tracking(gtk_state_to_mouse_mods(state)) {
                     let mut term = terminal_rc.lock();
                     if mouse_tracking_active(&term)
                         && report_mouse(
@@ -877,12 +1790,14 @@ impl TerminalWidget {
             // Report the release of a forwarded right-button press.
             let terminal_rr = Arc::clone(&terminal);
             let cell_dims_rr = Rc::clone(&cell_dims);
+            let magnifier_rr = Rc::clone(&magnifier);
             let pressed_button_rr = Rc::clone(&pressed_button);
             right_click.connect_released(move |gesture, _n_press, x, y| {
                 if *pressed_button_rr.borrow() != Some(MouseButton::Right) {
                     return;
                 }
                 *pressed_button_rr.borrow_mut() = None;
+                let (x, y) = magnifier_rr.borrow().view_to_surface(x, y);
                 let dims = cell_dims_rr.borrow();
                 let col = (x / dims.width).floor() as usize;
                 let row = (y / dims.height).floor() as usize;
@@ -913,7 +1828,8 @@ impl TerminalWidget {
 
             let terminal_middle = Arc::clone(&terminal);
             let cell_dims_middle = Rc::clone(&cell_dims);
-            let drawing_area_middle = self.drawing_area.clone();
+            let magnifier_middle = Rc::clone(&magnifier);
+            let paster_middle = self.paster();
             let pressed_button_middle = Rc::clone(&pressed_button);
 
             middle_click_controller.connect_pressed(move |gesture, _n_press, x, y| {
@@ -921,10 +1837,10 @@ impl TerminalWidget {
                     .current_event()
                     .map(|e| e.modifier_state())
                     .unwrap_or_else(gdk::ModifierType::empty);
-                let shift = state.contains(gdk::ModifierType::SHIFT_MASK);
 
-                // Forward to a mouse-tracking app unless Shift is held.
-                if !shift {
+                // Forward to a mouse-tracking app unless the override is held.
+                if !input.overrides_mouse_tracking(gtk_state_to_mouse_mods(state)) {
+                    let (x, y) = magnifier_middle.borrow().view_to_surface(x, y);
                     let dims = cell_dims_middle.borrow();
                     let col = (x / dims.width).floor() as usize;
                     let row = (y / dims.height).floor() as usize;
@@ -946,24 +1862,18 @@ impl TerminalWidget {
                     }
                 }
 
+                if !input.middle_click_paste {
+                    return;
+                }
                 let Some(display) = gdk::Display::default() else {
                     return;
                 };
                 let primary = display.primary_clipboard();
-                let terminal = Arc::clone(&terminal_middle);
-                let drawing_area = drawing_area_middle.clone();
+                let paster = paster_middle.clone();
 
                 primary.read_text_async(None::<&gio::Cancellable>, move |result| {
                     if let Ok(Some(text)) = result {
-                        let mut term = terminal.lock();
-                        // Use bracketed paste if enabled
-                        let paste_text = if term.screen().modes.bracketed_paste {
-                            format!("\x1b[200~{}\x1b[201~", text)
-                        } else {
-                            text.to_string()
-                        };
-                        let _ = term.write_str(&paste_text);
-                        drawing_area.queue_draw();
+                        paster.paste(&text, true);
                     }
                 });
             });
@@ -971,12 +1881,14 @@ impl TerminalWidget {
             // Report the release of a forwarded middle-button press.
             let terminal_mr = Arc::clone(&terminal);
             let cell_dims_mr = Rc::clone(&cell_dims);
+            let magnifier_mr = Rc::clone(&magnifier);
             let pressed_button_mr = Rc::clone(&pressed_button);
             middle_click_controller.connect_released(move |gesture, _n_press, x, y| {
                 if *pressed_button_mr.borrow() != Some(MouseButton::Middle) {
                     return;
                 }
                 *pressed_button_mr.borrow_mut() = None;
+                let (x, y) = magnifier_mr.borrow().view_to_surface(x, y);
                 let dims = cell_dims_mr.borrow();
                 let col = (x / dims.width).floor() as usize;
                 let row = (y / dims.height).floor() as usize;
@@ -1004,12 +1916,32 @@ impl TerminalWidget {
 
         let terminal_motion = Arc::clone(&terminal);
         let cell_dims_motion = Rc::clone(&cell_dims);
+        let magnifier_motion = Rc::clone(&magnifier);
         let drawing_area_motion = self.drawing_area.clone();
         let selecting_motion = Rc::clone(&selecting);
+        let autoscroll_motion = Rc::clone(&autoscroll);
         let last_cell_motion = Rc::clone(&last_cell);
         let pressed_button_motion = Rc::clone(&pressed_button);
+        let link_popover = LinkPopover::new(&self.drawing_area);
+        let link_popover_motion = Rc::clone(&link_popover);
+        let command_gutter = self.command_gutter;
+        let pointer_hider_motion = Rc::clone(&self.pointer_hider);
+
+        motion_controller.connect_motion(move |controller, view_x, view_y| {
+            // Typing hid the pointer: keep it hidden until the mouse moves
+            match pointer_hider_motion
+                .borrow_mut()
+                .pointer_moved(view_x, view_y)
+            {
+                PointerMotion::Still => return,
+                PointerMotion::Shown => {
+                    let shape = terminal_motion.lock().screen().pointer_shape();
+                    drawing_area_motion.set_cursor_from_name(Some(pointer_cursor_name(shape)));
+                }
+                PointerMotion::Moved => {}
+            }
 
-        motion_controller.connect_motion(move |controller, x, y| {
+            let (x, y) = magnifier_motion.borrow().view_to_surface(view_x, view_y);
             let dims = cell_dims_motion.borrow();
             let col = (x / dims.width).floor() as usize;
             let row = (y / dims.height).floor() as usize;
@@ -1024,11 +1956,11 @@ impl TerminalWidget {
                 .current_event()
                 .map(|e| e.modifier_state())
                 .unwrap_or_else(gdk::ModifierType::empty);
-            let shift = state.contains(gdk::ModifierType::SHIFT_MASK);
+            let local = input.overrides_mouse_tracking(gtk_state_to_mouse_mods(state));
 
             // If a button was forwarded to a mouse-tracking app, this drag belongs to
             // the app: report motion (on cell change, to avoid flooding) and swallow it.
-            if !shift {
+            if !local {
                 if let Some(button) = *pressed_button_motion.borrow() {
                     if prev_cell != (col, row) {
                         let mut term = terminal_motion.lock();
@@ -1045,8 +1977,24 @@ impl TerminalWidget {
                 }
             }
 
-            // Selection drag
+            // Selection drag, scrolling while the pointer is past an edge
             if *selecting_motion.borrow() {
+                let direction = if view_y < 0.0 {
+                    -1
+                } else if view_y >= drawing_area_motion.height() as f64 {
+                    1
+                } else {
+                    0
+                };
+                update_autoscroll(
+                    &autoscroll_motion,
+                    direction,
+                    col,
+                    input.autoscroll_lines,
+                    &terminal_motion,
+                    &drawing_area_motion,
+                );
+
                 let mut term = terminal_motion.lock();
                 let line = term.screen().visible_row_to_absolute_line(row);
                 term.screen_mut().extend_selection(line, col);
@@ -1055,6 +2003,31 @@ impl TerminalWidget {
                 return;
             }
 
+            // Preview for the link or image path under the pointer
+            if input.hover_previews && prev_cell != (col, row) {
+                let term = terminal_motion.lock();
+                let line = term.screen().visible_row_to_absolute_line(row);
+                let cwd = term
+                    .foreground_cwd()
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .or_else(|| term.screen().local_current_dir().map(String::from));
+                let target = detect::span_at(term.screen(), line, col)
+                    .and_then(|span| PreviewTarget::for_span(&span, cwd.as_deref()));
+                drop(term);
+                let rect = gdk::Rectangle::new(view_x as i32, view_y as i32, 1, 1);
+                link_popover_motion.hover(target, rect);
+            }
+
+            // Exit status and run time of the command under the gutter
+            if command_gutter && x < GUTTER_HOVER_WIDTH {
+                let blocks = terminal_motion.lock().screen().command_blocks();
+                if let Some(block) = gutter_block_at(&blocks, x, row) {
+                    drawing_area_motion.set_cursor_from_name(Some("default"));
+                    drawing_area_motion.set_tooltip_text(Some(&gutter_tooltip(block)));
+                    return;
+                }
+            }
+
             // Check for hyperlink under cursor
             let term = terminal_motion.lock();
             let has_link = term
@@ -1067,6 +2040,7 @@ impl TerminalWidget {
                 .get_cell(row, col)
                 .and_then(|c| c.hyperlink.as_ref())
                 .map(|h| h.uri.clone());
+            let shape = term.screen().pointer_shape();
             drop(term);
 
             if has_link {
@@ -1075,11 +2049,23 @@ impl TerminalWidget {
                     drawing_area_motion.set_tooltip_text(Some(&uri));
                 }
             } else {
-                drawing_area_motion.set_cursor_from_name(Some("text"));
+                drawing_area_motion.set_cursor_from_name(Some(pointer_cursor_name(shape)));
                 drawing_area_motion.set_tooltip_text(None);
             }
         });
 
+        let pointer_hider_leave = Rc::clone(&self.pointer_hider);
+        let terminal_leave = Arc::clone(&terminal);
+        let drawing_area_leave = self.drawing_area.clone();
+        motion_controller.connect_leave(move |_| {
+            link_popover.hide();
+            if pointer_hider_leave.borrow().is_hidden() {
+                let shape = terminal_leave.lock().screen().pointer_shape();
+                drawing_area_leave.set_cursor_from_name(Some(pointer_cursor_name(shape)));
+            }
+            pointer_hider_leave.borrow_mut().pointer_left();
+        });
+
         self.drawing_area.add_controller(motion_controller);
 
         // Scroll handling
@@ -1088,23 +2074,26 @@ impl TerminalWidget {
         let terminal_scroll = Arc::clone(&terminal);
         let drawing_area_scroll = self.drawing_area.clone();
         let last_cell_scroll = Rc::clone(&last_cell);
+        let scroll_peer = Rc::clone(&self.scroll_peer);
 
         // Lines of cursor-key / viewport movement per wheel notch.
-        const SCROLL_LINES: usize = 3;
+        let scroll_lines = input.wheel_scroll_lines(1);
 
         scroll_controller.connect_scroll(move |controller, _dx, dy| {
-            let up = dy < 0.0;
+            // GTK doesn't say whether natural scrolling already reversed dy
+            let up = input.wheel_up(dy < 0.0, false);
             let state = controller
                 .current_event()
                 .map(|e| e.modifier_state())
                 .unwrap_or_else(gdk::ModifierType::empty);
-            let shift = state.contains(gdk::ModifierType::SHIFT_MASK);
+            let local = input.overrides_mouse_tracking(gtk_state_to_mouse_mods(state));
 
             let mut term = terminal_scroll.lock();
 
-            // Shift+wheel always scrolls cterm's own scrollback, overriding any
-            // application mouse/alternate-scroll handling (xterm/VTE convention).
-            if !shift {
+            // The override modifier (Shift by default, the xterm/VTE convention)
+            // makes the wheel scroll cterm's own scrollback, overriding any
+            // application mouse/alternate-scroll handling.
+            if !local {
                 // 1) Application is tracking the mouse: forward a wheel report.
                 if mouse_tracking_active(&term) {
                     let (col, row) = *last_cell_scroll.borrow();
@@ -1126,10 +2115,13 @@ impl TerminalWidget {
 
                 // 2) Alternate screen + alternate-scroll: translate the wheel into
                 //    cursor-key input so pagers (less/man) scroll.
-                if term.screen().modes.alternate_screen && term.screen().modes.alternate_scroll {
+                if term.screen().modes.alternate_screen
+                    && term.screen().modes.alternate_scroll
+                    && !term.is_input_locked()
+                {
                     let key = if up { Key::Up } else { Key::Down };
                     if let Some(bytes) = term.handle_key(key, Modifiers::empty()) {
-                        for _ in 0..SCROLL_LINES {
+                        for _ in 0..scroll_lines {
                             let _ = term.write(&bytes);
                         }
                     }
@@ -1138,13 +2130,23 @@ impl TerminalWidget {
             }
 
             // 3) Default: scroll cterm's local scrollback viewport.
+            let from = term.screen().scroll_offset;
             if up {
-                term.scroll_viewport_up(SCROLL_LINES);
+                term.scroll_viewport_up(scroll_lines);
             } else {
-                term.scroll_viewport_down(SCROLL_LINES);
+                term.scroll_viewport_down(scroll_lines);
             }
+            let to = term.screen().scroll_offset;
             drop(term);
             drawing_area_scroll.queue_draw();
+            if from != to {
+                if let Some(ref peer) = *scroll_peer.borrow() {
+                    session::mirror_scroll(&mut peer.terminal.lock(), from, to);
+                    if let Some(drawing_area) = peer.drawing_area.upgrade() {
+                        drawing_area.queue_draw();
+                    }
+                }
+            }
             glib::Propagation::Stop
         });
 
@@ -1154,8 +2156,10 @@ impl TerminalWidget {
     /// Set up file drag-and-drop
     fn setup_drop(&self) {
         let drop_target = gtk4::DropTarget::new(gio::File::static_type(), gdk::DragAction::COPY);
-        let terminal = Arc::clone(&self.terminal);
+        let paster = self.paster();
         let drawing_area = self.drawing_area.clone();
+        let terminal = Arc::clone(&self.terminal);
+        let path_quoting = self.input.path_quoting;
 
         drop_target.connect_drop(move |_, value, _, _| {
             let file = match value.get::<gio::File>() {
@@ -1181,7 +2185,8 @@ impl TerminalWidget {
                 return false;
             };
 
-            let terminal = Arc::clone(&terminal);
+            let paster = paster.clone();
+            let quoting = path_quoting.for_terminal(&terminal.lock());
             let info = std::rc::Rc::new(info);
             let info_for_cb = std::rc::Rc::clone(&info);
 
@@ -1189,7 +2194,9 @@ impl TerminalWidget {
                 use cterm_app::file_drop::{build_pty_input, FileDropAction};
 
                 let action = match choice {
-                    crate::dialogs::FileDropChoice::PastePath => FileDropAction::PastePath,
+                    crate::dialogs::FileDropChoice::PastePath => {
+                        FileDropAction::PastePath { quoting }
+                    }
                     crate::dialogs::FileDropChoice::PasteContents => FileDropAction::PasteContents,
                     crate::dialogs::FileDropChoice::CreateViaBase64(name) => {
                         FileDropAction::CreateViaBase64 { filename: name }
@@ -1203,15 +2210,7 @@ impl TerminalWidget {
                 let use_bracketed = matches!(action, FileDropAction::PasteContents);
 
                 match build_pty_input(&info_for_cb, action) {
-                    Ok(text) => {
-                        let mut term = terminal.lock();
-                        if use_bracketed && term.screen().modes.bracketed_paste {
-                            let paste = format!("\x1b[200~{}\x1b[201~", text);
-                            let _ = term.write_str(&paste);
-                        } else {
-                            let _ = term.write_str(&text);
-                        }
-                    }
+                    Ok(text) => paster.paste(&text, use_bracketed),
                     Err(e) => {
                         log::error!("Failed to build PTY input for dropped file: {}", e);
                     }
@@ -1262,7 +2261,7 @@ impl TerminalWidget {
         let font_size = config.appearance.font.size;
         let cell_dims = calculate_cell_dimensions(&font_family, font_size);
 
-        let drawing_area = DrawingArea::new();
+        let drawing_area = TerminalArea::new();
         drawing_area.set_can_focus(true);
         drawing_area.set_focusable(true);
         drawing_area.add_css_class("terminal");
@@ -1280,7 +2279,19 @@ impl TerminalWidget {
         let (cmd_tx, cmd_rx) = tokio::sync::mpsc::unbounded_channel::<DaemonCommand>();
 
         // Create a Terminal with no PTY — write callback forwards via channel
-        let mut terminal = Terminal::new(80, 24, ScreenConfig::default());
+        let screen_config = ScreenConfig {
+            selection: config.selection.clone(),
+            conformance_level: config.general.conformance_level,
+            cursor_style: config.appearance.cursor_style.into(),
+            cursor_blink: config.appearance.cursor_blink,
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(80, 24, screen_config);
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(config.permissions);
+        let triggers = TriggerEngine::new(&config.triggers);
+        triggers.attach(&mut terminal);
+        terminal.set_color_scheme(theme.colors.color_scheme());
         let write_tx = cmd_tx.clone();
         terminal.set_write_fn(Box::new(move |data: &[u8]| {
             let _ = write_tx.send(DaemonCommand::Write(data.to_vec()));
@@ -1293,18 +2304,48 @@ impl TerminalWidget {
         let widget = Self {
             drawing_area: drawing_area.clone(),
             terminal: Arc::clone(&terminal),
-            theme: theme.clone(),
-            font_family,
+            theme: Rc::new(RefCell::new(theme.clone())),
+            font_family: Rc::new(RefCell::new(font_family)),
+            attr_overrides: config.appearance.text_styles,
+            command_gutter: config.appearance.command_gutter,
+            opacity: Rc::new(Cell::new(config.appearance.background_opacity(theme))),
+            input: config.input,
+            pointer_hider: Rc::new(RefCell::new(PointerHider::new(
+                config.input.hide_pointer_while_typing,
+            ))),
+            scroll_peer: Rc::new(RefCell::new(None)),
             font_size: Rc::new(RefCell::new(font_size)),
-            default_font_size: font_size,
+            default_font_size: Rc::new(Cell::new(font_size)),
             cell_dims,
+            magnifier: Rc::new(RefCell::new(Magnifier::new())),
             background_override: Rc::new(RefCell::new(None)),
             on_exit: Rc::new(RefCell::new(None)),
             on_bell: Rc::new(RefCell::new(None)),
+            on_output: Rc::new(RefCell::new(None)),
             on_title_change: Rc::new(RefCell::new(None)),
             preedit: Rc::new(RefCell::new(PreeditState::default())),
+            presentation: Rc::new(RefCell::new(PresentationState::default())),
+            hints: Rc::new(RefCell::new(None)),
+            status: Rc::new(RefCell::new(StatusStrip::new())),
+            paste: Rc::new(RefCell::new(PasteState::default())),
+            paste_guard: PasteGuard::from(&config.general),
+            command_notify: Rc::new(RefCell::new(config.command_notify)),
+            on_command_notify: Rc::new(RefCell::new(None)),
+            on_command_finished: Rc::new(RefCell::new(None)),
             on_file_transfer: Rc::new(RefCell::new(None)),
+            on_notification: Rc::new(RefCell::new(None)),
+            on_permission: Rc::new(RefCell::new(None)),
+            on_tmux_control: Rc::new(RefCell::new(None)),
+            on_watch_match: Rc::new(RefCell::new(None)),
+            triggers: Rc::new(RefCell::new(triggers)),
+            on_trigger: Rc::new(RefCell::new(None)),
+            auto_switch: Rc::new(RefCell::new(AutoSwitch {
+                switcher: AutoSwitcher::new(config.auto_switch.clone()),
+                saved_theme: None,
+            })),
+            on_auto_switch: Rc::new(RefCell::new(None)),
             daemon_cmd_tx: Some(cmd_tx.clone()),
+            playback: None,
         };
 
         let daemon_socket = session.socket_path().map(|p| p.to_owned());
@@ -1330,7 +2371,7 @@ impl TerminalWidget {
         let font_size = config.appearance.font.size;
         let cell_dims = calculate_cell_dimensions(&font_family, font_size);
 
-        let drawing_area = DrawingArea::new();
+        let drawing_area = TerminalArea::new();
         drawing_area.set_can_focus(true);
         drawing_area.set_focusable(true);
         drawing_area.add_css_class("terminal");
@@ -1342,7 +2383,19 @@ impl TerminalWidget {
         drawing_area.set_size_request(min_width, min_height);
 
         // Create a Terminal with no PTY
-        let mut terminal = Terminal::new(80, 24, ScreenConfig::default());
+        let screen_config = ScreenConfig {
+            selection: config.selection.clone(),
+            conformance_level: config.general.conformance_level,
+            cursor_style: config.appearance.cursor_style.into(),
+            cursor_blink: config.appearance.cursor_blink,
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(80, 24, screen_config);
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(config.permissions);
+        let triggers = TriggerEngine::new(&config.triggers);
+        triggers.attach(&mut terminal);
+        terminal.set_color_scheme(theme.colors.color_scheme());
 
         // Apply screen snapshot BEFORE wrapping in Arc<Mutex<>>
         recon.apply_screen(&mut terminal);
@@ -1366,18 +2419,48 @@ impl TerminalWidget {
         let widget = Self {
             drawing_area: drawing_area.clone(),
             terminal: Arc::clone(&terminal),
-            theme: theme.clone(),
-            font_family,
+            theme: Rc::new(RefCell::new(theme.clone())),
+            font_family: Rc::new(RefCell::new(font_family)),
+            attr_overrides: config.appearance.text_styles,
+            command_gutter: config.appearance.command_gutter,
+            opacity: Rc::new(Cell::new(config.appearance.background_opacity(theme))),
+            input: config.input,
+            pointer_hider: Rc::new(RefCell::new(PointerHider::new(
+                config.input.hide_pointer_while_typing,
+            ))),
+            scroll_peer: Rc::new(RefCell::new(None)),
             font_size: Rc::new(RefCell::new(font_size)),
-            default_font_size: font_size,
+            default_font_size: Rc::new(Cell::new(font_size)),
             cell_dims,
+            magnifier: Rc::new(RefCell::new(Magnifier::new())),
             background_override: Rc::new(RefCell::new(None)),
             on_exit: Rc::new(RefCell::new(None)),
             on_bell: Rc::new(RefCell::new(None)),
+            on_output: Rc::new(RefCell::new(None)),
             on_title_change: Rc::new(RefCell::new(None)),
             preedit: Rc::new(RefCell::new(PreeditState::default())),
+            presentation: Rc::new(RefCell::new(PresentationState::default())),
+            hints: Rc::new(RefCell::new(None)),
+            status: Rc::new(RefCell::new(StatusStrip::new())),
+            paste: Rc::new(RefCell::new(PasteState::default())),
+            paste_guard: PasteGuard::from(&config.general),
+            command_notify: Rc::new(RefCell::new(config.command_notify)),
+            on_command_notify: Rc::new(RefCell::new(None)),
+            on_command_finished: Rc::new(RefCell::new(None)),
             on_file_transfer: Rc::new(RefCell::new(None)),
+            on_notification: Rc::new(RefCell::new(None)),
+            on_permission: Rc::new(RefCell::new(None)),
+            on_tmux_control: Rc::new(RefCell::new(None)),
+            on_watch_match: Rc::new(RefCell::new(None)),
+            triggers: Rc::new(RefCell::new(triggers)),
+            on_trigger: Rc::new(RefCell::new(None)),
+            auto_switch: Rc::new(RefCell::new(AutoSwitch {
+                switcher: AutoSwitcher::new(config.auto_switch.clone()),
+                saved_theme: None,
+            })),
+            on_auto_switch: Rc::new(RefCell::new(None)),
             daemon_cmd_tx: Some(cmd_tx.clone()),
+            playback: None,
         };
 
         let daemon_socket = recon.handle.socket_path().map(|p| p.to_owned());
@@ -1390,6 +2473,239 @@ impl TerminalWidget {
         widget
     }
 
+    /// Create a read-only terminal widget playing back a recording.
+    ///
+    /// The Terminal keeps the recording's size and has no PTY; keys control
+    /// the player (see [`Player::handle_key`]) instead of being sent anywhere.
+    pub fn for_playback(player: Player, config: &Config, theme: &Theme) -> Self {
+        let font_family = config.appearance.font.family.clone();
+        let font_size = config.appearance.font.size;
+        let cell_dims = calculate_cell_dimensions(&font_family, font_size);
+
+        let drawing_area = TerminalArea::new();
+        drawing_area.set_can_focus(true);
+        drawing_area.set_focusable(true);
+        drawing_area.add_css_class("terminal");
+        drawing_area.set_vexpand(true);
+        drawing_area.set_hexpand(true);
+
+        let min_width = (cell_dims.width * 80.0).ceil() as i32;
+        let min_height = (cell_dims.height * 24.0).ceil() as i32;
+        drawing_area.set_size_request(min_width, min_height);
+
+        let screen_config = ScreenConfig {
+            selection: config.selection.clone(),
+            conformance_level: config.general.conformance_level,
+            cursor_style: config.appearance.cursor_style.into(),
+            cursor_blink: config.appearance.cursor_blink,
+            ..Default::default()
+        };
+        let terminal = Arc::new(Mutex::new(player.new_terminal(screen_config)));
+
+        let widget = Self {
+            drawing_area: drawing_area.clone(),
+            terminal: Arc::clone(&terminal),
+            theme: Rc::new(RefCell::new(theme.clone())),
+            font_family: Rc::new(RefCell::new(font_family)),
+            attr_overrides: config.appearance.text_styles,
+            command_gutter: config.appearance.command_gutter,
+            opacity: Rc::new(Cell::new(config.appearance.background_opacity(theme))),
+            input: config.input,
+            pointer_hider: Rc::new(RefCell::new(PointerHider::new(
+                config.input.hide_pointer_while_typing,
+            ))),
+            scroll_peer: Rc::new(RefCell::new(None)),
+            font_size: Rc::new(RefCell::new(font_size)),
+            default_font_size: Rc::new(Cell::new(font_size)),
+            cell_dims: Rc::new(RefCell::new(cell_dims)),
+            magnifier: Rc::new(RefCell::new(Magnifier::new())),
+            background_override: Rc::new(RefCell::new(None)),
+            on_exit: Rc::new(RefCell::new(None)),
+            on_bell: Rc::new(RefCell::new(None)),
+            on_output: Rc::new(RefCell::new(None)),
+            on_title_change: Rc::new(RefCell::new(None)),
+            preedit: Rc::new(RefCell::new(PreeditState::default())),
+            presentation: Rc::new(RefCell::new(PresentationState::default())),
+            hints: Rc::new(RefCell::new(None)),
+            status: Rc::new(RefCell::new(StatusStrip::new())),
+            paste: Rc::new(RefCell::new(PasteState::default())),
+            paste_guard: PasteGuard::from(&config.general),
+            command_notify: Rc::new(RefCell::new(config.command_notify)),
+            on_command_notify: Rc::new(RefCell::new(None)),
+            on_command_finished: Rc::new(RefCell::new(None)),
+            on_file_transfer: Rc::new(RefCell::new(None)),
+            on_notification: Rc::new(RefCell::new(None)),
+            on_permission: Rc::new(RefCell::new(None)),
+            on_tmux_control: Rc::new(RefCell::new(None)),
+            on_watch_match: Rc::new(RefCell::new(None)),
+            triggers: Rc::new(RefCell::new(TriggerEngine::default())),
+            on_trigger: Rc::new(RefCell::new(None)),
+            auto_switch: Rc::new(RefCell::new(AutoSwitch {
+                switcher: AutoSwitcher::new(config.auto_switch.clone()),
+                saved_theme: None,
+            })),
+            on_auto_switch: Rc::new(RefCell::new(None)),
+            daemon_cmd_tx: None,
+            playback: Some(Rc::new(RefCell::new(player))),
+        };
+
+        widget.setup_drawing();
+        widget.setup_input();
+        widget.setup_playback();
+
+        widget
+    }
+
+    /// Advance the player every [`Player::TICK`] until the widget goes away
+    fn setup_playback(&self) {
+        let Some(ref player) = self.playback else {
+            return;
+        };
+        let player = Rc::clone(player);
+        let terminal = Arc::clone(&self.terminal);
+        let status = Rc::clone(&self.status);
+        let drawing_area = self.drawing_area.downgrade();
+        glib::timeout_add_local(Player::TICK, move || {
+            let Some(drawing_area) = drawing_area.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            let mut player = player.borrow_mut();
+            let changed = player.tick(&mut terminal.lock());
+            if status.borrow_mut().set_playback(Some(&player.status())) || changed {
+                drawing_area.queue_draw();
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
+    /// Create a terminal widget for a pane of a tmux session in control mode.
+    ///
+    /// The Terminal keeps the pane's size, set by tmux; output comes from
+    /// [`TerminalWidget::feed`] and input goes to the pane through `gateway`.
+    pub fn for_tmux_pane(
+        gateway: &Arc<TmuxGateway>,
+        pane: PaneId,
+        cols: usize,
+        rows: usize,
+        config: &Config,
+        theme: &Theme,
+    ) -> Self {
+        let font_family = config.appearance.font.family.clone();
+        let font_size = config.appearance.font.size;
+        let cell_dims = calculate_cell_dimensions(&font_family, font_size);
+
+        let drawing_area = TerminalArea::new();
+        drawing_area.set_can_focus(true);
+        drawing_area.set_focusable(true);
+        drawing_area.add_css_class("terminal");
+        drawing_area.set_vexpand(true);
+        drawing_area.set_hexpand(true);
+
+        let screen_config = ScreenConfig {
+            selection: config.selection.clone(),
+            conformance_level: config.general.conformance_level,
+            cursor_style: config.appearance.cursor_style.into(),
+            cursor_blink: config.appearance.cursor_blink,
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(cols, rows, screen_config);
+        terminal.set_write_fn(gateway.pane_write_fn(pane));
+        terminal.set_color_scheme(theme.colors.color_scheme());
+        let terminal = Arc::new(Mutex::new(terminal));
+
+        let widget = Self {
+            drawing_area: drawing_area.clone(),
+            terminal: Arc::clone(&terminal),
+            theme: Rc::new(RefCell::new(theme.clone())),
+            font_family: Rc::new(RefCell::new(font_family)),
+            attr_overrides: config.appearance.text_styles,
+            command_gutter: config.appearance.command_gutter,
+            opacity: Rc::new(Cell::new(config.appearance.background_opacity(theme))),
+            input: config.input,
+            pointer_hider: Rc::new(RefCell::new(PointerHider::new(
+                config.input.hide_pointer_while_typing,
+            ))),
+            scroll_peer: Rc::new(RefCell::new(None)),
+            font_size: Rc::new(RefCell::new(font_size)),
+            default_font_size: Rc::new(Cell::new(font_size)),
+            cell_dims: Rc::new(RefCell::new(cell_dims)),
+            magnifier: Rc::new(RefCell::new(Magnifier::new())),
+            background_override: Rc::new(RefCell::new(None)),
+            on_exit: Rc::new(RefCell::new(None)),
+            on_bell: Rc::new(RefCell::new(None)),
+            on_output: Rc::new(RefCell::new(None)),
+            on_title_change: Rc::new(RefCell::new(None)),
+            preedit: Rc::new(RefCell::new(PreeditState::default())),
+            presentation: Rc::new(RefCell::new(PresentationState::default())),
+            hints: Rc::new(RefCell::new(None)),
+            status: Rc::new(RefCell::new(StatusStrip::new())),
+            paste: Rc::new(RefCell::new(PasteState::default())),
+            paste_guard: PasteGuard::from(&config.general),
+            command_notify: Rc::new(RefCell::new(config.command_notify)),
+            on_command_notify: Rc::new(RefCell::new(None)),
+            on_command_finished: Rc::new(RefCell::new(None)),
+            on_file_transfer: Rc::new(RefCell::new(None)),
+            on_notification: Rc::new(RefCell::new(None)),
+            on_permission: Rc::new(RefCell::new(None)),
+            on_tmux_control: Rc::new(RefCell::new(None)),
+            on_watch_match: Rc::new(RefCell::new(None)),
+            triggers: Rc::new(RefCell::new(TriggerEngine::default())),
+            on_trigger: Rc::new(RefCell::new(None)),
+            auto_switch: Rc::new(RefCell::new(AutoSwitch {
+                switcher: AutoSwitcher::new(config.auto_switch.clone()),
+                saved_theme: None,
+            })),
+            on_auto_switch: Rc::new(RefCell::new(None)),
+            daemon_cmd_tx: None,
+            playback: None,
+        };
+
+        widget.setup_drawing();
+        widget.setup_input();
+        widget.setup_tmux_resize(Arc::clone(gateway));
+
+        widget
+    }
+
+    /// Tell tmux the size of the tab, which its panes are laid out in
+    fn setup_tmux_resize(&self, gateway: Arc<TmuxGateway>) {
+        let cell_dims = Rc::clone(&self.cell_dims);
+        self.drawing_area
+            .connect_resize(move |_area, width, height| {
+                let dims = cell_dims.borrow();
+                let cols = ((width as f64) / dims.width).floor() as usize;
+                let rows = ((height as f64) / dims.height).floor() as usize;
+                drop(dims);
+                gateway.resize(cols, rows);
+            });
+    }
+
+    /// Process output of a tmux pane
+    pub fn feed(&self, data: &[u8]) {
+        let events = self.terminal.lock().process(data);
+        if events.iter().any(|e| matches!(e, TerminalEvent::Bell)) {
+            if let Some(ref callback) = *self.on_bell.borrow() {
+                callback();
+            }
+        }
+        update_auto_switch(
+            &self.terminal,
+            &self.auto_switch,
+            &self.theme,
+            &self.on_auto_switch,
+        );
+        if let Some(ref callback) = *self.on_output.borrow() {
+            callback();
+        }
+        self.drawing_area.queue_draw();
+    }
+
+    /// Resize the grid to the size tmux gave a pane
+    pub fn resize_grid(&self, cols: usize, rows: usize) {
+        self.terminal.lock().resize(cols, rows);
+        self.drawing_area.queue_draw();
+    }
+
     /// Set up the daemon output reader — streams raw PTY output from the daemon
     /// and feeds it through the local terminal parser.
     ///
@@ -1556,6 +2872,27 @@ impl TerminalWidget {
                                     log::error!("Failed to clear alert: {}", e);
                                 }
                             }
+                            DaemonCommand::ClearImages => {
+                                if let Err(e) = cmd_session.clear_images(Vec::new()).await {
+                                    log::error!("Failed to clear images: {}", e);
+                                }
+                            }
+                            DaemonCommand::SetClipboard(data) => {
+                                if let Err(e) = cmd_session
+                                    .set_clipboard(cterm_proto::proto::ClipboardSelection::Clipboard, data)
+                                    .await
+                                {
+                                    log::error!("Failed to copy to daemon clipboard: {}", e);
+                                }
+                            }
+                            DaemonCommand::Remote(request, reply_tx) => {
+                                // Transfers can take a while; keep serving input meanwhile
+                                let s = cmd_session.clone();
+                                tokio::spawn(async move {
+                                    let reply = run_remote_request(&s, request).await;
+                                    let _ = reply_tx.send(reply);
+                                });
+                            }
                         }
                     }
                 });
@@ -1644,48 +2981,50 @@ impl TerminalWidget {
         let terminal_main = Arc::clone(&self.terminal);
         let on_exit = Rc::clone(&self.on_exit);
         let on_bell = Rc::clone(&self.on_bell);
+        let on_output = Rc::clone(&self.on_output);
         let on_title_change = Rc::clone(&self.on_title_change);
         let on_file_transfer = Rc::clone(&self.on_file_transfer);
+        let on_notification = Rc::clone(&self.on_notification);
+        let on_permission = Rc::clone(&self.on_permission);
+        let on_tmux_control = Rc::clone(&self.on_tmux_control);
+        let on_watch_match = Rc::clone(&self.on_watch_match);
+        let triggers = Rc::clone(&self.triggers);
+        let on_trigger = Rc::clone(&self.on_trigger);
+        let auto_switch = Rc::clone(&self.auto_switch);
+        let on_auto_switch = Rc::clone(&self.on_auto_switch);
+        let theme = Rc::clone(&self.theme);
+        let status = Rc::clone(&self.status);
+        let command_notify = Rc::clone(&self.command_notify);
+        let pointer_hider = Rc::clone(&self.pointer_hider);
+        let on_command_notify = Rc::clone(&self.on_command_notify);
+        let on_command_finished = Rc::clone(&self.on_command_finished);
+        let paster = self.paster();
         glib::timeout_add_local(Duration::from_millis(10), move || {
             while let Ok(msg) = rx.try_recv() {
                 match msg {
                     PtyMessage::Data(data) => {
                         let mut term = terminal_main.lock();
                         let events = term.process(&data);
+                        let mut files_requested = None;
+                        let mut upload_requested = false;
+                        let mut tmux_control = Vec::new();
+                        let mut watch_match = None;
+                        let mut completed_lines = Vec::new();
+                        let mut permission_requests = Vec::new();
 
                         for event in events {
                             match event {
                                 TerminalEvent::ClipboardRequest(op) => {
-                                    if let Some(display) = gdk::Display::default() {
-                                        let clipboard = display.clipboard();
-                                        match op {
-                                            ClipboardOperation::Set { selection: _, data } => {
-                                                if let Ok(text) = String::from_utf8(data) {
-                                                    clipboard.set_text(&text);
-                                                }
-                                            }
-                                            ClipboardOperation::Query { selection } => {
-                                                let terminal_clip = Arc::clone(&terminal_main);
-                                                let sel = selection;
-                                                clipboard.read_text_async(
-                                                    None::<&gio::Cancellable>,
-                                                    move |result| {
-                                                        let text = result
-                                                            .ok()
-                                                            .flatten()
-                                                            .map(|s| s.to_string())
-                                                            .unwrap_or_default();
-                                                        let mut term = terminal_clip.lock();
-                                                        let _ = term.send_clipboard_response(
-                                                            sel,
-                                                            text.as_bytes(),
-                                                        );
-                                                    },
-                                                );
-                                            }
-                                        }
+                                    handle_clipboard(&terminal_main, op);
+                                }
+                                TerminalEvent::Notification(ref notification) => {
+                                    if let Some(ref callback) = *on_notification.borrow() {
+                                        callback(notification);
                                     }
                                 }
+                                TerminalEvent::PermissionRequested(capability) => {
+                                    permission_requests.push(capability);
+                                }
                                 TerminalEvent::Bell => {
                                     if let Some(ref callback) = *on_bell.borrow() {
                                         callback();
@@ -1696,8 +3035,80 @@ impl TerminalWidget {
                                         callback(title);
                                     }
                                 }
-                                TerminalEvent::ContentChanged | TerminalEvent::ProcessExited(_) => {
+                                // Without a title from the shell, show where it is
+                                TerminalEvent::CwdChanged(ref dir) => {
+                                    if term.screen().title.is_empty() && !dir.is_empty() {
+                                        if let Some(ref callback) = *on_title_change.borrow() {
+                                            callback(&dir_title(dir));
+                                        }
+                                    }
+                                }
+                                TerminalEvent::PointerShapeChanged(shape) => {
+                                    if !pointer_hider.borrow().is_hidden() {
+                                        drawing_area
+                                            .set_cursor_from_name(Some(pointer_cursor_name(shape)));
+                                    }
+                                }
+                                TerminalEvent::CommandFinished(finished) => {
+                                    cterm_app::usage_stats::record_command(
+                                        &cterm_app::usage_stats::tab_label(term.screen()),
+                                        &finished,
+                                    );
+                                    let notify = *command_notify.borrow();
+                                    if !notify.should_notify(finished.duration) {
+                                        continue;
+                                    }
+                                    status
+                                        .borrow_mut()
+                                        .show_command_finished(&finished, Instant::now());
+                                    let status = Rc::clone(&status);
+                                    let drawing_area = drawing_area.clone();
+                                    glib::timeout_add_local_once(
+                                        StatusStrip::TOAST_DURATION,
+                                        move || {
+                                            if status.borrow_mut().expire_toast(Instant::now()) {
+                                                drawing_area.queue_draw();
+                                            }
+                                        },
+                                    );
+                                    if let Some(ref callback) = *on_command_finished.borrow() {
+                                        callback(&finished);
+                                    }
+                                    if notify.desktop_when_unfocused {
+                                        if let Some(ref callback) = *on_command_notify.borrow() {
+                                            callback(&command_summary(&finished));
+                                        }
+                                    }
+                                }
+                                TerminalEvent::TransferStarted(protocol, direction) => {
+                                    status
+                                        .borrow_mut()
+                                        .set_transfer(Some((protocol, direction)));
+                                }
+                                TerminalEvent::TransferSendRequested => {
+                                    files_requested = term.file_transfer().map(|(p, _)| p);
+                                }
+                                TerminalEvent::TransferFinished => {
+                                    status.borrow_mut().set_transfer(None);
                                 }
+                                TerminalEvent::UploadRequested => {
+                                    upload_requested = true;
+                                }
+                                TerminalEvent::OutputStats(stats) => {
+                                    status.borrow_mut().set_output_stats(&stats);
+                                }
+                                TerminalEvent::TmuxControl(control) => {
+                                    tmux_control.extend(control);
+                                }
+                                TerminalEvent::WatchMatched(found) => {
+                                    watch_match = Some(found);
+                                }
+                                TerminalEvent::LinesCompleted(lines) => {
+                                    completed_lines.extend(lines);
+                                }
+                                TerminalEvent::ContentChanged
+                                | TerminalEvent::ProcessExited(_)
+                                | TerminalEvent::UserVarChanged { .. } => {}
                             }
                         }
 
@@ -1711,12 +3122,51 @@ impl TerminalWidget {
                         let transfers = term.screen_mut().take_file_transfers();
                         drop(term);
 
+                        if let Some(protocol) = files_requested {
+                            request_transfer_files(
+                                &drawing_area,
+                                &terminal_main,
+                                &status,
+                                protocol,
+                            );
+                        }
+                        if upload_requested {
+                            request_upload(&drawing_area, &paster);
+                        }
+                        for capability in permission_requests {
+                            if let Some(ref callback) = *on_permission.borrow() {
+                                callback(capability);
+                            }
+                        }
+                        if !tmux_control.is_empty() {
+                            if let Some(ref callback) = *on_tmux_control.borrow() {
+                                callback(tmux_control);
+                            }
+                        }
+                        if let Some(found) = watch_match {
+                            if let Some(ref callback) = *on_watch_match.borrow() {
+                                callback(&found);
+                            }
+                        }
+                        if !completed_lines.is_empty() {
+                            let effects =
+                                triggers.borrow_mut().scan(&completed_lines, Instant::now());
+                            for effect in effects {
+                                perform_trigger(&terminal_main, &on_trigger, &effect);
+                            }
+                        }
+
                         for transfer in transfers {
                             if let Some(ref callback) = *on_file_transfer.borrow() {
                                 callback(transfer);
                             }
                         }
 
+                        update_auto_switch(&terminal_main, &auto_switch, &theme, &on_auto_switch);
+                        if let Some(ref callback) = *on_output.borrow() {
+                            callback();
+                        }
+
                         terminal_main.lock().screen_mut().dirty = false;
                         drawing_area.queue_draw();
                     }
@@ -1734,6 +3184,13 @@ impl TerminalWidget {
                     }
                 }
             }
+            // Hide the fast output indicator once output stops
+            let stats = terminal_main.lock().poll_output_stats();
+            if let Some(stats) = stats {
+                if status.borrow_mut().set_output_stats(&stats) {
+                    drawing_area.queue_draw();
+                }
+            }
             glib::ControlFlow::Continue
         });
     }
@@ -1811,8 +3268,30 @@ enum PtyMessage {
     Exited,
 }
 
+/// Sends [`RemoteRequest`]s to a tab's daemon I/O thread
+pub struct RemoteRequester {
+    tx: tokio::sync::mpsc::UnboundedSender<DaemonCommand>,
+}
+
+impl RemoteRequester {
+    /// Queue a request; the reply arrives on the returned receiver.
+    /// Returns `None` once the session's I/O loop has shut down.
+    pub fn request(
+        &self,
+        request: RemoteRequest,
+    ) -> Option<std::sync::mpsc::Receiver<RemoteReply>> {
+        let (reply_tx, reply_rx) = std::sync::mpsc::channel();
+        self.tx
+            .send(DaemonCommand::Remote(request, reply_tx))
+            .ok()?;
+        Some(reply_rx)
+    }
+}
+
 /// Commands sent to the daemon I/O thread
 enum DaemonCommand {
+    /// SFTP operation for the transfer panel; the reply goes back on the sender
+    Remote(RemoteRequest, std::sync::mpsc::Sender<RemoteReply>),
     Write(Vec<u8>),
     Resize(u32, u32),
     /// Kill the remote PTY and shut down the I/O loop.
@@ -1825,300 +3304,252 @@ enum DaemonCommand {
     SetTabColor(String),
     SetTemplateName(String),
     ClearAlert,
+    /// Remove all images and kitty placements from the daemon's screen
+    ClearImages,
+    /// Copy to the daemon's clipboard
+    SetClipboard(Vec<u8>),
 }
 
-/// Rendering parameters for draw_terminal
-struct RenderConfig<'a> {
-    font_family: &'a str,
-    font_size: f64,
-    cell_dims: CellDimensions,
-    background_override: Option<cterm_core::color::Rgb>,
+/// Ask for the files a remote `rz` or `kermit -r` is waiting for and send
+/// them; cancelling the dialog declines the transfer
+fn request_transfer_files(
+    drawing_area: &TerminalArea,
+    terminal: &Arc<Mutex<Terminal>>,
+    status: &Rc<RefCell<StatusStrip>>,
+    protocol: TransferProtocol,
+) {
+    let Some(window) = drawing_area
+        .root()
+        .and_then(|root| root.downcast::<gtk4::Window>().ok())
+    else {
+        terminal.lock().cancel_file_transfer();
+        status.borrow_mut().set_transfer(None);
+        return;
+    };
+
+    let drawing_area = drawing_area.clone();
+    let terminal = Arc::clone(terminal);
+    let status = Rc::clone(status);
+    let title = format!("Send Files ({})", protocol.name());
+    crate::dialogs::show_send_files_dialog(&window, &title, move |paths| {
+        let files = read_files(&paths);
+        let events = terminal.lock().send_transfer_files(files);
+        if events
+            .iter()
+            .any(|e| matches!(e, TerminalEvent::TransferFinished))
+        {
+            status.borrow_mut().set_transfer(None);
+        }
+        drawing_area.queue_draw();
+    });
 }
 
-/// Draw the terminal contents
-fn draw_terminal(
-    cr: &cairo::Context,
+/// Apply `[[auto_switch]]` rules when the shell moves to another host: switch
+/// the theme here and tell the window, which switches the tab color
+fn update_auto_switch(
     terminal: &Arc<Mutex<Terminal>>,
-    theme: &Theme,
-    config: &RenderConfig<'_>,
-    preedit: &PreeditState,
+    auto_switch: &RefCell<AutoSwitch>,
+    theme: &RefCell<Theme>,
+    on_auto_switch: &AutoSwitchCallback,
 ) {
-    let term = terminal.lock();
-    let screen = term.screen();
-    let palette = &theme.colors;
-
-    // Draw background (use override if set, otherwise use theme)
-    let bg = config
-        .background_override
-        .as_ref()
-        .unwrap_or(&palette.background);
-    let (r, g, b) = bg.to_f64();
-    cr.set_source_rgb(r, g, b);
-    cr.paint().ok();
-
-    // Create Pango layout for text rendering
-    let pango_context = pangocairo::functions::create_context(cr);
-    let layout = pango::Layout::new(&pango_context);
-
-    // Set font
-    let font_desc = pango::FontDescription::from_string(&format!(
-        "{} {}",
-        config.font_family, config.font_size
-    ));
-    layout.set_font_description(Some(&font_desc));
-
-    // Use pre-calculated cell dimensions
-    let cell_width = config.cell_dims.width;
-    let cell_height = config.cell_dims.height;
-
-    // Draw cells - use absolute line indices to render scrollback content
-    let grid = screen.grid();
-    let scroll_offset = screen.scroll_offset;
-    let rows = grid.height();
-    let cols = grid.width();
-
-    for row_idx in 0..rows {
-        let y = row_idx as f64 * cell_height;
-        let absolute_line = screen.visible_row_to_absolute_line(row_idx);
-
-        for col_idx in 0..cols {
-            let cell = if let Some(c) = screen.get_cell_with_scrollback(absolute_line, col_idx) {
-                c
-            } else {
-                continue;
-            };
-            let x = col_idx as f64 * cell_width;
-
-            // Skip wide char spacers
-            if cell.attrs.contains(CellAttrs::WIDE_SPACER) {
-                continue;
-            }
-
-            // Check if this cell is selected
-            let is_selected = screen.is_selected(absolute_line, col_idx);
-
-            // Determine if cell has INVERSE attribute (XOR with selection)
-            let is_inverted = cell.attrs.contains(CellAttrs::INVERSE) != is_selected;
-
-            // Draw background (always draw for selected cells to show highlight)
-            let needs_bg = cell.bg != Color::Default || is_inverted || is_selected;
-
-            if needs_bg {
-                let bg_color = if is_inverted {
-                    // Inverted: use foreground color as background
-                    if cell.fg == Color::Default {
-                        palette.foreground
-                    } else {
-                        cell.fg.to_rgb(palette)
-                    }
-                } else {
-                    cell.bg.to_rgb(palette)
-                };
-
-                let (r, g, b) = bg_color.to_f64();
-                cr.set_source_rgb(r, g, b);
-
-                let char_width = if cell.attrs.contains(CellAttrs::WIDE) {
-                    cell_width * 2.0
-                } else {
-                    cell_width
-                };
-
-                cr.rectangle(x, y, char_width, cell_height);
-                cr.fill().ok();
+    let mut state = auto_switch.borrow_mut();
+    let Some(change) = state.switcher.update(terminal.lock().screen()) else {
+        return;
+    };
+    let new_theme = match change {
+        AutoSwitchChange::Apply(ref rule) => {
+            if state.saved_theme.is_none() {
+                state.saved_theme = Some(theme.borrow().clone());
             }
-
-            // Draw character
-            if cell.c != ' ' {
-                let fg_color = if is_inverted {
-                    // Inverted: use background color as foreground
-                    cell.bg.to_rgb(palette)
-                } else if cell.hyperlink.is_some() && cell.fg == Color::Default {
-                    // Cornflower blue for hyperlinks with default fg
-                    Rgb::new(100, 149, 237)
-                } else if cell.fg == Color::Default {
-                    palette.foreground
-                } else {
-                    cell.fg.to_rgb(palette)
-                };
-
-                // Apply dim
-                let fg_color = if cell.attrs.contains(CellAttrs::DIM) {
-                    Rgb::new(
-                        (fg_color.r as f64 * 0.5) as u8,
-                        (fg_color.g as f64 * 0.5) as u8,
-                        (fg_color.b as f64 * 0.5) as u8,
-                    )
-                } else {
-                    fg_color
-                };
-
-                let (r, g, b) = fg_color.to_f64();
-                cr.set_source_rgb(r, g, b);
-
-                // Apply text attributes to font
-                let attrs = pango::AttrList::new();
-
-                if cell.attrs.contains(CellAttrs::BOLD) {
-                    let attr = pango::AttrInt::new_weight(pango::Weight::Bold);
-                    attrs.insert(attr);
-                }
-
-                if cell.attrs.contains(CellAttrs::ITALIC) {
-                    let attr = pango::AttrInt::new_style(pango::Style::Italic);
-                    attrs.insert(attr);
-                }
-
-                if cell.attrs.contains(CellAttrs::UNDERLINE) || cell.hyperlink.is_some() {
-                    let attr = pango::AttrInt::new_underline(pango::Underline::Single);
-                    attrs.insert(attr);
-                    if cell.hyperlink.is_some() {
-                        // Cornflower blue for hyperlinks
-                        let attr =
-                            pango::AttrColor::new_underline_color(100 * 256, 149 * 256, 237 * 256);
-                        attrs.insert(attr);
-                    }
-                }
-
-                if cell.attrs.contains(CellAttrs::STRIKETHROUGH) {
-                    let attr = pango::AttrInt::new_strikethrough(true);
-                    attrs.insert(attr);
+            rule.theme.as_deref().and_then(|name| {
+                let theme = cterm_app::config::find_theme(name);
+                if theme.is_none() {
+                    log::warn!("Auto switch: unknown theme {:?}", name);
                 }
+                theme
+            })
+        }
+        AutoSwitchChange::Revert => state.saved_theme.take(),
+    };
+    drop(state);
 
-                layout.set_attributes(Some(&attrs));
-                layout.set_text(&cell.c.to_string());
+    if let Some(new_theme) = new_theme {
+        terminal
+            .lock()
+            .set_color_scheme(new_theme.colors.color_scheme());
+        *theme.borrow_mut() = new_theme;
+    }
+    if let Some(ref callback) = *on_auto_switch.borrow() {
+        callback(&change);
+    }
+}
 
-                cr.move_to(x, y);
-                pangocairo::functions::show_layout(cr, &layout);
+/// Carry out what a trigger fired for: typing and commands here, the rest
+/// through the window's callback
+fn perform_trigger(
+    terminal: &Arc<Mutex<Terminal>>,
+    on_trigger: &TriggerCallback,
+    effect: &TriggerEffect,
+) {
+    match effect {
+        TriggerEffect::Send(text) => {
+            if let Err(e) = terminal.lock().write_str(text) {
+                log::error!("Failed to write to terminal: {}", e);
+            }
+        }
+        TriggerEffect::Command { program, args } => {
+            if let Err(e) = triggers::run_command(program, args) {
+                log::warn!("Trigger command {:?} failed: {}", program, e);
+            }
+        }
+        TriggerEffect::Notify { .. } | TriggerEffect::TabColor(_) => {
+            if let Some(ref callback) = *on_trigger.borrow() {
+                callback(effect);
+            }
+        }
+    }
+}
 
-                // Reset attributes
-                layout.set_attributes(None::<&pango::AttrList>);
+/// Carry out an allowed OSC 52 clipboard write or read
+fn handle_clipboard(terminal: &Arc<Mutex<Terminal>>, op: ClipboardOperation) {
+    match op {
+        ClipboardOperation::Set { selection: _, data } => {
+            if let (Some(display), Ok(text)) = (gdk::Display::default(), String::from_utf8(data)) {
+                display.clipboard().set_text(&text);
+                clipboard_history::record(&text, ClipSource::Osc52);
             }
         }
+        ClipboardOperation::Query { selection } => send_clipboard(terminal, selection),
     }
+}
 
-    // Draw cursor
-    if screen.modes.show_cursor && scroll_offset == 0 {
-        let cursor = &screen.cursor;
-        let x = cursor.col as f64 * cell_width;
-        let y = cursor.row as f64 * cell_height;
-
-        let (r, g, b) = theme.cursor.color.to_f64();
-        cr.set_source_rgb(r, g, b);
-
-        match cursor.style {
-            CursorStyle::Block => {
-                cr.rectangle(x, y, cell_width, cell_height);
-                cr.fill().ok();
-
-                // Draw character under cursor with inverted color
-                if let Some(cell) = screen.get_cell(cursor.row, cursor.col) {
-                    if cell.c != ' ' {
-                        let (r, g, b) = theme.cursor.text_color.to_f64();
-                        cr.set_source_rgb(r, g, b);
-                        layout.set_text(&cell.c.to_string());
-                        cr.move_to(x, y);
-                        pangocairo::functions::show_layout(cr, &layout);
-                    }
-                }
+/// Answer an OSC 52 read with the clipboard contents
+fn send_clipboard(terminal: &Arc<Mutex<Terminal>>, selection: ClipboardSelection) {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+    let terminal = Arc::clone(terminal);
+    display
+        .clipboard()
+        .read_text_async(None::<&gio::Cancellable>, move |result| {
+            let text = result
+                .ok()
+                .flatten()
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+            if let Err(e) = terminal
+                .lock()
+                .send_clipboard_response(selection, text.as_bytes())
+            {
+                log::error!("Failed to send clipboard contents: {}", e);
             }
-            CursorStyle::Underline => {
-                cr.rectangle(x, y + cell_height - 2.0, cell_width, 2.0);
-                cr.fill().ok();
+        });
+}
+
+/// Ask for files to upload after an OSC 1337 RequestUpload (`it2ul`) and
+/// stream them to the remote; aborts the upload if none are chosen
+fn request_upload(drawing_area: &TerminalArea, paster: &Paster) {
+    let Some(window) = drawing_area
+        .root()
+        .and_then(|root| root.downcast::<gtk4::Window>().ok())
+    else {
+        paster.write(cterm_core::iterm2::UPLOAD_ABORT);
+        return;
+    };
+
+    let paster = paster.clone();
+    crate::dialogs::show_send_files_dialog(&window, "Upload Files", move |paths| {
+        let files = read_files(&paths);
+        if files.is_empty() {
+            paster.write(cterm_core::iterm2::UPLOAD_ABORT);
+            return;
+        }
+        match cterm_core::iterm2::encode_upload(&files) {
+            Ok(data) => paster.upload(data),
+            Err(e) => {
+                log::error!("Failed to encode upload: {}", e);
+                paster.write(cterm_core::iterm2::UPLOAD_ABORT);
             }
-            CursorStyle::Bar => {
-                cr.rectangle(x, y, 2.0, cell_height);
-                cr.fill().ok();
+        }
+    });
+}
+
+/// Read the chosen files, skipping (and logging) any that can't be read
+fn read_files(paths: &[std::path::PathBuf]) -> Vec<TransferFile> {
+    paths
+        .iter()
+        .filter_map(|path| match TransferFile::from_path(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                log::error!("Failed to read {}: {}", path.display(), e);
+                None
             }
+        })
+        .collect()
+}
+
+/// Extract mouse-report modifier bits from a GTK modifier state.
+/// Handle a key press while hint mode is active
+///
+/// Letters narrow down the labels, Backspace undoes one and Escape (or a
+/// letter matching no label) leaves hint mode.
+fn hint_mode_key(
+    hints: &RefCell<Option<HintMode>>,
+    status: &RefCell<StatusStrip>,
+    terminal: &Mutex<Terminal>,
+    path_quoting: PathQuoting,
+    keyval: gdk::Key,
+) {
+    let mut hints = hints.borrow_mut();
+    let Some(mode) = hints.as_mut() else {
+        return;
+    };
+    let input = match keyval {
+        gdk::Key::Escape => HintInput::NoMatch,
+        gdk::Key::BackSpace => {
+            mode.backspace();
+            HintInput::Pending
         }
+        _ => match keyval.to_unicode() {
+            Some(c) if c.is_alphabetic() => mode.input(c),
+            _ => HintInput::Pending,
+        },
+    };
+
+    if let HintInput::Pending = input {
+        status.borrow_mut().set_hints(Some(mode.typed()));
+        return;
     }
-
-    // Draw IM preedit (composition) text at the cursor position
-    if preedit.active && !preedit.text.is_empty() && scroll_offset == 0 {
-        let cursor = &screen.cursor;
-        let x = cursor.col as f64 * cell_width;
-        let y = cursor.row as f64 * cell_height;
-
-        // Draw preedit background
-        let preedit_width = preedit.text.chars().count() as f64 * cell_width;
-        let (r, g, b) = palette.foreground.to_f64();
-        cr.set_source_rgb(r, g, b);
-        cr.rectangle(x, y, preedit_width, cell_height);
-        cr.fill().ok();
-
-        // Draw preedit text
-        let (r, g, b) = palette.background.to_f64();
-        cr.set_source_rgb(r, g, b);
-        layout.set_text(&preedit.text);
-        cr.move_to(x, y);
-        pangocairo::functions::show_layout(cr, &layout);
-
-        // Draw underline to indicate composition
-        let (r, g, b) = palette.foreground.to_f64();
-        cr.set_source_rgb(r, g, b);
-        cr.rectangle(x, y + cell_height - 1.0, preedit_width, 1.0);
-        cr.fill().ok();
-    }
-
-    // Draw scrollbar overlay when there is scrollback content
-    let scrollback_len = screen.scrollback().len();
-    if scrollback_len > 0 {
-        let total_lines = scrollback_len + rows;
-        let view_height = rows as f64 * cell_height;
-        let view_width = cols as f64 * cell_width;
-
-        let bar_width: f64 = 6.0;
-        let bar_inset: f64 = 2.0;
-        let bar_x = view_width - bar_width - bar_inset;
-        let min_thumb_height: f64 = 20.0;
-
-        let thumb_height = (rows as f64 / total_lines as f64 * view_height).max(min_thumb_height);
-
-        let scrollable = view_height - thumb_height;
-        let fraction = screen.scroll_offset as f64 / scrollback_len as f64;
-        // fraction=0 (at bottom) → thumb at bottom, fraction=1 → thumb at top
-        let thumb_y = (1.0 - fraction) * scrollable;
-
-        let opacity = if screen.scroll_offset > 0 { 0.5 } else { 0.25 };
-        let radius = bar_width / 2.0;
-
-        // Draw rounded rect thumb
-        cr.new_sub_path();
-        cr.arc(
-            bar_x + bar_width - radius,
-            thumb_y + radius,
-            radius,
-            -std::f64::consts::FRAC_PI_2,
-            0.0,
-        );
-        cr.arc(
-            bar_x + bar_width - radius,
-            thumb_y + thumb_height - radius,
-            radius,
-            0.0,
-            std::f64::consts::FRAC_PI_2,
-        );
-        cr.arc(
-            bar_x + radius,
-            thumb_y + thumb_height - radius,
-            radius,
-            std::f64::consts::FRAC_PI_2,
-            std::f64::consts::PI,
-        );
-        cr.arc(
-            bar_x + radius,
-            thumb_y + radius,
-            radius,
-            std::f64::consts::PI,
-            3.0 * std::f64::consts::FRAC_PI_2,
-        );
-        cr.close_path();
-        cr.set_source_rgba(0.5, 0.5, 0.5, opacity);
-        cr.fill().ok();
+    *hints = None;
+    status.borrow_mut().set_hints(None);
+
+    let HintInput::Chosen(hint, action) = input else {
+        return;
+    };
+    let cwd = {
+        let term = terminal.lock();
+        term.foreground_cwd()
+            .map(|p| p.to_string_lossy().into_owned())
+            .or_else(|| Some(term.screen().current_dir.clone()).filter(|d| !d.is_empty()))
+    };
+    match (action, hint.open_target(cwd.as_deref())) {
+        (HintAction::Open, Some(target)) => {
+            if let Err(e) = open::that(&target) {
+                log::error!("Failed to open {}: {}", target, e);
+            }
+        }
+        // Hashes have nothing to open, so they are always copied
+        _ => {
+            let text = path_quoting
+                .for_terminal(&terminal.lock())
+                .quote_hint(&hint);
+            if let Some(display) = gdk::Display::default() {
+                display.clipboard().set_text(&text);
+            }
+        }
     }
 }
 
-/// Extract mouse-report modifier bits from a GTK modifier state.
 fn gtk_state_to_mouse_mods(state: gdk::ModifierType) -> MouseModifiers {
     MouseModifiers {
         shift: state.contains(gdk::ModifierType::SHIFT_MASK),
@@ -2127,9 +3558,64 @@ fn gtk_state_to_mouse_mods(state: gdk::ModifierType) -> MouseModifiers {
     }
 }
 
-/// Whether an application has enabled any mouse tracking mode.
+/// Start, redirect or stop selection autoscroll (`direction` 0 stops)
+fn update_autoscroll(
+    autoscroll: &Rc<RefCell<AutoScroll>>,
+    direction: i32,
+    col: usize,
+    lines: usize,
+    terminal: &Arc<Mutex<Terminal>>,
+    drawing_area: &TerminalArea,
+) {
+    let mut state = autoscroll.borrow_mut();
+    state.col = col;
+    if direction == 0 || lines == 0 {
+        state.stop();
+        return;
+    }
+    state.direction = direction;
+    if state.timer.is_some() {
+        return;
+    }
+
+    let autoscroll = Rc::clone(autoscroll);
+    let terminal = Arc::clone(terminal);
+    let drawing_area = drawing_area.clone();
+    state.timer = Some(glib::timeout_add_local(
+        InputConfig::AUTOSCROLL_INTERVAL,
+        move || {
+            let state = autoscroll.borrow();
+            terminal
+                .lock()
+                .autoscroll_selection(state.direction < 0, lines, state.col);
+            drawing_area.queue_draw();
+            glib::ControlFlow::Continue
+        },
+    ));
+}
+
+/// CSS cursor name for the pointer shape a program asked for (OSC 22); the
+/// default over a terminal is the text beam
+fn pointer_cursor_name(shape: PointerShape) -> &'static str {
+    match shape {
+        PointerShape::Default | PointerShape::Text => "text",
+        PointerShape::Arrow => "default",
+        PointerShape::Pointer => "pointer",
+        PointerShape::Crosshair => "crosshair",
+        PointerShape::Wait => "wait",
+        PointerShape::Help => "help",
+        PointerShape::Move => "move",
+        PointerShape::NotAllowed => "not-allowed",
+        PointerShape::ColumnResize => "ew-resize",
+        PointerShape::RowResize => "ns-resize",
+        PointerShape::Hidden => "none",
+    }
+}
+
+/// Whether an application has enabled any mouse tracking mode. A read-only
+/// terminal keeps the mouse for selecting and scrolling.
 fn mouse_tracking_active(term: &Terminal) -> bool {
-    term.screen().modes.mouse_mode != MouseMode::None
+    term.screen().modes.mouse_mode != MouseMode::None && !term.is_input_locked()
 }
 
 /// Encode a mouse event for the current tracking/encoding modes and, if it
@@ -2189,7 +3675,8 @@ fn keyval_to_key(keyval: gdk::Key) -> Option<Key> {
         GK::Insert => Key::Insert,
         GK::Delete => Key::Delete,
         GK::BackSpace => Key::Backspace,
-        GK::Return | GK::KP_Enter => Key::Enter,
+        GK::Return => Key::Enter,
+        GK::KP_Enter => Key::Keypad('\r'),
         GK::Tab | GK::ISO_Left_Tab => Key::Tab,
         GK::Escape => Key::Escape,
         GK::F1 => Key::F(1),
@@ -2204,6 +3691,23 @@ fn keyval_to_key(keyval: gdk::Key) -> Option<Key> {
         GK::F10 => Key::F(10),
         GK::F11 => Key::F(11),
         GK::F12 => Key::F(12),
+        GK::KP_0 => Key::Keypad('0'),
+        GK::KP_1 => Key::Keypad('1'),
+        GK::KP_2 => Key::Keypad('2'),
+        GK::KP_3 => Key::Keypad('3'),
+        GK::KP_4 => Key::Keypad('4'),
+        GK::KP_5 => Key::Keypad('5'),
+        GK::KP_6 => Key::Keypad('6'),
+        GK::KP_7 => Key::Keypad('7'),
+        GK::KP_8 => Key::Keypad('8'),
+        GK::KP_9 => Key::Keypad('9'),
+        GK::KP_Decimal => Key::Keypad('.'),
+        GK::KP_Separator => Key::Keypad(','),
+        GK::KP_Add => Key::Keypad('+'),
+        GK::KP_Subtract => Key::Keypad('-'),
+        GK::KP_Multiply => Key::Keypad('*'),
+        GK::KP_Divide => Key::Keypad('/'),
+        GK::KP_Equal => Key::Keypad('='),
         _ => return None,
     })
 }
@@ -0,0 +1,393 @@
+//! Remote file transfer panel for SSH sessions
+//!
+//! Lists the remote working directory over SFTP, through the daemon that owns
+//! the tab's SSH connection, so no second login is needed. Dropping local
+//! files onto the list uploads them; dragging a remote file out (or
+//! activating it) downloads it.
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Duration;
+
+use gtk4::prelude::*;
+use gtk4::{gdk, gio, glib, Align, Label, ListBox, Orientation, ScrolledWindow, Window};
+
+use cterm_app::file_drop::format_size;
+use cterm_core::{join_remote_path, parent_remote_path};
+use cterm_proto::proto::RemoteFileEntry;
+
+use crate::terminal_widget::RemoteRequester;
+
+/// How often pending replies from the daemon I/O thread are polled
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a drag-out waits for its download before giving up
+const DRAG_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A remote file operation, run on the tab's daemon I/O thread
+pub enum RemoteRequest {
+    /// List a directory (empty = the shell's reported working directory)
+    List(String),
+    Upload {
+        local: PathBuf,
+        remote: String,
+    },
+    Download {
+        remote: String,
+        local: PathBuf,
+    },
+}
+
+/// Result of a [`RemoteRequest`]
+pub enum RemoteReply {
+    Listing {
+        path: String,
+        entries: Vec<RemoteFileEntry>,
+    },
+    Transferred {
+        name: String,
+        bytes: u64,
+    },
+    Failed(String),
+}
+
+/// Run a request against the daemon session.
+pub(crate) async fn run_remote_request(
+    session: &cterm_client::SessionHandle,
+    request: RemoteRequest,
+) -> RemoteReply {
+    match request {
+        RemoteRequest::List(path) => match session.list_remote_dir(&path).await {
+            Ok(listing) => RemoteReply::Listing {
+                path: listing.path,
+                entries: listing.entries,
+            },
+            Err(e) => failure(e),
+        },
+        RemoteRequest::Upload { local, remote } => {
+            match session.upload_file(&local, &remote).await {
+                Ok(bytes) => RemoteReply::Transferred {
+                    name: file_name(&remote),
+                    bytes,
+                },
+                Err(e) => failure(e),
+            }
+        }
+        RemoteRequest::Download { remote, local } => {
+            match session.download_file(&remote, &local).await {
+                Ok(bytes) => RemoteReply::Transferred {
+                    name: file_name(&remote),
+                    bytes,
+                },
+                Err(e) => failure(e),
+            }
+        }
+    }
+}
+
+/// Daemon errors carry a readable message; skip the gRPC framing around it
+fn failure(e: cterm_client::ClientError) -> RemoteReply {
+    match e {
+        cterm_client::ClientError::Grpc(status) => RemoteReply::Failed(status.message().into()),
+        other => RemoteReply::Failed(other.to_string()),
+    }
+}
+
+/// Panel state shared by the widget callbacks
+struct PanelState {
+    /// Channel to the tab's daemon session (None for local PTY tabs)
+    requester: Option<RemoteRequester>,
+    /// Absolute path of the listed directory
+    path: String,
+    /// Entries in list order (after the ".." row, when present)
+    entries: Vec<RemoteFileEntry>,
+    list: ListBox,
+    path_label: Label,
+    status_label: Label,
+}
+
+impl PanelState {
+    fn request(&self, request: RemoteRequest) -> Option<Receiver<RemoteReply>> {
+        self.requester.as_ref()?.request(request)
+    }
+
+    /// Entry shown at a list row index, or `None` for the ".." row
+    fn entry_at(&self, index: i32) -> Option<&RemoteFileEntry> {
+        let offset = if self.path == "/" { 0 } else { 1 };
+        usize::try_from(index - offset)
+            .ok()
+            .and_then(|i| self.entries.get(i))
+    }
+}
+
+/// Show the transfer panel for a tab.
+pub fn show_transfer_panel(parent: &impl IsA<Window>, requester: Option<RemoteRequester>) {
+    let window = Window::builder()
+        .title("Transfer Files")
+        .transient_for(parent)
+        .default_width(420)
+        .default_height(480)
+        .build();
+
+    let vbox = gtk4::Box::new(Orientation::Vertical, 6);
+    vbox.set_margin_top(8);
+    vbox.set_margin_bottom(8);
+    vbox.set_margin_start(8);
+    vbox.set_margin_end(8);
+
+    let path_label = Label::new(None);
+    path_label.set_halign(Align::Start);
+    path_label.set_ellipsize(gtk4::pango::EllipsizeMode::Start);
+    path_label.add_css_class("heading");
+    vbox.append(&path_label);
+
+    let list = ListBox::new();
+    list.set_activate_on_single_click(false);
+    let scroll = ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_child(Some(&list));
+    vbox.append(&scroll);
+
+    let status_label = Label::new(Some("Drop files here to upload"));
+    status_label.set_halign(Align::Start);
+    status_label.add_css_class("dim-label");
+    vbox.append(&status_label);
+
+    window.set_child(Some(&vbox));
+
+    let state = Rc::new(RefCell::new(PanelState {
+        requester,
+        path: String::new(),
+        entries: Vec::new(),
+        list: list.clone(),
+        path_label,
+        status_label,
+    }));
+
+    // Activate: enter directories, download files
+    {
+        let state = Rc::clone(&state);
+        list.connect_row_activated(move |_, row| {
+            let index = row.index();
+            let (target, is_dir) = {
+                let s = state.borrow();
+                match s.entry_at(index) {
+                    Some(entry) => (join_remote_path(&s.path, &entry.name), entry.is_dir),
+                    None => (parent_remote_path(&s.path), true),
+                }
+            };
+            if is_dir {
+                request_listing(&state, target);
+            } else {
+                download_to_downloads(&state, target);
+            }
+        });
+    }
+
+    // Drop local files to upload them into the listed directory
+    {
+        let drop_target =
+            gtk4::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+        let state = Rc::clone(&state);
+        drop_target.connect_drop(move |_, value, _, _| {
+            let Ok(files) = value.get::<gdk::FileList>() else {
+                return false;
+            };
+            for path in files.files().iter().filter_map(|f| f.path()) {
+                if path.is_file() {
+                    upload(&state, path);
+                }
+            }
+            true
+        });
+        list.add_controller(drop_target);
+    }
+
+    // Drag a remote file out to download it
+    {
+        let drag_source = gtk4::DragSource::new();
+        drag_source.set_actions(gdk::DragAction::COPY);
+        let state = Rc::clone(&state);
+        drag_source.connect_prepare(move |_, _, y| {
+            let s = state.borrow();
+            let row = s.list.row_at_y(y as i32)?;
+            let entry = s.entry_at(row.index()).filter(|e| !e.is_dir)?;
+            let remote = join_remote_path(&s.path, &entry.name);
+            let local = std::env::temp_dir()
+                .join("cterm-transfer")
+                .join(&entry.name);
+            if let Some(dir) = local.parent() {
+                if let Err(e) = std::fs::create_dir_all(dir) {
+                    log::error!("Failed to create transfer directory: {}", e);
+                    return None;
+                }
+            }
+            let rx = s.request(RemoteRequest::Download {
+                remote,
+                local: local.clone(),
+            })?;
+            // The drop target reads the file as soon as it is dropped, so the
+            // download has to finish before the drag can start.
+            match rx.recv_timeout(DRAG_DOWNLOAD_TIMEOUT) {
+                Ok(RemoteReply::Transferred { .. }) => {
+                    let file = gio::File::for_path(&local);
+                    Some(gdk::ContentProvider::for_value(&file.to_value()))
+                }
+                Ok(RemoteReply::Failed(e)) => {
+                    s.status_label.set_text(&format!("Download failed: {e}"));
+                    None
+                }
+                _ => None,
+            }
+        });
+        list.add_controller(drag_source);
+    }
+
+    request_listing(&state, String::new());
+    window.present();
+}
+
+/// List `path` (empty = the shell's working directory) and refill the panel.
+fn request_listing(state: &Rc<RefCell<PanelState>>, path: String) {
+    let Some(rx) = state.borrow().request(RemoteRequest::List(path)) else {
+        state
+            .borrow()
+            .status_label
+            .set_text("File transfer is only available for SSH sessions");
+        return;
+    };
+    let state = Rc::clone(state);
+    poll_reply(rx, move |reply| match reply {
+        RemoteReply::Listing { path, entries } => {
+            let mut s = state.borrow_mut();
+            s.path_label.set_text(&path);
+            s.path = path;
+            s.entries = entries;
+            fill_list(&s);
+        }
+        RemoteReply::Failed(e) => state.borrow().status_label.set_text(&e),
+        RemoteReply::Transferred { .. } => {}
+    });
+}
+
+fn upload(state: &Rc<RefCell<PanelState>>, local: PathBuf) {
+    let name = local
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (rx, dir) = {
+        let s = state.borrow();
+        let remote = join_remote_path(&s.path, &name);
+        let rx = s.request(RemoteRequest::Upload { local, remote });
+        (rx, s.path.clone())
+    };
+    let Some(rx) = rx else {
+        return;
+    };
+    state
+        .borrow()
+        .status_label
+        .set_text(&format!("Uploading {name}…"));
+    let state = Rc::clone(state);
+    poll_reply(rx, move |reply| {
+        report_transfer(&state, "Uploaded", reply);
+        // Refresh if the user is still looking at the target directory
+        if state.borrow().path == dir {
+            request_listing(&state, dir);
+        }
+    });
+}
+
+fn download_to_downloads(state: &Rc<RefCell<PanelState>>, remote: String) {
+    let Some(dir) = cterm_app::file_transfer::dirs::download_dir() else {
+        return;
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::error!("Failed to create download directory: {}", e);
+        return;
+    }
+    let name = file_name(&remote);
+    let local = dir.join(&name);
+    let Some(rx) = state
+        .borrow()
+        .request(RemoteRequest::Download { remote, local })
+    else {
+        return;
+    };
+    state
+        .borrow()
+        .status_label
+        .set_text(&format!("Downloading {name}…"));
+    let state = Rc::clone(state);
+    poll_reply(rx, move |reply| {
+        report_transfer(&state, "Downloaded", reply)
+    });
+}
+
+fn report_transfer(state: &Rc<RefCell<PanelState>>, verb: &str, reply: RemoteReply) {
+    let text = match reply {
+        RemoteReply::Transferred { name, bytes } => {
+            format!("{verb} {name} ({})", format_size(bytes))
+        }
+        RemoteReply::Failed(e) => format!("Transfer failed: {e}"),
+        RemoteReply::Listing { .. } => return,
+    };
+    state.borrow().status_label.set_text(&text);
+}
+
+fn fill_list(state: &PanelState) {
+    while let Some(row) = state.list.row_at_index(0) {
+        state.list.remove(&row);
+    }
+    if state.path != "/" {
+        state.list.append(&entry_row("..", "", true));
+    }
+    for entry in &state.entries {
+        let size = if entry.is_dir {
+            String::new()
+        } else {
+            format_size(entry.size)
+        };
+        state
+            .list
+            .append(&entry_row(&entry.name, &size, entry.is_dir));
+    }
+}
+
+fn entry_row(name: &str, size: &str, is_dir: bool) -> gtk4::Box {
+    let row = gtk4::Box::new(Orientation::Horizontal, 8);
+    row.set_margin_top(2);
+    row.set_margin_bottom(2);
+    let icon = if is_dir { "folder" } else { "text-x-generic" };
+    row.append(&gtk4::Image::from_icon_name(icon));
+    let name_label = Label::new(Some(name));
+    name_label.set_halign(Align::Start);
+    name_label.set_hexpand(true);
+    name_label.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+    row.append(&name_label);
+    let size_label = Label::new(Some(size));
+    size_label.add_css_class("dim-label");
+    row.append(&size_label);
+    row
+}
+
+/// Poll `rx` on the main loop and hand the reply to `on_reply`.
+fn poll_reply(rx: Receiver<RemoteReply>, on_reply: impl FnOnce(RemoteReply) + 'static) {
+    let mut on_reply = Some(on_reply);
+    glib::timeout_add_local(POLL_INTERVAL, move || match rx.try_recv() {
+        Ok(reply) => {
+            if let Some(f) = on_reply.take() {
+                f(reply);
+            }
+            glib::ControlFlow::Break
+        }
+        Err(TryRecvError::Empty) => glib::ControlFlow::Continue,
+        Err(TryRecvError::Disconnected) => glib::ControlFlow::Break,
+    });
+}
+
+fn file_name(remote: &str) -> String {
+    remote.rsplit('/').next().unwrap_or(remote).to_string()
+}
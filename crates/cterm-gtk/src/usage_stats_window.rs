@@ -0,0 +1,72 @@
+//! Usage Statistics window
+//!
+//! Shows the local usage statistics collected by `cterm_app::usage_stats`.
+
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Button, Orientation, ScrolledWindow, TextView, Window};
+
+/// Show the usage statistics window
+pub fn show_usage_stats(parent: &impl IsA<Window>) {
+    let window = Window::builder()
+        .title("Usage Statistics")
+        .transient_for(parent)
+        .default_width(640)
+        .default_height(560)
+        .build();
+
+    let text_view = TextView::new();
+    text_view.set_editable(false);
+    text_view.set_cursor_visible(false);
+    text_view.set_monospace(true);
+    text_view.set_wrap_mode(gtk4::WrapMode::None);
+    text_view.set_left_margin(8);
+    text_view.set_right_margin(8);
+    text_view.set_top_margin(8);
+    text_view.set_bottom_margin(8);
+    text_view
+        .buffer()
+        .set_text(&cterm_app::usage_stats::report());
+
+    let scroll = ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_hexpand(true);
+    scroll.set_child(Some(&text_view));
+
+    let refresh_button = Button::with_label("Refresh");
+    {
+        let text_view = text_view.clone();
+        refresh_button.connect_clicked(move |_| {
+            text_view
+                .buffer()
+                .set_text(&cterm_app::usage_stats::report());
+        });
+    }
+
+    let clear_button = Button::with_label("Clear Statistics");
+    {
+        let text_view = text_view.clone();
+        clear_button.connect_clicked(move |_| {
+            if let Err(e) = cterm_app::usage_stats::clear() {
+                log::error!("Failed to clear usage statistics: {}", e);
+            }
+            text_view
+                .buffer()
+                .set_text(&cterm_app::usage_stats::report());
+        });
+    }
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 8);
+    buttons.set_halign(gtk4::Align::End);
+    buttons.set_margin_top(8);
+    buttons.set_margin_bottom(8);
+    buttons.set_margin_end(8);
+    buttons.append(&clear_button);
+    buttons.append(&refresh_button);
+
+    let vbox = GtkBox::new(Orientation::Vertical, 0);
+    vbox.append(&scroll);
+    vbox.append(&buttons);
+
+    window.set_child(Some(&vbox));
+    window.present();
+}
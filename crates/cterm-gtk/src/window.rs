@@ -1,7 +1,9 @@
 //! Main window implementation
 
 use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::rc::Rc;
+use std::sync::Arc;
 
 use gtk4::prelude::*;
 use gtk4::{
@@ -9,16 +11,30 @@ use gtk4::{
     Orientation, PopoverMenuBar,
 };
 
-use cterm_app::config::Config;
+use cterm_app::auto_switch::AutoSwitchChange;
+use cterm_app::closed_tabs::{self, ClosedTab};
+use cterm_app::config::{Config, ProfileConfig, TabBarPosition};
 use cterm_app::file_transfer::PendingFileManager;
+use cterm_app::live_config;
+use cterm_app::session::{
+    self, PaneTree, SplitAxis, WindowGeometry, WindowSessionState, PANE_RESIZE_STEP,
+};
 use cterm_app::shortcuts::ShortcutManager;
+use cterm_app::sounds::{self, Cue};
+use cterm_app::status_segments;
+use cterm_app::tmux::{PaneId, TmuxEvent, TmuxGateway};
+use cterm_app::triggers::TriggerEffect;
+use cterm_app::workspace::{self, WorkspacePane, WorkspaceTab};
+use cterm_core::screen::TmuxControl;
 use cterm_ui::events::{Action, KeyCode, Modifiers};
+use cterm_ui::prompt::{Prompt, PromptAction};
 use cterm_ui::theme::Theme;
 
 use crate::dialogs;
 use crate::docker_dialog::{self, DockerSelection};
 use crate::menu;
 use crate::notification_bar::NotificationBar;
+use crate::panes;
 use crate::quick_open::QuickOpenOverlay;
 use crate::tab_bar::TabBar;
 use crate::terminal_widget::{CellDimensions, TerminalWidget};
@@ -27,7 +43,12 @@ use crate::terminal_widget::{CellDimensions, TerminalWidget};
 struct TabEntry {
     id: u64,
     title: String,
+    /// Terminal of the focused pane
     terminal: TerminalWidget,
+    /// Every pane of the tab, the first one having the tab's id
+    panes: Vec<PaneEntry>,
+    /// Layout and focus of the panes
+    pane_tree: PaneTree,
     /// Whether title was explicitly set (locks out OSC updates)
     title_locked: bool,
     /// Tab color override
@@ -41,6 +62,33 @@ struct TabEntry {
     /// can be torn down via the right-click "Disconnect" menu item, which kills
     /// the shared SSH tunnel and removes every tab with the same name.
     remote_name: Option<String>,
+    /// Name of the tab template the tab was opened from
+    template: Option<String>,
+}
+
+/// A split pane's terminal
+struct PaneEntry {
+    id: u64,
+    terminal: TerminalWidget,
+    /// Daemon session ID (for upgrade state preservation)
+    session_id: Option<String>,
+}
+
+impl TabEntry {
+    fn pane(&self, id: u64) -> Option<&PaneEntry> {
+        self.panes.iter().find(|p| p.id == id)
+    }
+
+    /// Tell each pane which pane scrolls together with it
+    fn sync_scroll_peers(&self) {
+        for pane in &self.panes {
+            let peer = self
+                .pane_tree
+                .scroll_partner(pane.id)
+                .and_then(|id| self.pane(id));
+            pane.terminal.set_scroll_peer(peer.map(|p| &p.terminal));
+        }
+    }
 }
 
 /// Main window container
@@ -59,6 +107,8 @@ pub struct CtermWindow {
     file_manager: Rc<RefCell<PendingFileManager>>,
     quick_open: QuickOpenOverlay,
     remote_manager: cterm_client::RemoteManager,
+    /// Whether presentation mode is on for this window
+    presenting: Rc<RefCell<bool>>,
 }
 
 /// Show an error dialog when a seamless upgrade fails.
@@ -92,17 +142,27 @@ impl CtermWindow {
             .default_width(default_width)
             .default_height(default_height)
             .build();
+        if config.appearance.is_translucent(theme) {
+            window.add_css_class("translucent");
+        }
 
         // Create the main container
         let main_box = GtkBox::new(Orientation::Vertical, 0);
 
         // Create menu bar
-        let menu_model = menu::create_menu_model_with_options(config.general.show_debug_menu);
+        let menu_model = menu::create_menu_model_with_options(
+            config.general.show_debug_menu,
+            &config.profiles,
+            &config.workspaces,
+        );
         let menu_bar = PopoverMenuBar::from_model(Some(&menu_model));
         main_box.append(&menu_bar);
 
         // Create tab bar
         let tab_bar = TabBar::new();
+        tab_bar.set_widgets(config.widgets);
+        tab_bar.set_config(&config.tabs);
+        tab_bar.set_profiles(&config.profiles);
         main_box.append(tab_bar.widget());
 
         // Create notification bar for file transfers (initially hidden)
@@ -122,6 +182,7 @@ impl CtermWindow {
             .build();
 
         main_box.append(&notebook);
+        place_tab_bar(&tab_bar, config.tabs.tab_bar_position, &menu_bar, &notebook);
 
         window.set_child(Some(&main_box));
 
@@ -146,6 +207,7 @@ impl CtermWindow {
             file_manager,
             quick_open,
             remote_manager: cterm_client::RemoteManager::new(),
+            presenting: Rc::new(RefCell::new(false)),
         };
 
         // Set up window actions
@@ -160,6 +222,9 @@ impl CtermWindow {
         // Set up window focus handler to clear bell on focus
         cterm_window.setup_focus_handler();
 
+        // Restyle open terminals when preferences are applied
+        cterm_window.setup_live_appearance();
+
         // Set up terminal focus restoration after menu interactions
         cterm_window.setup_terminal_focus_restore();
 
@@ -181,6 +246,12 @@ impl CtermWindow {
         // Set up close request handler for process confirmation
         cterm_window.setup_close_request_handler();
 
+        // Remember the window's tabs for session restore
+        cterm_window.setup_session_save();
+
+        // Keep status segments in tab titles current
+        cterm_window.setup_status_segments();
+
         cterm_window
     }
 
@@ -203,14 +274,24 @@ impl CtermWindow {
             .default_width(default_width)
             .default_height(default_height)
             .build();
+        if config.appearance.is_translucent(theme) {
+            window.add_css_class("translucent");
+        }
 
         let main_box = GtkBox::new(Orientation::Vertical, 0);
 
-        let menu_model = menu::create_menu_model_with_options(config.general.show_debug_menu);
+        let menu_model = menu::create_menu_model_with_options(
+            config.general.show_debug_menu,
+            &config.profiles,
+            &config.workspaces,
+        );
         let menu_bar = PopoverMenuBar::from_model(Some(&menu_model));
         main_box.append(&menu_bar);
 
         let tab_bar = TabBar::new();
+        tab_bar.set_widgets(config.widgets);
+        tab_bar.set_config(&config.tabs);
+        tab_bar.set_profiles(&config.profiles);
         main_box.append(tab_bar.widget());
 
         let notification_bar = NotificationBar::new();
@@ -227,6 +308,7 @@ impl CtermWindow {
             .build();
 
         main_box.append(&notebook);
+        place_tab_bar(&tab_bar, config.tabs.tab_bar_position, &menu_bar, &notebook);
         window.set_child(Some(&main_box));
 
         let shortcuts = ShortcutManager::from_config(&config.shortcuts);
@@ -248,12 +330,16 @@ impl CtermWindow {
             file_manager,
             quick_open,
             remote_manager: cterm_client::RemoteManager::new(),
+            presenting: Rc::new(RefCell::new(false)),
         };
 
         cterm_window.setup_actions();
         cterm_window.setup_quick_open();
         cterm_window.setup_key_handler();
         cterm_window.setup_focus_handler();
+
+        // Restyle open terminals when preferences are applied
+        cterm_window.setup_live_appearance();
         cterm_window.setup_terminal_focus_restore();
         cterm_window.setup_notification_bar();
 
@@ -263,7 +349,45 @@ impl CtermWindow {
         cterm_window.setup_tab_bar_callbacks();
         cterm_window.setup_tab_switch_handler();
         cterm_window.setup_close_request_handler();
+        cterm_window.setup_session_save();
+        cterm_window.setup_status_segments();
+
+        cterm_window
+    }
 
+    /// Create a window with the tabs saved in a previous session
+    pub fn restore(
+        app: &Application,
+        config: &Config,
+        theme: &Theme,
+        state: &WindowSessionState,
+    ) -> Self {
+        let cterm_window = Self::new_empty(app, config, theme);
+        let geometry = &state.geometry;
+        if geometry.width > 0 && geometry.height > 0 {
+            cterm_window
+                .window
+                .set_default_size(geometry.width as i32, geometry.height as i32);
+        }
+        if geometry.maximized {
+            cterm_window.window.maximize();
+        }
+        for tab in &state.tabs {
+            open_closed_tab(
+                &cterm_window.notebook,
+                &cterm_window.tabs,
+                &cterm_window.next_tab_id,
+                &cterm_window.config,
+                &cterm_window.theme,
+                &cterm_window.tab_bar,
+                &cterm_window.window,
+                &cterm_window.has_bell,
+                &cterm_window.file_manager,
+                &cterm_window.notification_bar,
+                &cterm_window.remote_manager,
+                tab.to_closed_tab(),
+            );
+        }
         cterm_window
     }
 
@@ -291,10 +415,12 @@ impl CtermWindow {
             let has_bell = Rc::clone(&has_bell);
             let file_manager = Rc::clone(&self.file_manager);
             let notification_bar = self.notification_bar.clone();
-            let action = gio::SimpleAction::new("new-tab", None);
-            action.connect_activate(move |_, _| {
+            // Opens in `directory`, or else where the active tab is
+            let open_tab = Rc::new(move |directory: Option<String>| {
                 // Get info from the active terminal
-                let (cwd, daemon_socket) = {
+                let (cwd, daemon_socket) = if directory.is_some() {
+                    (directory, None)
+                } else {
                     let tabs_borrow = tabs.borrow();
                     if let Some(page_idx) = notebook.current_page() {
                         let entry = tabs_borrow.get(page_idx as usize);
@@ -324,6 +450,66 @@ impl CtermWindow {
                     daemon_socket,
                 );
             });
+
+            let action = gio::SimpleAction::new("new-tab", None);
+            let new_tab = Rc::clone(&open_tab);
+            action.connect_activate(move |_, _| new_tab(None));
+            window.add_action(&action);
+
+            // `cterm new-tab --directory`
+            let action = gio::SimpleAction::new("new-tab-in", Some(glib::VariantTy::STRING));
+            action.connect_activate(move |_, directory| {
+                open_tab(directory.and_then(|d| d.get::<String>()));
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let notebook = notebook.clone();
+            let tabs = Rc::clone(&tabs);
+            let next_tab_id = Rc::clone(&next_tab_id);
+            let config = Rc::clone(&config);
+            let theme = theme.clone();
+            let tab_bar = tab_bar.clone();
+            let window_clone = window.clone();
+            let has_bell = Rc::clone(&has_bell);
+            let file_manager = Rc::clone(&self.file_manager);
+            let notification_bar = self.notification_bar.clone();
+            // File > New Tab with Profile, by index into `[[profiles]]`
+            let action = gio::SimpleAction::new("new-profile-tab", Some(glib::VariantTy::STRING));
+            action.connect_activate(move |_, param| {
+                let profile = param
+                    .and_then(|p| p.get::<String>())
+                    .and_then(|idx| idx.parse::<usize>().ok())
+                    .and_then(|idx| config.borrow().profiles.get(idx).cloned());
+                if let Some(profile) = profile {
+                    create_profile_tab(
+                        &notebook,
+                        &tabs,
+                        &next_tab_id,
+                        &config,
+                        &theme,
+                        &tab_bar,
+                        &window_clone,
+                        &has_bell,
+                        &file_manager,
+                        &notification_bar,
+                        profile,
+                    );
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            // File > Workspaces and `cterm --workspace`, by name
+            let opener = self.workspace_opener();
+            let action = gio::SimpleAction::new("open-workspace", Some(glib::VariantTy::STRING));
+            action.connect_activate(move |_, name| {
+                if let Some(name) = name.and_then(|n| n.get::<String>()) {
+                    opener.open(&name);
+                }
+            });
             window.add_action(&action);
         }
 
@@ -367,6 +553,37 @@ impl CtermWindow {
             window.add_action(&action);
         }
 
+        {
+            let notebook = notebook.clone();
+            let tabs = Rc::clone(&tabs);
+            let next_tab_id = Rc::clone(&next_tab_id);
+            let config = Rc::clone(&config);
+            let theme = theme.clone();
+            let tab_bar = tab_bar.clone();
+            let window_clone = window.clone();
+            let has_bell = Rc::clone(&has_bell);
+            let file_manager = Rc::clone(&self.file_manager);
+            let notification_bar = self.notification_bar.clone();
+            let remote_manager = self.remote_manager.clone();
+            let action = gio::SimpleAction::new("reopen-closed-tab", None);
+            action.connect_activate(move |_, _| {
+                reopen_closed_tab(
+                    &notebook,
+                    &tabs,
+                    &next_tab_id,
+                    &config,
+                    &theme,
+                    &tab_bar,
+                    &window_clone,
+                    &has_bell,
+                    &file_manager,
+                    &notification_bar,
+                    &remote_manager,
+                );
+            });
+            window.add_action(&action);
+        }
+
         {
             let window_clone = window.clone();
             let action = gio::SimpleAction::new("quit", None);
@@ -451,6 +668,55 @@ impl CtermWindow {
             window.add_action(&action);
         }
 
+        // New Serial Tab action
+        {
+            let window_clone = window.clone();
+            let notebook = notebook.clone();
+            let tabs = Rc::clone(&tabs);
+            let next_tab_id = Rc::clone(&next_tab_id);
+            let config = Rc::clone(&config);
+            let theme = theme.clone();
+            let tab_bar = tab_bar.clone();
+            let has_bell = Rc::clone(&has_bell);
+            let file_manager = Rc::clone(&self.file_manager);
+            let notification_bar = self.notification_bar.clone();
+            let remote_manager = self.remote_manager.clone();
+            let action = gio::SimpleAction::new("serial-connect", None);
+            action.connect_activate(move |_, _| {
+                let notebook = notebook.clone();
+                let tabs = Rc::clone(&tabs);
+                let next_tab_id = Rc::clone(&next_tab_id);
+                let config = Rc::clone(&config);
+                let theme = theme.clone();
+                let tab_bar = tab_bar.clone();
+                let window_for_tab = window_clone.clone();
+                let has_bell = Rc::clone(&has_bell);
+                let file_manager = Rc::clone(&file_manager);
+                let notification_bar = notification_bar.clone();
+                let remote_manager = remote_manager.clone();
+                crate::serial_dialog::show_serial_dialog(&window_clone, move |serial| {
+                    let template = cterm_app::config::StickyTabConfig::serial(serial);
+                    create_tab_from_template(
+                        &notebook,
+                        &tabs,
+                        &next_tab_id,
+                        &config,
+                        &theme,
+                        &tab_bar,
+                        &window_for_tab,
+                        &has_bell,
+                        &file_manager,
+                        &notification_bar,
+                        &template,
+                        &remote_manager,
+                        None,
+                        None,
+                    );
+                });
+            });
+            window.add_action(&action);
+        }
+
         // Session actions (daemon attach)
         {
             let notebook = notebook.clone();
@@ -544,7 +810,7 @@ impl CtermWindow {
 
                         let tab_id = generate_tab_id(&next_tab_id);
                         let page_num =
-                            notebook.append_page(terminal.widget(), None::<&gtk4::Widget>);
+                            notebook.append_page(&panes::page(&terminal), None::<&gtk4::Widget>);
                         tab_bar.add_tab(tab_id, &title);
 
                         setup_tab_callbacks(
@@ -673,7 +939,7 @@ impl CtermWindow {
                             if let Some(page_idx) = notebook_paste.current_page() {
                                 let tabs = tabs_paste.borrow();
                                 if let Some(tab) = tabs.get(page_idx as usize) {
-                                    tab.terminal.write_str(&text);
+                                    tab.terminal.paste(&text);
                                 }
                             }
                         }
@@ -699,6 +965,51 @@ impl CtermWindow {
             window.add_action(&action);
         }
 
+        for (name, next) in [("previous-prompt", false), ("next-prompt", true)] {
+            let notebook = notebook.clone();
+            let tabs = Rc::clone(&tabs);
+            let action = gio::SimpleAction::new(name, None);
+            action.connect_activate(move |_, _| {
+                if let Some(page_idx) = notebook.current_page() {
+                    let tabs = tabs.borrow();
+                    if let Some(tab) = tabs.get(page_idx as usize) {
+                        tab.terminal.scroll_to_prompt(next);
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let notebook = notebook.clone();
+            let tabs = Rc::clone(&tabs);
+            let action = gio::SimpleAction::new("paste-history", None);
+            action.connect_activate(move |_, _| {
+                if let Some(page_idx) = notebook.current_page() {
+                    let tabs = tabs.borrow();
+                    if let Some(tab) = tabs.get(page_idx as usize) {
+                        tab.terminal.show_paste_history();
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let notebook = notebook.clone();
+            let tabs = Rc::clone(&tabs);
+            let action = gio::SimpleAction::new("select-command-output", None);
+            action.connect_activate(move |_, _| {
+                if let Some(page_idx) = notebook.current_page() {
+                    let tabs = tabs.borrow();
+                    if let Some(tab) = tabs.get(page_idx as usize) {
+                        tab.terminal.select_command_output();
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
         // Terminal menu actions
         {
             let window_clone = window.clone();
@@ -766,93 +1077,369 @@ impl CtermWindow {
         }
 
         {
-            let action =
-                gio::SimpleAction::new("set-encoding", Some(&glib::VariantType::new("s").unwrap()));
-            action.connect_activate(|_, param| {
-                if let Some(encoding) = param.and_then(|p| p.get::<String>()) {
-                    if encoding == "utf8" {
-                        log::info!("Encoding set to UTF-8");
-                    } else {
-                        // Terminal currently only supports UTF-8
-                        log::warn!(
-                            "Encoding '{}' requested but only UTF-8 is currently supported",
-                            encoding
-                        );
-                    }
-                }
-            });
-            window.add_action(&action);
-        }
-
-        {
-            let tabs = Rc::clone(&tabs);
-            let notebook = notebook.clone();
-            let action =
-                gio::SimpleAction::new("send-signal", Some(&glib::VariantType::new("s").unwrap()));
-            action.connect_activate(move |_, param| {
-                if let Some(signal_str) = param.and_then(|p| p.get::<String>()) {
-                    if let Ok(signal) = signal_str.parse::<i32>() {
-                        if let Some(page_idx) = notebook.current_page() {
-                            let tabs = tabs.borrow();
-                            if let Some(tab) = tabs.get(page_idx as usize) {
-                                log::info!("Sending signal {} to terminal", signal);
-                                tab.terminal.send_signal(signal);
-                            }
-                        }
-                    }
-                }
-            });
-            window.add_action(&action);
-        }
-
-        {
+            let window_clone = window.clone();
             let tabs = Rc::clone(&tabs);
             let notebook = notebook.clone();
-            let action = gio::SimpleAction::new("reset", None);
+            let action = gio::SimpleAction::new("transfer-files", None);
             action.connect_activate(move |_, _| {
-                if let Some(page_idx) = notebook.current_page() {
-                    let tabs = tabs.borrow();
-                    if let Some(tab) = tabs.get(page_idx as usize) {
-                        tab.terminal.reset();
-                    }
-                }
+                let requester = notebook.current_page().and_then(|idx| {
+                    tabs.borrow()
+                        .get(idx as usize)
+                        .and_then(|t| t.terminal.remote_requester())
+                });
+                crate::transfer_panel::show_transfer_panel(&window_clone, requester);
             });
             window.add_action(&action);
         }
 
         {
+            let window_clone = window.clone();
             let tabs = Rc::clone(&tabs);
             let notebook = notebook.clone();
-            let action = gio::SimpleAction::new("clear-reset", None);
+            let action = gio::SimpleAction::new("save-buffer", None);
             action.connect_activate(move |_, _| {
-                if let Some(page_idx) = notebook.current_page() {
-                    let tabs = tabs.borrow();
-                    if let Some(tab) = tabs.get(page_idx as usize) {
-                        tab.terminal.clear_scrollback_and_reset();
+                // Snapshot the buffer now; it may scroll while the dialog is open
+                let text = notebook.current_page().and_then(|idx| {
+                    tabs.borrow()
+                        .get(idx as usize)
+                        .map(|t| t.terminal.terminal().lock().screen().to_ansi())
+                });
+                let Some(text) = text else {
+                    return;
+                };
+                dialogs::show_save_buffer_dialog(&window_clone, move |path| {
+                    if let Err(e) = std::fs::write(&path, &text) {
+                        log::error!("Failed to save buffer to {:?}: {}", path, e);
                     }
-                }
+                });
             });
             window.add_action(&action);
         }
 
-        // Tabs menu actions
         {
             let notebook = notebook.clone();
             let tabs = Rc::clone(&tabs);
+            let next_tab_id = Rc::clone(&next_tab_id);
+            let config = Rc::clone(&config);
+            let theme = theme.clone();
             let tab_bar = tab_bar.clone();
-            let action = gio::SimpleAction::new("prev-tab", None);
+            let window_clone = window.clone();
+            let has_bell = Rc::clone(&has_bell);
+            let file_manager = Rc::clone(&self.file_manager);
+            let notification_bar = self.notification_bar.clone();
+            let action = gio::SimpleAction::new("play-recording", None);
             action.connect_activate(move |_, _| {
-                let n = notebook.n_pages();
-                if n > 0 {
-                    let current = notebook.current_page().unwrap_or(0);
-                    let prev = if current == 0 { n - 1 } else { current - 1 };
-                    notebook.set_current_page(Some(prev));
-                    sync_tab_bar_active(&tab_bar, &tabs, &notebook);
-                }
-            });
-            window.add_action(&action);
-        }
-
+                let notebook = notebook.clone();
+                let tabs = Rc::clone(&tabs);
+                let next_tab_id = Rc::clone(&next_tab_id);
+                let config = Rc::clone(&config);
+                let theme = theme.clone();
+                let tab_bar = tab_bar.clone();
+                let window = window_clone.clone();
+                let has_bell = Rc::clone(&has_bell);
+                let file_manager = Rc::clone(&file_manager);
+                let notification_bar = notification_bar.clone();
+                dialogs::show_open_recording_dialog(&window_clone, move |path| {
+                    let recording = match cterm_core::Recording::open(&path) {
+                        Ok(recording) => recording,
+                        Err(e) => {
+                            log::error!("Failed to open recording {:?}: {}", path, e);
+                            let dialog = gtk4::MessageDialog::new(
+                                Some(&window),
+                                gtk4::DialogFlags::MODAL,
+                                gtk4::MessageType::Error,
+                                gtk4::ButtonsType::Ok,
+                                format!("Failed to open {}: {}", path.display(), e),
+                            );
+                            dialog.connect_response(|d, _| d.close());
+                            dialog.present();
+                            return;
+                        }
+                    };
+                    create_playback_tab(
+                        &notebook,
+                        &tabs,
+                        &next_tab_id,
+                        &config,
+                        &theme,
+                        &tab_bar,
+                        &window,
+                        &has_bell,
+                        &file_manager,
+                        &notification_bar,
+                        recording,
+                        &path,
+                    );
+                });
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let window_clone = window.clone();
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let action = gio::SimpleAction::new("record", None);
+            action.connect_activate(move |_, _| {
+                let Some(id) = notebook.current_page().and_then(|idx| {
+                    let tabs = tabs.borrow();
+                    let tab = tabs.get(idx as usize)?;
+                    // Stop the recording in progress, otherwise ask where to start one
+                    (!tab.terminal.stop_recording()).then_some(tab.id)
+                }) else {
+                    return;
+                };
+                let tabs = Rc::clone(&tabs);
+                dialogs::show_save_recording_dialog(&window_clone, move |path| {
+                    if let Some(tab) = tabs.borrow().iter().find(|t| t.id == id) {
+                        if let Err(e) = tab.terminal.start_recording(&path) {
+                            log::error!("Failed to start recording to {:?}: {}", path, e);
+                        }
+                    }
+                });
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let window_clone = window.clone();
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let action = gio::SimpleAction::new("images", None);
+            action.connect_activate(move |_, _| {
+                let current = notebook.current_page().and_then(|idx| {
+                    tabs.borrow()
+                        .get(idx as usize)
+                        .map(|t| (t.id, t.terminal.images()))
+                });
+                let Some((tab_id, images)) = current else {
+                    return;
+                };
+                let tabs = Rc::clone(&tabs);
+                dialogs::show_images_dialog(&window_clone, images, move |id| {
+                    let tabs = tabs.borrow();
+                    let Some(tab) = tabs.iter().find(|t| t.id == tab_id) else {
+                        return;
+                    };
+                    match id {
+                        Some(id) => tab.terminal.remove_image(id),
+                        None => tab.terminal.clear_images(),
+                    }
+                });
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let window_clone = window.clone();
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let tab_bar = tab_bar.clone();
+            let action = gio::SimpleAction::new("watch", None);
+            action.connect_activate(move |_, _| {
+                let current = notebook.current_page().and_then(|idx| {
+                    tabs.borrow()
+                        .get(idx as usize)
+                        .map(|t| (t.id, Arc::clone(t.terminal.terminal())))
+                });
+                let Some((tab_id, terminal)) = current else {
+                    return;
+                };
+                let current_pattern = terminal
+                    .lock()
+                    .watch()
+                    .map(|watch| watch.pattern().to_string())
+                    .unwrap_or_default();
+                let window = window_clone.clone();
+                let tab_bar = tab_bar.clone();
+                dialogs::show_watch_dialog(&window_clone, &current_pattern, move |pattern| {
+                    let pattern = Some(pattern.as_str()).filter(|p| !p.is_empty());
+                    let result = terminal.lock().set_watch(pattern);
+                    if let Err(e) = result {
+                        let dialog = gtk4::MessageDialog::new(
+                            Some(&window),
+                            gtk4::DialogFlags::MODAL,
+                            gtk4::MessageType::Error,
+                            gtk4::ButtonsType::Ok,
+                            format!("Invalid watch expression: {}", e),
+                        );
+                        dialog.connect_response(|d, _| d.close());
+                        dialog.present();
+                        return;
+                    }
+                    tab_bar.set_watch_count(tab_id, pattern.map(|_| 0));
+                });
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let window_clone = window.clone();
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let config = Rc::clone(&config);
+            let action = gio::SimpleAction::new("extract-matches", None);
+            action.connect_activate(move |_, _| {
+                let extract = notebook.current_page().and_then(|idx| {
+                    tabs.borrow()
+                        .get(idx as usize)
+                        .map(|t| t.terminal.match_extractor())
+                });
+                if let Some(extract) = extract {
+                    crate::extract_panel::show_extract_panel(
+                        &window_clone,
+                        Rc::clone(&config),
+                        extract,
+                    );
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let action = gio::SimpleAction::new("hints", None);
+            action.connect_activate(move |_, _| {
+                if let Some(page_idx) = notebook.current_page() {
+                    if let Some(tab) = tabs.borrow().get(page_idx as usize) {
+                        tab.terminal.start_hint_mode();
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let action =
+                gio::SimpleAction::new("set-encoding", Some(&glib::VariantType::new("s").unwrap()));
+            action.connect_activate(|_, param| {
+                if let Some(encoding) = param.and_then(|p| p.get::<String>()) {
+                    if encoding == "utf8" {
+                        log::info!("Encoding set to UTF-8");
+                    } else {
+                        // Terminal currently only supports UTF-8
+                        log::warn!(
+                            "Encoding '{}' requested but only UTF-8 is currently supported",
+                            encoding
+                        );
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let action =
+                gio::SimpleAction::new("send-signal", Some(&glib::VariantType::new("s").unwrap()));
+            action.connect_activate(move |_, param| {
+                if let Some(signal_str) = param.and_then(|p| p.get::<String>()) {
+                    if let Ok(signal) = signal_str.parse::<i32>() {
+                        if let Some(page_idx) = notebook.current_page() {
+                            let tabs = tabs.borrow();
+                            if let Some(tab) = tabs.get(page_idx as usize) {
+                                log::info!("Sending signal {} to terminal", signal);
+                                tab.terminal.send_signal(signal);
+                            }
+                        }
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let action = gio::SimpleAction::new("reset", None);
+            action.connect_activate(move |_, _| {
+                if let Some(page_idx) = notebook.current_page() {
+                    let tabs = tabs.borrow();
+                    if let Some(tab) = tabs.get(page_idx as usize) {
+                        tab.terminal.reset();
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let action = gio::SimpleAction::new("clear-reset", None);
+            action.connect_activate(move |_, _| {
+                if let Some(page_idx) = notebook.current_page() {
+                    let tabs = tabs.borrow();
+                    if let Some(tab) = tabs.get(page_idx as usize) {
+                        tab.terminal.clear_scrollback_and_reset();
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let action = gio::SimpleAction::new("ascii-art-safe", None);
+            action.connect_activate(move |_, _| {
+                if let Some(page_idx) = notebook.current_page() {
+                    let tabs = tabs.borrow();
+                    if let Some(tab) = tabs.get(page_idx as usize) {
+                        tab.terminal.toggle_ascii_art_safe();
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let tabs = Rc::clone(&tabs);
+            let notebook = notebook.clone();
+            let action = gio::SimpleAction::new("read-only", None);
+            action.connect_activate(move |_, _| {
+                if let Some(page_idx) = notebook.current_page() {
+                    let tabs = tabs.borrow();
+                    if let Some(tab) = tabs.get(page_idx as usize) {
+                        tab.terminal.toggle_read_only();
+                    }
+                }
+            });
+            window.add_action(&action);
+        }
+
+        {
+            let tabs = Rc::clone(&tabs);
+            let tab_bar = tab_bar.clone();
+            let presenting = Rc::clone(&self.presenting);
+            let config = Rc::clone(&config);
+            let action = gio::SimpleAction::new("presentation-mode", None);
+            action.connect_activate(move |_, _| {
+                toggle_presentation_mode(&tabs, &tab_bar, &presenting, &config);
+            });
+            window.add_action(&action);
+        }
+
+        // Tabs menu actions
+        {
+            let notebook = notebook.clone();
+            let tabs = Rc::clone(&tabs);
+            let tab_bar = tab_bar.clone();
+            let action = gio::SimpleAction::new("prev-tab", None);
+            action.connect_activate(move |_, _| {
+                let n = notebook.n_pages();
+                if n > 0 {
+                    let current = notebook.current_page().unwrap_or(0);
+                    let prev = if current == 0 { n - 1 } else { current - 1 };
+                    notebook.set_current_page(Some(prev));
+                    sync_tab_bar_active(&tab_bar, &tabs, &notebook);
+                }
+            });
+            window.add_action(&action);
+        }
+
         {
             let notebook = notebook.clone();
             let tabs = Rc::clone(&tabs);
@@ -976,11 +1563,15 @@ impl CtermWindow {
             let window_clone = window.clone();
             let config = Rc::clone(&config);
             let menu_bar_clone = menu_bar.clone();
+            let tab_bar = tab_bar.clone();
+            let notebook = notebook.clone();
             let action = gio::SimpleAction::new("preferences", None);
             action.connect_activate(move |_, _| {
                 let cfg = config.borrow().clone();
                 let config_for_save = Rc::clone(&config);
                 let menu_bar = menu_bar_clone.clone();
+                let tab_bar = tab_bar.clone();
+                let notebook = notebook.clone();
                 dialogs::show_preferences_dialog(&window_clone, &cfg, move |new_config| {
                     log::info!("Preferences saved");
                     // Save to disk
@@ -990,7 +1581,21 @@ impl CtermWindow {
                         log::info!("Configuration saved to disk");
                     }
                     // Rebuild menu bar to reflect debug menu preference
-                    menu::rebuild_menu_bar(&menu_bar, new_config.general.show_debug_menu);
+                    menu::rebuild_menu_bar(
+                        &menu_bar,
+                        new_config.general.show_debug_menu,
+                        &new_config.profiles,
+                        &new_config.workspaces,
+                    );
+                    tab_bar.set_widgets(new_config.widgets);
+                    tab_bar.set_config(&new_config.tabs);
+                    tab_bar.set_profiles(&new_config.profiles);
+                    place_tab_bar(
+                        &tab_bar,
+                        new_config.tabs.tab_bar_position,
+                        &menu_bar,
+                        &notebook,
+                    );
                     // Update internal config state
                     *config_for_save.borrow_mut() = new_config;
                 });
@@ -1034,7 +1639,11 @@ impl CtermWindow {
                     window_state.maximized = window_clone.is_maximized();
                     window_state.fullscreen = window_clone.is_fullscreen();
 
-                    for tab in tabs_borrowed.iter() {
+                    let current_page = notebook_upgrade.current_page().unwrap_or(0) as usize;
+                    for (page, tab) in tabs_borrowed.iter().enumerate() {
+                        if page == current_page {
+                            window_state.active_tab = window_state.tabs.len();
+                        }
                         let mut tab_state = cterm_app::upgrade::TabUpgradeState::new(tab.id);
                         tab_state.title = tab.title.clone();
                         if tab.title_locked {
@@ -1053,10 +1662,16 @@ impl CtermWindow {
                         }
 
                         window_state.tabs.push(tab_state);
-                    }
 
-                    // Set active tab
-                    window_state.active_tab = notebook_upgrade.current_page().unwrap_or(0) as usize;
+                        // Split panes come back as tabs of their own
+                        for pane in tab.panes.iter().filter(|p| p.id != tab.id) {
+                            let mut pane_state = cterm_app::upgrade::TabUpgradeState::new(pane.id);
+                            pane_state.title = tab.title.clone();
+                            pane_state.color = tab.color.clone();
+                            pane_state.session_id = pane.session_id.clone();
+                            window_state.tabs.push(pane_state);
+                        }
+                    }
 
                     upgrade_state.windows.push(window_state);
 
@@ -1143,6 +1758,8 @@ impl CtermWindow {
                             &notification_bar,
                             &template,
                             &remote_manager,
+                            None,
+                            None,
                         );
                     },
                 );
@@ -1150,6 +1767,16 @@ impl CtermWindow {
             window.add_action(&action);
         }
 
+        // Usage Statistics action
+        {
+            let window_clone = window.clone();
+            let action = gio::SimpleAction::new("usage-stats", None);
+            action.connect_activate(move |_, _| {
+                crate::usage_stats_window::show_usage_stats(&window_clone);
+            });
+            window.add_action(&action);
+        }
+
         // View Logs action (debug menu)
         {
             let window_clone = window.clone();
@@ -1327,6 +1954,7 @@ impl CtermWindow {
         let has_bell = Rc::clone(&self.has_bell);
         let file_manager = Rc::clone(&self.file_manager);
         let notification_bar = self.notification_bar.clone();
+        let presenting = Rc::clone(&self.presenting);
 
         key_controller.connect_key_pressed(move |_, keyval, _keycode, state| {
             // Convert GTK modifiers to our modifiers
@@ -1344,12 +1972,26 @@ impl CtermWindow {
 
             // Convert keyval to our key code
             if let Some(key) = keyval_to_keycode(keyval) {
-                // Check for shortcut match
-                if let Some(action) = shortcuts.match_event(key, modifiers) {
-                    match action {
-                        Action::NewTab => {
-                            let (cwd, daemon_socket) = {
-                                let tabs_borrow = tabs.borrow();
+                // Ctrl+Shift+Enter and friends answer the notification bar
+                if let Some(action) = notification_bar.action_for_key(key, modifiers) {
+                    notification_bar.answer(action);
+                    return glib::Propagation::Stop;
+                }
+
+                if *presenting.borrow() {
+                    if let Some(page_idx) = notebook.current_page() {
+                        if let Some(tab) = tabs.borrow().get(page_idx as usize) {
+                            tab.terminal.record_keystroke(key, modifiers);
+                        }
+                    }
+                }
+
+                // Check for shortcut match
+                if let Some(action) = shortcuts.match_event(key, modifiers) {
+                    match action {
+                        Action::NewTab => {
+                            let (cwd, daemon_socket) = {
+                                let tabs_borrow = tabs.borrow();
                                 if let Some(page_idx) = notebook.current_page() {
                                     let entry = tabs_borrow.get(page_idx as usize);
                                     #[cfg(unix)]
@@ -1383,13 +2025,21 @@ impl CtermWindow {
                             close_current_tab(&notebook, &tabs, &tab_bar, &window, &config);
                             return glib::Propagation::Stop;
                         }
+                        Action::ReopenClosedTab => {
+                            gtk4::prelude::ActionGroupExt::activate_action(
+                                &window,
+                                "reopen-closed-tab",
+                                None,
+                            );
+                            return glib::Propagation::Stop;
+                        }
                         Action::NextTab => {
                             let n = notebook.n_pages();
                             if n > 0 {
                                 let current = notebook.current_page().unwrap_or(0);
                                 notebook.set_current_page(Some((current + 1) % n));
                                 sync_tab_bar_active(&tab_bar, &tabs, &notebook);
-                                focus_current_terminal(&notebook);
+                                focus_current_terminal(&notebook, &tabs);
                             }
                             return glib::Propagation::Stop;
                         }
@@ -1400,7 +2050,7 @@ impl CtermWindow {
                                 let prev = if current == 0 { n - 1 } else { current - 1 };
                                 notebook.set_current_page(Some(prev));
                                 sync_tab_bar_active(&tab_bar, &tabs, &notebook);
-                                focus_current_terminal(&notebook);
+                                focus_current_terminal(&notebook, &tabs);
                             }
                             return glib::Propagation::Stop;
                         }
@@ -1416,7 +2066,7 @@ impl CtermWindow {
                                             drop(tabs_ref);
                                             notebook.set_current_page(Some(idx as u32));
                                             sync_tab_bar_active(&tab_bar, &tabs, &notebook);
-                                            focus_current_terminal(&notebook);
+                                            focus_current_terminal(&notebook, &tabs);
                                             break;
                                         }
                                     }
@@ -1429,7 +2079,7 @@ impl CtermWindow {
                             if idx < notebook.n_pages() {
                                 notebook.set_current_page(Some(idx));
                                 sync_tab_bar_active(&tab_bar, &tabs, &notebook);
-                                focus_current_terminal(&notebook);
+                                focus_current_terminal(&notebook, &tabs);
                             }
                             return glib::Propagation::Stop;
                         }
@@ -1457,7 +2107,7 @@ impl CtermWindow {
                                             if let Some(page_idx) = notebook_paste.current_page() {
                                                 let tabs = tabs_paste.borrow();
                                                 if let Some(tab) = tabs.get(page_idx as usize) {
-                                                    tab.terminal.write_str(&text);
+                                                    tab.terminal.paste(&text);
                                                 }
                                             }
                                         }
@@ -1493,6 +2143,37 @@ impl CtermWindow {
                             }
                             return glib::Propagation::Stop;
                         }
+                        Action::MagnifyIn => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    tab.terminal.magnify_in();
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::MagnifyOut => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    tab.terminal.magnify_out();
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::MagnifyReset => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    tab.terminal.magnify_reset();
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::TogglePresentationMode => {
+                            toggle_presentation_mode(&tabs, &tab_bar, &presenting, &config);
+                            return glib::Propagation::Stop;
+                        }
                         Action::NewWindow => {
                             gtk4::prelude::ActionGroupExt::activate_action(
                                 &window,
@@ -1505,6 +2186,152 @@ impl CtermWindow {
                             window.close();
                             return glib::Propagation::Stop;
                         }
+                        Action::SplitRight | Action::SplitDown => {
+                            let axis = if action == Action::SplitRight {
+                                SplitAxis::Horizontal
+                            } else {
+                                SplitAxis::Vertical
+                            };
+                            split_pane(
+                                &notebook,
+                                &tabs,
+                                &next_tab_id,
+                                &config,
+                                &theme,
+                                &tab_bar,
+                                &window,
+                                &has_bell,
+                                &file_manager,
+                                &notification_bar,
+                                axis,
+                            );
+                            return glib::Propagation::Stop;
+                        }
+                        Action::ClosePane => {
+                            let ids = notebook.current_page().and_then(|page_idx| {
+                                let tabs_ref = tabs.borrow();
+                                let tab = tabs_ref.get(page_idx as usize)?;
+                                Some((tab.id, tab.pane_tree.focused(), tab.pane_tree.is_split()))
+                            });
+                            match ids {
+                                Some((tab_id, pane_id, true)) => close_pane(
+                                    &notebook, &tabs, &tab_bar, &window, &config, tab_id, pane_id,
+                                ),
+                                Some((tab_id, _, false)) => request_close_tab_by_id(
+                                    &notebook, &tabs, &tab_bar, &window, &config, tab_id,
+                                ),
+                                None => {}
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::FocusPane(direction) => {
+                            let target = notebook.current_page().and_then(|page_idx| {
+                                let page = notebook.nth_page(Some(page_idx))?;
+                                let tabs_ref = tabs.borrow();
+                                let tab = tabs_ref.get(page_idx as usize)?;
+                                let widgets: Vec<(u64, gtk4::Widget)> = tab
+                                    .panes
+                                    .iter()
+                                    .map(|p| (p.id, p.terminal.widget().clone().upcast()))
+                                    .collect();
+                                let areas = panes::areas(&page, &widgets);
+                                let id = session::pane_in_direction(
+                                    &areas,
+                                    tab.pane_tree.focused(),
+                                    direction,
+                                )?;
+                                Some(tab.pane(id)?.terminal.widget().clone())
+                            });
+                            if let Some(widget) = target {
+                                widget.grab_focus();
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::ResizePane(direction) => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    panes::resize(
+                                        tab.terminal.widget().upcast_ref(),
+                                        direction,
+                                        PANE_RESIZE_STEP,
+                                    );
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::ToggleScrollLock => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let mut tabs_ref = tabs.borrow_mut();
+                                if let Some(tab) = tabs_ref.get_mut(page_idx as usize) {
+                                    tab.pane_tree.toggle_scroll_lock();
+                                    tab.sync_scroll_peers();
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::ToggleAsciiArtSafe => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    tab.terminal.toggle_ascii_art_safe();
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::ToggleReadOnly => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    tab.terminal.toggle_read_only();
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::PreviousPrompt | Action::NextPrompt => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    tab.terminal.scroll_to_prompt(*action == Action::NextPrompt);
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::PasteHistory => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    tab.terminal.show_paste_history();
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::SelectCommandOutput => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    tab.terminal.select_command_output();
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::HintMode => {
+                            if let Some(page_idx) = notebook.current_page() {
+                                let tabs_ref = tabs.borrow();
+                                if let Some(tab) = tabs_ref.get(page_idx as usize) {
+                                    tab.terminal.start_hint_mode();
+                                }
+                            }
+                            return glib::Propagation::Stop;
+                        }
+                        Action::ExtractMatches => {
+                            gtk4::prelude::ActionGroupExt::activate_action(
+                                &window,
+                                "extract-matches",
+                                None,
+                            );
+                            return glib::Propagation::Stop;
+                        }
                         Action::QuickOpenTemplate => {
                             // Activate the quick-open action
                             gtk4::prelude::ActionGroupExt::activate_action(
@@ -1526,6 +2353,30 @@ impl CtermWindow {
         self.window.add_controller(key_controller);
     }
 
+    /// Restyle the window's terminals whenever a changed appearance is
+    /// published, for as long as the window exists
+    fn setup_live_appearance(&self) {
+        let tabs = Rc::clone(&self.tabs);
+        let window = self.window.downgrade();
+        let subscription = live_config::subscribe(move |config, change| {
+            let theme = cterm_app::resolve_theme(config);
+            if let Some(window) = window.upgrade() {
+                if config.appearance.is_translucent(&theme) {
+                    window.add_css_class("translucent");
+                } else {
+                    window.remove_css_class("translucent");
+                }
+            }
+            for pane in tabs.borrow().iter().flat_map(|t| &t.panes) {
+                pane.terminal.apply_appearance(config, &theme, change);
+            }
+        });
+        let subscription = RefCell::new(Some(subscription));
+        self.window.connect_destroy(move |_| {
+            subscription.take();
+        });
+    }
+
     /// Set up window focus handler to clear bell when window becomes active
     /// and send focus events to the terminal (DECSET 1004)
     fn setup_focus_handler(&self) {
@@ -1677,72 +2528,22 @@ impl CtermWindow {
         self.window.add_controller(focus_controller);
     }
 
-    /// Set up notification bar callbacks for file transfers
+    /// Set up notification bar callbacks for received files and permission
+    /// requests
     fn setup_notification_bar(&self) {
         let file_manager = Rc::clone(&self.file_manager);
         let notification_bar = self.notification_bar.clone();
+        let tabs = Rc::clone(&self.tabs);
         let window = self.window.clone();
 
-        // Save button - save to default location (Downloads or last saved dir)
-        let file_manager_save = Rc::clone(&file_manager);
-        let notification_bar_save = notification_bar.clone();
-        notification_bar.set_on_save(move |id| {
-            let mut manager = file_manager_save.borrow_mut();
-            if let Some(path) = manager.default_save_path() {
-                match manager.save_to_path(id, &path) {
-                    Ok(size) => {
-                        log::info!("Saved file to {:?} ({} bytes)", path, size);
-                    }
-                    Err(e) => {
-                        log::error!("Failed to save file: {}", e);
-                    }
-                }
-            }
-            drop(manager);
-            notification_bar_save.hide();
-        });
-
-        // Save As button - show file chooser dialog
-        let file_manager_save_as = Rc::clone(&file_manager);
-        let notification_bar_save_as = notification_bar.clone();
-        notification_bar.set_on_save_as(move |id| {
-            let manager = file_manager_save_as.borrow();
-            let suggested_name = manager.suggested_filename().map(|s| s.to_string());
-            let initial_dir = manager.last_save_dir().cloned();
-            drop(manager);
-
-            let file_chooser = gtk4::FileChooserDialog::new(
-                Some("Save File As"),
-                Some(&window),
-                gtk4::FileChooserAction::Save,
-                &[
-                    ("Cancel", gtk4::ResponseType::Cancel),
-                    ("Save", gtk4::ResponseType::Accept),
-                ],
-            );
-
-            // Set suggested filename
-            if let Some(name) = suggested_name {
-                file_chooser.set_current_name(&name);
-            }
-
-            // Set initial folder
-            if let Some(dir) = initial_dir {
-                let file = gio::File::for_path(&dir);
-                file_chooser.set_current_folder(Some(&file)).ok();
-            } else if let Some(downloads) = cterm_app::file_transfer::dirs::download_dir() {
-                let file = gio::File::for_path(&downloads);
-                file_chooser.set_current_folder(Some(&file)).ok();
-            }
-
-            let file_manager_dialog = Rc::clone(&file_manager_save_as);
-            let notification_bar_dialog = notification_bar_save_as.clone();
-
-            file_chooser.connect_response(move |dialog, response| {
-                if response == gtk4::ResponseType::Accept {
-                    if let Some(file) = dialog.file() {
-                        if let Some(path) = file.path() {
-                            let mut manager = file_manager_dialog.borrow_mut();
+        notification_bar
+            .clone()
+            .set_on_answer(move |prompt, action| match prompt {
+                Prompt::File { id, name, size } => match action {
+                    // Save to default location (Downloads or last saved dir)
+                    PromptAction::Save => {
+                        let mut manager = file_manager.borrow_mut();
+                        if let Some(path) = manager.default_save_path() {
                             match manager.save_to_path(id, &path) {
                                 Ok(size) => {
                                     log::info!("Saved file to {:?} ({} bytes)", path, size);
@@ -1753,22 +2554,29 @@ impl CtermWindow {
                             }
                         }
                     }
+                    PromptAction::SaveAs => show_save_file_dialog(
+                        &window,
+                        &file_manager,
+                        &notification_bar,
+                        id,
+                        name,
+                        size,
+                    ),
+                    _ => {
+                        file_manager.borrow_mut().discard(id);
+                        log::debug!("Discarded pending file {}", id);
+                    }
+                },
+                Prompt::Permission { tab, capability } => {
+                    if let Some(pane) = tabs.borrow().iter().find_map(|t| t.pane(tab)) {
+                        pane.terminal.answer_permission(
+                            capability,
+                            !action.is_dismissal(),
+                            action == PromptAction::AlwaysAllow,
+                        );
+                    }
                 }
-                notification_bar_dialog.hide();
-                dialog.close();
             });
-
-            file_chooser.present();
-        });
-
-        // Discard button - discard the pending file
-        let file_manager_discard = Rc::clone(&file_manager);
-        let notification_bar_discard = notification_bar.clone();
-        notification_bar.set_on_discard(move |id| {
-            file_manager_discard.borrow_mut().discard(id);
-            notification_bar_discard.hide();
-            log::debug!("Discarded pending file {}", id);
-        });
     }
 
     /// Set up Quick Open overlay callback
@@ -1799,6 +2607,8 @@ impl CtermWindow {
                 &notification_bar,
                 &template,
                 &remote_manager,
+                None,
+                None,
             );
             log::info!("Opened template tab from Quick Open: {}", template.name);
         });
@@ -1952,8 +2762,9 @@ impl CtermWindow {
             let tab_infos: Vec<(String, Option<std::path::PathBuf>, String)> = {
                 let tabs = tabs.borrow();
                 tabs.iter()
-                    .filter_map(|tab| {
-                        let sid = tab.session_id.clone()?;
+                    .flat_map(|tab| tab.panes.iter().map(move |pane| (tab, pane)))
+                    .filter_map(|(tab, pane)| {
+                        let sid = pane.session_id.clone()?;
                         if sid.is_empty() {
                             return None;
                         }
@@ -2039,6 +2850,55 @@ impl CtermWindow {
         // No process detection on non-Unix platforms
     }
 
+    /// Record the window's tabs as it closes, so they can be saved for the
+    /// next launch if it is the last window
+    fn setup_session_save(&self) {
+        let tabs = Rc::clone(&self.tabs);
+        let notebook = self.notebook.clone();
+        self.window.connect_destroy(move |window| {
+            let tabs = tabs.borrow();
+            let (width, height) = window.default_size();
+            let geometry = WindowGeometry {
+                width: width.max(0) as u32,
+                height: height.max(0) as u32,
+                maximized: window.is_maximized(),
+                ..Default::default()
+            };
+            session::remember_window(WindowSessionState {
+                geometry,
+                tabs: tabs.iter().map(|tab| closed_tab(tab).into()).collect(),
+                active_tab: notebook.current_page().unwrap_or(0) as usize,
+            });
+        });
+    }
+
+    /// Refresh the status segments in the tab titles every
+    /// [`status_segments::REFRESH_INTERVAL`], each from the directory of the
+    /// tab's focused pane
+    fn setup_status_segments(&self) {
+        let tabs = Rc::downgrade(&self.tabs);
+        let config = Rc::clone(&self.config);
+        let tab_bar = self.tab_bar.clone();
+        glib::timeout_add_local(status_segments::REFRESH_INTERVAL, move || {
+            let Some(tabs) = tabs.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            let config = config.borrow();
+            if config.status_segments.is_empty() {
+                return glib::ControlFlow::Continue;
+            }
+            for tab in tabs.borrow().iter() {
+                #[cfg(unix)]
+                let dir = tab.terminal.foreground_cwd().map(std::path::PathBuf::from);
+                #[cfg(not(unix))]
+                let dir: Option<std::path::PathBuf> = None;
+                let segments = status_segments::values(&config.status_segments, dir.as_deref());
+                tab_bar.set_segments(tab.id, segments);
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
     /// Create a new tab
     pub fn new_tab(&self) {
         create_new_tab(
@@ -2057,6 +2917,45 @@ impl CtermWindow {
         );
     }
 
+    /// Open the tabs and panes of workspace `name`
+    pub fn open_workspace(&self, name: &str) {
+        self.workspace_opener().open(name);
+    }
+
+    fn workspace_opener(&self) -> WorkspaceOpener {
+        WorkspaceOpener {
+            notebook: self.notebook.clone(),
+            tabs: Rc::clone(&self.tabs),
+            next_tab_id: Rc::clone(&self.next_tab_id),
+            config: Rc::clone(&self.config),
+            theme: self.theme.clone(),
+            tab_bar: self.tab_bar.clone(),
+            window: self.window.clone(),
+            has_bell: Rc::clone(&self.has_bell),
+            file_manager: Rc::clone(&self.file_manager),
+            notification_bar: self.notification_bar.clone(),
+            remote_manager: self.remote_manager.clone(),
+        }
+    }
+
+    /// Add a tab playing back `recording`, read from `path` (`cterm --play`)
+    pub fn add_playback_tab(&self, recording: cterm_core::Recording, path: &std::path::Path) {
+        create_playback_tab(
+            &self.notebook,
+            &self.tabs,
+            &self.next_tab_id,
+            &self.config,
+            &self.theme,
+            &self.tab_bar,
+            &self.window,
+            &self.has_bell,
+            &self.file_manager,
+            &self.notification_bar,
+            recording,
+            path,
+        );
+    }
+
     /// Add a tab for a reconnected daemon session (with screen snapshot).
     ///
     /// Used during startup reconnection to create tabs for existing daemon sessions.
@@ -2092,7 +2991,7 @@ impl CtermWindow {
         let tab_id = generate_tab_id(&self.next_tab_id);
         let page_num = self
             .notebook
-            .append_page(terminal.widget(), None::<&gtk4::Widget>);
+            .append_page(&panes::page(&terminal), None::<&gtk4::Widget>);
         self.tab_bar.add_tab(tab_id, &title);
 
         setup_tab_callbacks(
@@ -2109,6 +3008,20 @@ impl CtermWindow {
             false,
         );
 
+        setup_tmux_gateway(
+            &self.notebook,
+            &self.tabs,
+            &self.next_tab_id,
+            &self.config,
+            &self.theme,
+            &self.tab_bar,
+            &self.window,
+            &self.has_bell,
+            &self.file_manager,
+            &self.notification_bar,
+            &terminal,
+        );
+
         finalize_new_tab(
             &self.notebook,
             &self.tabs,
@@ -2144,6 +3057,7 @@ impl CtermWindow {
                 window.set_title(Some(&tab.title));
                 tab_bar.set_active(tab.id);
                 tab_bar.clear_bell(tab.id);
+                tab_bar.set_unread(tab.id, false);
                 tab.terminal.clear_alert();
                 *has_bell.borrow_mut() = false;
             }
@@ -2151,6 +3065,23 @@ impl CtermWindow {
     }
 }
 
+/// Put the tab bar under the menu bar or under the terminals
+fn place_tab_bar(
+    tab_bar: &TabBar,
+    position: TabBarPosition,
+    menu_bar: &PopoverMenuBar,
+    notebook: &Notebook,
+) {
+    let Some(main_box) = notebook.parent().and_downcast::<GtkBox>() else {
+        return;
+    };
+    let sibling: &gtk4::Widget = match position {
+        TabBarPosition::Top => menu_bar.upcast_ref(),
+        TabBarPosition::Bottom => notebook.upcast_ref(),
+    };
+    main_box.reorder_child_after(tab_bar.widget(), Some(sibling));
+}
+
 /// Show the rename dialog for a tab and persist the new title.
 /// Used by both the menu bar "Set Title" action and the right-click context menu.
 fn show_rename_tab_dialog(
@@ -2182,6 +3113,84 @@ fn show_rename_tab_dialog(
     });
 }
 
+/// Ask where to save a received file and save it there
+///
+/// Cancelling leaves the file waiting in the notification bar.
+fn show_save_file_dialog(
+    window: &ApplicationWindow,
+    file_manager: &Rc<RefCell<PendingFileManager>>,
+    notification_bar: &NotificationBar,
+    id: u64,
+    name: Option<String>,
+    size: usize,
+) {
+    let manager = file_manager.borrow();
+    let suggested_name = manager.suggested_filename().map(|s| s.to_string());
+    let initial_dir = manager.last_save_dir().cloned();
+    drop(manager);
+
+    let file_chooser = gtk4::FileChooserDialog::new(
+        Some("Save File As"),
+        Some(window),
+        gtk4::FileChooserAction::Save,
+        &[
+            ("Cancel", gtk4::ResponseType::Cancel),
+            ("Save", gtk4::ResponseType::Accept),
+        ],
+    );
+
+    // Set suggested filename
+    if let Some(name) = suggested_name {
+        file_chooser.set_current_name(&name);
+    }
+
+    // Set initial folder
+    if let Some(dir) = initial_dir {
+        let file = gio::File::for_path(&dir);
+        file_chooser.set_current_folder(Some(&file)).ok();
+    } else if let Some(downloads) = cterm_app::file_transfer::dirs::download_dir() {
+        let file = gio::File::for_path(&downloads);
+        file_chooser.set_current_folder(Some(&file)).ok();
+    }
+
+    let file_manager = Rc::clone(file_manager);
+    let notification_bar = notification_bar.clone();
+    file_chooser.connect_response(move |dialog, response| {
+        let path = (response == gtk4::ResponseType::Accept)
+            .then(|| dialog.file().and_then(|file| file.path()))
+            .flatten();
+        dialog.close();
+
+        let Some(path) = path else {
+            // Unless a newer file has replaced it meanwhile
+            if file_manager.borrow().pending().is_some_and(|p| p.id == id) {
+                notification_bar.show_file(id, name.as_deref(), size);
+            }
+            return;
+        };
+        let mut manager = file_manager.borrow_mut();
+        match manager.save_to_path(id, &path) {
+            Ok(size) => {
+                log::info!("Saved file to {:?} ({} bytes)", path, size);
+            }
+            Err(e) => {
+                log::error!("Failed to save file: {}", e);
+            }
+        }
+    });
+
+    file_chooser.present();
+}
+
+/// Play a `[sounds]` cue, the beep being GTK's alert sound
+fn play_cue(window: &ApplicationWindow, cue: Option<Cue>) {
+    match cue {
+        Some(Cue::Beep) => window.error_bell(),
+        Some(cue) => sounds::play(&cue),
+        None => {}
+    }
+}
+
 /// Generate a unique tab ID from the shared counter
 fn generate_tab_id(next_tab_id: &Rc<RefCell<u64>>) -> u64 {
     let mut id = next_tab_id.borrow_mut();
@@ -2233,26 +3242,91 @@ fn setup_tab_callbacks(
             tab_bar_click.set_active(tab_id);
             tab_bar_click.clear_bell(tab_id);
             tabs[idx].terminal.clear_alert();
-            if let Some(widget) = notebook_click.nth_page(Some(idx as u32)) {
-                widget.grab_focus();
-            }
+            tabs[idx].terminal.widget().grab_focus();
         }
     });
 
-    // Exit callback
-    let notebook_exit = notebook.clone();
-    let tabs_exit = Rc::clone(tabs);
-    let tab_bar_exit = tab_bar.clone();
-    let window_exit = window.clone();
-    terminal.set_on_exit(move || {
-        if !keep_open {
-            close_tab_by_id(
-                &notebook_exit,
-                &tabs_exit,
-                &tab_bar_exit,
-                &window_exit,
-                tab_id,
-            );
+    setup_pane_callbacks(
+        notebook,
+        tabs,
+        config,
+        tab_bar,
+        window,
+        has_bell,
+        file_manager,
+        notification_bar,
+        terminal,
+        tab_id,
+        tab_id,
+        keep_open,
+    );
+}
+
+/// Set up the callbacks of the terminal in pane `pane_id` of tab `tab_id`
+#[allow(clippy::too_many_arguments)]
+fn setup_pane_callbacks(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    config: &Rc<RefCell<Config>>,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    has_bell: &Rc<RefCell<bool>>,
+    file_manager: &Rc<RefCell<PendingFileManager>>,
+    notification_bar: &NotificationBar,
+    terminal: &TerminalWidget,
+    tab_id: u64,
+    pane_id: u64,
+    keep_open: bool,
+) {
+    // Exit callback: closes the pane, or the tab with its last pane
+    let notebook_exit = notebook.clone();
+    let tabs_exit = Rc::clone(tabs);
+    let tab_bar_exit = tab_bar.clone();
+    let window_exit = window.clone();
+    let config_exit = Rc::clone(config);
+    terminal.set_on_exit(move || {
+        if !keep_open {
+            close_pane(
+                &notebook_exit,
+                &tabs_exit,
+                &tab_bar_exit,
+                &window_exit,
+                &config_exit,
+                tab_id,
+                pane_id,
+            );
+        }
+    });
+
+    // Clicking or tabbing into the pane focuses it
+    let focus = gtk4::EventControllerFocus::new();
+    let tabs_focus = Rc::clone(tabs);
+    let tab_bar_focus = tab_bar.clone();
+    let window_focus = window.clone();
+    let notebook_focus = notebook.clone();
+    focus.connect_enter(move |_| {
+        focus_pane(
+            &notebook_focus,
+            &tabs_focus,
+            &tab_bar_focus,
+            &window_focus,
+            tab_id,
+            pane_id,
+        );
+    });
+    terminal.widget().add_controller(focus);
+
+    // Activity marker for output in a background tab
+    let tab_bar_output = tab_bar.clone();
+    let notebook_output = notebook.clone();
+    let tabs_output = Rc::clone(tabs);
+    terminal.set_on_output(move || {
+        let is_current_tab = notebook_output.current_page().is_some_and(|page| {
+            let tabs = tabs_output.borrow();
+            tabs.get(page as usize).is_some_and(|t| t.id == tab_id)
+        });
+        if !is_current_tab {
+            tab_bar_output.set_unread(tab_id, true);
         }
     });
 
@@ -2262,8 +3336,14 @@ fn setup_tab_callbacks(
     let tabs_bell = Rc::clone(tabs);
     let window_bell = window.clone();
     let has_bell_bell = Rc::clone(has_bell);
+    let config_bell = Rc::clone(config);
     terminal.set_on_bell(move || {
         let is_window_active = window_bell.is_active();
+        let config = config_bell.borrow();
+        let sounds = &config.sounds;
+        if sounds.active(is_window_active) {
+            play_cue(&window_bell, sounds.bell_cue());
+        }
         let is_current_tab = if let Some(current_page) = notebook_bell.current_page() {
             let tabs = tabs_bell.borrow();
             tabs.get(current_page as usize)
@@ -2283,6 +3363,156 @@ fn setup_tab_callbacks(
         }
     });
 
+    // Sound or speech for finished commands, and status segments that
+    // refresh after them
+    let window_sound = window.clone();
+    let config_sound = Rc::clone(config);
+    terminal.set_on_command_finished(move |finished| {
+        let config = config_sound.borrow();
+        status_segments::command_finished(&config.status_segments);
+        let sounds = &config.sounds;
+        if sounds.active(window_sound.is_active()) {
+            play_cue(&window_sound, sounds.command_cue(finished));
+        }
+    });
+
+    // Desktop notification for long commands finishing while unfocused
+    let window_notify = window.clone();
+    let tabs_notify = Rc::clone(tabs);
+    terminal.set_on_command_notify(move |summary| {
+        if window_notify.is_active() {
+            return;
+        }
+        let Some(app) = window_notify.application() else {
+            return;
+        };
+        let title = tabs_notify
+            .borrow()
+            .iter()
+            .find(|t| t.id == tab_id)
+            .map(|t| t.title.clone())
+            .unwrap_or_else(|| "cterm".to_string());
+        let notification = gio::Notification::new(&title);
+        notification.set_body(Some(summary));
+        app.send_notification(Some(&format!("command-{}", tab_id)), &notification);
+    });
+
+    // Notifications the program asks for (OSC 9, OSC 777)
+    let window_osc_notify = window.clone();
+    let tabs_osc_notify = Rc::clone(tabs);
+    terminal.set_on_notification(move |requested| {
+        let Some(app) = window_osc_notify.application() else {
+            return;
+        };
+        let title = requested.title.clone().unwrap_or_else(|| {
+            tabs_osc_notify
+                .borrow()
+                .iter()
+                .find(|t| t.id == tab_id)
+                .map(|t| t.title.clone())
+                .unwrap_or_else(|| "cterm".to_string())
+        });
+        let notification = gio::Notification::new(&title);
+        notification.set_body(Some(&requested.body));
+        app.send_notification(Some(&format!("program-{}", tab_id)), &notification);
+    });
+
+    // Watch expression matches: count badge, and a notification while the
+    // tab isn't in view
+    let tab_bar_watch = tab_bar.clone();
+    let notebook_watch = notebook.clone();
+    let tabs_watch = Rc::clone(tabs);
+    let window_watch = window.clone();
+    terminal.set_on_watch_match(move |found| {
+        tab_bar_watch.set_watch_count(tab_id, Some(found.count));
+        if !found.notify {
+            return;
+        }
+        let is_current_tab = notebook_watch.current_page().is_some_and(|page| {
+            tabs_watch
+                .borrow()
+                .get(page as usize)
+                .is_some_and(|t| t.id == tab_id)
+        });
+        if is_current_tab && window_watch.is_active() {
+            return;
+        }
+        let Some(app) = window_watch.application() else {
+            return;
+        };
+        let title = tabs_watch
+            .borrow()
+            .iter()
+            .find(|t| t.id == tab_id)
+            .map(|t| t.title.clone())
+            .unwrap_or_else(|| "cterm".to_string());
+        let notification = gio::Notification::new(&format!("{title}: watch matched"));
+        notification.set_body(Some(&found.line));
+        app.send_notification(Some(&format!("watch-{}", tab_id)), &notification);
+    });
+
+    // Trigger notifications and tab colors
+    let tab_bar_trigger = tab_bar.clone();
+    let tabs_trigger = Rc::clone(tabs);
+    let window_trigger = window.clone();
+    terminal.set_on_trigger(move |effect| match effect {
+        TriggerEffect::Notify { title, body } => {
+            let Some(app) = window_trigger.application() else {
+                return;
+            };
+            let tab_title = tabs_trigger
+                .borrow()
+                .iter()
+                .find(|t| t.id == tab_id)
+                .map(|t| t.title.clone())
+                .unwrap_or_else(|| "cterm".to_string());
+            let notification = gio::Notification::new(&format!("{tab_title}: {title}"));
+            notification.set_body(Some(body));
+            app.send_notification(Some(&format!("trigger-{}", tab_id)), &notification);
+        }
+        TriggerEffect::TabColor(color) => {
+            tab_bar_trigger.set_color(tab_id, Some(color));
+            if let Some(tab) = tabs_trigger
+                .borrow_mut()
+                .iter_mut()
+                .find(|t| t.id == tab_id)
+            {
+                tab.color = Some(color.clone());
+                tab.terminal.set_tab_color_on_daemon(color);
+            }
+        }
+        TriggerEffect::Send(_) | TriggerEffect::Command { .. } => {}
+    });
+
+    // Tab color of `[[auto_switch]]` rules, restored once none matches
+    let tab_bar_switch = tab_bar.clone();
+    let tabs_switch = Rc::clone(tabs);
+    let saved_color = RefCell::new(None);
+    terminal.set_on_auto_switch(move |change| {
+        let mut tabs = tabs_switch.borrow_mut();
+        let Some(tab) = tabs.iter_mut().find(|t| t.id == tab_id) else {
+            return;
+        };
+        let color = match change {
+            AutoSwitchChange::Apply(rule) => {
+                let Some(ref color) = rule.tab_color else {
+                    return;
+                };
+                let mut saved = saved_color.borrow_mut();
+                if saved.is_none() {
+                    *saved = Some(tab.color.clone());
+                }
+                Some(color.clone())
+            }
+            AutoSwitchChange::Revert => match saved_color.take() {
+                Some(color) => color,
+                None => return,
+            },
+        };
+        tab_bar_switch.set_color(tab_id, color.as_deref());
+        tab.color = color;
+    });
+
     // Title change callback
     let tab_bar_title = tab_bar.clone();
     let tabs_title = Rc::clone(tabs);
@@ -2290,11 +3520,12 @@ fn setup_tab_callbacks(
     let notebook_title = notebook.clone();
     let has_bell_title = Rc::clone(has_bell);
     terminal.set_on_title_change(move |title| {
-        // Check if title is locked (user-set or template)
+        // Check if title is locked (user-set or template), or if it comes
+        // from a pane other than the focused one
         {
             let tabs = tabs_title.borrow();
             if let Some(entry) = tabs.iter().find(|t| t.id == tab_id) {
-                if entry.title_locked {
+                if entry.title_locked || entry.pane_tree.focused() != pane_id {
                     return;
                 }
             }
@@ -2325,6 +3556,12 @@ fn setup_tab_callbacks(
         }
     });
 
+    // What the permission policy asks about waits for the user's answer
+    let notification_bar_permission = notification_bar.clone();
+    terminal.set_on_permission(move |capability| {
+        notification_bar_permission.ask_permission(pane_id, capability)
+    });
+
     // File transfer callback
     let file_manager_transfer = Rc::clone(file_manager);
     let notification_bar_transfer = notification_bar.clone();
@@ -2363,6 +3600,124 @@ fn setup_tab_callbacks(
     });
 }
 
+/// Open the panes of tmux as tabs when `tmux -CC` runs in `terminal`'s tab,
+/// which stays the gateway that talks to tmux
+#[allow(clippy::too_many_arguments)]
+fn setup_tmux_gateway(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    next_tab_id: &Rc<RefCell<u64>>,
+    config: &Rc<RefCell<Config>>,
+    theme: &Theme,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    has_bell: &Rc<RefCell<bool>>,
+    file_manager: &Rc<RefCell<PendingFileManager>>,
+    notification_bar: &NotificationBar,
+    terminal: &TerminalWidget,
+) {
+    let gateway: Rc<RefCell<Option<Arc<TmuxGateway>>>> = Rc::new(RefCell::new(None));
+    let pane_tabs: Rc<RefCell<HashMap<PaneId, u64>>> = Rc::new(RefCell::new(HashMap::new()));
+    let gateway_terminal = Arc::clone(terminal.terminal());
+    let notebook = notebook.clone();
+    let tabs = Rc::clone(tabs);
+    let next_tab_id = Rc::clone(next_tab_id);
+    let config = Rc::clone(config);
+    let theme = theme.clone();
+    let tab_bar = tab_bar.clone();
+    let window = window.clone();
+    let has_bell = Rc::clone(has_bell);
+    let file_manager = Rc::clone(file_manager);
+    let notification_bar = notification_bar.clone();
+    terminal.set_on_tmux_control(move |control| {
+        if control.contains(&TmuxControl::Started) {
+            let terminal = Arc::clone(&gateway_terminal);
+            *gateway.borrow_mut() = Some(TmuxGateway::new(move |data| {
+                if let Err(e) = terminal.lock().write(data) {
+                    log::error!("Failed to write to tmux: {}", e);
+                }
+            }));
+        }
+        let Some(gw) = gateway.borrow().clone() else {
+            return;
+        };
+        let pane_tab = |pane: PaneId| pane_tabs.borrow().get(&pane).copied();
+        for event in gw.handle(&control) {
+            match event {
+                TmuxEvent::PaneOpened {
+                    pane,
+                    title,
+                    cols,
+                    rows,
+                } => {
+                    let terminal = TerminalWidget::for_tmux_pane(
+                        &gw,
+                        pane,
+                        cols,
+                        rows,
+                        &config.borrow(),
+                        &theme,
+                    );
+                    let tab_id = generate_tab_id(&next_tab_id);
+                    let page_num =
+                        notebook.append_page(&panes::page(&terminal), None::<&gtk4::Widget>);
+                    tab_bar.add_tab(tab_id, &title);
+                    setup_tab_callbacks(
+                        &notebook,
+                        &tabs,
+                        &config,
+                        &tab_bar,
+                        &window,
+                        &has_bell,
+                        &file_manager,
+                        &notification_bar,
+                        &terminal,
+                        tab_id,
+                        false,
+                    );
+                    finalize_new_tab(
+                        &notebook, &tabs, &tab_bar, tab_id, page_num, title, terminal, false, None,
+                        None, None,
+                    );
+                    pane_tabs.borrow_mut().insert(pane, tab_id);
+                }
+                TmuxEvent::PaneResized { pane, cols, rows } => {
+                    let tabs = tabs.borrow();
+                    if let Some(tab) = tabs.iter().find(|t| Some(t.id) == pane_tab(pane)) {
+                        tab.terminal.resize_grid(cols, rows);
+                    }
+                }
+                TmuxEvent::PaneOutput { pane, data } => {
+                    let tabs = tabs.borrow();
+                    if let Some(tab) = tabs.iter().find(|t| Some(t.id) == pane_tab(pane)) {
+                        tab.terminal.feed(&data);
+                    }
+                }
+                TmuxEvent::PaneTitle { pane, title } => {
+                    let mut tabs = tabs.borrow_mut();
+                    if let Some(tab) = tabs.iter_mut().find(|t| Some(t.id) == pane_tab(pane)) {
+                        tab_bar.set_title(tab.id, &title);
+                        tab.title = title;
+                    }
+                }
+                TmuxEvent::PaneClosed { pane } => {
+                    if let Some(id) = pane_tabs.borrow_mut().remove(&pane) {
+                        remove_tab_from_ui(&notebook, &tabs, &tab_bar, &window, id);
+                    }
+                }
+                TmuxEvent::Exit { reason } => {
+                    log::info!("tmux control mode ended: {}", reason.unwrap_or_default());
+                    let ids: Vec<u64> = pane_tabs.borrow_mut().drain().map(|(_, id)| id).collect();
+                    for id in ids {
+                        remove_tab_from_ui(&notebook, &tabs, &tab_bar, &window, id);
+                    }
+                    *gateway.borrow_mut() = None;
+                }
+            }
+        }
+    });
+}
+
 /// Finalize a new tab: store entry, update visibility, switch to it, and focus
 #[allow(clippy::too_many_arguments)]
 fn finalize_new_tab(
@@ -2382,24 +3737,74 @@ fn finalize_new_tab(
         tab_bar.mark_tab_remote(tab_id);
     }
 
+    let widget = terminal.widget().clone();
     tabs.borrow_mut().push(TabEntry {
         id: tab_id,
         title,
-        terminal,
+        terminal: terminal.clone(),
+        panes: vec![PaneEntry {
+            id: tab_id,
+            terminal,
+            session_id: session_id.clone(),
+        }],
+        pane_tree: PaneTree::new(tab_id),
         title_locked,
         color: None,
         session_id,
         daemon_socket,
         remote_name,
+        template: None,
     });
 
     tab_bar.update_visibility();
     notebook.set_current_page(Some(page_num));
     tab_bar.set_active(tab_id);
 
-    if let Some(widget) = notebook.nth_page(Some(page_num)) {
-        widget.grab_focus();
-    }
+    widget.grab_focus();
+}
+
+/// Create a read-only tab playing back `recording`, read from `path`
+#[allow(clippy::too_many_arguments)]
+fn create_playback_tab(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    next_tab_id: &Rc<RefCell<u64>>,
+    config: &Rc<RefCell<Config>>,
+    theme: &Theme,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    has_bell: &Rc<RefCell<bool>>,
+    file_manager: &Rc<RefCell<PendingFileManager>>,
+    notification_bar: &NotificationBar,
+    recording: cterm_core::Recording,
+    path: &std::path::Path,
+) {
+    let title = recording.tab_title(path);
+
+    let terminal =
+        TerminalWidget::for_playback(cterm_core::Player::new(recording), &config.borrow(), theme);
+
+    let tab_id = generate_tab_id(next_tab_id);
+    let page_num = notebook.append_page(&panes::page(&terminal), None::<&gtk4::Widget>);
+    tab_bar.add_tab(tab_id, &title);
+
+    setup_tab_callbacks(
+        notebook,
+        tabs,
+        config,
+        tab_bar,
+        window,
+        has_bell,
+        file_manager,
+        notification_bar,
+        &terminal,
+        tab_id,
+        true,
+    );
+
+    finalize_new_tab(
+        notebook, tabs, tab_bar, tab_id, page_num, title, terminal, true, None, None, None,
+    );
 }
 
 /// Create a new terminal tab (daemon-backed via ctermd)
@@ -2471,7 +3876,59 @@ fn create_new_tab(
         None,
         false,
         None,
+        None,
+        None,
         daemon_socket,
+        None,
+        None,
+        None,
+        None,
+    );
+}
+
+/// Create a new tab with a profile's shell, environment, theme, font and
+/// scrollback size
+#[allow(clippy::too_many_arguments)]
+fn create_profile_tab(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    next_tab_id: &Rc<RefCell<u64>>,
+    config: &Rc<RefCell<Config>>,
+    theme: &Theme,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    has_bell: &Rc<RefCell<bool>>,
+    file_manager: &Rc<RefCell<PendingFileManager>>,
+    notification_bar: &NotificationBar,
+    profile: ProfileConfig,
+) {
+    let opts = profile.session_opts(&config.borrow().general);
+    let theme = profile.theme(theme);
+
+    spawn_daemon_tab(
+        notebook,
+        tabs,
+        next_tab_id,
+        config,
+        &theme,
+        tab_bar,
+        window,
+        has_bell,
+        file_manager,
+        notification_bar,
+        opts,
+        profile.name.clone(),
+        None,
+        None,
+        false,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(profile),
+        None,
     );
 }
 
@@ -2518,6 +3975,12 @@ fn create_docker_tab(
         false,
         None,
         None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
     );
 }
 
@@ -2527,6 +3990,9 @@ fn create_docker_tab(
 /// 1. `daemon_socket` — connect to a specific socket (e.g. SSH-tunneled remote)
 /// 2. `remote` — connect via RemoteManager (template-based remotes)
 /// 3. Neither — connect to local daemon
+///
+/// A `profile` sets the tab's font and scrollback size; its theme is
+/// expected in `theme`.
 #[allow(clippy::too_many_arguments)]
 fn spawn_daemon_tab(
     notebook: &Notebook,
@@ -2544,8 +4010,14 @@ fn spawn_daemon_tab(
     color: Option<String>,
     background_color: Option<String>,
     keep_open: bool,
+    command_notify: Option<cterm_app::config::CommandNotifyConfig>,
+    permissions: Option<cterm_core::PermissionPolicy>,
     remote: Option<(cterm_client::RemoteManager, String, String, bool)>,
     daemon_socket: Option<std::path::PathBuf>,
+    template: Option<String>,
+    reopened: Option<ClosedTab>,
+    profile: Option<ProfileConfig>,
+    on_open: Option<OnOpen>,
 ) {
     let notebook = notebook.clone();
     let tabs = Rc::clone(tabs);
@@ -2561,6 +4033,7 @@ fn spawn_daemon_tab(
     // Capture the remote name (if any) for the resulting TabEntry — enables
     // the "Disconnect" right-click menu item.
     let remote_name = remote.as_ref().map(|(_, name, _, _)| name.clone());
+    let mut on_open = on_open;
 
     let (tx, rx) = std::sync::mpsc::channel::<DaemonAttachResult>();
 
@@ -2595,17 +4068,32 @@ fn spawn_daemon_tab(
                         let sid = Some(session.session_id().to_string());
                         let daemon_socket = session.socket_path().map(|p| p.to_owned());
                         let cfg = config.borrow();
-                        let terminal = TerminalWidget::from_daemon(session, &cfg, &theme);
+                        let terminal = match profile {
+                            Some(ref profile) => {
+                                TerminalWidget::from_daemon(session, &profile.apply(&cfg), &theme)
+                            }
+                            None => TerminalWidget::from_daemon(session, &cfg, &theme),
+                        };
                         drop(cfg);
 
                         // Apply background color override from template
                         if let Some(ref bg) = background_color {
                             terminal.set_background_override(Some(bg));
                         }
+                        if let Some(notify) = command_notify {
+                            terminal.set_command_notify(notify);
+                        }
+                        if let Some(permissions) = permissions {
+                            terminal.set_permissions(permissions);
+                        }
+                        // A reopened tab shows its old scrollback above the new shell
+                        if let Some(ref closed) = reopened {
+                            closed.replay_scrollback(&mut terminal.terminal().lock());
+                        }
 
                         let tab_id = generate_tab_id(&next_tab_id);
                         let page_num =
-                            notebook.append_page(terminal.widget(), None::<&gtk4::Widget>);
+                            notebook.append_page(&panes::page(&terminal), None::<&gtk4::Widget>);
                         tab_bar.add_tab(tab_id, &title);
 
                         if let Some(ref c) = color {
@@ -2626,6 +4114,20 @@ fn spawn_daemon_tab(
                             keep_open,
                         );
 
+                        setup_tmux_gateway(
+                            &notebook,
+                            &tabs,
+                            &next_tab_id,
+                            &config,
+                            &theme,
+                            &tab_bar,
+                            &window,
+                            &has_bell,
+                            &file_manager,
+                            &notification_bar,
+                            &terminal,
+                        );
+
                         finalize_new_tab(
                             &notebook,
                             &tabs,
@@ -2640,12 +4142,10 @@ fn spawn_daemon_tab(
                             remote_name.clone(),
                         );
 
-                        // Store color in tab entry and send metadata to daemon
-                        if color.is_some() {
-                            if let Some(tab) = tabs.borrow_mut().iter_mut().find(|t| t.id == tab_id)
-                            {
-                                tab.color = color.clone();
-                            }
+                        // Store color and template in tab entry and send metadata to daemon
+                        if let Some(tab) = tabs.borrow_mut().iter_mut().find(|t| t.id == tab_id) {
+                            tab.color = color.clone();
+                            tab.template = template.clone();
                         }
                         // Persist tab metadata to daemon
                         if let Some(tab) = tabs.borrow().iter().find(|t| t.id == tab_id) {
@@ -2656,9 +4156,15 @@ fn spawn_daemon_tab(
                                 tab.terminal.set_template_name_on_daemon(&title);
                             }
                         }
+                        if let Some(on_open) = on_open.take() {
+                            on_open(Some(tab_id));
+                        }
                     }
                     Err(e) => {
                         log::error!("Failed to create daemon session: {}", e);
+                        if let Some(on_open) = on_open.take() {
+                            on_open(None);
+                        }
                     }
                 }
                 glib::ControlFlow::Break
@@ -2740,7 +4246,7 @@ fn create_daemon_tab(
 
                         let tab_id = generate_tab_id(&next_tab_id);
                         let page_num =
-                            notebook.append_page(terminal.widget(), None::<&gtk4::Widget>);
+                            notebook.append_page(&panes::page(&terminal), None::<&gtk4::Widget>);
                         tab_bar.add_tab(tab_id, &title);
 
                         setup_tab_callbacks(
@@ -2757,15 +4263,29 @@ fn create_daemon_tab(
                             false,
                         );
 
-                        finalize_new_tab(
+                        setup_tmux_gateway(
                             &notebook,
                             &tabs,
+                            &next_tab_id,
+                            &config,
+                            &theme,
                             &tab_bar,
-                            tab_id,
-                            page_num,
-                            title,
-                            terminal,
-                            false,
+                            &window,
+                            &has_bell,
+                            &file_manager,
+                            &notification_bar,
+                            &terminal,
+                        );
+
+                        finalize_new_tab(
+                            &notebook,
+                            &tabs,
+                            &tab_bar,
+                            tab_id,
+                            page_num,
+                            title,
+                            terminal,
+                            false,
                             Some(sid),
                             None,
                             None,
@@ -2786,6 +4306,10 @@ fn create_daemon_tab(
 type DaemonAttachResult =
     std::result::Result<cterm_client::SessionHandle, cterm_client::ClientError>;
 
+/// Called with the id of a new tab or pane once it is open, or with None if
+/// it failed to open
+type OnOpen = Box<dyn FnOnce(Option<u64>)>;
+
 /// Create a new terminal tab from a template
 #[allow(clippy::too_many_arguments)]
 fn create_tab_from_template(
@@ -2801,7 +4325,15 @@ fn create_tab_from_template(
     notification_bar: &NotificationBar,
     template: &cterm_app::config::StickyTabConfig,
     remote_manager: &cterm_client::RemoteManager,
+    reopened: Option<ClosedTab>,
+    on_open: Option<OnOpen>,
 ) {
+    // Take what the template leaves unset from its profile
+    let (template, profile) = template.resolve_profile(&config.borrow());
+    let template = &template;
+    let profile_theme = profile.as_ref().map(|p| p.theme(theme));
+    let theme = profile_theme.as_ref().unwrap_or(theme);
+
     // Prepare working directory (clone from git if needed)
     if let Some(ref working_dir) = template.working_directory {
         if let Err(e) =
@@ -2814,31 +4346,7 @@ fn create_tab_from_template(
     let cfg = config.borrow();
 
     // Build daemon session options from template
-    let opts = cterm_client::CreateSessionOpts {
-        cols: 80,
-        rows: 24,
-        shell: template
-            .command
-            .clone()
-            .or_else(|| cfg.general.default_shell.clone()),
-        args: if template.args.is_empty() && template.command.is_none() {
-            cfg.general.shell_args.clone()
-        } else {
-            template.args.clone()
-        },
-        cwd: template
-            .working_directory
-            .as_ref()
-            .map(|p| p.to_string_lossy().to_string()),
-        env: template
-            .env
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect(),
-        // SSH tabs open a native puressh connection on the daemon.
-        ssh: template.ssh.as_ref().map(|s| s.to_ssh_params()),
-        ..Default::default()
-    };
+    let opts = template_session_opts(template, profile.as_ref(), &cfg);
 
     // Resolve remote from template
     let remote_cfg = template
@@ -2873,12 +4381,55 @@ fn create_tab_from_template(
             template.color.clone(),
             template.background_color.clone(),
             template.keep_open,
+            template.command_notify,
+            template.permissions,
             remote,
             None,
+            Some(template.name.clone()),
+            reopened,
+            profile,
+            on_open,
         );
     }
 }
 
+/// Daemon session options for a tab or pane opened from `template`
+fn template_session_opts(
+    template: &cterm_app::config::StickyTabConfig,
+    profile: Option<&ProfileConfig>,
+    config: &Config,
+) -> cterm_client::CreateSessionOpts {
+    cterm_client::CreateSessionOpts {
+        cols: 80,
+        rows: 24,
+        shell: template
+            .command
+            .clone()
+            .or_else(|| config.general.default_shell.clone()),
+        args: if template.args.is_empty() && template.command.is_none() {
+            config.general.shell_args.clone()
+        } else {
+            template.args.clone()
+        },
+        cwd: template
+            .working_directory
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string()),
+        env: template
+            .env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        // SSH tabs open a native puressh connection on the daemon.
+        ssh: template.ssh.as_ref().map(|s| s.to_ssh_params()),
+        serial: template.serial.as_ref().map(|s| s.to_serial_params()),
+        startup_commands: template.startup_commands.clone(),
+        clear_env: !template.inherit_env,
+        scrollback_lines: profile.and_then(|p| p.scrollback_lines),
+        ..Default::default()
+    }
+}
+
 /// Close current tab (with confirmation if process is running)
 fn close_current_tab(
     notebook: &Notebook,
@@ -2904,6 +4455,7 @@ fn close_tab_by_id(
     tabs: &Rc<RefCell<Vec<TabEntry>>>,
     tab_bar: &TabBar,
     window: &ApplicationWindow,
+    config: &Rc<RefCell<Config>>,
     id: u64,
 ) {
     // Find index of this tab
@@ -2914,15 +4466,521 @@ fn close_tab_by_id(
 
     let Some(index) = index else { return };
 
-    // Destroy the daemon session (kill the PTY process)
+    // Destroy the daemon sessions (kill the PTY processes)
     {
         let tabs = tabs.borrow();
-        tabs[index].terminal.destroy_session();
+        remember_closed_tab(&tabs[index], config);
+        for pane in &tabs[index].panes {
+            pane.terminal.destroy_session();
+        }
     }
 
     remove_tab_from_ui(notebook, tabs, tab_bar, window, id);
 }
 
+/// Split the current tab's focused pane along `axis`, the new pane starting
+/// a shell in the same directory on the same daemon
+#[allow(clippy::too_many_arguments)]
+fn split_pane(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    next_tab_id: &Rc<RefCell<u64>>,
+    config: &Rc<RefCell<Config>>,
+    theme: &Theme,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    has_bell: &Rc<RefCell<bool>>,
+    file_manager: &Rc<RefCell<PendingFileManager>>,
+    notification_bar: &NotificationBar,
+    axis: SplitAxis,
+) {
+    let Some((tab_id, cwd, daemon_socket)) = notebook.current_page().and_then(|page| {
+        let tabs = tabs.borrow();
+        let tab = tabs.get(page as usize)?;
+        #[cfg(unix)]
+        let cwd = tab.terminal.foreground_cwd();
+        #[cfg(not(unix))]
+        let cwd: Option<String> = None;
+        Some((tab.id, cwd, tab.daemon_socket.clone()))
+    }) else {
+        return;
+    };
+
+    let opts = {
+        let cfg = config.borrow();
+        if daemon_socket.is_some() {
+            cterm_client::CreateSessionOpts {
+                cols: 80,
+                rows: 24,
+                cwd,
+                ..Default::default()
+            }
+        } else {
+            cterm_client::CreateSessionOpts {
+                cols: 80,
+                rows: 24,
+                shell: cfg.general.default_shell.clone(),
+                args: cfg.general.shell_args.clone(),
+                cwd,
+                ..Default::default()
+            }
+        }
+    };
+
+    split_tab(
+        notebook,
+        tabs,
+        next_tab_id,
+        config,
+        theme,
+        tab_bar,
+        window,
+        has_bell,
+        file_manager,
+        notification_bar,
+        tab_id,
+        axis,
+        opts,
+        daemon_socket,
+        None,
+    );
+}
+
+/// Split the focused pane of tab `tab_id` along `axis`, the new pane running
+/// a session started with `opts` on the daemon at `daemon_socket` (the local
+/// one if None)
+#[allow(clippy::too_many_arguments)]
+fn split_tab(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    next_tab_id: &Rc<RefCell<u64>>,
+    config: &Rc<RefCell<Config>>,
+    theme: &Theme,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    has_bell: &Rc<RefCell<bool>>,
+    file_manager: &Rc<RefCell<PendingFileManager>>,
+    notification_bar: &NotificationBar,
+    tab_id: u64,
+    axis: SplitAxis,
+    opts: cterm_client::CreateSessionOpts,
+    daemon_socket: Option<std::path::PathBuf>,
+    on_open: Option<OnOpen>,
+) {
+    let notebook = notebook.clone();
+    let tabs = Rc::clone(tabs);
+    let next_tab_id = Rc::clone(next_tab_id);
+    let config = Rc::clone(config);
+    let theme = theme.clone();
+    let tab_bar = tab_bar.clone();
+    let window = window.clone();
+    let has_bell = Rc::clone(has_bell);
+    let file_manager = Rc::clone(file_manager);
+    let notification_bar = notification_bar.clone();
+    let mut on_open = on_open;
+
+    let (tx, rx) = std::sync::mpsc::channel::<DaemonAttachResult>();
+
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+
+        let result = match rt {
+            Ok(rt) => rt.block_on(async {
+                let conn = if let Some(ref path) = daemon_socket {
+                    cterm_client::DaemonConnection::connect_unix(path, false).await?
+                } else {
+                    cterm_client::DaemonConnection::connect_local().await?
+                };
+                conn.create_session(opts).await
+            }),
+            Err(e) => Err(cterm_client::ClientError::Connection(e.to_string())),
+        };
+
+        let _ = tx.send(result);
+    });
+
+    glib::timeout_add_local(std::time::Duration::from_millis(50), move || {
+        match rx.try_recv() {
+            Ok(Ok(session)) => {
+                let session_id = Some(session.session_id().to_string());
+                let cfg = config.borrow();
+                let terminal = TerminalWidget::from_daemon(session, &cfg, &theme);
+                drop(cfg);
+
+                // The tab may have closed while the session started
+                let focused = tabs
+                    .borrow()
+                    .iter()
+                    .find(|t| t.id == tab_id)
+                    .map(|t| (t.pane_tree.focused(), t.terminal.clone()));
+                let Some((split_id, focused)) = focused else {
+                    terminal.destroy_session();
+                    if let Some(on_open) = on_open.take() {
+                        on_open(None);
+                    }
+                    return glib::ControlFlow::Break;
+                };
+                // Moving the focused widget can move focus: no borrow here
+                panes::split(focused.widget(), terminal.widget(), axis);
+
+                let pane_id = generate_tab_id(&next_tab_id);
+                if let Some(tab) = tabs.borrow_mut().iter_mut().find(|t| t.id == tab_id) {
+                    tab.pane_tree.split(split_id, axis, pane_id);
+                    tab.panes.push(PaneEntry {
+                        id: pane_id,
+                        terminal: terminal.clone(),
+                        session_id,
+                    });
+                    tab.terminal = terminal.clone();
+                }
+
+                setup_pane_callbacks(
+                    &notebook,
+                    &tabs,
+                    &config,
+                    &tab_bar,
+                    &window,
+                    &has_bell,
+                    &file_manager,
+                    &notification_bar,
+                    &terminal,
+                    tab_id,
+                    pane_id,
+                    false,
+                );
+                terminal.widget().grab_focus();
+                if let Some(on_open) = on_open.take() {
+                    on_open(Some(pane_id));
+                }
+                glib::ControlFlow::Break
+            }
+            Ok(Err(e)) => {
+                log::error!("Failed to create daemon session for pane: {}", e);
+                if let Some(on_open) = on_open.take() {
+                    on_open(None);
+                }
+                glib::ControlFlow::Break
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => glib::ControlFlow::Continue,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => glib::ControlFlow::Break,
+        }
+    });
+}
+
+/// Opens the tabs of a workspace one after another, splitting each for its
+/// panes once it is open
+#[derive(Clone)]
+struct WorkspaceOpener {
+    notebook: Notebook,
+    tabs: Rc<RefCell<Vec<TabEntry>>>,
+    next_tab_id: Rc<RefCell<u64>>,
+    config: Rc<RefCell<Config>>,
+    theme: Theme,
+    tab_bar: TabBar,
+    window: ApplicationWindow,
+    has_bell: Rc<RefCell<bool>>,
+    file_manager: Rc<RefCell<PendingFileManager>>,
+    notification_bar: NotificationBar,
+    remote_manager: cterm_client::RemoteManager,
+}
+
+impl WorkspaceOpener {
+    /// Open workspace `name`
+    fn open(&self, name: &str) {
+        let Some(workspace) = workspace::find(&self.config.borrow(), name).cloned() else {
+            log::warn!("No workspace named {:?}", name);
+            return;
+        };
+        let templates = cterm_app::config::load_sticky_tabs().unwrap_or_default();
+        self.open_tabs(workspace::resolve(&workspace, &templates).into());
+    }
+
+    fn open_tabs(&self, mut queue: VecDeque<WorkspaceTab>) {
+        let Some(tab) = queue.pop_front() else {
+            return;
+        };
+        let opener = self.clone();
+        let panes: VecDeque<_> = tab.panes.into();
+        let on_open: OnOpen = Box::new(move |tab_id| match tab_id {
+            Some(tab_id) => opener.open_panes(tab_id, panes, queue),
+            None => opener.open_tabs(queue),
+        });
+        create_tab_from_template(
+            &self.notebook,
+            &self.tabs,
+            &self.next_tab_id,
+            &self.config,
+            &self.theme,
+            &self.tab_bar,
+            &self.window,
+            &self.has_bell,
+            &self.file_manager,
+            &self.notification_bar,
+            &tab.template,
+            &self.remote_manager,
+            None,
+            Some(on_open),
+        );
+    }
+
+    fn open_panes(
+        &self,
+        tab_id: u64,
+        mut panes: VecDeque<WorkspacePane>,
+        queue: VecDeque<WorkspaceTab>,
+    ) {
+        let Some(pane) = panes.pop_front() else {
+            self.open_tabs(queue);
+            return;
+        };
+        let daemon_socket = self
+            .tabs
+            .borrow()
+            .iter()
+            .find(|t| t.id == tab_id)
+            .and_then(|t| t.daemon_socket.clone());
+        let opts = {
+            let cfg = self.config.borrow();
+            let (template, profile) = pane.template.resolve_profile(&cfg);
+            template_session_opts(&template, profile.as_ref(), &cfg)
+        };
+        let opener = self.clone();
+        split_tab(
+            &self.notebook,
+            &self.tabs,
+            &self.next_tab_id,
+            &self.config,
+            &self.theme,
+            &self.tab_bar,
+            &self.window,
+            &self.has_bell,
+            &self.file_manager,
+            &self.notification_bar,
+            tab_id,
+            pane.axis,
+            opts,
+            daemon_socket,
+            Some(Box::new(move |_| opener.open_panes(tab_id, panes, queue))),
+        );
+    }
+}
+
+/// Close pane `pane_id` of tab `tab_id` (unconditionally), or the whole tab
+/// if it is the last pane
+fn close_pane(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    config: &Rc<RefCell<Config>>,
+    tab_id: u64,
+    pane_id: u64,
+) {
+    let is_split = tabs
+        .borrow()
+        .iter()
+        .find(|t| t.id == tab_id)
+        .is_some_and(|t| t.pane_tree.is_split());
+    if !is_split {
+        close_tab_by_id(notebook, tabs, tab_bar, window, config, tab_id);
+        return;
+    }
+
+    let (pane, focused) = {
+        let mut tabs = tabs.borrow_mut();
+        let Some(tab) = tabs.iter_mut().find(|t| t.id == tab_id) else {
+            return;
+        };
+        let Some(index) = tab.panes.iter().position(|p| p.id == pane_id) else {
+            return;
+        };
+        let pane = tab.panes.remove(index);
+        tab.pane_tree.remove(pane_id);
+        tab.sync_scroll_peers();
+        if let Some(focused) = tab.pane(tab.pane_tree.focused()) {
+            tab.terminal = focused.terminal.clone();
+        }
+        (pane, tab.terminal.clone())
+    };
+
+    pane.terminal.destroy_session();
+    panes::remove(pane.terminal.widget());
+    focused.widget().grab_focus();
+}
+
+/// Make pane `pane_id` the focused pane of tab `tab_id`, the tab taking its
+/// title
+fn focus_pane(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    tab_id: u64,
+    pane_id: u64,
+) {
+    let mut tabs_mut = tabs.borrow_mut();
+    let Some(page) = tabs_mut.iter().position(|t| t.id == tab_id) else {
+        return;
+    };
+    let tab = &mut tabs_mut[page];
+    if tab.pane_tree.focused() == pane_id {
+        return;
+    }
+    let Some(terminal) = tab.pane(pane_id).map(|p| p.terminal.clone()) else {
+        return;
+    };
+    tab.pane_tree.focus(pane_id);
+    tab.terminal = terminal;
+    if tab.title_locked {
+        return;
+    }
+
+    let title = tab.terminal.terminal().lock().title().to_string();
+    if title.is_empty() {
+        return;
+    }
+    tab.title = title.clone();
+    drop(tabs_mut);
+    tab_bar.set_title(tab_id, &title);
+    if notebook.current_page() == Some(page as u32) {
+        window.set_title(Some(&title));
+    }
+}
+
+/// What reopening a tab needs: its template or working directory, title,
+/// color and scrollback
+fn closed_tab(tab: &TabEntry) -> ClosedTab {
+    let mut closed = ClosedTab::new(tab.title.clone());
+    closed.template = tab.template.clone();
+    closed.color = tab.color.clone();
+    #[cfg(unix)]
+    {
+        closed.cwd = tab.terminal.foreground_cwd();
+    }
+    closed.capture_scrollback(tab.terminal.terminal().lock().screen());
+    closed
+}
+
+/// Record a closing tab for Reopen Closed Tab and the usage statistics
+fn remember_closed_tab(tab: &TabEntry, config: &Rc<RefCell<Config>>) {
+    let closed = closed_tab(tab);
+    cterm_app::usage_stats::record_session(tab.terminal.terminal().lock().age());
+    closed_tabs::remember(closed, &config.borrow().tabs);
+}
+
+/// Reopen the most recently closed tab with its template or working
+/// directory, title, color and scrollback
+#[allow(clippy::too_many_arguments)]
+fn reopen_closed_tab(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    next_tab_id: &Rc<RefCell<u64>>,
+    config: &Rc<RefCell<Config>>,
+    theme: &Theme,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    has_bell: &Rc<RefCell<bool>>,
+    file_manager: &Rc<RefCell<PendingFileManager>>,
+    notification_bar: &NotificationBar,
+    remote_manager: &cterm_client::RemoteManager,
+) {
+    let Some(closed) = closed_tabs::take_last() else {
+        return;
+    };
+    open_closed_tab(
+        notebook,
+        tabs,
+        next_tab_id,
+        config,
+        theme,
+        tab_bar,
+        window,
+        has_bell,
+        file_manager,
+        notification_bar,
+        remote_manager,
+        closed,
+    );
+}
+
+/// Open a tab like `closed` from its template or in its working directory,
+/// with its title, color and scrollback
+#[allow(clippy::too_many_arguments)]
+fn open_closed_tab(
+    notebook: &Notebook,
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    next_tab_id: &Rc<RefCell<u64>>,
+    config: &Rc<RefCell<Config>>,
+    theme: &Theme,
+    tab_bar: &TabBar,
+    window: &ApplicationWindow,
+    has_bell: &Rc<RefCell<bool>>,
+    file_manager: &Rc<RefCell<PendingFileManager>>,
+    notification_bar: &NotificationBar,
+    remote_manager: &cterm_client::RemoteManager,
+    closed: ClosedTab,
+) {
+    let templates = cterm_app::config::load_sticky_tabs().unwrap_or_default();
+    if let Some(template) = closed.find_template(&templates) {
+        create_tab_from_template(
+            notebook,
+            tabs,
+            next_tab_id,
+            config,
+            theme,
+            tab_bar,
+            window,
+            has_bell,
+            file_manager,
+            notification_bar,
+            template,
+            remote_manager,
+            Some(closed.clone()),
+            None,
+        );
+        return;
+    }
+
+    let cfg = config.borrow();
+    let opts = cterm_client::CreateSessionOpts {
+        cols: 80,
+        rows: 24,
+        shell: cfg.general.default_shell.clone(),
+        args: cfg.general.shell_args.clone(),
+        cwd: closed.cwd.clone(),
+        ..Default::default()
+    };
+    drop(cfg);
+
+    spawn_daemon_tab(
+        notebook,
+        tabs,
+        next_tab_id,
+        config,
+        theme,
+        tab_bar,
+        window,
+        has_bell,
+        file_manager,
+        notification_bar,
+        opts,
+        closed.title.clone(),
+        closed.color.clone(),
+        None,
+        false,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(closed),
+        None,
+        None,
+    );
+}
+
 /// Remove a tab from the UI (notebook, tabs vec, tab bar) WITHOUT issuing any
 /// destroy/detach RPC. Closes the window when the last tab is gone.
 ///
@@ -2953,12 +5011,7 @@ fn remove_tab_from_ui(
     }
 
     sync_tab_bar_active(tab_bar, tabs, notebook);
-
-    if let Some(page) = notebook.current_page() {
-        if let Some(widget) = notebook.nth_page(Some(page)) {
-            widget.grab_focus();
-        }
-    }
+    focus_current_terminal(notebook, tabs);
 }
 
 /// Disconnect from a remote: send `detach` to each tab's daemon session
@@ -2982,7 +5035,9 @@ fn disconnect_remote(
             .iter()
             .filter(|t| t.remote_name.as_deref() == Some(remote_name))
             .map(|t| {
-                t.terminal.detach_session();
+                for pane in &t.panes {
+                    pane.terminal.detach_session();
+                }
                 t.id
             })
             .collect()
@@ -3031,7 +5086,7 @@ fn request_close_tab_by_id(
 ) {
     let confirm_close = config.borrow().general.confirm_close_with_running;
     if !confirm_close {
-        close_tab_by_id(notebook, tabs, tab_bar, window, id);
+        close_tab_by_id(notebook, tabs, tab_bar, window, config, id);
         return;
     }
 
@@ -3052,7 +5107,7 @@ fn request_close_tab_by_id(
     };
 
     if session_id.is_empty() {
-        close_tab_by_id(notebook, tabs, tab_bar, window, id);
+        close_tab_by_id(notebook, tabs, tab_bar, window, config, id);
         return;
     }
 
@@ -3061,6 +5116,7 @@ fn request_close_tab_by_id(
     let tabs = Rc::clone(tabs);
     let tab_bar = tab_bar.clone();
     let window = window.clone();
+    let config = Rc::clone(config);
     let (result_tx, result_rx) = std::sync::mpsc::channel();
 
     std::thread::spawn(move || {
@@ -3102,7 +5158,7 @@ fn request_close_tab_by_id(
             Ok(Some(name)) => name,
             _ => {
                 // No foreground process or query failed — close directly
-                close_tab_by_id(&notebook, &tabs, &tab_bar, &window, id);
+                close_tab_by_id(&notebook, &tabs, &tab_bar, &window, &config, id);
                 return;
             }
         };
@@ -3111,13 +5167,21 @@ fn request_close_tab_by_id(
         let notebook_c = notebook.clone();
         let tabs_c = Rc::clone(&tabs);
         let tab_bar_c = tab_bar.clone();
+        let config_c = Rc::clone(&config);
 
         dialogs::show_close_confirmation_dialog(
             &window,
             vec![(tab_title, process_name)],
             move |confirmed| {
                 if confirmed {
-                    close_tab_by_id(&notebook_c, &tabs_c, &tab_bar_c, &window_for_closure, id);
+                    close_tab_by_id(
+                        &notebook_c,
+                        &tabs_c,
+                        &tab_bar_c,
+                        &window_for_closure,
+                        &config_c,
+                        id,
+                    );
                 }
             },
         );
@@ -3131,10 +5195,10 @@ fn request_close_tab_by_id(
     tabs: &Rc<RefCell<Vec<TabEntry>>>,
     tab_bar: &TabBar,
     window: &ApplicationWindow,
-    _config: &Rc<RefCell<Config>>,
+    config: &Rc<RefCell<Config>>,
     id: u64,
 ) {
-    close_tab_by_id(notebook, tabs, tab_bar, window, id);
+    close_tab_by_id(notebook, tabs, tab_bar, window, config, id);
 }
 
 /// Close all tabs except the current one
@@ -3188,10 +5252,30 @@ fn close_other_tabs(
 
 /// Sync tab bar active state with notebook
 /// Focus the terminal widget in the currently visible notebook page
-fn focus_current_terminal(notebook: &Notebook) {
+/// Toggle presentation mode for every tab in the window
+fn toggle_presentation_mode(
+    tabs: &Rc<RefCell<Vec<TabEntry>>>,
+    tab_bar: &TabBar,
+    presenting: &Rc<RefCell<bool>>,
+    config: &Rc<RefCell<Config>>,
+) {
+    let on = {
+        let mut presenting = presenting.borrow_mut();
+        *presenting = !*presenting;
+        *presenting
+    };
+    let config = config.borrow();
+    let presentation = on.then_some(&config.presentation);
+    for pane in tabs.borrow().iter().flat_map(|t| &t.panes) {
+        pane.terminal.set_presentation(presentation);
+    }
+    tab_bar.set_force_hidden(on);
+}
+
+fn focus_current_terminal(notebook: &Notebook, tabs: &Rc<RefCell<Vec<TabEntry>>>) {
     if let Some(page) = notebook.current_page() {
-        if let Some(widget) = notebook.nth_page(Some(page)) {
-            widget.grab_focus();
+        if let Some(tab) = tabs.borrow().get(page as usize) {
+            tab.terminal.widget().grab_focus();
         }
     }
 }
@@ -1,6 +1,8 @@
 //! Async PTY reader task
 
 use crate::session::{OutputData, SessionState};
+use cterm_core::term::TerminalEvent;
+use cterm_core::ThroughputMeter;
 use std::io::Read;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -26,12 +28,24 @@ impl PtyReader {
         let mut reader = self.reader;
 
         loop {
-            let read_result = tokio::task::spawn_blocking(move || {
+            let mut read = tokio::task::spawn_blocking(move || {
                 let mut buf = [0u8; 8192];
                 let n = reader.read(&mut buf);
                 (n, buf, reader)
-            })
-            .await;
+            });
+
+            // While waiting for output, send the final throughput report once
+            // it has stopped
+            let read_result = loop {
+                tokio::select! {
+                    result = &mut read => break result,
+                    _ = tokio::time::sleep(ThroughputMeter::INTERVAL) => {
+                        if let Some(stats) = session.poll_output_stats() {
+                            session.broadcast_event(TerminalEvent::OutputStats(stats));
+                        }
+                    }
+                }
+            };
 
             let (n, data, returned) = match read_result {
                 Ok(triple) => triple,
@@ -66,7 +80,7 @@ impl PtyReader {
 
                     // Broadcast events; set alerted state on bell
                     for event in &events {
-                        if matches!(event, cterm_core::term::TerminalEvent::Bell) {
+                        if matches!(event, TerminalEvent::Bell) {
                             session.set_alerted(true);
                         }
                     }
@@ -1,15 +1,15 @@
 //! gRPC TerminalService implementation
 
 use crate::convert::{
-    cell_to_proto, cursor_to_proto, event_to_proto, modes_to_proto, proto_to_key,
-    proto_to_modifiers, screen_to_proto, screen_to_text, visible_rows_to_proto,
+    cell_to_proto, cursor_to_proto, event_to_proto, images_to_proto, modes_to_proto, proto_to_key,
+    proto_to_modifiers, proto_to_selection, screen_to_proto, screen_to_text, visible_rows_to_proto,
 };
 use crate::proto::terminal_service_server::TerminalService;
 use crate::proto::*;
 use crate::session::SessionManager;
 #[cfg(unix)]
 use libc;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
@@ -20,6 +20,10 @@ use tokio_stream::{
 };
 use tonic::{Request, Response, Status};
 
+/// Largest read/write chunk for remote file transfer RPCs, kept well under
+/// tonic's default 4 MiB message limit
+const MAX_REMOTE_CHUNK: usize = 1024 * 1024;
+
 /// TerminalService implementation
 pub struct TerminalServiceImpl {
     session_manager: Arc<SessionManager>,
@@ -75,6 +79,50 @@ impl TerminalService for TerminalServiceImpl {
 
         let cols = req.cols.max(1) as usize;
         let rows = req.rows.max(1) as usize;
+        let startup_commands = req.startup_commands;
+        let scrollback_lines = req.scrollback_lines.map(|n| n as usize);
+
+        // Serial session: talk to a device instead of a local shell.
+        if let Some(serial) = req.serial {
+            let serial_config = cterm_core::SerialConfig {
+                baud_rate: match serial.baud_rate {
+                    0 => 115200,
+                    n => n,
+                },
+                // Out-of-range values are rejected when the port is opened.
+                data_bits: match serial.data_bits {
+                    0 => 8,
+                    n => n.try_into().unwrap_or(u8::MAX),
+                },
+                parity: match serial.parity() {
+                    SerialParity::None => cterm_core::Parity::None,
+                    SerialParity::Odd => cterm_core::Parity::Odd,
+                    SerialParity::Even => cterm_core::Parity::Even,
+                },
+                stop_bits: match serial.stop_bits {
+                    0 => 1,
+                    n => n.try_into().unwrap_or(u8::MAX),
+                },
+                flow_control: match serial.flow_control() {
+                    SerialFlowControl::None => cterm_core::FlowControl::None,
+                    SerialFlowControl::Software => cterm_core::FlowControl::Software,
+                    SerialFlowControl::Hardware => cterm_core::FlowControl::Hardware,
+                },
+                ..cterm_core::SerialConfig::new(serial.path, 0)
+            };
+
+            let session = self
+                .session_manager
+                .create_serial_session(cols, rows, serial_config, scrollback_lines)
+                .map_err(Status::from)?;
+            session.spawn_startup_commands(startup_commands);
+
+            return Ok(Response::new(CreateSessionResponse {
+                session_id: session.id.clone(),
+                cols: cols as u32,
+                rows: rows as u32,
+            }));
+        }
 
         // Native SSH session: open a puressh connection instead of a local shell.
         if let Some(ssh) = req.ssh {
@@ -101,6 +149,8 @@ impl TerminalService for TerminalServiceImpl {
                 // Interactive shell PTY; compression is left off (small,
                 // latency-sensitive traffic).
                 compress: false,
+                keepalive_interval: (ssh.keepalive_interval > 0)
+                    .then(|| std::time::Duration::from_secs(ssh.keepalive_interval.into())),
                 host_key_prompt: None,
                 password_prompt: None,
                 passphrase_prompt: None,
@@ -112,8 +162,9 @@ impl TerminalService for TerminalServiceImpl {
 
             let session = self
                 .session_manager
-                .create_ssh_session(cols, rows, ssh_config)
+                .create_ssh_session(cols, rows, ssh_config, scrollback_lines)
                 .map_err(Status::from)?;
+            session.spawn_startup_commands(startup_commands);
 
             return Ok(Response::new(CreateSessionResponse {
                 session_id: session.id.clone(),
@@ -123,6 +174,10 @@ impl TerminalService for TerminalServiceImpl {
         }
 
         let env: Vec<(String, String)> = req.env.into_iter().collect();
+        for (key, value) in &env {
+            cterm_core::validate_env_var(key, value)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        }
 
         let session = self
             .session_manager
@@ -133,9 +188,12 @@ impl TerminalService for TerminalServiceImpl {
                 req.args,
                 req.cwd.map(PathBuf::from),
                 env,
+                req.clear_env,
                 req.term,
+                scrollback_lines,
             )
             .map_err(Status::from)?;
+        session.spawn_startup_commands(startup_commands);
 
         Ok(Response::new(CreateSessionResponse {
             session_id: session.id.clone(),
@@ -168,6 +226,10 @@ impl TerminalService for TerminalServiceImpl {
                     has_foreground_process: s.has_foreground_process(),
                     foreground_process_name: s.foreground_process_name().unwrap_or_default(),
                     alerted: s.is_alerted(),
+                    badge: s.badge(),
+                    user_vars: s.user_vars(),
+                    ssh: s.is_ssh(),
+                    cwd: s.current_dir(),
                 }
             })
             .collect();
@@ -202,6 +264,10 @@ impl TerminalService for TerminalServiceImpl {
             has_foreground_process: session.has_foreground_process(),
             foreground_process_name: session.foreground_process_name().unwrap_or_default(),
             alerted: session.is_alerted(),
+            badge: session.badge(),
+            user_vars: session.user_vars(),
+            ssh: session.is_ssh(),
+            cwd: session.current_dir(),
         };
 
         Ok(Response::new(GetSessionResponse {
@@ -500,6 +566,77 @@ impl TerminalService for TerminalServiceImpl {
         Ok(Response::new(GetScreenTextResponse { lines }))
     }
 
+    async fn get_screenshot(
+        &self,
+        request: Request<GetScreenshotRequest>,
+    ) -> Result<Response<GetScreenshotResponse>, Status> {
+        let req = request.into_inner();
+        let session = self
+            .session_manager
+            .get_session(&req.session_id)
+            .map_err(Status::from)?;
+
+        let renderer = cterm_core::ScreenRenderer::default()
+            .with_scale(req.scale as usize)
+            .with_cursor(!req.hide_cursor);
+        let shot = session.with_terminal(|term| renderer.render(term.screen()));
+        let png = shot
+            .to_png()
+            .map_err(|e| Status::internal(format!("PNG encoding failed: {}", e)))?;
+
+        Ok(Response::new(GetScreenshotResponse {
+            png,
+            width: shot.width as u32,
+            height: shot.height as u32,
+        }))
+    }
+
+    // ========================================================================
+    // Graphics
+    // ========================================================================
+
+    async fn list_images(
+        &self,
+        request: Request<ListImagesRequest>,
+    ) -> Result<Response<ListImagesResponse>, Status> {
+        let req = request.into_inner();
+        let session = self
+            .session_manager
+            .get_session(&req.session_id)
+            .map_err(Status::from)?;
+
+        let images = session.with_terminal(|term| images_to_proto(term.screen()));
+
+        Ok(Response::new(ListImagesResponse { images }))
+    }
+
+    async fn clear_images(
+        &self,
+        request: Request<ClearImagesRequest>,
+    ) -> Result<Response<ClearImagesResponse>, Status> {
+        let req = request.into_inner();
+        let session = self
+            .session_manager
+            .get_session(&req.session_id)
+            .map_err(Status::from)?;
+
+        let removed = session.with_terminal_mut(|term| {
+            let screen = term.screen_mut();
+            if req.ids.is_empty() {
+                screen.clear_images()
+            } else {
+                req.ids
+                    .iter()
+                    .filter(|&&id| screen.remove_image(id))
+                    .count()
+            }
+        });
+
+        Ok(Response::new(ClearImagesResponse {
+            removed: removed as u32,
+        }))
+    }
+
     // ========================================================================
     // Control
     // ========================================================================
@@ -549,6 +686,43 @@ impl TerminalService for TerminalServiceImpl {
         Ok(Response::new(ClearAlertResponse {}))
     }
 
+    async fn start_recording(
+        &self,
+        request: Request<StartRecordingRequest>,
+    ) -> Result<Response<StartRecordingResponse>, Status> {
+        let req = request.into_inner();
+        let session = self
+            .session_manager
+            .get_session(&req.session_id)
+            .map_err(Status::from)?;
+        if req.path.is_empty() {
+            return Err(Status::invalid_argument("path is required"));
+        }
+
+        session
+            .with_terminal_mut(|term| term.start_recording(Path::new(&req.path)))
+            .map_err(|e| Status::internal(format!("Failed to start recording: {}", e)))?;
+
+        Ok(Response::new(StartRecordingResponse { success: true }))
+    }
+
+    async fn stop_recording(
+        &self,
+        request: Request<StopRecordingRequest>,
+    ) -> Result<Response<StopRecordingResponse>, Status> {
+        let req = request.into_inner();
+        let session = self
+            .session_manager
+            .get_session(&req.session_id)
+            .map_err(Status::from)?;
+
+        let was_recording = session
+            .with_terminal_mut(|term| term.stop_recording())
+            .map_err(|e| Status::internal(format!("Failed to finish recording: {}", e)))?;
+
+        Ok(Response::new(StopRecordingResponse { was_recording }))
+    }
+
     // ========================================================================
     // Event Streaming
     // ========================================================================
@@ -569,7 +743,7 @@ impl TerminalService for TerminalServiceImpl {
         let rx = session.subscribe_events();
         let session_id = req.session_id.clone();
         let events = BroadcastStream::new(rx).filter_map(move |result| match result {
-            Ok(event) => Some(Ok(event_to_proto(&event))),
+            Ok(event) => event_to_proto(&event).map(Ok),
             Err(BroadcastStreamRecvError::Lagged(count)) => {
                 log::warn!(
                     "stream_events: client lagged, dropped {} events for session {}",
@@ -615,6 +789,146 @@ impl TerminalService for TerminalServiceImpl {
         Ok(Response::new(RespondPromptResponse { success }))
     }
 
+    async fn respond_upload(
+        &self,
+        request: Request<RespondUploadRequest>,
+    ) -> Result<Response<RespondUploadResponse>, Status> {
+        let req = request.into_inner();
+        let session = self
+            .session_manager
+            .get_session(&req.session_id)
+            .map_err(Status::from)?;
+
+        let files: Vec<cterm_core::TransferFile> = req
+            .files
+            .into_iter()
+            .map(|file| cterm_core::TransferFile {
+                name: file.name,
+                data: file.data,
+            })
+            .collect();
+        run_blocking(move || {
+            let data = if files.is_empty() {
+                cterm_core::iterm2::UPLOAD_ABORT.to_vec()
+            } else {
+                cterm_core::iterm2::encode_upload(&files)?
+            };
+            session.write_input(&data)?;
+            Ok(())
+        })
+        .await?;
+
+        Ok(Response::new(RespondUploadResponse { success: true }))
+    }
+
+    async fn get_clipboard(
+        &self,
+        request: Request<GetClipboardRequest>,
+    ) -> Result<Response<GetClipboardResponse>, Status> {
+        let selection = proto_to_selection(request.into_inner().selection());
+        let data = self.session_manager.clipboard().get(selection);
+        Ok(Response::new(GetClipboardResponse { data }))
+    }
+
+    async fn set_clipboard(
+        &self,
+        request: Request<SetClipboardRequest>,
+    ) -> Result<Response<SetClipboardResponse>, Status> {
+        let req = request.into_inner();
+        let selection = proto_to_selection(req.selection());
+        self.session_manager.clipboard().set(selection, req.data);
+        Ok(Response::new(SetClipboardResponse {}))
+    }
+
+    // ========================================================================
+    // Remote File Transfer
+    // ========================================================================
+
+    async fn list_remote_dir(
+        &self,
+        request: Request<ListRemoteDirRequest>,
+    ) -> Result<Response<ListRemoteDirResponse>, Status> {
+        let req = request.into_inner();
+        let session = self
+            .session_manager
+            .get_session(&req.session_id)
+            .map_err(Status::from)?;
+
+        let mut path = req.path;
+        if path.is_empty() {
+            path = session.current_dir();
+        }
+        let (path, entries) = run_blocking(move || {
+            session.with_remote_fs(|fs| {
+                let path = fs.realpath(&path)?;
+                let entries = fs.list_dir(&path)?;
+                Ok((path, entries))
+            })
+        })
+        .await?;
+
+        Ok(Response::new(ListRemoteDirResponse {
+            path,
+            entries: entries
+                .into_iter()
+                .map(|e| RemoteFileEntry {
+                    name: e.name,
+                    is_dir: e.is_dir,
+                    size: e.size,
+                    mtime: e.mtime,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn read_remote_file(
+        &self,
+        request: Request<ReadRemoteFileRequest>,
+    ) -> Result<Response<ReadRemoteFileResponse>, Status> {
+        let req = request.into_inner();
+        let session = self
+            .session_manager
+            .get_session(&req.session_id)
+            .map_err(Status::from)?;
+
+        let length = (req.length as usize).min(MAX_REMOTE_CHUNK);
+        let (data, size) = run_blocking(move || {
+            session.with_remote_fs(|fs| {
+                let size = fs.file_size(&req.path)?;
+                let data = fs.read_at(&req.path, req.offset, length)?;
+                Ok((data, size))
+            })
+        })
+        .await?;
+
+        Ok(Response::new(ReadRemoteFileResponse { data, size }))
+    }
+
+    async fn write_remote_file(
+        &self,
+        request: Request<WriteRemoteFileRequest>,
+    ) -> Result<Response<WriteRemoteFileResponse>, Status> {
+        let req = request.into_inner();
+        if req.data.len() > MAX_REMOTE_CHUNK {
+            return Err(Status::invalid_argument(format!(
+                "Chunk too large: {} bytes (max {})",
+                req.data.len(),
+                MAX_REMOTE_CHUNK
+            )));
+        }
+        let session = self
+            .session_manager
+            .get_session(&req.session_id)
+            .map_err(Status::from)?;
+
+        run_blocking(move || {
+            session.with_remote_fs(|fs| fs.write_at(&req.path, req.offset, &req.data, req.truncate))
+        })
+        .await?;
+
+        Ok(Response::new(WriteRemoteFileResponse {}))
+    }
+
     // ========================================================================
     // Connection Management (new RPCs)
     // ========================================================================
@@ -675,6 +989,10 @@ impl TerminalService for TerminalServiceImpl {
             has_foreground_process: session.has_foreground_process(),
             foreground_process_name: session.foreground_process_name().unwrap_or_default(),
             alerted: session.is_alerted(),
+            badge: session.badge(),
+            user_vars: session.user_vars(),
+            ssh: session.is_ssh(),
+            cwd: session.current_dir(),
         };
 
         let initial_screen = if req.want_screen_snapshot {
@@ -1060,6 +1378,18 @@ impl<F: FnOnce()> Stream for StreamNotify<F> {
     }
 }
 
+/// Run a blocking session operation (e.g. SFTP) off the async workers
+async fn run_blocking<T, F>(f: F) -> Result<T, Status>
+where
+    F: FnOnce() -> crate::error::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| Status::internal(format!("Task failed: {}", e)))?
+        .map_err(Status::from)
+}
+
 fn gethostname() -> String {
     #[cfg(unix)]
     {
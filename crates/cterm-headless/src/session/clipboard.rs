@@ -0,0 +1,77 @@
+//! Clipboard shared by the daemon's sessions
+//!
+//! Holds what programs last wrote with OSC 52 and what clients last copied
+//! (`SetClipboard`), per selection. Sessions no client is attached to answer
+//! OSC 52 reads from it, and clients of a remote daemon read it with
+//! `GetClipboard`.
+
+use cterm_core::screen::ClipboardSelection;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+/// Latest contents of each clipboard selection
+#[derive(Debug, Default)]
+pub struct Clipboard {
+    contents: Mutex<HashMap<ClipboardSelection, Vec<u8>>>,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Contents of `selection`, if anything was copied to it
+    ///
+    /// `Select` stands for both the clipboard and the primary selection, so
+    /// it reads whichever of them was written last.
+    pub fn get(&self, selection: ClipboardSelection) -> Option<Vec<u8>> {
+        self.contents.lock().get(&selection).cloned()
+    }
+
+    /// Replace the contents of `selection`
+    pub fn set(&self, selection: ClipboardSelection, data: Vec<u8>) {
+        let mut contents = self.contents.lock();
+        match selection {
+            ClipboardSelection::Select => {
+                contents.insert(ClipboardSelection::Clipboard, data.clone());
+                contents.insert(ClipboardSelection::Primary, data.clone());
+            }
+            _ => {
+                contents.insert(ClipboardSelection::Select, data.clone());
+            }
+        }
+        contents.insert(selection, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_selections() {
+        let clipboard = Clipboard::new();
+        assert_eq!(clipboard.get(ClipboardSelection::Clipboard), None);
+
+        clipboard.set(ClipboardSelection::Clipboard, b"copied".to_vec());
+        assert_eq!(
+            clipboard.get(ClipboardSelection::Clipboard).as_deref(),
+            Some(&b"copied"[..])
+        );
+        assert_eq!(clipboard.get(ClipboardSelection::Primary), None);
+        assert_eq!(
+            clipboard.get(ClipboardSelection::Select).as_deref(),
+            Some(&b"copied"[..])
+        );
+
+        clipboard.set(ClipboardSelection::Select, b"both".to_vec());
+        assert_eq!(
+            clipboard.get(ClipboardSelection::Primary).as_deref(),
+            Some(&b"both"[..])
+        );
+        assert_eq!(
+            clipboard.get(ClipboardSelection::Clipboard).as_deref(),
+            Some(&b"both"[..])
+        );
+    }
+}
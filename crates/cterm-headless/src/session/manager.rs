@@ -1,7 +1,7 @@
 //! Thread-safe session manager
 
 use crate::error::{HeadlessError, Result};
-use crate::session::{generate_session_id, SessionState};
+use crate::session::{generate_session_id, Clipboard, SessionState};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -17,6 +17,8 @@ pub struct SessionManager {
     scrollback_lines: usize,
     /// Whether at least one session has ever been created
     had_sessions: AtomicBool,
+    /// Clipboard shared by all sessions
+    clipboard: Arc<Clipboard>,
 }
 
 impl SessionManager {
@@ -32,6 +34,7 @@ impl SessionManager {
             named_sessions: RwLock::new(HashMap::new()),
             scrollback_lines,
             had_sessions: AtomicBool::new(false),
+            clipboard: Arc::new(Clipboard::new()),
         }
     }
 
@@ -45,7 +48,9 @@ impl SessionManager {
         args: Vec<String>,
         cwd: Option<PathBuf>,
         env: Vec<(String, String)>,
+        clear_env: bool,
         term: Option<String>,
+        scrollback_lines: Option<usize>,
     ) -> Result<Arc<SessionState>> {
         let id = generate_session_id();
 
@@ -62,8 +67,10 @@ impl SessionManager {
             args,
             cwd,
             env,
+            clear_env,
             term,
-            self.scrollback_lines,
+            scrollback_lines.unwrap_or(self.scrollback_lines),
+            Arc::clone(&self.clipboard),
         )?;
 
         // Start the PTY reader task
@@ -78,6 +85,43 @@ impl SessionManager {
         Ok(state)
     }
 
+    /// Create a new session attached to a serial device
+    pub fn create_serial_session(
+        &self,
+        cols: usize,
+        rows: usize,
+        serial_config: cterm_core::SerialConfig,
+        scrollback_lines: Option<usize>,
+    ) -> Result<Arc<SessionState>> {
+        let id = generate_session_id();
+
+        if self.sessions.read().contains_key(&id) {
+            return Err(HeadlessError::SessionAlreadyExists(id));
+        }
+
+        let state = SessionState::new_serial(
+            id.clone(),
+            cols,
+            rows,
+            &serial_config,
+            scrollback_lines.unwrap_or(self.scrollback_lines),
+            Arc::clone(&self.clipboard),
+        )?;
+        let state = state.start_reader()?;
+
+        self.had_sessions.store(true, Ordering::Relaxed);
+        self.sessions.write().insert(id, Arc::clone(&state));
+
+        log::info!(
+            "Created serial session {} on {} at {} baud",
+            state.id,
+            serial_config.path,
+            serial_config.baud_rate
+        );
+
+        Ok(state)
+    }
+
     /// Create a new session backed by a native SSH connection (puressh).
     ///
     /// Returns immediately with a "connecting" session; the SSH connection is
@@ -88,6 +132,7 @@ impl SessionManager {
         cols: usize,
         rows: usize,
         ssh_config: cterm_core::SshConfig,
+        scrollback_lines: Option<usize>,
     ) -> Result<Arc<SessionState>> {
         let id = generate_session_id();
 
@@ -96,7 +141,13 @@ impl SessionManager {
         }
 
         let host = ssh_config.host.clone();
-        let state = SessionState::new_ssh_connecting(id.clone(), cols, rows, self.scrollback_lines);
+        let state = SessionState::new_ssh_connecting(
+            id.clone(),
+            cols,
+            rows,
+            scrollback_lines.unwrap_or(self.scrollback_lines),
+            Arc::clone(&self.clipboard),
+        );
 
         // Store the session before driving the connection so prompt/event
         // subscribers can attach immediately.
@@ -117,6 +168,11 @@ impl SessionManager {
         Ok(state)
     }
 
+    /// Clipboard shared by all sessions
+    pub fn clipboard(&self) -> &Clipboard {
+        &self.clipboard
+    }
+
     /// Get a session by ID
     pub fn get_session(&self, id: &str) -> Result<Arc<SessionState>> {
         self.sessions
@@ -183,6 +239,7 @@ impl SessionManager {
             tab_color,
             template_name,
             scrollback_lines,
+            Arc::clone(&self.clipboard),
         )?;
 
         // Start the PTY reader task
@@ -232,7 +289,8 @@ impl SessionManager {
         }
 
         // Create new session
-        let session = self.create_session(cols, rows, shell, Vec::new(), None, env, term)?;
+        let session =
+            self.create_session(cols, rows, shell, Vec::new(), None, env, false, term, None)?;
 
         // Register the name
         session.set_session_name(Some(name.to_string()));
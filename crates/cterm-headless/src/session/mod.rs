@@ -1,9 +1,11 @@
 //! Session management for ctermd
 
+mod clipboard;
 mod id;
 mod manager;
 mod state;
 
+pub use clipboard::Clipboard;
 pub use id::generate_session_id;
 pub use manager::SessionManager;
 pub use state::{OutputData, PromptReply, SessionState};
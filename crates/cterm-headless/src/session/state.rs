@@ -1,18 +1,26 @@
 //! Session state management
 
+use super::Clipboard;
 use crate::bridge::{PtyReader, PtyWriter};
-use crate::error::Result;
-use cterm_core::screen::ScreenConfig;
+use crate::error::{HeadlessError, Result};
+use cterm_core::screen::{ClipboardOperation, ScreenConfig};
 use cterm_core::term::TerminalEvent;
 #[cfg(unix)]
 use cterm_core::Pty;
-use cterm_core::{PtyConfig, PtySize, Terminal};
+use cterm_core::{OutputStats, PermissionPolicy, PtyConfig, PtySize, Terminal};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use tokio::sync::broadcast;
 
+/// How long the shell must stay quiet before a startup command is typed
+const STARTUP_IDLE: Duration = Duration::from_millis(300);
+
+/// Longest wait for the shell before typing a startup command anyway
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Output chunk with timestamp
 #[derive(Clone, Debug)]
 pub struct OutputData {
@@ -80,6 +88,14 @@ pub struct SessionState {
     /// under the terminal lock. Initialized lazily in `start_reader`, once the PTY
     /// exists (which for SSH sessions is only after the connection is established).
     pty_writer: OnceLock<PtyWriter>,
+
+    /// SFTP session for remote file transfer, opened on first use over the
+    /// SSH connection (native SSH sessions only).
+    remote_fs: parking_lot::Mutex<Option<cterm_core::RemoteFs>>,
+
+    /// Daemon clipboard: keeps OSC 52 writes and answers OSC 52 reads while
+    /// no client is attached
+    clipboard: Arc<Clipboard>,
 }
 
 impl SessionState {
@@ -93,8 +109,10 @@ impl SessionState {
         args: Vec<String>,
         cwd: Option<std::path::PathBuf>,
         env: Vec<(String, String)>,
+        clear_env: bool,
         term: Option<String>,
         scrollback_lines: usize,
+        clipboard: Arc<Clipboard>,
     ) -> Result<Arc<Self>> {
         let pty_config = PtyConfig {
             size: PtySize {
@@ -106,35 +124,36 @@ impl SessionState {
             args,
             cwd,
             env,
+            clear_env,
             term,
         };
 
-        let screen_config = ScreenConfig { scrollback_lines };
+        let screen_config = ScreenConfig {
+            scrollback_lines,
+            ..Default::default()
+        };
         let terminal = Terminal::with_shell(cols, rows, screen_config, &pty_config)?;
 
-        // Create broadcast channels
-        let (output_tx, _) = broadcast::channel(1024);
-        let (event_tx, _) = broadcast::channel(256);
+        Ok(Self::with_terminal(id, terminal, false, clipboard))
+    }
 
-        let state = Arc::new(Self {
-            terminal: RwLock::new(terminal),
-            id,
-            output_tx,
-            event_tx,
-            attached_clients: AtomicU32::new(0),
-            custom_title: RwLock::new(String::new()),
-            tab_color: RwLock::new(String::new()),
-            template_name: RwLock::new(String::new()),
-            session_name: RwLock::new(None),
-            alerted: std::sync::atomic::AtomicBool::new(false),
-            connecting: std::sync::atomic::AtomicBool::new(false),
-            prompt_tx: broadcast::channel(16).0,
-            prompt_registry: parking_lot::Mutex::new(HashMap::new()),
-            prompt_counter: AtomicU64::new(0),
-            pty_writer: OnceLock::new(),
-        });
+    /// Create a session attached to a serial device instead of a shell
+    pub fn new_serial(
+        id: String,
+        cols: usize,
+        rows: usize,
+        serial_config: &cterm_core::SerialConfig,
+        scrollback_lines: usize,
+        clipboard: Arc<Clipboard>,
+    ) -> Result<Arc<Self>> {
+        let screen_config = ScreenConfig {
+            scrollback_lines,
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(cols, rows, screen_config);
+        terminal.set_pty(cterm_core::Pty::open_serial(serial_config)?);
 
-        Ok(state)
+        Ok(Self::with_terminal(id, terminal, false, clipboard))
     }
 
     /// Create a placeholder session for a native SSH connection that is still
@@ -146,10 +165,25 @@ impl SessionState {
         cols: usize,
         rows: usize,
         scrollback_lines: usize,
+        clipboard: Arc<Clipboard>,
     ) -> Arc<Self> {
-        let screen_config = ScreenConfig { scrollback_lines };
+        let screen_config = ScreenConfig {
+            scrollback_lines,
+            ..Default::default()
+        };
         let terminal = Terminal::new(cols, rows, screen_config);
 
+        Self::with_terminal(id, terminal, true, clipboard)
+    }
+
+    fn with_terminal(
+        id: String,
+        mut terminal: Terminal,
+        connecting: bool,
+        clipboard: Arc<Clipboard>,
+    ) -> Arc<Self> {
+        // Clients parse the output again and apply their own policy
+        terminal.set_permissions(PermissionPolicy::allow_all());
         let (output_tx, _) = broadcast::channel(1024);
         let (event_tx, _) = broadcast::channel(256);
         let (prompt_tx, _) = broadcast::channel(16);
@@ -165,11 +199,13 @@ impl SessionState {
             template_name: RwLock::new(String::new()),
             session_name: RwLock::new(None),
             alerted: std::sync::atomic::AtomicBool::new(false),
-            connecting: std::sync::atomic::AtomicBool::new(true),
+            connecting: std::sync::atomic::AtomicBool::new(connecting),
             prompt_tx,
             prompt_registry: parking_lot::Mutex::new(HashMap::new()),
             prompt_counter: AtomicU64::new(0),
             pty_writer: OnceLock::new(),
+            remote_fs: parking_lot::Mutex::new(None),
+            clipboard,
         })
     }
 
@@ -340,11 +376,16 @@ impl SessionState {
         tab_color: String,
         template_name: String,
         scrollback_lines: usize,
+        clipboard: Arc<Clipboard>,
     ) -> Result<Arc<Self>> {
         let pty = Pty::from_raw_fd(fd, child_pid);
-        let screen_config = ScreenConfig { scrollback_lines };
+        let screen_config = ScreenConfig {
+            scrollback_lines,
+            ..Default::default()
+        };
         let mut terminal = Terminal::new(cols, rows, screen_config);
         terminal.set_pty(pty);
+        terminal.set_permissions(PermissionPolicy::allow_all());
 
         let (output_tx, _) = broadcast::channel(1024);
         let (event_tx, _) = broadcast::channel(256);
@@ -365,6 +406,8 @@ impl SessionState {
             prompt_registry: parking_lot::Mutex::new(HashMap::new()),
             prompt_counter: AtomicU64::new(0),
             pty_writer: OnceLock::new(),
+            remote_fs: parking_lot::Mutex::new(None),
+            clipboard,
         });
 
         Ok(state)
@@ -424,6 +467,22 @@ impl SessionState {
         self.terminal.read().title().to_string()
     }
 
+    /// Get the badge text (iTerm2 SetBadgeFormat, variables expanded)
+    pub fn badge(&self) -> String {
+        self.terminal.read().screen().badge()
+    }
+
+    /// Get the user variables set by the shell
+    pub fn user_vars(&self) -> std::collections::HashMap<String, String> {
+        self.terminal
+            .read()
+            .screen()
+            .user_vars
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
     /// Get the user-set custom title
     pub fn custom_title(&self) -> String {
         self.custom_title.read().clone()
@@ -479,6 +538,47 @@ impl SessionState {
         }
     }
 
+    /// Whether this session runs over a native SSH connection
+    pub fn is_ssh(&self) -> bool {
+        self.terminal.read().pty().is_some_and(|pty| pty.is_ssh())
+    }
+
+    /// Shell working directory reported via OSC 7 or OSC 1337 CurrentDir (empty if unknown)
+    pub fn current_dir(&self) -> String {
+        self.terminal.read().screen().current_dir.clone()
+    }
+
+    /// Run `f` against this session's SFTP connection, opening it on first use.
+    ///
+    /// Blocks on network round trips, so call it from a blocking task. A
+    /// transport failure drops the cached connection so the next call
+    /// reopens it.
+    pub fn with_remote_fs<R>(
+        &self,
+        f: impl FnOnce(&mut cterm_core::RemoteFs) -> std::io::Result<R>,
+    ) -> Result<R> {
+        let mut guard = self.remote_fs.lock();
+        if guard.is_none() {
+            let fs = match self.terminal.read().pty() {
+                Some(pty) if pty.is_ssh() => pty.open_sftp()?,
+                _ => {
+                    return Err(HeadlessError::InvalidArgument(format!(
+                        "Session {} is not an SSH session",
+                        self.id
+                    )))
+                }
+            };
+            *guard = Some(fs);
+        }
+        let result = f(guard.as_mut().expect("remote fs opened above"));
+        if let Err(e) = &result {
+            if e.kind() != std::io::ErrorKind::Other {
+                *guard = None;
+            }
+        }
+        Ok(result?)
+    }
+
     /// Check if the terminal is still running
     pub fn is_running(&self) -> bool {
         // A session still establishing its SSH connection has no PTY yet but
@@ -531,6 +631,35 @@ impl SessionState {
         Ok(data.len())
     }
 
+    /// Type `commands` into the shell as it becomes ready, one per line.
+    ///
+    /// Before each command this waits for the shell to print something (its
+    /// prompt, or the output of the previous command) and then go quiet, so
+    /// commands land at a prompt rather than in the middle of shell startup.
+    /// Must be called from within the Tokio runtime.
+    pub fn spawn_startup_commands(self: &Arc<Self>, commands: Vec<String>) {
+        if commands.is_empty() {
+            return;
+        }
+        // Subscribe now so the first prompt can't slip past before the task runs
+        let mut output = self.subscribe_output();
+        let state = Arc::clone(self);
+        tokio::spawn(async move {
+            for command in commands {
+                wait_for_quiet(&mut output, STARTUP_IDLE, STARTUP_TIMEOUT).await;
+                if !state.is_running() {
+                    break;
+                }
+                log::debug!("Session {}: running startup command", state.id);
+                let line = format!("{}\r", command);
+                if let Err(e) = state.write_input(line.as_bytes()) {
+                    log::warn!("Session {}: startup command failed: {}", state.id, e);
+                    break;
+                }
+            }
+        });
+    }
+
     /// Resize the terminal
     pub fn resize(&self, cols: usize, rows: usize) {
         self.terminal.write().resize(cols, rows);
@@ -549,11 +678,17 @@ impl SessionState {
     /// This guarantees the terminal lock is never held across a (potentially blocking)
     /// PTY write — the root cause of the daemon deadlock this avoids.
     pub fn process_output(&self, data: &[u8]) -> Vec<TerminalEvent> {
-        let (events, responses) = {
+        let (events, mut responses) = {
             let mut term = self.terminal.write();
             term.process_collecting(data)
         }; // terminal lock released here
 
+        for event in &events {
+            if let TerminalEvent::ClipboardRequest(op) = event {
+                responses.extend(self.handle_clipboard(op));
+            }
+        }
+
         if !responses.is_empty() {
             match self.pty_writer.get() {
                 Some(writer) => {
@@ -576,6 +711,29 @@ impl SessionState {
         events
     }
 
+    /// Keep an OSC 52 write in the daemon clipboard, or answer an OSC 52
+    /// read from it when no client is attached to answer it
+    fn handle_clipboard(&self, op: &ClipboardOperation) -> Option<Vec<u8>> {
+        match op {
+            ClipboardOperation::Set { selection, data } => {
+                self.clipboard.set(*selection, data.clone());
+                None
+            }
+            ClipboardOperation::Query { selection } => {
+                if self.attached_clients() > 0 {
+                    return None;
+                }
+                let data = self.clipboard.get(*selection).unwrap_or_default();
+                Some(Terminal::clipboard_response(*selection, &data))
+            }
+        }
+    }
+
+    /// Output rates if a second has passed since the last report
+    pub fn poll_output_stats(&self) -> Option<OutputStats> {
+        self.terminal.write().poll_output_stats()
+    }
+
     /// Broadcast output data to subscribers
     pub fn broadcast_output(&self, data: OutputData) {
         let _ = self.output_tx.send(data);
@@ -623,3 +781,61 @@ impl SessionState {
         f(&mut term)
     }
 }
+
+/// Wait until some output has arrived and then none for `idle`, or `timeout`
+async fn wait_for_quiet(
+    output: &mut broadcast::Receiver<OutputData>,
+    idle: Duration,
+    timeout: Duration,
+) {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut seen = false;
+    loop {
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            return;
+        }
+        let wait = if seen {
+            idle.min(deadline - now)
+        } else {
+            deadline - now
+        };
+        match tokio::time::timeout(wait, output.recv()).await {
+            Ok(Ok(_)) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => seen = true,
+            // Session gone: nothing left to wait for
+            Ok(Err(broadcast::error::RecvError::Closed)) => return,
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk() -> OutputData {
+        OutputData {
+            data: b"$ ".to_vec(),
+            timestamp_ms: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_quiet() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let idle = Duration::from_millis(50);
+        let timeout = Duration::from_millis(500);
+
+        // Output followed by silence: ready after the idle period
+        tx.send(chunk()).unwrap();
+        let start = std::time::Instant::now();
+        wait_for_quiet(&mut rx, idle, timeout).await;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= idle && elapsed < timeout, "{elapsed:?}");
+
+        // No output at all: give up at the timeout
+        let start = std::time::Instant::now();
+        wait_for_quiet(&mut rx, idle, timeout).await;
+        assert!(start.elapsed() >= timeout);
+    }
+}
@@ -122,6 +122,10 @@ async fn test_create_and_list_sessions() {
             env: Default::default(),
             term: None,
             ssh: None,
+            startup_commands: vec![],
+            clear_env: false,
+            serial: None,
+            scrollback_lines: None,
         })
         .await
         .expect("create_session failed");
@@ -173,6 +177,10 @@ async fn test_get_session() {
             env: Default::default(),
             term: None,
             ssh: None,
+            startup_commands: vec![],
+            clear_env: false,
+            serial: None,
+            scrollback_lines: None,
         })
         .await
         .expect("create_session failed");
@@ -221,6 +229,10 @@ async fn test_write_input_and_get_screen() {
             env: Default::default(),
             term: Some("xterm".to_string()),
             ssh: None,
+            startup_commands: vec![],
+            clear_env: false,
+            serial: None,
+            scrollback_lines: None,
         })
         .await
         .expect("create_session failed");
@@ -288,6 +300,10 @@ async fn test_resize() {
             env: Default::default(),
             term: None,
             ssh: None,
+            startup_commands: vec![],
+            clear_env: false,
+            serial: None,
+            scrollback_lines: None,
         })
         .await
         .expect("create_session failed");
@@ -343,6 +359,10 @@ async fn test_get_cursor() {
             env: Default::default(),
             term: None,
             ssh: None,
+            startup_commands: vec![],
+            clear_env: false,
+            serial: None,
+            scrollback_lines: None,
         })
         .await
         .expect("create_session failed");
@@ -389,6 +409,10 @@ async fn test_get_screen_full() {
             env: Default::default(),
             term: None,
             ssh: None,
+            startup_commands: vec![],
+            clear_env: false,
+            serial: None,
+            scrollback_lines: None,
         })
         .await
         .expect("create_session failed");
@@ -423,6 +447,121 @@ async fn test_get_screen_full() {
         .await;
 }
 
+#[tokio::test]
+async fn test_get_screenshot() {
+    let server = CtermdServer::spawn();
+    let mut client = connect(&server.address()).await;
+
+    let create_response = client
+        .create_session(CreateSessionRequest {
+            cols: 80,
+            rows: 24,
+            shell: None,
+            args: vec![],
+            cwd: None,
+            env: Default::default(),
+            term: None,
+            ssh: None,
+            startup_commands: vec![],
+            clear_env: false,
+            serial: None,
+            scrollback_lines: None,
+        })
+        .await
+        .expect("create_session failed");
+
+    let session_id = create_response.get_ref().session_id.clone();
+
+    // Cells are 8x12 pixels at scale 1
+    let screenshot = client
+        .get_screenshot(GetScreenshotRequest {
+            session_id: session_id.clone(),
+            scale: 2,
+            hide_cursor: false,
+        })
+        .await
+        .expect("get_screenshot failed");
+
+    let screenshot = screenshot.get_ref();
+    assert_eq!(screenshot.width, 80 * 16);
+    assert_eq!(screenshot.height, 24 * 24);
+    assert!(screenshot.png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+    // Cleanup
+    let _ = client
+        .destroy_session(DestroySessionRequest {
+            session_id,
+            signal: None,
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn test_recording() {
+    let server = CtermdServer::spawn();
+    let mut client = connect(&server.address()).await;
+
+    let create_response = client
+        .create_session(CreateSessionRequest {
+            cols: 80,
+            rows: 24,
+            shell: Some("/bin/sh".to_string()),
+            args: vec!["-c".to_string(), "cat".to_string()],
+            cwd: None,
+            env: Default::default(),
+            term: Some("xterm".to_string()),
+            ssh: None,
+            startup_commands: vec![],
+            clear_env: false,
+            serial: None,
+            scrollback_lines: None,
+        })
+        .await
+        .expect("create_session failed");
+
+    let session_id = create_response.get_ref().session_id.clone();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let path = std::env::temp_dir().join(format!("ctermd_recording_{}.cast", std::process::id()));
+    client
+        .start_recording(StartRecordingRequest {
+            session_id: session_id.clone(),
+            path: path.to_string_lossy().into_owned(),
+        })
+        .await
+        .expect("start_recording failed");
+
+    client
+        .write_input(WriteInputRequest {
+            session_id: session_id.clone(),
+            data: b"recorded\n".to_vec(),
+        })
+        .await
+        .expect("write_input failed");
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let stop = client
+        .stop_recording(StopRecordingRequest {
+            session_id: session_id.clone(),
+        })
+        .await
+        .expect("stop_recording failed");
+    assert!(stop.get_ref().was_recording);
+
+    let cast = std::fs::read_to_string(&path).expect("recording not written");
+    let _ = std::fs::remove_file(&path);
+    assert!(cast.starts_with("{\"version\": 2, \"width\": 80, \"height\": 24"));
+    assert!(cast.contains("recorded"));
+
+    // Cleanup
+    let _ = client
+        .destroy_session(DestroySessionRequest {
+            session_id,
+            signal: None,
+        })
+        .await;
+}
+
 #[tokio::test]
 async fn test_multiple_sessions() {
     let server = CtermdServer::spawn();
@@ -441,6 +580,10 @@ async fn test_multiple_sessions() {
                 env: Default::default(),
                 term: None,
                 ssh: None,
+                startup_commands: vec![],
+                clear_env: false,
+                serial: None,
+                scrollback_lines: None,
             })
             .await
             .expect("create_session failed");
@@ -486,3 +629,110 @@ async fn test_invalid_session_id() {
 
     assert!(result.is_err(), "Should fail for invalid session ID");
 }
+
+#[tokio::test]
+async fn test_create_session_rejects_invalid_env() {
+    let server = CtermdServer::spawn();
+    let mut client = connect(&server.address()).await;
+
+    let result = client
+        .create_session(CreateSessionRequest {
+            cols: 80,
+            rows: 24,
+            shell: None,
+            args: vec![],
+            cwd: None,
+            env: [("BAD=NAME".to_string(), "x".to_string())].into(),
+            term: None,
+            ssh: None,
+            startup_commands: vec![],
+            clear_env: false,
+            serial: None,
+            scrollback_lines: None,
+        })
+        .await;
+
+    let status = result.expect_err("Should reject an env name containing '='");
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_clipboard() {
+    let server = CtermdServer::spawn();
+    let mut client = connect(&server.address()).await;
+
+    let get_clipboard = |mut client: TerminalServiceClient<Channel>| async move {
+        client
+            .get_clipboard(GetClipboardRequest {
+                selection: ClipboardSelection::Clipboard as i32,
+            })
+            .await
+            .expect("get_clipboard failed")
+            .into_inner()
+            .data
+    };
+
+    client
+        .set_clipboard(SetClipboardRequest {
+            selection: ClipboardSelection::Clipboard as i32,
+            data: b"world".to_vec(),
+        })
+        .await
+        .expect("set_clipboard failed");
+    assert_eq!(
+        get_clipboard(client.clone()).await.as_deref(),
+        Some(&b"world"[..])
+    );
+
+    // A program's OSC 52 write lands in the daemon clipboard
+    let session = |script: &str| CreateSessionRequest {
+        cols: 80,
+        rows: 24,
+        shell: Some("/bin/sh".to_string()),
+        args: vec!["-c".to_string(), script.to_string()],
+        cwd: None,
+        env: Default::default(),
+        term: Some("xterm".to_string()),
+        ssh: None,
+        startup_commands: vec![],
+        clear_env: false,
+        serial: None,
+        scrollback_lines: None,
+    };
+    client
+        .create_session(session("printf '\\033]52;c;aGVsbG8=\\007'; sleep 5"))
+        .await
+        .expect("create_session failed");
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(
+        get_clipboard(client.clone()).await.as_deref(),
+        Some(&b"hello"[..])
+    );
+
+    // With no client attached, the daemon answers OSC 52 reads itself; the
+    // tty echoes the answer
+    let session_id = client
+        .create_session(session("printf '\\033]52;c;?\\007'; cat"))
+        .await
+        .expect("create_session failed")
+        .into_inner()
+        .session_id;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let screen_text = client
+        .get_screen_text(GetScreenTextRequest {
+            session_id,
+            include_scrollback: false,
+            start_row: None,
+            end_row: None,
+        })
+        .await
+        .expect("get_screen_text failed")
+        .into_inner()
+        .lines
+        .join("\n");
+    assert!(
+        screen_text.contains("aGVsbG8="),
+        "Screen should echo the clipboard answer, got: {:?}",
+        screen_text
+    );
+}
@@ -7,7 +7,11 @@ use cterm_core::screen::{
 use cterm_core::term::TerminalEvent as CoreEvent;
 
 /// Convert cterm_core TerminalEvent to proto TerminalEvent
-pub fn event_to_proto(event: &CoreEvent) -> proto::TerminalEvent {
+///
+/// Returns `None` for events clients derive from the output themselves (user
+/// variables, pointer shapes, finished commands, file transfers, tmux control
+/// mode).
+pub fn event_to_proto(event: &CoreEvent) -> Option<proto::TerminalEvent> {
     use proto::terminal_event::Event;
 
     let event = match event {
@@ -38,13 +42,31 @@ pub fn event_to_proto(event: &CoreEvent) -> proto::TerminalEvent {
                 data,
             })
         }
+        CoreEvent::UploadRequested => Event::UploadRequested(proto::UploadRequestedEvent {}),
+        CoreEvent::OutputStats(stats) => Event::OutputStats(proto::OutputStatsEvent {
+            bytes_per_sec: stats.bytes_per_sec,
+            lines_per_sec: stats.lines_per_sec,
+            fast: stats.is_fast(),
+        }),
+        CoreEvent::UserVarChanged { .. }
+        | CoreEvent::CwdChanged(_)
+        | CoreEvent::PointerShapeChanged(_)
+        | CoreEvent::CommandFinished(_)
+        | CoreEvent::TransferStarted(..)
+        | CoreEvent::TransferSendRequested
+        | CoreEvent::TransferFinished
+        | CoreEvent::TmuxControl(_)
+        | CoreEvent::WatchMatched(_)
+        | CoreEvent::LinesCompleted(_)
+        | CoreEvent::Notification(_)
+        | CoreEvent::PermissionRequested(_) => return None,
     };
 
-    proto::TerminalEvent { event: Some(event) }
+    Some(proto::TerminalEvent { event: Some(event) })
 }
 
 /// Convert clipboard selection to proto
-fn selection_to_proto(sel: &CoreClipboardSel) -> proto::ClipboardSelection {
+pub fn selection_to_proto(sel: &CoreClipboardSel) -> proto::ClipboardSelection {
     match sel {
         CoreClipboardSel::Clipboard => proto::ClipboardSelection::Clipboard,
         CoreClipboardSel::Primary => proto::ClipboardSelection::Primary,
@@ -52,6 +74,18 @@ fn selection_to_proto(sel: &CoreClipboardSel) -> proto::ClipboardSelection {
     }
 }
 
+/// Convert proto clipboard selection to cterm_core (unspecified means the
+/// clipboard)
+pub fn proto_to_selection(sel: proto::ClipboardSelection) -> CoreClipboardSel {
+    match sel {
+        proto::ClipboardSelection::Unspecified | proto::ClipboardSelection::Clipboard => {
+            CoreClipboardSel::Clipboard
+        }
+        proto::ClipboardSelection::Primary => CoreClipboardSel::Primary,
+        proto::ClipboardSelection::Select => CoreClipboardSel::Select,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,7 +93,7 @@ mod tests {
     #[test]
     fn test_title_changed_event() {
         let event = CoreEvent::TitleChanged("test".to_string());
-        let proto = event_to_proto(&event);
+        let proto = event_to_proto(&event).unwrap();
         match proto.event {
             Some(proto::terminal_event::Event::TitleChanged(e)) => {
                 assert_eq!(e.title, "test");
@@ -71,7 +105,7 @@ mod tests {
     #[test]
     fn test_bell_event() {
         let event = CoreEvent::Bell;
-        let proto = event_to_proto(&event);
+        let proto = event_to_proto(&event).unwrap();
         assert!(matches!(
             proto.event,
             Some(proto::terminal_event::Event::Bell(_))
@@ -81,7 +115,7 @@ mod tests {
     #[test]
     fn test_process_exited_event() {
         let event = CoreEvent::ProcessExited(42);
-        let proto = event_to_proto(&event);
+        let proto = event_to_proto(&event).unwrap();
         match proto.event {
             Some(proto::terminal_event::Event::ProcessExited(e)) => {
                 assert_eq!(e.exit_code, 42);
@@ -89,4 +123,50 @@ mod tests {
             _ => panic!("Expected ProcessExited event"),
         }
     }
+
+    #[test]
+    fn test_upload_requested_event() {
+        let proto = event_to_proto(&CoreEvent::UploadRequested).unwrap();
+        assert!(matches!(
+            proto.event,
+            Some(proto::terminal_event::Event::UploadRequested(_))
+        ));
+    }
+
+    #[test]
+    fn test_output_stats_event() {
+        let stats = cterm_core::OutputStats {
+            bytes_per_sec: 4 << 20,
+            lines_per_sec: 100,
+        };
+        let proto = event_to_proto(&CoreEvent::OutputStats(stats)).unwrap();
+        match proto.event {
+            Some(proto::terminal_event::Event::OutputStats(e)) => {
+                assert_eq!(e.bytes_per_sec, 4 << 20);
+                assert_eq!(e.lines_per_sec, 100);
+                assert!(e.fast);
+            }
+            _ => panic!("Expected OutputStats event"),
+        }
+    }
+
+    #[test]
+    fn test_selection_roundtrip() {
+        for sel in [
+            CoreClipboardSel::Clipboard,
+            CoreClipboardSel::Primary,
+            CoreClipboardSel::Select,
+        ] {
+            assert_eq!(proto_to_selection(selection_to_proto(&sel)), sel);
+        }
+        assert_eq!(
+            proto_to_selection(proto::ClipboardSelection::Unspecified),
+            CoreClipboardSel::Clipboard
+        );
+    }
+
+    #[test]
+    fn test_local_only_event() {
+        assert!(event_to_proto(&CoreEvent::TransferFinished).is_none());
+    }
 }
@@ -37,6 +37,7 @@ pub fn proto_to_key(key: &proto::Key) -> Option<Key> {
                 None
             }
         }
+        Some(KeyType::Keypad(s)) => s.chars().next().map(Key::Keypad),
         None => None,
     }
 }
@@ -81,6 +82,7 @@ pub fn key_to_proto(key: Key) -> proto::Key {
         Key::Insert => Some(KeyType::Special(SpecialKey::Insert as i32)),
         Key::Delete => Some(KeyType::Special(SpecialKey::Delete as i32)),
         Key::F(n) => Some(KeyType::Function(n as u32)),
+        Key::Keypad(c) => Some(KeyType::Keypad(c.to_string())),
     };
 
     proto::Key { key_type }
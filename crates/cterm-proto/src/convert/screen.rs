@@ -81,7 +81,7 @@ pub fn proto_to_attrs(attrs: &proto::CellAttributes) -> CellAttrs {
 /// Convert a cell to proto
 pub fn cell_to_proto(cell: &Cell) -> proto::Cell {
     proto::Cell {
-        char: cell.c.to_string(),
+        char: cell.grapheme(),
         fg: Some(color_to_proto(&cell.fg)),
         bg: Some(color_to_proto(&cell.bg)),
         attrs: Some(attrs_to_proto(cell.attrs)),
@@ -227,6 +227,29 @@ pub fn cursor_to_proto(screen: &Screen) -> proto::CursorPosition {
     }
 }
 
+/// List the screen's images, including those in scrollback
+pub fn images_to_proto(screen: &Screen) -> Vec<proto::ImageInfo> {
+    let top = screen.scrollback().len() as i64;
+    screen
+        .images()
+        .into_iter()
+        .map(|img| {
+            let placement = screen.kitty_graphics().placement_for(img.id);
+            proto::ImageInfo {
+                id: img.id,
+                col: img.col as u32,
+                row: img.line as i64 - top,
+                cell_width: img.cell_width as u32,
+                cell_height: img.cell_height as u32,
+                pixel_width: img.pixel_width as u32,
+                pixel_height: img.pixel_height as u32,
+                kitty_image_id: placement.map(|p| p.image_id),
+                kitty_placement_id: placement.map(|p| p.placement_id),
+            }
+        })
+        .collect()
+}
+
 /// Build terminal modes proto from the screen state
 pub fn modes_to_proto(screen: &Screen) -> proto::TerminalModes {
     proto::TerminalModes {
@@ -237,6 +260,7 @@ pub fn modes_to_proto(screen: &Screen) -> proto::TerminalModes {
         charset_g0: screen.modes.charset_g0.clone(),
         charset_g1: screen.modes.charset_g1.clone(),
         charset_g1_active: screen.modes.charset_g1_active,
+        color_scheme_updates: screen.modes.color_scheme_updates,
     }
 }
 
@@ -288,7 +312,7 @@ pub fn apply_screen_snapshot(terminal: &mut Terminal, screen_data: &proto::GetSc
     for (row_idx, row) in screen_data.visible_rows.iter().enumerate() {
         for (col_idx, cell) in row.cells.iter().enumerate() {
             if let Some(grid_cell) = screen.grid_mut().get_mut(row_idx, col_idx) {
-                grid_cell.c = cell.char.chars().next().unwrap_or(' ');
+                grid_cell.set_grapheme(&cell.char);
                 if let Some(fg) = &cell.fg {
                     grid_cell.fg = proto_to_color(fg);
                 }
@@ -309,7 +333,7 @@ pub fn apply_screen_snapshot(terminal: &mut Terminal, screen_data: &proto::GetSc
             let mut row = Row::new(screen_data.cols as usize);
             for (col_idx, cell) in proto_row.cells.iter().enumerate() {
                 if let Some(grid_cell) = row.get_mut(col_idx) {
-                    grid_cell.c = cell.char.chars().next().unwrap_or(' ');
+                    grid_cell.set_grapheme(&cell.char);
                     if let Some(fg) = &cell.fg {
                         grid_cell.fg = proto_to_color(fg);
                     }
@@ -346,6 +370,7 @@ pub fn apply_screen_snapshot(terminal: &mut Terminal, screen_data: &proto::GetSc
         screen.modes.charset_g0 = modes.charset_g0.clone();
         screen.modes.charset_g1 = modes.charset_g1.clone();
         screen.modes.charset_g1_active = modes.charset_g1_active;
+        screen.modes.color_scheme_updates = modes.color_scheme_updates;
     }
 
     // Restore DRCS soft fonts
@@ -378,4 +403,23 @@ mod tests {
         let proto = cell_to_proto(&cell);
         assert_eq!(proto.char, "A");
     }
+
+    #[test]
+    fn test_images_to_proto() {
+        let mut screen = Screen::new(80, 24, Default::default());
+        screen.add_image(
+            3,
+            2,
+            cterm_core::SixelImage {
+                data: vec![0; 4 * 4 * 4],
+                width: 4,
+                height: 4,
+            },
+        );
+        let images = images_to_proto(&screen);
+        assert_eq!(images.len(), 1);
+        assert_eq!((images[0].col, images[0].row), (3, 2));
+        assert_eq!(images[0].pixel_width, 4);
+        assert_eq!(images[0].kitty_image_id, None);
+    }
 }
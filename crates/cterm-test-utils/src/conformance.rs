@@ -0,0 +1,698 @@
+//! VT conformance cases and capability report
+//!
+//! A table of control sequences in the spirit of vttest and esctest, each fed
+//! into a fresh terminal and checked against the behavior xterm documents.
+//! [`run`] executes every case; [`Report::to_markdown`] renders the pass/fail
+//! summary that `tests/conformance.md` tracks as a golden file, so a change in
+//! compliance shows up as a diff in review.
+
+use std::fmt::Write;
+
+use cterm_core::screen::CursorStyle;
+use cterm_core::{CellAttrs, Color, Rgb, Screen};
+
+use crate::snapshot::row_text;
+use crate::TestTerminal;
+
+/// Terminal size every case runs at
+pub const COLS: usize = 20;
+/// Terminal size every case runs at
+pub const ROWS: usize = 6;
+
+/// An expected outcome after feeding a case's input
+pub enum Expect {
+    /// Cursor at (row, col), 0-based
+    Cursor(usize, usize),
+    /// Row text, with trailing blanks ignored
+    Row(usize, &'static str),
+    /// Exact bytes the terminal replied with
+    Reply(&'static str),
+    /// Reply starts with these bytes
+    ReplyPrefix(&'static str),
+    /// Arbitrary screen predicate, with a description for the report
+    Screen(&'static str, fn(&Screen) -> bool),
+}
+
+/// One conformance check
+pub struct Case {
+    pub category: &'static str,
+    pub name: &'static str,
+    pub input: &'static str,
+    pub expect: &'static [Expect],
+}
+
+/// Outcome of one case
+pub struct CaseResult {
+    pub category: &'static str,
+    pub name: &'static str,
+    /// Why the case failed, `None` if it passed
+    pub failure: Option<String>,
+}
+
+/// Results of a conformance run
+pub struct Report {
+    pub results: Vec<CaseResult>,
+}
+
+impl Report {
+    /// Number of cases that passed
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.failure.is_none()).count()
+    }
+
+    /// Render the report as Markdown: a per-category table, then each failure
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# VT conformance report\n\n");
+        out.push_str(
+            "Generated by `cargo test -p cterm-test-utils --test conformance`.\n\
+             Set `CTERM_UPDATE_GOLDEN=1` to refresh it after a behavior change.\n\n",
+        );
+        out.push_str("| Category | Passed | Total |\n|---|---|---|\n");
+        let mut categories: Vec<&str> = Vec::new();
+        for result in &self.results {
+            if !categories.contains(&result.category) {
+                categories.push(result.category);
+            }
+        }
+        for category in categories {
+            let in_category = self.results.iter().filter(|r| r.category == category);
+            let total = in_category.clone().count();
+            let passed = in_category.filter(|r| r.failure.is_none()).count();
+            let _ = writeln!(out, "| {} | {} | {} |", category, passed, total);
+        }
+        let _ = writeln!(
+            out,
+            "| **all** | **{}** | **{}** |",
+            self.passed(),
+            self.results.len()
+        );
+
+        let failures: Vec<&CaseResult> = self
+            .results
+            .iter()
+            .filter(|r| r.failure.is_some())
+            .collect();
+        if !failures.is_empty() {
+            out.push_str("\n## Failures\n\n");
+            for result in failures {
+                let _ = writeln!(
+                    out,
+                    "- {}/{}: {}",
+                    result.category,
+                    result.name,
+                    result.failure.as_deref().unwrap_or_default()
+                );
+            }
+        }
+        out
+    }
+}
+
+/// Run every case in [`CASES`]
+pub fn run() -> Report {
+    Report {
+        results: CASES.iter().map(run_case).collect(),
+    }
+}
+
+/// Run a single case in a fresh terminal
+pub fn run_case(case: &Case) -> CaseResult {
+    let mut term = TestTerminal::new(COLS, ROWS);
+    term.feed_str(case.input);
+    let failures: Vec<String> = case
+        .expect
+        .iter()
+        .filter_map(|expect| check(&term, expect).err())
+        .collect();
+    CaseResult {
+        category: case.category,
+        name: case.name,
+        failure: (!failures.is_empty()).then(|| failures.join("; ")),
+    }
+}
+
+fn check(term: &TestTerminal, expect: &Expect) -> Result<(), String> {
+    let screen = term.screen();
+    match *expect {
+        Expect::Cursor(row, col) => {
+            let actual = (screen.cursor.row, screen.cursor.col);
+            if actual == (row, col) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "cursor at {},{}, expected {},{}",
+                    actual.0, actual.1, row, col
+                ))
+            }
+        }
+        Expect::Row(row, text) => {
+            let actual = screen
+                .grid()
+                .row(row)
+                .map(|r| row_text(r).trim_end().to_string())
+                .unwrap_or_default();
+            if actual == text {
+                Ok(())
+            } else {
+                Err(format!("row {} is {:?}, expected {:?}", row, actual, text))
+            }
+        }
+        Expect::Reply(reply) => {
+            if term.replies() == reply.as_bytes() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "replied {:?}, expected {:?}",
+                    String::from_utf8_lossy(term.replies()),
+                    reply
+                ))
+            }
+        }
+        Expect::ReplyPrefix(prefix) => {
+            if term.replies().starts_with(prefix.as_bytes()) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "replied {:?}, expected a reply starting with {:?}",
+                    String::from_utf8_lossy(term.replies()),
+                    prefix
+                ))
+            }
+        }
+        Expect::Screen(description, predicate) => {
+            if predicate(screen) {
+                Ok(())
+            } else {
+                Err(format!("expected {}", description))
+            }
+        }
+    }
+}
+
+fn cell_attrs(screen: &Screen, row: usize, col: usize) -> CellAttrs {
+    screen
+        .grid()
+        .get(row, col)
+        .map(|c| c.attrs)
+        .unwrap_or_default()
+}
+
+fn cell_fg(screen: &Screen, row: usize, col: usize) -> Color {
+    screen
+        .grid()
+        .get(row, col)
+        .map(|c| c.fg)
+        .unwrap_or_default()
+}
+
+macro_rules! case {
+    ($category:literal, $name:literal, $input:expr, [$($expect:expr),+ $(,)?]) => {
+        Case {
+            category: $category,
+            name: $name,
+            input: $input,
+            expect: &[$($expect),+],
+        }
+    };
+}
+
+use Expect::{Cursor, Reply, ReplyPrefix, Row};
+
+/// Every conformance case, grouped by category
+pub static CASES: &[Case] = &[
+    // Cursor movement
+    case!("cursor", "CUP", "\x1b[3;5H", [Cursor(2, 4)]),
+    case!(
+        "cursor",
+        "CUP defaults to home",
+        "\x1b[3;5H\x1b[H",
+        [Cursor(0, 0)]
+    ),
+    case!(
+        "cursor",
+        "CUP clamps to screen",
+        "\x1b[99;99H",
+        [Cursor(5, 19)]
+    ),
+    case!("cursor", "HVP", "\x1b[2;3f", [Cursor(1, 2)]),
+    case!(
+        "cursor",
+        "CUU stops at top",
+        "\x1b[3;1H\x1b[10A",
+        [Cursor(0, 0)]
+    ),
+    case!("cursor", "CUD stops at bottom", "\x1b[10B", [Cursor(5, 0)]),
+    case!(
+        "cursor",
+        "CUF stops at right margin",
+        "\x1b[30C",
+        [Cursor(0, 19)]
+    ),
+    case!(
+        "cursor",
+        "CUB stops at left margin",
+        "\x1b[1;5H\x1b[10D",
+        [Cursor(0, 0)]
+    ),
+    case!("cursor", "CHA", "\x1b[2;2H\x1b[7G", [Cursor(1, 6)]),
+    case!("cursor", "HPA", "\x1b[2;2H\x1b[7`", [Cursor(1, 6)]),
+    case!("cursor", "VPA", "\x1b[1;4H\x1b[5d", [Cursor(4, 3)]),
+    case!("cursor", "CNL", "\x1b[1;5H\x1b[2E", [Cursor(2, 0)]),
+    case!("cursor", "CPL", "\x1b[4;5H\x1b[2F", [Cursor(1, 0)]),
+    case!(
+        "cursor",
+        "DECSC/DECRC",
+        "\x1b[3;4H\x1b7\x1b[H\x1b8",
+        [Cursor(2, 3)]
+    ),
+    case!(
+        "cursor",
+        "SCOSC/SCORC",
+        "\x1b[3;4H\x1b[s\x1b[H\x1b[u",
+        [Cursor(2, 3)]
+    ),
+    case!("cursor", "CR LF BS", "ab\r\ncd\x08", [Cursor(1, 1)]),
+    case!(
+        "cursor",
+        "DECOM homes to scroll region",
+        "\x1b[3;5r\x1b[?6h\x1b[1;1H",
+        [Cursor(2, 0)]
+    ),
+    // Reports
+    case!(
+        "reports",
+        "DSR cursor position",
+        "\x1b[2;3H\x1b[6n",
+        [Reply("\x1b[2;3R")]
+    ),
+    case!(
+        "reports",
+        "DSR operating status",
+        "\x1b[5n",
+        [Reply("\x1b[0n")]
+    ),
+    case!(
+        "reports",
+        "DECXCPR",
+        "\x1b[2;3H\x1b[?6n",
+        [ReplyPrefix("\x1b[?2;3")]
+    ),
+    case!("reports", "DA1", "\x1b[c", [ReplyPrefix("\x1b[?")]),
+    case!("reports", "DA2", "\x1b[>c", [ReplyPrefix("\x1b[>")]),
+    case!(
+        "reports",
+        "DECRQM set mode",
+        "\x1b[?7$p",
+        [Reply("\x1b[?7;1$y")]
+    ),
+    case!(
+        "reports",
+        "DECRQM reset mode",
+        "\x1b[?25l\x1b[?25$p",
+        [Reply("\x1b[?25;2$y")]
+    ),
+    case!(
+        "reports",
+        "XTWINOPS text area size",
+        "\x1b[18t",
+        [Reply("\x1b[8;6;20t")]
+    ),
+    // Erasing and editing
+    case!(
+        "erase",
+        "EL to end",
+        "abcdefghij\x1b[1;4H\x1b[K",
+        [Row(0, "abc")]
+    ),
+    case!(
+        "erase",
+        "EL to start",
+        "abcdefghij\x1b[1;4H\x1b[1K",
+        [Row(0, "    efghij")]
+    ),
+    case!("erase", "EL whole line", "abcdefghij\x1b[2K", [Row(0, "")]),
+    case!(
+        "erase",
+        "ED below",
+        "aaa\r\nbbb\r\nccc\x1b[2;2H\x1b[J",
+        [Row(0, "aaa"), Row(1, "b"), Row(2, "")]
+    ),
+    case!(
+        "erase",
+        "ED above",
+        "aaa\r\nbbb\r\nccc\x1b[2;2H\x1b[1J",
+        [Row(0, ""), Row(1, "  b"), Row(2, "ccc")]
+    ),
+    case!(
+        "erase",
+        "ED all keeps cursor",
+        "aaa\r\nbbb\x1b[2;2H\x1b[2J",
+        [Row(0, ""), Row(1, ""), Cursor(1, 1)]
+    ),
+    case!(
+        "erase",
+        "ECH",
+        "abcdef\x1b[1;2H\x1b[3X",
+        [Row(0, "a   ef"), Cursor(0, 1)]
+    ),
+    case!("erase", "DCH", "abcdef\x1b[1;2H\x1b[2P", [Row(0, "adef")]),
+    case!(
+        "erase",
+        "ICH",
+        "abcdef\x1b[1;2H\x1b[2@",
+        [Row(0, "a  bcdef")]
+    ),
+    case!(
+        "erase",
+        "IL",
+        "aaa\r\nbbb\r\nccc\x1b[2;1H\x1b[L",
+        [Row(1, ""), Row(2, "bbb"), Row(3, "ccc")]
+    ),
+    case!(
+        "erase",
+        "DL",
+        "aaa\r\nbbb\r\nccc\x1b[2;1H\x1b[M",
+        [Row(1, "ccc"), Row(2, "")]
+    ),
+    case!(
+        "erase",
+        "DECALN",
+        "abc\x1b#8",
+        [
+            Row(0, "EEEEEEEEEEEEEEEEEEEE"),
+            Row(5, "EEEEEEEEEEEEEEEEEEEE"),
+            Cursor(0, 0)
+        ]
+    ),
+    // Scrolling
+    case!(
+        "scroll",
+        "IND scrolls at bottom",
+        "1\r\n2\r\n3\r\n4\r\n5\r\n6\x1bD",
+        [Row(0, "2"), Row(4, "6"), Row(5, "")]
+    ),
+    case!(
+        "scroll",
+        "RI scrolls at top",
+        "1\r\n2\x1b[H\x1bM",
+        [Row(0, ""), Row(1, "1"), Row(2, "2")]
+    ),
+    case!("scroll", "NEL", "ab\x1bE", [Cursor(1, 0)]),
+    case!(
+        "scroll",
+        "SU",
+        "1\r\n2\r\n3\x1b[S",
+        [Row(0, "2"), Row(1, "3")]
+    ),
+    case!("scroll", "SD", "1\r\n2\x1b[T", [Row(0, ""), Row(1, "1")]),
+    case!(
+        "scroll",
+        "DECSTBM scrolls inside region",
+        "1\r\n2\r\n3\r\n4\r\n5\r\n6\x1b[2;4r\x1b[4;1H\n",
+        [
+            Row(0, "1"),
+            Row(1, "3"),
+            Row(2, "4"),
+            Row(3, ""),
+            Row(4, "5")
+        ]
+    ),
+    case!(
+        "scroll",
+        "DECSTBM homes cursor",
+        "\x1b[3;4H\x1b[2;4r",
+        [Cursor(0, 0)]
+    ),
+    // Left/right margins and origin mode
+    case!(
+        "margins",
+        "CSI s saves cursor without DECLRMM",
+        "\x1b[3;4H\x1b[5;10s\x1b[H\x1b[u",
+        [Cursor(2, 3)]
+    ),
+    case!(
+        "margins",
+        "DECSLRM homes cursor",
+        "\x1b[?69h\x1b[3;4H\x1b[5;10s",
+        [Cursor(0, 0)]
+    ),
+    case!(
+        "margins",
+        "Text wraps at right margin",
+        "\x1b[?69h\x1b[5;10s\x1b[1;5Habcdefghij",
+        [Row(0, "    abcdef"), Row(1, "    ghij"), Cursor(1, 8)]
+    ),
+    case!(
+        "margins",
+        "DECOM addresses from margins",
+        "\x1b[?69h\x1b[2;5r\x1b[5;10s\x1b[?6h\x1b[2;3H",
+        [Cursor(2, 6)]
+    ),
+    case!(
+        "margins",
+        "DECOM clamps to margins",
+        "\x1b[?69h\x1b[2;5r\x1b[5;10s\x1b[?6h\x1b[99;99H",
+        [Cursor(4, 9)]
+    ),
+    case!(
+        "margins",
+        "DSR in origin mode",
+        "\x1b[2;5r\x1b[?6h\x1b[2;3H\x1b[6n",
+        [Reply("\x1b[2;3R")]
+    ),
+    case!(
+        "margins",
+        "CUU stops at top margin",
+        "\x1b[2;5r\x1b[4;1H\x1b[99A",
+        [Cursor(1, 0)]
+    ),
+    case!(
+        "margins",
+        "CUF stops at right margin",
+        "\x1b[?69h\x1b[5;10s\x1b[1;6H\x1b[99C",
+        [Cursor(0, 9)]
+    ),
+    case!(
+        "margins",
+        "CR returns to left margin",
+        "\x1b[?69h\x1b[5;10s\x1b[1;8Hx\r",
+        [Cursor(0, 4)]
+    ),
+    case!(
+        "margins",
+        "SU scrolls between margins",
+        "abcdefgh\r\nijklmnop\x1b[?69h\x1b[3;5s\x1b[S",
+        [Row(0, "abklmfgh"), Row(1, "ij   nop")]
+    ),
+    case!(
+        "margins",
+        "IL between margins",
+        "abcdefgh\r\nijklmnop\x1b[?69h\x1b[3;5s\x1b[1;3H\x1b[L",
+        [Row(0, "ab   fgh"), Row(1, "ijcdenop"), Cursor(0, 2)]
+    ),
+    case!(
+        "margins",
+        "DCH stops at right margin",
+        "abcdefgh\x1b[?69h\x1b[3;6s\x1b[1;3H\x1b[P",
+        [Row(0, "abdef gh")]
+    ),
+    case!(
+        "margins",
+        "Resetting DECLRMM clears margins",
+        "\x1b[?69h\x1b[5;10s\x1b[?69l\x1b[1;18Habcd",
+        [Row(0, "                 abc"), Row(1, "d")]
+    ),
+    // Tab stops
+    case!(
+        "tabs",
+        "HT default stops",
+        "\tx",
+        [Row(0, "        x"), Cursor(0, 9)]
+    ),
+    case!(
+        "tabs",
+        "HT stops at right margin",
+        "\t\t\t\t",
+        [Cursor(0, 19)]
+    ),
+    case!("tabs", "HTS", "\x1b[1;4H\x1bH\r\tx", [Row(0, "   x")]),
+    case!(
+        "tabs",
+        "TBC clears all",
+        "\x1b[3g\tx",
+        [Row(0, "                   x")]
+    ),
+    case!("tabs", "CHT", "\x1b[2I", [Cursor(0, 16)]),
+    case!("tabs", "CBT", "\x1b[1;15H\x1b[Z", [Cursor(0, 8)]),
+    // Modes
+    case!(
+        "modes",
+        "DECAWM wraps",
+        "abcdefghijklmnopqrstuv",
+        [Row(0, "abcdefghijklmnopqrst"), Row(1, "uv")]
+    ),
+    case!(
+        "modes",
+        "DECAWM off overwrites last column",
+        "\x1b[?7labcdefghijklmnopqrstuv",
+        [Row(0, "abcdefghijklmnopqrsv"), Row(1, "")]
+    ),
+    case!("modes", "IRM", "abc\x1b[1;2H\x1b[4hX", [Row(0, "aXbc")]),
+    case!("modes", "LNM", "\x1b[20ha\nb", [Row(1, "b"), Cursor(1, 1)]),
+    case!(
+        "modes",
+        "DECTCEM",
+        "\x1b[?25l",
+        [Expect::Screen("hidden cursor", |s| !s.modes.show_cursor)]
+    ),
+    case!(
+        "modes",
+        "DECCKM",
+        "\x1b[?1h",
+        [Expect::Screen("application cursor keys", |s| {
+            s.modes.application_cursor
+        })]
+    ),
+    case!(
+        "modes",
+        "Bracketed paste",
+        "\x1b[?2004h",
+        [Expect::Screen("bracketed paste", |s| s
+            .modes
+            .bracketed_paste)]
+    ),
+    case!(
+        "modes",
+        "SGR mouse encoding",
+        "\x1b[?1006h",
+        [Expect::Screen("SGR mouse", |s| s.modes.sgr_mouse)]
+    ),
+    case!(
+        "modes",
+        "Alternate screen 1049",
+        "main\x1b[?1049halt\x1b[?1049l",
+        [Row(0, "main"), Cursor(0, 4)]
+    ),
+    // Character sets
+    case!(
+        "charsets",
+        "DEC special graphics",
+        "\x1b(0qx\x1b(B",
+        [Row(0, "─│")]
+    ),
+    case!(
+        "charsets",
+        "SO/SI with G1",
+        "\x1b)0\x0eq\x0fq",
+        [Row(0, "─q")]
+    ),
+    // Rendition
+    case!(
+        "sgr",
+        "Bold and reset",
+        "\x1b[1mA\x1b[0mB",
+        [Expect::Screen("bold A then plain B", |s| {
+            cell_attrs(s, 0, 0).contains(CellAttrs::BOLD)
+                && !cell_attrs(s, 0, 1).contains(CellAttrs::BOLD)
+        })]
+    ),
+    case!(
+        "sgr",
+        "256-color foreground",
+        "\x1b[38;5;100mA",
+        [Expect::Screen("indexed color 100", |s| {
+            cell_fg(s, 0, 0) == Color::Indexed(100)
+        })]
+    ),
+    case!(
+        "sgr",
+        "Truecolor with colons",
+        "\x1b[38:2::1:2:3mA",
+        [Expect::Screen("rgb 1,2,3", |s| {
+            cell_fg(s, 0, 0) == Color::Rgb(Rgb::new(1, 2, 3))
+        })]
+    ),
+    case!(
+        "sgr",
+        "Default foreground",
+        "\x1b[31m\x1b[39mA",
+        [Expect::Screen("default color", |s| {
+            cell_fg(s, 0, 0) == Color::Default
+        })]
+    ),
+    // Miscellaneous
+    case!(
+        "misc",
+        "RIS",
+        "abc\x1b[?25l\x1bc",
+        [
+            Row(0, ""),
+            Cursor(0, 0),
+            Expect::Screen("visible cursor", |s| s.modes.show_cursor)
+        ]
+    ),
+    case!("misc", "REP", "a\x1b[3b", [Row(0, "aaaa")]),
+    case!(
+        "misc",
+        "OSC 0 title with BEL",
+        "\x1b]0;hello\x07",
+        [Expect::Screen("title \"hello\"", |s| s.title == "hello")]
+    ),
+    case!(
+        "misc",
+        "OSC 2 title with ST",
+        "\x1b]2;st\x1b\\",
+        [Expect::Screen("title \"st\"", |s| s.title == "st")]
+    ),
+    case!(
+        "misc",
+        "DECSCUSR",
+        "\x1b[6 q",
+        [Expect::Screen("bar cursor", |s| {
+            s.cursor.style == CursorStyle::Bar
+        })]
+    ),
+    case!(
+        "misc",
+        "Wide character wraps at margin",
+        "\x1b[1;20H日",
+        [Row(0, ""), Row(1, "日")]
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_failure_describes_mismatch() {
+        let case = case!("t", "wrong cursor", "ab", [Cursor(0, 5)]);
+        let result = run_case(&case);
+        assert_eq!(
+            result.failure.as_deref(),
+            Some("cursor at 0,2, expected 0,5")
+        );
+    }
+
+    #[test]
+    fn test_report_counts_by_category() {
+        let report = Report {
+            results: vec![
+                CaseResult {
+                    category: "a",
+                    name: "one",
+                    failure: None,
+                },
+                CaseResult {
+                    category: "a",
+                    name: "two",
+                    failure: Some("broken".into()),
+                },
+            ],
+        };
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| a | 1 | 2 |"));
+        assert!(markdown.contains("- a/two: broken"));
+    }
+}
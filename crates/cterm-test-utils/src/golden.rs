@@ -0,0 +1,172 @@
+//! Golden-file comparison with readable diffs
+
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::TestTerminal;
+
+/// Environment variable that makes golden checks rewrite files instead of failing
+pub const UPDATE_ENV: &str = "CTERM_UPDATE_GOLDEN";
+
+/// Lines of unchanged context shown around each change
+const CONTEXT: usize = 2;
+
+fn update_requested() -> bool {
+    std::env::var_os(UPDATE_ENV).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Compare `actual` with the golden file at `path`, describing any mismatch
+///
+/// With `CTERM_UPDATE_GOLDEN=1` the file is written instead and the check passes.
+pub fn check_golden(path: &Path, actual: &str) -> Result<(), String> {
+    if update_requested() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        return std::fs::write(path, actual).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    let expected = std::fs::read_to_string(path).map_err(|e| {
+        format!(
+            "{}: {} (run with {}=1 to create it)",
+            path.display(),
+            e,
+            UPDATE_ENV
+        )
+    })?;
+    if expected == actual {
+        return Ok(());
+    }
+    Err(format!(
+        "{} does not match (run with {}=1 to accept the new output)\n{}",
+        path.display(),
+        UPDATE_ENV,
+        diff_lines(&expected, actual)
+    ))
+}
+
+/// Assert that `actual` matches the golden file at `path`
+#[track_caller]
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    if let Err(message) = check_golden(path.as_ref(), actual) {
+        panic!("{}", message);
+    }
+}
+
+/// Replay every `*.in` byte stream in `dir` and compare against its `.snap` file
+///
+/// Each stream is fed into a fresh `cols` x `rows` terminal. All mismatches
+/// are reported together, so one run shows every regression.
+#[track_caller]
+pub fn check_golden_dir(dir: impl AsRef<Path>, cols: usize, rows: usize) {
+    let dir = dir.as_ref();
+    let mut inputs: Vec<_> = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("{}: {}", dir.display(), e))
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "in"))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "no *.in streams in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for input in &inputs {
+        let mut term = TestTerminal::new(cols, rows);
+        if let Err(e) = term.feed_file(input) {
+            failures.push(format!("{}: {}", input.display(), e));
+            continue;
+        }
+        if let Err(message) = check_golden(&input.with_extension("snap"), &term.snapshot()) {
+            failures.push(message);
+        }
+    }
+
+    if !failures.is_empty() {
+        panic!(
+            "{} of {} golden streams failed:\n\n{}",
+            failures.len(),
+            inputs.len(),
+            failures.join("\n")
+        );
+    }
+}
+
+/// Line diff of `expected` against `actual`, as `-`/`+` lines with some context
+pub fn diff_lines(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    // Walk the table into (tag, line) pairs
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', old[i]));
+            i += 1;
+        } else {
+            ops.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    // Keep changes and the context around them
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != ' ').collect();
+    let mut out = String::new();
+    let mut last_shown: Option<usize> = None;
+    for (k, &(tag, line)) in ops.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|&c| k + CONTEXT >= c && k <= c + CONTEXT);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_some_and(|last| last + 1 != k) {
+            out.push_str("...\n");
+        }
+        let _ = writeln!(out, "{}{}", tag, line);
+        last_shown = Some(k);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines_marks_changes_with_context() {
+        let expected = "a\nb\nc\nd\ne\nf\ng\n";
+        let actual = "a\nb\nc\nD\ne\nf\ng\n";
+        assert_eq!(diff_lines(expected, actual), " b\n c\n-d\n+D\n e\n f\n");
+    }
+
+    #[test]
+    fn test_diff_lines_separates_distant_hunks() {
+        let expected = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let actual = "x\n2\n3\n4\n5\n6\n7\ny\n";
+        assert_eq!(
+            diff_lines(expected, actual),
+            "-1\n+x\n 2\n 3\n...\n 6\n 7\n-8\n+y\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_identical_is_empty() {
+        assert_eq!(diff_lines("same\n", "same\n"), "");
+    }
+}
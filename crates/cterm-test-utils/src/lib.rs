@@ -0,0 +1,119 @@
+//! cterm-test-utils: Golden-file testing for terminal emulation
+//!
+//! Feeds recorded byte streams into a headless [`Terminal`](cterm_core::Terminal)
+//! and compares a text rendering of the resulting screen against files checked
+//! into the repository:
+//! - [`TestTerminal`] drives a terminal without a PTY and renders snapshots
+//! - [`snapshot`] formats a screen as text plus attribute runs
+//! - [`golden`] compares snapshots with golden files and prints line diffs
+//! - [`conformance`] runs vttest/esctest-style checks and reports VT compliance
+//!
+//! Set `CTERM_UPDATE_GOLDEN=1` to (re)write golden files from the current output.
+
+pub mod conformance;
+pub mod golden;
+pub mod snapshot;
+
+use std::io;
+use std::path::Path;
+
+use cterm_core::screen::ScreenConfig;
+use cterm_core::{Screen, Terminal};
+
+pub use golden::{assert_golden, check_golden_dir, diff_lines, UPDATE_ENV};
+pub use snapshot::snapshot;
+
+/// A terminal without a PTY, for feeding recorded output in tests
+pub struct TestTerminal {
+    terminal: Terminal,
+    replies: Vec<u8>,
+}
+
+impl TestTerminal {
+    /// Create a terminal of the given size with a small scrollback
+    pub fn new(cols: usize, rows: usize) -> Self {
+        Self::with_config(
+            cols,
+            rows,
+            ScreenConfig {
+                scrollback_lines: 1000,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Create a terminal with an explicit screen configuration
+    pub fn with_config(cols: usize, rows: usize, config: ScreenConfig) -> Self {
+        Self {
+            terminal: Terminal::new(cols, rows, config),
+            replies: Vec::new(),
+        }
+    }
+
+    /// Feed bytes as if they had been read from the PTY
+    ///
+    /// Replies the terminal would write back (DSR, DA, ...) are collected and
+    /// available through [`replies`](Self::replies).
+    pub fn feed(&mut self, data: &[u8]) -> &mut Self {
+        let (_, responses) = self.terminal.process_collecting(data);
+        for response in responses {
+            self.replies.extend_from_slice(&response);
+        }
+        self
+    }
+
+    /// Feed a string as if it had been read from the PTY
+    pub fn feed_str(&mut self, data: &str) -> &mut Self {
+        self.feed(data.as_bytes())
+    }
+
+    /// Feed the contents of a recorded byte stream
+    pub fn feed_file(&mut self, path: impl AsRef<Path>) -> io::Result<&mut Self> {
+        let data = std::fs::read(path)?;
+        Ok(self.feed(&data))
+    }
+
+    /// Bytes the terminal replied with so far
+    pub fn replies(&self) -> &[u8] {
+        &self.replies
+    }
+
+    /// Take the collected replies, leaving none behind
+    pub fn take_replies(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.replies)
+    }
+
+    /// The underlying terminal
+    pub fn terminal(&self) -> &Terminal {
+        &self.terminal
+    }
+
+    /// Mutable access to the underlying terminal, e.g. to resize it
+    pub fn terminal_mut(&mut self) -> &mut Terminal {
+        &mut self.terminal
+    }
+
+    /// The terminal's screen
+    pub fn screen(&self) -> &Screen {
+        self.terminal.screen()
+    }
+
+    /// Render the screen in the golden-file format
+    pub fn snapshot(&self) -> String {
+        snapshot(self.terminal.screen())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_collects_replies() {
+        let mut term = TestTerminal::new(10, 3);
+        term.feed_str("ab\x1b[6n");
+        assert_eq!(term.replies(), b"\x1b[1;3R");
+        assert_eq!(term.take_replies(), b"\x1b[1;3R");
+        assert!(term.replies().is_empty());
+    }
+}
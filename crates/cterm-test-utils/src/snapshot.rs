@@ -0,0 +1,178 @@
+//! Text rendering of a screen for golden files
+//!
+//! The format is meant to be read in a diff: a short header, the visible
+//! grid framed by `|` so trailing blanks show, then one line per run of
+//! cells whose style differs from the default.
+//!
+//! ```text
+//! size: 10x2
+//! cursor: 0,5 visible
+//! screen: primary
+//! --- text ---
+//! |hello     |
+//! |          |
+//! --- styles ---
+//! 0: 0-4 fg=red bold
+//! ```
+
+use std::fmt::Write;
+
+use cterm_core::grid::Row;
+use cterm_core::{Cell, CellAttrs, Color, Screen};
+
+/// Render `screen` in the golden-file format
+pub fn snapshot(screen: &Screen) -> String {
+    let mut out = String::new();
+    let cursor = &screen.cursor;
+    let _ = writeln!(out, "size: {}x{}", screen.width(), screen.height());
+    let _ = writeln!(
+        out,
+        "cursor: {},{} {}",
+        cursor.row,
+        cursor.col,
+        if screen.modes.show_cursor {
+            "visible"
+        } else {
+            "hidden"
+        }
+    );
+    let _ = writeln!(
+        out,
+        "screen: {}",
+        if screen.modes.alternate_screen {
+            "alternate"
+        } else {
+            "primary"
+        }
+    );
+    if !screen.title.is_empty() {
+        let _ = writeln!(out, "title: {}", screen.title);
+    }
+
+    if !screen.scrollback().is_empty() {
+        out.push_str("--- scrollback ---\n");
+        for row in screen.scrollback() {
+            let _ = writeln!(out, "|{}|", row_text(row));
+        }
+    }
+
+    let grid = screen.grid();
+    out.push_str("--- text ---\n");
+    for row in 0..grid.height() {
+        if let Some(row) = grid.row(row) {
+            let _ = writeln!(out, "|{}|", row_text(row));
+        }
+    }
+
+    let mut styles = String::new();
+    for row in 0..grid.height() {
+        if let Some(row_cells) = grid.row(row) {
+            for (start, end, style) in style_runs(row_cells) {
+                let _ = writeln!(styles, "{}: {}-{} {}", row, start, end, style);
+            }
+        }
+    }
+    if !styles.is_empty() {
+        out.push_str("--- styles ---\n");
+        out.push_str(&styles);
+    }
+
+    out
+}
+
+/// The grapheme clusters of a row, skipping the spacer half of wide
+/// characters
+pub(crate) fn row_text(row: &Row) -> String {
+    let mut text = String::new();
+    for cell in (0..row.len()).map(|col| &row[col]) {
+        if !cell.attrs.contains(CellAttrs::WIDE_SPACER) {
+            cell.push_grapheme(&mut text);
+        }
+    }
+    text
+}
+
+/// Runs of equally styled cells as `(first col, last col, style)`, default style omitted
+fn style_runs(row: &Row) -> Vec<(usize, usize, String)> {
+    let mut runs: Vec<(usize, usize, String)> = Vec::new();
+    for col in 0..row.len() {
+        let style = cell_style(&row[col]);
+        match runs.last_mut() {
+            Some((_, end, last)) if *end + 1 == col && *last == style => *end = col,
+            _ if style.is_empty() => {}
+            _ => runs.push((col, col, style)),
+        }
+    }
+    runs
+}
+
+/// Describe everything about a cell other than its character
+fn cell_style(cell: &Cell) -> String {
+    let mut parts = Vec::new();
+    if cell.fg != Color::Default {
+        parts.push(format!("fg={}", color_name(&cell.fg)));
+    }
+    if cell.bg != Color::Default {
+        parts.push(format!("bg={}", color_name(&cell.bg)));
+    }
+    if let Some(ref color) = cell.underline_color {
+        parts.push(format!("ul={}", color_name(color)));
+    }
+    let attrs = cell.attrs - (CellAttrs::WIDE | CellAttrs::WIDE_SPACER);
+    for (name, _) in attrs.iter_names() {
+        parts.push(name.to_lowercase());
+    }
+    if let Some(ref link) = cell.hyperlink {
+        parts.push(format!("link={}", link.uri));
+    }
+    parts.join(" ")
+}
+
+fn color_name(color: &Color) -> String {
+    match color {
+        Color::Default => "default".to_string(),
+        Color::Ansi(ansi) => {
+            const NAMES: [&str; 8] = [
+                "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+            ];
+            let index = *ansi as usize;
+            if index < 8 {
+                NAMES[index].to_string()
+            } else {
+                format!("bright-{}", NAMES[index - 8])
+            }
+        }
+        Color::Indexed(index) => format!("idx{}", index),
+        Color::Rgb(rgb) => format!("#{:02x}{:02x}{:02x}", rgb.r, rgb.g, rgb.b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TestTerminal;
+
+    #[test]
+    fn test_snapshot_text_and_styles() {
+        let mut term = TestTerminal::new(8, 2);
+        term.feed_str("\x1b[1;31mhi\x1b[0m \x1b[48;5;17mx\x1b[m");
+        assert_eq!(
+            term.snapshot(),
+            "size: 8x2\n\
+             cursor: 0,4 visible\n\
+             screen: primary\n\
+             --- text ---\n\
+             |hi x    |\n\
+             |        |\n\
+             --- styles ---\n\
+             0: 0-1 fg=red bold\n\
+             0: 3-3 bg=idx17\n"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_wide_chars_keep_alignment() {
+        let mut term = TestTerminal::new(4, 1);
+        term.feed_str("日a");
+        assert!(term.snapshot().contains("|日a |\n"));
+    }
+}
@@ -0,0 +1,16 @@
+//! VT conformance report
+//!
+//! Runs every case in `cterm_test_utils::conformance` and compares the
+//! resulting report with `conformance.md`. A newly passing or newly failing
+//! case changes the report, so compliance is tracked in review.
+
+use cterm_test_utils::{assert_golden, conformance};
+
+#[test]
+fn conformance_report_matches() {
+    let report = conformance::run();
+    assert_golden(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/conformance.md"),
+        &report.to_markdown(),
+    );
+}
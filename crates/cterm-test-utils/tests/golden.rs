@@ -0,0 +1,22 @@
+//! Golden tests for parser and screen behavior
+//!
+//! Each `golden/*.in` file is a recorded byte stream; the matching `.snap`
+//! file is the expected screen. Run with `CTERM_UPDATE_GOLDEN=1` to accept
+//! intentional changes, then review the diff of the `.snap` files.
+
+use cterm_test_utils::{assert_golden, check_golden_dir, TestTerminal};
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+#[test]
+fn recorded_streams_match_golden_files() {
+    check_golden_dir(GOLDEN_DIR, 40, 10);
+}
+
+#[test]
+fn resize_matches_golden_file() {
+    let mut term = TestTerminal::new(20, 4);
+    term.feed_str("a line that is longer than twenty columns\r\nshort");
+    term.terminal_mut().resize(30, 4);
+    assert_golden(format!("{}/resize.snap", GOLDEN_DIR), &term.snapshot());
+}
@@ -331,6 +331,8 @@ pub enum Action {
     // Tab actions
     NewTab,
     CloseTab,
+    /// Reopen the most recently closed tab
+    ReopenClosedTab,
     NextTab,
     PrevTab,
     NextAlertedTab,
@@ -339,17 +341,52 @@ pub enum Action {
     // Window actions
     NewWindow,
     CloseWindow,
+    /// Move the window to the left half of its display
+    SnapLeft,
+    /// Move the window to the right half of its display
+    SnapRight,
+    /// Fill the window's display, leaving the menu bar and Dock visible
+    SnapMaximize,
+
+    // Pane actions
+    /// Split the focused pane, opening a new one to its right
+    SplitRight,
+    /// Split the focused pane, opening a new one below it
+    SplitDown,
+    /// Close the focused pane (the tab when it is the only one)
+    ClosePane,
+    /// Focus the neighboring pane in a direction
+    FocusPane(PaneDirection),
+    /// Move the divider of the focused pane in a direction
+    ResizePane(PaneDirection),
+    /// Scroll the focused pane together with the pane across its split, or
+    /// stop
+    ToggleScrollLock,
 
     // Edit actions
     Copy,
     Paste,
+    /// Pick an earlier copy from the clipboard history and paste it
+    PasteHistory,
     SelectAll,
+    /// Select the output of the last command
+    SelectCommandOutput,
 
     // View actions
     ZoomIn,
     ZoomOut,
     ZoomReset,
+    /// Scale the rendered surface without changing the font or grid size
+    MagnifyIn,
+    MagnifyOut,
+    MagnifyReset,
     ToggleFullscreen,
+    /// Larger font, no tab bar/scrollbar, minimum contrast, optional keystrokes
+    TogglePresentationMode,
+    /// Toggle ligature-free, cell-exact rendering for the active tab
+    ToggleAsciiArtSafe,
+    /// Lock or unlock input to the active tab (scrolling and copying still work)
+    ToggleReadOnly,
 
     // Scroll actions
     ScrollUp,
@@ -358,14 +395,31 @@ pub enum Action {
     ScrollPageDown,
     ScrollToTop,
     ScrollToBottom,
+    /// Scroll the shell prompt above the view to its top
+    PreviousPrompt,
+    /// Scroll the next shell prompt to the top of the view
+    NextPrompt,
 
     // Other
+    /// Label URLs, paths and hashes on screen to open or copy by keyboard
+    HintMode,
+    /// List the distinct regex matches in the buffer and scrollback
+    ExtractMatches,
     OpenPreferences,
     FindText,
     ResetTerminal,
     QuickOpenTemplate,
 }
 
+/// Direction to move the focus or a divider between split panes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PaneDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
 /// A keyboard shortcut
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Shortcut {
@@ -0,0 +1,83 @@
+//! Exit status gutter
+//!
+//! With shell integration marks, each finished command's rows on screen get
+//! a thin bar along the left edge: green when it succeeded, red when it
+//! failed and gray when the shell reported no status. The frontends draw the
+//! [`cterm_core::Screen::command_blocks`] with these colors and show
+//! [`gutter_tooltip`] while the pointer is over a bar.
+
+use cterm_core::color::{ColorPalette, Rgb};
+use cterm_core::screen::CommandFinished;
+use cterm_core::CommandBlock;
+
+use crate::status::command_summary;
+
+/// Width of a gutter bar in points
+pub const GUTTER_WIDTH: f64 = 3.0;
+
+/// How far from the left edge the pointer counts as over the gutter, in
+/// points; wider than the bar so it is easy to hit
+pub const GUTTER_HOVER_WIDTH: f64 = 8.0;
+
+/// Color of `block`'s bar: the theme's green, red, or bright black
+pub fn gutter_color(block: &CommandBlock, palette: &ColorPalette) -> Rgb {
+    match block.exit_code {
+        Some(0) => palette.ansi[2],
+        Some(_) => palette.ansi[1],
+        None => palette.ansi[8],
+    }
+}
+
+/// The block whose bar is at visible `row`, if the pointer at `x` points
+/// from the view's left edge is over the gutter
+pub fn gutter_block_at(blocks: &[CommandBlock], x: f64, row: usize) -> Option<&CommandBlock> {
+    if !(0.0..GUTTER_HOVER_WIDTH).contains(&x) {
+        return None;
+    }
+    blocks.iter().find(|block| block.contains(row))
+}
+
+/// Hover text for a block, e.g. "Command finished in 2m 05s (exit 1)"
+pub fn gutter_tooltip(block: &CommandBlock) -> String {
+    match (block.duration, block.exit_code) {
+        (Some(duration), exit_code) => command_summary(&CommandFinished {
+            duration,
+            exit_code,
+        }),
+        (None, Some(code)) => format!("Command finished (exit {})", code),
+        (None, None) => "Command finished".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_gutter() {
+        let block = |top, bottom, exit_code, duration| CommandBlock {
+            top,
+            bottom,
+            exit_code,
+            duration,
+        };
+        let blocks = [
+            block(0, 2, Some(0), Some(Duration::from_secs(3))),
+            block(2, 5, Some(2), None),
+        ];
+        assert_eq!(gutter_block_at(&blocks, 1.0, 3), Some(&blocks[1]));
+        assert_eq!(gutter_block_at(&blocks, 20.0, 3), None);
+        assert_eq!(gutter_block_at(&blocks, 1.0, 5), None);
+
+        assert_eq!(
+            gutter_tooltip(&blocks[0]),
+            "Command finished in 3s (exit 0)"
+        );
+        assert_eq!(gutter_tooltip(&blocks[1]), "Command finished (exit 2)");
+
+        let palette = ColorPalette::default();
+        assert_eq!(gutter_color(&blocks[0], &palette), palette.ansi[2]);
+        assert_eq!(gutter_color(&blocks[1], &palette), palette.ansi[1]);
+    }
+}
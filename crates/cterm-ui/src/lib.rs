@@ -5,11 +5,23 @@
 //! interface.
 
 pub mod events;
+pub mod gutter;
+pub mod pointer;
+pub mod prompt;
+pub mod scroll_marks;
+pub mod status;
 pub mod theme;
+pub mod theme_editor;
 pub mod traits;
 pub mod utils;
 
 pub use events::*;
+pub use gutter::{gutter_block_at, gutter_color, gutter_tooltip, GUTTER_WIDTH};
+pub use pointer::{PointerHider, PointerMotion};
+pub use prompt::{Prompt, PromptAction, PromptQueue};
+pub use scroll_marks::{mark_color, scroll_mark_ticks, MARK_HEIGHT};
+pub use status::*;
 pub use theme::*;
+pub use theme_editor::{PreviewSpan, ThemeColor, ThemeEditor, PREVIEW_COLUMNS};
 pub use traits::*;
 pub use utils::*;
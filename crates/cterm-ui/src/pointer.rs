@@ -0,0 +1,115 @@
+//! Hiding the mouse pointer while typing
+//!
+//! With `input.hide_pointer_while_typing`, a key press over the terminal
+//! hides the pointer so it doesn't cover the text being typed, and moving
+//! the mouse shows it again. The frontends feed key presses and pointer
+//! motion to a [`PointerHider`] and hide or restore their cursor as it says.
+
+/// When to hide and show the pointer, for one terminal view
+///
+/// Toolkits often send a motion event at the same spot when the cursor
+/// changes or the view scrolls under a still mouse; those don't count as
+/// moving, so the pointer stays hidden until the mouse really moves.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PointerHider {
+    enabled: bool,
+    hidden: bool,
+    /// Where the pointer last was, in view coordinates
+    position: Option<(f64, f64)>,
+}
+
+/// What a frontend does with a motion event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerMotion {
+    /// The pointer shows as usual
+    Moved,
+    /// The pointer was hidden and shows again: restore the cursor
+    Shown,
+    /// Still at the same spot while hidden: leave the cursor hidden
+    Still,
+}
+
+impl PointerHider {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Turn hiding on or off; returns true when the pointer was hidden and
+    /// must be shown again
+    pub fn set_enabled(&mut self, enabled: bool) -> bool {
+        self.enabled = enabled;
+        !enabled && std::mem::take(&mut self.hidden)
+    }
+
+    /// Whether the pointer is hidden
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// A key was pressed; returns true when the pointer should be hidden now
+    ///
+    /// Modifier keys alone don't hide it, since they often start a click or
+    /// a drag.
+    pub fn key_pressed(&mut self, modifier_only: bool) -> bool {
+        if !self.enabled || modifier_only || self.hidden {
+            return false;
+        }
+        self.hidden = true;
+        true
+    }
+
+    /// The pointer is at `x`, `y` in the view
+    pub fn pointer_moved(&mut self, x: f64, y: f64) -> PointerMotion {
+        let still = self
+            .position
+            .is_some_and(|(px, py)| (px - x).abs() < 1.0 && (py - y).abs() < 1.0);
+        self.position = Some((x, y));
+        match (self.hidden, still) {
+            (false, _) => PointerMotion::Moved,
+            (true, true) => PointerMotion::Still,
+            (true, false) => {
+                self.hidden = false;
+                PointerMotion::Shown
+            }
+        }
+    }
+
+    /// The pointer left the view: it shows again wherever it goes
+    pub fn pointer_left(&mut self) {
+        self.hidden = false;
+        self.position = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pointer_hider() {
+        let mut hider = PointerHider::new(true);
+        assert_eq!(hider.pointer_moved(10.0, 10.0), PointerMotion::Moved);
+
+        assert!(!hider.key_pressed(true));
+        assert!(hider.key_pressed(false));
+        assert!(!hider.key_pressed(false));
+        assert!(hider.is_hidden());
+
+        // A synthetic event at the same spot keeps it hidden
+        assert_eq!(hider.pointer_moved(10.0, 10.4), PointerMotion::Still);
+        assert_eq!(hider.pointer_moved(14.0, 10.0), PointerMotion::Shown);
+        assert_eq!(hider.pointer_moved(20.0, 10.0), PointerMotion::Moved);
+
+        assert!(hider.key_pressed(false));
+        hider.pointer_left();
+        assert!(!hider.is_hidden());
+
+        assert!(hider.key_pressed(false));
+        assert!(hider.set_enabled(false));
+        assert!(!hider.key_pressed(false));
+        assert!(!hider.set_enabled(true));
+    }
+}
@@ -0,0 +1,341 @@
+//! In-window prompts
+//!
+//! Things a program in a terminal did that need the user's answer are
+//! queued as prompts and shown one at a time in the notification bar along
+//! the top of the window: a file it sent (save or discard it) or something
+//! the [permission policy](cterm_core::PermissionPolicy) asks about, such as
+//! an OSC 52 clipboard read (allow or deny it). Frontends keep a
+//! [`PromptQueue`], draw [`PromptQueue::message`] with a button per action,
+//! and pass key presses through [`PromptQueue::action_for_key`] before their
+//! own shortcuts.
+
+use std::collections::VecDeque;
+
+use cterm_core::Capability;
+
+use crate::events::{KeyCode, Modifiers};
+use crate::utils::format_size;
+
+/// Something waiting for the user's answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Prompt {
+    /// A file the program sent, held until it is saved or discarded
+    File {
+        id: u64,
+        name: Option<String>,
+        size: usize,
+    },
+    /// The program did something the permission policy asks about
+    Permission {
+        /// Frontend's id for the terminal that asked
+        tab: u64,
+        capability: Capability,
+    },
+}
+
+/// An answer to a prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PromptAction {
+    /// Show the file with Quick Look (macOS only)
+    Preview,
+    /// Save the file to the download directory
+    Save,
+    /// Ask where to save the file
+    SaveAs,
+    /// Delete the file
+    Discard,
+    /// Let the program go ahead this once
+    Allow,
+    /// Let the program go ahead now and every later time in the same
+    /// terminal
+    AlwaysAllow,
+    /// Refuse it
+    Deny,
+}
+
+impl PromptAction {
+    /// Button label
+    pub fn label(self) -> &'static str {
+        match self {
+            PromptAction::Preview => "Preview",
+            PromptAction::Save => "Save",
+            PromptAction::SaveAs => "Save As...",
+            PromptAction::Discard => "Discard",
+            PromptAction::Allow => "Allow",
+            PromptAction::AlwaysAllow => "Always Allow",
+            PromptAction::Deny => "Deny",
+        }
+    }
+
+    /// Key that picks this action together with Ctrl+Shift (Cmd+Shift on
+    /// macOS)
+    pub fn key(self) -> Option<KeyCode> {
+        match self {
+            PromptAction::Preview => Some(KeyCode::Space),
+            PromptAction::Save | PromptAction::Allow => Some(KeyCode::Enter),
+            PromptAction::SaveAs => Some(KeyCode::S),
+            PromptAction::Discard | PromptAction::Deny => Some(KeyCode::Backspace),
+            PromptAction::AlwaysAllow => None,
+        }
+    }
+
+    /// Whether this action answers a prompt in the negative
+    pub fn is_dismissal(self) -> bool {
+        matches!(self, PromptAction::Discard | PromptAction::Deny)
+    }
+}
+
+impl Prompt {
+    /// Text shown next to the buttons
+    pub fn message(&self) -> String {
+        match self {
+            Prompt::File { name, size, .. } => format!(
+                "Received file: {} ({})",
+                name.as_deref().unwrap_or("unnamed file"),
+                format_size(*size)
+            ),
+            Prompt::Permission { capability, .. } => {
+                format!("The program wants to {}", capability.description())
+            }
+        }
+    }
+
+    /// Actions offered, in button order (`can_preview` for frontends that
+    /// can show a file)
+    pub fn actions(&self, can_preview: bool) -> Vec<PromptAction> {
+        match self {
+            Prompt::File { .. } => {
+                let mut actions = Vec::with_capacity(4);
+                if can_preview {
+                    actions.push(PromptAction::Preview);
+                }
+                actions.extend([
+                    PromptAction::Save,
+                    PromptAction::SaveAs,
+                    PromptAction::Discard,
+                ]);
+                actions
+            }
+            Prompt::Permission { .. } => vec![
+                PromptAction::Allow,
+                PromptAction::AlwaysAllow,
+                PromptAction::Deny,
+            ],
+        }
+    }
+
+    /// Id of the received file this prompt is about
+    pub fn file_id(&self) -> Option<u64> {
+        match self {
+            Prompt::File { id, .. } => Some(*id),
+            Prompt::Permission { .. } => None,
+        }
+    }
+}
+
+/// Prompts waiting for an answer, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct PromptQueue {
+    prompts: VecDeque<Prompt>,
+}
+
+impl PromptQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prompt being shown
+    pub fn current(&self) -> Option<&Prompt> {
+        self.prompts.front()
+    }
+
+    /// Number of prompts waiting, the current one included
+    pub fn len(&self) -> usize {
+        self.prompts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prompts.is_empty()
+    }
+
+    /// Text for the current prompt, noting how many more are waiting
+    pub fn message(&self) -> Option<String> {
+        let current = self.current()?;
+        Some(match self.len() - 1 {
+            0 => current.message(),
+            more => format!("{} (+{} more)", current.message(), more),
+        })
+    }
+
+    /// Queue a received file
+    ///
+    /// Only the latest file is kept until it is saved, so it replaces any
+    /// file still waiting.
+    pub fn push_file(&mut self, id: u64, name: Option<String>, size: usize) {
+        self.prompts.retain(|p| p.file_id().is_none());
+        self.prompts.push_back(Prompt::File { id, name, size });
+    }
+
+    /// Ask whether terminal `tab` may use `capability`
+    ///
+    /// Nothing is queued if the same question is already waiting.
+    pub fn ask_permission(&mut self, tab: u64, capability: Capability) {
+        let prompt = Prompt::Permission { tab, capability };
+        if !self.prompts.contains(&prompt) {
+            self.prompts.push_back(prompt);
+        }
+    }
+
+    /// Answer the current prompt, returning it if `action` is one of its
+    /// actions
+    ///
+    /// Previewing a file does not answer it, so the prompt stays.
+    pub fn resolve(&mut self, action: PromptAction) -> Option<Prompt> {
+        if action == PromptAction::Preview || !self.current()?.actions(true).contains(&action) {
+            return None;
+        }
+        self.prompts.pop_front()
+    }
+
+    /// Action of the current prompt picked by a key press, if any
+    pub fn action_for_key(
+        &self,
+        key: KeyCode,
+        modifiers: Modifiers,
+        can_preview: bool,
+    ) -> Option<PromptAction> {
+        if modifiers != Modifiers::CTRL | Modifiers::SHIFT
+            && modifiers != Modifiers::SUPER | Modifiers::SHIFT
+        {
+            return None;
+        }
+        let key = match key {
+            KeyCode::NumpadEnter => KeyCode::Enter,
+            key => key,
+        };
+        self.current()?
+            .actions(can_preview)
+            .into_iter()
+            .find(|action| action.key() == Some(key))
+    }
+
+    /// Drop the prompts of a closed terminal
+    pub fn forget_tab(&mut self, tab: u64) {
+        self.prompts
+            .retain(|p| !matches!(p, Prompt::Permission { tab: t, .. } if *t == tab));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_queue_order() {
+        let mut queue = PromptQueue::new();
+        assert!(queue.message().is_none());
+
+        queue.push_file(1, Some("a.bin".into()), 2048);
+        queue.ask_permission(7, Capability::ClipboardRead);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(
+            queue.message().unwrap(),
+            "Received file: a.bin (2.0 KB) (+1 more)"
+        );
+
+        // Not an action of the file prompt
+        assert!(queue.resolve(PromptAction::Allow).is_none());
+        assert!(queue.resolve(PromptAction::Preview).is_none());
+        assert_eq!(
+            queue.resolve(PromptAction::Save).unwrap().file_id(),
+            Some(1)
+        );
+        assert_eq!(
+            queue.message().unwrap(),
+            "The program wants to read the clipboard"
+        );
+        assert!(queue.resolve(PromptAction::Deny).is_some());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_prompt_queue_latest_file() {
+        let mut queue = PromptQueue::new();
+        queue.push_file(1, None, 10);
+        queue.push_file(2, None, 20);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.current().unwrap().file_id(), Some(2));
+        assert_eq!(
+            queue.message().unwrap(),
+            "Received file: unnamed file (20 bytes)"
+        );
+    }
+
+    #[test]
+    fn test_prompt_queue_permissions() {
+        let mut queue = PromptQueue::new();
+        queue.ask_permission(1, Capability::ClipboardRead);
+        // Repeated requests wait on the same prompt
+        queue.ask_permission(1, Capability::ClipboardRead);
+        queue.ask_permission(1, Capability::TitleChange);
+        queue.ask_permission(2, Capability::ClipboardRead);
+        assert_eq!(queue.len(), 3);
+        assert_eq!(
+            queue.message().unwrap(),
+            "The program wants to read the clipboard (+2 more)"
+        );
+
+        assert_eq!(
+            queue.resolve(PromptAction::AlwaysAllow),
+            Some(Prompt::Permission {
+                tab: 1,
+                capability: Capability::ClipboardRead
+            })
+        );
+        queue.forget_tab(1);
+        assert_eq!(queue.len(), 1);
+        queue.forget_tab(2);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_prompt_queue_keys() {
+        let mut queue = PromptQueue::new();
+        let ctrl_shift = Modifiers::CTRL | Modifiers::SHIFT;
+        let cmd_shift = Modifiers::SUPER | Modifiers::SHIFT;
+        assert_eq!(queue.action_for_key(KeyCode::Enter, ctrl_shift, true), None);
+
+        queue.push_file(1, None, 10);
+        assert_eq!(
+            queue.action_for_key(KeyCode::Enter, ctrl_shift, true),
+            Some(PromptAction::Save)
+        );
+        assert_eq!(
+            queue.action_for_key(KeyCode::S, cmd_shift, true),
+            Some(PromptAction::SaveAs)
+        );
+        assert_eq!(
+            queue.action_for_key(KeyCode::Space, ctrl_shift, true),
+            Some(PromptAction::Preview)
+        );
+        assert_eq!(
+            queue.action_for_key(KeyCode::Space, ctrl_shift, false),
+            None
+        );
+        assert_eq!(
+            queue.action_for_key(KeyCode::Enter, Modifiers::CTRL, true),
+            None
+        );
+
+        queue.resolve(PromptAction::Discard);
+        queue.ask_permission(1, Capability::ClipboardRead);
+        assert_eq!(
+            queue.action_for_key(KeyCode::NumpadEnter, ctrl_shift, false),
+            Some(PromptAction::Allow)
+        );
+        assert_eq!(
+            queue.action_for_key(KeyCode::Backspace, ctrl_shift, false),
+            Some(PromptAction::Deny)
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Scrollbar marks
+//!
+//! Alongside the scrollbar thumb, short ticks across the track show where
+//! the prompts (blue), errors (red) and search matches (yellow) are in the
+//! scrollback. The frontends lay out [`cterm_core::Screen::marks`] with
+//! [`scroll_mark_ticks`] and draw each tick [`MARK_HEIGHT`] tall under the
+//! thumb.
+
+use cterm_core::color::{ColorPalette, Rgb};
+use cterm_core::{ScrollMark, ScrollMarkKind};
+
+/// Height of a tick in points
+pub const MARK_HEIGHT: f64 = 2.0;
+
+/// Color of a mark: the theme's blue, red, or yellow
+pub fn mark_color(kind: ScrollMarkKind, palette: &ColorPalette) -> Rgb {
+    match kind {
+        ScrollMarkKind::Prompt => palette.ansi[4],
+        ScrollMarkKind::Error => palette.ansi[1],
+        ScrollMarkKind::SearchHit => palette.ansi[3],
+    }
+}
+
+/// Ticks for `marks` along a track `height` points tall that spans
+/// `total_lines` lines: the top of each tick and its color
+///
+/// Marks of one kind that land on the same pixel share a tick.
+pub fn scroll_mark_ticks(
+    marks: &[ScrollMark],
+    total_lines: usize,
+    height: f64,
+    palette: &ColorPalette,
+) -> Vec<(f64, Rgb)> {
+    if total_lines == 0 {
+        return Vec::new();
+    }
+    let max_y = (height - MARK_HEIGHT).max(0.0);
+    let mut last = [None; 3];
+    let mut ticks = Vec::new();
+    for mark in marks {
+        let center = (mark.line as f64 + 0.5) / total_lines as f64 * height;
+        let y = (center - MARK_HEIGHT / 2.0).clamp(0.0, max_y).round();
+        let last = &mut last[mark.kind as usize];
+        if *last == Some(y) {
+            continue;
+        }
+        *last = Some(y);
+        ticks.push((y, mark_color(mark.kind, palette)));
+    }
+    ticks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_mark_ticks() {
+        let palette = ColorPalette::default();
+        let mark = |line, kind| ScrollMark { line, kind };
+        let marks = [
+            mark(0, ScrollMarkKind::Prompt),
+            mark(1, ScrollMarkKind::Prompt),
+            mark(1, ScrollMarkKind::Error),
+            mark(50, ScrollMarkKind::SearchHit),
+            mark(999, ScrollMarkKind::Prompt),
+        ];
+        let ticks = scroll_mark_ticks(&marks, 1000, 100.0, &palette);
+        assert_eq!(
+            ticks,
+            [
+                (0.0, palette.ansi[4]),
+                (0.0, palette.ansi[1]),
+                (4.0, palette.ansi[3]),
+                (98.0, palette.ansi[4]),
+            ]
+        );
+        assert!(scroll_mark_ticks(&marks, 0, 100.0, &palette).is_empty());
+    }
+}
@@ -0,0 +1,517 @@
+//! Status strip for transient terminal states
+//!
+//! The status strip is a thin, single-line bar along the bottom edge of the
+//! terminal that appears only while a transient mode is active: input being
+//! locked or broadcast to several terminals, the session being recorded or
+//! played back, keyboard copy mode, hint mode,
+//! incremental search, a large paste or file transfer in progress, output
+//! too fast to draw every frame of, or input method composition.
+//! It also shows short-lived
+//! toasts, such as a long command finishing. Frontends keep one [`StatusStrip`] per terminal,
+//! update it as the modes change, and draw [`StatusStrip::segments`] over the
+//! bottom row (it never changes the grid size).
+
+use std::time::{Duration, Instant};
+
+use cterm_core::color::Rgb;
+use cterm_core::file_transfer::{TransferDirection, TransferProtocol};
+use cterm_core::screen::CommandFinished;
+use cterm_core::throughput::OutputStats;
+
+use crate::theme::Theme;
+use crate::utils::format_size;
+
+/// One piece of text in the status strip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusSegment {
+    /// Text to display
+    pub text: String,
+    /// Whether the segment needs attention (drawn in the emphasis color)
+    pub emphasis: bool,
+}
+
+impl StatusSegment {
+    /// Foreground color for this segment
+    pub fn color(&self, theme: &Theme) -> Rgb {
+        if self.emphasis {
+            theme.colors.ansi[9]
+        } else {
+            theme.ui.tab_active_text
+        }
+    }
+}
+
+/// Search state shown in the strip
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SearchStatus {
+    query: String,
+    /// 1-based index of the current match (0 when unknown)
+    current: usize,
+    total: usize,
+}
+
+/// Toast shown in the strip until it expires
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Toast {
+    segment: StatusSegment,
+    until: Instant,
+}
+
+/// Transient states to show along the bottom of a terminal
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusStrip {
+    read_only: bool,
+    broadcast: bool,
+    recording: bool,
+    /// Position and speed of a recording being played back
+    playback: Option<String>,
+    copy_mode: bool,
+    /// Label letters typed so far while hint mode is active
+    hints: Option<String>,
+    search: Option<SearchStatus>,
+    /// Bytes written and total bytes of a paste in progress
+    paste: Option<(usize, usize)>,
+    /// Protocol and direction of a ZMODEM or Kermit transfer in progress
+    transfer: Option<(TransferProtocol, TransferDirection)>,
+    /// Bytes per second of output too fast to draw every frame of
+    fast_output: Option<u64>,
+    composing: Option<String>,
+    toast: Option<Toast>,
+}
+
+impl StatusStrip {
+    /// How long a toast stays visible
+    pub const TOAST_DURATION: Duration = Duration::from_secs(6);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Background color of the strip
+    pub fn background(theme: &Theme) -> Rgb {
+        theme.ui.tab_bar_background
+    }
+
+    /// Show or hide the read-only (input locked) indicator; returns true if it
+    /// changed
+    pub fn set_read_only(&mut self, on: bool) -> bool {
+        std::mem::replace(&mut self.read_only, on) != on
+    }
+
+    /// Show or hide the broadcast-input indicator; returns true if it changed
+    pub fn set_broadcast(&mut self, on: bool) -> bool {
+        std::mem::replace(&mut self.broadcast, on) != on
+    }
+
+    /// Show or hide the recording indicator; returns true if it changed
+    pub fn set_recording(&mut self, on: bool) -> bool {
+        std::mem::replace(&mut self.recording, on) != on
+    }
+
+    /// Show (`Some` with the player's status) or hide the playback
+    /// indicator; returns true if it changed
+    pub fn set_playback(&mut self, status: Option<&str>) -> bool {
+        let playback = status.map(str::to_string);
+        if self.playback == playback {
+            return false;
+        }
+        self.playback = playback;
+        true
+    }
+
+    /// Show or hide the copy mode indicator; returns true if it changed
+    pub fn set_copy_mode(&mut self, on: bool) -> bool {
+        std::mem::replace(&mut self.copy_mode, on) != on
+    }
+
+    /// Show (`Some` with the letters typed so far) or hide the hint mode
+    /// indicator; returns true if it changed
+    pub fn set_hints(&mut self, typed: Option<&str>) -> bool {
+        let hints = typed.map(str::to_string);
+        if self.hints == hints {
+            return false;
+        }
+        self.hints = hints;
+        true
+    }
+
+    /// Show the search query and match position (`current` is 1-based, 0 if
+    /// not yet known); returns true if it changed
+    pub fn set_search(&mut self, query: &str, current: usize, total: usize) -> bool {
+        let search = Some(SearchStatus {
+            query: query.to_string(),
+            current,
+            total,
+        });
+        if self.search == search {
+            return false;
+        }
+        self.search = search;
+        true
+    }
+
+    /// Hide the search indicator; returns true if it changed
+    pub fn clear_search(&mut self) -> bool {
+        self.search.take().is_some()
+    }
+
+    /// Show the progress of a paste (`Some((written, total))` in bytes) or
+    /// hide it; returns true if it changed
+    ///
+    /// While the progress is shown, a click on the strip cancels the paste.
+    pub fn set_paste_progress(&mut self, progress: Option<(usize, usize)>) -> bool {
+        std::mem::replace(&mut self.paste, progress) != progress
+    }
+
+    /// Whether a paste's progress is shown
+    pub fn is_pasting(&self) -> bool {
+        self.paste.is_some()
+    }
+
+    /// Show (`Some` with its protocol and direction) or hide a file transfer
+    /// in progress; returns true if it changed
+    ///
+    /// While it is shown, a click on the strip cancels the transfer.
+    pub fn set_transfer(
+        &mut self,
+        transfer: Option<(TransferProtocol, TransferDirection)>,
+    ) -> bool {
+        std::mem::replace(&mut self.transfer, transfer) != transfer
+    }
+
+    /// Whether a file transfer is shown
+    pub fn is_transferring(&self) -> bool {
+        self.transfer.is_some()
+    }
+
+    /// Show the fast output indicator while `stats` are fast, else hide it;
+    /// returns true if it changed
+    pub fn set_output_stats(&mut self, stats: &OutputStats) -> bool {
+        let fast_output = stats.is_fast().then_some(stats.bytes_per_sec);
+        std::mem::replace(&mut self.fast_output, fast_output) != fast_output
+    }
+
+    /// Show (or with `None`/empty text, hide) the IME composition text;
+    /// returns true if it changed
+    pub fn set_composing(&mut self, text: Option<&str>) -> bool {
+        let composing = text.filter(|t| !t.is_empty()).map(str::to_string);
+        if self.composing == composing {
+            return false;
+        }
+        self.composing = composing;
+        true
+    }
+
+    /// Show `text` for [`Self::TOAST_DURATION`] from `now`, replacing any
+    /// current toast
+    pub fn show_toast(&mut self, text: String, emphasis: bool, now: Instant) {
+        self.toast = Some(Toast {
+            segment: StatusSegment { text, emphasis },
+            until: now + Self::TOAST_DURATION,
+        });
+    }
+
+    /// Show a toast for a finished command (emphasized if it failed)
+    pub fn show_command_finished(&mut self, finished: &CommandFinished, now: Instant) {
+        let failed = finished.exit_code.is_some_and(|code| code != 0);
+        self.show_toast(command_summary(finished), failed, now);
+    }
+
+    /// Drop the toast once it has expired; returns true if it changed
+    pub fn expire_toast(&mut self, now: Instant) -> bool {
+        if self.toast.as_ref().is_some_and(|toast| now >= toast.until) {
+            self.toast = None;
+            return true;
+        }
+        false
+    }
+
+    /// Whether the strip has anything to show
+    pub fn is_visible(&self) -> bool {
+        self.read_only
+            || self.broadcast
+            || self.recording
+            || self.playback.is_some()
+            || self.copy_mode
+            || self.hints.is_some()
+            || self.search.is_some()
+            || self.paste.is_some()
+            || self.transfer.is_some()
+            || self.fast_output.is_some()
+            || self.composing.is_some()
+            || self.toast.is_some()
+    }
+
+    /// Segments to draw, left to right
+    pub fn segments(&self) -> Vec<StatusSegment> {
+        let mut segments = Vec::new();
+        if self.read_only {
+            segments.push(StatusSegment {
+                text: "READ-ONLY".into(),
+                emphasis: true,
+            });
+        }
+        if self.broadcast {
+            segments.push(StatusSegment {
+                text: "BROADCAST".into(),
+                emphasis: true,
+            });
+        }
+        if self.recording {
+            segments.push(StatusSegment {
+                text: "REC".into(),
+                emphasis: true,
+            });
+        }
+        if let Some(ref playback) = self.playback {
+            segments.push(StatusSegment {
+                text: playback.clone(),
+                emphasis: false,
+            });
+        }
+        if self.copy_mode {
+            segments.push(StatusSegment {
+                text: "COPY".into(),
+                emphasis: false,
+            });
+        }
+        if let Some(ref typed) = self.hints {
+            segments.push(StatusSegment {
+                text: format!("HINTS {}", typed).trim_end().to_string(),
+                emphasis: false,
+            });
+        }
+        if let Some(ref search) = self.search {
+            let text = if search.total == 0 {
+                format!("Search: {} (no matches)", search.query)
+            } else if search.current == 0 {
+                format!("Search: {} ({} matches)", search.query, search.total)
+            } else {
+                format!(
+                    "Search: {} ({}/{})",
+                    search.query, search.current, search.total
+                )
+            };
+            segments.push(StatusSegment {
+                text,
+                emphasis: search.total == 0,
+            });
+        }
+        if let Some((written, total)) = self.paste {
+            let percent = written * 100 / total.max(1);
+            segments.push(StatusSegment {
+                text: format!("Pasting {}% of {}", percent, format_size(total)),
+                emphasis: false,
+            });
+            segments.push(StatusSegment {
+                text: "[Cancel]".into(),
+                emphasis: true,
+            });
+        }
+        if let Some((protocol, direction)) = self.transfer {
+            let action = match direction {
+                TransferDirection::Receive => "receiving",
+                TransferDirection::Send => "sending",
+            };
+            segments.push(StatusSegment {
+                text: format!("{}: {}", protocol.name(), action),
+                emphasis: false,
+            });
+            segments.push(StatusSegment {
+                text: "[Cancel]".into(),
+                emphasis: true,
+            });
+        }
+        if let Some(bytes_per_sec) = self.fast_output {
+            segments.push(StatusSegment {
+                text: format!("FAST OUTPUT {}/s", format_size(bytes_per_sec as usize)),
+                emphasis: false,
+            });
+        }
+        if let Some(ref text) = self.composing {
+            segments.push(StatusSegment {
+                text: format!("IME: {}", text),
+                emphasis: false,
+            });
+        }
+        if let Some(ref toast) = self.toast {
+            segments.push(toast.segment.clone());
+        }
+        segments
+    }
+}
+
+/// One-line description of a finished command, e.g. "Command finished in
+/// 2m 05s (exit 1)"
+pub fn command_summary(finished: &CommandFinished) -> String {
+    let duration = format_duration(finished.duration);
+    match finished.exit_code {
+        Some(code) => format!("Command finished in {} (exit {})", duration, code),
+        None => format!("Command finished in {}", duration),
+    }
+}
+
+/// Format a duration as "45s", "2m 05s" or "1h 02m"
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_strip_segments() {
+        let mut strip = StatusStrip::new();
+        assert!(!strip.is_visible());
+        assert!(strip.segments().is_empty());
+
+        assert!(strip.set_search("foo", 2, 5));
+        assert!(!strip.set_search("foo", 2, 5));
+        assert!(strip.set_broadcast(true));
+        assert!(strip.is_visible());
+
+        let segments = strip.segments();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].text, "BROADCAST");
+        assert!(segments[0].emphasis);
+        assert_eq!(segments[1].text, "Search: foo (2/5)");
+
+        assert!(strip.clear_search());
+        assert!(strip.set_broadcast(false));
+        assert!(!strip.is_visible());
+    }
+
+    #[test]
+    fn test_status_strip_read_only() {
+        let mut strip = StatusStrip::new();
+        assert!(strip.set_read_only(true));
+        assert!(!strip.set_read_only(true));
+        strip.set_broadcast(true);
+        let segments = strip.segments();
+        assert_eq!(segments[0].text, "READ-ONLY");
+        assert_eq!(segments[1].text, "BROADCAST");
+        assert!(strip.set_read_only(false));
+        assert_eq!(strip.segments().len(), 1);
+        assert!(strip.set_recording(true));
+        assert_eq!(strip.segments()[1].text, "REC");
+        assert!(strip.set_playback(Some("▶ 0:12 / 1:30")));
+        assert!(!strip.set_playback(Some("▶ 0:12 / 1:30")));
+        assert_eq!(strip.segments()[2].text, "▶ 0:12 / 1:30");
+    }
+
+    #[test]
+    fn test_status_strip_paste() {
+        let mut strip = StatusStrip::new();
+        assert!(strip.set_paste_progress(Some((4096, 16384))));
+        assert!(!strip.set_paste_progress(Some((4096, 16384))));
+        assert!(strip.is_pasting());
+        let segments = strip.segments();
+        assert_eq!(segments[0].text, "Pasting 25% of 16.0 KB");
+        assert_eq!(segments[1].text, "[Cancel]");
+        assert!(strip.set_paste_progress(None));
+        assert!(!strip.is_visible());
+    }
+
+    #[test]
+    fn test_status_strip_output_stats() {
+        let mut strip = StatusStrip::new();
+        let slow = OutputStats {
+            bytes_per_sec: 2048,
+            lines_per_sec: 30,
+        };
+        assert!(!strip.set_output_stats(&slow));
+        assert!(!strip.is_visible());
+
+        let fast = OutputStats {
+            bytes_per_sec: 3 << 20,
+            lines_per_sec: 40_000,
+        };
+        assert!(strip.set_output_stats(&fast));
+        assert!(!strip.set_output_stats(&fast));
+        assert_eq!(strip.segments()[0].text, "FAST OUTPUT 3.0 MB/s");
+        assert!(strip.set_output_stats(&OutputStats::default()));
+        assert!(!strip.is_visible());
+    }
+
+    #[test]
+    fn test_status_strip_transfer() {
+        let mut strip = StatusStrip::new();
+        let zmodem = (TransferProtocol::Zmodem, TransferDirection::Receive);
+        assert!(strip.set_transfer(Some(zmodem)));
+        assert!(!strip.set_transfer(Some(zmodem)));
+        assert!(strip.is_transferring());
+        let segments = strip.segments();
+        assert_eq!(segments[0].text, "ZMODEM: receiving");
+        assert_eq!(segments[1].text, "[Cancel]");
+        assert!(strip.set_transfer(Some((TransferProtocol::Kermit, TransferDirection::Send))));
+        assert_eq!(strip.segments()[0].text, "Kermit: sending");
+        assert!(strip.set_transfer(None));
+        assert!(!strip.is_visible());
+    }
+
+    #[test]
+    fn test_status_strip_composing() {
+        let mut strip = StatusStrip::new();
+        assert!(!strip.set_composing(Some("")));
+        assert!(strip.set_composing(Some("にほん")));
+        assert_eq!(strip.segments()[0].text, "IME: にほん");
+        assert!(strip.set_composing(None));
+        assert!(!strip.is_visible());
+    }
+
+    #[test]
+    fn test_status_strip_hints() {
+        let mut strip = StatusStrip::new();
+        assert!(strip.set_hints(Some("")));
+        assert_eq!(strip.segments()[0].text, "HINTS");
+        assert!(strip.set_hints(Some("as")));
+        assert_eq!(strip.segments()[0].text, "HINTS as");
+        assert!(!strip.set_hints(Some("as")));
+        assert!(strip.set_hints(None));
+        assert!(!strip.is_visible());
+    }
+
+    #[test]
+    fn test_status_strip_toast() {
+        let now = Instant::now();
+        let mut strip = StatusStrip::new();
+        strip.show_command_finished(
+            &CommandFinished {
+                duration: Duration::from_secs(125),
+                exit_code: Some(1),
+            },
+            now,
+        );
+        assert!(strip.is_visible());
+        let segments = strip.segments();
+        assert_eq!(segments[0].text, "Command finished in 2m 05s (exit 1)");
+        assert!(segments[0].emphasis);
+
+        assert!(!strip.expire_toast(now));
+        assert!(strip.expire_toast(now + StatusStrip::TOAST_DURATION));
+        assert!(!strip.is_visible());
+    }
+
+    #[test]
+    fn test_command_summary() {
+        let summary = |secs, exit_code| {
+            command_summary(&CommandFinished {
+                duration: Duration::from_secs(secs),
+                exit_code,
+            })
+        };
+        assert_eq!(summary(42, None), "Command finished in 42s");
+        assert_eq!(
+            summary(3720, Some(0)),
+            "Command finished in 1h 02m (exit 0)"
+        );
+    }
+}
@@ -2,11 +2,15 @@
 //!
 //! Defines the theme structure for customizing terminal appearance.
 
-use cterm_core::color::{ColorPalette, Rgb};
+use cterm_core::color::{ColorPalette, ColorVisionDeficiency, Rgb};
 use serde::{Deserialize, Serialize};
 
+/// Delta E that [`Theme::adjusted_for_cvd`] aims for between chromatic ANSI
+/// colors, comfortably above the just-noticeable difference of about 2.3
+pub const CVD_MIN_DELTA_E: f64 = 15.0;
+
 /// Complete terminal theme
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Theme {
     /// Theme name
     pub name: String,
@@ -18,6 +22,14 @@ pub struct Theme {
     pub ui: UiColors,
     /// Cursor appearance
     pub cursor: CursorTheme,
+    /// Optional palette in Display P3 coordinates, used instead of `colors`
+    /// on wide-gamut displays
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p3_colors: Option<ColorPalette>,
+    /// Background opacity (0.0 - 1.0) this theme is meant to be used with,
+    /// overriding `appearance.opacity`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
 }
 
 impl Default for Theme {
@@ -35,6 +47,8 @@ impl Theme {
             colors: ColorPalette::default_dark(),
             ui: UiColors::dark(),
             cursor: CursorTheme::default(),
+            p3_colors: None,
+            opacity: None,
         }
     }
 
@@ -49,6 +63,8 @@ impl Theme {
                 color: Rgb::new(0, 0, 0),
                 text_color: Rgb::new(255, 255, 255),
             },
+            p3_colors: None,
+            opacity: None,
         }
     }
 
@@ -95,6 +111,8 @@ impl Theme {
                 color: Rgb::new(0xc0, 0xca, 0xf5),
                 text_color: Rgb::new(0x1a, 0x1b, 0x26),
             },
+            p3_colors: None,
+            opacity: None,
         }
     }
 
@@ -141,6 +159,8 @@ impl Theme {
                 color: Rgb::new(0xf8, 0xf8, 0xf2),
                 text_color: Rgb::new(0x28, 0x2a, 0x36),
             },
+            p3_colors: None,
+            opacity: None,
         }
     }
 
@@ -187,6 +207,34 @@ impl Theme {
                 color: Rgb::new(0xd8, 0xde, 0xe9),
                 text_color: Rgb::new(0x2e, 0x34, 0x40),
             },
+            p3_colors: None,
+            opacity: None,
+        }
+    }
+
+    /// Palette to draw with on a display of the given gamut
+    ///
+    /// Theme colors are sRGB. On a Display P3 target the hand-tuned
+    /// `p3_colors` palette wins when present; otherwise the sRGB palette is
+    /// re-expressed in P3 so it looks the same as on an sRGB display.
+    pub fn palette_for(&self, wide_gamut: bool) -> ColorPalette {
+        match (&self.p3_colors, wide_gamut) {
+            (_, false) => self.colors.clone(),
+            (Some(p3), true) => p3.clone(),
+            (None, true) => self.colors.to_display_p3(),
+        }
+    }
+
+    /// A theme derived from this one with ANSI colors that stay
+    /// distinguishable under `cvd`, named e.g. "Nord (Deuteranopia)"
+    ///
+    /// The hand-tuned P3 palette is dropped since it no longer matches.
+    pub fn adjusted_for_cvd(&self, cvd: ColorVisionDeficiency) -> Theme {
+        Theme {
+            name: format!("{} ({})", self.name, cvd.name()),
+            colors: self.colors.adjusted_for_cvd(cvd, CVD_MIN_DELTA_E),
+            p3_colors: None,
+            ..self.clone()
         }
     }
 
@@ -203,7 +251,7 @@ impl Theme {
 }
 
 /// UI element colors
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UiColors {
     /// Tab bar background
     pub tab_bar_background: Rgb,
@@ -254,7 +302,7 @@ impl UiColors {
 }
 
 /// Cursor appearance
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CursorTheme {
     /// Cursor color
     pub color: Rgb,
@@ -272,7 +320,7 @@ impl Default for CursorTheme {
 }
 
 /// Font configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FontConfig {
     /// Font family name
     pub family: String,
@@ -0,0 +1,381 @@
+//! Theme editor model
+//!
+//! Holds a theme being edited, addresses each editable color by a
+//! [`ThemeColor`] slot, and lays out a small sample of terminal output so
+//! frontends can show a live preview as colors change.
+
+use cterm_core::color::Rgb;
+
+use crate::theme::Theme;
+
+/// Names of the 16 ANSI colors, in palette order
+const ANSI_NAMES: [&str; 16] = [
+    "Black",
+    "Red",
+    "Green",
+    "Yellow",
+    "Blue",
+    "Magenta",
+    "Cyan",
+    "White",
+    "Bright Black",
+    "Bright Red",
+    "Bright Green",
+    "Bright Yellow",
+    "Bright Blue",
+    "Bright Magenta",
+    "Bright Cyan",
+    "Bright White",
+];
+
+/// An editable color of a theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThemeColor {
+    /// One of the 16 ANSI colors
+    Ansi(usize),
+    Foreground,
+    Background,
+    Cursor,
+    /// Text drawn under a block cursor
+    CursorText,
+    Selection,
+    TabBarBackground,
+    TabActiveBackground,
+    TabInactiveBackground,
+    TabActiveText,
+    TabInactiveText,
+    Border,
+    Scrollbar,
+    ScrollbarHover,
+}
+
+impl ThemeColor {
+    /// Every slot, in the order editors list them
+    pub fn all() -> Vec<ThemeColor> {
+        let mut slots = vec![
+            ThemeColor::Foreground,
+            ThemeColor::Background,
+            ThemeColor::Cursor,
+            ThemeColor::CursorText,
+            ThemeColor::Selection,
+        ];
+        slots.extend((0..16).map(ThemeColor::Ansi));
+        slots.extend([
+            ThemeColor::TabBarBackground,
+            ThemeColor::TabActiveBackground,
+            ThemeColor::TabInactiveBackground,
+            ThemeColor::TabActiveText,
+            ThemeColor::TabInactiveText,
+            ThemeColor::Border,
+            ThemeColor::Scrollbar,
+            ThemeColor::ScrollbarHover,
+        ]);
+        slots
+    }
+
+    /// Label shown next to the color
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeColor::Ansi(i) => ANSI_NAMES.get(i).copied().unwrap_or("ANSI"),
+            ThemeColor::Foreground => "Foreground",
+            ThemeColor::Background => "Background",
+            ThemeColor::Cursor => "Cursor",
+            ThemeColor::CursorText => "Cursor Text",
+            ThemeColor::Selection => "Selection",
+            ThemeColor::TabBarBackground => "Tab Bar",
+            ThemeColor::TabActiveBackground => "Active Tab",
+            ThemeColor::TabInactiveBackground => "Inactive Tab",
+            ThemeColor::TabActiveText => "Active Tab Text",
+            ThemeColor::TabInactiveText => "Inactive Tab Text",
+            ThemeColor::Border => "Border",
+            ThemeColor::Scrollbar => "Scrollbar",
+            ThemeColor::ScrollbarHover => "Scrollbar Hover",
+        }
+    }
+
+    /// Whether the slot is a UI color rather than a terminal color
+    pub fn is_ui(self) -> bool {
+        matches!(
+            self,
+            ThemeColor::TabBarBackground
+                | ThemeColor::TabActiveBackground
+                | ThemeColor::TabInactiveBackground
+                | ThemeColor::TabActiveText
+                | ThemeColor::TabInactiveText
+                | ThemeColor::Border
+                | ThemeColor::Scrollbar
+                | ThemeColor::ScrollbarHover
+        )
+    }
+}
+
+/// Width of the preview in cells; lines are padded to it with background
+pub const PREVIEW_COLUMNS: usize = 40;
+
+/// A run of preview text drawn in one pair of colors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewSpan {
+    pub text: String,
+    pub fg: Rgb,
+    pub bg: Rgb,
+}
+
+/// Color of a preview span, resolved against the theme when laid out
+#[derive(Clone, Copy)]
+enum Ink {
+    Default,
+    Ansi(usize),
+    Selection,
+    Cursor,
+}
+
+/// Sample output: a prompt, a colored `ls`, a selection and the cursor
+const PREVIEW: &[&[(Ink, &str)]] = &[
+    &[
+        (Ink::Ansi(2), "user@host"),
+        (Ink::Default, ":"),
+        (Ink::Ansi(4), "~/src"),
+        (Ink::Default, "$ ls --color"),
+    ],
+    &[
+        (Ink::Ansi(12), "docs"),
+        (Ink::Default, "  "),
+        (Ink::Ansi(10), "build.sh"),
+        (Ink::Default, "  "),
+        (Ink::Ansi(9), "src.tar"),
+        (Ink::Default, "  "),
+        (Ink::Ansi(14), "link"),
+        (Ink::Default, "  README.md"),
+    ],
+    &[
+        (Ink::Ansi(1), "error"),
+        (Ink::Default, ": "),
+        (Ink::Ansi(3), "warning"),
+        (Ink::Default, " "),
+        (Ink::Ansi(5), "note"),
+        (Ink::Default, " "),
+        (Ink::Ansi(6), "info"),
+    ],
+    &[
+        (Ink::Default, "Some "),
+        (Ink::Selection, "selected text"),
+        (Ink::Default, " here"),
+    ],
+    &[
+        (Ink::Ansi(0), "0 "),
+        (Ink::Ansi(1), "1 "),
+        (Ink::Ansi(2), "2 "),
+        (Ink::Ansi(3), "3 "),
+        (Ink::Ansi(4), "4 "),
+        (Ink::Ansi(5), "5 "),
+        (Ink::Ansi(6), "6 "),
+        (Ink::Ansi(7), "7"),
+    ],
+    &[
+        (Ink::Ansi(8), "8 "),
+        (Ink::Ansi(9), "9 "),
+        (Ink::Ansi(10), "10 "),
+        (Ink::Ansi(11), "11 "),
+        (Ink::Ansi(12), "12 "),
+        (Ink::Ansi(13), "13 "),
+        (Ink::Ansi(14), "14 "),
+        (Ink::Ansi(15), "15"),
+    ],
+    &[
+        (Ink::Ansi(2), "user@host"),
+        (Ink::Default, ":"),
+        (Ink::Ansi(4), "~/src"),
+        (Ink::Default, "$ "),
+        (Ink::Cursor, " "),
+    ],
+];
+
+/// A theme being edited
+#[derive(Debug, Clone)]
+pub struct ThemeEditor {
+    theme: Theme,
+    original: Theme,
+}
+
+impl ThemeEditor {
+    /// Start editing a copy of `theme`
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            original: theme.clone(),
+            theme,
+        }
+    }
+
+    /// The theme with all edits applied
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn name(&self) -> &str {
+        &self.theme.name
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.theme.name = name.to_string();
+    }
+
+    /// Current color of a slot
+    pub fn color(&self, slot: ThemeColor) -> Rgb {
+        Self::color_of(&self.theme, slot)
+    }
+
+    fn color_of(theme: &Theme, slot: ThemeColor) -> Rgb {
+        match slot {
+            ThemeColor::Ansi(i) => theme.colors.ansi[i],
+            ThemeColor::Foreground => theme.colors.foreground,
+            ThemeColor::Background => theme.colors.background,
+            ThemeColor::Cursor => theme.cursor.color,
+            ThemeColor::CursorText => theme.cursor.text_color,
+            ThemeColor::Selection => theme.colors.selection,
+            ThemeColor::TabBarBackground => theme.ui.tab_bar_background,
+            ThemeColor::TabActiveBackground => theme.ui.tab_active_background,
+            ThemeColor::TabInactiveBackground => theme.ui.tab_inactive_background,
+            ThemeColor::TabActiveText => theme.ui.tab_active_text,
+            ThemeColor::TabInactiveText => theme.ui.tab_inactive_text,
+            ThemeColor::Border => theme.ui.border,
+            ThemeColor::Scrollbar => theme.ui.scrollbar,
+            ThemeColor::ScrollbarHover => theme.ui.scrollbar_hover,
+        }
+    }
+
+    /// Change the color of a slot
+    ///
+    /// Any hand-tuned P3 palette is dropped once a terminal color changes,
+    /// since it would no longer match the edited sRGB palette.
+    pub fn set_color(&mut self, slot: ThemeColor, color: Rgb) {
+        let theme = &mut self.theme;
+        match slot {
+            ThemeColor::Ansi(i) => theme.colors.ansi[i] = color,
+            ThemeColor::Foreground => theme.colors.foreground = color,
+            ThemeColor::Background => theme.colors.background = color,
+            ThemeColor::Cursor => {
+                theme.cursor.color = color;
+                theme.colors.cursor = color;
+            }
+            ThemeColor::CursorText => theme.cursor.text_color = color,
+            ThemeColor::Selection => theme.colors.selection = color,
+            ThemeColor::TabBarBackground => theme.ui.tab_bar_background = color,
+            ThemeColor::TabActiveBackground => theme.ui.tab_active_background = color,
+            ThemeColor::TabInactiveBackground => theme.ui.tab_inactive_background = color,
+            ThemeColor::TabActiveText => theme.ui.tab_active_text = color,
+            ThemeColor::TabInactiveText => theme.ui.tab_inactive_text = color,
+            ThemeColor::Border => theme.ui.border = color,
+            ThemeColor::Scrollbar => theme.ui.scrollbar = color,
+            ThemeColor::ScrollbarHover => theme.ui.scrollbar_hover = color,
+        }
+        if !slot.is_ui() && slot != ThemeColor::CursorText {
+            theme.p3_colors = None;
+        }
+    }
+
+    /// Whether anything differs from the theme editing started with
+    pub fn is_modified(&self) -> bool {
+        self.theme.name != self.original.name
+            || ThemeColor::all()
+                .into_iter()
+                .any(|slot| self.color(slot) != Self::color_of(&self.original, slot))
+    }
+
+    /// Undo all edits
+    pub fn reset(&mut self) {
+        self.theme = self.original.clone();
+    }
+
+    /// Color a slot had before editing
+    pub fn original_color(&self, slot: ThemeColor) -> Rgb {
+        Self::color_of(&self.original, slot)
+    }
+
+    /// Sample terminal output in the current colors, one entry per line.
+    /// Each line ends with a background span filling it to
+    /// [`PREVIEW_COLUMNS`], so every line has the same spans in any colors.
+    pub fn preview_lines(&self) -> Vec<Vec<PreviewSpan>> {
+        let colors = &self.theme.colors;
+        PREVIEW
+            .iter()
+            .map(|line| {
+                let width: usize = line.iter().map(|(_, text)| text.chars().count()).sum();
+                let fill = " ".repeat(PREVIEW_COLUMNS.saturating_sub(width));
+                line.iter()
+                    .copied()
+                    .chain(std::iter::once((Ink::Default, fill.as_str())))
+                    .map(|(ink, text)| {
+                        let (fg, bg) = match ink {
+                            Ink::Default => (colors.foreground, colors.background),
+                            Ink::Ansi(i) => (colors.ansi[i], colors.background),
+                            Ink::Selection => (colors.foreground, colors.selection),
+                            Ink::Cursor => (self.theme.cursor.text_color, self.theme.cursor.color),
+                        };
+                        PreviewSpan {
+                            text: text.to_string(),
+                            fg,
+                            bg,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slots_cover_palette() {
+        let slots = ThemeColor::all();
+        assert_eq!(slots.len(), 5 + 16 + 8);
+        assert_eq!(ThemeColor::Ansi(9).label(), "Bright Red");
+        assert!(ThemeColor::Border.is_ui());
+        assert!(!ThemeColor::Ansi(0).is_ui());
+    }
+
+    #[test]
+    fn test_set_color() {
+        let mut editor = ThemeEditor::new(Theme::nord());
+        assert!(!editor.is_modified());
+
+        let red = Rgb::new(0xff, 0, 0);
+        editor.set_color(ThemeColor::Cursor, red);
+        assert_eq!(editor.theme().cursor.color, red);
+        assert_eq!(editor.theme().colors.cursor, red);
+        assert!(editor.is_modified());
+
+        editor.set_color(ThemeColor::Ansi(1), red);
+        assert_eq!(editor.color(ThemeColor::Ansi(1)), red);
+        assert_eq!(
+            editor.original_color(ThemeColor::Ansi(1)),
+            Theme::nord().colors.ansi[1]
+        );
+
+        editor.reset();
+        assert!(!editor.is_modified());
+        editor.set_name("My Nord");
+        assert!(editor.is_modified());
+    }
+
+    #[test]
+    fn test_preview_follows_edits() {
+        let mut editor = ThemeEditor::new(Theme::dark());
+        let blue = Rgb::new(0, 0, 0xff);
+        editor.set_color(ThemeColor::Ansi(2), blue);
+        editor.set_color(ThemeColor::Selection, blue);
+
+        let lines = editor.preview_lines();
+        assert_eq!(lines[0][0].text, "user@host");
+        assert_eq!(lines[0][0].fg, blue);
+        let selected = lines[3].iter().find(|s| s.text == "selected text");
+        assert_eq!(selected.map(|s| s.bg), Some(blue));
+        let cursor = &lines[6][4];
+        assert_eq!(cursor.bg, editor.color(ThemeColor::Cursor));
+        assert!(lines.iter().all(|line| {
+            line.iter().map(|s| s.text.chars().count()).sum::<usize>() == PREVIEW_COLUMNS
+        }));
+    }
+}
@@ -1,5 +1,7 @@
 //! Shared utility functions for UI components
 
+use crate::events::Action;
+
 /// Format a byte size for human-readable display
 ///
 /// Returns a string like "1.5 KB", "2.3 MB", "1.0 GB", or "123 bytes"
@@ -19,6 +21,167 @@ pub fn format_size(bytes: usize) -> String {
     }
 }
 
+/// Tab title for a working directory when the shell set none: the last
+/// path component, or "/" for the root
+pub fn dir_title(dir: &str) -> String {
+    dir.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("/")
+        .to_string()
+}
+
+/// Magnification of the rendered terminal surface
+///
+/// Unlike text zoom, which changes the font size and therefore the number of
+/// rows and columns, magnifying scales the rendered surface and leaves the grid
+/// (and the PTY size) untouched. Only part of the grid fits in the view while
+/// magnified, so the view pans to follow a focus rectangle (usually the cursor).
+///
+/// Coordinates are in the frontend's drawing units: "surface" is the unscaled
+/// terminal drawing, "view" is the widget the magnified surface is shown in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Magnifier {
+    factor: f64,
+    pan_x: f64,
+    pan_y: f64,
+}
+
+impl Magnifier {
+    /// Smallest magnification (no magnification)
+    pub const MIN: f64 = 1.0;
+    /// Largest magnification
+    pub const MAX: f64 = 4.0;
+    /// Magnification change per step
+    pub const STEP: f64 = 0.25;
+
+    pub fn new() -> Self {
+        Self {
+            factor: Self::MIN,
+            pan_x: 0.0,
+            pan_y: 0.0,
+        }
+    }
+
+    /// Current scale factor
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+
+    /// Whether the surface is currently magnified
+    pub fn is_active(&self) -> bool {
+        self.factor > Self::MIN
+    }
+
+    /// Current pan offset in surface coordinates
+    pub fn pan(&self) -> (f64, f64) {
+        (self.pan_x, self.pan_y)
+    }
+
+    /// Magnify one step; returns false if already at the maximum
+    pub fn magnify_in(&mut self) -> bool {
+        self.set_factor(self.factor + Self::STEP)
+    }
+
+    /// Reduce magnification one step; returns false if already unmagnified
+    pub fn magnify_out(&mut self) -> bool {
+        self.set_factor(self.factor - Self::STEP)
+    }
+
+    /// Return to 1:1; returns false if not magnified
+    pub fn reset(&mut self) -> bool {
+        self.set_factor(Self::MIN)
+    }
+
+    fn set_factor(&mut self, factor: f64) -> bool {
+        let factor = factor.clamp(Self::MIN, Self::MAX);
+        if factor == self.factor {
+            return false;
+        }
+        self.factor = factor;
+        if !self.is_active() {
+            self.pan_x = 0.0;
+            self.pan_y = 0.0;
+        }
+        true
+    }
+
+    /// Pan the view so the focus rectangle stays visible
+    ///
+    /// `focus` is `(x, y, width, height)` and `view` is `(width, height)`; the
+    /// surface is assumed to be the same size as the view. The pan only moves
+    /// when the focus would otherwise leave the visible region.
+    pub fn follow(&mut self, focus: (f64, f64, f64, f64), view: (f64, f64)) {
+        let (fx, fy, fw, fh) = focus;
+        let (vw, vh) = view;
+        self.pan_x = Self::follow_axis(self.pan_x, fx, fw, vw, self.factor);
+        self.pan_y = Self::follow_axis(self.pan_y, fy, fh, vh, self.factor);
+    }
+
+    fn follow_axis(pan: f64, pos: f64, len: f64, view: f64, factor: f64) -> f64 {
+        let visible = view / factor;
+        let mut pan = pan;
+        if pos < pan {
+            pan = pos;
+        } else if pos + len > pan + visible {
+            pan = pos + len - visible;
+        }
+        pan.clamp(0.0, (view - visible).max(0.0))
+    }
+
+    /// Map a point in view coordinates to surface coordinates
+    pub fn view_to_surface(&self, x: f64, y: f64) -> (f64, f64) {
+        (x / self.factor + self.pan_x, y / self.factor + self.pan_y)
+    }
+}
+
+impl Default for Magnifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Turns a continuous pinch gesture into discrete font zoom steps
+///
+/// Trackpads report pinches as a stream of small magnification deltas
+/// (positive when the fingers spread). The deltas are accumulated and every
+/// [`PinchZoom::STEP`] of travel becomes one [`Action::ZoomIn`] or
+/// [`Action::ZoomOut`], so a pinch behaves like pressing the zoom shortcut.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PinchZoom {
+    accumulated: f64,
+}
+
+impl PinchZoom {
+    /// Magnification travel per zoom step
+    pub const STEP: f64 = 0.08;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one magnification delta; returns the zoom action to perform, if
+    /// the gesture has travelled far enough since the last one
+    pub fn update(&mut self, delta: f64) -> Option<Action> {
+        self.accumulated += delta;
+        if self.accumulated >= Self::STEP {
+            self.accumulated -= Self::STEP;
+            Some(Action::ZoomIn)
+        } else if self.accumulated <= -Self::STEP {
+            self.accumulated += Self::STEP;
+            Some(Action::ZoomOut)
+        } else {
+            None
+        }
+    }
+
+    /// Forget any partial step when the gesture ends
+    pub fn end(&mut self) {
+        self.accumulated = 0.0;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,4 +197,64 @@ mod tests {
         assert_eq!(format_size(1572864), "1.5 MB");
         assert_eq!(format_size(1073741824), "1.0 GB");
     }
+
+    #[test]
+    fn test_dir_title() {
+        assert_eq!(dir_title("/home/me/src"), "src");
+        assert_eq!(dir_title("/home/me/src/"), "src");
+        assert_eq!(dir_title("/"), "/");
+    }
+
+    #[test]
+    fn test_magnifier_steps() {
+        let mut m = Magnifier::new();
+        assert!(!m.is_active());
+        assert!(!m.magnify_out());
+        assert!(m.magnify_in());
+        assert_eq!(m.factor(), 1.25);
+        for _ in 0..20 {
+            m.magnify_in();
+        }
+        assert_eq!(m.factor(), Magnifier::MAX);
+        assert!(m.reset());
+        assert_eq!(m.factor(), 1.0);
+    }
+
+    #[test]
+    fn test_magnifier_follow_and_mapping() {
+        let mut m = Magnifier::new();
+        m.magnify_in();
+        m.magnify_in();
+        m.magnify_in();
+        m.magnify_in(); // 2x: half of the 800x600 surface is visible
+
+        // Focus near the top-left needs no pan
+        m.follow((10.0, 10.0, 8.0, 16.0), (800.0, 600.0));
+        assert_eq!(m.pan(), (0.0, 0.0));
+
+        // Focus at the bottom-right pans just enough, clamped to the surface
+        m.follow((792.0, 584.0, 8.0, 16.0), (800.0, 600.0));
+        assert_eq!(m.pan(), (400.0, 300.0));
+        assert_eq!(m.view_to_surface(0.0, 0.0), (400.0, 300.0));
+        assert_eq!(m.view_to_surface(800.0, 600.0), (800.0, 600.0));
+
+        // Resetting drops the pan
+        m.reset();
+        assert_eq!(m.pan(), (0.0, 0.0));
+        assert_eq!(m.view_to_surface(100.0, 50.0), (100.0, 50.0));
+    }
+
+    #[test]
+    fn test_pinch_zoom_steps() {
+        let mut pinch = PinchZoom::new();
+        assert_eq!(pinch.update(0.05), None);
+        assert_eq!(pinch.update(0.05), Some(Action::ZoomIn));
+        assert_eq!(pinch.update(-0.03), None);
+        assert_eq!(pinch.update(-0.1), Some(Action::ZoomOut));
+
+        // A partial step does not carry over into the next gesture
+        pinch.update(0.07);
+        pinch.end();
+        assert_eq!(pinch.update(0.02), None);
+    }
 }
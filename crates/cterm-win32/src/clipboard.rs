@@ -6,22 +6,47 @@ use std::ffi::OsStr;
 use std::os::windows::ffi::OsStrExt;
 use std::ptr;
 
+use winapi::shared::minwindef::UINT;
 use winapi::um::winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
 use winapi::um::winuser::{
-    CloseClipboard, EmptyClipboard, GetClipboardData, OpenClipboard, SetClipboardData,
-    CF_UNICODETEXT,
+    CloseClipboard, EmptyClipboard, GetClipboardData, OpenClipboard, RegisterClipboardFormatW,
+    SetClipboardData, CF_UNICODETEXT,
 };
 
 /// Copy text to the clipboard
 pub fn copy_to_clipboard(text: &str) -> Result<(), ClipboardError> {
-    // Convert to wide string (UTF-16)
-    let wide: Vec<u16> = OsStr::new(text)
+    set_clipboard(&[(CF_UNICODETEXT, &wide_bytes(text))])
+}
+
+/// Copy text to the clipboard along with a styled RTF version of it
+///
+/// Word, Outlook and other rich text editors paste the RTF with its colors;
+/// plain text editors get the text.
+pub fn copy_rich_text(text: &str, rtf: &str) -> Result<(), ClipboardError> {
+    let name: Vec<u16> = OsStr::new("Rich Text Format")
         .encode_wide()
-        .chain(std::iter::once(0)) // null terminator
+        .chain(std::iter::once(0))
         .collect();
+    let format = unsafe { RegisterClipboardFormatW(name.as_ptr()) };
+    if format == 0 {
+        return copy_to_clipboard(text);
+    }
+    let mut rtf = rtf.as_bytes().to_vec();
+    rtf.push(0);
+    set_clipboard(&[(CF_UNICODETEXT, &wide_bytes(text)), (format, &rtf)])
+}
 
-    let size = wide.len() * std::mem::size_of::<u16>();
+/// `text` as null-terminated UTF-16, in bytes
+fn wide_bytes(text: &str) -> Vec<u8> {
+    OsStr::new(text)
+        .encode_wide()
+        .chain(std::iter::once(0)) // null terminator
+        .flat_map(u16::to_ne_bytes)
+        .collect()
+}
 
+/// Replace the clipboard contents with `data` in each of its formats
+fn set_clipboard(data: &[(UINT, &[u8])]) -> Result<(), ClipboardError> {
     unsafe {
         // Open clipboard
         if OpenClipboard(ptr::null_mut()) == 0 {
@@ -34,35 +59,38 @@ pub fn copy_to_clipboard(text: &str) -> Result<(), ClipboardError> {
             return Err(ClipboardError::EmptyFailed);
         }
 
-        // Allocate global memory
-        let hglobal = GlobalAlloc(GMEM_MOVEABLE, size);
-        if hglobal.is_null() {
-            CloseClipboard();
-            return Err(ClipboardError::AllocFailed);
-        }
-
-        // Lock memory and copy data
-        let ptr = GlobalLock(hglobal) as *mut u16;
-        if ptr.is_null() {
-            GlobalFree(hglobal);
-            CloseClipboard();
-            return Err(ClipboardError::LockFailed);
-        }
-
-        ptr::copy_nonoverlapping(wide.as_ptr(), ptr, wide.len());
-        GlobalUnlock(hglobal);
-
-        // Set clipboard data
-        if SetClipboardData(CF_UNICODETEXT, hglobal).is_null() {
-            GlobalFree(hglobal);
-            CloseClipboard();
-            return Err(ClipboardError::SetDataFailed);
-        }
+        let result = data
+            .iter()
+            .try_for_each(|&(format, bytes)| set_clipboard_data(format, bytes));
 
         // Close clipboard (data is now owned by clipboard)
         CloseClipboard();
+        result
+    }
+}
+
+/// Put `bytes` on the open clipboard in `format`
+unsafe fn set_clipboard_data(format: UINT, bytes: &[u8]) -> Result<(), ClipboardError> {
+    // Allocate global memory
+    let hglobal = GlobalAlloc(GMEM_MOVEABLE, bytes.len());
+    if hglobal.is_null() {
+        return Err(ClipboardError::AllocFailed);
     }
 
+    // Lock memory and copy data
+    let ptr = GlobalLock(hglobal) as *mut u8;
+    if ptr.is_null() {
+        GlobalFree(hglobal);
+        return Err(ClipboardError::LockFailed);
+    }
+    ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+    GlobalUnlock(hglobal);
+
+    // Set clipboard data
+    if SetClipboardData(format, hglobal).is_null() {
+        GlobalFree(hglobal);
+        return Err(ClipboardError::SetDataFailed);
+    }
     Ok(())
 }
 
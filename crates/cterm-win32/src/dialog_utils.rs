@@ -92,6 +92,14 @@ pub fn create_edit(parent: HWND, id: i32, x: i32, y: i32, w: i32, h: i32) -> HWN
     hwnd
 }
 
+/// Set the placeholder text shown in an empty edit control
+pub fn set_cue_banner(hwnd: HWND, text: &str) {
+    let wide = to_wide(text);
+    unsafe {
+        SendMessageW(hwnd, EM_SETCUEBANNER, 0, wide.as_ptr() as LPARAM);
+    }
+}
+
 /// Create an edit control with initial text
 pub fn create_edit_with_text(
     parent: HWND,
@@ -572,6 +580,28 @@ pub fn add_tab(tab_ctrl: HWND, index: i32, text: &str) {
     }
 }
 
+/// Change the text of a tab
+pub fn set_tab_text(tab_ctrl: HWND, index: i32, text: &str) {
+    let wide = to_wide(text);
+    let mut item = TCITEMW {
+        mask: TCIF_TEXT,
+        dwState: 0,
+        dwStateMask: 0,
+        pszText: wide.as_ptr() as *mut _,
+        cchTextMax: 0,
+        iImage: -1,
+        lParam: 0,
+    };
+    unsafe {
+        SendMessageW(
+            tab_ctrl,
+            TCM_SETITEMW,
+            index as WPARAM,
+            &mut item as *mut _ as LPARAM,
+        );
+    }
+}
+
 /// Get the selected tab index
 pub fn get_selected_tab(tab_ctrl: HWND) -> i32 {
     unsafe { SendMessageW(tab_ctrl, TCM_GETCURSEL, 0, 0) as i32 }
@@ -70,6 +70,18 @@ pub fn show_confirm(parent: HWND, title: &str, message: &str) -> bool {
     show_message(parent, title, message, MB_YESNO | MB_ICONQUESTION) == IDYES
 }
 
+/// Ask whether to start in safe mode after a crash during startup
+pub fn confirm_safe_mode() -> bool {
+    let message = cterm_app::safe_mode::recovery_message();
+    let flags = MB_YESNO | MB_ICONWARNING | MB_SETFOREGROUND;
+    show_message(
+        ptr::null_mut(),
+        cterm_app::safe_mode::RECOVERY_TITLE,
+        &message,
+        flags,
+    ) == IDYES
+}
+
 /// Result from an input dialog
 pub enum InputDialogResult {
     Ok(String),
@@ -443,6 +455,19 @@ pub fn show_set_title_dialog(parent: HWND, current_title: &str) -> Option<String
     }
 }
 
+/// Show the watch expression dialog
+pub fn show_watch_dialog(parent: HWND, current_pattern: &str) -> Option<String> {
+    match show_input_dialog(
+        parent,
+        "Watch Output",
+        "Count new output lines matching this regular expression (empty to stop):",
+        current_pattern,
+    ) {
+        InputDialogResult::Ok(pattern) => Some(pattern),
+        InputDialogResult::Cancel => None,
+    }
+}
+
 /// Find dialog options
 #[derive(Debug, Clone)]
 pub struct FindOptions {
@@ -525,6 +550,122 @@ pub fn show_save_file_dialog(
     }
 }
 
+/// Open file dialog result
+pub fn show_open_file_dialog(
+    parent: HWND,
+    title: &str,
+    filter: &str,
+) -> Option<std::path::PathBuf> {
+    use winapi::um::commdlg::{
+        GetOpenFileNameW, OFN_FILEMUSTEXIST, OFN_PATHMUSTEXIST, OPENFILENAMEW,
+    };
+
+    let title = to_wide(title);
+    let filter = to_wide(filter);
+    let mut filename = [0u16; 260];
+
+    let mut ofn = OPENFILENAMEW {
+        lStructSize: std::mem::size_of::<OPENFILENAMEW>() as u32,
+        hwndOwner: parent,
+        hInstance: ptr::null_mut(),
+        lpstrFilter: filter.as_ptr(),
+        lpstrCustomFilter: ptr::null_mut(),
+        nMaxCustFilter: 0,
+        nFilterIndex: 1,
+        lpstrFile: filename.as_mut_ptr(),
+        nMaxFile: filename.len() as u32,
+        lpstrFileTitle: ptr::null_mut(),
+        nMaxFileTitle: 0,
+        lpstrInitialDir: ptr::null(),
+        lpstrTitle: title.as_ptr(),
+        Flags: OFN_FILEMUSTEXIST | OFN_PATHMUSTEXIST,
+        nFileOffset: 0,
+        nFileExtension: 0,
+        lpstrDefExt: ptr::null(),
+        lCustData: 0,
+        lpfnHook: None,
+        lpTemplateName: ptr::null(),
+        pvReserved: ptr::null_mut(),
+        dwReserved: 0,
+        FlagsEx: 0,
+    };
+
+    unsafe {
+        if GetOpenFileNameW(&mut ofn) != 0 {
+            let len = filename
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(filename.len());
+            let path = String::from_utf16_lossy(&filename[..len]);
+            Some(std::path::PathBuf::from(path))
+        } else {
+            None
+        }
+    }
+}
+
+/// Open dialog for the files a remote program asked for (`rz` or
+/// `kermit -r`)
+///
+/// Returns the selected paths, empty if cancelled.
+pub fn show_open_files_dialog(parent: HWND, title: &str) -> Vec<std::path::PathBuf> {
+    use winapi::um::commdlg::{
+        GetOpenFileNameW, OFN_ALLOWMULTISELECT, OFN_EXPLORER, OFN_FILEMUSTEXIST, OFN_PATHMUSTEXIST,
+        OPENFILENAMEW,
+    };
+
+    let title = to_wide(title);
+    let filter = to_wide("All Files (*.*)\0*.*\0\0");
+    // Room for many names: the directory, then each file name
+    let mut buffer = vec![0u16; 32 * 1024];
+
+    let mut ofn = OPENFILENAMEW {
+        lStructSize: std::mem::size_of::<OPENFILENAMEW>() as u32,
+        hwndOwner: parent,
+        hInstance: ptr::null_mut(),
+        lpstrFilter: filter.as_ptr(),
+        lpstrCustomFilter: ptr::null_mut(),
+        nMaxCustFilter: 0,
+        nFilterIndex: 1,
+        lpstrFile: buffer.as_mut_ptr(),
+        nMaxFile: buffer.len() as u32,
+        lpstrFileTitle: ptr::null_mut(),
+        nMaxFileTitle: 0,
+        lpstrInitialDir: ptr::null(),
+        lpstrTitle: title.as_ptr(),
+        Flags: OFN_FILEMUSTEXIST | OFN_PATHMUSTEXIST | OFN_ALLOWMULTISELECT | OFN_EXPLORER,
+        nFileOffset: 0,
+        nFileExtension: 0,
+        lpstrDefExt: ptr::null(),
+        lCustData: 0,
+        lpfnHook: None,
+        lpTemplateName: ptr::null(),
+        pvReserved: ptr::null_mut(),
+        dwReserved: 0,
+        FlagsEx: 0,
+    };
+
+    if unsafe { GetOpenFileNameW(&mut ofn) } == 0 {
+        return Vec::new();
+    }
+
+    // One file is a full path; several are the directory followed by the
+    // names, each null-terminated, with an empty string at the end
+    let parts: Vec<String> = buffer
+        .split(|&c| c == 0)
+        .take_while(|part| !part.is_empty())
+        .map(String::from_utf16_lossy)
+        .collect();
+    match parts.as_slice() {
+        [] => Vec::new(),
+        [path] => vec![std::path::PathBuf::from(path)],
+        [dir, names @ ..] => names
+            .iter()
+            .map(|name| std::path::Path::new(dir).join(name))
+            .collect(),
+    }
+}
+
 /// Show a save dialog for file transfer (wrapper for show_save_file_dialog)
 ///
 /// Takes a windows crate HWND and converts it for winapi
@@ -566,6 +707,34 @@ pub fn show_input_dialog_win(
     }
 }
 
+/// Characters of a paste shown in its confirmation message box, which
+/// doesn't scroll
+const PASTE_PREVIEW_CHARS: usize = 500;
+
+/// Ask before pasting `text` into a program that hasn't enabled bracketed
+/// paste, showing its start with line breaks and control characters set off
+/// in «»; returns true if the user chose to paste
+pub fn confirm_paste(
+    hwnd: windows::Win32::Foundation::HWND,
+    text: &str,
+    warning: &cterm_app::paste::PasteWarning,
+) -> bool {
+    let preview: String = cterm_app::paste::paste_preview(text, PASTE_PREVIEW_CHARS)
+        .into_iter()
+        .map(|(run, highlight)| {
+            if highlight {
+                format!("«{}»", run)
+            } else {
+                run
+            }
+        })
+        .collect();
+    let message = format!("{}\n\n{}", warning.message(), preview);
+    // Cancel is the default, so Enter doesn't paste by accident
+    let flags = MB_OKCANCEL | MB_ICONWARNING | MB_DEFBUTTON2;
+    show_message(hwnd.0 as *mut _, "Paste Text?", &message, flags) == IDOK
+}
+
 /// Show a set color dialog (wrapper that takes windows crate HWND)
 pub fn show_set_color_dialog_win(hwnd: windows::Win32::Foundation::HWND) -> Option<Option<String>> {
     let parent = hwnd.0 as *mut _;
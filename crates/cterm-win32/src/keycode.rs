@@ -5,6 +5,42 @@
 use cterm_ui::events::{KeyCode, Modifiers};
 use winapi::um::winuser;
 
+/// Convert a Windows virtual key code to a terminal key for the kitty
+/// keyboard protocol. Letters and digits map to their unshifted character.
+pub fn vk_to_terminal_key(vk: u16) -> Option<cterm_core::term::Key> {
+    use cterm_core::term::Key;
+    Some(match vk as i32 {
+        0x41..=0x5A => Key::Char((vk as u8).to_ascii_lowercase() as char),
+        0x30..=0x39 => Key::Char(vk as u8 as char),
+        winuser::VK_SPACE => Key::Char(' '),
+        winuser::VK_RETURN => Key::Enter,
+        winuser::VK_TAB => Key::Tab,
+        winuser::VK_BACK => Key::Backspace,
+        winuser::VK_ESCAPE => Key::Escape,
+        winuser::VK_UP => Key::Up,
+        winuser::VK_DOWN => Key::Down,
+        winuser::VK_LEFT => Key::Left,
+        winuser::VK_RIGHT => Key::Right,
+        winuser::VK_HOME => Key::Home,
+        winuser::VK_END => Key::End,
+        winuser::VK_PRIOR => Key::PageUp,
+        winuser::VK_NEXT => Key::PageDown,
+        winuser::VK_INSERT => Key::Insert,
+        winuser::VK_DELETE => Key::Delete,
+        winuser::VK_F1..=winuser::VK_F24 => Key::F((vk as i32 - winuser::VK_F1 + 1) as u8),
+        winuser::VK_NUMPAD0..=winuser::VK_NUMPAD9 => {
+            Key::Keypad((b'0' + (vk as i32 - winuser::VK_NUMPAD0) as u8) as char)
+        }
+        winuser::VK_DECIMAL => Key::Keypad('.'),
+        winuser::VK_SEPARATOR => Key::Keypad(','),
+        winuser::VK_ADD => Key::Keypad('+'),
+        winuser::VK_SUBTRACT => Key::Keypad('-'),
+        winuser::VK_MULTIPLY => Key::Keypad('*'),
+        winuser::VK_DIVIDE => Key::Keypad('/'),
+        _ => return None,
+    })
+}
+
 /// Convert a Windows virtual key code to our KeyCode
 pub fn vk_to_keycode(vk: u16) -> Option<KeyCode> {
     Some(match vk as i32 {
@@ -289,6 +325,67 @@ pub fn vk_to_terminal_seq(
     }
 }
 
+/// What a key produces under the active keyboard layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyText {
+    /// The key doesn't produce text (navigation, function keys, ...)
+    None,
+    /// A dead key: it combines with the next key press into one character
+    Dead,
+    /// The text the key types, e.g. "@" for AltGr+Q on a German layout
+    Text(String),
+}
+
+/// Ask the keyboard layout what `vk` types with the current modifier state
+///
+/// This goes through `ToUnicodeEx` so AltGr combinations and dead keys on
+/// international layouts are resolved the same way `TranslateMessage` does.
+/// The layout's dead-key state is left untouched, so the `WM_CHAR` that
+/// follows still carries the composed character.
+pub fn key_text(vk: u16, scancode: u16) -> KeyText {
+    // Don't modify the keyboard state (Windows 10 1607 and later)
+    const TO_UNICODE_NO_STATE_CHANGE: u32 = 0x4;
+
+    let mut state = [0u8; 256];
+    let mut buf = [0u16; 8];
+    let rc = unsafe {
+        if winuser::GetKeyboardState(state.as_mut_ptr()) == 0 {
+            return KeyText::None;
+        }
+        winuser::ToUnicodeEx(
+            vk as u32,
+            scancode as u32,
+            state.as_ptr(),
+            buf.as_mut_ptr(),
+            buf.len() as i32,
+            TO_UNICODE_NO_STATE_CHANGE,
+            winuser::GetKeyboardLayout(0),
+        )
+    };
+    classify_to_unicode(rc, &buf)
+}
+
+/// Interpret the return value and buffer of `ToUnicodeEx`
+fn classify_to_unicode(rc: i32, buf: &[u16]) -> KeyText {
+    match rc {
+        rc if rc < 0 => KeyText::Dead,
+        0 => KeyText::None,
+        rc => {
+            let len = (rc as usize).min(buf.len());
+            KeyText::Text(String::from_utf16_lossy(&buf[..len]))
+        }
+    }
+}
+
+/// Whether a key press with `modifiers` is AltGr producing `text`
+///
+/// Windows reports AltGr as Ctrl+Alt. When the layout maps the chord to a
+/// printable character it is text input, not a Ctrl/Alt shortcut.
+pub fn is_altgr_text(modifiers: Modifiers, text: &KeyText) -> bool {
+    modifiers.contains(Modifiers::CTRL | Modifiers::ALT)
+        && matches!(text, KeyText::Text(s) if !s.is_empty() && !s.chars().any(char::is_control))
+}
+
 /// Check if a virtual key is a modifier key
 pub fn is_modifier_key(vk: u16) -> bool {
     matches!(
@@ -323,6 +420,36 @@ mod tests {
         assert_eq!(vk_to_keycode(0x39), Some(KeyCode::Key9));
     }
 
+    #[test]
+    fn test_vk_to_terminal_key_keypad() {
+        use cterm_core::term::Key;
+        assert_eq!(
+            vk_to_terminal_key(winuser::VK_NUMPAD7 as u16),
+            Some(Key::Keypad('7'))
+        );
+        assert_eq!(
+            vk_to_terminal_key(winuser::VK_DIVIDE as u16),
+            Some(Key::Keypad('/'))
+        );
+    }
+
+    #[test]
+    fn test_classify_to_unicode() {
+        let buf = [0x20AC, 0, 0, 0]; // €
+        assert_eq!(classify_to_unicode(-1, &buf), KeyText::Dead);
+        assert_eq!(classify_to_unicode(0, &buf), KeyText::None);
+        assert_eq!(classify_to_unicode(1, &buf), KeyText::Text("€".into()));
+    }
+
+    #[test]
+    fn test_is_altgr_text() {
+        let altgr = Modifiers::CTRL | Modifiers::ALT;
+        assert!(is_altgr_text(altgr, &KeyText::Text("@".into())));
+        assert!(!is_altgr_text(altgr, &KeyText::Text("\x11".into())));
+        assert!(!is_altgr_text(altgr, &KeyText::None));
+        assert!(!is_altgr_text(Modifiers::CTRL, &KeyText::Text("q".into())));
+    }
+
     #[test]
     fn test_is_modifier_key() {
         assert!(is_modifier_key(winuser::VK_SHIFT as u16));
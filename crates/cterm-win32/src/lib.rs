@@ -19,6 +19,7 @@ pub mod notification_bar;
 pub mod preferences_dialog;
 pub mod quick_open;
 pub mod remotes_dialog;
+pub mod serial_dialog;
 pub mod session_dialog;
 pub mod ssh_prompt;
 pub mod tab_bar;
@@ -63,9 +64,26 @@ pub struct Args {
     #[arg(short = 't', long = "title")]
     pub title: Option<String>,
 
+    /// Play back an asciicast (.cast) or ttyrec recording in a read-only tab
+    #[arg(long, value_name = "FILE")]
+    pub play: Option<PathBuf>,
+
+    /// Open the tabs and panes of a `[[workspaces]]` entry, in the running
+    /// cterm if there is one
+    #[arg(long, value_name = "NAME")]
+    pub workspace: Option<String>,
+
+    /// Start with the default settings and theme, ignoring the configuration
+    #[arg(long)]
+    pub safe_mode: bool,
+
     /// Path to upgrade state file (internal use)
     #[arg(long, hide = true)]
     pub upgrade_state: Option<String>,
+
+    /// Run a subcommand instead of opening a window
+    #[command(subcommand)]
+    pub subcommand: Option<cterm_app::cli::Command>,
 }
 
 /// Global application arguments (accessible from window creation)
@@ -79,7 +97,19 @@ pub fn get_args() -> &'static Args {
 /// Run the Windows application
 pub fn run() {
     // Parse command-line arguments
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    // Subcommands like `cterm config get` run without a window
+    if let Some(command) = args.subcommand.take() {
+        std::process::exit(cterm_app::cli::run(command));
+    }
+
+    // A running cterm opens the workspace in its own window
+    if let Some(ref name) = args.workspace {
+        if cterm_app::workspace::open_in_running(name) {
+            std::process::exit(0);
+        }
+    }
 
     // Initialize logging
     cterm_app::log_capture::init();
@@ -96,6 +126,8 @@ pub fn run() {
         std::process::exit(exit_code);
     }
 
+    let safe_mode = args.safe_mode;
+
     // Store args for later access
     let _ = APP_ARGS.set(args);
 
@@ -105,6 +137,12 @@ pub fn run() {
     // Initialize common controls for dialogs
     dialog_utils::init_common_controls();
 
+    // Offer safe mode when the previous launch crashed before showing a window
+    let crashed = cterm_app::safe_mode::begin_startup();
+    if safe_mode || (crashed && dialogs::confirm_safe_mode()) {
+        cterm_app::safe_mode::enable();
+    }
+
     // Load configuration
     let config = match cterm_app::load_config() {
         Ok(c) => c,
@@ -113,6 +151,8 @@ pub fn run() {
             cterm_app::Config::default()
         }
     };
+    cterm_app::usage_stats::configure(&config);
+    cterm_app::clipboard_history::configure(&config);
 
     // Load theme
     let theme = load_theme(&config);
@@ -141,7 +181,19 @@ fn run_main_loop(
     window::register_window_class()?;
 
     // Create main window
-    let _hwnd = window::create_window(config, theme)?;
+    let hwnd = window::create_window(config, theme)?;
+
+    // The first window is up, so this launch did not crash during startup
+    cterm_app::safe_mode::finish_startup();
+
+    // `cterm new-tab` and `cterm new-window` from a shell
+    let hwnd_value = hwnd.0 as usize;
+    let result = cterm_app::control::listen(move |request| {
+        window::post_control_request(hwnd_value, request);
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to open the control pipe: {}", e);
+    }
 
     // Message loop
     let mut msg = MSG::default();
@@ -181,7 +233,9 @@ mod tests {
             fullscreen: false,
             maximized: false,
             title: None,
+            safe_mode: false,
             upgrade_state: None,
+            subcommand: None,
         };
         assert!(!args.fullscreen);
         assert!(args.upgrade_state.is_none());
@@ -1,6 +1,7 @@
 //! Log viewer window for cterm
 //!
-//! Provides an in-app log viewer window with refresh capability.
+//! Provides an in-app log viewer window with refresh capability. The same
+//! window shows the usage statistics.
 
 use std::ptr;
 
@@ -20,6 +21,26 @@ const IDC_LOG_EDIT: i32 = 1001;
 const IDC_REFRESH_BTN: i32 = 1002;
 const IDC_CLOSE_BTN: i32 = 1003;
 
+/// What a viewer window shows
+struct TextSource {
+    title: &'static str,
+    text: fn() -> String,
+    /// Start at the end, where the newest lines are
+    scroll_to_end: bool,
+}
+
+static LOGS: TextSource = TextSource {
+    title: "Debug Log",
+    text: cterm_app::log_capture::get_logs_formatted,
+    scroll_to_end: true,
+};
+
+static USAGE_STATS: TextSource = TextSource {
+    title: "Usage Statistics",
+    text: cterm_app::usage_stats::report,
+    scroll_to_end: false,
+};
+
 /// Register the log viewer window class
 pub fn register_window_class() -> bool {
     let class_name = to_wide(LOG_VIEWER_CLASS);
@@ -44,6 +65,15 @@ pub fn register_window_class() -> bool {
 
 /// Show the log viewer window
 pub fn show_log_viewer(parent: HWND) {
+    show_viewer(parent, &LOGS);
+}
+
+/// Show the usage statistics window
+pub fn show_usage_stats(parent: HWND) {
+    show_viewer(parent, &USAGE_STATS);
+}
+
+fn show_viewer(parent: HWND, source: &'static TextSource) {
     // Register window class (only once)
     static REGISTERED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
     let _ = REGISTERED.get_or_init(register_window_class);
@@ -70,7 +100,7 @@ pub fn show_log_viewer(parent: HWND) {
     };
 
     let class_name = to_wide(LOG_VIEWER_CLASS);
-    let title = to_wide("Debug Log");
+    let title = to_wide(source.title);
 
     let hwnd = unsafe {
         CreateWindowExW(
@@ -85,7 +115,7 @@ pub fn show_log_viewer(parent: HWND) {
             parent,
             ptr::null_mut(),
             ptr::null_mut(),
-            ptr::null_mut(),
+            source as *const TextSource as *mut _,
         )
     };
 
@@ -106,6 +136,9 @@ unsafe extern "system" fn window_proc(
 ) -> LRESULT {
     match msg {
         WM_CREATE => {
+            // The source is a static, so the pointer stays valid
+            let create = &*(lparam as *const CREATESTRUCTW);
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, create.lpCreateParams as isize);
             init_window(hwnd);
             0
         }
@@ -282,20 +315,21 @@ unsafe fn handle_resize(hwnd: HWND) {
 /// Refresh the log display
 unsafe fn refresh_logs(hwnd: HWND) {
     let edit = GetDlgItem(hwnd, IDC_LOG_EDIT);
-    if edit.is_null() {
+    let source = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const TextSource;
+    if edit.is_null() || source.is_null() {
         return;
     }
-
-    // Get formatted logs
-    let logs = cterm_app::log_capture::get_logs_formatted();
+    let source = &*source;
 
     // Convert newlines to CRLF for Windows edit control
-    let logs = logs.replace('\n', "\r\n");
+    let text = (source.text)().replace('\n', "\r\n");
 
     // Set text
-    set_edit_text(edit, &logs);
+    set_edit_text(edit, &text);
 
     // Scroll to bottom
-    let line_count = SendMessageW(edit, EM_GETLINECOUNT as u32, 0, 0);
-    SendMessageW(edit, EM_LINESCROLL as u32, 0, line_count as LPARAM);
+    if source.scroll_to_end {
+        let line_count = SendMessageW(edit, EM_GETLINECOUNT as u32, 0, 0);
+        SendMessageW(edit, EM_LINESCROLL as u32, 0, line_count as LPARAM);
+    }
 }
@@ -4,6 +4,7 @@
 
 use std::ptr;
 
+use cterm_app::config::{ProfileConfig, WorkspaceConfig};
 use winapi::shared::windef::HMENU;
 use winapi::um::winuser::{
     AppendMenuW, CreateMenu, CreatePopupMenu, SetMenu, MF_POPUP, MF_SEPARATOR, MF_STRING,
@@ -19,20 +20,31 @@ pub enum MenuAction {
     QuickOpen = 1007,
     CloseTab = 1003,
     CloseOtherTabs = 1004,
+    ReopenClosedTab = 1008,
     DockerPicker = 1005,
+    SerialConnect = 1012,
     Quit = 1006,
+    SaveBuffer = 1009,
+    Record = 1010,
+    PlayRecording = 1011,
 
     // Edit menu
     Copy = 2001,
     CopyHtml = 2002,
     Paste = 2003,
     SelectAll = 2004,
+    SelectCommandOutput = 2005,
+    PasteHistory = 2006,
 
     // View menu
     ZoomIn = 2501,
     ZoomOut = 2502,
     ZoomReset = 2503,
     Fullscreen = 2504,
+    MagnifyIn = 2505,
+    MagnifyOut = 2506,
+    MagnifyReset = 2507,
+    PresentationMode = 2508,
 
     // Terminal menu
     SetTitle = 3001,
@@ -44,6 +56,12 @@ pub enum MenuAction {
     SendSignalKill = 3007,
     SendSignalHup = 3008,
     SendSignalTerm = 3009,
+    AsciiArtSafe = 3010,
+    Hints = 3011,
+    ReadOnly = 3012,
+    WatchOutput = 3013,
+    PreviousPrompt = 3014,
+    NextPrompt = 3015,
 
     // Tabs menu
     PrevTab = 4001,
@@ -64,6 +82,7 @@ pub enum MenuAction {
     CheckUpdates = 5002,
     TabTemplates = 5003,
     About = 5004,
+    UsageStats = 5005,
 
     // Sessions menu
     AttachSession = 7001,
@@ -87,16 +106,27 @@ impl MenuAction {
             1007 => Some(Self::QuickOpen),
             1003 => Some(Self::CloseTab),
             1004 => Some(Self::CloseOtherTabs),
+            1008 => Some(Self::ReopenClosedTab),
             1005 => Some(Self::DockerPicker),
+            1012 => Some(Self::SerialConnect),
             1006 => Some(Self::Quit),
+            1009 => Some(Self::SaveBuffer),
+            1010 => Some(Self::Record),
+            1011 => Some(Self::PlayRecording),
             2001 => Some(Self::Copy),
             2002 => Some(Self::CopyHtml),
             2003 => Some(Self::Paste),
             2004 => Some(Self::SelectAll),
+            2005 => Some(Self::SelectCommandOutput),
+            2006 => Some(Self::PasteHistory),
             2501 => Some(Self::ZoomIn),
             2502 => Some(Self::ZoomOut),
             2503 => Some(Self::ZoomReset),
             2504 => Some(Self::Fullscreen),
+            2505 => Some(Self::MagnifyIn),
+            2506 => Some(Self::MagnifyOut),
+            2507 => Some(Self::MagnifyReset),
+            2508 => Some(Self::PresentationMode),
             3001 => Some(Self::SetTitle),
             3002 => Some(Self::SetColor),
             3003 => Some(Self::Find),
@@ -106,6 +136,12 @@ impl MenuAction {
             3007 => Some(Self::SendSignalKill),
             3008 => Some(Self::SendSignalHup),
             3009 => Some(Self::SendSignalTerm),
+            3010 => Some(Self::AsciiArtSafe),
+            3011 => Some(Self::Hints),
+            3012 => Some(Self::ReadOnly),
+            3013 => Some(Self::WatchOutput),
+            3014 => Some(Self::PreviousPrompt),
+            3015 => Some(Self::NextPrompt),
             4001 => Some(Self::PrevTab),
             4002 => Some(Self::NextTab),
             4003 => Some(Self::NextAlertedTab),
@@ -122,6 +158,7 @@ impl MenuAction {
             5002 => Some(Self::CheckUpdates),
             5003 => Some(Self::TabTemplates),
             5004 => Some(Self::About),
+            5005 => Some(Self::UsageStats),
             7001 => Some(Self::AttachSession),
             7002 => Some(Self::SSHConnect),
             7003 => Some(Self::ManageRemotes),
@@ -140,26 +177,88 @@ impl MenuAction {
     }
 }
 
+/// ID of the "New Tab with Profile" item for the first profile; the others
+/// follow it
+const PROFILE_MENU_BASE: u16 = 8000;
+
+/// Most profiles listed in the menu
+const MAX_PROFILE_ITEMS: usize = 500;
+
+/// Index of the profile a menu ID opens, if it is a profile item
+pub fn profile_index(id: u16) -> Option<usize> {
+    let index = id.checked_sub(PROFILE_MENU_BASE)? as usize;
+    (index < MAX_PROFILE_ITEMS).then_some(index)
+}
+
+/// ID of the File > Workspaces item for the first workspace; the others
+/// follow it
+const WORKSPACE_MENU_BASE: u16 = 8500;
+
+/// Most workspaces listed in the menu
+const MAX_WORKSPACE_ITEMS: usize = 500;
+
+/// Index of the workspace a menu ID opens, if it is a workspace item
+pub fn workspace_index(id: u16) -> Option<usize> {
+    let index = id.checked_sub(WORKSPACE_MENU_BASE)? as usize;
+    (index < MAX_WORKSPACE_ITEMS).then_some(index)
+}
+
 /// Convert a Rust string to a null-terminated wide string
 fn to_wide_string(s: &str) -> Vec<u16> {
     s.encode_utf16().chain(std::iter::once(0)).collect()
 }
 
 /// Create the main menu bar
-pub fn create_menu_bar(show_debug: bool) -> HMENU {
+pub fn create_menu_bar(
+    show_debug: bool,
+    profiles: &[ProfileConfig],
+    workspaces: &[WorkspaceConfig],
+) -> HMENU {
     unsafe {
         let menu_bar = CreateMenu();
 
         // File menu
         let file_menu = CreatePopupMenu();
         append_menu_item(file_menu, MenuAction::NewTab, "&New Tab\tCtrl+T");
+        if !profiles.is_empty() {
+            let profiles_menu = CreatePopupMenu();
+            for (i, profile) in profiles.iter().take(MAX_PROFILE_ITEMS).enumerate() {
+                let wide = to_wide_string(&profile.name);
+                AppendMenuW(
+                    profiles_menu,
+                    MF_STRING,
+                    (PROFILE_MENU_BASE as usize) + i,
+                    wide.as_ptr(),
+                );
+            }
+            append_popup_menu(file_menu, profiles_menu, "New Tab with &Profile");
+        }
+        if !workspaces.is_empty() {
+            let workspaces_menu = CreatePopupMenu();
+            for (i, workspace) in workspaces.iter().take(MAX_WORKSPACE_ITEMS).enumerate() {
+                let wide = to_wide_string(&workspace.name);
+                AppendMenuW(
+                    workspaces_menu,
+                    MF_STRING,
+                    (WORKSPACE_MENU_BASE as usize) + i,
+                    wide.as_ptr(),
+                );
+            }
+            append_popup_menu(file_menu, workspaces_menu, "Wor&kspaces");
+        }
         append_menu_item(file_menu, MenuAction::NewWindow, "New &Window\tCtrl+N");
         append_menu_item(file_menu, MenuAction::QuickOpen, "&Quick Open\tCtrl+G");
         append_separator(file_menu);
         append_menu_item(file_menu, MenuAction::CloseTab, "&Close Tab\tCtrl+W");
         append_menu_item(file_menu, MenuAction::CloseOtherTabs, "Close &Other Tabs");
+        append_menu_item(
+            file_menu,
+            MenuAction::ReopenClosedTab,
+            "&Reopen Closed Tab\tCtrl+Alt+Shift+T",
+        );
         append_separator(file_menu);
         append_menu_item(file_menu, MenuAction::DockerPicker, "&Docker...");
+        append_menu_item(file_menu, MenuAction::SerialConnect, "New &Serial Tab...");
 
         // Sessions submenu
         let sessions_menu = CreatePopupMenu();
@@ -176,6 +275,10 @@ pub fn create_menu_bar(show_debug: bool) -> HMENU {
         );
         append_popup_menu(file_menu, sessions_menu, "S&essions");
 
+        append_separator(file_menu);
+        append_menu_item(file_menu, MenuAction::SaveBuffer, "Save &Buffer As...");
+        append_menu_item(file_menu, MenuAction::Record, "Start/Stop &Recording...");
+        append_menu_item(file_menu, MenuAction::PlayRecording, "&Play Recording...");
         append_separator(file_menu);
         append_menu_item(file_menu, MenuAction::Quit, "&Quit\tAlt+F4");
         append_popup_menu(menu_bar, file_menu, "&File");
@@ -185,12 +288,22 @@ pub fn create_menu_bar(show_debug: bool) -> HMENU {
         append_menu_item(edit_menu, MenuAction::Copy, "&Copy\tCtrl+Shift+C");
         append_menu_item(edit_menu, MenuAction::CopyHtml, "Copy as &HTML");
         append_menu_item(edit_menu, MenuAction::Paste, "&Paste\tCtrl+Shift+V");
+        append_menu_item(
+            edit_menu,
+            MenuAction::PasteHistory,
+            "Paste from &History...\tCtrl+Alt+Shift+V",
+        );
         append_separator(edit_menu);
         append_menu_item(
             edit_menu,
             MenuAction::SelectAll,
             "Select &All\tCtrl+Shift+A",
         );
+        append_menu_item(
+            edit_menu,
+            MenuAction::SelectCommandOutput,
+            "Select Command &Output\tCtrl+Shift+O",
+        );
         append_popup_menu(menu_bar, edit_menu, "&Edit");
 
         // View menu
@@ -199,6 +312,23 @@ pub fn create_menu_bar(show_debug: bool) -> HMENU {
         append_menu_item(view_menu, MenuAction::ZoomOut, "Zoom &Out\tCtrl+-");
         append_menu_item(view_menu, MenuAction::ZoomReset, "&Reset Zoom\tCtrl+0");
         append_separator(view_menu);
+        append_menu_item(view_menu, MenuAction::MagnifyIn, "&Magnify\tCtrl+Alt++");
+        append_menu_item(
+            view_menu,
+            MenuAction::MagnifyOut,
+            "Magnify Le&ss\tCtrl+Alt+-",
+        );
+        append_menu_item(
+            view_menu,
+            MenuAction::MagnifyReset,
+            "&Actual Size\tCtrl+Alt+0",
+        );
+        append_separator(view_menu);
+        append_menu_item(
+            view_menu,
+            MenuAction::PresentationMode,
+            "&Presentation Mode\tCtrl+Shift+P",
+        );
         append_menu_item(view_menu, MenuAction::Fullscreen, "&Fullscreen\tF11");
         append_popup_menu(menu_bar, view_menu, "&View");
 
@@ -208,6 +338,22 @@ pub fn create_menu_bar(show_debug: bool) -> HMENU {
         append_menu_item(terminal_menu, MenuAction::SetColor, "Set &Color...");
         append_separator(terminal_menu);
         append_menu_item(terminal_menu, MenuAction::Find, "&Find...\tCtrl+Shift+F");
+        append_menu_item(
+            terminal_menu,
+            MenuAction::Hints,
+            "Select &Link by Keyboard\tCtrl+Shift+E",
+        );
+        append_menu_item(
+            terminal_menu,
+            MenuAction::PreviousPrompt,
+            "Pre&vious Prompt\tCtrl+Shift+PgUp",
+        );
+        append_menu_item(
+            terminal_menu,
+            MenuAction::NextPrompt,
+            "Ne&xt Prompt\tCtrl+Shift+PgDn",
+        );
+        append_menu_item(terminal_menu, MenuAction::WatchOutput, "&Watch Output...");
         append_separator(terminal_menu);
 
         // Signal submenu
@@ -227,6 +373,16 @@ pub fn create_menu_bar(show_debug: bool) -> HMENU {
         append_popup_menu(terminal_menu, signal_menu, "Send &Signal");
 
         append_separator(terminal_menu);
+        append_menu_item(
+            terminal_menu,
+            MenuAction::AsciiArtSafe,
+            "&ASCII Art Safe Rendering",
+        );
+        append_menu_item(
+            terminal_menu,
+            MenuAction::ReadOnly,
+            "Read-&Only (Lock Input)",
+        );
         append_menu_item(terminal_menu, MenuAction::Reset, "&Reset Terminal");
         append_menu_item(terminal_menu, MenuAction::ClearReset, "Clear and R&eset");
         append_popup_menu(menu_bar, terminal_menu, "&Terminal");
@@ -263,6 +419,7 @@ pub fn create_menu_bar(show_debug: bool) -> HMENU {
         append_menu_item(help_menu, MenuAction::TabTemplates, "&Tab Templates...");
         append_separator(help_menu);
         append_menu_item(help_menu, MenuAction::CheckUpdates, "Check for &Updates...");
+        append_menu_item(help_menu, MenuAction::UsageStats, "Usage &Statistics...");
         append_separator(help_menu);
         append_menu_item(help_menu, MenuAction::About, "&About cterm");
         append_popup_menu(menu_bar, help_menu, "&Help");
@@ -456,6 +613,19 @@ mod tests {
         assert_eq!(MenuAction::from_id(action.id()), Some(action));
     }
 
+    #[test]
+    fn test_profile_index() {
+        assert_eq!(profile_index(PROFILE_MENU_BASE), Some(0));
+        assert_eq!(profile_index(PROFILE_MENU_BASE + 3), Some(3));
+        assert_eq!(profile_index(MenuAction::NewTab.id()), None);
+        assert_eq!(
+            profile_index(PROFILE_MENU_BASE + MAX_PROFILE_ITEMS as u16),
+            None
+        );
+        assert_eq!(workspace_index(WORKSPACE_MENU_BASE + 2), Some(2));
+        assert_eq!(profile_index(WORKSPACE_MENU_BASE), None);
+    }
+
     #[test]
     fn test_to_wide_string() {
         let wide = to_wide_string("Test");
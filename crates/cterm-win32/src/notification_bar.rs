@@ -1,9 +1,14 @@
-//! Notification bar for file transfers
+//! Notification bar for in-window prompts
 //!
-//! Displays file transfer notifications with save/discard actions.
+//! Shows the current prompt of the window's [`PromptQueue`] (a received file
+//! to save or discard, or a request the permission policy asks about, such as
+//! an OSC 52 clipboard read, to allow or deny) with a button per action.
 
 use cterm_core::color::Rgb;
-use cterm_ui::{format_size, Theme};
+use cterm_core::Capability;
+use cterm_ui::events::{KeyCode, Modifiers};
+use cterm_ui::prompt::{Prompt, PromptAction, PromptQueue};
+use cterm_ui::Theme;
 use windows::core::Interface;
 use windows::Win32::Graphics::Direct2D::Common::{D2D1_COLOR_F, D2D_POINT_2F, D2D_RECT_F};
 use windows::Win32::Graphics::Direct2D::{
@@ -19,53 +24,34 @@ use crate::dpi::DpiInfo;
 pub const NOTIFICATION_BAR_HEIGHT: i32 = 36;
 
 /// Button definitions
-const BUTTON_WIDTH: f32 = 80.0;
+const BUTTON_WIDTH: f32 = 96.0;
 const BUTTON_HEIGHT: f32 = 24.0;
 const BUTTON_MARGIN: f32 = 8.0;
 const BUTTON_CORNER_RADIUS: f32 = 4.0;
 
-/// Pending file notification
-#[derive(Debug, Clone)]
-pub struct PendingFile {
-    pub id: u64,
-    pub name: Option<String>,
-    pub size: usize,
-}
-
-/// Button hit area
-#[derive(Debug, Clone, Copy)]
-pub struct ButtonRect {
-    pub bounds: D2D_RECT_F,
-}
-
 /// Notification bar state
 pub struct NotificationBar {
-    pending_file: Option<PendingFile>,
-    visible: bool,
+    prompts: PromptQueue,
     theme: Theme,
     dpi: DpiInfo,
-    save_button_rect: D2D_RECT_F,
-    save_as_button_rect: D2D_RECT_F,
-    discard_button_rect: D2D_RECT_F,
+    /// Buttons of the current prompt, left to right
+    buttons: Vec<(D2D_RECT_F, PromptAction)>,
 }
 
 impl NotificationBar {
     /// Create a new notification bar
     pub fn new(theme: &Theme) -> Self {
         Self {
-            pending_file: None,
-            visible: false,
+            prompts: PromptQueue::new(),
             theme: theme.clone(),
             dpi: DpiInfo::default(),
-            save_button_rect: D2D_RECT_F::default(),
-            save_as_button_rect: D2D_RECT_F::default(),
-            discard_button_rect: D2D_RECT_F::default(),
+            buttons: Vec::new(),
         }
     }
 
     /// Get the notification bar height in physical pixels
     pub fn height(&self) -> i32 {
-        if self.visible {
+        if self.is_visible() {
             self.dpi.scale(NOTIFICATION_BAR_HEIGHT)
         } else {
             0
@@ -74,7 +60,7 @@ impl NotificationBar {
 
     /// Check if visible
     pub fn is_visible(&self) -> bool {
-        self.visible
+        !self.prompts.is_empty()
     }
 
     /// Update DPI
@@ -82,44 +68,46 @@ impl NotificationBar {
         self.dpi = dpi;
     }
 
-    /// Show a file transfer notification
+    /// Ask what to do with a received file
     pub fn show_file(&mut self, id: u64, name: Option<&str>, size: usize) {
-        self.pending_file = Some(PendingFile {
-            id,
-            name: name.map(|s| s.to_string()),
-            size,
-        });
-        self.visible = true;
+        self.prompts
+            .push_file(id, name.map(|s| s.to_string()), size);
+    }
+
+    /// Ask whether tab `tab_id` may use `capability`
+    pub fn ask_permission(&mut self, tab_id: u64, capability: Capability) {
+        self.prompts.ask_permission(tab_id, capability);
     }
 
-    /// Hide the notification bar
-    pub fn hide(&mut self) {
-        self.pending_file = None;
-        self.visible = false;
+    /// Prompt being shown
+    pub fn current(&self) -> Option<&Prompt> {
+        self.prompts.current()
     }
 
-    /// Get the current pending file ID if any
-    pub fn pending_file_id(&self) -> Option<u64> {
-        self.pending_file.as_ref().map(|f| f.id)
+    /// Answer the current prompt, returning it if `action` applies to it
+    pub fn answer(&mut self, action: PromptAction) -> Option<Prompt> {
+        self.prompts.resolve(action)
+    }
+
+    /// Action of the current prompt picked by a key press, if any
+    pub fn action_for_key(&self, key: KeyCode, modifiers: Modifiers) -> Option<PromptAction> {
+        self.prompts.action_for_key(key, modifiers, false)
+    }
+
+    /// Drop the prompts of a closed tab
+    pub fn forget_tab(&mut self, tab_id: u64) {
+        self.prompts.forget_tab(tab_id);
     }
 
     /// Hit test - returns which button was clicked if any
-    pub fn hit_test(&self, x: f32, y: f32) -> Option<NotificationAction> {
-        if !self.visible {
+    pub fn hit_test(&self, x: f32, y: f32) -> Option<PromptAction> {
+        if !self.is_visible() {
             return None;
         }
-
-        if point_in_rect(x, y, &self.save_button_rect) {
-            return Some(NotificationAction::Save);
-        }
-        if point_in_rect(x, y, &self.save_as_button_rect) {
-            return Some(NotificationAction::SaveAs);
-        }
-        if point_in_rect(x, y, &self.discard_button_rect) {
-            return Some(NotificationAction::Discard);
-        }
-
-        None
+        self.buttons
+            .iter()
+            .find(|(rect, _)| point_in_rect(x, y, rect))
+            .map(|&(_, action)| action)
     }
 
     /// Calculate button layout
@@ -131,30 +119,26 @@ impl NotificationBar {
 
         let button_y = (height - button_height) / 2.0;
 
-        // Buttons from right to left
-        let discard_x = width - margin - button_width;
-        self.discard_button_rect = D2D_RECT_F {
-            left: discard_x,
-            top: button_y,
-            right: discard_x + button_width,
-            bottom: button_y + button_height,
-        };
-
-        let save_as_x = discard_x - margin - button_width;
-        self.save_as_button_rect = D2D_RECT_F {
-            left: save_as_x,
-            top: button_y,
-            right: save_as_x + button_width,
-            bottom: button_y + button_height,
-        };
-
-        let save_x = save_as_x - margin - button_width;
-        self.save_button_rect = D2D_RECT_F {
-            left: save_x,
-            top: button_y,
-            right: save_x + button_width,
-            bottom: button_y + button_height,
-        };
+        // Buttons against the right edge
+        let actions = self
+            .prompts
+            .current()
+            .map(|p| p.actions(false))
+            .unwrap_or_default();
+        let mut x = width - actions.len() as f32 * (margin + button_width);
+        self.buttons = actions
+            .into_iter()
+            .map(|action| {
+                let rect = D2D_RECT_F {
+                    left: x,
+                    top: button_y,
+                    right: x + button_width,
+                    bottom: button_y + button_height,
+                };
+                x += button_width + margin;
+                (rect, action)
+            })
+            .collect();
     }
 
     /// Render the notification bar
@@ -165,9 +149,9 @@ impl NotificationBar {
         width: f32,
         text_format: &IDWriteTextFormat,
     ) -> windows::core::Result<()> {
-        if !self.visible {
+        let Some(message) = self.prompts.message() else {
             return Ok(());
-        }
+        };
 
         // Calculate layout
         self.calculate_layout(width);
@@ -195,59 +179,36 @@ impl NotificationBar {
         unsafe { base.FillRectangle(&bg_rect, &bg_brush) };
 
         // Draw message text
-        if let Some(ref file) = self.pending_file {
-            let message = format!(
-                "File received: {} ({})",
-                file.name.as_deref().unwrap_or("unnamed"),
-                format_size(file.size)
-            );
-
-            let text_color = self.theme.ui.tab_active_text;
-            let text_brush =
-                unsafe { base.CreateSolidColorBrush(&rgb_to_d2d_color(text_color), None)? };
-
-            let text_wide: Vec<u16> = message.encode_utf16().collect();
-            let text_width = self.save_button_rect.left - self.dpi.scale_f32(BUTTON_MARGIN * 2.0);
-
-            let layout: IDWriteTextLayout =
-                unsafe { dwrite.CreateTextLayout(&text_wide, text_format, text_width, height)? };
-
-            unsafe {
-                layout.SetParagraphAlignment(DWRITE_PARAGRAPH_ALIGNMENT_CENTER)?;
-            }
-
-            let text_origin = D2D_POINT_2F {
-                x: self.dpi.scale_f32(BUTTON_MARGIN),
-                y: 0.0,
-            };
-            unsafe { base.DrawTextLayout(text_origin, &layout, &text_brush, Default::default()) };
+        let text_color = self.theme.ui.tab_active_text;
+        let text_brush =
+            unsafe { base.CreateSolidColorBrush(&rgb_to_d2d_color(text_color), None)? };
+
+        let text_wide: Vec<u16> = message.encode_utf16().collect();
+        let buttons_left = self.buttons.first().map_or(width, |(rect, _)| rect.left);
+        let text_width = buttons_left - self.dpi.scale_f32(BUTTON_MARGIN * 2.0);
+
+        let layout: IDWriteTextLayout =
+            unsafe { dwrite.CreateTextLayout(&text_wide, text_format, text_width, height)? };
+
+        unsafe {
+            layout.SetParagraphAlignment(DWRITE_PARAGRAPH_ALIGNMENT_CENTER)?;
         }
 
+        let text_origin = D2D_POINT_2F {
+            x: self.dpi.scale_f32(BUTTON_MARGIN),
+            y: 0.0,
+        };
+        unsafe { base.DrawTextLayout(text_origin, &layout, &text_brush, Default::default()) };
+
         // Draw buttons
-        self.render_button(
-            rt,
-            dwrite,
-            &self.save_button_rect,
-            "Save",
-            Rgb::new(0, 128, 0),
-            text_format,
-        )?;
-        self.render_button(
-            rt,
-            dwrite,
-            &self.save_as_button_rect,
-            "Save As...",
-            Rgb::new(0, 100, 150),
-            text_format,
-        )?;
-        self.render_button(
-            rt,
-            dwrite,
-            &self.discard_button_rect,
-            "Discard",
-            Rgb::new(150, 50, 50),
-            text_format,
-        )?;
+        for (rect, action) in &self.buttons {
+            let color = match action {
+                PromptAction::Save | PromptAction::Allow => Rgb::new(0, 128, 0),
+                PromptAction::Discard | PromptAction::Deny => Rgb::new(150, 50, 50),
+                _ => Rgb::new(0, 100, 150),
+            };
+            self.render_button(rt, dwrite, rect, action.label(), color, text_format)?;
+        }
 
         // Draw bottom border
         let border_color = rgb_to_d2d_color(self.theme.ui.border);
@@ -328,14 +289,6 @@ impl NotificationBar {
     }
 }
 
-/// Notification bar action
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum NotificationAction {
-    Save,
-    SaveAs,
-    Discard,
-}
-
 /// Check if a point is inside a rectangle
 fn point_in_rect(x: f32, y: f32, rect: &D2D_RECT_F) -> bool {
     x >= rect.left && x <= rect.right && y >= rect.top && y <= rect.bottom
@@ -367,7 +320,7 @@ mod tests {
         assert!(bar.is_visible());
         assert!(bar.height() > 0);
 
-        bar.hide();
+        bar.answer(PromptAction::Discard);
         assert!(!bar.is_visible());
         assert_eq!(bar.height(), 0);
     }
@@ -377,12 +330,19 @@ mod tests {
         let theme = Theme::dark();
         let mut bar = NotificationBar::new(&theme);
 
-        assert!(bar.pending_file_id().is_none());
+        assert!(bar.current().is_none());
 
         bar.show_file(42, Some("data.bin"), 2048);
-        assert_eq!(bar.pending_file_id(), Some(42));
+        assert_eq!(bar.current().and_then(|p| p.file_id()), Some(42));
 
-        bar.hide();
-        assert!(bar.pending_file_id().is_none());
+        bar.ask_permission(7, Capability::ClipboardRead);
+        assert_eq!(
+            bar.answer(PromptAction::Save).and_then(|p| p.file_id()),
+            Some(42)
+        );
+        assert!(bar.current().is_some_and(|p| p.file_id().is_none()));
+
+        bar.forget_tab(7);
+        assert!(!bar.is_visible());
     }
 }
@@ -1,7 +1,7 @@
 //! Preferences dialog for cterm settings
 //!
 //! Provides a multi-tab dialog for configuring general settings, appearance,
-//! tab behavior, and keyboard shortcuts.
+//! tab behavior, keyboard shortcuts, external tools, and git sync.
 
 use std::cell::RefCell;
 use std::ptr;
@@ -15,37 +15,31 @@ use winapi::um::winuser::*;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::dialog_utils::*;
-use cterm_app::config::{
-    config_dir, Config, CursorStyleConfig, NewTabPosition, TabBarPosition, TabBarVisibility,
+use cterm_app::config::{config_dir, Config, ShortcutsConfig, ToolShortcutEntry};
+use cterm_app::live_config::Preview;
+use cterm_app::settings::{
+    self, Setting, SettingCategory, SettingError, SettingKind, Value, SETTINGS,
 };
 use cterm_app::{git_sync, PullResult};
 
-// Control IDs - General tab
+// Control IDs - tab control and settings search
 const IDC_TABS: i32 = 1001;
-const IDC_SCROLLBACK_EDIT: i32 = 1010;
-const IDC_SCROLLBACK_SPIN: i32 = 1011;
-const IDC_CONFIRM_CLOSE: i32 = 1012;
-const IDC_COPY_ON_SELECT: i32 = 1013;
-
-// Control IDs - Appearance tab
-const IDC_THEME: i32 = 1020;
-const IDC_FONT_FAMILY: i32 = 1021;
-const IDC_FONT_SIZE_EDIT: i32 = 1022;
-const IDC_FONT_SIZE_SPIN: i32 = 1023;
-const IDC_CURSOR_STYLE: i32 = 1024;
-const IDC_CURSOR_BLINK: i32 = 1025;
-const IDC_OPACITY_TRACK: i32 = 1026;
-const IDC_OPACITY_LABEL: i32 = 1027;
-const IDC_BOLD_BRIGHT: i32 = 1028;
-
-// Control IDs - Tabs tab
-const IDC_SHOW_TABBAR: i32 = 1030;
-const IDC_TAB_POSITION: i32 = 1031;
-const IDC_NEW_TAB_POS: i32 = 1032;
-const IDC_SHOW_CLOSE_BTN: i32 = 1033;
+const IDC_SEARCH: i32 = 1002;
 
 // Control IDs - Shortcuts tab
 const IDC_SHORTCUTS_LIST: i32 = 1040;
+const IDC_SHORTCUT_EDIT: i32 = 1041;
+const IDC_SHORTCUT_SET: i32 = 1042;
+
+// Control IDs - Tools tab
+const IDC_TOOLS_LIST: i32 = 1060;
+const IDC_TOOL_NAME: i32 = 1061;
+const IDC_TOOL_COMMAND: i32 = 1062;
+const IDC_TOOL_ARGS: i32 = 1063;
+const IDC_TOOL_ADD: i32 = 1064;
+const IDC_TOOL_UPDATE: i32 = 1065;
+const IDC_TOOL_REMOVE: i32 = 1066;
+const IDC_TOOL_DEFAULTS: i32 = 1067;
 
 // Control IDs - Git Sync tab
 const IDC_GIT_REMOTE: i32 = 1050;
@@ -57,24 +51,87 @@ const IDC_GIT_SYNC_NOW: i32 = 1055;
 
 // Button IDs
 const IDC_APPLY: i32 = 1099;
+/// Reset buttons use this plus the setting's index in `SETTINGS`
+const IDC_RESET_BASE: i32 = 1100;
+/// Input controls of the settings rows, by index in `SETTINGS`
+const IDC_SETTING_BASE: i32 = 1200;
+/// Up-down buddies and value labels of the settings rows
+const IDC_SETTING_AUX_BASE: i32 = 1300;
 
 // Tab indices
 const TAB_GENERAL: i32 = 0;
 const TAB_APPEARANCE: i32 = 1;
 const TAB_TABS: i32 = 2;
 const TAB_SHORTCUTS: i32 = 3;
-const TAB_GIT_SYNC: i32 = 4;
+const TAB_TOOLS: i32 = 4;
+const TAB_GIT_SYNC: i32 = 5;
+
+/// Actions listed on the Shortcuts tab, in list order
+const SHORTCUT_ACTIONS: [&str; 14] = [
+    "New Tab",
+    "Close Tab",
+    "Next Tab",
+    "Previous Tab",
+    "New Window",
+    "Close Window",
+    "Copy",
+    "Paste",
+    "Select All",
+    "Zoom In",
+    "Zoom Out",
+    "Zoom Reset",
+    "Find",
+    "Reset Terminal",
+];
+
+/// The shortcut for the `index`th entry of `SHORTCUT_ACTIONS`
+fn shortcut_mut(shortcuts: &mut ShortcutsConfig, index: usize) -> Option<&mut String> {
+    Some(match index {
+        0 => &mut shortcuts.new_tab,
+        1 => &mut shortcuts.close_tab,
+        2 => &mut shortcuts.next_tab,
+        3 => &mut shortcuts.prev_tab,
+        4 => &mut shortcuts.new_window,
+        5 => &mut shortcuts.close_window,
+        6 => &mut shortcuts.copy,
+        7 => &mut shortcuts.paste,
+        8 => &mut shortcuts.select_all,
+        9 => &mut shortcuts.zoom_in,
+        10 => &mut shortcuts.zoom_out,
+        11 => &mut shortcuts.zoom_reset,
+        12 => &mut shortcuts.find,
+        13 => &mut shortcuts.reset,
+        _ => return None,
+    })
+}
+
+/// Controls of a setting's row, the reset button last
+struct SettingRow {
+    setting: &'static Setting,
+    controls: Vec<HWND>,
+    /// Checkbox, edit, trackbar or combo box holding the value
+    input: HWND,
+    /// Percentage shown next to a trackbar
+    value_label: Option<HWND>,
+    /// `(value, label)` of the combo box items
+    choices: Vec<(String, String)>,
+}
 
 /// Dialog state
 struct DialogState {
     config: Config,
+    /// External tool shortcuts, saved separately from config.toml
+    tools: Vec<ToolShortcutEntry>,
     current_tab: i32,
-    // Control handles for each tab
-    general_controls: Vec<HWND>,
-    appearance_controls: Vec<HWND>,
-    tabs_controls: Vec<HWND>,
+    // Control handles for each tab after the settings tabs
     shortcuts_controls: Vec<HWND>,
+    tools_controls: Vec<HWND>,
     git_sync_controls: Vec<HWND>,
+    // Search and per-setting reset
+    search: String,
+    setting_rows: Vec<SettingRow>,
+    /// Appearance shown in open windows while the dialog is open
+    preview: Preview,
 }
 
 // Thread-local storage for dialog state
@@ -88,16 +145,19 @@ thread_local! {
 pub fn show_preferences_dialog(parent: HWND) -> bool {
     // Load current config
     let config = cterm_app::load_config().unwrap_or_default();
+    let tools = cterm_app::config::load_tool_shortcuts().unwrap_or_default();
 
     DIALOG_STATE.with(|s| {
         *s.borrow_mut() = Some(DialogState {
+            preview: Preview::new(&config),
             config,
+            tools,
             current_tab: TAB_GENERAL,
-            general_controls: Vec::new(),
-            appearance_controls: Vec::new(),
-            tabs_controls: Vec::new(),
             shortcuts_controls: Vec::new(),
+            tools_controls: Vec::new(),
             git_sync_controls: Vec::new(),
+            search: String::new(),
+            setting_rows: Vec::new(),
         });
     });
 
@@ -184,7 +244,8 @@ unsafe extern "system" fn dialog_proc(
         }
         WM_COMMAND => {
             let id = (wparam & 0xFFFF) as i32;
-            handle_command(hwnd, id);
+            let code = ((wparam >> 16) & 0xFFFF) as u16;
+            handle_command(hwnd, id, code);
             1
         }
         WM_NOTIFY => {
@@ -203,6 +264,7 @@ unsafe extern "system" fn dialog_proc(
             0
         }
         WM_CLOSE => {
+            revert_preview();
             EndDialog(hwnd, IDCANCEL as isize);
             1
         }
@@ -221,13 +283,26 @@ unsafe fn init_dialog(hwnd: HWND) {
     let button_height = 25;
     let button_width = 75;
     let tab_height = 28;
+    let search_height = 22;
+
+    // Search box filtering the settings on every tab
+    let search = create_edit(
+        hwnd,
+        IDC_SEARCH,
+        margin,
+        margin,
+        dlg_width - margin * 2,
+        search_height,
+    );
+    set_cue_banner(search, "Search settings");
 
     // Create tab control
+    let tab_top = margin + search_height + 6;
     let tab_ctrl = create_tab_control(
         hwnd,
         IDC_TABS,
         margin,
-        margin,
+        tab_top,
         dlg_width - margin * 2,
         tab_height,
     );
@@ -235,35 +310,29 @@ unsafe fn init_dialog(hwnd: HWND) {
     add_tab(tab_ctrl, TAB_APPEARANCE, "Appearance");
     add_tab(tab_ctrl, TAB_TABS, "Tabs");
     add_tab(tab_ctrl, TAB_SHORTCUTS, "Shortcuts");
+    add_tab(tab_ctrl, TAB_TOOLS, "Tools");
     add_tab(tab_ctrl, TAB_GIT_SYNC, "Git Sync");
 
     // Content area
-    let content_top = margin + tab_height + 5;
+    let content_top = tab_top + tab_height + 5;
     let content_height = dlg_height - content_top - button_height - margin * 2 - 5;
 
     // Create controls for each tab
-    create_general_controls(
-        hwnd,
-        margin,
-        content_top,
-        dlg_width - margin * 2,
-        content_height,
-    );
-    create_appearance_controls(
+    create_setting_controls(
         hwnd,
         margin,
         content_top,
         dlg_width - margin * 2,
         content_height,
     );
-    create_tabs_controls(
+    create_shortcuts_controls(
         hwnd,
         margin,
         content_top,
         dlg_width - margin * 2,
         content_height,
     );
-    create_shortcuts_controls(
+    create_tools_controls(
         hwnd,
         margin,
         content_top,
@@ -315,343 +384,175 @@ unsafe fn init_dialog(hwnd: HWND) {
     populate_controls();
 }
 
-/// Create controls for the General tab
-unsafe fn create_general_controls(hwnd: HWND, x: i32, y: i32, _w: i32, _h: i32) {
-    let mut controls = Vec::new();
-    let row_height = 26;
+/// Horizontal offset of the reset buttons from the tab content's left edge
+const RESET_X: i32 = 330;
+
+/// Create a row of controls for each setting, on the tab of its category
+unsafe fn create_setting_controls(hwnd: HWND, x: i32, y: i32, _w: i32, _h: i32) {
+    let row_height = 31;
     let label_width = 120;
     let control_width = 180;
-
-    // Scrollback lines
-    let mut cy = y;
-    controls.push(create_label(
-        hwnd,
-        -1,
-        "Scrollback lines:",
-        x,
-        cy + 3,
-        label_width,
-        18,
-    ));
-    let (edit, spin) = create_spinner(
-        hwnd,
-        IDC_SCROLLBACK_EDIT,
-        IDC_SCROLLBACK_SPIN,
-        x + label_width + 10,
-        cy,
-        control_width,
-        22,
-        0,
-        100000,
-        10000,
-    );
-    controls.push(edit);
-    controls.push(spin);
-
-    // Confirm close
-    cy += row_height + 5;
-    controls.push(create_checkbox(
-        hwnd,
-        IDC_CONFIRM_CLOSE,
-        "Confirm close with running process",
-        x,
-        cy,
-        300,
-        20,
-    ));
-
-    // Copy on select
-    cy += row_height;
-    controls.push(create_checkbox(
-        hwnd,
-        IDC_COPY_ON_SELECT,
-        "Copy text on selection",
-        x,
-        cy,
-        300,
-        20,
-    ));
+    let control_x = x + label_width + 10;
+
+    let mut rows = Vec::new();
+    for category in SettingCategory::ALL {
+        let mut cy = y;
+        for setting in category.settings() {
+            let index = SETTINGS
+                .iter()
+                .position(|s| s.key == setting.key)
+                .unwrap_or_default() as i32;
+            let id = IDC_SETTING_BASE + index;
+            let aux_id = IDC_SETTING_AUX_BASE + index;
+            let label = format!("{}:", setting.label);
+
+            let mut controls = Vec::new();
+            let mut value_label = None;
+            let mut choices = Vec::new();
+            let input = match setting.kind {
+                SettingKind::Bool => create_checkbox(hwnd, id, setting.label, x, cy, 300, 20),
+                SettingKind::Integer { min, max, .. } => {
+                    controls.push(create_label(hwnd, -1, &label, x, cy + 3, label_width, 18));
+                    let (edit, spin) = create_spinner(
+                        hwnd, id, aux_id, control_x, cy, 100, 22, min as i32, max as i32,
+                        min as i32,
+                    );
+                    controls.push(spin);
+                    edit
+                }
+                SettingKind::Float { .. } | SettingKind::Text => {
+                    controls.push(create_label(hwnd, -1, &label, x, cy + 3, label_width, 18));
+                    create_edit(hwnd, id, control_x, cy, control_width, 22)
+                }
+                SettingKind::Ratio => {
+                    controls.push(create_label(hwnd, -1, &label, x, cy + 3, label_width, 18));
+                    let trackbar = create_trackbar(hwnd, id, control_x, cy, 150, 22, 0, 100);
+                    let percent =
+                        create_label(hwnd, aux_id, "100%", control_x + 155, cy + 3, 40, 18);
+                    controls.push(percent);
+                    value_label = Some(percent);
+                    trackbar
+                }
+                SettingKind::Choice(_) | SettingKind::Theme => {
+                    controls.push(create_label(hwnd, -1, &label, x, cy + 3, label_width, 18));
+                    let combo = create_combobox(hwnd, id, control_x, cy, control_width, 22);
+                    choices = setting.choices();
+                    for (_, title) in &choices {
+                        add_combobox_item(combo, title);
+                    }
+                    combo
+                }
+            };
+            controls.push(input);
+            controls.push(create_button(
+                hwnd,
+                IDC_RESET_BASE + index,
+                "Reset",
+                x + RESET_X,
+                cy,
+                60,
+                22,
+            ));
+
+            rows.push(SettingRow {
+                setting,
+                controls,
+                input,
+                value_label,
+                choices,
+            });
+            cy += row_height;
+        }
+    }
 
     DIALOG_STATE.with(|s| {
         if let Some(ref mut state) = *s.borrow_mut() {
-            state.general_controls = controls;
+            state.setting_rows = rows;
         }
     });
 }
 
-/// Create controls for the Appearance tab
-unsafe fn create_appearance_controls(hwnd: HWND, x: i32, y: i32, _w: i32, _h: i32) {
+/// Create controls for the Shortcuts tab
+unsafe fn create_shortcuts_controls(hwnd: HWND, x: i32, y: i32, w: i32, h: i32) {
     let mut controls = Vec::new();
-    let row_height = 26;
-    let label_width = 100;
-    let control_width = 180;
 
-    // Theme
-    let mut cy = y;
-    controls.push(create_label(hwnd, -1, "Theme:", x, cy + 3, label_width, 18));
-    let theme_combo = create_combobox(hwnd, IDC_THEME, x + label_width + 10, cy, control_width, 22);
-    add_combobox_item(theme_combo, "Default Dark");
-    add_combobox_item(theme_combo, "Default Light");
-    add_combobox_item(theme_combo, "Tokyo Night");
-    add_combobox_item(theme_combo, "Dracula");
-    add_combobox_item(theme_combo, "Nord");
-    controls.push(theme_combo);
-
-    // Font family
-    cy += row_height + 5;
-    controls.push(create_label(
-        hwnd,
-        -1,
-        "Font family:",
-        x,
-        cy + 3,
-        label_width,
-        18,
-    ));
-    let font_edit = create_edit(
-        hwnd,
-        IDC_FONT_FAMILY,
-        x + label_width + 10,
-        cy,
-        control_width,
-        22,
-    );
-    controls.push(font_edit);
+    // Create a listview with two columns: Action and Shortcut
+    let listview = create_listview(hwnd, IDC_SHORTCUTS_LIST, x, y, w, h - 35);
+    add_listview_column(listview, 0, "Action", (w * 40) / 100);
+    add_listview_column(listview, 1, "Shortcut", (w * 55) / 100);
+    controls.push(listview);
 
-    // Font size
-    cy += row_height + 5;
-    controls.push(create_label(
-        hwnd,
-        -1,
-        "Font size:",
-        x,
-        cy + 3,
-        label_width,
-        18,
-    ));
-    let (edit, spin) = create_spinner(
-        hwnd,
-        IDC_FONT_SIZE_EDIT,
-        IDC_FONT_SIZE_SPIN,
-        x + label_width + 10,
-        cy,
-        80,
-        22,
-        6,
-        72,
-        14,
-    );
+    // The selected action's shortcut is typed as text, e.g. "Ctrl+Shift+T"
+    let cy = y + h - 27;
+    controls.push(create_label(hwnd, -1, "Shortcut:", x, cy + 3, 60, 18));
+    let edit = create_edit(hwnd, IDC_SHORTCUT_EDIT, x + 65, cy, w - 150, 22);
+    enable_control(edit, false);
     controls.push(edit);
-    controls.push(spin);
-
-    // Cursor style
-    cy += row_height + 5;
-    controls.push(create_label(
-        hwnd,
-        -1,
-        "Cursor style:",
-        x,
-        cy + 3,
-        label_width,
-        18,
-    ));
-    let cursor_combo = create_combobox(
-        hwnd,
-        IDC_CURSOR_STYLE,
-        x + label_width + 10,
-        cy,
-        control_width,
-        22,
-    );
-    add_combobox_item(cursor_combo, "Block");
-    add_combobox_item(cursor_combo, "Underline");
-    add_combobox_item(cursor_combo, "Bar");
-    controls.push(cursor_combo);
-
-    // Cursor blink
-    cy += row_height + 5;
-    controls.push(create_checkbox(
-        hwnd,
-        IDC_CURSOR_BLINK,
-        "Cursor blink",
-        x,
-        cy,
-        200,
-        20,
-    ));
-
-    // Opacity
-    cy += row_height + 5;
-    controls.push(create_label(
-        hwnd,
-        -1,
-        "Opacity:",
-        x,
-        cy + 3,
-        label_width,
-        18,
-    ));
-    let trackbar = create_trackbar(
-        hwnd,
-        IDC_OPACITY_TRACK,
-        x + label_width + 10,
-        cy,
-        150,
-        22,
-        20,
-        100,
-    );
-    controls.push(trackbar);
-    controls.push(create_label(
-        hwnd,
-        IDC_OPACITY_LABEL,
-        "100%",
-        x + label_width + 165,
-        cy + 3,
-        40,
-        18,
-    ));
-
-    // Bold is bright
-    cy += row_height + 5;
-    controls.push(create_checkbox(
-        hwnd,
-        IDC_BOLD_BRIGHT,
-        "Bold text is bright",
-        x,
-        cy,
-        200,
-        20,
-    ));
+    let set_button = create_button(hwnd, IDC_SHORTCUT_SET, "Set", x + w - 75, cy, 75, 24);
+    enable_control(set_button, false);
+    controls.push(set_button);
 
     DIALOG_STATE.with(|s| {
         if let Some(ref mut state) = *s.borrow_mut() {
-            state.appearance_controls = controls;
+            state.shortcuts_controls = controls;
         }
     });
 }
 
-/// Create controls for the Tabs tab
-unsafe fn create_tabs_controls(hwnd: HWND, x: i32, y: i32, _w: i32, _h: i32) {
+/// Create controls for the Tools tab
+unsafe fn create_tools_controls(hwnd: HWND, x: i32, y: i32, w: i32, h: i32) {
     let mut controls = Vec::new();
     let row_height = 26;
-    let label_width = 120;
-    let control_width = 150;
-
-    // Show tab bar
-    let mut cy = y;
-    controls.push(create_label(
-        hwnd,
-        -1,
-        "Show tab bar:",
-        x,
-        cy + 3,
-        label_width,
-        18,
-    ));
-    let show_combo = create_combobox(
-        hwnd,
-        IDC_SHOW_TABBAR,
-        x + label_width + 10,
-        cy,
-        control_width,
-        22,
-    );
-    add_combobox_item(show_combo, "Always");
-    add_combobox_item(show_combo, "When multiple tabs");
-    add_combobox_item(show_combo, "Never");
-    controls.push(show_combo);
-
-    // Tab bar position
-    cy += row_height + 5;
-    controls.push(create_label(
-        hwnd,
-        -1,
-        "Tab bar position:",
-        x,
-        cy + 3,
-        label_width,
-        18,
-    ));
-    let pos_combo = create_combobox(
-        hwnd,
-        IDC_TAB_POSITION,
-        x + label_width + 10,
-        cy,
-        control_width,
-        22,
-    );
-    add_combobox_item(pos_combo, "Top");
-    add_combobox_item(pos_combo, "Bottom");
-    controls.push(pos_combo);
-
-    // New tab position
-    cy += row_height + 5;
-    controls.push(create_label(
-        hwnd,
-        -1,
-        "New tab position:",
-        x,
-        cy + 3,
-        label_width,
-        18,
-    ));
-    let new_tab_combo = create_combobox(
-        hwnd,
-        IDC_NEW_TAB_POS,
-        x + label_width + 10,
-        cy,
-        control_width,
-        22,
-    );
-    add_combobox_item(new_tab_combo, "At end");
-    add_combobox_item(new_tab_combo, "After current");
-    controls.push(new_tab_combo);
-
-    // Show close button
-    cy += row_height + 5;
-    controls.push(create_checkbox(
-        hwnd,
-        IDC_SHOW_CLOSE_BTN,
-        "Show close button on tabs",
-        x,
-        cy,
-        250,
-        20,
-    ));
-
-    DIALOG_STATE.with(|s| {
-        if let Some(ref mut state) = *s.borrow_mut() {
-            state.tabs_controls = controls;
-        }
-    });
-}
-
-/// Create controls for the Shortcuts tab
-unsafe fn create_shortcuts_controls(hwnd: HWND, x: i32, y: i32, w: i32, h: i32) {
-    let mut controls = Vec::new();
+    let label_width = 70;
+    let button_width = 75;
 
-    // Create a listview with two columns: Action and Shortcut
-    let listview = create_listview(hwnd, IDC_SHORTCUTS_LIST, x, y, w, h - 30);
-    add_listview_column(listview, 0, "Action", (w * 40) / 100);
-    add_listview_column(listview, 1, "Shortcut", (w * 55) / 100);
+    let listview = create_listview(hwnd, IDC_TOOLS_LIST, x, y, w, h - row_height * 4);
+    add_listview_column(listview, 0, "Name", (w * 25) / 100);
+    add_listview_column(listview, 1, "Command", (w * 35) / 100);
+    add_listview_column(listview, 2, "Args", (w * 35) / 100);
     controls.push(listview);
 
-    // Note: Editing shortcuts would require a custom key capture dialog
-    // For now, just display them
-    controls.push(create_label(
+    let mut cy = y + h - row_height * 4 + 8;
+    for (id, label) in [
+        (IDC_TOOL_NAME, "Name:"),
+        (IDC_TOOL_COMMAND, "Command:"),
+        (IDC_TOOL_ARGS, "Args:"),
+    ] {
+        controls.push(create_label(hwnd, -1, label, x, cy + 3, label_width, 18));
+        controls.push(create_edit(
+            hwnd,
+            id,
+            x + label_width,
+            cy,
+            w - label_width - button_width - 10,
+            22,
+        ));
+        cy += row_height;
+    }
+
+    let bx = x + w - button_width;
+    let mut by = y + h - row_height * 4 + 8;
+    for (id, label) in [
+        (IDC_TOOL_ADD, "Add"),
+        (IDC_TOOL_UPDATE, "Update"),
+        (IDC_TOOL_REMOVE, "Remove"),
+    ] {
+        controls.push(create_button(hwnd, id, label, bx, by, button_width, 24));
+        by += row_height;
+    }
+    controls.push(create_button(
         hwnd,
-        -1,
-        "Edit shortcuts in config.toml",
+        IDC_TOOL_DEFAULTS,
+        "Reset to Defaults",
         x,
-        y + h - 25,
-        w,
-        20,
+        y + h - 24,
+        120,
+        24,
     ));
 
     DIALOG_STATE.with(|s| {
         if let Some(ref mut state) = *s.borrow_mut() {
-            state.shortcuts_controls = controls;
+            state.tools_controls = controls;
         }
     });
 }
@@ -965,30 +866,40 @@ fn show_tab(tab_index: i32) {
     DIALOG_STATE.with(|s| {
         if let Some(ref state) = *s.borrow() {
             // Hide all
-            for hwnd in &state.general_controls {
-                show_control(*hwnd, false);
-            }
-            for hwnd in &state.appearance_controls {
-                show_control(*hwnd, false);
+            for row in &state.setting_rows {
+                for hwnd in &row.controls {
+                    show_control(*hwnd, false);
+                }
             }
-            for hwnd in &state.tabs_controls {
+            for hwnd in &state.shortcuts_controls {
                 show_control(*hwnd, false);
             }
-            for hwnd in &state.shortcuts_controls {
+            for hwnd in &state.tools_controls {
                 show_control(*hwnd, false);
             }
             for hwnd in &state.git_sync_controls {
                 show_control(*hwnd, false);
             }
 
+            // The settings tabs come first, in category order, and show
+            // the rows matching the search
+            if let Some(category) = SettingCategory::ALL.get(tab_index as usize) {
+                for row in &state.setting_rows {
+                    if row.setting.category == *category && row.setting.matches(&state.search) {
+                        for hwnd in &row.controls {
+                            show_control(*hwnd, true);
+                        }
+                    }
+                }
+                return;
+            }
+
             // Show the selected tab's controls
             let controls = match tab_index {
-                TAB_GENERAL => &state.general_controls,
-                TAB_APPEARANCE => &state.appearance_controls,
-                TAB_TABS => &state.tabs_controls,
                 TAB_SHORTCUTS => &state.shortcuts_controls,
+                TAB_TOOLS => &state.tools_controls,
                 TAB_GIT_SYNC => &state.git_sync_controls,
-                _ => &state.general_controls,
+                _ => return,
             };
             for hwnd in controls {
                 show_control(*hwnd, true);
@@ -997,120 +908,147 @@ fn show_tab(tab_index: i32) {
     });
 }
 
+/// Filter the setting rows by the text in the search box, showing match
+/// counts on the tabs and switching to the first tab with a match if the
+/// current one has none
+fn update_search(hwnd: HWND) {
+    let query = get_edit_text(get_dialog_item(hwnd, IDC_SEARCH))
+        .trim()
+        .to_string();
+    let mut matches = [0usize; SettingCategory::ALL.len()];
+    let current_tab = DIALOG_STATE.with(|s| {
+        let mut s = s.borrow_mut();
+        let state = s.as_mut()?;
+        for row in &state.setting_rows {
+            if row.setting.matches(&query) {
+                if let Some(i) = SettingCategory::ALL
+                    .iter()
+                    .position(|category| *category == row.setting.category)
+                {
+                    matches[i] += 1;
+                }
+            }
+        }
+        state.search = query.clone();
+        Some(state.current_tab)
+    });
+    let Some(mut current_tab) = current_tab else {
+        return;
+    };
+
+    // The settings tabs come first, in category order
+    let tab_ctrl = get_dialog_item(hwnd, IDC_TABS);
+    for (i, category) in SettingCategory::ALL.iter().enumerate() {
+        let text = if query.is_empty() {
+            category.name().to_string()
+        } else {
+            format!("{} ({})", category.name(), matches[i])
+        };
+        set_tab_text(tab_ctrl, i as i32, &text);
+    }
+
+    let current_matches = matches.get(current_tab as usize).copied().unwrap_or(0);
+    if !query.is_empty() && current_matches == 0 {
+        if let Some(first) = matches.iter().position(|&count| count > 0) {
+            current_tab = first as i32;
+            set_selected_tab(tab_ctrl, current_tab);
+            DIALOG_STATE.with(|s| {
+                if let Some(ref mut state) = *s.borrow_mut() {
+                    state.current_tab = current_tab;
+                }
+            });
+        }
+    }
+    show_tab(current_tab);
+}
+
+/// Put a setting's controls back to the default value
+fn reset_setting(key: &str) {
+    let Some(default) = settings::default_value(key) else {
+        return;
+    };
+    DIALOG_STATE.with(|s| {
+        if let Some(ref state) = *s.borrow() {
+            if let Some(row) = state.setting_rows.iter().find(|row| row.setting.key == key) {
+                show_setting_value(row, &default);
+            }
+        }
+    });
+}
+
 /// Populate controls with current config values
 fn populate_controls() {
     DIALOG_STATE.with(|s| {
         if let Some(ref state) = *s.borrow() {
             let config = &state.config;
 
-            // General tab
-            if let Some(&edit) = state.general_controls.get(1) {
-                set_edit_text(edit, &config.general.scrollback_lines.to_string());
-            }
-            if let Some(&checkbox) = state.general_controls.get(3) {
-                set_checkbox_state(checkbox, config.general.confirm_close_with_running);
-            }
-            if let Some(&checkbox) = state.general_controls.get(4) {
-                set_checkbox_state(checkbox, config.general.copy_on_select);
-            }
-
-            // Appearance tab
-            if let Some(&combo) = state.appearance_controls.get(1) {
-                let idx = match config.appearance.theme.as_str() {
-                    "Default Dark" | "dark" => 0,
-                    "Default Light" | "light" => 1,
-                    "Tokyo Night" | "tokyo-night" => 2,
-                    "Dracula" | "dracula" => 3,
-                    "Nord" | "nord" => 4,
-                    _ => 0,
-                };
-                set_combobox_selection(combo, idx);
-            }
-            if let Some(&edit) = state.appearance_controls.get(3) {
-                set_edit_text(edit, &config.appearance.font.family);
-            }
-            if let Some(&edit) = state.appearance_controls.get(5) {
-                set_edit_text(edit, &config.appearance.font.size.to_string());
-            }
-            if let Some(&combo) = state.appearance_controls.get(8) {
-                let idx = match config.appearance.cursor_style {
-                    CursorStyleConfig::Block => 0,
-                    CursorStyleConfig::Underline => 1,
-                    CursorStyleConfig::Bar => 2,
-                };
-                set_combobox_selection(combo, idx);
-            }
-            if let Some(&checkbox) = state.appearance_controls.get(9) {
-                set_checkbox_state(checkbox, config.appearance.cursor_blink);
-            }
-            if let Some(&trackbar) = state.appearance_controls.get(11) {
-                let opacity_pct = (config.appearance.opacity * 100.0) as i32;
-                set_trackbar_value(trackbar, opacity_pct);
-            }
-            if let Some(&label) = state.appearance_controls.get(12) {
-                let opacity_pct = (config.appearance.opacity * 100.0) as i32;
-                set_edit_text(label, &format!("{}%", opacity_pct));
-            }
-            if let Some(&checkbox) = state.appearance_controls.get(13) {
-                set_checkbox_state(checkbox, config.appearance.bold_is_bright);
-            }
-
-            // Tabs tab
-            if let Some(&combo) = state.tabs_controls.get(1) {
-                let idx = match config.tabs.show_tab_bar {
-                    TabBarVisibility::Always => 0,
-                    TabBarVisibility::Multiple => 1,
-                    TabBarVisibility::Never => 2,
-                };
-                set_combobox_selection(combo, idx);
-            }
-            if let Some(&combo) = state.tabs_controls.get(3) {
-                let idx = match config.tabs.tab_bar_position {
-                    TabBarPosition::Top => 0,
-                    TabBarPosition::Bottom => 1,
-                };
-                set_combobox_selection(combo, idx);
-            }
-            if let Some(&combo) = state.tabs_controls.get(5) {
-                let idx = match config.tabs.new_tab_position {
-                    NewTabPosition::End => 0,
-                    NewTabPosition::AfterCurrent => 1,
-                };
-                set_combobox_selection(combo, idx);
-            }
-            if let Some(&checkbox) = state.tabs_controls.get(6) {
-                set_checkbox_state(checkbox, config.tabs.show_close_button);
+            for row in &state.setting_rows {
+                if let Some(value) = settings::value(config, row.setting.key) {
+                    show_setting_value(row, &value);
+                }
             }
 
             // Shortcuts tab - populate listview
             if let Some(&listview) = state.shortcuts_controls.first() {
-                let shortcuts = &config.shortcuts;
-                let items = [
-                    ("New Tab", &shortcuts.new_tab),
-                    ("Close Tab", &shortcuts.close_tab),
-                    ("Next Tab", &shortcuts.next_tab),
-                    ("Previous Tab", &shortcuts.prev_tab),
-                    ("New Window", &shortcuts.new_window),
-                    ("Close Window", &shortcuts.close_window),
-                    ("Copy", &shortcuts.copy),
-                    ("Paste", &shortcuts.paste),
-                    ("Select All", &shortcuts.select_all),
-                    ("Zoom In", &shortcuts.zoom_in),
-                    ("Zoom Out", &shortcuts.zoom_out),
-                    ("Zoom Reset", &shortcuts.zoom_reset),
-                    ("Find", &shortcuts.find),
-                    ("Reset Terminal", &shortcuts.reset),
-                ];
-
-                for (i, (action, shortcut)) in items.iter().enumerate() {
+                let mut shortcuts = config.shortcuts.clone();
+                for (i, action) in SHORTCUT_ACTIONS.iter().enumerate() {
                     let idx = add_listview_item(listview, i as i32, action);
-                    set_listview_subitem(listview, idx, 1, shortcut);
+                    if let Some(shortcut) = shortcut_mut(&mut shortcuts, i) {
+                        set_listview_subitem(listview, idx, 1, shortcut);
+                    }
                 }
             }
+
+            // Tools tab
+            if let Some(&listview) = state.tools_controls.first() {
+                fill_tools_list(listview, &state.tools);
+            }
         }
     });
 }
 
+/// Show `value` in the controls of a setting row
+fn show_setting_value(row: &SettingRow, value: &Value) {
+    match row.setting.kind {
+        SettingKind::Bool => set_checkbox_state(row.input, value.as_bool().unwrap_or(false)),
+        SettingKind::Ratio => {
+            let percent = (value.as_float().unwrap_or(1.0) * 100.0).round() as i32;
+            set_trackbar_value(row.input, percent);
+            if let Some(label) = row.value_label {
+                set_edit_text(label, &format!("{}%", percent));
+            }
+        }
+        SettingKind::Choice(_) | SettingKind::Theme => {
+            if let Some(index) = row.setting.choice_index(&row.choices, value) {
+                set_combobox_selection(row.input, index as i32);
+            }
+        }
+        SettingKind::Integer { .. } | SettingKind::Float { .. } | SettingKind::Text => {
+            set_edit_text(row.input, &settings::format_value(value));
+        }
+    }
+}
+
+/// Read the value shown in the controls of a setting row
+fn setting_value(row: &SettingRow) -> Result<Value, SettingError> {
+    match row.setting.kind {
+        SettingKind::Bool => Ok(Value::Boolean(get_checkbox_state(row.input))),
+        SettingKind::Ratio => row
+            .setting
+            .check(Value::Float(get_trackbar_value(row.input) as f64 / 100.0)),
+        SettingKind::Choice(_) | SettingKind::Theme => {
+            let value = get_combobox_selection(row.input)
+                .and_then(|index| row.choices.get(index as usize))
+                .map(|(value, _)| value.clone())
+                .unwrap_or_default();
+            row.setting.check(Value::String(value))
+        }
+        SettingKind::Integer { .. } | SettingKind::Float { .. } | SettingKind::Text => {
+            row.setting.parse(&get_edit_text(row.input))
+        }
+    }
+}
+
 /// Collect values from controls into config
 fn collect_config() -> Config {
     let mut config = Config::default();
@@ -1120,93 +1058,57 @@ fn collect_config() -> Config {
             // Start with current config
             config = state.config.clone();
 
-            // General tab
-            if let Some(&edit) = state.general_controls.get(1) {
-                if let Ok(lines) = get_edit_text(edit).parse() {
-                    config.general.scrollback_lines = lines;
+            for row in &state.setting_rows {
+                let result = setting_value(row)
+                    .and_then(|value| settings::set(&mut config, row.setting.key, value));
+                if let Err(e) = result {
+                    log::warn!("{}", e);
                 }
             }
-            if let Some(&checkbox) = state.general_controls.get(3) {
-                config.general.confirm_close_with_running = get_checkbox_state(checkbox);
-            }
-            if let Some(&checkbox) = state.general_controls.get(4) {
-                config.general.copy_on_select = get_checkbox_state(checkbox);
-            }
+        }
+    });
 
-            // Appearance tab
-            if let Some(&combo) = state.appearance_controls.get(1) {
-                config.appearance.theme = match get_combobox_selection(combo) {
-                    Some(0) => "dark".to_string(),
-                    Some(1) => "light".to_string(),
-                    Some(2) => "tokyo_night".to_string(),
-                    Some(3) => "dracula".to_string(),
-                    Some(4) => "nord".to_string(),
-                    _ => "dark".to_string(),
-                };
-            }
-            if let Some(&edit) = state.appearance_controls.get(3) {
-                config.appearance.font.family = get_edit_text(edit);
-            }
-            if let Some(&edit) = state.appearance_controls.get(5) {
-                if let Ok(size) = get_edit_text(edit).parse() {
-                    config.appearance.font.size = size;
-                }
-            }
-            if let Some(&combo) = state.appearance_controls.get(8) {
-                config.appearance.cursor_style = match get_combobox_selection(combo) {
-                    Some(0) => CursorStyleConfig::Block,
-                    Some(1) => CursorStyleConfig::Underline,
-                    Some(2) => CursorStyleConfig::Bar,
-                    _ => CursorStyleConfig::Block,
-                };
-            }
-            if let Some(&checkbox) = state.appearance_controls.get(9) {
-                config.appearance.cursor_blink = get_checkbox_state(checkbox);
-            }
-            if let Some(&trackbar) = state.appearance_controls.get(11) {
-                let opacity_pct = get_trackbar_value(trackbar);
-                config.appearance.opacity = (opacity_pct as f64) / 100.0;
-            }
-            if let Some(&checkbox) = state.appearance_controls.get(13) {
-                config.appearance.bold_is_bright = get_checkbox_state(checkbox);
-            }
+    config
+}
 
-            // Tabs tab
-            if let Some(&combo) = state.tabs_controls.get(1) {
-                config.tabs.show_tab_bar = match get_combobox_selection(combo) {
-                    Some(0) => TabBarVisibility::Always,
-                    Some(1) => TabBarVisibility::Multiple,
-                    Some(2) => TabBarVisibility::Never,
-                    _ => TabBarVisibility::Always,
-                };
-            }
-            if let Some(&combo) = state.tabs_controls.get(3) {
-                config.tabs.tab_bar_position = match get_combobox_selection(combo) {
-                    Some(0) => TabBarPosition::Top,
-                    Some(1) => TabBarPosition::Bottom,
-                    _ => TabBarPosition::Top,
-                };
-            }
-            if let Some(&combo) = state.tabs_controls.get(5) {
-                config.tabs.new_tab_position = match get_combobox_selection(combo) {
-                    Some(0) => NewTabPosition::End,
-                    Some(1) => NewTabPosition::AfterCurrent,
-                    _ => NewTabPosition::End,
-                };
-            }
-            if let Some(&checkbox) = state.tabs_controls.get(6) {
-                config.tabs.show_close_button = get_checkbox_state(checkbox);
-            }
+/// Show the edited appearance in open windows without saving it
+fn apply_preview() {
+    let config = collect_config();
+    DIALOG_STATE.with(|s| {
+        if let Some(ref mut state) = *s.borrow_mut() {
+            state.preview.apply(config);
         }
     });
+}
 
-    config
+/// Show the saved appearance again
+fn revert_preview() {
+    DIALOG_STATE.with(|s| {
+        if let Some(ref mut state) = *s.borrow_mut() {
+            state.preview.revert();
+        }
+    });
 }
 
 /// Save the current config
 fn save_config(_hwnd: HWND) -> Result<(), cterm_app::config::ConfigError> {
     let config = collect_config();
     cterm_app::save_config(&config)?;
+    DIALOG_STATE.with(|s| {
+        if let Some(ref mut state) = *s.borrow_mut() {
+            state.preview.commit(config);
+        }
+    });
+
+    let tools = DIALOG_STATE.with(|s| {
+        s.borrow()
+            .as_ref()
+            .map(|state| state.tools.clone())
+            .unwrap_or_default()
+    });
+    if let Err(e) = cterm_app::config::save_tool_shortcuts(&tools) {
+        log::error!("Failed to save tool shortcuts: {}", e);
+    }
 
     // If git sync is configured, commit and push
     if let Some(dir) = config_dir() {
@@ -1221,8 +1123,14 @@ fn save_config(_hwnd: HWND) -> Result<(), cterm_app::config::ConfigError> {
 }
 
 /// Handle WM_COMMAND
-fn handle_command(hwnd: HWND, id: i32) {
+fn handle_command(hwnd: HWND, id: i32, code: u16) {
     match id {
+        IDC_SEARCH if code == EN_CHANGE => {
+            update_search(hwnd);
+        }
+        id if (IDC_RESET_BASE..IDC_RESET_BASE + SETTINGS.len() as i32).contains(&id) => {
+            reset_setting(SETTINGS[(id - IDC_RESET_BASE) as usize].key);
+        }
         IDOK => {
             if save_config(hwnd).is_ok() {
                 unsafe { EndDialog(hwnd, IDOK as isize) };
@@ -1230,15 +1138,19 @@ fn handle_command(hwnd: HWND, id: i32) {
                 crate::dialogs::show_error(hwnd, "Error", "Failed to save configuration");
             }
         }
-        IDC_APPLY => {
-            if save_config(hwnd).is_err() {
-                crate::dialogs::show_error(hwnd, "Error", "Failed to save configuration");
-            }
-        }
+        // Apply previews; only OK saves
+        IDC_APPLY => apply_preview(),
         IDC_GIT_SYNC_NOW => {
             perform_sync_now(hwnd);
         }
+        IDC_SHORTCUT_SET => {
+            set_selected_shortcut(hwnd);
+        }
+        IDC_TOOL_ADD | IDC_TOOL_UPDATE | IDC_TOOL_REMOVE | IDC_TOOL_DEFAULTS => {
+            edit_tools(hwnd, id);
+        }
         IDCANCEL => {
+            revert_preview();
             unsafe { EndDialog(hwnd, IDCANCEL as isize) };
         }
         _ => {}
@@ -1260,21 +1172,153 @@ fn handle_notify(hwnd: HWND, nmhdr: &NMHDR) {
 
             show_tab(new_tab);
         }
+        LVN_ITEMCHANGED if nmhdr.idFrom == IDC_SHORTCUTS_LIST as usize => {
+            let selected = get_listview_selection(get_dialog_item(hwnd, IDC_SHORTCUTS_LIST));
+            let shortcut = selected.and_then(|idx| {
+                // Only borrow immutably: this also fires while the list is being filled
+                let mut shortcuts = DIALOG_STATE.with(|s| {
+                    s.borrow()
+                        .as_ref()
+                        .map(|state| state.config.shortcuts.clone())
+                })?;
+                shortcut_mut(&mut shortcuts, idx as usize).cloned()
+            });
+            let edit = get_dialog_item(hwnd, IDC_SHORTCUT_EDIT);
+            set_edit_text(edit, shortcut.as_deref().unwrap_or(""));
+            enable_control(edit, selected.is_some());
+            enable_control(get_dialog_item(hwnd, IDC_SHORTCUT_SET), selected.is_some());
+        }
+        LVN_ITEMCHANGED if nmhdr.idFrom == IDC_TOOLS_LIST as usize => {
+            let selected = get_listview_selection(get_dialog_item(hwnd, IDC_TOOLS_LIST));
+            let tool = selected.and_then(|idx| {
+                DIALOG_STATE.with(|s| {
+                    s.borrow()
+                        .as_ref()
+                        .and_then(|state| state.tools.get(idx as usize).cloned())
+                })
+            });
+            if let Some(tool) = tool {
+                set_edit_text(get_dialog_item(hwnd, IDC_TOOL_NAME), &tool.name);
+                set_edit_text(get_dialog_item(hwnd, IDC_TOOL_COMMAND), &tool.command);
+                set_edit_text(get_dialog_item(hwnd, IDC_TOOL_ARGS), &tool.args.join(" "));
+            }
+        }
         _ => {}
     }
 }
 
-/// Handle trackbar (slider) changes
-fn handle_trackbar_change(hwnd: HWND, trackbar: HWND) {
-    // Check if this is the opacity trackbar
-    let opacity_track = get_dialog_item(hwnd, IDC_OPACITY_TRACK);
-    if trackbar == opacity_track {
-        let value = get_trackbar_value(trackbar);
-        let label = get_dialog_item(hwnd, IDC_OPACITY_LABEL);
-        set_edit_text(label, &format!("{}%", value));
+/// Assign the text in the shortcut edit box to the selected action
+fn set_selected_shortcut(hwnd: HWND) {
+    let listview = get_dialog_item(hwnd, IDC_SHORTCUTS_LIST);
+    let Some(idx) = get_listview_selection(listview) else {
+        return;
+    };
+    let text = get_edit_text(get_dialog_item(hwnd, IDC_SHORTCUT_EDIT));
+    let text = text.trim();
+
+    let updated = DIALOG_STATE.with(|s| {
+        let mut state = s.borrow_mut();
+        match state
+            .as_mut()
+            .and_then(|state| shortcut_mut(&mut state.config.shortcuts, idx as usize))
+        {
+            Some(shortcut) => {
+                *shortcut = text.to_string();
+                true
+            }
+            None => false,
+        }
+    });
+    if updated {
+        set_listview_subitem(listview, idx, 1, text);
+    }
+}
+
+/// Show `tools` in the Tools tab list view
+fn fill_tools_list(listview: HWND, tools: &[ToolShortcutEntry]) {
+    clear_listview(listview);
+    for (i, tool) in tools.iter().enumerate() {
+        let idx = add_listview_item(listview, i as i32, &tool.name);
+        set_listview_subitem(listview, idx, 1, &tool.command);
+        set_listview_subitem(listview, idx, 2, &tool.args.join(" "));
+    }
+}
+
+/// Read the tool entry typed into the Tools tab edit boxes
+fn tool_from_fields(hwnd: HWND) -> Option<ToolShortcutEntry> {
+    let name = get_edit_text(get_dialog_item(hwnd, IDC_TOOL_NAME));
+    let command = get_edit_text(get_dialog_item(hwnd, IDC_TOOL_COMMAND));
+    if name.trim().is_empty() || command.trim().is_empty() {
+        return None;
+    }
+    let args = get_edit_text(get_dialog_item(hwnd, IDC_TOOL_ARGS));
+    Some(ToolShortcutEntry {
+        name: name.trim().to_string(),
+        command: command.trim().to_string(),
+        args: args.split_whitespace().map(str::to_string).collect(),
+    })
+}
+
+/// Handle the Tools tab buttons
+fn edit_tools(hwnd: HWND, id: i32) {
+    let listview = get_dialog_item(hwnd, IDC_TOOLS_LIST);
+    let selected = get_listview_selection(listview).map(|idx| idx as usize);
+
+    let new_selection = DIALOG_STATE.with(|s| {
+        let mut state = s.borrow_mut();
+        let state = state.as_mut()?;
+        match id {
+            IDC_TOOL_ADD => {
+                state.tools.push(tool_from_fields(hwnd)?);
+                Some(state.tools.len() - 1)
+            }
+            IDC_TOOL_UPDATE => {
+                let idx = selected.filter(|&idx| idx < state.tools.len())?;
+                state.tools[idx] = tool_from_fields(hwnd)?;
+                Some(idx)
+            }
+            IDC_TOOL_REMOVE => {
+                let idx = selected.filter(|&idx| idx < state.tools.len())?;
+                state.tools.remove(idx);
+                Some(idx.min(state.tools.len().saturating_sub(1)))
+            }
+            IDC_TOOL_DEFAULTS => {
+                state.tools = cterm_app::config::default_tool_shortcuts();
+                Some(0)
+            }
+            _ => None,
+        }
+    });
+
+    if let Some(idx) = new_selection {
+        DIALOG_STATE.with(|s| {
+            if let Some(ref state) = *s.borrow() {
+                fill_tools_list(listview, &state.tools);
+            }
+        });
+        if (idx as i32) < get_listview_item_count(listview) {
+            select_listview_item(listview, idx as i32);
+        }
     }
 }
 
+/// Handle trackbar (slider) changes
+fn handle_trackbar_change(_hwnd: HWND, trackbar: HWND) {
+    // Show the percentage next to the slider
+    DIALOG_STATE.with(|s| {
+        if let Some(ref state) = *s.borrow() {
+            let label = state
+                .setting_rows
+                .iter()
+                .filter(|row| row.input == trackbar)
+                .find_map(|row| row.value_label);
+            if let Some(label) = label {
+                set_edit_text(label, &format!("{}%", get_trackbar_value(trackbar)));
+            }
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -1284,5 +1328,28 @@ mod tests {
         assert_eq!(TAB_APPEARANCE, 1);
         assert_eq!(TAB_TABS, 2);
         assert_eq!(TAB_SHORTCUTS, 3);
+        assert_eq!(TAB_TOOLS, 4);
+        assert_eq!(TAB_GIT_SYNC, 5);
+    }
+
+    #[test]
+    fn test_setting_tabs_match_categories() {
+        use super::*;
+        for (i, category) in SettingCategory::ALL.iter().enumerate() {
+            assert_eq!(category.name(), ["General", "Appearance", "Tabs"][i]);
+        }
+        assert_eq!(SettingCategory::ALL.len() as i32, TAB_SHORTCUTS);
+    }
+
+    #[test]
+    fn test_shortcut_fields_match_actions() {
+        use super::*;
+        let mut shortcuts = ShortcutsConfig::default();
+        for i in 0..SHORTCUT_ACTIONS.len() {
+            *shortcut_mut(&mut shortcuts, i).expect("action has a field") = format!("F{}", i);
+        }
+        assert!(shortcut_mut(&mut shortcuts, SHORTCUT_ACTIONS.len()).is_none());
+        assert_eq!(shortcuts.new_tab, "F0");
+        assert_eq!(shortcuts.reset, "F13");
     }
 }
@@ -0,0 +1,249 @@
+//! New Serial Tab dialog for Win32
+//!
+//! Lists the COM ports found on this machine and lets the user pick the line
+//! settings before opening a serial console tab.
+
+use std::cell::RefCell;
+use std::ptr;
+
+use winapi::shared::basetsd::INT_PTR;
+use winapi::shared::minwindef::{LPARAM, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::*;
+
+use crate::dialog_utils::*;
+use cterm_app::config::SerialTabConfig;
+use cterm_core::{FlowControl, Parity, SerialConfig};
+
+// Control IDs
+const IDC_SERIAL_PORT: i32 = 1001;
+const IDC_SERIAL_BAUD: i32 = 1002;
+const IDC_SERIAL_DATA_BITS: i32 = 1003;
+const IDC_SERIAL_PARITY: i32 = 1004;
+const IDC_SERIAL_STOP_BITS: i32 = 1005;
+const IDC_SERIAL_FLOW: i32 = 1006;
+
+thread_local! {
+    static SERIAL_RESULT: RefCell<Option<SerialTabConfig>> = const { RefCell::new(None) };
+}
+
+/// Show the New Serial Tab dialog. Returns the chosen port and settings.
+pub fn show_serial_dialog(parent: HWND) -> Option<SerialTabConfig> {
+    SERIAL_RESULT.with(|r| {
+        *r.borrow_mut() = None;
+    });
+
+    let template = build_serial_dialog_template();
+    let _ret = unsafe {
+        DialogBoxIndirectParamW(
+            ptr::null_mut(),
+            template.as_ptr() as *const DLGTEMPLATE,
+            parent,
+            Some(serial_dialog_proc),
+            0,
+        )
+    };
+
+    SERIAL_RESULT.with(|r| r.borrow_mut().take())
+}
+
+fn build_serial_dialog_template() -> Vec<u8> {
+    let mut template = Vec::new();
+    let width: i16 = 200;
+    let height: i16 = 150;
+    let style = DS_MODALFRAME | DS_CENTER | WS_POPUP | WS_CAPTION | WS_SYSMENU | DS_SETFONT;
+    let ex_style = 0u32;
+    let c_dit = 0u16;
+
+    template.extend_from_slice(&style.to_le_bytes());
+    template.extend_from_slice(&ex_style.to_le_bytes());
+    template.extend_from_slice(&c_dit.to_le_bytes());
+    template.extend_from_slice(&0i16.to_le_bytes());
+    template.extend_from_slice(&0i16.to_le_bytes());
+    template.extend_from_slice(&width.to_le_bytes());
+    template.extend_from_slice(&height.to_le_bytes());
+
+    template.extend_from_slice(&[0u8, 0]); // menu
+    template.extend_from_slice(&[0u8, 0]); // class
+    let title = to_wide("New Serial Tab");
+    for c in &title {
+        template.extend_from_slice(&c.to_le_bytes());
+    }
+
+    align_to_word(&mut template);
+    template.extend_from_slice(&9u16.to_le_bytes());
+    let font = to_wide("Segoe UI");
+    for c in &font {
+        template.extend_from_slice(&c.to_le_bytes());
+    }
+
+    template
+}
+
+unsafe extern "system" fn serial_dialog_proc(
+    hwnd: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    _lparam: LPARAM,
+) -> INT_PTR {
+    match msg {
+        WM_INITDIALOG => {
+            init_serial_dialog(hwnd);
+            1
+        }
+        WM_COMMAND => {
+            let id = (wparam & 0xFFFF) as i32;
+            handle_serial_command(hwnd, id);
+            1
+        }
+        WM_CLOSE => {
+            EndDialog(hwnd, IDCANCEL as isize);
+            1
+        }
+        _ => 0,
+    }
+}
+
+unsafe fn init_serial_dialog(hwnd: HWND) {
+    let defaults = SerialTabConfig::default();
+
+    let mut rect = std::mem::zeroed();
+    GetClientRect(hwnd, &mut rect);
+    let dlg_width = rect.right - rect.left;
+    let dlg_height = rect.bottom - rect.top;
+    let margin = 10;
+    let button_height = 25;
+    let button_width = 80;
+    let label_width = 80;
+    let field_x = margin + label_width + 5;
+    let field_width = dlg_width - field_x - margin;
+    let row = |i: i32| margin + i * 30;
+
+    // Port and baud rate also accept values that are not listed
+    create_label(hwnd, -1, "Port:", margin, row(0) + 3, label_width, 20);
+    let port = create_editable_combobox(hwnd, IDC_SERIAL_PORT, field_x, row(0), field_width, 22);
+    for name in cterm_core::available_ports() {
+        add_combobox_item(port, &name);
+    }
+    set_combobox_selection(port, 0);
+
+    create_label(hwnd, -1, "Baud rate:", margin, row(1) + 3, label_width, 20);
+    let baud = create_editable_combobox(hwnd, IDC_SERIAL_BAUD, field_x, row(1), field_width, 22);
+    for rate in SerialConfig::BAUD_RATES {
+        add_combobox_item(baud, &rate.to_string());
+    }
+    set_combobox_text(baud, &defaults.baud_rate.to_string());
+
+    create_label(hwnd, -1, "Data bits:", margin, row(2) + 3, label_width, 20);
+    let data_bits = create_combobox(hwnd, IDC_SERIAL_DATA_BITS, field_x, row(2), field_width, 22);
+    for bits in 5..=8 {
+        add_combobox_item(data_bits, &bits.to_string());
+    }
+    set_combobox_selection(data_bits, defaults.data_bits as i32 - 5);
+
+    create_label(hwnd, -1, "Parity:", margin, row(3) + 3, label_width, 20);
+    let parity = create_combobox(hwnd, IDC_SERIAL_PARITY, field_x, row(3), field_width, 22);
+    for p in Parity::ALL {
+        add_combobox_item(parity, p.name());
+    }
+    set_combobox_selection(parity, 0);
+
+    create_label(hwnd, -1, "Stop bits:", margin, row(4) + 3, label_width, 20);
+    let stop_bits = create_combobox(hwnd, IDC_SERIAL_STOP_BITS, field_x, row(4), field_width, 22);
+    add_combobox_item(stop_bits, "1");
+    add_combobox_item(stop_bits, "2");
+    set_combobox_selection(stop_bits, defaults.stop_bits as i32 - 1);
+
+    create_label(
+        hwnd,
+        -1,
+        "Flow control:",
+        margin,
+        row(5) + 3,
+        label_width,
+        20,
+    );
+    let flow = create_combobox(hwnd, IDC_SERIAL_FLOW, field_x, row(5), field_width, 22);
+    for f in FlowControl::ALL {
+        add_combobox_item(flow, f.name());
+    }
+    set_combobox_selection(flow, 0);
+
+    // Cancel / Open buttons at bottom
+    let btn_y = dlg_height - button_height - margin;
+    create_button(
+        hwnd,
+        IDCANCEL,
+        "Cancel",
+        dlg_width - margin - button_width * 2 - 10,
+        btn_y,
+        button_width,
+        button_height,
+    );
+    create_default_button(
+        hwnd,
+        IDOK,
+        "Open",
+        dlg_width - margin - button_width,
+        btn_y,
+        button_width,
+        button_height,
+    );
+}
+
+/// Read the dialog's fields, `None` while the port or baud rate is unusable
+fn read_serial_fields(hwnd: HWND) -> Option<SerialTabConfig> {
+    let defaults = SerialTabConfig::default();
+    let path = get_combobox_text(get_dialog_item(hwnd, IDC_SERIAL_PORT))
+        .trim()
+        .to_string();
+    let baud_rate = get_combobox_text(get_dialog_item(hwnd, IDC_SERIAL_BAUD))
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|&rate| rate > 0)?;
+    if path.is_empty() {
+        return None;
+    }
+
+    let selection = |id| get_combobox_selection(get_dialog_item(hwnd, id)).map(|i| i as usize);
+    Some(SerialTabConfig {
+        path,
+        baud_rate,
+        data_bits: selection(IDC_SERIAL_DATA_BITS).map_or(defaults.data_bits, |i| i as u8 + 5),
+        parity: selection(IDC_SERIAL_PARITY)
+            .and_then(|i| Parity::ALL.get(i).copied())
+            .unwrap_or_default(),
+        stop_bits: selection(IDC_SERIAL_STOP_BITS).map_or(defaults.stop_bits, |i| i as u8 + 1),
+        flow_control: selection(IDC_SERIAL_FLOW)
+            .and_then(|i| FlowControl::ALL.get(i).copied())
+            .unwrap_or_default(),
+    })
+}
+
+unsafe fn handle_serial_command(hwnd: HWND, id: i32) {
+    match id {
+        IDOK => match read_serial_fields(hwnd) {
+            Some(serial) => {
+                SERIAL_RESULT.with(|r| {
+                    *r.borrow_mut() = Some(serial);
+                });
+                EndDialog(hwnd, IDOK as isize);
+            }
+            // Keep the dialog open until there is something to connect to
+            None => {
+                MessageBeep(MB_ICONWARNING);
+            }
+        },
+        IDCANCEL => {
+            EndDialog(hwnd, IDCANCEL as isize);
+        }
+        _ => {}
+    }
+}
+
+fn align_to_word(v: &mut Vec<u8>) {
+    while !v.len().is_multiple_of(2) {
+        v.push(0);
+    }
+}
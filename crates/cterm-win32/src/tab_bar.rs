@@ -2,6 +2,9 @@
 //!
 //! Provides a tab bar similar to modern browsers with close buttons and indicators.
 
+use cterm_app::config::{TabBarPosition, TabBarVisibility, TabsConfig, WidgetsConfig};
+use cterm_app::status_segments;
+use cterm_app::tab_widgets::{self, WidgetKind};
 use cterm_core::color::Rgb;
 use cterm_ui::theme::Theme;
 use windows::core::Interface;
@@ -11,6 +14,7 @@ use windows::Win32::Graphics::Direct2D::{
 };
 use windows::Win32::Graphics::DirectWrite::{
     IDWriteFactory, IDWriteTextFormat, IDWriteTextLayout, DWRITE_PARAGRAPH_ALIGNMENT_CENTER,
+    DWRITE_TEXT_METRICS,
 };
 
 use crate::dpi::DpiInfo;
@@ -29,6 +33,10 @@ const NEW_TAB_BUTTON_WIDTH: f32 = 32.0;
 const MIN_TAB_WIDTH: f32 = 80.0;
 /// Maximum tab width
 const MAX_TAB_WIDTH: f32 = 200.0;
+/// Gap between widgets at the end of the bar
+const WIDGET_SPACING: f32 = 12.0;
+/// Color of a widget that needs attention
+const WIDGET_EMPHASIS_COLOR: Rgb = Rgb::new(0xe8, 0xa7, 0x35);
 
 /// Information about a single tab
 #[derive(Debug, Clone)]
@@ -37,6 +45,12 @@ pub struct TabInfo {
     pub title: String,
     pub color: Option<Rgb>,
     pub has_bell: bool,
+    /// Output arrived while the tab was in the background
+    pub has_unread: bool,
+    /// Match count of the tab's watch expression, if it has one
+    pub watch_count: Option<u64>,
+    /// Status segment texts for the title format
+    pub segments: Vec<(String, String)>,
     pub is_active: bool,
 }
 
@@ -44,7 +58,8 @@ pub struct TabInfo {
 #[derive(Debug, Clone, Copy)]
 pub struct TabRect {
     pub bounds: D2D_RECT_F,
-    pub close_button: D2D_RECT_F,
+    /// `None` when close buttons are turned off
+    pub close_button: Option<D2D_RECT_F>,
 }
 
 /// Tab bar state and rendering
@@ -58,6 +73,18 @@ pub struct TabBar {
     hover_tab_id: Option<u64>,
     hover_close_button: bool,
     visible: bool,
+    /// Keep the bar hidden regardless of tab count (presentation mode)
+    force_hidden: bool,
+    /// When the bar is shown, from the tabs config
+    visibility: TabBarVisibility,
+    /// Whether the bar is above or below the terminal
+    position: TabBarPosition,
+    show_close_button: bool,
+    /// Tab label format, from the tabs config
+    title_format: String,
+    /// Clock, timer and battery widgets to show after the tabs
+    widgets: WidgetsConfig,
+    widget_rects: Vec<(WidgetKind, D2D_RECT_F)>,
 }
 
 impl TabBar {
@@ -72,7 +99,14 @@ impl TabBar {
             dpi: DpiInfo::default(),
             hover_tab_id: None,
             hover_close_button: false,
-            visible: false, // Initially hidden, shown when there are multiple tabs
+            visible: false, // Initially hidden, shown once tabs are added
+            force_hidden: false,
+            visibility: TabBarVisibility::default(),
+            position: TabBarPosition::default(),
+            show_close_button: true,
+            title_format: String::from("{title}"),
+            widgets: WidgetsConfig::default(),
+            widget_rects: Vec::new(),
         }
     }
 
@@ -95,10 +129,49 @@ impl TabBar {
         self.visible
     }
 
-    /// Update visibility based on tab count
+    /// Update visibility based on tab count and the configured visibility
     pub fn update_visibility(&mut self) {
-        // Show tab bar only if there are multiple tabs
-        self.visible = self.tabs.len() > 1;
+        self.visible = !self.tabs.is_empty()
+            && self.visibility.shows(self.tabs.len(), self.widgets.any())
+            && !self.force_hidden;
+    }
+
+    /// Apply the tab bar visibility, position, close button and title format
+    /// settings
+    pub fn set_config(&mut self, config: &TabsConfig) {
+        self.visibility = config.show_tab_bar;
+        self.position = config.tab_bar_position;
+        self.show_close_button = config.show_close_button;
+        self.title_format = config.title_format.clone();
+        self.update_visibility();
+    }
+
+    /// Whether the bar is above or below the terminal
+    pub fn position(&self) -> TabBarPosition {
+        self.position
+    }
+
+    /// Hide the tab bar even when several tabs are open
+    pub fn set_force_hidden(&mut self, hidden: bool) {
+        self.force_hidden = hidden;
+        self.update_visibility();
+    }
+
+    /// Choose the widgets shown after the tabs
+    pub fn set_widgets(&mut self, widgets: WidgetsConfig) {
+        self.widgets = widgets;
+        if !widgets.any() {
+            self.widget_rects.clear();
+        }
+        self.update_visibility();
+    }
+
+    /// Widget under a point, as of the last render
+    pub fn widget_at(&self, x: f32, y: f32) -> Option<WidgetKind> {
+        self.widget_rects
+            .iter()
+            .find(|(_, rect)| point_in_rect(x, y, rect))
+            .map(|(kind, _)| *kind)
     }
 
     /// Update DPI
@@ -113,6 +186,9 @@ impl TabBar {
             title: title.to_string(),
             color: None,
             has_bell: false,
+            has_unread: false,
+            watch_count: None,
+            segments: Vec::new(),
             is_active: false,
         });
         self.update_visibility();
@@ -143,6 +219,18 @@ impl TabBar {
         }
     }
 
+    /// Update the status segment texts shown in a tab's title; returns
+    /// whether they changed
+    pub fn set_segments(&mut self, id: u64, segments: Vec<(String, String)>) -> bool {
+        match self.tabs.iter_mut().find(|t| t.id == id) {
+            Some(tab) if tab.segments != segments => {
+                tab.segments = segments;
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Set tab color
     pub fn set_color(&mut self, id: u64, color: Option<Rgb>) {
         if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == id) {
@@ -157,6 +245,20 @@ impl TabBar {
         }
     }
 
+    /// Mark a tab as having output the user hasn't seen
+    pub fn set_unread(&mut self, id: u64, has_unread: bool) {
+        if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == id) {
+            tab.has_unread = has_unread;
+        }
+    }
+
+    /// Set the watch expression match count, `None` without a watch
+    pub fn set_watch_count(&mut self, id: u64, count: Option<u64>) {
+        if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == id) {
+            tab.watch_count = count;
+        }
+    }
+
     /// Clear bell indicator
     pub fn clear_bell(&mut self, id: u64) {
         self.set_bell(id, false);
@@ -182,7 +284,10 @@ impl TabBar {
 
         // Check tabs (in reverse order so foreground tabs are hit first)
         for (id, rect) in self.tab_rects.iter().rev() {
-            if point_in_rect(x, y, &rect.close_button) {
+            if rect
+                .close_button
+                .is_some_and(|close| point_in_rect(x, y, &close))
+            {
                 return (Some(*id), true, false);
             }
             if point_in_rect(x, y, &rect.bounds) {
@@ -228,12 +333,12 @@ impl TabBar {
             };
 
             // Close button in the right side of the tab
-            let close_button = D2D_RECT_F {
+            let close_button = self.show_close_button.then(|| D2D_RECT_F {
                 left: bounds.right - close_size - 4.0,
                 top: (height - close_size) / 2.0,
                 right: bounds.right - 4.0,
                 bottom: (height + close_size) / 2.0,
-            };
+            });
 
             self.tab_rects.push((
                 tab.id,
@@ -266,11 +371,34 @@ impl TabBar {
             return Ok(());
         }
 
-        // Calculate layout
-        self.calculate_layout(width);
-
         let height = self.dpi.scale_f32(TAB_BAR_HEIGHT as f32);
 
+        // Lay out the widgets from the right edge, leaving the rest to the tabs
+        let spacing = self.dpi.scale_f32(WIDGET_SPACING);
+        let mut widgets = Vec::new();
+        let mut right = width;
+        self.widget_rects.clear();
+        for widget in tab_widgets::widgets(&self.widgets).into_iter().rev() {
+            let text: Vec<u16> = widget.text.encode_utf16().collect();
+            let layout: IDWriteTextLayout =
+                unsafe { dwrite.CreateTextLayout(&text, text_format, width, height)? };
+            unsafe { layout.SetParagraphAlignment(DWRITE_PARAGRAPH_ALIGNMENT_CENTER)? };
+            let mut metrics = DWRITE_TEXT_METRICS::default();
+            unsafe { layout.GetMetrics(&mut metrics)? };
+            let rect = D2D_RECT_F {
+                left: right - spacing - metrics.widthIncludingTrailingWhitespace,
+                top: 0.0,
+                right: right - spacing,
+                bottom: height,
+            };
+            right = rect.left;
+            self.widget_rects.push((widget.kind, rect));
+            widgets.push((layout, rect, widget.emphasis));
+        }
+
+        // Calculate layout
+        self.calculate_layout(right);
+
         // Cast to parent interface to access render methods
         let base: ID2D1RenderTarget = rt.cast()?;
 
@@ -293,15 +421,37 @@ impl TabBar {
         // Draw new tab button
         self.render_new_tab_button(rt)?;
 
-        // Draw bottom border
+        // Draw widgets
+        for (layout, rect, emphasis) in &widgets {
+            let color = if *emphasis {
+                WIDGET_EMPHASIS_COLOR
+            } else {
+                self.theme.ui.tab_inactive_text
+            };
+            let brush = unsafe { base.CreateSolidColorBrush(&rgb_to_d2d_color(color), None)? };
+            let origin = D2D_POINT_2F {
+                x: rect.left,
+                y: rect.top,
+            };
+            unsafe { base.DrawTextLayout(origin, layout, &brush, Default::default()) };
+        }
+
+        // Draw the border facing the terminal
+        let border_y = match self.position {
+            TabBarPosition::Top => height,
+            TabBarPosition::Bottom => 0.0,
+        };
         let border_color = rgb_to_d2d_color(self.theme.ui.border);
         let border_brush = unsafe { base.CreateSolidColorBrush(&border_color, None)? };
         unsafe {
             base.DrawLine(
-                D2D_POINT_2F { x: 0.0, y: height },
+                D2D_POINT_2F {
+                    x: 0.0,
+                    y: border_y,
+                },
                 D2D_POINT_2F {
                     x: width,
-                    y: height,
+                    y: border_y,
                 },
                 &border_brush,
                 1.0,
@@ -367,8 +517,8 @@ impl TabBar {
             unsafe { base.FillRectangle(&indicator_rect, &indicator_brush) };
         }
 
-        // Title text
-        let text_color = if tab.is_active {
+        // Title text, highlighted while it has unread output
+        let text_color = if tab.is_active || tab.has_unread {
             self.theme.ui.tab_active_text
         } else {
             self.theme.ui.tab_inactive_text
@@ -377,15 +527,24 @@ impl TabBar {
         let text_brush =
             unsafe { base.CreateSolidColorBrush(&rgb_to_d2d_color(text_color), None)? };
 
-        // Create text with bell indicator if needed
-        let display_title = if tab.has_bell {
-            format!("* {}", tab.title)
+        // Create text with bell or activity indicator and watch count if needed
+        let title = status_segments::format_title(&self.title_format, &tab.title, &tab.segments);
+        let mut display_title = if tab.has_bell {
+            format!("* {}", title)
+        } else if tab.has_unread {
+            format!("\u{2022} {}", title)
         } else {
-            tab.title.clone()
+            title
         };
+        if let Some(count) = tab.watch_count {
+            display_title.push_str(&format!(" [{count}]"));
+        }
 
         let text_wide: Vec<u16> = display_title.encode_utf16().collect();
-        let close_size = self.dpi.scale_f32(CLOSE_BUTTON_SIZE);
+        let close_size = match rect.close_button {
+            Some(_) => self.dpi.scale_f32(CLOSE_BUTTON_SIZE),
+            None => 0.0,
+        };
         let text_width = rect.bounds.right - rect.bounds.left - close_size - 8.0;
 
         let layout: IDWriteTextLayout = unsafe {
@@ -410,6 +569,9 @@ impl TabBar {
         unsafe { base.DrawTextLayout(text_origin, &layout, &text_brush, Default::default()) };
 
         // Close button
+        let Some(close_button) = rect.close_button else {
+            return Ok(());
+        };
         let close_hover = self.hover_tab_id == Some(tab.id) && self.hover_close_button;
         let close_color = if close_hover {
             Rgb::new(255, 100, 100)
@@ -424,12 +586,12 @@ impl TabBar {
         unsafe {
             base.DrawLine(
                 D2D_POINT_2F {
-                    x: rect.close_button.left + padding,
-                    y: rect.close_button.top + padding,
+                    x: close_button.left + padding,
+                    y: close_button.top + padding,
                 },
                 D2D_POINT_2F {
-                    x: rect.close_button.right - padding,
-                    y: rect.close_button.bottom - padding,
+                    x: close_button.right - padding,
+                    y: close_button.bottom - padding,
                 },
                 &close_brush,
                 1.5,
@@ -437,12 +599,12 @@ impl TabBar {
             );
             base.DrawLine(
                 D2D_POINT_2F {
-                    x: rect.close_button.right - padding,
-                    y: rect.close_button.top + padding,
+                    x: close_button.right - padding,
+                    y: close_button.top + padding,
                 },
                 D2D_POINT_2F {
-                    x: rect.close_button.left + padding,
-                    y: rect.close_button.bottom - padding,
+                    x: close_button.left + padding,
+                    y: close_button.bottom - padding,
                 },
                 &close_brush,
                 1.5,
@@ -545,7 +707,14 @@ mod tests {
         // Add first tab
         tab_bar.add_tab(1, "Tab 1");
         assert_eq!(tab_bar.tab_count(), 1);
-        assert!(!tab_bar.is_visible()); // Still hidden with one tab
+        assert!(tab_bar.is_visible()); // Always shown by default
+
+        // Hidden with one tab when only shown for multiple
+        tab_bar.set_config(&TabsConfig {
+            show_tab_bar: TabBarVisibility::Multiple,
+            ..TabsConfig::default()
+        });
+        assert!(!tab_bar.is_visible());
 
         // Add second tab
         tab_bar.add_tab(2, "Tab 2");
@@ -563,6 +732,28 @@ mod tests {
         assert!(!tab_bar.is_visible());
     }
 
+    #[test]
+    fn test_tab_bar_close_buttons() {
+        let theme = Theme::dark();
+        let mut tab_bar = TabBar::new(&theme);
+        tab_bar.add_tab(1, "Tab 1");
+        tab_bar.add_tab(2, "Tab 2");
+
+        tab_bar.calculate_layout(800.0);
+        let close = tab_bar.tab_rects[0].1.close_button.unwrap();
+        let (x, y) = (close.left + 1.0, close.top + 1.0);
+        assert_eq!(tab_bar.hit_test(x, y), (Some(1), true, false));
+
+        // Without close buttons the whole tab selects it
+        tab_bar.set_config(&TabsConfig {
+            show_close_button: false,
+            ..TabsConfig::default()
+        });
+        tab_bar.calculate_layout(800.0);
+        assert!(tab_bar.tab_rects[0].1.close_button.is_none());
+        assert_eq!(tab_bar.hit_test(x, y), (Some(1), false, false));
+    }
+
     #[test]
     fn test_point_in_rect() {
         let rect = D2D_RECT_F {
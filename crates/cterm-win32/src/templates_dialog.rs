@@ -36,6 +36,7 @@ const IDC_TMPL_COLOR_BTN: i32 = 1025;
 const IDC_TMPL_UNIQUE: i32 = 1026;
 const IDC_TMPL_KEEPOPEN: i32 = 1028;
 const IDC_TMPL_REMOTE: i32 = 1027;
+const IDC_TMPL_PROFILE: i32 = 1019;
 
 // Control IDs - Docker tab
 const IDC_DOCKER_MODE: i32 = 1030;
@@ -117,6 +118,8 @@ struct DialogState {
     ssh_controls: Vec<HWND>,
     /// Remote names loaded from config (for remote combo)
     remote_names: Vec<String>,
+    /// Profile names loaded from config (for profile combo)
+    profile_names: Vec<String>,
 }
 
 // Thread-local storage for dialog state
@@ -130,12 +133,9 @@ thread_local! {
 pub fn show_templates_dialog(parent: HWND) -> bool {
     // Load current templates and config
     let templates = cterm_app::load_sticky_tabs().unwrap_or_default();
-    let remote_names: Vec<String> = cterm_app::config::load_config()
-        .unwrap_or_default()
-        .remotes
-        .iter()
-        .map(|r| r.name.clone())
-        .collect();
+    let config = cterm_app::config::load_config().unwrap_or_default();
+    let remote_names: Vec<String> = config.remotes.iter().map(|r| r.name.clone()).collect();
+    let profile_names: Vec<String> = config.profiles.iter().map(|p| p.name.clone()).collect();
 
     DIALOG_STATE.with(|s| {
         *s.borrow_mut() = Some(DialogState {
@@ -146,6 +146,7 @@ pub fn show_templates_dialog(parent: HWND) -> bool {
             docker_controls: Vec::new(),
             ssh_controls: Vec::new(),
             remote_names,
+            profile_names,
         });
     });
 
@@ -528,6 +529,29 @@ unsafe fn create_general_controls(hwnd: HWND, x: i32, y: i32, w: i32, _h: i32) {
     set_combobox_selection(remote_combo, 0);
     controls.push(remote_combo);
 
+    // Profile
+    cy += row_height + 5;
+    controls.push(create_label(
+        hwnd,
+        -1,
+        "Profile:",
+        x,
+        cy + 3,
+        label_width,
+        18,
+    ));
+    let profile_combo = create_combobox(hwnd, IDC_TMPL_PROFILE, x + label_width + 10, cy, 200, 22);
+    add_combobox_item(profile_combo, "None");
+    DIALOG_STATE.with(|s| {
+        if let Some(ref state) = *s.borrow() {
+            for name in &state.profile_names {
+                add_combobox_item(profile_combo, name);
+            }
+        }
+    });
+    set_combobox_selection(profile_combo, 0);
+    controls.push(profile_combo);
+
     // Checkboxes
     cy += row_height + 10;
     controls.push(create_checkbox(
@@ -892,11 +916,24 @@ fn load_current_template() {
                 };
                 set_combobox_selection(combo, idx);
             }
+            // Profile combo
+            if let Some(&combo) = state.general_controls.get(16) {
+                let idx = match &template.profile {
+                    Some(profile_name) => state
+                        .profile_names
+                        .iter()
+                        .position(|n| n == profile_name)
+                        .map(|i| (i + 1) as i32)
+                        .unwrap_or(0),
+                    None => 0,
+                };
+                set_combobox_selection(combo, idx);
+            }
 
-            if let Some(&checkbox) = state.general_controls.get(15) {
+            if let Some(&checkbox) = state.general_controls.get(17) {
                 set_checkbox_state(checkbox, template.unique);
             }
-            if let Some(&checkbox) = state.general_controls.get(16) {
+            if let Some(&checkbox) = state.general_controls.get(18) {
                 set_checkbox_state(checkbox, template.keep_open);
             }
 
@@ -1044,11 +1081,18 @@ fn save_current_template() {
                     _ => None,
                 };
             }
+            // Profile combo
+            if let Some(&combo) = state.general_controls.get(16) {
+                template.profile = match get_combobox_selection(combo) {
+                    Some(idx) if idx > 0 => state.profile_names.get((idx - 1) as usize).cloned(),
+                    _ => None,
+                };
+            }
 
-            if let Some(&checkbox) = state.general_controls.get(15) {
+            if let Some(&checkbox) = state.general_controls.get(17) {
                 template.unique = get_checkbox_state(checkbox);
             }
-            if let Some(&checkbox) = state.general_controls.get(16) {
+            if let Some(&checkbox) = state.general_controls.get(18) {
                 template.keep_open = get_checkbox_state(checkbox);
             }
 
@@ -3,10 +3,18 @@
 //! Hardware-accelerated terminal rendering using Direct2D and DirectWrite.
 
 use std::collections::HashMap;
+use std::sync::Arc;
+
+use cterm_app::link_preview::LinkPreview;
+use cterm_app::session::PaneRect;
 
 use cterm_core::color::{Color, Rgb};
-use cterm_core::{Cell, CellAttrs, Screen, Selection};
+use cterm_core::{AttrOverrides, Cell, CellAttrs, HintMode, Screen, Selection};
+use cterm_ui::gutter::{gutter_color, GUTTER_WIDTH};
+use cterm_ui::scroll_marks::{scroll_mark_ticks, MARK_HEIGHT};
+use cterm_ui::status::{StatusSegment, StatusStrip};
 use cterm_ui::theme::Theme;
+use cterm_ui::utils::Magnifier;
 use windows::core::{Interface, PCWSTR};
 use windows::Win32::Foundation::{HWND, RECT};
 use windows::Win32::Graphics::Direct2D::Common::{
@@ -14,20 +22,24 @@ use windows::Win32::Graphics::Direct2D::Common::{
     D2D_SIZE_U,
 };
 use windows::Win32::Graphics::Direct2D::{
-    D2D1CreateFactory, ID2D1Factory, ID2D1HwndRenderTarget, ID2D1RenderTarget,
-    ID2D1SolidColorBrush, D2D1_ANTIALIAS_MODE_PER_PRIMITIVE, D2D1_FACTORY_OPTIONS,
-    D2D1_FACTORY_TYPE_SINGLE_THREADED, D2D1_FEATURE_LEVEL_DEFAULT,
-    D2D1_HWND_RENDER_TARGET_PROPERTIES, D2D1_PRESENT_OPTIONS_NONE, D2D1_RENDER_TARGET_PROPERTIES,
-    D2D1_RENDER_TARGET_TYPE_DEFAULT, D2D1_RENDER_TARGET_USAGE_NONE,
-    D2D1_TEXT_ANTIALIAS_MODE_CLEARTYPE,
+    D2D1CreateFactory, ID2D1Bitmap, ID2D1Factory, ID2D1HwndRenderTarget, ID2D1RenderTarget,
+    ID2D1SolidColorBrush, D2D1_ANTIALIAS_MODE_ALIASED, D2D1_ANTIALIAS_MODE_PER_PRIMITIVE,
+    D2D1_BITMAP_INTERPOLATION_MODE_LINEAR, D2D1_BITMAP_PROPERTIES, D2D1_DRAW_TEXT_OPTIONS_CLIP,
+    D2D1_DRAW_TEXT_OPTIONS_NONE, D2D1_FACTORY_OPTIONS, D2D1_FACTORY_TYPE_SINGLE_THREADED,
+    D2D1_FEATURE_LEVEL_DEFAULT, D2D1_HWND_RENDER_TARGET_PROPERTIES, D2D1_PRESENT_OPTIONS_NONE,
+    D2D1_RENDER_TARGET_PROPERTIES, D2D1_RENDER_TARGET_TYPE_DEFAULT, D2D1_RENDER_TARGET_USAGE_NONE,
+    D2D1_ROUNDED_RECT, D2D1_TEXT_ANTIALIAS_MODE, D2D1_TEXT_ANTIALIAS_MODE_CLEARTYPE,
+    D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE,
 };
 use windows::Win32::Graphics::DirectWrite::{
     DWriteCreateFactory, IDWriteFactory, IDWriteTextFormat, IDWriteTextLayout,
     DWRITE_FACTORY_TYPE_SHARED, DWRITE_FONT_STRETCH_NORMAL, DWRITE_FONT_STYLE_NORMAL,
-    DWRITE_FONT_WEIGHT_BOLD, DWRITE_FONT_WEIGHT_NORMAL, DWRITE_TEXT_METRICS,
+    DWRITE_FONT_WEIGHT_BOLD, DWRITE_FONT_WEIGHT_NORMAL, DWRITE_TEXT_ALIGNMENT_TRAILING,
+    DWRITE_TEXT_METRICS, DWRITE_TEXT_RANGE,
 };
 use windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT_B8G8R8A8_UNORM;
 use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
+use windows_numerics::Matrix3x2;
 
 use crate::dpi::DpiInfo;
 
@@ -65,6 +77,28 @@ pub struct TerminalRenderer {
     hwnd: HWND,
     /// Optional background color override (from template)
     background_override: Option<Rgb>,
+    /// Opacity of the default background (1.0 is opaque)
+    opacity: f32,
+    /// Surface magnification (scales the view, keeps the grid size)
+    magnifier: Magnifier,
+    /// Minimum text contrast ratio (presentation mode); 1.0 disables
+    minimum_contrast: f64,
+    /// Replacements for blink, concealed and dim text
+    attr_overrides: AttrOverrides,
+    /// Whether to draw the exit status gutter beside finished commands
+    command_gutter: bool,
+    /// Whether to draw the scrollbar overlay while there is scrollback
+    show_scrollbar: bool,
+    /// Keystroke overlay text drawn in the bottom-right corner
+    keystroke_overlay: Option<String>,
+    /// Status strip contents (empty hides the strip)
+    status: Vec<StatusSegment>,
+    /// Active hint mode, whose labels are drawn over their targets
+    hints: Option<HintMode>,
+    /// Hover preview of a link or image path and the point it is shown at
+    link_preview: Option<(Arc<LinkPreview>, f32, f32)>,
+    /// The preview's image, created on the render target when first drawn
+    link_preview_bitmap: Option<ID2D1Bitmap>,
 }
 
 impl TerminalRenderer {
@@ -101,6 +135,17 @@ impl TerminalRenderer {
             brush_cache: HashMap::new(),
             hwnd,
             background_override: None,
+            opacity: 1.0,
+            magnifier: Magnifier::new(),
+            minimum_contrast: 1.0,
+            attr_overrides: AttrOverrides::default(),
+            command_gutter: true,
+            show_scrollbar: true,
+            keystroke_overlay: None,
+            status: Vec::new(),
+            hints: None,
+            link_preview: None,
+            link_preview_bitmap: None,
         };
 
         renderer.create_device_resources()?;
@@ -147,12 +192,13 @@ impl TerminalRenderer {
         };
 
         unsafe {
-            render_target.SetTextAntialiasMode(D2D1_TEXT_ANTIALIAS_MODE_CLEARTYPE);
+            render_target.SetTextAntialiasMode(self.text_antialias_mode());
             render_target.SetAntialiasMode(D2D1_ANTIALIAS_MODE_PER_PRIMITIVE);
         }
 
         self.render_target = Some(render_target);
         self.brush_cache.clear();
+        self.link_preview_bitmap = None;
 
         // Create text format
         self.create_text_format()?;
@@ -297,6 +343,80 @@ impl TerminalRenderer {
         });
     }
 
+    /// Set the opacity of the default background (1.0 is opaque)
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = opacity as f32;
+        if let Some(ref rt) = self.render_target {
+            unsafe { rt.SetTextAntialiasMode(self.text_antialias_mode()) };
+        }
+    }
+
+    /// ClearType needs an opaque background to blend against, so text over
+    /// a translucent one is drawn with grayscale antialiasing
+    fn text_antialias_mode(&self) -> D2D1_TEXT_ANTIALIAS_MODE {
+        if self.opacity < 1.0 {
+            D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE
+        } else {
+            D2D1_TEXT_ANTIALIAS_MODE_CLEARTYPE
+        }
+    }
+
+    /// Get the current magnification state
+    pub fn magnifier(&self) -> &Magnifier {
+        &self.magnifier
+    }
+
+    /// Get mutable access to the magnification state
+    pub fn magnifier_mut(&mut self) -> &mut Magnifier {
+        &mut self.magnifier
+    }
+
+    /// Set the minimum text contrast ratio (1.0 disables)
+    pub fn set_minimum_contrast(&mut self, ratio: f64) {
+        self.minimum_contrast = ratio;
+    }
+
+    /// Set how blink, concealed and dim text are drawn
+    pub fn set_attr_overrides(&mut self, overrides: AttrOverrides) {
+        self.attr_overrides = overrides;
+    }
+
+    /// Set whether finished commands get an exit status bar on the left edge
+    pub fn set_command_gutter(&mut self, enabled: bool) {
+        self.command_gutter = enabled;
+    }
+
+    /// Set whether the scrollbar overlay is drawn
+    pub fn set_scrollbar(&mut self, enabled: bool) {
+        self.show_scrollbar = enabled;
+    }
+
+    /// Set the keystroke overlay text, or clear it with `None`
+    pub fn set_keystroke_overlay(&mut self, text: Option<String>) {
+        self.keystroke_overlay = text;
+    }
+
+    /// Set the hint mode to draw labels for, or clear it with `None`
+    pub fn set_hints(&mut self, hints: Option<HintMode>) {
+        self.hints = hints;
+    }
+
+    /// Show a hover preview below the point `x`, `y`, or hide it with `None`
+    pub fn set_link_preview(&mut self, preview: Option<(Arc<LinkPreview>, f32, f32)>) {
+        self.link_preview = preview;
+        self.link_preview_bitmap = None;
+    }
+
+    /// Whether a hover preview is shown
+    pub fn has_link_preview(&self) -> bool {
+        self.link_preview.is_some()
+    }
+
+    /// Set the status strip contents
+    pub fn set_status(&mut self, segments: Vec<StatusSegment>) {
+        self.status = segments;
+    }
+
     /// Calculate terminal size in cells
     pub fn terminal_size(&self, width: u32, height: u32) -> (usize, usize) {
         let cols = (width as f32 / self.cell_dims.width).floor() as usize;
@@ -310,22 +430,41 @@ impl TerminalRenderer {
             return Ok(());
         }
 
-        // Begin drawing
-        unsafe {
-            let rt = self.render_target.as_ref().unwrap();
-            rt.BeginDraw();
+        self.begin_frame();
 
-            // Clear with background color (use override if set)
-            let bg = self
-                .background_override
-                .as_ref()
-                .unwrap_or(&self.theme.colors.background);
-            let bg_color = rgb_to_d2d_color(*bg);
-            rt.Clear(Some(&bg_color));
+        // Magnify: scale the whole surface and pan to keep the cursor in view
+        if self.magnifier.is_active() {
+            let rt = self.render_target.as_ref().unwrap();
+            let size = unsafe { rt.GetSize() };
+            let cursor = &screen.cursor;
+            let width = self.cell_dims.width as f64;
+            let height = self.cell_dims.height as f64;
+            self.magnifier.follow(
+                (
+                    cursor.col as f64 * width,
+                    cursor.row as f64 * height,
+                    width,
+                    height,
+                ),
+                (size.width as f64, size.height as f64),
+            );
+            let factor = self.magnifier.factor() as f32;
+            let (pan_x, pan_y) = self.magnifier.pan();
+            let transform = Matrix3x2 {
+                M11: factor,
+                M12: 0.0,
+                M21: 0.0,
+                M22: factor,
+                M31: -(pan_x as f32) * factor,
+                M32: -(pan_y as f32) * factor,
+            };
+            unsafe { rt.SetTransform(&transform) };
         }
 
         // Draw grid cells
         self.draw_grid(screen)?;
+        self.draw_command_gutter(screen)?;
+        self.draw_scrollbar(screen)?;
 
         // Draw selection
         if let Some(selection) = screen.selection.clone() {
@@ -335,6 +474,18 @@ impl TerminalRenderer {
         // Draw cursor
         self.draw_cursor(screen)?;
 
+        self.draw_hints()?;
+
+        // Overlays are drawn unmagnified
+        unsafe {
+            let rt = self.render_target.as_ref().unwrap();
+            rt.SetTransform(&Matrix3x2::identity());
+        }
+        self.draw_badge(screen)?;
+        self.draw_status_strip(screen)?;
+        self.draw_keystroke_overlay()?;
+        self.draw_link_preview()?;
+
         // End drawing
         unsafe {
             let rt = self.render_target.as_ref().unwrap();
@@ -344,12 +495,119 @@ impl TerminalRenderer {
         Ok(())
     }
 
+    /// Render the panes of a split tab, each screen in its area of the
+    /// surface with a divider in the `gap` after it
+    ///
+    /// Only the focused pane shows its cursor and the overlays; the
+    /// magnifier is not applied.
+    pub fn render_split(
+        &mut self,
+        panes: &[(&Screen, PaneRect)],
+        focused: usize,
+        gap: f32,
+    ) -> windows::core::Result<()> {
+        if self.render_target.is_none() {
+            return Ok(());
+        }
+        self.begin_frame();
+
+        for (i, (screen, area)) in panes.iter().enumerate() {
+            let clip = D2D_RECT_F {
+                left: 0.0,
+                top: 0.0,
+                right: area.width as f32,
+                bottom: area.height as f32,
+            };
+            unsafe {
+                let rt = self.render_target.as_ref().unwrap();
+                rt.SetTransform(&Matrix3x2::translation(area.x as f32, area.y as f32));
+                rt.PushAxisAlignedClip(&clip, D2D1_ANTIALIAS_MODE_ALIASED);
+            }
+            self.draw_grid(screen)?;
+            self.draw_command_gutter(screen)?;
+            self.draw_scrollbar(screen)?;
+            if let Some(selection) = screen.selection.clone() {
+                self.draw_selection(screen, &selection)?;
+            }
+            if i == focused {
+                self.draw_cursor(screen)?;
+                self.draw_hints()?;
+            }
+            unsafe {
+                let rt = self.render_target.as_ref().unwrap();
+                rt.PopAxisAlignedClip();
+            }
+        }
+
+        let rt = self.render_target.as_ref().unwrap();
+        unsafe { rt.SetTransform(&Matrix3x2::identity()) };
+        let size = unsafe { rt.GetSize() };
+        let base: ID2D1RenderTarget = rt.cast()?;
+        let brush = self.get_brush(self.theme.ui.border)?;
+        for (_, area) in panes {
+            let (right, bottom) = (area.right() as f32, area.bottom() as f32);
+            let mut dividers = Vec::with_capacity(2);
+            if right < size.width - gap / 2.0 {
+                dividers.push(D2D_RECT_F {
+                    left: right,
+                    top: area.y as f32,
+                    right: right + gap,
+                    bottom,
+                });
+            }
+            if bottom < size.height - gap / 2.0 {
+                dividers.push(D2D_RECT_F {
+                    left: area.x as f32,
+                    top: bottom,
+                    right,
+                    bottom: bottom + gap,
+                });
+            }
+            for rect in dividers {
+                unsafe { base.FillRectangle(&rect, &brush) };
+            }
+        }
+
+        if let Some((screen, _)) = panes.get(focused) {
+            self.draw_badge(screen)?;
+            self.draw_status_strip(screen)?;
+        }
+        self.draw_keystroke_overlay()?;
+        self.draw_link_preview()?;
+
+        unsafe {
+            let rt = self.render_target.as_ref().unwrap();
+            rt.EndDraw(None, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Begin drawing, clearing the surface to the background color (the
+    /// override if set)
+    fn begin_frame(&self) {
+        let rt = self.render_target.as_ref().unwrap();
+        let bg = self
+            .background_override
+            .as_ref()
+            .unwrap_or(&self.theme.colors.background);
+        let bg_color = D2D1_COLOR_F {
+            a: self.opacity,
+            ..rgb_to_d2d_color(*bg)
+        };
+        unsafe {
+            rt.BeginDraw();
+            rt.Clear(Some(&bg_color));
+        }
+    }
+
     /// Draw the terminal grid
     fn draw_grid(&mut self, screen: &Screen) -> windows::core::Result<()> {
         let grid = screen.grid();
         let scroll_offset = screen.scroll_offset;
         let rows = grid.height();
         let cols = grid.width();
+        let ascii_art_safe = screen.modes.ascii_art_safe;
 
         for row in 0..rows {
             let grid_row = if scroll_offset > 0 {
@@ -364,7 +622,7 @@ impl TerminalRenderer {
 
             for col in 0..cols {
                 if let Some(cell) = grid.get(grid_row, col) {
-                    self.draw_cell(row, col, cell)?;
+                    self.draw_cell(row, col, cell, ascii_art_safe)?;
                 }
             }
         }
@@ -373,10 +631,20 @@ impl TerminalRenderer {
     }
 
     /// Draw a single cell
-    fn draw_cell(&mut self, row: usize, col: usize, cell: &Cell) -> windows::core::Result<()> {
+    ///
+    /// In ASCII-art-safe mode the glyph is clipped to the cell it occupies.
+    fn draw_cell(
+        &mut self,
+        row: usize,
+        col: usize,
+        cell: &Cell,
+        ascii_art_safe: bool,
+    ) -> windows::core::Result<()> {
         let x = col as f32 * self.cell_dims.width;
         let y = row as f32 * self.cell_dims.height;
 
+        let cell = self.attr_overrides.apply_to(cell);
+        let cell = cell.as_ref();
         let attrs = cell.attrs;
         let (fg, bg) = self.resolve_colors(cell);
 
@@ -387,7 +655,11 @@ impl TerminalRenderer {
             None
         };
 
-        let c = cell.c;
+        let c = if attrs.contains(CellAttrs::HIDDEN) {
+            ' '
+        } else {
+            cell.c
+        };
         let has_hyperlink = cell.hyperlink.is_some();
         let needs_fg = c != ' ' && c != '\0'
             || attrs.has_underline()
@@ -429,27 +701,31 @@ impl TerminalRenderer {
                 self.text_format.as_ref().unwrap()
             };
 
-            let mut buf = [0u16; 2];
-            let text: &[u16] = c.encode_utf16(&mut buf);
+            let text: Vec<u16> = cell.grapheme().encode_utf16().collect();
+
+            let (layout_width, options) = if ascii_art_safe {
+                let width = if cell.is_wide() {
+                    self.cell_dims.width * 2.0
+                } else {
+                    self.cell_dims.width
+                };
+                (width, D2D1_DRAW_TEXT_OPTIONS_CLIP)
+            } else {
+                // Allow for wide chars
+                (self.cell_dims.width * 2.0, D2D1_DRAW_TEXT_OPTIONS_NONE)
+            };
 
             let layout: IDWriteTextLayout = unsafe {
                 self.dwrite_factory.CreateTextLayout(
-                    text,
+                    &text,
                     text_format,
-                    self.cell_dims.width * 2.0, // Allow for wide chars
+                    layout_width,
                     self.cell_dims.height,
                 )?
             };
 
             let origin = D2D_POINT_2F { x, y };
-            unsafe {
-                base.DrawTextLayout(
-                    origin,
-                    &layout,
-                    fg_brush.as_ref().unwrap(),
-                    Default::default(),
-                )
-            };
+            unsafe { base.DrawTextLayout(origin, &layout, fg_brush.as_ref().unwrap(), options) };
         }
 
         // Draw underline (also for hyperlinks)
@@ -494,6 +770,391 @@ impl TerminalRenderer {
         Ok(())
     }
 
+    /// Draw the session badge (OSC 1337 SetBadgeFormat) as large translucent
+    /// text in the top-right corner
+    fn draw_badge(&mut self, screen: &Screen) -> windows::core::Result<()> {
+        let badge = screen.badge();
+        if badge.is_empty() {
+            return Ok(());
+        }
+        let Some(format) = self.text_format_bold.clone() else {
+            return Ok(());
+        };
+        let brush = self.get_brush(self.theme.colors.foreground)?;
+        let rt = self.render_target.clone().unwrap();
+        let base: ID2D1RenderTarget = rt.cast()?;
+        let size = unsafe { rt.GetSize() };
+
+        let margin = self.cell_dims.width * 2.0;
+        let text_wide: Vec<u16> = badge.encode_utf16().collect();
+        unsafe {
+            let layout: IDWriteTextLayout = self.dwrite_factory.CreateTextLayout(
+                &text_wide,
+                &format,
+                (size.width - margin).max(0.0),
+                size.height,
+            )?;
+            let range = DWRITE_TEXT_RANGE {
+                startPosition: 0,
+                length: text_wide.len() as u32,
+            };
+            layout.SetFontSize(format.GetFontSize() * 2.5, range)?;
+            layout.SetTextAlignment(DWRITE_TEXT_ALIGNMENT_TRAILING)?;
+            brush.SetOpacity(0.2);
+            base.DrawTextLayout(
+                D2D_POINT_2F {
+                    x: 0.0,
+                    y: self.cell_dims.height / 2.0,
+                },
+                &layout,
+                &brush,
+                D2D1_DRAW_TEXT_OPTIONS_NONE,
+            );
+            brush.SetOpacity(1.0);
+        }
+        Ok(())
+    }
+
+    /// Draw the status strip over the bottom row
+    fn draw_status_strip(&mut self, screen: &Screen) -> windows::core::Result<()> {
+        if self.status.is_empty() {
+            return Ok(());
+        }
+        let bg_brush = self.get_brush(StatusStrip::background(&self.theme))?;
+        let rt = self.render_target.clone().unwrap();
+        let base: ID2D1RenderTarget = rt.cast()?;
+        let size = unsafe { rt.GetSize() };
+
+        let top = screen.height().saturating_sub(1) as f32 * self.cell_dims.height;
+        let rect = D2D_RECT_F {
+            left: 0.0,
+            top,
+            right: size.width,
+            bottom: top + self.cell_dims.height,
+        };
+        unsafe {
+            bg_brush.SetOpacity(0.9);
+            base.FillRectangle(&rect, &bg_brush);
+            bg_brush.SetOpacity(1.0);
+        }
+
+        let mut x = self.cell_dims.width / 2.0;
+        for segment in self.status.clone() {
+            let brush = self.get_brush(segment.color(&self.theme))?;
+            let text_wide: Vec<u16> = segment.text.encode_utf16().collect();
+            let layout: IDWriteTextLayout = unsafe {
+                self.dwrite_factory.CreateTextLayout(
+                    &text_wide,
+                    self.text_format.as_ref().unwrap(),
+                    size.width,
+                    self.cell_dims.height,
+                )?
+            };
+            let mut metrics = DWRITE_TEXT_METRICS::default();
+            unsafe {
+                layout.GetMetrics(&mut metrics)?;
+                base.DrawTextLayout(
+                    D2D_POINT_2F { x, y: top },
+                    &layout,
+                    &brush,
+                    D2D1_DRAW_TEXT_OPTIONS_NONE,
+                );
+            }
+            x += metrics.widthIncludingTrailingWhitespace + self.cell_dims.width * 2.0;
+        }
+        Ok(())
+    }
+
+    /// Draw the exit status bars of finished commands along the left edge
+    fn draw_command_gutter(&mut self, screen: &Screen) -> windows::core::Result<()> {
+        if !self.command_gutter {
+            return Ok(());
+        }
+        let rt = self.render_target.clone().unwrap();
+        let base: ID2D1RenderTarget = rt.cast()?;
+        let cell_height = self.cell_dims.height;
+        for block in screen.command_blocks() {
+            let brush = self.get_brush(gutter_color(&block, &self.theme.colors))?;
+            let rect = D2D_RECT_F {
+                left: 0.0,
+                top: block.top as f32 * cell_height,
+                right: GUTTER_WIDTH as f32,
+                bottom: block.bottom as f32 * cell_height - 1.0,
+            };
+            unsafe { base.FillRectangle(&rect, &brush) };
+        }
+        Ok(())
+    }
+
+    /// Draw a thin scrollbar overlay on the right edge, with the prompt,
+    /// error and search marks under its thumb
+    fn draw_scrollbar(&mut self, screen: &Screen) -> windows::core::Result<()> {
+        let scrollback_len = screen.scrollback().len();
+        if !self.show_scrollbar || scrollback_len == 0 {
+            return Ok(());
+        }
+        let rows = screen.height();
+        let total_lines = scrollback_len + rows;
+        let view_height = rows as f32 * self.cell_dims.height;
+        let view_width = screen.width() as f32 * self.cell_dims.width;
+
+        let bar_width = 6.0;
+        let bar_inset = 2.0;
+        let min_thumb_height = 20.0;
+        let bar_x = view_width - bar_width - bar_inset;
+        let thumb_height = (rows as f32 / total_lines as f32 * view_height).max(min_thumb_height);
+        let fraction = screen.scroll_offset as f32 / scrollback_len as f32;
+        // fraction=0 (at bottom) → thumb at bottom, fraction=1 → thumb at top
+        let thumb_y = (1.0 - fraction) * (view_height - thumb_height);
+        let opacity = if screen.scroll_offset > 0 { 0.5 } else { 0.25 };
+
+        let rt = self.render_target.clone().unwrap();
+        let base: ID2D1RenderTarget = rt.cast()?;
+        let marks = screen.marks();
+        let ticks = scroll_mark_ticks(&marks, total_lines, view_height as f64, &self.theme.colors);
+        for (y, color) in ticks {
+            let brush = self.get_brush(color)?;
+            let rect = D2D_RECT_F {
+                left: bar_x,
+                top: y as f32,
+                right: bar_x + bar_width,
+                bottom: (y + MARK_HEIGHT) as f32,
+            };
+            unsafe {
+                brush.SetOpacity(0.9);
+                base.FillRectangle(&rect, &brush);
+                brush.SetOpacity(1.0);
+            }
+        }
+
+        let brush = self.get_brush(Rgb::new(128, 128, 128))?;
+        let thumb = D2D1_ROUNDED_RECT {
+            rect: D2D_RECT_F {
+                left: bar_x,
+                top: thumb_y,
+                right: bar_x + bar_width,
+                bottom: thumb_y + thumb_height,
+            },
+            radiusX: bar_width / 2.0,
+            radiusY: bar_width / 2.0,
+        };
+        unsafe {
+            brush.SetOpacity(opacity);
+            base.FillRoundedRectangle(&thumb, &brush);
+            brush.SetOpacity(1.0);
+        }
+        Ok(())
+    }
+
+    /// Draw hint mode labels over the start of each target, with the target
+    /// underlined
+    fn draw_hints(&mut self) -> windows::core::Result<()> {
+        let Some(mode) = self.hints.clone() else {
+            return Ok(());
+        };
+        let box_brush = self.get_brush(self.theme.cursor.color)?;
+        let text_brush = self.get_brush(self.theme.cursor.text_color)?;
+        let rt = self.render_target.clone().unwrap();
+        let base: ID2D1RenderTarget = rt.cast()?;
+        let (cell_width, cell_height) = (self.cell_dims.width, self.cell_dims.height);
+
+        for hint in mode.visible() {
+            let x = hint.col as f32 * cell_width;
+            let y = hint.row as f32 * cell_height;
+            let label = &hint.label[mode.typed().len()..];
+            let underline = D2D_RECT_F {
+                left: x,
+                top: y + cell_height - 2.0,
+                right: x + hint.width as f32 * cell_width,
+                bottom: y + cell_height,
+            };
+            let label_rect = D2D_RECT_F {
+                left: x,
+                top: y,
+                right: x + label.chars().count() as f32 * cell_width,
+                bottom: y + cell_height,
+            };
+            let text_wide: Vec<u16> = label.encode_utf16().collect();
+            let layout: IDWriteTextLayout = unsafe {
+                self.dwrite_factory.CreateTextLayout(
+                    &text_wide,
+                    self.text_format.as_ref().unwrap(),
+                    label_rect.right - label_rect.left,
+                    cell_height,
+                )?
+            };
+            unsafe {
+                base.FillRectangle(&underline, &box_brush);
+                base.FillRectangle(&label_rect, &box_brush);
+                base.DrawTextLayout(
+                    D2D_POINT_2F { x, y },
+                    &layout,
+                    &text_brush,
+                    D2D1_DRAW_TEXT_OPTIONS_NONE,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw the presentation keystroke overlay in the bottom-right corner
+    fn draw_keystroke_overlay(&mut self) -> windows::core::Result<()> {
+        let Some(text) = self.keystroke_overlay.clone() else {
+            return Ok(());
+        };
+        let box_brush = self.get_brush(self.theme.colors.foreground)?;
+        let text_brush = self.get_brush(self.theme.colors.background)?;
+        let rt = self.render_target.clone().unwrap();
+        let base: ID2D1RenderTarget = rt.cast()?;
+        let size = unsafe { rt.GetSize() };
+
+        let text_wide: Vec<u16> = text.encode_utf16().collect();
+        let layout: IDWriteTextLayout = unsafe {
+            self.dwrite_factory.CreateTextLayout(
+                &text_wide,
+                self.text_format.as_ref().unwrap(),
+                size.width,
+                size.height,
+            )?
+        };
+        let mut metrics = DWRITE_TEXT_METRICS::default();
+        unsafe { layout.GetMetrics(&mut metrics)? };
+
+        let padding = self.cell_dims.height / 2.0;
+        let box_width = metrics.widthIncludingTrailingWhitespace + padding * 2.0;
+        let box_height = metrics.height + padding;
+        let left = (size.width - box_width - padding).max(0.0);
+        let top = (size.height - box_height - padding).max(0.0);
+        let rect = D2D_RECT_F {
+            left,
+            top,
+            right: left + box_width,
+            bottom: top + box_height,
+        };
+
+        unsafe {
+            box_brush.SetOpacity(0.85);
+            base.FillRectangle(&rect, &box_brush);
+            box_brush.SetOpacity(1.0);
+            let origin = D2D_POINT_2F {
+                x: left + padding,
+                y: top + padding / 2.0,
+            };
+            base.DrawTextLayout(origin, &layout, &text_brush, D2D1_DRAW_TEXT_OPTIONS_NONE);
+        }
+        Ok(())
+    }
+
+    /// Draw the hover preview box: image, title and target, below the
+    /// pointer and kept inside the window
+    fn draw_link_preview(&mut self) -> windows::core::Result<()> {
+        let Some((preview, x, y)) = self.link_preview.clone() else {
+            return Ok(());
+        };
+        let box_brush = self.get_brush(self.theme.colors.background)?;
+        let text_brush = self.get_brush(self.theme.colors.foreground)?;
+        let rt = self.render_target.clone().unwrap();
+        let base: ID2D1RenderTarget = rt.cast()?;
+        let size = unsafe { rt.GetSize() };
+
+        if self.link_preview_bitmap.is_none() {
+            if let Some(ref image) = preview.image {
+                self.link_preview_bitmap = Some(create_bitmap(&base, image)?);
+            }
+        }
+        let image_size = preview
+            .image
+            .as_ref()
+            .map_or((0.0, 0.0), |i| (i.width as f32, i.height as f32));
+
+        let padding = self.cell_dims.height / 2.0;
+        let max_width = (self.cell_dims.width * 48.0).min(size.width - padding * 4.0);
+        let layout = |text: &str, bold: bool| -> windows::core::Result<IDWriteTextLayout> {
+            let wide: Vec<u16> = text.encode_utf16().collect();
+            let format = if bold {
+                self.text_format_bold.as_ref()
+            } else {
+                self.text_format.as_ref()
+            };
+            unsafe {
+                self.dwrite_factory.CreateTextLayout(
+                    &wide,
+                    format.unwrap(),
+                    max_width.max(1.0),
+                    self.cell_dims.height,
+                )
+            }
+        };
+        let mut lines = Vec::new();
+        if let Some(ref title) = preview.title {
+            lines.push(layout(title, true)?);
+        }
+        lines.push(layout(&preview.target.label(), false)?);
+
+        let mut text_width: f32 = 0.0;
+        for line in &lines {
+            let mut metrics = DWRITE_TEXT_METRICS::default();
+            unsafe { line.GetMetrics(&mut metrics)? };
+            text_width = text_width.max(metrics.widthIncludingTrailingWhitespace.min(max_width));
+        }
+        let text_height = lines.len() as f32 * self.cell_dims.height;
+        let image_gap = if image_size.1 > 0.0 {
+            padding / 2.0
+        } else {
+            0.0
+        };
+        let box_width = image_size.0.max(text_width) + padding * 2.0;
+        let box_height = image_size.1 + image_gap + text_height + padding * 2.0;
+
+        // Below the pointer, or above it if there is no room
+        let left = x.min(size.width - box_width).max(0.0);
+        let below = y + self.cell_dims.height;
+        let top = if below + box_height <= size.height {
+            below
+        } else {
+            (y - box_height).max(0.0)
+        };
+        let rect = D2D_RECT_F {
+            left,
+            top,
+            right: left + box_width,
+            bottom: top + box_height,
+        };
+
+        unsafe {
+            box_brush.SetOpacity(0.95);
+            base.FillRectangle(&rect, &box_brush);
+            box_brush.SetOpacity(1.0);
+            base.DrawRectangle(&rect, &text_brush, 1.0, None);
+
+            if let Some(ref bitmap) = self.link_preview_bitmap {
+                let image_rect = D2D_RECT_F {
+                    left: left + padding,
+                    top: top + padding,
+                    right: left + padding + image_size.0,
+                    bottom: top + padding + image_size.1,
+                };
+                base.DrawBitmap(
+                    bitmap,
+                    Some(&image_rect as *const _),
+                    1.0,
+                    D2D1_BITMAP_INTERPOLATION_MODE_LINEAR,
+                    None,
+                );
+            }
+            let mut line_top = top + padding + image_size.1 + image_gap;
+            for line in &lines {
+                let origin = D2D_POINT_2F {
+                    x: left + padding,
+                    y: line_top,
+                };
+                base.DrawTextLayout(origin, line, &text_brush, D2D1_DRAW_TEXT_OPTIONS_CLIP);
+                line_top += self.cell_dims.height;
+            }
+        }
+        Ok(())
+    }
+
     /// Resolve foreground and background colors from a cell
     fn resolve_colors(&self, cell: &Cell) -> (Rgb, Rgb) {
         let palette = &self.theme.colors;
@@ -523,6 +1184,18 @@ impl TerminalRenderer {
             fg = Rgb::new(100, 149, 237);
         }
 
+        if self.minimum_contrast > 1.0 {
+            let behind = if cell.bg == Color::Default && !cell.attrs.contains(CellAttrs::INVERSE) {
+                *self
+                    .background_override
+                    .as_ref()
+                    .unwrap_or(&self.theme.colors.background)
+            } else {
+                bg
+            };
+            fg = fg.with_min_contrast(&behind, self.minimum_contrast);
+        }
+
         (fg, bg)
     }
 
@@ -613,12 +1286,11 @@ impl TerminalRenderer {
                 let text_brush = self.get_brush(text_color)?;
 
                 let text_format = self.text_format.as_ref().unwrap();
-                let mut buf = [0u16; 2];
-                let text: &[u16] = c.encode_utf16(&mut buf);
+                let text: Vec<u16> = cell.grapheme().encode_utf16().collect();
 
                 let layout: IDWriteTextLayout = unsafe {
                     self.dwrite_factory.CreateTextLayout(
-                        text,
+                        &text,
                         text_format,
                         self.cell_dims.width * 2.0,
                         self.cell_dims.height,
@@ -661,6 +1333,43 @@ impl TerminalRenderer {
 }
 
 /// Convert Rgb to D2D1_COLOR_F
+/// Create a bitmap from RGBA pixels, converted to premultiplied BGRA
+fn create_bitmap(
+    rt: &ID2D1RenderTarget,
+    image: &cterm_core::DecodedImage,
+) -> windows::core::Result<ID2D1Bitmap> {
+    let mut pixels = Vec::with_capacity(image.data.len());
+    for px in image.data.chunks_exact(4) {
+        let alpha = px[3] as u32;
+        let premultiply = |c: u8| (c as u32 * alpha / 255) as u8;
+        pixels.extend_from_slice(&[
+            premultiply(px[2]),
+            premultiply(px[1]),
+            premultiply(px[0]),
+            px[3],
+        ]);
+    }
+    let properties = D2D1_BITMAP_PROPERTIES {
+        pixelFormat: D2D1_PIXEL_FORMAT {
+            format: DXGI_FORMAT_B8G8R8A8_UNORM,
+            alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+        },
+        dpiX: 96.0,
+        dpiY: 96.0,
+    };
+    unsafe {
+        rt.CreateBitmap(
+            D2D_SIZE_U {
+                width: image.width as u32,
+                height: image.height as u32,
+            },
+            Some(pixels.as_ptr() as *const _),
+            image.width as u32 * 4,
+            &properties,
+        )
+    }
+}
+
 fn rgb_to_d2d_color(rgb: Rgb) -> D2D1_COLOR_F {
     D2D1_COLOR_F {
         r: rgb.r as f32 / 255.0,
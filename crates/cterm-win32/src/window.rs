@@ -2,35 +2,79 @@
 //!
 //! Manages the main window, tabs, terminal rendering, and message handling.
 
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
 use windows::core::PCWSTR;
-use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, POINT, RECT, WPARAM};
+use windows::Win32::Graphics::Dwm::{
+    DwmExtendFrameIntoClientArea, DwmSetWindowAttribute, DWMSBT_AUTO, DWMSBT_TRANSIENTWINDOW,
+    DWMWA_SYSTEMBACKDROP_TYPE, DWM_SYSTEMBACKDROP_TYPE,
+};
 use windows::Win32::Graphics::Gdi::{
-    BeginPaint, EndPaint, InvalidateRect, UpdateWindow, HBRUSH, PAINTSTRUCT,
+    BeginPaint, EndPaint, InvalidateRect, ScreenToClient, UpdateWindow, HBRUSH, PAINTSTRUCT,
 };
+use windows::Win32::UI::Controls::MARGINS;
+use windows::Win32::UI::Input::KeyboardAndMouse::{ReleaseCapture, SetCapture};
 use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
-use cterm_app::config::Config;
+use cterm_app::auto_switch::{AutoSwitchChange, AutoSwitcher};
+use cterm_app::clipboard_history::{self, ClipSource};
+use cterm_app::closed_tabs::{self, ClosedTab};
+use cterm_app::config::{
+    find_theme, CommandNotifyConfig, Config, InputConfig, ProfileConfig, StickyTabConfig,
+    TabBarPosition,
+};
+use cterm_app::control::ControlRequest;
 use cterm_app::file_transfer::PendingFileManager;
+use cterm_app::link_preview::{self, LinkPreview, PreviewTarget, HOVER_DELAY};
+use cterm_app::live_config::{self, AppearanceChange, Subscription};
+use cterm_app::paste::{PasteGuard, PasteJob};
+use cterm_app::presentation::{KeystrokeLog, KEYSTROKE_TIMEOUT};
+use cterm_app::session::{
+    self, PaneRect, PaneTree, SplitAxis, WindowGeometry, WindowSessionState, PANE_RESIZE_STEP,
+};
 use cterm_app::shortcuts::ShortcutManager;
+use cterm_app::sounds::{self, Cue};
+use cterm_app::startup::{StartupCommands, STARTUP_POLL_INTERVAL};
+use cterm_app::status_segments;
+use cterm_app::tab_widgets::{self, WidgetKind};
+use cterm_app::tmux::{PaneId, TmuxEvent, TmuxGateway};
+use cterm_app::triggers::{self, TriggerEffect, TriggerEngine};
+use cterm_app::workspace;
 use cterm_core::color::Rgb;
+use cterm_core::detect;
+use cterm_core::hints::{HintAction, HintInput, HintMode};
+use cterm_core::iterm2::UPLOAD_ABORT;
 use cterm_core::mouse::{encode_mouse_event, MouseButton as ReportButton, MouseModifiers};
-use cterm_core::pty::{PtyConfig, PtySize};
-use cterm_core::screen::{FileTransferOperation, MouseMode, ScreenConfig};
-use cterm_core::term::{Terminal, TerminalEvent};
-use cterm_ui::events::{Action, Modifiers};
+use cterm_core::pty::{Pty, PtyConfig, PtySize};
+use cterm_core::screen::{
+    ClipboardOperation, ClipboardSelection, CommandFinished, FileTransferOperation, MouseMode,
+    PointerShape, ScreenConfig, SelectionMode, TmuxControl,
+};
+use cterm_core::term::{Key, Terminal, TerminalEvent};
+use cterm_core::{
+    Capability, OutputStats, PlaybackError, Player, Recording, ThroughputMeter, TransferFile,
+    WatchMatch,
+};
+use cterm_ui::events::{Action, Modifiers, MouseButton, PaneDirection};
+use cterm_ui::gutter::{gutter_block_at, gutter_tooltip};
+use cterm_ui::pointer::{PointerHider, PointerMotion};
+use cterm_ui::prompt::{Prompt, PromptAction};
+use cterm_ui::status::{command_summary, StatusStrip};
 use cterm_ui::theme::Theme;
+use cterm_ui::utils::Magnifier;
 
 use crate::clipboard;
 use crate::dpi::{self, DpiInfo};
 use crate::keycode;
 use crate::menu::{self, MenuAction};
 use crate::mouse::{self, MouseState};
-use crate::notification_bar::{NotificationAction, NotificationBar};
+use crate::notification_bar::NotificationBar;
 use crate::tab_bar::{TabBar, TAB_BAR_HEIGHT};
 use crate::terminal_canvas::TerminalRenderer;
 
@@ -39,6 +83,59 @@ pub const WM_APP_PTY_DATA: u32 = WM_APP + 1;
 pub const WM_APP_PTY_EXIT: u32 = WM_APP + 2;
 pub const WM_APP_TITLE_CHANGED: u32 = WM_APP + 3;
 pub const WM_APP_BELL: u32 = WM_APP + 4;
+/// A long command finished; LPARAM owns a boxed `CommandFinished`
+pub const WM_APP_COMMAND_FINISHED: u32 = WM_APP + 5;
+/// `cterm new-tab`, `cterm new-window` or `cterm --workspace`; LPARAM owns a
+/// boxed `ControlRequest`
+pub const WM_APP_CONTROL_REQUEST: u32 = WM_APP + 6;
+/// A tab's output rates; LPARAM owns a boxed `OutputStats`
+pub const WM_APP_OUTPUT_STATS: u32 = WM_APP + 7;
+/// tmux control mode output of a tab; LPARAM owns a boxed `Vec<TmuxControl>`
+pub const WM_APP_TMUX_CONTROL: u32 = WM_APP + 8;
+/// New output matched a tab's watch expression; LPARAM owns a boxed
+/// `WatchMatch`
+pub const WM_APP_WATCH_MATCHED: u32 = WM_APP + 9;
+/// A hover preview finished loading; LPARAM owns a boxed `Arc<LinkPreview>`
+pub const WM_APP_LINK_PREVIEW: u32 = WM_APP + 10;
+/// A ZMODEM or Kermit transfer started or finished in a tab
+pub const WM_APP_TRANSFER_CHANGED: u32 = WM_APP + 11;
+/// A remote `rz` or `kermit -r` in a tab is waiting for files
+pub const WM_APP_TRANSFER_FILES: u32 = WM_APP + 12;
+/// A clipboard operation, notification or permission request from a tab;
+/// LPARAM owns a boxed `TerminalEvent`
+pub const WM_APP_TERMINAL_REQUEST: u32 = WM_APP + 13;
+/// Preferences changed the appearance; LPARAM owns a boxed
+/// `(Config, AppearanceChange)`
+pub const WM_APP_APPEARANCE: u32 = WM_APP + 14;
+/// A program in a tab asked for another mouse pointer shape (OSC 22)
+pub const WM_APP_POINTER_SHAPE: u32 = WM_APP + 15;
+/// A remote `it2ul` in a tab is waiting for files (OSC 1337 RequestUpload)
+pub const WM_APP_UPLOAD_FILES: u32 = WM_APP + 16;
+
+/// Timer that clears expired entries from the keystroke overlay
+const KEYSTROKE_TIMER_ID: usize = 1;
+/// Timer that scrolls while a selection is dragged past an edge
+const AUTOSCROLL_TIMER_ID: usize = 2;
+/// Timer that clears the command-finished toast from the status strip
+const TOAST_TIMER_ID: usize = 3;
+/// Timer that writes the next chunk of a large paste
+const PASTE_TIMER_ID: usize = 4;
+/// Timer that refreshes the tab bar clock, timer and battery widgets
+const WIDGETS_TIMER_ID: usize = 5;
+/// Timer that advances the recordings played back in tabs
+const PLAYBACK_TIMER_ID: usize = 6;
+/// Timer that polls tabs with fast output for the report sent once it stops
+const OUTPUT_STATS_TIMER_ID: usize = 7;
+/// Timer that previews the hovered link once the pointer has rested on it
+const LINK_PREVIEW_TIMER_ID: usize = 8;
+/// Timer that refreshes the status segments in tab titles
+const SEGMENTS_TIMER_ID: usize = 9;
+/// Timer that types startup commands into local shells once they are ready
+const STARTUP_TIMER_ID: usize = 10;
+/// Tray icon used to show desktop notifications
+const NOTIFY_ICON_ID: u32 = 1;
+/// Width of the dividers between split panes, in DIPs
+const PANE_GAP: f32 = 2.0;
 
 /// Commands sent to the daemon I/O thread
 pub enum DaemonCmd {
@@ -47,6 +144,8 @@ pub enum DaemonCmd {
     SetTitle(String),
     SetTabColor(String),
     SetTemplateName(String),
+    /// Copy to the daemon's clipboard
+    SetClipboard(Vec<u8>),
 }
 
 /// Tab entry
@@ -66,6 +165,65 @@ pub struct TabEntry {
     /// Command sender for daemon-backed tabs (write/resize)
     #[allow(dead_code)]
     pub daemon_cmd_tx: Option<tokio::sync::mpsc::UnboundedSender<DaemonCmd>>,
+    /// Long command notification override from the template
+    pub command_notify: Option<CommandNotifyConfig>,
+    /// Name of the tab template the tab was opened from
+    pub template: Option<String>,
+    /// Recording played into the terminal — None for live sessions
+    pub playback: Option<Player>,
+    /// Output rates last reported for the tab
+    pub output_stats: OutputStats,
+    /// Gateway of the tmux session the tab shows a pane of
+    pub tmux_pane: Option<(Arc<TmuxGateway>, PaneId)>,
+    /// Panes of the tab once it has been split; `terminal` is then the
+    /// focused pane's
+    pub split: Option<SplitPanes>,
+    /// Theme from the tab's profile — None uses the window's
+    pub theme: Option<Theme>,
+    /// Host rules restyling the tab while its focused pane is on a
+    /// matching host
+    pub auto_switch: AutoSwitcher,
+    /// Theme and color to restore once no host rule matches
+    pub auto_switch_saved: Option<(Option<Theme>, Option<String>)>,
+}
+
+impl TabEntry {
+    /// Whether `id` is this tab or one of its panes
+    fn has_pane(&self, id: u64) -> bool {
+        match self.split {
+            Some(ref split) => split.tree.contains(id),
+            None => self.id == id,
+        }
+    }
+
+    /// Terminal of pane `id`, the tab's own pane having the tab's id
+    fn pane_terminal(&self, id: u64) -> Option<Arc<Mutex<Terminal>>> {
+        match self.split {
+            Some(ref split) => split
+                .panes
+                .iter()
+                .find(|p| p.id == id)
+                .map(|p| Arc::clone(&p.terminal)),
+            None => (self.id == id).then(|| Arc::clone(&self.terminal)),
+        }
+    }
+}
+
+/// Panes of a split tab
+pub struct SplitPanes {
+    pub tree: PaneTree,
+    /// Every pane, the tab's own one included
+    pub panes: Vec<PaneEntry>,
+}
+
+/// A pane of a split tab
+pub struct PaneEntry {
+    pub id: u64,
+    pub terminal: Arc<Mutex<Terminal>>,
+    /// Command sender for a daemon-backed pane (resize)
+    pub daemon_cmd_tx: Option<tokio::sync::mpsc::UnboundedSender<DaemonCmd>>,
+    #[allow(dead_code)]
+    pub reader_handle: Option<thread::JoinHandle<()>>,
 }
 
 /// Window state
@@ -91,12 +249,43 @@ pub struct WindowState {
     last_mouse_pos: (f32, f32),
     /// Last reported pointer cell, to avoid flooding drag reports per pixel.
     last_mouse_cell: Option<(usize, usize)>,
+    /// Hides the pointer while typing
+    pointer_hider: PointerHider,
+    /// Selection autoscroll direction (-1 = up, 0 = none, 1 = down)
+    autoscroll_direction: i32,
+    /// Pointer column the selection follows while autoscrolling
+    autoscroll_col: usize,
     #[allow(dead_code)]
     menu_handle: winapi::shared::windef::HMENU,
     /// Skip close confirmation (set during relaunch)
     pub skip_close_confirm: bool,
     /// Remote host connection manager
     pub remote_manager: cterm_client::RemoteManager,
+    /// Whether presentation mode is on
+    presenting: bool,
+    /// Recent keystrokes while presenting with the keystroke overlay enabled
+    keystrokes: Option<KeystrokeLog>,
+    /// Labelled links, paths and hashes while hint mode is active
+    hints: Option<HintMode>,
+    /// Transient states shown along the bottom edge
+    status: StatusStrip,
+    /// Paste being written a chunk at a time, with the terminal it goes to
+    paste: Option<(Arc<Mutex<Terminal>>, PasteJob)>,
+    /// Whether the notification tray icon has been added
+    notify_icon: bool,
+    /// Clients of tmux running in control mode, by the tab it runs in
+    tmux: HashMap<u64, Arc<TmuxGateway>>,
+    /// Link or image path under the pointer and where the pointer was
+    hovered_link: Option<(PreviewTarget, (f32, f32))>,
+    /// `[[triggers]]`, set up on each new terminal
+    triggers: TriggerEngine,
+    /// Triggers of each tab or pane whose output they have looked at
+    pane_triggers: HashMap<u64, TriggerEngine>,
+    /// Startup commands still to type, by the tab or pane whose local shell
+    /// runs them
+    startup: HashMap<u64, StartupCommands>,
+    /// Restyles the window when preferences are applied
+    _appearance: Subscription,
 }
 
 impl WindowState {
@@ -107,14 +296,26 @@ impl WindowState {
 
         let mut tab_bar = TabBar::new(theme);
         tab_bar.set_dpi(dpi);
+        tab_bar.set_widgets(config.widgets);
+        tab_bar.set_config(&config.tabs);
+        sync_widgets_timer(hwnd, config.widgets.any());
+        sync_segments_timer(hwnd, !config.status_segments.is_empty());
 
         let mut notification_bar = NotificationBar::new(theme);
         notification_bar.set_dpi(dpi);
 
         // Create menu
-        let menu_handle = menu::create_menu_bar(false);
+        let menu_handle = menu::create_menu_bar(false, &config.profiles, &config.workspaces);
         menu::set_window_menu(hwnd.0 as *mut _, menu_handle);
 
+        // Files dropped from Explorer arrive as WM_DROPFILES
+        unsafe { winapi::um::shellapi::DragAcceptFiles(hwnd.0 as _, 1) };
+
+        let hwnd_value = hwnd.0 as usize;
+        let appearance = live_config::subscribe(move |config, change| {
+            post_appearance(hwnd_value, config.clone(), change);
+        });
+
         Self {
             hwnd,
             config: config.clone(),
@@ -132,9 +333,24 @@ impl WindowState {
             mouse_report_button: None,
             last_mouse_pos: (0.0, 0.0),
             last_mouse_cell: None,
+            pointer_hider: PointerHider::new(config.input.hide_pointer_while_typing),
+            autoscroll_direction: 0,
+            autoscroll_col: 0,
             menu_handle,
             skip_close_confirm: false,
             remote_manager: cterm_client::RemoteManager::new(),
+            presenting: false,
+            keystrokes: None,
+            hints: None,
+            status: StatusStrip::new(),
+            paste: None,
+            notify_icon: false,
+            tmux: HashMap::new(),
+            hovered_link: None,
+            triggers: TriggerEngine::new(&config.triggers),
+            pane_triggers: HashMap::new(),
+            startup: HashMap::new(),
+            _appearance: appearance,
         }
     }
 
@@ -143,13 +359,149 @@ impl WindowState {
         let font_family = &self.config.appearance.font.family;
         let font_size = self.config.appearance.font.size as f32;
 
-        let renderer = TerminalRenderer::new(self.hwnd, &self.theme, font_family, font_size)?;
+        let mut renderer = TerminalRenderer::new(self.hwnd, &self.theme, font_family, font_size)?;
+        renderer.set_attr_overrides(self.config.appearance.text_styles);
+        renderer.set_command_gutter(self.config.appearance.command_gutter);
         self.renderer = Some(renderer);
+        self.sync_translucency();
         Ok(())
     }
 
+    /// Apply the configured background opacity and blur
+    fn sync_translucency(&mut self) {
+        let opacity = self.config.appearance.background_opacity(&self.theme);
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_opacity(opacity);
+        }
+        set_window_translucency(
+            self.hwnd,
+            opacity < 1.0,
+            self.config.appearance.background_blur,
+        );
+    }
+
+    /// Restyle after the configured appearance changed, e.g. while
+    /// preferences are previewed
+    fn apply_appearance(&mut self, config: &Config, change: AppearanceChange) {
+        let old_size = self.config.appearance.font.size;
+        self.config.appearance = config.appearance.clone();
+        if change.theme {
+            self.theme = cterm_app::resolve_theme(config);
+            if let Some(ref mut renderer) = self.renderer {
+                renderer.set_theme(&self.theme);
+            }
+            self.tab_bar.set_theme(&self.theme);
+            self.notification_bar.set_theme(&self.theme);
+        }
+        if change.theme || change.opacity {
+            self.sync_translucency();
+        }
+        for tab in &self.tabs {
+            let terminals: Vec<&Arc<Mutex<Terminal>>> = match tab.split {
+                Some(ref split) => split.panes.iter().map(|p| &p.terminal).collect(),
+                None => vec![&tab.terminal],
+            };
+            for terminal in terminals {
+                let Ok(mut terminal) = terminal.lock() else {
+                    continue;
+                };
+                // Tabs with a profile theme keep it
+                if change.theme && tab.theme.is_none() {
+                    terminal.set_color_scheme(self.theme.colors.color_scheme());
+                }
+                if change.cursor {
+                    terminal.screen_mut().set_cursor_shape(
+                        config.appearance.cursor_style.into(),
+                        config.appearance.cursor_blink,
+                    );
+                }
+            }
+        }
+        if change.font {
+            if let Some(ref mut renderer) = self.renderer {
+                // Keep zoom steps
+                let size = renderer.font_size() + (config.appearance.font.size - old_size) as f32;
+                let family = &config.appearance.font.family;
+                if let Err(e) = renderer.set_font(family, size.clamp(6.0, 72.0)) {
+                    log::error!("Failed to load font {:?}: {}", family, e);
+                }
+            }
+            self.on_font_size_changed();
+        } else {
+            self.invalidate();
+        }
+    }
+
     /// Create a new tab
     pub fn new_tab(&mut self) -> Result<u64, Box<dyn std::error::Error>> {
+        self.new_tab_in(None)
+    }
+
+    /// Create a read-only tab playing back the recording at `path`
+    pub fn play_recording(&mut self, path: &Path) -> Result<u64, PlaybackError> {
+        let recording = Recording::open(path)?;
+        let tab_id = self.next_tab_id.fetch_add(1, Ordering::SeqCst);
+        let title = recording.tab_title(path);
+
+        let screen_config = ScreenConfig {
+            scrollback_lines: self.config.general.scrollback_lines,
+            selection: self.config.selection.clone(),
+            conformance_level: self.config.general.conformance_level,
+            cursor_style: self.config.appearance.cursor_style.into(),
+            cursor_blink: self.config.appearance.cursor_blink,
+        };
+        let player = Player::new(recording);
+        let terminal = Arc::new(Mutex::new(player.new_terminal(screen_config)));
+
+        let entry = TabEntry {
+            id: tab_id,
+            title: title.clone(),
+            terminal,
+            color: None,
+            background_color: None,
+            has_bell: false,
+            title_locked: true,
+            reader_handle: None,
+            session_id: None,
+            daemon_cmd_tx: None,
+            command_notify: None,
+            template: None,
+            playback: Some(player),
+            output_stats: OutputStats::default(),
+            tmux_pane: None,
+            split: None,
+            theme: None,
+            auto_switch: self.auto_switcher(None),
+            auto_switch_saved: None,
+        };
+
+        self.tabs.push(entry);
+        self.active_tab_index = self.tabs.len() - 1;
+        self.tab_bar.add_tab(tab_id, &title);
+        self.tab_bar.set_active(tab_id);
+
+        unsafe {
+            SetTimer(
+                Some(self.hwnd),
+                PLAYBACK_TIMER_ID,
+                Player::TICK.as_millis() as u32,
+                None,
+            );
+        }
+        self.sync_theme();
+        self.sync_read_only();
+        self.sync_recording();
+        self.sync_playback();
+        self.invalidate();
+
+        Ok(tab_id)
+    }
+
+    /// Create a new tab in `directory`, or else the configured working directory
+    pub fn new_tab_in(
+        &mut self,
+        directory: Option<PathBuf>,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
         let tab_id = self.next_tab_id.fetch_add(1, Ordering::SeqCst);
 
         // Get terminal size
@@ -158,29 +510,19 @@ impl WindowState {
         // Create terminal
         let screen_config = ScreenConfig {
             scrollback_lines: self.config.general.scrollback_lines,
+            selection: self.config.selection.clone(),
+            conformance_level: self.config.general.conformance_level,
+            cursor_style: self.config.appearance.cursor_style.into(),
+            cursor_blink: self.config.appearance.cursor_blink,
         };
 
-        let pty_config = PtyConfig {
-            size: PtySize {
-                cols: cols as u16,
-                rows: rows as u16,
-                pixel_width: 0,
-                pixel_height: 0,
-            },
-            shell: self.config.general.default_shell.clone(),
-            args: self.config.general.shell_args.clone(),
-            cwd: self.config.general.working_directory.clone(),
-            env: self
-                .config
-                .general
-                .env
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect(),
-            term: self.config.general.term.clone(),
-        };
+        let pty_config = self.shell_pty_config(cols, rows, directory);
 
-        let terminal = Terminal::with_shell(cols, rows, screen_config, &pty_config)?;
+        let mut terminal = Terminal::with_shell(cols, rows, screen_config, &pty_config)?;
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(self.config.permissions);
+        self.triggers.attach(&mut terminal);
+        terminal.set_color_scheme(self.theme.colors.color_scheme());
         let terminal = Arc::new(Mutex::new(terminal));
 
         // Get shell basename for initial title
@@ -210,6 +552,15 @@ impl WindowState {
             reader_handle: Some(reader_handle),
             session_id: None,
             daemon_cmd_tx: None,
+            command_notify: None,
+            template: None,
+            playback: None,
+            output_stats: OutputStats::default(),
+            tmux_pane: None,
+            split: None,
+            theme: None,
+            auto_switch: self.auto_switcher(None),
+            auto_switch_saved: None,
         };
 
         self.tabs.push(entry);
@@ -218,15 +569,343 @@ impl WindowState {
         // Update tab bar with shell basename
         self.tab_bar.add_tab(tab_id, &initial_title);
         self.tab_bar.set_active(tab_id);
+        self.sync_theme();
+
+        Ok(tab_id)
+    }
+
+    /// Create a new tab using `profile`
+    pub fn new_profile_tab(
+        &mut self,
+        profile: &ProfileConfig,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let tab_id = self.next_tab_id.fetch_add(1, Ordering::SeqCst);
+        let (cols, rows) = self.terminal_size();
+
+        let pty_config = PtyConfig {
+            size: PtySize {
+                cols: cols as u16,
+                rows: rows as u16,
+                pixel_width: 0,
+                pixel_height: 0,
+            },
+            ..profile.pty_config(&self.config.general)
+        };
+        let screen_config = profile.screen_config(&self.config);
+        let theme = profile.theme(&self.theme);
+
+        let mut terminal = Terminal::with_shell(cols, rows, screen_config, &pty_config)?;
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(profile.permissions.unwrap_or(self.config.permissions));
+        self.triggers.attach(&mut terminal);
+        terminal.set_color_scheme(theme.colors.color_scheme());
+        let terminal = Arc::new(Mutex::new(terminal));
+
+        let reader_handle = self.start_pty_reader(tab_id, Arc::clone(&terminal));
+        self.queue_startup_commands(tab_id, &profile.startup_commands);
+
+        let entry = TabEntry {
+            id: tab_id,
+            title: profile.name.clone(),
+            terminal,
+            color: None,
+            background_color: None,
+            has_bell: false,
+            title_locked: false,
+            reader_handle: Some(reader_handle),
+            session_id: None,
+            daemon_cmd_tx: None,
+            command_notify: profile.command_notify,
+            template: None,
+            playback: None,
+            output_stats: OutputStats::default(),
+            tmux_pane: None,
+            split: None,
+            theme: Some(theme),
+            auto_switch: self.auto_switcher(Some(profile)),
+            auto_switch_saved: None,
+        };
+
+        self.tabs.push(entry);
+        self.active_tab_index = self.tabs.len() - 1;
+        self.tab_bar.add_tab(tab_id, &profile.name);
+        self.tab_bar.set_active(tab_id);
+        self.sync_theme();
+        self.sync_read_only();
+        self.invalidate();
 
         Ok(tab_id)
     }
 
+    /// `[[auto_switch]]` rules for a tab, with those of its profile first
+    fn auto_switcher(&self, profile: Option<&ProfileConfig>) -> AutoSwitcher {
+        AutoSwitcher::new(match profile {
+            Some(profile) => profile.auto_switch_rules(&self.config),
+            None => self.config.auto_switch.clone(),
+        })
+    }
+
+    /// Apply `[[auto_switch]]` rules when the shell of tab `tab_id`'s
+    /// focused pane moves to another host
+    fn update_auto_switch(&mut self, tab_id: u64) {
+        let Some(tab) = self.tabs.iter_mut().find(|t| t.id == tab_id) else {
+            return;
+        };
+        let Some(change) = tab
+            .auto_switch
+            .update(tab.terminal.lock().unwrap().screen())
+        else {
+            return;
+        };
+        let (theme, color) = match change {
+            AutoSwitchChange::Apply(rule) => {
+                if tab.auto_switch_saved.is_none() {
+                    tab.auto_switch_saved = Some((tab.theme.clone(), tab.color.clone()));
+                }
+                let theme = rule.theme.as_deref().and_then(|name| {
+                    let theme = find_theme(name);
+                    if theme.is_none() {
+                        log::warn!("Auto switch: unknown theme {:?}", name);
+                    }
+                    theme
+                });
+                (theme.map(Some), rule.tab_color.map(Some))
+            }
+            AutoSwitchChange::Revert => match tab.auto_switch_saved.take() {
+                Some((theme, color)) => (Some(theme), Some(color)),
+                None => return,
+            },
+        };
+        if let Some(theme) = theme {
+            let scheme = theme.as_ref().unwrap_or(&self.theme).colors.color_scheme();
+            tab.terminal.lock().unwrap().set_color_scheme(scheme);
+            tab.theme = theme;
+        }
+        if let Some(color) = color {
+            self.tab_bar
+                .set_color(tab_id, color.as_deref().and_then(parse_hex_color));
+            tab.color = color;
+        }
+        self.sync_theme();
+    }
+
+    /// Draw with the active tab's theme, or else the window's
+    fn sync_theme(&mut self) {
+        let theme = self
+            .tabs
+            .get(self.active_tab_index)
+            .and_then(|t| t.theme.as_ref())
+            .unwrap_or(&self.theme);
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_theme(theme);
+        }
+    }
+
+    /// PTY settings for the configured shell in `directory`, or else the
+    /// configured working directory
+    fn shell_pty_config(&self, cols: usize, rows: usize, directory: Option<PathBuf>) -> PtyConfig {
+        PtyConfig {
+            size: PtySize {
+                cols: cols as u16,
+                rows: rows as u16,
+                pixel_width: 0,
+                pixel_height: 0,
+            },
+            shell: self.config.general.default_shell.clone(),
+            args: self.config.general.shell_args.clone(),
+            cwd: directory.or_else(|| self.config.general.working_directory.clone()),
+            env: self
+                .config
+                .general
+                .env
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            clear_env: false,
+            term: self.config.general.term.clone(),
+        }
+    }
+
+    /// Split the active tab's focused pane along `axis`, starting the
+    /// configured shell in the new pane
+    fn split_pane(&mut self, axis: SplitAxis) {
+        self.split_pane_with(axis, None);
+    }
+
+    /// Split the active tab's focused pane along `axis`, starting what
+    /// `template` runs in the new pane, or the configured shell
+    fn split_pane_with(&mut self, axis: SplitAxis, template: Option<&StickyTabConfig>) {
+        let Some(tab) = self.tabs.get(self.active_tab_index) else {
+            return;
+        };
+        // Recordings and tmux panes keep their own layout
+        if tab.playback.is_some() || tab.tmux_pane.is_some() {
+            return;
+        }
+
+        let pane_id = self.next_tab_id.fetch_add(1, Ordering::SeqCst);
+        let (cols, rows) = self.terminal_size();
+        let screen_config = ScreenConfig {
+            scrollback_lines: self.config.general.scrollback_lines,
+            selection: self.config.selection.clone(),
+            conformance_level: self.config.general.conformance_level,
+            cursor_style: self.config.appearance.cursor_style.into(),
+            cursor_blink: self.config.appearance.cursor_blink,
+        };
+        let (pty_config, startup_commands) = match template {
+            Some(template) => {
+                let (template, _) = template.resolve_profile(&self.config);
+                let shell = self.shell_pty_config(cols, rows, template.working_directory);
+                let pty_config = PtyConfig {
+                    args: if template.command.is_some() {
+                        template.args
+                    } else {
+                        shell.args.clone()
+                    },
+                    shell: template.command.or(shell.shell.clone()),
+                    env: template.env.into_iter().chain(shell.env.clone()).collect(),
+                    clear_env: !template.inherit_env,
+                    ..shell
+                };
+                (pty_config, template.startup_commands)
+            }
+            None => (self.shell_pty_config(cols, rows, None), Vec::new()),
+        };
+        let mut terminal = match Terminal::with_shell(cols, rows, screen_config, &pty_config) {
+            Ok(terminal) => terminal,
+            Err(e) => {
+                log::error!("Failed to start shell for new pane: {}", e);
+                return;
+            }
+        };
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(self.config.permissions);
+        self.triggers.attach(&mut terminal);
+        terminal.set_color_scheme(self.theme.colors.color_scheme());
+        let terminal = Arc::new(Mutex::new(terminal));
+        let reader_handle = self.start_pty_reader(pane_id, Arc::clone(&terminal));
+        self.queue_startup_commands(pane_id, &startup_commands);
+
+        let tab = &mut self.tabs[self.active_tab_index];
+        let split = tab.split.get_or_insert_with(|| SplitPanes {
+            tree: PaneTree::new(tab.id),
+            panes: vec![PaneEntry {
+                id: tab.id,
+                terminal: Arc::clone(&tab.terminal),
+                daemon_cmd_tx: tab.daemon_cmd_tx.clone(),
+                reader_handle: None,
+            }],
+        });
+        let focused = split.tree.focused();
+        split.tree.split(focused, axis, pane_id);
+        split.panes.push(PaneEntry {
+            id: pane_id,
+            terminal: Arc::clone(&terminal),
+            daemon_cmd_tx: None,
+            reader_handle: Some(reader_handle),
+        });
+        tab.terminal = terminal;
+
+        self.resize_terminals();
+        self.pane_focus_changed();
+    }
+
+    /// Close pane `pane_id` of the tab at `index`, or the tab if it is its
+    /// last pane
+    fn close_pane(&mut self, index: usize, pane_id: u64) {
+        let Some(tab) = self.tabs.get_mut(index) else {
+            return;
+        };
+        // The last pane takes the tab with it
+        if !tab.split.as_mut().is_some_and(|s| s.tree.remove(pane_id)) {
+            let tab_id = tab.id;
+            self.close_tab(tab_id);
+            return;
+        }
+        let Some(split) = tab.split.as_mut() else {
+            return;
+        };
+        split.panes.retain(|p| p.id != pane_id);
+        let focused = split.tree.focused();
+        if let Some(pane) = split.panes.iter().find(|p| p.id == focused) {
+            tab.terminal = Arc::clone(&pane.terminal);
+        }
+        if pane_id == tab.id {
+            // The tab's own session went with its pane
+            tab.daemon_cmd_tx = None;
+        }
+        self.notification_bar.forget_tab(pane_id);
+
+        self.resize_terminals();
+        if index == self.active_tab_index {
+            self.pane_focus_changed();
+        }
+    }
+
+    /// Focus pane `pane_id` of the active tab
+    fn focus_pane(&mut self, pane_id: u64) {
+        let Some(tab) = self.tabs.get_mut(self.active_tab_index) else {
+            return;
+        };
+        let Some(split) = tab.split.as_mut() else {
+            return;
+        };
+        if split.tree.focused() == pane_id || !split.tree.focus(pane_id) {
+            return;
+        }
+        if let Some(pane) = split.panes.iter().find(|p| p.id == pane_id) {
+            tab.terminal = Arc::clone(&pane.terminal);
+        }
+        self.pane_focus_changed();
+    }
+
+    /// Show the newly focused pane's title and state
+    fn pane_focus_changed(&mut self) {
+        if let Some(tab) = self.tabs.get(self.active_tab_index) {
+            let focused = tab.split.as_ref().map_or(tab.id, |s| s.tree.focused());
+            self.on_title_changed(focused);
+        }
+        self.sync_read_only();
+        self.sync_transfer();
+        self.invalidate();
+    }
+
+    /// Areas of the active tab's panes within the terminal area, if it is
+    /// split
+    fn pane_layout(&self) -> Option<Vec<(u64, PaneRect)>> {
+        let split = self.tabs.get(self.active_tab_index)?.split.as_ref()?;
+        split.tree.is_split().then(|| {
+            split
+                .tree
+                .layout(self.terminal_area(), self.dpi.scale_f32(PANE_GAP) as f64)
+        })
+    }
+
+    /// Where the active tab's focused pane starts within the terminal area
+    fn focused_pane_origin(&self) -> (f32, f32) {
+        let Some(focused) = self
+            .tabs
+            .get(self.active_tab_index)
+            .and_then(|t| t.split.as_ref())
+            .map(|s| s.tree.focused())
+        else {
+            return (0.0, 0.0);
+        };
+        self.pane_layout()
+            .and_then(|layout| layout.into_iter().find(|(id, _)| *id == focused))
+            .map_or((0.0, 0.0), |(_, area)| (area.x as f32, area.y as f32))
+    }
+
     /// Create a new tab from a template
     pub fn new_tab_from_template(
         &mut self,
         template: &cterm_app::config::StickyTabConfig,
     ) -> Result<u64, Box<dyn std::error::Error>> {
+        let (template, profile) = template.resolve_profile(&self.config);
+        let template = &template;
+        let theme = profile.as_ref().map(|p| p.theme(&self.theme));
+        let auto_switch = self.auto_switcher(profile.as_ref());
+
         // If the template specifies a remote, use a daemon-backed tab
         if let Some(ref remote_name) = template.remote {
             let remote_cfg = self
@@ -267,6 +946,10 @@ impl WindowState {
                     .collect(),
                 // SSH tabs open a native puressh connection on the daemon.
                 ssh: template.ssh.as_ref().map(|s| s.to_ssh_params()),
+                serial: template.serial.as_ref().map(|s| s.to_serial_params()),
+                startup_commands: template.startup_commands.clone(),
+                clear_env: !template.inherit_env,
+                scrollback_lines: profile.as_ref().and_then(|p| p.scrollback_lines),
                 ..Default::default()
             };
             let tab_id = self.spawn_daemon_tab(
@@ -277,6 +960,16 @@ impl WindowState {
                 template.keep_open,
                 remote,
             );
+            if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == tab_id) {
+                if let Some(permissions) = template.permissions {
+                    tab.terminal.lock().unwrap().set_permissions(permissions);
+                }
+                tab.command_notify = template.command_notify;
+                tab.template = Some(template.name.clone());
+                tab.theme = theme;
+                tab.auto_switch = auto_switch;
+            }
+            self.sync_theme();
             return Ok(tab_id);
         }
 
@@ -286,8 +979,15 @@ impl WindowState {
         let (cols, rows) = self.terminal_size();
 
         // Create terminal
-        let screen_config = ScreenConfig {
-            scrollback_lines: self.config.general.scrollback_lines,
+        let screen_config = match profile {
+            Some(ref profile) => profile.screen_config(&self.config),
+            None => ScreenConfig {
+                scrollback_lines: self.config.general.scrollback_lines,
+                selection: self.config.selection.clone(),
+                conformance_level: self.config.general.conformance_level,
+                cursor_style: self.config.appearance.cursor_style.into(),
+                cursor_blink: self.config.appearance.cursor_blink,
+            },
         };
 
         // Build the shell command and args from the template
@@ -351,14 +1051,27 @@ impl WindowState {
                         .map(|(k, v)| (k.clone(), v.clone())),
                 )
                 .collect(),
+            clear_env: !template.inherit_env,
             term: self.config.general.term.clone(),
         };
 
-        let terminal = Terminal::with_shell(cols, rows, screen_config, &pty_config)?;
+        let mut terminal = if let Some(ref serial) = template.serial {
+            // Serial tabs talk to the device instead of spawning a shell
+            let mut terminal = Terminal::new(cols, rows, screen_config);
+            terminal.set_pty(Pty::open_serial(&serial.to_serial_config())?);
+            terminal
+        } else {
+            Terminal::with_shell(cols, rows, screen_config, &pty_config)?
+        };
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(template.permissions.unwrap_or(self.config.permissions));
+        self.triggers.attach(&mut terminal);
+        terminal.set_color_scheme(self.theme.colors.color_scheme());
         let terminal = Arc::new(Mutex::new(terminal));
 
         // Start PTY reader thread
         let reader_handle = self.start_pty_reader(tab_id, Arc::clone(&terminal));
+        self.queue_startup_commands(tab_id, &template.startup_commands);
 
         let entry = TabEntry {
             id: tab_id,
@@ -371,6 +1084,15 @@ impl WindowState {
             reader_handle: Some(reader_handle),
             session_id: None,
             daemon_cmd_tx: None,
+            command_notify: template.command_notify,
+            template: Some(template.name.clone()),
+            playback: None,
+            output_stats: OutputStats::default(),
+            tmux_pane: None,
+            split: None,
+            theme,
+            auto_switch,
+            auto_switch_saved: None,
         };
 
         self.tabs.push(entry);
@@ -379,6 +1101,7 @@ impl WindowState {
         // Update tab bar
         self.tab_bar.add_tab(tab_id, &template.name);
         self.tab_bar.set_active(tab_id);
+        self.sync_theme();
 
         // Set tab color if specified
         if let Some(ref color_hex) = template.color {
@@ -408,6 +1131,10 @@ impl WindowState {
 
         let screen_config = ScreenConfig {
             scrollback_lines: self.config.general.scrollback_lines,
+            selection: self.config.selection.clone(),
+            conformance_level: self.config.general.conformance_level,
+            cursor_style: self.config.appearance.cursor_style.into(),
+            cursor_blink: self.config.appearance.cursor_blink,
         };
 
         // Build the docker command based on selection
@@ -458,10 +1185,15 @@ impl WindowState {
                 .iter()
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect(),
+            clear_env: false,
             term: self.config.general.term.clone(),
         };
 
-        let terminal = Terminal::with_shell(cols, rows, screen_config, &pty_config)?;
+        let mut terminal = Terminal::with_shell(cols, rows, screen_config, &pty_config)?;
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(self.config.permissions);
+        self.triggers.attach(&mut terminal);
+        terminal.set_color_scheme(self.theme.colors.color_scheme());
         let terminal = Arc::new(Mutex::new(terminal));
 
         let reader_handle = self.start_pty_reader(tab_id, Arc::clone(&terminal));
@@ -477,6 +1209,15 @@ impl WindowState {
             reader_handle: Some(reader_handle),
             session_id: None,
             daemon_cmd_tx: None,
+            command_notify: None,
+            template: None,
+            playback: None,
+            output_stats: OutputStats::default(),
+            tmux_pane: None,
+            split: None,
+            theme: None,
+            auto_switch: self.auto_switcher(None),
+            auto_switch_saved: None,
         };
 
         self.tabs.push(entry);
@@ -484,6 +1225,7 @@ impl WindowState {
 
         self.tab_bar.add_tab(tab_id, &title);
         self.tab_bar.set_active(tab_id);
+        self.sync_theme();
 
         self.invalidate();
 
@@ -510,8 +1252,16 @@ impl WindowState {
 
         let screen_config = ScreenConfig {
             scrollback_lines: self.config.general.scrollback_lines,
+            selection: self.config.selection.clone(),
+            conformance_level: self.config.general.conformance_level,
+            cursor_style: self.config.appearance.cursor_style.into(),
+            cursor_blink: self.config.appearance.cursor_blink,
         };
         let mut terminal = Terminal::new(cols, rows, screen_config);
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(self.config.permissions);
+        self.triggers.attach(&mut terminal);
+        terminal.set_color_scheme(self.theme.colors.color_scheme());
 
         let (cmd_tx, cmd_rx) = tokio::sync::mpsc::unbounded_channel::<DaemonCmd>();
         let write_tx = cmd_tx.clone();
@@ -533,12 +1283,22 @@ impl WindowState {
             reader_handle: None,
             session_id: None,
             daemon_cmd_tx: Some(cmd_tx),
+            command_notify: None,
+            template: None,
+            playback: None,
+            output_stats: OutputStats::default(),
+            tmux_pane: None,
+            split: None,
+            theme: None,
+            auto_switch: self.auto_switcher(None),
+            auto_switch_saved: None,
         };
 
         self.tabs.push(entry);
         self.active_tab_index = self.tabs.len() - 1;
         self.tab_bar.add_tab(tab_id, &title);
         self.tab_bar.set_active(tab_id);
+        self.sync_theme();
 
         if let Some(ref color_hex) = color {
             let rgb = parse_hex_color(color_hex);
@@ -589,8 +1349,16 @@ impl WindowState {
 
         let screen_config = ScreenConfig {
             scrollback_lines: self.config.general.scrollback_lines,
+            selection: self.config.selection.clone(),
+            conformance_level: self.config.general.conformance_level,
+            cursor_style: self.config.appearance.cursor_style.into(),
+            cursor_blink: self.config.appearance.cursor_blink,
         };
         let mut terminal = Terminal::new(cols, rows, screen_config);
+        terminal.set_file_transfers_enabled(true);
+        terminal.set_permissions(self.config.permissions);
+        self.triggers.attach(&mut terminal);
+        terminal.set_color_scheme(self.theme.colors.color_scheme());
 
         // Apply screen snapshot if available
         if let Some(ref screen_data) = screen_snapshot {
@@ -622,12 +1390,22 @@ impl WindowState {
             reader_handle: None,
             session_id: Some(session_id.to_string()),
             daemon_cmd_tx: Some(cmd_tx),
+            command_notify: None,
+            template: None,
+            playback: None,
+            output_stats: OutputStats::default(),
+            tmux_pane: None,
+            split: None,
+            theme: None,
+            auto_switch: self.auto_switcher(None),
+            auto_switch_saved: None,
         };
 
         self.tabs.push(entry);
         self.active_tab_index = self.tabs.len() - 1;
         self.tab_bar.add_tab(tab_id, &display_title);
         self.tab_bar.set_active(tab_id);
+        self.sync_theme();
 
         if let Some(ref color_hex) = color {
             let rgb = parse_hex_color(color_hex);
@@ -700,6 +1478,7 @@ impl WindowState {
                 };
 
                 // Process the data (briefly lock the terminal)
+                let mut tmux_control = Vec::new();
                 {
                     let mut term = terminal.lock().unwrap();
                     let events = term.process(&buffer[..bytes_read]);
@@ -727,6 +1506,37 @@ impl WindowState {
                                     LPARAM(0),
                                 );
                             },
+                            TerminalEvent::PointerShapeChanged(_) => {
+                                post_message(hwnd, WM_APP_POINTER_SHAPE, tab_id);
+                            }
+                            TerminalEvent::CommandFinished(finished) => {
+                                post_command_finished(hwnd, tab_id, finished);
+                            }
+                            TerminalEvent::OutputStats(stats) => {
+                                post_output_stats(hwnd, tab_id, stats);
+                            }
+                            TerminalEvent::TmuxControl(control) => {
+                                tmux_control.extend(control);
+                            }
+                            TerminalEvent::WatchMatched(found) => {
+                                post_watch_matched(hwnd, tab_id, found);
+                            }
+                            TerminalEvent::TransferStarted(..)
+                            | TerminalEvent::TransferFinished => {
+                                post_message(hwnd, WM_APP_TRANSFER_CHANGED, tab_id);
+                            }
+                            TerminalEvent::TransferSendRequested => {
+                                post_message(hwnd, WM_APP_TRANSFER_FILES, tab_id);
+                            }
+                            TerminalEvent::UploadRequested => {
+                                post_message(hwnd, WM_APP_UPLOAD_FILES, tab_id);
+                            }
+                            TerminalEvent::ClipboardRequest(_)
+                            | TerminalEvent::Notification(_)
+                            | TerminalEvent::PermissionRequested(_)
+                            | TerminalEvent::LinesCompleted(_) => {
+                                post_terminal_request(hwnd, tab_id, event);
+                            }
                             TerminalEvent::ProcessExited(_) => {
                                 unsafe {
                                     let _ = PostMessageW(
@@ -742,6 +1552,9 @@ impl WindowState {
                         }
                     }
                 }
+                if !tmux_control.is_empty() {
+                    post_tmux_control(hwnd, tab_id, tmux_control);
+                }
 
                 // Request redraw
                 unsafe {
@@ -791,8 +1604,15 @@ impl WindowState {
     /// Close a tab
     pub fn close_tab(&mut self, tab_id: u64) {
         if let Some(index) = self.tabs.iter().position(|t| t.id == tab_id) {
-            self.tabs.remove(index);
+            let tab = self.tabs.remove(index);
+            self.remember_closed_tab(&tab);
+            self.tmux.remove(&tab_id);
+            self.pane_triggers.remove(&tab_id);
             self.tab_bar.remove_tab(tab_id);
+            self.notification_bar.forget_tab(tab_id);
+            for pane in tab.split.iter().flat_map(|s| &s.panes) {
+                self.notification_bar.forget_tab(pane.id);
+            }
 
             if self.tabs.is_empty() {
                 // Close window
@@ -806,8 +1626,127 @@ impl WindowState {
                 }
                 let new_active_id = self.tabs[self.active_tab_index].id;
                 self.tab_bar.set_active(new_active_id);
+                self.sync_theme();
+                self.sync_read_only();
+                self.sync_recording();
+                self.sync_playback();
+                self.sync_output_stats();
+                self.sync_transfer();
+            }
+        }
+    }
+
+    /// What reopening a tab needs: its template or working directory,
+    /// title, color and scrollback
+    fn closed_tab(tab: &TabEntry) -> ClosedTab {
+        let mut closed = ClosedTab::new(tab.title.clone());
+        closed.template = tab.template.clone();
+        closed.color = tab.color.clone();
+        if let Ok(term) = tab.terminal.lock() {
+            closed.cwd = term.screen().local_current_dir().map(String::from);
+            closed.capture_scrollback(term.screen());
+        }
+        closed
+    }
+
+    /// Record a closing tab for Reopen Closed Tab and the usage statistics
+    fn remember_closed_tab(&self, tab: &TabEntry) {
+        let closed = Self::closed_tab(tab);
+        if let Ok(term) = tab.terminal.lock() {
+            cterm_app::usage_stats::record_session(term.age());
+        }
+        closed_tabs::remember(closed, &self.config.tabs);
+    }
+
+    /// Reopen the most recently closed tab with its template or working
+    /// directory, color and scrollback
+    pub fn reopen_closed_tab(&mut self) {
+        let Some(closed) = closed_tabs::take_last() else {
+            return;
+        };
+        if let Err(e) = self.open_closed_tab(closed) {
+            log::error!("Failed to reopen closed tab: {}", e);
+        }
+        self.invalidate();
+    }
+
+    /// Open a tab like `closed` from its template or in its working
+    /// directory, with its color and scrollback
+    fn open_closed_tab(&mut self, closed: ClosedTab) -> Result<u64, Box<dyn std::error::Error>> {
+        let templates = cterm_app::config::load_sticky_tabs().unwrap_or_default();
+        let tab_id = match closed.find_template(&templates) {
+            Some(template) => self.new_tab_from_template(template)?,
+            None => self.new_tab_in(closed.cwd.as_ref().map(PathBuf::from))?,
+        };
+
+        if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == tab_id) {
+            if tab.template.is_none() && closed.color.is_some() {
+                tab.color = closed.color.clone();
+                let rgb = closed.color.as_deref().and_then(parse_hex_color);
+                self.tab_bar.set_color(tab_id, rgb);
             }
+            if let Ok(mut term) = tab.terminal.lock() {
+                closed.replay_scrollback(&mut term);
+            }
+        }
+        Ok(tab_id)
+    }
+
+    /// The window's tabs and placement, saved for session restore
+    fn session_state(&self) -> WindowSessionState {
+        let mut placement = WINDOWPLACEMENT {
+            length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+            ..Default::default()
+        };
+        let _ = unsafe { GetWindowPlacement(self.hwnd, &mut placement) };
+        let rect = placement.rcNormalPosition;
+        WindowSessionState {
+            geometry: WindowGeometry {
+                x: rect.left,
+                y: rect.top,
+                width: (rect.right - rect.left).max(0) as u32,
+                height: (rect.bottom - rect.top).max(0) as u32,
+                maximized: placement.showCmd == SW_SHOWMAXIMIZED.0 as u32,
+            },
+            tabs: self
+                .tabs
+                .iter()
+                .map(|tab| Self::closed_tab(tab).into())
+                .collect(),
+            active_tab: self.active_tab_index,
+        }
+    }
+
+    /// Reopen the tabs and placement of a window saved in a previous session
+    fn restore(&mut self, saved: &WindowSessionState) -> Result<(), Box<dyn std::error::Error>> {
+        for tab in &saved.tabs {
+            if let Err(e) = self.open_closed_tab(tab.to_closed_tab()) {
+                log::error!("Failed to restore tab '{}': {}", tab.title, e);
+            }
+        }
+        if self.tabs.is_empty() {
+            self.new_tab()?;
+        }
+        self.switch_to_tab(saved.active_tab);
+
+        let geometry = &saved.geometry;
+        if geometry.width > 0 && geometry.height > 0 {
+            let _ = unsafe {
+                SetWindowPos(
+                    self.hwnd,
+                    None,
+                    geometry.x,
+                    geometry.y,
+                    geometry.width as i32,
+                    geometry.height as i32,
+                    SWP_NOZORDER,
+                )
+            };
         }
+        if geometry.maximized {
+            let _ = unsafe { ShowWindow(self.hwnd, SW_MAXIMIZE) };
+        }
+        Ok(())
     }
 
     /// Switch to tab
@@ -817,12 +1756,19 @@ impl WindowState {
             let tab_id = self.tabs[index].id;
             self.tab_bar.set_active(tab_id);
             self.tab_bar.clear_bell(tab_id);
+            self.tab_bar.set_unread(tab_id, false);
             self.tabs[index].has_bell = false;
 
             // Apply per-tab background color override
             if let Some(ref mut renderer) = self.renderer {
                 renderer.set_background_override(self.tabs[index].background_color.as_deref());
             }
+            self.sync_theme();
+            self.sync_read_only();
+            self.sync_recording();
+            self.sync_playback();
+            self.sync_output_stats();
+            self.sync_transfer();
 
             self.invalidate();
         }
@@ -887,6 +1833,16 @@ impl WindowState {
 
     /// Get terminal size in cells
     pub fn terminal_size(&self) -> (usize, usize) {
+        let area = self.terminal_area();
+        if let Some(ref renderer) = self.renderer {
+            renderer.terminal_size(area.width as u32, area.height as u32)
+        } else {
+            (80, 24)
+        }
+    }
+
+    /// Area the terminal is drawn in, in pixels from its top-left corner
+    fn terminal_area(&self) -> PaneRect {
         let mut rect = RECT::default();
         unsafe { GetClientRect(self.hwnd, &mut rect).ok() };
 
@@ -898,11 +1854,7 @@ impl WindowState {
         let notification_bar_height = self.notification_bar.height() as u32;
         let terminal_height = height.saturating_sub(tab_bar_height + notification_bar_height);
 
-        if let Some(ref renderer) = self.renderer {
-            renderer.terminal_size(width, terminal_height)
-        } else {
-            (80, 24)
-        }
+        PaneRect::new(0.0, 0.0, width as f64, terminal_height as f64)
     }
 
     /// Handle window resize
@@ -911,9 +1863,38 @@ impl WindowState {
             renderer.resize(width, height).ok();
         }
 
-        // Resize all terminals
+        self.resize_terminals();
+    }
+
+    /// Resize all terminals (and their PTYs) to fit the current client area
+    fn resize_terminals(&self) {
         let (cols, rows) = self.terminal_size();
-        for tab in &self.tabs {
+        // tmux sizes its panes to fit the window
+        for gateway in self.tmux.values() {
+            gateway.resize(cols, rows);
+        }
+        // Playback keeps the size of the recording
+        for tab in self
+            .tabs
+            .iter()
+            .filter(|t| t.playback.is_none() && t.tmux_pane.is_none())
+        {
+            // Split tabs give each pane the size of its area
+            if let (Some(split), Some(renderer)) = (&tab.split, &self.renderer) {
+                let gap = self.dpi.scale_f32(PANE_GAP) as f64;
+                for (id, area) in split.tree.layout(self.terminal_area(), gap) {
+                    let Some(pane) = split.panes.iter().find(|p| p.id == id) else {
+                        continue;
+                    };
+                    let (cols, rows) =
+                        renderer.terminal_size(area.width as u32, area.height as u32);
+                    pane.terminal.lock().unwrap().resize(cols, rows);
+                    if let Some(ref tx) = pane.daemon_cmd_tx {
+                        let _ = tx.send(DaemonCmd::Resize(cols as u32, rows as u32));
+                    }
+                }
+                continue;
+            }
             let mut term = tab.terminal.lock().unwrap();
             term.resize(cols, rows);
             // Forward resize to daemon if this is a daemon-backed tab
@@ -923,15 +1904,41 @@ impl WindowState {
         }
     }
 
-    /// Handle DPI change
-    pub fn on_dpi_changed(&mut self, dpi: u32) {
+    /// Handle DPI change, e.g. when the window moves to another monitor
+    ///
+    /// `suggested` is the window rect Windows proposes for the new DPI. Fonts
+    /// and cell metrics are rescaled before the window is moved there, so the
+    /// WM_SIZE it triggers already lays the grid out with the new metrics.
+    pub fn on_dpi_changed(&mut self, dpi: u32, suggested: &RECT) {
+        log::debug!("DPI changed from {} to {}", self.dpi.dpi, dpi);
+
         self.dpi = DpiInfo::from_dpi(dpi);
         self.tab_bar.set_dpi(self.dpi);
         self.notification_bar.set_dpi(self.dpi);
 
         if let Some(ref mut renderer) = self.renderer {
-            renderer.update_dpi(dpi).ok();
+            if let Err(e) = renderer.update_dpi(dpi) {
+                log::error!("Failed to rescale renderer for DPI {}: {}", dpi, e);
+            }
+        }
+
+        unsafe {
+            SetWindowPos(
+                self.hwnd,
+                None,
+                suggested.left,
+                suggested.top,
+                suggested.right - suggested.left,
+                suggested.bottom - suggested.top,
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            )
         }
+        .ok();
+
+        // The suggested rect may keep the client size unchanged (no WM_SIZE),
+        // but the cell size did change
+        self.resize_terminals();
+        self.invalidate();
     }
 
     /// Invalidate and request redraw
@@ -950,6 +1957,33 @@ impl WindowState {
             return Ok(());
         }
 
+        // Split tabs draw every pane in its area
+        if let Some(layout) = self.pane_layout() {
+            let tab = &self.tabs[self.active_tab_index];
+            let focused = tab.split.as_ref().map_or(tab.id, |s| s.tree.focused());
+            let terminals: Vec<_> = layout
+                .iter()
+                .filter_map(|(id, area)| Some((*id, tab.pane_terminal(*id)?, *area)))
+                .collect();
+            let guards: Vec<_> = terminals
+                .iter()
+                .map(|(id, terminal, area)| (*id, terminal.lock().unwrap(), *area))
+                .collect();
+            let panes: Vec<_> = guards
+                .iter()
+                .map(|(_, term, area)| (term.screen(), *area))
+                .collect();
+            let focused = guards
+                .iter()
+                .position(|(id, ..)| *id == focused)
+                .unwrap_or(0);
+            let gap = self.dpi.scale_f32(PANE_GAP);
+            if let Some(renderer) = self.renderer.as_mut() {
+                renderer.render_split(&panes, focused, gap)?;
+            }
+            return Ok(());
+        }
+
         // Get the active terminal first (before borrowing renderer)
         let terminal = self.active_terminal();
 
@@ -966,11 +2000,57 @@ impl WindowState {
     }
 
     /// Handle keyboard input
-    pub fn on_key_down(&mut self, vk: u16, _scancode: u16) -> bool {
+    pub fn on_key_down(&mut self, vk: u16, scancode: u16, repeat: bool) -> bool {
         let modifiers = keycode::get_modifiers();
 
+        if self.pointer_over_terminal()
+            && self.pointer_hider.key_pressed(keycode::is_modifier_key(vk))
+        {
+            let _ = unsafe { SetCursor(None) };
+            track_mouse_leave(self.hwnd);
+        }
+
+        // AltGr characters and dead keys are typed text: let TranslateMessage
+        // turn them into WM_CHAR (composed with the next key for dead keys)
+        // instead of matching them as Ctrl+Alt shortcuts or control characters.
+        if !keycode::is_modifier_key(vk) {
+            let text = keycode::key_text(vk, scancode);
+            if text == keycode::KeyText::Dead || keycode::is_altgr_text(modifiers, &text) {
+                return false;
+            }
+        }
+
+        // Hint mode takes every key until a label is chosen or cancelled;
+        // letters arrive through WM_CHAR so Shift picks the copy action
+        if self.hints.is_some() {
+            match vk {
+                0x1B => self.hint_input(HintInput::NoMatch), // VK_ESCAPE
+                0x08 => {
+                    // VK_BACK
+                    if let Some(ref mut mode) = self.hints {
+                        mode.backspace();
+                    }
+                    self.hint_input(HintInput::Pending);
+                }
+                0x41..=0x5A => return false,
+                _ => {}
+            }
+            return !keycode::is_modifier_key(vk);
+        }
+
+        // Escape cancels a paste in progress
+        if vk == 0x1B && self.cancel_paste() {
+            return true;
+        }
+
         // Check for shortcuts first
         if let Some(key) = keycode::vk_to_keycode(vk) {
+            self.record_keystroke(key, modifiers);
+            // Ctrl+Shift+Enter and friends answer the notification bar
+            if let Some(action) = self.notification_bar.action_for_key(key, modifiers) {
+                self.handle_notification_action(action);
+                return true;
+            }
             if let Some(action) = self.shortcuts.match_event(key, modifiers) {
                 self.handle_action(action.clone());
                 return true;
@@ -982,11 +2062,93 @@ impl WindowState {
             return false;
         }
 
+        // Playback tabs take unmodified keys as player controls; text keys
+        // arrive through WM_CHAR
+        if self
+            .tabs
+            .get(self.active_tab_index)
+            .is_some_and(|t| t.playback.is_some())
+        {
+            let key = match vk {
+                0x25 => Key::Left,  // VK_LEFT
+                0x26 => Key::Up,    // VK_UP
+                0x27 => Key::Right, // VK_RIGHT
+                0x28 => Key::Down,  // VK_DOWN
+                0x24 => Key::Home,  // VK_HOME
+                0x23 => Key::End,   // VK_END
+                _ => return false,
+            };
+            return !modifiers.intersects(Modifiers::CTRL | Modifiers::ALT)
+                && self.playback_key(key);
+        }
+
+        // Read-only: swallow the key (and its WM_CHAR)
+        if self
+            .active_terminal()
+            .is_some_and(|t| t.lock().unwrap().is_input_locked())
+        {
+            let _ = unsafe { MessageBeep(MB_OK) };
+            return true;
+        }
+
+        // Typing again ends the search shown in the status strip
+        if self.status.clear_search() {
+            if let Some(terminal) = self.active_terminal() {
+                terminal.lock().unwrap().screen_mut().set_search_lines([]);
+            }
+            self.sync_status();
+            self.invalidate();
+        }
+
         // Send to terminal
         if let Some(terminal) = self.active_terminal() {
             let mut term = terminal.lock().unwrap();
             let app_cursor = term.screen().modes.application_cursor;
 
+            // Kitty keyboard protocol: encode special keys and modified
+            // characters as CSI u. Plain text still arrives via WM_CHAR.
+            let flags = term.screen().keyboard_flags();
+            if !flags.is_empty() {
+                if let Some(key) = keycode::vk_to_terminal_key(vk) {
+                    let is_text = matches!(key, cterm_core::term::Key::Char(_));
+                    let core_mods =
+                        cterm_core::term::Modifiers::from_bits_truncate(modifiers.bits());
+                    if !is_text
+                        || modifiers.intersects(Modifiers::CTRL | Modifiers::ALT)
+                        || flags.contains(cterm_core::KeyboardFlags::REPORT_ALL_KEYS)
+                    {
+                        let kind = if repeat {
+                            cterm_core::KeyEventKind::Repeat
+                        } else {
+                            cterm_core::KeyEventKind::Press
+                        };
+                        if let Some(bytes) = term.handle_key_event(key, core_mods, kind) {
+                            term.write(&bytes).ok();
+                            drop(term);
+                            self.invalidate();
+                            return true;
+                        }
+                    }
+                }
+            }
+
+            // Application keypad mode (DECKPAM): keypad keys send SS3
+            // sequences instead of the digits WM_CHAR would type
+            if term.screen().modes.application_keypad {
+                if let Some(key @ cterm_core::term::Key::Keypad(_)) =
+                    keycode::vk_to_terminal_key(vk)
+                {
+                    let core_mods =
+                        cterm_core::term::Modifiers::from_bits_truncate(modifiers.bits());
+                    if let Some(bytes) = term.handle_key(key, core_mods) {
+                        term.write(&bytes).ok();
+                        drop(term);
+                        self.invalidate();
+                        return true;
+                    }
+                }
+            }
+
             // Get terminal sequence for special keys
             if let Some(seq) = keycode::vk_to_terminal_seq(vk, modifiers, app_cursor) {
                 term.write(seq.as_bytes()).ok();
@@ -1015,8 +2177,36 @@ impl WindowState {
         false
     }
 
+    /// Handle key release. Only reported when the application asked for
+    /// kitty keyboard event types.
+    pub fn on_key_up(&mut self, vk: u16) {
+        let Some(key) = keycode::vk_to_terminal_key(vk) else {
+            return;
+        };
+        let modifiers = keycode::get_modifiers();
+        if let Some(terminal) = self.active_terminal() {
+            let mut term = terminal.lock().unwrap();
+            let core_mods = cterm_core::term::Modifiers::from_bits_truncate(modifiers.bits());
+            if let Some(bytes) =
+                term.handle_key_event(key, core_mods, cterm_core::KeyEventKind::Release)
+            {
+                term.write(&bytes).ok();
+            }
+        }
+    }
+
     /// Handle character input
     pub fn on_char(&mut self, c: char) {
+        if let Some(ref mut mode) = self.hints {
+            if c.is_alphabetic() {
+                let input = mode.input(c);
+                self.hint_input(input);
+            }
+            return;
+        }
+        if self.playback_key(Key::Char(c)) {
+            return;
+        }
         if let Some(terminal) = self.active_terminal() {
             let mut term = terminal.lock().unwrap();
             let mut buf = [0u8; 4];
@@ -1042,6 +2232,7 @@ impl WindowState {
                     self.close_tab(id);
                 }
             }
+            Action::ReopenClosedTab => self.reopen_closed_tab(),
             Action::NextTab => self.next_tab(),
             Action::PrevTab => self.prev_tab(),
             Action::NextAlertedTab => self.next_alerted_tab(),
@@ -1051,9 +2242,13 @@ impl WindowState {
             }
             Action::Copy => self.copy_selection(),
             Action::Paste => self.paste(),
+            Action::PasteHistory => self.paste_history(),
             Action::ZoomIn => self.zoom_in(),
             Action::ZoomOut => self.zoom_out(),
             Action::ZoomReset => self.zoom_reset(),
+            Action::MagnifyIn => self.magnify(Magnifier::magnify_in),
+            Action::MagnifyOut => self.magnify(Magnifier::magnify_out),
+            Action::MagnifyReset => self.magnify(Magnifier::reset),
             Action::CloseWindow => {
                 unsafe {
                     let _ = PostMessageW(Some(self.hwnd), WM_CLOSE, WPARAM(0), LPARAM(0));
@@ -1072,29 +2267,179 @@ impl WindowState {
                 }
                 self.invalidate();
             }
+            Action::ToggleAsciiArtSafe => self.toggle_ascii_art_safe(),
+            Action::ToggleReadOnly => self.toggle_read_only(),
+            Action::TogglePresentationMode => self.toggle_presentation_mode(),
+            Action::HintMode => self.start_hint_mode(),
+            Action::SelectCommandOutput => self.select_command_output(),
+            Action::PreviousPrompt => self.scroll_to_prompt(false),
+            Action::NextPrompt => self.scroll_to_prompt(true),
+            Action::SplitRight => self.split_pane(SplitAxis::Horizontal),
+            Action::SplitDown => self.split_pane(SplitAxis::Vertical),
+            Action::ClosePane => {
+                if let Some(tab) = self.tabs.get(self.active_tab_index) {
+                    let focused = tab.split.as_ref().map_or(tab.id, |s| s.tree.focused());
+                    self.close_pane(self.active_tab_index, focused);
+                }
+            }
+            Action::FocusPane(direction) => self.focus_pane_toward(direction),
+            Action::ResizePane(direction) => {
+                let resized = self
+                    .tabs
+                    .get_mut(self.active_tab_index)
+                    .and_then(|t| t.split.as_mut())
+                    .is_some_and(|s| s.tree.resize(direction, PANE_RESIZE_STEP));
+                if resized {
+                    self.resize_terminals();
+                    self.invalidate();
+                }
+            }
+            Action::ToggleScrollLock => {
+                if let Some(split) = self
+                    .tabs
+                    .get_mut(self.active_tab_index)
+                    .and_then(|t| t.split.as_mut())
+                {
+                    split.tree.toggle_scroll_lock();
+                }
+            }
             _ => {}
         }
     }
 
+    /// Focus the pane beside the focused one in `direction`
+    fn focus_pane_toward(&mut self, direction: PaneDirection) {
+        let Some(layout) = self.pane_layout() else {
+            return;
+        };
+        let Some(focused) = self
+            .tabs
+            .get(self.active_tab_index)
+            .and_then(|t| t.split.as_ref())
+            .map(|s| s.tree.focused())
+        else {
+            return;
+        };
+        if let Some(id) = session::pane_in_direction(&layout, focused, direction) {
+            self.focus_pane(id);
+        }
+    }
+
+    /// Toggle ligature-free, cell-exact rendering for the active tab
+    fn toggle_ascii_art_safe(&mut self) {
+        if let Some(terminal) = self.active_terminal() {
+            let mut term = terminal.lock().unwrap();
+            let screen = term.screen_mut();
+            screen.modes.ascii_art_safe = !screen.modes.ascii_art_safe;
+            drop(term);
+        }
+        self.invalidate();
+    }
+
+    /// Lock or unlock input to the active tab
+    fn toggle_read_only(&mut self) {
+        if let Some(terminal) = self.active_terminal() {
+            let mut term = terminal.lock().unwrap();
+            let locked = !term.is_input_locked();
+            term.set_input_locked(locked);
+        }
+        self.sync_read_only();
+        self.invalidate();
+    }
+
+    /// Show the active tab's read-only state in the status strip
+    fn sync_read_only(&mut self) {
+        // Playback tabs are always locked and show the player instead
+        let locked = self
+            .tabs
+            .get(self.active_tab_index)
+            .is_some_and(|t| t.playback.is_none() && t.terminal.lock().unwrap().is_input_locked());
+        if self.status.set_read_only(locked) {
+            self.sync_status();
+        }
+    }
+
+    /// Open a window by launching a new instance of the application
+    fn new_window(&self) {
+        if let Ok(exe) = std::env::current_exe() {
+            std::process::Command::new(exe).spawn().ok();
+        }
+    }
+
+    /// Carry out a `cterm new-tab` or `cterm new-window` request
+    fn on_control_request(&mut self, request: ControlRequest) {
+        match request {
+            ControlRequest::NewTab { directory } => {
+                if let Err(e) = self.new_tab_in(directory) {
+                    log::error!("Failed to open tab: {}", e);
+                }
+                unsafe {
+                    let _ = SetForegroundWindow(self.hwnd);
+                }
+                self.invalidate();
+            }
+            ControlRequest::NewWindow => self.new_window(),
+            ControlRequest::OpenWorkspace { name } => {
+                self.open_workspace(&name);
+                unsafe {
+                    let _ = SetForegroundWindow(self.hwnd);
+                }
+            }
+        }
+    }
+
+    /// Open the tabs and panes of workspace `name`
+    pub fn open_workspace(&mut self, name: &str) {
+        let Some(workspace) = workspace::find(&self.config, name).cloned() else {
+            log::warn!("No workspace named {:?}", name);
+            return;
+        };
+        let templates = cterm_app::config::load_sticky_tabs().unwrap_or_default();
+        for tab in workspace::resolve(&workspace, &templates) {
+            if let Err(e) = self.new_tab_from_template(&tab.template) {
+                log::error!(
+                    "Failed to open workspace tab {:?}: {}",
+                    tab.template.name,
+                    e
+                );
+                continue;
+            }
+            for pane in &tab.panes {
+                self.split_pane_with(pane.axis, Some(&pane.template));
+            }
+        }
+        self.invalidate();
+    }
+
     /// Handle menu command
     pub fn on_menu_command(&mut self, cmd: u16) {
+        if let Some(index) = menu::workspace_index(cmd) {
+            if let Some(name) = self.config.workspaces.get(index).map(|w| w.name.clone()) {
+                self.open_workspace(&name);
+            }
+            return;
+        }
+        if let Some(index) = menu::profile_index(cmd) {
+            if let Some(profile) = self.config.profiles.get(index).cloned() {
+                if let Err(e) = self.new_profile_tab(&profile) {
+                    log::error!("Failed to open profile {:?}: {}", profile.name, e);
+                }
+            }
+            return;
+        }
         if let Some(action) = MenuAction::from_id(cmd) {
             match action {
                 MenuAction::NewTab => {
                     self.new_tab().ok();
                 }
-                MenuAction::NewWindow => {
-                    // Launch a new instance of the application
-                    if let Ok(exe) = std::env::current_exe() {
-                        std::process::Command::new(exe).spawn().ok();
-                    }
-                }
+                MenuAction::NewWindow => self.new_window(),
                 MenuAction::CloseTab => {
                     if let Some(tab) = self.tabs.get(self.active_tab_index) {
                         let id = tab.id;
                         self.close_tab(id);
                     }
                 }
+                MenuAction::ReopenClosedTab => self.reopen_closed_tab(),
                 MenuAction::CloseOtherTabs => {
                     // Close all but active
                     let active_id = self.tabs.get(self.active_tab_index).map(|t| t.id);
@@ -1136,18 +2481,58 @@ impl WindowState {
                         }
                     }
                 }
+                MenuAction::SerialConnect => {
+                    if let Some(serial) =
+                        crate::serial_dialog::show_serial_dialog(self.hwnd.0 as *mut _)
+                    {
+                        let template = cterm_app::config::StickyTabConfig::serial(serial);
+                        if let Err(e) = self.new_tab_from_template(&template) {
+                            log::error!("Failed to open serial port: {}", e);
+                            crate::dialogs::show_error(
+                                self.hwnd.0 as *mut _,
+                                "Serial Error",
+                                &format!("Failed to open serial port: {}", e),
+                            );
+                        }
+                    }
+                }
                 MenuAction::Quit => {
                     unsafe {
                         let _ = PostMessageW(Some(self.hwnd), WM_CLOSE, WPARAM(0), LPARAM(0));
                     };
                 }
+                MenuAction::SaveBuffer => self.save_buffer(),
+                MenuAction::Record => self.toggle_recording(),
+                MenuAction::PlayRecording => {
+                    if let Some(path) = crate::dialogs::show_open_file_dialog(
+                        self.hwnd.0 as *mut _,
+                        "Play Recording",
+                        "Recordings (*.cast)\0*.cast\0All Files\0*.*\0\0",
+                    ) {
+                        if let Err(e) = self.play_recording(&path) {
+                            log::error!("Failed to open recording {:?}: {}", path, e);
+                            crate::dialogs::show_error_msg(
+                                self.hwnd,
+                                &format!("Failed to open {}: {}", path.display(), e),
+                            );
+                        }
+                    }
+                }
                 MenuAction::Copy => self.copy_selection(),
                 MenuAction::CopyHtml => self.copy_selection_as_html(),
                 MenuAction::Paste => self.paste(),
+                MenuAction::PasteHistory => self.paste_history(),
                 MenuAction::SelectAll => self.select_all(),
+                MenuAction::SelectCommandOutput => self.select_command_output(),
+                MenuAction::PreviousPrompt => self.scroll_to_prompt(false),
+                MenuAction::NextPrompt => self.scroll_to_prompt(true),
                 MenuAction::ZoomIn => self.zoom_in(),
                 MenuAction::ZoomOut => self.zoom_out(),
                 MenuAction::ZoomReset => self.zoom_reset(),
+                MenuAction::MagnifyIn => self.magnify(Magnifier::magnify_in),
+                MenuAction::MagnifyOut => self.magnify(Magnifier::magnify_out),
+                MenuAction::MagnifyReset => self.magnify(Magnifier::reset),
+                MenuAction::PresentationMode => self.toggle_presentation_mode(),
                 MenuAction::Fullscreen => self.toggle_fullscreen(),
                 MenuAction::SetTitle => self.show_set_title_dialog(),
                 MenuAction::SetColor => self.show_set_color_dialog(),
@@ -1168,6 +2553,10 @@ impl WindowState {
                     }
                     self.invalidate();
                 }
+                MenuAction::AsciiArtSafe => self.toggle_ascii_art_safe(),
+                MenuAction::ReadOnly => self.toggle_read_only(),
+                MenuAction::Hints => self.start_hint_mode(),
+                MenuAction::WatchOutput => self.show_watch_dialog(),
                 MenuAction::SendSignalInt => self.send_signal(2), // SIGINT
                 MenuAction::SendSignalKill => self.send_signal(9), // SIGKILL
                 MenuAction::SendSignalHup => self.send_signal(1), // SIGHUP
@@ -1187,9 +2576,23 @@ impl WindowState {
                 MenuAction::Preferences => {
                     if crate::preferences_dialog::show_preferences_dialog(self.hwnd.0 as *mut _) {
                         // Reload config and apply changes
-                        if let Ok(config) = cterm_app::load_config() {
+                        if let Ok(mut config) = cterm_app::load_config() {
+                            // A changed appearance arrives as WM_APP_APPEARANCE
+                            config.appearance = self.config.appearance.clone();
+                            self.tab_bar.set_widgets(config.widgets);
+                            self.tab_bar.set_config(&config.tabs);
+                            sync_widgets_timer(self.hwnd, config.widgets.any());
+                            sync_segments_timer(self.hwnd, !config.status_segments.is_empty());
+                            if self
+                                .pointer_hider
+                                .set_enabled(config.input.hide_pointer_while_typing)
+                            {
+                                set_pointer_cursor(self.pointer_shape());
+                            }
                             self.config = config;
-                            // TODO: Apply theme and other changes without restart
+                            self.sync_translucency();
+                            self.resize_terminals();
+                            self.invalidate();
                             log::info!("Preferences saved and reloaded");
                         }
                     }
@@ -1294,6 +2697,9 @@ impl WindowState {
                         });
                     });
                 }
+                MenuAction::UsageStats => {
+                    crate::log_viewer::show_usage_stats(self.hwnd.0 as *mut _);
+                }
                 MenuAction::ViewLogs => {
                     // Show the in-app log viewer
                     crate::log_viewer::show_log_viewer(self.hwnd.0 as *mut _);
@@ -1356,23 +2762,192 @@ impl WindowState {
         }
     }
 
-    /// Show set color dialog
-    fn show_set_color_dialog(&mut self) {
-        if let Some(tab) = self.tabs.get(self.active_tab_index) {
-            let tab_id = tab.id;
-            if let Some(color_result) = crate::dialogs::show_set_color_dialog(self.hwnd.0 as *mut _)
-            {
-                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == tab_id) {
-                    tab.color = color_result.clone();
-                    // Persist to daemon
-                    if let Some(ref tx) = tab.daemon_cmd_tx {
-                        let _ = tx.send(DaemonCmd::SetTabColor(
-                            color_result.as_deref().unwrap_or("").to_string(),
-                        ));
-                    }
-                    // Parse color to Rgb
-                    let rgb = color_result.and_then(|c| parse_hex_color(&c));
-                    self.tab_bar.set_color(tab_id, rgb);
+    /// Show the watch expression dialog; an empty expression stops watching
+    fn show_watch_dialog(&mut self) {
+        let Some(tab) = self.tabs.get(self.active_tab_index) else {
+            return;
+        };
+        let tab_id = tab.id;
+        let terminal = Arc::clone(&tab.terminal);
+        let current = terminal
+            .lock()
+            .unwrap()
+            .watch()
+            .map(|watch| watch.pattern().to_string())
+            .unwrap_or_default();
+        let Some(pattern) = crate::dialogs::show_watch_dialog(self.hwnd.0 as *mut _, &current)
+        else {
+            return;
+        };
+        let pattern = Some(pattern.trim()).filter(|p| !p.is_empty());
+        let result = terminal.lock().unwrap().set_watch(pattern);
+        if let Err(e) = result {
+            crate::dialogs::show_error_msg(self.hwnd, &format!("Invalid watch expression: {e}"));
+            return;
+        }
+        self.tab_bar.set_watch_count(tab_id, pattern.map(|_| 0));
+        self.invalidate();
+    }
+
+    /// Show a tab's watch match count, with a desktop notification if the
+    /// window isn't focused
+    pub fn on_watch_matched(&mut self, tab_id: u64, found: &WatchMatch) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == tab_id) else {
+            return;
+        };
+        let title = tab.title.clone();
+        self.tab_bar.set_watch_count(tab_id, Some(found.count));
+        self.invalidate();
+        if found.notify && unsafe { GetForegroundWindow() } != self.hwnd {
+            self.show_desktop_notification(&format!("{title}: watch matched"), &found.line);
+        }
+    }
+
+    /// Carry out a clipboard operation or notification the permission
+    /// policy allowed, or ask about what it holds
+    pub fn on_terminal_request(&mut self, tab_id: u64, event: TerminalEvent) {
+        match event {
+            TerminalEvent::ClipboardRequest(ClipboardOperation::Set { selection: _, data }) => {
+                if let Ok(text) = String::from_utf8(data) {
+                    if let Err(e) = clipboard::copy_to_clipboard(&text) {
+                        log::error!("Failed to set clipboard: {}", e);
+                    }
+                    clipboard_history::record(&text, ClipSource::Osc52);
+                }
+            }
+            TerminalEvent::ClipboardRequest(ClipboardOperation::Query { selection }) => {
+                self.send_clipboard(tab_id, selection);
+            }
+            TerminalEvent::Notification(notification) => {
+                let title = notification.title.unwrap_or_else(|| {
+                    let tab_id = self.tab_of_pane(tab_id);
+                    self.tabs
+                        .iter()
+                        .find(|t| t.id == tab_id)
+                        .map(|t| t.title.clone())
+                        .unwrap_or_else(|| "cterm".to_string())
+                });
+                self.show_desktop_notification(&title, &notification.body);
+            }
+            TerminalEvent::PermissionRequested(capability) => {
+                self.notification_bar.ask_permission(tab_id, capability);
+                self.invalidate();
+            }
+            TerminalEvent::LinesCompleted(lines) => {
+                let effects = self
+                    .pane_triggers
+                    .entry(tab_id)
+                    .or_insert_with(|| self.triggers.clone())
+                    .scan(&lines, std::time::Instant::now());
+                for effect in effects {
+                    self.perform_trigger(tab_id, effect);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Carry out what a trigger on the output of tab or pane `tab_id` fired
+    /// for
+    fn perform_trigger(&mut self, tab_id: u64, effect: TriggerEffect) {
+        match effect {
+            TriggerEffect::Send(text) => {
+                if let Some(terminal) = self.pane_terminal(tab_id) {
+                    if let Err(e) = terminal.lock().unwrap().write_str(&text) {
+                        log::error!("Failed to write to terminal: {}", e);
+                    }
+                }
+            }
+            TriggerEffect::Notify { title, body } => {
+                let tab_id = self.tab_of_pane(tab_id);
+                let tab_title = self
+                    .tabs
+                    .iter()
+                    .find(|t| t.id == tab_id)
+                    .map(|t| t.title.clone())
+                    .unwrap_or_else(|| "cterm".to_string());
+                self.show_desktop_notification(&format!("{tab_title}: {title}"), &body);
+            }
+            TriggerEffect::Command { program, args } => {
+                if let Err(e) = triggers::run_command(&program, &args) {
+                    log::warn!("Trigger command {:?} failed: {}", program, e);
+                }
+            }
+            TriggerEffect::TabColor(color) => {
+                let tab_id = self.tab_of_pane(tab_id);
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == tab_id) {
+                    if let Some(ref tx) = tab.daemon_cmd_tx {
+                        let _ = tx.send(DaemonCmd::SetTabColor(color.clone()));
+                    }
+                    self.tab_bar.set_color(tab_id, parse_hex_color(&color));
+                    tab.color = Some(color);
+                    self.invalidate();
+                }
+            }
+        }
+    }
+
+    /// Release or drop what tab or pane `tab_id` holds for the user's
+    /// permission; with `remember` the answer holds for later requests
+    fn answer_permission(
+        &mut self,
+        tab_id: u64,
+        capability: Capability,
+        allow: bool,
+        remember: bool,
+    ) {
+        let Some(terminal) = self.pane_terminal(tab_id) else {
+            return;
+        };
+        let events = terminal
+            .lock()
+            .unwrap()
+            .answer_permission(capability, allow, remember);
+        for event in events {
+            match event {
+                TerminalEvent::TitleChanged(_) => self.on_title_changed(tab_id),
+                TerminalEvent::TransferStarted(..) | TerminalEvent::TransferFinished => {
+                    self.on_transfer_changed(self.tab_of_pane(tab_id));
+                }
+                TerminalEvent::TransferSendRequested => self.on_transfer_files(tab_id),
+                event => self.on_terminal_request(tab_id, event),
+            }
+        }
+        self.invalidate();
+    }
+
+    /// Answer a tab's OSC 52 read with the clipboard contents
+    fn send_clipboard(&self, tab_id: u64, selection: ClipboardSelection) {
+        let Some(terminal) = self.pane_terminal(tab_id) else {
+            return;
+        };
+        let text = clipboard::paste_from_clipboard().unwrap_or_default();
+        let result = terminal
+            .lock()
+            .unwrap()
+            .send_clipboard_response(selection, text.as_bytes());
+        if let Err(e) = result {
+            log::error!("Failed to send clipboard contents: {}", e);
+        }
+    }
+
+    /// Show set color dialog
+    fn show_set_color_dialog(&mut self) {
+        if let Some(tab) = self.tabs.get(self.active_tab_index) {
+            let tab_id = tab.id;
+            if let Some(color_result) = crate::dialogs::show_set_color_dialog(self.hwnd.0 as *mut _)
+            {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == tab_id) {
+                    tab.color = color_result.clone();
+                    // Persist to daemon
+                    if let Some(ref tx) = tab.daemon_cmd_tx {
+                        let _ = tx.send(DaemonCmd::SetTabColor(
+                            color_result.as_deref().unwrap_or("").to_string(),
+                        ));
+                    }
+                    // Parse color to Rgb
+                    let rgb = color_result.and_then(|c| parse_hex_color(&c));
+                    self.tab_bar.set_color(tab_id, rgb);
                     self.invalidate();
                 }
             }
@@ -1388,6 +2963,16 @@ impl WindowState {
                 let results =
                     term.screen()
                         .find(&options.text, options.case_sensitive, options.regex);
+                let current = usize::from(!results.is_empty());
+                if self
+                    .status
+                    .set_search(&options.text, current, results.len())
+                {
+                    self.sync_status();
+                }
+                // Matches are marked on the scrollbar until the search ends
+                term.screen_mut()
+                    .set_search_lines(results.iter().map(|result| result.line));
                 if !results.is_empty() {
                     log::info!("Found {} matches for: {}", results.len(), options.text);
                     // Scroll to first result
@@ -1410,6 +2995,64 @@ impl WindowState {
         }
     }
 
+    /// Label the links, paths and hashes on screen for keyboard selection
+    ///
+    /// Beeps instead when there is nothing to label.
+    fn start_hint_mode(&mut self) {
+        self.hints = self.active_terminal().and_then(|terminal| {
+            let term = terminal.lock().unwrap();
+            HintMode::new(term.screen(), term.screen().hint_alphabet())
+        });
+        if self.hints.is_none() {
+            let _ = unsafe { MessageBeep(MB_OK) };
+        }
+        self.hint_input(HintInput::Pending);
+    }
+
+    /// Apply the result of a key typed in hint mode
+    fn hint_input(&mut self, input: HintInput) {
+        if !matches!(input, HintInput::Pending) {
+            self.hints = None;
+        }
+        self.status
+            .set_hints(self.hints.as_ref().map(HintMode::typed));
+        self.sync_status();
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_hints(self.hints.clone());
+        }
+        self.invalidate();
+
+        let HintInput::Chosen(hint, action) = input else {
+            return;
+        };
+        let cwd = self.active_terminal().and_then(|terminal| {
+            let dir = terminal.lock().unwrap().screen().current_dir.clone();
+            (!dir.is_empty()).then_some(dir)
+        });
+        match (action, hint.open_target(cwd.as_deref())) {
+            (HintAction::Open, Some(target)) => self.open_url(&target),
+            // Hashes have nothing to open, so they are always copied
+            _ => {
+                let Some(terminal) = self.active_terminal() else {
+                    return;
+                };
+                let quoting = self
+                    .config
+                    .input
+                    .path_quoting
+                    .for_terminal(&terminal.lock().unwrap());
+                clipboard::copy_to_clipboard(&quoting.quote_hint(&hint)).ok();
+            }
+        }
+    }
+
+    /// Push the status strip contents to the renderer
+    fn sync_status(&mut self) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_status(self.status.segments());
+        }
+    }
+
     /// Select all text in the terminal
     fn select_all(&mut self) {
         if let Some(terminal) = self.active_terminal() {
@@ -1427,6 +3070,29 @@ impl WindowState {
         self.invalidate();
     }
 
+    /// Select the output of the last command, beeping when there is none
+    fn select_command_output(&mut self) {
+        let selected = self
+            .active_terminal()
+            .is_some_and(|terminal| terminal.lock().unwrap().select_last_command_output());
+        if !selected {
+            let _ = unsafe { MessageBeep(MB_OK) };
+        }
+        self.invalidate();
+    }
+
+    /// Scroll the previous (or with `next`, the next) shell prompt to the
+    /// top of the view, beeping when there is none
+    fn scroll_to_prompt(&mut self, next: bool) {
+        let moved = self
+            .active_terminal()
+            .is_some_and(|terminal| terminal.lock().unwrap().scroll_to_prompt(next));
+        if !moved {
+            let _ = unsafe { MessageBeep(MB_OK) };
+        }
+        self.invalidate();
+    }
+
     /// Copy selection as HTML
     fn copy_selection_as_html(&mut self) {
         if let Some(terminal) = self.active_terminal() {
@@ -1439,6 +3105,371 @@ impl WindowState {
         }
     }
 
+    /// Save the scrollback and screen, with their colors, to a file
+    fn save_buffer(&mut self) {
+        let Some(terminal) = self.active_terminal() else {
+            return;
+        };
+        let text = terminal.lock().unwrap().screen().to_ansi();
+        if let Some(path) = crate::dialogs::show_save_file_dialog(
+            self.hwnd.0 as *mut _,
+            "Save Buffer As",
+            Some("scrollback.log"),
+            "Log Files (*.log)\0*.log\0All Files\0*.*\0\0",
+        ) {
+            if let Err(e) = std::fs::write(&path, text) {
+                log::error!("Failed to save buffer to {:?}: {}", path, e);
+            }
+        }
+    }
+
+    /// Stop the active tab's recording, or ask where to start one
+    fn toggle_recording(&mut self) {
+        let Some(terminal) = self.active_terminal() else {
+            return;
+        };
+        let stopped = terminal.lock().unwrap().stop_recording();
+        match stopped {
+            Ok(false) => {
+                if let Some(path) = crate::dialogs::show_save_file_dialog(
+                    self.hwnd.0 as *mut _,
+                    "Record Session",
+                    Some("session.cast"),
+                    "Asciicast Files (*.cast)\0*.cast\0All Files\0*.*\0\0",
+                ) {
+                    if let Err(e) = terminal.lock().unwrap().start_recording(&path) {
+                        log::error!("Failed to start recording to {:?}: {}", path, e);
+                    }
+                }
+            }
+            Ok(true) => {}
+            Err(e) => log::error!("Failed to finish recording: {}", e),
+        }
+        self.sync_recording();
+    }
+
+    /// Show whether the active tab is being recorded in the status strip
+    fn sync_recording(&mut self) {
+        let recording = self
+            .active_terminal()
+            .is_some_and(|t| t.lock().unwrap().recording().is_some());
+        if self.status.set_recording(recording) {
+            self.sync_status();
+        }
+    }
+
+    /// Show the active tab's playback position in the status strip; returns
+    /// true if it changed
+    fn sync_playback(&mut self) -> bool {
+        let status = self
+            .tabs
+            .get(self.active_tab_index)
+            .and_then(|t| t.playback.as_ref())
+            .map(Player::status);
+        let changed = self.status.set_playback(status.as_deref());
+        if changed {
+            self.sync_status();
+        }
+        changed
+    }
+
+    /// Store a tab's output rates, showing them if it is the active tab
+    pub fn on_output_stats(&mut self, tab_id: u64, stats: OutputStats) {
+        let Some(tab) = self.tabs.iter_mut().find(|t| t.id == tab_id) else {
+            return;
+        };
+        tab.output_stats = stats;
+        if stats.is_fast() {
+            // The reader thread blocks once output stops, so the final
+            // report has to be polled for
+            unsafe {
+                SetTimer(
+                    Some(self.hwnd),
+                    OUTPUT_STATS_TIMER_ID,
+                    ThroughputMeter::INTERVAL.as_millis() as u32,
+                    None,
+                );
+            }
+        }
+        self.sync_output_stats();
+    }
+
+    /// Poll the tabs with fast output for the report sent once it stops
+    fn poll_output_stats(&mut self) {
+        let mut fast = false;
+        for tab in self.tabs.iter_mut().filter(|t| t.output_stats.is_fast()) {
+            if let Some(stats) = tab.terminal.lock().unwrap().poll_output_stats() {
+                tab.output_stats = stats;
+            }
+            fast |= tab.output_stats.is_fast();
+        }
+        if !fast {
+            unsafe { KillTimer(Some(self.hwnd), OUTPUT_STATS_TIMER_ID).ok() };
+        }
+        self.sync_output_stats();
+    }
+
+    /// Show the active tab's ZMODEM or Kermit transfer in the status strip
+    fn sync_transfer(&mut self) {
+        let transfer = self
+            .active_terminal()
+            .and_then(|t| t.lock().unwrap().file_transfer());
+        if self.status.set_transfer(transfer) {
+            self.sync_status();
+            self.invalidate();
+        }
+    }
+
+    /// Handle a ZMODEM or Kermit transfer starting or finishing in a tab
+    pub fn on_transfer_changed(&mut self, tab_id: u64) {
+        if self.tabs.get(self.active_tab_index).map(|t| t.id) == Some(tab_id) {
+            self.sync_transfer();
+        }
+    }
+
+    /// Ask for the files a remote `rz` or `kermit -r` is waiting for and
+    /// send them; cancelling the dialog declines the transfer
+    pub fn on_transfer_files(&mut self, tab_id: u64) {
+        let Some(terminal) = self.pane_terminal(tab_id) else {
+            return;
+        };
+        let Some((protocol, _)) = terminal.lock().unwrap().file_transfer() else {
+            return;
+        };
+
+        let title = format!("Send Files ({})", protocol.name());
+        let paths = crate::dialogs::show_open_files_dialog(self.hwnd.0 as *mut _, &title);
+        let mut term = terminal.lock().unwrap();
+        if paths.is_empty() {
+            term.cancel_file_transfer();
+        } else {
+            term.send_transfer_files(read_files(&paths));
+        }
+        drop(term);
+        self.sync_transfer();
+    }
+
+    /// Ask for files to upload after an OSC 1337 RequestUpload (`it2ul`) and
+    /// stream them to the remote; aborts the upload if none are chosen
+    pub fn on_upload_files(&mut self, tab_id: u64) {
+        let Some(terminal) = self.pane_terminal(tab_id) else {
+            return;
+        };
+        let paths = crate::dialogs::show_open_files_dialog(self.hwnd.0 as *mut _, "Upload Files");
+        let files = read_files(&paths);
+        if files.is_empty() {
+            terminal.lock().unwrap().write(UPLOAD_ABORT).ok();
+            return;
+        }
+        match cterm_core::iterm2::encode_upload(&files) {
+            Ok(data) => self.start_paste(terminal, PasteJob::upload(data)),
+            Err(e) => {
+                log::error!("Failed to encode upload: {}", e);
+                terminal.lock().unwrap().write(UPLOAD_ABORT).ok();
+            }
+        }
+    }
+
+    /// Show whether the active tab's output is too fast to draw every frame
+    /// of in the status strip
+    fn sync_output_stats(&mut self) {
+        let stats = self
+            .tabs
+            .get(self.active_tab_index)
+            .map(|t| t.output_stats)
+            .unwrap_or_default();
+        if self.status.set_output_stats(&stats) {
+            self.sync_status();
+            self.invalidate();
+        }
+    }
+
+    /// Pass control mode output of a tab to its tmux gateway, starting one
+    /// when control mode starts, and open, feed and close pane tabs as tmux
+    /// asks
+    pub fn on_tmux_control(&mut self, tab_id: u64, control: Vec<TmuxControl>) {
+        if control.contains(&TmuxControl::Started) {
+            let Some(tab) = self.tabs.iter().find(|t| t.id == tab_id) else {
+                return;
+            };
+            let terminal = Arc::clone(&tab.terminal);
+            let gateway = TmuxGateway::new(move |data| {
+                if let Err(e) = terminal.lock().unwrap().write(data) {
+                    log::error!("Failed to write to tmux: {}", e);
+                }
+            });
+            let (cols, rows) = self.terminal_size();
+            gateway.resize(cols, rows);
+            self.tmux.insert(tab_id, gateway);
+        }
+        let Some(gateway) = self.tmux.get(&tab_id).cloned() else {
+            return;
+        };
+        let pane_tab = |tabs: &[TabEntry], pane: PaneId| {
+            tabs.iter()
+                .find(|t| {
+                    t.tmux_pane
+                        .as_ref()
+                        .is_some_and(|(g, p)| Arc::ptr_eq(g, &gateway) && *p == pane)
+                })
+                .map(|t| t.id)
+        };
+        for event in gateway.handle(&control) {
+            match event {
+                TmuxEvent::PaneOpened {
+                    pane,
+                    title,
+                    cols,
+                    rows,
+                } => self.new_tmux_pane_tab(&gateway, pane, title, cols, rows),
+                TmuxEvent::PaneResized { pane, cols, rows } => {
+                    if let Some(tab) = pane_tab(&self.tabs, pane)
+                        .and_then(|id| self.tabs.iter().find(|t| t.id == id))
+                    {
+                        tab.terminal.lock().unwrap().resize(cols, rows);
+                        self.invalidate();
+                    }
+                }
+                TmuxEvent::PaneOutput { pane, data } => {
+                    let Some(id) = pane_tab(&self.tabs, pane) else {
+                        continue;
+                    };
+                    let Some(tab) = self.tabs.iter().find(|t| t.id == id) else {
+                        continue;
+                    };
+                    let events = tab.terminal.lock().unwrap().process(&data);
+                    if events.iter().any(|e| matches!(e, TerminalEvent::Bell)) {
+                        self.on_bell(id);
+                    }
+                    if events
+                        .iter()
+                        .any(|e| matches!(e, TerminalEvent::UploadRequested))
+                    {
+                        self.on_upload_files(id);
+                    }
+                    self.on_pty_data(id);
+                }
+                TmuxEvent::PaneTitle { pane, title } => {
+                    let id = pane_tab(&self.tabs, pane);
+                    if let Some(tab) = self.tabs.iter_mut().find(|t| Some(t.id) == id) {
+                        self.tab_bar.set_title(tab.id, &title);
+                        tab.title = title;
+                    }
+                    self.invalidate();
+                }
+                TmuxEvent::PaneClosed { pane } => {
+                    if let Some(id) = pane_tab(&self.tabs, pane) {
+                        self.close_tab(id);
+                    }
+                }
+                TmuxEvent::Exit { reason } => {
+                    log::info!("tmux control mode ended: {}", reason.unwrap_or_default());
+                    self.tmux.remove(&tab_id);
+                    let panes: Vec<u64> = self
+                        .tabs
+                        .iter()
+                        .filter(|t| {
+                            t.tmux_pane
+                                .as_ref()
+                                .is_some_and(|(g, _)| Arc::ptr_eq(g, &gateway))
+                        })
+                        .map(|t| t.id)
+                        .collect();
+                    for id in panes {
+                        self.close_tab(id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Open a tab for a pane of the tmux session `gateway` talks to
+    fn new_tmux_pane_tab(
+        &mut self,
+        gateway: &Arc<TmuxGateway>,
+        pane: PaneId,
+        title: String,
+        cols: usize,
+        rows: usize,
+    ) {
+        let tab_id = self.next_tab_id.fetch_add(1, Ordering::SeqCst);
+        let screen_config = ScreenConfig {
+            scrollback_lines: self.config.general.scrollback_lines,
+            selection: self.config.selection.clone(),
+            conformance_level: self.config.general.conformance_level,
+            cursor_style: self.config.appearance.cursor_style.into(),
+            cursor_blink: self.config.appearance.cursor_blink,
+        };
+        let mut terminal = Terminal::new(cols, rows, screen_config);
+        terminal.set_write_fn(gateway.pane_write_fn(pane));
+        terminal.set_color_scheme(self.theme.colors.color_scheme());
+        let auto_switch = self.auto_switcher(None);
+
+        self.tabs.push(TabEntry {
+            id: tab_id,
+            title: title.clone(),
+            terminal: Arc::new(Mutex::new(terminal)),
+            color: None,
+            background_color: None,
+            has_bell: false,
+            title_locked: false,
+            reader_handle: None,
+            session_id: None,
+            daemon_cmd_tx: None,
+            command_notify: None,
+            template: None,
+            playback: None,
+            output_stats: OutputStats::default(),
+            tmux_pane: Some((Arc::clone(gateway), pane)),
+            split: None,
+            theme: None,
+            auto_switch,
+            auto_switch_saved: None,
+        });
+        self.tab_bar.add_tab(tab_id, &title);
+        self.switch_to_tab(self.tabs.len() - 1);
+    }
+
+    /// Advance every tab's playback, redrawing if the active one changed
+    fn tick_playback(&mut self) {
+        let mut changed = false;
+        let mut playing = false;
+        for (i, tab) in self.tabs.iter_mut().enumerate() {
+            let Some(ref mut player) = tab.playback else {
+                continue;
+            };
+            playing = true;
+            let mut term = tab.terminal.lock().unwrap();
+            if player.tick(&mut term) && i == self.active_tab_index {
+                changed = true;
+            }
+        }
+        if !playing {
+            unsafe { KillTimer(Some(self.hwnd), PLAYBACK_TIMER_ID).ok() };
+            return;
+        }
+        if self.sync_playback() || changed {
+            self.invalidate();
+        }
+    }
+
+    /// Pass a key to the active tab's player; returns false if it isn't
+    /// playing back a recording or the key isn't a control
+    fn playback_key(&mut self, key: Key) -> bool {
+        let Some(tab) = self.tabs.get_mut(self.active_tab_index) else {
+            return false;
+        };
+        let Some(ref mut player) = tab.playback else {
+            return false;
+        };
+        let handled = player.handle_key(&mut tab.terminal.lock().unwrap(), key);
+        if handled {
+            self.sync_playback();
+            self.invalidate();
+        }
+        handled
+    }
+
     /// Send a signal to the active terminal's process
     fn send_signal(&mut self, _signal: i32) {
         // On Windows, signals work differently than Unix
@@ -1483,16 +3514,146 @@ impl WindowState {
         }
     }
 
-    /// Called when font size changes to resize terminals
-    fn on_font_size_changed(&mut self) {
-        let (cols, rows) = self.terminal_size();
+    /// Change the view magnification (the grid and PTY size stay the same)
+    fn magnify(&mut self, change: fn(&mut Magnifier) -> bool) {
+        if let Some(ref mut renderer) = self.renderer {
+            if change(renderer.magnifier_mut()) {
+                self.invalidate();
+            }
+        }
+    }
+
+    /// Toggle presentation mode: larger font, no tab bar, and a contrast floor
+    /// for the text
+    fn toggle_presentation_mode(&mut self) {
+        self.presenting = !self.presenting;
+        let presentation = &self.config.presentation;
+        let base_size = self.config.appearance.font.size;
+        let (font_size, minimum_contrast) = if self.presenting {
+            (
+                presentation.font_size(base_size),
+                presentation.minimum_contrast,
+            )
+        } else {
+            (base_size, 1.0)
+        };
+        self.keystrokes = (self.presenting && presentation.show_keystrokes).then(KeystrokeLog::new);
+        self.tab_bar.set_force_hidden(self.presenting);
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_minimum_contrast(minimum_contrast);
+            renderer.set_scrollbar(!self.presenting);
+            renderer.set_keystroke_overlay(None);
+            renderer.set_font_size(font_size as f32).ok();
+        }
+        self.on_font_size_changed();
+    }
+
+    /// Record a key press for the presentation keystroke overlay
+    fn record_keystroke(&mut self, key: cterm_ui::events::KeyCode, modifiers: Modifiers) {
+        let Some(ref mut log) = self.keystrokes else {
+            return;
+        };
+        log.record(key, modifiers, std::time::Instant::now());
+        let text = log.text();
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_keystroke_overlay(text);
+        }
+        unsafe {
+            SetTimer(
+                Some(self.hwnd),
+                KEYSTROKE_TIMER_ID,
+                KEYSTROKE_TIMEOUT.as_millis() as u32,
+                None,
+            )
+        };
+        self.invalidate();
+    }
+
+    /// Update the status segments of every tab, each run in the directory
+    /// its shell reported, and redraw the tab bar if any changed
+    fn refresh_status_segments(&mut self) {
+        let mut changed = false;
         for tab in &self.tabs {
-            let mut term = tab.terminal.lock().unwrap();
-            term.resize(cols, rows);
+            let dir = tab.terminal.lock().ok().and_then(|term| {
+                term.screen()
+                    .local_current_dir()
+                    .map(std::path::PathBuf::from)
+            });
+            let segments = status_segments::values(&self.config.status_segments, dir.as_deref());
+            changed |= self.tab_bar.set_segments(tab.id, segments);
+        }
+        if changed && self.tab_bar.is_visible() {
+            self.invalidate();
+        }
+    }
+
+    /// Handle WM_TIMER
+    pub fn on_timer(&mut self, timer_id: usize) {
+        if timer_id == AUTOSCROLL_TIMER_ID {
+            self.on_autoscroll();
+            return;
+        }
+        if timer_id == PASTE_TIMER_ID {
+            self.write_paste_chunk();
+            return;
+        }
+        if timer_id == PLAYBACK_TIMER_ID {
+            self.tick_playback();
+            return;
+        }
+        if timer_id == OUTPUT_STATS_TIMER_ID {
+            self.poll_output_stats();
+            return;
+        }
+        if timer_id == LINK_PREVIEW_TIMER_ID {
+            unsafe { KillTimer(Some(self.hwnd), LINK_PREVIEW_TIMER_ID).ok() };
+            self.load_link_preview();
+            return;
+        }
+        if timer_id == WIDGETS_TIMER_ID {
+            if self.tab_bar.is_visible() {
+                self.invalidate();
+            }
+            return;
+        }
+        if timer_id == SEGMENTS_TIMER_ID {
+            self.refresh_status_segments();
+            return;
+        }
+        if timer_id == STARTUP_TIMER_ID {
+            self.poll_startup_commands();
+            return;
+        }
+        if timer_id == TOAST_TIMER_ID {
+            if self.status.expire_toast(std::time::Instant::now()) {
+                unsafe { KillTimer(Some(self.hwnd), TOAST_TIMER_ID).ok() };
+                self.sync_status();
+                self.invalidate();
+            }
+            return;
+        }
+        if timer_id != KEYSTROKE_TIMER_ID {
+            return;
+        }
+        let text = self.keystrokes.as_mut().and_then(|log| {
+            log.expire(std::time::Instant::now());
+            log.text()
+        });
+        if text.is_none() {
+            unsafe { KillTimer(Some(self.hwnd), KEYSTROKE_TIMER_ID).ok() };
+        }
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_keystroke_overlay(text);
         }
         self.invalidate();
     }
 
+    /// Called when font size changes to resize terminals
+    fn on_font_size_changed(&mut self) {
+        self.resize_terminals();
+        self.invalidate();
+    }
+
     /// Toggle fullscreen mode
     fn toggle_fullscreen(&mut self) {
         use windows::Win32::UI::WindowsAndMessaging::{
@@ -1529,33 +3690,240 @@ impl WindowState {
         }
     }
 
-    /// Copy selection to clipboard
-    fn copy_selection(&mut self) {
-        if let Some(terminal) = self.active_terminal() {
-            let term = terminal.lock().unwrap();
-            if let Some(text) = term.screen().get_selected_text() {
-                clipboard::copy_to_clipboard(&text).ok();
+    /// Copy selection to clipboard
+    fn copy_selection(&mut self) {
+        let Some(terminal) = self.active_terminal() else {
+            return;
+        };
+        let (text, rtf) = {
+            let term = terminal.lock().unwrap();
+            let Some(text) = term.screen().get_selected_text() else {
+                return;
+            };
+            let rtf = if self.config.general.copy_rich_text {
+                term.screen()
+                    .selection_to_rtf(&self.theme.colors, &self.config.appearance.font.family)
+            } else {
+                None
+            };
+            (text, rtf)
+        };
+        match rtf {
+            Some(rtf) => clipboard::copy_rich_text(&text, &rtf),
+            None => clipboard::copy_to_clipboard(&text),
+        }
+        .ok();
+        clipboard_history::record(&text, ClipSource::Copy);
+        // Programs on the daemon's host can read it while detached
+        if let Some(tx) = self
+            .tabs
+            .get(self.active_tab_index)
+            .and_then(|t| t.daemon_cmd_tx.as_ref())
+        {
+            let _ = tx.send(DaemonCmd::SetClipboard(text.into_bytes()));
+        }
+    }
+
+    /// Paste from clipboard
+    fn paste(&mut self) {
+        if let Ok(text) = clipboard::paste_from_clipboard() {
+            self.paste_text(&text);
+        }
+    }
+
+    /// Paste the paths of files dropped on the window, quoted for the shell
+    /// of the active terminal and separated by spaces
+    fn on_drop_files(&mut self, hdrop: usize) {
+        use winapi::um::shellapi::{DragFinish, DragQueryFileW, HDROP};
+
+        let hdrop = hdrop as HDROP;
+        let paths: Vec<String> = unsafe {
+            let count = DragQueryFileW(hdrop, u32::MAX, std::ptr::null_mut(), 0);
+            let paths = (0..count)
+                .filter_map(|i| {
+                    let len = DragQueryFileW(hdrop, i, std::ptr::null_mut(), 0) as usize;
+                    let mut buf = vec![0u16; len + 1];
+                    let copied =
+                        DragQueryFileW(hdrop, i, buf.as_mut_ptr(), buf.len() as u32) as usize;
+                    (copied > 0).then(|| String::from_utf16_lossy(&buf[..copied]))
+                })
+                .collect();
+            DragFinish(hdrop);
+            paths
+        };
+        let Some(terminal) = self.active_terminal() else {
+            return;
+        };
+        let quoting = self
+            .config
+            .input
+            .path_quoting
+            .for_terminal(&terminal.lock().unwrap());
+        let text = paths
+            .iter()
+            .map(|path| quoting.quote(path))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !text.is_empty() {
+            self.paste_text(&text);
+        }
+    }
+
+    /// Pick an earlier copy from the clipboard history in a menu below the
+    /// cursor and paste it, beeping when the history is empty
+    fn paste_history(&mut self) {
+        let items = clipboard_history::items();
+        if items.is_empty() {
+            let _ = unsafe { MessageBeep(MB_OK) };
+            return;
+        }
+        let Some(terminal) = self.active_terminal() else {
+            return;
+        };
+        let (row, col) = {
+            let term = terminal.lock().unwrap();
+            let cursor = &term.screen().cursor;
+            (cursor.row, cursor.col)
+        };
+        let cell_dims = self
+            .renderer
+            .as_ref()
+            .map(|renderer| renderer.cell_dimensions())
+            .unwrap_or_default();
+        let (pane_x, pane_y) = self.focused_pane_origin();
+        let x = pane_x + col as f32 * cell_dims.width;
+        let y = self.terminal_y_offset() + pane_y + (row + 1) as f32 * cell_dims.height;
+
+        let cmd = unsafe {
+            let menu = CreatePopupMenu().unwrap();
+            for (i, item) in items.iter().enumerate() {
+                // Keep "&" from underlining the next character
+                let label = item
+                    .label(clipboard_history::LABEL_CHARS)
+                    .replace('&', "&&");
+                let wide = crate::dialog_utils::to_wide(&label);
+                let _ = AppendMenuW(menu, MF_STRING, i + 1, PCWSTR(wide.as_ptr()));
+            }
+            let mut pt = windows::Win32::Foundation::POINT {
+                x: x as i32,
+                y: y as i32,
+            };
+            let _ = windows::Win32::Graphics::Gdi::ClientToScreen(self.hwnd, &mut pt);
+            let cmd = TrackPopupMenu(
+                menu,
+                TPM_LEFTALIGN | TPM_TOPALIGN | TPM_RETURNCMD,
+                pt.x,
+                pt.y,
+                None,
+                self.hwnd,
+                None,
+            );
+            let _ = DestroyMenu(menu);
+            cmd.0 as usize
+        };
+        if let Some(item) = cmd.checked_sub(1).and_then(|i| items.get(i)) {
+            self.paste_text(&item.text);
+        }
+    }
+
+    /// Paste `text` into the active terminal, a chunk at a time if it is
+    /// large, in bracketed paste markers if the application asked for them;
+    /// replaces any paste in progress
+    ///
+    /// Pasting into an application without bracketed paste asks first if
+    /// `confirm_paste_lines` or `confirm_paste_control` say so.
+    fn paste_text(&mut self, text: &str) {
+        let Some(terminal) = self.active_terminal() else {
+            return;
+        };
+        let bracketed = terminal.lock().unwrap().screen().modes.bracketed_paste;
+        if !bracketed {
+            if let Some(warning) = PasteGuard::from(&self.config.general).check(text) {
+                if !crate::dialogs::confirm_paste(self.hwnd, text, &warning) {
+                    return;
+                }
             }
         }
+        self.start_paste(terminal, PasteJob::new(text, bracketed));
     }
 
-    /// Paste from clipboard
-    fn paste(&mut self) {
-        if let Ok(text) = clipboard::paste_from_clipboard() {
-            if let Some(terminal) = self.active_terminal() {
-                let mut term = terminal.lock().unwrap();
-                term.write(text.as_bytes()).ok();
-                drop(term);
+    /// Write `job` to `terminal` at once if it is small, else a chunk at a
+    /// time; replaces any paste in progress
+    fn start_paste(&mut self, terminal: Arc<Mutex<Terminal>>, mut job: PasteJob) {
+        self.cancel_paste();
+        if !job.is_chunked(self.config.input.paste_chunk_bytes) {
+            if let Some(data) = job.next_chunk(0) {
+                terminal.lock().unwrap().write(data).ok();
             }
             self.invalidate();
+            return;
+        }
+
+        self.paste = Some((terminal, job));
+        self.write_paste_chunk();
+        unsafe {
+            SetTimer(
+                Some(self.hwnd),
+                PASTE_TIMER_ID,
+                InputConfig::PASTE_CHUNK_INTERVAL.as_millis() as u32,
+                None,
+            );
+        }
+    }
+
+    /// Write the next chunk of the paste in progress and update its progress
+    fn write_paste_chunk(&mut self) {
+        let Some((ref terminal, ref mut job)) = self.paste else {
+            return;
+        };
+        if let Some(data) = job.next_chunk(self.config.input.paste_chunk_bytes) {
+            terminal.lock().unwrap().write(data).ok();
+        }
+        let progress = (!job.is_done()).then(|| (job.written(), job.len()));
+        if progress.is_none() {
+            self.paste = None;
+            unsafe { KillTimer(Some(self.hwnd), PASTE_TIMER_ID).ok() };
         }
+        if self.status.set_paste_progress(progress) {
+            self.sync_status();
+        }
+        self.invalidate();
+    }
+
+    /// Stop the paste in progress; returns false if there was none
+    fn cancel_paste(&mut self) -> bool {
+        let Some((terminal, mut job)) = self.paste.take() else {
+            return false;
+        };
+        unsafe { KillTimer(Some(self.hwnd), PASTE_TIMER_ID).ok() };
+        terminal.lock().unwrap().write(job.cancel()).ok();
+        if self.status.set_paste_progress(None) {
+            self.sync_status();
+        }
+        self.invalidate();
+        true
     }
 
-    /// Handle PTY data received
-    pub fn on_pty_data(&mut self, tab_id: u64) {
+    /// Handle PTY data received from tab or pane `pane_id`
+    pub fn on_pty_data(&mut self, pane_id: u64) {
+        let Some(tab_id) = self.tab_id_of(pane_id) else {
+            return;
+        };
+        if let Some(startup) = self.startup.get_mut(&pane_id) {
+            startup.output(std::time::Instant::now());
+        }
+        // Mark output in background tabs
+        let is_current_tab = self
+            .tabs
+            .get(self.active_tab_index)
+            .is_some_and(|t| t.id == tab_id);
+        if !is_current_tab {
+            self.tab_bar.set_unread(tab_id, true);
+        }
+
         // Check for file transfers from the terminal
-        if let Some(tab) = self.tabs.iter().find(|t| t.id == tab_id) {
-            if let Ok(mut terminal) = tab.terminal.lock() {
+        if let Some(terminal) = self.pane_terminal(pane_id) {
+            if let Ok(mut terminal) = terminal.lock() {
                 let transfers = terminal.screen_mut().take_file_transfers();
                 for transfer in transfers {
                     match transfer {
@@ -1587,18 +3955,79 @@ impl WindowState {
                 }
             }
         }
+        self.update_auto_switch(tab_id);
 
         // Invalidate to redraw
         self.invalidate();
     }
 
-    /// Handle PTY exit
-    pub fn on_pty_exit(&mut self, tab_id: u64) {
-        self.close_tab(tab_id);
+    /// Handle PTY exit of tab or pane `pane_id`
+    pub fn on_pty_exit(&mut self, pane_id: u64) {
+        if let Some(index) = self.tabs.iter().position(|t| t.has_pane(pane_id)) {
+            self.close_pane(index, pane_id);
+        }
+    }
+
+    /// Id of the tab showing tab or pane `id`
+    fn tab_id_of(&self, id: u64) -> Option<u64> {
+        self.tabs.iter().find(|t| t.has_pane(id)).map(|t| t.id)
+    }
+
+    /// Id of the tab showing pane `id`, for messages about the whole tab;
+    /// `id` itself if no tab has such a pane
+    fn tab_of_pane(&self, id: u64) -> u64 {
+        self.tab_id_of(id).unwrap_or(id)
+    }
+
+    /// Terminal of tab or pane `id`
+    fn pane_terminal(&self, id: u64) -> Option<Arc<Mutex<Terminal>>> {
+        self.tabs.iter().find_map(|t| t.pane_terminal(id))
+    }
+
+    /// Type `commands` into the local shell of tab or pane `id` as it gets
+    /// ready for them, the way ctermd does for its sessions
+    fn queue_startup_commands(&mut self, id: u64, commands: &[String]) {
+        let Some(startup) = StartupCommands::new(commands, std::time::Instant::now()) else {
+            return;
+        };
+        self.startup.insert(id, startup);
+        unsafe {
+            SetTimer(
+                Some(self.hwnd),
+                STARTUP_TIMER_ID,
+                STARTUP_POLL_INTERVAL.as_millis() as u32,
+                None,
+            );
+        }
+    }
+
+    /// Type the next startup command into each shell that is ready for it
+    fn poll_startup_commands(&mut self) {
+        let now = std::time::Instant::now();
+        let mut startup = std::mem::take(&mut self.startup);
+        startup.retain(|&id, commands| {
+            // The tab or pane was closed
+            let Some(terminal) = self.pane_terminal(id) else {
+                return false;
+            };
+            if let Some(line) = commands.poll(now) {
+                if let Err(e) = terminal.lock().unwrap().write(line.as_bytes()) {
+                    log::warn!("Startup command failed: {}", e);
+                    return false;
+                }
+            }
+            !commands.is_done()
+        });
+        self.startup = startup;
+        if self.startup.is_empty() {
+            unsafe { KillTimer(Some(self.hwnd), STARTUP_TIMER_ID).ok() };
+        }
     }
 
     /// Handle bell
     pub fn on_bell(&mut self, tab_id: u64) {
+        self.play_cue(self.config.sounds.bell_cue());
+
         // Only show bell indicator if this tab is not the current tab
         let is_current_tab = self
             .tabs
@@ -1616,13 +4045,122 @@ impl WindowState {
         }
     }
 
-    /// Handle title change from terminal
-    pub fn on_title_changed(&mut self, tab_id: u64) {
-        if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == tab_id) {
+    /// Play a `[sounds]` cue, unless it is for the background only and the
+    /// window is focused
+    fn play_cue(&self, cue: Option<Cue>) {
+        let focused = unsafe { GetForegroundWindow() } == self.hwnd;
+        if !self.config.sounds.active(focused) {
+            return;
+        }
+        match cue {
+            Some(Cue::Beep) => {
+                let _ = unsafe { MessageBeep(MB_OK) };
+            }
+            Some(cue) => sounds::play(&cue),
+            None => {}
+        }
+    }
+
+    /// Handle a finished command: toast it if its tab is showing, and send a
+    /// desktop notification if the window isn't focused
+    pub fn on_command_finished(&mut self, tab_id: u64, finished: &CommandFinished) {
+        status_segments::command_finished(&self.config.status_segments);
+        let Some(tab) = self.tabs.iter().find(|t| t.id == tab_id) else {
+            return;
+        };
+        if let Ok(term) = tab.terminal.lock() {
+            let label = cterm_app::usage_stats::tab_label(term.screen());
+            cterm_app::usage_stats::record_command(&label, finished);
+        }
+        let notify = tab.command_notify.unwrap_or(self.config.command_notify);
+        if !notify.should_notify(finished.duration) {
+            return;
+        }
+        let title = tab.title.clone();
+        let is_current_tab = self
+            .tabs
+            .get(self.active_tab_index)
+            .is_some_and(|t| t.id == tab_id);
+        self.play_cue(self.config.sounds.command_cue(finished));
+
+        if is_current_tab {
+            self.status
+                .show_command_finished(finished, std::time::Instant::now());
+            self.sync_status();
+            unsafe {
+                SetTimer(
+                    Some(self.hwnd),
+                    TOAST_TIMER_ID,
+                    StatusStrip::TOAST_DURATION.as_millis() as u32,
+                    None,
+                )
+            };
+            self.invalidate();
+        }
+
+        if notify.desktop_when_unfocused && unsafe { GetForegroundWindow() } != self.hwnd {
+            self.show_desktop_notification(&title, &command_summary(finished));
+        }
+    }
+
+    /// Show a balloon notification from the tray icon, adding the icon on
+    /// first use
+    fn show_desktop_notification(&mut self, title: &str, body: &str) {
+        use winapi::um::shellapi::{
+            Shell_NotifyIconW, NIF_ICON, NIF_INFO, NIIF_INFO, NIM_ADD, NIM_MODIFY, NOTIFYICONDATAW,
+        };
+        use winapi::um::winuser::{LoadIconW, IDI_APPLICATION};
+
+        let mut data: NOTIFYICONDATAW = unsafe { std::mem::zeroed() };
+        data.cbSize = std::mem::size_of::<NOTIFYICONDATAW>() as u32;
+        data.hWnd = self.hwnd.0 as _;
+        data.uID = NOTIFY_ICON_ID;
+        data.uFlags = NIF_ICON | NIF_INFO;
+        data.hIcon = unsafe { LoadIconW(std::ptr::null_mut(), IDI_APPLICATION) };
+        data.dwInfoFlags = NIIF_INFO;
+        copy_wide(&mut data.szInfoTitle, title);
+        copy_wide(&mut data.szInfo, body);
+
+        let op = if self.notify_icon {
+            NIM_MODIFY
+        } else {
+            NIM_ADD
+        };
+        if unsafe { Shell_NotifyIconW(op, &mut data) } != 0 {
+            self.notify_icon = true;
+        }
+    }
+
+    /// Remove the notification tray icon, if it was added
+    fn remove_notify_icon(&mut self) {
+        use winapi::um::shellapi::{Shell_NotifyIconW, NIM_DELETE, NOTIFYICONDATAW};
+
+        if !std::mem::take(&mut self.notify_icon) {
+            return;
+        }
+        let mut data: NOTIFYICONDATAW = unsafe { std::mem::zeroed() };
+        data.cbSize = std::mem::size_of::<NOTIFYICONDATAW>() as u32;
+        data.hWnd = self.hwnd.0 as _;
+        data.uID = NOTIFY_ICON_ID;
+        unsafe { Shell_NotifyIconW(NIM_DELETE, &mut data) };
+    }
+
+    /// Handle title change from tab or pane `pane_id`
+    pub fn on_title_changed(&mut self, pane_id: u64) {
+        if let Some(tab) = self.tabs.iter_mut().find(|t| t.has_pane(pane_id)) {
             // Don't update if title is locked (user-set or template)
             if tab.title_locked {
                 return;
             }
+            // A split tab shows its focused pane's title
+            if tab
+                .split
+                .as_ref()
+                .is_some_and(|s| s.tree.focused() != pane_id)
+            {
+                return;
+            }
+            let tab_id = tab.id;
 
             // Get title from terminal's screen
             let new_title = {
@@ -1639,12 +4177,34 @@ impl WindowState {
 
     /// Get the vertical offset from window top to terminal content area
     fn terminal_y_offset(&self) -> f32 {
-        let tab_bar_height = self.dpi.scale_f32(TAB_BAR_HEIGHT as f32);
+        let tab_bar_height = match self.tab_bar.position() {
+            TabBarPosition::Top => self.dpi.scale_f32(TAB_BAR_HEIGHT as f32),
+            TabBarPosition::Bottom => 0.0,
+        };
         let notification_height = self.notification_bar.height() as f32;
         tab_bar_height + notification_height
     }
 
-    /// Get the hyperlink URI at a window pixel position, if any
+    /// The offset of window position `y` into the tab bar, if the tab bar is
+    /// shown there
+    fn tab_bar_y(&self, y: f32) -> Option<f32> {
+        if !self.tab_bar.is_visible() {
+            return None;
+        }
+        let height = self.dpi.scale_f32(TAB_BAR_HEIGHT as f32);
+        let top = match self.tab_bar.position() {
+            TabBarPosition::Top => 0.0,
+            TabBarPosition::Bottom => {
+                let mut rect = RECT::default();
+                unsafe { GetClientRect(self.hwnd, &mut rect).ok() };
+                (rect.bottom - rect.top) as f32 - height
+            }
+        };
+        (y >= top && y < top + height).then_some(y - top)
+    }
+
+    /// Get the hyperlink URI, or the target of a detected URL or file path,
+    /// at a window pixel position
     fn hyperlink_at(&self, x: f32, y: f32) -> Option<String> {
         let y_offset = self.terminal_y_offset();
         if y < y_offset {
@@ -1652,13 +4212,138 @@ impl WindowState {
         }
         let renderer = self.renderer.as_ref()?;
         let cell_dims = renderer.cell_dimensions();
+        let (x, y) = renderer
+            .magnifier()
+            .view_to_surface(x as f64, (y - y_offset) as f64);
+        let terminal = self.active_terminal()?;
+        let term = terminal.lock().unwrap();
+        let (col, row) = mouse::pixel_to_cell(x as i32, y as i32, &cell_dims, 0);
+        let screen = term.screen();
+        let cwd = Some(screen.current_dir.as_str()).filter(|dir| !dir.is_empty());
+        detect::span_at(screen, screen.visible_row_to_absolute_line(row), col)
+            .and_then(|span| span.open_target(cwd))
+    }
+
+    /// Describe the finished command in the status strip when the pointer
+    /// at a window pixel position is over its exit status bar
+    fn hover_command_gutter(&mut self, x: f32, y: f32) {
+        if !self.config.appearance.command_gutter {
+            return;
+        }
+        let Some(renderer) = self.renderer.as_ref() else {
+            return;
+        };
+        let Some((_, row)) = self.terminal_cell_at(x, y) else {
+            return;
+        };
+        let (surface_x, _) = renderer.magnifier().view_to_surface(x as f64, 0.0);
+        let pane_x = surface_x - self.focused_pane_origin().0 as f64;
+        let Some(terminal) = self.active_terminal() else {
+            return;
+        };
+        let blocks = terminal.lock().unwrap().screen().command_blocks();
+        let Some(block) = gutter_block_at(&blocks, pane_x, row) else {
+            return;
+        };
+        self.status.show_toast(
+            gutter_tooltip(block),
+            block.failed(),
+            std::time::Instant::now(),
+        );
+        self.sync_status();
+        unsafe {
+            SetTimer(
+                Some(self.hwnd),
+                TOAST_TIMER_ID,
+                StatusStrip::TOAST_DURATION.as_millis() as u32,
+                None,
+            )
+        };
+        self.invalidate();
+    }
+
+    /// What to preview for the link or image path at a window pixel position
+    fn preview_target_at(&self, x: f32, y: f32) -> Option<PreviewTarget> {
+        let (col, row) = self.terminal_cell_at(x, y)?;
         let terminal = self.active_terminal()?;
         let term = terminal.lock().unwrap();
-        let (col, row) = mouse::pixel_to_cell(x as i32, (y - y_offset) as i32, &cell_dims, 0);
-        term.screen()
-            .get_cell(row, col)
-            .and_then(|c| c.hyperlink.as_ref())
-            .map(|h| h.uri.clone())
+        let screen = term.screen();
+        let cwd = Some(screen.current_dir.as_str()).filter(|dir| !dir.is_empty());
+        detect::span_at(screen, screen.visible_row_to_absolute_line(row), col)
+            .and_then(|span| PreviewTarget::for_span(&span, cwd))
+    }
+
+    /// Switch the hovered link, hiding the preview of the previous one
+    ///
+    /// A new link is previewed after [`HOVER_DELAY`] unless the pointer
+    /// moves on first.
+    fn set_hovered_link(&mut self, hovered: Option<(PreviewTarget, (f32, f32))>) {
+        let unchanged = match (&self.hovered_link, &hovered) {
+            (Some((old, _)), Some((new, _))) => old == new,
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+
+        unsafe { KillTimer(Some(self.hwnd), LINK_PREVIEW_TIMER_ID).ok() };
+        if let Some(ref mut renderer) = self.renderer {
+            if renderer.has_link_preview() {
+                renderer.set_link_preview(None);
+                self.invalidate();
+            }
+        }
+        if hovered.is_some() {
+            unsafe {
+                SetTimer(
+                    Some(self.hwnd),
+                    LINK_PREVIEW_TIMER_ID,
+                    HOVER_DELAY.as_millis() as u32,
+                    None,
+                )
+            };
+        }
+        self.hovered_link = hovered;
+    }
+
+    /// Show the hovered link's preview, loading it in the background first
+    /// unless it is cached
+    fn load_link_preview(&mut self) {
+        let Some((target, _)) = self.hovered_link.clone() else {
+            return;
+        };
+        match link_preview::cached(&target) {
+            Some(Some(preview)) => self.on_link_preview(preview),
+            Some(None) => {}
+            None => {
+                let hwnd = self.hwnd.0 as usize;
+                std::thread::spawn(move || {
+                    if let Some(preview) = link_preview::preview(&target) {
+                        post_link_preview(hwnd, preview);
+                    }
+                });
+            }
+        }
+    }
+
+    /// Handle a loaded hover preview, shown if its link is still hovered
+    pub fn on_link_preview(&mut self, preview: Arc<LinkPreview>) {
+        let (x, y) = match self.hovered_link {
+            Some((ref target, point)) if *target == preview.target => point,
+            _ => return,
+        };
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_link_preview(Some((preview, x, y)));
+        }
+        self.invalidate();
+    }
+
+    /// Handle the pointer leaving the window
+    pub fn on_mouse_leave(&mut self) {
+        self.last_mouse_cell = None;
+        self.set_hovered_link(None);
+        self.pointer_hider.pointer_left();
     }
 
     /// Open a URL using the system default handler
@@ -1690,19 +4375,33 @@ impl WindowState {
         if y < y_offset {
             return None;
         }
-        let cell_dims = self.renderer.as_ref()?.cell_dimensions();
-        Some(mouse::pixel_to_cell(
-            x as i32,
-            (y - y_offset) as i32,
-            &cell_dims,
-            0,
-        ))
+        let renderer = self.renderer.as_ref()?;
+        let cell_dims = renderer.cell_dimensions();
+        let (x, y) = renderer
+            .magnifier()
+            .view_to_surface(x as f64, (y - y_offset) as f64);
+        // Split tabs take cells of the focused pane
+        let (pane_x, pane_y) = self.focused_pane_origin();
+        let (x, y) = ((x as f32 - pane_x).max(0.0), (y as f32 - pane_y).max(0.0));
+        Some(mouse::pixel_to_cell(x as i32, y as i32, &cell_dims, 0))
+    }
+
+    /// Whether the `input.mouse_override` modifier is held, keeping the mouse
+    /// for local selection, menus and scrolling under a tracking application
+    fn mouse_override_held(&self) -> bool {
+        self.config
+            .input
+            .overrides_mouse_tracking(current_mouse_modifiers())
     }
 
-    /// Whether the active terminal has enabled any mouse tracking mode.
+    /// Whether the active terminal has enabled any mouse tracking mode. A
+    /// read-only terminal keeps the mouse for selecting and scrolling.
     fn mouse_tracking_active(&self) -> bool {
         self.active_terminal()
-            .map(|t| t.lock().unwrap().screen().modes.mouse_mode != MouseMode::None)
+            .map(|t| {
+                let term = t.lock().unwrap();
+                term.screen().modes.mouse_mode != MouseMode::None && !term.is_input_locked()
+            })
             .unwrap_or(false)
     }
 
@@ -1718,7 +4417,7 @@ impl WindowState {
         };
         let mut term = terminal.lock().unwrap();
         let mode = term.screen().modes.mouse_mode;
-        if mode == MouseMode::None {
+        if mode == MouseMode::None || term.is_input_locked() {
             return false;
         }
         let sgr = term.screen().modes.sgr_mouse;
@@ -1744,20 +4443,60 @@ impl WindowState {
     }
 
     pub fn on_mouse_down(&mut self, x: f32, y: f32) {
+        // Clicking the tab bar timer starts or pauses it
+        if self
+            .tab_bar_y(y)
+            .is_some_and(|bar_y| self.tab_bar.widget_at(x, bar_y) == Some(WidgetKind::Timer))
+        {
+            tab_widgets::toggle_timer(&self.config.widgets);
+            self.invalidate();
+            return;
+        }
+
         // Check if click is in notification bar area
-        let tab_bar_height = self.dpi.scale_f32(TAB_BAR_HEIGHT as f32);
         let notification_height = self.notification_bar.height() as f32;
 
-        // Notification bar is right below tab bar
-        if y >= tab_bar_height && y < tab_bar_height + notification_height {
+        // Notification bar is right above the terminal
+        let notification_top = self.terminal_y_offset() - notification_height;
+        if y >= notification_top && y < notification_top + notification_height {
             // Adjust y coordinate relative to notification bar
-            let rel_y = y - tab_bar_height;
+            let rel_y = y - notification_top;
             if let Some(action) = self.notification_bar.hit_test(x, rel_y) {
                 self.handle_notification_action(action);
             }
             return;
         }
 
+        // The status strip's Cancel button covers the bottom row
+        if self.status.is_pasting() || self.status.is_transferring() {
+            let height = self
+                .active_terminal()
+                .map_or(0, |t| t.lock().unwrap().screen().height());
+            if self
+                .terminal_cell_at(x, y)
+                .is_some_and(|(_, row)| row + 1 >= height)
+            {
+                if !self.cancel_paste() {
+                    if let Some(terminal) = self.active_terminal() {
+                        terminal.lock().unwrap().cancel_file_transfer();
+                    }
+                    self.sync_transfer();
+                }
+                return;
+            }
+        }
+
+        // Clicking a pane of a split tab focuses it
+        if let Some(layout) = self.pane_layout() {
+            let y = (y - self.terminal_y_offset()) as f64;
+            if let Some((id, _)) = layout
+                .into_iter()
+                .find(|(_, area)| area.contains(x as f64, y))
+            {
+                self.focus_pane(id);
+            }
+        }
+
         // Ctrl+click to open hyperlinks in the terminal area
         let ctrl_pressed = unsafe {
             windows::Win32::UI::Input::KeyboardAndMouse::GetKeyState(
@@ -1771,14 +4510,17 @@ impl WindowState {
             }
         }
 
-        // Forward to a mouse-tracking application unless Shift is held (Shift is
-        // reserved for local interaction, matching the xterm/VTE convention).
-        if !shift_pressed()
+        // Forward to a mouse-tracking application unless the override modifier
+        // (Shift by default, matching the xterm/VTE convention) is held.
+        if !self.mouse_override_held()
             && self.mouse_tracking_active()
             && self.forward_mouse_event(ReportButton::Left, x, y, false)
         {
             self.mouse_report_button = Some(ReportButton::Left);
+            return;
         }
+
+        self.start_selection(x, y);
     }
 
     /// Handle mouse button release.
@@ -1786,29 +4528,169 @@ impl WindowState {
         // If a press was forwarded to a mouse-tracking app, report the release.
         if self.mouse_report_button.take().is_some() {
             self.forward_mouse_event(ReportButton::Release, x, y, false);
+            return;
+        }
+        if self.mouse_state.selecting {
+            self.finish_selection();
         }
     }
 
     /// Handle middle-button press.
     pub fn on_middle_down(&mut self, x: f32, y: f32) {
-        if !shift_pressed()
+        // Middle-clicking a tab closes it
+        if let Some(bar_y) = self.tab_bar_y(y) {
+            if let (Some(tab_id), _, _) = self.tab_bar.hit_test(x, bar_y) {
+                self.close_tab(tab_id);
+            }
+            return;
+        }
+
+        if !self.mouse_override_held()
             && self.mouse_tracking_active()
             && self.forward_mouse_event(ReportButton::Middle, x, y, false)
         {
             self.mouse_report_button = Some(ReportButton::Middle);
+        } else if self.config.input.middle_click_paste && self.terminal_cell_at(x, y).is_some() {
+            // No primary selection on Windows: paste the clipboard
+            self.paste();
+        }
+    }
+
+    /// Start a local text selection; double and triple clicks select words
+    /// and lines
+    fn start_selection(&mut self, x: f32, y: f32) {
+        let Some((col, row)) = self.terminal_cell_at(x, y) else {
+            return;
+        };
+        let (Some(terminal), Some(renderer)) = (self.active_terminal(), self.renderer.as_ref())
+        else {
+            return;
+        };
+        let cell_dims = renderer.cell_dimensions();
+        let mode = self
+            .mouse_state
+            .on_button_down(
+                MouseButton::Left,
+                x as i32,
+                y as i32,
+                Modifiers::empty(),
+                &cell_dims,
+                0,
+            )
+            .map_or(SelectionMode::Char, |selection| selection.mode);
+
+        let mut term = terminal.lock().unwrap();
+        let line = term.screen().visible_row_to_absolute_line(row);
+        term.screen_mut().start_selection(line, col, mode);
+        drop(term);
+
+        // Keep receiving mouse moves when the drag leaves the window
+        unsafe { SetCapture(self.hwnd) };
+        self.invalidate();
+    }
+
+    /// Extend the local selection to the pointer, autoscrolling while it is
+    /// above or below the terminal
+    fn extend_selection_to(&mut self, x: f32, y: f32) {
+        let y_offset = self.terminal_y_offset();
+        let mut rect = RECT::default();
+        unsafe { GetClientRect(self.hwnd, &mut rect).ok() };
+        let direction = if y < y_offset {
+            -1
+        } else if y >= rect.bottom as f32 {
+            1
+        } else {
+            0
+        };
+        let Some((col, row)) = self.terminal_cell_at(x.max(0.0), y.max(y_offset)) else {
+            return;
+        };
+        self.autoscroll_col = col;
+        self.set_autoscroll(direction);
+
+        if let Some(terminal) = self.active_terminal() {
+            let mut term = terminal.lock().unwrap();
+            let screen = term.screen();
+            let line =
+                screen.visible_row_to_absolute_line(row.min(screen.height().saturating_sub(1)));
+            let col = col.min(screen.width().saturating_sub(1));
+            term.screen_mut().extend_selection(line, col);
+        }
+        self.invalidate();
+    }
+
+    /// End a selection drag, dropping a selection that never left its cell
+    fn finish_selection(&mut self) {
+        self.mouse_state.selecting = false;
+        self.set_autoscroll(0);
+        unsafe { ReleaseCapture().ok() };
+
+        if let Some(terminal) = self.active_terminal() {
+            let mut term = terminal.lock().unwrap();
+            let empty = term.screen().selection.as_ref().is_some_and(|selection| {
+                selection.anchor == selection.end
+                    && matches!(selection.mode, SelectionMode::Char | SelectionMode::Block)
+            });
+            if empty {
+                term.screen_mut().clear_selection();
+            }
+        }
+        self.invalidate();
+    }
+
+    /// Start, redirect or stop the selection autoscroll timer
+    fn set_autoscroll(&mut self, direction: i32) {
+        let direction = if self.config.input.autoscroll_lines == 0 {
+            0
+        } else {
+            direction
+        };
+        if direction == self.autoscroll_direction {
+            return;
+        }
+        self.autoscroll_direction = direction;
+        unsafe {
+            if direction == 0 {
+                KillTimer(Some(self.hwnd), AUTOSCROLL_TIMER_ID).ok();
+            } else {
+                SetTimer(
+                    Some(self.hwnd),
+                    AUTOSCROLL_TIMER_ID,
+                    InputConfig::AUTOSCROLL_INTERVAL.as_millis() as u32,
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Autoscroll tick: scroll and extend the selection onto the new rows
+    fn on_autoscroll(&mut self) {
+        if self.autoscroll_direction == 0 {
+            return;
+        }
+        if let Some(terminal) = self.active_terminal() {
+            terminal.lock().unwrap().autoscroll_selection(
+                self.autoscroll_direction < 0,
+                self.config.input.autoscroll_lines,
+                self.autoscroll_col,
+            );
         }
+        self.invalidate();
     }
 
     /// Handle mouse wheel: forward to a tracking app, translate to cursor keys on
     /// the alternate screen (alternate-scroll), or scroll the local scrollback.
     pub fn on_wheel(&mut self, delta: i32) {
-        let up = delta > 0;
-        let shift = shift_pressed();
+        let input = self.config.input;
+        let up = input.wheel_up(delta > 0, false);
+        // One notch is 120; high-resolution wheels send smaller steps
+        let lines = input.wheel_scroll_lines(delta.unsigned_abs() as usize / 120);
+        let local = self.mouse_override_held();
         let Some(terminal) = self.active_terminal() else {
             return;
         };
 
-        if !shift {
+        if !local {
             // 1) Application is tracking the mouse: forward a wheel report.
             if self.mouse_tracking_active() {
                 let (x, y) = self.last_mouse_pos;
@@ -1824,14 +4706,17 @@ impl WindowState {
             // 2) Alternate screen + alternate-scroll: translate to cursor keys so
             //    pagers (less/man) scroll.
             let mut term = terminal.lock().unwrap();
-            if term.screen().modes.alternate_screen && term.screen().modes.alternate_scroll {
+            if term.screen().modes.alternate_screen
+                && term.screen().modes.alternate_scroll
+                && !term.is_input_locked()
+            {
                 let key = if up {
                     cterm_core::term::Key::Up
                 } else {
                     cterm_core::term::Key::Down
                 };
                 if let Some(bytes) = term.handle_key(key, cterm_core::term::Modifiers::empty()) {
-                    for _ in 0..3 {
+                    for _ in 0..lines {
                         let _ = term.write(&bytes);
                     }
                 }
@@ -1844,22 +4729,47 @@ impl WindowState {
 
         // 3) Default: scroll the local scrollback viewport.
         let mut term = terminal.lock().unwrap();
+        let from = term.screen().scroll_offset;
         if up {
-            term.scroll_viewport_up(3);
+            term.scroll_viewport_up(lines);
         } else {
-            term.scroll_viewport_down(3);
+            term.scroll_viewport_down(lines);
         }
+        let to = term.screen().scroll_offset;
         drop(term);
+        if from != to {
+            self.mirror_scroll(from, to);
+        }
         self.invalidate();
     }
 
+    /// Scroll the pane locked to the focused one as far as the focused one
+    /// scrolled, from `from` to `to` lines back
+    fn mirror_scroll(&self, from: usize, to: usize) {
+        let partner = self.tabs.get(self.active_tab_index).and_then(|tab| {
+            let split = tab.split.as_ref()?;
+            let id = split.tree.scroll_partner(split.tree.focused())?;
+            tab.pane_terminal(id)
+        });
+        if let Some(partner) = partner {
+            session::mirror_scroll(&mut partner.lock().unwrap(), from, to);
+        }
+    }
+
     /// Handle mouse move for hyperlink hover and drag forwarding.
     pub fn on_mouse_move(&mut self, x: f32, y: f32) {
         self.last_mouse_pos = (x, y);
 
+        // Typing hid the pointer: keep it hidden until the mouse moves
+        match self.pointer_hider.pointer_moved(x as f64, y as f64) {
+            PointerMotion::Still => return,
+            PointerMotion::Shown => set_pointer_cursor(self.pointer_shape()),
+            PointerMotion::Moved => {}
+        }
+
         // If a button is held for a mouse-tracking app, report drag motion (only
         // when the pointer crosses into a new cell, to avoid flooding).
-        if !shift_pressed() {
+        if !self.mouse_override_held() {
             if let Some(button) = self.mouse_report_button {
                 let cell = self.terminal_cell_at(x, y);
                 if cell.is_some() && cell != self.last_mouse_cell {
@@ -1869,37 +4779,90 @@ impl WindowState {
                 return;
             }
         }
-        self.last_mouse_cell = self.terminal_cell_at(x, y);
+        if self.mouse_state.selecting {
+            self.extend_selection_to(x, y);
+            return;
+        }
+        let cell = self.terminal_cell_at(x, y);
+        if self.config.input.hover_previews && cell != self.last_mouse_cell {
+            track_mouse_leave(self.hwnd);
+            self.set_hovered_link(self.preview_target_at(x, y).map(|t| (t, (x, y))));
+        }
+        if cell != self.last_mouse_cell {
+            self.hover_command_gutter(x, y);
+        }
+        self.last_mouse_cell = cell;
+
+        let shape = if self.hyperlink_at(x, y).is_some() {
+            PointerShape::Pointer
+        } else {
+            self.pointer_shape()
+        };
+        set_pointer_cursor(shape);
+    }
+
+    /// Mouse pointer shape the active tab's program asked for (OSC 22)
+    fn pointer_shape(&self) -> PointerShape {
+        self.active_terminal()
+            .map(|terminal| terminal.lock().unwrap().screen().pointer_shape())
+            .unwrap_or_default()
+    }
 
-        let has_link = self.hyperlink_at(x, y).is_some();
+    /// A program asked for another pointer shape: show it at once if it is
+    /// in the current tab and the mouse is over the terminal, unless typing
+    /// hid it
+    pub fn on_pointer_shape_changed(&mut self, tab_id: u64) {
+        let is_current_tab = self
+            .tabs
+            .get(self.active_tab_index)
+            .is_some_and(|t| t.id == tab_id);
+        if is_current_tab && self.pointer_over_terminal() && !self.pointer_hider.is_hidden() {
+            let (x, y) = self.last_mouse_pos;
+            if self.hyperlink_at(x, y).is_none() {
+                set_pointer_cursor(self.pointer_shape());
+            }
+        }
+    }
 
+    /// Whether the mouse is over the terminal area of the window
+    fn pointer_over_terminal(&self) -> bool {
+        let mut point = POINT::default();
+        let mut rect = RECT::default();
         unsafe {
-            use windows::Win32::UI::WindowsAndMessaging::{LoadCursorW, SetCursor};
-            let cursor = if has_link {
-                LoadCursorW(None, IDC_HAND).unwrap_or_default()
-            } else {
-                LoadCursorW(None, IDC_IBEAM).unwrap_or_default()
-            };
-            let _ = SetCursor(Some(cursor));
+            if GetCursorPos(&mut point).is_err()
+                || !ScreenToClient(self.hwnd, &mut point).as_bool()
+                || GetClientRect(self.hwnd, &mut rect).is_err()
+            {
+                return false;
+            }
         }
+        point.x >= rect.left
+            && point.x < rect.right
+            && point.y < rect.bottom
+            && point.y as f32 >= self.terminal_y_offset()
     }
 
     /// Handle right-click for context menu
     pub fn on_right_click(&mut self, x: f32, y: f32) {
         // Check if click is in tab bar area
-        let tab_bar_height = self.dpi.scale_f32(TAB_BAR_HEIGHT as f32);
+        if let Some(bar_y) = self.tab_bar_y(y) {
+            // Right-clicking the timer resets it
+            if self.tab_bar.widget_at(x, bar_y) == Some(WidgetKind::Timer) {
+                tab_widgets::reset_timer();
+                self.invalidate();
+                return;
+            }
 
-        if y < tab_bar_height && self.tab_bar.is_visible() {
             // Hit test the tab bar
-            let (tab_id, _is_close, _is_new) = self.tab_bar.hit_test(x, y);
+            let (tab_id, _is_close, _is_new) = self.tab_bar.hit_test(x, bar_y);
             if let Some(tab_id) = tab_id {
                 self.show_tab_context_menu(tab_id, x as i32, y as i32);
             }
             return;
         }
 
-        // Forward to a mouse-tracking application unless Shift is held.
-        if !shift_pressed()
+        // Forward to a mouse-tracking application unless the override is held.
+        if !self.mouse_override_held()
             && self.mouse_tracking_active()
             && self.forward_mouse_event(ReportButton::Right, x, y, false)
         {
@@ -2092,26 +5055,42 @@ impl WindowState {
     }
 
     /// Handle notification bar action
-    fn handle_notification_action(&mut self, action: NotificationAction) {
-        if let Some(file_id) = self.notification_bar.pending_file_id() {
-            match action {
-                NotificationAction::Save => {
-                    self.save_file(file_id, false);
-                }
-                NotificationAction::SaveAs => {
-                    self.save_file(file_id, true);
-                }
-                NotificationAction::Discard => {
-                    self.file_manager.discard(file_id);
-                    self.notification_bar.hide();
-                    self.invalidate();
-                }
+    fn handle_notification_action(&mut self, action: PromptAction) {
+        let file_id = self.notification_bar.current().and_then(|p| p.file_id());
+        if let (PromptAction::SaveAs, Some(file_id)) = (action, file_id) {
+            // Cancelling the dialog leaves the file waiting
+            if !self.save_file(file_id, true) {
+                return;
+            }
+            // Another file may have replaced it while the dialog was open
+            if self.notification_bar.current().and_then(|p| p.file_id()) != Some(file_id) {
+                self.invalidate();
+                return;
             }
         }
+
+        match self.notification_bar.answer(action) {
+            Some(Prompt::File { id, .. }) => match action {
+                PromptAction::Save => {
+                    self.save_file(id, false);
+                }
+                PromptAction::SaveAs => {}
+                _ => self.file_manager.discard(id),
+            },
+            Some(Prompt::Permission { tab, capability }) => self.answer_permission(
+                tab,
+                capability,
+                !action.is_dismissal(),
+                action == PromptAction::AlwaysAllow,
+            ),
+            None => return,
+        }
+        self.invalidate();
     }
 
-    /// Save file (optionally with dialog)
-    fn save_file(&mut self, file_id: u64, show_dialog: bool) {
+    /// Save file (optionally with dialog); returns false if the dialog was
+    /// cancelled
+    fn save_file(&mut self, file_id: u64, show_dialog: bool) -> bool {
         // Get default path from file manager
         let default_path = self.file_manager.default_save_path();
 
@@ -2126,23 +5105,19 @@ impl WindowState {
             default_path
         };
 
-        if let Some(path) = save_path {
-            match self.file_manager.save_to_path(file_id, &path) {
-                Ok(_size) => {
-                    log::info!("File saved to {:?}", path);
-                }
-                Err(e) => {
-                    log::error!("Failed to save file: {}", e);
-                    crate::dialogs::show_error_msg(
-                        self.hwnd,
-                        &format!("Failed to save file: {}", e),
-                    );
-                }
+        let Some(path) = save_path else {
+            return false;
+        };
+        match self.file_manager.save_to_path(file_id, &path) {
+            Ok(_size) => {
+                log::info!("File saved to {:?}", path);
+            }
+            Err(e) => {
+                log::error!("Failed to save file: {}", e);
+                crate::dialogs::show_error_msg(self.hwnd, &format!("Failed to save file: {}", e));
             }
         }
-
-        self.notification_bar.hide();
-        self.invalidate();
+        true
     }
 }
 
@@ -2179,6 +5154,70 @@ pub fn register_window_class() -> windows::core::Result<()> {
     Ok(())
 }
 
+/// Let DWM compose the client area with the alpha the renderer draws, and
+/// put an acrylic backdrop behind it when `blur` is set
+fn set_window_translucency(hwnd: HWND, translucent: bool, blur: bool) {
+    // Margins of -1 extend the frame over the whole client area
+    let inset = if translucent { -1 } else { 0 };
+    let margins = MARGINS {
+        cxLeftWidth: inset,
+        cxRightWidth: inset,
+        cyTopHeight: inset,
+        cyBottomHeight: inset,
+    };
+    let backdrop = if translucent && blur {
+        DWMSBT_TRANSIENTWINDOW
+    } else {
+        DWMSBT_AUTO
+    };
+    unsafe {
+        if let Err(e) = DwmExtendFrameIntoClientArea(hwnd, &margins) {
+            log::warn!("Failed to extend the frame into the client area: {}", e);
+        }
+        // Only Windows 11 22H2 and later have system backdrops
+        if let Err(e) = DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_SYSTEMBACKDROP_TYPE,
+            &backdrop as *const DWM_SYSTEMBACKDROP_TYPE as *const std::ffi::c_void,
+            std::mem::size_of::<DWM_SYSTEMBACKDROP_TYPE>() as u32,
+        ) {
+            log::debug!("System backdrop unavailable: {}", e);
+        }
+    }
+}
+
+/// Start or stop the timer that redraws the tab bar widgets
+fn sync_widgets_timer(hwnd: HWND, enabled: bool) {
+    unsafe {
+        if enabled {
+            SetTimer(
+                Some(hwnd),
+                WIDGETS_TIMER_ID,
+                tab_widgets::REFRESH_INTERVAL.as_millis() as u32,
+                None,
+            );
+        } else {
+            KillTimer(Some(hwnd), WIDGETS_TIMER_ID).ok();
+        }
+    }
+}
+
+/// Start or stop the timer that refreshes the status segments in tab titles
+fn sync_segments_timer(hwnd: HWND, enabled: bool) {
+    unsafe {
+        if enabled {
+            SetTimer(
+                Some(hwnd),
+                SEGMENTS_TIMER_ID,
+                status_segments::REFRESH_INTERVAL.as_millis() as u32,
+                None,
+            );
+        } else {
+            KillTimer(Some(hwnd), SEGMENTS_TIMER_ID).ok();
+        }
+    }
+}
+
 /// Create the main window
 pub fn create_window(config: &Config, theme: &Theme) -> windows::core::Result<HWND> {
     let class_name: Vec<u16> = WINDOW_CLASS
@@ -2211,10 +5250,40 @@ pub fn create_window(config: &Config, theme: &Theme) -> windows::core::Result<HW
     // Create window state
     let mut state = Box::new(WindowState::new(hwnd, config, theme));
     state.init_renderer()?;
-    state.new_tab().map_err(|e| {
-        log::error!("Failed to create initial tab: {}", e);
-        windows::core::Error::from_win32()
-    })?;
+
+    // `cterm --play` opens just the recording
+    let playing = match crate::get_args().play {
+        Some(ref path) => match state.play_recording(path) {
+            Ok(_) => true,
+            Err(e) => {
+                log::error!("Failed to open recording {:?}: {}", path, e);
+                crate::dialogs::show_error_msg(
+                    hwnd,
+                    &format!("Failed to open {}: {}", path.display(), e),
+                );
+                false
+            }
+        },
+        None => false,
+    };
+    if !playing {
+        // `cterm --workspace` opens the workspace instead of the last session
+        if let Some(ref name) = crate::get_args().workspace {
+            state.open_workspace(name);
+        }
+    }
+    if !playing && state.tabs.is_empty() {
+        // Reopen the window of the last session, or start with a fresh tab
+        let restored = session::windows_to_restore(config);
+        let result = match restored.first() {
+            Some(saved) => state.restore(saved),
+            None => state.new_tab().map(|_| ()),
+        };
+        result.map_err(|e| {
+            log::error!("Failed to create initial tab: {}", e);
+            windows::core::Error::from_win32()
+        })?;
+    }
 
     // Store state pointer in window
     unsafe {
@@ -2560,6 +5629,14 @@ async fn run_daemon_io_loop(
                         log::error!("Failed to set template name: {}", e);
                     }
                 }
+                DaemonCmd::SetClipboard(data) => {
+                    if let Err(e) = cmd_session
+                        .set_clipboard(cterm_proto::proto::ClipboardSelection::Clipboard, data)
+                        .await
+                    {
+                        log::error!("Failed to copy to daemon clipboard: {}", e);
+                    }
+                }
             }
         }
     });
@@ -2620,6 +5697,7 @@ async fn run_daemon_io_loop(
                     while let Some(result) = stream.next().await {
                         match result {
                             Ok(chunk) => {
+                                let mut tmux_control = Vec::new();
                                 {
                                     let mut term = terminal.lock().unwrap();
                                     let events = term.process(&chunk.data);
@@ -2631,10 +5709,48 @@ async fn run_daemon_io_loop(
                                             TerminalEvent::Bell => {
                                                 post_message(hwnd, WM_APP_BELL, tab_id);
                                             }
+                                            TerminalEvent::PointerShapeChanged(_) => {
+                                                post_message(hwnd, WM_APP_POINTER_SHAPE, tab_id);
+                                            }
+                                            TerminalEvent::CommandFinished(finished) => {
+                                                post_command_finished(hwnd, tab_id, finished);
+                                            }
+                                            TerminalEvent::OutputStats(stats) => {
+                                                post_output_stats(hwnd, tab_id, stats);
+                                            }
+                                            TerminalEvent::TmuxControl(control) => {
+                                                tmux_control.extend(control);
+                                            }
+                                            TerminalEvent::WatchMatched(found) => {
+                                                post_watch_matched(hwnd, tab_id, found);
+                                            }
+                                            TerminalEvent::TransferStarted(..)
+                                            | TerminalEvent::TransferFinished => {
+                                                post_message(
+                                                    hwnd,
+                                                    WM_APP_TRANSFER_CHANGED,
+                                                    tab_id,
+                                                );
+                                            }
+                                            TerminalEvent::TransferSendRequested => {
+                                                post_message(hwnd, WM_APP_TRANSFER_FILES, tab_id);
+                                            }
+                                            TerminalEvent::UploadRequested => {
+                                                post_message(hwnd, WM_APP_UPLOAD_FILES, tab_id);
+                                            }
+                                            TerminalEvent::ClipboardRequest(_)
+                                            | TerminalEvent::Notification(_)
+                                            | TerminalEvent::PermissionRequested(_)
+                                            | TerminalEvent::LinesCompleted(_) => {
+                                                post_terminal_request(hwnd, tab_id, event);
+                                            }
                                             _ => {}
                                         }
                                     }
                                 }
+                                if !tmux_control.is_empty() {
+                                    post_tmux_control(hwnd, tab_id, tmux_control);
+                                }
                                 post_message(hwnd, WM_APP_PTY_DATA, tab_id);
                             }
                             Err(e) => {
@@ -2655,6 +5771,31 @@ async fn run_daemon_io_loop(
 }
 
 /// Post a WM_APP message to the window
+/// Show the system cursor for the pointer shape a program asked for
+/// (OSC 22); the default over a terminal is the I-beam
+fn set_pointer_cursor(shape: PointerShape) {
+    let id = match shape {
+        PointerShape::Default | PointerShape::Text => IDC_IBEAM,
+        PointerShape::Arrow => IDC_ARROW,
+        PointerShape::Pointer => IDC_HAND,
+        PointerShape::Crosshair => IDC_CROSS,
+        PointerShape::Wait => IDC_WAIT,
+        PointerShape::Help => IDC_HELP,
+        PointerShape::Move => IDC_SIZEALL,
+        PointerShape::NotAllowed => IDC_NO,
+        PointerShape::ColumnResize => IDC_SIZEWE,
+        PointerShape::RowResize => IDC_SIZENS,
+        PointerShape::Hidden => {
+            let _ = unsafe { SetCursor(None) };
+            return;
+        }
+    };
+    unsafe {
+        let cursor = LoadCursorW(None, id).unwrap_or_default();
+        let _ = SetCursor(Some(cursor));
+    }
+}
+
 fn post_message(hwnd: usize, msg: u32, tab_id: u64) {
     unsafe {
         let _ = PostMessageW(
@@ -2666,19 +5807,196 @@ fn post_message(hwnd: usize, msg: u32, tab_id: u64) {
     }
 }
 
+/// Read the chosen files, skipping (and logging) any that can't be read
+fn read_files(paths: &[std::path::PathBuf]) -> Vec<TransferFile> {
+    paths
+        .iter()
+        .filter_map(|path| match TransferFile::from_path(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                log::error!("Failed to read {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Post a control request to the window, which takes ownership of the box
+pub fn post_control_request(hwnd: usize, request: ControlRequest) {
+    let request = Box::into_raw(Box::new(request));
+    unsafe {
+        if PostMessageW(
+            Some(HWND(hwnd as *mut _)),
+            WM_APP_CONTROL_REQUEST,
+            WPARAM(0),
+            LPARAM(request as isize),
+        )
+        .is_err()
+        {
+            drop(Box::from_raw(request));
+        }
+    }
+}
+
+/// Post a finished command to the window, which takes ownership of the box
+fn post_command_finished(hwnd: usize, tab_id: u64, finished: CommandFinished) {
+    let finished = Box::into_raw(Box::new(finished));
+    unsafe {
+        if PostMessageW(
+            Some(HWND(hwnd as *mut _)),
+            WM_APP_COMMAND_FINISHED,
+            WPARAM(tab_id as usize),
+            LPARAM(finished as isize),
+        )
+        .is_err()
+        {
+            drop(Box::from_raw(finished));
+        }
+    }
+}
+
+/// Post a tab's output rates to the window, which takes ownership of the box
+fn post_output_stats(hwnd: usize, tab_id: u64, stats: OutputStats) {
+    let stats = Box::into_raw(Box::new(stats));
+    unsafe {
+        if PostMessageW(
+            Some(HWND(hwnd as *mut _)),
+            WM_APP_OUTPUT_STATS,
+            WPARAM(tab_id as usize),
+            LPARAM(stats as isize),
+        )
+        .is_err()
+        {
+            drop(Box::from_raw(stats));
+        }
+    }
+}
+
+/// Post a changed appearance to the window, which takes ownership of the
+/// box
+fn post_appearance(hwnd: usize, config: Config, change: AppearanceChange) {
+    let appearance = Box::into_raw(Box::new((config, change)));
+    unsafe {
+        if PostMessageW(
+            Some(HWND(hwnd as *mut _)),
+            WM_APP_APPEARANCE,
+            WPARAM(0),
+            LPARAM(appearance as isize),
+        )
+        .is_err()
+        {
+            drop(Box::from_raw(appearance));
+        }
+    }
+}
+
+/// Post a loaded hover preview to the window, which takes ownership of the
+/// box
+fn post_link_preview(hwnd: usize, preview: Arc<LinkPreview>) {
+    let preview = Box::into_raw(Box::new(preview));
+    unsafe {
+        if PostMessageW(
+            Some(HWND(hwnd as *mut _)),
+            WM_APP_LINK_PREVIEW,
+            WPARAM(0),
+            LPARAM(preview as isize),
+        )
+        .is_err()
+        {
+            drop(Box::from_raw(preview));
+        }
+    }
+}
+
+/// Ask for a WM_MOUSELEAVE when the pointer leaves the window
+fn track_mouse_leave(hwnd: HWND) {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        TrackMouseEvent, TME_LEAVE, TRACKMOUSEEVENT,
+    };
+
+    let mut tme = TRACKMOUSEEVENT {
+        cbSize: std::mem::size_of::<TRACKMOUSEEVENT>() as u32,
+        dwFlags: TME_LEAVE,
+        hwndTrack: hwnd,
+        dwHoverTime: 0,
+    };
+    unsafe {
+        let _ = TrackMouseEvent(&mut tme);
+    }
+}
+
+/// Post a tab's watch expression match to the window, which takes ownership
+/// of the box
+fn post_watch_matched(hwnd: usize, tab_id: u64, found: WatchMatch) {
+    let found = Box::into_raw(Box::new(found));
+    unsafe {
+        if PostMessageW(
+            Some(HWND(hwnd as *mut _)),
+            WM_APP_WATCH_MATCHED,
+            WPARAM(tab_id as usize),
+            LPARAM(found as isize),
+        )
+        .is_err()
+        {
+            drop(Box::from_raw(found));
+        }
+    }
+}
+
+/// Post a clipboard operation, notification or permission request to the
+/// window, which takes ownership of the box
+fn post_terminal_request(hwnd: usize, tab_id: u64, event: TerminalEvent) {
+    let event = Box::into_raw(Box::new(event));
+    unsafe {
+        if PostMessageW(
+            Some(HWND(hwnd as *mut _)),
+            WM_APP_TERMINAL_REQUEST,
+            WPARAM(tab_id as usize),
+            LPARAM(event as isize),
+        )
+        .is_err()
+        {
+            drop(Box::from_raw(event));
+        }
+    }
+}
+
+/// Post a tab's tmux control mode output to the window, which takes
+/// ownership of the box
+fn post_tmux_control(hwnd: usize, tab_id: u64, control: Vec<TmuxControl>) {
+    let control = Box::into_raw(Box::new(control));
+    unsafe {
+        if PostMessageW(
+            Some(HWND(hwnd as *mut _)),
+            WM_APP_TMUX_CONTROL,
+            WPARAM(tab_id as usize),
+            LPARAM(control as isize),
+        )
+        .is_err()
+        {
+            drop(Box::from_raw(control));
+        }
+    }
+}
+
+/// Copy `text` into a fixed-size, NUL-terminated UTF-16 buffer, truncating
+/// if needed
+fn copy_wide(buffer: &mut [u16], text: &str) {
+    let len = buffer.len() - 1;
+    let mut written = 0;
+    for (slot, unit) in buffer[..len].iter_mut().zip(text.encode_utf16()) {
+        *slot = unit;
+        written += 1;
+    }
+    buffer[written] = 0;
+}
+
 /// Post a PTY exit message to close the tab
 fn post_tab_exit(hwnd: usize, tab_id: u64) {
     post_message(hwnd, WM_APP_PTY_EXIT, tab_id);
 }
 
 /// Window procedure
-/// Whether Shift is currently held (used to bypass mouse forwarding so local
-/// interaction — hyperlink menu, scrollback — keeps working under a tracking app).
-fn shift_pressed() -> bool {
-    use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_SHIFT};
-    unsafe { GetKeyState(VK_SHIFT.0 as i32) < 0 }
-}
-
 /// Current Shift/Alt/Ctrl state for encoding into a mouse report.
 fn current_mouse_modifiers() -> MouseModifiers {
     use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_CONTROL, VK_MENU, VK_SHIFT};
@@ -2719,34 +6037,28 @@ extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPA
 
         WM_DPICHANGED => {
             let dpi = (wparam.0 & 0xFFFF) as u32;
-            state.on_dpi_changed(dpi);
-            // Resize window to suggested rect
-            let rect = unsafe { &*(lparam.0 as *const RECT) };
-            unsafe {
-                SetWindowPos(
-                    hwnd,
-                    None,
-                    rect.left,
-                    rect.top,
-                    rect.right - rect.left,
-                    rect.bottom - rect.top,
-                    SWP_NOZORDER | SWP_NOACTIVATE,
-                )
-            }
-            .ok();
+            let suggested = unsafe { &*(lparam.0 as *const RECT) };
+            state.on_dpi_changed(dpi, suggested);
             LRESULT(0)
         }
 
         WM_KEYDOWN | WM_SYSKEYDOWN => {
             let vk = (wparam.0 & 0xFFFF) as u16;
             let scancode = ((lparam.0 >> 16) & 0xFF) as u16;
-            if state.on_key_down(vk, scancode) {
+            // Bit 30: the key was already down (auto-repeat)
+            let repeat = (lparam.0 >> 30) & 1 != 0;
+            if state.on_key_down(vk, scancode, repeat) {
                 LRESULT(0)
             } else {
                 unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
             }
         }
 
+        WM_KEYUP | WM_SYSKEYUP => {
+            state.on_key_up((wparam.0 & 0xFFFF) as u16);
+            unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+        }
+
         WM_CHAR => {
             if let Some(c) = char::from_u32(wparam.0 as u32) {
                 // Only handle printable characters here. Control characters like
@@ -2810,6 +6122,16 @@ extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPA
             LRESULT(0)
         }
 
+        WM_MOUSELEAVE => {
+            state.on_mouse_leave();
+            LRESULT(0)
+        }
+
+        WM_DROPFILES => {
+            state.on_drop_files(wparam.0);
+            LRESULT(0)
+        }
+
         WM_MOUSEWHEEL => {
             // High word of wParam is the signed wheel delta (multiple of 120).
             let delta = ((wparam.0 >> 16) & 0xFFFF) as u16 as i16 as i32;
@@ -2847,11 +6169,89 @@ extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPA
         }
 
         WM_APP_BELL => {
-            let tab_id = wparam.0 as u64;
+            let tab_id = state.tab_of_pane(wparam.0 as u64);
             state.on_bell(tab_id);
             LRESULT(0)
         }
 
+        WM_APP_POINTER_SHAPE => {
+            let tab_id = state.tab_of_pane(wparam.0 as u64);
+            state.on_pointer_shape_changed(tab_id);
+            LRESULT(0)
+        }
+
+        WM_APP_TRANSFER_CHANGED => {
+            let tab_id = state.tab_of_pane(wparam.0 as u64);
+            state.on_transfer_changed(tab_id);
+            LRESULT(0)
+        }
+
+        WM_APP_TRANSFER_FILES => {
+            let tab_id = wparam.0 as u64;
+            state.on_transfer_files(tab_id);
+            LRESULT(0)
+        }
+
+        WM_APP_UPLOAD_FILES => {
+            let tab_id = wparam.0 as u64;
+            state.on_upload_files(tab_id);
+            LRESULT(0)
+        }
+
+        WM_APP_COMMAND_FINISHED => {
+            let tab_id = state.tab_of_pane(wparam.0 as u64);
+            let finished = unsafe { Box::from_raw(lparam.0 as *mut CommandFinished) };
+            state.on_command_finished(tab_id, &finished);
+            LRESULT(0)
+        }
+
+        WM_APP_OUTPUT_STATS => {
+            let tab_id = state.tab_of_pane(wparam.0 as u64);
+            let stats = unsafe { Box::from_raw(lparam.0 as *mut OutputStats) };
+            state.on_output_stats(tab_id, *stats);
+            LRESULT(0)
+        }
+
+        WM_APP_APPEARANCE => {
+            let appearance = unsafe { Box::from_raw(lparam.0 as *mut (Config, AppearanceChange)) };
+            let (config, change) = *appearance;
+            state.apply_appearance(&config, change);
+            LRESULT(0)
+        }
+
+        WM_APP_LINK_PREVIEW => {
+            let preview = unsafe { Box::from_raw(lparam.0 as *mut Arc<LinkPreview>) };
+            state.on_link_preview(*preview);
+            LRESULT(0)
+        }
+
+        WM_APP_TERMINAL_REQUEST => {
+            let tab_id = wparam.0 as u64;
+            let event = unsafe { Box::from_raw(lparam.0 as *mut TerminalEvent) };
+            state.on_terminal_request(tab_id, *event);
+            LRESULT(0)
+        }
+
+        WM_APP_WATCH_MATCHED => {
+            let tab_id = state.tab_of_pane(wparam.0 as u64);
+            let found = unsafe { Box::from_raw(lparam.0 as *mut WatchMatch) };
+            state.on_watch_matched(tab_id, &found);
+            LRESULT(0)
+        }
+
+        WM_APP_TMUX_CONTROL => {
+            let tab_id = wparam.0 as u64;
+            let control = unsafe { Box::from_raw(lparam.0 as *mut Vec<TmuxControl>) };
+            state.on_tmux_control(tab_id, *control);
+            LRESULT(0)
+        }
+
+        WM_APP_CONTROL_REQUEST => {
+            let request = unsafe { Box::from_raw(lparam.0 as *mut ControlRequest) };
+            state.on_control_request(*request);
+            LRESULT(0)
+        }
+
         WM_APP_TITLE_CHANGED => {
             let tab_id = wparam.0 as u64;
             state.on_title_changed(tab_id);
@@ -2870,6 +6270,11 @@ extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPA
             LRESULT(0)
         }
 
+        WM_TIMER => {
+            state.on_timer(wparam.0);
+            LRESULT(0)
+        }
+
         WM_CLOSE => {
             // Check if we should confirm before closing
             if state.should_confirm_close() {
@@ -2891,8 +6296,10 @@ extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPA
         }
 
         WM_DESTROY => {
-            // Clean up
-            let state = unsafe { Box::from_raw(state_ptr) };
+            // Save the window for the next launch, then clean up
+            let mut state = unsafe { Box::from_raw(state_ptr) };
+            session::save_at_exit(vec![state.session_state()], &state.config);
+            state.remove_notify_icon();
             drop(state);
             unsafe { SetWindowLongPtrW(hwnd, GWLP_USERDATA, 0) };
             unsafe { PostQuitMessage(0) };